@@ -5,6 +5,13 @@ fn main() {
     println!("cargo:rerun-if-changed=resources/");
     println!("cargo:rerun-if-changed=build.rs");
 
+    // AVX-512 intrinsics are stable from Rust 1.89; older compilers build
+    // the SIMD kernels without them
+    println!("cargo:rustc-check-cfg=cfg(avx512_intrinsics)");
+    if rustc_minor_version().is_some_and(|minor| minor >= 89) {
+        println!("cargo:rustc-cfg=avx512_intrinsics");
+    }
+
     // Only embed resources on Windows
     #[cfg(windows)]
     {
@@ -17,3 +24,11 @@ fn main() {
         }
     }
 }
+
+/// Minor version of the compiler building this crate, e.g. 89 for 1.89.0
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = std::env::var_os("RUSTC")?;
+    let output = std::process::Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    version.split_whitespace().nth(1)?.split('.').nth(1)?.parse().ok()
+}
@@ -2,6 +2,8 @@
 
 pub mod cpu;
 pub mod simd;
+pub mod topology;
 
 pub use cpu::CpuCapabilities;
 pub use simd::SimdOptimizer;
+pub use topology::CpuTopology;
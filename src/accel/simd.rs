@@ -1,124 +1,328 @@
 //! SIMD-optimized operations for pattern matching
+//!
+//! f32 dot product and squared Euclidean distance kernels for AVX2, AVX-512
+//! and NEON, with a scalar fallback. The fastest kernel the CPU supports is
+//! picked once at runtime; [`dot`] and [`l2_distance`] use it, and so does
+//! the pattern search in `neural::hnsw_patterns`. The AVX-512 kernel needs
+//! Rust 1.89 or newer to build; older compilers stop at AVX2.
+
+use std::sync::OnceLock;
 
 use super::cpu::CpuCapabilities;
 
-/// SIMD optimizer for vector operations
-pub struct SimdOptimizer {
-    caps: CpuCapabilities,
+/// Kernel implementation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    Scalar,
+    Avx2,
+    Avx512,
+    Neon,
 }
 
-impl SimdOptimizer {
-    pub fn new() -> Self {
-        Self { caps: CpuCapabilities::detect() }
+impl Kernel {
+    /// Kernels this CPU and build can run, slowest first
+    pub fn available() -> Vec<Kernel> {
+        #[allow(unused_mut)]
+        let mut kernels = vec![Kernel::Scalar];
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+                kernels.push(Kernel::Avx2);
+            }
+            #[cfg(avx512_intrinsics)]
+            if is_x86_feature_detected!("avx512f") {
+                kernels.push(Kernel::Avx512);
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        kernels.push(Kernel::Neon);
+
+        kernels
     }
 
-    /// SIMD-optimized Euclidean distance calculation
-    #[cfg(target_arch = "x86_64")]
-    pub fn euclidean_distance(&self, a: &[f32], b: &[f32]) -> f32 {
-        if a.len() != b.len() { return f32::MAX; }
+    /// Fastest kernel this CPU supports
+    pub fn detect() -> Kernel {
+        Self::available().last().copied().unwrap_or(Kernel::Scalar)
+    }
 
-        if self.caps.has_avx2 && a.len() >= 8 {
-            // SAFETY: We've checked has_avx2 is true
-            unsafe { self.euclidean_distance_avx2(a, b) }
-        } else {
-            self.euclidean_distance_scalar(a, b)
+    /// f32 values per instruction
+    pub fn lanes(&self) -> usize {
+        match self {
+            Kernel::Scalar => 1,
+            Kernel::Avx2 => 8,
+            Kernel::Avx512 => 16,
+            Kernel::Neon => 4,
         }
     }
+}
 
-    #[cfg(not(target_arch = "x86_64"))]
-    pub fn euclidean_distance(&self, a: &[f32], b: &[f32]) -> f32 {
-        self.euclidean_distance_scalar(a, b)
+impl std::fmt::Display for Kernel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Kernel::Scalar => "scalar",
+            Kernel::Avx2 => "AVX2",
+            Kernel::Avx512 => "AVX-512",
+            Kernel::Neon => "NEON",
+        })
     }
+}
 
-    fn euclidean_distance_scalar(&self, a: &[f32], b: &[f32]) -> f32 {
-        a.iter().zip(b.iter())
-            .map(|(x, y)| (x - y).powi(2))
-            .sum::<f32>()
-            .sqrt()
+type KernelFn = fn(&[f32], &[f32]) -> f32;
+
+/// The functions of one kernel
+///
+/// Only built for kernels [`Kernel::available`] lists, which is what makes
+/// calling the `target_feature` functions behind them sound.
+#[derive(Clone, Copy)]
+pub struct Kernels {
+    kernel: Kernel,
+    dot: KernelFn,
+    l2_squared: KernelFn,
+}
+
+impl Kernels {
+    /// `None` if this CPU or build can't run `kernel`
+    pub fn new(kernel: Kernel) -> Option<Self> {
+        if !Kernel::available().contains(&kernel) {
+            return None;
+        }
+        let (dot, l2_squared): (KernelFn, KernelFn) = match kernel {
+            #[cfg(target_arch = "x86_64")]
+            Kernel::Avx2 => (x86::dot_avx2, x86::l2_squared_avx2),
+            #[cfg(all(target_arch = "x86_64", avx512_intrinsics))]
+            Kernel::Avx512 => (x86::dot_avx512, x86::l2_squared_avx512),
+            #[cfg(target_arch = "aarch64")]
+            Kernel::Neon => (neon::dot, neon::l2_squared),
+            _ => (scalar::dot, scalar::l2_squared),
+        };
+        Some(Self { kernel, dot, l2_squared })
     }
 
-    #[cfg(target_arch = "x86_64")]
-    #[target_feature(enable = "avx2")]
-    unsafe fn euclidean_distance_avx2(&self, a: &[f32], b: &[f32]) -> f32 {
-        use std::arch::x86_64::*;
+    /// The fastest kernels for this CPU, chosen on first use
+    pub fn active() -> &'static Kernels {
+        static ACTIVE: OnceLock<Kernels> = OnceLock::new();
+        ACTIVE.get_or_init(|| Self::new(Kernel::detect()).unwrap_or(SCALAR))
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        self.kernel
+    }
 
-        let len = a.len();
-        let chunks = len / 8;
+    /// Dot product over the length of the shorter slice
+    pub fn dot(&self, a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len().min(b.len());
+        (self.dot)(&a[..len], &b[..len])
+    }
+
+    /// Squared Euclidean distance over the length of the shorter slice
+    pub fn l2_squared(&self, a: &[f32], b: &[f32]) -> f32 {
+        let len = a.len().min(b.len());
+        (self.l2_squared)(&a[..len], &b[..len])
+    }
+
+    pub fn l2(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.l2_squared(a, b).sqrt()
+    }
+}
+
+const SCALAR: Kernels = Kernels { kernel: Kernel::Scalar, dot: scalar::dot, l2_squared: scalar::l2_squared };
+
+/// Kernel in use
+pub fn active_kernel() -> Kernel {
+    Kernels::active().kernel()
+}
+
+/// Dot product with the active kernel
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    Kernels::active().dot(a, b)
+}
+
+/// Euclidean distance with the active kernel
+pub fn l2_distance(a: &[f32], b: &[f32]) -> f32 {
+    Kernels::active().l2(a, b)
+}
+
+mod scalar {
+    pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    pub fn l2_squared(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+    }
+}
+
+/// Callers pass equal-length slices and have checked the CPU features
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    pub fn dot_avx2(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: only reachable through `Kernels::new`, which checks for AVX2 and FMA
+        unsafe { dot_avx2_impl(a, b) }
+    }
+
+    pub fn l2_squared_avx2(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: as above
+        unsafe { l2_squared_avx2_impl(a, b) }
+    }
+
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn dot_avx2_impl(a: &[f32], b: &[f32]) -> f32 {
+        let chunks = a.len() / 8;
         let mut sum = _mm256_setzero_ps();
+        for i in 0..chunks {
+            let va = _mm256_loadu_ps(a.as_ptr().add(i * 8));
+            let vb = _mm256_loadu_ps(b.as_ptr().add(i * 8));
+            sum = _mm256_fmadd_ps(va, vb, sum);
+        }
+        horizontal_sum_avx2(sum) + super::scalar::dot(&a[chunks * 8..], &b[chunks * 8..])
+    }
 
+    #[target_feature(enable = "avx2", enable = "fma")]
+    unsafe fn l2_squared_avx2_impl(a: &[f32], b: &[f32]) -> f32 {
+        let chunks = a.len() / 8;
+        let mut sum = _mm256_setzero_ps();
         for i in 0..chunks {
-            let offset = i * 8;
-            let va = _mm256_loadu_ps(a.as_ptr().add(offset));
-            let vb = _mm256_loadu_ps(b.as_ptr().add(offset));
+            let va = _mm256_loadu_ps(a.as_ptr().add(i * 8));
+            let vb = _mm256_loadu_ps(b.as_ptr().add(i * 8));
             let diff = _mm256_sub_ps(va, vb);
-            let sq = _mm256_mul_ps(diff, diff);
-            sum = _mm256_add_ps(sum, sq);
+            sum = _mm256_fmadd_ps(diff, diff, sum);
         }
+        horizontal_sum_avx2(sum) + super::scalar::l2_squared(&a[chunks * 8..], &b[chunks * 8..])
+    }
 
-        // Horizontal sum
+    #[target_feature(enable = "avx2")]
+    unsafe fn horizontal_sum_avx2(sum: __m256) -> f32 {
         let high = _mm256_extractf128_ps(sum, 1);
         let low = _mm256_castps256_ps128(sum);
         let sum128 = _mm_add_ps(low, high);
         let sum64 = _mm_add_ps(sum128, _mm_movehl_ps(sum128, sum128));
         let sum32 = _mm_add_ss(sum64, _mm_shuffle_ps(sum64, sum64, 1));
+        _mm_cvtss_f32(sum32)
+    }
 
-        let mut result = _mm_cvtss_f32(sum32);
-
-        // Handle remainder
-        for i in (chunks * 8)..len {
-            let diff = a[i] - b[i];
-            result += diff * diff;
-        }
+    #[cfg(avx512_intrinsics)]
+    pub fn dot_avx512(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: only reachable through `Kernels::new`, which checks for AVX-512F
+        unsafe { dot_avx512_impl(a, b) }
+    }
 
-        result.sqrt()
+    #[cfg(avx512_intrinsics)]
+    pub fn l2_squared_avx512(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: as above
+        unsafe { l2_squared_avx512_impl(a, b) }
     }
 
-    /// SIMD-optimized dot product
-    pub fn dot_product(&self, a: &[f32], b: &[f32]) -> f32 {
-        if a.len() != b.len() { return 0.0; }
+    // The intrinsics are newer than the MSRV; build.rs only sets
+    // `avx512_intrinsics` on compilers that have them
+    #[cfg(avx512_intrinsics)]
+    #[allow(clippy::incompatible_msrv)]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn dot_avx512_impl(a: &[f32], b: &[f32]) -> f32 {
+        let chunks = a.len() / 16;
+        let mut sum = _mm512_setzero_ps();
+        for i in 0..chunks {
+            let va = _mm512_loadu_ps(a.as_ptr().add(i * 16));
+            let vb = _mm512_loadu_ps(b.as_ptr().add(i * 16));
+            sum = _mm512_fmadd_ps(va, vb, sum);
+        }
+        // The remainder in one masked load, so 8-value patterns stay in SIMD
+        let mask = tail_mask(a.len() - chunks * 16);
+        let va = _mm512_maskz_loadu_ps(mask, a.as_ptr().add(chunks * 16));
+        let vb = _mm512_maskz_loadu_ps(mask, b.as_ptr().add(chunks * 16));
+        _mm512_reduce_add_ps(_mm512_fmadd_ps(va, vb, sum))
+    }
 
-        #[cfg(target_arch = "x86_64")]
-        if self.caps.has_avx2 && a.len() >= 8 {
-            return unsafe { self.dot_product_avx2(a, b) };
+    #[cfg(avx512_intrinsics)]
+    #[allow(clippy::incompatible_msrv)]
+    #[target_feature(enable = "avx512f")]
+    unsafe fn l2_squared_avx512_impl(a: &[f32], b: &[f32]) -> f32 {
+        let chunks = a.len() / 16;
+        let mut sum = _mm512_setzero_ps();
+        for i in 0..chunks {
+            let va = _mm512_loadu_ps(a.as_ptr().add(i * 16));
+            let vb = _mm512_loadu_ps(b.as_ptr().add(i * 16));
+            let diff = _mm512_sub_ps(va, vb);
+            sum = _mm512_fmadd_ps(diff, diff, sum);
         }
+        let mask = tail_mask(a.len() - chunks * 16);
+        let va = _mm512_maskz_loadu_ps(mask, a.as_ptr().add(chunks * 16));
+        let vb = _mm512_maskz_loadu_ps(mask, b.as_ptr().add(chunks * 16));
+        let diff = _mm512_sub_ps(va, vb);
+        _mm512_reduce_add_ps(_mm512_fmadd_ps(diff, diff, sum))
+    }
 
-        self.dot_product_scalar(a, b)
+    /// Mask of the first `len` (under 16) lanes
+    #[cfg(avx512_intrinsics)]
+    #[allow(clippy::incompatible_msrv)]
+    fn tail_mask(len: usize) -> __mmask16 {
+        ((1u32 << len) - 1) as __mmask16
     }
+}
 
-    fn dot_product_scalar(&self, a: &[f32], b: &[f32]) -> f32 {
-        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+/// Callers pass equal-length slices; NEON is always there on aarch64
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: NEON is part of the aarch64 baseline
+        unsafe { dot_impl(a, b) }
     }
 
-    #[cfg(target_arch = "x86_64")]
-    #[target_feature(enable = "avx2", enable = "fma")]
-    unsafe fn dot_product_avx2(&self, a: &[f32], b: &[f32]) -> f32 {
-        use std::arch::x86_64::*;
+    pub fn l2_squared(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: as above
+        unsafe { l2_squared_impl(a, b) }
+    }
 
-        let len = a.len().min(b.len());
-        let chunks = len / 8;
-        let mut sum = _mm256_setzero_ps();
+    #[target_feature(enable = "neon")]
+    unsafe fn dot_impl(a: &[f32], b: &[f32]) -> f32 {
+        let chunks = a.len() / 4;
+        let mut sum = vdupq_n_f32(0.0);
+        for i in 0..chunks {
+            let va = vld1q_f32(a.as_ptr().add(i * 4));
+            let vb = vld1q_f32(b.as_ptr().add(i * 4));
+            sum = vfmaq_f32(sum, va, vb);
+        }
+        vaddvq_f32(sum) + super::scalar::dot(&a[chunks * 4..], &b[chunks * 4..])
+    }
 
+    #[target_feature(enable = "neon")]
+    unsafe fn l2_squared_impl(a: &[f32], b: &[f32]) -> f32 {
+        let chunks = a.len() / 4;
+        let mut sum = vdupq_n_f32(0.0);
         for i in 0..chunks {
-            let offset = i * 8;
-            let va = _mm256_loadu_ps(a.as_ptr().add(offset));
-            let vb = _mm256_loadu_ps(b.as_ptr().add(offset));
-            sum = _mm256_fmadd_ps(va, vb, sum);
+            let diff = vsubq_f32(vld1q_f32(a.as_ptr().add(i * 4)), vld1q_f32(b.as_ptr().add(i * 4)));
+            sum = vfmaq_f32(sum, diff, diff);
         }
+        vaddvq_f32(sum) + super::scalar::l2_squared(&a[chunks * 4..], &b[chunks * 4..])
+    }
+}
 
-        // Horizontal sum
-        let high = _mm256_extractf128_ps(sum, 1);
-        let low = _mm256_castps256_ps128(sum);
-        let sum128 = _mm_add_ps(low, high);
-        let sum64 = _mm_add_ps(sum128, _mm_movehl_ps(sum128, sum128));
-        let sum32 = _mm_add_ss(sum64, _mm_shuffle_ps(sum64, sum64, 1));
+/// SIMD optimizer for vector operations
+pub struct SimdOptimizer {
+    caps: CpuCapabilities,
+    kernels: &'static Kernels,
+}
 
-        let mut result = _mm_cvtss_f32(sum32);
+impl SimdOptimizer {
+    pub fn new() -> Self {
+        Self { caps: CpuCapabilities::detect(), kernels: Kernels::active() }
+    }
 
-        for i in (chunks * 8)..len {
-            result += a[i] * b[i];
-        }
+    /// SIMD-optimized Euclidean distance calculation
+    pub fn euclidean_distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() { return f32::MAX; }
+        self.kernels.l2(a, b)
+    }
 
-        result
+    /// SIMD-optimized dot product
+    pub fn dot_product(&self, a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() { return 0.0; }
+        self.kernels.dot(a, b)
     }
 
     /// Batch distance calculation
@@ -138,17 +342,17 @@ impl SimdOptimizer {
 
         // Warmup
         for _ in 0..100 {
-            let _ = black_box(self.euclidean_distance_scalar(black_box(&a), black_box(&b)));
+            let _ = black_box(SCALAR.l2(black_box(&a), black_box(&b)));
         }
 
         // Scalar benchmark
         let start = Instant::now();
         for _ in 0..iterations {
-            let _ = black_box(self.euclidean_distance_scalar(black_box(&a), black_box(&b)));
+            let _ = black_box(SCALAR.l2(black_box(&a), black_box(&b)));
         }
         let scalar_time = start.elapsed().as_secs_f64();
 
-        // SIMD benchmark (on ARM, this also uses scalar path but auto-vectorized by LLVM)
+        // Active kernel benchmark (the scalar one may still be auto-vectorized by LLVM)
         let start = Instant::now();
         for _ in 0..iterations {
             let _ = black_box(self.euclidean_distance(black_box(&a), black_box(&b)));
@@ -160,6 +364,9 @@ impl SimdOptimizer {
         (scalar_time, simd_time, speedup)
     }
 
+    /// Kernel the distance functions run on
+    pub fn kernel(&self) -> Kernel { self.kernels.kernel() }
+
     pub fn capabilities(&self) -> &CpuCapabilities { &self.caps }
 }
 
@@ -170,6 +377,7 @@ impl Default for SimdOptimizer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     #[test]
     fn test_euclidean_distance() {
@@ -186,4 +394,37 @@ mod tests {
         let b = vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
         assert!((opt.dot_product(&a, &b) - 36.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        // Random lengths cover every chunk remainder of every lane width;
+        // results differ from scalar only by summation order
+        let mut rng = StdRng::seed_from_u64(7);
+        for kernel in Kernel::available() {
+            let kernels = Kernels::new(kernel).unwrap();
+            for _ in 0..500 {
+                let len = rng.gen_range(0..100);
+                let a: Vec<f32> = (0..len).map(|_| rng.gen_range(-100.0..100.0)).collect();
+                let b: Vec<f32> = (0..len).map(|_| rng.gen_range(-100.0..100.0)).collect();
+
+                let magnitude: f32 = a.iter().zip(&b).map(|(x, y)| (x * y).abs()).sum();
+                let (dot, expected) = (kernels.dot(&a, &b), SCALAR.dot(&a, &b));
+                assert!((dot - expected).abs() <= 1e-5 * (magnitude + 1.0), "{} dot, len {}: {} vs {}", kernel, len, dot, expected);
+
+                let (l2, expected) = (kernels.l2_squared(&a, &b), SCALAR.l2_squared(&a, &b));
+                assert!((l2 - expected).abs() <= 1e-5 * (expected + 1.0), "{} l2, len {}: {} vs {}", kernel, len, l2, expected);
+                assert!(l2 >= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsupported_kernels_are_refused() {
+        let available = Kernel::available();
+        assert_eq!(available[0], Kernel::Scalar);
+        assert_eq!(Kernels::active().kernel(), *available.last().unwrap());
+        for kernel in [Kernel::Avx2, Kernel::Avx512, Kernel::Neon] {
+            assert_eq!(Kernels::new(kernel).is_some(), available.contains(&kernel));
+        }
+    }
 }
@@ -0,0 +1,381 @@
+//! Hybrid CPU topology and core pinning
+//!
+//! Intel 12th-gen and later, Apple Silicon and ARM big.LITTLE parts mix fast
+//! performance cores with slower efficiency cores. Detection uses:
+//!
+//! - Windows: `GetSystemCpuSetInformation` efficiency classes
+//! - Linux: the `cpu_core`/`cpu_atom` PMUs on Intel, `cpu_capacity` on ARM
+//! - macOS: `hw.perflevel*` sysctls
+//!
+//! The slowest class counts as efficiency cores and everything faster counts
+//! as performance cores. macOS has no affinity API, so pinning there toggles
+//! the background QoS clamp that schedules a process onto E-cores.
+
+use std::sync::OnceLock;
+
+/// Whether processes can be restricted to specific CPUs on this platform
+pub const AFFINITY_SUPPORTED: bool = cfg!(any(windows, target_os = "linux"));
+
+/// Logical CPUs grouped by core type
+#[derive(Debug, Clone, Default)]
+pub struct CpuTopology {
+    /// Logical CPU indices of performance cores (all CPUs when not hybrid)
+    pub performance: Vec<usize>,
+    /// Logical CPU indices of efficiency cores (empty when not hybrid)
+    pub efficiency: Vec<usize>,
+    /// Where the topology came from
+    pub source: &'static str,
+}
+
+impl CpuTopology {
+    /// Topology of this machine, detected once
+    pub fn get() -> &'static CpuTopology {
+        static TOPOLOGY: OnceLock<CpuTopology> = OnceLock::new();
+        TOPOLOGY.get_or_init(Self::detect)
+    }
+
+    pub fn detect() -> Self {
+        detect_platform().unwrap_or_else(|| Self {
+            performance: (0..num_cpus::get()).collect(),
+            efficiency: Vec::new(),
+            source: "uniform",
+        })
+    }
+
+    /// Build from `(cpu, class)` pairs where a higher class is faster
+    pub fn from_classes(classes: &[(usize, u32)], source: &'static str) -> Option<Self> {
+        let min = classes.iter().map(|&(_, c)| c).min()?;
+        let (mut efficiency, mut performance): (Vec<usize>, Vec<usize>) =
+            (Vec::new(), Vec::new());
+        for &(cpu, class) in classes {
+            if class == min { efficiency.push(cpu) } else { performance.push(cpu) }
+        }
+        if performance.is_empty() {
+            // Everything is the same class: uniform CPU
+            std::mem::swap(&mut performance, &mut efficiency);
+        }
+        performance.sort_unstable();
+        efficiency.sort_unstable();
+        Some(Self { performance, efficiency, source })
+    }
+
+    pub fn is_hybrid(&self) -> bool {
+        !self.performance.is_empty() && !self.efficiency.is_empty()
+    }
+
+    /// Every logical CPU
+    pub fn all(&self) -> Vec<usize> {
+        let mut all: Vec<usize> = self.performance.iter().chain(&self.efficiency).copied().collect();
+        all.sort_unstable();
+        all
+    }
+
+    pub fn print_report(&self) {
+        println!("CPU Topology:");
+        if self.is_hybrid() {
+            println!("  Hybrid:     Yes ({})", self.source);
+            println!("  P-cores:    {} logical ({})", self.performance.len(), format_cpu_list(&self.performance));
+            println!("  E-cores:    {} logical ({})", self.efficiency.len(), format_cpu_list(&self.efficiency));
+        } else {
+            println!("  Hybrid:     No ({} logical CPUs)", self.performance.len());
+        }
+    }
+}
+
+/// Restrict a process to performance cores; returns how many it may use
+pub fn pin_to_performance_cores(pid: u32) -> Result<usize, String> {
+    let topology = CpuTopology::get();
+    if !topology.is_hybrid() {
+        return Err("CPU has no efficiency cores".into());
+    }
+    #[cfg(target_os = "macos")]
+    {
+        set_background_clamp(pid, false)?;
+        Ok(topology.performance.len())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        set_process_affinity(pid, &topology.performance)?;
+        Ok(topology.performance.len())
+    }
+}
+
+/// Restrict a process to efficiency cores; returns how many it may use
+pub fn pin_to_efficiency_cores(pid: u32) -> Result<usize, String> {
+    let topology = CpuTopology::get();
+    if !topology.is_hybrid() {
+        return Err("CPU has no efficiency cores".into());
+    }
+    #[cfg(target_os = "macos")]
+    {
+        set_background_clamp(pid, true)?;
+        Ok(topology.efficiency.len())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        set_process_affinity(pid, &topology.efficiency)?;
+        Ok(topology.efficiency.len())
+    }
+}
+
+/// Let a process run on every core again
+pub fn unpin(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        set_background_clamp(pid, false)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        set_process_affinity(pid, &CpuTopology::get().all())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_background_clamp(pid: u32, background: bool) -> Result<(), String> {
+    let flag = if background { "-b" } else { "-B" };
+    let output = std::process::Command::new("taskpolicy")
+        .args([flag, "-p", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run taskpolicy: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// CPUs a process may currently run on
+#[cfg(windows)]
+pub fn process_affinity(pid: u32) -> Option<Vec<usize>> {
+    let (process, _) = crate::windows::process::get_affinity_mask(pid)?;
+    Some((0..usize::BITS as usize).filter(|c| process & (1 << c) != 0).collect())
+}
+
+#[cfg(target_os = "linux")]
+pub fn process_affinity(pid: u32) -> Option<Vec<usize>> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return None;
+        }
+        Some((0..libc::CPU_SETSIZE as usize).filter(|&c| libc::CPU_ISSET(c, &set)).collect())
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn process_affinity(_pid: u32) -> Option<Vec<usize>> {
+    None
+}
+
+/// Restrict a process to `cpus`
+///
+/// On Windows only the first processor group (64 CPUs) is addressable.
+#[cfg(windows)]
+pub fn set_process_affinity(pid: u32, cpus: &[usize]) -> Result<(), String> {
+    let mask = cpus.iter().filter(|&&c| c < usize::BITS as usize).fold(0usize, |m, &c| m | 1 << c);
+    if mask == 0 {
+        return Err("Empty CPU set".into());
+    }
+    crate::windows::process::set_affinity_mask(pid, mask)
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_process_affinity(pid: u32, cpus: &[usize]) -> Result<(), String> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus.iter().filter(|&&c| c < libc::CPU_SETSIZE as usize) {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        let result = if libc::sched_setaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        };
+        crate::security::audit::record(
+            crate::security::audit::AuditAction::Affinity,
+            "",
+            Some(pid),
+            &format!("cpus {:?}", cpus),
+            &result,
+        );
+        result
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn set_process_affinity(_pid: u32, _cpus: &[usize]) -> Result<(), String> {
+    Err("Affinity control is not available on this platform".into())
+}
+
+#[cfg(windows)]
+fn detect_platform() -> Option<CpuTopology> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemCpuSetInformation(
+            information: *mut u8,
+            buffer_length: u32,
+            returned_length: *mut u32,
+            process: isize,
+            flags: u32,
+        ) -> i32;
+    }
+
+    unsafe {
+        let mut needed = 0u32;
+        GetSystemCpuSetInformation(std::ptr::null_mut(), 0, &mut needed, 0, 0);
+        if needed == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; needed as usize];
+        if GetSystemCpuSetInformation(buffer.as_mut_ptr(), needed, &mut needed, 0, 0) == 0 {
+            return None;
+        }
+        CpuTopology::from_classes(&parse_cpu_sets(&buffer[..needed as usize]), "CPU set efficiency classes")
+    }
+}
+
+/// `(cpu, efficiency class)` from a `SYSTEM_CPU_SET_INFORMATION` buffer
+///
+/// Entries are variable-sized, so this walks the `Size` fields rather than
+/// casting to the struct.
+pub fn parse_cpu_sets(buffer: &[u8]) -> Vec<(usize, u32)> {
+    let mut classes = Vec::new();
+    let mut offset = 0;
+    while let Some(entry) = buffer.get(offset..offset + 19) {
+        let size = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as usize;
+        let kind = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+        if size == 0 {
+            break;
+        }
+        // CpuSetInformation: Id @8, Group @12, LogicalProcessorIndex @14, EfficiencyClass @18
+        if kind == 0 {
+            let group = u16::from_le_bytes([entry[12], entry[13]]) as usize;
+            classes.push((group * 64 + entry[14] as usize, entry[18] as u32));
+        }
+        offset += size;
+    }
+    classes
+}
+
+#[cfg(target_os = "linux")]
+fn detect_platform() -> Option<CpuTopology> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+
+    // Intel hybrid parts expose one PMU per core type
+    if let (Some(core), Some(atom)) = (read("/sys/devices/cpu_core/cpus"), read("/sys/devices/cpu_atom/cpus")) {
+        let topology = CpuTopology {
+            performance: parse_cpu_list(&core),
+            efficiency: parse_cpu_list(&atom),
+            source: "cpu_core/cpu_atom PMUs",
+        };
+        if topology.is_hybrid() {
+            return Some(topology);
+        }
+    }
+
+    // ARM: relative capacity per CPU, 1024 for the fastest
+    let classes: Vec<(usize, u32)> = std::fs::read_dir("/sys/devices/system/cpu")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let cpu: usize = name.strip_prefix("cpu")?.parse().ok()?;
+            let capacity = std::fs::read_to_string(entry.path().join("cpu_capacity")).ok()?;
+            Some((cpu, capacity.trim().parse().ok()?))
+        })
+        .collect();
+    CpuTopology::from_classes(&classes, "cpu_capacity")
+}
+
+#[cfg(target_os = "macos")]
+fn detect_platform() -> Option<CpuTopology> {
+    let sysctl = |name: &str| -> Option<usize> {
+        let output = std::process::Command::new("sysctl").args(["-n", name]).output().ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    };
+
+    // perflevel0 is the fastest level
+    if sysctl("hw.nperflevels")? < 2 {
+        return None;
+    }
+    let performance = sysctl("hw.perflevel0.logicalcpu")?;
+    let efficiency = sysctl("hw.perflevel1.logicalcpu")?;
+    Some(CpuTopology {
+        performance: (0..performance).collect(),
+        efficiency: (performance..performance + efficiency).collect(),
+        source: "hw.perflevel sysctls",
+    })
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn detect_platform() -> Option<CpuTopology> {
+    None
+}
+
+/// Parse a kernel CPU list such as `0-7,16,18-19`
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    cpus.extend(start..=end);
+                }
+            }
+            None => cpus.extend(part.parse::<usize>().ok()),
+        }
+    }
+    cpus
+}
+
+/// Inverse of [`parse_cpu_list`]
+fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = cpus.iter().copied().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end = iter.next().unwrap_or(end);
+        }
+        ranges.push(if start == end { start.to_string() } else { format!("{}-{}", start, end) });
+    }
+    ranges.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_list_roundtrip() {
+        let cpus = parse_cpu_list("0-3,8,10-11\n");
+        assert_eq!(cpus, vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(format_cpu_list(&cpus), "0-3,8,10-11");
+    }
+
+    #[test]
+    fn test_from_classes() {
+        // Two P-core threads at class 1, two E-cores at class 0
+        let hybrid = CpuTopology::from_classes(&[(0, 1), (1, 1), (2, 0), (3, 0)], "test").unwrap();
+        assert!(hybrid.is_hybrid());
+        assert_eq!(hybrid.performance, vec![0, 1]);
+        assert_eq!(hybrid.efficiency, vec![2, 3]);
+
+        let uniform = CpuTopology::from_classes(&[(0, 1024), (1, 1024)], "test").unwrap();
+        assert!(!uniform.is_hybrid());
+        assert_eq!(uniform.performance, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_parse_cpu_sets() {
+        let mut buffer = Vec::new();
+        for (index, class) in [(0u8, 1u8), (1, 0)] {
+            let mut entry = vec![0u8; 32];
+            entry[..4].copy_from_slice(&32u32.to_le_bytes());
+            entry[14] = index;
+            entry[18] = class;
+            buffer.extend(entry);
+        }
+        assert_eq!(parse_cpu_sets(&buffer), vec![(0, 1), (1, 0)]);
+    }
+}
@@ -1,10 +1,12 @@
 //! Resource Bridge - CPU/GPU/NPU Unified Resource Management
 //!
 //! Orchestrates memory and compute resources across different devices
-//! for optimal AI workload performance.
+//! for optimal AI workload performance. On Apple silicon the GPU shares
+//! system RAM, so layers are never split between GPU and RAM and pressure
+//! is relieved by unloading rather than offloading.
 
 use serde::{Deserialize, Serialize};
-use super::gpu::{GpuMonitor, VramStatus, GpuVendor};
+use super::gpu::{GpuMonitor, UnifiedMemory, VramStatus};
 use std::collections::HashMap;
 
 /// Device types for resource allocation
@@ -114,6 +116,8 @@ pub struct SystemResources {
     pub available_ram: u64,
     /// Per-GPU VRAM status
     pub gpu_vram: Vec<VramStatus>,
+    /// Wired and GPU memory when the GPU shares system RAM
+    pub unified: Option<UnifiedMemory>,
     /// CPU utilization percentage
     pub cpu_utilization: f32,
     /// CPU temperature (if available)
@@ -160,6 +164,7 @@ impl ResourceBridge {
             .as_ref()
             .map(|m| m.status())
             .unwrap_or_default();
+        let unified = self.unified_memory();
 
         // Get system memory info
         let (total_ram, available_ram) = self.get_system_memory();
@@ -168,6 +173,7 @@ impl ResourceBridge {
             total_ram,
             available_ram,
             gpu_vram,
+            unified,
             cpu_utilization: self.get_cpu_utilization(),
             cpu_temp: None, // Would need WMI or similar
         }
@@ -196,7 +202,14 @@ impl ResourceBridge {
 
     #[cfg(not(windows))]
     fn get_system_memory(&self) -> (u64, u64) {
-        (0, 0)
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        (system.total_memory(), system.available_memory())
+    }
+
+    /// Wired and GPU memory, when the GPU shares system RAM
+    pub fn unified_memory(&self) -> Option<UnifiedMemory> {
+        self.gpu_monitor.as_ref().and_then(|m| m.unified_memory())
     }
 
     /// Get CPU utilization
@@ -212,13 +225,20 @@ impl ResourceBridge {
     }
 
     /// Calculate available VRAM considering reserve
+    ///
+    /// With unified memory the RAM reserve applies too, as both come out of
+    /// the same pool.
     pub fn available_vram(&self) -> u64 {
         let resources = self.get_resources();
         let total_vram: u64 = resources.gpu_vram.iter().map(|v| v.total).sum();
-        let used_vram: u64 = resources.gpu_vram.iter().map(|v| v.used).sum();
+        let free_vram: u64 = resources.gpu_vram.iter().map(|v| v.free).sum();
         let reserved = (total_vram as f64 * self.vram_reserve_percent as f64 / 100.0) as u64;
+        let available = free_vram.saturating_sub(reserved);
 
-        total_vram.saturating_sub(used_vram).saturating_sub(reserved)
+        match resources.unified {
+            Some(_) => available.min(resources.available_ram.saturating_sub(self.ram_reserve_bytes)),
+            None => available,
+        }
     }
 
     /// Calculate available RAM considering reserve
@@ -228,6 +248,9 @@ impl ResourceBridge {
     }
 
     /// Plan placement for a model
+    ///
+    /// With unified memory every layer that fits goes to the GPU, since CPU
+    /// layers would take the same RAM; the rest stay paged in from storage.
     pub fn plan_placement(
         &self,
         model_id: &str,
@@ -237,6 +260,7 @@ impl ResourceBridge {
     ) -> PlacementPlan {
         let available_vram = self.available_vram();
         let _available_ram = self.available_ram();
+        let unified = self.unified_memory().is_some();
 
         let layer_size = model_size_bytes / num_layers as u64;
         let mut layers = Vec::new();
@@ -247,12 +271,16 @@ impl ResourceBridge {
         let vram_for_layers = available_vram.saturating_sub(kv_cache_bytes);
         let max_gpu_layers = (vram_for_layers / layer_size) as u32;
 
-        let gpu_layers = match self.strategy {
-            PlacementStrategy::GPUFirst => max_gpu_layers.min(num_layers),
-            PlacementStrategy::Balanced => (max_gpu_layers / 2).min(num_layers),
-            PlacementStrategy::LatencyOptimized => max_gpu_layers.min(num_layers),
-            PlacementStrategy::PowerEfficient => (max_gpu_layers / 3).min(num_layers),
-            PlacementStrategy::ThroughputOptimized => max_gpu_layers.min(num_layers),
+        let gpu_layers = if unified {
+            max_gpu_layers.min(num_layers)
+        } else {
+            match self.strategy {
+                PlacementStrategy::GPUFirst => max_gpu_layers.min(num_layers),
+                PlacementStrategy::Balanced => (max_gpu_layers / 2).min(num_layers),
+                PlacementStrategy::LatencyOptimized => max_gpu_layers.min(num_layers),
+                PlacementStrategy::PowerEfficient => (max_gpu_layers / 3).min(num_layers),
+                PlacementStrategy::ThroughputOptimized => max_gpu_layers.min(num_layers),
+            }
         };
 
         // Allocate layers
@@ -260,6 +288,8 @@ impl ResourceBridge {
             let device = if i < gpu_layers {
                 vram_used += layer_size;
                 DeviceType::GPU(0)
+            } else if unified {
+                DeviceType::Storage
             } else {
                 ram_used += layer_size;
                 DeviceType::RAM
@@ -274,7 +304,7 @@ impl ResourceBridge {
         }
 
         // Allocate KV cache (prefer VRAM)
-        let kv_device = if vram_used + kv_cache_bytes <= available_vram {
+        let kv_device = if unified || vram_used + kv_cache_bytes <= available_vram {
             vram_used += kv_cache_bytes;
             DeviceType::GPU(0)
         } else {
@@ -307,6 +337,11 @@ impl ResourceBridge {
         let resources = self.get_resources();
 
         // Check VRAM pressure
+        if let Some(unified) = &resources.unified {
+            if unified.usage_percent() > 95.0 {
+                return true;
+            }
+        }
         for vram in &resources.gpu_vram {
             if vram.usage_percent() > 95.0 {
                 return true;
@@ -315,7 +350,7 @@ impl ResourceBridge {
 
         // Check RAM pressure
         let ram_usage = 100.0 - (resources.available_ram as f64 / resources.total_ram as f64 * 100.0);
-        if ram_usage > 90.0 {
+        if resources.total_ram > 0 && ram_usage > 90.0 {
             return true;
         }
 
@@ -323,10 +358,36 @@ impl ResourceBridge {
     }
 
     /// Suggest offloading if under pressure
+    ///
+    /// With unified memory, offloading to RAM frees nothing; the suggestion
+    /// is to drop weights to storage by unloading or using a smaller quant.
     pub fn suggest_offload(&self) -> Vec<OffloadSuggestion> {
         let mut suggestions = Vec::new();
         let resources = self.get_resources();
 
+        if let Some(unified) = &resources.unified {
+            if unified.usage_percent() > 90.0 {
+                let over_mb = unified
+                    .gpu_in_use_mb
+                    .saturating_sub(unified.gpu_limit_mb * 4 / 5)
+                    .max(unified.wired_mb.saturating_sub(unified.total_mb * 4 / 5));
+                suggestions.push(OffloadSuggestion {
+                    from: DeviceType::GPU(0),
+                    to: DeviceType::Storage,
+                    bytes: over_mb * 1024 * 1024,
+                    reason: format!(
+                        "unified memory at {:.1}% ({} MB wired, GPU using {} of {} MB); \
+                         the CPU shares this RAM, so unload a model or use a smaller quantization",
+                        unified.usage_percent(),
+                        unified.wired_mb,
+                        unified.gpu_in_use_mb,
+                        unified.gpu_limit_mb
+                    ),
+                });
+            }
+            return suggestions;
+        }
+
         for (i, vram) in resources.gpu_vram.iter().enumerate() {
             if vram.usage_percent() > 90.0 {
                 let to_offload = vram.used.saturating_sub(
@@ -0,0 +1,292 @@
+//! PyTorch and other CUDA workloads in Python
+//!
+//! Python processes holding a CUDA context show up in the NVML or
+//! `nvidia-smi` process list. A few seconds of per-process SM utilization
+//! tell training, which keeps the GPU busy, from inference, which runs in
+//! bursts. PyTorch's caching allocator keeps freed blocks reserved, so an
+//! idle process can hold gigabytes it no longer uses, and without
+//! `expandable_segments` those blocks fragment as tensor sizes change.
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use super::gpu::{GpuMonitor, GpuVendor};
+use crate::core::undo::{self, UndoAction, UndoEntry};
+use crate::features::build::priority;
+
+/// Smaller CUDA contexts are left out
+pub const MIN_CONTEXT_MB: u64 = 1024;
+/// Seconds of utilization sampled per run
+pub const SAMPLES: u32 = 5;
+
+/// Command-line words that mark a training run
+const TRAINING_HINTS: &[&str] = &["train", "finetune", "fine_tune", "torchrun", "deepspeed", "accelerate", "pretrain"];
+
+/// What a CUDA process is doing, from its utilization pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CudaRole {
+    /// Steady, high utilization
+    Training,
+    /// Bursts of work between idle stretches
+    Inference,
+    /// Holding memory with no work running
+    Idle,
+}
+
+impl std::fmt::Display for CudaRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CudaRole::Training => write!(f, "training"),
+            CudaRole::Inference => write!(f, "inference"),
+            CudaRole::Idle => write!(f, "idle"),
+        }
+    }
+}
+
+/// A Python process with a CUDA context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CudaProcess {
+    pub pid: u32,
+    pub name: String,
+    /// Script or module it runs, e.g. `train.py`
+    pub script: Option<String>,
+    pub gpu_index: Option<u32>,
+    pub vram_mb: u64,
+    pub role: CudaRole,
+    /// SM utilization, one sample per second
+    pub utilization: Vec<u32>,
+    /// `PYTORCH_CUDA_ALLOC_CONF` has `expandable_segments:True`
+    pub expandable_segments: bool,
+}
+
+impl CudaProcess {
+    pub fn mean_utilization(&self) -> u32 {
+        mean(&self.utilization)
+    }
+
+    /// Ways to free or defragment this process's VRAM
+    pub fn hints(&self) -> Vec<String> {
+        let mut hints = Vec::new();
+        match self.role {
+            CudaRole::Idle => hints.push(format!(
+                "holds {} MB with no work running; call torch.cuda.empty_cache() when done, or stop it",
+                self.vram_mb
+            )),
+            CudaRole::Inference => hints.push(
+                "call torch.cuda.empty_cache() between batches of requests to hand reserved blocks back".to_string(),
+            ),
+            CudaRole::Training => hints.push(
+                "keeps the GPU busy; lower its priority while you use the desktop (`ai cuda --lower-priority`)"
+                    .to_string(),
+            ),
+        }
+        if !self.expandable_segments {
+            hints.push(
+                "set PYTORCH_CUDA_ALLOC_CONF=expandable_segments:True before starting it to reduce fragmentation"
+                    .to_string(),
+            );
+        }
+        hints
+    }
+}
+
+/// Python processes holding at least [`MIN_CONTEXT_MB`] on an NVIDIA GPU
+///
+/// Blocks for `samples` seconds while utilization is sampled.
+pub fn detect(monitor: &GpuMonitor, samples: u32) -> Vec<CudaProcess> {
+    let on_nvidia = |index: Option<u32>| {
+        index.map_or(true, |i| monitor.gpus().iter().any(|g| g.index == i && g.vendor == GpuVendor::Nvidia))
+    };
+    let holders: Vec<_> = monitor
+        .get_gpu_processes()
+        .into_iter()
+        .filter(|p| p.vram_mb() >= MIN_CONTEXT_MB && is_python(&p.name) && on_nvidia(p.gpu_index))
+        .collect();
+    if holders.is_empty() {
+        return Vec::new();
+    }
+
+    let pids: Vec<Pid> = holders.iter().map(|p| Pid::from_u32(p.pid)).collect();
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing().with_cmd(UpdateKind::OnlyIfNotSet).with_environ(UpdateKind::OnlyIfNotSet),
+    );
+    let history = monitor.sample_process_utilization(samples);
+
+    let mut processes: Vec<CudaProcess> = holders
+        .into_iter()
+        .map(|holder| {
+            let process = system.process(Pid::from_u32(holder.pid));
+            let args: Vec<String> = process
+                .map(|p| p.cmd().iter().map(|a| a.to_string_lossy().into_owned()).collect())
+                .unwrap_or_default();
+            let expandable_segments = process.is_some_and(|p| {
+                p.environ().iter().any(|var| has_expandable_segments(&var.to_string_lossy()))
+            });
+            let utilization = history.get(&holder.pid).cloned().unwrap_or_default();
+            CudaProcess {
+                pid: holder.pid,
+                vram_mb: holder.vram_mb(),
+                name: holder.name,
+                script: script_arg(&args),
+                gpu_index: holder.gpu_index,
+                role: classify(&utilization, is_training_command(&args.join(" "))),
+                utilization,
+                expandable_segments,
+            }
+        })
+        .collect();
+    processes.sort_by_key(|p| std::cmp::Reverse(p.vram_mb));
+    processes
+}
+
+/// Give training processes below-normal priority, undoable with `undo`
+///
+/// Returns the PIDs lowered. Only normal-priority processes are changed.
+pub fn lower_training_priority(processes: &[CudaProcess]) -> Result<Vec<u32>, String> {
+    if !priority::can_reprioritize() {
+        return Err("Restoring priority needs root; run with sudo".to_string());
+    }
+    let mut lowered = Vec::new();
+    let mut actions = Vec::new();
+    for process in processes.iter().filter(|p| p.role == CudaRole::Training) {
+        if priority::priority(process.pid) != Some(priority::NORMAL) {
+            continue;
+        }
+        if priority::set_priority(process.pid, priority::BELOW_NORMAL).is_err() {
+            continue;
+        }
+        lowered.push(process.pid);
+        actions.push(UndoAction::RestorePriority {
+            name: process.name.clone(),
+            pid: process.pid,
+            priority: priority::NORMAL,
+        });
+    }
+    if !actions.is_empty() {
+        undo::record(UndoEntry::now(format!("Lowered priority of {} training process(es)", lowered.len()), actions));
+    }
+    Ok(lowered)
+}
+
+/// Print a table of CUDA processes with their hints
+pub fn print(processes: &[CudaProcess]) {
+    println!("\n🔥 CUDA Workloads\n");
+    if processes.is_empty() {
+        println!("No Python process holds {} MB or more on an NVIDIA GPU.", MIN_CONTEXT_MB);
+        return;
+    }
+
+    for process in processes {
+        let gpu = process.gpu_index.map(|i| format!("GPU {}", i)).unwrap_or_else(|| "GPU ?".to_string());
+        let script = process.script.as_deref().map(|s| format!(" {}", s)).unwrap_or_default();
+        let utilization = if process.utilization.is_empty() {
+            "utilization unknown".to_string()
+        } else {
+            format!("{}% SM on average", process.mean_utilization())
+        };
+        println!(
+            "{:>7}  {}{}  {}: {} MB, {}, {}",
+            process.pid, process.name, script, gpu, process.vram_mb, process.role, utilization
+        );
+        for hint in process.hints() {
+            println!("         • {}", hint);
+        }
+    }
+}
+
+/// Training keeps the SMs busy in nearly every sample; inference leaves gaps
+fn classify(utilization: &[u32], training_command: bool) -> CudaRole {
+    if utilization.is_empty() {
+        return if training_command { CudaRole::Training } else { CudaRole::Inference };
+    }
+    let mean = mean(utilization);
+    if mean < 5 {
+        return CudaRole::Idle;
+    }
+    // Busy in at least four samples out of five
+    let busy = utilization.iter().filter(|&&u| u >= 30).count();
+    let sustained = busy * 5 >= utilization.len() * 4;
+    if (sustained && mean >= 60) || (training_command && mean >= 30) {
+        CudaRole::Training
+    } else {
+        CudaRole::Inference
+    }
+}
+
+fn mean(values: &[u32]) -> u32 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.iter().sum::<u32>() / values.len() as u32
+}
+
+fn is_python(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let stem = name.strip_suffix(".exe").unwrap_or(&name);
+    stem.starts_with("python") || stem == "torchrun" || stem == "accelerate" || stem == "deepspeed"
+}
+
+fn is_training_command(command: &str) -> bool {
+    let command = command.to_lowercase();
+    TRAINING_HINTS.iter().any(|hint| command.contains(hint))
+}
+
+/// `PYTORCH_CUDA_ALLOC_CONF=expandable_segments:True,...`
+fn has_expandable_segments(var: &str) -> bool {
+    var.strip_prefix("PYTORCH_CUDA_ALLOC_CONF=")
+        .or_else(|| var.strip_prefix("PYTORCH_ALLOC_CONF="))
+        .is_some_and(|conf| {
+            conf.split(',').any(|option| option.trim().eq_ignore_ascii_case("expandable_segments:true"))
+        })
+}
+
+/// The script or `-m` module after the interpreter
+fn script_arg(args: &[String]) -> Option<String> {
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-m" {
+            return iter.next().cloned();
+        }
+        if !arg.starts_with('-') {
+            return std::path::Path::new(arg).file_name().map(|n| n.to_string_lossy().into_owned());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_by_utilization_pattern() {
+        assert_eq!(classify(&[97, 99, 95, 98, 96], false), CudaRole::Training);
+        assert_eq!(classify(&[0, 85, 0, 0, 60], false), CudaRole::Inference);
+        assert_eq!(classify(&[0, 0, 0, 2, 0], false), CudaRole::Idle);
+        // A training script stalled on its data loader is still training
+        assert_eq!(classify(&[40, 0, 55, 45, 35], true), CudaRole::Training);
+        assert_eq!(classify(&[], true), CudaRole::Training);
+        assert_eq!(classify(&[], false), CudaRole::Inference);
+    }
+
+    #[test]
+    fn reads_command_and_environment() {
+        let args: Vec<String> = ["python3", "-u", "/home/me/llm/train.py", "--epochs", "3"].map(String::from).into();
+        assert_eq!(script_arg(&args), Some("train.py".to_string()));
+        assert!(is_training_command(&args.join(" ")));
+        let args: Vec<String> = ["python", "-m", "vllm.entrypoints.api_server"].map(String::from).into();
+        assert_eq!(script_arg(&args), Some("vllm.entrypoints.api_server".to_string()));
+        assert!(!is_training_command(&args.join(" ")));
+
+        assert!(is_python("python3.11"));
+        assert!(is_python("pythonw.exe"));
+        assert!(!is_python("ollama"));
+
+        assert!(has_expandable_segments("PYTORCH_CUDA_ALLOC_CONF=max_split_size_mb:128,expandable_segments:True"));
+        assert!(!has_expandable_segments("PYTORCH_CUDA_ALLOC_CONF=max_split_size_mb:128"));
+        assert!(!has_expandable_segments("PATH=/usr/bin"));
+    }
+}
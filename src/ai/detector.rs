@@ -3,7 +3,10 @@
 //! Automatically detects running AI inference engines and workloads.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// Process names too common to mean an AI runtime without a command-line hint
+const GENERIC_NAMES: &[&str] = &["python", "python3", "pythonw", "main", "server", "ray"];
 
 /// Known AI runtime types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -73,167 +76,138 @@ pub struct ActiveWorkload {
     pub model: Option<String>,
     /// Is actively running inference
     pub is_active: bool,
+    /// Port of its HTTP API on this machine, from `--port` or the runtime's default
+    #[serde(default)]
+    pub port: Option<u16>,
 }
 
 /// Process signatures for AI runtime detection
 struct ProcessSignature {
     name_patterns: Vec<&'static str>,
+    /// Libraries or command-line fragments; required when the name is generic
     dll_patterns: Vec<&'static str>,
     port: Option<u16>,
 }
 
 /// AI workload detector
 pub struct AIWorkloadDetector {
-    signatures: HashMap<AIRuntime, ProcessSignature>,
+    /// Checked in order, so specific Python runtimes come before PyTorch
+    signatures: Vec<(AIRuntime, ProcessSignature)>,
 }
 
 impl AIWorkloadDetector {
     pub fn new() -> Self {
-        let mut signatures = HashMap::new();
-
-        signatures.insert(AIRuntime::Ollama, ProcessSignature {
-            name_patterns: vec!["ollama", "ollama_llama_server"],
-            dll_patterns: vec![],
-            port: Some(11434),
-        });
-
-        signatures.insert(AIRuntime::LlamaCpp, ProcessSignature {
-            name_patterns: vec!["llama", "main", "server", "llama-server", "llama-cli"],
-            dll_patterns: vec!["ggml"],
-            port: Some(8080),
-        });
-
-        signatures.insert(AIRuntime::VLLM, ProcessSignature {
-            name_patterns: vec!["vllm", "ray"],
-            dll_patterns: vec![],
-            port: Some(8000),
-        });
-
-        signatures.insert(AIRuntime::PyTorch, ProcessSignature {
-            name_patterns: vec!["python", "python3", "pythonw"],
-            dll_patterns: vec!["torch", "cuda", "cudnn"],
-            port: None,
-        });
-
-        signatures.insert(AIRuntime::ONNXRuntime, ProcessSignature {
-            name_patterns: vec![],
-            dll_patterns: vec!["onnxruntime", "onnxruntime_providers_cuda"],
-            port: None,
-        });
-
-        signatures.insert(AIRuntime::Whisper, ProcessSignature {
-            name_patterns: vec!["whisper", "whisper.cpp"],
-            dll_patterns: vec![],
-            port: None,
-        });
-
-        signatures.insert(AIRuntime::StableDiffusion, ProcessSignature {
-            name_patterns: vec!["python"],
-            dll_patterns: vec!["diffusers", "stable_diffusion"],
-            port: Some(7860), // Gradio default
-        });
-
-        signatures.insert(AIRuntime::ComfyUI, ProcessSignature {
-            name_patterns: vec!["python"],
-            dll_patterns: vec!["comfy"],
-            port: Some(8188),
-        });
-
-        signatures.insert(AIRuntime::RuVLLM, ProcessSignature {
-            name_patterns: vec!["ruvllm", "ruvector-llm"],
-            dll_patterns: vec![],
-            port: Some(8080),
-        });
-
-        signatures.insert(AIRuntime::LMStudio, ProcessSignature {
-            name_patterns: vec!["lm studio", "lmstudio"],
-            dll_patterns: vec![],
-            port: Some(1234),
-        });
-
-        signatures.insert(AIRuntime::TGI, ProcessSignature {
-            name_patterns: vec!["text-generation"],
-            dll_patterns: vec![],
-            port: Some(3000),
-        });
+        let signatures = vec![
+            (AIRuntime::Ollama, ProcessSignature {
+                name_patterns: vec!["ollama", "ollama_llama_server"],
+                dll_patterns: vec![],
+                port: Some(11434),
+            }),
+            (AIRuntime::LlamaCpp, ProcessSignature {
+                name_patterns: vec!["llama", "main", "server", "llama-server", "llama-cli"],
+                dll_patterns: vec!["ggml", "gguf"],
+                port: Some(8080),
+            }),
+            (AIRuntime::VLLM, ProcessSignature {
+                name_patterns: vec!["vllm", "ray"],
+                dll_patterns: vec!["vllm"],
+                port: Some(8000),
+            }),
+            (AIRuntime::ONNXRuntime, ProcessSignature {
+                name_patterns: vec![],
+                dll_patterns: vec!["onnxruntime", "onnxruntime_providers_cuda"],
+                port: None,
+            }),
+            (AIRuntime::Whisper, ProcessSignature {
+                name_patterns: vec!["whisper", "whisper.cpp"],
+                dll_patterns: vec![],
+                port: None,
+            }),
+            (AIRuntime::StableDiffusion, ProcessSignature {
+                name_patterns: vec!["python"],
+                dll_patterns: vec!["diffusers", "stable_diffusion"],
+                port: Some(7860), // Gradio default
+            }),
+            (AIRuntime::ComfyUI, ProcessSignature {
+                name_patterns: vec!["python"],
+                dll_patterns: vec!["comfy"],
+                port: Some(8188),
+            }),
+            (AIRuntime::PyTorch, ProcessSignature {
+                name_patterns: vec!["python", "python3", "pythonw"],
+                dll_patterns: vec!["torch", "cuda", "cudnn"],
+                port: None,
+            }),
+            (AIRuntime::RuVLLM, ProcessSignature {
+                name_patterns: vec!["ruvllm", "ruvector-llm"],
+                dll_patterns: vec![],
+                port: Some(8080),
+            }),
+            (AIRuntime::LMStudio, ProcessSignature {
+                name_patterns: vec!["lm studio", "lmstudio"],
+                dll_patterns: vec![],
+                port: Some(1234),
+            }),
+            (AIRuntime::TGI, ProcessSignature {
+                name_patterns: vec!["text-generation"],
+                dll_patterns: vec![],
+                port: Some(3000),
+            }),
+        ];
 
         Self { signatures }
     }
 
     /// Detect all running AI workloads
     pub fn detect(&self) -> Vec<ActiveWorkload> {
-        let mut workloads = Vec::new();
-
-        // Use Windows API to enumerate processes
-        #[cfg(windows)]
-        {
-            use windows::Win32::System::ProcessStatus::{
-                EnumProcesses, GetModuleBaseNameW,
-            };
-            use windows::Win32::System::Threading::{
-                OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-            };
-            use windows::Win32::Foundation::CloseHandle;
-
-            unsafe {
-                let mut pids = [0u32; 2048];
-                let mut bytes_returned = 0u32;
-
-                if EnumProcesses(
-                    pids.as_mut_ptr(),
-                    (pids.len() * std::mem::size_of::<u32>()) as u32,
-                    &mut bytes_returned,
-                ).is_ok() {
-                    let num_processes = bytes_returned as usize / std::mem::size_of::<u32>();
-
-                    for &pid in &pids[..num_processes] {
-                        if pid == 0 {
-                            continue;
-                        }
-
-                        if let Ok(handle) = OpenProcess(
-                            PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-                            false,
-                            pid,
-                        ) {
-                            let mut name_buf = [0u16; 260];
-                            let len = GetModuleBaseNameW(handle, None, &mut name_buf);
-
-                            if len > 0 {
-                                let name = String::from_utf16_lossy(&name_buf[..len as usize])
-                                    .to_lowercase();
-
-                                // Check against signatures
-                                if let Some(runtime) = self.match_process(&name) {
-                                    workloads.push(ActiveWorkload {
-                                        pid,
-                                        name: name.clone(),
-                                        runtime,
-                                        vram_mb: None, // Will be filled by GPU monitor
-                                        ram_mb: self.get_process_memory(pid),
-                                        model: None,
-                                        is_active: true,
-                                    });
-                                }
-                            }
-
-                            let _ = CloseHandle(handle);
-                        }
-                    }
-                }
-            }
-        }
-
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_memory().with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+
+        let mut workloads: Vec<ActiveWorkload> = system
+            .processes()
+            .iter()
+            .filter(|(_, process)| process.thread_kind().is_none())
+            .filter_map(|(pid, process)| {
+                let name = process.name().to_string_lossy().to_lowercase();
+                let args: Vec<String> = process.cmd().iter().map(|a| a.to_string_lossy().into_owned()).collect();
+                let runtime = self.match_process(&name, &args.join(" ").to_lowercase())?;
+                Some(ActiveWorkload {
+                    pid: pid.as_u32(),
+                    name,
+                    runtime,
+                    vram_mb: None, // Will be filled by GPU monitor
+                    ram_mb: process.memory() / (1024 * 1024),
+                    model: model_arg(&args),
+                    is_active: true,
+                    port: port_arg(&args).or_else(|| self.default_port(runtime)),
+                })
+            })
+            .collect();
+        workloads.sort_by_key(|w| std::cmp::Reverse(w.ram_mb));
         workloads
     }
 
-    /// Match process name to AI runtime
-    fn match_process(&self, name: &str) -> Option<AIRuntime> {
+    fn default_port(&self, runtime: AIRuntime) -> Option<u16> {
+        self.signatures.iter().find(|(r, _)| *r == runtime).and_then(|(_, sig)| sig.port)
+    }
+
+    /// Match a process name, and its command line for generic names, to an AI runtime
+    fn match_process(&self, name: &str, command: &str) -> Option<AIRuntime> {
         let name_lower = name.to_lowercase();
+        let stem = name_lower.strip_suffix(".exe").unwrap_or(&name_lower);
 
         for (runtime, sig) in &self.signatures {
             for pattern in &sig.name_patterns {
-                if name_lower.contains(pattern) {
+                let matched = if GENERIC_NAMES.contains(pattern) {
+                    stem == *pattern && sig.dll_patterns.iter().any(|hint| command.contains(hint))
+                } else {
+                    stem.contains(pattern)
+                };
+                if matched {
                     return Some(*runtime);
                 }
             }
@@ -242,43 +216,6 @@ impl AIWorkloadDetector {
         None
     }
 
-    /// Get process memory usage
-    #[cfg(windows)]
-    fn get_process_memory(&self, pid: u32) -> u64 {
-        use windows::Win32::System::ProcessStatus::GetProcessMemoryInfo;
-        use windows::Win32::System::ProcessStatus::PROCESS_MEMORY_COUNTERS;
-        use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
-        use windows::Win32::Foundation::CloseHandle;
-
-        unsafe {
-            if let Ok(handle) = OpenProcess(
-                PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-                false,
-                pid,
-            ) {
-                let mut pmc = PROCESS_MEMORY_COUNTERS::default();
-                pmc.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
-
-                if GetProcessMemoryInfo(
-                    handle,
-                    &mut pmc,
-                    std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
-                ).is_ok() {
-                    let _ = CloseHandle(handle);
-                    return pmc.WorkingSetSize as u64 / (1024 * 1024); // Convert to MB
-                }
-
-                let _ = CloseHandle(handle);
-            }
-        }
-        0
-    }
-
-    #[cfg(not(windows))]
-    fn get_process_memory(&self, _pid: u32) -> u64 {
-        0
-    }
-
     /// Check if a specific port is in use (indicating a service)
     pub fn check_port(&self, port: u16) -> bool {
         use std::net::TcpStream;
@@ -306,3 +243,54 @@ impl Default for AIWorkloadDetector {
         Self::new()
     }
 }
+
+/// Model given with `-m` or `--model`, as llama.cpp, vLLM and whisper.cpp take it
+fn model_arg(args: &[String]) -> Option<String> {
+    let value = args.iter().enumerate().find_map(|(i, arg)| match arg.as_str() {
+        "-m" | "--model" => args.get(i + 1).map(String::as_str),
+        _ => arg.strip_prefix("--model="),
+    })?;
+    let file = value.rsplit(['/', '\\']).next()?;
+    Some(file.strip_suffix(".gguf").unwrap_or(file).to_string())
+}
+
+/// Port given with `--port`, as llama-server and `vllm serve` take it
+fn port_arg(args: &[String]) -> Option<u16> {
+    args.iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.as_str() {
+            "--port" => args.get(i + 1).map(String::as_str),
+            _ => arg.strip_prefix("--port="),
+        })?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generic_names_need_a_command_line_hint() {
+        let detector = AIWorkloadDetector::new();
+        assert_eq!(detector.match_process("ollama.exe", ""), Some(AIRuntime::Ollama));
+        assert_eq!(detector.match_process("ollama_llama_server", ""), Some(AIRuntime::Ollama));
+        assert_eq!(detector.match_process("llama-server", ""), Some(AIRuntime::LlamaCpp));
+        assert_eq!(detector.match_process("server", "server --port 3000"), None);
+        assert_eq!(detector.match_process("main", "./main -m models/q4.gguf"), Some(AIRuntime::LlamaCpp));
+        assert_eq!(detector.match_process("python3", "python3 manage.py runserver"), None);
+        assert_eq!(detector.match_process("python", "python comfyui/main.py --cuda-device 0"), Some(AIRuntime::ComfyUI));
+        assert_eq!(detector.match_process("python.exe", "python train.py --device cuda"), Some(AIRuntime::PyTorch));
+    }
+
+    #[test]
+    fn reads_model_and_port_arguments() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(model_arg(&args(&["llama-server", "-m", "/models/llama-3-8b.Q4_K_M.gguf"])), Some("llama-3-8b.Q4_K_M".to_string()));
+        assert_eq!(model_arg(&args(&["vllm", "serve", "--model=mistral-7b"])), Some("mistral-7b".to_string()));
+        assert_eq!(model_arg(&args(&["ollama", "serve"])), None);
+        assert_eq!(port_arg(&args(&["llama-server", "--port", "8081"])), Some(8081));
+        assert_eq!(port_arg(&args(&["vllm", "serve", "--port=9000"])), Some(9000));
+        assert_eq!(port_arg(&args(&["vllm", "serve"])), None);
+    }
+}
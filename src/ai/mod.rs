@@ -8,6 +8,8 @@
 //! - **Workload Detection**: ML-based classification of gaming, coding, video editing
 //! - **GPU/VRAM Management**: Monitor and optimize GPU memory for AI workloads
 //! - **Ollama Integration**: Manage models, unload inactive, optimize VRAM
+//! - **llama.cpp and vLLM**: Report KV-cache use and unload idle servers
+//! - **PyTorch/CUDA**: Tell training from inference and suggest allocator fixes
 //! - **Resource Bridge**: Unified CPU/GPU/NPU resource orchestration
 //! - **Game Mode**: Auto-detect games and maximize performance
 //! - **Focus Mode**: Detect meetings/calls and reduce background activity
@@ -15,20 +17,28 @@
 //! - **Thermal Prediction**: Preemptive cooling to prevent throttling
 
 pub mod detector;
-pub mod gpu;
+pub use crate::monitor::gpu;
 pub mod ollama;
 pub mod bridge;
 pub mod workload;
 pub mod modes;
+pub mod runtimes;
+pub mod cuda;
 
 pub use detector::{AIWorkloadDetector, AIRuntime, ActiveWorkload};
-pub use gpu::{GpuMonitor, VramStatus, GpuInfo, GpuVendor};
+pub use gpu::{GpuMonitor, VramStatus, GpuInfo, GpuVendor, UnifiedMemory};
 pub use ollama::OllamaClient;
+pub use runtimes::{RuntimeClient, RuntimeState};
 pub use bridge::{ResourceBridge, PlacementStrategy, PlacementPlan, DeviceType};
 pub use workload::{WorkloadClassifier, WorkloadType, WorkloadProfile};
 pub use modes::{GameMode, FocusMode, PerformanceMode};
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Loaded models unused for this long are unloaded by [`AIMode::optimize`]
+const IDLE_UNLOAD: Duration = Duration::from_secs(10 * 60);
 
 /// AI Mode configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,8 +131,19 @@ impl AIMode {
 
     /// Get comprehensive AI status
     pub async fn status(&self) -> AIStatus {
-        let workloads = self.detector.detect();
+        let mut workloads = self.detector.detect();
+        if let Some(gpu) = &self.gpu_monitor {
+            let vram: HashMap<u32, u64> = gpu.get_gpu_processes().iter().map(|p| (p.pid, p.vram_mb())).collect();
+            for workload in &mut workloads {
+                workload.vram_mb = vram.get(&workload.pid).copied().or(workload.vram_mb);
+            }
+        }
+        let mut runtimes = Vec::new();
+        for client in RuntimeClient::for_workloads(&workloads) {
+            runtimes.push(client.state().await);
+        }
         let gpu = self.gpu_monitor.as_ref().map(|m| m.status());
+        let unified = self.gpu_monitor.as_ref().and_then(|m| m.unified_memory());
         let ollama = if let Some(client) = &self.ollama {
             client.get_models().await.ok()
         } else {
@@ -134,7 +155,9 @@ impl AIMode {
 
         AIStatus {
             workloads,
+            runtimes,
             gpu,
+            unified,
             ollama_models: ollama,
             current_workload,
             game_mode_active: game_active,
@@ -143,37 +166,98 @@ impl AIMode {
     }
 
     /// Optimize AI workloads
+    ///
+    /// Unloads Ollama models left idle for [`IDLE_UNLOAD`], or every loaded
+    /// model once VRAM use is over `auto_offload_threshold`. llama.cpp and
+    /// vLLM servers report their KV cache, and idle ones are unloaded under
+    /// the same VRAM pressure. On Apple silicon, wired RAM counts as VRAM.
     pub async fn optimize(&self) -> AIOptimizeResult {
         let mut result = AIOptimizeResult::default();
+        let vram_pressure = self
+            .gpu_monitor
+            .as_ref()
+            .is_some_and(|gpu| gpu.is_under_pressure(self.config.auto_offload_threshold as f64));
 
-        // Check VRAM pressure
-        if let Some(gpu) = &self.gpu_monitor {
-            let status = gpu.status();
-            if let Some(vram) = status.first() {
-                let usage_percent = (vram.used as f64 / vram.total as f64 * 100.0) as u32;
-
-                if usage_percent > self.config.auto_offload_threshold {
-                    result.vram_freed_mb = self.optimize_vram().await;
+        if let Some(client) = &self.ollama {
+            if let Ok(models) = client.get_models().await {
+                let keep_alive = ollama::keep_alive();
+                let now = chrono::Utc::now();
+                for model in models.iter().filter(|m| m.is_active) {
+                    let idle = model.last_used(keep_alive).is_some_and(|used| {
+                        (now - used.with_timezone(&chrono::Utc)).to_std().is_ok_and(|idle| idle >= IDLE_UNLOAD)
+                    });
+                    if !vram_pressure && !idle {
+                        continue;
+                    }
+                    if client.unload_model(&model.name).await.is_ok() {
+                        result.models_unloaded += 1;
+                        result.vram_freed_mb += model.vram_mb.unwrap_or(0);
+                    }
                 }
             }
         }
 
-        // Unload inactive Ollama models
-        if let Some(client) = &self.ollama {
-            if let Ok(models) = client.get_models().await {
-                for model in models {
-                    if !model.is_active {
-                        if client.unload_model(&model.name).await.is_ok() {
-                            result.models_unloaded += 1;
-                        }
-                    }
+        for client in RuntimeClient::for_workloads(&self.detector.detect()) {
+            let state = client.state().await;
+            if vram_pressure && state.is_idle() {
+                match client.unload(&state.models).await {
+                    Ok(unloaded) => result.models_unloaded += unloaded,
+                    Err(e) => result.errors.push(e),
                 }
             }
+            result.runtimes.push(state);
         }
 
         result
     }
 
+    /// Python processes holding large CUDA contexts
+    ///
+    /// Takes [`cuda::SAMPLES`] seconds to sample their utilization.
+    pub fn cuda_workloads(&self) -> Vec<cuda::CudaProcess> {
+        self.gpu_monitor
+            .as_ref()
+            .map(|gpu| cuda::detect(gpu, cuda::SAMPLES))
+            .unwrap_or_default()
+    }
+
+    /// Installed Ollama models, with the loaded ones marked
+    pub async fn ollama_models(&self) -> Result<Vec<ollama::OllamaModel>, String> {
+        match &self.ollama {
+            Some(client) => client.get_models().await,
+            None => Err("Ollama integration is off".to_string()),
+        }
+    }
+
+    /// Unload the named Ollama models, or every loaded model for `None`
+    ///
+    /// Names may leave out the `:latest` tag. Models that are not loaded are
+    /// an error, since asking Ollama to unload them would load them first.
+    pub async fn unload(&self, names: Option<&[String]>) -> Result<Vec<String>, String> {
+        let client = self.ollama.as_ref().ok_or("Ollama integration is off")?;
+        let running = client.get_running_models().await?;
+        let targets: Vec<String> = match names {
+            None => running.into_iter().map(|m| m.name).collect(),
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    running
+                        .iter()
+                        .find(|m| m.name == *name || m.name == format!("{}:latest", name))
+                        .map(|m| m.name.clone())
+                        .ok_or_else(|| format!("{} is not loaded", name))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        let mut unloaded = Vec::new();
+        for name in targets {
+            client.unload_model(&name).await?;
+            unloaded.push(name);
+        }
+        Ok(unloaded)
+    }
+
     /// Check and apply Game Mode if needed
     pub fn check_game_mode(&mut self) -> Option<GameModeAction> {
         if !self.config.game_mode_enabled {
@@ -206,39 +290,86 @@ impl AIMode {
     pub fn workload_classifier(&self) -> &WorkloadClassifier {
         &self.workload_classifier
     }
-
-    async fn optimize_vram(&self) -> u64 {
-        // Unload inactive models first
-        if let Some(client) = &self.ollama {
-            if let Ok(models) = client.get_models().await {
-                let mut freed = 0u64;
-                for model in models {
-                    if !model.is_active {
-                        if let Some(vram) = model.vram_mb {
-                            if client.unload_model(&model.name).await.is_ok() {
-                                freed += vram;
-                            }
-                        }
-                    }
-                }
-                return freed;
-            }
-        }
-        0
-    }
 }
 
 /// AI system status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AIStatus {
     pub workloads: Vec<ActiveWorkload>,
+    /// llama.cpp and vLLM servers among the workloads
+    pub runtimes: Vec<RuntimeState>,
     pub gpu: Option<Vec<VramStatus>>,
+    /// Set on Apple silicon, where the GPU shares system RAM
+    pub unified: Option<UnifiedMemory>,
     pub ollama_models: Option<Vec<ollama::OllamaModel>>,
     pub current_workload: WorkloadType,
     pub game_mode_active: bool,
     pub focus_mode_active: bool,
 }
 
+impl AIStatus {
+    pub fn print(&self) {
+        println!("\n🤖 AI Mode\n");
+        println!("Workload:   {}", self.current_workload);
+        println!("Game mode:  {}", if self.game_mode_active { "active" } else { "off" });
+        println!("Focus mode: {}", if self.focus_mode_active { "active" } else { "off" });
+
+        println!("\nAI runtimes:");
+        if self.workloads.is_empty() {
+            println!("  None running");
+        }
+        for workload in &self.workloads {
+            let vram = workload.vram_mb.map_or("-".to_string(), |mb| format!("{} MB", mb));
+            let model = workload.model.as_deref().map(|m| format!("  {}", m)).unwrap_or_default();
+            println!(
+                "  {:>7}  {:<16} {:>8} MB RAM {:>9} VRAM  {}{}",
+                workload.pid, workload.runtime.to_string(), workload.ram_mb, vram, workload.name, model
+            );
+        }
+
+        if !self.runtimes.is_empty() {
+            println!("\nServers:");
+            for runtime in &self.runtimes {
+                println!("  {}", runtime.summary());
+            }
+        }
+
+        println!("\nGPU memory:");
+        match (&self.unified, &self.gpu) {
+            (Some(unified), _) => {
+                println!(
+                    "  Unified: GPU {} / {} MB limit, {} of {} MB RAM wired ({:.0}%)",
+                    unified.gpu_in_use_mb,
+                    unified.gpu_limit_mb,
+                    unified.wired_mb,
+                    unified.total_mb,
+                    unified.usage_percent()
+                );
+            }
+            (None, Some(gpus)) if !gpus.is_empty() => {
+                for vram in gpus {
+                    let temperature = vram.temperature.map(|t| format!(", {}°C", t)).unwrap_or_default();
+                    println!(
+                        "  GPU {}: {} / {} MB used ({:.0}%){}",
+                        vram.gpu_index,
+                        vram.used_mb(),
+                        vram.total_mb(),
+                        vram.usage_percent(),
+                        temperature
+                    );
+                }
+            }
+            _ => println!("  No GPU found"),
+        }
+
+        println!("\nOllama models:");
+        match &self.ollama_models {
+            Some(models) => ollama::print_models(models),
+            None => println!("  Ollama is not running"),
+        }
+    }
+}
+
 /// Result of AI optimization
 #[derive(Debug, Clone, Default)]
 pub struct AIOptimizeResult {
@@ -246,6 +377,10 @@ pub struct AIOptimizeResult {
     pub ram_freed_mb: u64,
     pub kv_cache_compacted_mb: u64,
     pub models_unloaded: usize,
+    /// llama.cpp and vLLM servers as found before unloading
+    pub runtimes: Vec<RuntimeState>,
+    /// Unloads the servers refused
+    pub errors: Vec<String>,
 }
 
 /// Game Mode action taken
@@ -268,6 +403,13 @@ impl std::fmt::Display for AIOptimizeResult {
             f,
             "VRAM: {} MB, RAM: {} MB, KV: {} MB, Models unloaded: {}",
             self.vram_freed_mb, self.ram_freed_mb, self.kv_cache_compacted_mb, self.models_unloaded
-        )
+        )?;
+        for runtime in &self.runtimes {
+            write!(f, "\n  {}", runtime.summary())?;
+        }
+        for error in &self.errors {
+            write!(f, "\n  Not unloaded: {}", error)?;
+        }
+        Ok(())
     }
 }
@@ -3,7 +3,9 @@
 //! Intelligent mode detection and automatic optimization switching.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+
+use crate::features::gamemode::{GameBooster, GameDetector, RunningGame};
+use crate::monitor::capture;
 
 /// Performance mode settings
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -25,49 +27,22 @@ pub struct GameMode {
     enabled: bool,
     active: bool,
     detected_game: Option<String>,
-    known_games: HashSet<String>,
+    /// Scores running programs on name, fullscreen and GPU signals
+    detector: GameDetector,
     optimizations_applied: Vec<String>,
+    /// Undoes the boosts on deactivate
+    booster: GameBooster,
 }
 
 impl GameMode {
     pub fn new(enabled: bool) -> Self {
-        let mut known_games = HashSet::new();
-
-        // Popular games and launchers
-        let games = [
-            "steam.exe", "steamwebhelper.exe",
-            "epicgameslauncher.exe", "fortnitelauncher.exe",
-            "battlenet.exe", "agent.exe",
-            "origin.exe", "eadesktop.exe",
-            "upc.exe", "uplay.exe",
-            "gog galaxy.exe",
-            "valorant.exe", "valorant-win64-shipping.exe",
-            "csgo.exe", "cs2.exe",
-            "dota2.exe",
-            "leagueoflegends.exe", "league of legends.exe",
-            "overwatch.exe",
-            "minecraft.exe", "javaw.exe",
-            "fortnite.exe", "fortniteclient-win64-shipping.exe",
-            "rocketleague.exe",
-            "pubg.exe", "tslgame.exe",
-            "apexlegends.exe", "r5apex.exe",
-            "gta5.exe", "gtavlauncher.exe",
-            "eldenring.exe",
-            "cyberpunk2077.exe",
-            "baldursgate3.exe", "bg3.exe",
-            "starfield.exe",
-        ];
-
-        for game in games {
-            known_games.insert(game.to_lowercase());
-        }
-
         Self {
             enabled,
             active: false,
             detected_game: None,
-            known_games,
+            detector: GameDetector::open_default(),
             optimizations_applied: Vec::new(),
+            booster: GameBooster::new(),
         }
     }
 
@@ -77,58 +52,37 @@ impl GameMode {
             return None;
         }
 
-        let processes = self.get_foreground_processes();
-
-        for process in &processes {
-            let process_lower = process.to_lowercase();
-            if self.known_games.contains(&process_lower) ||
-               self.looks_like_game(&process_lower) {
-                if !self.active || self.detected_game.as_ref() != Some(process) {
-                    return Some(self.activate(process.clone()));
-                }
-                return None;
+        let Some(candidate) = self.detector.detect() else {
+            // No game detected, deactivate if active
+            if self.active {
+                self.deactivate();
             }
-        }
+            return None;
+        };
 
-        // No game detected, deactivate if active
-        if self.active {
-            self.deactivate();
+        if self.active && self.detected_game.as_deref() == Some(candidate.game.name.as_str()) {
+            self.booster.follow(&candidate.game);
+            return None;
         }
-
-        None
+        tracing::debug!("Game Mode: detected {}", candidate);
+        Some(self.activate(candidate.game))
     }
 
     /// Activate game mode
-    fn activate(&mut self, game: String) -> super::GameModeAction {
+    fn activate(&mut self, game: RunningGame) -> super::GameModeAction {
         self.active = true;
-        self.detected_game = Some(game.clone());
-        self.optimizations_applied.clear();
-
-        // Apply optimizations
-        let mut optimizations = Vec::new();
-
-        // 1. Increase process priority
-        self.boost_process_priority(&game);
-        optimizations.push("Boosted game process priority".into());
-
-        // 2. Reduce background process priority
-        self.reduce_background_priority();
-        optimizations.push("Reduced background process priority".into());
-
-        // 3. Disable unnecessary services (placeholder)
-        optimizations.push("Disabled non-essential background tasks".into());
-
-        // 4. Set GPU to performance mode (would need vendor-specific API)
-        optimizations.push("Requested GPU performance mode".into());
+        self.detected_game = Some(game.name.clone());
 
-        // 5. Optimize memory
-        optimizations.push("Freed memory for game usage".into());
-
-        self.optimizations_applied = optimizations.clone();
+        // Priority, P-cores, background apps, Search/Defender and power plan
+        let report = self.booster.start(&game);
+        for skipped in &report.skipped {
+            tracing::debug!("Game Mode: {}", skipped);
+        }
+        self.optimizations_applied = report.applied.clone();
 
         super::GameModeAction {
-            game_detected: game,
-            optimizations_applied: optimizations,
+            game_detected: game.name,
+            optimizations_applied: report.applied,
         }
     }
 
@@ -138,6 +92,7 @@ impl GameMode {
         self.detected_game = None;
         self.optimizations_applied.clear();
         // Restore normal settings
+        self.booster.restore();
     }
 
     /// Check if game mode is active
@@ -149,148 +104,43 @@ impl GameMode {
     pub fn detected_game(&self) -> Option<&String> {
         self.detected_game.as_ref()
     }
-
-    /// Heuristic to detect games not in known list
-    fn looks_like_game(&self, name: &str) -> bool {
-        // Games often have these patterns
-        name.contains("game") ||
-        name.contains("win64-shipping") ||
-        name.contains("win32-shipping") ||
-        name.ends_with("-dx11.exe") ||
-        name.ends_with("-dx12.exe") ||
-        name.ends_with("-vulkan.exe")
-    }
-
-    /// Get foreground processes
-    #[cfg(windows)]
-    fn get_foreground_processes(&self) -> Vec<String> {
-        use windows::Win32::System::ProcessStatus::{EnumProcesses, GetModuleBaseNameW};
-        use windows::Win32::System::Threading::{
-            OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-        };
-        use windows::Win32::Foundation::CloseHandle;
-
-        let mut processes = Vec::new();
-
-        unsafe {
-            let mut pids = [0u32; 1024];
-            let mut bytes_returned = 0u32;
-
-            if EnumProcesses(
-                pids.as_mut_ptr(),
-                (pids.len() * std::mem::size_of::<u32>()) as u32,
-                &mut bytes_returned,
-            ).is_ok() {
-                let num_processes = bytes_returned as usize / std::mem::size_of::<u32>();
-
-                for &pid in &pids[..num_processes.min(100)] {
-                    if pid == 0 {
-                        continue;
-                    }
-
-                    if let Ok(handle) = OpenProcess(
-                        PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-                        false,
-                        pid,
-                    ) {
-                        let mut name_buf = [0u16; 260];
-                        let len = GetModuleBaseNameW(handle, None, &mut name_buf);
-
-                        if len > 0 {
-                            let name = String::from_utf16_lossy(&name_buf[..len as usize]);
-                            processes.push(name);
-                        }
-
-                        let _ = CloseHandle(handle);
-                    }
-                }
-            }
-        }
-
-        processes
-    }
-
-    #[cfg(not(windows))]
-    fn get_foreground_processes(&self) -> Vec<String> {
-        Vec::new()
-    }
-
-    /// Boost game process priority
-    #[cfg(windows)]
-    fn boost_process_priority(&self, _game: &str) {
-        // Would use SetPriorityClass
-    }
-
-    #[cfg(not(windows))]
-    fn boost_process_priority(&self, _game: &str) {}
-
-    /// Reduce background process priority
-    #[cfg(windows)]
-    fn reduce_background_priority(&self) {
-        // Would iterate non-essential processes and lower priority
-    }
-
-    #[cfg(not(windows))]
-    fn reduce_background_priority(&self) {}
 }
 
 /// Focus Mode - Detect meetings/calls and optimize for them
+///
+/// A call is on while an app holds the microphone or camera open, as the
+/// OS privacy indicators report it.
 pub struct FocusMode {
     enabled: bool,
     active: bool,
     trigger: Option<String>,
-    known_call_apps: HashSet<String>,
     actions_taken: Vec<String>,
 }
 
 impl FocusMode {
     pub fn new(enabled: bool) -> Self {
-        let mut known_call_apps = HashSet::new();
-
-        let apps = [
-            "zoom.exe", "zoom",
-            "teams.exe", "ms-teams.exe",
-            "slack.exe",
-            "discord.exe",
-            "skype.exe",
-            "webex.exe", "ciscowebexstart.exe",
-            "facetime",
-            "meet.google.com",
-        ];
-
-        for app in apps {
-            known_call_apps.insert(app.to_lowercase());
-        }
-
         Self {
             enabled,
             active: false,
             trigger: None,
-            known_call_apps,
             actions_taken: Vec::new(),
         }
     }
 
-    /// Check if a call app is in use and activate focus mode
+    /// Check if a call is in progress and activate focus mode
     pub fn check_and_activate(&mut self) -> Option<super::FocusModeAction> {
         if !self.enabled {
             return None;
         }
 
-        // Check for video call applications
-        let processes = self.get_audio_video_processes();
-
-        for process in &processes {
-            let process_lower = process.to_lowercase();
-            if self.known_call_apps.contains(&process_lower) {
-                if !self.active {
-                    return Some(self.activate(process.clone()));
-                }
-                return None;
+        if let Some(capture) = capture::in_use().into_iter().next() {
+            if !self.active {
+                return Some(self.activate(capture.to_string()));
             }
+            return None;
         }
 
-        // No call app detected, deactivate if active
+        // No microphone or camera in use, deactivate if active
         if self.active {
             self.deactivate();
         }
@@ -342,63 +192,6 @@ impl FocusMode {
     pub fn trigger(&self) -> Option<&String> {
         self.trigger.as_ref()
     }
-
-    /// Get processes using audio/video
-    #[cfg(windows)]
-    fn get_audio_video_processes(&self) -> Vec<String> {
-        // Simplified - would check audio session API
-        use windows::Win32::System::ProcessStatus::{EnumProcesses, GetModuleBaseNameW};
-        use windows::Win32::System::Threading::{
-            OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-        };
-        use windows::Win32::Foundation::CloseHandle;
-
-        let mut processes = Vec::new();
-
-        unsafe {
-            let mut pids = [0u32; 512];
-            let mut bytes_returned = 0u32;
-
-            if EnumProcesses(
-                pids.as_mut_ptr(),
-                (pids.len() * std::mem::size_of::<u32>()) as u32,
-                &mut bytes_returned,
-            ).is_ok() {
-                let num_processes = bytes_returned as usize / std::mem::size_of::<u32>();
-
-                for &pid in &pids[..num_processes.min(50)] {
-                    if pid == 0 {
-                        continue;
-                    }
-
-                    if let Ok(handle) = OpenProcess(
-                        PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-                        false,
-                        pid,
-                    ) {
-                        let mut name_buf = [0u16; 260];
-                        let len = GetModuleBaseNameW(handle, None, &mut name_buf);
-
-                        if len > 0 {
-                            let name = String::from_utf16_lossy(&name_buf[..len as usize]);
-                            if self.known_call_apps.contains(&name.to_lowercase()) {
-                                processes.push(name);
-                            }
-                        }
-
-                        let _ = CloseHandle(handle);
-                    }
-                }
-            }
-        }
-
-        processes
-    }
-
-    #[cfg(not(windows))]
-    fn get_audio_video_processes(&self) -> Vec<String> {
-        Vec::new()
-    }
 }
 
 impl Default for GameMode {
@@ -2,7 +2,12 @@
 //!
 //! Client for interacting with Ollama API to manage models and inference.
 
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long Ollama keeps an idle model loaded unless `OLLAMA_KEEP_ALIVE` says otherwise
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(5 * 60);
 
 /// Ollama model information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +30,19 @@ pub struct OllamaModel {
     pub is_active: bool,
     /// VRAM usage if loaded
     pub vram_mb: Option<u64>,
+    /// When a loaded model will be unloaded; each request pushes it back
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+impl OllamaModel {
+    /// When the model last served a request: its unload time minus the keep-alive
+    ///
+    /// Unknown for models that are not loaded or are kept loaded forever.
+    pub fn last_used(&self, keep_alive: Option<Duration>) -> Option<DateTime<FixedOffset>> {
+        let expires = DateTime::parse_from_rfc3339(self.expires_at.as_deref()?).ok()?;
+        Some(expires - chrono::Duration::from_std(keep_alive?).ok()?)
+    }
 }
 
 /// Ollama running model info
@@ -127,9 +145,9 @@ impl OllamaClient {
 
         let models = tags.models.into_iter().map(|m| {
             let is_active = running_names.contains(&m.name);
-            let vram_mb = running.iter()
-                .find(|r| r.name == m.name)
-                .map(|r| r.size_vram / (1024 * 1024));
+            let loaded = running.iter().find(|r| r.name == m.name);
+            let vram_mb = loaded.map(|r| r.size_vram / (1024 * 1024));
+            let expires_at = loaded.map(|r| r.expires_at.clone());
 
             OllamaModel {
                 name: m.name,
@@ -141,6 +159,7 @@ impl OllamaClient {
                 quantization_level: m.details.as_ref().and_then(|d| d.quantization_level.clone()),
                 is_active,
                 vram_mb,
+                expires_at,
             }
         }).collect();
 
@@ -268,6 +287,86 @@ impl OllamaClient {
     }
 }
 
+/// Keep-alive the Ollama server uses, from `OLLAMA_KEEP_ALIVE`
+///
+/// `None` when models stay loaded until unloaded by hand.
+pub fn keep_alive() -> Option<Duration> {
+    match std::env::var("OLLAMA_KEEP_ALIVE") {
+        Ok(value) => parse_keep_alive(&value),
+        Err(_) => Some(DEFAULT_KEEP_ALIVE),
+    }
+}
+
+/// Seconds, or a Go duration such as `5m` or `1h30m`; negative means forever
+fn parse_keep_alive(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if value.starts_with('-') {
+        return None;
+    }
+    if let Ok(secs) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(secs));
+    }
+    let mut secs = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(rest.len());
+        let unit = match &rest[..unit_end] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        secs += number * unit;
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_secs_f64(secs))
+}
+
+/// `3m ago`, `2h ago` or `just now`
+fn ago(when: DateTime<FixedOffset>) -> String {
+    let minutes = (Utc::now() - when.with_timezone(&Utc)).num_minutes();
+    match minutes {
+        m if m < 1 => "just now".to_string(),
+        m if m < 120 => format!("{}m ago", m),
+        m => format!("{}h ago", m / 60),
+    }
+}
+
+/// Table of models, loaded ones first
+pub fn print_models(models: &[OllamaModel]) {
+    if models.is_empty() {
+        println!("No Ollama models installed.");
+        return;
+    }
+
+    let keep_alive = keep_alive();
+    let mut models: Vec<&OllamaModel> = models.iter().collect();
+    models.sort_by(|a, b| b.is_active.cmp(&a.is_active).then(b.size.cmp(&a.size)));
+
+    println!("{:<32} {:>8} {:>8} {:>9} {:>10} {:>10}", "Model", "Params", "Quant", "Size", "VRAM", "Last used");
+    for model in models {
+        let vram = match (model.is_active, model.vram_mb) {
+            (true, Some(vram)) => format!("{} MB", vram),
+            (true, None) => "loaded".to_string(),
+            (false, _) => "-".to_string(),
+        };
+        let last_used = model.last_used(keep_alive).map_or("-".to_string(), ago);
+        println!(
+            "{:<32} {:>8} {:>8} {:>6.1} GB {:>10} {:>10}",
+            model.name,
+            model.parameter_size.as_deref().unwrap_or("-"),
+            model.quantization_level.as_deref().unwrap_or("-"),
+            model.size as f64 / (1024.0 * 1024.0 * 1024.0),
+            vram,
+            last_used
+        );
+    }
+}
+
 impl std::fmt::Display for OllamaModel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let size_gb = self.size as f64 / (1024.0 * 1024.0 * 1024.0);
@@ -290,3 +389,37 @@ impl std::fmt::Display for OllamaModel {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_alive_accepts_seconds_and_go_durations() {
+        assert_eq!(parse_keep_alive("300"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_keep_alive("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_keep_alive("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_keep_alive("-1"), None);
+        assert_eq!(parse_keep_alive("-1m"), None);
+        assert_eq!(parse_keep_alive("soon"), None);
+    }
+
+    #[test]
+    fn last_used_is_expiry_minus_keep_alive() {
+        let model = OllamaModel {
+            name: "llama3.2:latest".to_string(),
+            size: 2_000_000_000,
+            digest: String::new(),
+            modified_at: String::new(),
+            family: None,
+            parameter_size: None,
+            quantization_level: None,
+            is_active: true,
+            vram_mb: Some(2048),
+            expires_at: Some("2024-06-04T14:38:31.83753-07:00".to_string()),
+        };
+        let last_used = model.last_used(Some(Duration::from_secs(300))).unwrap();
+        assert_eq!(last_used.to_rfc3339(), "2024-06-04T14:33:31.837530-07:00");
+        assert_eq!(model.last_used(None), None);
+    }
+}
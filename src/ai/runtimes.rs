@@ -0,0 +1,285 @@
+//! llama.cpp and vLLM server adapters
+//!
+//! Both runtimes serve HTTP on a local port that the detector reads from
+//! their command line. llama-server reports its slots on `/slots` and, with
+//! `--metrics`, KV-cache use on `/metrics`; in router mode it can unload a
+//! model with `/models/unload`. vLLM reports KV-cache use and queued requests
+//! on `/metrics` and, started with `--enable-sleep-mode` and
+//! `VLLM_SERVER_DEV_MODE=1`, releases its weights and KV cache on `/sleep`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::detector::{AIRuntime, ActiveWorkload};
+
+/// State of one llama.cpp or vLLM server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeState {
+    pub runtime: AIRuntime,
+    pub port: u16,
+    pub pid: u32,
+    pub healthy: bool,
+    pub models: Vec<String>,
+    /// Share of the KV cache in use, 0.0 to 1.0
+    pub kv_cache_usage: Option<f64>,
+    pub requests_running: Option<u64>,
+    pub requests_waiting: Option<u64>,
+}
+
+impl RuntimeState {
+    /// No request running or waiting, as far as the server says
+    pub fn is_idle(&self) -> bool {
+        self.healthy && self.requests_running.unwrap_or(0) == 0 && self.requests_waiting.unwrap_or(0) == 0
+    }
+
+    /// One line such as `llama.cpp :8080 (qwen2.5-7b): KV cache 42%, 1 running`
+    pub fn summary(&self) -> String {
+        let mut line = format!("{} :{}", self.runtime, self.port);
+        if !self.models.is_empty() {
+            line.push_str(&format!(" ({})", self.models.join(", ")));
+        }
+        if !self.healthy {
+            line.push_str(": not answering");
+            return line;
+        }
+        let mut parts = Vec::new();
+        if let Some(usage) = self.kv_cache_usage {
+            parts.push(format!("KV cache {:.0}%", usage * 100.0));
+        }
+        if let Some(running) = self.requests_running {
+            parts.push(format!("{} running", running));
+        }
+        if let Some(waiting) = self.requests_waiting.filter(|&w| w > 0) {
+            parts.push(format!("{} waiting", waiting));
+        }
+        if parts.is_empty() {
+            parts.push("idle".to_string());
+        }
+        line.push_str(&format!(": {}", parts.join(", ")));
+        line
+    }
+}
+
+/// HTTP client for one llama.cpp or vLLM server
+pub struct RuntimeClient {
+    runtime: AIRuntime,
+    pid: u32,
+    port: u16,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl RuntimeClient {
+    /// Clients for the llama.cpp and vLLM servers among `workloads`, one per port
+    pub fn for_workloads(workloads: &[ActiveWorkload]) -> Vec<RuntimeClient> {
+        let mut seen = HashSet::new();
+        workloads
+            .iter()
+            .filter(|w| matches!(w.runtime, AIRuntime::LlamaCpp | AIRuntime::VLLM))
+            .filter_map(|w| {
+                let port = w.port?;
+                // vLLM's engine processes share the API server's command line
+                if !seen.insert(port) {
+                    return None;
+                }
+                RuntimeClient::new(w.runtime, w.pid, port).ok()
+            })
+            .collect()
+    }
+
+    pub fn new(runtime: AIRuntime, pid: u32, port: u16) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(3))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        Ok(Self { runtime, pid, port, base_url: format!("http://127.0.0.1:{}", port), client })
+    }
+
+    /// Health, served models, KV-cache use and queued requests
+    pub async fn state(&self) -> RuntimeState {
+        let mut state = RuntimeState {
+            runtime: self.runtime,
+            port: self.port,
+            pid: self.pid,
+            healthy: self.get("/health").await.is_ok(),
+            models: Vec::new(),
+            kv_cache_usage: None,
+            requests_running: None,
+            requests_waiting: None,
+        };
+        if !state.healthy {
+            return state;
+        }
+
+        if let Ok(body) = self.get("/v1/models").await {
+            state.models = model_ids(&body);
+        }
+        let metrics = self.get("/metrics").await.unwrap_or_default();
+        match self.runtime {
+            AIRuntime::VLLM => {
+                // Renamed from gpu_cache_usage_perc in newer releases; both are 0-1
+                state.kv_cache_usage =
+                    metric_max(&metrics, "vllm:kv_cache_usage_perc").or_else(|| metric_max(&metrics, "vllm:gpu_cache_usage_perc"));
+                state.requests_running = metric_sum(&metrics, "vllm:num_requests_running").map(|n| n as u64);
+                state.requests_waiting = metric_sum(&metrics, "vllm:num_requests_waiting").map(|n| n as u64);
+            }
+            _ => {
+                state.kv_cache_usage = metric_max(&metrics, "llamacpp:kv_cache_usage_ratio");
+                state.requests_waiting = metric_sum(&metrics, "llamacpp:requests_deferred").map(|n| n as u64);
+                let slots = self.get("/slots").await.ok().and_then(|body| busy_slots(&body));
+                state.requests_running =
+                    slots.or_else(|| metric_sum(&metrics, "llamacpp:requests_processing").map(|n| n as u64));
+            }
+        }
+        state
+    }
+
+    /// Free the memory held by the served models
+    ///
+    /// vLLM goes to sleep, dropping its weights and KV cache until woken with
+    /// `/wake_up`. llama-server unloads its models in router mode; a single
+    /// model server has to be stopped instead.
+    pub async fn unload(&self, models: &[String]) -> Result<usize, String> {
+        match self.runtime {
+            AIRuntime::VLLM => {
+                self.post("/sleep?level=1", Value::Null)
+                    .await
+                    .map_err(|e| format!("{} (start vLLM with --enable-sleep-mode and VLLM_SERVER_DEV_MODE=1)", e))?;
+                Ok(models.len().max(1))
+            }
+            _ => {
+                for model in models {
+                    self.post("/models/unload", serde_json::json!({ "model": model }))
+                        .await
+                        .map_err(|e| format!("{} (only llama-server in router mode can unload; stop PID {} instead)", e, self.pid))?;
+                }
+                Ok(models.len())
+            }
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<String, String> {
+        let resp = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .send()
+            .await
+            .map_err(|e| format!("{} {} failed: {}", self.runtime, path, e))?;
+        if !resp.status().is_success() {
+            return Err(format!("{} {} returned {}", self.runtime, path, resp.status()));
+        }
+        resp.text().await.map_err(|e| format!("{} {} failed: {}", self.runtime, path, e))
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<(), String> {
+        let mut request = self.client.post(format!("{}{}", self.base_url, path));
+        if !body.is_null() {
+            request = request.json(&body);
+        }
+        let resp = request.send().await.map_err(|e| format!("{} {} failed: {}", self.runtime, path, e))?;
+        if !resp.status().is_success() {
+            return Err(format!("{} {} returned {}", self.runtime, path, resp.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Model ids from an OpenAI-style `/v1/models` response
+fn model_ids(body: &str) -> Vec<String> {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return Vec::new();
+    };
+    value["data"]
+        .as_array()
+        .map(|models| models.iter().filter_map(|m| m["id"].as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Slots working on a request, from llama-server's `/slots`
+fn busy_slots(body: &str) -> Option<u64> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    // `is_processing` in current releases; older ones report `state` 1 while busy
+    let busy = value
+        .as_array()?
+        .iter()
+        .filter(|s| s["is_processing"].as_bool().unwrap_or_else(|| s["state"].as_u64().is_some_and(|state| state != 0)))
+        .count();
+    Some(busy as u64)
+}
+
+/// Values of one metric in Prometheus text format, over all its label sets
+fn metric_values<'a>(text: &'a str, name: &'a str) -> impl Iterator<Item = f64> + 'a {
+    text.lines().filter(|line| !line.starts_with('#')).filter_map(move |line| {
+        let rest = line.strip_prefix(name)?;
+        let value = if rest.starts_with('{') { &rest[rest.find('}')? + 1..] } else { rest };
+        if !value.starts_with(' ') {
+            return None; // A longer metric name with this one as prefix
+        }
+        value.split_whitespace().next()?.parse().ok()
+    })
+}
+
+fn metric_sum(text: &str, name: &str) -> Option<f64> {
+    metric_values(text, name).reduce(|a, b| a + b)
+}
+
+fn metric_max(text: &str, name: &str) -> Option<f64> {
+    metric_values(text, name).reduce(f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_prometheus_metrics() {
+        let vllm = "# HELP vllm:num_requests_running Number of requests in model execution batches.\n\
+                    # TYPE vllm:num_requests_running gauge\n\
+                    vllm:num_requests_running{engine=\"0\",model_name=\"Qwen/Qwen2.5-7B\"} 2.0\n\
+                    vllm:num_requests_running{engine=\"1\",model_name=\"Qwen/Qwen2.5-7B\"} 1.0\n\
+                    vllm:num_requests_waiting{model_name=\"Qwen/Qwen2.5-7B\"} 0.0\n\
+                    vllm:gpu_cache_usage_perc{model_name=\"Qwen/Qwen2.5-7B\"} 0.42\n\
+                    vllm:gpu_cache_usage_perc_total 9.0\n";
+        assert_eq!(metric_sum(vllm, "vllm:num_requests_running"), Some(3.0));
+        assert_eq!(metric_sum(vllm, "vllm:num_requests_waiting"), Some(0.0));
+        assert_eq!(metric_max(vllm, "vllm:gpu_cache_usage_perc"), Some(0.42));
+        assert_eq!(metric_max(vllm, "vllm:kv_cache_usage_perc"), None);
+
+        let llama = "llamacpp:kv_cache_usage_ratio 0.125\nllamacpp:requests_processing 1\n";
+        assert_eq!(metric_max(llama, "llamacpp:kv_cache_usage_ratio"), Some(0.125));
+        assert_eq!(metric_sum(llama, "llamacpp:requests_processing"), Some(1.0));
+    }
+
+    #[test]
+    fn reads_slots_and_models() {
+        let slots = r#"[{"id":0,"n_ctx":4096,"is_processing":true},{"id":1,"n_ctx":4096,"is_processing":false}]"#;
+        assert_eq!(busy_slots(slots), Some(1));
+        assert_eq!(busy_slots(r#"[{"id":0,"n_ctx":2048,"state":0}]"#), Some(0));
+        assert_eq!(busy_slots(r#"{"error":"slots endpoint is disabled"}"#), None);
+
+        let models = r#"{"object":"list","data":[{"id":"qwen2.5-7b-instruct","object":"model"}]}"#;
+        assert_eq!(model_ids(models), vec!["qwen2.5-7b-instruct".to_string()]);
+        assert!(model_ids("not json").is_empty());
+    }
+
+    #[test]
+    fn idle_needs_a_healthy_server_with_nothing_queued() {
+        let mut state = RuntimeState {
+            runtime: AIRuntime::LlamaCpp,
+            port: 8080,
+            pid: 42,
+            healthy: true,
+            models: vec!["qwen2.5-7b".to_string()],
+            kv_cache_usage: Some(0.42),
+            requests_running: Some(0),
+            requests_waiting: None,
+        };
+        assert!(state.is_idle());
+        assert_eq!(state.summary(), "llama.cpp :8080 (qwen2.5-7b): KV cache 42%, 0 running");
+        state.requests_running = Some(1);
+        assert!(!state.is_idle());
+        state.healthy = false;
+        assert_eq!(state.summary(), "llama.cpp :8080 (qwen2.5-7b): not answering");
+    }
+}
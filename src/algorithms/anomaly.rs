@@ -0,0 +1,339 @@
+//! Seasonal anomaly detection
+//!
+//! [`SeasonalBaseline`] keeps a running mean and spread of a metric for each
+//! hour of the day, so memory that is normal during a nightly backup isn't
+//! flagged at 2am while the same level at noon is. A reading's z-score is
+//! its distance from its hour's mean in standard deviations; until an hour
+//! has seen enough readings the all-day baseline stands in.
+//!
+//! [`AnomalyDetector`] runs one baseline per [`Metric`] and reports a reading
+//! once it has stayed unusual for a couple of samples, then stays quiet until
+//! the metric is back near normal.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Readings an hour needs before its own baseline is used
+const MIN_HOUR_SAMPLES: u32 = 30;
+
+/// Readings before the all-day baseline is trusted
+const MIN_SAMPLES: u32 = 60;
+
+/// Readings averaged evenly; after that older ones fade out
+const MEMORY: f64 = 1000.0;
+
+/// Unusual readings in a row before an anomaly is reported
+const CONFIRM_SAMPLES: u32 = 2;
+
+/// Default z-score at which a reading is unusual
+pub const DEFAULT_THRESHOLD: f64 = 4.0;
+
+/// Running mean and variance; an even average at first, exponential later
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunningStats {
+    pub count: u32,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl RunningStats {
+    pub fn update(&mut self, value: f64) {
+        self.count = self.count.saturating_add(1);
+        let weight = 1.0 / (self.count as f64).min(MEMORY);
+        let delta = value - self.mean;
+        self.mean += weight * delta;
+        self.variance = (1.0 - weight) * (self.variance + weight * delta * delta);
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+/// Per-hour-of-day statistics of one metric
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalBaseline {
+    overall: RunningStats,
+    hours: Vec<RunningStats>,
+}
+
+impl SeasonalBaseline {
+    pub fn new() -> Self {
+        Self { overall: RunningStats::default(), hours: vec![RunningStats::default(); 24] }
+    }
+
+    /// Statistics a reading at `hour` (0-23) is compared against; `None`
+    /// until there are enough readings
+    pub fn expected(&self, hour: usize) -> Option<RunningStats> {
+        match self.hours.get(hour) {
+            Some(stats) if stats.count >= MIN_HOUR_SAMPLES => Some(*stats),
+            _ => (self.overall.count >= MIN_SAMPLES).then_some(self.overall),
+        }
+    }
+
+    /// Standard deviations `value` is from normal for `hour`
+    ///
+    /// The spread is at least `min_spread`, so a metric that barely moves
+    /// doesn't turn every small change into an anomaly.
+    pub fn z_score(&self, hour: usize, value: f64, min_spread: f64) -> Option<f64> {
+        let expected = self.expected(hour)?;
+        Some((value - expected.mean) / expected.std_dev().max(min_spread))
+    }
+
+    pub fn update(&mut self, hour: usize, value: f64) {
+        self.overall.update(value);
+        if let Some(stats) = self.hours.get_mut(hour) {
+            stats.update(value);
+        }
+    }
+}
+
+impl Default for SeasonalBaseline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Metrics watched for anomalies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Physical memory in use, percent
+    MemoryLoad,
+    /// Running processes
+    ProcessCount,
+    /// GPU memory in use across all GPUs, MB
+    GpuVram,
+}
+
+impl Metric {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Metric::MemoryLoad => "memory load",
+            Metric::ProcessCount => "process count",
+            Metric::GpuVram => "GPU memory",
+        }
+    }
+
+    fn format(&self, value: f64) -> String {
+        match self {
+            Metric::MemoryLoad => format!("{:.0}%", value),
+            Metric::ProcessCount => format!("{:.0}", value),
+            Metric::GpuVram => format!("{:.0} MB", value),
+        }
+    }
+
+    /// Smallest spread the z-score divides by
+    fn min_spread(&self) -> f64 {
+        match self {
+            Metric::MemoryLoad => 2.0,
+            Metric::ProcessCount => 5.0,
+            Metric::GpuVram => 256.0,
+        }
+    }
+
+    /// Whether a drop is as unusual as a rise; less memory in use is never
+    /// a problem, a burst of exiting processes may be
+    fn both_ways(&self) -> bool {
+        matches!(self, Metric::ProcessCount)
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// A reading well outside what is normal for the time of day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Anomaly {
+    pub metric: Metric,
+    pub value: f64,
+    /// Mean for this hour of the day
+    pub expected: f64,
+    pub z_score: f64,
+}
+
+impl Anomaly {
+    pub fn title(&self) -> &'static str {
+        match (self.metric, self.z_score > 0.0) {
+            (Metric::MemoryLoad, _) => "Unusual Memory Spike",
+            (Metric::ProcessCount, true) => "Unusually Many Processes",
+            (Metric::ProcessCount, false) => "Processes Exited Suddenly",
+            (Metric::GpuVram, _) => "Unusual GPU Memory Jump",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        format!(
+            "{} is {}, usually {} at this time of day ({:+.1}σ)",
+            capitalize(self.metric.label()),
+            self.metric.format(self.value),
+            self.metric.format(self.expected),
+            self.z_score
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct AlertState {
+    /// Unusual readings in a row
+    streak: u32,
+    /// Reported, and not back to normal since
+    active: bool,
+}
+
+/// Seasonal baselines for each metric, with alert hysteresis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyDetector {
+    baselines: HashMap<Metric, SeasonalBaseline>,
+    #[serde(skip)]
+    alerts: HashMap<Metric, AlertState>,
+    #[serde(skip, default = "default_threshold")]
+    threshold: f64,
+}
+
+fn default_threshold() -> f64 {
+    DEFAULT_THRESHOLD
+}
+
+impl AnomalyDetector {
+    pub fn new(threshold: f64) -> Self {
+        Self { baselines: HashMap::new(), alerts: HashMap::new(), threshold }
+    }
+
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+
+    /// Add a reading taken at `hour` (0-23); returns an anomaly when the
+    /// metric has just become unusual
+    ///
+    /// The reading is compared before it joins the baseline. A metric has to
+    /// fall back to half the threshold before it can be reported again.
+    pub fn observe(&mut self, metric: Metric, hour: usize, value: f64) -> Option<Anomaly> {
+        let baseline = self.baselines.entry(metric).or_default();
+        let expected = baseline.expected(hour).map(|s| s.mean);
+        let z = baseline.z_score(hour, value, metric.min_spread());
+        baseline.update(hour, value);
+        let (z, expected) = (z?, expected?);
+
+        let deviation = if metric.both_ways() { z.abs() } else { z };
+        let state = self.alerts.entry(metric).or_default();
+        if deviation < self.threshold / 2.0 {
+            *state = AlertState::default();
+            return None;
+        }
+        if deviation < self.threshold {
+            state.streak = 0;
+            return None;
+        }
+        if state.active {
+            return None;
+        }
+        state.streak += 1;
+        if state.streak < CONFIRM_SAMPLES {
+            return None;
+        }
+        state.active = true;
+        Some(Anomaly { metric, value, expected, z_score: z })
+    }
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self::new(DEFAULT_THRESHOLD)
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A detector that has seen `load` +/- 3 for every hour of a few days
+    fn trained(load: impl Fn(usize) -> f64) -> AnomalyDetector {
+        let mut detector = AnomalyDetector::default();
+        for day in 0..3 {
+            for hour in 0..24 {
+                for i in 0..40 {
+                    let noise = if (i + day) % 2 == 0 { 3.0 } else { -3.0 };
+                    detector.observe(Metric::MemoryLoad, hour, load(hour) + noise);
+                }
+            }
+        }
+        detector
+    }
+
+    #[test]
+    fn test_running_stats() {
+        let mut stats = RunningStats::default();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.update(value);
+        }
+        assert!((stats.mean - 5.0).abs() < 1e-9);
+        assert!((stats.std_dev() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_spike_is_reported_once() {
+        let mut detector = trained(|_| 50.0);
+        assert!(detector.observe(Metric::MemoryLoad, 12, 80.0).is_none(), "one reading is not enough");
+        let anomaly = detector.observe(Metric::MemoryLoad, 12, 80.0).expect("anomaly");
+        assert_eq!(anomaly.title(), "Unusual Memory Spike");
+        assert!(anomaly.z_score > 4.0);
+        assert!(anomaly.message().starts_with("Memory load is 80%, usually 50%"), "{}", anomaly.message());
+        assert!(detector.observe(Metric::MemoryLoad, 12, 80.0).is_none(), "still the same spike");
+
+        for _ in 0..3 {
+            detector.observe(Metric::MemoryLoad, 12, 50.0);
+        }
+        detector.observe(Metric::MemoryLoad, 12, 80.0);
+        assert!(detector.observe(Metric::MemoryLoad, 12, 80.0).is_some(), "a new spike after recovering");
+    }
+
+    #[test]
+    fn test_normal_for_the_hour_is_not_anomalous() {
+        // A nightly job lifts memory to 85% at 2am
+        let mut detector = trained(|hour| if hour == 2 { 85.0 } else { 45.0 });
+        for _ in 0..5 {
+            assert!(detector.observe(Metric::MemoryLoad, 2, 85.0).is_none());
+        }
+        detector.observe(Metric::MemoryLoad, 14, 85.0);
+        assert!(detector.observe(Metric::MemoryLoad, 14, 85.0).is_some());
+    }
+
+    #[test]
+    fn test_drops_count_only_where_they_matter() {
+        let mut detector = trained(|_| 70.0);
+        for _ in 0..5 {
+            assert!(detector.observe(Metric::MemoryLoad, 9, 20.0).is_none());
+        }
+
+        let mut detector = AnomalyDetector::default();
+        for i in 0..200 {
+            detector.observe(Metric::ProcessCount, 9, 300.0 + (i % 5) as f64);
+        }
+        detector.observe(Metric::ProcessCount, 9, 150.0);
+        let anomaly = detector.observe(Metric::ProcessCount, 9, 150.0).expect("anomaly");
+        assert_eq!(anomaly.title(), "Processes Exited Suddenly");
+    }
+
+    #[test]
+    fn test_baselines_roundtrip() {
+        let detector = trained(|_| 50.0);
+        let json = serde_json::to_string(&detector).unwrap();
+        let mut restored: AnomalyDetector = serde_json::from_str(&json).unwrap();
+        restored.observe(Metric::MemoryLoad, 12, 80.0);
+        assert!(restored.observe(Metric::MemoryLoad, 12, 80.0).is_some());
+    }
+}
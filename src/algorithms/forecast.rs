@@ -0,0 +1,319 @@
+//! Available memory forecasting
+//!
+//! Holt's exponential smoothing with a damped trend over one value a
+//! minute: the level follows available memory, the trend how fast it moves,
+//! and damping keeps a burst of growth from being extrapolated forever.
+//! Confidence bands come from the spread of the one-step-ahead errors,
+//! widened for every step further out.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Minutes per smoothing step
+const STEP_MS: u64 = 60_000;
+
+/// Default horizons, in minutes
+pub const HORIZONS: [u64; 3] = [30, 60, 120];
+
+/// Spacing of chart points, in minutes
+const CHART_STEP_MINUTES: u64 = 5;
+
+/// Time between optimizations run ahead of a forecast exhaustion; a trim
+/// doesn't change where a leak is heading, only how far it has to go
+pub const FORECAST_RUN_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Steps of history needed before forecasting
+pub const MIN_STEPS: usize = 15;
+
+/// Gaps up to this many steps are interpolated; longer ones (a suspended
+/// machine, a stopped daemon) start the model over
+const MAX_GAP_STEPS: u64 = 10;
+
+/// Weight of the newest squared error in the error variance
+const ERROR_WEIGHT: f64 = 0.05;
+
+/// Standard normal quantile of an 80% band
+const BAND_Z: f64 = 1.2816;
+
+/// Holt damped-trend forecaster fed with `(timestamp, available MB)` samples
+#[derive(Debug, Clone)]
+pub struct HoltForecaster {
+    /// Level smoothing
+    alpha: f64,
+    /// Trend smoothing
+    beta: f64,
+    /// Trend damping per step
+    phi: f64,
+    level: f64,
+    trend: f64,
+    /// Smoothed square of the one-step-ahead errors
+    error_variance: f64,
+    steps: usize,
+    /// Last completed step, as `(minute, value)`
+    last: Option<(u64, f64)>,
+    /// Minute being collected, with the sum and count of its samples
+    bucket: Option<(u64, f64, u32)>,
+}
+
+/// Forecast value at one horizon
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ForecastPoint {
+    pub minutes: u64,
+    pub available_mb: f64,
+    /// Lower and upper bounds of the 80% band
+    pub low_mb: f64,
+    pub high_mb: f64,
+}
+
+/// Where available memory is heading
+#[derive(Debug, Clone, Serialize)]
+pub struct Forecast {
+    pub total_mb: f64,
+    /// Smoothed available memory now
+    pub available_mb: f64,
+    /// Change per minute the forecast starts from
+    pub trend_mb_per_min: f64,
+    pub points: Vec<ForecastPoint>,
+}
+
+/// Horizons every few minutes up to the longest of [`HORIZONS`], for charts
+pub fn chart_horizons() -> Vec<u64> {
+    (1..=HORIZONS[HORIZONS.len() - 1] / CHART_STEP_MINUTES).map(|i| i * CHART_STEP_MINUTES).collect()
+}
+
+impl HoltForecaster {
+    pub fn new() -> Self {
+        Self::with_params(0.3, 0.1, 0.995)
+    }
+
+    pub fn with_params(alpha: f64, beta: f64, phi: f64) -> Self {
+        Self {
+            alpha,
+            beta,
+            phi,
+            level: 0.0,
+            trend: 0.0,
+            error_variance: 0.0,
+            steps: 0,
+            last: None,
+            bucket: None,
+        }
+    }
+
+    /// Forecaster fed with `(timestamp_ms, available_mb)` samples, oldest first
+    pub fn from_samples(samples: impl IntoIterator<Item = (u64, f64)>) -> Self {
+        let mut forecaster = Self::new();
+        for (timestamp_ms, available_mb) in samples {
+            forecaster.observe(timestamp_ms, available_mb);
+        }
+        forecaster
+    }
+
+    /// Whether there is enough history to forecast
+    pub fn is_ready(&self) -> bool {
+        self.steps >= MIN_STEPS
+    }
+
+    /// Add a sample; samples within the same minute are averaged into one step
+    pub fn observe(&mut self, timestamp_ms: u64, available_mb: f64) {
+        let minute = timestamp_ms / STEP_MS;
+        match self.bucket {
+            Some((current, sum, count)) if current == minute => {
+                self.bucket = Some((minute, sum + available_mb, count + 1));
+            }
+            // Out of order; the minute is already smoothed in
+            Some((current, _, _)) if minute < current => {}
+            _ => {
+                if let Some((current, sum, count)) = self.bucket.take() {
+                    self.complete(current, sum / count as f64);
+                }
+                self.bucket = Some((minute, available_mb, 1));
+            }
+        }
+    }
+
+    /// Smooth in the mean of a finished minute
+    fn complete(&mut self, minute: u64, value: f64) {
+        let Some((last_minute, last_value)) = self.last else {
+            self.level = value;
+            self.last = Some((minute, value));
+            self.steps = 1;
+            return;
+        };
+        let gap = minute - last_minute;
+        if gap > MAX_GAP_STEPS {
+            *self = Self::with_params(self.alpha, self.beta, self.phi);
+            self.complete(minute, value);
+            return;
+        }
+        for step in 1..=gap {
+            let value = last_value + (value - last_value) * step as f64 / gap as f64;
+            self.update(value);
+        }
+        self.last = Some((minute, value));
+    }
+
+    fn update(&mut self, value: f64) {
+        let predicted = self.level + self.phi * self.trend;
+        let error = value - predicted;
+        // The first difference seeds the trend
+        if self.steps == 1 {
+            self.trend = value - self.level;
+            self.level = value;
+        } else {
+            let level = predicted + self.alpha * error;
+            self.trend = self.phi * self.trend + self.beta * (level - self.level - self.phi * self.trend);
+            self.level = level;
+            self.error_variance += ERROR_WEIGHT * (error * error - self.error_variance);
+        }
+        self.steps += 1;
+    }
+
+    /// Forecast `minutes` ahead of the last complete minute; `None` until ready
+    pub fn predict(&self, minutes: u64) -> Option<ForecastPoint> {
+        if !self.is_ready() {
+            return None;
+        }
+        let mut damped = 0.0; // phi + phi^2 + ... + phi^j
+        let mut power = 1.0;
+        let mut variance_factor = 1.0;
+        for step in 1..=minutes {
+            if step > 1 {
+                // Error of a j-step forecast grows with each step before it
+                let c = self.alpha * (1.0 + self.beta * damped);
+                variance_factor += c * c;
+            }
+            power *= self.phi;
+            damped += power;
+        }
+        let available_mb = self.level + self.trend * damped;
+        let band = BAND_Z * (self.error_variance * variance_factor).sqrt();
+        Some(ForecastPoint { minutes, available_mb, low_mb: available_mb - band, high_mb: available_mb + band })
+    }
+
+    /// Forecast at each horizon, clamped to `[0, total_mb]`
+    pub fn forecast(&self, total_mb: f64, horizons: &[u64]) -> Option<Forecast> {
+        let clamp = |mb: f64| mb.clamp(0.0, total_mb);
+        let points = horizons
+            .iter()
+            .map(|&minutes| {
+                self.predict(minutes).map(|p| ForecastPoint {
+                    minutes,
+                    available_mb: clamp(p.available_mb),
+                    low_mb: clamp(p.low_mb),
+                    high_mb: clamp(p.high_mb),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Forecast { total_mb, available_mb: clamp(self.level), trend_mb_per_min: self.trend, points })
+    }
+
+    /// Minutes until available memory is forecast to drop to `floor_mb`,
+    /// looking at most `max_minutes` ahead
+    pub fn minutes_until(&self, floor_mb: f64, max_minutes: u64) -> Option<u64> {
+        if !self.is_ready() {
+            return None;
+        }
+        if self.level <= floor_mb {
+            return Some(0);
+        }
+        if self.trend >= 0.0 {
+            return None;
+        }
+        (1..=max_minutes).find(|&m| self.predict(m).is_some_and(|p| p.available_mb <= floor_mb))
+    }
+}
+
+impl Default for HoltForecaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Forecast {
+    pub fn print(&self) {
+        println!("  Available now: {:>8.0} MB of {:.0} MB", self.available_mb, self.total_mb);
+        let sign = if self.trend_mb_per_min >= 0.0 { "+" } else { "" };
+        println!("  Trend:         {:>8} MB/min", format!("{}{:.1}", sign, self.trend_mb_per_min));
+        println!();
+        println!("  {:>9}  {:>10}  {:>20}", "Horizon", "Available", "80% band");
+        for p in &self.points {
+            println!(
+                "  {:>5} min  {:>7.0} MB  {:>8.0} – {:>6.0} MB",
+                p.minutes, p.available_mb, p.low_mb, p.high_mb
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fed(values: impl IntoIterator<Item = f64>) -> HoltForecaster {
+        HoltForecaster::from_samples(values.into_iter().enumerate().map(|(i, v)| (i as u64 * STEP_MS, v)))
+    }
+
+    #[test]
+    fn test_needs_history() {
+        let forecaster = fed([4000.0; 5]);
+        assert!(!forecaster.is_ready());
+        assert!(forecaster.forecast(8000.0, &HORIZONS).is_none());
+    }
+
+    #[test]
+    fn test_steady_memory_is_not_exhausted() {
+        let forecaster = fed((0..60).map(|i| 4000.0 + if i % 2 == 0 { 20.0 } else { -20.0 }));
+        let forecast = forecaster.forecast(8000.0, &HORIZONS).unwrap();
+        for p in &forecast.points {
+            assert!((p.available_mb - 4000.0).abs() < 100.0, "{:?}", p);
+        }
+        assert_eq!(forecaster.minutes_until(800.0, 120), None);
+    }
+
+    #[test]
+    fn test_falling_memory_predicts_exhaustion() {
+        // Losing 20 MB a minute from 4 GB
+        let forecaster = fed((0..60).map(|i| 4000.0 - 20.0 * i as f64));
+        let forecast = forecaster.forecast(8000.0, &HORIZONS).unwrap();
+        assert!(forecast.trend_mb_per_min < -15.0, "{}", forecast.trend_mb_per_min);
+        assert!(forecast.points[0].available_mb < 2800.0);
+
+        // 2820 MB left at -20 MB/min reaches 800 MB in about 100 minutes;
+        // damping stretches that a little
+        let minutes = forecaster.minutes_until(800.0, 240).expect("exhaustion");
+        assert!((100..=200).contains(&minutes), "{}", minutes);
+        assert_eq!(forecaster.minutes_until(800.0, 30), None);
+    }
+
+    #[test]
+    fn test_bands_widen_with_horizon() {
+        let forecaster = fed((0..60).map(|i| 4000.0 - 5.0 * i as f64 + (i % 3) as f64 * 40.0));
+        let forecast = forecaster.forecast(8000.0, &HORIZONS).unwrap();
+        let widths: Vec<f64> = forecast.points.iter().map(|p| p.high_mb - p.low_mb).collect();
+        assert!(widths[0] > 0.0);
+        assert!(widths.windows(2).all(|w| w[1] > w[0]), "{:?}", widths);
+    }
+
+    #[test]
+    fn test_samples_within_a_minute_are_averaged() {
+        let mut forecaster = HoltForecaster::new();
+        for minute in 0..20u64 {
+            for (offset, value) in [(0, 3900.0), (10_000, 4100.0), (20_000, 4000.0)] {
+                forecaster.observe(minute * STEP_MS + offset, value);
+            }
+        }
+        let point = forecaster.predict(30).unwrap();
+        assert!((point.available_mb - 4000.0).abs() < 1.0, "{:?}", point);
+    }
+
+    #[test]
+    fn test_long_gap_starts_over() {
+        let mut forecaster = fed([4000.0; 30]);
+        assert!(forecaster.is_ready());
+        forecaster.observe(10 * 24 * 60 * STEP_MS, 2000.0);
+        forecaster.observe(10 * 24 * 60 * STEP_MS + STEP_MS, 2000.0);
+        assert!(!forecaster.is_ready());
+    }
+}
@@ -4,7 +4,11 @@
 //! Optimizing related processes together improves cache coherence.
 
 use std::collections::{HashMap, HashSet, VecDeque};
-use sysinfo::{System, ProcessesToUpdate};
+use sysinfo::System;
+
+/// Share of a cluster's edge weight that may lead outside it for the
+/// cluster to be trimmed as one group
+pub const MAX_GROUP_CUT: f64 = 0.2;
 
 /// Edge in the process graph
 #[derive(Debug, Clone)]
@@ -23,6 +27,13 @@ pub struct ProcessCluster {
     pub connectivity: f64,
 }
 
+impl ProcessCluster {
+    /// Share of the cluster's edge weight that leads to other processes
+    pub fn cut_share(&self) -> f64 {
+        1.0 - self.connectivity
+    }
+}
+
 /// MinCut-based process clustering
 pub struct MinCutClusterer {
     adjacency: HashMap<u32, Vec<(u32, f64)>>,
@@ -198,6 +209,45 @@ impl MinCutClusterer {
         ordered
     }
 
+    /// Reorder `ranked` (best trim candidates first) so loosely tied
+    /// clusters are trimmed together
+    ///
+    /// A cluster is pulled in where its best-ranked member stands, its
+    /// members in [`Self::get_trim_order`]. Each PID comes back with the id
+    /// of the cluster it is trimmed with; PIDs not in `ranked` are left out.
+    pub fn group_order(&self, ranked: &[u32]) -> Vec<(u32, Option<usize>)> {
+        let present: HashSet<u32> = ranked.iter().copied().collect();
+        let mut cluster_of: HashMap<u32, &ProcessCluster> = HashMap::new();
+        let clusters = self.find_clusters(usize::MAX);
+        for cluster in clusters.iter().filter(|c| c.cut_share() <= MAX_GROUP_CUT) {
+            for &pid in &cluster.processes {
+                cluster_of.insert(pid, cluster);
+            }
+        }
+
+        let mut placed: HashSet<u32> = HashSet::new();
+        let mut order = Vec::with_capacity(ranked.len());
+        for &pid in ranked {
+            if placed.contains(&pid) {
+                continue;
+            }
+            match cluster_of.get(&pid) {
+                Some(cluster) => {
+                    for member in self.get_trim_order(cluster) {
+                        if present.contains(&member) && placed.insert(member) {
+                            order.push((member, Some(cluster.id)));
+                        }
+                    }
+                }
+                None => {
+                    placed.insert(pid);
+                    order.push((pid, None));
+                }
+            }
+        }
+        order
+    }
+
     /// Statistics for benchmarking
     pub fn stats(&self) -> MinCutStats {
         MinCutStats {
@@ -246,4 +296,25 @@ mod tests {
         let stats = clusterer.stats();
         assert_eq!(stats.total_processes, 5);
     }
+
+    #[test]
+    fn test_group_order() {
+        let mut clusterer = MinCutClusterer::new();
+        clusterer.add_edge(1, 2, 1.0);
+        clusterer.add_edge(2, 3, 1.0);
+        clusterer.add_edge(4, 5, 1.0);
+        for (pid, mb) in [(1, 100), (2, 200), (3, 150), (4, 50), (5, 75), (6, 500)] {
+            clusterer.process_memory.insert(pid, mb * 1024 * 1024);
+        }
+
+        // 6 has no edges; 3 is not running any more
+        let order = clusterer.group_order(&[4, 1, 6, 5, 2]);
+        let pids: Vec<u32> = order.iter().map(|(pid, _)| *pid).collect();
+        assert_eq!(pids, vec![5, 4, 2, 1, 6]);
+        assert_eq!(order[0].1, order[1].1);
+        assert_eq!(order[2].1, order[3].1);
+        assert_ne!(order[0].1, order[2].1);
+        assert!(order[0].1.is_some() && order[2].1.is_some());
+        assert_eq!(order[4].1, None);
+    }
 }
@@ -1,17 +1,22 @@
 //! Advanced algorithms for intelligent memory optimization
 //!
 //! This module provides graph-based and sublinear algorithms:
+//! - Anomaly: Seasonal z-scores over monitored metrics
+//! - Forecast: Available memory prediction with confidence bands
 //! - MinCut: Process clustering for grouped optimization
 //! - PageRank: Process importance scoring
 //! - Count-Min Sketch: Sublinear frequency estimation
 //! - Spectral Analysis: Memory pattern classification
 
+pub mod anomaly;
+pub mod forecast;
 pub mod mincut;
 pub mod pagerank;
 pub mod sketch;
 pub mod spectral;
 
+pub use forecast::HoltForecaster;
 pub use mincut::MinCutClusterer;
 pub use pagerank::ProcessPageRank;
-pub use sketch::CountMinSketch;
+pub use sketch::{AccessTracker, CountMinSketch};
 pub use spectral::SpectralAnalyzer;
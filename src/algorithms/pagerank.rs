@@ -2,18 +2,22 @@
 //!
 //! Ranks processes by their importance in the process dependency graph.
 //! High-rank processes are preserved; low-rank processes are trimmed first.
+//! Edges come from [`crate::monitor::relations`]: parents, IPC peers and
+//! shared modules, each pointing at the process depended on.
 
 use std::collections::HashMap;
 use sysinfo::System;
 
+use crate::monitor::relations::{self, Relation};
+
 /// PageRank-based process prioritization
 pub struct ProcessPageRank {
     /// PageRank scores for each process
     scores: HashMap<u32, f64>,
-    /// Adjacency list (outgoing edges)
-    outlinks: HashMap<u32, Vec<u32>>,
-    /// Reverse adjacency list (incoming edges) - for O(1) inlink lookup
-    inlinks: HashMap<u32, Vec<u32>>,
+    /// Total weight of each process's outgoing edges
+    out_weights: HashMap<u32, f64>,
+    /// Reverse adjacency list (weighted incoming edges) - for O(1) inlink lookup
+    inlinks: HashMap<u32, Vec<(u32, f64)>>,
     /// Damping factor (typically 0.85)
     damping: f64,
     /// Convergence threshold
@@ -26,7 +30,7 @@ impl ProcessPageRank {
     pub fn new() -> Self {
         Self {
             scores: HashMap::new(),
-            outlinks: HashMap::new(),
+            out_weights: HashMap::new(),
             inlinks: HashMap::new(),
             damping: 0.85,
             epsilon: 1e-6,
@@ -36,51 +40,49 @@ impl ProcessPageRank {
 
     /// Build the process graph and compute PageRank scores
     pub fn compute(&mut self, system: &System) {
-        self.outlinks.clear();
+        self.compute_with(system, &relations::collect(system));
+    }
+
+    /// Compute PageRank scores over relations already collected
+    pub fn compute_with(&mut self, system: &System, relations: &[Relation]) {
+        let processes: Vec<u32> = system.processes().keys().map(|p| p.as_u32()).collect();
+        self.rank(&processes, relations);
+    }
+
+    fn rank(&mut self, processes: &[u32], relations: &[Relation]) {
+        self.out_weights.clear();
         self.inlinks.clear();
         self.scores.clear();
 
-        let processes: Vec<u32> = system.processes().keys().map(|p| p.as_u32()).collect();
         let n = processes.len();
 
         if n == 0 {
             return;
         }
 
-        // Build outlink + inlink graphs (parent -> children)
-        for (pid, process) in system.processes() {
-            let pid_u32 = pid.as_u32();
-
-            // Parent points to child (influence flows from parent)
-            if let Some(parent_pid) = process.parent() {
-                let parent_u32 = parent_pid.as_u32();
-                self.outlinks
-                    .entry(parent_u32)
-                    .or_default()
-                    .push(pid_u32);
-                // Build reverse index: child -> list of parents pointing to it
-                self.inlinks
-                    .entry(pid_u32)
-                    .or_default()
-                    .push(parent_u32);
+        // Weighted outlink totals + inlink graph, between running processes only
+        let present: std::collections::HashSet<u32> = processes.iter().copied().collect();
+        for relation in relations {
+            if relation.weight <= 0.0 || !present.contains(&relation.from) || !present.contains(&relation.to) {
+                continue;
             }
+            *self.out_weights.entry(relation.from).or_default() += relation.weight;
+            // Build reverse index: process -> processes depending on it
+            self.inlinks
+                .entry(relation.to)
+                .or_default()
+                .push((relation.from, relation.weight));
         }
 
-        // Pre-compute out-degrees for O(1) lookup
-        let out_degrees: HashMap<u32, f64> = self.outlinks
-            .iter()
-            .map(|(&k, v)| (k, v.len() as f64))
-            .collect();
-
         // Collect dangling nodes (no outlinks)
         let dangling_nodes: Vec<u32> = processes.iter()
-            .filter(|p| !self.outlinks.contains_key(p))
+            .filter(|p| !self.out_weights.contains_key(p))
             .copied()
             .collect();
 
         // Initialize scores uniformly
         let initial_score = 1.0 / n as f64;
-        for &pid in &processes {
+        for &pid in processes {
             self.scores.insert(pid, initial_score);
         }
 
@@ -101,15 +103,15 @@ impl ProcessPageRank {
             let teleport = (1.0 - self.damping) / n_f64;
             let base_score = teleport + dangling_contrib;
 
-            for &pid in &processes {
+            for &pid in processes {
                 let mut score = base_score;
 
                 // O(in-degree) instead of O(m): only iterate inlinks to this node
                 if let Some(sources) = self.inlinks.get(&pid) {
-                    for &source in sources {
+                    for &(source, weight) in sources {
                         let source_score = self.scores.get(&source).unwrap_or(&0.0);
-                        let out_deg = out_degrees.get(&source).unwrap_or(&1.0);
-                        score += self.damping * source_score / out_deg;
+                        let out_weight = self.out_weights.get(&source).unwrap_or(&1.0);
+                        score += self.damping * source_score * weight / out_weight;
                     }
                 }
 
@@ -183,6 +185,9 @@ impl ProcessPageRank {
     }
 
     /// Combine PageRank with memory usage for final priority
+    ///
+    /// Both are scaled against the largest, so the most central process
+    /// counts 1.0 of rank just as the largest counts 1.0 of memory.
     pub fn get_weighted_candidates(
         &self,
         system: &System,
@@ -199,10 +204,11 @@ impl ProcessPageRank {
             .map(|p| p.memory())
             .max()
             .unwrap_or(1) as f64;
+        let max_rank = self.scores.values().copied().fold(0.0f64, f64::max);
 
         for (pid, process) in system.processes() {
             let pid_u32 = pid.as_u32();
-            let rank_score = self.get_score(pid_u32);
+            let rank_score = if max_rank > 0.0 { self.get_score(pid_u32) / max_rank } else { 0.0 };
             let memory_score = process.memory() as f64 / max_memory;
 
             // Combined score: high memory + low rank = good trim candidate
@@ -241,4 +247,24 @@ mod tests {
         let stats = pagerank.stats();
         assert_eq!(stats.process_count, 0);
     }
+
+    #[test]
+    fn test_rank_follows_dependencies() {
+        use crate::monitor::relations::RelationKind;
+
+        let relation = |from, to, kind| Relation { from, to, kind, weight: 1.0 };
+        // 2..=5 talk to server 1; 6 is a child of 5; 7 stands alone
+        let mut relations: Vec<Relation> = (2..=5).map(|pid| relation(pid, 1, RelationKind::Socket)).collect();
+        relations.push(relation(6, 5, RelationKind::Parent));
+        relations.push(relation(8, 1, RelationKind::Socket)); // 8 is not running
+
+        let mut pagerank = ProcessPageRank::new();
+        pagerank.rank(&[1, 2, 3, 4, 5, 6, 7], &relations);
+        let server = pagerank.get_score(1);
+        assert_eq!(pagerank.get_critical_processes(1)[0].0, 1);
+        assert!(pagerank.get_score(5) > pagerank.get_score(6));
+        assert!((pagerank.get_score(2) - pagerank.get_score(7)).abs() < 1e-9);
+        assert!(server > 3.0 * pagerank.get_score(7));
+        assert_eq!(pagerank.get_score(8), 0.0);
+    }
 }
@@ -1,10 +1,13 @@
 //! Count-Min Sketch for sublinear frequency estimation
 //!
 //! Provides O(1) space approximate frequency counting for memory patterns.
-//! Used to detect frequent memory pressure events without storing all history.
+//! Used to detect frequent memory pressure events without storing all history,
+//! and to tell processes whose pages are cold from ones still touching them.
 
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use chrono::Timelike;
 
 /// Count-Min Sketch for approximate frequency counting
@@ -232,6 +235,128 @@ pub struct PressureTrackerStats {
     pub time_stats: SketchStats,
 }
 
+/// Time covered by one page access window
+pub const ACCESS_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Windows kept; faults older than these are forgotten
+pub const ACCESS_WINDOWS: usize = 12;
+
+/// Columns per window sketch; PIDs in use rarely exceed a few hundred
+const ACCESS_SKETCH_WIDTH: usize = 1024;
+
+/// Page access frequency per process over sliding time windows
+///
+/// Each window is a Count-Min Sketch of the page faults every process took
+/// in it, keyed by PID. Recent windows weigh more: a window's faults count
+/// half as much as the next newer one's.
+pub struct AccessTracker {
+    /// Newest window last
+    windows: VecDeque<CountMinSketch>,
+    window_started: Option<Instant>,
+    /// Fault counter at the previous sample, and whether one came before it
+    last: HashMap<u32, (u64, bool)>,
+}
+
+impl AccessTracker {
+    pub fn new() -> Self {
+        Self { windows: VecDeque::new(), window_started: None, last: HashMap::new() }
+    }
+
+    /// Record cumulative fault counters `(pid, faults)` sampled at `now`
+    ///
+    /// Processes missing from `samples` are taken to have exited.
+    pub fn record(&mut self, samples: impl IntoIterator<Item = (u32, u64)>, now: Instant) {
+        self.rotate(now);
+        let mut last = HashMap::with_capacity(self.last.len());
+        for (pid, faults) in samples {
+            let seen = match self.last.get(&pid) {
+                Some(&(before, _)) => {
+                    // A smaller counter is a new process that reused the PID
+                    let delta = faults.saturating_sub(before);
+                    if delta > 0 {
+                        if let Some(window) = self.windows.back_mut() {
+                            window.add_count(pid as u64, delta);
+                        }
+                    }
+                    faults >= before
+                }
+                None => false,
+            };
+            last.insert(pid, (faults, seen));
+        }
+        self.last = last;
+    }
+
+    /// Start a new window once the current one has run its course
+    fn rotate(&mut self, now: Instant) {
+        let Some(started) = self.window_started else {
+            self.windows.push_back(CountMinSketch::with_dimensions(ACCESS_SKETCH_WIDTH, 4));
+            self.window_started = Some(now);
+            return;
+        };
+        let elapsed = now.saturating_duration_since(started);
+        let passed = (elapsed.as_secs() / ACCESS_WINDOW.as_secs()) as usize;
+        if passed == 0 {
+            return;
+        }
+        for _ in 0..passed.min(ACCESS_WINDOWS) {
+            self.windows.push_back(CountMinSketch::with_dimensions(ACCESS_SKETCH_WIDTH, 4));
+        }
+        while self.windows.len() > ACCESS_WINDOWS {
+            self.windows.pop_front();
+        }
+        self.window_started = Some(started + ACCESS_WINDOW * passed as u32);
+    }
+
+    /// Recency-weighted page faults of `pid`
+    pub fn heat(&self, pid: u32) -> f64 {
+        self.windows
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(age, window)| window.estimate(pid as u64) as f64 / (1u64 << age) as f64)
+            .sum()
+    }
+
+    /// How cold `pid`'s pages are: 0.0 for the most often faulting process,
+    /// up to 1.0 for one that took no faults; `None` until it has been
+    /// sampled twice
+    pub fn coldness(&self, pid: u32) -> Option<f64> {
+        self.coldness_all().remove(&pid)
+    }
+
+    /// Coldness of every process sampled at least twice
+    ///
+    /// On a log scale against the hottest process, so one heavy faulter
+    /// doesn't make everything else look equally cold.
+    pub fn coldness_all(&self) -> HashMap<u32, f64> {
+        let heat: Vec<(u32, f64)> = self
+            .last
+            .iter()
+            .filter(|(_, (_, seen))| *seen)
+            .map(|(&pid, _)| (pid, self.heat(pid)))
+            .collect();
+        let hottest = heat.iter().map(|(_, h)| *h).fold(0.0, f64::max);
+        heat.into_iter()
+            .map(|(pid, h)| {
+                let coldness = if hottest > 0.0 { 1.0 - (1.0 + h).ln() / (1.0 + hottest).ln() } else { 1.0 };
+                (pid, coldness)
+            })
+            .collect()
+    }
+
+    /// Memory used by the window sketches
+    pub fn memory_usage(&self) -> usize {
+        self.windows.iter().map(|w| w.memory_usage()).sum()
+    }
+}
+
+impl Default for AccessTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,4 +396,41 @@ mod tests {
         // Process 1234 should be more frequent
         assert!(tracker.process_pressure_frequency(1234) > tracker.process_pressure_frequency(9999));
     }
+
+    #[test]
+    fn test_access_tracker_coldness() {
+        let mut tracker = AccessTracker::new();
+        let start = Instant::now();
+        tracker.record([(1, 100), (2, 100), (3, 100)], start);
+        assert!(tracker.coldness_all().is_empty());
+
+        tracker.record([(1, 50_100), (2, 150), (3, 100), (4, 7)], start + Duration::from_secs(60));
+        let coldness = tracker.coldness_all();
+        assert_eq!(coldness[&1], 0.0);
+        assert!(coldness[&2] > 0.5 && coldness[&2] < 1.0);
+        assert_eq!(coldness[&3], 1.0);
+        // Sampled once, nothing known yet
+        assert_eq!(tracker.coldness(4), None);
+    }
+
+    #[test]
+    fn test_access_tracker_forgets_old_windows() {
+        let mut tracker = AccessTracker::new();
+        let start = Instant::now();
+        tracker.record([(1, 0), (2, 0)], start);
+        tracker.record([(1, 1000), (2, 10)], start + Duration::from_secs(10));
+        let fresh = tracker.heat(1);
+        assert!(fresh >= 1000.0);
+
+        // One window on, the old faults count half
+        tracker.record([(1, 1000), (2, 10)], start + ACCESS_WINDOW);
+        assert_eq!(tracker.heat(1), fresh / 2.0);
+
+        // Past the last window they are gone
+        tracker.record([(1, 1000), (2, 20)], start + ACCESS_WINDOW * (ACCESS_WINDOWS as u32 + 1));
+        assert_eq!(tracker.heat(1), 0.0);
+        assert_eq!(tracker.coldness(1), Some(1.0));
+        assert_eq!(tracker.coldness(2), Some(0.0));
+        assert_eq!(tracker.memory_usage(), ACCESS_WINDOWS * ACCESS_SKETCH_WIDTH * 4 * 8);
+    }
 }
@@ -72,11 +72,17 @@ impl SpectralAnalyzer {
 
         let mean = self.mean();
         let variance = self.variance();
-        let trend = self.trend();
+        // Change across the whole window, so slow growth over a long window
+        // counts as much as fast growth over a short one
+        let rise = self.trend() * self.samples.len() as f64;
         let dominant_freq = self.dominant_frequency();
 
         // Classification rules
-        if variance < 0.01 {
+        if rise > 0.1 {
+            MemoryPatternClass::Increasing
+        } else if rise < -0.1 {
+            MemoryPatternClass::Decreasing
+        } else if variance < 0.01 {
             if mean > 0.8 {
                 MemoryPatternClass::ConstantHigh
             } else if mean < 0.4 {
@@ -84,10 +90,6 @@ impl SpectralAnalyzer {
             } else {
                 MemoryPatternClass::Stable
             }
-        } else if trend > 0.1 {
-            MemoryPatternClass::Increasing
-        } else if trend < -0.1 {
-            MemoryPatternClass::Decreasing
         } else if dominant_freq > 0 && self.frequency_bins[dominant_freq] > 0.1 {
             MemoryPatternClass::Oscillating
         } else if variance > 0.1 {
@@ -239,6 +241,23 @@ pub enum MemoryPatternClass {
     Normal,
 }
 
+impl std::fmt::Display for MemoryPatternClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MemoryPatternClass::Unknown => "unknown",
+            MemoryPatternClass::Stable => "stable",
+            MemoryPatternClass::ConstantLow => "constant low",
+            MemoryPatternClass::ConstantHigh => "constant high",
+            MemoryPatternClass::Increasing => "increasing",
+            MemoryPatternClass::Decreasing => "decreasing",
+            MemoryPatternClass::Oscillating => "oscillating",
+            MemoryPatternClass::Volatile => "volatile",
+            MemoryPatternClass::Normal => "normal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Recommended action based on spectral analysis
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RecommendedAction {
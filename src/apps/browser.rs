@@ -3,17 +3,22 @@
 //! Detects and manages memory usage for major browsers:
 //! - Google Chrome / Chromium
 //! - Mozilla Firefox
-//! - Apple Safari
+//! - Apple Safari (including WebKit WebContent/Networking/GPU helpers)
 //! - Microsoft Edge
 //! - Arc Browser
 //! - Brave Browser
 //! - Opera / Opera GX
 //! - Vivaldi
+//!
+//! `TabAdvisor` ranks idle tabs by memory × idle time for suspension.
 
-use super::{AppCategory, AppInfo, AppProcess, OptimizationAction, OptimizationResult};
+use super::{process_memory_mb, OptimizationAction, OptimizationResult};
+use crate::platform::ProcessMemoryInfo;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use sysinfo::{System, ProcessesToUpdate, Pid};
+use crate::core::text::truncate_padded;
 
 /// Browser identification patterns
 #[derive(Debug, Clone)]
@@ -28,6 +33,8 @@ pub struct BrowserPattern {
     pub gpu_patterns: &'static [&'static str],
     /// Extension/plugin patterns
     pub extension_patterns: &'static [&'static str],
+    /// Network service process patterns
+    pub network_patterns: &'static [&'static str],
 }
 
 /// Known browser patterns
@@ -39,6 +46,7 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["chrome helper", "google chrome helper", "chromedriver"],
         gpu_patterns: &["chrome helper (gpu)", "chrome gpu"],
         extension_patterns: &["chrome helper (renderer)", "chrome helper (plugin)"],
+        network_patterns: &[],
     },
     BrowserPattern {
         name: "firefox",
@@ -47,14 +55,16 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["firefox helper", "plugin-container", "firefox-bin"],
         gpu_patterns: &["firefox gpu"],
         extension_patterns: &["web content", "webextensions"],
+        network_patterns: &[],
     },
     BrowserPattern {
         name: "safari",
         display_name: "Apple Safari",
         main_patterns: &["safari", "safari.app"],
-        helper_patterns: &["safari web content", "webkit networking", "safari networking"],
-        gpu_patterns: &["safari graphics"],
+        helper_patterns: &["safari web content", "com.apple.webkit.webcontent"],
+        gpu_patterns: &["safari graphics", "com.apple.webkit.gpu"],
         extension_patterns: &["safari extension"],
+        network_patterns: &["webkit networking", "safari networking", "com.apple.webkit.networking"],
     },
     BrowserPattern {
         name: "edge",
@@ -63,6 +73,7 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["microsoft edge helper", "msedge helper"],
         gpu_patterns: &["msedge helper (gpu)"],
         extension_patterns: &["msedge helper (renderer)"],
+        network_patterns: &[],
     },
     BrowserPattern {
         name: "arc",
@@ -71,6 +82,7 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["arc helper", "arc helper (renderer)"],
         gpu_patterns: &["arc helper (gpu)"],
         extension_patterns: &["arc helper (plugin)"],
+        network_patterns: &[],
     },
     BrowserPattern {
         name: "brave",
@@ -79,6 +91,7 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["brave browser helper", "brave helper"],
         gpu_patterns: &["brave browser helper (gpu)"],
         extension_patterns: &["brave browser helper (renderer)"],
+        network_patterns: &[],
     },
     BrowserPattern {
         name: "opera",
@@ -87,6 +100,7 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["opera helper", "opera gx helper"],
         gpu_patterns: &["opera helper (gpu)"],
         extension_patterns: &["opera helper (renderer)"],
+        network_patterns: &[],
     },
     BrowserPattern {
         name: "vivaldi",
@@ -95,6 +109,7 @@ pub const BROWSERS: &[BrowserPattern] = &[
         helper_patterns: &["vivaldi helper"],
         gpu_patterns: &["vivaldi helper (gpu)"],
         extension_patterns: &["vivaldi helper (renderer)"],
+        network_patterns: &[],
     },
 ];
 
@@ -106,6 +121,7 @@ pub struct BrowserProcess {
     pub process_type: BrowserProcessType,
     pub memory_mb: f64,
     pub cpu_percent: f32,
+    pub run_time_secs: u64,
 }
 
 /// Type of browser process
@@ -166,6 +182,107 @@ impl BrowserInfo {
     }
 }
 
+/// Single WebContent process above this is worth closing on its own
+const SAFARI_HEAVY_TAB_MB: f64 = 1000.0;
+/// WebKit Networking above this usually means a bloated disk/memory cache
+const SAFARI_NETWORKING_MB: f64 = 300.0;
+/// Combined WebContent memory above this warrants a pressure hint
+const SAFARI_WEB_CONTENT_MB: f64 = 1500.0;
+
+/// Per-helper memory breakdown of Safari and its WebKit processes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafariBreakdown {
+    pub main_mb: f64,
+    pub web_content_mb: f64,
+    pub web_content_count: usize,
+    pub networking_mb: f64,
+    pub gpu_mb: f64,
+    pub extension_mb: f64,
+    /// Largest WebContent process (typically one heavy tab or site)
+    pub heaviest_content: Option<BrowserProcess>,
+}
+
+impl SafariBreakdown {
+    pub fn from_info(info: &BrowserInfo) -> Self {
+        let mut breakdown = Self {
+            main_mb: 0.0,
+            web_content_mb: 0.0,
+            web_content_count: 0,
+            networking_mb: 0.0,
+            gpu_mb: 0.0,
+            extension_mb: 0.0,
+            heaviest_content: None,
+        };
+
+        for proc in &info.processes {
+            match proc.process_type {
+                BrowserProcessType::Main | BrowserProcessType::Utility => breakdown.main_mb += proc.memory_mb,
+                BrowserProcessType::Renderer => {
+                    breakdown.web_content_mb += proc.memory_mb;
+                    breakdown.web_content_count += 1;
+                    let heavier = breakdown
+                        .heaviest_content
+                        .as_ref()
+                        .map_or(true, |h| proc.memory_mb > h.memory_mb);
+                    if heavier {
+                        breakdown.heaviest_content = Some(proc.clone());
+                    }
+                }
+                BrowserProcessType::Network => breakdown.networking_mb += proc.memory_mb,
+                BrowserProcessType::GPU => breakdown.gpu_mb += proc.memory_mb,
+                BrowserProcessType::Extension | BrowserProcessType::Plugin => {
+                    breakdown.extension_mb += proc.memory_mb
+                }
+                BrowserProcessType::Unknown => {}
+            }
+        }
+
+        breakdown
+    }
+
+    /// Safari-specific suggestions as (action, reason, estimated savings MB)
+    pub fn suggestions(&self) -> Vec<(OptimizationAction, String, f64)> {
+        let mut suggestions = Vec::new();
+
+        if let Some(heavy) = &self.heaviest_content {
+            if heavy.memory_mb > SAFARI_HEAVY_TAB_MB {
+                suggestions.push((
+                    OptimizationAction::Close,
+                    format!(
+                        "A Safari tab (WebContent PID {}) is using {:.0} MB - close or reload it",
+                        heavy.pid, heavy.memory_mb
+                    ),
+                    heavy.memory_mb,
+                ));
+            }
+        }
+
+        if self.networking_mb > SAFARI_NETWORKING_MB {
+            suggestions.push((
+                OptimizationAction::ClearCache,
+                format!(
+                    "WebKit Networking is using {:.0} MB - empty Safari's caches (Develop > Empty Caches)",
+                    self.networking_mb
+                ),
+                self.networking_mb * 0.5,
+            ));
+        }
+
+        if self.web_content_mb > SAFARI_WEB_CONTENT_MB {
+            suggestions.push((
+                OptimizationAction::TrimMemory,
+                format!(
+                    "Safari web content is using {:.0} MB across {} processes - 'memory_pressure -l warn' makes WebKit drop caches, 'sudo purge' flushes the file cache",
+                    self.web_content_mb, self.web_content_count
+                ),
+                self.web_content_mb * 0.2,
+            ));
+        }
+
+        suggestions
+    }
+}
+
 /// Browser memory optimizer
 pub struct BrowserOptimizer {
     system: System,
@@ -214,7 +331,7 @@ impl BrowserOptimizer {
 
             for (pid, process) in self.system.processes() {
                 let name = process.name().to_string_lossy().to_lowercase();
-                let memory_mb = process.memory() as f64 / (1024.0 * 1024.0);
+                let memory_mb = process_memory_mb(process);
                 let cpu_percent = process.cpu_usage();
 
                 let process_type = self.classify_process(&name, pattern);
@@ -227,6 +344,7 @@ impl BrowserOptimizer {
                         process_type,
                         memory_mb,
                         cpu_percent,
+                        run_time_secs: process.run_time(),
                     };
 
                     browser_info.total_memory_mb += memory_mb;
@@ -255,6 +373,12 @@ impl BrowserOptimizer {
                 }
             }
 
+            // WebKit helpers are shared with Mail and other WebKit clients;
+            // only attribute them to Safari while Safari itself is running
+            if pattern.name == "safari" && browser_info.main_pid.is_none() {
+                continue;
+            }
+
             // Only include if we found any processes
             if browser_info.process_count > 0 {
                 // Estimate tabs from renderer processes (each tab ~= 1 renderer)
@@ -270,14 +394,8 @@ impl BrowserOptimizer {
 
     /// Classify a process based on browser pattern
     fn classify_process(&self, name: &str, pattern: &BrowserPattern) -> BrowserProcessType {
-        // Check main process first
-        for p in pattern.main_patterns {
-            if name.contains(p) && !name.contains("helper") {
-                return BrowserProcessType::Main;
-            }
-        }
-
-        // Check GPU process
+        // Check GPU process first so helpers named after the browser
+        // (e.g. "Safari Web Content") are not mistaken for the main process
         for p in pattern.gpu_patterns {
             if name.contains(p) {
                 return BrowserProcessType::GPU;
@@ -291,6 +409,13 @@ impl BrowserOptimizer {
             }
         }
 
+        // Check network service process
+        for p in pattern.network_patterns {
+            if name.contains(p) {
+                return BrowserProcessType::Network;
+            }
+        }
+
         // Check helper/renderer process
         for p in pattern.helper_patterns {
             if name.contains(p) {
@@ -298,6 +423,13 @@ impl BrowserOptimizer {
             }
         }
 
+        // Check main process
+        for p in pattern.main_patterns {
+            if name.contains(p) && !name.contains("helper") {
+                return BrowserProcessType::Main;
+            }
+        }
+
         // Check if it matches any main pattern (catch-all for related processes)
         for p in pattern.main_patterns {
             if name.contains(p) {
@@ -318,6 +450,23 @@ impl BrowserOptimizer {
         self.browsers.get(name)
     }
 
+    /// Per-helper breakdown for Safari, if it is running
+    pub fn safari_breakdown(&self) -> Option<SafariBreakdown> {
+        self.browsers.get("safari").map(SafariBreakdown::from_info)
+    }
+
+    /// Remote debugging (CDP) port the browser was started with, if any
+    pub fn debugging_port(&self, name: &str) -> Option<u16> {
+        let pid = self.browsers.get(name)?.main_pid?;
+        let process = self.system.process(Pid::from_u32(pid))?;
+        process.cmd().iter().find_map(|arg| {
+            arg.to_str()?
+                .strip_prefix("--remote-debugging-port=")?
+                .parse()
+                .ok()
+        })
+    }
+
     /// Get total browser memory usage
     pub fn total_memory_mb(&self) -> f64 {
         self.browsers.values().map(|b| b.total_memory_mb).sum()
@@ -377,6 +526,12 @@ impl BrowserOptimizer {
             }
         }
 
+        if let Some(breakdown) = self.safari_breakdown() {
+            for (action, reason, _) in breakdown.suggestions() {
+                suggestions.push(("Apple Safari".to_string(), action, reason));
+            }
+        }
+
         // Sort by memory (highest first)
         suggestions.sort_by(|a, b| {
             let mem_a = self.browsers.values().find(|browser| browser.display_name == a.0).map(|browser| browser.total_memory_mb).unwrap_or(0.0);
@@ -419,7 +574,15 @@ impl BrowserOptimizer {
                     proc.pid,
                 ) {
                     let before = proc.memory_mb;
-                    if EmptyWorkingSet(handle).is_ok() {
+                    let result = EmptyWorkingSet(handle);
+                    crate::security::audit::record(
+                        crate::security::audit::AuditAction::Trim,
+                        &proc.name,
+                        Some(proc.pid),
+                        "",
+                        &result,
+                    );
+                    if result.is_ok() {
                         // Estimate ~30% reduction
                         total_freed += before * 0.3;
                         trimmed += 1;
@@ -457,6 +620,20 @@ impl BrowserOptimizer {
         // For individual apps, we can send SIGURG or use madvise hints
         // But direct memory trimming isn't as straightforward as Windows
 
+        if browser.name == "safari" {
+            let breakdown = SafariBreakdown::from_info(browser);
+            return OptimizationResult {
+                app_name: browser.display_name.clone(),
+                action: OptimizationAction::TrimMemory,
+                success: false,
+                memory_freed_mb: 0.0,
+                message: format!(
+                    "Safari using {:.0} MB ({:.0} MB in {} WebContent processes). Run 'memory_pressure -l warn' to make WebKit release caches, or 'sudo purge'.",
+                    browser.total_memory_mb, breakdown.web_content_mb, breakdown.web_content_count
+                ),
+            };
+        }
+
         OptimizationResult {
             app_name: browser.display_name.clone(),
             action: OptimizationAction::TrimMemory,
@@ -502,7 +679,7 @@ impl BrowserOptimizer {
             println!(
                 "│ {} {:18} │ {:>7.0} MB │ {:>6.1}%  │ {:>5} │ {:>11} │",
                 mem_indicator,
-                truncate(&browser.display_name, 18),
+                truncate_padded(&browser.display_name, 18),
                 browser.total_memory_mb,
                 browser.total_cpu_percent,
                 browser.estimated_tabs,
@@ -521,6 +698,40 @@ impl BrowserOptimizer {
             browsers.len()
         );
 
+        // Renderers map the same libraries and shared memory, so the sums
+        // above count those pages once per process
+        let split: Vec<(&str, ProcessMemoryInfo)> = browsers
+            .iter()
+            .filter_map(|b| Some((b.display_name.as_str(), ProcessMemoryInfo::combined(&b.pids)?)))
+            .collect();
+        if !split.is_empty() {
+            println!("\nCounting shared pages once:");
+            for (name, info) in &split {
+                println!(
+                    "   {:18} {:>7.0} MB  (private {:.0} · shared {:.0} · file-backed {:.0} MB)",
+                    truncate_padded(name, 18),
+                    info.footprint_mb,
+                    info.private_mb,
+                    info.shared_mb,
+                    info.mapped_mb
+                );
+            }
+        }
+
+        if let Some(breakdown) = self.safari_breakdown() {
+            println!("\n🧭 Safari Breakdown");
+            println!("   Safari:            {:>7.0} MB", breakdown.main_mb);
+            println!(
+                "   WebContent:        {:>7.0} MB ({} processes)",
+                breakdown.web_content_mb, breakdown.web_content_count
+            );
+            println!("   WebKit Networking: {:>7.0} MB", breakdown.networking_mb);
+            println!("   WebKit GPU:        {:>7.0} MB", breakdown.gpu_mb);
+            if breakdown.extension_mb > 0.0 {
+                println!("   Extensions:        {:>7.0} MB", breakdown.extension_mb);
+            }
+        }
+
         // Print suggestions
         let suggestions = self.get_suggestions();
         if !suggestions.is_empty() {
@@ -538,10 +749,239 @@ impl Default for BrowserOptimizer {
     }
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        format!("{:width$}", s, width = max)
-    } else {
-        format!("{}...", &s[..max - 3])
+/// Renderers below this CPU usage count as idle
+const TAB_IDLE_CPU_PERCENT: f32 = 0.5;
+/// Tabs idle for less than this are never recommended for suspension
+const TAB_MIN_IDLE: Duration = Duration::from_secs(15 * 60);
+/// Renderers smaller than this are not worth suspending
+const TAB_MIN_MEMORY_MB: f64 = 50.0;
+
+/// A background tab known by title, from CDP or the OS window list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabTitle {
+    pub title: String,
+    pub url: Option<String>,
+}
+
+/// A renderer process (one tab or site) considered for suspension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabCandidate {
+    pub pid: u32,
+    pub memory_mb: f64,
+    pub idle_secs: u64,
+    /// memory (MB) × idle time (minutes)
+    pub score: f64,
+}
+
+/// Concrete suspension recommendation for one browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabRecommendation {
+    pub browser: String,
+    pub display_name: String,
+    /// Idle renderers, highest score first
+    pub tabs: Vec<TabCandidate>,
+    /// Background tab titles, when the browser exposes them
+    pub titles: Vec<TabTitle>,
+    pub estimated_savings_mb: f64,
+}
+
+impl TabRecommendation {
+    pub fn pids(&self) -> Vec<u32> {
+        self.tabs.iter().map(|t| t.pid).collect()
+    }
+
+    /// One-line summary, e.g. "Suspend 12 idle Google Chrome tabs to free ~1.4 GB"
+    pub fn summary(&self) -> String {
+        let savings = if self.estimated_savings_mb >= 1024.0 {
+            format!("~{:.1} GB", self.estimated_savings_mb / 1024.0)
+        } else {
+            format!("~{:.0} MB", self.estimated_savings_mb)
+        };
+        format!(
+            "Suspend {} idle {} tab{} to free {}",
+            self.tabs.len(),
+            self.display_name,
+            if self.tabs.len() == 1 { "" } else { "s" },
+            savings
+        )
     }
 }
+
+/// Ranks browser tabs by memory × idle time.
+///
+/// Renderer processes stand in for tabs: a renderer that stays below
+/// `TAB_IDLE_CPU_PERCENT` between refreshes accumulates idle time. On first
+/// sight a renderer is assumed idle since it started, so one-shot CLI runs
+/// still rank long-lived background tabs; repeated refreshes (daemon, tray)
+/// correct that as soon as a tab does any work. Tab titles come from the
+/// Chrome DevTools Protocol when the browser runs with
+/// `--remote-debugging-port`, minus the foreground tab of each window, which
+/// is read from the OS window titles.
+pub struct TabAdvisor {
+    idle_since: HashMap<u32, Instant>,
+    titles: HashMap<String, Vec<TabTitle>>,
+    min_idle: Duration,
+}
+
+impl TabAdvisor {
+    pub fn new() -> Self {
+        Self {
+            idle_since: HashMap::new(),
+            titles: HashMap::new(),
+            min_idle: TAB_MIN_IDLE,
+        }
+    }
+
+    pub fn with_min_idle(mut self, min_idle: Duration) -> Self {
+        self.min_idle = min_idle;
+        self
+    }
+
+    /// Update idle tracking and tab titles from a refreshed optimizer
+    pub fn refresh(&mut self, optimizer: &BrowserOptimizer) {
+        let now = Instant::now();
+        let mut seen = Vec::new();
+        self.titles.clear();
+
+        for browser in optimizer.get_browsers() {
+            for proc in &browser.processes {
+                if proc.process_type != BrowserProcessType::Renderer {
+                    continue;
+                }
+                seen.push(proc.pid);
+                let since = self.idle_since.entry(proc.pid).or_insert_with(|| {
+                    now.checked_sub(Duration::from_secs(proc.run_time_secs))
+                        .unwrap_or(now)
+                });
+                if proc.cpu_percent > TAB_IDLE_CPU_PERCENT {
+                    *since = now;
+                }
+            }
+
+            if let Some(port) = optimizer.debugging_port(&browser.name) {
+                let foreground = window_titles(&browser.pids);
+                let background: Vec<TabTitle> = cdp_tabs(port)
+                    .into_iter()
+                    .filter(|tab| !foreground.iter().any(|w| w.starts_with(&tab.title)))
+                    .collect();
+                self.titles.insert(browser.name.clone(), background);
+            }
+        }
+
+        self.idle_since.retain(|pid, _| seen.contains(pid));
+    }
+
+    /// Idle renderers of one browser, highest memory × idle first
+    pub fn rank(&self, browser: &BrowserInfo) -> Vec<TabCandidate> {
+        let now = Instant::now();
+        let mut renderers: Vec<&BrowserProcess> = browser
+            .processes
+            .iter()
+            .filter(|p| p.process_type == BrowserProcessType::Renderer)
+            .collect();
+
+        // Keep the busiest renderer: it is most likely the tab in use
+        renderers.sort_by(|a, b| b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap());
+        let mut candidates: Vec<TabCandidate> = renderers
+            .iter()
+            .skip(1)
+            .filter_map(|p| {
+                let idle = now.duration_since(*self.idle_since.get(&p.pid)?);
+                if idle < self.min_idle || p.memory_mb < TAB_MIN_MEMORY_MB {
+                    return None;
+                }
+                Some(TabCandidate {
+                    pid: p.pid,
+                    memory_mb: p.memory_mb,
+                    idle_secs: idle.as_secs(),
+                    score: p.memory_mb * idle.as_secs_f64() / 60.0,
+                })
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        candidates
+    }
+
+    /// Suspension recommendations for every browser with idle tabs
+    pub fn recommendations(&self, optimizer: &BrowserOptimizer) -> Vec<TabRecommendation> {
+        let mut recommendations: Vec<TabRecommendation> = optimizer
+            .get_browsers()
+            .into_iter()
+            .filter_map(|browser| {
+                let tabs = self.rank(browser);
+                if tabs.is_empty() {
+                    return None;
+                }
+                Some(TabRecommendation {
+                    browser: browser.name.clone(),
+                    display_name: browser.display_name.clone(),
+                    estimated_savings_mb: tabs.iter().map(|t| t.memory_mb).sum(),
+                    titles: self.titles.get(&browser.name).cloned().unwrap_or_default(),
+                    tabs,
+                })
+            })
+            .collect();
+
+        recommendations.sort_by(|a, b| b.estimated_savings_mb.partial_cmp(&a.estimated_savings_mb).unwrap());
+        recommendations
+    }
+}
+
+impl Default for TabAdvisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// List page targets from the DevTools HTTP endpoint (`/json/list`)
+fn cdp_tabs(port: u16) -> Vec<TabTitle> {
+    super::devtools::list_targets(port)
+        .into_iter()
+        .filter(|t| t.kind == "page")
+        .map(|t| TabTitle {
+            title: t.title,
+            url: Some(t.url),
+        })
+        .collect()
+}
+
+/// Titles of visible top-level windows owned by the given processes
+#[cfg(target_os = "windows")]
+fn window_titles(pids: &[u32]) -> Vec<String> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+    };
+
+    struct Search<'a> {
+        pids: &'a [u32],
+        titles: Vec<String>,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+        if search.pids.contains(&pid) && IsWindowVisible(hwnd).as_bool() {
+            let mut buf = [0u16; 512];
+            let len = GetWindowTextW(hwnd, &mut buf);
+            if len > 0 {
+                search.titles.push(String::from_utf16_lossy(&buf[..len as usize]));
+            }
+        }
+        BOOL(1)
+    }
+
+    let mut search = Search { pids, titles: Vec::new() };
+    unsafe {
+        let _ = EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize));
+    }
+    search.titles
+}
+
+#[cfg(not(target_os = "windows"))]
+fn window_titles(_pids: &[u32]) -> Vec<String> {
+    Vec::new()
+}
+
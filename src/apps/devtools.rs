@@ -0,0 +1,150 @@
+//! Minimal Chrome DevTools Protocol client
+//!
+//! Enough CDP to list targets over HTTP (`/json/list`) and send commands to
+//! one of them over its WebSocket. Everything talks to 127.0.0.1 only and
+//! uses short timeouts, since the endpoint may belong to a busy app.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// Connect/read timeout for the HTTP endpoint
+pub const HTTP_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Time a single command may take; sampling profiles can be slow to build
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A debuggable target from `/json/list`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Target {
+    pub id: String,
+    /// `page`, `iframe`, `worker`, `node`, ...
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(rename = "webSocketDebuggerUrl", default)]
+    pub ws_url: Option<String>,
+}
+
+/// Debugging port passed on a command line
+///
+/// Chromium's `--remote-debugging-port=N`, Node's `--inspect[-brk]=[host:]N`
+/// and VS Code's `--inspect[-brk]-extensions=N` for the extension host.
+pub fn port_from_args<S: AsRef<str>>(args: &[S]) -> Option<u16> {
+    const FLAGS: &[&str] = &[
+        "--remote-debugging-port=",
+        "--inspect-extensions=",
+        "--inspect-brk-extensions=",
+        "--inspect=",
+        "--inspect-brk=",
+    ];
+    args.iter().find_map(|arg| {
+        let value = FLAGS.iter().find_map(|flag| arg.as_ref().strip_prefix(flag))?;
+        let port = value.rsplit(':').next()?;
+        port.parse().ok().filter(|&p| p != 0)
+    })
+}
+
+/// Targets listed by the endpoint on `port`, empty if nothing answers
+pub fn list_targets(port: u16) -> Vec<Target> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let Ok(mut stream) = TcpStream::connect_timeout(&addr, HTTP_TIMEOUT) else {
+        return Vec::new();
+    };
+    let _ = stream.set_read_timeout(Some(HTTP_TIMEOUT));
+    let request = format!("GET /json/list HTTP/1.0\r\nHost: 127.0.0.1:{}\r\n\r\n", port);
+    if stream.write_all(request.as_bytes()).is_err() {
+        return Vec::new();
+    }
+
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    let Some((_, body)) = response.split_once("\r\n\r\n") else {
+        return Vec::new();
+    };
+    serde_json::from_str(body).unwrap_or_default()
+}
+
+/// A WebSocket session with one target
+pub struct Session {
+    socket: WebSocket<TcpStream>,
+    next_id: u64,
+}
+
+impl Session {
+    /// Attach to a target; only local endpoints are accepted
+    pub fn connect(target: &Target) -> Result<Self, String> {
+        let url = target
+            .ws_url
+            .as_deref()
+            .ok_or_else(|| format!("Target '{}' is already being debugged", target.title))?;
+        let host = url
+            .strip_prefix("ws://")
+            .and_then(|rest| rest.split('/').next())
+            .ok_or_else(|| format!("Unsupported debugger URL {}", url))?;
+        let addr: SocketAddr = host
+            .replace("localhost", "127.0.0.1")
+            .parse()
+            .map_err(|_| format!("Unsupported debugger URL {}", url))?;
+        if !addr.ip().is_loopback() {
+            return Err(format!("Refusing non-local debugger URL {}", url));
+        }
+
+        let stream = TcpStream::connect_timeout(&addr, HTTP_TIMEOUT).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(COMMAND_TIMEOUT)).map_err(|e| e.to_string())?;
+        let (socket, _) = tungstenite::client(url, stream).map_err(|e| e.to_string())?;
+        Ok(Self { socket, next_id: 0 })
+    }
+
+    /// Send a command and wait for its result, skipping events
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let request = json!({ "id": id, "method": method, "params": params });
+        self.socket
+            .send(Message::Text(request.to_string()))
+            .map_err(|e| format!("{}: {}", method, e))?;
+
+        loop {
+            let text = match self.socket.read().map_err(|e| format!("{}: {}", method, e))? {
+                Message::Text(text) => text,
+                Message::Close(_) => return Err(format!("{}: target closed the session", method)),
+                _ => continue,
+            };
+            let mut reply: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            if reply["id"].as_u64() != Some(id) {
+                continue;
+            }
+            if let Some(error) = reply.get("error") {
+                return Err(format!("{}: {}", method, error["message"].as_str().unwrap_or("failed")));
+            }
+            return Ok(reply["result"].take());
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.socket.close(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_from_args() {
+        assert_eq!(port_from_args(&["code", "--remote-debugging-port=9222"]), Some(9222));
+        assert_eq!(port_from_args(&["code", "--inspect-extensions=9333"]), Some(9333));
+        assert_eq!(port_from_args(&["node", "--inspect=127.0.0.1:9229"]), Some(9229));
+        assert_eq!(port_from_args(&["slack", "--remote-debugging-port=0"]), None);
+        assert_eq!(port_from_args(&["discord", "--type=renderer"]), None);
+    }
+}
@@ -0,0 +1,316 @@
+//! Disk space taken by well-known caches and old installers
+//!
+//! A nearly full system drive leaves no room for the pagefile or swap to
+//! grow, which makes memory pressure worse. This sizes the places that
+//! usually hold gigabytes nobody needs: browser caches, package manager
+//! caches and installers left in Downloads. Nothing is removed; each entry
+//! carries the command or setting that clears it safely.
+//!
+//! The scan stays light: symlinks are not followed and each location stops
+//! counting after [`MAX_FILES`] files, in which case its size is a lower
+//! bound.
+
+use crate::features::health::metrics::{self, DiskSpace};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Files counted per location before its size is reported as partial
+pub const MAX_FILES: u64 = 200_000;
+
+/// Installers younger than this may still be needed
+const INSTALLER_MIN_AGE: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Installer file names in Downloads
+#[cfg(target_os = "windows")]
+const INSTALLER_PATTERNS: &[&str] = &["*.exe", "*.msi", "*.msix", "*.iso", "*.zip"];
+#[cfg(target_os = "macos")]
+const INSTALLER_PATTERNS: &[&str] = &["*.dmg", "*.pkg", "*.iso", "*.zip"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const INSTALLER_PATTERNS: &[&str] = &["*.deb", "*.rpm", "*.appimage", "*.run", "*.iso", "*.tar.gz"];
+
+/// What a location holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    Browser,
+    Package,
+    Installer,
+}
+
+impl std::fmt::Display for CacheKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CacheKind::Browser => "browser",
+            CacheKind::Package => "package",
+            CacheKind::Installer => "installer",
+        };
+        f.pad(name)
+    }
+}
+
+/// Directory a location's path is relative to
+#[derive(Debug, Clone, Copy)]
+enum Base {
+    Home,
+    /// `%LOCALAPPDATA%`
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    LocalAppData,
+    /// `%SystemRoot%`
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    SystemRoot,
+    /// The path is absolute
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    Root,
+}
+
+/// A well-known location: name, kind, base, path with `/` separators, and
+/// how to clear it
+type Known = (&'static str, CacheKind, Base, &'static str, &'static str);
+
+const COMMON: &[Known] = &[
+    ("Cargo registry", CacheKind::Package, Base::Home, ".cargo/registry/cache", "Delete it; cargo downloads crates again when needed"),
+    ("Gradle", CacheKind::Package, Base::Home, ".gradle/caches", "Delete it while no Gradle daemon is running"),
+    ("NuGet", CacheKind::Package, Base::Home, ".nuget/packages", "Run `dotnet nuget locals all --clear`"),
+];
+
+#[cfg(target_os = "windows")]
+const PLATFORM: &[Known] = &[
+    ("Chrome", CacheKind::Browser, Base::LocalAppData, "Google/Chrome/User Data/Default/Cache", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Edge", CacheKind::Browser, Base::LocalAppData, "Microsoft/Edge/User Data/Default/Cache", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Brave", CacheKind::Browser, Base::LocalAppData, "BraveSoftware/Brave-Browser/User Data/Default/Cache", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Firefox", CacheKind::Browser, Base::LocalAppData, "Mozilla/Firefox/Profiles", "Clear it under Settings > Privacy > Cookies and Site Data"),
+    ("npm", CacheKind::Package, Base::LocalAppData, "npm-cache", "Run `npm cache clean --force`"),
+    ("pip", CacheKind::Package, Base::LocalAppData, "pip/Cache", "Run `pip cache purge`"),
+    ("Yarn", CacheKind::Package, Base::LocalAppData, "Yarn/Cache", "Run `yarn cache clean`"),
+    ("Windows Update downloads", CacheKind::Installer, Base::SystemRoot, "SoftwareDistribution/Download", "Run Disk Cleanup and pick Windows Update Cleanup"),
+];
+
+#[cfg(target_os = "macos")]
+const PLATFORM: &[Known] = &[
+    ("Chrome", CacheKind::Browser, Base::Home, "Library/Caches/Google/Chrome", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Safari", CacheKind::Browser, Base::Home, "Library/Caches/com.apple.Safari", "Use Develop > Empty Caches in Safari"),
+    ("Edge", CacheKind::Browser, Base::Home, "Library/Caches/Microsoft Edge", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Brave", CacheKind::Browser, Base::Home, "Library/Caches/BraveSoftware", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Firefox", CacheKind::Browser, Base::Home, "Library/Caches/Firefox/Profiles", "Clear it under Settings > Privacy > Cookies and Site Data"),
+    ("npm", CacheKind::Package, Base::Home, ".npm/_cacache", "Run `npm cache clean --force`"),
+    ("pip", CacheKind::Package, Base::Home, "Library/Caches/pip", "Run `pip cache purge`"),
+    ("Yarn", CacheKind::Package, Base::Home, "Library/Caches/Yarn", "Run `yarn cache clean`"),
+    ("Homebrew", CacheKind::Package, Base::Home, "Library/Caches/Homebrew", "Run `brew cleanup -s`"),
+    ("CocoaPods", CacheKind::Package, Base::Home, "Library/Caches/CocoaPods", "Run `pod cache clean --all`"),
+    ("Xcode DerivedData", CacheKind::Package, Base::Home, "Library/Developer/Xcode/DerivedData", "Delete it while Xcode is closed; projects rebuild"),
+];
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const PLATFORM: &[Known] = &[
+    ("Chrome", CacheKind::Browser, Base::Home, ".cache/google-chrome", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Chromium", CacheKind::Browser, Base::Home, ".cache/chromium", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Brave", CacheKind::Browser, Base::Home, ".cache/BraveSoftware", "Clear it under Settings > Privacy > Clear browsing data"),
+    ("Firefox", CacheKind::Browser, Base::Home, ".cache/mozilla/firefox", "Clear it under Settings > Privacy > Cookies and Site Data"),
+    ("npm", CacheKind::Package, Base::Home, ".npm/_cacache", "Run `npm cache clean --force`"),
+    ("pip", CacheKind::Package, Base::Home, ".cache/pip", "Run `pip cache purge`"),
+    ("Yarn", CacheKind::Package, Base::Home, ".cache/yarn", "Run `yarn cache clean`"),
+    ("apt", CacheKind::Package, Base::Root, "/var/cache/apt/archives", "Run `sudo apt-get clean`"),
+    ("dnf", CacheKind::Package, Base::Root, "/var/cache/dnf", "Run `sudo dnf clean packages`"),
+    ("pacman", CacheKind::Package, Base::Root, "/var/cache/pacman/pkg", "Run `sudo paccache -r`"),
+];
+
+/// Space taken by one location
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheUsage {
+    pub name: String,
+    pub kind: CacheKind,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub files: u64,
+    /// Counting stopped at [`MAX_FILES`]; `bytes` is a lower bound
+    pub partial: bool,
+    /// How to clear it
+    pub hint: String,
+}
+
+impl CacheUsage {
+    pub fn size_mb(&self) -> f64 {
+        self.bytes as f64 / (1024.0 * 1024.0)
+    }
+}
+
+/// Free space on the system drive and what the known locations take
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskReport {
+    pub disk: Option<DiskSpace>,
+    /// Largest first; empty locations are left out
+    pub caches: Vec<CacheUsage>,
+}
+
+impl DiskReport {
+    /// Everything the known locations hold, in GB
+    pub fn reclaimable_gb(&self) -> f64 {
+        self.caches.iter().map(|c| c.size_mb()).sum::<f64>() / 1024.0
+    }
+
+    pub fn print(&self) {
+        println!("\n💽 Disk Space\n");
+        match &self.disk {
+            Some(disk) => println!(
+                "System drive {}: {:.1} GB free of {:.1} GB ({:.0}%)\n",
+                disk.mount,
+                disk.free_mb / 1024.0,
+                disk.total_mb / 1024.0,
+                disk.free_percent()
+            ),
+            None => println!("System drive not found\n"),
+        }
+        if self.caches.is_empty() {
+            println!("No known caches or old installers found.");
+            return;
+        }
+
+        println!("{:<26} {:<10} {:>10}  Path", "Location", "Kind", "Size");
+        for cache in &self.caches {
+            let size = format!("{}{:.2} GB", if cache.partial { "≥" } else { "" }, cache.size_mb() / 1024.0);
+            println!("{:<26} {:<10} {:>10}  {}", cache.name, cache.kind, size, cache.path.display());
+        }
+        println!("\nReclaimable: {:.1} GB", self.reclaimable_gb());
+
+        println!("\nHow to clear them:");
+        for cache in &self.caches {
+            println!("  {}: {}", cache.name, cache.hint);
+        }
+    }
+}
+
+/// Free space on the system drive plus [`scan_caches`]
+pub fn scan() -> DiskReport {
+    DiskReport { disk: metrics::system_disk(), caches: scan_caches() }
+}
+
+/// Size every known location that exists, largest first
+pub fn scan_caches() -> Vec<CacheUsage> {
+    let mut caches: Vec<CacheUsage> = COMMON
+        .iter()
+        .chain(PLATFORM)
+        .filter_map(|&(name, kind, base, path, hint)| {
+            let root = resolve(base, path)?;
+            let (bytes, files, partial) = measure(&root, MAX_FILES);
+            Some(CacheUsage { name: name.to_string(), kind, path: root, bytes, files, partial, hint: hint.to_string() })
+        })
+        .chain(old_installers())
+        .filter(|c| c.bytes > 0)
+        .collect();
+    caches.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+    caches
+}
+
+fn resolve(base: Base, path: &str) -> Option<PathBuf> {
+    let mut dir = match base {
+        Base::Home => home()?,
+        Base::LocalAppData => PathBuf::from(std::env::var_os("LOCALAPPDATA")?),
+        Base::SystemRoot => PathBuf::from(std::env::var_os("SystemRoot")?),
+        Base::Root => PathBuf::from("/"),
+    };
+    dir.extend(path.split('/').filter(|part| !part.is_empty()));
+    dir.is_dir().then_some(dir)
+}
+
+/// Total size and count of the files below `root`, and whether counting
+/// stopped at `max_files`
+fn measure(root: &Path, max_files: u64) -> (u64, u64, bool) {
+    let mut bytes = 0;
+    let mut files = 0;
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.path().symlink_metadata() else { continue };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                if files == max_files {
+                    return (bytes, files, true);
+                }
+                bytes += metadata.len();
+                files += 1;
+            }
+        }
+    }
+    (bytes, files, false)
+}
+
+/// Installers in Downloads that haven't been touched for a month
+fn old_installers() -> Option<CacheUsage> {
+    let downloads = home()?.join("Downloads");
+    let (bytes, files) = installers_in(&downloads, INSTALLER_MIN_AGE);
+    Some(CacheUsage {
+        name: "Old installers".to_string(),
+        kind: CacheKind::Installer,
+        path: downloads,
+        bytes,
+        files,
+        partial: false,
+        hint: format!("Delete {} in Downloads you have already installed", INSTALLER_PATTERNS.join(", ")),
+    })
+}
+
+/// Size and count of the installer files directly in `dir` older than `min_age`
+fn installers_in(dir: &Path, min_age: Duration) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.path().symlink_metadata().ok().filter(|m| m.is_file())?;
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            if !INSTALLER_PATTERNS.iter().any(|p| crate::security::validator::matches_glob(p, &name)) {
+                return None;
+            }
+            let age = SystemTime::now().duration_since(metadata.modified().ok()?).ok()?;
+            (age >= min_age).then_some(metadata.len())
+        })
+        .fold((0, 0), |(bytes, files), len| (bytes + len, files + 1))
+}
+
+fn home() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_stops_at_budget() {
+        let root = std::env::temp_dir().join(format!("ruvector-diskspace-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        for name in ["one", "a/two", "a/b/three"] {
+            std::fs::write(root.join(name), [0u8; 10]).unwrap();
+        }
+
+        assert_eq!(measure(&root, MAX_FILES), (30, 3, false));
+        let (bytes, files, partial) = measure(&root, 2);
+        assert_eq!((bytes, files, partial), (20, 2, true));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_installers_need_age_and_pattern() {
+        let dir = std::env::temp_dir().join(format!("ruvector-installers-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let installer = INSTALLER_PATTERNS[0].replace('*', "setup");
+        let old = SystemTime::now() - Duration::from_secs(60 * 24 * 3600);
+        for name in [installer.as_str(), "notes.txt"] {
+            std::fs::write(dir.join(name), [0u8; 100]).unwrap();
+            std::fs::File::options().write(true).open(dir.join(name)).unwrap().set_modified(old).unwrap();
+        }
+        std::fs::write(dir.join(format!("fresh-{}", installer)), [0u8; 100]).unwrap();
+
+        // The fresh one only counts once it is old enough
+        assert_eq!(installers_in(&dir, INSTALLER_MIN_AGE), (100, 1));
+        assert_eq!(installers_in(&dir, Duration::ZERO), (200, 2));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
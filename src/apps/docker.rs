@@ -9,6 +9,7 @@
 use super::{OptimizationAction, OptimizationResult};
 use serde::{Deserialize, Serialize};
 use std::process::Command;
+use crate::core::text::truncate_padded;
 
 /// Docker container info
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -426,11 +427,11 @@ impl DockerManager {
                 _ => "❓ Unknown",
             };
 
-            let image = truncate(&container.image, 8);
+            let image = truncate_padded(&container.image, 8);
 
             println!(
                 "│ {:20} │ {:>7.0} MB │ {:>6.1}%  │ {:8} │ {:8} │",
-                truncate(&container.name, 20),
+                truncate_padded(&container.name, 20),
                 container.memory_mb,
                 container.cpu_percent,
                 status,
@@ -497,10 +498,3 @@ fn parse_memory_string(s: &str) -> f64 {
     }
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        format!("{:width$}", s, width = max)
-    } else {
-        format!("{}...", &s[..max.saturating_sub(3)])
-    }
-}
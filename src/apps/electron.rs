@@ -13,11 +13,41 @@
 //! - 1Password
 //! - Postman
 //! - And many more...
+//!
+//! Apps started with a debugging port (`--remote-debugging-port`, or
+//! `--inspect-extensions` for the VS Code extension host) can also be looked
+//! into: [`ElectronManager::heap_snapshot`] reads the JS heap of every
+//! renderer window and extension host over the DevTools protocol, and samples
+//! new allocations to tell which extension or script they came from.
+//!
+//! [`ElectronManager::restart`] closes an app the way the user would, waits
+//! for it to exit and starts it again with the same arguments and working
+//! directory, so it reopens its workspace. Only apps listed in
+//! `electron_restart_apps` in `config.toml` are restarted, and nothing is
+//! ever force-killed.
 
-use super::{AppCategory, AppInfo, OptimizationAction, OptimizationResult};
+use super::devtools::{self, Session, Target};
+use super::vscode::{self, ExtensionBlame, VsCodeProcess};
+use super::{process_memory_mb, AppCategory, AppInfo, OptimizationAction};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::collections::HashMap;
-use sysinfo::{System, ProcessesToUpdate, Pid};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sysinfo::{System, ProcessRefreshKind, ProcessesToUpdate, Pid, UpdateKind};
+use crate::core::text::truncate_padded;
+
+/// File name of the last heap snapshot inside the config directory
+pub const HEAP_SNAPSHOT_FILE: &str = "electron_heap.json";
+
+/// Bytes between allocation samples; coarse enough to be cheap
+const HEAP_SAMPLING_INTERVAL: u64 = 32 * 1024;
+
+/// Target types whose heap is read
+const HEAP_TARGET_KINDS: &[&str] = &["page", "node", "worker", "shared_worker", "service_worker"];
+
+/// Owners listed per target
+const MAX_HEAP_OWNERS: usize = 5;
 
 /// Known Electron app patterns
 #[derive(Debug, Clone)]
@@ -241,6 +271,23 @@ impl ElectronAppInfo {
     }
 }
 
+impl From<&ElectronAppInfo> for AppInfo {
+    /// Idle state is left unset; see [`super::idle::IdleTracker::classify`]
+    fn from(app: &ElectronAppInfo) -> Self {
+        AppInfo {
+            name: app.display_name.clone(),
+            category: app.category,
+            process_count: app.process_count,
+            total_memory_mb: app.total_memory_mb,
+            total_cpu_percent: app.total_cpu_percent,
+            main_pid: app.main_pid,
+            pids: app.pids.clone(),
+            is_idle: false,
+            idle_duration_secs: 0,
+        }
+    }
+}
+
 /// Electron app manager
 pub struct ElectronManager {
     system: System,
@@ -293,7 +340,7 @@ impl ElectronManager {
                 let matches = pattern.patterns.iter().any(|p| name.contains(p));
 
                 if matches {
-                    let memory_mb = process.memory() as f64 / (1024.0 * 1024.0);
+                    let memory_mb = process_memory_mb(process);
                     let cpu_percent = process.cpu_usage();
 
                     app_info.total_memory_mb += memory_mb;
@@ -403,7 +450,7 @@ impl ElectronManager {
 
             println!(
                 "│ {:20} │ {:>7.0} MB │ {:>6.0} MB │ {:>8.0}% │ {:8} │",
-                truncate(&app.display_name, 20),
+                truncate_padded(&app.display_name, 20),
                 app.total_memory_mb,
                 app.baseline_memory_mb,
                 app.memory_overhead_percent,
@@ -435,16 +482,696 @@ impl ElectronManager {
     }
 }
 
+impl ElectronManager {
+    /// Debugging ports of the detected apps, by app name
+    pub fn debugging_ports(&mut self) -> Vec<(String, u32, u16)> {
+        let pids: Vec<Pid> = self.apps.values().flat_map(|a| a.pids.iter().map(|&p| Pid::from_u32(p))).collect();
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&pids),
+            false,
+            ProcessRefreshKind::nothing().with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+
+        let mut ports: Vec<(String, u32, u16)> = Vec::new();
+        for app in self.apps.values() {
+            for &pid in &app.pids {
+                let Some(process) = self.system.process(Pid::from_u32(pid)) else {
+                    continue;
+                };
+                let args: Vec<String> = process.cmd().iter().map(|a| a.to_string_lossy().into_owned()).collect();
+                if let Some(port) = devtools::port_from_args(&args) {
+                    if !ports.iter().any(|(_, _, p)| *p == port) {
+                        ports.push((app.name.clone(), pid, port));
+                    }
+                }
+            }
+        }
+        ports
+    }
+
+    /// Read the JS heap of every reachable renderer and extension host
+    ///
+    /// New allocations are sampled for `window` to attribute them to
+    /// extensions or scripts. Apps without a debugging port are skipped.
+    pub fn heap_snapshot(&mut self, window: Duration) -> HeapSnapshot {
+        let mut sampling: Vec<(TargetHeap, Session)> = Vec::new();
+        for (app, pid, port) in self.debugging_ports() {
+            for target in devtools::list_targets(port) {
+                if !HEAP_TARGET_KINDS.contains(&target.kind.as_str()) {
+                    continue;
+                }
+                match start_sampling(&target) {
+                    Ok(session) => sampling.push((TargetHeap::new(&app, pid, &target), session)),
+                    Err(e) => tracing::debug!("Skipping {} target '{}': {}", app, target.title, e),
+                }
+            }
+        }
+
+        if !sampling.is_empty() {
+            std::thread::sleep(window);
+        }
+        let targets = sampling
+            .into_iter()
+            .filter_map(|(mut heap, mut session)| match finish_sampling(&mut session, &mut heap) {
+                Ok(()) => Some(heap),
+                Err(e) => {
+                    tracing::debug!("Lost {} target '{}': {}", heap.app, heap.label, e);
+                    None
+                }
+            })
+            .collect();
+        HeapSnapshot { timestamp_ms: chrono::Utc::now().timestamp_millis() as u64, targets }
+    }
+
+    /// Which extensions VS Code's memory goes to; `None` when it is not running
+    ///
+    /// With `heap`, extension hosts started with `--inspect-extensions` are
+    /// sampled for that long to split their own memory between extensions.
+    pub fn vscode_blame(&mut self, heap: Option<Duration>) -> Option<ExtensionBlame> {
+        let roots = self.apps.get("vscode")?.pids.clone();
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            false,
+            ProcessRefreshKind::nothing()
+                .with_memory()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+        let processes: Vec<VsCodeProcess> = self
+            .system
+            .processes()
+            .iter()
+            .map(|(pid, process)| VsCodeProcess {
+                pid: pid.as_u32(),
+                parent: process.parent().map(|p| p.as_u32()),
+                name: process.name().to_string_lossy().into_owned(),
+                rss_mb: process_memory_mb(process),
+                command: process
+                    .exe()
+                    .map(|exe| exe.to_string_lossy().into_owned())
+                    .into_iter()
+                    .chain(process.cmd().iter().map(|a| a.to_string_lossy().into_owned()))
+                    .collect(),
+            })
+            .collect();
+
+        let mut blame = ExtensionBlame::from_processes(&roots, &processes);
+        if let Some(window) = heap {
+            blame.add_heap(&self.heap_snapshot(window).targets);
+        }
+        Some(blame)
+    }
+
+    /// Running apps without a debugging port, which `heap_snapshot` cannot see
+    pub fn apps_without_port(&mut self) -> Vec<String> {
+        let with_port: Vec<String> = self.debugging_ports().into_iter().map(|(app, _, _)| app).collect();
+        let mut apps: Vec<String> = self
+            .apps
+            .values()
+            .filter(|a| !with_port.contains(&a.name))
+            .map(|a| a.display_name.clone())
+            .collect();
+        apps.sort();
+        apps
+    }
+}
+
+fn start_sampling(target: &Target) -> Result<Session, String> {
+    let mut session = Session::connect(target)?;
+    session.call("HeapProfiler.enable", json!({}))?;
+    session.call("HeapProfiler.startSampling", json!({ "samplingInterval": HEAP_SAMPLING_INTERVAL }))?;
+    Ok(session)
+}
+
+fn finish_sampling(session: &mut Session, heap: &mut TargetHeap) -> Result<(), String> {
+    let profile = session.call("HeapProfiler.getSamplingProfile", json!({}))?;
+    let _ = session.call("HeapProfiler.stopSampling", json!({}));
+    let usage = session.call("Runtime.getHeapUsage", json!({}))?;
+    heap.used_mb = usage["usedSize"].as_f64().unwrap_or(0.0) / (1024.0 * 1024.0);
+    heap.total_mb = usage["totalSize"].as_f64().unwrap_or(0.0) / (1024.0 * 1024.0);
+
+    let mut owners: HashMap<String, f64> = HashMap::new();
+    collect_owners(&profile["profile"]["head"], &mut owners);
+    let mut owners: Vec<HeapShare> = owners
+        .into_iter()
+        .filter(|(_, bytes)| *bytes > 0.0)
+        .map(|(owner, bytes)| HeapShare { owner, mb: bytes / (1024.0 * 1024.0) })
+        .collect();
+    owners.sort_by(|a, b| b.mb.total_cmp(&a.mb));
+    owners.truncate(MAX_HEAP_OWNERS);
+    heap.owners = owners;
+    Ok(())
+}
+
+/// Sum sampled bytes per owner over a sampling profile tree
+fn collect_owners(node: &Value, owners: &mut HashMap<String, f64>) {
+    let url = node["callFrame"]["url"].as_str().unwrap_or("");
+    let bytes = node["selfSize"].as_f64().unwrap_or(0.0);
+    if bytes > 0.0 {
+        *owners.entry(heap_owner(url)).or_insert(0.0) += bytes;
+    }
+    if let Some(children) = node["children"].as_array() {
+        for child in children {
+            collect_owners(child, owners);
+        }
+    }
+}
+
+/// Who a script belongs to: an extension id, a web origin, Node or the app
+fn heap_owner(url: &str) -> String {
+    if let Some(id) = vscode::extension_id(url) {
+        return id;
+    }
+    if url.is_empty() {
+        return "(runtime)".to_string();
+    }
+    if url.starts_with("node:") || url.starts_with("internal/") {
+        return "node".to_string();
+    }
+    if let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+        return rest.split('/').next().unwrap_or(rest).to_string();
+    }
+    "app".to_string()
+}
+
+/// Share of sampled allocations attributed to one owner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeapShare {
+    pub owner: String,
+    pub mb: f64,
+}
+
+/// JS heap of one renderer window, worker or extension host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetHeap {
+    pub app: String,
+    /// Process whose debugging port led to the target
+    pub pid: u32,
+    pub target_id: String,
+    pub kind: String,
+    /// Window title, or `extension host`
+    pub label: String,
+    pub used_mb: f64,
+    pub total_mb: f64,
+    /// Live allocations sampled during the window, largest owner first
+    pub owners: Vec<HeapShare>,
+}
+
+impl TargetHeap {
+    fn new(app: &str, pid: u32, target: &Target) -> Self {
+        let label = match target.kind.as_str() {
+            "node" if app == "vscode" => "extension host".to_string(),
+            _ if target.title.is_empty() => target.kind.clone(),
+            _ => target.title.clone(),
+        };
+        Self {
+            app: app.to_string(),
+            pid,
+            target_id: target.id.clone(),
+            kind: target.kind.clone(),
+            label,
+            used_mb: 0.0,
+            total_mb: 0.0,
+            owners: Vec::new(),
+        }
+    }
+
+    /// The owner behind most new allocations and its share, if it dominates
+    pub fn main_owner(&self) -> Option<(&str, f64)> {
+        let total: f64 = self.owners.iter().map(|o| o.mb).sum();
+        let top = self.owners.first()?;
+        let share = top.mb / total;
+        (share >= 0.5).then_some((top.owner.as_str(), share))
+    }
+}
+
+/// Heaps of all reachable targets at one point in time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeapSnapshot {
+    pub timestamp_ms: u64,
+    pub targets: Vec<TargetHeap>,
+}
+
+impl HeapSnapshot {
+    /// The snapshot saved by the previous run, if any
+    pub fn load_previous() -> Option<Self> {
+        let path = crate::features::config_file(HEAP_SNAPSHOT_FILE).ok()?;
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = crate::features::config_file(HEAP_SNAPSHOT_FILE)?;
+        let content = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    /// Targets that grew since `previous`, largest growth first
+    ///
+    /// Targets are matched by process and target id, so a restarted window or
+    /// extension host starts over.
+    pub fn growth_since(&self, previous: &HeapSnapshot) -> Vec<HeapGrowth> {
+        let mut growth: Vec<HeapGrowth> = self
+            .targets
+            .iter()
+            .filter_map(|t| {
+                let before = previous.targets.iter().find(|p| p.pid == t.pid && p.target_id == t.target_id)?;
+                let grew_mb = t.used_mb - before.used_mb;
+                (grew_mb > 0.0).then(|| HeapGrowth {
+                    app: t.app.clone(),
+                    label: t.label.clone(),
+                    grew_mb,
+                    over_secs: self.timestamp_ms.saturating_sub(previous.timestamp_ms) / 1000,
+                    owner: t.main_owner().map(|(owner, share)| (owner.to_string(), share)),
+                })
+            })
+            .collect();
+        growth.sort_by(|a, b| b.grew_mb.total_cmp(&a.grew_mb));
+        growth
+    }
+
+    pub fn print(&self) {
+        if self.targets.is_empty() {
+            println!("No renderer or extension host heaps could be read.");
+            return;
+        }
+        println!("\n🧠 JS Heap by Window / Extension Host\n");
+        println!("┌────────────┬────────────────────────────────┬──────────┬──────────────────────────────────┐");
+        println!("│ App        │ Target                         │ Heap     │ New allocations from             │");
+        println!("├────────────┼────────────────────────────────┼──────────┼──────────────────────────────────┤");
+        let mut targets: Vec<&TargetHeap> = self.targets.iter().collect();
+        targets.sort_by(|a, b| b.used_mb.total_cmp(&a.used_mb));
+        for t in targets {
+            let owners = t
+                .owners
+                .iter()
+                .take(2)
+                .map(|o| format!("{} {:.1} MB", o.owner, o.mb))
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!(
+                "│ {:10} │ {:30} │ {:>5.0} MB │ {:32} │",
+                truncate_padded(&t.app, 10),
+                truncate_padded(&t.label, 30),
+                t.used_mb,
+                truncate_padded(&owners, 32)
+            );
+        }
+        println!("└────────────┴────────────────────────────────┴──────────┴──────────────────────────────────┘");
+    }
+}
+
+/// Heap growth of one target between two snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeapGrowth {
+    pub app: String,
+    pub label: String,
+    pub grew_mb: f64,
+    pub over_secs: u64,
+    /// Owner of most new allocations and its share (0-1)
+    pub owner: Option<(String, f64)>,
+}
+
+impl HeapGrowth {
+    /// e.g. "the eamodio.gitlens extension host grew 600 MB in 3h 10m"
+    pub fn describe(&self) -> String {
+        let over = match self.over_secs {
+            s if s >= 3600 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+            s => format!("{}m", s / 60),
+        };
+        match &self.owner {
+            Some((owner, share)) if self.label == "extension host" => format!(
+                "the {} extension host grew {:.0} MB in {} ({:.0}% of new allocations)",
+                owner, self.grew_mb, over, share * 100.0
+            ),
+            Some((owner, share)) => format!(
+                "{} '{}' grew {:.0} MB in {}, mostly from {} ({:.0}% of new allocations)",
+                self.app, self.label, self.grew_mb, over, owner, share * 100.0
+            ),
+            None => format!("{} '{}' grew {:.0} MB in {}", self.app, self.label, self.grew_mb, over),
+        }
+    }
+}
+
+/// How to start an app again the way it was started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relaunch {
+    /// PID of the main (browser) process
+    pub pid: u32,
+    pub exe: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl Relaunch {
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.exe.display().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|a| if a.contains(' ') { format!("\"{}\"", a) } else { a })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Result of a restart
+#[derive(Debug, Clone)]
+pub struct RestartOutcome {
+    pub display_name: String,
+    /// Memory the app used before it was closed
+    pub before_mb: f64,
+    pub new_pid: u32,
+}
+
+impl ElectronManager {
+    /// Find a detected app by name (`vscode`) or display name
+    pub fn find_app(&self, query: &str) -> Option<&ElectronAppInfo> {
+        self.apps
+            .values()
+            .find(|a| a.name.eq_ignore_ascii_case(query) || a.display_name.eq_ignore_ascii_case(query))
+    }
+
+    /// The main process of a running app and its command line
+    pub fn relaunch_plan(&mut self, name: &str) -> Result<Relaunch, String> {
+        let app = self.find_app(name).ok_or_else(|| format!("{} is not running", name))?.clone();
+        let pids: Vec<Pid> = app.pids.iter().map(|&p| Pid::from_u32(p)).collect();
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&pids),
+            false,
+            ProcessRefreshKind::nothing()
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cwd(UpdateKind::OnlyIfNotSet),
+        );
+
+        let main = pids
+            .iter()
+            .filter_map(|pid| self.system.process(*pid))
+            .find(|p| is_main_process(p.cmd(), p.parent().map(|parent| parent.as_u32()), &app.pids))
+            .ok_or_else(|| format!("Could not find the main process of {}", app.display_name))?;
+        let exe = main
+            .exe()
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("Could not read the executable of {}", app.display_name))?;
+
+        Ok(Relaunch {
+            pid: main.pid().as_u32(),
+            exe,
+            args: main.cmd().iter().skip(1).map(|a| a.to_string_lossy().into_owned()).collect(),
+            cwd: main.cwd().map(PathBuf::from),
+        })
+    }
+
+    /// Close an app gracefully, wait up to `timeout` for it to exit and
+    /// start it again with the same command line
+    ///
+    /// Fails without touching the app unless it is in `allowed`. If the app
+    /// does not exit in time (e.g. it minimized to the tray, or asked about
+    /// unsaved work) it is left running.
+    pub fn restart(&mut self, name: &str, allowed: &[String], timeout: Duration) -> Result<RestartOutcome, String> {
+        let app = self.find_app(name).ok_or_else(|| format!("{} is not running", name))?.clone();
+        check_restart_allowed(&app, allowed)?;
+        let plan = self.relaunch_plan(&app.name)?;
+
+        let closed = request_close(&app, plan.pid);
+        crate::security::audit::record(
+            crate::security::audit::AuditAction::Kill,
+            &app.name,
+            Some(plan.pid),
+            "close for restart",
+            &closed,
+        );
+        closed?;
+        let deadline = Instant::now() + timeout;
+        while self.any_alive(&app.pids) {
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "{} did not exit within {}s and was left running",
+                    app.display_name,
+                    timeout.as_secs()
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+
+        let new_pid = launch(&plan).map_err(|e| format!("{} closed but failed to start again: {}", app.display_name, e))?;
+        tracing::info!("Restarted {} ({} -> {})", app.display_name, plan.pid, new_pid);
+        Ok(RestartOutcome { display_name: app.display_name, before_mb: app.total_memory_mb, new_pid })
+    }
+
+    fn any_alive(&mut self, pids: &[u32]) -> bool {
+        let pids: Vec<Pid> = pids.iter().map(|&p| Pid::from_u32(p)).collect();
+        self.system.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+        pids.iter().any(|pid| self.system.process(*pid).is_some())
+    }
+}
+
+/// Refuse to restart apps the user has not listed in `electron_restart_apps`
+fn check_restart_allowed(app: &ElectronAppInfo, allowed: &[String]) -> Result<(), String> {
+    if allowed.iter().any(|a| a.eq_ignore_ascii_case(&app.name)) {
+        return Ok(());
+    }
+    Err(format!(
+        "{} is not in electron_restart_apps; allow it with `electron allow {}`",
+        app.display_name, app.name
+    ))
+}
+
+/// Helpers carry `--type=renderer|gpu-process|utility`; the main process
+/// has no such flag and its parent is outside the app
+fn is_main_process<S: AsRef<std::ffi::OsStr>>(args: &[S], parent: Option<u32>, app_pids: &[u32]) -> bool {
+    let helper = args.iter().any(|a| a.as_ref().to_string_lossy().starts_with("--type="));
+    let child = parent.is_some_and(|parent| app_pids.contains(&parent));
+    !helper && !child
+}
+
+/// Add or remove an app from `electron_restart_apps` and save the config
+///
+/// Accepts the app's name or display name and returns its name.
+pub fn set_restart_allowed(app: &str, allowed: bool) -> Result<String, String> {
+    let pattern = ELECTRON_APPS
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(app) || p.display_name.eq_ignore_ascii_case(app))
+        .ok_or_else(|| format!("Unknown Electron app '{}'", app))?;
+    // Only the file's own list, so env and `--set` overrides aren't written back
+    let mut apps = crate::core::config::OptimizerConfig::load_file().electron_restart_apps;
+    apps.retain(|a| !a.eq_ignore_ascii_case(pattern.name));
+    if allowed {
+        apps.push(pattern.name.to_string());
+    }
+    let value = serde_json::to_string(&apps).map_err(|e| e.to_string())?;
+    crate::core::config::set_in_file("electron_restart_apps", &value)?;
+    Ok(pattern.name.to_string())
+}
+
+/// Ask every window of the app to close, as clicking X would
+#[cfg(target_os = "windows")]
+fn request_close(app: &ElectronAppInfo, _main_pid: u32) -> Result<(), String> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowThreadProcessId, IsWindowVisible, PostMessageW, WM_CLOSE,
+    };
+
+    struct Search<'a> {
+        pids: &'a [u32],
+        windows: Vec<HWND>,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+        if search.pids.contains(&pid) && IsWindowVisible(hwnd).as_bool() {
+            search.windows.push(hwnd);
+        }
+        BOOL(1)
+    }
+
+    let mut search = Search { pids: &app.pids, windows: Vec::new() };
+    unsafe {
+        let _ = EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize));
+    }
+    if search.windows.is_empty() {
+        return Err(format!("{} has no open windows to close", app.display_name));
+    }
+    for hwnd in search.windows {
+        unsafe {
+            let _ = PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
+    }
+    Ok(())
+}
+
+/// Quit through AppleScript, as Cmd+Q would
+#[cfg(target_os = "macos")]
+fn request_close(app: &ElectronAppInfo, main_pid: u32) -> Result<(), String> {
+    let quit = format!(
+        "tell application \"System Events\" to tell (first process whose unix id is {}) to set appName to name\n\
+         tell application appName to quit",
+        main_pid
+    );
+    let status = std::process::Command::new("osascript")
+        .args(["-e", &quit])
+        .status()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    if !status.success() {
+        return Err(format!("{} refused to quit", app.display_name));
+    }
+    Ok(())
+}
+
+/// SIGTERM the main process; Chromium treats it as a normal quit
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn request_close(app: &ElectronAppInfo, main_pid: u32) -> Result<(), String> {
+    if unsafe { libc::kill(main_pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(format!("Failed to signal {}: {}", app.display_name, std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// Start the app detached from this process
+fn launch(plan: &Relaunch) -> std::io::Result<u32> {
+    let mut command = std::process::Command::new(&plan.exe);
+    command
+        .args(&plan.args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    if let Some(cwd) = plan.cwd.as_ref().filter(|d| d.is_dir()) {
+        command.current_dir(cwd);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn().map(|child| child.id())
+}
+
 impl Default for ElectronManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        format!("{:width$}", s, width = max)
-    } else {
-        format!("{}...", &s[..max - 3])
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heap(target_id: &str, used_mb: f64, owners: &[(&str, f64)]) -> TargetHeap {
+        TargetHeap {
+            app: "vscode".into(),
+            pid: 42,
+            target_id: target_id.into(),
+            kind: "node".into(),
+            label: "extension host".into(),
+            used_mb,
+            total_mb: used_mb * 1.5,
+            owners: owners.iter().map(|&(owner, mb)| HeapShare { owner: owner.into(), mb }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_heap_owner() {
+        assert_eq!(
+            heap_owner("file:///home/me/.vscode/extensions/eamodio.gitlens-14.0.0/dist/gitlens.js"),
+            "eamodio.gitlens"
+        );
+        assert_eq!(
+            heap_owner("c:\\Users\\me\\.vscode\\extensions\\rust-lang.rust-analyzer-0.3.1-win32-x64\\out\\main.js"),
+            "rust-lang.rust-analyzer"
+        );
+        assert_eq!(heap_owner("https://app.slack.com/client/T1/C2"), "app.slack.com");
+        assert_eq!(heap_owner("node:internal/fs/promises"), "node");
+        assert_eq!(heap_owner(""), "(runtime)");
+        assert_eq!(heap_owner("vscode-file://vscode-app/out/vs/workbench.js"), "app");
+    }
+
+    #[test]
+    fn test_growth_is_attributed_to_main_owner() {
+        let before = HeapSnapshot { timestamp_ms: 0, targets: vec![heap("ext", 200.0, &[]), heap("win", 100.0, &[])] };
+        let after = HeapSnapshot {
+            timestamp_ms: 2 * 3600 * 1000,
+            targets: vec![
+                heap("ext", 800.0, &[("eamodio.gitlens", 30.0), ("ms-python.python", 5.0)]),
+                heap("win", 90.0, &[]),
+                heap("new", 500.0, &[]),
+            ],
+        };
+        let growth = after.growth_since(&before);
+        assert_eq!(growth.len(), 1);
+        assert_eq!(growth[0].grew_mb, 600.0);
+        assert!(growth[0].describe().starts_with("the eamodio.gitlens extension host grew 600 MB in 2h 0m"));
+    }
+
+    fn vscode(pids: Vec<u32>) -> ElectronManager {
+        let app = ElectronAppInfo {
+            name: "vscode".into(),
+            display_name: "Visual Studio Code".into(),
+            category: AppCategory::Development,
+            total_memory_mb: 900.0,
+            total_cpu_percent: 0.0,
+            process_count: pids.len(),
+            main_pid: pids.first().copied(),
+            pids,
+            baseline_memory_mb: 300.0,
+            memory_overhead_percent: 200.0,
+            is_running: true,
+        };
+        ElectronManager {
+            system: System::new(),
+            apps: HashMap::from([(app.name.clone(), app)]),
+            last_update: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_restart_needs_opt_in() {
+        let mut manager = vscode(vec![u32::MAX - 1]);
+        let app = manager.find_app("Visual Studio Code").unwrap().clone();
+        assert!(check_restart_allowed(&app, &["VSCode".into()]).is_ok());
+        assert!(check_restart_allowed(&app, &["slack".into()]).unwrap_err().contains("electron allow vscode"));
+
+        // Refused before the app is looked at or closed
+        let err = manager.restart("vscode", &["slack".into()], Duration::from_secs(1)).unwrap_err();
+        assert!(err.contains("not in electron_restart_apps"), "{}", err);
+        let err = manager.restart("discord", &["discord".into()], Duration::from_secs(1)).unwrap_err();
+        assert!(err.contains("not running"), "{}", err);
+    }
+
+    #[test]
+    fn test_main_process_is_not_a_helper() {
+        let pids = [10, 11, 12];
+        assert!(is_main_process(&["/usr/share/code/code", "--new-window"], Some(1), &pids));
+        assert!(!is_main_process(&["/usr/share/code/code", "--type=renderer"], Some(10), &pids));
+        assert!(!is_main_process(&["/usr/share/code/code", "--type=gpu-process"], Some(1), &pids));
+        // Started by the app, e.g. a zygote without a --type flag
+        assert!(!is_main_process(&["/usr/share/code/code"], Some(10), &pids));
+    }
+
+    #[test]
+    fn test_relaunch_command_line() {
+        let plan = Relaunch {
+            pid: 1,
+            exe: PathBuf::from("/opt/My App/app"),
+            args: vec!["--folder-uri".into(), "/home/me/my project".into(), "-n".into()],
+            cwd: None,
+        };
+        assert_eq!(plan.command_line(), r#""/opt/My App/app" --folder-uri "/home/me/my project" -n"#);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relaunch_plan_reads_main_process() {
+        let dir = std::env::temp_dir();
+        let mut child = std::process::Command::new("sleep").arg("30").current_dir(&dir).spawn().unwrap();
+        let plan = vscode(vec![child.id()]).relaunch_plan("vscode");
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let plan = plan.unwrap();
+        assert_eq!(plan.pid, child.id());
+        assert!(plan.exe.is_absolute());
+        assert_eq!(plan.args, vec!["30"]);
+        assert_eq!(plan.cwd.map(|cwd| cwd.canonicalize().unwrap()), Some(dir.canonicalize().unwrap()));
     }
 }
@@ -0,0 +1,456 @@
+//! Idle-app detection from foreground and input telemetry
+//!
+//! An app is idle when none of its windows has been used for a while, it is
+//! not doing background work and it is not playing audio. The pieces:
+//!
+//! - [`ActivityRecorder`] runs in the tray or daemon and stamps the app in
+//!   the foreground every few seconds while the user is at the keyboard,
+//!   keeping the last stamp per app in `app_activity.json`
+//! - [`IdleTracker`] reads that file and combines it with the current
+//!   foreground app, time since the last keyboard/mouse input, per-app CPU
+//!   and the processes playing audio to classify an [`AppInfo`]
+//!
+//! Foreground and input come from `GetForegroundWindow`/`GetLastInputInfo`
+//! on Windows, `lsappinfo` and `HIDIdleTime` on macOS, and `xprop` and
+//! `xprintidle` (or Mutter's idle monitor) on Linux. Wayland sessions have no
+//! generic foreground API, so there only input idle time is used. Audio comes
+//! from WASAPI sessions, `pmset -g assertions` and `pactl list sink-inputs`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use super::AppInfo;
+
+/// File name of the activity record inside the config directory
+pub const ACTIVITY_FILE: &str = "app_activity.json";
+
+/// Default for `app_idle_minutes`
+pub const DEFAULT_IDLE_AFTER_MINUTES: u64 = 30;
+
+/// Apps using more CPU than this are working, not idle
+pub const CPU_FLOOR_PERCENT: f32 = 2.0;
+
+/// How often the recorder checks the foreground app
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the recorder writes the activity file
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The foreground app is only stamped if there was input this recently
+const AWAY_AFTER: Duration = Duration::from_secs(60);
+
+/// Without a save for this long the recorder is assumed not to be running
+const STALE_AFTER: Duration = Duration::from_secs(180);
+
+/// Last time each app was in use, as kept in [`ACTIVITY_FILE`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityState {
+    /// Start of the recorder's current unbroken run; 0 until it has seen a
+    /// foreground app, which never happens where tracking is unsupported
+    pub tracked_since_ms: u64,
+    /// Last save; coverage ends here if the recorder stopped
+    pub updated_ms: u64,
+    /// App key (see [`app_key`]) to last time it was in the foreground and used
+    pub last_foreground_ms: HashMap<String, u64>,
+}
+
+impl ActivityState {
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    /// Whether the recorder was tracking the foreground up to now, so apps
+    /// it never saw there really were not used
+    pub fn is_live(&self, now_ms: u64) -> bool {
+        self.tracked_since_ms > 0 && now_ms.saturating_sub(self.updated_ms) < STALE_AFTER.as_millis() as u64
+    }
+}
+
+/// Background recorder of foreground app use
+pub struct ActivityRecorder {
+    path: PathBuf,
+}
+
+impl ActivityRecorder {
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self { path: crate::features::config_file(ACTIVITY_FILE)? })
+    }
+
+    /// Record on a background thread until the process exits
+    pub fn spawn(self) {
+        std::thread::spawn(move || {
+            let mut state = ActivityState::load(&self.path);
+            if !state.is_live(now_ms()) {
+                state.tracked_since_ms = 0;
+            }
+            let mut system = System::new();
+            let mut last_save: Option<std::time::Instant> = None;
+            loop {
+                let foreground = foreground_pid();
+                if foreground.is_some() && state.tracked_since_ms == 0 {
+                    state.tracked_since_ms = now_ms();
+                }
+                let away = input_idle().is_some_and(|idle| idle >= AWAY_AFTER);
+                // An app frozen from a suggestion thaws once it is used again
+                #[cfg(target_os = "macos")]
+                if let Some(name) = foreground.and_then(crate::macos::process::resume_app_of) {
+                    tracing::info!("Resumed {} after it came to the front", name);
+                }
+                if let Some(key) = foreground.filter(|_| !away).and_then(|pid| pid_key(&mut system, pid)) {
+                    state.last_foreground_ms.insert(key, now_ms());
+                }
+                if last_save.map_or(true, |t| t.elapsed() >= SAVE_INTERVAL) {
+                    state.updated_ms = now_ms();
+                    if let Err(e) = state.save(&self.path) {
+                        tracing::warn!("Failed to save app activity: {}", e);
+                    }
+                    last_save = Some(std::time::Instant::now());
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+}
+
+/// Classifies apps as idle from recorded and current activity
+pub struct IdleTracker {
+    state: ActivityState,
+    idle_after: Duration,
+    now_ms: u64,
+    input_idle_secs: u64,
+    foreground: Option<u32>,
+    audio: HashSet<u32>,
+    system: System,
+}
+
+impl IdleTracker {
+    /// Snapshot of the current activity, with `app_idle_minutes` from the config
+    pub fn load() -> Self {
+        let config = crate::core::config::OptimizerConfig::load_or_default();
+        let state = crate::features::config_file(ACTIVITY_FILE)
+            .map(|path| ActivityState::load(&path))
+            .unwrap_or_default();
+        Self::new(state, Duration::from_secs(config.app_idle_minutes * 60))
+    }
+
+    pub fn new(state: ActivityState, idle_after: Duration) -> Self {
+        Self {
+            state,
+            idle_after,
+            now_ms: now_ms(),
+            input_idle_secs: input_idle().map_or(0, |d| d.as_secs()),
+            foreground: foreground_pid(),
+            audio: audio_pids(),
+            system: System::new(),
+        }
+    }
+
+    /// Seconds since the app was last used; 0 while it is in use, busy or
+    /// playing audio
+    pub fn idle_secs(&mut self, pids: &[u32], cpu_percent: f32) -> u64 {
+        if cpu_percent > CPU_FLOOR_PERCENT || pids.iter().any(|pid| self.audio.contains(pid)) {
+            return 0;
+        }
+        // In front: unused for as long as the user has been away
+        if self.foreground.is_some_and(|pid| pids.contains(&pid)) {
+            return self.input_idle_secs;
+        }
+
+        let pid_list: Vec<Pid> = pids.iter().map(|&p| Pid::from_u32(p)).collect();
+        self.system.refresh_processes(ProcessesToUpdate::Some(&pid_list), true);
+        let last_used = pid_list
+            .iter()
+            .filter_map(|pid| self.system.process(*pid))
+            .filter_map(|p| self.state.last_foreground_ms.get(&app_key(&p.name().to_string_lossy(), p.exe())))
+            .max()
+            .copied();
+        let started_ms = pid_list
+            .iter()
+            .filter_map(|pid| self.system.process(*pid))
+            .map(|p| p.start_time() * 1000)
+            .min()
+            .unwrap_or(0);
+
+        // With the recorder running, an app never seen in front since it (or
+        // the recorder) started has been idle all that time
+        let since = match self.state.is_live(self.now_ms) {
+            true => Some(last_used.unwrap_or(0).max(self.state.tracked_since_ms).max(started_ms)),
+            false => None,
+        };
+        let unused_secs = since.map_or(0, |t| self.now_ms.saturating_sub(t) / 1000);
+        unused_secs.max(self.input_idle_secs)
+    }
+
+    /// Set `is_idle` and `idle_duration_secs` on an app
+    pub fn classify(&mut self, app: &mut AppInfo) {
+        app.idle_duration_secs = self.idle_secs(&app.pids, app.total_cpu_percent);
+        app.is_idle = app.idle_duration_secs >= self.idle_after.as_secs();
+    }
+}
+
+/// Human-readable idle time, e.g. `3 h` or `45 min`
+pub fn format_idle(secs: u64) -> String {
+    match secs {
+        s if s >= 2 * 3600 => format!("{} h", s / 3600),
+        s if s >= 3600 => format!("{} h {} min", s / 3600, s % 3600 / 60),
+        s => format!("{} min", s / 60),
+    }
+}
+
+/// Key an app is recorded under: the `.app` bundle on macOS, otherwise the
+/// executable name without `.exe`, lowercase
+pub fn app_key(process_name: &str, exe: Option<&Path>) -> String {
+    let bundle = exe.and_then(|exe| {
+        exe.ancestors()
+            .find(|p| p.extension().is_some_and(|e| e == "app"))
+            .and_then(|p| p.file_stem())
+    });
+    let name = match bundle {
+        Some(stem) => stem.to_string_lossy().into_owned(),
+        None => process_name.to_string(),
+    };
+    let name = name.to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+fn pid_key(system: &mut System, pid: u32) -> Option<String> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).map(|p| app_key(&p.name().to_string_lossy(), p.exe()))
+}
+
+/// Process owning the window the user is looking at
+#[cfg(target_os = "windows")]
+pub fn foreground_pid() -> Option<u32> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let mut pid = 0u32;
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+    }
+    (pid != 0).then_some(pid)
+}
+
+#[cfg(target_os = "macos")]
+pub fn foreground_pid() -> Option<u32> {
+    let asn = command_output("lsappinfo", &["front"])?;
+    let info = command_output("lsappinfo", &["info", "-only", "pid", asn.trim()])?;
+    // "pid"=1234
+    info.rsplit('=').next()?.trim().parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+pub fn foreground_pid() -> Option<u32> {
+    // _NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007
+    let active = command_output("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window = active.split_whitespace().last().filter(|w| w.starts_with("0x") && *w != "0x0")?;
+    // _NET_WM_PID(CARDINAL) = 1234
+    let pid = command_output("xprop", &["-id", window, "_NET_WM_PID"])?;
+    pid.rsplit('=').next()?.trim().parse().ok()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn foreground_pid() -> Option<u32> {
+    None
+}
+
+/// Time since the last keyboard or mouse input in this session
+#[cfg(target_os = "windows")]
+pub fn input_idle() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        Some(Duration::from_millis(GetTickCount().wrapping_sub(info.dwTime) as u64))
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn input_idle() -> Option<Duration> {
+    // "HIDIdleTime" = 1234567890 (nanoseconds)
+    let out = command_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+    let line = out.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+    let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+#[cfg(target_os = "linux")]
+pub fn input_idle() -> Option<Duration> {
+    if let Some(ms) = command_output("xprintidle", &[]).and_then(|out| out.trim().parse().ok()) {
+        return Some(Duration::from_millis(ms));
+    }
+    // GNOME on Wayland: (uint64 12345,)
+    let out = command_output(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ],
+    )?;
+    let ms = out.split_whitespace().nth(1)?.trim_end_matches([',', ')']);
+    ms.parse().ok().map(Duration::from_millis)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn input_idle() -> Option<Duration> {
+    None
+}
+
+/// Processes with an active audio output stream
+#[cfg(target_os = "windows")]
+pub fn audio_pids() -> HashSet<u32> {
+    use windows::core::{Interface, Result};
+    use windows::Win32::Media::Audio::{
+        eMultimedia, eRender, AudioSessionStateActive, IAudioSessionControl2, IAudioSessionManager2,
+        IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    // Sessions on the default output device that are playing right now
+    unsafe fn active_sessions() -> Result<HashSet<u32>> {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let devices: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = devices.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+        let sessions = manager.GetSessionEnumerator()?;
+
+        let mut pids = HashSet::new();
+        for i in 0..sessions.GetCount()? {
+            let session = sessions.GetSession(i)?;
+            if session.GetState()? != AudioSessionStateActive {
+                continue;
+            }
+            pids.insert(session.cast::<IAudioSessionControl2>()?.GetProcessId()?);
+        }
+        Ok(pids)
+    }
+
+    unsafe { active_sessions() }.unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+pub fn audio_pids() -> HashSet<u32> {
+    // coreaudiod holds an assertion per playing client:
+    //   pid 381(coreaudiod): [...] PreventUserIdleSystemSleep named: "com.apple.audio..."
+    //      Created for PID: 1234.
+    let Some(out) = command_output("pmset", &["-g", "assertions"]) else {
+        return HashSet::new();
+    };
+    let mut pids = HashSet::new();
+    let mut in_audio = false;
+    for line in out.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("pid ") {
+            in_audio = trimmed.contains("(coreaudiod)");
+        } else if let Some(pid) = trimmed.strip_prefix("Created for PID:").filter(|_| in_audio) {
+            if let Ok(pid) = pid.trim().trim_end_matches('.').parse() {
+                pids.insert(pid);
+            }
+        }
+    }
+    pids
+}
+
+#[cfg(target_os = "linux")]
+pub fn audio_pids() -> HashSet<u32> {
+    let Some(out) = command_output("pactl", &["list", "sink-inputs"]) else {
+        return HashSet::new();
+    };
+    parse_sink_inputs(&out)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn audio_pids() -> HashSet<u32> {
+    HashSet::new()
+}
+
+/// PIDs of uncorked streams in `pactl list sink-inputs` output
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_sink_inputs(out: &str) -> HashSet<u32> {
+    let mut pids = HashSet::new();
+    for input in out.split("Sink Input #").skip(1) {
+        if input.lines().any(|l| l.trim() == "Corked: yes") {
+            continue;
+        }
+        let pid = input
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("application.process.id = "))
+            .and_then(|v| v.trim_matches('"').parse().ok());
+        if let Some(pid) = pid {
+            pids.insert(pid);
+        }
+    }
+    pids
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_key() {
+        assert_eq!(app_key("Slack.exe", None), "slack");
+        assert_eq!(app_key("slack", Some(Path::new("/usr/lib/slack/slack"))), "slack");
+        assert_eq!(
+            app_key("Electron", Some(Path::new("/Applications/Visual Studio Code.app/Contents/MacOS/Electron"))),
+            "visual studio code"
+        );
+    }
+
+    #[test]
+    fn test_parse_sink_inputs_skips_paused_streams() {
+        let out = "Sink Input #41\n\tCorked: no\n\tProperties:\n\t\tapplication.process.id = \"1234\"\n\
+                   Sink Input #42\n\tCorked: yes\n\tProperties:\n\t\tapplication.process.id = \"5678\"\n";
+        assert_eq!(parse_sink_inputs(out), HashSet::from([1234]));
+    }
+
+    #[test]
+    fn test_stale_activity_is_not_trusted() {
+        let now = now_ms();
+        let live = ActivityState { tracked_since_ms: now - 3_600_000, updated_ms: now - 10_000, ..Default::default() };
+        let stale = ActivityState { updated_ms: now - 3_600_000, ..live.clone() };
+        let untracked = ActivityState { tracked_since_ms: 0, ..live.clone() };
+        assert!(live.is_live(now));
+        assert!(!stale.is_live(now));
+        assert!(!untracked.is_live(now));
+    }
+}
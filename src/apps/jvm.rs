@@ -0,0 +1,559 @@
+//! JVM-based IDEs and build daemons
+//!
+//! IntelliJ-based IDEs, Android Studio and Gradle or Kotlin daemons keep a
+//! garbage-collected heap sized by `-Xmx`. Trimming their working set only
+//! makes the next collection fault every page back in, so they are left out
+//! of trimming and handled here instead: heap use is read with `jcmd` (or
+//! `jstat`) where a JDK is around, and the advice is a right-sized `-Xmx` or
+//! stopping Gradle daemons left behind by an older Gradle version.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// JVMs smaller than this are not worth a `jcmd` round trip
+pub const HEAP_CHECK_MIN_MB: f64 = 300.0;
+
+/// Heaps from this size up are considered for shrinking
+const SHRINK_MIN_HEAP_MB: f64 = 2048.0;
+/// Heap use under this share of `-Xmx` suggests a smaller heap
+const SHRINK_BELOW: f64 = 0.35;
+/// Heap use over this share of `-Xmx` means the JVM is collecting constantly
+const GROW_ABOVE: f64 = 0.85;
+/// Smaller savings are not worth restarting an IDE for
+const MIN_SAVING_MB: f64 = 1024.0;
+
+/// IntelliJ platform products, by launcher name or platform prefix
+const IDE_PRODUCTS: &[(&str, &str)] = &[
+    ("androidstudio", "Android Studio"),
+    ("studio", "Android Studio"),
+    ("idea", "IntelliJ IDEA"),
+    ("pycharm", "PyCharm"),
+    ("webstorm", "WebStorm"),
+    ("phpstorm", "PhpStorm"),
+    ("clion", "CLion"),
+    ("goland", "GoLand"),
+    ("rider", "Rider"),
+    ("rubymine", "RubyMine"),
+    ("datagrip", "DataGrip"),
+    ("rustrover", "RustRover"),
+];
+
+const GRADLE_DAEMON_MAIN: &str = "org.gradle.launcher.daemon.bootstrap.GradleDaemon";
+const KOTLIN_DAEMON_MAIN: &str = "org.jetbrains.kotlin.daemon.KotlinCompileDaemon";
+const INTELLIJ_MAIN: &str = "com.intellij.idea.Main";
+
+/// What a JVM process is running
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum JvmKind {
+    Ide { product: String },
+    GradleDaemon { version: Option<String> },
+    KotlinDaemon,
+    Other,
+}
+
+impl JvmKind {
+    /// Where the user changes `-Xmx` for this kind of JVM
+    fn heap_setting(&self) -> &'static str {
+        match self {
+            JvmKind::Ide { .. } => "Help > Change Memory Settings",
+            JvmKind::GradleDaemon { .. } => "org.gradle.jvmargs in gradle.properties",
+            JvmKind::KotlinDaemon => "kotlin.daemon.jvmargs in gradle.properties",
+            JvmKind::Other => "its -Xmx option",
+        }
+    }
+
+    /// Smallest heap worth suggesting
+    fn heap_floor_mb(&self) -> f64 {
+        match self {
+            JvmKind::Ide { .. } => 1024.0,
+            _ => 512.0,
+        }
+    }
+}
+
+/// Heap use as the JVM reports it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HeapUsage {
+    pub used_mb: f64,
+    pub committed_mb: f64,
+}
+
+/// A running JVM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JvmProcess {
+    pub pid: u32,
+    pub name: String,
+    pub kind: JvmKind,
+    pub rss_mb: f64,
+    /// From `-Xmx` or `-XX:MaxHeapSize`; `None` when left to the JVM
+    pub max_heap_mb: Option<f64>,
+    /// Filled in by [`JvmManager::read_heaps`] when `jcmd` or `jstat` answered
+    pub heap: Option<HeapUsage>,
+    #[serde(skip)]
+    exe: Option<PathBuf>,
+}
+
+impl JvmProcess {
+    pub fn label(&self) -> String {
+        match &self.kind {
+            JvmKind::Ide { product } => product.clone(),
+            JvmKind::GradleDaemon { version: Some(version) } => format!("Gradle daemon {}", version),
+            JvmKind::GradleDaemon { version: None } => "Gradle daemon".to_string(),
+            JvmKind::KotlinDaemon => "Kotlin daemon".to_string(),
+            JvmKind::Other => self.name.clone(),
+        }
+    }
+}
+
+/// What to do about one JVM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "advice", rename_all = "kebab-case")]
+pub enum JvmAdvice {
+    /// The heap stays mostly empty, so a smaller `-Xmx` stops it growing into RAM
+    ShrinkHeap { pid: u32, label: String, kind: JvmKind, used_mb: f64, committed_mb: f64, max_heap_mb: f64, suggested_mb: u64 },
+    /// The heap is nearly full and the JVM spends its time collecting
+    GrowHeap { pid: u32, label: String, kind: JvmKind, used_mb: f64, max_heap_mb: f64, suggested_mb: u64 },
+    /// A Gradle daemon of an older version than the newest one running
+    StaleDaemon { pid: u32, version: String, newest: String, rss_mb: f64 },
+}
+
+impl JvmAdvice {
+    pub fn pid(&self) -> u32 {
+        match self {
+            JvmAdvice::ShrinkHeap { pid, .. } | JvmAdvice::GrowHeap { pid, .. } | JvmAdvice::StaleDaemon { pid, .. } => *pid,
+        }
+    }
+
+    /// Memory given back by following the advice
+    pub fn estimated_mb(&self) -> f64 {
+        match self {
+            JvmAdvice::ShrinkHeap { committed_mb, suggested_mb, .. } => (committed_mb - *suggested_mb as f64).max(0.0),
+            JvmAdvice::GrowHeap { .. } => 0.0,
+            JvmAdvice::StaleDaemon { rss_mb, .. } => *rss_mb,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            JvmAdvice::ShrinkHeap { label, suggested_mb, .. } => format!("Lower {} heap to {} MB", label, suggested_mb),
+            JvmAdvice::GrowHeap { label, suggested_mb, .. } => format!("Raise {} heap to {} MB", label, suggested_mb),
+            JvmAdvice::StaleDaemon { version, .. } => format!("Stop Gradle {} daemon", version),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            JvmAdvice::ShrinkHeap { label, kind, used_mb, max_heap_mb, suggested_mb, .. } => format!(
+                "{} uses {:.0} MB of its {:.0} MB heap. -Xmx{}m still leaves it room and keeps it from \
+                 growing into memory other apps need ({}).",
+                label, used_mb, max_heap_mb, suggested_mb, kind.heap_setting()
+            ),
+            JvmAdvice::GrowHeap { label, kind, used_mb, max_heap_mb, suggested_mb, .. } => format!(
+                "{} has filled {:.0} MB of its {:.0} MB heap and is mostly collecting garbage. \
+                 Raise it to -Xmx{}m ({}).",
+                label, used_mb, max_heap_mb, suggested_mb, kind.heap_setting()
+            ),
+            JvmAdvice::StaleDaemon { pid, version, newest, rss_mb } => format!(
+                "Gradle {} daemon (PID {}) holds {:.0} MB, but projects now build with Gradle {}. \
+                 Run `gradle --stop` from a Gradle {} project, or end the process.",
+                version, pid, rss_mb, newest, version
+            ),
+        }
+    }
+}
+
+/// Tracks running JVMs and reads their heaps
+pub struct JvmManager {
+    system: System,
+    jvms: Vec<JvmProcess>,
+}
+
+impl Default for JvmManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JvmManager {
+    pub fn new() -> Self {
+        Self { system: System::new(), jvms: Vec::new() }
+    }
+
+    /// Find running JVMs; heap use is left to [`Self::read_heaps`]
+    pub fn refresh(&mut self) {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_memory()
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_exe(UpdateKind::OnlyIfNotSet),
+        );
+
+        let previous: HashMap<u32, HeapUsage> =
+            self.jvms.iter().filter_map(|j| Some((j.pid, j.heap?))).collect();
+        self.jvms.clear();
+        for (pid, process) in self.system.processes().iter().filter(|(_, p)| p.thread_kind().is_none()) {
+            let name = process.name().to_string_lossy().into_owned();
+            if !is_jvm(&name) {
+                continue;
+            }
+            let args: Vec<String> = process.cmd().iter().map(|a| a.to_string_lossy().into_owned()).collect();
+            let pid = pid.as_u32();
+            self.jvms.push(JvmProcess {
+                pid,
+                kind: classify(&name, &args),
+                name,
+                rss_mb: super::process_memory_mb(process),
+                max_heap_mb: max_heap_mb(&args),
+                heap: previous.get(&pid).copied(),
+                exe: process.exe().map(Path::to_path_buf),
+            });
+        }
+        self.jvms.sort_by(|a, b| b.rss_mb.total_cmp(&a.rss_mb));
+    }
+
+    /// Ask `jcmd`, or `jstat` failing that, for the heap of every JVM of at
+    /// least `min_rss_mb`
+    pub fn read_heaps(&mut self, min_rss_mb: f64) {
+        for jvm in self.jvms.iter_mut().filter(|j| j.rss_mb >= min_rss_mb) {
+            let pid = jvm.pid.to_string();
+            let exe = jvm.exe.as_deref();
+            jvm.heap = run_tool("jcmd", exe, &[&pid, "GC.heap_info"])
+                .and_then(|out| parse_heap_info(&out))
+                .or_else(|| run_tool("jstat", exe, &["-gc", &pid]).and_then(|out| parse_jstat_gc(&out)));
+        }
+    }
+
+    pub fn get_jvms(&self) -> &[JvmProcess] {
+        &self.jvms
+    }
+
+    pub fn total_memory_mb(&self) -> f64 {
+        self.jvms.iter().map(|j| j.rss_mb).sum()
+    }
+
+    pub fn advice(&self) -> Vec<JvmAdvice> {
+        advise(&self.jvms)
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n☕ JVM IDEs and Build Daemons\n");
+        if self.jvms.is_empty() {
+            println!("No running JVMs found.");
+            return;
+        }
+
+        println!("{:>7}  {:<28} {:>9} {:>9} {:>9}", "PID", "Process", "Memory", "Heap", "Max heap");
+        for jvm in &self.jvms {
+            let heap = jvm.heap.map_or("-".to_string(), |h| format!("{:.0} MB", h.used_mb));
+            let max = jvm.max_heap_mb.map_or("default".to_string(), |m| format!("{:.0} MB", m));
+            println!(
+                "{:>7}  {:<28} {:>6.0} MB {:>9} {:>9}",
+                jvm.pid,
+                crate::core::text::truncate_padded(&jvm.label(), 28),
+                jvm.rss_mb,
+                heap,
+                max
+            );
+        }
+        if self.jvms.iter().any(|j| j.rss_mb >= HEAP_CHECK_MIN_MB && j.heap.is_none()) {
+            println!("\nHeap use needs jcmd or jstat from a JDK on PATH.");
+        }
+
+        let advice = self.advice();
+        if !advice.is_empty() {
+            println!("\n💡 Advice:");
+            for item in &advice {
+                println!("  • {}", item.describe());
+            }
+        }
+    }
+}
+
+/// Whether a process name is a JVM launcher: `java`, `javaw` or an IntelliJ
+/// platform IDE such as `idea64.exe`
+pub fn is_jvm(name: &str) -> bool {
+    let stem = launcher_stem(name);
+    stem == "java" || stem == "javaw" || ide_product(&stem).is_some()
+}
+
+fn launcher_stem(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let stem = lower.strip_suffix(".exe").unwrap_or(&lower);
+    stem.strip_suffix("64").unwrap_or(stem).to_string()
+}
+
+fn ide_product(stem: &str) -> Option<&'static str> {
+    IDE_PRODUCTS.iter().find(|(key, _)| stem == *key).map(|(_, product)| *product)
+}
+
+/// Tell IDEs and build daemons apart by launcher name and main class
+pub fn classify(name: &str, args: &[String]) -> JvmKind {
+    if args.iter().any(|a| a == GRADLE_DAEMON_MAIN) {
+        return JvmKind::GradleDaemon { version: gradle_version(args) };
+    }
+    if args.iter().any(|a| a == KOTLIN_DAEMON_MAIN) {
+        return JvmKind::KotlinDaemon;
+    }
+    if let Some(product) = ide_product(&launcher_stem(name)) {
+        return JvmKind::Ide { product: product.to_string() };
+    }
+    if args.iter().any(|a| a == INTELLIJ_MAIN) {
+        // Launched through java on Linux; the platform prefix names the product
+        let prefix = args.iter().find_map(|a| a.strip_prefix("-Didea.platform.prefix=")).map(str::to_lowercase);
+        let product = prefix
+            .and_then(|p| IDE_PRODUCTS.iter().find(|(key, _)| p.starts_with(key)).map(|(_, product)| *product))
+            .unwrap_or("IntelliJ IDEA");
+        return JvmKind::Ide { product: product.to_string() };
+    }
+    JvmKind::Other
+}
+
+/// Gradle version from the daemon's classpath, e.g. `gradle-launcher-8.5.jar`
+fn gradle_version(args: &[String]) -> Option<String> {
+    let segments = args.iter().flat_map(|a| a.split([':', ';', '/', '\\']));
+    for segment in segments {
+        for prefix in ["gradle-launcher-", "gradle-daemon-main-"] {
+            if let Some(version) = segment.strip_prefix(prefix).and_then(|s| s.strip_suffix(".jar")) {
+                return Some(version.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// `-Xmx` or `-XX:MaxHeapSize`; the JVM honours the last one given
+pub fn max_heap_mb(args: &[String]) -> Option<f64> {
+    args.iter()
+        .rev()
+        .find_map(|a| a.strip_prefix("-Xmx").or_else(|| a.strip_prefix("-XX:MaxHeapSize=")).and_then(parse_size_mb))
+}
+
+/// JVM size such as `4g`, `512m`, `262144K` or plain bytes, in MB
+fn parse_size_mb(size: &str) -> Option<f64> {
+    let split = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+    let (digits, unit) = size.split_at(split);
+    let n: f64 = digits.parse().ok()?;
+    match unit.to_ascii_lowercase().as_str() {
+        "" => Some(n / (1024.0 * 1024.0)),
+        "k" => Some(n / 1024.0),
+        "m" => Some(n),
+        "g" => Some(n * 1024.0),
+        "t" => Some(n * 1024.0 * 1024.0),
+        _ => None,
+    }
+}
+
+/// Parse `jcmd <pid> GC.heap_info`
+///
+/// G1, Parallel and Serial print `total N K, used N K` per heap or
+/// generation; ZGC prints `used N M, capacity N M`. Metaspace is left out.
+pub fn parse_heap_info(output: &str) -> Option<HeapUsage> {
+    let mut usage = HeapUsage { used_mb: 0.0, committed_mb: 0.0 };
+    let mut found = false;
+    for line in output.lines().map(str::trim) {
+        if line.starts_with("Metaspace") || line.starts_with("class space") {
+            continue;
+        }
+        let committed = heap_field(line, "total ").or_else(|| heap_field(line, "capacity "));
+        if let (Some(committed), Some(used)) = (committed, heap_field(line, "used ")) {
+            usage.committed_mb += committed;
+            usage.used_mb += used;
+            found = true;
+        }
+    }
+    found.then_some(usage)
+}
+
+fn heap_field(line: &str, key: &str) -> Option<f64> {
+    let start = line.find(key)? + key.len();
+    let value = line[start..].split([',', ' ']).next()?;
+    parse_size_mb(value)
+}
+
+/// Parse `jstat -gc <pid>`: survivor, eden and old space, in KB
+pub fn parse_jstat_gc(output: &str) -> Option<HeapUsage> {
+    let mut lines = output.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<&str> = lines.next()?.split_whitespace().collect();
+    let values: Vec<&str> = lines.next()?.split_whitespace().collect();
+    let column = |name: &str| header.iter().position(|h| *h == name).and_then(|i| values.get(i)?.parse::<f64>().ok());
+    let sum = |names: [&str; 4]| names.iter().map(|n| column(n)).sum::<Option<f64>>();
+    Some(HeapUsage {
+        used_mb: sum(["S0U", "S1U", "EU", "OU"])? / 1024.0,
+        committed_mb: sum(["S0C", "S1C", "EC", "OC"])? / 1024.0,
+    })
+}
+
+/// Where `tool` might be: next to the JVM's own launcher, in an IDE's
+/// bundled runtime, then on PATH
+fn run_tool(tool: &str, exe: Option<&Path>, args: &[&str]) -> Option<String> {
+    let file = if cfg!(windows) { format!("{}.exe", tool) } else { tool.to_string() };
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(dir) = exe.and_then(Path::parent) {
+        candidates.push(dir.join(&file));
+        candidates.push(dir.join("..").join("jbr").join("bin").join(&file));
+    }
+    candidates.retain(|p| p.exists());
+    candidates.push(PathBuf::from(&file));
+
+    candidates.iter().find_map(|path| {
+        let output = Command::new(path).args(args).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+/// Round `mb` up to a multiple of 512
+fn round_heap(mb: f64) -> u64 {
+    ((mb / 512.0).ceil() as u64).max(1) * 512
+}
+
+/// Heap sizing and stale-daemon advice for a set of JVMs
+pub fn advise(jvms: &[JvmProcess]) -> Vec<JvmAdvice> {
+    let mut advice = Vec::new();
+    for jvm in jvms {
+        let (Some(heap), Some(max)) = (jvm.heap, jvm.max_heap_mb) else {
+            continue;
+        };
+        if max >= SHRINK_MIN_HEAP_MB && heap.used_mb < max * SHRINK_BELOW {
+            let suggested = round_heap((heap.used_mb * 2.0).max(jvm.kind.heap_floor_mb()));
+            if max - suggested as f64 >= MIN_SAVING_MB {
+                advice.push(JvmAdvice::ShrinkHeap {
+                    pid: jvm.pid,
+                    label: jvm.label(),
+                    kind: jvm.kind.clone(),
+                    used_mb: heap.used_mb,
+                    committed_mb: heap.committed_mb,
+                    max_heap_mb: max,
+                    suggested_mb: suggested,
+                });
+            }
+        } else if heap.used_mb > max * GROW_ABOVE {
+            advice.push(JvmAdvice::GrowHeap {
+                pid: jvm.pid,
+                label: jvm.label(),
+                kind: jvm.kind.clone(),
+                used_mb: heap.used_mb,
+                max_heap_mb: max,
+                suggested_mb: round_heap(max * 1.5),
+            });
+        }
+    }
+
+    let daemons: Vec<(&JvmProcess, &str)> = jvms
+        .iter()
+        .filter_map(|j| match &j.kind {
+            JvmKind::GradleDaemon { version: Some(version) } => Some((j, version.as_str())),
+            _ => None,
+        })
+        .collect();
+    if let Some(newest) = daemons.iter().map(|(_, v)| *v).max_by_key(|v| version_key(v)) {
+        for (jvm, version) in &daemons {
+            if version_key(version) < version_key(newest) {
+                advice.push(JvmAdvice::StaleDaemon {
+                    pid: jvm.pid,
+                    version: version.to_string(),
+                    newest: newest.to_string(),
+                    rss_mb: jvm.rss_mb,
+                });
+            }
+        }
+    }
+    advice
+}
+
+/// `8.10.2` sorts after `8.9`; suffixes such as `-rc-1` are ignored
+fn version_key(version: &str) -> Vec<u32> {
+    version.split(['.', '-']).map_while(|part| part.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn jvm(pid: u32, kind: JvmKind, rss_mb: f64, max_heap_mb: f64, used_mb: f64) -> JvmProcess {
+        JvmProcess {
+            pid,
+            name: "java".to_string(),
+            kind,
+            rss_mb,
+            max_heap_mb: Some(max_heap_mb),
+            heap: Some(HeapUsage { used_mb, committed_mb: rss_mb * 0.8 }),
+            exe: None,
+        }
+    }
+
+    #[test]
+    fn classifies_ides_and_daemons() {
+        assert!(is_jvm("idea64.exe"));
+        assert!(is_jvm("javaw.exe"));
+        assert!(!is_jvm("ideas"));
+
+        let gradle = args(&[
+            "/usr/lib/jvm/bin/java",
+            "-Xmx2g",
+            "-cp",
+            "/home/me/.gradle/wrapper/dists/gradle-8.5-bin/lib/gradle-launcher-8.5.jar",
+            GRADLE_DAEMON_MAIN,
+            "8.5",
+        ]);
+        assert_eq!(classify("java", &gradle), JvmKind::GradleDaemon { version: Some("8.5".to_string()) });
+        assert_eq!(max_heap_mb(&gradle), Some(2048.0));
+
+        let studio = args(&["java", "-Xmx1024m", "-XX:MaxHeapSize=4g", "-Didea.platform.prefix=AndroidStudio", INTELLIJ_MAIN]);
+        assert_eq!(classify("java", &studio), JvmKind::Ide { product: "Android Studio".to_string() });
+        assert_eq!(max_heap_mb(&studio), Some(4096.0));
+        assert_eq!(classify("pycharm64.exe", &[]), JvmKind::Ide { product: "PyCharm".to_string() });
+        assert_eq!(classify("java", &args(&["java", KOTLIN_DAEMON_MAIN])), JvmKind::KotlinDaemon);
+    }
+
+    #[test]
+    fn parses_jcmd_and_jstat() {
+        let g1 = " garbage-first heap   total 4194304K, used 1048576K [0x0000000700000000, 0x0000000800000000)\n  \
+                  region size 4096K, 120 young (491520K), 3 survivors (12288K)\n \
+                  Metaspace       used 310000K, committed 315000K, reserved 1310720K\n  \
+                  class space    used 40000K, committed 42000K, reserved 1048576K";
+        assert_eq!(parse_heap_info(g1), Some(HeapUsage { used_mb: 1024.0, committed_mb: 4096.0 }));
+
+        let parallel = " PSYoungGen      total 76288K, used 3932K\n  eden space 65536K, 6% used\n \
+                        ParOldGen       total 175104K, used 0K\n  object space 175104K, 0% used";
+        let usage = parse_heap_info(parallel).unwrap();
+        assert_eq!(usage.committed_mb, (76288.0 + 175104.0) / 1024.0);
+
+        let zgc = " ZHeap           used 20M, capacity 64M, max capacity 4096M";
+        assert_eq!(parse_heap_info(zgc), Some(HeapUsage { used_mb: 20.0, committed_mb: 64.0 }));
+        assert_eq!(parse_heap_info("12345:\ncom.sun.tools.attach.AttachNotSupportedException"), None);
+
+        let jstat = " S0C    S1C    S0U    S1U      EC       EU        OC         OU       MC     MU\n\
+                     0.0   4096.0  0.0   4096.0 262144.0 131072.0  782336.0   387072.0  65536.0 60000.0";
+        let usage = parse_jstat_gc(jstat).unwrap();
+        assert_eq!(usage.used_mb, (4096.0 + 131072.0 + 387072.0) / 1024.0);
+        assert_eq!(usage.committed_mb, (4096.0 + 262144.0 + 782336.0) / 1024.0);
+    }
+
+    #[test]
+    fn advises_heap_sizes_and_stale_daemons() {
+        let ide = JvmKind::Ide { product: "IntelliJ IDEA".to_string() };
+        let jvms = vec![
+            jvm(1, ide.clone(), 3000.0, 8192.0, 900.0),
+            jvm(2, ide, 1500.0, 2048.0, 1900.0),
+            jvm(3, JvmKind::GradleDaemon { version: Some("8.10.2".to_string()) }, 1200.0, 2048.0, 1000.0),
+            jvm(4, JvmKind::GradleDaemon { version: Some("8.9".to_string()) }, 700.0, 2048.0, 600.0),
+        ];
+        let advice = advise(&jvms);
+        assert_eq!(advice.len(), 3);
+        assert!(matches!(&advice[0], JvmAdvice::ShrinkHeap { pid: 1, suggested_mb: 2048, .. }));
+        assert_eq!(advice[0].estimated_mb(), 2400.0 - 2048.0);
+        assert!(matches!(&advice[1], JvmAdvice::GrowHeap { pid: 2, suggested_mb: 3072, .. }));
+        assert!(matches!(&advice[2], JvmAdvice::StaleDaemon { pid: 4, .. }));
+        assert!(advice[2].describe().contains("Gradle 8.10.2"));
+    }
+}
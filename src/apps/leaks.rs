@@ -4,25 +4,306 @@
 //! - Tracks memory usage history
 //! - Detects consistent memory growth
 //! - Identifies processes with abnormal memory patterns
+//! - Tracks handle, GDI/USER object and thread counts, since many apps that
+//!   "leak memory" actually leak handles until the system runs out
 //! - Provides recommendations
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use sysinfo::{System, ProcessesToUpdate, Pid};
+use std::collections::{HashMap, HashSet};
+
+use crate::monitor::procs;
+use crate::core::text::truncate_padded;
+
+/// Samples needed before a process is classified
+pub const MIN_SAMPLES: usize = 10;
+
+/// Time the samples must span before a process is classified
+pub const MIN_SPAN_SECS: u64 = 30;
+
+/// Samples kept per process; a rolling window usually drops them sooner
+const MAX_SAMPLES: usize = 1000;
+
+/// Fit quality below which growth is treated as noise
+pub const MIN_R_SQUARED: f64 = 0.7;
+
+/// Samples at which confidence stops growing with the sample count
+const FULL_CONFIDENCE_SAMPLES: f64 = 30.0;
+
+/// Only processes above this size are tracked
+const MIN_TRACKED_MB: f64 = 50.0;
+
+/// Kernel and GUI resources counted per process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// Kernel handles on Windows, open file descriptors on Linux
+    Handles,
+    /// Windows GDI objects (bitmaps, fonts, device contexts)
+    GdiObjects,
+    /// Windows USER objects (windows, menus, hooks)
+    UserObjects,
+    Threads,
+}
+
+/// When a resource count is tracked and how fast it may grow; rates are per hour
+struct ResourceThresholds {
+    /// Processes below the memory floor are still tracked above this count
+    track_above: u32,
+    watch: f64,
+    medium: f64,
+    high: f64,
+    critical: f64,
+}
+
+impl ResourceKind {
+    pub const ALL: [ResourceKind; 4] =
+        [ResourceKind::Handles, ResourceKind::GdiObjects, ResourceKind::UserObjects, ResourceKind::Threads];
+
+    fn thresholds(self) -> ResourceThresholds {
+        match self {
+            ResourceKind::Handles => {
+                ResourceThresholds { track_above: 1000, watch: 20.0, medium: 200.0, high: 1000.0, critical: 5000.0 }
+            }
+            ResourceKind::GdiObjects | ResourceKind::UserObjects => {
+                ResourceThresholds { track_above: 500, watch: 5.0, medium: 50.0, high: 200.0, critical: 1000.0 }
+            }
+            ResourceKind::Threads => {
+                ResourceThresholds { track_above: 100, watch: 2.0, medium: 20.0, high: 100.0, critical: 500.0 }
+            }
+        }
+    }
+
+    /// Per-process limit; Windows refuses to create GDI and USER objects
+    /// past 10,000 by default, and the app usually crashes or stops drawing
+    pub fn quota(self) -> Option<u32> {
+        match self {
+            ResourceKind::GdiObjects | ResourceKind::UserObjects => Some(10_000),
+            ResourceKind::Handles | ResourceKind::Threads => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceKind::Handles => write!(f, "handles"),
+            ResourceKind::GdiObjects => write!(f, "GDI objects"),
+            ResourceKind::UserObjects => write!(f, "USER objects"),
+            ResourceKind::Threads => write!(f, "threads"),
+        }
+    }
+}
+
+/// Resource counts of one process; `None` where the platform or access
+/// rights don't allow reading them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceCounts {
+    pub handles: Option<u32>,
+    pub gdi_objects: Option<u32>,
+    pub user_objects: Option<u32>,
+    pub threads: Option<u32>,
+}
+
+impl ResourceCounts {
+    pub fn get(&self, kind: ResourceKind) -> Option<u32> {
+        match kind {
+            ResourceKind::Handles => self.handles,
+            ResourceKind::GdiObjects => self.gdi_objects,
+            ResourceKind::UserObjects => self.user_objects,
+            ResourceKind::Threads => self.threads,
+        }
+    }
+
+    /// Whether any count is high enough to track a small process for
+    fn is_notable(&self) -> bool {
+        ResourceKind::ALL.iter().any(|&kind| self.get(kind).is_some_and(|count| count > kind.thresholds().track_above))
+    }
+}
+
+/// Resource counts of a process at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceSample {
+    pub timestamp_ms: u64,
+    pub counts: ResourceCounts,
+}
+
+/// A resource count growing like a leak
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLeak {
+    pub kind: ResourceKind,
+    pub start: u32,
+    pub current: u32,
+    pub growth_per_hour: f64,
+    pub r_squared: f64,
+    pub severity: LeakSeverity,
+    /// Per-process limit the count runs into, if there is one
+    pub quota: Option<u32>,
+}
 
 /// Memory sample for a process
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemorySample {
-    pub timestamp: u64,
+    pub timestamp_ms: u64,
     pub memory_mb: f64,
     pub cpu_percent: f32,
 }
 
+/// Least-squares line through a process's memory samples
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GrowthModel {
+    pub slope_mb_per_hour: f64,
+    /// Fitted memory at the first sample
+    pub intercept_mb: f64,
+    /// Share of the variance the line explains (0-1)
+    pub r_squared: f64,
+    pub samples: usize,
+    pub span_secs: u64,
+}
+
+impl GrowthModel {
+    /// Fit memory against time; `None` with fewer than two distinct timestamps
+    pub fn fit(samples: &[MemorySample]) -> Option<Self> {
+        let series: Vec<(u64, f64)> = samples.iter().map(|s| (s.timestamp_ms, s.memory_mb)).collect();
+        Self::fit_series(&series)
+    }
+
+    /// Fit any `(timestamp_ms, value)` series; the slope is then in units per hour
+    pub fn fit_series(series: &[(u64, f64)]) -> Option<Self> {
+        let first = series.first()?;
+        let last = series.last()?;
+        let n = series.len() as f64;
+        let points: Vec<(f64, f64)> =
+            series.iter().map(|&(timestamp_ms, value)| ((timestamp_ms - first.0) as f64 / 3_600_000.0, value)).collect();
+
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        if sxx <= 0.0 {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+        let ss_tot: f64 = points.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+        let ss_res: f64 = points.iter().map(|p| (p.1 - (intercept + slope * p.0)).powi(2)).sum();
+        let r_squared = if ss_tot > 0.0 { (1.0 - ss_res / ss_tot).clamp(0.0, 1.0) } else { 0.0 };
+
+        Some(Self {
+            slope_mb_per_hour: slope,
+            intercept_mb: intercept,
+            r_squared,
+            samples: series.len(),
+            span_secs: (last.0 - first.0) / 1000,
+        })
+    }
+
+    /// R² discounted while there are few samples
+    pub fn confidence(&self) -> f64 {
+        self.r_squared * (self.samples as f64 / FULL_CONFIDENCE_SAMPLES).min(1.0)
+    }
+
+    /// Whether there is enough data to classify
+    pub fn is_conclusive(&self) -> bool {
+        self.samples >= MIN_SAMPLES && self.span_secs >= MIN_SPAN_SECS
+    }
+}
+
+/// How worrying a process's growth is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeakSeverity {
+    /// Not enough data, or no steady growth
+    None,
+    /// Growing steadily, but slowly
+    Watch,
+    /// >10 MB/hour or doubled
+    Medium,
+    /// >50 MB/hour or tripled
+    High,
+    /// >100 MB/hour or six times the starting size
+    Critical,
+}
+
+impl LeakSeverity {
+    /// Classify a fitted model; `growth_percent` is relative to the first sample
+    pub fn classify(model: &GrowthModel, growth_percent: f64) -> Self {
+        let rate = model.slope_mb_per_hour;
+        if !model.is_conclusive() || model.r_squared < MIN_R_SQUARED || rate <= 0.0 {
+            LeakSeverity::None
+        } else if rate > 100.0 || growth_percent > 500.0 {
+            LeakSeverity::Critical
+        } else if rate > 50.0 || growth_percent > 200.0 {
+            LeakSeverity::High
+        } else if rate > 10.0 || growth_percent > 100.0 {
+            LeakSeverity::Medium
+        } else if rate > 1.0 {
+            LeakSeverity::Watch
+        } else {
+            LeakSeverity::None
+        }
+    }
+
+    /// Classify a fitted resource count, with the thresholds for its kind
+    pub fn classify_resource(kind: ResourceKind, model: &GrowthModel, current: u32) -> Self {
+        let limits = kind.thresholds();
+        let rate = model.slope_mb_per_hour;
+        if !model.is_conclusive() || model.r_squared < MIN_R_SQUARED || rate <= 0.0 {
+            return LeakSeverity::None;
+        }
+        let by_rate = if rate > limits.critical {
+            LeakSeverity::Critical
+        } else if rate > limits.high {
+            LeakSeverity::High
+        } else if rate > limits.medium {
+            LeakSeverity::Medium
+        } else if rate > limits.watch {
+            LeakSeverity::Watch
+        } else {
+            LeakSeverity::None
+        };
+        // Steady growth close to a quota ends badly however slow it is
+        match kind.quota() {
+            Some(quota) if by_rate > LeakSeverity::None && current >= quota / 10 * 9 => LeakSeverity::Critical,
+            Some(quota) if by_rate > LeakSeverity::None && current >= quota / 2 => by_rate.max(LeakSeverity::High),
+            _ => by_rate,
+        }
+    }
+
+    /// 0-3 scale where 1 and up is a likely leak
+    pub fn level(&self) -> u8 {
+        match self {
+            LeakSeverity::None | LeakSeverity::Watch => 0,
+            LeakSeverity::Medium => 1,
+            LeakSeverity::High => 2,
+            LeakSeverity::Critical => 3,
+        }
+    }
+
+    pub fn is_leak(&self) -> bool {
+        *self >= LeakSeverity::Medium
+    }
+}
+
+impl std::fmt::Display for LeakSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LeakSeverity::None => write!(f, "none"),
+            LeakSeverity::Watch => write!(f, "watch"),
+            LeakSeverity::Medium => write!(f, "medium"),
+            LeakSeverity::High => write!(f, "high"),
+            LeakSeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
 /// Process memory history
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessHistory {
     pub pid: u32,
     pub name: String,
+    /// Process start time (seconds since the epoch), tells recycled PIDs apart
+    pub start_time: u64,
     pub samples: Vec<MemorySample>,
     pub start_memory_mb: f64,
     pub current_memory_mb: f64,
@@ -30,16 +311,28 @@ pub struct ProcessHistory {
     pub growth_rate_mb_per_hour: f64,
     pub is_likely_leak: bool,
     pub confidence: f64,
+    pub model: Option<GrowthModel>,
+    pub severity: LeakSeverity,
+    /// Earlier processes of the same name that exited while monitored
+    pub restarts: u32,
+    #[serde(default)]
+    pub resources: Vec<ResourceSample>,
 }
 
 impl ProcessHistory {
     /// Create new process history
     pub fn new(pid: u32, name: String, initial_memory_mb: f64) -> Self {
+        Self::started_at(pid, name, 0, initial_memory_mb)
+    }
+
+    /// Create history for a process with a known start time
+    pub fn started_at(pid: u32, name: String, start_time: u64, initial_memory_mb: f64) -> Self {
         Self {
             pid,
             name,
+            start_time,
             samples: vec![MemorySample {
-                timestamp: current_timestamp(),
+                timestamp_ms: current_timestamp_ms(),
                 memory_mb: initial_memory_mb,
                 cpu_percent: 0.0,
             }],
@@ -49,90 +342,98 @@ impl ProcessHistory {
             growth_rate_mb_per_hour: 0.0,
             is_likely_leak: false,
             confidence: 0.0,
+            model: None,
+            severity: LeakSeverity::None,
+            restarts: 0,
+            resources: Vec::new(),
         }
     }
 
     /// Add a memory sample
     pub fn add_sample(&mut self, memory_mb: f64, cpu_percent: f32) {
-        let sample = MemorySample {
-            timestamp: current_timestamp(),
-            memory_mb,
-            cpu_percent,
-        };
+        self.push(MemorySample { timestamp_ms: current_timestamp_ms(), memory_mb, cpu_percent });
+    }
 
+    /// Add a sample with an explicit timestamp
+    pub fn push(&mut self, sample: MemorySample) {
+        self.current_memory_mb = sample.memory_mb;
+        self.peak_memory_mb = self.peak_memory_mb.max(sample.memory_mb);
         self.samples.push(sample);
-        self.current_memory_mb = memory_mb;
-
-        if memory_mb > self.peak_memory_mb {
-            self.peak_memory_mb = memory_mb;
-        }
 
-        // Keep only last 100 samples
-        if self.samples.len() > 100 {
+        if self.samples.len() > MAX_SAMPLES {
             self.samples.remove(0);
         }
 
         self.analyze();
     }
 
-    /// Analyze memory pattern for leaks
-    fn analyze(&mut self) {
-        if self.samples.len() < 5 {
-            return;
-        }
-
-        // Calculate growth rate using linear regression
-        let n = self.samples.len() as f64;
-        let mut sum_x = 0.0;
-        let mut sum_y = 0.0;
-        let mut sum_xy = 0.0;
-        let mut sum_xx = 0.0;
-
-        let base_time = self.samples[0].timestamp;
-
-        for sample in &self.samples {
-            let x = (sample.timestamp - base_time) as f64 / 3600.0; // Hours
-            let y = sample.memory_mb;
+    /// Add resource counts taken now
+    pub fn add_resources(&mut self, counts: ResourceCounts) {
+        self.push_resources(ResourceSample { timestamp_ms: current_timestamp_ms(), counts });
+    }
 
-            sum_x += x;
-            sum_y += y;
-            sum_xy += x * y;
-            sum_xx += x * x;
+    /// Add resource counts with an explicit timestamp
+    pub fn push_resources(&mut self, sample: ResourceSample) {
+        self.resources.push(sample);
+        if self.resources.len() > MAX_SAMPLES {
+            self.resources.remove(0);
         }
+    }
 
-        let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_xx - sum_x * sum_x);
-        self.growth_rate_mb_per_hour = slope;
-
-        // Calculate R-squared for confidence
-        let mean_y = sum_y / n;
-        let mut ss_tot = 0.0;
-        let mut ss_res = 0.0;
-
-        for sample in &self.samples {
-            let x = (sample.timestamp - base_time) as f64 / 3600.0;
-            let y = sample.memory_mb;
-            let predicted = self.samples[0].memory_mb + slope * x;
-
-            ss_tot += (y - mean_y).powi(2);
-            ss_res += (y - predicted).powi(2);
-        }
+    /// Resource counts growing steadily enough to classify, worst first
+    pub fn resource_leaks(&self) -> Vec<ResourceLeak> {
+        let mut leaks: Vec<ResourceLeak> = ResourceKind::ALL
+            .into_iter()
+            .filter_map(|kind| {
+                let series: Vec<(u64, u32)> = self
+                    .resources
+                    .iter()
+                    .filter_map(|s| Some((s.timestamp_ms, s.counts.get(kind)?)))
+                    .collect();
+                let (_, start) = *series.first()?;
+                let (_, current) = *series.last()?;
+                let points: Vec<(u64, f64)> = series.iter().map(|&(t, count)| (t, count as f64)).collect();
+                let model = GrowthModel::fit_series(&points)?;
+                let severity = LeakSeverity::classify_resource(kind, &model, current);
+                (severity > LeakSeverity::None).then_some(ResourceLeak {
+                    kind,
+                    start,
+                    current,
+                    growth_per_hour: model.slope_mb_per_hour,
+                    r_squared: model.r_squared,
+                    severity,
+                    quota: kind.quota(),
+                })
+            })
+            .collect();
+        leaks.sort_by_key(|l| std::cmp::Reverse(l.severity));
+        leaks
+    }
 
-        let r_squared = if ss_tot > 0.0 {
-            1.0 - (ss_res / ss_tot)
-        } else {
-            0.0
+    /// Fit the growth model and classify it
+    fn analyze(&mut self) {
+        self.model = GrowthModel::fit(&self.samples);
+        let Some(model) = self.model else {
+            return;
         };
+        self.growth_rate_mb_per_hour = model.slope_mb_per_hour;
+        self.confidence = model.confidence();
+        self.severity = LeakSeverity::classify(&model, self.growth_percent());
+        self.is_likely_leak = self.severity.is_leak();
+    }
 
-        self.confidence = r_squared.max(0.0).min(1.0);
-
-        // Determine if likely leak
-        // Criteria: consistent growth (high R²), significant rate, not just startup
-        let memory_doubled = self.current_memory_mb > self.start_memory_mb * 2.0;
-        let significant_growth = self.growth_rate_mb_per_hour > 10.0; // >10 MB/hour
-        let consistent = self.confidence > 0.7;
-        let enough_samples = self.samples.len() >= 10;
-
-        self.is_likely_leak = (memory_doubled || significant_growth) && consistent && enough_samples;
+    /// Drop samples taken before `cutoff_ms`, keeping at least the latest
+    pub fn drop_before(&mut self, cutoff_ms: u64) {
+        let stale_resources = self.resources.iter().take_while(|s| s.timestamp_ms < cutoff_ms).count();
+        if stale_resources > 0 {
+            self.resources.drain(..stale_resources.min(self.resources.len() - 1));
+        }
+        let stale = self.samples.iter().take_while(|s| s.timestamp_ms < cutoff_ms).count();
+        if stale == 0 {
+            return;
+        }
+        self.samples.drain(..stale.min(self.samples.len() - 1));
+        self.analyze();
     }
 
     /// Get memory growth percentage
@@ -146,16 +447,34 @@ impl ProcessHistory {
 
     /// Get severity level (0-3)
     pub fn severity(&self) -> u8 {
-        if !self.is_likely_leak {
-            return 0;
-        }
+        self.severity.level()
+    }
 
-        if self.growth_rate_mb_per_hour > 100.0 || self.growth_percent() > 500.0 {
-            3 // Critical
-        } else if self.growth_rate_mb_per_hour > 50.0 || self.growth_percent() > 200.0 {
-            2 // High
-        } else {
-            1 // Medium
+    /// Machine-readable summary
+    pub fn report(&self) -> LeakReport {
+        let model = self.model.unwrap_or(GrowthModel {
+            slope_mb_per_hour: 0.0,
+            intercept_mb: self.current_memory_mb,
+            r_squared: 0.0,
+            samples: self.samples.len(),
+            span_secs: 0,
+        });
+        LeakReport {
+            process_name: self.name.clone(),
+            pid: self.pid,
+            current_memory_mb: self.current_memory_mb,
+            start_memory_mb: self.start_memory_mb,
+            peak_memory_mb: self.peak_memory_mb,
+            growth_rate_mb_per_hour: model.slope_mb_per_hour,
+            growth_percent: self.growth_percent(),
+            r_squared: model.r_squared,
+            confidence: self.confidence,
+            samples: model.samples,
+            span_secs: model.span_secs,
+            severity: self.severity,
+            restarts: self.restarts,
+            recommendation: recommendation(self),
+            resource_leaks: self.resource_leaks(),
         }
     }
 }
@@ -167,32 +486,50 @@ pub struct LeakReport {
     pub pid: u32,
     pub current_memory_mb: f64,
     pub start_memory_mb: f64,
+    pub peak_memory_mb: f64,
     pub growth_rate_mb_per_hour: f64,
     pub growth_percent: f64,
+    pub r_squared: f64,
+    /// R² discounted for small sample counts
     pub confidence: f64,
-    pub severity: u8,
+    pub samples: usize,
+    pub span_secs: u64,
+    pub severity: LeakSeverity,
+    /// Earlier processes of the same name that exited while monitored
+    pub restarts: u32,
     pub recommendation: String,
+    /// Handle, GDI/USER object and thread counts growing like a leak
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resource_leaks: Vec<ResourceLeak>,
+}
+
+impl LeakReport {
+    /// Memory a restart would likely give back
+    pub fn reclaimable_mb(&self) -> f64 {
+        (self.current_memory_mb - self.start_memory_mb).max(0.0)
+    }
 }
 
 /// Memory leak detector
 pub struct LeakDetector {
-    system: System,
     process_history: HashMap<u32, ProcessHistory>,
+    /// Tracked processes that exited, by name
+    exits: HashMap<String, u32>,
     monitoring_duration_secs: u64,
     sample_interval_secs: u64,
+    /// Samples older than this are dropped; `None` keeps up to [`MAX_SAMPLES`]
+    window_secs: Option<u64>,
     last_sample: std::time::Instant,
 }
 
 impl LeakDetector {
     pub fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_processes(ProcessesToUpdate::All, true);
-
         Self {
-            system,
             process_history: HashMap::new(),
+            exits: HashMap::new(),
             monitoring_duration_secs: 0,
             sample_interval_secs: 30,
+            window_secs: None,
             last_sample: std::time::Instant::now(),
         }
     }
@@ -202,35 +539,67 @@ impl LeakDetector {
         self.sample_interval_secs = secs;
     }
 
+    /// Fit growth over a rolling window instead of the whole session
+    pub fn set_window(&mut self, window: std::time::Duration) {
+        self.window_secs = Some(window.as_secs());
+    }
+
     /// Take a sample of all processes
     pub fn sample(&mut self) {
-        self.system.refresh_processes(ProcessesToUpdate::All, true);
+        // Half the interval, so consecutive samples never share a process list
+        let max_age = std::time::Duration::from_secs(self.sample_interval_secs / 2);
+        let processes: Vec<(u32, String, f64, f32, u64)> = procs::with_processes(max_age, |system| {
+            system
+                .processes()
+                .iter()
+                .map(|(pid, p)| {
+                    let memory_mb = p.memory() as f64 / (1024.0 * 1024.0);
+                    (pid.as_u32(), p.name().to_string_lossy().to_string(), memory_mb, p.cpu_usage(), p.start_time())
+                })
+                .collect()
+        });
+        let resources = resource_counts();
 
-        let mut seen_pids = Vec::new();
+        let mut seen_pids = HashSet::new();
 
-        for (pid, process) in self.system.processes() {
-            let pid_u32 = pid.as_u32();
-            let name = process.name().to_string_lossy().to_string();
-            let memory_mb = process.memory() as f64 / (1024.0 * 1024.0);
-            let cpu_percent = process.cpu_usage();
+        for (pid_u32, name, memory_mb, cpu_percent, start_time) in processes {
+            seen_pids.insert(pid_u32);
+            let counts = resources.get(&pid_u32).copied().unwrap_or_default();
 
-            seen_pids.push(pid_u32);
+            // A PID reused by a new process starts a fresh history
+            let recycled = self
+                .process_history
+                .get(&pid_u32)
+                .is_some_and(|h| h.start_time != start_time || h.name != name);
+            if recycled {
+                if let Some(old) = self.process_history.remove(&pid_u32) {
+                    *self.exits.entry(old.name).or_insert(0) += 1;
+                }
+            }
 
             if let Some(history) = self.process_history.get_mut(&pid_u32) {
                 history.add_sample(memory_mb, cpu_percent);
-            } else {
-                // Only track processes using >50MB
-                if memory_mb > 50.0 {
-                    self.process_history.insert(
-                        pid_u32,
-                        ProcessHistory::new(pid_u32, name, memory_mb),
-                    );
+                history.add_resources(counts);
+                if let Some(window) = self.window_secs {
+                    history.drop_before(current_timestamp_ms().saturating_sub(window * 1000));
                 }
+            } else if memory_mb > MIN_TRACKED_MB || counts.is_notable() {
+                let mut history = ProcessHistory::started_at(pid_u32, name, start_time, memory_mb);
+                history.restarts = self.exits.get(&history.name).copied().unwrap_or(0);
+                history.add_resources(counts);
+                self.process_history.insert(pid_u32, history);
             }
         }
 
-        // Remove dead processes
-        self.process_history.retain(|pid, _| seen_pids.contains(pid));
+        // Remove dead processes, remembering them so restarts are recognised
+        let exits = &mut self.exits;
+        self.process_history.retain(|pid, history| {
+            let alive = seen_pids.contains(pid);
+            if !alive {
+                *exits.entry(history.name.clone()).or_insert(0) += 1;
+            }
+            alive
+        });
 
         self.last_sample = std::time::Instant::now();
         self.monitoring_duration_secs += self.sample_interval_secs;
@@ -241,38 +610,32 @@ impl LeakDetector {
         self.last_sample.elapsed().as_secs() >= self.sample_interval_secs
     }
 
-    /// Get processes with likely memory leaks
-    pub fn get_leaks(&self) -> Vec<LeakReport> {
-        let mut leaks: Vec<LeakReport> = self
+    /// Reports for every process with enough data, worst first
+    pub fn reports(&self) -> Vec<LeakReport> {
+        let mut reports: Vec<LeakReport> = self
             .process_history
             .values()
-            .filter(|h| h.is_likely_leak)
-            .map(|h| LeakReport {
-                process_name: h.name.clone(),
-                pid: h.pid,
-                current_memory_mb: h.current_memory_mb,
-                start_memory_mb: h.start_memory_mb,
-                growth_rate_mb_per_hour: h.growth_rate_mb_per_hour,
-                growth_percent: h.growth_percent(),
-                confidence: h.confidence,
-                severity: h.severity(),
-                recommendation: self.get_recommendation(h),
-            })
+            .filter(|h| h.model.is_some_and(|m| m.is_conclusive()))
+            .map(ProcessHistory::report)
             .collect();
+        reports.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then(b.growth_rate_mb_per_hour.total_cmp(&a.growth_rate_mb_per_hour))
+        });
+        reports
+    }
 
-        // Sort by severity (highest first)
-        leaks.sort_by(|a, b| b.severity.cmp(&a.severity));
-
-        leaks
+    /// Get processes with likely memory leaks
+    pub fn get_leaks(&self) -> Vec<LeakReport> {
+        self.reports().into_iter().filter(|r| r.severity.is_leak()).collect()
     }
 
     /// Get all monitored processes sorted by memory growth
     pub fn get_all_monitored(&self) -> Vec<&ProcessHistory> {
         let mut procs: Vec<_> = self.process_history.values().collect();
         procs.sort_by(|a, b| {
-            b.growth_rate_mb_per_hour
-                .partial_cmp(&a.growth_rate_mb_per_hour)
-                .unwrap()
+            b.growth_rate_mb_per_hour.total_cmp(&a.growth_rate_mb_per_hour)
         });
         procs
     }
@@ -286,33 +649,12 @@ impl LeakDetector {
             .collect();
 
         procs.sort_by(|a, b| {
-            b.growth_rate_mb_per_hour
-                .partial_cmp(&a.growth_rate_mb_per_hour)
-                .unwrap()
+            b.growth_rate_mb_per_hour.total_cmp(&a.growth_rate_mb_per_hour)
         });
 
         procs.into_iter().take(count).collect()
     }
 
-    /// Get recommendation for a process
-    fn get_recommendation(&self, history: &ProcessHistory) -> String {
-        match history.severity() {
-            3 => format!(
-                "CRITICAL: {} is growing at {:.0} MB/hour. Restart immediately!",
-                history.name, history.growth_rate_mb_per_hour
-            ),
-            2 => format!(
-                "HIGH: {} has grown {:.0}%. Consider restarting soon.",
-                history.name, history.growth_percent()
-            ),
-            1 => format!(
-                "MEDIUM: {} shows gradual memory growth. Monitor closely.",
-                history.name
-            ),
-            _ => String::from("No action needed"),
-        }
-    }
-
     /// Get monitoring stats
     pub fn stats(&self) -> LeakDetectorStats {
         let total_processes = self.process_history.len();
@@ -342,6 +684,11 @@ impl LeakDetector {
         let stats = self.stats();
         let leaks = self.get_leaks();
         let top_growing = self.get_top_growing(5);
+        let reports = self.reports();
+        let resource_leaks: Vec<(&LeakReport, &ResourceLeak)> = reports
+            .iter()
+            .flat_map(|r| r.resource_leaks.iter().filter(|l| l.severity.is_leak()).map(move |l| (r, l)))
+            .collect();
 
         println!("\n🔍 Memory Leak Detection\n");
         println!(
@@ -359,15 +706,15 @@ impl LeakDetector {
 
             for leak in &leaks {
                 let severity_icon = match leak.severity {
-                    3 => "🔴 Crit",
-                    2 => "🟠 High",
-                    1 => "🟡 Med",
+                    LeakSeverity::Critical => "🔴 Crit",
+                    LeakSeverity::High => "🟠 High",
+                    LeakSeverity::Medium => "🟡 Med",
                     _ => "🟢 Low",
                 };
 
                 println!(
                     "│ {:20} │ {:>7.0} MB │ {:>+7.0} MB │ {:>+7.0}% │ {:8} │",
-                    truncate(&leak.process_name, 20),
+                    truncate_padded(&leak.process_name, 20),
                     leak.current_memory_mb,
                     leak.growth_rate_mb_per_hour,
                     leak.growth_percent,
@@ -379,7 +726,7 @@ impl LeakDetector {
 
             println!("\n💡 Recommendations:");
             for leak in leaks.iter().take(3) {
-                println!("   • {}", leak.recommendation);
+                println!("   • {} (R² {:.2}, {} samples)", leak.recommendation, leak.r_squared, leak.samples);
             }
         } else if !top_growing.is_empty() {
             println!("No confirmed leaks detected, but monitoring these growing processes:\n");
@@ -390,7 +737,7 @@ impl LeakDetector {
             for proc in &top_growing {
                 println!(
                     "│ {:20} │ {:>7.0} MB │ {:>+7.1} MB │ {:>10.0}%  │",
-                    truncate(&proc.name, 20),
+                    truncate_padded(&proc.name, 20),
                     proc.current_memory_mb,
                     proc.growth_rate_mb_per_hour,
                     proc.confidence * 100.0
@@ -398,13 +745,48 @@ impl LeakDetector {
             }
 
             println!("└──────────────────────┴───────────┴───────────┴──────────────┘");
-        } else {
+        } else if resource_leaks.is_empty() {
             println!("✅ No memory leaks or unusual growth patterns detected.");
         }
 
-        println!(
-            "\nTip: Run with longer duration for better detection accuracy."
-        );
+        if !resource_leaks.is_empty() {
+            println!("\n⚠️  HANDLE AND THREAD LEAKS:\n");
+            println!("┌──────────────────────┬──────────────┬──────────┬───────────┬──────────┐");
+            println!("│ Process              │ Resource     │ Current  │ Growth/hr │ Severity │");
+            println!("├──────────────────────┼──────────────┼──────────┼───────────┼──────────┤");
+            for (report, leak) in &resource_leaks {
+                println!(
+                    "│ {:20} │ {:12} │ {:>8} │ {:>+9.0} │ {:8} │",
+                    truncate_padded(&report.process_name, 20),
+                    leak.kind.to_string(),
+                    leak.current,
+                    leak.growth_per_hour,
+                    leak.severity.to_string()
+                );
+            }
+            println!("└──────────────────────┴──────────────┴──────────┴───────────┴──────────┘");
+
+            println!("\n💡 Handle leaks exhaust the system before memory does:");
+            for (report, leak) in resource_leaks.iter().take(3) {
+                let quota = match leak.quota {
+                    Some(quota) => format!(" of its {} limit", quota),
+                    None => String::new(),
+                };
+                println!(
+                    "   • Restart {} (PID {}): {} {}{}, up from {}",
+                    report.process_name, report.pid, leak.current, leak.kind, quota, leak.start
+                );
+            }
+        }
+
+        if stats.sample_count < MIN_SAMPLES {
+            println!(
+                "\nNote: processes are classified after {} samples spanning {}s; take more samples.",
+                MIN_SAMPLES, MIN_SPAN_SECS
+            );
+        } else {
+            println!("\nTip: Run with longer duration for better detection accuracy.");
+        }
     }
 }
 
@@ -424,17 +806,193 @@ pub struct LeakDetectorStats {
     pub sample_count: usize,
 }
 
-fn current_timestamp() -> u64 {
+/// Advice for a process, by severity
+fn recommendation(history: &ProcessHistory) -> String {
+    let restarted = match history.restarts {
+        0 => String::new(),
+        n => format!(" It has restarted {} time(s) while monitored and keeps growing.", n),
+    };
+    match history.severity {
+        LeakSeverity::Critical => format!(
+            "CRITICAL: {} is growing at {:.0} MB/hour. Restart immediately!{}",
+            history.name, history.growth_rate_mb_per_hour, restarted
+        ),
+        LeakSeverity::High => format!(
+            "HIGH: {} has grown {:.0}%. Consider restarting soon.{}",
+            history.name, history.growth_percent(), restarted
+        ),
+        LeakSeverity::Medium => format!(
+            "MEDIUM: {} shows gradual memory growth. Monitor closely.{}",
+            history.name, restarted
+        ),
+        LeakSeverity::Watch => format!(
+            "{} is growing slowly ({:.1} MB/hour); not a leak yet.",
+            history.name, history.growth_rate_mb_per_hour
+        ),
+        LeakSeverity::None => String::from("No action needed"),
+    }
+}
+
+/// Resource counts for every process they can be read for
+#[cfg(target_os = "windows")]
+pub fn resource_counts() -> HashMap<u32, ResourceCounts> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{GetProcessHandleCount, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+    use windows::Win32::UI::WindowsAndMessaging::{GetGuiResources, GR_GDIOBJECTS, GR_USEROBJECTS};
+
+    let mut counts = HashMap::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else {
+            return counts;
+        };
+        let mut entry = PROCESSENTRY32W { dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32, ..Default::default() };
+        let mut more = Process32FirstW(snapshot, &mut entry).is_ok();
+        while more {
+            let pid = entry.th32ProcessID;
+            let mut process_counts = ResourceCounts { threads: Some(entry.cntThreads), ..Default::default() };
+            if let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+                let mut handles = 0u32;
+                process_counts.handles = GetProcessHandleCount(process, &mut handles).is_ok().then_some(handles);
+                process_counts.gdi_objects = Some(GetGuiResources(process, GR_GDIOBJECTS));
+                process_counts.user_objects = Some(GetGuiResources(process, GR_USEROBJECTS));
+                let _ = CloseHandle(process);
+            }
+            counts.insert(pid, process_counts);
+            more = Process32NextW(snapshot, &mut entry).is_ok();
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    counts
+}
+
+/// Open file descriptors and threads; other users' processes need root
+#[cfg(target_os = "linux")]
+pub fn resource_counts() -> HashMap<u32, ResourceCounts> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .map(|pid| {
+            let handles = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok().map(|fds| fds.count() as u32);
+            let threads = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok().and_then(|status| {
+                status.lines().find_map(|line| line.strip_prefix("Threads:")?.trim().parse().ok())
+            });
+            (pid, ResourceCounts { handles, threads, ..Default::default() })
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn resource_counts() -> HashMap<u32, ResourceCounts> {
+    HashMap::new()
+}
+
+fn current_timestamp_ms() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        format!("{:width$}", s, width = max)
-    } else {
-        format!("{}...", &s[..max - 3])
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(points: &[(u64, f64)]) -> ProcessHistory {
+        let mut history = ProcessHistory::new(1, "app".into(), points[0].1);
+        history.samples.clear();
+        for &(secs, memory_mb) in points {
+            history.push(MemorySample { timestamp_ms: secs * 1000, memory_mb, cpu_percent: 0.0 });
+        }
+        history
+    }
+
+    #[test]
+    fn test_fit_recovers_linear_growth() {
+        // 1 MB per minute = 60 MB/hour
+        let points: Vec<(u64, f64)> = (0..20).map(|i| (i * 60, 200.0 + i as f64)).collect();
+        let history = history(&points);
+        let model = history.model.unwrap();
+        assert!((model.slope_mb_per_hour - 60.0).abs() < 1e-6);
+        assert!((model.intercept_mb - 200.0).abs() < 1e-6);
+        assert!(model.r_squared > 0.999);
+        assert_eq!(history.severity, LeakSeverity::High);
+    }
+
+    #[test]
+    fn test_gating_and_noise() {
+        // Steep but too few samples
+        let short: Vec<(u64, f64)> = (0..5).map(|i| (i * 60, 200.0 + 50.0 * i as f64)).collect();
+        assert_eq!(history(&short).severity, LeakSeverity::None);
+
+        // Enough samples, but oscillating rather than growing
+        let noisy: Vec<(u64, f64)> = (0..20).map(|i| (i * 60, if i % 2 == 0 { 200.0 } else { 400.0 })).collect();
+        assert_eq!(history(&noisy).severity, LeakSeverity::None);
+
+        assert!(GrowthModel::fit(&[MemorySample { timestamp_ms: 0, memory_mb: 1.0, cpu_percent: 0.0 }]).is_none());
+    }
+
+    #[test]
+    fn test_drop_before_refits_the_window() {
+        // Flat for an hour, then 2 MB per minute
+        let mut points: Vec<(u64, f64)> = (0..60).map(|i| (i * 60, 300.0)).collect();
+        points.extend((60..90).map(|i| (i * 60, 300.0 + 2.0 * (i - 60) as f64)));
+        let mut history = history(&points);
+        history.drop_before(3600 * 1000);
+        assert_eq!(history.samples.len(), 30);
+        assert!((history.model.unwrap().slope_mb_per_hour - 120.0).abs() < 1e-6);
+
+        history.drop_before(u64::MAX);
+        assert_eq!(history.samples.len(), 1);
+    }
+
+    fn with_resources(points: &[(u64, ResourceCounts)]) -> ProcessHistory {
+        let mut history = ProcessHistory::new(1, "app".into(), 100.0);
+        for &(secs, counts) in points {
+            history.push_resources(ResourceSample { timestamp_ms: secs * 1000, counts });
+        }
+        history
+    }
+
+    #[test]
+    fn test_resource_leaks_use_their_own_thresholds() {
+        // 10 handles and 10 threads a minute: 600/hour is a medium handle
+        // leak but a critical thread leak
+        let points: Vec<(u64, ResourceCounts)> = (0..20)
+            .map(|i| {
+                let counts = ResourceCounts {
+                    handles: Some(2000 + 10 * i as u32),
+                    threads: Some(40 + 10 * i as u32),
+                    ..Default::default()
+                };
+                (i * 60, counts)
+            })
+            .collect();
+        let leaks = with_resources(&points).resource_leaks();
+        assert_eq!(leaks.len(), 2);
+        assert_eq!((leaks[0].kind, leaks[0].severity), (ResourceKind::Threads, LeakSeverity::Critical));
+        assert_eq!((leaks[1].kind, leaks[1].severity), (ResourceKind::Handles, LeakSeverity::Medium));
+        assert_eq!((leaks[1].start, leaks[1].current), (2000, 2190));
+
+        // Flat counts are no leak
+        let flat: Vec<(u64, ResourceCounts)> =
+            (0..20).map(|i| (i * 60, ResourceCounts { handles: Some(5000), ..Default::default() })).collect();
+        assert!(with_resources(&flat).resource_leaks().is_empty());
+    }
+
+    #[test]
+    fn test_gdi_growth_near_the_quota_is_critical() {
+        // 6 GDI objects an hour would only be watched, but not at 9,500
+        let points: Vec<(u64, ResourceCounts)> = (0..20)
+            .map(|i| (i * 600, ResourceCounts { gdi_objects: Some(9500 + i as u32), ..Default::default() }))
+            .collect();
+        let leaks = with_resources(&points).resource_leaks();
+        assert_eq!(leaks[0].kind, ResourceKind::GdiObjects);
+        assert_eq!(leaks[0].severity, LeakSeverity::Critical);
+        assert_eq!(leaks[0].quota, Some(10_000));
     }
 }
@@ -4,20 +4,31 @@
 //! - Browsers (Chrome, Firefox, Safari, Edge, Arc, Brave)
 //! - Electron apps (VS Code, Discord, Slack, Teams, etc.)
 //! - Docker containers
-//! - Development tools
+//! - Virtual machines (Hyper-V, VirtualBox, VMware)
+//! - Development tools, including JVM IDEs and Gradle daemons
 //! - AI/ML workloads
+//! - Caches filling the system drive
 
 pub mod browser;
+pub mod devtools;
+pub mod diskspace;
 pub mod electron;
 pub mod docker;
+pub mod idle;
+pub mod jvm;
 pub mod leaks;
 pub mod suggestions;
+pub mod vms;
+pub mod vscode;
 
-pub use browser::BrowserOptimizer;
+pub use browser::{BrowserOptimizer, TabAdvisor};
 pub use electron::ElectronManager;
 pub use docker::DockerManager;
-pub use leaks::LeakDetector;
-pub use suggestions::SmartSuggestions;
+pub use idle::IdleTracker;
+pub use jvm::JvmManager;
+pub use leaks::{LeakDetector, LeakReport, LeakSeverity};
+pub use suggestions::{SmartSuggestions, SuggestionRisk};
+pub use vms::VmManager;
 
 use serde::{Deserialize, Serialize};
 
@@ -32,10 +43,80 @@ pub enum AppCategory {
     Media,
     System,
     Container,
+    VirtualMachine,
     AI,
     Other,
 }
 
+/// Well-known process names outside the browser and Electron app tables
+const KNOWN_PROCESSES: &[(AppCategory, &[&str])] = &[
+    (AppCategory::Container, &["docker", "containerd", "podman", "colima"]),
+    (AppCategory::VirtualMachine, &["vmmem", "vmwp", "virtualbox", "vboxheadless", "vmware-vmx", "qemu", "prl_vm_app"]),
+    (AppCategory::AI, &["ollama", "llama", "lmstudio", "vllm", "comfyui"]),
+    (AppCategory::Development, &["cargo", "rustc", "rust-analyzer", "node", "python", "java", "msbuild", "devenv", "clang", "gcc"]),
+    (AppCategory::Creative, &["photoshop", "illustrator", "blender", "gimp", "premiere", "resolve"]),
+    (AppCategory::Communication, &["zoom", "skype", "outlook", "thunderbird"]),
+    (AppCategory::Media, &["vlc", "mpv", "obs64", "obs-studio", "itunes", "music"]),
+    (AppCategory::System, &["system", "svchost", "csrss", "lsass", "dwm", "explorer", "kernel_task", "launchd", "windowserver"]),
+];
+
+/// Category of a process, by name: browsers and Electron apps from their
+/// pattern tables, then a few well-known names
+pub fn categorize(process_name: &str) -> AppCategory {
+    let name = process_name.to_lowercase();
+    // The app tables have short patterns like "arc" and "code", so they
+    // have to start the name rather than appear anywhere in it
+    let starts = |pattern: &str| {
+        name.strip_prefix(pattern)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '.', '-', '(']))
+    };
+    if browser::BROWSERS.iter().any(|b| b.main_patterns.iter().chain(b.helper_patterns).any(|p| starts(p))) {
+        return AppCategory::Browser;
+    }
+    if let Some(app) = electron::ELECTRON_APPS.iter().find(|a| a.patterns.iter().any(|p| starts(p))) {
+        return app.category;
+    }
+    KNOWN_PROCESSES
+        .iter()
+        .find(|(_, patterns)| patterns.iter().any(|p| name.contains(p)))
+        .map_or(AppCategory::Other, |(category, _)| *category)
+}
+
+/// Memory of a process in MB, as the OS's own monitor shows it
+///
+/// On macOS that is Activity Monitor's `phys_footprint`: the resident size
+/// counts the shared framework pages mapped into every app, so summing it
+/// over an app's helpers overstates the app several times. Elsewhere it is
+/// the resident size.
+pub fn process_memory_mb(process: &sysinfo::Process) -> f64 {
+    #[cfg(target_os = "macos")]
+    if let Some(footprint) = crate::macos::process::phys_footprint_mb(process.pid().as_u32()) {
+        return footprint;
+    }
+    process.memory() as f64 / (1024.0 * 1024.0)
+}
+
+/// Share of the footprint of `pids` already compressed or swapped out
+///
+/// Freezing or trimming such an app frees little, since the compressor
+/// already holds most of it. `None` where the platform doesn't say.
+pub fn compressed_share(pids: &[u32]) -> Option<f64> {
+    #[cfg(target_os = "macos")]
+    {
+        use crate::macos::process::{compressed_mb, phys_footprint_mb};
+        let (compressed, footprint) = pids.iter().fold((0.0, 0.0), |(c, f), &pid| {
+            (c + compressed_mb(pid).unwrap_or(0.0), f + phys_footprint_mb(pid).unwrap_or(0.0))
+        });
+        (footprint > 0.0).then(|| (compressed / footprint).min(1.0))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = pids;
+        None
+    }
+}
+
 /// Process info with app categorization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppProcess {
@@ -5,28 +5,176 @@
 //! - Considers user patterns and usage
 //! - Provides actionable recommendations
 //! - Learns from system behavior
+//!
+//! Each suggestion has a stable id (`restart-slack`, `pause-redis`) that
+//! `suggest --apply <id>`, the tray and the dashboard use to carry it out
+//! through the existing optimizers. Every attempt is appended to
+//! `suggestion_outcomes.jsonl`; kinds of suggestion that fail or free less
+//! than estimated sink in the ranking.
+//!
+//! When the system drive runs low, large browser and package manager caches
+//! are suggested too: a full drive stops the pagefile or swap from growing.
 
 use super::{
-    browser::BrowserOptimizer,
+    browser::{BrowserOptimizer, TabAdvisor},
+    diskspace,
     docker::DockerManager,
     electron::ElectronManager,
-    leaks::LeakDetector,
-    AppCategory, OptimizationAction,
+    idle::{self, IdleTracker},
+    jvm::{self, JvmAdvice, JvmManager},
+    leaks::{LeakReport, LeakSeverity},
+    vms::VmManager,
+    compressed_share, process_memory_mb, AppCategory, AppInfo, OptimizationAction, OptimizationResult,
 };
+use crate::core::config::OptimizerConfig;
+use crate::core::undo::{self, UndoAction, UndoEntry};
+use crate::features::health::metrics;
 use serde::{Deserialize, Serialize};
-use sysinfo::{System, ProcessesToUpdate};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::System;
+use crate::core::text::truncate_padded;
+
+/// Outcomes of applied suggestions, one JSON object per line
+pub const OUTCOMES_FILE: &str = "suggestion_outcomes.jsonl";
+
+/// How long an Electron app may take to close when restarted from a suggestion
+const RESTART_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Idle apps smaller than this are not worth a suggestion
+const IDLE_MIN_MB: f64 = 300.0;
+
+/// Share of an idle app's memory a working-set trim is expected to return
+const IDLE_TRIM_SHARE: f64 = 0.3;
+
+/// Apps with more of their memory compressed than this get no trim or
+/// suspend suggestion; the compressor got there first
+const MOSTLY_COMPRESSED: f64 = 0.5;
+
+/// Free share of the system drive below which large caches are suggested
+const LOW_DISK_PERCENT: f64 = 15.0;
+
+/// Free share of the system drive below which those suggestions are urgent
+const CRITICAL_DISK_PERCENT: f64 = 5.0;
+
+/// Caches smaller than this are not worth a suggestion
+const CACHE_MIN_MB: f64 = 500.0;
+
+/// Pseudo-count that keeps a kind's first few outcomes from swinging its rank
+const PRIOR_MB: f64 = 500.0;
 
 /// Optimization suggestion
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Suggestion {
+    /// Stable handle for `suggest --apply`, e.g. `restart-slack`
+    pub id: String,
     pub priority: SuggestionPriority,
     pub category: AppCategory,
     pub title: String,
     pub description: String,
     pub action: OptimizationAction,
-    pub estimated_savings_mb: f64,
-    pub app_name: Option<String>,
-    pub pids: Vec<u32>,
+    pub target: SuggestionTarget,
+    pub estimated_mb: f64,
+    pub risk: SuggestionRisk,
+    /// Whether [`SmartSuggestions::apply`] can carry it out on this platform
+    pub actionable: bool,
+}
+
+impl Default for Suggestion {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            priority: SuggestionPriority::Low,
+            category: AppCategory::Other,
+            title: String::new(),
+            description: String::new(),
+            action: OptimizationAction::None,
+            target: SuggestionTarget::System,
+            estimated_mb: 0.0,
+            risk: SuggestionRisk::Low,
+            actionable: false,
+        }
+    }
+}
+
+impl Suggestion {
+    /// Kind of suggestion whose outcomes are pooled for ranking, e.g. `restart/electron`
+    pub fn kind(&self) -> String {
+        format!("{}/{:?}", action_slug(&self.action), self.category).to_lowercase()
+    }
+
+    fn base_id(&self) -> String {
+        let key = match &self.target {
+            SuggestionTarget::App { name, .. }
+            | SuggestionTarget::Container { name }
+            | SuggestionTarget::Vm { name }
+            | SuggestionTarget::Cache { name, .. } => slug(name),
+            SuggestionTarget::System => "system".to_string(),
+        };
+        format!("{}-{}", action_slug(&self.action), key)
+    }
+
+    fn is_actionable(&self) -> bool {
+        use OptimizationAction as A;
+        match (&self.action, &self.target) {
+            (A::TrimMemory | A::SuspendTabs, SuggestionTarget::App { .. }) => cfg!(target_os = "windows"),
+            (A::Suspend, SuggestionTarget::App { .. }) => cfg!(target_os = "macos"),
+            (A::Restart, SuggestionTarget::App { .. }) => self.category == AppCategory::Electron,
+            (A::PauseContainer | A::StopContainer, SuggestionTarget::Container { .. }) => true,
+            (A::TrimMemory, SuggestionTarget::Vm { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+/// What a suggestion acts on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuggestionTarget {
+    App { name: String, pids: Vec<u32> },
+    Container { name: String },
+    Vm { name: String },
+    /// A cache directory on the system drive
+    Cache { name: String, path: PathBuf },
+    System,
+}
+
+/// How disruptive applying a suggestion is
+///
+/// Anything above `Low` can lose state or interrupt work, so the CLI and
+/// dashboard ask for confirmation and the tray does not offer `High`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionRisk {
+    /// Reversible, nothing is closed (trim, pause, balloon)
+    Low,
+    /// The app comes back, but loses unsaved state (restart, save VM)
+    Medium,
+    /// Something is closed or stopped
+    High,
+}
+
+impl SuggestionRisk {
+    fn of(action: &OptimizationAction) -> Self {
+        match action {
+            OptimizationAction::Close | OptimizationAction::StopContainer => SuggestionRisk::High,
+            OptimizationAction::Restart | OptimizationAction::Suspend => SuggestionRisk::Medium,
+            _ => SuggestionRisk::Low,
+        }
+    }
+}
+
+impl std::fmt::Display for SuggestionRisk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuggestionRisk::Low => write!(f, "low"),
+            SuggestionRisk::Medium => write!(f, "medium"),
+            SuggestionRisk::High => write!(f, "high"),
+        }
+    }
 }
 
 /// Suggestion priority level
@@ -62,19 +210,42 @@ pub enum MemoryPressure {
 pub struct SmartSuggestions {
     system: System,
     browser_optimizer: BrowserOptimizer,
+    tab_advisor: TabAdvisor,
     electron_manager: ElectronManager,
     docker_manager: DockerManager,
+    vm_manager: VmManager,
+    jvm_manager: JvmManager,
+    leak_reports: Vec<LeakReport>,
+    idle: Option<IdleTracker>,
     suggestions: Vec<Suggestion>,
+    outcomes: Option<OutcomeLog>,
+    ranker: SuggestionRanker,
 }
 
 impl SmartSuggestions {
     pub fn new() -> Self {
+        let outcomes = OutcomeLog::open_default()
+            .map_err(|e| tracing::warn!("Suggestion outcomes unavailable: {}", e))
+            .ok();
+        let ranker = outcomes
+            .as_ref()
+            .and_then(|log| log.all().ok())
+            .map(|past| SuggestionRanker::from_outcomes(&past))
+            .unwrap_or_default();
+
         Self {
             system: System::new_all(),
             browser_optimizer: BrowserOptimizer::new(),
+            tab_advisor: TabAdvisor::new(),
             electron_manager: ElectronManager::new(),
             docker_manager: DockerManager::new(),
+            vm_manager: VmManager::new(),
+            jvm_manager: JvmManager::new(),
+            leak_reports: Vec::new(),
+            idle: None,
             suggestions: Vec::new(),
+            outcomes,
+            ranker,
         }
     }
 
@@ -82,12 +253,25 @@ impl SmartSuggestions {
     pub fn refresh(&mut self) {
         self.system.refresh_all();
         self.browser_optimizer.refresh();
+        self.tab_advisor.refresh(&self.browser_optimizer);
         self.electron_manager.refresh();
         self.docker_manager.refresh();
+        self.vm_manager.refresh();
+        self.jvm_manager.refresh();
+        self.jvm_manager.read_heaps(jvm::HEAP_CHECK_MIN_MB);
+        self.idle = Some(IdleTracker::load());
 
         self.generate_suggestions();
     }
 
+    /// Use leak reports from a running `LeakDetector` on the next refresh
+    ///
+    /// Leaks need minutes of samples, so the engine does not detect them
+    /// itself; the tray, daemon and dashboard pass in what they have.
+    pub fn set_leak_reports(&mut self, reports: Vec<LeakReport>) {
+        self.leak_reports = reports;
+    }
+
     /// Get current memory pressure level
     pub fn memory_pressure(&self) -> MemoryPressure {
         let total = self.system.total_memory();
@@ -125,19 +309,83 @@ impl SmartSuggestions {
         // Docker suggestions
         self.add_docker_suggestions(pressure);
 
+        // Virtual machine suggestions
+        self.add_vm_suggestions(pressure);
+
+        // JVM heap sizing and stale Gradle daemons
+        self.add_jvm_suggestions(pressure);
+
+        // Leaking processes
+        self.add_leak_suggestions();
+
+        // Caches on a nearly full system drive
+        self.add_disk_suggestions();
+
         // General high-memory process suggestions
         self.add_general_suggestions(pressure);
 
-        // Sort by priority (highest first) then by estimated savings
+        // Stable ids, risk and whether each one can be applied here
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for suggestion in &mut self.suggestions {
+            let base = suggestion.base_id();
+            let n = seen.entry(base.clone()).or_insert(0);
+            *n += 1;
+            suggestion.id = if *n == 1 { base } else { format!("{}-{}", base, n) };
+            suggestion.risk = SuggestionRisk::of(&suggestion.action);
+            suggestion.actionable = suggestion.is_actionable();
+        }
+
+        // Sort by priority (highest first) then by what this kind of
+        // suggestion has actually delivered before
+        let ranker = &self.ranker;
         self.suggestions.sort_by(|a, b| {
             b.priority
                 .cmp(&a.priority)
-                .then(b.estimated_savings_mb.partial_cmp(&a.estimated_savings_mb).unwrap())
+                .then(ranker.expected_mb(b).total_cmp(&ranker.expected_mb(a)))
         });
     }
 
     /// Add browser-related suggestions
     fn add_browser_suggestions(&mut self, pressure: MemoryPressure) {
+        // Concrete idle-tab suspension, ranked by memory × idle time
+        for rec in self.tab_advisor.recommendations(&self.browser_optimizer) {
+            if compressed_share(&rec.pids()).is_some_and(|share| share > MOSTLY_COMPRESSED) {
+                continue;
+            }
+            let priority = if rec.estimated_savings_mb > 1000.0 || pressure == MemoryPressure::Critical {
+                SuggestionPriority::High
+            } else if rec.estimated_savings_mb > 300.0 || pressure == MemoryPressure::High {
+                SuggestionPriority::Medium
+            } else {
+                SuggestionPriority::Low
+            };
+
+            let mut description = format!(
+                "{} renderers idle for {}+ min hold {:.0} MB.",
+                rec.tabs.len(),
+                rec.tabs.iter().map(|t| t.idle_secs).min().unwrap_or(0) / 60,
+                rec.estimated_savings_mb
+            );
+            if !rec.titles.is_empty() {
+                let titles: Vec<&str> = rec.titles.iter().take(5).map(|t| t.title.as_str()).collect();
+                description.push_str(&format!(" Background tabs: {}", titles.join(", ")));
+                if rec.titles.len() > 5 {
+                    description.push_str(&format!(" (+{} more)", rec.titles.len() - 5));
+                }
+            }
+
+            self.suggestions.push(Suggestion {
+                priority,
+                category: AppCategory::Browser,
+                title: rec.summary(),
+                description,
+                action: OptimizationAction::SuspendTabs,
+                estimated_mb: rec.estimated_savings_mb,
+                target: SuggestionTarget::App { name: rec.browser.clone(), pids: rec.pids() },
+                ..Default::default()
+            });
+        }
+
         for browser in self.browser_optimizer.get_browsers() {
             // Tab reduction suggestions
             if browser.estimated_tabs > 20 {
@@ -163,9 +411,9 @@ impl SmartSuggestions {
                     action: OptimizationAction::ReduceTabs {
                         suggested_count: suggested_tabs,
                     },
-                    estimated_savings_mb: estimated_savings,
-                    app_name: Some(browser.name.clone()),
-                    pids: browser.pids.clone(),
+                    estimated_mb: estimated_savings,
+                    target: SuggestionTarget::App { name: browser.name.clone(), pids: browser.pids.clone() },
+                    ..Default::default()
                 });
             }
 
@@ -180,9 +428,44 @@ impl SmartSuggestions {
                         browser.name, browser.total_memory_mb
                     ),
                     action: OptimizationAction::Restart,
-                    estimated_savings_mb: browser.total_memory_mb * 0.5,
-                    app_name: Some(browser.name.clone()),
-                    pids: browser.pids.clone(),
+                    estimated_mb: browser.total_memory_mb * 0.5,
+                    target: SuggestionTarget::App { name: browser.name.clone(), pids: browser.pids.clone() },
+                    ..Default::default()
+                });
+            }
+        }
+
+        if let Some(breakdown) = self.browser_optimizer.safari_breakdown() {
+            let pids = self
+                .browser_optimizer
+                .get_browser("safari")
+                .map(|b| b.pids.clone())
+                .unwrap_or_default();
+
+            for (action, description, estimated_savings_mb) in breakdown.suggestions() {
+                let priority = match pressure {
+                    MemoryPressure::Critical => SuggestionPriority::High,
+                    MemoryPressure::High => SuggestionPriority::Medium,
+                    _ => SuggestionPriority::Low,
+                };
+                let (title, pids) = match action {
+                    OptimizationAction::Close => (
+                        "Close heavy Safari tab".to_string(),
+                        breakdown.heaviest_content.iter().map(|p| p.pid).collect(),
+                    ),
+                    OptimizationAction::ClearCache => ("Empty Safari caches".to_string(), pids.clone()),
+                    _ => ("Relieve Safari memory pressure".to_string(), pids.clone()),
+                };
+
+                self.suggestions.push(Suggestion {
+                    priority,
+                    category: AppCategory::Browser,
+                    title,
+                    description,
+                    action,
+                    estimated_mb: estimated_savings_mb,
+                    target: SuggestionTarget::App { name: "safari".to_string(), pids },
+                    ..Default::default()
                 });
             }
         }
@@ -190,6 +473,7 @@ impl SmartSuggestions {
 
     /// Add Electron app suggestions
     fn add_electron_suggestions(&mut self, pressure: MemoryPressure) {
+        let vscode = self.electron_manager.vscode_blame(None);
         for app in self.electron_manager.get_apps() {
             // Bloated app warning
             if app.is_bloated() {
@@ -216,10 +500,25 @@ impl SmartSuggestions {
                         excess
                     ),
                     action: OptimizationAction::Restart,
-                    estimated_savings_mb: excess,
-                    app_name: Some(app.display_name.clone()),
-                    pids: app.pids.clone(),
+                    estimated_mb: excess,
+                    target: SuggestionTarget::App { name: app.display_name.clone(), pids: app.pids.clone() },
+                    ..Default::default()
                 });
+
+                // A restart only buys time when one extension keeps growing
+                let blame = vscode.as_ref().filter(|_| app.name == "vscode");
+                if let Some((blame, ext)) = blame.and_then(|b| Some((b, b.culprit()?))) {
+                    self.suggestions.push(Suggestion {
+                        priority,
+                        category: AppCategory::Electron,
+                        title: format!("Disable the {} extension", ext.id),
+                        description: blame.recommendation().unwrap_or_default(),
+                        action: OptimizationAction::None,
+                        estimated_mb: ext.total_mb(),
+                        target: SuggestionTarget::App { name: ext.id.clone(), pids: ext.pids.clone() },
+                        ..Default::default()
+                    });
+                }
             }
 
             // Very high memory apps
@@ -233,9 +532,45 @@ impl SmartSuggestions {
                         app.display_name, app.total_memory_mb, app.process_count
                     ),
                     action: OptimizationAction::Close,
-                    estimated_savings_mb: app.total_memory_mb,
-                    app_name: Some(app.display_name.clone()),
-                    pids: app.pids.clone(),
+                    estimated_mb: app.total_memory_mb,
+                    target: SuggestionTarget::App { name: app.display_name.clone(), pids: app.pids.clone() },
+                    ..Default::default()
+                });
+            }
+
+            // Not used for a while, no background work or audio
+            let Some(tracker) = self.idle.as_mut() else { continue };
+            let mut info = AppInfo::from(app);
+            tracker.classify(&mut info);
+            let mostly_compressed = || compressed_share(&app.pids).is_some_and(|share| share > MOSTLY_COMPRESSED);
+            if info.is_idle && info.total_memory_mb >= IDLE_MIN_MB && !mostly_compressed() {
+                let priority = if info.total_memory_mb > 1000.0 || pressure == MemoryPressure::Critical {
+                    SuggestionPriority::Medium
+                } else {
+                    SuggestionPriority::Low
+                };
+                let idle_for = idle::format_idle(info.idle_duration_secs);
+
+                // macOS cannot trim another process, but it can freeze one
+                // and let the compressor take its pages
+                let (verb, action) = if cfg!(target_os = "macos") {
+                    ("Suspend", OptimizationAction::Suspend)
+                } else {
+                    ("Trim", OptimizationAction::TrimMemory)
+                };
+
+                self.suggestions.push(Suggestion {
+                    priority,
+                    category: AppCategory::Electron,
+                    title: format!("{} idle for {}", app.display_name, idle_for),
+                    description: format!(
+                        "{} has not been used for {} and holds {:.0} MB. {} it, or close it if you no longer need it.",
+                        app.display_name, idle_for, info.total_memory_mb, verb
+                    ),
+                    action,
+                    estimated_mb: info.total_memory_mb * IDLE_TRIM_SHARE,
+                    target: SuggestionTarget::App { name: app.display_name.clone(), pids: app.pids.clone() },
+                    ..Default::default()
                 });
             }
         }
@@ -267,9 +602,9 @@ impl SmartSuggestions {
                         container.name, container.memory_mb
                     ),
                     action: OptimizationAction::PauseContainer,
-                    estimated_savings_mb: 0.0, // Pausing doesn't free memory but saves CPU
-                    app_name: Some(container.name.clone()),
-                    pids: Vec::new(),
+                    estimated_mb: 0.0, // Pausing doesn't free memory but saves CPU
+                    target: SuggestionTarget::Container { name: container.name.clone() },
+                    ..Default::default()
                 });
             }
         }
@@ -286,14 +621,162 @@ impl SmartSuggestions {
                         container.name, container.image, container.memory_mb, container.memory_percent
                     ),
                     action: OptimizationAction::StopContainer,
-                    estimated_savings_mb: container.memory_mb,
-                    app_name: Some(container.name.clone()),
-                    pids: Vec::new(),
+                    estimated_mb: container.memory_mb,
+                    target: SuggestionTarget::Container { name: container.name.clone() },
+                    ..Default::default()
                 });
             }
         }
     }
 
+    /// Add virtual machine suggestions
+    fn add_vm_suggestions(&mut self, pressure: MemoryPressure) {
+        for vm in self.vm_manager.get_vms() {
+            let (title, description, estimated_savings_mb) = match vm.get_suggested_action() {
+                OptimizationAction::TrimMemory => (
+                    format!("Balloon VM {}", vm.name),
+                    format!(
+                        "VM '{}' has {:.0} MB assigned but only demands {:.0} MB. Lower its dynamic memory buffer to return the rest.",
+                        vm.name,
+                        vm.memory_mb,
+                        vm.demand_mb.unwrap_or(0.0)
+                    ),
+                    vm.balloonable_mb(),
+                ),
+                OptimizationAction::Suspend => (
+                    format!("Save idle VM {}", vm.name),
+                    format!(
+                        "{} VM '{}' is idle but holding {:.0} MB. Save its state to free it: {}",
+                        vm.hypervisor, vm.name, vm.memory_mb, vm.save_command()
+                    ),
+                    vm.memory_mb,
+                ),
+                _ => continue,
+            };
+
+            let priority = if pressure == MemoryPressure::Critical {
+                SuggestionPriority::High
+            } else if pressure == MemoryPressure::High || estimated_savings_mb > 4096.0 {
+                SuggestionPriority::Medium
+            } else {
+                SuggestionPriority::Low
+            };
+
+            self.suggestions.push(Suggestion {
+                priority,
+                category: AppCategory::VirtualMachine,
+                title,
+                description,
+                action: vm.get_suggested_action(),
+                estimated_mb: estimated_savings_mb,
+                target: SuggestionTarget::Vm { name: vm.name.clone() },
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Add heap sizing and Gradle daemon suggestions
+    ///
+    /// Trimming a JVM only makes its next collection fault the heap back in,
+    /// so heap changes are left to the user as notices.
+    fn add_jvm_suggestions(&mut self, pressure: MemoryPressure) {
+        for advice in self.jvm_manager.advice() {
+            let estimated_mb = advice.estimated_mb();
+            let priority = if matches!(advice, JvmAdvice::GrowHeap { .. }) {
+                SuggestionPriority::Low
+            } else if matches!(pressure, MemoryPressure::High | MemoryPressure::Critical) || estimated_mb > 2048.0 {
+                SuggestionPriority::Medium
+            } else {
+                SuggestionPriority::Low
+            };
+            let (name, action) = match &advice {
+                JvmAdvice::StaleDaemon { version, .. } => (format!("Gradle {} daemon", version), OptimizationAction::Close),
+                JvmAdvice::ShrinkHeap { label, .. } | JvmAdvice::GrowHeap { label, .. } => {
+                    (label.clone(), OptimizationAction::None)
+                }
+            };
+
+            self.suggestions.push(Suggestion {
+                priority,
+                category: AppCategory::Development,
+                title: advice.title(),
+                description: advice.describe(),
+                action,
+                estimated_mb,
+                target: SuggestionTarget::App { name, pids: vec![advice.pid()] },
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Add restart suggestions for likely leaks
+    fn add_leak_suggestions(&mut self) {
+        for leak in self.leak_reports.iter().filter(|l| l.severity.is_leak()) {
+            let priority = match leak.severity {
+                LeakSeverity::Critical => SuggestionPriority::Critical,
+                LeakSeverity::High => SuggestionPriority::High,
+                _ => SuggestionPriority::Medium,
+            };
+
+            self.suggestions.push(Suggestion {
+                priority,
+                category: AppCategory::Other,
+                title: format!("Restart leaking {}", leak.process_name),
+                description: format!(
+                    "'{}' (PID {}) has grown steadily at {:.0} MB/hour for {} min (R² {:.2}) to {:.0} MB.",
+                    leak.process_name,
+                    leak.pid,
+                    leak.growth_rate_mb_per_hour,
+                    leak.span_secs / 60,
+                    leak.r_squared,
+                    leak.current_memory_mb
+                ),
+                action: OptimizationAction::Restart,
+                estimated_mb: leak.reclaimable_mb(),
+                target: SuggestionTarget::App { name: leak.process_name.clone(), pids: vec![leak.pid] },
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Add cache suggestions when the system drive is nearly full
+    ///
+    /// Clearing a cache frees disk rather than memory, so these carry no
+    /// MB estimate and are left to the user; the description says how.
+    fn add_disk_suggestions(&mut self) {
+        let Some(disk) = metrics::system_disk() else { return };
+        let free_percent = disk.free_percent();
+        if free_percent >= LOW_DISK_PERCENT {
+            return;
+        }
+        let priority = if free_percent < CRITICAL_DISK_PERCENT {
+            SuggestionPriority::High
+        } else {
+            SuggestionPriority::Medium
+        };
+
+        for cache in diskspace::scan_caches().into_iter().filter(|c| c.size_mb() >= CACHE_MIN_MB) {
+            self.suggestions.push(Suggestion {
+                priority,
+                category: AppCategory::System,
+                title: format!("Clear {} ({:.1} GB)", cache.name, cache.size_mb() / 1024.0),
+                description: format!(
+                    "{} has only {:.1} GB free ({:.0}%), so the pagefile or swap can't grow. {} takes {:.1} GB in {}. {}.",
+                    disk.mount,
+                    disk.free_mb / 1024.0,
+                    free_percent,
+                    cache.name,
+                    cache.size_mb() / 1024.0,
+                    cache.path.display(),
+                    cache.hint
+                ),
+                action: OptimizationAction::ClearCache,
+                target: SuggestionTarget::Cache { name: cache.name, path: cache.path },
+                ..Default::default()
+            });
+        }
+    }
+
     /// Add general process suggestions
     fn add_general_suggestions(&mut self, pressure: MemoryPressure) {
         // Find high-memory processes not covered by specific optimizers
@@ -311,15 +794,37 @@ impl SmartSuggestions {
             .flat_map(|a| a.pids.clone())
             .collect();
 
+        let vm_pids: std::collections::HashSet<u32> = self
+            .vm_manager
+            .get_vms()
+            .iter()
+            .filter_map(|vm| vm.pid)
+            .collect();
+
+        let jvm_pids: std::collections::HashSet<u32> =
+            self.jvm_manager.get_jvms().iter().map(|j| j.pid).collect();
+
+        let leak_pids: std::collections::HashSet<u32> = self
+            .leak_reports
+            .iter()
+            .filter(|l| l.severity.is_leak())
+            .map(|l| l.pid)
+            .collect();
+
         for (pid, process) in self.system.processes() {
             let pid_u32 = pid.as_u32();
 
             // Skip if already covered
-            if browser_pids.contains(&pid_u32) || electron_pids.contains(&pid_u32) {
+            if browser_pids.contains(&pid_u32)
+                || electron_pids.contains(&pid_u32)
+                || vm_pids.contains(&pid_u32)
+                || jvm_pids.contains(&pid_u32)
+                || leak_pids.contains(&pid_u32)
+            {
                 continue;
             }
 
-            let memory_mb = process.memory() as f64 / (1024.0 * 1024.0);
+            let memory_mb = process_memory_mb(process);
             let name = process.name().to_string_lossy().to_string();
 
             // High memory processes
@@ -339,9 +844,9 @@ impl SmartSuggestions {
                         name, pid_u32, memory_mb
                     ),
                     action: OptimizationAction::Close,
-                    estimated_savings_mb: memory_mb,
-                    app_name: Some(name),
-                    pids: vec![pid_u32],
+                    estimated_mb: memory_mb,
+                    target: SuggestionTarget::App { name, pids: vec![pid_u32] },
+                    ..Default::default()
                 });
             }
         }
@@ -361,9 +866,9 @@ impl SmartSuggestions {
                     available, total, (used / total) * 100.0
                 ),
                 action: OptimizationAction::None,
-                estimated_savings_mb: 0.0,
-                app_name: None,
-                pids: Vec::new(),
+                estimated_mb: 0.0,
+                target: SuggestionTarget::System,
+                ..Default::default()
             });
         }
     }
@@ -396,7 +901,107 @@ impl SmartSuggestions {
 
     /// Get total potential savings
     pub fn total_potential_savings(&self) -> f64 {
-        self.suggestions.iter().map(|s| s.estimated_savings_mb).sum()
+        self.suggestions.iter().map(|s| s.estimated_mb).sum()
+    }
+
+    /// Find a current suggestion by id
+    pub fn get(&self, id: &str) -> Option<&Suggestion> {
+        self.suggestions.iter().find(|s| s.id == id)
+    }
+
+    /// Carry out a suggestion from the last refresh and record the outcome
+    ///
+    /// Failures are recorded too, so the ranking learns from both. Risk is
+    /// not checked here; callers confirm anything above `Low` first.
+    pub fn apply(&mut self, id: &str) -> Result<SuggestionOutcome, String> {
+        let suggestion = self
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("No suggestion '{}'; run `suggest` to list current ids", id))?;
+        if !suggestion.actionable {
+            return Err(format!("'{}' has to be done by hand: {}", suggestion.title, suggestion.description));
+        }
+
+        let before_mb = self.available_mb();
+        let result = self.execute(&suggestion);
+        let measured_mb = (self.available_mb() - before_mb).max(0.0);
+
+        let (success, message, freed_mb) = match result {
+            Ok((message, reported_mb)) => (true, message, reported_mb.unwrap_or(measured_mb)),
+            Err(e) => (false, e, 0.0),
+        };
+        let outcome = SuggestionOutcome {
+            timestamp_ms: now_ms(),
+            id: suggestion.id.clone(),
+            kind: suggestion.kind(),
+            estimated_mb: suggestion.estimated_mb,
+            freed_mb,
+            success,
+            message,
+        };
+        if let Some(log) = &self.outcomes {
+            if let Err(e) = log.append(&outcome) {
+                tracing::warn!("Failed to record suggestion outcome: {}", e);
+            }
+        }
+        self.ranker.record(&outcome);
+
+        match outcome.success {
+            true => Ok(outcome),
+            false => Err(outcome.message),
+        }
+    }
+
+    /// Run a suggestion through the optimizer that owns its target
+    ///
+    /// Returns a message and the MB freed if the optimizer reports it.
+    fn execute(&mut self, suggestion: &Suggestion) -> Result<(String, Option<f64>), String> {
+        use OptimizationAction as A;
+        match (&suggestion.action, &suggestion.target) {
+            (A::TrimMemory | A::SuspendTabs, SuggestionTarget::App { pids, .. }) => {
+                let freed_mb = trim_pids(pids)?;
+                Ok((format!("trimmed {} processes, freed {:.0} MB", pids.len(), freed_mb), Some(freed_mb)))
+            }
+            (A::Suspend, SuggestionTarget::App { name, pids }) => {
+                let stopped = suspend_app(name, pids)?;
+                undo::record(UndoEntry::now(
+                    format!("Suspended {}", name),
+                    vec![UndoAction::Resume { name: name.clone(), pids: pids.clone() }],
+                ));
+                Ok((format!("suspended {} processes of {} until it is brought to the front", stopped, name), None))
+            }
+            (A::Restart, SuggestionTarget::App { name, .. }) if suggestion.category == AppCategory::Electron => {
+                let allowed = OptimizerConfig::load_or_default().electron_restart_apps;
+                let outcome = self.electron_manager.restart(name, &allowed, RESTART_TIMEOUT)?;
+                Ok((
+                    format!("restarted {} (was {:.0} MB, new PID {})", outcome.display_name, outcome.before_mb, outcome.new_pid),
+                    None,
+                ))
+            }
+            (A::PauseContainer, SuggestionTarget::Container { name }) => {
+                let result = into_result(self.docker_manager.pause_container(name))?;
+                undo::record(UndoEntry::now(
+                    format!("Paused container {}", name),
+                    vec![UndoAction::UnpauseContainer { name: name.clone() }],
+                ));
+                Ok(result)
+            }
+            (A::StopContainer, SuggestionTarget::Container { name }) => {
+                let result = into_result(self.docker_manager.stop_container(name))?;
+                undo::record(UndoEntry::now(
+                    format!("Stopped container {}", name),
+                    vec![UndoAction::StartContainer { name: name.clone() }],
+                ));
+                Ok(result)
+            }
+            (A::TrimMemory, SuggestionTarget::Vm { name }) => into_result(self.vm_manager.balloon(name)),
+            _ => Err(format!("'{}' has to be done by hand", suggestion.title)),
+        }
+    }
+
+    fn available_mb(&mut self) -> f64 {
+        self.system.refresh_memory();
+        self.system.available_memory() as f64 / (1024.0 * 1024.0)
     }
 
     /// Print suggestions summary
@@ -426,9 +1031,9 @@ impl SmartSuggestions {
             return;
         }
 
-        println!("┌──────────┬──────────────────────────────┬───────────────┐");
-        println!("│ Priority │ Suggestion                   │ Est. Savings  │");
-        println!("├──────────┼──────────────────────────────┼───────────────┤");
+        println!("┌──────────┬──────────────────────┬──────────────────────────────┬───────────────┬────────┐");
+        println!("│ Priority │ ID                   │ Suggestion                   │ Est. Savings  │ Risk   │");
+        println!("├──────────┼──────────────────────┼──────────────────────────────┼───────────────┼────────┤");
 
         for suggestion in self.suggestions.iter().take(10) {
             let priority_icon = match suggestion.priority {
@@ -438,21 +1043,29 @@ impl SmartSuggestions {
                 SuggestionPriority::Low => "🟢 Low",
             };
 
-            let savings = if suggestion.estimated_savings_mb > 0.0 {
-                format!("{:.0} MB", suggestion.estimated_savings_mb)
+            let savings = if suggestion.estimated_mb > 0.0 {
+                format!("{:.0} MB", suggestion.estimated_mb)
             } else {
                 "-".to_string()
             };
 
+            let risk = if suggestion.actionable {
+                suggestion.risk.to_string()
+            } else {
+                "manual".to_string()
+            };
+
             println!(
-                "│ {:8} │ {:28} │ {:>13} │",
+                "│ {:8} │ {} │ {} │ {:>13} │ {:6} │",
                 priority_icon,
-                truncate(&suggestion.title, 28),
-                savings
+                truncate_padded(&suggestion.id, 20),
+                truncate_padded(&suggestion.title, 28),
+                savings,
+                risk
             );
         }
 
-        println!("└──────────┴──────────────────────────────┴───────────────┘");
+        println!("└──────────┴──────────────────────┴──────────────────────────────┴───────────────┴────────┘");
 
         let total_savings = self.total_potential_savings();
         if total_savings > 0.0 {
@@ -467,6 +1080,10 @@ impl SmartSuggestions {
         if self.suggestions.len() > 5 {
             println!("   ... and {} more suggestions", self.suggestions.len() - 5);
         }
+
+        if self.suggestions.iter().any(|s| s.actionable) {
+            println!("\nApply one with `suggest --apply <id>`; medium and high risk ones also need --confirm.");
+        }
     }
 }
 
@@ -476,10 +1093,240 @@ impl Default for SmartSuggestions {
     }
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        format!("{:width$}", s, width = max)
-    } else {
-        format!("{}...", &s[..max - 3])
+/// Result of applying a suggestion, as stored in [`OUTCOMES_FILE`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestionOutcome {
+    pub timestamp_ms: u64,
+    pub id: String,
+    /// [`Suggestion::kind`]
+    pub kind: String,
+    pub estimated_mb: f64,
+    /// Reported by the optimizer, or the rise in available memory
+    pub freed_mb: f64,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Append-only log of [`SuggestionOutcome`]s
+pub struct OutcomeLog {
+    path: PathBuf,
+}
+
+impl OutcomeLog {
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self::open(crate::features::config_file(OUTCOMES_FILE)?))
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn append(&self, outcome: &SuggestionOutcome) -> Result<(), String> {
+        let line = serde_json::to_string(outcome).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Every recorded outcome, skipping malformed lines
+    pub fn all(&self) -> Result<Vec<SuggestionOutcome>, String> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+}
+
+/// Per-kind record of how suggestions worked out
+#[derive(Debug, Clone, Copy, Default)]
+struct KindStats {
+    attempts: u32,
+    successes: u32,
+    estimated_mb: f64,
+    freed_mb: f64,
+}
+
+/// Weighs estimates by what each kind of suggestion delivered before
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionRanker {
+    stats: HashMap<String, KindStats>,
+}
+
+impl SuggestionRanker {
+    pub fn from_outcomes(outcomes: &[SuggestionOutcome]) -> Self {
+        let mut ranker = Self::default();
+        for outcome in outcomes {
+            ranker.record(outcome);
+        }
+        ranker
+    }
+
+    pub fn record(&mut self, outcome: &SuggestionOutcome) {
+        let stats = self.stats.entry(outcome.kind.clone()).or_default();
+        stats.attempts += 1;
+        if outcome.success {
+            stats.successes += 1;
+            stats.estimated_mb += outcome.estimated_mb;
+            stats.freed_mb += outcome.freed_mb;
+        }
+    }
+
+    /// Multiplier for a kind's estimates; 1.0 until it has been tried
+    ///
+    /// The smoothed success rate times the freed/estimated ratio, clamped so
+    /// one lucky or unlucky run cannot bury or promote a kind for good.
+    pub fn factor(&self, kind: &str) -> f64 {
+        let Some(stats) = self.stats.get(kind) else {
+            return 1.0;
+        };
+        let success_rate = (stats.successes as f64 + 1.0) / (stats.attempts as f64 + 1.0);
+        let accuracy = (stats.freed_mb + PRIOR_MB) / (stats.estimated_mb + PRIOR_MB);
+        (success_rate * accuracy).clamp(0.1, 2.0)
+    }
+
+    /// Estimated savings adjusted by [`Self::factor`]
+    pub fn expected_mb(&self, suggestion: &Suggestion) -> f64 {
+        suggestion.estimated_mb * self.factor(&suggestion.kind())
+    }
+}
+
+fn into_result(result: OptimizationResult) -> Result<(String, Option<f64>), String> {
+    match result.success {
+        true => Ok((result.message, Some(result.memory_freed_mb))),
+        false => Err(result.message),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn trim_pids(pids: &[u32]) -> Result<f64, String> {
+    use crate::core::history::{self, HistoryRecord, OptimizationTrigger};
+    use crate::windows::memory::WindowsMemoryOptimizer;
+
+    let start = std::time::Instant::now();
+    let mut freed_bytes = 0u64;
+    let mut trimmed = 0;
+    for &pid in pids {
+        if let Ok(bytes) = WindowsMemoryOptimizer::trim_process_working_set(pid) {
+            freed_bytes += bytes;
+            trimmed += 1;
+        }
+    }
+    if trimmed == 0 {
+        return Err("None of the processes could be trimmed".into());
+    }
+    let freed_mb = freed_bytes as f64 / (1024.0 * 1024.0);
+    history::record(HistoryRecord::now(
+        OptimizationTrigger::Manual,
+        false,
+        freed_mb,
+        trimmed,
+        start.elapsed().as_millis() as u64,
+    ));
+    Ok(freed_mb)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn trim_pids(_pids: &[u32]) -> Result<f64, String> {
+    Err("Trimming single processes is only supported on Windows".into())
+}
+
+#[cfg(target_os = "macos")]
+fn suspend_app(name: &str, pids: &[u32]) -> Result<usize, String> {
+    crate::macos::process::suspend_app(name, pids)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn suspend_app(_name: &str, _pids: &[u32]) -> Result<usize, String> {
+    Err("Suspending apps is only supported on macOS".into())
+}
+
+/// Short verb used in suggestion ids
+fn action_slug(action: &OptimizationAction) -> &'static str {
+    match action {
+        OptimizationAction::Close => "close",
+        OptimizationAction::Suspend => "suspend",
+        OptimizationAction::TrimMemory => "trim",
+        OptimizationAction::Restart => "restart",
+        OptimizationAction::ReduceTabs { .. } => "reduce-tabs",
+        OptimizationAction::SuspendTabs => "suspend-tabs",
+        OptimizationAction::StopContainer => "stop",
+        OptimizationAction::PauseContainer => "pause",
+        OptimizationAction::ClearCache => "clear-cache",
+        OptimizationAction::None => "notice",
+    }
+}
+
+/// Lowercase, with runs of anything but letters and digits turned into `-`
+fn slug(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if c.is_alphanumeric() {
+            out.extend(c.to_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(kind: &str, estimated_mb: f64, freed_mb: f64, success: bool) -> SuggestionOutcome {
+        SuggestionOutcome {
+            timestamp_ms: 0,
+            id: String::new(),
+            kind: kind.to_string(),
+            estimated_mb,
+            freed_mb,
+            success,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_ids_are_slugs_of_action_and_target() {
+        let suggestion = Suggestion {
+            action: OptimizationAction::Restart,
+            category: AppCategory::Electron,
+            target: SuggestionTarget::App { name: "Microsoft Teams".into(), pids: vec![1] },
+            ..Default::default()
+        };
+        assert_eq!(suggestion.base_id(), "restart-microsoft-teams");
+        assert_eq!(suggestion.kind(), "restart/electron");
+        assert!(suggestion.is_actionable());
+        assert_eq!(SuggestionRisk::of(&suggestion.action), SuggestionRisk::Medium);
+    }
+
+    #[test]
+    fn test_ranker_learns_from_outcomes() {
+        let ranker = SuggestionRanker::from_outcomes(&[
+            outcome("restart/electron", 800.0, 900.0, true),
+            outcome("restart/electron", 800.0, 750.0, true),
+            outcome("pause/container", 1000.0, 0.0, false),
+            outcome("pause/container", 1000.0, 0.0, false),
+            outcome("trim/browser", 1000.0, 100.0, true),
+        ]);
+        assert_eq!(ranker.factor("close/other"), 1.0);
+        assert!(ranker.factor("restart/electron") > 0.9);
+        assert!(ranker.factor("pause/container") < 0.5);
+        assert!(ranker.factor("trim/browser") < ranker.factor("restart/electron"));
     }
 }
@@ -0,0 +1,462 @@
+//! Virtual machine memory visibility
+//!
+//! Guest RAM is usually the single biggest consumer on a host running VMs:
+//! - Hyper-V (`vmwp.exe` worker per VM, names and assigned memory via `Get-VM`)
+//! - VirtualBox (`VBoxHeadless` / `VirtualBoxVM`, name from `--comment`)
+//! - VMware (`vmware-vmx`, name from the `.vmx` path)
+//!
+//! Safe actions only: Hyper-V dynamic-memory VMs can be asked to balloon down
+//! to their demand, and idle VMs get "save state" suggestions.
+
+use super::{OptimizationAction, OptimizationResult};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use sysinfo::{ProcessesToUpdate, System};
+use crate::core::text::truncate_padded;
+
+/// VMs below this CPU usage count as idle
+const VM_IDLE_CPU_PERCENT: f32 = 2.0;
+
+/// Hyper-V memory buffer (%) requested when ballooning a VM down
+const BALLOON_BUFFER_PERCENT: u32 = 5;
+
+/// Hypervisor hosting a VM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hypervisor {
+    HyperV,
+    VirtualBox,
+    VMware,
+}
+
+impl Hypervisor {
+    /// Identify a VM host process by (lowercase) name
+    fn from_process_name(name: &str) -> Option<Self> {
+        let name = name.trim_end_matches(".exe");
+        match name {
+            "vmwp" => Some(Hypervisor::HyperV),
+            "vboxheadless" | "virtualboxvm" => Some(Hypervisor::VirtualBox),
+            "vmware-vmx" => Some(Hypervisor::VMware),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Hypervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hypervisor::HyperV => write!(f, "Hyper-V"),
+            Hypervisor::VirtualBox => write!(f, "VirtualBox"),
+            Hypervisor::VMware => write!(f, "VMware"),
+        }
+    }
+}
+
+/// A running virtual machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VmInfo {
+    pub name: String,
+    pub hypervisor: Hypervisor,
+    /// Host process backing the VM, when it could be matched
+    pub pid: Option<u32>,
+    /// Host memory attributed to the VM
+    pub memory_mb: f64,
+    /// Memory the guest is asking for (Hyper-V dynamic memory only)
+    pub demand_mb: Option<f64>,
+    pub dynamic_memory: bool,
+    pub cpu_percent: f32,
+    pub is_idle: bool,
+    /// `.vmx` path (VMware) used for save-state commands
+    pub config_path: Option<String>,
+}
+
+impl VmInfo {
+    /// Memory above guest demand that ballooning could return to the host
+    pub fn balloonable_mb(&self) -> f64 {
+        match (self.dynamic_memory, self.demand_mb) {
+            (true, Some(demand)) => (self.memory_mb - demand * (1.0 + BALLOON_BUFFER_PERCENT as f64 / 100.0)).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Command that saves the VM's state, freeing all of its memory
+    pub fn save_command(&self) -> String {
+        match self.hypervisor {
+            Hypervisor::HyperV => format!("Save-VM -Name \"{}\"", self.name),
+            Hypervisor::VirtualBox => format!("VBoxManage controlvm \"{}\" savestate", self.name),
+            Hypervisor::VMware => format!(
+                "vmrun suspend \"{}\"",
+                self.config_path.as_deref().unwrap_or(&self.name)
+            ),
+        }
+    }
+
+    /// Get suggested action for this VM
+    pub fn get_suggested_action(&self) -> OptimizationAction {
+        if self.balloonable_mb() > 512.0 {
+            OptimizationAction::TrimMemory
+        } else if self.is_idle && self.memory_mb > 1024.0 {
+            OptimizationAction::Suspend
+        } else {
+            OptimizationAction::None
+        }
+    }
+}
+
+/// Hyper-V VM as reported by `Get-VM`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HyperVVm {
+    id: String,
+    name: String,
+    memory_assigned: u64,
+    memory_demand: u64,
+    dynamic_memory_enabled: bool,
+}
+
+/// Virtual machine manager
+pub struct VmManager {
+    system: System,
+    vms: Vec<VmInfo>,
+    last_update: std::time::Instant,
+}
+
+impl VmManager {
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::All, true);
+
+        Self {
+            system,
+            vms: Vec::new(),
+            last_update: std::time::Instant::now(),
+        }
+    }
+
+    /// Refresh VM data
+    pub fn refresh(&mut self) {
+        self.system.refresh_processes(ProcessesToUpdate::All, true);
+        self.vms.clear();
+
+        let mut hyperv_workers = Vec::new();
+
+        for (pid, process) in self.system.processes() {
+            let name = process.name().to_string_lossy().to_lowercase();
+            let Some(hypervisor) = Hypervisor::from_process_name(&name) else {
+                continue;
+            };
+
+            let args: Vec<String> = process
+                .cmd()
+                .iter()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect();
+            let memory_mb = process.memory() as f64 / (1024.0 * 1024.0);
+            let cpu_percent = process.cpu_usage();
+
+            match hypervisor {
+                Hypervisor::HyperV => {
+                    // Guest RAM is not in vmwp's working set; Get-VM fills it in
+                    hyperv_workers.push((pid.as_u32(), args, memory_mb, cpu_percent));
+                }
+                Hypervisor::VirtualBox => {
+                    let vm_name = arg_value(&args, "--comment")
+                        .or_else(|| arg_value(&args, "--startvm"))
+                        .unwrap_or_else(|| format!("VirtualBox VM {}", pid));
+                    self.vms.push(VmInfo {
+                        name: vm_name,
+                        hypervisor,
+                        pid: Some(pid.as_u32()),
+                        memory_mb,
+                        demand_mb: None,
+                        dynamic_memory: false,
+                        cpu_percent,
+                        is_idle: cpu_percent < VM_IDLE_CPU_PERCENT,
+                        config_path: None,
+                    });
+                }
+                Hypervisor::VMware => {
+                    let vmx = args.iter().rev().find(|a| a.to_lowercase().ends_with(".vmx")).cloned();
+                    let vm_name = vmx
+                        .as_deref()
+                        .and_then(|p| std::path::Path::new(p).file_stem())
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| format!("VMware VM {}", pid));
+                    self.vms.push(VmInfo {
+                        name: vm_name,
+                        hypervisor,
+                        pid: Some(pid.as_u32()),
+                        memory_mb,
+                        demand_mb: None,
+                        dynamic_memory: false,
+                        cpu_percent,
+                        is_idle: cpu_percent < VM_IDLE_CPU_PERCENT,
+                        config_path: vmx,
+                    });
+                }
+            }
+        }
+
+        if !hyperv_workers.is_empty() {
+            self.add_hyperv_vms(&hyperv_workers);
+        }
+
+        self.last_update = std::time::Instant::now();
+    }
+
+    /// Attribute Hyper-V workers to named VMs
+    fn add_hyperv_vms(&mut self, workers: &[(u32, Vec<String>, f64, f32)]) {
+        let vms = query_hyperv_vms().unwrap_or_default();
+
+        if vms.is_empty() {
+            // Get-VM needs Hyper-V Administrators; fall back to the workers
+            for (pid, args, memory_mb, cpu_percent) in workers {
+                let id = args.get(1).cloned().unwrap_or_else(|| pid.to_string());
+                self.vms.push(VmInfo {
+                    name: format!("Hyper-V VM {}", id),
+                    hypervisor: Hypervisor::HyperV,
+                    pid: Some(*pid),
+                    memory_mb: *memory_mb,
+                    demand_mb: None,
+                    dynamic_memory: false,
+                    cpu_percent: *cpu_percent,
+                    is_idle: *cpu_percent < VM_IDLE_CPU_PERCENT,
+                    config_path: None,
+                });
+            }
+            return;
+        }
+
+        for vm in vms.into_iter().filter(|vm| vm.memory_assigned > 0) {
+            // vmwp.exe is started with the VM's GUID as its first argument
+            let worker = workers.iter().find(|(_, args, _, _)| {
+                args.iter().any(|a| a.eq_ignore_ascii_case(&vm.id))
+            });
+            let cpu_percent = worker.map(|w| w.3).unwrap_or(0.0);
+
+            self.vms.push(VmInfo {
+                name: vm.name,
+                hypervisor: Hypervisor::HyperV,
+                pid: worker.map(|w| w.0),
+                memory_mb: vm.memory_assigned as f64 / (1024.0 * 1024.0),
+                demand_mb: Some(vm.memory_demand as f64 / (1024.0 * 1024.0)),
+                dynamic_memory: vm.dynamic_memory_enabled,
+                cpu_percent,
+                is_idle: worker.is_some() && cpu_percent < VM_IDLE_CPU_PERCENT,
+                config_path: None,
+            });
+        }
+    }
+
+    /// Get all running VMs
+    pub fn get_vms(&self) -> &[VmInfo] {
+        &self.vms
+    }
+
+    /// Get idle VMs
+    pub fn get_idle_vms(&self) -> Vec<&VmInfo> {
+        self.vms.iter().filter(|vm| vm.is_idle).collect()
+    }
+
+    /// Get total memory attributed to VMs
+    pub fn total_memory_mb(&self) -> f64 {
+        self.vms.iter().map(|vm| vm.memory_mb).sum()
+    }
+
+    /// Ask a Hyper-V dynamic-memory VM to balloon down to its demand
+    ///
+    /// Lowers the memory buffer so Hyper-V reclaims assigned memory above
+    /// demand; the guest keeps running and can grow again under load.
+    pub fn balloon(&self, name: &str) -> OptimizationResult {
+        let Some(vm) = self.vms.iter().find(|vm| vm.name.eq_ignore_ascii_case(name)) else {
+            return OptimizationResult {
+                app_name: name.to_string(),
+                action: OptimizationAction::TrimMemory,
+                success: false,
+                memory_freed_mb: 0.0,
+                message: "VM not found".to_string(),
+            };
+        };
+
+        if vm.hypervisor != Hypervisor::HyperV || !vm.dynamic_memory {
+            return OptimizationResult {
+                app_name: vm.name.clone(),
+                action: OptimizationAction::TrimMemory,
+                success: false,
+                memory_freed_mb: 0.0,
+                message: format!(
+                    "Ballooning needs Hyper-V dynamic memory; save the VM instead: {}",
+                    vm.save_command()
+                ),
+            };
+        }
+
+        // The name is passed through the environment, never into the script
+        let script = format!(
+            "Set-VMMemory -VMName $env:RUVECTOR_VM_NAME -Buffer {}",
+            BALLOON_BUFFER_PERCENT
+        );
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .env("RUVECTOR_VM_NAME", &vm.name)
+            .output();
+
+        match output {
+            Ok(output) if output.status.success() => OptimizationResult {
+                app_name: vm.name.clone(),
+                action: OptimizationAction::TrimMemory,
+                success: true,
+                memory_freed_mb: vm.balloonable_mb(),
+                message: format!(
+                    "Buffer set to {}%; Hyper-V will reclaim ~{:.0} MB as the balloon inflates",
+                    BALLOON_BUFFER_PERCENT,
+                    vm.balloonable_mb()
+                ),
+            },
+            Ok(output) => OptimizationResult {
+                app_name: vm.name.clone(),
+                action: OptimizationAction::TrimMemory,
+                success: false,
+                memory_freed_mb: 0.0,
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            },
+            Err(e) => OptimizationResult {
+                app_name: vm.name.clone(),
+                action: OptimizationAction::TrimMemory,
+                success: false,
+                memory_freed_mb: 0.0,
+                message: e.to_string(),
+            },
+        }
+    }
+
+    /// Get optimization suggestions
+    pub fn get_suggestions(&self) -> Vec<(String, OptimizationAction, String)> {
+        let mut suggestions = Vec::new();
+
+        for vm in &self.vms {
+            let action = vm.get_suggested_action();
+            let reason = match &action {
+                OptimizationAction::TrimMemory => format!(
+                    "VM '{}' holds {:.0} MB above its demand - balloon it with 'vms --balloon \"{}\"'",
+                    vm.name,
+                    vm.balloonable_mb(),
+                    vm.name
+                ),
+                OptimizationAction::Suspend => format!(
+                    "VM '{}' is idle but using {:.0} MB - save its state: {}",
+                    vm.name,
+                    vm.memory_mb,
+                    vm.save_command()
+                ),
+                _ => continue,
+            };
+
+            suggestions.push((vm.name.clone(), action, reason));
+        }
+
+        suggestions
+    }
+
+    /// Print VM summary
+    pub fn print_summary(&self) {
+        println!("\n🖥️  Virtual Machine Memory Usage\n");
+
+        if self.vms.is_empty() {
+            println!("No running VMs found (Hyper-V, VirtualBox, VMware).");
+            return;
+        }
+
+        println!("┌──────────────────────┬────────────┬───────────┬───────────┬──────────┐");
+        println!("│ VM                   │ Hypervisor │ Memory    │ Demand    │ Status   │");
+        println!("├──────────────────────┼────────────┼───────────┼───────────┼──────────┤");
+
+        let mut vms: Vec<_> = self.vms.iter().collect();
+        vms.sort_by(|a, b| b.memory_mb.partial_cmp(&a.memory_mb).unwrap());
+
+        for vm in &vms {
+            let demand = vm
+                .demand_mb
+                .map(|d| format!("{:>7.0} MB", d))
+                .unwrap_or_else(|| format!("{:>10}", "-"));
+            let status = if vm.is_idle { "💤 Idle" } else { "🟢 Active" };
+
+            println!(
+                "│ {:20} │ {:10} │ {:>7.0} MB │ {} │ {:8} │",
+                truncate_padded(&vm.name, 20),
+                vm.hypervisor.to_string(),
+                vm.memory_mb,
+                demand,
+                status
+            );
+        }
+
+        println!("└──────────────────────┴────────────┴───────────┴───────────┴──────────┘");
+
+        println!(
+            "\nTotal: {:.0} MB across {} VMs ({} idle)",
+            self.total_memory_mb(),
+            vms.len(),
+            self.get_idle_vms().len()
+        );
+
+        let suggestions = self.get_suggestions();
+        if !suggestions.is_empty() {
+            println!("\n💡 Suggestions:");
+            for (_, _, reason) in suggestions.iter().take(3) {
+                println!("   • {}", reason);
+            }
+        }
+    }
+}
+
+impl Default for VmManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Running Hyper-V VMs via PowerShell (requires Hyper-V Administrators)
+#[cfg(target_os = "windows")]
+fn query_hyperv_vms() -> Result<Vec<HyperVVm>, String> {
+    const SCRIPT: &str = "Get-VM | Where-Object State -eq 'Running' | \
+        Select-Object @{n='Id';e={$_.Id.ToString()}},Name,MemoryAssigned,MemoryDemand,DynamicMemoryEnabled | \
+        ConvertTo-Json -Compress";
+
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    parse_hyperv_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn query_hyperv_vms() -> Result<Vec<HyperVVm>, String> {
+    Ok(Vec::new())
+}
+
+/// ConvertTo-Json emits a bare object for one VM and an array for several
+#[cfg(target_os = "windows")]
+fn parse_hyperv_json(json: &str) -> Result<Vec<HyperVVm>, String> {
+    let json = json.trim();
+    if json.is_empty() {
+        return Ok(Vec::new());
+    }
+    if json.starts_with('[') {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str::<HyperVVm>(json)
+            .map(|vm| vec![vm])
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Value following `flag` in a command line
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    let pos = args.iter().position(|a| a.eq_ignore_ascii_case(flag))?;
+    args.get(pos + 1).cloned()
+}
+
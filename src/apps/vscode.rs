@@ -0,0 +1,320 @@
+//! Memory blame for VS Code extensions
+//!
+//! VS Code runs most extensions inside its extension host, and many of them
+//! start language servers and other helpers of their own. Their command lines
+//! point into `extensions/<publisher.name-version>`, so [`ExtensionBlame`]
+//! walks the VS Code process tree and charges each helper, and everything it
+//! started, to that extension. Memory of the extension host itself can only be
+//! split over the DevTools protocol: with `--inspect-extensions` the share of
+//! new JS allocations each extension made is applied to the host's heap.
+//!
+//! The result names the extension to disable instead of "VS Code uses 4 GB".
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::electron::TargetHeap;
+use crate::core::text::truncate_padded;
+
+/// Extensions using less than this are not worth disabling
+pub const MIN_BLAME_MB: f64 = 300.0;
+
+/// Ancestors followed before giving up on a process tree
+const MAX_DEPTH: usize = 32;
+
+/// One process, as far as blame needs to know it
+#[derive(Debug, Clone)]
+pub struct VsCodeProcess {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub rss_mb: f64,
+    /// Executable path followed by the arguments
+    pub command: Vec<String>,
+}
+
+/// Memory charged to one extension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionUsage {
+    /// `publisher.name`; built-in extensions are `vscode.<name>`
+    pub id: String,
+    /// Language servers and other helpers it started
+    pub pids: Vec<u32>,
+    pub process_mb: f64,
+    /// Estimated share of the extension host's JS heap
+    pub heap_mb: f64,
+}
+
+impl ExtensionUsage {
+    pub fn total_mb(&self) -> f64 {
+        self.process_mb + self.heap_mb
+    }
+}
+
+/// Where the memory of a running VS Code goes, largest extension first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtensionBlame {
+    pub total_mb: f64,
+    pub process_count: usize,
+    pub extension_hosts: Vec<u32>,
+    pub extension_host_mb: f64,
+    /// Whether an extension host heap was read over the DevTools protocol
+    pub heap_sampled: bool,
+    pub extensions: Vec<ExtensionUsage>,
+}
+
+impl ExtensionBlame {
+    /// Charge every process below `roots` (the VS Code processes) to the
+    /// extension it, or its nearest ancestor, runs from
+    pub fn from_processes(roots: &[u32], processes: &[VsCodeProcess]) -> Self {
+        let roots: HashSet<u32> = roots.iter().copied().collect();
+        let by_pid: HashMap<u32, &VsCodeProcess> = processes.iter().map(|p| (p.pid, p)).collect();
+        let ancestors = |pid: u32| {
+            std::iter::successors(by_pid.get(&pid).copied(), |p| p.parent.and_then(|parent| by_pid.get(&parent).copied()))
+                .take(MAX_DEPTH)
+        };
+
+        let mut blame = Self::default();
+        let mut usage: HashMap<String, ExtensionUsage> = HashMap::new();
+        for process in processes {
+            if !ancestors(process.pid).any(|p| roots.contains(&p.pid)) {
+                continue;
+            }
+            blame.total_mb += process.rss_mb;
+            blame.process_count += 1;
+            if is_extension_host(process) {
+                blame.extension_hosts.push(process.pid);
+                blame.extension_host_mb += process.rss_mb;
+                continue;
+            }
+            let owner = ancestors(process.pid)
+                .take_while(|p| !is_extension_host(p))
+                .find_map(|p| p.command.iter().find_map(|arg| extension_id(arg)));
+            if let Some(id) = owner {
+                let entry = usage.entry(id.clone()).or_insert_with(|| ExtensionUsage {
+                    id,
+                    pids: Vec::new(),
+                    process_mb: 0.0,
+                    heap_mb: 0.0,
+                });
+                entry.pids.push(process.pid);
+                entry.process_mb += process.rss_mb;
+            }
+        }
+        blame.extensions = usage.into_values().collect();
+        blame.sort();
+        blame
+    }
+
+    /// Split extension host heaps between the extensions that allocated in them
+    pub fn add_heap(&mut self, targets: &[TargetHeap]) {
+        for target in targets.iter().filter(|t| t.app == "vscode" && t.label == "extension host") {
+            self.heap_sampled = true;
+            let sampled: f64 = target.owners.iter().map(|o| o.mb).sum();
+            if sampled <= 0.0 {
+                continue;
+            }
+            for owner in &target.owners {
+                let Some(id) = owner.owner.contains('.').then_some(&owner.owner) else {
+                    continue;
+                };
+                let share = target.used_mb * owner.mb / sampled;
+                match self.extensions.iter_mut().find(|e| &e.id == id) {
+                    Some(usage) => usage.heap_mb += share,
+                    None => self.extensions.push(ExtensionUsage {
+                        id: id.clone(),
+                        pids: Vec::new(),
+                        process_mb: 0.0,
+                        heap_mb: share,
+                    }),
+                }
+            }
+        }
+        self.sort();
+    }
+
+    /// Extension host memory not charged to any extension
+    pub fn unattributed_host_mb(&self) -> f64 {
+        let heap: f64 = self.extensions.iter().map(|e| e.heap_mb).sum();
+        (self.extension_host_mb - heap).max(0.0)
+    }
+
+    /// The extension to disable, if one stands out
+    pub fn culprit(&self) -> Option<&ExtensionUsage> {
+        self.extensions.first().filter(|e| e.total_mb() >= MIN_BLAME_MB)
+    }
+
+    /// What to do about it, in one or two sentences
+    pub fn recommendation(&self) -> Option<String> {
+        if let Some(ext) = self.culprit() {
+            return Some(format!(
+                "{} accounts for {:.0} MB of VS Code's {:.0} MB. Disable it in workspaces that don't need it; `code --disable-extension {}` tries a session without it.",
+                ext.id,
+                ext.total_mb(),
+                self.total_mb,
+                ext.id
+            ));
+        }
+        (!self.heap_sampled && self.extension_host_mb >= MIN_BLAME_MB).then(|| {
+            format!(
+                "The extension host uses {:.0} MB. Start VS Code with --inspect-extensions=9333 and run `electron vscode --heap` to see which extension holds it.",
+                self.extension_host_mb
+            )
+        })
+    }
+
+    pub fn print(&self) {
+        println!("\n🧩 VS Code Memory by Extension\n");
+        println!("  Total:          {:.0} MB in {} processes", self.total_mb, self.process_count);
+        let split = if self.heap_sampled { "split by JS heap allocations" } else { "not split between extensions" };
+        println!(
+            "  Extension host: {:.0} MB in {} processes ({})",
+            self.extension_host_mb,
+            self.extension_hosts.len(),
+            split
+        );
+        if !self.extensions.is_empty() {
+            println!();
+            println!("┌────────────────────────────────────────┬───────────┬───────────┬───────────┐");
+            println!("│ Extension                              │ Helpers   │ JS heap   │ Total     │");
+            println!("├────────────────────────────────────────┼───────────┼───────────┼───────────┤");
+            for ext in &self.extensions {
+                println!(
+                    "│ {:38} │ {:>6.0} MB │ {:>6.0} MB │ {:>6.0} MB │",
+                    truncate_padded(&ext.id, 38),
+                    ext.process_mb,
+                    ext.heap_mb,
+                    ext.total_mb()
+                );
+            }
+            println!("└────────────────────────────────────────┴───────────┴───────────┴───────────┘");
+        }
+        if let Some(recommendation) = self.recommendation() {
+            println!("\n💡 {}", recommendation);
+        }
+    }
+
+    fn sort(&mut self) {
+        self.extensions.sort_by(|a, b| b.total_mb().total_cmp(&a.total_mb()).then_with(|| a.id.cmp(&b.id)));
+    }
+}
+
+/// Extension id of a path inside an `extensions` directory
+///
+/// `.../extensions/eamodio.gitlens-14.0.0/dist/gitlens.js` is
+/// `eamodio.gitlens`; built-in extensions have no publisher and get `vscode.`.
+pub fn extension_id(path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let (_, rest) = path.split_once("/extensions/")?;
+    let mut parts = rest.split('/');
+    let dir = parts.next().filter(|d| !d.is_empty())?;
+    if dir == "node_modules" {
+        // The TypeScript server VS Code ships is run by its TypeScript extension
+        return (parts.next() == Some("typescript")).then(|| "vscode.typescript-language-features".to_string());
+    }
+    // `publisher.name-1.2.3[-platform]` -> `publisher.name`
+    let version = dir
+        .match_indices('-')
+        .map(|(i, _)| i)
+        .find(|&i| dir[i + 1..].starts_with(|c: char| c.is_ascii_digit()));
+    let id = &dir[..version.unwrap_or(dir.len())];
+    Some(if id.contains('.') { id.to_string() } else { format!("vscode.{}", id) })
+}
+
+/// Whether a process is an extension host: `Code Helper (Plugin)` on macOS,
+/// `--type=extensionHost` elsewhere
+fn is_extension_host(process: &VsCodeProcess) -> bool {
+    process.name.to_lowercase().contains("(plugin)")
+        || process.command.iter().any(|arg| arg == "--type=extensionHost" || arg.ends_with("extensionHostProcess.js"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::electron::HeapShare;
+
+    fn process(pid: u32, parent: u32, name: &str, rss_mb: f64, command: &[&str]) -> VsCodeProcess {
+        VsCodeProcess {
+            pid,
+            parent: Some(parent),
+            name: name.into(),
+            rss_mb,
+            command: command.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn vscode() -> Vec<VsCodeProcess> {
+        let ext = "/home/me/.vscode/extensions";
+        vec![
+            process(10, 1, "code", 300.0, &["/usr/share/code/code"]),
+            process(11, 10, "code", 900.0, &["/usr/share/code/code", "--type=renderer"]),
+            process(12, 10, "code", 700.0, &["/usr/share/code/code", "out/bootstrap-fork", "--type=extensionHost"]),
+            process(20, 12, "rust-analyzer", 1500.0, &[&format!("{}/rust-lang.rust-analyzer-0.3.2029-linux-x64/server/rust-analyzer", ext)]),
+            process(21, 20, "rust-analyzer-proc-macro-srv", 200.0, &["/home/me/.rustup/toolchains/stable/libexec/rust-analyzer-proc-macro-srv"]),
+            process(
+                22,
+                12,
+                "code",
+                400.0,
+                &["/usr/share/code/code", "/usr/share/code/resources/app/extensions/node_modules/typescript/lib/tsserver.js"],
+            ),
+            process(23, 12, "node", 50.0, &["node", "--version"]),
+            process(30, 1, "bash", 5.0, &["/bin/bash"]),
+        ]
+    }
+
+    #[test]
+    fn test_extension_id() {
+        assert_eq!(
+            extension_id("c:\\Users\\me\\.vscode\\extensions\\ms-python.vscode-pylance-2024.8.1\\dist\\server.bundle.js").as_deref(),
+            Some("ms-python.vscode-pylance")
+        );
+        assert_eq!(
+            extension_id("/Applications/Visual Studio Code.app/Contents/Resources/app/extensions/git/dist/askpass-main.js").as_deref(),
+            Some("vscode.git")
+        );
+        assert_eq!(
+            extension_id("/usr/share/code/resources/app/extensions/node_modules/typescript/lib/tsserver.js").as_deref(),
+            Some("vscode.typescript-language-features")
+        );
+        assert_eq!(extension_id("/usr/share/code/resources/app/out/main.js"), None);
+    }
+
+    #[test]
+    fn test_helpers_are_charged_to_their_extension() {
+        let blame = ExtensionBlame::from_processes(&[10, 11, 12, 22], &vscode());
+        assert_eq!(blame.process_count, 7);
+        assert_eq!(blame.total_mb, 4050.0);
+        assert_eq!(blame.extension_hosts, vec![12]);
+        assert_eq!(blame.extension_host_mb, 700.0);
+
+        let ids: Vec<&str> = blame.extensions.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["rust-lang.rust-analyzer", "vscode.typescript-language-features"]);
+        assert_eq!(blame.extensions[0].pids, vec![20, 21]);
+        assert_eq!(blame.extensions[0].process_mb, 1700.0);
+        assert!(blame.recommendation().unwrap().starts_with("rust-lang.rust-analyzer accounts for 1700 MB"));
+    }
+
+    #[test]
+    fn test_extension_host_heap_is_split_by_allocations() {
+        let mut blame = ExtensionBlame::from_processes(&[10, 12], &vscode()[..3]);
+        assert!(blame.culprit().is_none());
+        assert!(blame.recommendation().unwrap().contains("--inspect-extensions=9333"));
+
+        let owners = [("eamodio.gitlens", 30.0), ("app", 10.0), ("vscode.git", 10.0)];
+        blame.add_heap(&[TargetHeap {
+            app: "vscode".into(),
+            pid: 12,
+            target_id: "ext".into(),
+            kind: "node".into(),
+            label: "extension host".into(),
+            used_mb: 600.0,
+            total_mb: 800.0,
+            owners: owners.iter().map(|&(owner, mb)| HeapShare { owner: owner.into(), mb }).collect(),
+        }]);
+        assert_eq!(blame.extensions[0].id, "eamodio.gitlens");
+        assert_eq!(blame.extensions[0].heap_mb, 360.0);
+        assert_eq!(blame.unattributed_host_mb(), 220.0);
+        assert_eq!(blame.culprit().map(|e| e.id.as_str()), Some("eamodio.gitlens"));
+    }
+}
@@ -11,6 +11,7 @@ use crate::algorithms::{
     sketch::CountMinSketch,
     spectral::SpectralAnalyzer,
 };
+use crate::monitor::relations;
 
 /// Benchmark results for a single algorithm
 #[derive(Debug, Clone)]
@@ -25,6 +26,10 @@ pub struct AlgorithmBenchmark {
     pub min_ns: u64,
     pub max_us: u64,
     pub max_ns: u64,
+    /// Latency percentiles per iteration (per op for batched benchmarks)
+    pub p50_ns: u64,
+    pub p95_ns: u64,
+    pub p99_ns: u64,
     pub ops_per_sec: f64,
     pub memory_bytes: usize,
 }
@@ -54,6 +59,8 @@ pub struct ImprovementSummary {
 pub struct AdvancedBenchmarkRunner {
     iterations: usize,
     warmup: usize,
+    /// Print each benchmark as it finishes
+    progress: bool,
 }
 
 impl AdvancedBenchmarkRunner {
@@ -61,14 +68,27 @@ impl AdvancedBenchmarkRunner {
         Self {
             iterations,
             warmup: 5,
+            progress: true,
+        }
+    }
+
+    /// Print progress as benchmarks run (on by default); off for JSON output
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn report(&self, line: &str) {
+        if self.progress {
+            println!("{}", line);
         }
     }
 
     /// Run all benchmarks
     pub fn run_all(&self) -> BenchmarkSuite {
-        println!("🚀 Running RuVector Advanced Benchmark Suite\n");
-        println!("Iterations: {}", self.iterations);
-        println!("{}", "=".repeat(60));
+        self.report("🚀 Running RuVector Advanced Benchmark Suite\n");
+        self.report(&format!("Iterations: {}", self.iterations));
+        self.report(&"=".repeat(60));
 
         let mincut = self.bench_mincut();
         let pagerank = self.bench_pagerank();
@@ -97,7 +117,7 @@ impl AdvancedBenchmarkRunner {
 
     /// Benchmark MinCut clustering
     fn bench_mincut(&self) -> AlgorithmBenchmark {
-        println!("\n📊 MinCut Process Clustering");
+        self.report("\n📊 MinCut Process Clustering");
 
         let mut system = System::new_all();
         system.refresh_processes(ProcessesToUpdate::All, true);
@@ -127,15 +147,17 @@ impl AdvancedBenchmarkRunner {
 
     /// Benchmark PageRank computation
     fn bench_pagerank(&self) -> AlgorithmBenchmark {
-        println!("📊 PageRank Process Priority");
+        self.report("📊 PageRank Process Priority");
 
         let mut system = System::new_all();
         system.refresh_processes(ProcessesToUpdate::All, true);
+        // Collected once: the benchmark times the ranking, not /proc or ss
+        let relations = relations::collect(&system);
 
         // Warmup
         for _ in 0..self.warmup {
             let mut pagerank = ProcessPageRank::new();
-            pagerank.compute(&system);
+            pagerank.compute_with(&system, &relations);
         }
 
         let mut times = Vec::with_capacity(self.iterations);
@@ -144,7 +166,7 @@ impl AdvancedBenchmarkRunner {
         for _ in 0..self.iterations {
             let iter_start = Instant::now();
             let mut pagerank = ProcessPageRank::new();
-            pagerank.compute(&system);
+            pagerank.compute_with(&system, &relations);
             let _ = pagerank.get_trim_candidates(10);
             times.push(iter_start.elapsed().as_micros() as u64);
         }
@@ -155,7 +177,7 @@ impl AdvancedBenchmarkRunner {
 
     /// Benchmark Count-Min Sketch add operations (batch for sub-µs precision)
     fn bench_sketch_add(&self) -> AlgorithmBenchmark {
-        println!("📊 Count-Min Sketch (Add)");
+        self.report("📊 Count-Min Sketch (Add)");
 
         let mut sketch = CountMinSketch::new(0.01, 0.001);
         let batch = 1000; // batch ops to get measurable time
@@ -183,7 +205,7 @@ impl AdvancedBenchmarkRunner {
 
     /// Benchmark Count-Min Sketch query operations (batch for sub-µs precision)
     fn bench_sketch_query(&self) -> AlgorithmBenchmark {
-        println!("📊 Count-Min Sketch (Query)");
+        self.report("📊 Count-Min Sketch (Query)");
 
         let mut sketch = CountMinSketch::new(0.01, 0.001);
         let batch = 1000;
@@ -215,7 +237,7 @@ impl AdvancedBenchmarkRunner {
 
     /// Benchmark Spectral Analysis
     fn bench_spectral(&self) -> AlgorithmBenchmark {
-        println!("📊 Spectral Analysis");
+        self.report("📊 Spectral Analysis");
 
         let mut analyzer = SpectralAnalyzer::new(60);
 
@@ -246,7 +268,7 @@ impl AdvancedBenchmarkRunner {
 
     /// Benchmark baseline process scorer (for comparison)
     fn bench_baseline_scorer(&self) -> AlgorithmBenchmark {
-        println!("📊 Baseline Process Scorer");
+        self.report("📊 Baseline Process Scorer");
 
         use crate::core::process_scorer::ProcessScorer;
 
@@ -292,6 +314,8 @@ impl AdvancedBenchmarkRunner {
         total_ops: usize,
         memory_bytes: usize,
     ) -> AlgorithmBenchmark {
+        let mut sorted = times_ns.clone();
+        sorted.sort_unstable();
         let min_ns = times_ns.iter().min().copied().unwrap_or(0);
         let max_ns = times_ns.iter().max().copied().unwrap_or(0);
         let avg_ns = if !times_ns.is_empty() {
@@ -316,21 +340,24 @@ impl AdvancedBenchmarkRunner {
             min_ns,
             max_us: max_ns / 1000,
             max_ns,
+            p50_ns: percentile(&sorted, 50.0),
+            p95_ns: percentile(&sorted, 95.0),
+            p99_ns: percentile(&sorted, 99.0),
             ops_per_sec: ops,
             memory_bytes,
         };
 
         // Smart display: use ns for sub-µs, µs for sub-ms
         if result.avg_ns < 1000.0 {
-            println!(
-                "   avg: {:.1}ns | min: {}ns | max: {}ns | {:.0} ops/sec | {}",
-                result.avg_ns, result.min_ns, result.max_ns, result.ops_per_sec, format_bytes(result.memory_bytes)
-            );
+            self.report(&format!(
+                "   avg: {:.1}ns | p95: {}ns | min: {}ns | max: {}ns | {:.0} ops/sec | {}",
+                result.avg_ns, result.p95_ns, result.min_ns, result.max_ns, result.ops_per_sec, format_bytes(result.memory_bytes)
+            ));
         } else {
-            println!(
-                "   avg: {:.2}µs | min: {}µs | max: {}µs | {:.0} ops/sec | {}",
-                result.avg_us, result.min_us, result.max_us, result.ops_per_sec, format_bytes(result.memory_bytes)
-            );
+            self.report(&format!(
+                "   avg: {:.2}µs | p95: {}µs | min: {}µs | max: {}µs | {:.0} ops/sec | {}",
+                result.avg_us, result.p95_ns / 1000, result.min_us, result.max_us, result.ops_per_sec, format_bytes(result.memory_bytes)
+            ));
         }
 
         result
@@ -338,6 +365,18 @@ impl AdvancedBenchmarkRunner {
 }
 
 impl BenchmarkSuite {
+    /// Every benchmark, in run order
+    pub fn benchmarks(&self) -> [&AlgorithmBenchmark; 6] {
+        [
+            &self.mincut,
+            &self.pagerank,
+            &self.sketch_add,
+            &self.sketch_query,
+            &self.spectral,
+            &self.baseline_scorer,
+        ]
+    }
+
     /// Print formatted results
     pub fn print_summary(&self) {
         println!("\n{}", "=".repeat(60));
@@ -348,14 +387,7 @@ impl BenchmarkSuite {
         println!("│ Algorithm           │  Avg Time      │    Ops/sec  │  Memory    │");
         println!("├─────────────────────┼────────────────┼─────────────┼────────────┤");
 
-        for bench in [
-            &self.mincut,
-            &self.pagerank,
-            &self.sketch_add,
-            &self.sketch_query,
-            &self.spectral,
-            &self.baseline_scorer,
-        ] {
+        for bench in self.benchmarks() {
             let time_str = if bench.avg_ns < 1000.0 {
                 format!("{:>9.1} ns", bench.avg_ns)
             } else if bench.avg_us < 1000.0 {
@@ -389,6 +421,15 @@ impl BenchmarkSuite {
     }
 }
 
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
 fn format_bytes(bytes: usize) -> String {
     if bytes >= 1024 * 1024 {
         format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
@@ -407,6 +448,19 @@ mod tests {
     fn test_benchmark_runner() {
         let runner = AdvancedBenchmarkRunner::new(10);
         let sketch = runner.bench_sketch_add();
-        assert!(sketch.iterations == 10);
+        // Each of the 10 iterations adds a batch of 1000
+        assert_eq!(sketch.iterations, 10 * 1000);
+        assert!(sketch.p50_ns <= sketch.p95_ns && sketch.p95_ns <= sketch.p99_ns);
+        assert!(sketch.p99_ns <= sketch.max_ns);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50);
+        assert_eq!(percentile(&sorted, 95.0), 95);
+        assert_eq!(percentile(&sorted, 99.0), 99);
+        assert_eq!(percentile(&[7], 99.0), 7);
+        assert_eq!(percentile(&[], 50.0), 0);
     }
 }
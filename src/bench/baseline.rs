@@ -0,0 +1,348 @@
+//! Saved benchmark baselines and regression checks
+//!
+//! `bench --save-baseline main` stores the advanced suite's throughput and
+//! latency percentiles under a name; `bench --compare main` runs the suite
+//! again and diffs it against them. A benchmark regresses when its ops/sec
+//! drop, or its p95 latency rises, by more than the failure threshold; the
+//! command then exits non-zero so CI can fail the build. Baselines are only
+//! comparable on the same machine: save one on the main branch, then compare
+//! a branch against it.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::advanced::{AlgorithmBenchmark, BenchmarkSuite};
+
+/// Directory of baselines inside the config directory
+pub const BASELINE_DIR: &str = "bench";
+
+/// Default change (percent) that counts as a regression
+pub const DEFAULT_FAIL_THRESHOLD: f64 = 10.0;
+
+/// One benchmark's numbers as stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub name: String,
+    pub ops_per_sec: f64,
+    pub avg_ns: f64,
+    pub p50_ns: u64,
+    pub p95_ns: u64,
+    pub p99_ns: u64,
+}
+
+impl From<&AlgorithmBenchmark> for BaselineEntry {
+    fn from(bench: &AlgorithmBenchmark) -> Self {
+        Self {
+            name: bench.name.clone(),
+            ops_per_sec: bench.ops_per_sec,
+            avg_ns: bench.avg_ns,
+            p50_ns: bench.p50_ns,
+            p95_ns: bench.p95_ns,
+            p99_ns: bench.p99_ns,
+        }
+    }
+}
+
+/// A named set of results to compare later runs against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub name: String,
+    pub created_ms: u64,
+    pub iterations: usize,
+    pub results: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    pub fn from_suite(name: &str, iterations: usize, suite: &BenchmarkSuite) -> Self {
+        Self {
+            name: name.to_string(),
+            created_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+            iterations,
+            results: suite.benchmarks().into_iter().map(BaselineEntry::from).collect(),
+        }
+    }
+}
+
+/// How a benchmark moved against the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Regression,
+    Improvement,
+    Unchanged,
+    /// Not in the baseline
+    New,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Verdict::Regression => "REGRESSION",
+            Verdict::Improvement => "faster",
+            Verdict::Unchanged => "ok",
+            Verdict::New => "new",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComparisonRow {
+    pub name: String,
+    pub baseline_ops_per_sec: Option<f64>,
+    pub ops_per_sec: f64,
+    /// Positive is faster
+    pub ops_change_percent: Option<f64>,
+    pub baseline_p95_ns: Option<u64>,
+    pub p95_ns: u64,
+    /// Positive is slower
+    pub p95_change_percent: Option<f64>,
+    pub verdict: Verdict,
+}
+
+/// Current results diffed against a baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub baseline: String,
+    pub threshold_percent: f64,
+    pub rows: Vec<ComparisonRow>,
+    pub regressions: usize,
+}
+
+impl Comparison {
+    pub fn new(baseline: &Baseline, current: &[BaselineEntry], threshold_percent: f64) -> Self {
+        let rows: Vec<ComparisonRow> = current
+            .iter()
+            .map(|entry| {
+                let before = baseline.results.iter().find(|b| b.name == entry.name);
+                let ops_change = before.and_then(|b| change_percent(b.ops_per_sec, entry.ops_per_sec));
+                let p95_change = before.and_then(|b| change_percent(b.p95_ns as f64, entry.p95_ns as f64));
+                let verdict = match (ops_change, p95_change) {
+                    _ if before.is_none() => Verdict::New,
+                    (Some(ops), _) if ops < -threshold_percent => Verdict::Regression,
+                    (_, Some(p95)) if p95 > threshold_percent => Verdict::Regression,
+                    (Some(ops), _) if ops > threshold_percent => Verdict::Improvement,
+                    _ => Verdict::Unchanged,
+                };
+                ComparisonRow {
+                    name: entry.name.clone(),
+                    baseline_ops_per_sec: before.map(|b| b.ops_per_sec),
+                    ops_per_sec: entry.ops_per_sec,
+                    ops_change_percent: ops_change,
+                    baseline_p95_ns: before.map(|b| b.p95_ns),
+                    p95_ns: entry.p95_ns,
+                    p95_change_percent: p95_change,
+                    verdict,
+                }
+            })
+            .collect();
+        let regressions = rows.iter().filter(|r| r.verdict == Verdict::Regression).count();
+        Self { baseline: baseline.name.clone(), threshold_percent, rows, regressions }
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        self.regressions > 0
+    }
+
+    pub fn print(&self) {
+        println!("\nCompared with baseline '{}' (threshold {:.0}%):\n", self.baseline, self.threshold_percent);
+        println!("  {:<14} {:>12} {:>12} {:>8}   {:>10} {:>10} {:>8}   Result", "Benchmark", "Base ops/s", "Ops/s", "Change", "Base p95", "p95", "Change");
+        for row in &self.rows {
+            println!(
+                "  {:<14} {:>12} {:>12} {:>8}   {:>10} {:>10} {:>8}   {}",
+                row.name,
+                row.baseline_ops_per_sec.map_or("-".into(), format_ops),
+                format_ops(row.ops_per_sec),
+                format_change(row.ops_change_percent),
+                row.baseline_p95_ns.map_or("-".into(), format_ns),
+                format_ns(row.p95_ns),
+                format_change(row.p95_change_percent),
+                row.verdict
+            );
+        }
+        println!();
+        match self.regressions {
+            0 => println!("No regressions."),
+            1 => println!("1 benchmark regressed."),
+            n => println!("{} benchmarks regressed.", n),
+        }
+    }
+}
+
+/// Baselines as JSON files in one directory
+pub struct BaselineStore {
+    dir: PathBuf,
+}
+
+impl BaselineStore {
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_dir().join(BASELINE_DIR))
+    }
+
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, String> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(format!("Invalid baseline name '{}': use letters, digits, '-', '_' and '.'", name));
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+
+    pub fn save(&self, baseline: &Baseline) -> Result<PathBuf, String> {
+        let path = self.path(&baseline.name)?;
+        let json = serde_json::to_string_pretty(baseline).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> Result<Baseline, String> {
+        let path = self.path(name)?;
+        let json = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!("No baseline '{}' in {:?}; save one with --save-baseline", name, self.dir),
+            _ => format!("Failed to read {:?}: {}", path, e),
+        })?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid baseline {:?}: {}", path, e))
+    }
+}
+
+/// What `bench` should do with the advanced suite's results
+#[derive(Debug, Clone, Default)]
+pub struct BaselineRequest {
+    pub save: Option<String>,
+    pub compare: Option<String>,
+    /// Change in percent that counts as a regression
+    pub fail_threshold: f64,
+    /// Baseline directory instead of the one in the config directory
+    pub dir: Option<PathBuf>,
+    /// Print JSON instead of tables
+    pub json: bool,
+}
+
+impl BaselineRequest {
+    /// Whether the advanced suite has to run even without `--advanced`
+    pub fn needs_suite(&self) -> bool {
+        self.save.is_some() || self.compare.is_some() || self.json
+    }
+
+    /// Compare with and/or save the results; `Ok(false)` when a benchmark
+    /// regressed
+    ///
+    /// The comparison runs before the save, so `--compare main
+    /// --save-baseline main` checks against the old numbers and then moves
+    /// the baseline forward.
+    pub fn apply(&self, iterations: usize, suite: &BenchmarkSuite) -> Result<bool, String> {
+        let store = match &self.dir {
+            Some(dir) => BaselineStore::open(dir)?,
+            None => BaselineStore::open_default()?,
+        };
+        let current = Baseline::from_suite(self.save.as_deref().unwrap_or("current"), iterations, suite);
+
+        let mut passed = true;
+        match &self.compare {
+            Some(name) => {
+                let comparison = Comparison::new(&store.load(name)?, &current.results, self.fail_threshold);
+                if self.json {
+                    println!("{}", serde_json::to_string_pretty(&comparison).map_err(|e| e.to_string())?);
+                } else {
+                    comparison.print();
+                }
+                passed = !comparison.has_regressions();
+            }
+            None if self.json => {
+                println!("{}", serde_json::to_string_pretty(&current).map_err(|e| e.to_string())?);
+            }
+            None => {}
+        }
+
+        if self.save.is_some() {
+            let path = store.save(&current)?;
+            if !self.json {
+                println!("Saved baseline '{}' to {}", current.name, path.display());
+            }
+        }
+        Ok(passed)
+    }
+}
+
+fn change_percent(before: f64, after: f64) -> Option<f64> {
+    (before > 0.0).then(|| (after - before) / before * 100.0)
+}
+
+fn format_change(change: Option<f64>) -> String {
+    change.map_or("-".into(), |c| format!("{:+.1}%", c))
+}
+
+fn format_ops(ops: f64) -> String {
+    if ops >= 1_000_000.0 {
+        format!("{:.2}M", ops / 1_000_000.0)
+    } else if ops >= 1_000.0 {
+        format!("{:.1}K", ops / 1_000.0)
+    } else {
+        format!("{:.0}", ops)
+    }
+}
+
+fn format_ns(ns: u64) -> String {
+    if ns >= 1_000_000 {
+        format!("{:.2}ms", ns as f64 / 1_000_000.0)
+    } else if ns >= 1_000 {
+        format!("{:.1}µs", ns as f64 / 1_000.0)
+    } else {
+        format!("{}ns", ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, ops_per_sec: f64, p95_ns: u64) -> BaselineEntry {
+        BaselineEntry { name: name.into(), ops_per_sec, avg_ns: 0.0, p50_ns: 0, p95_ns, p99_ns: 0 }
+    }
+
+    fn baseline() -> Baseline {
+        Baseline {
+            name: "main".into(),
+            created_ms: 0,
+            iterations: 100,
+            results: vec![entry("MinCut", 1000.0, 2_000_000), entry("PageRank", 500.0, 4_000_000), entry("Sketch Add", 5e6, 200)],
+        }
+    }
+
+    #[test]
+    fn test_comparison_verdicts() {
+        let current = [
+            entry("MinCut", 850.0, 2_000_000),     // 15% fewer ops
+            entry("PageRank", 505.0, 4_600_000),   // p95 15% slower
+            entry("Sketch Add", 6e6, 190),         // 20% more ops
+            entry("Spectral", 1e5, 10_000),        // not in the baseline
+        ];
+        let comparison = Comparison::new(&baseline(), &current, 10.0);
+        let verdicts: Vec<Verdict> = comparison.rows.iter().map(|r| r.verdict).collect();
+        assert_eq!(verdicts, [Verdict::Regression, Verdict::Regression, Verdict::Improvement, Verdict::New]);
+        assert_eq!(comparison.regressions, 2);
+        assert!((comparison.rows[0].ops_change_percent.unwrap() + 15.0).abs() < 1e-9);
+
+        let lenient = Comparison::new(&baseline(), &current, 25.0);
+        assert!(!lenient.has_regressions());
+    }
+
+    #[test]
+    fn test_store_roundtrip_and_names() {
+        let dir = std::env::temp_dir().join(format!("ruvector-bench-{}", std::process::id()));
+        let store = BaselineStore::open(&dir).unwrap();
+        store.save(&baseline()).unwrap();
+        assert_eq!(store.load("main").unwrap(), baseline());
+        assert!(store.load("missing").unwrap_err().contains("--save-baseline"));
+        assert!(store.load("../main").is_err());
+        assert!(store.load("").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
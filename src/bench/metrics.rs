@@ -41,6 +41,14 @@ impl BenchmarkMetrics {
         self.history.push_back(metrics.clone());
     }
     
+    /// Halve the kept history, and how much is kept from now on; totals stay
+    pub fn shrink(&mut self) {
+        self.max_history = (self.max_history / 2).max(100);
+        let excess = self.history.len().saturating_sub(self.max_history);
+        self.history.drain(..excess);
+        self.history.shrink_to_fit();
+    }
+    
     pub fn summary(&self) -> MetricsSummary {
         let avg_freed = if self.total_optimizations > 0 {
             self.total_freed_mb / self.total_optimizations as f64
@@ -5,10 +5,16 @@
 //! - PageRank priority scoring
 //! - Count-Min Sketch sublinear counting
 //! - Spectral pattern analysis
+//!
+//! Advanced results can be saved as named baselines and compared against
+//! later runs to catch regressions, and `real` measures what optimization
+//! does on the live system.
 
 pub mod metrics;
 #[cfg(target_os = "windows")]
 pub mod runner;
 pub mod advanced;
+pub mod baseline;
+pub mod real;
 
 pub use advanced::{AdvancedBenchmarkRunner, BenchmarkSuite, AlgorithmBenchmark};
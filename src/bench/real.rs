@@ -0,0 +1,278 @@
+//! Real-world optimization efficacy benchmark
+//!
+//! `bench --real` runs a standard and then an aggressive optimization on the
+//! live system and measures what each one actually did: memory freed
+//! according to the OS rather than the optimizer's own estimate, how fast
+//! usage climbs back afterwards, and how much slower a working set is to
+//! use right after the trim. The working set is a buffer this process
+//! keeps resident, standing in for the foreground app that has to page its
+//! memory back in. The report can be saved as JSON to compare versions and
+//! machines.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::platform::{MemoryOptimizer, MemoryStatus};
+
+/// Size of the latency probe's working set
+const PROBE_MB: usize = 64;
+
+const PAGE_SIZE: usize = 4096;
+
+/// Time between memory samples while watching the refault
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Memory that stands in for a foreground app's working set
+struct LatencyProbe {
+    pages: Vec<u8>,
+}
+
+impl LatencyProbe {
+    fn new(mb: usize) -> Self {
+        let mut probe = Self { pages: vec![0; mb * 1024 * 1024] };
+        probe.touch();
+        probe
+    }
+
+    /// Milliseconds to write one byte in every page
+    fn touch(&mut self) -> f64 {
+        let start = Instant::now();
+        for i in (0..self.pages.len()).step_by(PAGE_SIZE) {
+            self.pages[i] = self.pages[i].wrapping_add(1);
+        }
+        std::hint::black_box(&self.pages);
+        start.elapsed().as_secs_f64() * 1000.0
+    }
+
+    /// Fastest of a few touches, once everything is resident
+    fn resident_ms(&mut self) -> f64 {
+        (0..3).map(|_| self.touch()).fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// What one optimization did
+#[derive(Debug, Clone, Serialize)]
+pub struct RealRun {
+    pub mode: &'static str,
+    pub before_available_mb: f64,
+    pub after_available_mb: f64,
+    /// Change in available memory measured by the OS
+    pub freed_mb: f64,
+    /// What the optimizer reported freeing
+    pub reported_freed_mb: f64,
+    pub processes_affected: usize,
+    pub duration_ms: u64,
+    /// Seconds until half of the freed memory was in use again; `None` if
+    /// that didn't happen within the watch window
+    pub refault_half_secs: Option<f64>,
+    /// Share of the freed memory in use again at the end of the window
+    pub refaulted_percent: f64,
+    /// Probe touch time before the optimization, right after it and at the
+    /// end of the window
+    pub latency_before_ms: f64,
+    pub latency_after_ms: f64,
+    pub latency_settled_ms: f64,
+}
+
+/// Machine and version a report came from
+#[derive(Debug, Clone, Serialize)]
+pub struct RealSystem {
+    pub version: String,
+    pub platform: String,
+    pub os: String,
+    pub cpus: usize,
+    pub total_mb: f64,
+    pub elevated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RealBenchReport {
+    pub created_ms: u64,
+    pub system: RealSystem,
+    pub refault_secs: u64,
+    pub probe_mb: usize,
+    pub runs: Vec<RealRun>,
+}
+
+/// Time until half of `freed_mb` is in use again, and the share in use at
+/// the last sample, from `(seconds, available_mb)` samples after the
+/// optimization left `after_mb` available
+pub fn refault_stats(after_mb: f64, freed_mb: f64, samples: &[(f64, f64)]) -> (Option<f64>, f64) {
+    if freed_mb <= 0.0 {
+        return (None, 0.0);
+    }
+    let half = samples.iter().find(|(_, available)| after_mb - available >= freed_mb / 2.0).map(|(secs, _)| *secs);
+    let last = samples.last().map_or(after_mb, |(_, available)| *available);
+    let refaulted = ((after_mb - last) / freed_mb * 100.0).clamp(0.0, 100.0);
+    (half, refaulted)
+}
+
+/// Runs the efficacy sequence against the live system
+pub struct RealBenchRunner {
+    refault_secs: u64,
+    progress: bool,
+}
+
+impl RealBenchRunner {
+    pub fn new(refault_secs: u64) -> Self {
+        Self { refault_secs, progress: true }
+    }
+
+    /// Print progress while running (on by default)
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn report(&self, line: &str) {
+        if self.progress {
+            println!("{}", line);
+        }
+    }
+
+    pub fn run(&self, optimizer: &dyn MemoryOptimizer) -> Result<RealBenchReport, String> {
+        let status = optimizer.get_memory_status()?;
+        let system = RealSystem {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            platform: optimizer.platform_name().to_string(),
+            os: sysinfo::System::long_os_version().unwrap_or_else(|| std::env::consts::OS.to_string()),
+            cpus: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            total_mb: status.total_physical_mb,
+            elevated: optimizer.has_elevated_privileges(),
+        };
+        if !system.elevated {
+            self.report("⚠️  Not elevated: optimizations are limited, so expect smaller numbers\n");
+        }
+
+        let mut probe = LatencyProbe::new(PROBE_MB);
+        let mut runs = Vec::new();
+        for (mode, aggressive) in [("standard", false), ("aggressive", true)] {
+            self.report(&format!("📊 {} optimization (watching for {}s)...", mode, self.refault_secs));
+            let run = self.run_once(optimizer, &mut probe, mode, aggressive)?;
+            self.report(&format!(
+                "   Freed {:.0} MB, probe {:.1} → {:.1} ms",
+                run.freed_mb, run.latency_before_ms, run.latency_after_ms
+            ));
+            runs.push(run);
+        }
+
+        Ok(RealBenchReport {
+            created_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+            system,
+            refault_secs: self.refault_secs,
+            probe_mb: PROBE_MB,
+            runs,
+        })
+    }
+
+    fn run_once(
+        &self,
+        optimizer: &dyn MemoryOptimizer,
+        probe: &mut LatencyProbe,
+        mode: &'static str,
+        aggressive: bool,
+    ) -> Result<RealRun, String> {
+        let latency_before_ms = probe.resident_ms();
+        let before = optimizer.get_memory_status()?;
+        let result = optimizer.optimize(aggressive)?;
+        let after = optimizer.get_memory_status()?;
+        let latency_after_ms = probe.touch();
+        let freed_mb = after.available_physical_mb - before.available_physical_mb;
+
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        while started.elapsed() < Duration::from_secs(self.refault_secs) {
+            std::thread::sleep(SAMPLE_INTERVAL);
+            if let Ok(MemoryStatus { available_physical_mb, .. }) = optimizer.get_memory_status() {
+                samples.push((started.elapsed().as_secs_f64(), available_physical_mb));
+            }
+        }
+        let (refault_half_secs, refaulted_percent) = refault_stats(after.available_physical_mb, freed_mb, &samples);
+
+        Ok(RealRun {
+            mode,
+            before_available_mb: before.available_physical_mb,
+            after_available_mb: after.available_physical_mb,
+            freed_mb,
+            reported_freed_mb: result.freed_mb,
+            processes_affected: result.processes_affected,
+            duration_ms: result.duration_ms,
+            refault_half_secs,
+            refaulted_percent,
+            latency_before_ms,
+            latency_after_ms,
+            latency_settled_ms: probe.resident_ms(),
+        })
+    }
+}
+
+/// Run with the platform's optimizer and print the report as a table or JSON
+pub fn run_and_print(refault_secs: u64, json: bool) -> Result<(), String> {
+    let optimizer = crate::platform::create_optimizer();
+    let report = RealBenchRunner::new(refault_secs).with_progress(!json).run(optimizer.as_ref())?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?);
+    } else {
+        report.print();
+    }
+    Ok(())
+}
+
+impl RealBenchReport {
+    pub fn print(&self) {
+        let s = &self.system;
+        println!("\n╔══════════════════════════════════════════════════════════════╗");
+        println!("║           Real-World Optimization Efficacy Report            ║");
+        println!("╚══════════════════════════════════════════════════════════════╝\n");
+        println!("  Version:   ruvector-memopt {}", s.version);
+        println!("  System:    {} ({}), {} CPUs, {:.0} MB RAM", s.os, s.platform, s.cpus, s.total_mb);
+        println!("  Elevated:  {}", if s.elevated { "yes" } else { "no" });
+        println!("  Window:    {}s after each run, {} MB latency probe\n", self.refault_secs, self.probe_mb);
+
+        println!("  {:<22} {:>12} {:>12}", "", "Standard", "Aggressive");
+        let row = |label: &str, value: &dyn Fn(&RealRun) -> String| {
+            let cells: Vec<String> = self.runs.iter().map(value).collect();
+            println!("  {:<22} {:>12} {:>12}", label, cells.first().map_or("-", |c| c), cells.get(1).map_or("-", |c| c));
+        };
+        row("Freed (measured)", &|r| format!("{:.0} MB", r.freed_mb));
+        row("Freed (reported)", &|r| format!("{:.0} MB", r.reported_freed_mb));
+        row("Processes affected", &|r| r.processes_affected.to_string());
+        row("Duration", &|r| format!("{} ms", r.duration_ms));
+        row("Half refaulted after", &|r| r.refault_half_secs.map_or(format!(">{}s", self.refault_secs), |t| format!("{:.0}s", t)));
+        row("Refaulted at end", &|r| format!("{:.0}%", r.refaulted_percent));
+        row("Probe before", &|r| format!("{:.1} ms", r.latency_before_ms));
+        row("Probe right after", &|r| format!("{:.1} ms", r.latency_after_ms));
+        row("Probe settled", &|r| format!("{:.1} ms", r.latency_settled_ms));
+        println!();
+        println!("  The probe is the time to touch a {} MB working set; the jump right", self.probe_mb);
+        println!("  after a run is what a foreground app pays to page its memory back in.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refault_stats() {
+        // 1000 MB freed, leaving 5000 MB available; usage climbs back 100 MB/s
+        let samples: Vec<(f64, f64)> = (1..=8).map(|s| (s as f64, 5000.0 - 100.0 * s as f64)).collect();
+        let (half, refaulted) = refault_stats(5000.0, 1000.0, &samples);
+        assert_eq!(half, Some(5.0));
+        assert!((refaulted - 80.0).abs() < 1e-9);
+
+        let steady = [(1.0, 5000.0), (2.0, 5010.0)];
+        assert_eq!(refault_stats(5000.0, 1000.0, &steady), (None, 0.0));
+        assert_eq!(refault_stats(5000.0, 0.0, &samples), (None, 0.0));
+    }
+
+    #[test]
+    fn test_probe_touches_every_page() {
+        let mut probe = LatencyProbe::new(1);
+        let before: u32 = probe.pages.iter().map(|&b| b as u32).sum();
+        assert!(probe.touch() >= 0.0);
+        let after: u32 = probe.pages.iter().map(|&b| b as u32).sum();
+        assert_eq!(after - before, (1024 * 1024 / PAGE_SIZE) as u32);
+    }
+}
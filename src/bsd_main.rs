@@ -0,0 +1,344 @@
+//! RuVector Memory Optimizer - FreeBSD/OpenBSD CLI
+//!
+//! Memory status, ZFS ARC shrinking and an rc.d-managed daemon for BSD hosts.
+
+#[cfg(not(any(target_os = "freebsd", target_os = "openbsd")))]
+fn main() {
+    eprintln!("This binary is FreeBSD/OpenBSD-only.");
+    eprintln!("On Linux use ruvector-memopt-linux, on macOS use ruvector-memopt-macos.");
+    std::process::exit(1);
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+mod bsd_impl {
+    use clap::{Parser, Subcommand};
+    use std::time::{Duration, Instant};
+    use tracing::{error, info, Level};
+    use tracing_subscriber::FmtSubscriber;
+
+    use ruvector_memopt::core::config::{ConfigWatcher, OptimizerConfig};
+    use ruvector_memopt::core::history::{self, HistoryRecord, OptimizationTrigger};
+    use ruvector_memopt::core::ipc::{self, DaemonControl, IpcClient, OptimizeReport, Pause};
+    use ruvector_memopt::platform::bsd::{BsdDaemonService, BsdMemoryOptimizer};
+    use ruvector_memopt::security::audit::AuditLog;
+
+    #[derive(Parser)]
+    #[command(name = "ruvector-memopt-bsd")]
+    #[command(about = "Memory optimizer for FreeBSD and OpenBSD", long_about = None)]
+    struct Cli {
+        #[command(subcommand)]
+        command: Commands,
+
+        /// Config file to use instead of the shared config.toml
+        #[arg(long, global = true, value_name = "PATH")]
+        config: Option<std::path::PathBuf>,
+
+        /// Override a config key for this run (repeatable)
+        #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+        overrides: Vec<String>,
+    }
+
+    #[derive(Subcommand)]
+    enum Commands {
+        /// Show current memory status
+        Status,
+
+        /// Run one-time optimization (root)
+        Optimize {
+            /// Shrink the ZFS ARC further
+            #[arg(short, long)]
+            aggressive: bool,
+
+            /// Optimize in this process even when a daemon is running
+            #[arg(long)]
+            local: bool,
+        },
+
+        /// Show the largest processes by resident memory
+        Processes {
+            #[arg(short, long, default_value = "15")]
+            top: usize,
+        },
+
+        /// Start continuous optimization daemon
+        Daemon {
+            #[arg(short, long, default_value = "60")]
+            interval: u64,
+        },
+
+        /// Pause the running daemon's automatic optimization
+        Pause {
+            /// How long, like `2h`, `30m` or `1h30m`, or until a time like
+            /// `17:00`; omit to pause until `resume`
+            when: Option<String>,
+        },
+
+        /// Resume the running daemon after `pause`
+        Resume,
+
+        /// Print the running daemon's events as they happen
+        Events,
+
+        /// Manage the rc.d service (root)
+        Service {
+            #[command(subcommand)]
+            action: ServiceAction,
+        },
+
+        /// Show the signed audit log of actions taken on other processes
+        Audit {
+            #[command(subcommand)]
+            action: AuditCommand,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum AuditCommand {
+        /// Print the most recent records, flagging any with a bad signature
+        Tail {
+            /// Number of records to show
+            #[arg(short = 'n', long, default_value = "20")]
+            lines: usize,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum ServiceAction {
+        /// Install, enable and start the rc.d script
+        Install {
+            #[arg(short, long, default_value = "60")]
+            interval: u64,
+        },
+        /// Stop, disable and remove the rc.d script
+        Uninstall,
+        /// Show service state
+        Status,
+    }
+
+    pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+        let subscriber = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_target(false)
+            .finish();
+        tracing::subscriber::set_global_default(subscriber)?;
+
+        let cli = Cli::parse();
+        if let Some(path) = cli.config.clone() {
+            ruvector_memopt::core::config::set_config_path(path);
+        }
+        ruvector_memopt::core::config::set_cli_overrides(&cli.overrides)?;
+
+        match cli.command {
+            Commands::Status => {
+                let status = BsdMemoryOptimizer::get_memory_status()?;
+                println!("Memory Status:");
+                println!("  Total:     {:.0} MB", status.total_physical_mb);
+                println!("  Available: {:.0} MB", status.available_physical_mb);
+                println!("  Used:      {:.0} MB", status.used_physical_mb());
+                if status.arc_mb > 0.0 {
+                    println!("  ZFS ARC:   {:.0} MB", status.arc_mb);
+                }
+                println!("  Load:      {}%", status.memory_load_percent);
+                println!("  Swap:      {:.0}/{:.0} MB",
+                    status.total_swap_mb - status.available_swap_mb,
+                    status.total_swap_mb);
+                println!("  Pressure:  {}",
+                    if status.is_critical() { "CRITICAL" }
+                    else if status.is_high_pressure() { "HIGH" }
+                    else { "Normal" }
+                );
+                if let Some(daemon) = IpcClient::connect().and_then(|mut client| client.status().ok()) {
+                    println!();
+                    daemon.print();
+                }
+            }
+
+            Commands::Optimize { aggressive, local } => {
+                // A running daemon applies its own rate limit
+                if !local {
+                    if let Some(mut daemon) = IpcClient::connect() {
+                        match daemon.optimize(aggressive) {
+                            Ok(report) => report.print(),
+                            Err(e) => println!("Daemon did not optimize: {}", e),
+                        }
+                        return Ok(());
+                    }
+                }
+                let optimizer = BsdMemoryOptimizer::new();
+                let status = BsdMemoryOptimizer::get_memory_status()?;
+                let result = optimizer.optimize(aggressive)?;
+                history::record(
+                    HistoryRecord::now(
+                        OptimizationTrigger::Manual,
+                        aggressive,
+                        result.freed_mb,
+                        result.processes_affected,
+                        result.duration_ms,
+                    )
+                    .with_memory_load(status.memory_load_percent),
+                );
+
+                println!("Optimization complete:");
+                println!("  Method:    {:?}", result.method);
+                println!("  Freed:     {:.1} MB", result.freed_mb);
+                println!("  Before:    {:.1} MB available", result.before_available_mb);
+                println!("  After:     {:.1} MB available", result.after_available_mb);
+                println!("  Duration:  {} ms", result.duration_ms);
+            }
+
+            Commands::Processes { top } => {
+                println!("{:>7}  {:>10}  Process", "PID", "RSS");
+                for process in BsdMemoryOptimizer::top_processes(top)? {
+                    println!("{:>7}  {:>7.0} MB  {}", process.pid, process.rss_mb, process.name);
+                }
+            }
+
+            Commands::Daemon { interval } => {
+                info!("Starting optimization daemon (interval: {}s)", interval);
+                let mut config = OptimizerConfig::load_or_default();
+                let mut config_watcher = ConfigWatcher::new();
+                let optimizer = BsdMemoryOptimizer::new();
+                let mut last_run: Option<Instant> = None;
+
+                let (wake_tx, wake_rx) = std::sync::mpsc::channel();
+                let control = DaemonControl::new(config.clone(), move || {
+                    let _ = wake_tx.send(());
+                });
+                match ipc::listen(control.clone()) {
+                    Ok(path) => info!("Listening for clients on {}", path.display()),
+                    Err(e) => tracing::warn!("Control channel unavailable: {}", e),
+                }
+
+                loop {
+                    // A client's optimize request cuts the wait short
+                    let _ = wake_rx.recv_timeout(Duration::from_secs(interval));
+
+                    if let Some(reloaded) = config_watcher.reload(&config) {
+                        config = reloaded;
+                        control.set_config(config.clone());
+                    }
+
+                    let min_interval = Duration::from_secs(config.min_interval_secs);
+                    for call in control.take_calls() {
+                        let result = match last_run.map(|t| t.elapsed()) {
+                            Some(elapsed) if elapsed < min_interval => Err(format!(
+                                "Rate limited: next optimization allowed in {}s",
+                                (min_interval - elapsed).as_secs()
+                            )),
+                            _ => {
+                                last_run = Some(Instant::now());
+                                optimize_once(&optimizer, call.aggressive, OptimizationTrigger::Manual)
+                            }
+                        };
+                        control.complete(call, result);
+                    }
+
+                    let status = match BsdMemoryOptimizer::get_memory_status() {
+                        Ok(status) => status,
+                        Err(e) => {
+                            error!("Failed to read memory status: {}", e);
+                            continue;
+                        }
+                    };
+                    if status.memory_load_percent < config.pressure_threshold
+                        || last_run.is_some_and(|t| t.elapsed() < min_interval)
+                        || !config.allows_auto_optimize(status.memory_load_percent)
+                        || control.is_paused()
+                    {
+                        continue;
+                    }
+
+                    let aggressive = status.memory_load_percent >= config.critical_threshold;
+                    last_run = Some(Instant::now());
+                    match optimize_once(&optimizer, aggressive, OptimizationTrigger::Auto) {
+                        Ok(report) => control.record(report),
+                        Err(e) => error!("Optimization failed: {}", e),
+                    }
+                }
+            }
+
+            Commands::Pause { when } => {
+                let pause = when.as_deref().map_or(Ok(Pause::until_resumed()), Pause::parse);
+                match pause.and_then(|pause| IpcClient::connect_required()?.pause(pause).map(|()| pause)) {
+                    Ok(pause) => println!("Daemon paused {}", pause),
+                    Err(e) => println!("{}", e),
+                }
+            }
+
+            Commands::Resume => match IpcClient::connect_required().and_then(|mut d| d.resume()) {
+                Ok(()) => println!("Daemon resumed"),
+                Err(e) => println!("{}", e),
+            },
+
+            Commands::Events => match IpcClient::connect_required().and_then(IpcClient::subscribe) {
+                Ok(events) => {
+                    for event in events {
+                        println!("{}  {}", chrono::Local::now().format("%H:%M:%S"), event);
+                    }
+                    println!("Daemon stopped");
+                }
+                Err(e) => println!("{}", e),
+            },
+
+            Commands::Service { action } => {
+                let service = BsdDaemonService::new();
+                match action {
+                    ServiceAction::Install { interval } => {
+                        println!("{}", service.install(interval)?);
+                    }
+                    ServiceAction::Uninstall => {
+                        println!("{}", service.uninstall()?);
+                    }
+                    ServiceAction::Status => {
+                        let status = service.status();
+                        println!("Service Status:");
+                        println!("  Script:    {:?}", status.script_path);
+                        println!("  Installed: {}", if status.installed { "Yes" } else { "No" });
+                        println!("  Enabled:   {}", if status.enabled { "Yes" } else { "No" });
+                        println!("  Active:    {}", if status.running { "Running" } else { "Stopped" });
+                    }
+                }
+            }
+
+            Commands::Audit { action } => match action {
+                AuditCommand::Tail { lines } => {
+                    let log = AuditLog::open_default()?;
+                    AuditLog::print_tail(&log.tail(lines)?);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Optimize once and record it in the history
+    fn optimize_once(
+        optimizer: &BsdMemoryOptimizer,
+        aggressive: bool,
+        trigger: OptimizationTrigger,
+    ) -> Result<OptimizeReport, String> {
+        let load = BsdMemoryOptimizer::get_memory_status().map(|s| s.memory_load_percent).ok();
+        let result = optimizer.optimize(aggressive)?;
+        let mut record = HistoryRecord::now(
+            trigger,
+            aggressive,
+            result.freed_mb,
+            result.processes_affected,
+            result.duration_ms,
+        );
+        record.memory_load_percent = load;
+        history::record(record);
+        Ok(OptimizeReport {
+            trigger,
+            aggressive,
+            freed_mb: result.freed_mb,
+            processes: result.processes_affected,
+            duration_ms: result.duration_ms,
+        })
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    bsd_impl::run()
+}
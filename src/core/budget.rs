@@ -0,0 +1,217 @@
+//! Resource budget for the optimizer's own process
+//!
+//! The daemon and the service sample their own resident memory and CPU use
+//! on every pass of their loop. Over `self_max_rss_mb` they drop the oldest
+//! learned patterns and run metrics. Over `self_max_cpu_percent` they stretch
+//! their interval until usage is back under budget. The latest sample is
+//! kept for `status` and the dashboard, see [`latest`].
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tracing::{info, warn};
+
+use super::config::OptimizerConfig;
+
+pub const DEFAULT_MAX_RSS_MB: u64 = 150;
+pub const DEFAULT_MAX_CPU_PERCENT: f64 = 2.0;
+/// Learned patterns kept however far over budget the process is
+pub const MIN_PATTERNS: usize = 64;
+/// Longest the loop interval is stretched while CPU is over budget
+const MAX_STRETCH: u32 = 8;
+
+static LATEST: Mutex<Option<Footprint>> = Mutex::new(None);
+
+/// Memory and CPU use of the optimizer process, against its budget
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Footprint {
+    pub rss_mb: f64,
+    /// Share of all cores since the previous sample, as Task Manager shows it
+    pub cpu_percent: f64,
+    /// 0 for no cap
+    pub max_rss_mb: u64,
+    /// 0 for no cap
+    pub max_cpu_percent: f64,
+    /// Times caches were trimmed to get back under `max_rss_mb`
+    pub trims: u64,
+    /// How many times the normal interval the loop is sleeping
+    pub stretch: u32,
+}
+
+impl Footprint {
+    pub fn over_memory(&self) -> bool {
+        self.max_rss_mb > 0 && self.rss_mb > self.max_rss_mb as f64
+    }
+
+    pub fn over_cpu(&self) -> bool {
+        self.max_cpu_percent > 0.0 && self.cpu_percent > self.max_cpu_percent
+    }
+
+    /// One line such as `42 MB, 0.3% CPU (budget 150 MB, 2%)`
+    pub fn summary(&self) -> String {
+        let mut caps = Vec::new();
+        if self.max_rss_mb > 0 {
+            caps.push(format!("{} MB", self.max_rss_mb));
+        }
+        if self.max_cpu_percent > 0.0 {
+            caps.push(format!("{}%", self.max_cpu_percent));
+        }
+        let budget = if caps.is_empty() { "no budget".to_string() } else { format!("budget {}", caps.join(", ")) };
+        let mut line = format!("{:.0} MB, {:.1}% CPU ({})", self.rss_mb, self.cpu_percent, budget);
+        if self.trims > 0 {
+            line.push_str(&format!(", {} cache trims", self.trims));
+        }
+        if self.stretch > 1 {
+            line.push_str(&format!(", slowed {}x", self.stretch));
+        }
+        line
+    }
+}
+
+/// Samples this process once per loop and tracks how far it is over budget
+pub struct SelfMonitor {
+    system: System,
+    pid: Pid,
+    cores: f64,
+    max_rss_mb: u64,
+    max_cpu_percent: f64,
+    trims: u64,
+    stretch: u32,
+}
+
+impl SelfMonitor {
+    pub fn new(config: &OptimizerConfig) -> Self {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            system: System::new(),
+            pid: Pid::from_u32(std::process::id()),
+            cores: cores as f64,
+            max_rss_mb: config.self_max_rss_mb,
+            max_cpu_percent: config.self_max_cpu_percent,
+            trims: 0,
+            stretch: 1,
+        }
+    }
+
+    pub fn set_config(&mut self, config: &OptimizerConfig) {
+        self.max_rss_mb = config.self_max_rss_mb;
+        self.max_cpu_percent = config.self_max_cpu_percent;
+    }
+
+    /// Sample this process and publish the result for [`latest`]
+    ///
+    /// CPU use is averaged since the previous call, so the first sample
+    /// reads 0%.
+    pub fn sample(&mut self) -> Option<Footprint> {
+        self.system.refresh_processes(ProcessesToUpdate::Some(&[self.pid]), true);
+        let process = self.system.process(self.pid)?;
+        let mut footprint = Footprint {
+            rss_mb: process.memory() as f64 / (1024.0 * 1024.0),
+            cpu_percent: process.cpu_usage() as f64 / self.cores,
+            max_rss_mb: self.max_rss_mb,
+            max_cpu_percent: self.max_cpu_percent,
+            trims: self.trims,
+            stretch: self.stretch,
+        };
+
+        let stretch = next_stretch(self.stretch, footprint.over_cpu());
+        if stretch > self.stretch {
+            info!(
+                "Using {:.1}% CPU, over the {}% budget; slowing the loop down {}x",
+                footprint.cpu_percent, self.max_cpu_percent, stretch
+            );
+        }
+        self.stretch = stretch;
+        footprint.stretch = stretch;
+        if footprint.over_memory() {
+            warn!("Using {:.0} MB, over the {} MB budget; trimming caches", footprint.rss_mb, self.max_rss_mb);
+        }
+        *LATEST.lock().unwrap_or_else(|e| e.into_inner()) = Some(footprint);
+        Some(footprint)
+    }
+
+    /// Count a cache trim done because [`Footprint::over_memory`] was set
+    pub fn trimmed(&mut self) {
+        self.trims += 1;
+        if let Some(footprint) = LATEST.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            footprint.trims = self.trims;
+        }
+    }
+
+    /// `interval` stretched while CPU use is over budget
+    pub fn stretch(&self, interval: Duration) -> Duration {
+        interval * self.stretch
+    }
+}
+
+/// Latest sample taken by a [`SelfMonitor`] in this process
+pub fn latest() -> Option<Footprint> {
+    *LATEST.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// How many of `len` learned patterns to keep after going over the memory cap
+pub fn keep_after_trim(len: usize) -> usize {
+    (len / 2).max(MIN_PATTERNS).min(len)
+}
+
+/// Double the stretch while over the CPU budget, halve it back once under
+fn next_stretch(stretch: u32, over_cpu: bool) -> u32 {
+    if over_cpu {
+        (stretch * 2).min(MAX_STRETCH)
+    } else {
+        (stretch / 2).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn footprint(rss_mb: f64, cpu_percent: f64) -> Footprint {
+        Footprint {
+            rss_mb,
+            cpu_percent,
+            max_rss_mb: DEFAULT_MAX_RSS_MB,
+            max_cpu_percent: DEFAULT_MAX_CPU_PERCENT,
+            trims: 0,
+            stretch: 1,
+        }
+    }
+
+    #[test]
+    fn zero_caps_are_never_exceeded() {
+        assert!(footprint(200.0, 5.0).over_memory());
+        assert!(footprint(200.0, 5.0).over_cpu());
+        assert!(!footprint(100.0, 1.0).over_memory());
+        assert!(!footprint(100.0, 1.0).over_cpu());
+        assert_eq!(footprint(42.0, 0.3).summary(), "42 MB, 0.3% CPU (budget 150 MB, 2%)");
+
+        let uncapped = Footprint { max_rss_mb: 0, max_cpu_percent: 0.0, ..footprint(4096.0, 90.0) };
+        assert!(!uncapped.over_memory());
+        assert!(!uncapped.over_cpu());
+        assert_eq!(uncapped.summary(), "4096 MB, 90.0% CPU (no budget)");
+    }
+
+    #[test]
+    fn stretch_backs_off_and_recovers() {
+        let mut stretch = 1;
+        for expected in [2, 4, 8, 8] {
+            stretch = next_stretch(stretch, true);
+            assert_eq!(stretch, expected);
+        }
+        for expected in [4, 2, 1, 1] {
+            stretch = next_stretch(stretch, false);
+            assert_eq!(stretch, expected);
+        }
+    }
+
+    #[test]
+    fn trims_halve_down_to_the_floor() {
+        assert_eq!(keep_after_trim(1000), 500);
+        assert_eq!(keep_after_trim(100), MIN_PATTERNS);
+        assert_eq!(keep_after_trim(10), 10);
+        assert_eq!(keep_after_trim(0), 0);
+    }
+}
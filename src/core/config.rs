@@ -1,10 +1,29 @@
 //! Configuration for the memory optimizer
+//!
+//! One `config.toml` in [`crate::features::config_dir`] is shared by the CLI,
+//! tray, daemon and service. [`OptimizerConfig::load_or_default`] layers it:
+//! built-in defaults, then the file, then `RUVECTOR_<KEY>` environment
+//! variables, then `--set KEY=VALUE` flags. Long-running processes pick up
+//! edits to the file, or a SIGHUP, through [`ConfigWatcher`].
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use crate::features::report::ReportFormat;
+use crate::features::throttle::ThrottleService;
+use crate::monitor::alerts::AlertsConfig;
+use crate::monitor::mqtt::MqttConfig;
 
 /// Main optimizer configuration
+///
+/// Keys missing from the file take their default, so a config can list only
+/// what it changes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OptimizerConfig {
     /// Memory pressure threshold to trigger optimization (0-100)
     pub pressure_threshold: u32,
@@ -38,6 +57,215 @@ pub struct OptimizerConfig {
     
     /// Benchmark mode - log detailed metrics
     pub benchmark_mode: bool,
+
+    /// Memory budget for warming a predicted app's files (MB)
+    #[serde(default = "default_prefetch_budget_mb")]
+    pub prefetch_budget_mb: u64,
+
+    /// Growth rate at which the background leak monitor alerts (MB/hour)
+    #[serde(default = "default_leak_alert_mb_per_hour")]
+    pub leak_alert_mb_per_hour: f64,
+
+    /// How long a process must keep growing at that rate before an alert
+    #[serde(default = "default_leak_alert_after_minutes")]
+    pub leak_alert_after_minutes: u64,
+
+    /// Electron apps (`vscode`, `slack`, ...) that may be closed and relaunched
+    #[serde(default)]
+    pub electron_restart_apps: Vec<String>,
+
+    /// Minutes without focus, input, CPU or audio before an app counts as idle
+    #[serde(default = "default_app_idle_minutes")]
+    pub app_idle_minutes: u64,
+
+    /// PSI `full avg10` (percent) at which the Linux low-memory killer may act
+    #[serde(default = "default_oom_psi_full_avg10")]
+    pub oom_psi_full_avg10: f64,
+
+    /// Available memory (percent of RAM) at or below which it may act
+    #[serde(default = "default_oom_min_available_percent")]
+    pub oom_min_available_percent: f64,
+
+    /// Process names the low-memory killer never terminates
+    #[serde(default)]
+    pub oom_never_kill: Vec<String>,
+
+    /// Let the low-memory killer fall back to any unprotected process when
+    /// no expendable or background one is running
+    #[serde(default)]
+    pub oom_kill_any: bool,
+
+    /// Minutes after an optimization during which `undo` can revert it
+    #[serde(default = "default_undo_window_minutes")]
+    pub undo_window_minutes: u64,
+
+    /// Daily `HH:MM-HH:MM` window in which automatic optimization only runs
+    /// at critical pressure
+    #[serde(default)]
+    pub quiet_hours: Option<String>,
+
+    /// Let critical pressure through in quiet hours; `false` makes the
+    /// window monitoring only
+    #[serde(default = "default_true")]
+    pub quiet_hours_critical: bool,
+
+    /// Hold routine notifications while the OS's do-not-disturb, a call or
+    /// a game is on
+    #[serde(default = "default_true")]
+    pub notify_respect_dnd: bool,
+
+    /// Routine notifications shown per minute before the rest are batched;
+    /// 0 for no limit
+    #[serde(default = "default_notify_per_minute")]
+    pub notify_per_minute: u32,
+
+    /// Trim loosely tied process clusters together in automatic runs (Windows)
+    #[serde(default)]
+    pub cluster_trim: ClusterTrim,
+
+    /// z-score at which memory load, the process count or GPU memory counts
+    /// as unusual for the time of day
+    #[serde(default = "default_anomaly_threshold")]
+    pub anomaly_threshold: f64,
+
+    /// Show anomalies as notifications, not only on the timeline
+    #[serde(default)]
+    pub anomaly_notify: bool,
+
+    /// Optimize ahead of time when memory is forecast to reach the critical
+    /// threshold within this many minutes; 0 turns it off
+    #[serde(default = "default_forecast_horizon_minutes")]
+    pub forecast_horizon_minutes: u64,
+
+    /// Background services Game Mode and the build booster may pause or
+    /// limit (Windows): `windows_update`, `windows_search`, `defender_scans`
+    #[serde(default)]
+    pub throttle_services: Vec<ThrottleService>,
+
+    /// Globs for files `cleanup` keeps, matched against the file name and
+    /// its path below the temp or cache directory
+    #[serde(default)]
+    pub cleanup_exclude: Vec<String>,
+
+    /// Where scheduled reports are written; `reports` in the config
+    /// directory when unset
+    #[serde(default)]
+    pub report_dir: Option<PathBuf>,
+
+    /// `html` or `markdown` for scheduled reports
+    #[serde(default)]
+    pub report_format: ReportFormat,
+
+    /// Write a signed audit record for every action on another process
+    #[serde(default = "default_true")]
+    pub audit_enabled: bool,
+
+    /// Also copy audit records to the Windows Event Log or journald
+    #[serde(default)]
+    pub audit_system_log: bool,
+
+    /// Run the daemon/service as a privileged broker plus an unprivileged worker
+    #[serde(default)]
+    pub least_privilege: bool,
+
+    /// Account the worker switches to in least-privilege mode (Unix)
+    #[serde(default = "default_worker_user")]
+    pub worker_user: String,
+
+    /// `http://` URL of a dashboard server in hub mode that the daemon or
+    /// service reports to
+    #[serde(default)]
+    pub fleet_hub: Option<String>,
+
+    /// API token of the hub
+    #[serde(default)]
+    pub fleet_token: Option<String>,
+
+    /// Seconds between reports to the hub
+    #[serde(default = "default_fleet_push_secs")]
+    pub fleet_push_secs: u64,
+
+    /// MQTT broker and topics for home automation
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+
+    /// Webhook, Slack, Discord and email alerts on critical events
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+
+    /// Resident memory the daemon or service may use before it drops the
+    /// oldest learned patterns; 0 for no cap
+    #[serde(default = "default_self_max_rss_mb")]
+    pub self_max_rss_mb: u64,
+
+    /// Share of all CPU time the daemon or service may use before it slows
+    /// its loop down; 0 for no cap
+    #[serde(default = "default_self_max_cpu_percent")]
+    pub self_max_cpu_percent: f64,
+
+    /// Tray-only preferences (theme, AI mode, ...), kept as-is for the tray
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tray: Option<toml::Table>,
+}
+
+fn default_prefetch_budget_mb() -> u64 {
+    crate::features::prefetch::DEFAULT_BUDGET_MB
+}
+
+fn default_leak_alert_mb_per_hour() -> f64 {
+    crate::features::leakwatch::DEFAULT_ALERT_MB_PER_HOUR
+}
+
+fn default_leak_alert_after_minutes() -> u64 {
+    crate::features::leakwatch::DEFAULT_ALERT_AFTER_MINUTES
+}
+
+fn default_app_idle_minutes() -> u64 {
+    crate::apps::idle::DEFAULT_IDLE_AFTER_MINUTES
+}
+
+fn default_oom_psi_full_avg10() -> f64 {
+    10.0
+}
+
+fn default_oom_min_available_percent() -> f64 {
+    5.0
+}
+
+fn default_undo_window_minutes() -> u64 {
+    crate::core::undo::DEFAULT_UNDO_WINDOW_MINUTES
+}
+
+fn default_notify_per_minute() -> u32 {
+    crate::monitor::notify::DEFAULT_PER_MINUTE
+}
+
+fn default_anomaly_threshold() -> f64 {
+    crate::algorithms::anomaly::DEFAULT_THRESHOLD
+}
+
+fn default_forecast_horizon_minutes() -> u64 {
+    30
+}
+
+fn default_fleet_push_secs() -> u64 {
+    crate::features::fleet::DEFAULT_PUSH_SECS
+}
+
+fn default_self_max_rss_mb() -> u64 {
+    crate::core::budget::DEFAULT_MAX_RSS_MB
+}
+
+fn default_self_max_cpu_percent() -> f64 {
+    crate::core::budget::DEFAULT_MAX_CPU_PERCENT
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_worker_user() -> String {
+    "nobody".into()
 }
 
 impl Default for OptimizerConfig {
@@ -47,7 +275,7 @@ impl Default for OptimizerConfig {
             critical_threshold: 95,
             min_interval_secs: 30,
             neural_enabled: true,
-            model_path: PathBuf::from("./data/neural"),
+            model_path: crate::features::config_dir().join("neural"),
             protected_processes: vec![
                 "System".into(),
                 "csrss.exe".into(),
@@ -60,22 +288,715 @@ impl Default for OptimizerConfig {
             learning_enabled: true,
             ewc_lambda: 0.4,
             benchmark_mode: false,
+            prefetch_budget_mb: default_prefetch_budget_mb(),
+            leak_alert_mb_per_hour: default_leak_alert_mb_per_hour(),
+            leak_alert_after_minutes: default_leak_alert_after_minutes(),
+            electron_restart_apps: Vec::new(),
+            app_idle_minutes: default_app_idle_minutes(),
+            oom_psi_full_avg10: default_oom_psi_full_avg10(),
+            oom_min_available_percent: default_oom_min_available_percent(),
+            oom_never_kill: Vec::new(),
+            oom_kill_any: false,
+            undo_window_minutes: default_undo_window_minutes(),
+            quiet_hours: None,
+            quiet_hours_critical: true,
+            notify_respect_dnd: true,
+            notify_per_minute: default_notify_per_minute(),
+            cluster_trim: ClusterTrim::Off,
+            anomaly_threshold: default_anomaly_threshold(),
+            anomaly_notify: false,
+            forecast_horizon_minutes: default_forecast_horizon_minutes(),
+            throttle_services: Vec::new(),
+            cleanup_exclude: Vec::new(),
+            report_dir: None,
+            report_format: ReportFormat::Html,
+            audit_enabled: true,
+            audit_system_log: false,
+            least_privilege: false,
+            worker_user: default_worker_user(),
+            fleet_hub: None,
+            fleet_token: None,
+            fleet_push_secs: default_fleet_push_secs(),
+            mqtt: MqttConfig::default(),
+            alerts: AlertsConfig::default(),
+            self_max_rss_mb: default_self_max_rss_mb(),
+            self_max_cpu_percent: default_self_max_cpu_percent(),
+            tray: None,
         }
     }
 }
 
+/// How automatic optimizations treat process clusters from `clusters`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClusterTrim {
+    /// Trim processes one by one, best candidates first
+    #[default]
+    Off,
+    /// Always trim loosely tied clusters together
+    On,
+    /// Alternate until the neural engine has measured both ways, then use
+    /// whichever keeps more memory reclaimed after re-faults
+    Auto,
+}
+
+/// File name of the optimizer config inside the config directory
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Prefix of environment variables that override config keys
+///
+/// `RUVECTOR_PRESSURE_THRESHOLD=85` sets `pressure_threshold`; a double
+/// underscore separates nested keys.
+pub const ENV_PREFIX: &str = "RUVECTOR_";
+
+/// Config file chosen with `--config`
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// `--set KEY=VALUE` overrides, applied on top of every load
+static CLI_OVERRIDES: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+/// Set by SIGHUP, consumed by [`ConfigWatcher::changed`]
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Use `path` instead of the default config file for the rest of the process
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+/// Apply `KEY=VALUE` pairs on top of every later load
+///
+/// Each pair is checked against the defaults first, so a typo or a value of
+/// the wrong type is reported instead of silently ignored.
+pub fn set_cli_overrides(pairs: &[String]) -> Result<(), String> {
+    let mut overrides = Vec::new();
+    let mut check = OptimizerConfig::default();
+    for pair in pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("Expected KEY=VALUE, got '{}'", pair))?;
+        let (key, value) = (key.trim(), value.trim());
+        check.set_value(key, value)?;
+        overrides.push((key.to_string(), value.to_string()));
+    }
+    let _ = CLI_OVERRIDES.set(overrides);
+    Ok(())
+}
+
+/// Config overrides taken from `RUVECTOR_*` environment variables
+///
+/// Variables that don't name a config key (such as `RUVECTOR_CONFIG_DIR`)
+/// are left to whoever reads them.
+fn env_overrides() -> Vec<(String, String)> {
+    std::env::vars()
+        .filter(|(name, _)| name != crate::features::CONFIG_DIR_ENV)
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase().replace("__", ".");
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Parse a raw override as a TOML value, treating anything else as a string
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Look up a dotted key in a TOML table
+fn get_path<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+/// Set a dotted key in a TOML table, creating intermediate tables
+fn set_path(table: &mut toml::Table, key: &str, value: toml::Value) -> Result<(), String> {
+    let (parents, last) = match key.rsplit_once('.') {
+        Some((parents, last)) => (parents.split('.').collect::<Vec<_>>(), last),
+        None => (Vec::new(), key),
+    };
+    let mut current = table;
+    for part in parents {
+        current = current
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| format!("'{}' is not a section", part))?;
+    }
+    current.insert(last.to_string(), value);
+    Ok(())
+}
+
 impl OptimizerConfig {
+    /// Config file location: `--config` if given, else the per-user config directory
+    pub fn default_path() -> PathBuf {
+        CONFIG_PATH
+            .get()
+            .cloned()
+            .unwrap_or_else(|| crate::features::config_dir().join(CONFIG_FILE))
+    }
+
+    /// Effective config: defaults, the config file, then env and `--set` overrides
+    pub fn load_or_default() -> Self {
+        let mut config = Self::load_file();
+        for (key, value) in env_overrides() {
+            if let Err(e) = config.set_value(&key, &value) {
+                tracing::debug!("Ignoring {}{}: {}", ENV_PREFIX, key.to_uppercase(), e);
+            }
+        }
+        for (key, value) in CLI_OVERRIDES.get().into_iter().flatten() {
+            if let Err(e) = config.set_value(key, value) {
+                tracing::warn!("Ignoring --set {}: {}", key, e);
+            }
+        }
+        config
+    }
+
+    /// Config file only, without env or `--set` overrides
+    ///
+    /// Use this to change and save the file, so overrides meant for one run
+    /// aren't written back.
+    pub fn load_file() -> Self {
+        let path = Self::default_path();
+        if !path.exists() {
+            return Self::default();
+        }
+        match Self::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Failed to load config from {:?}: {}. Using defaults.", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Set one (dotted) key from its text form, e.g. `pressure_threshold` to `85`
+    pub fn set_value(&mut self, key: &str, raw: &str) -> Result<(), String> {
+        let mut table = toml::Table::try_from(&*self).map_err(|e| e.to_string())?;
+        set_path(&mut table, key, parse_value(raw))?;
+        let updated: Self = table
+            .try_into()
+            .map_err(|e: toml::de::Error| format!("Invalid value for {}: {}", key, e.message()))?;
+
+        // Unknown keys are dropped on the way back in
+        let check = toml::Table::try_from(&updated).map_err(|e| e.to_string())?;
+        if get_path(&check, key).is_none() {
+            return Err(format!("Unknown config key: {}", key));
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Current value of one (dotted) key as text
+    pub fn get_value(&self, key: &str) -> Result<String, String> {
+        let table = toml::Table::try_from(self).map_err(|e| e.to_string())?;
+        match get_path(&table, key) {
+            Some(toml::Value::String(s)) => Ok(s.clone()),
+            Some(value) => Ok(value.to_string()),
+            None => Err(format!("{} is not set (or not a config key)", key)),
+        }
+    }
+
+    /// Values that parse but are out of range or contradict each other
+    pub fn problems(&self) -> Vec<String> {
+        use crate::security::validator::validate_config_value;
+
+        let percent = "a percentage from 0 to 100";
+        let checks = [
+            ("pressure_threshold", self.pressure_threshold.to_string(), percent),
+            ("critical_threshold", self.critical_threshold.to_string(), percent),
+            ("min_interval_secs", self.min_interval_secs.to_string(), "between 10 and 3600 seconds"),
+            ("ewc_lambda", self.ewc_lambda.to_string(), "between 0.0 and 1.0"),
+            ("quiet_hours", self.quiet_hours.clone().unwrap_or_default(), "a daily window like 22:00-07:00"),
+            ("anomaly_threshold", self.anomaly_threshold.to_string(), "between 2.0 and 10.0"),
+            ("fleet_hub", self.fleet_hub.clone().unwrap_or_default(), "an http:// URL like http://hub.lan:8080"),
+            ("fleet_push_secs", self.fleet_push_secs.to_string(), "between 10 and 3600 seconds"),
+            ("self_max_rss_mb", self.self_max_rss_mb.to_string(), "0 or at least 32 MB"),
+            ("self_max_cpu_percent", self.self_max_cpu_percent.to_string(), "a percentage from 0 to 100"),
+        ];
+        let mut problems: Vec<String> = checks
+            .iter()
+            .filter(|(key, value, _)| !validate_config_value(key, value))
+            .map(|(key, value, expected)| format!("{} = {} should be {}", key, value, expected))
+            .collect();
+        if self.pressure_threshold >= self.critical_threshold {
+            problems.push(format!(
+                "pressure_threshold ({}) should be below critical_threshold ({})",
+                self.pressure_threshold, self.critical_threshold
+            ));
+        }
+        problems.extend(self.mqtt.problems());
+        problems.extend(self.alerts.problems());
+        problems
+    }
+
+    /// Keys whose values differ from `other`, as `key: old -> new`
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let (before, after) = (flatten(self), flatten(other));
+        let keys: std::collections::BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        keys.into_iter()
+            .filter(|key| before.get(*key) != after.get(*key))
+            .map(|key| {
+                let show = |v: Option<&String>| v.cloned().unwrap_or_else(|| "(unset)".into());
+                format!("{}: {} -> {}", key, show(before.get(key)), show(after.get(key)))
+            })
+            .collect()
+    }
+
+    /// This config with tokens, passwords and webhook URLs masked, for display
+    pub fn redacted(mut self) -> Self {
+        for secret in [&mut self.fleet_token, &mut self.mqtt.password] {
+            if secret.is_some() {
+                *secret = Some("(set)".to_string());
+            }
+        }
+        self.alerts.channels = self.alerts.channels.iter().map(|channel| channel.redacted()).collect();
+        self
+    }
+
+    /// Whether the local time falls in `quiet_hours`; an invalid window is ignored
+    pub fn in_quiet_hours(&self) -> bool {
+        in_quiet_hours(self.quiet_hours.as_deref())
+    }
+
+    /// Whether an automatic run at `load_percent` may go ahead right now
+    pub fn allows_auto_optimize(&self, load_percent: u32) -> bool {
+        !self.in_quiet_hours() || (self.quiet_hours_critical && load_percent >= self.critical_threshold)
+    }
+
     /// Load config from TOML file
-    pub fn load(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
         let config: Self = toml::from_str(&content)?;
         Ok(config)
     }
     
     /// Save config to TOML file
-    pub fn save(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         let content = toml::to_string_pretty(self)?;
         std::fs::write(path, content)?;
         Ok(())
     }
 }
+
+/// Every leaf value by dotted key, for diffing
+fn flatten(config: &OptimizerConfig) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, table: &toml::Table, out: &mut BTreeMap<String, String>) {
+        for (key, value) in table {
+            let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            match value {
+                toml::Value::Table(table) => walk(&key, table, out),
+                value => {
+                    out.insert(key, value.to_string());
+                }
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    if let Ok(table) = toml::Table::try_from(config) {
+        walk("", &table, &mut out);
+    }
+    out
+}
+
+/// Problems found in a config file
+#[derive(Debug, Default)]
+pub struct Validation {
+    /// Problems that stop the file from being used
+    pub errors: Vec<String>,
+    /// Keys the optimizer doesn't know, which are ignored
+    pub warnings: Vec<String>,
+}
+
+impl Validation {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn print(&self, path: &Path) {
+        for error in &self.errors {
+            println!("  ✗ {}", error.trim_end().replace('\n', "\n    "));
+        }
+        for warning in &self.warnings {
+            println!("  ! {}", warning);
+        }
+        if self.is_ok() {
+            println!("{} is valid", path.display());
+        } else {
+            println!("{} has {} error(s)", path.display(), self.errors.len());
+        }
+    }
+}
+
+/// Check a config file: TOML syntax, value types, ranges and unknown keys
+pub fn validate_file(path: &Path) -> Validation {
+    let mut validation = Validation::default();
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            validation.errors.push(format!("Failed to read {:?}: {}", path, e));
+            return validation;
+        }
+    };
+    let table: toml::Table = match toml::from_str(&content) {
+        Ok(table) => table,
+        Err(e) => {
+            validation.errors.push(e.to_string());
+            return validation;
+        }
+    };
+    let config: OptimizerConfig = match toml::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            validation.errors.push(e.to_string());
+            return validation;
+        }
+    };
+
+    // Anything serde dropped on the way in is a key the optimizer doesn't know
+    if let Ok(known) = toml::Table::try_from(&config) {
+        validation.warnings = table
+            .keys()
+            .filter(|key| !known.contains_key(*key))
+            .map(|key| format!("Unknown key '{}' is ignored", key))
+            .collect();
+    }
+    validation.errors = config.problems();
+    validation
+}
+
+/// Set one key in the config file, refusing values that fail validation
+pub fn set_in_file(key: &str, value: &str) -> Result<PathBuf, String> {
+    set_values_in_file(&[(key, value)])
+}
+
+/// Set several keys in the config file at once; nothing is saved unless
+/// every value is accepted and the result validates
+pub fn set_values_in_file(values: &[(&str, &str)]) -> Result<PathBuf, String> {
+    let path = OptimizerConfig::default_path();
+    let mut config = if path.exists() {
+        OptimizerConfig::load(&path).map_err(|e| format!("{:?} failed to parse: {}", path, e))?
+    } else {
+        OptimizerConfig::default()
+    };
+    for (key, value) in values {
+        config.set_value(key, value)?;
+    }
+    let problems = config.problems();
+    if !problems.is_empty() {
+        return Err(problems.join("; "));
+    }
+    config
+        .save(&path)
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    Ok(path)
+}
+
+/// Edit the config file in `$VISUAL`/`$EDITOR` and save it only once it validates
+///
+/// The edit happens on a copy next to the file, so a running daemon never
+/// reloads a half-finished or broken config.
+pub fn edit() -> Result<String, String> {
+    let path = OptimizerConfig::default_path();
+    let draft = path.with_extension("toml.edit");
+    let original = if path.exists() {
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?
+    } else {
+        toml::to_string_pretty(&OptimizerConfig::default()).map_err(|e| e.to_string())?
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&draft, &original).map_err(|e| format!("Failed to write {:?}: {}", draft, e))?;
+
+    loop {
+        run_editor(&draft)?;
+        let validation = validate_file(&draft);
+        if validation.is_ok() {
+            for warning in &validation.warnings {
+                println!("  ! {}", warning);
+            }
+            let edited = std::fs::read_to_string(&draft).map_err(|e| e.to_string())?;
+            if edited == original {
+                let _ = std::fs::remove_file(&draft);
+                return Ok("No changes".into());
+            }
+            std::fs::rename(&draft, &path).map_err(|e| format!("Failed to save {:?}: {}", path, e))?;
+            return Ok(format!("Saved {}", path.display()));
+        }
+
+        validation.print(&draft);
+        if !confirm("Edit again? [Y/n] ") {
+            return Err(format!("Not saved; your edits are in {}", draft.display()));
+        }
+    }
+}
+
+fn run_editor(path: &Path) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".into() } else { "vi".into() });
+    // Editors such as `code --wait` come with arguments
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or("$EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", program, status))
+    }
+}
+
+fn confirm(prompt: &str) -> bool {
+    use std::io::Write;
+    print!("{}", prompt);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    !answer.trim().to_lowercase().starts_with('n')
+}
+
+/// Whether the local time falls in a `HH:MM-HH:MM` quiet-hours window
+pub fn in_quiet_hours(window: Option<&str>) -> bool {
+    let Some(window) = window.filter(|w| !w.trim().is_empty()) else {
+        return false;
+    };
+    match crate::features::agent::TimeWindow::parse(window) {
+        Ok(window) => window.contains(chrono::Local::now().time()),
+        Err(e) => {
+            tracing::warn!("Ignoring quiet hours: {}", e);
+            false
+        }
+    }
+}
+
+/// Notices edits to the config file by polling its modification time
+///
+/// Long-running loops call [`ConfigWatcher::reload`] once per tick; a change
+/// made by the tray, a profile or an editor shows up on the next one. On Unix
+/// a SIGHUP (`systemctl reload`, `kill -HUP`) forces a reload as well.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// Watch the config file, treating its current state as already loaded
+    pub fn new() -> Self {
+        reload_on_sighup();
+        let path = OptimizerConfig::default_path();
+        let modified = modified(&path);
+        Self { path, modified }
+    }
+
+    /// The watched file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file was written, created or removed, or a SIGHUP
+    /// arrived, since the last call
+    pub fn changed(&mut self) -> bool {
+        let requested = RELOAD_REQUESTED.swap(false, Ordering::SeqCst);
+        let modified = modified(&self.path);
+        if modified == self.modified && !requested {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    /// The effective config again if it changed, logging what is different
+    ///
+    /// A file that fails [`validate_file`] is reported and `current` stays
+    /// in effect until it is fixed.
+    pub fn reload(&mut self, current: &OptimizerConfig) -> Option<OptimizerConfig> {
+        if !self.changed() {
+            return None;
+        }
+        if self.path.exists() {
+            let validation = validate_file(&self.path);
+            if !validation.is_ok() {
+                tracing::warn!(
+                    "Keeping the current config, {:?} is invalid: {}",
+                    self.path,
+                    validation.errors.join("; ")
+                );
+                return None;
+            }
+        }
+
+        let config = OptimizerConfig::load_or_default();
+        let changes = current.diff(&config);
+        if changes.is_empty() {
+            tracing::info!("Reloaded {:?}: no changes", self.path);
+        } else {
+            tracing::info!("Reloaded {:?}: {}", self.path, changes.join(", "));
+        }
+        Some(config)
+    }
+}
+
+impl Default for ConfigWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Route SIGHUP to [`ConfigWatcher`] instead of terminating the process
+fn reload_on_sighup() {
+    #[cfg(unix)]
+    {
+        static INSTALL: std::sync::Once = std::sync::Once::new();
+        extern "C" fn on_sighup(_: libc::c_int) {
+            RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+        }
+        INSTALL.call_once(|| unsafe {
+            libc::signal(libc::SIGHUP, on_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        });
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::alerts::ChannelConfig;
+
+    #[test]
+    fn test_set_value() {
+        let mut config = OptimizerConfig::default();
+        config.set_value("pressure_threshold", "85").unwrap();
+        config.set_value("quiet_hours", "22:00-07:00").unwrap();
+        config.set_value("oom_never_kill", r#"["sshd", "Xorg"]"#).unwrap();
+        assert_eq!(config.pressure_threshold, 85);
+        assert_eq!(config.quiet_hours.as_deref(), Some("22:00-07:00"));
+        assert_eq!(config.oom_never_kill, vec!["sshd", "Xorg"]);
+    }
+
+    #[test]
+    fn test_set_value_rejects_bad_input() {
+        let mut config = OptimizerConfig::default();
+        assert!(config.set_value("pressure_treshold", "85").is_err());
+        assert!(config.set_value("pressure_threshold", "high").is_err());
+        assert_eq!(config.pressure_threshold, 80);
+    }
+
+    #[test]
+    fn test_get_value() {
+        let config = OptimizerConfig::default();
+        assert_eq!(config.get_value("pressure_threshold").unwrap(), "80");
+        assert!(config.get_value("quiet_hours").is_err());
+        assert!(config.get_value("bogus").is_err());
+    }
+
+    #[test]
+    fn test_problems() {
+        assert!(OptimizerConfig::default().problems().is_empty());
+        let config = OptimizerConfig {
+            pressure_threshold: 97,
+            min_interval_secs: 2,
+            quiet_hours: Some("late".into()),
+            ..OptimizerConfig::default()
+        };
+        let problems = config.problems();
+        assert_eq!(problems.len(), 3);
+        assert!(problems[0].starts_with("min_interval_secs = 2"));
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let now = chrono::Local::now();
+        let window = format!(
+            "{}-{}",
+            (now - chrono::Duration::hours(1)).format("%H:%M"),
+            (now + chrono::Duration::hours(1)).format("%H:%M")
+        );
+        let mut config = OptimizerConfig { quiet_hours: Some(window), ..OptimizerConfig::default() };
+        assert!(!config.allows_auto_optimize(90));
+        assert!(config.allows_auto_optimize(96));
+        config.quiet_hours_critical = false;
+        assert!(!config.allows_auto_optimize(96));
+        config.quiet_hours = None;
+        assert!(config.allows_auto_optimize(90));
+    }
+
+    #[test]
+    fn test_validate_file() {
+        let dir = std::env::temp_dir().join(format!("memopt-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+
+        std::fs::write(&path, "pressure_threshold = 85\npressure_treshold = 90\n").unwrap();
+        let validation = validate_file(&path);
+        assert!(validation.is_ok());
+        assert_eq!(validation.warnings, vec!["Unknown key 'pressure_treshold' is ignored"]);
+
+        std::fs::write(&path, "pressure_threshold = \"high\"\n").unwrap();
+        let validation = validate_file(&path);
+        assert!(!validation.is_ok());
+        assert!(validation.errors[0].contains("line 1"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = OptimizerConfig::default();
+        let mut after = before.clone();
+        after.pressure_threshold = 85;
+        after.quiet_hours = Some("22:00-07:00".into());
+        assert_eq!(
+            before.diff(&after),
+            vec!["pressure_threshold: 80 -> 85", "quiet_hours: (unset) -> \"22:00-07:00\""]
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_redacted() {
+        let mut config =
+            OptimizerConfig { fleet_token: Some("fleet-secret".into()), ..Default::default() };
+        config.mqtt.password = Some("mqtt-secret".into());
+        config.alerts.channels = vec![
+            ChannelConfig::Slack { url: "https://hooks.slack.com/services/T0/B0/slack-secret".into() },
+            ChannelConfig::Webhook { url: "https://example.com/hook?token=hook-secret".into() },
+            ChannelConfig::Email {
+                server: "smtps://smtp.example.com".into(),
+                username: Some("ops".into()),
+                password: Some("smtp-secret".into()),
+                from: "ops@example.com".into(),
+                to: vec!["me@example.com".into()],
+            },
+        ];
+        let shown = serde_json::to_string(&config.redacted()).unwrap();
+        assert!(!shown.contains("secret"), "{}", shown);
+        assert!(shown.contains("hooks.slack.com") && shown.contains("\"username\":\"ops\""));
+    }
+
+    #[test]
+    fn test_tray_section_round_trips() {
+        let mut config = OptimizerConfig::default();
+        config.set_value("tray.theme", "light").unwrap();
+        let restored: OptimizerConfig = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        let tray = restored.tray.unwrap();
+        assert_eq!(tray.get("theme").and_then(|v| v.as_str()), Some("light"));
+    }
+}
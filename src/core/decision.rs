@@ -0,0 +1,11 @@
+//! Optimization decisions shared by the rule-based and neural paths
+
+/// Decision from neural engine
+#[derive(Debug, Clone)]
+pub struct OptimizationDecision {
+    pub should_optimize: bool,
+    pub aggressive: bool,
+    pub confidence: f32,
+    pub reason: String,
+    pub target_processes: Vec<u32>,
+}
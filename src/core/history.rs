@@ -0,0 +1,405 @@
+//! Persistent optimization history
+//!
+//! Every optimization run is appended as one JSON line to
+//! `history.jsonl` under [`crate::features::config_dir`]. The store is
+//! append-only so concurrent writers (CLI, tray, service) never rewrite each
+//! other's records.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File name of the history store inside the config directory
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// What started an optimization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OptimizationTrigger {
+    /// Explicit user request (CLI or menu)
+    Manual,
+    /// Pressure-based automatic optimization (daemon/tray)
+    Auto,
+    /// Startup optimization mode
+    Startup,
+    /// Scheduled job
+    Scheduled,
+    /// Background service
+    Service,
+    /// Automation rule (`agent`)
+    Agent,
+}
+
+impl std::fmt::Display for OptimizationTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptimizationTrigger::Manual => write!(f, "manual"),
+            OptimizationTrigger::Auto => write!(f, "auto"),
+            OptimizationTrigger::Startup => write!(f, "startup"),
+            OptimizationTrigger::Scheduled => write!(f, "scheduled"),
+            OptimizationTrigger::Service => write!(f, "service"),
+            OptimizationTrigger::Agent => write!(f, "agent"),
+        }
+    }
+}
+
+impl std::str::FromStr for OptimizationTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "manual" => Ok(OptimizationTrigger::Manual),
+            "auto" => Ok(OptimizationTrigger::Auto),
+            "startup" => Ok(OptimizationTrigger::Startup),
+            "scheduled" => Ok(OptimizationTrigger::Scheduled),
+            "service" => Ok(OptimizationTrigger::Service),
+            "agent" => Ok(OptimizationTrigger::Agent),
+            other => Err(format!("Unknown trigger: {}", other)),
+        }
+    }
+}
+
+/// Optimization intensity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptimizationMode {
+    Normal,
+    Aggressive,
+}
+
+impl OptimizationMode {
+    pub fn from_aggressive(aggressive: bool) -> Self {
+        if aggressive { OptimizationMode::Aggressive } else { OptimizationMode::Normal }
+    }
+}
+
+impl std::fmt::Display for OptimizationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptimizationMode::Normal => write!(f, "normal"),
+            OptimizationMode::Aggressive => write!(f, "aggressive"),
+        }
+    }
+}
+
+/// A single recorded optimization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub trigger: OptimizationTrigger,
+    pub mode: OptimizationMode,
+    pub freed_mb: f64,
+    pub processes_trimmed: usize,
+    pub duration_ms: u64,
+    /// Memory load when the optimization started
+    #[serde(default)]
+    pub memory_load_percent: Option<u32>,
+}
+
+impl HistoryRecord {
+    /// Create a record stamped with the current time
+    pub fn now(
+        trigger: OptimizationTrigger,
+        aggressive: bool,
+        freed_mb: f64,
+        processes_trimmed: usize,
+        duration_ms: u64,
+    ) -> Self {
+        Self {
+            timestamp_ms: now_ms(),
+            trigger,
+            mode: OptimizationMode::from_aggressive(aggressive),
+            freed_mb,
+            processes_trimmed,
+            duration_ms,
+            memory_load_percent: None,
+        }
+    }
+
+    /// Attach the memory load observed before optimizing
+    pub fn with_memory_load(mut self, load: u32) -> Self {
+        self.memory_load_percent = Some(load);
+        self
+    }
+}
+
+/// Time window for queries (inclusive, unix ms)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeRange {
+    pub start_ms: Option<u64>,
+    pub end_ms: Option<u64>,
+}
+
+impl TimeRange {
+    /// Unbounded range
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Records from the last `duration`
+    pub fn last(duration: Duration) -> Self {
+        Self {
+            start_ms: Some(now_ms().saturating_sub(duration.as_millis() as u64)),
+            end_ms: None,
+        }
+    }
+
+    /// `all`, or the last span of time such as `30m`, `24h`, `7d` or `1d12h`
+    pub fn parse(range: &str) -> Result<Self, String> {
+        let range = range.trim();
+        if range.eq_ignore_ascii_case("all") {
+            return Ok(Self::all());
+        }
+        let invalid = || format!("Invalid range '{}', expected e.g. 30m, 24h, 7d or all", range);
+        let mut secs = 0;
+        let mut number = String::new();
+        for c in range.chars() {
+            match c {
+                '0'..='9' => number.push(c),
+                'm' | 'h' | 'd' => {
+                    let value: u64 = number.parse().map_err(|_| invalid())?;
+                    secs += value * match c {
+                        'm' => 60,
+                        'h' => 3600,
+                        _ => 86_400,
+                    };
+                    number.clear();
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        if !number.is_empty() || secs == 0 {
+            return Err(invalid());
+        }
+        Ok(Self::last(Duration::from_secs(secs)))
+    }
+
+    pub fn contains(&self, timestamp_ms: u64) -> bool {
+        self.start_ms.map_or(true, |s| timestamp_ms >= s)
+            && self.end_ms.map_or(true, |e| timestamp_ms <= e)
+    }
+}
+
+/// Record filter for queries
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub trigger: Option<OptimizationTrigger>,
+    pub mode: Option<OptimizationMode>,
+    pub min_freed_mb: Option<f64>,
+    /// Keep only the most recent N matches
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, record: &HistoryRecord) -> bool {
+        self.trigger.map_or(true, |t| record.trigger == t)
+            && self.mode.map_or(true, |m| record.mode == m)
+            && self.min_freed_mb.map_or(true, |m| record.freed_mb >= m)
+    }
+}
+
+/// Aggregate statistics over a set of records
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistorySummary {
+    pub count: usize,
+    pub total_freed_mb: f64,
+    pub avg_freed_mb: f64,
+    pub avg_duration_ms: f64,
+    pub total_processes_trimmed: usize,
+}
+
+/// Append-only JSONL optimization history
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Open the store at the default location
+    pub fn open_default() -> Result<Self, String> {
+        let path = crate::features::config_file(HISTORY_FILE)?;
+        Ok(Self { path })
+    }
+
+    /// Open a store at an explicit path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record
+    pub fn append(&self, record: &HistoryRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open history {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Return matching records in chronological order
+    ///
+    /// Malformed lines are skipped so a torn write never breaks the store.
+    pub fn query(&self, range: TimeRange, filter: &HistoryFilter) -> Result<Vec<HistoryRecord>, String> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mut records: Vec<HistoryRecord> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<HistoryRecord>(&line).ok())
+            .filter(|r| range.contains(r.timestamp_ms) && filter.matches(r))
+            .collect();
+
+        records.sort_by_key(|r| r.timestamp_ms);
+
+        if let Some(limit) = filter.limit {
+            if records.len() > limit {
+                records.drain(..records.len() - limit);
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Summarize a set of records
+    pub fn summarize(records: &[HistoryRecord]) -> HistorySummary {
+        if records.is_empty() {
+            return HistorySummary::default();
+        }
+        let count = records.len();
+        let total_freed_mb: f64 = records.iter().map(|r| r.freed_mb).sum();
+        let total_duration: u64 = records.iter().map(|r| r.duration_ms).sum();
+        HistorySummary {
+            count,
+            total_freed_mb,
+            avg_freed_mb: total_freed_mb / count as f64,
+            avg_duration_ms: total_duration as f64 / count as f64,
+            total_processes_trimmed: records.iter().map(|r| r.processes_trimmed).sum(),
+        }
+    }
+
+    /// Print records as a table followed by a summary line
+    pub fn print_records(records: &[HistoryRecord]) {
+        println!("\n📜 Optimization History\n");
+
+        if records.is_empty() {
+            println!("  No optimizations recorded in this range.");
+            return;
+        }
+
+        println!("┌─────────────────────┬───────────┬────────────┬────────────┬──────────┬──────────┐");
+        println!("│ Time                │ Trigger   │ Mode       │ Freed MB   │ Procs    │ ms       │");
+        println!("├─────────────────────┼───────────┼────────────┼────────────┼──────────┼──────────┤");
+
+        for r in records {
+            let time = chrono::DateTime::from_timestamp_millis(r.timestamp_ms as i64)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "?".into());
+            println!(
+                "│ {:19} │ {:9} │ {:10} │ {:>10.1} │ {:>8} │ {:>8} │",
+                time,
+                r.trigger.to_string(),
+                r.mode.to_string(),
+                r.freed_mb,
+                r.processes_trimmed,
+                r.duration_ms
+            );
+        }
+
+        println!("└─────────────────────┴───────────┴────────────┴────────────┴──────────┴──────────┘");
+
+        let summary = Self::summarize(records);
+        println!(
+            "\n  {} runs, {:.0} MB freed total ({:.1} MB avg, {:.0} ms avg)",
+            summary.count, summary.total_freed_mb, summary.avg_freed_mb, summary.avg_duration_ms
+        );
+    }
+}
+
+/// Record an optimization in the default store, logging instead of failing
+pub fn record(record: HistoryRecord) {
+    match HistoryStore::open_default().and_then(|store| store.append(&record)) {
+        Ok(()) => {}
+        Err(e) => tracing::warn!("Failed to record optimization history: {}", e),
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> HistoryStore {
+        let path = std::env::temp_dir()
+            .join(format!("ruvector-history-{}-{}", name, std::process::id()))
+            .join(HISTORY_FILE);
+        let _ = std::fs::remove_file(&path);
+        HistoryStore::open(path).unwrap()
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let store = temp_store("query");
+        store.append(&HistoryRecord::now(OptimizationTrigger::Manual, false, 120.0, 10, 50)).unwrap();
+        store.append(&HistoryRecord::now(OptimizationTrigger::Auto, true, 300.0, 40, 90)).unwrap();
+
+        let all = store.query(TimeRange::all(), &HistoryFilter::default()).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let auto = store.query(TimeRange::all(), &HistoryFilter {
+            trigger: Some(OptimizationTrigger::Auto),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(auto.len(), 1);
+        assert_eq!(auto[0].mode, OptimizationMode::Aggressive);
+
+        let summary = HistoryStore::summarize(&all);
+        assert!((summary.total_freed_mb - 420.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_missing_file_and_bad_lines() {
+        let store = temp_store("bad");
+        assert!(store.query(TimeRange::all(), &HistoryFilter::default()).unwrap().is_empty());
+
+        std::fs::write(store.path(), "not json\n").unwrap();
+        store.append(&HistoryRecord::now(OptimizationTrigger::Startup, false, 1.0, 1, 1)).unwrap();
+        let records = store.query(TimeRange::last(Duration::from_secs(60)), &HistoryFilter::default()).unwrap();
+        assert_eq!(records.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert!(TimeRange::parse("all").unwrap().start_ms.is_none());
+        let span = |range: &str| {
+            let range = TimeRange::parse(range).unwrap();
+            (now_ms() - range.start_ms.unwrap()) / 1000
+        };
+        assert!((span("24h") as i64 - 86_400).abs() <= 1);
+        assert!((span("1d12h") as i64 - 129_600).abs() <= 1);
+        assert!((span("30m") as i64 - 1800).abs() <= 1);
+        for bad in ["", "24", "h", "2w", "0h"] {
+            assert!(TimeRange::parse(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,914 @@
+//! Local control channel between a running daemon, the CLI and the tray
+//!
+//! The daemon (the Linux/BSD `daemon` command or the Windows service)
+//! listens on a Unix socket, or on the named pipe [`PIPE_NAME`] on Windows.
+//! Clients write one JSON [`IpcRequest`] per line and read one reply line,
+//! `{"Ok": ...}` or `{"Err": "..."}`. After `subscribe-events` the daemon
+//! keeps the connection open and writes one [`DaemonEvent`] per line.
+//!
+//! Optimizations asked for here run inside the daemon's own loop, so the CLI
+//! and tray share its sampling, safety checks and rate limit instead of
+//! starting optimizers of their own.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+use super::budget::{self, Footprint};
+use super::config::OptimizerConfig;
+use super::history::OptimizationTrigger;
+
+/// File name of the daemon's socket (Unix)
+pub const SOCKET_NAME: &str = "ruvector-memopt.sock";
+/// Where a daemon running as root puts its socket
+#[cfg(target_os = "linux")]
+pub const SYSTEM_SOCKET_DIR: &str = "/run";
+#[cfg(all(unix, not(target_os = "linux")))]
+pub const SYSTEM_SOCKET_DIR: &str = "/var/run";
+/// Named pipe of the Windows service
+#[cfg(windows)]
+pub const PIPE_NAME: &str = r"\\.\pipe\ruvector-memopt";
+/// Pipe ACL: full control for SYSTEM and administrators, read/write for
+/// interactive users
+#[cfg(windows)]
+const PIPE_SDDL: &str = "D:(A;;GA;;;SY)(A;;GA;;;BA)(A;;GRGW;;;IU)";
+
+/// How long a client waits for the daemon to run an optimization
+const OPTIMIZE_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long a client waits for any other reply
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest request line the daemon reads; longer ones end the connection
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// Commands a client can send
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+pub enum IpcRequest {
+    /// Memory status plus the daemon's own state, as [`DaemonStatus`]
+    Status,
+    /// Optimize now, subject to the daemon's safety checks and rate limit
+    Optimize {
+        #[serde(default)]
+        aggressive: bool,
+    },
+    /// Stop automatic optimization until `until_ms`, for `minutes`, or
+    /// until `resume`
+    Pause {
+        #[serde(default)]
+        minutes: Option<u64>,
+        #[serde(default)]
+        until_ms: Option<u64>,
+    },
+    /// Undo `pause`
+    Resume,
+    /// The config the daemon is running with; secrets are masked unless
+    /// the client is root or the daemon's user
+    GetConfig,
+    /// Stream [`DaemonEvent`]s on this connection
+    SubscribeEvents,
+}
+
+/// Reply to `status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub total_mb: f64,
+    pub available_mb: f64,
+    pub memory_load_percent: u32,
+    pub paused: bool,
+    /// End of a timed pause; `None` while paused means until `resume`
+    pub paused_until_ms: Option<u64>,
+    pub optimizations: u64,
+    pub freed_mb_total: f64,
+    pub last_optimization_ms: Option<u64>,
+    /// The daemon's own memory and CPU use, once its loop has sampled it
+    #[serde(default)]
+    pub footprint: Option<Footprint>,
+}
+
+/// One optimization the daemon ran, for whatever reason
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizeReport {
+    pub trigger: OptimizationTrigger,
+    pub aggressive: bool,
+    pub freed_mb: f64,
+    pub processes: usize,
+    pub duration_ms: u64,
+}
+
+/// Something subscribers are told about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum DaemonEvent {
+    Optimized(OptimizeReport),
+    Pressure { level: String, load_percent: u32 },
+    Paused { until_ms: Option<u64> },
+    Resumed,
+    ConfigReloaded,
+    /// The dominant memory pattern changed, e.g. stable to increasing
+    PatternShift { from: String, to: String },
+    /// A metric is far from normal for the time of day
+    Anomaly { metric: String, message: String },
+}
+
+impl std::fmt::Display for DaemonEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Optimized(report) => write!(
+                f,
+                "optimized ({}{}): freed {:.1} MB from {} processes in {} ms",
+                report.trigger,
+                if report.aggressive { ", aggressive" } else { "" },
+                report.freed_mb,
+                report.processes,
+                report.duration_ms
+            ),
+            Self::Pressure { level, load_percent } => write!(f, "pressure {} ({}% used)", level, load_percent),
+            Self::Paused { until_ms } => write!(f, "paused {}", Pause { until_ms: *until_ms }),
+            Self::Resumed => write!(f, "resumed"),
+            Self::ConfigReloaded => write!(f, "config reloaded"),
+            Self::PatternShift { from, to } => write!(f, "memory pattern {} -> {}", from, to),
+            Self::Anomaly { message, .. } => write!(f, "anomaly: {}", message),
+        }
+    }
+}
+
+/// Automatic optimization switched off, until a time or until resumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pause {
+    /// `None` lasts until `resume`
+    pub until_ms: Option<u64>,
+}
+
+impl Pause {
+    pub fn until_resumed() -> Self {
+        Self { until_ms: None }
+    }
+
+    pub fn for_minutes(minutes: u64) -> Self {
+        Self { until_ms: Some(now_ms() + minutes * 60_000) }
+    }
+
+    /// Parse a duration (`2h`, `30m`, `1h30m`; a bare number is minutes) or
+    /// a clock time (`17:00`, the next time it comes round)
+    pub fn parse(when: &str) -> Result<Self, String> {
+        Self::parse_at(when, chrono::Local::now())
+    }
+
+    fn parse_at(when: &str, now: chrono::DateTime<chrono::Local>) -> Result<Self, String> {
+        let when = when.trim();
+        let now_ms = now.timestamp_millis().max(0) as u64;
+        if when.contains(':') {
+            let (hour, minute) = super::scheduler::parse_time(when)?;
+            let mut at = now.date_naive().and_hms_opt(hour, minute, 0).ok_or("Invalid time")?;
+            if at <= now.naive_local() {
+                at += chrono::Duration::days(1);
+            }
+            let at = at
+                .and_local_timezone(chrono::Local)
+                .earliest()
+                .ok_or_else(|| format!("{} does not exist today", when))?;
+            return Ok(Self { until_ms: Some(at.timestamp_millis().max(0) as u64) });
+        }
+
+        let invalid = || format!("Invalid pause '{}', expected e.g. 2h, 30m or 17:00", when);
+        if let Ok(minutes) = when.parse::<u64>() {
+            return Ok(Self { until_ms: Some(now_ms + minutes * 60_000) });
+        }
+        let mut minutes = 0;
+        let mut number = String::new();
+        for c in when.chars() {
+            match c {
+                '0'..='9' => number.push(c),
+                'h' | 'm' => {
+                    let value: u64 = number.parse().map_err(|_| invalid())?;
+                    minutes += if c == 'h' { value * 60 } else { value };
+                    number.clear();
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        if !number.is_empty() || minutes == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { until_ms: Some(now_ms + minutes * 60_000) })
+    }
+
+    pub fn expired(&self) -> bool {
+        self.until_ms.is_some_and(|until| now_ms() >= until)
+    }
+}
+
+impl std::fmt::Display for Pause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.until_ms {
+            Some(until) => write!(f, "until {}", format_time(until)),
+            None => write!(f, "until resumed"),
+        }
+    }
+}
+
+/// An `optimize` request waiting for the daemon loop
+pub struct OptimizeCall {
+    pub aggressive: bool,
+    reply: mpsc::Sender<Result<OptimizeReport, String>>,
+}
+
+#[derive(Default)]
+struct ControlState {
+    pause: Option<Pause>,
+    optimizations: u64,
+    freed_mb_total: f64,
+    last_optimization_ms: Option<u64>,
+}
+
+/// State the daemon loop shares with connected clients
+///
+/// The loop owns the optimizer. It calls [`DaemonControl::take_calls`] each
+/// time `wake` fires and answers every call with [`DaemonControl::complete`]
+/// (or [`DaemonControl::reply`] if it records runs itself), checks
+/// [`DaemonControl::is_paused`] before optimizing on its own, and reports its
+/// own runs with [`DaemonControl::record`].
+pub struct DaemonControl {
+    config: Mutex<OptimizerConfig>,
+    state: Mutex<ControlState>,
+    calls: Mutex<Vec<OptimizeCall>>,
+    subscribers: Mutex<Vec<mpsc::Sender<DaemonEvent>>>,
+    wake: Box<dyn Fn() + Send + Sync>,
+}
+
+impl DaemonControl {
+    /// `wake` interrupts the daemon loop's sleep so a queued call runs now
+    pub fn new(config: OptimizerConfig, wake: impl Fn() + Send + Sync + 'static) -> Arc<Self> {
+        Arc::new(Self {
+            config: Mutex::new(config),
+            state: Mutex::new(ControlState::default()),
+            calls: Mutex::new(Vec::new()),
+            subscribers: Mutex::new(Vec::new()),
+            wake: Box::new(wake),
+        })
+    }
+
+    pub fn set_config(&self, config: OptimizerConfig) {
+        *self.config.lock().unwrap() = config;
+        self.publish(DaemonEvent::ConfigReloaded);
+    }
+
+    /// Whether automatic optimization is paused; ends an expired timed pause
+    pub fn is_paused(&self) -> bool {
+        let Some(pause) = self.state.lock().unwrap().pause else {
+            return false;
+        };
+        if pause.expired() {
+            self.resume();
+            return false;
+        }
+        true
+    }
+
+    pub fn pause(&self, pause: Pause) {
+        self.state.lock().unwrap().pause = Some(pause);
+        self.publish(DaemonEvent::Paused { until_ms: pause.until_ms });
+    }
+
+    pub fn resume(&self) {
+        if self.state.lock().unwrap().pause.take().is_some() {
+            self.publish(DaemonEvent::Resumed);
+        }
+    }
+
+    /// Optimize requests queued since the last call
+    pub fn take_calls(&self) -> Vec<OptimizeCall> {
+        std::mem::take(&mut *self.calls.lock().unwrap())
+    }
+
+    /// Answer a queued call, recording the run if there was one
+    pub fn complete(&self, call: OptimizeCall, result: Result<OptimizeReport, String>) {
+        if let Ok(report) = &result {
+            self.record(report.clone());
+        }
+        self.reply(call, result);
+    }
+
+    /// Answer a queued call whose run was already recorded
+    pub fn reply(&self, call: OptimizeCall, result: Result<OptimizeReport, String>) {
+        let _ = call.reply.send(result);
+    }
+
+    /// Count an optimization and tell subscribers about it
+    pub fn record(&self, report: OptimizeReport) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.optimizations += 1;
+            state.freed_mb_total += report.freed_mb.max(0.0);
+            state.last_optimization_ms = Some(now_ms());
+        }
+        self.publish(DaemonEvent::Optimized(report));
+    }
+
+    pub fn publish(&self, event: DaemonEvent) {
+        debug!("Daemon event: {}", event);
+        self.subscribers.lock().unwrap().retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    fn subscribe(&self) -> mpsc::Receiver<DaemonEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn status(&self) -> Result<DaemonStatus, String> {
+        let memory = crate::platform::create_optimizer().get_memory_status()?;
+        let paused = self.is_paused();
+        let state = self.state.lock().unwrap();
+        Ok(DaemonStatus {
+            pid: std::process::id(),
+            total_mb: memory.total_physical_mb,
+            available_mb: memory.available_physical_mb,
+            memory_load_percent: memory.memory_load_percent,
+            paused,
+            paused_until_ms: state.pause.and_then(|p| p.until_ms),
+            optimizations: state.optimizations,
+            freed_mb_total: state.freed_mb_total,
+            last_optimization_ms: state.last_optimization_ms,
+            footprint: budget::latest(),
+        })
+    }
+
+    fn optimize(&self, aggressive: bool) -> Result<OptimizeReport, String> {
+        let (reply, rx) = mpsc::channel();
+        self.calls.lock().unwrap().push(OptimizeCall { aggressive, reply });
+        (self.wake)();
+        rx.recv_timeout(OPTIMIZE_TIMEOUT)
+            .map_err(|_| "The daemon did not finish the optimization in time".to_string())?
+    }
+
+    /// Answer everything but `subscribe-events`
+    ///
+    /// `privileged` is false for clients that are neither root nor the
+    /// daemon's own user; they may look and ask to optimize, but not pause,
+    /// and only see the config with its secrets masked.
+    fn handle(&self, request: &IpcRequest, privileged: bool) -> Result<serde_json::Value, String> {
+        let value = match request {
+            IpcRequest::Status => serde_json::to_value(self.status()?),
+            IpcRequest::Optimize { aggressive } => serde_json::to_value(self.optimize(*aggressive)?),
+            IpcRequest::Pause { .. } | IpcRequest::Resume if !privileged => {
+                return Err("Only root or the daemon's user can pause or resume it".into())
+            }
+            IpcRequest::Pause { minutes, until_ms } => {
+                self.pause(match (until_ms, minutes) {
+                    (Some(until_ms), _) => Pause { until_ms: Some(*until_ms) },
+                    (None, Some(minutes)) => Pause::for_minutes(*minutes),
+                    (None, None) => Pause::until_resumed(),
+                });
+                Ok(serde_json::Value::Null)
+            }
+            IpcRequest::Resume => {
+                self.resume();
+                Ok(serde_json::Value::Null)
+            }
+            IpcRequest::GetConfig if privileged => serde_json::to_value(&*self.config.lock().unwrap()),
+            IpcRequest::GetConfig => serde_json::to_value(self.config.lock().unwrap().clone().redacted()),
+            IpcRequest::SubscribeEvents => return Err("subscribe-events needs its own connection".into()),
+        };
+        value.map_err(|e| e.to_string())
+    }
+}
+
+/// Answer one client until it hangs up
+fn serve_client(control: &DaemonControl, mut reader: impl BufRead, mut writer: impl Write, privileged: bool) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match (&mut reader).take(MAX_REQUEST_BYTES).read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        if !line.ends_with('\n') && line.len() as u64 == MAX_REQUEST_BYTES {
+            let _ = write_line(&mut writer, &Err::<(), _>(format!("Request longer than {} bytes", MAX_REQUEST_BYTES)));
+            return;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = serde_json::from_str::<IpcRequest>(&line).map_err(|e| format!("Bad request: {}", e));
+        if let Ok(IpcRequest::SubscribeEvents) = request {
+            // Ends on the first event after the client has gone
+            let events = control.subscribe();
+            if write_line(&mut writer, &Ok::<_, String>(serde_json::Value::Null)).is_err() {
+                return;
+            }
+            for event in events {
+                if write_line(&mut writer, &event).is_err() {
+                    return;
+                }
+            }
+            return;
+        }
+        let reply = request.and_then(|request| control.handle(&request, privileged));
+        if write_line(&mut writer, &reply).is_err() {
+            return;
+        }
+    }
+}
+
+fn write_line(writer: &mut impl Write, value: &impl Serialize) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(value).map_err(std::io::Error::other)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+/// Accept clients on a background thread; returns where it listens
+///
+/// A daemon running as root uses [`SYSTEM_SOCKET_DIR`] and lets every local
+/// user connect; otherwise the socket goes in the user's runtime directory.
+#[cfg(unix)]
+pub fn listen(control: Arc<DaemonControl>) -> Result<PathBuf, String> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let is_root = unsafe { libc::geteuid() } == 0;
+    let path = if is_root { system_socket_path() } else { user_socket_path() };
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            return Err(format!("Another daemon is already listening on {}", path.display()));
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let listener = UnixListener::bind(&path).map_err(|e| format!("Failed to listen on {}: {}", path.display(), e))?;
+    if is_root {
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666))
+            .map_err(|e| format!("Failed to open up {}: {}", path.display(), e))?;
+    }
+
+    let daemon_uid = unsafe { libc::geteuid() };
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("IPC accept failed: {}", e);
+                    continue;
+                }
+            };
+            let control = control.clone();
+            std::thread::spawn(move || {
+                let privileged = peer_uid(&stream).is_some_and(|uid| uid == 0 || uid == daemon_uid);
+                if let Ok(reader) = stream.try_clone() {
+                    serve_client(&control, BufReader::new(reader), stream, privileged);
+                }
+            });
+        }
+    });
+    Ok(path)
+}
+
+/// Accept clients on a background thread; returns where it listens
+#[cfg(windows)]
+pub fn listen(control: Arc<DaemonControl>) -> Result<PathBuf, String> {
+    use windows::Win32::System::Pipes::{ConnectNamedPipe, DisconnectNamedPipe};
+    use windows::Win32::Foundation::{ERROR_PIPE_CONNECTED, HANDLE};
+    use std::os::windows::io::AsRawHandle;
+
+    // The first instance fails if someone else already owns the name
+    let first = create_pipe(true)?;
+    std::thread::spawn(move || {
+        let mut pipe = first;
+        loop {
+            let handle = HANDLE(pipe.as_raw_handle() as _);
+            match unsafe { ConnectNamedPipe(handle, None) } {
+                Err(e) if e.code() != ERROR_PIPE_CONNECTED.to_hresult() => {
+                    debug!("IPC connect failed: {}", e);
+                    let _ = unsafe { DisconnectNamedPipe(handle) };
+                    continue;
+                }
+                _ => {}
+            }
+            let connected = std::mem::replace(&mut pipe, match create_pipe(false) {
+                Ok(next) => next,
+                Err(e) => {
+                    warn!("IPC server stopped: {}", e);
+                    return;
+                }
+            });
+            let control = control.clone();
+            std::thread::spawn(move || {
+                let Ok(reader) = connected.try_clone() else { return };
+                let mut reader = BufReader::new(reader);
+                // Windows only lets us impersonate a client that has written something
+                if reader.fill_buf().is_err() {
+                    return;
+                }
+                let privileged = client_is_privileged(&connected);
+                serve_client(&control, reader, connected, privileged);
+            });
+        }
+    });
+    Ok(PathBuf::from(PIPE_NAME))
+}
+
+#[cfg(windows)]
+fn create_pipe(first: bool) -> Result<std::fs::File, String> {
+    use std::os::windows::io::FromRawHandle;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+    use windows::Win32::Security::Authorization::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+    };
+    use windows::Win32::Security::{PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES};
+    use windows::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+    use windows::Win32::System::Pipes::{
+        CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    let sddl: Vec<u16> = PIPE_SDDL.encode_utf16().chain(Some(0)).collect();
+    let name: Vec<u16> = PIPE_NAME.encode_utf16().chain(Some(0)).collect();
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+    unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            PCWSTR(sddl.as_ptr()),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            None,
+        )
+    }
+    .map_err(|e| format!("Failed to build the pipe ACL: {}", e))?;
+    let attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: descriptor.0,
+        bInheritHandle: false.into(),
+    };
+    let mode = if first { PIPE_ACCESS_DUPLEX | FILE_FLAG_FIRST_PIPE_INSTANCE } else { PIPE_ACCESS_DUPLEX };
+    let handle = unsafe {
+        CreateNamedPipeW(
+            PCWSTR(name.as_ptr()),
+            mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+            PIPE_UNLIMITED_INSTANCES,
+            4096,
+            4096,
+            0,
+            Some(&attributes as *const SECURITY_ATTRIBUTES),
+        )
+    };
+    unsafe { LocalFree(HLOCAL(descriptor.0)) };
+    if handle.is_invalid() {
+        return Err(format!("Failed to create {}: {}", PIPE_NAME, std::io::Error::last_os_error()));
+    }
+    Ok(unsafe { std::fs::File::from_raw_handle(handle.0 as _) })
+}
+
+/// Whether the client on `pipe` is elevated or runs as the daemon's user
+///
+/// The pipe ACL lets every interactive user in, so this plays the part of
+/// the peer uid check on Unix.
+#[cfg(windows)]
+fn client_is_privileged(pipe: &std::fs::File) -> bool {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::{CloseHandle, BOOL, HANDLE};
+    use windows::Win32::Security::{RevertToSelf, TOKEN_QUERY};
+    use windows::Win32::System::Pipes::ImpersonateNamedPipeClient;
+    use windows::Win32::System::Threading::{GetCurrentProcess, GetCurrentThread, OpenProcessToken, OpenThreadToken};
+
+    unsafe {
+        if ImpersonateNamedPipeClient(HANDLE(pipe.as_raw_handle() as _)).is_err() {
+            return false;
+        }
+        let mut client = HANDLE::default();
+        let opened = OpenThreadToken(GetCurrentThread(), TOKEN_QUERY, BOOL::from(true), &mut client);
+        if RevertToSelf().is_err() {
+            // Carrying on as the client would run its requests with its rights
+            std::process::abort();
+        }
+        if opened.is_err() {
+            return false;
+        }
+        let mut daemon = HANDLE::default();
+        let privileged = token_is_elevated(client)
+            || (OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut daemon).is_ok() && {
+                let same_user = token_user(client).is_some_and(|user| Some(user) == token_user(daemon));
+                let _ = CloseHandle(daemon);
+                same_user
+            });
+        let _ = CloseHandle(client);
+        privileged
+    }
+}
+
+#[cfg(windows)]
+unsafe fn token_is_elevated(token: windows::Win32::Foundation::HANDLE) -> bool {
+    use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION};
+
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut size = 0u32;
+    GetTokenInformation(
+        token,
+        TokenElevation,
+        Some(&mut elevation as *mut _ as *mut _),
+        std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+        &mut size,
+    )
+    .is_ok()
+        && elevation.TokenIsElevated != 0
+}
+
+/// The token's user SID as bytes
+#[cfg(windows)]
+unsafe fn token_user(token: windows::Win32::Foundation::HANDLE) -> Option<Vec<u8>> {
+    use windows::Win32::Security::{GetLengthSid, GetTokenInformation, TokenUser, TOKEN_USER};
+
+    let mut size = 0u32;
+    let _ = GetTokenInformation(token, TokenUser, None, 0, &mut size);
+    // u64 words keep the buffer aligned for TOKEN_USER
+    let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+    GetTokenInformation(token, TokenUser, Some(buffer.as_mut_ptr() as *mut _), size, &mut size).ok()?;
+    let sid = (*(buffer.as_ptr() as *const TOKEN_USER)).User.Sid;
+    let len = GetLengthSid(sid) as usize;
+    Some(std::slice::from_raw_parts(sid.0 as *const u8, len).to_vec())
+}
+
+/// Socket of a daemon running as root
+#[cfg(unix)]
+pub fn system_socket_path() -> PathBuf {
+    PathBuf::from(SYSTEM_SOCKET_DIR).join(SOCKET_NAME)
+}
+
+/// Socket of a daemon running as the current user
+#[cfg(unix)]
+pub fn user_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(crate::features::config_dir)
+        .join(SOCKET_NAME)
+}
+
+/// Uid of the process on the other end of `stream`
+#[cfg(unix)]
+fn peer_uid(stream: &std::os::unix::net::UnixStream) -> Option<u32> {
+    use std::os::unix::io::AsRawFd;
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        let rc = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut cred as *mut libc::ucred as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        (rc == 0).then_some(cred.uid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let (mut uid, mut gid) = (0, 0);
+        let rc = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+        (rc == 0).then_some(uid)
+    }
+}
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Stream = std::fs::File;
+
+/// Connection to a running daemon
+pub struct IpcClient {
+    reader: BufReader<Stream>,
+    writer: Stream,
+}
+
+impl IpcClient {
+    /// Connect to the user's own daemon, then the system one
+    ///
+    /// `None` means no daemon is running and the caller should do the work
+    /// itself.
+    pub fn connect() -> Option<Self> {
+        #[cfg(unix)]
+        let stream = [user_socket_path(), system_socket_path()]
+            .iter()
+            .find_map(|path| std::os::unix::net::UnixStream::connect(path).ok())?;
+        #[cfg(windows)]
+        let stream = std::fs::OpenOptions::new().read(true).write(true).open(PIPE_NAME).ok()?;
+
+        let reader = BufReader::new(stream.try_clone().ok()?);
+        Some(Self { reader, writer: stream })
+    }
+
+    /// [`IpcClient::connect`] for commands that only make sense with a daemon
+    pub fn connect_required() -> Result<Self, String> {
+        Self::connect().ok_or_else(|| "No daemon is running".to_string())
+    }
+
+    pub fn status(&mut self) -> Result<DaemonStatus, String> {
+        self.call(&IpcRequest::Status, REPLY_TIMEOUT)
+    }
+
+    pub fn optimize(&mut self, aggressive: bool) -> Result<OptimizeReport, String> {
+        // The daemon gives up on its own loop first
+        self.call(&IpcRequest::Optimize { aggressive }, OPTIMIZE_TIMEOUT + REPLY_TIMEOUT)
+    }
+
+    pub fn pause(&mut self, pause: Pause) -> Result<(), String> {
+        self.call(&IpcRequest::Pause { minutes: None, until_ms: pause.until_ms }, REPLY_TIMEOUT)
+    }
+
+    pub fn resume(&mut self) -> Result<(), String> {
+        self.call(&IpcRequest::Resume, REPLY_TIMEOUT)
+    }
+
+    pub fn get_config(&mut self) -> Result<OptimizerConfig, String> {
+        self.call(&IpcRequest::GetConfig, REPLY_TIMEOUT)
+    }
+
+    /// Turn this connection into a stream of events; ends when the daemon stops
+    pub fn subscribe(mut self) -> Result<impl Iterator<Item = DaemonEvent>, String> {
+        self.call::<()>(&IpcRequest::SubscribeEvents, REPLY_TIMEOUT)?;
+        #[cfg(unix)]
+        let _ = self.writer.set_read_timeout(None);
+        Ok(self.reader.lines().map_while(|line| serde_json::from_str(&line.ok()?).ok()))
+    }
+
+    fn call<T: DeserializeOwned>(&mut self, request: &IpcRequest, timeout: Duration) -> Result<T, String> {
+        #[cfg(unix)]
+        self.writer.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        #[cfg(not(unix))]
+        let _ = timeout;
+
+        write_line(&mut self.writer, request).map_err(|e| format!("Daemon connection lost: {}", e))?;
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => return Err("Daemon closed the connection".into()),
+            Ok(_) => {}
+            Err(e) => return Err(format!("Daemon connection lost: {}", e)),
+        }
+        serde_json::from_str::<Result<T, String>>(&line).map_err(|e| format!("Bad reply from daemon: {}", e))?
+    }
+}
+
+impl DaemonStatus {
+    pub fn print(&self) {
+        println!("Daemon (pid {}):", self.pid);
+        if self.paused {
+            println!("  State:     paused {}", Pause { until_ms: self.paused_until_ms });
+        } else {
+            println!("  State:     running");
+        }
+        println!("  Load:      {}% ({:.0} of {:.0} MB available)", self.memory_load_percent, self.available_mb, self.total_mb);
+        println!("  Optimized: {} times, {:.0} MB freed", self.optimizations, self.freed_mb_total);
+        if let Some(last) = self.last_optimization_ms {
+            println!("  Last:      {}", format_time(last));
+        }
+        if let Some(footprint) = self.footprint {
+            println!("  Footprint: {}", footprint.summary());
+        }
+    }
+}
+
+impl OptimizeReport {
+    pub fn print(&self) {
+        println!("Optimization complete (by the daemon):");
+        println!("  Freed:     {:.1} MB", self.freed_mb);
+        println!("  Processes: {}", self.processes);
+        println!("  Duration:  {} ms", self.duration_ms);
+    }
+}
+
+/// Local time of a Unix timestamp in milliseconds
+pub fn format_time(ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_wire_format() {
+        let request: IpcRequest = serde_json::from_str(r#"{"cmd":"optimize"}"#).unwrap();
+        assert_eq!(request, IpcRequest::Optimize { aggressive: false });
+        let request: IpcRequest = serde_json::from_str(r#"{"cmd":"pause","minutes":30}"#).unwrap();
+        assert_eq!(request, IpcRequest::Pause { minutes: Some(30), until_ms: None });
+        assert_eq!(serde_json::to_string(&IpcRequest::SubscribeEvents).unwrap(), r#"{"cmd":"subscribe-events"}"#);
+    }
+
+    #[test]
+    fn timed_pause_expires() {
+        let control = DaemonControl::new(OptimizerConfig::default(), || {});
+        assert!(!control.is_paused());
+        control.pause(Pause::for_minutes(10));
+        assert!(control.is_paused());
+        control.state.lock().unwrap().pause = Some(Pause { until_ms: Some(now_ms() - 1) });
+        assert!(!control.is_paused());
+    }
+
+    #[test]
+    fn parse_pause() {
+        use chrono::TimeZone;
+        let now = chrono::Local.with_ymd_and_hms(2026, 3, 10, 14, 30, 0).unwrap();
+        let now_ms = now.timestamp_millis() as u64;
+        let minutes_from_now = |when: &str| (Pause::parse_at(when, now).unwrap().until_ms.unwrap() - now_ms) / 60_000;
+        assert_eq!(minutes_from_now("2h"), 120);
+        assert_eq!(minutes_from_now("1h30m"), 90);
+        assert_eq!(minutes_from_now("45"), 45);
+        assert_eq!(minutes_from_now("17:00"), 150);
+        assert_eq!(minutes_from_now("14:00"), 23 * 60 + 30);
+        assert!(Pause::parse_at("2 hours", now).is_err());
+        assert!(Pause::parse_at("h", now).is_err());
+        assert!(Pause::parse_at("25:00", now).is_err());
+    }
+
+    #[test]
+    fn optimize_waits_for_the_daemon_loop() {
+        let (wake_tx, wake_rx) = mpsc::channel();
+        let control = DaemonControl::new(OptimizerConfig::default(), move || {
+            let _ = wake_tx.send(());
+        });
+        let events = control.subscribe();
+        let daemon = {
+            let control = control.clone();
+            std::thread::spawn(move || {
+                wake_rx.recv().unwrap();
+                for call in control.take_calls() {
+                    let report = OptimizeReport {
+                        trigger: OptimizationTrigger::Manual,
+                        aggressive: call.aggressive,
+                        freed_mb: 64.0,
+                        processes: 3,
+                        duration_ms: 5,
+                    };
+                    control.complete(call, Ok(report));
+                }
+            })
+        };
+
+        let mut reply = Vec::new();
+        serve_client(&control, &br#"{"cmd":"optimize","aggressive":true}"#[..], &mut reply, false);
+        daemon.join().unwrap();
+        let reply: Result<OptimizeReport, String> = serde_json::from_slice(&reply).unwrap();
+        assert!(reply.unwrap().aggressive);
+        assert!(matches!(events.try_recv(), Ok(DaemonEvent::Optimized(r)) if r.freed_mb == 64.0));
+        assert_eq!(control.state.lock().unwrap().optimizations, 1);
+    }
+
+    #[test]
+    fn unprivileged_clients_cannot_pause() {
+        let control = DaemonControl::new(OptimizerConfig::default(), || {});
+        let mut reply = Vec::new();
+        serve_client(&control, &b"{\"cmd\":\"pause\"}\n{\"cmd\":\"bogus\"}\n"[..], &mut reply, false);
+        let lines: Vec<Result<serde_json::Value, String>> =
+            reply.split(|b| *b == b'\n').filter(|l| !l.is_empty()).map(|l| serde_json::from_slice(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|reply| reply.is_err()));
+        assert!(!control.is_paused());
+    }
+
+    #[test]
+    fn unprivileged_clients_see_a_redacted_config() {
+        let config = OptimizerConfig { fleet_token: Some("fleet-secret".into()), ..Default::default() };
+        let control = DaemonControl::new(config, || {});
+        let request = &b"{\"cmd\":\"get-config\"}\n"[..];
+
+        let mut reply = Vec::new();
+        serve_client(&control, request, &mut reply, false);
+        let config: Result<OptimizerConfig, String> = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(config.unwrap().fleet_token.as_deref(), Some("(set)"));
+
+        let mut reply = Vec::new();
+        serve_client(&control, request, &mut reply, true);
+        let config: Result<OptimizerConfig, String> = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(config.unwrap().fleet_token.as_deref(), Some("fleet-secret"));
+    }
+
+    #[test]
+    fn overlong_requests_end_the_connection() {
+        let control = DaemonControl::new(OptimizerConfig::default(), || {});
+        let mut request = vec![b' '; MAX_REQUEST_BYTES as usize + 10];
+        request.extend_from_slice(b"{\"cmd\":\"pause\"}\n");
+        let mut reply = Vec::new();
+        serve_client(&control, &request[..], &mut reply, true);
+        let reply: Result<serde_json::Value, String> = serde_json::from_slice(&reply).unwrap();
+        assert!(reply.unwrap_err().contains("longer than"));
+        assert!(!control.is_paused());
+    }
+}
@@ -0,0 +1,53 @@
+//! Advisory file locks shared between processes
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+/// Open (creating if needed) `path` and block until this process holds an
+/// exclusive lock on it; dropping the file releases the lock
+pub fn lock_exclusive(path: &Path) -> Result<File, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    sys::lock(&file).map_err(|e| format!("Failed to lock {:?}: {}", path, e))?;
+    Ok(file)
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::unix::io::AsRawFd;
+
+    pub fn lock(file: &std::fs::File) -> Result<(), String> {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::{LockFileEx, LOCKFILE_EXCLUSIVE_LOCK};
+    use windows::Win32::System::IO::OVERLAPPED;
+
+    pub fn lock(file: &std::fs::File) -> Result<(), String> {
+        let mut overlapped = OVERLAPPED::default();
+        unsafe {
+            LockFileEx(HANDLE(file.as_raw_handle()), LOCKFILE_EXCLUSIVE_LOCK, 0, u32::MAX, u32::MAX, &mut overlapped)
+        }
+        .map_err(|e| e.message().to_string())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub fn lock(_file: &std::fs::File) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -1,8 +1,16 @@
 //! Core optimizer logic
 
+pub mod budget;
 pub mod config;
+pub mod decision;
+pub mod history;
+pub mod ipc;
+pub mod lock;
 #[cfg(target_os = "windows")]
 pub mod optimizer;
-#[cfg(target_os = "windows")]
 pub mod patterns;
 pub mod process_scorer;
+pub mod scheduler;
+pub mod text;
+pub mod undo;
+pub mod watchdog;
@@ -7,22 +7,32 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
-use super::config::OptimizerConfig;
+use super::budget::{self, SelfMonitor};
+use super::config::{ClusterTrim, ConfigWatcher, OptimizerConfig};
+pub use super::decision::OptimizationDecision;
+use super::history::{self, HistoryRecord, OptimizationTrigger};
+use super::ipc::{DaemonControl, OptimizeReport};
 use super::patterns::MemoryPattern;
 use super::process_scorer::ProcessScorer;
+use super::scheduler::{self, ScheduledAction, Scheduler};
+use super::watchdog::Heartbeat;
+use crate::algorithms::forecast::{HoltForecaster, FORECAST_RUN_INTERVAL};
+use crate::algorithms::AccessTracker;
 use crate::neural::engine::NeuralDecisionEngine;
-use crate::windows::memory::{MemoryStatus, OptimizationResult, WindowsMemoryOptimizer};
+use crate::neural::refault::{RefaultTracker, REFAULT_SAMPLE_INTERVAL};
+use crate::windows::etw;
+use crate::windows::process::get_process_counters;
+use crate::platform::MemoryStatus;
+use crate::windows::memory::{OptimizationResult, WindowsMemoryOptimizer};
 use crate::bench::metrics::{BenchmarkMetrics, OptimizationMetrics};
-
-/// Decision from neural engine
-#[derive(Debug, Clone)]
-pub struct OptimizationDecision {
-    pub should_optimize: bool,
-    pub aggressive: bool,
-    pub confidence: f32,
-    pub reason: String,
-    pub target_processes: Vec<u32>,
-}
+use crate::monitor::alerts;
+use crate::monitor::faults;
+use crate::monitor::pressure::PressureWatcher;
+use crate::features::plugins::{OptimizeOutcome, OptimizeRequest, PluginHost, Sample, Verdict};
+use crate::features::report::{self, ReportPeriod};
+use crate::features::timeline;
+use crate::features::wsl2;
+use crate::security::broker::{BrokerClient, BrokerRequest};
 
 /// Intelligent memory optimizer with neural decision making
 pub struct IntelligentOptimizer {
@@ -32,24 +42,27 @@ pub struct IntelligentOptimizer {
     process_scorer: ProcessScorer,
     last_optimization: Option<Instant>,
     metrics: BenchmarkMetrics,
+    scheduler: Scheduler,
+    refault: RefaultTracker,
+    /// Page faults per process, so trims go to processes whose pages are cold
+    access: AccessTracker,
+    /// Trim mode of the last exploring run under `cluster_trim = "auto"`
+    last_clustered: bool,
+    /// Available memory trend; seeded from the timeline on the first tick
+    forecaster: Option<HoltForecaster>,
+    last_forecast_run: Option<Instant>,
+    plugins: PluginHost,
+    /// Set in a least-privilege worker; optimizations go through the broker
+    broker: Option<BrokerClient>,
+    /// Set in the service; runs are reported to connected clients
+    control: Option<Arc<DaemonControl>>,
 }
 
 impl IntelligentOptimizer {
     /// Create a new intelligent optimizer
     pub fn new(config: OptimizerConfig) -> Self {
         let windows_opt = WindowsMemoryOptimizer::new();
-        
-        let neural_engine = if config.neural_enabled {
-            match NeuralDecisionEngine::new(&config) {
-                Ok(engine) => Some(Arc::new(RwLock::new(engine))),
-                Err(e) => {
-                    warn!("Failed to initialize neural engine: {}. Using rule-based fallback.", e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
+        let neural_engine = Self::start_neural_engine(&config);
         
         Self {
             config,
@@ -58,37 +71,69 @@ impl IntelligentOptimizer {
             process_scorer: ProcessScorer::new(),
             last_optimization: None,
             metrics: BenchmarkMetrics::new(),
+            scheduler: Scheduler::load_default(),
+            refault: RefaultTracker::new(),
+            access: AccessTracker::new(),
+            last_clustered: false,
+            forecaster: None,
+            last_forecast_run: None,
+            plugins: PluginHost::load_default(),
+            broker: None,
+            control: None,
+        }
+    }
+    
+    fn start_neural_engine(config: &OptimizerConfig) -> Option<Arc<RwLock<NeuralDecisionEngine>>> {
+        if !config.neural_enabled {
+            return None;
+        }
+        match NeuralDecisionEngine::new(config) {
+            Ok(engine) => Some(Arc::new(RwLock::new(engine))),
+            Err(e) => {
+                warn!("Failed to initialize neural engine: {}. Using rule-based fallback.", e);
+                None
+            }
         }
     }
     
     /// Check current memory pressure and make optimization decision
     pub async fn evaluate(&self) -> Result<OptimizationDecision, String> {
-        let status = WindowsMemoryOptimizer::get_memory_status()?;
+        let status: MemoryStatus = (&WindowsMemoryOptimizer::get_memory_status()?).into();
         let pattern = MemoryPattern::from_status(&status);
         
         // Check minimum interval
-        if let Some(last) = self.last_optimization {
-            let elapsed = last.elapsed().as_secs();
-            if elapsed < self.config.min_interval_secs {
-                return Ok(OptimizationDecision {
-                    should_optimize: false,
-                    aggressive: false,
-                    confidence: 1.0,
-                    reason: format!("Cooldown: {}s remaining", 
-                        self.config.min_interval_secs - elapsed),
-                    target_processes: vec![],
-                });
-            }
+        if let Some(remaining) = self.cooldown_remaining() {
+            return Ok(OptimizationDecision {
+                should_optimize: false,
+                aggressive: false,
+                confidence: 1.0,
+                reason: format!("Cooldown: {}s remaining", remaining),
+                target_processes: vec![],
+            });
         }
         
+        if !self.config.allows_auto_optimize(status.memory_load_percent) {
+            return Ok(OptimizationDecision {
+                should_optimize: false,
+                aggressive: false,
+                confidence: 1.0,
+                reason: "Quiet hours".into(),
+                target_processes: vec![],
+            });
+        }
+
         // Neural decision if available
         if let Some(ref engine) = self.neural_engine {
             let engine = engine.read().await;
-            return engine.decide(&pattern, &status).await;
+            let decision = engine.decide(&pattern, &status).await?;
+            trace_decision("Neural", &decision);
+            return Ok(decision);
         }
         
         // Fallback to rule-based decision
-        self.rule_based_decision(&status)
+        let decision = self.rule_based_decision(&status)?;
+        trace_decision("Rules", &decision);
+        Ok(decision)
     }
     
     /// Rule-based fallback decision making
@@ -127,6 +172,15 @@ impl IntelligentOptimizer {
     
     /// Execute memory optimization based on decision
     pub async fn optimize(&mut self, decision: &OptimizationDecision) -> Result<OptimizationResult, String> {
+        self.optimize_with_trigger(decision, OptimizationTrigger::Auto).await
+    }
+
+    /// Execute memory optimization and record it under the given trigger
+    pub async fn optimize_with_trigger(
+        &mut self,
+        decision: &OptimizationDecision,
+        trigger: OptimizationTrigger,
+    ) -> Result<OptimizationResult, String> {
         if !decision.should_optimize {
             return Err("Optimization not recommended".into());
         }
@@ -135,8 +189,52 @@ impl IntelligentOptimizer {
         info!("Starting optimization (aggressive={}): {}", 
             decision.aggressive, decision.reason);
         
+        let load_before = WindowsMemoryOptimizer::get_memory_status()
+            .map(|s| s.memory_load_percent)
+            .ok();
+
+        let request = OptimizeRequest {
+            trigger,
+            aggressive: decision.aggressive,
+            reason: decision.reason.clone(),
+            load_percent: load_before,
+        };
+        if let Verdict::Skip(reason) = self.plugins.pre_optimize(&request) {
+            return Err(format!("Skipped by plugin {}", reason));
+        }
+
         // Execute Windows optimization
-        let result = self.windows_opt.optimize(decision.aggressive)?;
+        let result = match &self.broker {
+            Some(broker) => broker.call(&BrokerRequest::Optimize { aggressive: decision.aggressive }),
+            None => {
+                let clustered = self.trim_clusters().await;
+                self.windows_opt.set_coldness(self.access.coldness_all());
+                self.windows_opt.set_group_clusters(clustered);
+                self.windows_opt.optimize(decision.aggressive)
+            }
+        };
+        alerts::record_optimization(result.as_ref().map(|_| ()).map_err(String::as_str));
+        let result = result?;
+
+        // Hand unused WSL2 VM memory back to Windows under critical pressure
+        if decision.aggressive {
+            match wsl2::auto_reclaim() {
+                Some(Ok(reclaim)) => info!(
+                    "WSL2 reclaim ({}): freed {:.1} MB", reclaim.method, reclaim.freed_mb()),
+                Some(Err(e)) => warn!("WSL2 reclaim failed: {}", e),
+                None => {}
+            }
+        }
+
+        let mut record = HistoryRecord::now(
+            trigger,
+            decision.aggressive,
+            result.freed_mb,
+            result.processes_trimmed,
+            result.duration_ms,
+        );
+        record.memory_load_percent = load_before;
+        history::record(record);
         
         // Record metrics
         let opt_metrics = OptimizationMetrics {
@@ -147,17 +245,30 @@ impl IntelligentOptimizer {
             confidence: decision.confidence,
         };
         self.metrics.record_optimization(&opt_metrics);
+
+        self.plugins.post_optimize(&OptimizeOutcome {
+            trigger,
+            aggressive: decision.aggressive,
+            freed_mb: result.freed_mb,
+            processes_trimmed: result.processes_trimmed,
+            duration_ms: result.duration_ms,
+        });
         
         // Learn from result if enabled
         if self.config.learning_enabled {
             if let Some(ref engine) = self.neural_engine {
                 let mut engine = engine.write().await;
                 let success = result.freed_mb > 100.0; // Consider >100MB freed as success
-                engine.learn_from_result(&decision, &result, success).await;
+                engine.learn_from_result(decision, &(&result).into(), success).await;
+                let index = engine.pattern_count() - 1;
+                self.refault.track(index, &result.trimmed, get_process_counters);
             }
         }
         
         self.last_optimization = Some(Instant::now());
+        if let Some(ref control) = self.control {
+            control.record(report(trigger, decision.aggressive, &result));
+        }
         
         info!("Optimization complete: freed {:.1} MB in {}ms", 
             result.freed_mb, start.elapsed().as_millis());
@@ -165,6 +276,27 @@ impl IntelligentOptimizer {
         Ok(result)
     }
     
+    /// Whether this run trims loosely tied clusters together
+    ///
+    /// In auto mode runs alternate between the two ways until the neural
+    /// engine has measured enough of both to prefer one.
+    async fn trim_clusters(&mut self) -> bool {
+        match self.config.cluster_trim {
+            ClusterTrim::Off => false,
+            ClusterTrim::On => true,
+            ClusterTrim::Auto => {
+                let preferred = match &self.neural_engine {
+                    Some(engine) => engine.read().await.prefers_clusters(),
+                    None => None,
+                };
+                preferred.unwrap_or_else(|| {
+                    self.last_clustered = !self.last_clustered;
+                    self.last_clustered
+                })
+            }
+        }
+    }
+
     /// Run startup optimization mode (aggressive, one-shot)
     pub async fn startup_optimize(&mut self) -> Result<OptimizationResult, String> {
         info!("Running startup optimization mode");
@@ -181,33 +313,273 @@ impl IntelligentOptimizer {
             target_processes: vec![],
         };
         
-        self.optimize(&decision).await
+        self.optimize_with_trigger(&decision, OptimizationTrigger::Startup).await
     }
     
+    /// Answer the optimize requests clients queued on the control channel
+    ///
+    /// They share the cooldown with automatic runs, so the tray and CLI
+    /// cannot push the service past `min_interval_secs`.
+    pub async fn serve_requests(&mut self) {
+        let Some(control) = self.control.clone() else {
+            return;
+        };
+        for call in control.take_calls() {
+            let result = match self.cooldown_remaining() {
+                Some(remaining) => Err(format!("Cooldown: {}s remaining", remaining)),
+                None => {
+                    let decision = OptimizationDecision {
+                        should_optimize: true,
+                        aggressive: call.aggressive,
+                        confidence: 1.0,
+                        reason: "Requested by a client".into(),
+                        target_processes: vec![],
+                    };
+                    self.optimize_with_trigger(&decision, OptimizationTrigger::Manual)
+                        .await
+                        .map(|result| report(OptimizationTrigger::Manual, call.aggressive, &result))
+                }
+            };
+            control.reply(call, result);
+        }
+    }
+
+    /// Seconds left before `min_interval_secs` allows another run
+    fn cooldown_remaining(&self) -> Option<u64> {
+        let elapsed = self.last_optimization?.elapsed().as_secs();
+        (elapsed < self.config.min_interval_secs).then(|| self.config.min_interval_secs - elapsed)
+    }
+
+    /// Run a single evaluate/optimize cycle
+    ///
+    /// Returns `Ok(None)` when the decision was to skip.
+    pub async fn tick(&mut self, trigger: OptimizationTrigger) -> Result<Option<OptimizationResult>, String> {
+        self.access.record(faults::sample(), Instant::now());
+        self.sample_refaults().await;
+        self.sample_plugins();
+        self.sample_forecast();
+        self.run_due_schedules().await;
+
+        let mut decision = self.evaluate().await
+            .map_err(|e| format!("Evaluation failed: {}", e))?;
+
+        if !decision.should_optimize {
+            match self.forecast_decision() {
+                Some(forecast) => {
+                    info!("{}", forecast.reason);
+                    self.last_forecast_run = Some(Instant::now());
+                    decision = forecast;
+                }
+                None => {
+                    debug!("Skipping: {}", decision.reason);
+                    return Ok(None);
+                }
+            }
+        }
+
+        self.optimize_with_trigger(&decision, trigger).await
+            .map(Some)
+            .map_err(|e| format!("Optimization failed: {}", e))
+    }
+
+    /// Sample trimmed processes and learn from windows that have closed
+    pub async fn sample_refaults(&mut self) {
+        let reports = self.refault.poll(get_process_counters);
+        if let Some(ref engine) = self.neural_engine {
+            let mut engine = engine.write().await;
+            for report in &reports {
+                engine.learn_from_refault(report);
+            }
+        }
+    }
+
+    /// Pass the current memory status to plugins
+    fn sample_plugins(&mut self) {
+        if !self.plugins.is_active() {
+            return;
+        }
+        if let Ok(status) = WindowsMemoryOptimizer::get_memory_status() {
+            self.plugins.on_sample(&Sample::new(
+                status.memory_load_percent,
+                status.total_physical_mb,
+                status.available_physical_mb,
+            ));
+        }
+    }
+
+    /// Feed available memory to the forecaster
+    fn sample_forecast(&mut self) {
+        let Ok(status) = WindowsMemoryOptimizer::get_memory_status() else {
+            return;
+        };
+        let forecaster = self.forecaster.get_or_insert_with(|| {
+            timeline::forecaster(status.total_physical_mb).map(|(f, _)| f).unwrap_or_else(|e| {
+                debug!("Forecasting without history: {}", e);
+                HoltForecaster::new()
+            })
+        });
+        forecaster.observe(chrono::Utc::now().timestamp_millis().max(0) as u64, status.available_physical_mb);
+    }
+
+    /// A run ahead of time when available memory is forecast to reach the
+    /// critical threshold within `forecast_horizon_minutes`
+    fn forecast_decision(&self) -> Option<OptimizationDecision> {
+        let horizon = self.config.forecast_horizon_minutes;
+        if horizon == 0
+            || self.cooldown_remaining().is_some()
+            || self.last_forecast_run.is_some_and(|t| t.elapsed() < FORECAST_RUN_INTERVAL)
+        {
+            return None;
+        }
+        let status = WindowsMemoryOptimizer::get_memory_status().ok()?;
+        if !self.config.allows_auto_optimize(status.memory_load_percent) {
+            return None;
+        }
+        let floor_mb = status.total_physical_mb * 100u32.saturating_sub(self.config.critical_threshold) as f64 / 100.0;
+        let minutes = self.forecaster.as_ref()?.minutes_until(floor_mb, horizon)?;
+        Some(OptimizationDecision {
+            should_optimize: true,
+            aggressive: false,
+            confidence: 0.7,
+            reason: format!("Forecast: memory runs out in ~{} min", minutes),
+            target_processes: self.process_scorer.get_trim_candidates(10),
+        })
+    }
+
+    /// Sleep for `interval`, sampling re-faults along the way
+    async fn sleep_sampling_refaults(&mut self, interval: Duration) {
+        let deadline = Instant::now() + interval;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return;
+            }
+            if !self.refault.is_tracking() {
+                tokio::time::sleep(remaining).await;
+                return;
+            }
+            tokio::time::sleep(remaining.min(REFAULT_SAMPLE_INTERVAL)).await;
+            self.sample_refaults().await;
+        }
+    }
+
+    /// Drop the oldest learned patterns and run metrics to get back under
+    /// `self_max_rss_mb`
+    async fn trim_caches(&mut self) {
+        self.metrics.shrink();
+        if let Some(ref engine) = self.neural_engine {
+            let mut engine = engine.write().await;
+            let keep = budget::keep_after_trim(engine.pattern_count());
+            self.refault.forget_oldest(engine.shrink(keep));
+        }
+    }
+
+    /// Run every schedule that has come due since the last check
+    pub async fn run_due_schedules(&mut self) {
+        for schedule in self.scheduler.due_now() {
+            info!("Running schedule #{}: {}", schedule.id, schedule.describe());
+            match schedule.action {
+                ScheduledAction::Optimize | ScheduledAction::AggressiveOptimize => {
+                    let decision = OptimizationDecision {
+                        should_optimize: true,
+                        aggressive: schedule.action == ScheduledAction::AggressiveOptimize,
+                        confidence: 1.0,
+                        reason: format!("Schedule #{}", schedule.id),
+                        target_processes: vec![],
+                    };
+                    if let Err(e) = self.optimize_with_trigger(&decision, OptimizationTrigger::Scheduled).await {
+                        error!("Scheduled optimization failed: {}", e);
+                    }
+                }
+                ScheduledAction::BrowserTrim => {
+                    let (freed, trimmed) = scheduler::trim_browsers(OptimizationTrigger::Scheduled);
+                    info!("Scheduled browser trim: {:.1} MB from {} browsers", freed, trimmed);
+                }
+                ScheduledAction::DailyReport => report::run_scheduled(ReportPeriod::Day),
+                ScheduledAction::WeeklyReport => report::run_scheduled(ReportPeriod::Week),
+            }
+        }
+    }
+
     /// Main optimization loop
+    ///
+    /// Evaluates immediately when memory pressure rises, and every `interval`
+    /// otherwise so schedules run and sustained pressure is retried.
     pub async fn run_loop(&mut self, interval: Duration) -> ! {
         info!("Starting optimization loop (interval: {:?})", interval);
-        
+        let mut pressure = PressureWatcher::from_config(&self.config).subscribe();
+        let mut watching = true;
+        let mut config_watcher = ConfigWatcher::new();
+        let mut heartbeat = Heartbeat::from_env();
+        let mut self_monitor = SelfMonitor::new(&self.config);
+
         loop {
-            match self.evaluate().await {
-                Ok(decision) => {
-                    if decision.should_optimize {
-                        if let Err(e) = self.optimize(&decision).await {
-                            error!("Optimization failed: {}", e);
-                        }
-                    } else {
-                        debug!("Skipping: {}", decision.reason);
+            heartbeat.beat();
+            if let Some(config) = config_watcher.reload(&self.config) {
+                self.set_config(config);
+                self_monitor.set_config(&self.config);
+                pressure = PressureWatcher::from_config(&self.config).subscribe();
+                watching = true;
+            }
+
+            if let Err(e) = self.tick(OptimizationTrigger::Auto).await {
+                error!("{}", e);
+            }
+
+            if self_monitor.sample().is_some_and(|footprint| footprint.over_memory()) {
+                self.trim_caches().await;
+                self_monitor.trimmed();
+            }
+
+            tokio::select! {
+                event = pressure.next(), if watching => match event {
+                    Some(event) => {
+                        info!("Memory pressure {} ({}% used)",
+                            event.level(), event.reading().load_percent);
+                        self.plugins.on_pressure(&event);
                     }
-                }
-                Err(e) => {
-                    error!("Evaluation failed: {}", e);
-                }
+                    None => {
+                        warn!("Pressure watcher stopped, polling every {:?}", interval);
+                        watching = false;
+                    }
+                },
+                _ = self.sleep_sampling_refaults(self_monitor.stretch(interval)) => {}
             }
-            
-            tokio::time::sleep(interval).await;
         }
     }
     
+    /// Swap in a reloaded config without restarting
+    ///
+    /// Schedules are read again, and the neural engine is started or
+    /// dropped when `neural_enabled` changed.
+    pub fn set_config(&mut self, config: OptimizerConfig) {
+        if config.neural_enabled != self.config.neural_enabled {
+            self.neural_engine = Self::start_neural_engine(&config);
+        }
+        self.config = config;
+        self.scheduler = Scheduler::load_default();
+    }
+
+    /// Send optimizations to a privileged broker instead of running them here
+    pub fn set_broker(&mut self, broker: BrokerClient) {
+        self.broker = Some(broker);
+    }
+
+    /// Whether the broker connection has been lost
+    pub fn broker_lost(&self) -> bool {
+        self.broker.as_ref().is_some_and(|b| b.is_closed())
+    }
+
+    /// Report runs to, and take requests from, the control channel
+    pub fn set_control(&mut self, control: Arc<DaemonControl>) {
+        self.control = Some(control);
+    }
+
+    /// Config currently in effect
+    pub fn config(&self) -> &OptimizerConfig {
+        &self.config
+    }
+
     /// Get current metrics
     pub fn get_metrics(&self) -> &BenchmarkMetrics {
         &self.metrics
@@ -218,3 +590,17 @@ impl IntelligentOptimizer {
         self.neural_engine.is_some()
     }
 }
+
+fn report(trigger: OptimizationTrigger, aggressive: bool, result: &OptimizationResult) -> OptimizeReport {
+    OptimizeReport {
+        trigger,
+        aggressive,
+        freed_mb: result.freed_mb,
+        processes: result.processes_trimmed,
+        duration_ms: result.duration_ms,
+    }
+}
+
+fn trace_decision(source: &str, decision: &OptimizationDecision) {
+    etw::decision(source, decision.should_optimize, decision.aggressive, decision.confidence, &decision.reason);
+}
@@ -1,7 +1,7 @@
 //! Memory pattern representation for neural learning
 
 use chrono::{Datelike, Timelike};
-use crate::windows::memory::MemoryStatus;
+use crate::platform::MemoryStatus;
 use serde::{Deserialize, Serialize};
 
 /// Memory pattern vector for HNSW indexing
@@ -13,7 +13,7 @@ pub struct MemoryPattern {
     pub consumption_rate: f32,
     /// Available physical memory ratio
     pub available_ratio: f32,
-    /// Page file usage ratio
+    /// Swap (page file) usage ratio
     pub page_file_ratio: f32,
     /// Number of processes
     pub process_count: u32,
@@ -34,7 +34,7 @@ impl MemoryPattern {
             load: status.memory_load_percent as f32 / 100.0,
             consumption_rate: 0.0, // Would need historical data
             available_ratio: (status.available_physical_mb / status.total_physical_mb) as f32,
-            page_file_ratio: 1.0 - (status.available_page_file_mb / status.total_page_file_mb) as f32,
+            page_file_ratio: status.swap_used_ratio() as f32,
             process_count: 0, // Would need process enumeration
             hour: now.hour() as u8,
             day_of_week: now.weekday().num_days_from_monday() as u8,
@@ -72,4 +72,108 @@ pub struct LabeledPattern {
     pub freed_mb: f32,
     /// Whether the optimization was considered successful
     pub success: bool,
+    /// Whether loosely tied process clusters were trimmed together
+    #[serde(default)]
+    pub clustered: bool,
+    /// Re-fault penalty measured after the trim (0.0 stuck to 1.0 paged
+    /// straight back in), once its window has closed
+    #[serde(default)]
+    pub refault_penalty: Option<f32>,
+}
+
+/// Re-fault-measured runs each way of trimming needs before one is preferred
+pub const MIN_TRIM_MODE_RUNS: usize = 5;
+
+/// How one way of trimming, per process or by cluster, has worked out
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrimModeStats {
+    /// Optimizations run this way
+    pub runs: usize,
+    /// Of those, runs whose re-fault penalty has been measured
+    pub measured: usize,
+    /// Mean memory freed by the measured runs
+    pub mean_freed_mb: f32,
+    pub mean_refault_penalty: f32,
+}
+
+impl TrimModeStats {
+    /// Stats for the clustered or the per-process runs in `history`
+    pub fn from_history(history: &[LabeledPattern], clustered: bool) -> Self {
+        let runs: Vec<&LabeledPattern> = history
+            .iter()
+            .filter(|p| p.optimized && p.clustered == clustered)
+            .collect();
+        let measured: Vec<(f32, f32)> = runs
+            .iter()
+            .filter_map(|p| p.refault_penalty.map(|penalty| (p.freed_mb, penalty)))
+            .collect();
+        let n = measured.len().max(1) as f32;
+        Self {
+            runs: runs.len(),
+            measured: measured.len(),
+            mean_freed_mb: measured.iter().map(|(freed, _)| freed).sum::<f32>() / n,
+            mean_refault_penalty: measured.iter().map(|(_, penalty)| penalty).sum::<f32>() / n,
+        }
+    }
+
+    /// Memory that stayed reclaimed: what was freed less what paged back in
+    pub fn kept_mb(&self) -> f32 {
+        self.mean_freed_mb * (1.0 - self.mean_refault_penalty)
+    }
+
+    /// Whether cluster-grouped trimming has kept more memory reclaimed than
+    /// per-process trimming; `None` until both have enough measured runs
+    pub fn prefer_clusters(per_process: &Self, clustered: &Self) -> Option<bool> {
+        if per_process.measured < MIN_TRIM_MODE_RUNS || clustered.measured < MIN_TRIM_MODE_RUNS {
+            return None;
+        }
+        Some(clustered.kept_mb() > per_process.kept_mb())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(clustered: bool, freed_mb: f32, refault_penalty: Option<f32>) -> LabeledPattern {
+        LabeledPattern {
+            pattern: MemoryPattern {
+                load: 0.8,
+                consumption_rate: 0.0,
+                available_ratio: 0.2,
+                page_file_ratio: 0.0,
+                process_count: 150,
+                hour: 10,
+                day_of_week: 2,
+                time_since_last_opt: 0.0,
+            },
+            optimized: true,
+            aggressive: false,
+            freed_mb,
+            success: true,
+            clustered,
+            refault_penalty,
+        }
+    }
+
+    #[test]
+    fn test_trim_mode_preference() {
+        // Clusters free less but far less of it pages back in
+        let mut history: Vec<LabeledPattern> = (0..MIN_TRIM_MODE_RUNS)
+            .flat_map(|_| [run(false, 400.0, Some(0.6)), run(true, 300.0, Some(0.1))])
+            .collect();
+        history.push(run(true, 900.0, None));
+
+        let per_process = TrimModeStats::from_history(&history, false);
+        let clustered = TrimModeStats::from_history(&history, true);
+        assert_eq!((clustered.runs, clustered.measured), (MIN_TRIM_MODE_RUNS + 1, MIN_TRIM_MODE_RUNS));
+        assert!((clustered.mean_freed_mb - 300.0).abs() < 1e-3);
+        assert!((per_process.kept_mb() - 160.0).abs() < 1e-3);
+        assert_eq!(TrimModeStats::prefer_clusters(&per_process, &clustered), Some(true));
+
+        history.truncate(2);
+        let per_process = TrimModeStats::from_history(&history, false);
+        let clustered = TrimModeStats::from_history(&history, true);
+        assert_eq!(TrimModeStats::prefer_clusters(&per_process, &clustered), None);
+    }
 }
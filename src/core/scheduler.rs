@@ -0,0 +1,505 @@
+//! Cron-like optimization schedules
+//!
+//! Schedules are stored in `schedules.toml` under the config directory and are
+//! shared by the daemon, the Windows service and the tray app. Two kinds are
+//! supported:
+//! - `Daily` - fire once a day at a fixed local time
+//! - `Every` - fire every N minutes, optionally limited to an hour window
+//!
+//! Both can be restricted to a set of weekdays. Besides optimizing, a
+//! schedule can write a daily or weekly report.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// File name of the schedule store inside the config directory
+pub const SCHEDULES_FILE: &str = "schedules.toml";
+
+/// What a schedule does when it fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduledAction {
+    /// Normal system optimization
+    Optimize,
+    /// Aggressive system optimization
+    AggressiveOptimize,
+    /// Trim working sets of running browsers only
+    BrowserTrim,
+    /// Write a report covering the last day
+    DailyReport,
+    /// Write a report covering the last week
+    WeeklyReport,
+}
+
+impl std::fmt::Display for ScheduledAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduledAction::Optimize => write!(f, "optimize"),
+            ScheduledAction::AggressiveOptimize => write!(f, "aggressive"),
+            ScheduledAction::BrowserTrim => write!(f, "browser-trim"),
+            ScheduledAction::DailyReport => write!(f, "report-day"),
+            ScheduledAction::WeeklyReport => write!(f, "report-week"),
+        }
+    }
+}
+
+impl std::str::FromStr for ScheduledAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "optimize" => Ok(ScheduledAction::Optimize),
+            "aggressive" => Ok(ScheduledAction::AggressiveOptimize),
+            "browser-trim" | "browsers" => Ok(ScheduledAction::BrowserTrim),
+            "report-day" | "daily-report" => Ok(ScheduledAction::DailyReport),
+            "report-week" | "weekly-report" => Ok(ScheduledAction::WeeklyReport),
+            other => Err(format!(
+                "Unknown action: {} (optimize, aggressive, browser-trim, report-day, report-week)",
+                other
+            )),
+        }
+    }
+}
+
+/// When a schedule fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ScheduleSpec {
+    /// Once a day at `hour:minute` local time
+    Daily { hour: u32, minute: u32 },
+    /// Every `minutes` minutes
+    Every { minutes: u32 },
+}
+
+/// Which days a schedule is active on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DayFilter {
+    All,
+    Weekdays,
+    Weekends,
+    /// Explicit days, 0 = Monday .. 6 = Sunday
+    Days(Vec<u32>),
+}
+
+impl DayFilter {
+    pub fn matches(&self, day: Weekday) -> bool {
+        let idx = day.num_days_from_monday();
+        match self {
+            DayFilter::All => true,
+            DayFilter::Weekdays => idx < 5,
+            DayFilter::Weekends => idx >= 5,
+            DayFilter::Days(days) => days.contains(&idx),
+        }
+    }
+}
+
+impl std::str::FromStr for DayFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" | "daily" => Ok(DayFilter::All),
+            "weekdays" => Ok(DayFilter::Weekdays),
+            "weekends" => Ok(DayFilter::Weekends),
+            list => {
+                let days = list
+                    .split(',')
+                    .map(|d| match d.trim() {
+                        "mon" => Ok(0),
+                        "tue" => Ok(1),
+                        "wed" => Ok(2),
+                        "thu" => Ok(3),
+                        "fri" => Ok(4),
+                        "sat" => Ok(5),
+                        "sun" => Ok(6),
+                        other => Err(format!("Unknown day: {}", other)),
+                    })
+                    .collect::<Result<Vec<u32>, String>>()?;
+                Ok(DayFilter::Days(days))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DayFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+        match self {
+            DayFilter::All => write!(f, "every day"),
+            DayFilter::Weekdays => write!(f, "weekdays"),
+            DayFilter::Weekends => write!(f, "weekends"),
+            DayFilter::Days(days) => {
+                let names: Vec<&str> = days.iter()
+                    .filter_map(|d| NAMES.get(*d as usize).copied())
+                    .collect();
+                write!(f, "{}", names.join(","))
+            }
+        }
+    }
+}
+
+/// A single optimization schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: u32,
+    pub action: ScheduledAction,
+    pub spec: ScheduleSpec,
+    #[serde(default = "default_days")]
+    pub days: DayFilter,
+    /// Active hour window `[start, end)` for `Every` schedules
+    #[serde(default)]
+    pub hours: Option<(u32, u32)>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_days() -> DayFilter {
+    DayFilter::All
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Schedule {
+    /// Build a schedule from CLI-style arguments
+    ///
+    /// Exactly one of `at` (`HH:MM`) or `every` (minutes) must be given.
+    pub fn from_args(
+        action: &str,
+        at: Option<&str>,
+        every: Option<u32>,
+        days: &str,
+        hours: Option<&str>,
+    ) -> Result<Self, String> {
+        let spec = match (at, every) {
+            (Some(time), None) => {
+                let (hour, minute) = parse_time(time)?;
+                ScheduleSpec::Daily { hour, minute }
+            }
+            (None, Some(minutes)) if minutes > 0 => ScheduleSpec::Every { minutes },
+            (None, Some(_)) => return Err("--every must be at least 1 minute".into()),
+            _ => return Err("Specify exactly one of --at HH:MM or --every MINUTES".into()),
+        };
+
+        Ok(Self {
+            id: 0,
+            action: action.parse()?,
+            spec,
+            days: days.parse()?,
+            hours: hours.map(parse_hours).transpose()?,
+            enabled: true,
+        })
+    }
+
+    /// Human-readable description
+    pub fn describe(&self) -> String {
+        let when = match &self.spec {
+            ScheduleSpec::Daily { hour, minute } => format!("at {:02}:{:02}", hour, minute),
+            ScheduleSpec::Every { minutes } => format!("every {} min", minutes),
+        };
+        let window = self.hours
+            .map(|(s, e)| format!(" {:02}-{:02}h", s, e))
+            .unwrap_or_default();
+        format!("{} {}, {}{}", self.action, when, self.days, window)
+    }
+
+    fn in_window(&self, time: &DateTime<Local>) -> bool {
+        match self.hours {
+            Some((start, end)) => {
+                let h = time.hour();
+                if start <= end { h >= start && h < end } else { h >= start || h < end }
+            }
+            None => true,
+        }
+    }
+}
+
+/// Parse `HH:MM` into `(hour, minute)`
+pub fn parse_time(s: &str) -> Result<(u32, u32), String> {
+    let t = NaiveTime::parse_from_str(s, "%H:%M")
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", s))?;
+    Ok((t.hour(), t.minute()))
+}
+
+/// Parse `START-END` hour window (e.g. `9-18`)
+pub fn parse_hours(s: &str) -> Result<(u32, u32), String> {
+    let (a, b) = s.split_once('-').ok_or_else(|| format!("Invalid hours '{}', expected START-END", s))?;
+    let start: u32 = a.trim().parse().map_err(|_| format!("Invalid start hour '{}'", a))?;
+    let end: u32 = b.trim().parse().map_err(|_| format!("Invalid end hour '{}'", b))?;
+    if start > 23 || end > 24 {
+        return Err("Hours must be within 0-24".into());
+    }
+    Ok((start, end))
+}
+
+/// Trim all running browsers, recording the run in history under `trigger`
+///
+/// Returns `(freed_mb, processes_trimmed)`.
+pub fn trim_browsers(trigger: crate::core::history::OptimizationTrigger) -> (f64, usize) {
+    let start = std::time::Instant::now();
+    let mut optimizer = crate::apps::BrowserOptimizer::new();
+    optimizer.refresh();
+
+    let names: Vec<String> = optimizer.get_browsers().iter().map(|b| b.name.clone()).collect();
+    let mut freed = 0.0;
+    let mut trimmed = 0;
+    for name in &names {
+        let result = optimizer.trim_browser_memory(name);
+        if result.success {
+            freed += result.memory_freed_mb;
+            trimmed += 1;
+        }
+    }
+
+    crate::core::history::record(crate::core::history::HistoryRecord::now(
+        trigger,
+        false,
+        freed,
+        trimmed,
+        start.elapsed().as_millis() as u64,
+    ));
+    (freed, trimmed)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleFile {
+    #[serde(default)]
+    schedules: Vec<Schedule>,
+}
+
+/// Persistent schedule list
+pub struct ScheduleStore {
+    path: PathBuf,
+}
+
+impl ScheduleStore {
+    /// Store at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self { path: crate::features::config_file(SCHEDULES_FILE)? })
+    }
+
+    pub fn open(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Load all schedules (empty if the file does not exist)
+    pub fn load(&self) -> Result<Vec<Schedule>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        let file: ScheduleFile = toml::from_str(&content)
+            .map_err(|e| format!("Failed to parse {:?}: {}", self.path, e))?;
+        Ok(file.schedules)
+    }
+
+    pub fn save(&self, schedules: &[Schedule]) -> Result<(), String> {
+        let file = ScheduleFile { schedules: schedules.to_vec() };
+        let content = toml::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+
+    /// Add a schedule, assigning the next free ID
+    pub fn add(&self, mut schedule: Schedule) -> Result<u32, String> {
+        let mut schedules = self.load()?;
+        schedule.id = schedules.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        let id = schedule.id;
+        schedules.push(schedule);
+        self.save(&schedules)?;
+        Ok(id)
+    }
+
+    /// Remove a schedule by ID
+    pub fn remove(&self, id: u32) -> Result<(), String> {
+        let mut schedules = self.load()?;
+        let before = schedules.len();
+        schedules.retain(|s| s.id != id);
+        if schedules.len() == before {
+            return Err(format!("No schedule with id {}", id));
+        }
+        self.save(&schedules)
+    }
+}
+
+/// Evaluates schedules against the clock
+pub struct Scheduler {
+    schedules: Vec<Schedule>,
+    last_check: DateTime<Local>,
+    last_run: HashMap<u32, DateTime<Local>>,
+}
+
+impl Scheduler {
+    pub fn new(schedules: Vec<Schedule>) -> Self {
+        Self {
+            schedules,
+            last_check: Local::now(),
+            last_run: HashMap::new(),
+        }
+    }
+
+    /// Load schedules from the default store, logging instead of failing
+    pub fn load_default() -> Self {
+        let schedules = ScheduleStore::open_default()
+            .and_then(|store| store.load())
+            .unwrap_or_else(|e| {
+                warn!("Failed to load schedules: {}", e);
+                Vec::new()
+            });
+        Self::new(schedules)
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        &self.schedules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+
+    /// Schedules due at the current time
+    pub fn due_now(&mut self) -> Vec<Schedule> {
+        self.due(Local::now())
+    }
+
+    /// Schedules that became due between the previous check and `now`
+    ///
+    /// Missed `Daily` occurrences from before the scheduler started are not
+    /// replayed.
+    pub fn due(&mut self, now: DateTime<Local>) -> Vec<Schedule> {
+        let since = self.last_check;
+        let mut due = Vec::new();
+
+        for schedule in self.schedules.iter().filter(|s| s.enabled) {
+            let fire = match schedule.spec {
+                ScheduleSpec::Daily { hour, minute } => {
+                    let today = now.date_naive().and_hms_opt(hour, minute, 0)
+                        .and_then(|t| Local.from_local_datetime(&t).earliest());
+                    match today {
+                        Some(t) => t > since && t <= now && schedule.days.matches(t.weekday()),
+                        None => false,
+                    }
+                }
+                ScheduleSpec::Every { minutes } => {
+                    let elapsed_ok = match self.last_run.get(&schedule.id) {
+                        Some(last) => now - *last >= ChronoDuration::minutes(minutes as i64),
+                        None => true,
+                    };
+                    elapsed_ok && schedule.days.matches(now.weekday()) && schedule.in_window(&now)
+                }
+            };
+
+            if fire {
+                due.push(schedule.clone());
+            }
+        }
+
+        for schedule in &due {
+            self.last_run.insert(schedule.id, now);
+        }
+        self.last_check = now;
+        due
+    }
+
+    /// Print schedules as a table
+    pub fn print_schedules(schedules: &[Schedule]) {
+        println!("\n⏰ Optimization Schedules\n");
+
+        if schedules.is_empty() {
+            println!("  No schedules defined. Add one with `schedule add`.");
+            return;
+        }
+
+        println!("┌──────┬─────────┬──────────────────────────────────────────────────────┐");
+        println!("│ ID   │ Enabled │ Schedule                                             │");
+        println!("├──────┼─────────┼──────────────────────────────────────────────────────┤");
+        for s in schedules {
+            println!("│ {:>4} │ {:7} │ {:52} │",
+                s.id, if s.enabled { "yes" } else { "no" }, s.describe());
+        }
+        println!("└──────┴─────────┴──────────────────────────────────────────────────────┘");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_daily_fires_once() {
+        let mut scheduler = Scheduler::new(vec![Schedule {
+            id: 1,
+            action: ScheduledAction::AggressiveOptimize,
+            spec: ScheduleSpec::Daily { hour: 3, minute: 0 },
+            days: DayFilter::All,
+            hours: None,
+            enabled: true,
+        }]);
+        scheduler.last_check = at(2024, 5, 6, 2, 50);
+
+        assert!(scheduler.due(at(2024, 5, 6, 2, 59)).is_empty());
+        assert_eq!(scheduler.due(at(2024, 5, 6, 3, 1)).len(), 1);
+        assert!(scheduler.due(at(2024, 5, 6, 3, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_every_respects_window_and_days() {
+        let mut scheduler = Scheduler::new(vec![Schedule {
+            id: 1,
+            action: ScheduledAction::BrowserTrim,
+            spec: ScheduleSpec::Every { minutes: 30 },
+            days: "weekdays".parse().unwrap(),
+            hours: Some(parse_hours("9-18").unwrap()),
+            enabled: true,
+        }]);
+
+        // 2024-05-06 is a Monday, 2024-05-11 a Saturday
+        assert!(scheduler.due(at(2024, 5, 6, 8, 0)).is_empty());
+        assert_eq!(scheduler.due(at(2024, 5, 6, 9, 0)).len(), 1);
+        assert!(scheduler.due(at(2024, 5, 6, 9, 15)).is_empty());
+        assert_eq!(scheduler.due(at(2024, 5, 6, 9, 30)).len(), 1);
+        assert!(scheduler.due(at(2024, 5, 11, 10, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!("ruvector-schedules-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = ScheduleStore::open(path.clone());
+
+        let id = store.add(Schedule {
+            id: 0,
+            action: ScheduledAction::BrowserTrim,
+            spec: ScheduleSpec::Every { minutes: 30 },
+            days: DayFilter::Days(vec![0, 2]),
+            hours: Some((9, 18)),
+            enabled: true,
+        }).unwrap();
+        assert_eq!(id, 1);
+
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].spec, ScheduleSpec::Every { minutes: 30 });
+        assert_eq!(loaded[0].hours, Some((9, 18)));
+
+        store.remove(1).unwrap();
+        assert!(store.load().unwrap().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_helpers() {
+        assert_eq!(parse_time("03:30").unwrap(), (3, 30));
+        assert!(parse_time("25:00").is_err());
+        assert_eq!("mon,fri".parse::<DayFilter>().unwrap(), DayFilter::Days(vec![0, 4]));
+        assert_eq!("browser-trim".parse::<ScheduledAction>().unwrap(), ScheduledAction::BrowserTrim);
+    }
+}
@@ -0,0 +1,35 @@
+//! Text helpers for terminal tables
+
+/// `s` cut to `max` characters, ending in `...` when it had to be cut
+///
+/// Counts characters rather than bytes, so names in any script are cut on
+/// a character boundary.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let kept: String = s.chars().take(max.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    }
+}
+
+/// [`truncate`], padded with spaces to `max` characters for a table column
+pub fn truncate_padded(s: &str, max: usize) -> String {
+    format!("{:width$}", truncate(s, max), width = max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("firefox", 10), "firefox");
+        assert_eq!(truncate("code-insiders", 10), "code-in...");
+        // Multibyte names are cut between characters, not inside one
+        assert_eq!(truncate("Überwachungsdienst", 8), "Überw...");
+        assert_eq!(truncate("微信开发者工具进程", 6), "微信开...");
+        assert_eq!(truncate_padded("größe", 8), "größe   ");
+        assert_eq!(truncate_padded("Überwachungsdienst", 8), "Überw...");
+    }
+}
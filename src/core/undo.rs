@@ -0,0 +1,374 @@
+//! Undo journal for reversible optimizations
+//!
+//! Optimizations that change a process's state rather than its memory
+//! (suspending an app, pausing or stopping a container, lowering a priority
+//! or moving a process to other cores) record how to put it back in
+//! `undo.json` under [`crate::features::config_dir`]. `undo` reverts the most
+//! recent entry while it is younger than the undo window.
+//!
+//! Working-set trims have nothing to restore: trimmed pages fault back in on
+//! their own, so they are never journaled.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::core::config::OptimizerConfig;
+
+/// File name of the journal inside the config directory
+pub const UNDO_FILE: &str = "undo.json";
+
+/// Default for `OptimizerConfig::undo_window_minutes`
+pub const DEFAULT_UNDO_WINDOW_MINUTES: u64 = 10;
+
+/// Most entries kept; older ones are dropped when a new one is recorded
+const MAX_ENTRIES: usize = 20;
+
+/// One change and how to revert it
+///
+/// Priorities are priority classes on Windows and nice values elsewhere.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoAction {
+    /// Resume processes stopped with SIGSTOP
+    Resume { name: String, pids: Vec<u32> },
+    /// Put a process back at its original priority
+    RestorePriority { name: String, pid: u32, priority: i32 },
+    /// Put a process back at its original I/O priority (Windows)
+    RestoreIoPriority { name: String, pid: u32, level: u32 },
+    /// Put a process back on its original CPUs
+    RestoreAffinity { name: String, pid: u32, cpus: Vec<usize> },
+    /// `docker unpause` a paused container
+    UnpauseContainer { name: String },
+    /// `docker start` a stopped container
+    StartContainer { name: String },
+}
+
+impl UndoAction {
+    /// Revert the change; returns a line for the report
+    ///
+    /// Priority and affinity changes check the pid still belongs to the same
+    /// program, so a recycled pid is left alone. SIGCONT is harmless to a
+    /// process that is not stopped, so `Resume` needs no such check.
+    fn revert(&self, system: &System) -> Result<String, String> {
+        match self {
+            UndoAction::Resume { name, pids } => {
+                let resumed = pids.iter().filter(|&&pid| sys::resume(pid).is_ok()).count();
+                #[cfg(target_os = "macos")]
+                if let Some(&pid) = pids.first() {
+                    // Also forget it in suspended.json so it is not resumed twice
+                    crate::macos::process::resume_app_of(pid);
+                }
+                match resumed {
+                    0 => Err(format!("{} is no longer running", name)),
+                    n => Ok(format!("Resumed {} ({} processes)", name, n)),
+                }
+            }
+            UndoAction::RestorePriority { name, pid, priority } => {
+                if !same_process(system, *pid, name) {
+                    return Err(format!("{} ({}) is no longer running", name, pid));
+                }
+                sys::set_priority(*pid, *priority)?;
+                Ok(format!("Restored priority of {} ({})", name, pid))
+            }
+            UndoAction::RestoreIoPriority { name, pid, level } => {
+                if !same_process(system, *pid, name) {
+                    return Err(format!("{} ({}) is no longer running", name, pid));
+                }
+                sys::set_io_priority(*pid, *level)?;
+                Ok(format!("Restored I/O priority of {} ({})", name, pid))
+            }
+            UndoAction::RestoreAffinity { name, pid, cpus } => {
+                if !same_process(system, *pid, name) {
+                    return Err(format!("{} ({}) is no longer running", name, pid));
+                }
+                crate::accel::topology::set_process_affinity(*pid, cpus)?;
+                Ok(format!("Restored CPU affinity of {} ({})", name, pid))
+            }
+            UndoAction::UnpauseContainer { name } => {
+                docker(&["unpause", name])?;
+                Ok(format!("Unpaused container {}", name))
+            }
+            UndoAction::StartContainer { name } => {
+                docker(&["start", name])?;
+                Ok(format!("Started container {}", name))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for UndoAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UndoAction::Resume { name, pids } => write!(f, "resume {} ({} processes)", name, pids.len()),
+            UndoAction::RestorePriority { name, pid, .. } => write!(f, "restore priority of {} ({})", name, pid),
+            UndoAction::RestoreIoPriority { name, pid, .. } => write!(f, "restore I/O priority of {} ({})", name, pid),
+            UndoAction::RestoreAffinity { name, pid, .. } => write!(f, "restore affinity of {} ({})", name, pid),
+            UndoAction::UnpauseContainer { name } => write!(f, "unpause container {}", name),
+            UndoAction::StartContainer { name } => write!(f, "start container {}", name),
+        }
+    }
+}
+
+/// Everything one optimization changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub timestamp_ms: u64,
+    /// What was done, e.g. "Suspended Slack"
+    pub description: String,
+    pub actions: Vec<UndoAction>,
+}
+
+impl UndoEntry {
+    pub fn now(description: impl Into<String>, actions: Vec<UndoAction>) -> Self {
+        Self { timestamp_ms: now_ms(), description: description.into(), actions }
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(now_ms().saturating_sub(self.timestamp_ms))
+    }
+
+    pub fn is_expired(&self, window: Duration) -> bool {
+        self.age() > window
+    }
+
+    pub fn print(&self) {
+        println!("{} ({} min ago), undo would:", self.description, self.age().as_secs() / 60);
+        for action in self.actions.iter().rev() {
+            println!("  - {}", action);
+        }
+    }
+}
+
+/// What `undo_last` did
+#[derive(Debug, Clone)]
+pub struct UndoReport {
+    pub description: String,
+    pub reverted: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl UndoReport {
+    pub fn print(&self) {
+        println!("Undid: {}", self.description);
+        for line in &self.reverted {
+            println!("  ✓ {}", line);
+        }
+        for line in &self.failed {
+            println!("  ✗ {}", line);
+        }
+    }
+}
+
+/// Entries in `undo.json`, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoJournal {
+    pub entries: Vec<UndoEntry>,
+}
+
+impl UndoJournal {
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    /// Add an entry, dropping the oldest beyond `MAX_ENTRIES`
+    pub fn push(&mut self, entry: UndoEntry) {
+        self.entries.push(entry);
+        let excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.drain(..excess);
+    }
+
+    /// Most recent entry still inside `window`
+    pub fn last(&self, window: Duration) -> Option<&UndoEntry> {
+        self.entries.last().filter(|entry| !entry.is_expired(window))
+    }
+
+    /// Remove and return the most recent entry if it is inside `window`
+    ///
+    /// An expired entry is an error naming its age rather than `None`, so the
+    /// CLI can say why nothing was undone.
+    pub fn take_last(&mut self, window: Duration) -> Result<UndoEntry, String> {
+        let entry = self.entries.last().ok_or("Nothing to undo")?;
+        if entry.is_expired(window) {
+            return Err(format!(
+                "The last optimization ({}) was {} minutes ago; undo is only available for {} minutes",
+                entry.description,
+                entry.age().as_secs() / 60,
+                window.as_secs() / 60
+            ));
+        }
+        Ok(self.entries.pop().expect("checked above"))
+    }
+}
+
+/// Undo window from the config
+pub fn window(config: &OptimizerConfig) -> Duration {
+    Duration::from_secs(config.undo_window_minutes * 60)
+}
+
+/// Journal an optimization, logging instead of failing
+pub fn record(entry: UndoEntry) {
+    if entry.actions.is_empty() {
+        return;
+    }
+    let result = crate::features::config_file(UNDO_FILE).and_then(|path| {
+        let mut journal = UndoJournal::load(&path);
+        journal.push(entry);
+        journal.save(&path)
+    });
+    if let Err(e) = result {
+        tracing::warn!("Failed to record undo entry: {}", e);
+    }
+}
+
+/// The optimization `undo_last` would revert, if any
+pub fn last_undoable(window: Duration) -> Option<UndoEntry> {
+    let path = crate::features::config_file(UNDO_FILE).ok()?;
+    UndoJournal::load(&path).last(window).cloned()
+}
+
+/// Revert the most recent optimization if it is inside `window`
+pub fn undo_last(window: Duration) -> Result<UndoReport, String> {
+    let path = crate::features::config_file(UNDO_FILE)?;
+    let mut journal = UndoJournal::load(&path);
+    let entry = journal.take_last(window)?;
+    journal.save(&path)?;
+
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let mut report = UndoReport { description: entry.description, reverted: Vec::new(), failed: Vec::new() };
+    // Newest change first, so stacked changes to one process unwind in order
+    for action in entry.actions.iter().rev() {
+        match action.revert(&system) {
+            Ok(line) => report.reverted.push(line),
+            Err(e) => report.failed.push(format!("Could not {}: {}", action, e)),
+        }
+    }
+    Ok(report)
+}
+
+fn same_process(system: &System, pid: u32, name: &str) -> bool {
+    system
+        .process(Pid::from_u32(pid))
+        .is_some_and(|p| p.name().to_string_lossy().eq_ignore_ascii_case(name))
+}
+
+fn docker(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    pub fn resume(_pid: u32) -> Result<(), String> {
+        Err("Suspended processes are not supported on Windows".into())
+    }
+
+    pub fn set_priority(pid: u32, priority: i32) -> Result<(), String> {
+        crate::windows::process::set_priority_class(pid, priority as u32)
+    }
+
+    pub fn set_io_priority(pid: u32, level: u32) -> Result<(), String> {
+        crate::features::build::priority::set_io_priority(pid, level)
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use crate::security::audit::{self, AuditAction};
+
+    pub fn resume(pid: u32) -> Result<(), String> {
+        let result = if unsafe { libc::kill(pid as libc::pid_t, libc::SIGCONT) } == 0 {
+            Ok(())
+        } else {
+            Err(format!("kill({}) failed: {}", pid, std::io::Error::last_os_error()))
+        };
+        audit::record(AuditAction::Resume, "", Some(pid), "SIGCONT (undo)", &result);
+        result
+    }
+
+    pub fn set_priority(pid: u32, priority: i32) -> Result<(), String> {
+        let result = if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, priority) } == 0 {
+            Ok(())
+        } else {
+            Err(format!("setpriority({}) failed: {}", pid, std::io::Error::last_os_error()))
+        };
+        audit::record(AuditAction::Priority, "", Some(pid), &format!("nice {} (undo)", priority), &result);
+        result
+    }
+
+    pub fn set_io_priority(_pid: u32, _level: u32) -> Result<(), String> {
+        Err("I/O priority is only available on Windows".into())
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(DEFAULT_UNDO_WINDOW_MINUTES * 60);
+
+    fn entry(description: &str, age_minutes: u64) -> UndoEntry {
+        let mut entry = UndoEntry::now(description, vec![UndoAction::UnpauseContainer { name: "db".into() }]);
+        entry.timestamp_ms -= age_minutes * 60 * 1000;
+        entry
+    }
+
+    #[test]
+    fn test_take_last_respects_window() {
+        let mut journal = UndoJournal::default();
+        assert_eq!(journal.take_last(WINDOW).unwrap_err(), "Nothing to undo");
+
+        journal.push(entry("old", 30));
+        let err = journal.take_last(WINDOW).unwrap_err();
+        assert!(err.contains("30 minutes ago"));
+        assert_eq!(journal.entries.len(), 1);
+
+        journal.push(entry("recent", 2));
+        assert_eq!(journal.last(WINDOW).unwrap().description, "recent");
+        assert_eq!(journal.take_last(WINDOW).unwrap().description, "recent");
+        assert!(journal.last(WINDOW).is_none());
+    }
+
+    #[test]
+    fn test_push_caps_entries() {
+        let mut journal = UndoJournal::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            journal.push(entry(&i.to_string(), 0));
+        }
+        assert_eq!(journal.entries.len(), MAX_ENTRIES);
+        assert_eq!(journal.entries[0].description, "5");
+    }
+
+    #[test]
+    fn test_action_round_trip() {
+        let action = UndoAction::RestoreAffinity { name: "code".into(), pid: 42, cpus: vec![0, 1] };
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains("\"kind\":\"restore_affinity\""));
+        assert_eq!(serde_json::from_str::<UndoAction>(&json).unwrap(), action);
+    }
+}
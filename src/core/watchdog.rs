@@ -0,0 +1,447 @@
+//! Self-watchdog for the daemons and the tray
+//!
+//! [`Watchdog::run`] starts the program again as its child and restarts it
+//! when it crashes, or when its heartbeat goes quiet for longer than
+//! `hang_after`. Each restart leaves a report in `crashes/` in the config
+//! directory - the exit status, the child's panic backtrace if it panicked,
+//! and for a hang the state of its threads (Linux) or a minidump (Windows) -
+//! and puts "the optimizer restarted at 03:12" on the timeline. Restarts
+//! back off, and the watchdog gives up after [`MAX_RESTARTS`] in
+//! [`RESTART_WINDOW`].
+
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+use crate::features::timeline::{self, EventKind};
+
+/// Set in a supervised child: the file it writes its heartbeat to
+pub const HEARTBEAT_ENV: &str = "RUVECTOR_MEMOPT_HEARTBEAT";
+
+/// Directory of crash reports inside the config directory
+pub const CRASH_DIR: &str = "crashes";
+
+/// Reports kept in [`CRASH_DIR`]; older ones are removed
+pub const MAX_REPORTS: usize = 20;
+
+/// Shortest hang timeout for a daemon, however short its interval
+pub const MIN_HANG: Duration = Duration::from_secs(300);
+
+/// Restarts allowed within [`RESTART_WINDOW`] before giving up
+pub const MAX_RESTARTS: usize = 5;
+
+/// Window [`MAX_RESTARTS`] counts over
+pub const RESTART_WINDOW: Duration = Duration::from_secs(600);
+
+/// How often a child writes its heartbeat, at most
+const BEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the watchdog checks on its child
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Whether a watchdog started this process
+pub fn is_supervised() -> bool {
+    std::env::var_os(HEARTBEAT_ENV).is_some()
+}
+
+/// Hang timeout for a daemon that wakes every `interval`
+pub fn hang_after(interval: Duration) -> Duration {
+    (interval * 3).max(MIN_HANG)
+}
+
+/// A supervised child's sign of life; does nothing without a watchdog
+pub struct Heartbeat {
+    path: Option<PathBuf>,
+    last: Option<Instant>,
+}
+
+impl Heartbeat {
+    pub fn from_env() -> Self {
+        Self { path: std::env::var_os(HEARTBEAT_ENV).map(PathBuf::from), last: None }
+    }
+
+    /// Record that the main loop is alive; cheap enough to call every pass
+    pub fn beat(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if self.last.is_some_and(|last| last.elapsed() < BEAT_INTERVAL) {
+            return;
+        }
+        self.last = Some(Instant::now());
+        if let Err(e) = std::fs::write(path, now_ms().to_string()) {
+            tracing::debug!("Failed to write heartbeat {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Leave the panic and a backtrace in [`CRASH_DIR`] for the watchdog's report
+///
+/// Does nothing unless a watchdog started this process.
+pub fn install_panic_hook() {
+    if !is_supervised() {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Ok(dir) = crash_dir() {
+            let backtrace = std::backtrace::Backtrace::force_capture();
+            let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+            let path = dir.join(panic_file(std::process::id()));
+            if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "Thread '{}' {}\n\n{}\n", thread, info, backtrace);
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Why a child was restarted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// Exited with an error or was killed, as the exit status shows it
+    Crashed(String),
+    /// Its heartbeat was this many seconds old
+    Hung(u64),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Crashed(status) => write!(f, "crashed ({})", status),
+            Failure::Hung(secs) => write!(f, "stopped responding for {} s", secs),
+        }
+    }
+}
+
+/// Backoff between restarts, and when to stop restarting
+#[derive(Debug)]
+pub struct RestartPolicy {
+    restarts: VecDeque<Instant>,
+    delay: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self { restarts: VecDeque::new(), delay: MIN_BACKOFF }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before restarting a child that ran for `ran_for`, or `None`
+    /// once it has failed too often
+    pub fn next(&mut self, ran_for: Duration, now: Instant) -> Option<Duration> {
+        // A child that stayed up for a while starts over at the shortest delay
+        if ran_for >= RESTART_WINDOW {
+            self.delay = MIN_BACKOFF;
+        }
+        while self.restarts.front().is_some_and(|&at| now.duration_since(at) >= RESTART_WINDOW) {
+            self.restarts.pop_front();
+        }
+        if self.restarts.len() >= MAX_RESTARTS {
+            return None;
+        }
+        self.restarts.push_back(now);
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(MAX_BACKOFF);
+        Some(delay)
+    }
+}
+
+/// Runs this program as a child and restarts it when it crashes or hangs
+pub struct Watchdog {
+    /// Names the heartbeat, the reports and the timeline entry ("daemon", "tray")
+    pub name: String,
+    /// Arguments the child is started with
+    pub args: Vec<OsString>,
+    /// Restart when the heartbeat is older than this; `None` only watches for crashes
+    pub hang_after: Option<Duration>,
+}
+
+impl Watchdog {
+    /// Watch this program's own command line, less `--watchdog`
+    pub fn new(name: &str, hang_after: Option<Duration>) -> Self {
+        Self { name: name.to_string(), args: child_args(std::env::args_os().skip(1)), hang_after }
+    }
+
+    /// Run the child until it exits cleanly, restarting it after crashes and hangs
+    pub fn run(&self) -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let heartbeat = crate::features::config_file(&format!("heartbeat-{}", self.name))?;
+        let mut policy = RestartPolicy::default();
+        tracing::info!("Watchdog supervising the {}", self.name);
+        loop {
+            let _ = std::fs::remove_file(&heartbeat);
+            let started = Instant::now();
+            let mut child = spawn(&exe, &self.args, &heartbeat)?;
+            let pid = child.id();
+            let failure = self.watch(&mut child, &heartbeat, started);
+            let panic = crash_dir().ok().map(|dir| dir.join(panic_file(pid)));
+            let Some(failure) = failure else {
+                if let Some(panic) = panic {
+                    let _ = std::fs::remove_file(panic);
+                }
+                return Ok(());
+            };
+
+            tracing::error!("The {} (PID {}) {}", self.name, pid, failure);
+            match self.report(pid, &failure, started.elapsed(), panic.as_deref()) {
+                Ok(path) => tracing::info!("Crash report written to {}", path.display()),
+                Err(e) => tracing::warn!("Failed to write crash report: {}", e),
+            }
+            if let Failure::Hung(_) = failure {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            let delay = policy.next(started.elapsed(), Instant::now()).ok_or_else(|| {
+                format!(
+                    "The {} failed {} times in {} minutes; not restarting it again",
+                    self.name,
+                    MAX_RESTARTS,
+                    RESTART_WINDOW.as_secs() / 60
+                )
+            })?;
+            std::thread::sleep(delay);
+            timeline::record(
+                EventKind::Restart,
+                format!(
+                    "The optimizer {} restarted at {} after it {}",
+                    self.name,
+                    chrono::Local::now().format("%H:%M"),
+                    failure
+                ),
+            );
+        }
+    }
+
+    /// Wait for the child to exit or hang; `None` when it exited cleanly
+    fn watch(&self, child: &mut Child, heartbeat: &Path, started: Instant) -> Option<Failure> {
+        let started_ms = now_ms();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => return None,
+                Ok(Some(status)) => return Some(Failure::Crashed(describe(status))),
+                Ok(None) => {}
+                Err(e) => return Some(Failure::Crashed(e.to_string())),
+            }
+            if let Some(hang_after) = self.hang_after {
+                let last_ms = std::fs::read_to_string(heartbeat)
+                    .ok()
+                    .and_then(|beat| beat.trim().parse::<u64>().ok())
+                    .unwrap_or(started_ms)
+                    .max(started_ms);
+                let silent = Duration::from_millis(now_ms().saturating_sub(last_ms));
+                if silent > hang_after && started.elapsed() > hang_after {
+                    return Some(Failure::Hung(silent.as_secs()));
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Write what is known about a failed child, before a hung one is stopped
+    fn report(&self, pid: u32, failure: &Failure, uptime: Duration, panic: Option<&Path>) -> Result<PathBuf, String> {
+        let dir = crash_dir()?;
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let path = dir.join(format!("{}-{}.txt", self.name, stamp));
+        let mut report = format!(
+            "RuVector MemOpt {} {}\nTime: {}\nPID: {}\nUptime: {} s\nFailure: {}\n",
+            self.name,
+            env!("CARGO_PKG_VERSION"),
+            chrono::Local::now().to_rfc3339(),
+            pid,
+            uptime.as_secs(),
+            failure
+        );
+
+        if let Failure::Hung(_) = failure {
+            let threads = thread_states(pid);
+            if !threads.is_empty() {
+                report.push_str("\nThreads:\n");
+                for thread in threads {
+                    report.push_str(&format!("  {}\n", thread));
+                }
+            }
+            #[cfg(target_os = "windows")]
+            {
+                let dump = dir.join(format!("{}-{}.dmp", self.name, stamp));
+                match crate::windows::process::write_minidump(pid, &dump) {
+                    Ok(()) => report.push_str(&format!("\nMinidump: {}\n", dump.display())),
+                    Err(e) => report.push_str(&format!("\nMinidump failed: {}\n", e)),
+                }
+            }
+        }
+
+        if let Some(panic) = panic {
+            if let Ok(text) = std::fs::read_to_string(panic) {
+                report.push_str(&format!("\nPanic:\n{}", text));
+                let _ = std::fs::remove_file(panic);
+            }
+        }
+
+        std::fs::write(&path, report).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        prune(&dir, MAX_REPORTS);
+        Ok(path)
+    }
+}
+
+/// The command line for the child: the same, without `--watchdog`
+fn child_args(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    args.filter(|arg| arg != "--watchdog").collect()
+}
+
+fn spawn(exe: &Path, args: &[OsString], heartbeat: &Path) -> Result<Child, String> {
+    let mut command = Command::new(exe);
+    command.args(args).env(HEARTBEAT_ENV, heartbeat);
+    // Take the child down with the watchdog (`systemctl stop`, `kill`)
+    #[cfg(target_os = "linux")]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+        command.pre_exec(|| {
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+            Ok(())
+        });
+    }
+    command.spawn().map_err(|e| format!("Failed to start {:?}: {}", exe, e))
+}
+
+#[cfg(unix)]
+fn describe(status: ExitStatus) -> String {
+    use std::os::unix::process::ExitStatusExt;
+    match status.signal() {
+        Some(signal) => format!("killed by signal {}", signal),
+        None => status.to_string(),
+    }
+}
+
+#[cfg(not(unix))]
+fn describe(status: ExitStatus) -> String {
+    status.to_string()
+}
+
+/// Name, state and wait channel of each thread of a hung process
+#[cfg(target_os = "linux")]
+fn thread_states(pid: u32) -> Vec<String> {
+    let Ok(tasks) = std::fs::read_dir(format!("/proc/{}/task", pid)) else {
+        return Vec::new();
+    };
+    let mut threads: Vec<String> = tasks
+        .flatten()
+        .map(|task| {
+            let read = |file: &str| std::fs::read_to_string(task.path().join(file)).unwrap_or_default();
+            // The state follows the parenthesised name in `stat`
+            let stat = read("stat");
+            let state = stat.rsplit_once(") ").and_then(|(_, rest)| rest.split(' ').next()).unwrap_or("?");
+            let wchan = read("wchan");
+            format!(
+                "{} {} state {} waiting in {}",
+                task.file_name().to_string_lossy(),
+                read("comm").trim(),
+                state,
+                if wchan.is_empty() || wchan == "0" { "-" } else { wchan.trim() }
+            )
+        })
+        .collect();
+    threads.sort();
+    threads
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_states(_pid: u32) -> Vec<String> {
+    Vec::new()
+}
+
+/// Keep the newest `keep` reports (and their dumps) in `dir`
+fn prune(dir: &Path, keep: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter(|path| !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("panic-")))
+        .collect();
+    reports.sort_by_key(|path| (std::fs::metadata(path).and_then(|m| m.modified()).ok(), path.clone()));
+    let excess = reports.len().saturating_sub(keep);
+    for report in reports.into_iter().take(excess) {
+        let _ = std::fs::remove_file(report.with_extension("dmp"));
+        let _ = std::fs::remove_file(report);
+    }
+}
+
+fn crash_dir() -> Result<PathBuf, String> {
+    let dir = crate::features::config_file(CRASH_DIR)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+fn panic_file(pid: u32) -> String {
+    format!("panic-{}.txt", pid)
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_policy() {
+        let mut policy = RestartPolicy::default();
+        let start = Instant::now();
+        let quick = Duration::from_secs(5);
+        let delays: Vec<_> = (0..MAX_RESTARTS as u64)
+            .map(|i| policy.next(quick, start + Duration::from_secs(i * 10)).unwrap())
+            .collect();
+        assert_eq!(delays[0], MIN_BACKOFF);
+        assert_eq!(delays[1], MIN_BACKOFF * 2);
+        assert_eq!(delays[4], MIN_BACKOFF * 16);
+        // One more inside the window is too many
+        assert!(policy.next(quick, start + Duration::from_secs(60)).is_none());
+
+        // Once the window has passed, a child that stayed up restarts quickly again
+        let later = start + RESTART_WINDOW + Duration::from_secs(60);
+        assert_eq!(policy.next(RESTART_WINDOW, later), Some(MIN_BACKOFF));
+    }
+
+    #[test]
+    fn test_child_args_and_failures() {
+        let args = ["daemon", "--watchdog", "--interval", "30"].map(OsString::from);
+        assert_eq!(child_args(args.into_iter()), ["daemon", "--interval", "30"].map(OsString::from));
+        assert_eq!(Failure::Hung(312).to_string(), "stopped responding for 312 s");
+        assert_eq!(Failure::Crashed("exit status: 101".into()).to_string(), "crashed (exit status: 101)");
+        assert_eq!(hang_after(Duration::from_secs(60)), MIN_HANG);
+        assert_eq!(hang_after(Duration::from_secs(600)), Duration::from_secs(1800));
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_reports() {
+        let dir = std::env::temp_dir().join(format!("ruvector-watchdog-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..4 {
+            std::fs::write(dir.join(format!("daemon-{}.txt", i)), "report").unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        std::fs::write(dir.join("daemon-0.dmp"), "dump").unwrap();
+        std::fs::write(dir.join("panic-1.txt"), "panic").unwrap();
+        prune(&dir, 2);
+        let mut left: Vec<String> =
+            std::fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+        left.sort();
+        assert_eq!(left, ["daemon-2.txt", "daemon-3.txt", "panic-1.txt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
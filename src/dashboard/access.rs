@@ -0,0 +1,271 @@
+//! Access control for the dashboard server
+//!
+//! By default the server only listens on 127.0.0.1 and needs no
+//! credentials. To watch a machine from elsewhere on the LAN it can bind
+//! another address, but then every request must carry an API token
+//! (`Authorization: Bearer <token>`, or `?token=` for the WebSocket, which
+//! browsers can't add headers to) or basic auth credentials. Origins listed
+//! for CORS may call the API from a browser page, including the endpoints
+//! that change things; other pages may only do that from localhost.
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+
+use crate::security::validator::constant_time_eq;
+
+/// Environment variable read for the API token, which keeps it out of the
+/// process list
+pub const TOKEN_ENV: &str = "RUVECTOR_DASHBOARD_TOKEN";
+
+/// Realm sent with basic auth challenges
+const REALM: &str = "ruvector-memopt";
+
+/// What a client must present
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    None,
+    Token(String),
+    Basic { user: String, password: String },
+}
+
+impl Credentials {
+    /// `user:password`, as passed to `--basic-auth`
+    pub fn parse_basic(value: &str) -> Result<Self, String> {
+        match value.split_once(':') {
+            Some((user, password)) if !user.is_empty() && !password.is_empty() => {
+                Ok(Self::Basic { user: user.to_string(), password: password.to_string() })
+            }
+            _ => Err("Basic auth must look like user:password".to_string()),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    /// `WWW-Authenticate` header for a 401
+    pub fn challenge(&self) -> String {
+        match self {
+            Self::Basic { .. } => format!("Basic realm=\"{}\"", REALM),
+            _ => "Bearer".to_string(),
+        }
+    }
+}
+
+/// Certificate chain and private key, both PEM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// How and to whom the dashboard server is exposed
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    pub bind: IpAddr,
+    pub port: u16,
+    pub credentials: Credentials,
+    pub tls: Option<TlsFiles>,
+    /// Origins allowed to call the API from a browser, e.g.
+    /// `https://grafana.lan:3000`; `*` allows any
+    pub cors_origins: Vec<String>,
+    /// Accept samples from other machines (see [`crate::features::fleet`])
+    pub hub: bool,
+}
+
+impl ServeOptions {
+    /// Loopback only, no credentials, plain HTTP
+    pub fn local(port: u16) -> Self {
+        Self {
+            bind: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port,
+            credentials: Credentials::None,
+            tls: None,
+            cors_origins: Vec::new(),
+            hub: false,
+        }
+    }
+
+    /// Refuse setups that would expose the machine to anyone on the network
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.bind.is_loopback() && self.credentials.is_none() {
+            return Err(format!(
+                "Refusing to listen on {} without credentials; pass --token (or set {}) or --basic-auth",
+                self.bind, TOKEN_ENV
+            ));
+        }
+        if let Credentials::Token(token) = &self.credentials {
+            if token.len() < 16 {
+                return Err("The API token must be at least 16 characters".to_string());
+            }
+        }
+        if self.tls.is_some() && !cfg!(feature = "dashboard-tls") {
+            return Err("TLS needs a build with the dashboard-tls feature".to_string());
+        }
+        if self.cors_origins.iter().any(|o| o == "*") && !self.credentials.is_none() {
+            return Err("CORS origin * can't be combined with credentials; list the origins instead".to_string());
+        }
+        Ok(())
+    }
+
+    /// Base URL clients should use
+    pub fn url(&self) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        match self.bind {
+            IpAddr::V4(ip) if ip.is_loopback() => format!("{}://localhost:{}", scheme, self.port),
+            IpAddr::V6(ip) => format!("{}://[{}]:{}", scheme, ip, self.port),
+            ip => format!("{}://{}:{}", scheme, ip, self.port),
+        }
+    }
+}
+
+/// Checks applied to every request
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    credentials: Credentials,
+    /// Exact `Authorization` header value that is accepted
+    expected_header: Option<String>,
+    cors_origins: Vec<String>,
+}
+
+impl AccessPolicy {
+    pub fn new(options: &ServeOptions) -> Self {
+        let expected_header = match &options.credentials {
+            Credentials::None => None,
+            Credentials::Token(token) => Some(format!("Bearer {}", token)),
+            Credentials::Basic { user, password } => {
+                Some(format!("Basic {}", base64(format!("{}:{}", user, password).as_bytes())))
+            }
+        };
+        Self { credentials: options.credentials.clone(), expected_header, cors_origins: options.cors_origins.clone() }
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    /// Whether a request with this `Authorization` header and query string
+    /// may go through
+    pub fn authorized(&self, authorization: Option<&str>, query: Option<&str>) -> bool {
+        let Some(expected) = &self.expected_header else {
+            return true;
+        };
+        if authorization.is_some_and(|value| constant_time_eq(value.trim().as_bytes(), expected.as_bytes())) {
+            return true;
+        }
+        // Browsers can't set headers on a WebSocket, so a token may come in
+        // the query string instead
+        match &self.credentials {
+            Credentials::Token(token) => query
+                .into_iter()
+                .flat_map(|q| q.split('&'))
+                .filter_map(|pair| pair.strip_prefix("token="))
+                .any(|value| constant_time_eq(value.as_bytes(), token.as_bytes())),
+            _ => false,
+        }
+    }
+
+    /// Value for `Access-Control-Allow-Origin` when a browser page on
+    /// `origin` calls the API, if it is allowed to
+    pub fn cors_origin(&self, origin: &str) -> Option<String> {
+        if self.cors_origins.iter().any(|o| o == "*") {
+            Some("*".to_string())
+        } else if self.cors_origins.iter().any(|o| o.trim_end_matches('/') == origin) {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Whether a page on `origin` may change things (`POST`): localhost, or
+    /// an origin listed for CORS by name
+    pub fn may_act(&self, origin: &str) -> bool {
+        is_localhost(origin) || self.cors_origins.iter().any(|o| o.trim_end_matches('/') == origin)
+    }
+}
+
+fn is_localhost(origin: &str) -> bool {
+    let host = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")).unwrap_or("");
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Standard base64 with padding, for the basic auth header
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exposed(credentials: Credentials) -> ServeOptions {
+        ServeOptions { bind: "0.0.0.0".parse().unwrap(), credentials, ..ServeOptions::local(8080) }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ServeOptions::local(8080).validate().is_ok());
+        assert!(exposed(Credentials::None).validate().is_err());
+        assert!(exposed(Credentials::Token("short".into())).validate().is_err());
+        assert!(exposed(Credentials::Token("a-long-enough-token".into())).validate().is_ok());
+
+        let mut wildcard = exposed(Credentials::parse_basic("admin:secret").unwrap());
+        assert!(wildcard.validate().is_ok());
+        wildcard.cors_origins = vec!["*".into()];
+        assert!(wildcard.validate().is_err());
+
+        assert!(Credentials::parse_basic("admin").is_err());
+        assert!(Credentials::parse_basic(":secret").is_err());
+    }
+
+    #[test]
+    fn test_authorized() {
+        let open = AccessPolicy::new(&ServeOptions::local(8080));
+        assert!(open.authorized(None, None));
+
+        let token = AccessPolicy::new(&exposed(Credentials::Token("a-long-enough-token".into())));
+        assert!(token.authorized(Some("Bearer a-long-enough-token"), None));
+        assert!(token.authorized(None, Some("x=1&token=a-long-enough-token")));
+        assert!(!token.authorized(Some("Bearer a-long-enough-tokeN"), None));
+        assert!(!token.authorized(None, Some("token=nope")));
+        assert!(!token.authorized(None, None));
+
+        // "admin:secret" in base64
+        let basic = AccessPolicy::new(&exposed(Credentials::parse_basic("admin:secret").unwrap()));
+        assert!(basic.authorized(Some("Basic YWRtaW46c2VjcmV0"), None));
+        assert!(!basic.authorized(Some("Basic YWRtaW46c2VjcmV1"), None));
+        assert!(!basic.authorized(None, Some("token=admin:secret")));
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"a"), "YQ==");
+    }
+
+    #[test]
+    fn test_origins() {
+        let mut options = ServeOptions::local(8080);
+        options.cors_origins = vec!["https://grafana.lan:3000/".into()];
+        let policy = AccessPolicy::new(&options);
+        assert_eq!(policy.cors_origin("https://grafana.lan:3000").as_deref(), Some("https://grafana.lan:3000"));
+        assert_eq!(policy.cors_origin("https://evil.example"), None);
+        assert!(policy.may_act("http://localhost:8080"));
+        assert!(policy.may_act("http://127.0.0.1"));
+        assert!(policy.may_act("https://grafana.lan:3000"));
+        assert!(!policy.may_act("http://localhost.evil.example"));
+        assert!(!policy.may_act("https://evil.example"));
+    }
+}
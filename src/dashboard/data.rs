@@ -5,6 +5,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
+use crate::core::budget::Footprint;
+use crate::monitor::gpu::VramStatus;
+
 /// Real-time system metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemMetrics {
@@ -17,6 +20,12 @@ pub struct SystemMetrics {
     pub process_count: usize,
     pub optimization_count: u32,
     pub total_freed_mb: f64,
+    /// Per-GPU VRAM (empty when no GPU is detected)
+    #[serde(default)]
+    pub gpu: Vec<VramStatus>,
+    /// Memory compression store size (Windows 10 and later)
+    #[serde(default)]
+    pub compressed_mb: Option<f64>,
 }
 
 /// Algorithm performance metrics
@@ -78,6 +87,12 @@ pub struct DashboardData {
     pub spectral: SpectralState,
     pub sketch: SketchStats,
     pub history: Vec<HistoryPoint>,
+    /// Leak suspects raised by the background monitor within its window
+    #[serde(default)]
+    pub leak_suspects: usize,
+    /// Memory and CPU use of the running daemon or service
+    #[serde(default)]
+    pub footprint: Option<Footprint>,
 }
 
 /// Historical data point for charts
@@ -208,6 +223,8 @@ impl DashboardCollector {
             spectral,
             sketch,
             history: self.history.iter().cloned().collect(),
+            leak_suspects: 0,
+            footprint: None,
         }
     }
 
@@ -270,6 +287,8 @@ mod tests {
             process_count: 100,
             optimization_count: 5,
             total_freed_mb: 500.0,
+            gpu: Vec::new(),
+            compressed_mb: None,
         };
 
         let json = serde_json::to_string(&metrics).unwrap();
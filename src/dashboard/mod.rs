@@ -3,6 +3,7 @@
 //! Provides data structures and API for WASM-based browser dashboard
 //! or native terminal dashboard.
 
+pub mod access;
 pub mod data;
 #[cfg(target_os = "windows")]
 pub mod server;
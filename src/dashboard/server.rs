@@ -1,19 +1,86 @@
-//! Simple HTTP server for dashboard data
+//! HTTP server for dashboard data
 //!
-//! Serves JSON API for dashboard frontend
+//! Serves the JSON API for the dashboard frontend:
+//! - `GET /api/dashboard` - full `DashboardData` snapshot, including the
+//!   count of leak suspects for a badge
+//! - `GET /api/health` - `HealthReport` with subscores and weekly trend
+//! - `GET /api/timeline?at=14:32&minutes=30` - `TimelineView` with the memory
+//!   curve, events and the ones most likely behind a spike
+//! - `GET /api/forecast` - `ForecastView` with recent available memory and
+//!   the forecast every 5 minutes up to 2 hours, with 80% bands
+//! - `GET /api/leaks` - `LeakReport`s for processes sampled long enough to
+//!   classify, worst first
+//! - `GET /api/suggestions` - current `Suggestion`s, best first
+//! - `POST /api/suggestions/{id}/apply[?confirm=true]` - carry one out and
+//!   return its `SuggestionOutcome`; medium and high risk need `confirm`
+//! - `POST /api/optimize[?aggressive=true]` - optimize through the service
+//!   when it runs, like the `optimize` command, and return an `OptimizeReport`
+//! - `POST /api/profile/{name}/apply` - switch profiles and return the
+//!   `ProfileApplied`
+//! - `GET /api/config` - the `OptimizerConfig`; `POST` a JSON object of
+//!   (dotted) keys and values to change several at once, validated like
+//!   `config set`, and get the new config back
+//! - `GET /api/history?range=24h&trigger=auto&limit=50` - `HistoryView` with
+//!   the matching optimizations and their summary
+//! - `POST /api/fleet/push`, `GET /api/fleet` - in hub mode, a
+//!   `FleetSample` from another machine, and the `FleetView` of all of them
+//! - `GET /ws` - WebSocket pushing `DashboardUpdate` frames (memory samples
+//!   every second, optimization events, leak alerts)
+//!
+//! Every route goes through the [`AccessPolicy`] built from the
+//! [`ServeOptions`]: credentials, CORS and the cross-origin check on `POST`.
 
+use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
 use sysinfo::System;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
 
+use super::access::{AccessPolicy, ServeOptions, TlsFiles};
 use super::data::{
-    DashboardCollector, DashboardData, SystemMetrics, ClusterInfo, SpectralState, SketchStats,
-    ProcessInfo,
+    DashboardCollector, DashboardData, DashboardUpdate, SystemMetrics, ClusterInfo, SpectralState,
+    SketchStats, ProcessInfo, UpdateData, UpdateType,
 };
+use crate::algorithms::forecast::chart_horizons;
 use crate::algorithms::{MinCutClusterer, ProcessPageRank, CountMinSketch, SpectralAnalyzer};
+use crate::apps::{LeakDetector, LeakReport, LeakSeverity, SmartSuggestions, SuggestionRisk};
+use crate::core::budget::Footprint;
+use crate::core::config::{self, OptimizerConfig};
+use crate::core::history::{self, HistoryFilter, HistoryRecord, HistoryStore, HistorySummary, OptimizationTrigger, TimeRange};
+use crate::core::ipc::{IpcClient, OptimizeReport};
+use crate::features::health::{HealthReport, HealthScorer};
+use crate::features::fleet::{self, FleetSample, FleetStore};
+use crate::features::leakwatch::SuspectLog;
+use crate::features::profiles::{Profile, ProfileManager};
+use crate::features::timeline::{self, EventKind};
+use crate::monitor::gpu::{GpuMonitor, VramStatus};
+use crate::monitor::relations;
 use crate::windows::memory::WindowsMemoryOptimizer;
+use crate::windows::performance::compression_store_mb;
+use crate::windows::safety::{SafetyConfig, SafetyGuard};
+
+/// Buffered updates per WebSocket client before it starts dropping frames
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
+/// How often optimizations from other processes are picked up from history
+const HISTORY_POLL_SECS: u64 = 5;
+/// How often processes are sampled for leaks
+const LEAK_SAMPLE_SECS: u64 = 30;
 
 /// Dashboard server state
+///
+/// Cloning is cheap; all clones share the same state and update channel.
+#[derive(Clone)]
 pub struct DashboardServer {
     collector: Arc<RwLock<DashboardCollector>>,
     mincut: Arc<RwLock<MinCutClusterer>>,
@@ -21,10 +88,15 @@ pub struct DashboardServer {
     sketch: Arc<RwLock<CountMinSketch>>,
     spectral: Arc<RwLock<SpectralAnalyzer>>,
     system: Arc<RwLock<System>>,
+    gpu: Arc<GpuMonitor>,
+    health: Arc<std::sync::Mutex<HealthScorer>>,
+    leaks: Arc<RwLock<Vec<LeakReport>>>,
+    updates: broadcast::Sender<DashboardUpdate>,
 }
 
 impl DashboardServer {
     pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
         Self {
             collector: Arc::new(RwLock::new(DashboardCollector::new())),
             mincut: Arc::new(RwLock::new(MinCutClusterer::new())),
@@ -32,9 +104,70 @@ impl DashboardServer {
             sketch: Arc::new(RwLock::new(CountMinSketch::new(0.01, 0.001))),
             spectral: Arc::new(RwLock::new(SpectralAnalyzer::new(60))),
             system: Arc::new(RwLock::new(System::new_all())),
+            gpu: Arc::new(GpuMonitor::default()),
+            health: Arc::new(std::sync::Mutex::new(HealthScorer::new())),
+            leaks: Arc::new(RwLock::new(Vec::new())),
+            updates,
         }
     }
 
+    /// Subscribe to live updates
+    pub fn subscribe(&self) -> broadcast::Receiver<DashboardUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Push an update to all subscribers
+    pub fn publish(&self, update_type: UpdateType, data: UpdateData) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.updates.send(DashboardUpdate {
+            update_type,
+            timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+            data,
+        });
+    }
+
+    /// Poll GPU VRAM off the async runtime (nvidia-smi spawns a process)
+    async fn gpu_status(&self) -> Vec<VramStatus> {
+        let gpu = self.gpu.clone();
+        tokio::task::spawn_blocking(move || gpu.status()).await.unwrap_or_default()
+    }
+
+    /// Take a lightweight memory sample and publish it
+    pub async fn sample_metrics(&self) -> Result<SystemMetrics, String> {
+        let status = WindowsMemoryOptimizer::get_memory_status()?;
+        let (process_count, compressed_mb) = {
+            let system = self.system.read().await;
+            (system.processes().len(), compression_store_mb(&system))
+        };
+        let gpu = self.gpu_status().await;
+
+        let mut collector = self.collector.write().await;
+        collector.record_sample(status.memory_load_percent as f64);
+        let stats = collector.stats();
+
+        let metrics = SystemMetrics {
+            timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+            memory_used_mb: status.used_physical_mb() as u64,
+            memory_total_mb: status.total_physical_mb as u64,
+            memory_percent: status.memory_load_percent as f64,
+            swap_used_mb: (status.total_page_file_mb - status.available_page_file_mb) as u64,
+            swap_total_mb: status.total_page_file_mb as u64,
+            process_count,
+            optimization_count: stats.optimization_count,
+            total_freed_mb: stats.total_freed_mb,
+            gpu,
+            compressed_mb,
+        };
+
+        self.publish(UpdateType::Metrics, UpdateData::Metrics(metrics.clone()));
+        Ok(metrics)
+    }
+
+    /// Publish an alert
+    pub fn alert(&self, level: &str, message: String) {
+        self.publish(UpdateType::Alert, UpdateData::Alert { level: level.to_string(), message });
+    }
+
     /// Update all data and return dashboard state
     pub async fn update(&self) -> Result<DashboardData, String> {
         // Refresh system info
@@ -77,13 +210,14 @@ impl DashboardServer {
         };
 
         // Update MinCut and PageRank
+        let relations = relations::collect(&system);
         let clusters = {
             let mut mincut = self.mincut.write().await;
             mincut.build_graph(&system);
             let raw_clusters = mincut.find_clusters(5);
 
             let mut pagerank = self.pagerank.write().await;
-            pagerank.compute(&system);
+            pagerank.compute_with(&system, &relations);
 
             raw_clusters
                 .into_iter()
@@ -125,19 +259,32 @@ impl DashboardServer {
             process_count: system.processes().len(),
             optimization_count: 0,
             total_freed_mb: 0.0,
+            gpu: self.gpu_status().await,
+            compressed_mb: compression_store_mb(&system),
         };
 
         // Record sample
         let mut collector = self.collector.write().await;
         collector.record_sample(metrics.memory_percent);
 
-        Ok(collector.get_data(metrics, clusters, spectral_state, sketch_stats))
+        let mut data = collector.get_data(metrics, clusters, spectral_state, sketch_stats);
+        data.leak_suspects = SuspectLog::open_default().and_then(|log| log.active()).map_or(0, |s| s.len());
+        data.footprint = daemon_footprint().await;
+        Ok(data)
     }
 
-    /// Record an optimization result
-    pub async fn record_optimization(&self, freed_mb: f64) {
-        let mut collector = self.collector.write().await;
-        collector.record_optimization(freed_mb);
+    /// Compute the health score off the async runtime (it shells out for sensors)
+    pub async fn health(&self) -> Result<HealthReport, String> {
+        let health = self.health.clone();
+        tokio::task::spawn_blocking(move || health.lock().unwrap_or_else(|e| e.into_inner()).compute())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Record an optimization result and publish it
+    pub async fn record_optimization(&self, freed_mb: f64, duration_ms: u64) {
+        self.collector.write().await.record_optimization(freed_mb);
+        self.publish(UpdateType::Optimization, UpdateData::Optimization { freed_mb, duration_ms });
     }
 
     /// Get JSON data
@@ -146,36 +293,495 @@ impl DashboardServer {
         serde_json::to_string_pretty(&data).map_err(|e| e.to_string())
     }
 
-    /// Run HTTP server (simplified - prints to console)
-    pub async fn serve(&self, port: u16) -> Result<(), String> {
-        println!("🌐 Dashboard server starting on http://localhost:{}", port);
+    /// Run the HTTP/WebSocket server until the process exits
+    pub async fn serve(&self, options: ServeOptions) -> Result<(), String> {
+        options.validate()?;
+
+        // Populate process and algorithm state before the first request
+        self.update().await?;
+        tokio::spawn(self.clone().run_publisher());
+
+        let policy = Arc::new(AccessPolicy::new(&options));
+        let mut app = Router::new()
+            .route("/api/dashboard", get(api_dashboard))
+            .route("/api/health", get(api_health))
+            .route("/api/timeline", get(api_timeline))
+            .route("/api/forecast", get(api_forecast))
+            .route("/api/leaks", get(api_leaks))
+            .route("/api/suggestions", get(api_suggestions))
+            .route("/api/suggestions/:id/apply", post(api_apply_suggestion))
+            .route("/api/optimize", post(api_optimize))
+            .route("/api/profile/:name/apply", post(api_apply_profile))
+            .route("/api/config", get(api_config).post(api_set_config))
+            .route("/api/history", get(api_history))
+            .route("/ws", get(ws_upgrade));
+        if options.hub {
+            app = app.route(fleet::PUSH_PATH, post(api_fleet_push)).route("/api/fleet", get(api_fleet));
+            tokio::spawn(prune_fleet());
+        }
+        let app = app
+            .layer(middleware::from_fn_with_state(policy, guard))
+            .with_state(self.clone());
+
+        let addr = SocketAddr::new(options.bind, options.port);
+        println!("🌐 Dashboard server running on {}", options.url());
+        println!("   GET /api/dashboard  Full dashboard snapshot (JSON)");
+        println!("   GET /api/health     Health score and weekly trend (JSON)");
+        println!("   GET /api/timeline   Events and memory curve, ?at=HH:MM&minutes=N (JSON)");
+        println!("   GET /api/forecast   Available memory forecast with bands (JSON)");
+        println!("   GET /api/leaks      Leak reports with growth rate and R² (JSON)");
+        println!("   GET /api/suggestions  Ranked suggestions with ids and risk (JSON)");
+        println!("   POST /api/suggestions/ID/apply  Apply one (?confirm=true above low risk)");
+        println!("   POST /api/optimize  Optimize now (?aggressive=true)");
+        println!("   POST /api/profile/NAME/apply  Switch profiles");
+        println!("   GET|POST /api/config  Read or change settings (JSON)");
+        println!("   GET /api/history    Optimizations, ?range=24h&trigger=auto&limit=N (JSON)");
+        println!("   GET /ws             Live updates (WebSocket)");
+        if options.hub {
+            println!("   POST /api/fleet/push  Samples from other machines (hub mode)");
+            println!("   GET /api/fleet      Every machine's latest state and last hour (JSON)");
+        }
+        if !options.bind.is_loopback() && options.tls.is_none() {
+            println!("⚠️  Without --tls-cert/--tls-key credentials cross the network in the clear");
+        }
         println!("   Press Ctrl+C to stop\n");
 
-        // In a real implementation, this would use a web framework like axum or warp
-        // For now, we'll just print updates periodically
+        match &options.tls {
+            Some(tls) => serve_tls(addr, tls, app).await,
+            None => {
+                let listener = tokio::net::TcpListener::bind(addr).await
+                    .map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+                axum::serve(listener, app).await.map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// Background task feeding the update channel
+    async fn run_publisher(self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        let mut last_history_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let mut leak_detector = LeakDetector::new();
+        let mut reported_leaks: HashSet<u32> = HashSet::new();
+        let mut tick: u64 = 0;
+
         loop {
-            match self.update().await {
-                Ok(data) => {
-                    println!("\n📊 Dashboard Update");
-                    println!("   Memory: {:.1}% ({} MB / {} MB)",
-                        data.metrics.memory_percent,
-                        data.metrics.memory_used_mb,
-                        data.metrics.memory_total_mb
-                    );
-                    println!("   Processes: {}", data.metrics.process_count);
-                    println!("   Pattern: {} (confidence: {:.0}%)",
-                        data.spectral.pattern_class,
-                        data.spectral.confidence * 100.0
-                    );
-                    println!("   Clusters: {}", data.clusters.len());
-                    println!("   Sketch events: {}", data.sketch.total_events);
+            ticker.tick().await;
+            tick += 1;
+
+            if let Err(e) = self.sample_metrics().await {
+                debug!("Dashboard sample failed: {}", e);
+            }
+
+            // Optimizations run by the tray, service or CLI land in history
+            if tick % HISTORY_POLL_SECS == 0 {
+                let range = TimeRange { start_ms: Some(last_history_ms + 1), end_ms: None };
+                match HistoryStore::open_default().and_then(|s| s.query(range, &HistoryFilter::default())) {
+                    Ok(records) => {
+                        for record in records {
+                            last_history_ms = last_history_ms.max(record.timestamp_ms);
+                            self.record_optimization(record.freed_mb, record.duration_ms).await;
+                        }
+                    }
+                    Err(e) => debug!("History poll failed: {}", e),
                 }
-                Err(e) => {
-                    eprintln!("   Error: {}", e);
+            }
+
+            if tick % LEAK_SAMPLE_SECS == 0 {
+                leak_detector.sample();
+                let reports = leak_detector.reports();
+                for leak in reports.iter().filter(|l| l.severity.is_leak()) {
+                    if reported_leaks.insert(leak.pid) {
+                        let level = if leak.severity == LeakSeverity::Critical { "critical" } else { "warning" };
+                        let message = format!(
+                            "Possible leak in {} (PID {}): +{:.0} MB/h, now {:.0} MB",
+                            leak.process_name, leak.pid, leak.growth_rate_mb_per_hour, leak.current_memory_mb
+                        );
+                        timeline::record(EventKind::LeakAlert, message.clone());
+                        self.alert(level, message);
+                    }
                 }
+                *self.leaks.write().await = reports;
             }
+        }
+    }
+}
+
+#[cfg(feature = "dashboard-tls")]
+async fn serve_tls(addr: SocketAddr, tls: &TlsFiles, app: Router) -> Result<(), String> {
+    let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert, &tls.key).await
+        .map_err(|e| format!("Failed to load {} / {}: {}", tls.cert.display(), tls.key.display(), e))?;
+    axum_server::bind_rustls(addr, tls_config)
+        .serve(app.into_make_service())
+        .await
+        .map_err(|e| format!("Failed to serve on {}: {}", addr, e))
+}
+
+#[cfg(not(feature = "dashboard-tls"))]
+async fn serve_tls(_addr: SocketAddr, _tls: &TlsFiles, _app: Router) -> Result<(), String> {
+    unreachable!("ServeOptions::validate rejects TLS without the dashboard-tls feature")
+}
+
+/// Credentials, CORS and the cross-origin check in front of every route
+async fn guard(State(policy): State<Arc<AccessPolicy>>, request: Request, next: Next) -> Response {
+    let origin = request.headers().get(header::ORIGIN).and_then(|o| o.to_str().ok()).map(str::to_string);
+    let allow_origin = origin.as_deref().and_then(|o| policy.cors_origin(o));
+
+    // Browsers send preflights without credentials
+    if request.method() == Method::OPTIONS && allow_origin.is_some() {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET, POST"));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static("authorization, content-type"));
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("600"));
+        return with_cors(response, allow_origin);
+    }
+
+    let authorization = request.headers().get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    if !policy.authorized(authorization, request.uri().query()) {
+        let mut response = (StatusCode::UNAUTHORIZED, "Missing or wrong credentials").into_response();
+        if let Ok(challenge) = HeaderValue::from_str(&policy.credentials().challenge()) {
+            response.headers_mut().insert(header::WWW_AUTHENTICATE, challenge);
+        }
+        return with_cors(response, allow_origin);
+    }
+
+    // Only the dashboard itself and listed origins may act; a page on another
+    // origin could otherwise post here from the user's browser
+    if request.method() == Method::POST && origin.as_deref().is_some_and(|o| !policy.may_act(o)) {
+        let response = (StatusCode::FORBIDDEN, "Cross-origin requests may not change anything").into_response();
+        return with_cors(response, allow_origin);
+    }
+
+    with_cors(next.run(request).await, allow_origin)
+}
+
+fn with_cors(mut response: Response, allow_origin: Option<String>) -> Response {
+    if let Some(value) = allow_origin.and_then(|o| HeaderValue::from_str(&o).ok()) {
+        let headers = response.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+    }
+    response
+}
+
+async fn api_dashboard(State(server): State<DashboardServer>) -> Response {
+    match server.update().await {
+        Ok(data) => Json(data).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+async fn api_health(State(server): State<DashboardServer>) -> Response {
+    match server.health().await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+async fn api_leaks(State(server): State<DashboardServer>) -> Response {
+    Json(server.leaks.read().await.clone()).into_response()
+}
+
+/// Suggestions built with the leak reports sampled so far
+fn suggestion_engine(leaks: Vec<LeakReport>) -> SmartSuggestions {
+    let mut engine = SmartSuggestions::new();
+    engine.set_leak_reports(leaks);
+    engine.refresh();
+    engine
+}
+
+async fn api_suggestions(State(server): State<DashboardServer>) -> Response {
+    let leaks = server.leaks.read().await.clone();
+    match tokio::task::spawn_blocking(move || suggestion_engine(leaks).get_suggestions().to_vec()).await {
+        Ok(suggestions) => Json(suggestions).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ApplyParams {
+    #[serde(default)]
+    confirm: bool,
+}
+
+async fn api_apply_suggestion(
+    State(server): State<DashboardServer>,
+    Path(id): Path<String>,
+    Query(params): Query<ApplyParams>,
+) -> Response {
+    let leaks = server.leaks.read().await.clone();
+    let applied = tokio::task::spawn_blocking(move || {
+        let mut engine = suggestion_engine(leaks);
+        match engine.get(&id) {
+            None => Err((StatusCode::NOT_FOUND, format!("No suggestion '{}'", id))),
+            Some(s) if s.risk > SuggestionRisk::Low && !params.confirm => Err((
+                StatusCode::CONFLICT,
+                format!("'{}' is {} risk; repeat with ?confirm=true", s.title, s.risk),
+            )),
+            Some(_) => engine.apply(&id).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e)),
+        }
+    })
+    .await;
+    match applied {
+        Ok(Ok(outcome)) => Json(outcome).into_response(),
+        Ok(Err((status, e))) => (status, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct OptimizeParams {
+    #[serde(default)]
+    aggressive: bool,
+}
+
+/// The service's own memory and CPU use, asked over its pipe off the runtime
+async fn daemon_footprint() -> Option<Footprint> {
+    tokio::task::spawn_blocking(|| IpcClient::connect()?.status().ok()?.footprint)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Optimize through the service when it runs, so its rate limit and safety
+/// checks apply, and in this process otherwise
+fn optimize_now(aggressive: bool) -> Result<OptimizeReport, String> {
+    if let Some(mut service) = IpcClient::connect() {
+        return service.optimize(aggressive);
+    }
+    let status = WindowsMemoryOptimizer::get_memory_status()?;
+    SafetyGuard::new(SafetyConfig::default()).check_safe(status.available_physical_mb)?;
+    let result = WindowsMemoryOptimizer::new().optimize(aggressive)?;
+    history::record(
+        HistoryRecord::now(
+            OptimizationTrigger::Manual,
+            aggressive,
+            result.freed_mb,
+            result.processes_trimmed,
+            result.duration_ms,
+        )
+        .with_memory_load(status.memory_load_percent),
+    );
+    Ok(OptimizeReport {
+        trigger: OptimizationTrigger::Manual,
+        aggressive,
+        freed_mb: result.freed_mb,
+        processes: result.processes_trimmed,
+        duration_ms: result.duration_ms,
+    })
+}
+
+async fn api_optimize(Query(params): Query<OptimizeParams>) -> Response {
+    match tokio::task::spawn_blocking(move || optimize_now(params.aggressive)).await {
+        Ok(Ok(report)) => Json(report).into_response(),
+        Ok(Err(e)) => (StatusCode::CONFLICT, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Result of `POST /api/profile/{name}/apply`
+#[derive(Serialize)]
+struct ProfileApplied {
+    active: String,
+    profile: Profile,
+}
+
+async fn api_apply_profile(Path(name): Path<String>) -> Response {
+    let applied = tokio::task::spawn_blocking(move || {
+        let mut manager = ProfileManager::open_default().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let name = name.to_lowercase();
+        let profile = manager.get(&name).cloned().ok_or_else(|| (StatusCode::NOT_FOUND, format!("No profile '{}'", name)))?;
+        manager.apply(&name).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+        Ok(ProfileApplied { active: name, profile })
+    })
+    .await;
+    match applied {
+        Ok(Ok(applied)) => Json(applied).into_response(),
+        Ok(Err((status, e))) => (status, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn api_config() -> Response {
+    Json(OptimizerConfig::load_or_default().redacted()).into_response()
+}
+
+/// Text form of a JSON value, as `config set` takes it
+fn config_value(key: &str, value: &serde_json::Value) -> Result<String, String> {
+    match value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Null | serde_json::Value::Object(_) => {
+            Err(format!("{}: expected a value; set nested keys as \"section.key\"", key))
+        }
+        other => Ok(other.to_string()),
+    }
+}
+
+async fn api_set_config(Json(changes): Json<serde_json::Map<String, serde_json::Value>>) -> Response {
+    let values: Result<Vec<(String, String)>, String> =
+        changes.iter().map(|(key, value)| Ok((key.clone(), config_value(key, value)?))).collect();
+    let values = match values {
+        Ok(values) if !values.is_empty() => values,
+        Ok(_) => return (StatusCode::BAD_REQUEST, "Nothing to change").into_response(),
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let saved = tokio::task::spawn_blocking(move || {
+        let pairs: Vec<(&str, &str)> = values.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        config::set_values_in_file(&pairs)
+    })
+    .await;
+    match saved {
+        Ok(Ok(_)) => Json(OptimizerConfig::load_or_default().redacted()).into_response(),
+        Ok(Err(e)) => (StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    /// `all`, or a span such as `30m`, `24h` or `7d`
+    #[serde(default = "default_history_range")]
+    range: String,
+    trigger: Option<String>,
+    limit: Option<usize>,
+}
 
-            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+fn default_history_range() -> String {
+    "24h".to_string()
+}
+
+/// Result of `GET /api/history`
+#[derive(Serialize)]
+struct HistoryView {
+    records: Vec<HistoryRecord>,
+    summary: HistorySummary,
+}
+
+async fn api_history(Query(params): Query<HistoryParams>) -> Response {
+    let range = match TimeRange::parse(&params.range) {
+        Ok(range) => range,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let trigger = match params.trigger.as_deref().map(str::parse::<OptimizationTrigger>).transpose() {
+        Ok(trigger) => trigger,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let filter = HistoryFilter { trigger, limit: params.limit, ..Default::default() };
+    let records = tokio::task::spawn_blocking(move || HistoryStore::open_default()?.query(range, &filter)).await;
+    match records {
+        Ok(Ok(records)) => {
+            let summary = HistoryStore::summarize(&records);
+            Json(HistoryView { records, summary }).into_response()
+        }
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+async fn api_fleet_push(Json(mut sample): Json<FleetSample>) -> Response {
+    if let Err(e) = sample.validate() {
+        return (StatusCode::BAD_REQUEST, e).into_response();
+    }
+    // Stamped here so hosts with a wrong clock still line up
+    sample.timestamp_ms = now_ms();
+    match tokio::task::spawn_blocking(move || FleetStore::open_default()?.record(&sample)).await {
+        Ok(Ok(())) => StatusCode::NO_CONTENT.into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn api_fleet() -> Response {
+    let high_percent = OptimizerConfig::load_or_default().pressure_threshold;
+    match tokio::task::spawn_blocking(move || FleetStore::open_default()?.view(now_ms(), high_percent)).await {
+        Ok(Ok(view)) => Json(view).into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Drop old fleet samples every hour
+async fn prune_fleet() {
+    let mut ticker = tokio::time::interval(Duration::from_secs(3600));
+    loop {
+        ticker.tick().await;
+        let keep_after_ms = now_ms().saturating_sub(fleet::KEEP_MS);
+        match tokio::task::spawn_blocking(move || FleetStore::open_default()?.prune(keep_after_ms)).await {
+            Ok(Ok(removed)) if removed > 0 => debug!("Pruned {} fleet samples", removed),
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("Fleet prune failed: {}", e),
+            Err(e) => warn!("Fleet prune failed: {}", e),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TimelineParams {
+    /// `HH:MM[:SS]` or `YYYY-MM-DD HH:MM[:SS]`
+    at: Option<String>,
+    #[serde(default = "default_timeline_minutes")]
+    minutes: u64,
+}
+
+fn default_timeline_minutes() -> u64 {
+    30
+}
+
+async fn api_timeline(Query(params): Query<TimelineParams>) -> Response {
+    let view = tokio::task::spawn_blocking(move || timeline::query(params.at.as_deref(), params.minutes)).await;
+    match view {
+        Ok(Ok(view)) => Json(view).into_response(),
+        Ok(Err(e)) => (StatusCode::BAD_REQUEST, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn api_forecast() -> Response {
+    let critical_threshold = OptimizerConfig::load_or_default().critical_threshold;
+    let view = tokio::task::spawn_blocking(move || timeline::forecast(critical_threshold, &chart_horizons())).await;
+    match view {
+        Ok(Ok(view)) => Json(view).into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn ws_upgrade(ws: WebSocketUpgrade, State(server): State<DashboardServer>) -> Response {
+    let updates = server.subscribe();
+    ws.on_upgrade(move |socket| stream_updates(socket, updates))
+}
+
+/// Forward updates to one client until it disconnects
+async fn stream_updates(mut socket: WebSocket, mut updates: broadcast::Receiver<DashboardUpdate>) {
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(update) => {
+                    let json = match serde_json::to_string(&update) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            warn!("Failed to serialize dashboard update: {}", e);
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("WebSocket client lagging, skipped {} updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // Clients only listen; anything but a close frame is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
         }
     }
 }
@@ -190,6 +796,16 @@ impl Default for DashboardServer {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_value() {
+        assert_eq!(config_value("a", &serde_json::json!(85)).unwrap(), "85");
+        assert_eq!(config_value("a", &serde_json::json!(true)).unwrap(), "true");
+        assert_eq!(config_value("a", &serde_json::json!("balanced")).unwrap(), "balanced");
+        assert_eq!(config_value("a", &serde_json::json!(["x", "y"])).unwrap(), r#"["x","y"]"#);
+        assert!(config_value("a", &serde_json::json!({ "b": 1 })).is_err());
+        assert!(config_value("a", &serde_json::Value::Null).is_err());
+    }
+
     #[tokio::test]
     async fn test_dashboard_server() {
         let server = DashboardServer::new();
@@ -0,0 +1,612 @@
+//! Automation rule engine (ADR-025)
+//!
+//! User-defined rules map triggers - memory above a level for some minutes,
+//! an app launching, a time window, running on battery - to actions:
+//! optimize, trim an app category, switch profile, notify or run a script.
+//! Rules live in `agent_rules.toml`; the format is described in `rules.rs`.
+//!
+//! [`Agent`] samples the machine and evaluates the rules; `agent run
+//! --dry-run` shows what would fire without running anything, and
+//! [`replay`] runs the rules against the recorded timeline so a new rule
+//! can be checked against the last day before it is trusted.
+
+mod rules;
+
+pub use rules::{
+    example_rules, parse_rules, parse_rules_json, Action, Rule, RuleStore, TimeWindow, Trigger, RULES_FILE,
+    RULES_JSON_FILE, TRIM_CATEGORIES,
+};
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use sysinfo::System;
+
+use crate::apps::{AppCategory, ElectronManager};
+use crate::core::history::{self, HistoryRecord, OptimizationTrigger};
+use crate::core::scheduler;
+use crate::features::profiles::{app_matches, ProfileManager};
+use crate::features::timeline::{MemoryPoint, Timeline, TimelineEvent};
+use crate::monitor::procs;
+use crate::platform::power::{self, PowerSource};
+use crate::core::text::truncate;
+
+/// How often long-running callers should evaluate the rules
+pub const AGENT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Oldest process list a snapshot is taken from
+const PROCESS_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// Firings listed by `agent test`
+const MAX_REPLAY_LINES: usize = 50;
+
+/// The machine state rules are evaluated against
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub timestamp_ms: u64,
+    pub load_percent: u32,
+    /// `None` when unknown, e.g. while replaying history
+    pub power: Option<PowerSource>,
+    /// Lowercase names of processes started since the previous snapshot
+    pub launched: Vec<String>,
+}
+
+/// A rule whose trigger matched
+#[derive(Debug, Clone)]
+pub struct Firing {
+    pub rule: String,
+    pub timestamp_ms: u64,
+    /// Which conditions held, e.g. "memory 91% > 85% for 5 min, on battery"
+    pub reason: String,
+    pub actions: Vec<Action>,
+}
+
+impl Firing {
+    pub fn print(&self, dry_run: bool) {
+        println!("▶ {} {} ({})", format_time(self.timestamp_ms, "%H:%M:%S"), self.rule, self.reason);
+        if dry_run {
+            for action in &self.actions {
+                println!("    would {}", action.describe());
+            }
+        }
+    }
+
+    /// Whether running the firing switches profiles
+    pub fn switches_profile(&self) -> bool {
+        self.actions.iter().any(|a| matches!(a, Action::Profile { .. }))
+    }
+}
+
+#[derive(Debug, Default)]
+struct RuleState {
+    /// When memory went above the rule's level; `None` while below
+    above_since: Option<u64>,
+    last_fired: Option<u64>,
+}
+
+/// Evaluates rules against a stream of snapshots
+pub struct RuleEngine {
+    rules: Vec<(Rule, Option<TimeWindow>)>,
+    state: Vec<RuleState>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<Rule>) -> Result<Self, String> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                rule.validate()?;
+                let window = rule.when.window()?;
+                Ok((rule, window))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let state = rules.iter().map(|_| RuleState::default()).collect();
+        Ok(Self { rules, state })
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rules that fire at this snapshot, in file order
+    ///
+    /// Snapshots must come in time order: how long memory has stayed high
+    /// and each rule's cooldown are tracked across calls.
+    pub fn evaluate(&mut self, snapshot: &Snapshot) -> Vec<Firing> {
+        let now = snapshot.timestamp_ms;
+        let local_time = Local.timestamp_millis_opt(now as i64).single().map(|t| t.time());
+        let mut firings = Vec::new();
+
+        for ((rule, window), state) in self.rules.iter().zip(self.state.iter_mut()) {
+            let when = &rule.when;
+            let mut reasons = Vec::new();
+            let mut matched = rule.enabled;
+
+            if let Some(limit) = when.memory_above {
+                if snapshot.load_percent > limit {
+                    let since = *state.above_since.get_or_insert(now);
+                    let held_ms = now.saturating_sub(since);
+                    if held_ms >= when.for_minutes * 60_000 {
+                        reasons.push(match when.for_minutes {
+                            0 => format!("memory {}% > {}%", snapshot.load_percent, limit),
+                            m => format!("memory {}% > {}% for {} min", snapshot.load_percent, limit, m),
+                        });
+                    } else {
+                        matched = false;
+                    }
+                } else {
+                    state.above_since = None;
+                    matched = false;
+                }
+            }
+
+            if !when.app_launched.is_empty() {
+                let launched = snapshot
+                    .launched
+                    .iter()
+                    .find(|name| when.app_launched.iter().any(|pattern| app_matches(pattern, name)));
+                match launched {
+                    Some(name) => reasons.push(format!("{} launched", name)),
+                    None => matched = false,
+                }
+            }
+
+            if let Some(window) = window {
+                match local_time {
+                    Some(t) if window.contains(t) => {
+                        reasons.push(format!("between {}-{}", window.start.format("%H:%M"), window.end.format("%H:%M")))
+                    }
+                    _ => matched = false,
+                }
+            }
+
+            if let Some(power) = when.power {
+                if snapshot.power == Some(power) {
+                    reasons.push(format!("on {}", power));
+                } else {
+                    matched = false;
+                }
+            }
+
+            let cooled_down = state.last_fired.map_or(true, |t| now >= t + rule.cooldown_minutes * 60_000);
+            if matched && cooled_down {
+                state.last_fired = Some(now);
+                firings.push(Firing {
+                    rule: rule.name.clone(),
+                    timestamp_ms: now,
+                    reason: reasons.join(", "),
+                    actions: rule.actions.clone(),
+                });
+            }
+        }
+        firings
+    }
+}
+
+/// Samples the machine and evaluates rules against it
+pub struct Agent {
+    engine: RuleEngine,
+    store: RuleStore,
+    system: System,
+    /// Process names at the previous snapshot; `None` before the first
+    running: Option<HashSet<String>>,
+}
+
+impl Agent {
+    /// Agent with the rules from the default rules file
+    pub fn open_default() -> Result<Self, String> {
+        let store = RuleStore::open_default()?;
+        let engine = RuleEngine::new(store.load()?)?;
+        Ok(Self { engine, store, system: System::new(), running: None })
+    }
+
+    pub fn engine(&self) -> &RuleEngine {
+        &self.engine
+    }
+
+    pub fn store(&self) -> &RuleStore {
+        &self.store
+    }
+
+    /// Current memory load, power source and processes started since the last call
+    pub fn snapshot(&mut self) -> Snapshot {
+        self.system.refresh_memory();
+
+        let total = self.system.total_memory();
+        let load_percent = if total > 0 {
+            (self.system.used_memory() as f64 / total as f64 * 100.0).round() as u32
+        } else {
+            0
+        };
+        let names: HashSet<String> = procs::with_processes(PROCESS_MAX_AGE, |system| {
+            system.processes().values().map(|p| p.name().to_string_lossy().to_lowercase()).collect()
+        });
+        let mut launched: Vec<String> = match &self.running {
+            Some(previous) => names.difference(previous).cloned().collect(),
+            None => Vec::new(),
+        };
+        launched.sort();
+        self.running = Some(names);
+
+        Snapshot { timestamp_ms: now_ms(), load_percent, power: Some(power::power_source()), launched }
+    }
+
+    /// Take a snapshot and return the rules that fire
+    pub fn tick(&mut self) -> Vec<Firing> {
+        if self.engine.is_empty() {
+            return Vec::new();
+        }
+        let snapshot = self.snapshot();
+        self.engine.evaluate(&snapshot)
+    }
+
+    /// Print all rules as a table
+    pub fn print_rules(&self) {
+        println!("\n🤖 Automation Rules\n");
+        if self.engine.is_empty() {
+            println!("  No rules yet. Run `agent init` to write examples to {:?}.", self.store.path());
+            return;
+        }
+        println!("┌───┬──────────────────┬────────────────────────────────────┬──────────────────────────────────┐");
+        println!("│   │ Rule             │ When                               │ Do                               │");
+        println!("├───┼──────────────────┼────────────────────────────────────┼──────────────────────────────────┤");
+        for rule in self.engine.rules() {
+            let marker = if rule.enabled { "●" } else { " " };
+            let actions: Vec<String> = rule.actions.iter().map(Action::describe).collect();
+            println!(
+                "│ {} │ {:16} │ {:34} │ {:32} │",
+                marker,
+                truncate(&rule.name, 16),
+                truncate(&rule.when.describe(), 34),
+                truncate(&actions.join(", "), 32)
+            );
+        }
+        println!("└───┴──────────────────┴────────────────────────────────────┴──────────────────────────────────┘");
+        println!("\n  Rules file: {:?}", self.store.path());
+    }
+}
+
+/// Run a firing's actions in order, returning one outcome per action
+///
+/// `notify(title, message)` shows `notify` actions; a failed action does not
+/// stop the ones after it.
+pub fn execute(firing: &Firing, notify: &mut dyn FnMut(&str, &str)) -> Vec<Result<String, String>> {
+    let title = format!("Agent: {}", firing.rule);
+    let outcomes: Vec<Result<String, String>> = firing
+        .actions
+        .iter()
+        .map(|action| match action {
+            Action::Optimize { aggressive } => optimize(*aggressive),
+            Action::Trim { category } => trim_category(category),
+            Action::Profile { name } => switch_profile(name),
+            Action::Notify { message } => {
+                notify(&title, message);
+                Ok("notified".to_string())
+            }
+            Action::Script { command } => run_script(command),
+        })
+        .collect();
+    for (action, outcome) in firing.actions.iter().zip(&outcomes) {
+        match outcome {
+            Ok(result) => tracing::info!("Rule '{}': {} - {}", firing.rule, action.describe(), result),
+            Err(e) => tracing::warn!("Rule '{}': {} failed: {}", firing.rule, action.describe(), e),
+        }
+    }
+    outcomes
+}
+
+/// Print a firing and, unless `dry_run`, run it and print the outcomes
+pub fn run_and_print(firing: &Firing, dry_run: bool) {
+    firing.print(dry_run);
+    if dry_run {
+        return;
+    }
+    let outcomes = execute(firing, &mut |_, message| println!("    🔔 {}", message));
+    for (action, outcome) in firing.actions.iter().zip(outcomes) {
+        match outcome {
+            Ok(result) => println!("    ✓ {}: {}", action.describe(), result),
+            Err(e) => println!("    ✗ {}: {}", action.describe(), e),
+        }
+    }
+}
+
+fn optimize(aggressive: bool) -> Result<String, String> {
+    let result = crate::platform::create_optimizer().optimize(aggressive)?;
+    history::record(HistoryRecord::now(
+        OptimizationTrigger::Agent,
+        aggressive,
+        result.freed_mb,
+        result.processes_affected,
+        result.duration_ms,
+    ));
+    Ok(format!("freed {:.0} MB", result.freed_mb))
+}
+
+fn trim_category(category: &str) -> Result<String, String> {
+    let category = category.to_lowercase();
+    let wanted = match category.as_str() {
+        "browser" => {
+            let (freed, trimmed) = scheduler::trim_browsers(OptimizationTrigger::Agent);
+            return Ok(format!("freed {:.0} MB from {} browsers", freed, trimmed));
+        }
+        "electron" => None,
+        "communication" => Some(AppCategory::Communication),
+        "development" => Some(AppCategory::Development),
+        "creative" => Some(AppCategory::Creative),
+        "media" => Some(AppCategory::Media),
+        other => return Err(format!("Unknown trim category '{}'", other)),
+    };
+
+    let mut manager = ElectronManager::new();
+    manager.refresh();
+    let pids: Vec<u32> = manager
+        .get_apps()
+        .iter()
+        .filter(|app| wanted.map_or(true, |c| app.category == c))
+        .flat_map(|app| app.pids.iter().copied())
+        .collect();
+    if pids.is_empty() {
+        return Ok(format!("no running {} apps", category));
+    }
+    let (freed, trimmed) = trim_pids(&pids)?;
+    Ok(format!("freed {:.0} MB from {} processes", freed, trimmed))
+}
+
+#[cfg(target_os = "windows")]
+fn trim_pids(pids: &[u32]) -> Result<(f64, usize), String> {
+    use crate::windows::memory::WindowsMemoryOptimizer;
+
+    let start = std::time::Instant::now();
+    let mut freed_bytes = 0u64;
+    let mut trimmed = 0;
+    for &pid in pids {
+        if let Ok(bytes) = WindowsMemoryOptimizer::trim_process_working_set(pid) {
+            freed_bytes += bytes;
+            trimmed += 1;
+        }
+    }
+    let freed_mb = freed_bytes as f64 / (1024.0 * 1024.0);
+    history::record(HistoryRecord::now(
+        OptimizationTrigger::Agent,
+        false,
+        freed_mb,
+        trimmed,
+        start.elapsed().as_millis() as u64,
+    ));
+    Ok((freed_mb, trimmed))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn trim_pids(_pids: &[u32]) -> Result<(f64, usize), String> {
+    Err("Trimming app categories other than browsers is only supported on Windows".into())
+}
+
+fn switch_profile(name: &str) -> Result<String, String> {
+    let mut manager = ProfileManager::open_default()?;
+    if name.eq_ignore_ascii_case("off") {
+        manager.off()?;
+        Ok("profile off".to_string())
+    } else {
+        manager.apply(name)?;
+        Ok(format!("profile {} applied", name.to_lowercase()))
+    }
+}
+
+fn run_script(command: &str) -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    let output = std::process::Command::new("cmd").args(["/C", command]).output();
+    #[cfg(not(target_os = "windows"))]
+    let output = std::process::Command::new("sh").args(["-c", command]).output();
+
+    let output = output.map_err(|e| format!("Failed to run script: {}", e))?;
+    if output.status.success() {
+        Ok("script finished".to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("script exited with {}: {}", output.status, stderr.trim()))
+    }
+}
+
+/// Rules replayed against recorded history
+pub struct ReplayReport {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub samples: usize,
+    pub rules: Vec<Rule>,
+    pub firings: Vec<Firing>,
+}
+
+impl ReplayReport {
+    pub fn print(&self) {
+        println!(
+            "\n🤖 Replaying {} rules against {} – {} ({} samples)\n",
+            self.rules.len(),
+            format_time(self.start_ms, "%Y-%m-%d %H:%M"),
+            format_time(self.end_ms, "%Y-%m-%d %H:%M"),
+            self.samples
+        );
+        if self.samples == 0 {
+            println!("  No memory samples recorded (the timeline is filled by the tray and daemon).");
+            return;
+        }
+
+        for rule in &self.rules {
+            let count = self.firings.iter().filter(|f| f.rule == rule.name).count();
+            let note = if rule.when.power.is_some() { "  (power is not recorded, never fires in replay)" } else { "" };
+            println!("  {:20} fired {:>3}×{}", truncate(&rule.name, 20), count, note);
+        }
+
+        if !self.firings.is_empty() {
+            println!();
+            for firing in self.firings.iter().take(MAX_REPLAY_LINES) {
+                firing.print(true);
+            }
+            if self.firings.len() > MAX_REPLAY_LINES {
+                println!("  ... and {} more", self.firings.len() - MAX_REPLAY_LINES);
+            }
+        }
+        println!("\n  Nothing was run.");
+    }
+}
+
+/// Evaluate rules against recorded memory samples and app launches
+///
+/// Each sample becomes a snapshot; launches since the previous sample are
+/// attached to it. Power is not recorded, so power conditions never match.
+pub fn replay(rules: Vec<Rule>, samples: &[MemoryPoint], events: &[TimelineEvent]) -> Result<Vec<Firing>, String> {
+    let mut engine = RuleEngine::new(rules)?;
+    let mut firings = Vec::new();
+    let mut previous_ms = 0;
+    for point in samples {
+        let launched = events
+            .iter()
+            .filter(|e| e.timestamp_ms > previous_ms && e.timestamp_ms <= point.timestamp_ms)
+            .filter_map(|e| e.launched_app())
+            .map(str::to_lowercase)
+            .collect();
+        let snapshot = Snapshot {
+            timestamp_ms: point.timestamp_ms,
+            load_percent: point.load_percent,
+            power: None,
+            launched,
+        };
+        firings.extend(engine.evaluate(&snapshot));
+        previous_ms = point.timestamp_ms;
+    }
+    Ok(firings)
+}
+
+/// Replay the default rules against the last `hours` of the timeline
+pub fn replay_recent(hours: u64) -> Result<ReplayReport, String> {
+    let rules = RuleStore::open_default()?.load()?;
+    let end_ms = now_ms();
+    let start_ms = end_ms.saturating_sub(hours * 3_600_000);
+    let (samples, events) = Timeline::open_default()?.read(start_ms, end_ms)?;
+    let firings = replay(rules.clone(), &samples, &events)?;
+    Ok(ReplayReport { start_ms, end_ms, samples: samples.len(), rules, firings })
+}
+
+fn format_time(ms: u64, format: &str) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_else(|| "?".into())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::timeline::EventKind;
+    use chrono::NaiveTime;
+
+    fn minute(m: u64) -> u64 {
+        // Noon local time on 2026-03-10, so windows are deterministic
+        let noon = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap().timestamp_millis() as u64;
+        noon + m * 60_000
+    }
+
+    fn point(m: u64, load: u32) -> MemoryPoint {
+        MemoryPoint { timestamp_ms: minute(m), load_percent: load, used_mb: 0, swap_used_mb: 0 }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let content = r#"
+            [[rules]]
+            name = "squeeze"
+            when = { memory_above = 85, for_minutes = 5 }
+            actions = [{ action = "trim", category = "browser" }, { action = "notify", message = "hi" }]
+
+            [[rules]]
+            name = "games"
+            when = { app_launched = ["steam"], between = "18:00-02:00", power = "battery" }
+            actions = [{ action = "profile", name = "gaming" }]
+            cooldown_minutes = 60
+        "#;
+        let rules = parse_rules(content).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].cooldown_minutes, 15);
+        assert_eq!(rules[0].actions[0], Action::Trim { category: "browser".into() });
+        assert_eq!(rules[1].when.describe(), "steam launched, between 18:00-02:00, on battery");
+
+        let json = serde_json::to_string(&serde_json::json!({ "rules": [{
+            "name": "opt", "when": { "memory_above": 90 }, "actions": [{ "action": "optimize", "aggressive": true }]
+        }]}))
+        .unwrap();
+        assert_eq!(parse_rules_json(&json).unwrap()[0].actions, vec![Action::Optimize { aggressive: true }]);
+
+        let bad = r#"
+            [[rules]]
+            name = "x"
+            when = { for_minutes = 5 }
+            actions = [{ action = "optimize" }]
+        "#;
+        assert!(parse_rules(bad).is_err());
+        assert!(parse_rules(&bad.replace("for_minutes = 5", "memory_above = 80, between = \"9-17\"")).is_err());
+        assert!(parse_rules(&content.replace("\"browser\"", "\"games\"")).is_err());
+
+        let window = TimeWindow::parse("22:00-06:30").unwrap();
+        assert!(window.contains(NaiveTime::from_hms_opt(23, 0, 0).unwrap()));
+        assert!(window.contains(NaiveTime::from_hms_opt(6, 0, 0).unwrap()));
+        assert!(!window.contains(NaiveTime::from_hms_opt(12, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_replay_sustained_memory_and_launch() {
+        let rules = parse_rules(
+            r#"
+            [[rules]]
+            name = "sustained"
+            when = { memory_above = 85, for_minutes = 5 }
+            actions = [{ action = "optimize" }]
+            cooldown_minutes = 10
+
+            [[rules]]
+            name = "teams"
+            when = { app_launched = ["teams"], between = "11:00-13:00" }
+            actions = [{ action = "notify", message = "Teams started" }]
+
+            [[rules]]
+            name = "battery"
+            when = { power = "battery" }
+            actions = [{ action = "profile", name = "battery" }]
+            "#,
+        )
+        .unwrap();
+
+        // 80% for 3 min, a 2-minute blip over 85%, then 90% for 20 minutes
+        let samples: Vec<MemoryPoint> = (0..30)
+            .map(|m| point(m, if m < 3 { 80 } else if m < 5 { 88 } else if m < 7 { 70 } else { 90 }))
+            .collect();
+        let events = vec![TimelineEvent {
+            timestamp_ms: minute(4) - 1000,
+            kind: EventKind::AppLaunch,
+            summary: "Teams.exe started (300 MB)".into(),
+            memory_load: None,
+        }];
+
+        let firings = replay(rules, &samples, &events).unwrap();
+        let fired: Vec<(&str, u64)> =
+            firings.iter().map(|f| (f.rule.as_str(), (f.timestamp_ms - minute(0)) / 60_000)).collect();
+        // The blip never lasts 5 minutes; 90% from minute 7 fires at 12 and, after the cooldown, at 22
+        assert_eq!(fired, vec![("teams", 4), ("sustained", 12), ("sustained", 22)]);
+        assert_eq!(firings[0].reason, "teams.exe launched, between 11:00-13:00");
+    }
+}
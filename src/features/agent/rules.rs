@@ -0,0 +1,322 @@
+//! Automation rules in `agent_rules.toml` (or `agent_rules.json`)
+//!
+//! ```toml
+//! [[rules]]
+//! name = "squeeze-browsers"
+//! when = { memory_above = 85, for_minutes = 5 }
+//! actions = [
+//!     { action = "trim", category = "browser" },
+//!     { action = "notify", message = "Browsers trimmed after 5 minutes above 85%" },
+//! ]
+//!
+//! [[rules]]
+//! name = "game-night"
+//! when = { app_launched = ["steam", "*-win64-shipping"], between = "18:00-02:00" }
+//! actions = [{ action = "profile", name = "gaming" }]
+//! cooldown_minutes = 60
+//! ```
+//!
+//! The JSON file has the same shape (`{"rules": [...]}`) and is only read
+//! when there is no TOML file.
+
+use std::path::PathBuf;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+use crate::platform::power::PowerSource;
+
+/// File name of the TOML rules inside the config directory
+pub const RULES_FILE: &str = "agent_rules.toml";
+
+/// File name of the JSON rules, used when there is no TOML file
+pub const RULES_JSON_FILE: &str = "agent_rules.json";
+
+/// App categories the `trim` action accepts
+pub const TRIM_CATEGORIES: &[&str] = &["browser", "electron", "communication", "development", "creative", "media"];
+
+/// Minutes a rule waits after firing unless it sets its own cooldown
+const DEFAULT_COOLDOWN_MINUTES: u64 = 15;
+
+/// Conditions under which a rule fires
+///
+/// All given conditions must hold; a trigger with none never fires.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Trigger {
+    /// Memory load (%) that must be exceeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_above: Option<u32>,
+    /// How long `memory_above` must hold before firing
+    #[serde(skip_serializing_if = "is_zero")]
+    pub for_minutes: u64,
+    /// Process names or globs; fires when one of them starts
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub app_launched: Vec<String>,
+    /// Local time window `HH:MM-HH:MM`, may wrap past midnight
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub between: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerSource>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl Trigger {
+    pub fn is_empty(&self) -> bool {
+        self.memory_above.is_none() && self.app_launched.is_empty() && self.between.is_none() && self.power.is_none()
+    }
+
+    /// The parsed `between` window
+    pub fn window(&self) -> Result<Option<TimeWindow>, String> {
+        self.between.as_deref().map(TimeWindow::parse).transpose()
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(load) = self.memory_above {
+            match self.for_minutes {
+                0 => parts.push(format!("memory > {}%", load)),
+                m => parts.push(format!("memory > {}% for {} min", load, m)),
+            }
+        }
+        if !self.app_launched.is_empty() {
+            parts.push(format!("{} launched", self.app_launched.join("/")));
+        }
+        if let Some(between) = &self.between {
+            parts.push(format!("between {}", between));
+        }
+        if let Some(power) = self.power {
+            parts.push(format!("on {}", power));
+        }
+        if parts.is_empty() {
+            "never".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// A daily `HH:MM-HH:MM` window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("Invalid time window '{}', expected HH:MM-HH:MM", s))?;
+        let time = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| format!("Invalid time '{}' in window '{}'", t.trim(), s))
+        };
+        Ok(Self { start: time(start)?, end: time(end)? })
+    }
+
+    /// Whether `t` falls in the window; the end is exclusive
+    pub fn contains(&self, t: NaiveTime) -> bool {
+        if self.start <= self.end {
+            t >= self.start && t < self.end
+        } else {
+            t >= self.start || t < self.end
+        }
+    }
+}
+
+/// What a rule does when it fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// System-wide optimization
+    Optimize {
+        #[serde(default)]
+        aggressive: bool,
+    },
+    /// Trim the working sets of one app category (`browser`, `electron`,
+    /// `communication`, `development`, ...)
+    Trim { category: String },
+    /// Switch to a profile; `off` restores the normal settings
+    Profile { name: String },
+    /// Show a notification
+    Notify { message: String },
+    /// Run a shell command
+    Script { command: String },
+}
+
+impl Action {
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Optimize { aggressive: false } => "optimize".to_string(),
+            Action::Optimize { aggressive: true } => "optimize (aggressive)".to_string(),
+            Action::Trim { category } => format!("trim {}", category),
+            Action::Profile { name } if name.eq_ignore_ascii_case("off") => "profile off".to_string(),
+            Action::Profile { name } => format!("profile {}", name),
+            Action::Notify { message } => format!("notify \"{}\"", message),
+            Action::Script { command } => format!("run `{}`", command),
+        }
+    }
+}
+
+/// A trigger mapped to actions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub when: Trigger,
+    pub actions: Vec<Action>,
+    /// Minutes before the rule may fire again
+    #[serde(default = "default_cooldown")]
+    pub cooldown_minutes: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_cooldown() -> u64 {
+    DEFAULT_COOLDOWN_MINUTES
+}
+
+impl Rule {
+    /// Reject rules that could never fire or cannot be run
+    pub fn validate(&self) -> Result<(), String> {
+        if self.when.is_empty() {
+            return Err(format!("Rule '{}' has no trigger conditions", self.name));
+        }
+        if self.actions.is_empty() {
+            return Err(format!("Rule '{}' has no actions", self.name));
+        }
+        if self.when.memory_above.is_some_and(|load| load > 100) {
+            return Err(format!("Rule '{}': memory_above must be at most 100", self.name));
+        }
+        if self.when.for_minutes > 0 && self.when.memory_above.is_none() {
+            return Err(format!("Rule '{}': for_minutes needs memory_above", self.name));
+        }
+        self.when.window().map_err(|e| format!("Rule '{}': {}", self.name, e))?;
+        for action in &self.actions {
+            if let Action::Trim { category } = action {
+                if !TRIM_CATEGORIES.contains(&category.to_lowercase().as_str()) {
+                    return Err(format!(
+                        "Rule '{}': unknown trim category '{}' (expected one of {})",
+                        self.name,
+                        category,
+                        TRIM_CATEGORIES.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+/// Parse TOML rules and validate them
+pub fn parse_rules(content: &str) -> Result<Vec<Rule>, String> {
+    let file: RulesFile = toml::from_str(content).map_err(|e| e.to_string())?;
+    validate_all(file.rules)
+}
+
+/// Parse JSON rules and validate them
+pub fn parse_rules_json(content: &str) -> Result<Vec<Rule>, String> {
+    let file: RulesFile = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    validate_all(file.rules)
+}
+
+fn validate_all(rules: Vec<Rule>) -> Result<Vec<Rule>, String> {
+    for (i, rule) in rules.iter().enumerate() {
+        rule.validate()?;
+        if rules[..i].iter().any(|r| r.name.eq_ignore_ascii_case(&rule.name)) {
+            return Err(format!("Duplicate rule name '{}'", rule.name));
+        }
+    }
+    Ok(rules)
+}
+
+/// Rules written by `agent init`
+pub fn example_rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            name: "squeeze-browsers".into(),
+            enabled: true,
+            when: Trigger { memory_above: Some(85), for_minutes: 5, ..Default::default() },
+            actions: vec![
+                Action::Trim { category: "browser".into() },
+                Action::Notify { message: "Browsers trimmed after 5 minutes above 85%".into() },
+            ],
+            cooldown_minutes: 30,
+        },
+        Rule {
+            name: "critical-memory".into(),
+            enabled: true,
+            when: Trigger { memory_above: Some(95), ..Default::default() },
+            actions: vec![Action::Optimize { aggressive: true }],
+            cooldown_minutes: DEFAULT_COOLDOWN_MINUTES,
+        },
+        Rule {
+            name: "unplugged".into(),
+            enabled: true,
+            when: Trigger { power: Some(PowerSource::Battery), ..Default::default() },
+            actions: vec![Action::Profile { name: "battery".into() }],
+            cooldown_minutes: 60,
+        },
+    ]
+}
+
+/// Persistent rule list
+pub struct RuleStore {
+    path: PathBuf,
+}
+
+impl RuleStore {
+    /// The TOML file, or the JSON file when only that one exists
+    pub fn open_default() -> Result<Self, String> {
+        let toml = crate::features::config_file(RULES_FILE)?;
+        let json = toml.with_file_name(RULES_JSON_FILE);
+        let path = if !toml.exists() && json.exists() { json } else { toml };
+        Ok(Self { path })
+    }
+
+    pub fn open(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn is_json(&self) -> bool {
+        self.path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    }
+
+    /// Load all rules (none if the file does not exist)
+    pub fn load(&self) -> Result<Vec<Rule>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        let rules = if self.is_json() { parse_rules_json(&content) } else { parse_rules(&content) };
+        rules.map_err(|e| format!("Failed to parse {:?}: {}", self.path, e))
+    }
+
+    pub fn save(&self, rules: &[Rule]) -> Result<(), String> {
+        let file = RulesFile { rules: rules.to_vec() };
+        let content = if self.is_json() {
+            serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?
+        } else {
+            toml::to_string_pretty(&file).map_err(|e| e.to_string())?
+        };
+        std::fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+}
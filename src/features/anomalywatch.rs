@@ -0,0 +1,131 @@
+//! Background anomaly watching
+//!
+//! Samples memory load, the process count and GPU memory in the daemons and
+//! the Windows service, and runs them through an [`AnomalyDetector`].
+//! Unlike the pressure thresholds this flags what is unusual for the time
+//! of day: 70% at noon on a machine that normally sits at 40% counts, 90%
+//! during the nightly build that always does it doesn't. Anomalies are
+//! recorded on the timeline, and shown as notifications when
+//! `anomaly_notify` is on. The baselines are kept in `anomaly_baseline.json`
+//! so they survive restarts.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use chrono::Timelike;
+use sysinfo::System;
+
+use crate::algorithms::anomaly::{Anomaly, AnomalyDetector, Metric};
+use crate::core::config::OptimizerConfig;
+use crate::features::timeline::{self, EventKind};
+use crate::monitor::gpu::GpuMonitor;
+use crate::monitor::procs;
+use crate::monitor::notify::{self, Notification};
+
+/// File name of the saved baselines inside the config directory
+pub const BASELINE_FILE: &str = "anomaly_baseline.json";
+
+/// How often the metrics are sampled
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Samples between saves of the baselines (an hour)
+const SAVE_EVERY: u32 = 240;
+
+/// When a reading counts as an anomaly and what happens then
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyPolicy {
+    /// z-score at which a reading is unusual
+    pub threshold: f64,
+    /// Show a notification as well as recording it
+    pub notify: bool,
+}
+
+impl AnomalyPolicy {
+    pub fn from_config(config: &OptimizerConfig) -> Self {
+        Self { threshold: config.anomaly_threshold, notify: config.anomaly_notify }
+    }
+}
+
+/// Baselines from `path`, or fresh ones when there are none yet
+pub fn load_detector(path: &Path, threshold: f64) -> AnomalyDetector {
+    let mut detector = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<AnomalyDetector>(&json).ok())
+        .unwrap_or_default();
+    detector.set_threshold(threshold);
+    detector
+}
+
+fn save_detector(path: &Path, detector: &AnomalyDetector) -> Result<(), String> {
+    let json = serde_json::to_string(detector).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// Current value of every metric that can be read
+fn sample(system: &mut System, gpu: Option<&GpuMonitor>) -> Vec<(Metric, f64)> {
+    system.refresh_memory();
+    let process_count = procs::with_processes(SAMPLE_INTERVAL, |system| system.processes().len());
+    let mut readings = vec![(Metric::ProcessCount, process_count as f64)];
+    if let Some(load) = (system.used_memory() * 100).checked_div(system.total_memory()) {
+        readings.push((Metric::MemoryLoad, load as f64));
+    }
+    if let Some(gpu) = gpu.filter(|g| !g.gpus().is_empty()) {
+        readings.push((Metric::GpuVram, (gpu.total_used_vram() / 1024 / 1024) as f64));
+    }
+    readings
+}
+
+/// Sample in the background; anomalies are logged, recorded on the timeline
+/// and sent on the returned channel
+pub fn watch(threshold: f64) -> Receiver<Anomaly> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let path: Option<PathBuf> = crate::features::config_file(BASELINE_FILE)
+            .map_err(|e| tracing::warn!("Anomaly baselines will not be saved: {}", e))
+            .ok();
+        let mut detector = match &path {
+            Some(path) => load_detector(path, threshold),
+            None => AnomalyDetector::new(threshold),
+        };
+        let mut system = System::new();
+        let gpu = GpuMonitor::new().ok();
+        let mut samples = 0u32;
+        loop {
+            let hour = chrono::Local::now().hour() as usize;
+            for (metric, value) in sample(&mut system, gpu.as_ref()) {
+                if let Some(anomaly) = detector.observe(metric, hour, value) {
+                    tracing::warn!("{}", anomaly.message());
+                    timeline::record(EventKind::Anomaly, anomaly.message());
+                    if tx.send(anomaly).is_err() {
+                        return;
+                    }
+                }
+            }
+            samples += 1;
+            if samples % SAVE_EVERY == 0 {
+                if let Some(path) = &path {
+                    if let Err(e) = save_detector(path, &detector) {
+                        tracing::warn!("{}", e);
+                    }
+                }
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+    rx
+}
+
+/// Watch in the background for headless daemons; `on_anomaly` sees every
+/// anomaly, e.g. to tell connected clients
+pub fn spawn(policy: AnomalyPolicy, on_anomaly: impl Fn(&Anomaly) + Send + 'static) {
+    let anomalies = watch(policy.threshold);
+    std::thread::spawn(move || {
+        for anomaly in anomalies {
+            on_anomaly(&anomaly);
+            if policy.notify {
+                notify::notify(Notification::new(anomaly.title(), anomaly.message()));
+            }
+        }
+    });
+}
@@ -0,0 +1,373 @@
+//! Installed app inventory and uninstall commands
+//!
+//! - Windows: AppX/UWP packages and the registry uninstall entries that
+//!   MSI and classic installers write
+//! - macOS: Homebrew casks
+//! - Linux: Flatpak apps and Snaps
+//!
+//! The parsers are pure functions over command output so they can be tested
+//! on any platform.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+/// Where an app was installed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackageSource {
+    Appx,
+    /// Registry uninstall entry (MSI or classic installer)
+    Program,
+    HomebrewCask,
+    Flatpak,
+    Snap,
+}
+
+impl PackageSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PackageSource::Appx => "AppX",
+            PackageSource::Program => "Program",
+            PackageSource::HomebrewCask => "Cask",
+            PackageSource::Flatpak => "Flatpak",
+            PackageSource::Snap => "Snap",
+        }
+    }
+}
+
+/// The command that removes an app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UninstallCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Must be run as root (shown with `sudo`)
+    pub needs_root: bool,
+}
+
+impl UninstallCommand {
+    fn new(program: &str, args: &[&str]) -> Self {
+        Self { program: program.into(), args: args.iter().map(|a| a.to_string()).collect(), needs_root: false }
+    }
+
+    /// Run the command, returning its output on success
+    pub fn run(&self) -> Result<String, String> {
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", self.program, e))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let code = output.status.code().map(|c| c.to_string()).unwrap_or_else(|| "signal".into());
+            Err(format!("{} exited with {}: {}", self.program, code, stderr.trim()))
+        }
+    }
+}
+
+impl std::fmt::Display for UninstallCommand {
+    /// The exact command line, as the user would type it
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.needs_root {
+            write!(f, "sudo ")?;
+        }
+        // `cmd /C <string>` runs a registry uninstall string verbatim
+        if self.program == "cmd" && self.args.first().map(String::as_str) == Some("/C") {
+            return write!(f, "{}", self.args[1..].join(" "));
+        }
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            if arg.contains(' ') || arg.contains('|') {
+                write!(f, " \"{}\"", arg.replace('"', "\\\""))?;
+            } else {
+                write!(f, " {}", arg)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One installed app
+#[derive(Debug, Clone)]
+pub struct InstalledApp {
+    /// Package name, registry key, cask token, Flatpak id or snap name
+    pub id: String,
+    pub name: String,
+    pub source: PackageSource,
+    pub version: Option<String>,
+    pub publisher: Option<String>,
+    pub install_dir: Option<PathBuf>,
+    pub size_mb: Option<f64>,
+    pub uninstall: UninstallCommand,
+}
+
+/// Every installed app this platform can list
+pub fn scan() -> Vec<InstalledApp> {
+    #[cfg(windows)]
+    {
+        match powershell(WINDOWS_SCRIPT) {
+            Ok(output) => parse_windows(&output),
+            Err(e) => {
+                tracing::warn!("App inventory failed: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        match run("brew", &["info", "--cask", "--installed", "--json=v2"]) {
+            Ok(json) => parse_brew_casks(&json),
+            Err(e) => {
+                tracing::debug!("Homebrew casks unavailable: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut apps = Vec::new();
+        if let Ok(output) = run("flatpak", &["list", "--app", "--columns=application,name,version,size,installation"]) {
+            apps.extend(parse_flatpak(&output));
+        }
+        if let Ok(output) = run("snap", &["list"]) {
+            apps.extend(parse_snap(&output));
+        }
+        apps
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Emits `kind|id|name|version|publisher|location|size_kb|uninstall` lines
+#[cfg(windows)]
+const WINDOWS_SCRIPT: &str = r#"
+$ErrorActionPreference = 'SilentlyContinue'
+Get-AppxPackage | Where-Object { -not $_.IsFramework -and -not $_.NonRemovable -and $_.SignatureKind -ne 'System' } | ForEach-Object {
+    "appx|$($_.Name)|$($_.Name)|$($_.Version)|$($_.Publisher)|$($_.InstallLocation)||"
+}
+foreach ($root in 'HKLM:\Software\Microsoft\Windows\CurrentVersion\Uninstall',
+                  'HKLM:\Software\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall',
+                  'HKCU:\Software\Microsoft\Windows\CurrentVersion\Uninstall') {
+    Get-ChildItem $root | ForEach-Object {
+        $p = Get-ItemProperty $_.PSPath
+        if ($p.DisplayName -and -not $p.SystemComponent -and -not $p.ParentKeyName -and ($p.UninstallString -or $p.QuietUninstallString)) {
+            $cmd = if ($p.QuietUninstallString) { $p.QuietUninstallString } else { $p.UninstallString }
+            "program|$($_.PSChildName)|$($p.DisplayName)|$($p.DisplayVersion)|$($p.Publisher)|$($p.InstallLocation)|$($p.EstimatedSize)|$cmd"
+        }
+    }
+}
+"#;
+
+/// Apps from [`WINDOWS_SCRIPT`] output
+pub fn parse_windows(output: &str) -> Vec<InstalledApp> {
+    let mut apps: Vec<InstalledApp> = Vec::new();
+    for line in output.lines() {
+        let parts: Vec<&str> = line.trim_end_matches('\r').splitn(8, '|').collect();
+        let [kind, id, name, version, publisher, location, size_kb, uninstall] = parts[..] else { continue };
+        let optional = |s: &str| (!s.trim().is_empty()).then(|| s.trim().to_string());
+
+        let (source, uninstall) = match kind {
+            "appx" => {
+                let script = format!("Get-AppxPackage -Name '{}' | Remove-AppxPackage", id.replace('\'', "''"));
+                (PackageSource::Appx, UninstallCommand::new("powershell", &["-NoProfile", "-Command", &script]))
+            }
+            "program" => (PackageSource::Program, program_uninstall(id, uninstall)),
+            _ => continue,
+        };
+        // 32- and 64-bit uninstall keys often list the same product twice
+        if apps.iter().any(|a| a.name == name && a.source == source) {
+            continue;
+        }
+        apps.push(InstalledApp {
+            id: id.to_string(),
+            name: name.to_string(),
+            source,
+            version: optional(version),
+            publisher: optional(publisher),
+            install_dir: optional(location).map(|l| PathBuf::from(l.trim_matches('"'))),
+            size_mb: size_kb.trim().parse::<f64>().ok().map(|kb| kb / 1024.0),
+            uninstall,
+        });
+    }
+    apps
+}
+
+/// Uninstall command for a registry entry
+///
+/// MSI entries often point `UninstallString` at `msiexec /I`, which opens the
+/// repair dialog; the product code with `/X` removes it instead.
+fn program_uninstall(key: &str, uninstall: &str) -> UninstallCommand {
+    let is_product_code = key.starts_with('{') && key.ends_with('}') && key.len() == 38;
+    if is_product_code && uninstall.trim().to_ascii_lowercase().starts_with("msiexec") {
+        return UninstallCommand::new("msiexec", &["/X", key]);
+    }
+    UninstallCommand::new("cmd", &["/C", uninstall.trim()])
+}
+
+#[derive(Deserialize)]
+struct BrewInfo {
+    #[serde(default)]
+    casks: Vec<BrewCask>,
+}
+
+#[derive(Deserialize)]
+struct BrewCask {
+    token: String,
+    #[serde(default)]
+    name: Vec<String>,
+    installed: Option<String>,
+    #[serde(default)]
+    artifacts: Vec<serde_json::Value>,
+}
+
+/// Casks from `brew info --cask --installed --json=v2`
+pub fn parse_brew_casks(json: &str) -> Vec<InstalledApp> {
+    let Ok(info) = serde_json::from_str::<BrewInfo>(json) else { return Vec::new() };
+    info.casks
+        .into_iter()
+        .map(|cask| {
+            // {"app": ["Foo.app"]} artifacts name the bundle in /Applications
+            let app = cask
+                .artifacts
+                .iter()
+                .filter_map(|a| a.get("app")?.as_array()?.first()?.as_str().map(String::from))
+                .next();
+            let install_dir = app.as_ref().map(|a| PathBuf::from("/Applications").join(a));
+            InstalledApp {
+                name: cask.name.first().cloned().unwrap_or_else(|| cask.token.clone()),
+                uninstall: UninstallCommand::new("brew", &["uninstall", "--cask", &cask.token]),
+                id: cask.token,
+                source: PackageSource::HomebrewCask,
+                version: cask.installed,
+                publisher: None,
+                size_mb: install_dir.as_deref().map(dir_size_mb),
+                install_dir,
+            }
+        })
+        .collect()
+}
+
+/// Apps from `flatpak list --app --columns=application,name,version,size,installation`
+pub fn parse_flatpak(output: &str) -> Vec<InstalledApp> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [id, name, version, size, installation] = fields[..] else { return None };
+            let user = installation.trim() == "user";
+            let mut uninstall = UninstallCommand::new("flatpak", &["uninstall", "-y", id]);
+            if user {
+                uninstall.args.insert(1, "--user".into());
+            }
+            let base = if user {
+                std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share/flatpak"))
+            } else {
+                Some(PathBuf::from("/var/lib/flatpak"))
+            };
+            Some(InstalledApp {
+                id: id.to_string(),
+                name: name.to_string(),
+                source: PackageSource::Flatpak,
+                version: (!version.is_empty()).then(|| version.to_string()),
+                publisher: None,
+                install_dir: base.map(|b| b.join("app").join(id)),
+                size_mb: parse_size_mb(size),
+                uninstall,
+            })
+        })
+        .collect()
+}
+
+/// Snaps from `snap list`, skipping bases, runtimes and snapd itself
+pub fn parse_snap(output: &str) -> Vec<InstalledApp> {
+    const RUNTIMES: &[&str] = &["gtk-common-themes", "gnome-", "kde-frameworks", "mesa-", "core", "bare", "snapd"];
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, version, revision, _tracking, publisher, notes @ ..] = &fields[..] else { return None };
+            let notes = notes.join(" ");
+            if ["base", "core", "snapd", "gadget", "kernel"].iter().any(|n| notes.contains(n))
+                || RUNTIMES.iter().any(|r| name.starts_with(r))
+            {
+                return None;
+            }
+            let snap_file = PathBuf::from(format!("/var/lib/snapd/snaps/{}_{}.snap", name, revision));
+            let mut uninstall = UninstallCommand::new("snap", &["remove", name]);
+            uninstall.needs_root = true;
+            Some(InstalledApp {
+                id: name.to_string(),
+                name: name.to_string(),
+                source: PackageSource::Snap,
+                version: Some(version.to_string()),
+                publisher: Some(publisher.trim_end_matches('✓').trim_end_matches('*').to_string()),
+                install_dir: Some(PathBuf::from("/snap").join(name)),
+                size_mb: std::fs::metadata(snap_file).ok().map(|m| m.len() as f64 / 1024.0 / 1024.0),
+                uninstall,
+            })
+        })
+        .collect()
+}
+
+/// MB from sizes like `1.2 GB`, `350.5 MB` or `12,3 kB`
+pub fn parse_size_mb(size: &str) -> Option<f64> {
+    let size = size.trim().replace(',', ".").replace('\u{a0}', " ");
+    let (number, unit) = size.split_once(' ')?;
+    let value: f64 = number.parse().ok()?;
+    let factor = match unit.trim().to_ascii_lowercase().as_str() {
+        "bytes" | "b" => 1.0 / 1024.0 / 1024.0,
+        "kb" => 1.0 / 1024.0,
+        "mb" => 1.0,
+        "gb" => 1024.0,
+        "tb" => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * factor)
+}
+
+/// Total size of files under `dir`
+fn dir_size_mb(dir: &Path) -> f64 {
+    let mut total = 0u64;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(path) = stack.pop() {
+        for entry in std::fs::read_dir(&path).into_iter().flatten().flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                total += meta.len();
+            }
+        }
+    }
+    total as f64 / 1024.0 / 1024.0
+}
+
+#[cfg(windows)]
+fn powershell(script: &str) -> Result<String, String> {
+    run("powershell", &["-NoProfile", "-NonInteractive", "-Command", script])
+}
+
+#[cfg(any(windows, target_os = "macos", target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
@@ -0,0 +1,430 @@
+//! Bloatware detector with safe uninstall recommendations (ADR-023)
+//!
+//! Lists installed apps (AppX/UWP and registry uninstall entries on Windows,
+//! Homebrew casks on macOS, Flatpak and Snap on Linux) and weighs what each
+//! costs (resident memory, starting at login, disk space) against how often
+//! it's used, taken from the predictive preloading launch history.
+//!
+//! Recommendations carry the exact uninstall command. Nothing is removed
+//! unless the user asks for one app with `--confirm`. System frameworks,
+//! driver packages and security software are never recommended.
+
+pub mod inventory;
+
+use std::path::Path;
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::features::prefetch::AppObserver;
+use crate::features::startup::{StartupManager, StartupState};
+
+pub use inventory::{InstalledApp, PackageSource, UninstallCommand};
+use crate::core::text::truncate;
+
+/// Usage window for launch counts
+pub const USAGE_WINDOW_DAYS: u64 = 30;
+
+/// Apps scoring at least this are recommended for removal
+pub const REMOVE_THRESHOLD: f64 = 0.35;
+
+/// Resident memory that counts as the full memory score
+const MEMORY_CAP_MB: f64 = 500.0;
+
+/// Disk size that counts as the full disk score
+const DISK_CAP_MB: f64 = 2048.0;
+
+/// Launches in the usage window that halve the score
+const LAUNCHES_HALF: f64 = 5.0;
+
+/// Preinstalled apps and OEM helpers that are rarely wanted
+const KNOWN_BLOAT: &[&str] = &[
+    "candycrush", "bubblewitch", "farmheroes", "marchofempires", "solitairecollection", "bingnews",
+    "bingweather", "bingfinance", "bingsports", "tiktok", "disney", "facebook", "instagram", "spotifyab",
+    "xboxgamingoverlay", "getstarted", "gethelp", "microsoft3dviewer", "mixedreality", "officehub",
+    "skypeapp", "zunemusic", "zunevideo", "wildtangent", "booking.com", "hpjumpstart", "hp support assistant",
+    "dell supportassist", "acer jumpstart", "acer care center", "asus giftbox", "mcafee webadvisor", "norton security ultra",
+];
+
+/// Runtimes, frameworks, drivers and the store itself; removing them breaks other apps
+const PROTECTED: &[&str] = &[
+    "windowsstore", "desktopappinstaller", "vclibs", ".net", "netframework", "windowsappruntime",
+    "webview2", "visual c++", "ui.xaml", "nvidia", "intel(r)", "intel ", "amd ", "realtek",
+    "securityhealth", "defender", "gnome.platform", "kde.platform", "freedesktop.platform", "snapd",
+];
+
+/// What to do about an app
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recommendation {
+    /// Uninstall with this command
+    Remove(UninstallCommand),
+    /// Used, but shouldn't start at login
+    DisableStartup(String),
+    Keep,
+}
+
+/// Costs of one app
+#[derive(Debug, Clone, Default)]
+pub struct Footprint {
+    /// Resident memory of its running processes
+    pub memory_mb: f64,
+    /// Starts at login (enabled or delayed)
+    pub startup: Option<String>,
+    pub disk_mb: Option<f64>,
+}
+
+/// How often an app is used
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    /// Launches in the last [`USAGE_WINDOW_DAYS`]
+    pub launches: usize,
+    /// Unix timestamp in milliseconds of the last launch
+    pub last_used_ms: Option<u64>,
+}
+
+/// An app with its score and recommendation
+#[derive(Debug, Clone)]
+pub struct AppAssessment {
+    pub app: InstalledApp,
+    pub footprint: Footprint,
+    /// `None` when there is no launch history to judge from
+    pub usage: Option<Usage>,
+    /// 0..1, higher means a better removal candidate
+    pub score: f64,
+    pub known_bloat: bool,
+    pub recommendation: Recommendation,
+}
+
+/// Footprint part of the score, 0..1
+pub fn footprint_score(footprint: &Footprint) -> f64 {
+    let memory = (footprint.memory_mb / MEMORY_CAP_MB).min(1.0);
+    let startup = if footprint.startup.is_some() { 1.0 } else { 0.0 };
+    let disk = (footprint.disk_mb.unwrap_or(0.0) / DISK_CAP_MB).min(1.0);
+    0.4 * memory + 0.3 * startup + 0.3 * disk
+}
+
+/// Score an app and decide what to recommend
+///
+/// Unused apps keep their full footprint score; each [`LAUNCHES_HALF`]
+/// launches halves it again. Known bloat gets a boost so it surfaces even
+/// when it's small.
+pub fn assess(app: InstalledApp, footprint: Footprint, usage: Option<Usage>) -> AppAssessment {
+    let key = normalize(&format!("{} {}", app.id, app.name));
+    let known_bloat = KNOWN_BLOAT.iter().any(|b| key.contains(&normalize(b)));
+    let protected = is_protected(&app);
+
+    let usage_factor = match usage {
+        Some(u) => 1.0 / (1.0 + u.launches as f64 / LAUNCHES_HALF),
+        // Without history only the footprint and the bloat list count
+        None => 0.6,
+    };
+    let mut score = footprint_score(&footprint) * usage_factor;
+    if known_bloat {
+        score = (score + 0.4).min(1.0);
+    }
+    if protected {
+        score = 0.0;
+    }
+
+    let used = usage.is_some_and(|u| u.launches > 0);
+    let recommendation = if protected {
+        Recommendation::Keep
+    } else if score >= REMOVE_THRESHOLD && !used {
+        Recommendation::Remove(app.uninstall.clone())
+    } else if let Some(startup) = footprint.startup.clone() {
+        Recommendation::DisableStartup(startup)
+    } else {
+        Recommendation::Keep
+    };
+
+    AppAssessment { app, footprint, usage, score, known_bloat, recommendation }
+}
+
+fn is_protected(app: &InstalledApp) -> bool {
+    let key = format!("{} {} {}", app.id, app.name, app.publisher.as_deref().unwrap_or_default()).to_lowercase();
+    PROTECTED.iter().any(|p| key.contains(p))
+}
+
+/// Lowercased alphanumerics, for matching names across sources
+fn normalize(s: &str) -> String {
+    s.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// A running process, reduced to what matching needs
+struct RunningProcess {
+    name: String,
+    exe: Option<std::path::PathBuf>,
+    memory_mb: f64,
+}
+
+/// Scans installed apps and ranks them
+pub struct BloatwareScanner {
+    assessments: Vec<AppAssessment>,
+    has_history: bool,
+}
+
+impl BloatwareScanner {
+    pub fn new() -> Self {
+        let mut scanner = Self { assessments: Vec::new(), has_history: false };
+        scanner.refresh();
+        scanner
+    }
+
+    /// Re-scan installed apps, running processes, startup items and usage
+    pub fn refresh(&mut self) {
+        let apps = inventory::scan();
+
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_memory().with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        let processes: Vec<RunningProcess> = system
+            .processes()
+            .values()
+            .map(|p| RunningProcess {
+                name: normalize(p.name().to_string_lossy().trim_end_matches(".exe")),
+                exe: p.exe().map(Path::to_path_buf),
+                memory_mb: p.memory() as f64 / 1024.0 / 1024.0,
+            })
+            .collect();
+
+        let startup: Vec<(String, String, String)> = StartupManager::new()
+            .items()
+            .iter()
+            .filter(|i| i.state != StartupState::Disabled)
+            .map(|i| (i.name.clone(), i.program(), i.command.to_lowercase()))
+            .collect();
+
+        let history = AppObserver::open_default().map(|o| o.history().iter().cloned().collect::<Vec<_>>()).unwrap_or_default();
+        self.has_history = !history.is_empty();
+        let cutoff = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(USAGE_WINDOW_DAYS * 86_400_000);
+
+        let mut assessments: Vec<AppAssessment> = apps
+            .into_iter()
+            .map(|app| {
+                let key = normalize(&app.name);
+                let id_key = normalize(app.id.rsplit(['.', '/']).next().unwrap_or(&app.id));
+                let matches_name = |name: &str| !name.is_empty() && (name == key || name == id_key);
+                let under_dir = |path: &Path| app.install_dir.as_deref().is_some_and(|dir| path.starts_with(dir));
+
+                let memory_mb = processes
+                    .iter()
+                    .filter(|p| p.exe.as_deref().is_some_and(under_dir) || matches_name(&p.name))
+                    .fold(0.0, |total, p| total + p.memory_mb);
+
+                let dir = app.install_dir.as_ref().map(|d| d.to_string_lossy().to_lowercase());
+                let startup = startup
+                    .iter()
+                    .find(|(name, program, command)| {
+                        matches_name(&normalize(name))
+                            || matches_name(&normalize(program))
+                            || dir.as_deref().is_some_and(|d| !d.is_empty() && command.contains(d))
+                    })
+                    .map(|(name, _, _)| name.clone());
+
+                let usage = self.has_history.then(|| {
+                    let mut usage = Usage::default();
+                    for launch in &history {
+                        let app_name = normalize(launch.app.trim_end_matches(".exe"));
+                        if launch.path.as_deref().is_some_and(under_dir) || matches_name(&app_name) {
+                            if launch.timestamp_ms >= cutoff {
+                                usage.launches += 1;
+                            }
+                            usage.last_used_ms = usage.last_used_ms.max(Some(launch.timestamp_ms));
+                        }
+                    }
+                    usage
+                });
+
+                let disk_mb = app.size_mb;
+                assess(app, Footprint { memory_mb, startup, disk_mb }, usage)
+            })
+            .collect();
+        assessments.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        self.assessments = assessments;
+    }
+
+    /// Every installed app, best removal candidates first
+    pub fn assessments(&self) -> &[AppAssessment] {
+        &self.assessments
+    }
+
+    /// Apps recommended for removal
+    pub fn recommendations(&self) -> impl Iterator<Item = &AppAssessment> {
+        self.assessments.iter().filter(|a| matches!(a.recommendation, Recommendation::Remove(_)))
+    }
+
+    /// Find an app by id or case-insensitive name
+    pub fn find(&self, query: &str) -> Result<&AppAssessment, String> {
+        let lower = query.to_lowercase();
+        if let Some(found) = self.assessments.iter().find(|a| a.app.id == query) {
+            return Ok(found);
+        }
+        let matches: Vec<&AppAssessment> = self
+            .assessments
+            .iter()
+            .filter(|a| a.app.name.to_lowercase() == lower || a.app.id.to_lowercase() == lower)
+            .collect();
+        match matches.len() {
+            1 => Ok(matches[0]),
+            0 => Err(format!("No installed app named '{}'", query)),
+            _ => Err(format!("'{}' matches {} apps; use the id shown by `bloatware --all`", query, matches.len())),
+        }
+    }
+
+    /// Uninstall one app; refused for protected apps
+    pub fn remove(&self, query: &str) -> Result<&AppAssessment, String> {
+        let assessment = self.find(query)?;
+        if is_protected(&assessment.app) {
+            return Err(format!("{} is a system component and won't be removed", assessment.app.name));
+        }
+        assessment.app.uninstall.run()?;
+        Ok(assessment)
+    }
+
+    pub fn print_table(&self, all: bool) {
+        println!("\n🧹 Bloatware Scan\n");
+        let shown: Vec<&AppAssessment> = if all {
+            self.assessments.iter().collect()
+        } else {
+            self.assessments.iter().filter(|a| a.recommendation != Recommendation::Keep).collect()
+        };
+        if self.assessments.is_empty() {
+            println!("  No installed apps found.");
+            return;
+        }
+        if shown.is_empty() {
+            println!("  Nothing to recommend among {} installed app(s).", self.assessments.len());
+        } else {
+            println!("┌───────┬──────────────────────────┬─────────┬─────────┬─────────┬──────────┬─────────┐");
+            println!("│ Score │ Name                     │ Source  │ Memory  │ Disk    │ Launches │ Startup │");
+            println!("├───────┼──────────────────────────┼─────────┼─────────┼─────────┼──────────┼─────────┤");
+            for a in &shown {
+                let disk = a.footprint.disk_mb.map(|d| format!("{:.0} MB", d)).unwrap_or_else(|| "-".into());
+                let launches = a.usage.map(|u| u.launches.to_string()).unwrap_or_else(|| "?".into());
+                println!(
+                    "│ {:>5.2} │ {:24} │ {:7} │ {:>7} │ {:>7} │ {:>8} │ {:7} │",
+                    a.score,
+                    truncate(&a.app.name, 24),
+                    a.app.source.label(),
+                    format!("{:.0} MB", a.footprint.memory_mb),
+                    disk,
+                    launches,
+                    if a.footprint.startup.is_some() { "yes" } else { "no" }
+                );
+            }
+            println!("└───────┴──────────────────────────┴─────────┴─────────┴─────────┴──────────┴─────────┘");
+        }
+
+        let removals: Vec<&AppAssessment> = self.recommendations().collect();
+        if !removals.is_empty() {
+            println!("\n  Recommended removals:");
+            for a in &removals {
+                let reason = if a.known_bloat { "preinstalled bloat" } else { "unused" };
+                println!("    {} ({})", a.app.name, reason);
+                println!("      {}", a.app.uninstall);
+            }
+        }
+        let startup: Vec<&AppAssessment> =
+            shown.iter().copied().filter(|a| matches!(a.recommendation, Recommendation::DisableStartup(_))).collect();
+        if !startup.is_empty() {
+            println!("\n  Used, but starting at login:");
+            for a in &startup {
+                if let Recommendation::DisableStartup(item) = &a.recommendation {
+                    println!("    startup disable \"{}\"", item);
+                }
+            }
+        }
+        if !self.has_history {
+            println!("\n  💡 No launch history yet; usage is unknown. Enable predictive preloading");
+            println!("     (`prefetch`) for a while to rank apps by how often they're used.");
+        }
+        if !removals.is_empty() {
+            println!("\n  Nothing was removed. Use `bloatware --remove <name> --confirm` to uninstall.");
+        }
+    }
+}
+
+impl Default for BloatwareScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(id: &str, name: &str, size_mb: Option<f64>) -> InstalledApp {
+        InstalledApp {
+            id: id.into(),
+            name: name.into(),
+            source: PackageSource::Appx,
+            version: None,
+            publisher: None,
+            install_dir: None,
+            size_mb,
+            uninstall: UninstallCommand { program: "true".into(), args: vec![], needs_root: false },
+        }
+    }
+
+    #[test]
+    fn test_assess_scoring() {
+        let heavy = Footprint { memory_mb: 600.0, startup: Some("Helper".into()), disk_mb: Some(4096.0) };
+        let unused = assess(app("Vendor.Helper", "Helper", None), heavy.clone(), Some(Usage::default()));
+        assert!((unused.score - 1.0).abs() < 1e-9);
+        assert!(matches!(unused.recommendation, Recommendation::Remove(_)));
+
+        let used = Usage { launches: 20, last_used_ms: Some(1) };
+        let daily = assess(app("Vendor.Helper", "Helper", None), heavy, Some(used));
+        assert!(daily.score < 0.35);
+        assert_eq!(daily.recommendation, Recommendation::DisableStartup("Helper".into()));
+
+        let bloat = assess(app("king.com.CandyCrushSaga", "Candy Crush Saga", None), Footprint::default(), None);
+        assert!(bloat.known_bloat);
+        assert!(matches!(bloat.recommendation, Recommendation::Remove(_)));
+
+        let runtime = Footprint { memory_mb: 800.0, startup: None, disk_mb: Some(3000.0) };
+        let vclibs = assess(app("Microsoft.VCLibs.140.00", "VCLibs", None), runtime, Some(Usage::default()));
+        assert_eq!(vclibs.score, 0.0);
+        assert_eq!(vclibs.recommendation, Recommendation::Keep);
+    }
+
+    #[test]
+    fn test_parse_windows() {
+        let output = "appx|king.com.CandyCrushSaga|king.com.CandyCrushSaga|1.2.3|CN=King|C:\\Program Files\\WindowsApps\\king||\r\n\
+                      program|{11111111-2222-3333-4444-555555555555}|Tool|2.0|Acme||10240|MsiExec.exe /I{11111111-2222-3333-4444-555555555555}\n\
+                      program|{11111111-2222-3333-4444-555555555555}|Tool|2.0|Acme||10240|MsiExec.exe /I{11111111-2222-3333-4444-555555555555}\n\
+                      program|Other|Other App|1.0||||\"C:\\Other\\uninst.exe\" /S\n\
+                      garbage line";
+        let apps = inventory::parse_windows(output);
+        assert_eq!(apps.len(), 3);
+        assert_eq!(apps[0].uninstall.to_string(), "powershell -NoProfile -Command \"Get-AppxPackage -Name 'king.com.CandyCrushSaga' | Remove-AppxPackage\"");
+        assert_eq!(apps[1].uninstall.to_string(), "msiexec /X {11111111-2222-3333-4444-555555555555}");
+        assert_eq!(apps[1].size_mb, Some(10.0));
+        assert_eq!(apps[2].uninstall.to_string(), "\"C:\\Other\\uninst.exe\" /S");
+    }
+
+    #[test]
+    fn test_parse_linux_packages() {
+        let flatpak = "org.gimp.GIMP\tGNU Image Manipulation Program\t2.10.38\t1,2 GB\tsystem\n\
+                       com.spotify.Client\tSpotify\t1.2\t350.5 MB\tuser\n";
+        let apps = inventory::parse_flatpak(flatpak);
+        assert_eq!(apps.len(), 2);
+        assert!((apps[0].size_mb.unwrap() - 1228.8).abs() < 0.1);
+        assert_eq!(apps[1].uninstall.to_string(), "flatpak uninstall --user -y com.spotify.Client");
+
+        let snap = "Name      Version   Rev    Tracking       Publisher   Notes\n\
+                    core22    20240111  1122   latest/stable  canonical✓  base\n\
+                    firefox   128.0     4650   latest/stable  mozilla✓    -\n\
+                    snapd     2.63      21759  latest/stable  canonical✓  snapd\n";
+        let snaps = inventory::parse_snap(snap);
+        assert_eq!(snaps.len(), 1);
+        assert_eq!(snaps[0].publisher.as_deref(), Some("mozilla"));
+        assert_eq!(snaps[0].uninstall.to_string(), "sudo snap remove firefox");
+
+        assert_eq!(inventory::parse_size_mb("512 kB"), Some(0.5));
+        assert_eq!(inventory::parse_size_mb("unknown"), None);
+    }
+}
@@ -0,0 +1,470 @@
+//! Boost actions applied while a build runs, restored when it finishes
+//!
+//! Every change records the value it replaced. Restoring checks that the pid
+//! still belongs to the same program, so a recycled pid is left alone.
+//!
+//! Background processes are throttled when they are busy or when a
+//! `background` policy rule names them. On Windows their I/O priority is
+//! lowered too, and RuVector moves itself into background mode.
+//!
+//! On hybrid CPUs the build keeps every core. Throttled background processes
+//! are moved onto the efficiency cores, so the performance cores stay free
+//! for the build.
+//!
+//! The power plan is switched to high performance for the first build and
+//! put back once no build is left. So are background services the user
+//! opted into (see [`crate::features::throttle`]).
+
+use std::collections::HashMap;
+
+use sysinfo::System;
+
+use crate::accel::topology::{self, CpuTopology};
+use crate::core::undo::{self, UndoAction, UndoEntry};
+use crate::platform::power::{self, ActivePlan, PowerPlan};
+use crate::features::throttle::{self, ThrottleGuard, ThrottleService};
+use crate::security::policy::{file_publisher, Policy, ProcessIdentity};
+
+use super::detect::{ancestors, snapshot, BuildDetector, BuildProcess, BuildSession};
+
+/// Background processes above this CPU usage (percent of one core) are throttled
+const BACKGROUND_CPU_PERCENT: f32 = 10.0;
+
+/// Most background processes throttled at once
+const MAX_THROTTLED: usize = 16;
+
+/// What a boost did
+#[derive(Debug, Clone, Default)]
+pub struct BoostReport {
+    pub applied: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl BoostReport {
+    pub fn print(&self) {
+        for line in &self.applied {
+            println!("  ✓ {}", line);
+        }
+        for line in &self.skipped {
+            println!("  · {}", line);
+        }
+    }
+}
+
+/// Original settings of a process we changed
+struct Saved {
+    name: String,
+    priority: Option<i32>,
+    affinity: Option<Vec<usize>>,
+    io_priority: Option<u32>,
+}
+
+/// Applies build boosts and undoes them
+pub struct BuildBooster {
+    boosted: HashMap<u32, Saved>,
+    throttled: HashMap<u32, Saved>,
+    protected: Vec<String>,
+    policy: Policy,
+    /// Plan active before the first build, if we switched it
+    power_plan: Option<ActivePlan>,
+    /// Whether we put our own process into background mode
+    background_mode: bool,
+    /// Services to pause while building, and the hold on them
+    throttle: Vec<ThrottleService>,
+    services: Option<ThrottleGuard>,
+}
+
+impl BuildBooster {
+    /// `protected` names are never throttled (see `OptimizerConfig::protected_processes`);
+    /// `throttle` services are paused while building (`OptimizerConfig::throttle_services`)
+    pub fn new(protected: Vec<String>, throttle: Vec<ThrottleService>) -> Self {
+        let protected = protected.iter().map(|p| normalize(p)).collect();
+        Self {
+            boosted: HashMap::new(),
+            throttled: HashMap::new(),
+            protected,
+            policy: Policy::load_default(),
+            power_plan: None,
+            background_mode: false,
+            throttle,
+            services: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.boosted.is_empty()
+            || !self.throttled.is_empty()
+            || self.power_plan.is_some()
+            || self.background_mode
+            || self.services.is_some()
+    }
+
+    /// Boost a newly detected build
+    ///
+    /// The first build also gets a standby flush and background throttling;
+    /// builds that start while another is boosted share them.
+    pub fn start(&mut self, session: &BuildSession, detector: &BuildDetector, processes: &[BuildProcess]) -> BoostReport {
+        let mut report = BoostReport::default();
+        let first = !self.is_active();
+
+        let raised = self.boost_tree(session, processes);
+        if sys::can_reprioritize() {
+            report.applied.push(format!("Raised priority of {} build process(es)", raised.priority));
+        } else {
+            report.skipped.push("Priority changes need root".into());
+        }
+        match raised.affinity {
+            Some(n) if n > 0 => report.applied.push(format!("Allowed {} process(es) on all cores", n)),
+            Some(_) => report.skipped.push("Build already runs on all cores".into()),
+            None => report.skipped.push("Affinity control not available on this platform".into()),
+        }
+
+        if first {
+            match power::switch_to(PowerPlan::HighPerformance) {
+                Ok(Some(previous)) => {
+                    report.applied.push(format!("Switched power plan from {} to {}", previous.name, PowerPlan::HighPerformance));
+                    self.power_plan = Some(previous);
+                }
+                Ok(None) => report.skipped.push(format!("Power plan already {}", PowerPlan::HighPerformance)),
+                Err(e) => report.skipped.push(format!("Power plan unchanged: {}", e)),
+            }
+            match sys::flush_standby() {
+                Ok(()) => report.applied.push("Flushed standby memory".into()),
+                Err(e) => report.skipped.push(format!("Standby flush skipped: {}", e)),
+            }
+            if sys::can_reprioritize() {
+                let throttled = self.throttle_background(detector, processes);
+                report.applied.push(format!("Lowered priority of {} background process(es)", throttled.priority));
+                if throttled.io > 0 {
+                    report.applied.push(format!("Lowered I/O priority of {} background process(es)", throttled.io));
+                }
+                if throttled.moved > 0 {
+                    report.applied.push(format!("Moved {} background process(es) to efficiency cores", throttled.moved));
+                }
+            }
+            if sys::set_background_mode(true).is_ok() {
+                self.background_mode = true;
+                report.applied.push("Moved RuVector into background mode".into());
+            }
+            self.services = throttle::hold(&format!("Build: {}", session.tool), &self.throttle, &mut report);
+        }
+        report
+    }
+
+    /// Boost processes the running builds spawned since the last call
+    pub fn follow(&mut self, detector: &BuildDetector, processes: &[BuildProcess]) {
+        for session in detector.sessions() {
+            self.boost_tree(session, processes);
+        }
+    }
+
+    /// Undo a finished build's boosts; background throttling is lifted once
+    /// no build is left
+    pub fn finish(&mut self, session: &BuildSession, detector: &BuildDetector, processes: &[BuildProcess]) {
+        let pids: Vec<u32> = session.tree.iter().copied().filter(|pid| self.boosted.contains_key(pid)).collect();
+        for pid in pids {
+            if let Some(saved) = self.boosted.remove(&pid) {
+                restore(pid, &saved, processes);
+            }
+        }
+        if !detector.is_building() {
+            for (pid, saved) in std::mem::take(&mut self.throttled) {
+                restore(pid, &saved, processes);
+            }
+            self.restore_power_plan();
+            self.end_background();
+        }
+    }
+
+    /// Undo everything still applied
+    pub fn restore_all(&mut self) {
+        if !self.is_active() {
+            return;
+        }
+        let processes = snapshot(&mut System::new());
+        for (pid, saved) in self.boosted.drain().chain(self.throttled.drain()) {
+            restore(pid, &saved, &processes);
+        }
+        self.restore_power_plan();
+        self.end_background();
+    }
+
+    fn restore_power_plan(&mut self) {
+        if let Some(previous) = self.power_plan.take() {
+            if let Err(e) = power::restore(&previous) {
+                tracing::warn!("Failed to restore power plan {}: {}", previous.name, e);
+            }
+        }
+    }
+
+    /// Leave background mode and let go of the paused services
+    fn end_background(&mut self) {
+        if std::mem::take(&mut self.background_mode) {
+            if let Err(e) = sys::set_background_mode(false) {
+                tracing::warn!("Failed to leave background mode: {}", e);
+            }
+        }
+        if let Some(services) = self.services.take() {
+            services.release();
+        }
+    }
+
+    fn boost_tree(&mut self, session: &BuildSession, processes: &[BuildProcess]) -> Raised {
+        let mut raised = Raised { priority: 0, affinity: topology::AFFINITY_SUPPORTED.then_some(0) };
+        for process in processes.iter().filter(|p| session.tree.contains(&p.pid)) {
+            if self.boosted.contains_key(&process.pid) {
+                continue;
+            }
+            let mut saved = Saved { name: process.name.clone(), priority: None, affinity: None, io_priority: None };
+
+            if sys::can_reprioritize() {
+                if let Some(current) = sys::priority(process.pid) {
+                    if current != sys::HIGH && sys::set_priority(process.pid, sys::HIGH).is_ok() {
+                        saved.priority = Some(current);
+                        raised.priority += 1;
+                    }
+                }
+            }
+
+            if let Some(current) = topology::process_affinity(process.pid) {
+                let all = CpuTopology::get().all();
+                if current != all && topology::set_process_affinity(process.pid, &all).is_ok() {
+                    saved.affinity = Some(current);
+                    raised.affinity = raised.affinity.map(|n| n + 1);
+                }
+            }
+
+            self.boosted.insert(process.pid, saved);
+        }
+        raised
+    }
+
+    fn throttle_background(&mut self, detector: &BuildDetector, processes: &[BuildProcess]) -> Throttled {
+        let by_pid: HashMap<u32, &BuildProcess> = processes.iter().map(|p| (p.pid, p)).collect();
+        // The shell or IDE that launched a build is in the foreground by definition
+        let launchers: Vec<u32> = detector.sessions().flat_map(|s| ancestors(s.pid, &by_pid)).collect();
+        let self_pid = std::process::id();
+
+        // Listed background work is throttled however idle it looks right now
+        let mut candidates: Vec<(&BuildProcess, bool)> = processes
+            .iter()
+            .filter(|p| p.pid > 4 && p.pid != self_pid)
+            .filter(|p| !detector.in_build(p.pid) && !launchers.contains(&p.pid))
+            .filter(|p| !self.protected.contains(&normalize(&p.name)))
+            .map(|p| (p, self.policy.is_background(&identity(p, &self.policy))))
+            .filter(|&(p, listed)| listed || p.cpu_percent >= BACKGROUND_CPU_PERCENT)
+            .collect();
+        candidates.sort_by(|(a, a_listed), (b, b_listed)| {
+            b_listed
+                .cmp(a_listed)
+                .then(b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap_or(std::cmp::Ordering::Equal))
+        });
+
+        let hybrid = CpuTopology::get().is_hybrid() && topology::AFFINITY_SUPPORTED;
+        let mut throttled = Throttled::default();
+        for (process, _) in candidates.into_iter().take(MAX_THROTTLED) {
+            // Only demote normal-priority work; anything already tuned is left alone
+            if sys::priority(process.pid) != Some(sys::NORMAL) {
+                continue;
+            }
+            if sys::set_priority(process.pid, sys::BELOW_NORMAL).is_err() {
+                continue;
+            }
+            let mut saved = Saved { name: process.name.clone(), priority: Some(sys::NORMAL), affinity: None, io_priority: None };
+            throttled.priority += 1;
+
+            if let Some(current) = sys::io_priority(process.pid) {
+                if current > sys::IO_VERY_LOW && sys::set_io_priority(process.pid, sys::IO_VERY_LOW).is_ok() {
+                    saved.io_priority = Some(current);
+                    throttled.io += 1;
+                }
+            }
+
+            if hybrid {
+                if let Some(current) = topology::process_affinity(process.pid) {
+                    if topology::pin_to_efficiency_cores(process.pid).is_ok() {
+                        saved.affinity = Some(current);
+                        throttled.moved += 1;
+                    }
+                }
+            }
+            self.throttled.insert(process.pid, saved);
+        }
+
+        // Lets `undo` lift the throttling before the build finishes
+        let actions = self
+            .throttled
+            .iter()
+            .flat_map(|(&pid, saved)| {
+                let name = saved.name.clone();
+                let priority = saved.priority.map(|priority| UndoAction::RestorePriority { name: name.clone(), pid, priority });
+                let io = saved.io_priority.map(|level| UndoAction::RestoreIoPriority { name: name.clone(), pid, level });
+                let affinity = saved.affinity.clone().map(|cpus| UndoAction::RestoreAffinity { name, pid, cpus });
+                priority.into_iter().chain(io).chain(affinity)
+            })
+            .collect();
+        undo::record(UndoEntry::now(format!("Throttled {} background process(es) for a build", throttled.priority), actions));
+        throttled
+    }
+}
+
+impl Drop for BuildBooster {
+    fn drop(&mut self) {
+        self.restore_all();
+    }
+}
+
+struct Raised {
+    priority: usize,
+    /// `None` where affinity can't be controlled
+    affinity: Option<usize>,
+}
+
+#[derive(Default)]
+struct Throttled {
+    priority: usize,
+    io: usize,
+    /// Moved to efficiency cores
+    moved: usize,
+}
+
+fn restore(pid: u32, saved: &Saved, processes: &[BuildProcess]) {
+    if !processes.iter().any(|p| p.pid == pid && p.name == saved.name) {
+        return;
+    }
+    if let Some(priority) = saved.priority {
+        if let Err(e) = sys::set_priority(pid, priority) {
+            tracing::debug!("Failed to restore priority of {}: {}", pid, e);
+        }
+    }
+    if let Some(level) = saved.io_priority {
+        if let Err(e) = sys::set_io_priority(pid, level) {
+            tracing::debug!("Failed to restore I/O priority of {}: {}", pid, e);
+        }
+    }
+    if let Some(ref cpus) = saved.affinity {
+        if let Err(e) = topology::set_process_affinity(pid, cpus) {
+            tracing::debug!("Failed to restore affinity of {}: {}", pid, e);
+        }
+    }
+}
+
+/// What the policy needs to match a process; the publisher is only looked up
+/// when a rule uses it
+fn identity(process: &BuildProcess, policy: &Policy) -> ProcessIdentity {
+    let publisher = if policy.needs_publisher() {
+        process.exe.as_deref().and_then(file_publisher)
+    } else {
+        None
+    };
+    ProcessIdentity { name: process.name.clone(), path: process.exe.clone(), publisher }
+}
+
+fn normalize(name: &str) -> String {
+    let lower = name.to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower).to_string()
+}
+
+#[cfg(windows)]
+pub(crate) mod sys {
+    use crate::windows::process::{self, get_priority_class, set_priority_class, IoPriority};
+
+    // Priority class values
+    pub const HIGH: i32 = 0x80;
+    pub const NORMAL: i32 = 0x20;
+    pub const BELOW_NORMAL: i32 = 0x4000;
+
+    pub const IO_VERY_LOW: u32 = IoPriority::VeryLow as u32;
+
+    pub fn can_reprioritize() -> bool {
+        true
+    }
+
+    pub fn priority(pid: u32) -> Option<i32> {
+        get_priority_class(pid).map(|c| c as i32)
+    }
+
+    pub fn set_priority(pid: u32, priority: i32) -> Result<(), String> {
+        set_priority_class(pid, priority as u32)
+    }
+
+    pub fn io_priority(pid: u32) -> Option<u32> {
+        process::get_io_priority(pid).map(|level| level as u32)
+    }
+
+    pub fn set_io_priority(pid: u32, level: u32) -> Result<(), String> {
+        let level = IoPriority::from_raw(level).ok_or_else(|| format!("Unknown I/O priority {}", level))?;
+        process::set_io_priority(pid, level)
+    }
+
+    /// `PROCESS_MODE_BACKGROUND_BEGIN`/`END` for our own process
+    pub fn set_background_mode(enabled: bool) -> Result<(), String> {
+        process::set_background_mode(enabled)
+    }
+
+    pub fn flush_standby() -> Result<(), String> {
+        crate::windows::memory::purge_standby_list()
+    }
+}
+
+#[cfg(unix)]
+pub(crate) mod sys {
+    use crate::security::audit::{self, AuditAction};
+
+    // Nice values
+    pub const HIGH: i32 = -5;
+    pub const NORMAL: i32 = 0;
+    pub const BELOW_NORMAL: i32 = 5;
+
+    pub const IO_VERY_LOW: u32 = 0;
+
+    /// Lowering a nice value (including restoring one we raised) needs root
+    pub fn can_reprioritize() -> bool {
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    pub fn priority(pid: u32) -> Option<i32> {
+        unsafe {
+            // -1 is a valid nice value, so errno tells failure apart
+            clear_errno();
+            let nice = libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t);
+            (nice != -1 || std::io::Error::last_os_error().raw_os_error() == Some(0)).then_some(nice)
+        }
+    }
+
+    pub fn set_priority(pid: u32, priority: i32) -> Result<(), String> {
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, priority) };
+        let result = if result == 0 { Ok(()) } else { Err(std::io::Error::last_os_error().to_string()) };
+        audit::record(AuditAction::Priority, "", Some(pid), &format!("nice {}", priority), &result);
+        result
+    }
+
+    /// I/O priority hints and background mode are Windows features
+    pub fn io_priority(_pid: u32) -> Option<u32> {
+        None
+    }
+
+    pub fn set_io_priority(_pid: u32, _level: u32) -> Result<(), String> {
+        Err("I/O priority is only available on Windows".into())
+    }
+
+    pub fn set_background_mode(_enabled: bool) -> Result<(), String> {
+        Err("Background mode is only available on Windows".into())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe fn clear_errno() {
+        *libc::__errno_location() = 0;
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    unsafe fn clear_errno() {
+        *libc::__error() = 0;
+    }
+
+    /// The page cache holds the build's own sources and objects here, so
+    /// dropping it would only slow the build down
+    pub fn flush_standby() -> Result<(), String> {
+        Err("no standby list on this platform".into())
+    }
+}
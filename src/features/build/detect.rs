@@ -0,0 +1,328 @@
+//! Build detection from process trees and command lines
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// Recognized build tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildTool {
+    Cargo,
+    MSBuild,
+    Gradle,
+    CMake,
+    Make,
+    Ninja,
+    Webpack,
+    Vite,
+    Go,
+    Bazel,
+}
+
+impl BuildTool {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildTool::Cargo => "cargo",
+            BuildTool::MSBuild => "msbuild",
+            BuildTool::Gradle => "gradle",
+            BuildTool::CMake => "cmake",
+            BuildTool::Make => "make",
+            BuildTool::Ninja => "ninja",
+            BuildTool::Webpack => "webpack",
+            BuildTool::Vite => "vite",
+            BuildTool::Go => "go",
+            BuildTool::Bazel => "bazel",
+        }
+    }
+
+    /// Identify a build from a process name and its arguments
+    ///
+    /// `args` includes the program itself as the first element, as sysinfo
+    /// reports it. Long-running servers (Gradle daemon, `vite` dev server,
+    /// `cargo watch`) are not builds and are not matched.
+    pub fn identify(name: &str, args: &[String]) -> Option<Self> {
+        let name = name.to_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+        let rest: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+        let has = |words: &[&str]| rest.iter().any(|a| words.contains(a));
+
+        match name {
+            "cargo" if has(&["build", "b", "check", "c", "test", "t", "run", "r", "bench", "clippy", "doc", "install"]) => {
+                Some(BuildTool::Cargo)
+            }
+            "msbuild" => Some(BuildTool::MSBuild),
+            "dotnet" if has(&["build", "publish", "test", "msbuild"]) => Some(BuildTool::MSBuild),
+            "gradle" | "gradlew" => Some(BuildTool::Gradle),
+            "java" if rest.iter().any(|a| *a == "org.gradle.wrapper.GradleWrapperMain" || *a == "org.gradle.launcher.GradleMain") => {
+                Some(BuildTool::Gradle)
+            }
+            "cmake" if has(&["--build"]) => Some(BuildTool::CMake),
+            "make" | "gmake" | "mingw32-make" | "nmake" => Some(BuildTool::Make),
+            "ninja" => Some(BuildTool::Ninja),
+            "node" | "bun" if rest.iter().any(|a| a.contains("webpack")) => Some(BuildTool::Webpack),
+            "node" | "bun" if rest.iter().any(|a| a.contains("vite")) && has(&["build"]) => Some(BuildTool::Vite),
+            "go" if has(&["build", "test", "install"]) => Some(BuildTool::Go),
+            "bazel" | "bazelisk" if has(&["build", "test", "run"]) => Some(BuildTool::Bazel),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BuildTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Per-process data the detector and booster work from
+#[derive(Debug, Clone)]
+pub struct BuildProcess {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub exe: Option<PathBuf>,
+    pub memory_bytes: u64,
+    pub cpu_percent: f32,
+}
+
+/// Refresh `system` and collect every process
+pub fn snapshot(system: &mut System) -> Vec<BuildProcess> {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_memory()
+            .with_cpu()
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_cwd(UpdateKind::OnlyIfNotSet)
+            .with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    system
+        .processes()
+        .iter()
+        .map(|(pid, process)| BuildProcess {
+            pid: pid.as_u32(),
+            parent: process.parent().map(|p| p.as_u32()),
+            name: process.name().to_string_lossy().into_owned(),
+            args: process.cmd().iter().map(|a| a.to_string_lossy().into_owned()).collect(),
+            cwd: process.cwd().map(PathBuf::from),
+            exe: process.exe().map(PathBuf::from),
+            memory_bytes: process.memory(),
+            cpu_percent: process.cpu_usage(),
+        })
+        .collect()
+}
+
+/// A build in progress: the root tool process and everything it spawned
+#[derive(Debug, Clone)]
+pub struct BuildSession {
+    pub pid: u32,
+    pub tool: BuildTool,
+    pub command: String,
+    pub project_dir: Option<PathBuf>,
+    pub started: Instant,
+    /// Every pid seen in the build's process tree, including exited ones
+    pub tree: HashSet<u32>,
+    pub peak_memory_mb: f64,
+}
+
+impl BuildSession {
+    /// Identifies "the same build" across runs for speedup baselines
+    pub fn key(&self) -> String {
+        build_key(self.tool, self.project_dir.as_ref(), &self.command)
+    }
+}
+
+pub(crate) fn build_key(tool: BuildTool, project_dir: Option<&PathBuf>, command: &str) -> String {
+    match project_dir {
+        Some(dir) => format!("{}|{}|{}", tool, dir.display(), command),
+        None => format!("{}|{}", tool, command),
+    }
+}
+
+/// A finished build
+#[derive(Debug, Clone)]
+pub struct BuildSummary {
+    pub session: BuildSession,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub enum BuildEvent {
+    /// Root pid of a newly detected build
+    Started(u32),
+    Completed(BuildSummary),
+}
+
+/// Tracks builds across process snapshots
+#[derive(Default)]
+pub struct BuildDetector {
+    sessions: HashMap<u32, BuildSession>,
+}
+
+impl BuildDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sessions(&self) -> impl Iterator<Item = &BuildSession> {
+        self.sessions.values()
+    }
+
+    pub fn session(&self, pid: u32) -> Option<&BuildSession> {
+        self.sessions.get(&pid)
+    }
+
+    pub fn is_building(&self) -> bool {
+        !self.sessions.is_empty()
+    }
+
+    /// Whether `pid` belongs to any active build
+    pub fn in_build(&self, pid: u32) -> bool {
+        self.sessions.values().any(|s| s.tree.contains(&pid))
+    }
+
+    /// Update sessions from a fresh snapshot
+    pub fn detect(&mut self, processes: &[BuildProcess]) -> Vec<BuildEvent> {
+        let by_pid: HashMap<u32, &BuildProcess> = processes.iter().map(|p| (p.pid, p)).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for process in processes {
+            if let Some(parent) = process.parent {
+                children.entry(parent).or_default().push(process.pid);
+            }
+        }
+
+        // Follow each build's tree and track its memory
+        for session in self.sessions.values_mut() {
+            let mut stack = vec![session.pid];
+            let mut live_bytes = 0u64;
+            while let Some(pid) = stack.pop() {
+                let Some(process) = by_pid.get(&pid) else { continue };
+                session.tree.insert(pid);
+                live_bytes += process.memory_bytes;
+                stack.extend(children.get(&pid).into_iter().flatten());
+            }
+            session.peak_memory_mb = session.peak_memory_mb.max(live_bytes as f64 / 1024.0 / 1024.0);
+        }
+
+        let mut events = Vec::new();
+
+        // New roots: build tools that aren't part of a tracked build and
+        // weren't started by another build tool (make -> make, cargo -> cargo)
+        let candidates: HashMap<u32, BuildTool> = processes
+            .iter()
+            .filter(|p| !self.in_build(p.pid))
+            .filter_map(|p| BuildTool::identify(&p.name, &p.args).map(|tool| (p.pid, tool)))
+            .collect();
+        for (&pid, &tool) in &candidates {
+            let nested = ancestors(pid, &by_pid).any(|a| candidates.contains_key(&a) || self.in_build(a));
+            if nested {
+                continue;
+            }
+            let process = by_pid[&pid];
+            let session = BuildSession {
+                pid,
+                tool,
+                command: command_line(process),
+                project_dir: process.cwd.clone(),
+                started: Instant::now(),
+                tree: HashSet::from([pid]),
+                peak_memory_mb: process.memory_bytes as f64 / 1024.0 / 1024.0,
+            };
+            self.sessions.insert(pid, session);
+            events.push(BuildEvent::Started(pid));
+        }
+
+        let finished: Vec<u32> = self.sessions.keys().filter(|pid| !by_pid.contains_key(pid)).copied().collect();
+        for pid in finished {
+            if let Some(session) = self.sessions.remove(&pid) {
+                let duration = session.started.elapsed();
+                events.push(BuildEvent::Completed(BuildSummary { session, duration }));
+            }
+        }
+
+        events
+    }
+}
+
+/// Parent chain of `pid`, nearest first
+pub(crate) fn ancestors<'a>(pid: u32, by_pid: &'a HashMap<u32, &BuildProcess>) -> impl Iterator<Item = u32> + 'a {
+    let mut current = by_pid.get(&pid).and_then(|p| p.parent);
+    // Bounded in case of pid reuse forming a cycle
+    std::iter::from_fn(move || {
+        let pid = current?;
+        current = by_pid.get(&pid).and_then(|p| p.parent).filter(|&next| next != pid);
+        Some(pid)
+    })
+    .take(64)
+}
+
+/// Program name plus arguments, without the full program path
+fn command_line(process: &BuildProcess) -> String {
+    let mut parts = vec![process.name.trim_end_matches(".exe").to_string()];
+    parts.extend(process.args.iter().skip(1).cloned());
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn process(pid: u32, parent: Option<u32>, name: &str, line: &str) -> BuildProcess {
+        BuildProcess {
+            pid,
+            parent,
+            name: name.into(),
+            args: args(line),
+            cwd: Some(PathBuf::from("/src/app")),
+            exe: None,
+            memory_bytes: 100 * 1024 * 1024,
+            cpu_percent: 50.0,
+        }
+    }
+
+    #[test]
+    fn test_identify_build_tools() {
+        assert_eq!(BuildTool::identify("cargo.exe", &args("C:\\cargo.exe build --release")), Some(BuildTool::Cargo));
+        assert_eq!(BuildTool::identify("cargo", &args("cargo watch")), None);
+        assert_eq!(BuildTool::identify("node", &args("node ./node_modules/.bin/webpack --mode production")), Some(BuildTool::Webpack));
+        assert_eq!(BuildTool::identify("node", &args("node ./node_modules/.bin/vite")), None);
+        assert_eq!(BuildTool::identify("node", &args("node ./node_modules/.bin/vite build")), Some(BuildTool::Vite));
+        assert_eq!(BuildTool::identify("cmake", &args("cmake --build out")), Some(BuildTool::CMake));
+        assert_eq!(BuildTool::identify("java", &args("java -cp gradle.jar org.gradle.launcher.daemon.bootstrap.GradleDaemon")), None);
+        assert_eq!(BuildTool::identify("go", &args("go build ./...")), Some(BuildTool::Go));
+    }
+
+    #[test]
+    fn test_session_follows_tree_and_completes() {
+        let mut detector = BuildDetector::new();
+        let shell = process(1, None, "bash", "bash");
+        let cargo = process(10, Some(1), "cargo", "cargo build");
+
+        let events = detector.detect(&[shell.clone(), cargo.clone()]);
+        assert!(matches!(events.as_slice(), [BuildEvent::Started(10)]));
+
+        // rustc children and a nested cargo join the existing session
+        let rustc = process(11, Some(10), "rustc", "rustc --crate-name app");
+        let nested = process(12, Some(10), "cargo", "cargo build -p dep");
+        assert!(detector.detect(&[shell.clone(), cargo, rustc, nested]).is_empty());
+        let session = detector.session(10).unwrap();
+        assert!(session.tree.contains(&11) && session.tree.contains(&12));
+        assert!(session.peak_memory_mb >= 299.0);
+
+        let events = detector.detect(&[shell]);
+        match events.as_slice() {
+            [BuildEvent::Completed(summary)] => assert_eq!(summary.session.command, "cargo build"),
+            other => panic!("unexpected events: {:?}", other),
+        }
+        assert!(!detector.is_building());
+    }
+}
@@ -0,0 +1,324 @@
+//! Build environment optimizer (ADR-017)
+//!
+//! Detects cargo, msbuild, gradle, cmake, make/ninja, webpack/vite, go and
+//! bazel builds from process names and command lines. Each build's whole
+//! process tree is tracked. While a build runs it is raised in priority and
+//! allowed on every core. Standby memory is flushed, the power plan is set
+//! to high performance and busy background processes are lowered.
+//! Everything is restored when the last build exits.
+//!
+//! Every finished build is appended to `builds.jsonl`. Speedups are measured
+//! against unboosted runs of the same command in the same directory.
+
+mod boost;
+mod detect;
+
+pub use boost::{BoostReport, BuildBooster};
+pub(crate) use boost::sys as priority;
+pub use detect::{snapshot, BuildDetector, BuildEvent, BuildProcess, BuildSession, BuildSummary, BuildTool};
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+use crate::core::text::truncate;
+
+/// File name of the build history inside the config directory
+pub const BUILDS_FILE: &str = "builds.jsonl";
+
+/// Builds shorter than this are too noisy to compare
+const MIN_RECORDED_SECS: f64 = 2.0;
+
+/// One finished build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub tool: String,
+    /// Matches runs of the same command in the same directory
+    pub key: String,
+    pub command: String,
+    pub project_dir: Option<PathBuf>,
+    pub duration_secs: f64,
+    pub peak_memory_mb: f64,
+    pub boosted: bool,
+}
+
+impl BuildRecord {
+    pub fn from_summary(summary: &BuildSummary, boosted: bool) -> Self {
+        Self {
+            timestamp_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+            tool: summary.session.tool.name().to_string(),
+            key: summary.session.key(),
+            command: summary.session.command.clone(),
+            project_dir: summary.session.project_dir.clone(),
+            duration_secs: summary.duration.as_secs_f64(),
+            peak_memory_mb: summary.session.peak_memory_mb,
+            boosted,
+        }
+    }
+}
+
+/// Boosted build compared with the unboosted average
+#[derive(Debug, Clone, Copy)]
+pub struct Speedup {
+    pub baseline_secs: f64,
+    pub baseline_runs: usize,
+    /// Positive when the boosted build was faster
+    pub percent: f64,
+}
+
+/// Append-only store of finished builds
+pub struct BuildHistory {
+    path: Option<PathBuf>,
+    records: Vec<BuildRecord>,
+}
+
+impl BuildHistory {
+    /// In-memory history that is never written to disk
+    pub fn new() -> Self {
+        Self { path: None, records: Vec::new() }
+    }
+
+    /// Open the history in the default config directory
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_file(BUILDS_FILE)?)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut records = Vec::new();
+        if path.exists() {
+            let file = File::open(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                // Skip torn or foreign lines instead of failing the whole load
+                if let Ok(record) = serde_json::from_str(&line) {
+                    records.push(record);
+                }
+            }
+        }
+        Ok(Self { path: Some(path), records })
+    }
+
+    pub fn records(&self) -> &[BuildRecord] {
+        &self.records
+    }
+
+    pub fn append(&mut self, record: BuildRecord) -> Result<(), String> {
+        if let Some(ref path) = self.path {
+            let line = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+            writeln!(file, "{}", line).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        }
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// Compare a boosted run with earlier unboosted runs of the same build
+    pub fn speedup(&self, record: &BuildRecord) -> Option<Speedup> {
+        if !record.boosted {
+            return None;
+        }
+        let baseline: Vec<f64> = self
+            .records
+            .iter()
+            .filter(|r| !r.boosted && r.key == record.key)
+            .map(|r| r.duration_secs)
+            .collect();
+        if baseline.is_empty() || record.duration_secs <= 0.0 {
+            return None;
+        }
+        let baseline_secs = baseline.iter().sum::<f64>() / baseline.len() as f64;
+        Some(Speedup {
+            baseline_secs,
+            baseline_runs: baseline.len(),
+            percent: (baseline_secs / record.duration_secs - 1.0) * 100.0,
+        })
+    }
+
+    pub fn print_summary(&self, limit: usize) {
+        println!("\n🔨 Build History\n");
+        if self.records.is_empty() {
+            println!("  No builds recorded yet. Run `build watch` while you build.");
+            return;
+        }
+
+        println!("┌──────────┬──────────────────────────────┬──────────┬─────────┬──────────┐");
+        println!("│ Tool     │ Command                      │ Duration │ Boosted │ Speedup  │");
+        println!("├──────────┼──────────────────────────────┼──────────┼─────────┼──────────┤");
+        for record in self.records.iter().rev().take(limit) {
+            let speedup = self
+                .speedup(record)
+                .map(|s| format!("{:+.0}%", s.percent))
+                .unwrap_or_else(|| "-".into());
+            println!(
+                "│ {:8} │ {:28} │ {:>8} │ {:^7} │ {:>8} │",
+                truncate(&record.tool, 8),
+                truncate(&record.command, 28),
+                format_duration(record.duration_secs),
+                if record.boosted { "yes" } else { "no" },
+                speedup
+            );
+        }
+        println!("└──────────┴──────────────────────────────┴──────────┴─────────┴──────────┘");
+    }
+}
+
+impl Default for BuildHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Detects builds, boosts them and reports how long they took
+pub struct BuildWatcher {
+    system: System,
+    detector: BuildDetector,
+    booster: Option<BuildBooster>,
+    history: BuildHistory,
+}
+
+impl BuildWatcher {
+    /// `boost: false` only measures, building up the unboosted baseline
+    pub fn new(boost: bool) -> Self {
+        let history = BuildHistory::open_default().unwrap_or_else(|e| {
+            tracing::warn!("Build history unavailable: {}", e);
+            BuildHistory::new()
+        });
+        let booster = boost.then(|| {
+            let config = crate::core::config::OptimizerConfig::load_or_default();
+            // Services a crashed earlier run left paused
+            crate::features::throttle::restore_stale();
+            BuildBooster::new(config.protected_processes, config.throttle_services)
+        });
+        // Our own polling is background work; keep it off the performance cores
+        if crate::accel::CpuTopology::get().is_hybrid() {
+            let _ = crate::accel::topology::pin_to_efficiency_cores(std::process::id());
+        }
+        Self { system: System::new(), detector: BuildDetector::new(), booster, history }
+    }
+
+    pub fn detector(&self) -> &BuildDetector {
+        &self.detector
+    }
+
+    pub fn history(&self) -> &BuildHistory {
+        &self.history
+    }
+
+    /// Take a process snapshot, handle started/finished builds and print them
+    pub fn tick(&mut self) {
+        let processes = snapshot(&mut self.system);
+        let events = self.detector.detect(&processes);
+
+        for event in events {
+            match event {
+                BuildEvent::Started(pid) => {
+                    let Some(session) = self.detector.session(pid) else { continue };
+                    println!("▶ {} build: {}", session.tool, truncate(&session.command, 60));
+                    if let Some(ref dir) = session.project_dir {
+                        println!("  in {}", dir.display());
+                    }
+                    if let Some(ref mut booster) = self.booster {
+                        booster.start(session, &self.detector, &processes).print();
+                    }
+                }
+                BuildEvent::Completed(summary) => {
+                    if let Some(ref mut booster) = self.booster {
+                        booster.finish(&summary.session, &self.detector, &processes);
+                    }
+                    self.complete(&summary);
+                }
+            }
+        }
+
+        if let Some(ref mut booster) = self.booster {
+            booster.follow(&self.detector, &processes);
+        }
+    }
+
+    fn complete(&mut self, summary: &BuildSummary) {
+        let record = BuildRecord::from_summary(summary, self.booster.is_some());
+        print!(
+            "■ {} finished in {} (peak {:.0} MB)",
+            summary.session.tool,
+            format_duration(record.duration_secs),
+            record.peak_memory_mb
+        );
+        if record.duration_secs < MIN_RECORDED_SECS {
+            println!(" — too short to record");
+            return;
+        }
+
+        match self.history.speedup(&record) {
+            Some(s) if s.percent >= 0.0 => println!(
+                " — {:.0}% faster than unboosted average of {} ({} run(s))",
+                s.percent, format_duration(s.baseline_secs), s.baseline_runs
+            ),
+            Some(s) => println!(
+                " — {:.0}% slower than unboosted average of {} ({} run(s))",
+                -s.percent, format_duration(s.baseline_secs), s.baseline_runs
+            ),
+            None if record.boosted => println!(" — no unboosted baseline yet (run `build watch --no-boost` once)"),
+            None => println!(" — recorded as baseline"),
+        }
+        if let Err(e) = self.history.append(record) {
+            tracing::warn!("Failed to record build: {}", e);
+        }
+        if self.booster.as_ref().is_some_and(|b| !b.is_active()) {
+            println!("  Restored normal priorities");
+        }
+    }
+
+    /// Undo any boosts still applied (call before exiting)
+    pub fn restore(&mut self) {
+        if let Some(ref mut booster) = self.booster {
+            booster.restore_all();
+        }
+    }
+}
+
+fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 60 { format!("{}m {:02}s", secs / 60, secs % 60) } else { format!("{}s", secs) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: &str, secs: f64, boosted: bool) -> BuildRecord {
+        BuildRecord {
+            timestamp_ms: 0,
+            tool: "cargo".into(),
+            key: key.into(),
+            command: "cargo build".into(),
+            project_dir: None,
+            duration_secs: secs,
+            peak_memory_mb: 0.0,
+            boosted,
+        }
+    }
+
+    #[test]
+    fn test_speedup_against_unboosted_baseline() {
+        let mut history = BuildHistory::new();
+        history.append(record("a", 100.0, false)).unwrap();
+        history.append(record("a", 140.0, false)).unwrap();
+        history.append(record("b", 10.0, false)).unwrap();
+
+        let boosted = record("a", 96.0, true);
+        let speedup = history.speedup(&boosted).unwrap();
+        assert_eq!(speedup.baseline_runs, 2);
+        assert!((speedup.percent - 25.0).abs() < 1e-9);
+
+        assert!(history.speedup(&record("a", 50.0, false)).is_none());
+        assert!(history.speedup(&record("c", 50.0, true)).is_none());
+    }
+}
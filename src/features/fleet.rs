@@ -0,0 +1,407 @@
+//! Fleet mode: many machines reporting to one dashboard server
+//!
+//! A daemon or Windows service with `fleet_hub` set pushes a [`FleetSample`]
+//! to `POST /api/fleet/push` on that hub every `fleet_push_secs`, with
+//! `fleet_token` as its bearer token. A dashboard server started with
+//! `--hub` appends the samples to `fleet.jsonl`, stamped with its own clock,
+//! and serves a [`FleetView`] of every host at `GET /api/fleet`: the latest
+//! sample, whether it is still reporting, and its memory curve over the
+//! last hour. Samples older than 7 days are pruned.
+//!
+//! The pusher speaks plain HTTP, so the token crosses the network in the
+//! clear; keep hubs on a trusted LAN.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::OptimizerConfig;
+use crate::core::history::{HistoryFilter, HistoryStore, TimeRange};
+use crate::monitor::procs;
+
+/// File name of the hub's sample store inside the config directory
+pub const FLEET_FILE: &str = "fleet.jsonl";
+
+/// Default time between pushes
+pub const DEFAULT_PUSH_SECS: u64 = 60;
+
+/// Path samples are pushed to
+pub const PUSH_PATH: &str = "/api/fleet/push";
+
+/// How long the hub keeps samples
+pub const KEEP_MS: u64 = 7 * 24 * 3600 * 1000;
+
+/// Window of the memory curve in the view
+const SERIES_MS: u64 = 3600 * 1000;
+
+/// Window in which a host's latest sample is looked for
+const LATEST_MS: u64 = 24 * 3600 * 1000;
+
+/// Missed pushes before a host counts as offline
+const OFFLINE_AFTER_PUSHES: u64 = 3;
+
+const MAX_HOST_LEN: usize = 64;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What one machine reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetSample {
+    pub host: String,
+    /// When the hub received it
+    #[serde(default)]
+    pub timestamp_ms: u64,
+    pub platform: String,
+    pub version: String,
+    pub memory_percent: f64,
+    pub available_mb: f64,
+    pub total_mb: f64,
+    pub process_count: usize,
+    /// Optimizations in the last 24 hours and what they freed
+    pub optimizations_24h: usize,
+    pub freed_mb_24h: f64,
+    /// The sender's push interval, which tells the hub when it went quiet
+    pub push_secs: u64,
+}
+
+impl FleetSample {
+    /// Sample this machine
+    pub fn collect(host: String, push_secs: u64) -> Result<Self, String> {
+        let optimizer = crate::platform::create_optimizer();
+        let status = optimizer.get_memory_status()?;
+        let process_count = procs::with_processes(Duration::from_secs(push_secs), |system| system.processes().len());
+        let recent = HistoryStore::open_default()
+            .and_then(|store| store.query(TimeRange::last(Duration::from_secs(24 * 3600)), &HistoryFilter::default()))
+            .unwrap_or_default();
+        let summary = HistoryStore::summarize(&recent);
+        Ok(Self {
+            host,
+            timestamp_ms: 0,
+            platform: optimizer.platform_name().to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            memory_percent: status.memory_load_percent as f64,
+            available_mb: status.available_physical_mb,
+            total_mb: status.total_physical_mb,
+            process_count,
+            optimizations_24h: summary.count,
+            freed_mb_24h: summary.total_freed_mb,
+            push_secs,
+        })
+    }
+
+    /// Refuse samples the hub shouldn't store
+    pub fn validate(&self) -> Result<(), String> {
+        let host_ok = !self.host.is_empty()
+            && self.host.len() <= MAX_HOST_LEN
+            && self.host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !host_ok {
+            return Err(format!("Invalid host name '{}'", self.host));
+        }
+        if !(0.0..=100.0).contains(&self.memory_percent) {
+            return Err(format!("memory_percent {} is not a percentage", self.memory_percent));
+        }
+        if self.push_secs == 0 {
+            return Err("push_secs must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether the host has missed too many pushes by `now_ms`
+    pub fn is_stale(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.timestamp_ms) > self.push_secs * 1000 * OFFLINE_AFTER_PUSHES
+    }
+}
+
+/// This machine's name as reported to the hub
+pub fn host_name() -> String {
+    sysinfo::System::host_name()
+        .map(|name| name.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')).collect())
+        .filter(|name: &String| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// One host in the combined view
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetHost {
+    pub host: String,
+    pub online: bool,
+    pub latest: FleetSample,
+    pub peak_percent_1h: f64,
+    /// `(timestamp_ms, memory_percent)` over the last hour, oldest first
+    pub series: Vec<(u64, f64)>,
+}
+
+/// Every host's state, the most loaded online host first
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetView {
+    pub hosts: Vec<FleetHost>,
+    pub online: usize,
+    pub offline: usize,
+    /// Online hosts at or above `high_percent`
+    pub under_pressure: usize,
+    pub high_percent: u32,
+}
+
+impl FleetView {
+    pub fn build(samples: &[FleetSample], now_ms: u64, high_percent: u32) -> Self {
+        let mut by_host: BTreeMap<&str, Vec<&FleetSample>> = BTreeMap::new();
+        for sample in samples {
+            by_host.entry(&sample.host).or_default().push(sample);
+        }
+
+        let mut hosts: Vec<FleetHost> = by_host
+            .into_values()
+            .filter_map(|mut samples| {
+                samples.sort_by_key(|s| s.timestamp_ms);
+                let latest = (*samples.last()?).clone();
+                let series: Vec<(u64, f64)> = samples
+                    .iter()
+                    .filter(|s| now_ms.saturating_sub(s.timestamp_ms) <= SERIES_MS)
+                    .map(|s| (s.timestamp_ms, s.memory_percent))
+                    .collect();
+                Some(FleetHost {
+                    host: latest.host.clone(),
+                    online: !latest.is_stale(now_ms),
+                    peak_percent_1h: series.iter().map(|(_, p)| *p).fold(0.0, f64::max),
+                    series,
+                    latest,
+                })
+            })
+            .collect();
+        hosts.sort_by(|a, b| {
+            b.online.cmp(&a.online).then(b.latest.memory_percent.total_cmp(&a.latest.memory_percent))
+        });
+
+        let online = hosts.iter().filter(|h| h.online).count();
+        let under_pressure =
+            hosts.iter().filter(|h| h.online && h.latest.memory_percent >= high_percent as f64).count();
+        Self { offline: hosts.len() - online, online, under_pressure, high_percent, hosts }
+    }
+}
+
+/// The hub's append-only sample store
+pub struct FleetStore {
+    path: PathBuf,
+}
+
+impl FleetStore {
+    /// Open the store at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_file(FLEET_FILE)?)
+    }
+
+    /// Open a store at an explicit path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(Self { path })
+    }
+
+    pub fn record(&self, sample: &FleetSample) -> Result<(), String> {
+        let line = serde_json::to_string(sample).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Samples received at or after `since_ms`, skipping malformed lines
+    pub fn read(&self, since_ms: u64) -> Result<Vec<FleetSample>, String> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<FleetSample>(&line).ok())
+            .filter(|s| s.timestamp_ms >= since_ms)
+            .collect())
+    }
+
+    /// The combined view as of `now_ms`
+    pub fn view(&self, now_ms: u64, high_percent: u32) -> Result<FleetView, String> {
+        Ok(FleetView::build(&self.read(now_ms.saturating_sub(LATEST_MS))?, now_ms, high_percent))
+    }
+
+    /// Drop samples older than `keep_after_ms`, returning how many were removed
+    pub fn prune(&self, keep_after_ms: u64) -> Result<usize, String> {
+        let all = self.read(0)?;
+        let kept: Vec<&FleetSample> = all.iter().filter(|s| s.timestamp_ms >= keep_after_ms).collect();
+        let removed = all.len() - kept.len();
+        if removed == 0 {
+            return Ok(0);
+        }
+        let mut content = String::new();
+        for sample in kept {
+            content.push_str(&serde_json::to_string(sample).map_err(|e| e.to_string())?);
+            content.push('\n');
+        }
+        std::fs::write(&self.path, content).map_err(|e| format!("Failed to write {:?}: {}", self.path, e))?;
+        Ok(removed)
+    }
+}
+
+/// `host:port` and path prefix of an `http://` hub URL
+pub fn parse_hub_url(url: &str) -> Result<(String, String), String> {
+    let rest = url
+        .trim()
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("Fleet hub '{}' should be an http:// URL", url))?;
+    let (authority, prefix) = rest.split_once('/').map_or((rest, ""), |(a, p)| (a, p));
+    if authority.is_empty() {
+        return Err(format!("Fleet hub '{}' has no host", url));
+    }
+    let authority = if authority.rsplit_once(':').is_some_and(|(_, port)| port.chars().all(|c| c.is_ascii_digit())) {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    let prefix = prefix.trim_end_matches('/');
+    Ok((authority, if prefix.is_empty() { String::new() } else { format!("/{}", prefix) }))
+}
+
+/// Send one sample to the hub
+pub fn push(hub: &str, token: Option<&str>, sample: &FleetSample) -> Result<(), String> {
+    let (authority, prefix) = parse_hub_url(hub)?;
+    let addr = authority
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", authority, e))?
+        .next()
+        .ok_or_else(|| format!("{} did not resolve", authority))?;
+    let mut stream = TcpStream::connect_timeout(&addr, HTTP_TIMEOUT).map_err(|e| format!("{}: {}", authority, e))?;
+    stream.set_read_timeout(Some(HTTP_TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(HTTP_TIMEOUT)).map_err(|e| e.to_string())?;
+
+    let body = serde_json::to_string(sample).map_err(|e| e.to_string())?;
+    let authorization = token.map(|t| format!("Authorization: Bearer {}\r\n", t)).unwrap_or_default();
+    let request = format!(
+        "POST {}{} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        prefix,
+        PUSH_PATH,
+        authority,
+        body.len(),
+        authorization,
+        body
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok()) {
+        Some(code) if (200..300).contains(&code) => Ok(()),
+        Some(_) => Err(format!("Hub answered {}", status_line)),
+        None => Err("Hub sent no HTTP response".to_string()),
+    }
+}
+
+/// Push samples in the background while `fleet_hub` is set
+///
+/// The config is re-read before every push, so setting or clearing the hub
+/// takes effect without a restart. Failures are logged once until a push
+/// succeeds again.
+pub fn spawn() {
+    std::thread::spawn(|| {
+        let host = host_name();
+        let mut failing = false;
+        loop {
+            let config = OptimizerConfig::load_or_default();
+            if let Some(hub) = config.fleet_hub.as_deref().filter(|h| !h.is_empty()) {
+                let pushed = FleetSample::collect(host.clone(), config.fleet_push_secs)
+                    .and_then(|sample| push(hub, config.fleet_token.as_deref(), &sample));
+                match pushed {
+                    Ok(()) if failing => {
+                        tracing::info!("Fleet pushes to {} work again", hub);
+                        failing = false;
+                    }
+                    Ok(()) => {}
+                    Err(e) if !failing => {
+                        tracing::warn!("Fleet push to {} failed: {}", hub, e);
+                        failing = true;
+                    }
+                    Err(e) => tracing::debug!("Fleet push to {} failed: {}", hub, e),
+                }
+            }
+            std::thread::sleep(Duration::from_secs(config.fleet_push_secs.max(10)));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(host: &str, timestamp_ms: u64, memory_percent: f64) -> FleetSample {
+        FleetSample {
+            host: host.to_string(),
+            timestamp_ms,
+            platform: "Linux".to_string(),
+            version: "0.0.0".to_string(),
+            memory_percent,
+            available_mb: 4096.0,
+            total_mb: 16384.0,
+            process_count: 300,
+            optimizations_24h: 2,
+            freed_mb_24h: 512.0,
+            push_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_view() {
+        let now = 10 * 3600 * 1000;
+        let samples = [
+            sample("ws-01", now - 120_000, 60.0),
+            sample("ws-01", now - 60_000, 70.0),
+            sample("ws-02", now - 30_000, 92.0),
+            sample("ws-03", now - 2 * 3600 * 1000, 99.0),
+        ];
+        let view = FleetView::build(&samples, now, 85);
+        let hosts: Vec<&str> = view.hosts.iter().map(|h| h.host.as_str()).collect();
+        assert_eq!(hosts, ["ws-02", "ws-01", "ws-03"], "online first, most loaded first");
+        assert_eq!((view.online, view.offline, view.under_pressure), (2, 1, 1));
+        assert_eq!(view.hosts[1].latest.memory_percent, 70.0);
+        assert_eq!(view.hosts[1].series.len(), 2);
+        assert_eq!(view.hosts[1].peak_percent_1h, 70.0);
+        assert!(view.hosts[2].series.is_empty());
+    }
+
+    #[test]
+    fn test_store_and_validate() {
+        let dir = std::env::temp_dir().join(format!("ruvector-fleet-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = FleetStore::open(dir.join(FLEET_FILE)).unwrap();
+        store.record(&sample("ws-01", 1_000, 50.0)).unwrap();
+        store.record(&sample("ws-01", 2_000, 55.0)).unwrap();
+        assert_eq!(store.read(1_500).unwrap().len(), 1);
+        assert_eq!(store.prune(1_500).unwrap(), 1);
+        assert_eq!(store.read(0).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(sample("ws-01", 0, 50.0).validate().is_ok());
+        assert!(sample("", 0, 50.0).validate().is_err());
+        assert!(sample("ws 01; rm", 0, 50.0).validate().is_err());
+        assert!(sample("ws-01", 0, 150.0).validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_hub_url() {
+        assert_eq!(parse_hub_url("http://hub.lan:8080").unwrap(), ("hub.lan:8080".to_string(), String::new()));
+        assert_eq!(parse_hub_url("http://hub.lan/memopt/").unwrap(), ("hub.lan:80".to_string(), "/memopt".to_string()));
+        assert!(parse_hub_url("https://hub.lan").is_err());
+        assert!(parse_hub_url("http://").is_err());
+    }
+}
@@ -0,0 +1,524 @@
+//! Telling games apart from everything else that runs
+//!
+//! A name list misses most games, so every program is scored on a few
+//! signals: a known or engine-style name, owning the fullscreen foreground
+//! window (exclusive or borderless), and keeping the GPU busy. Names the
+//! user taught are always games and names they ruled out never are; both
+//! live in `games.json` next to the config.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use crate::monitor::{gpu, procs};
+
+/// Taught games, in the config directory
+pub const GAMES_FILE: &str = "games.json";
+
+/// Confidence from which a candidate counts as a game
+pub const MIN_CONFIDENCE: f32 = 0.6;
+
+/// 3D engine share from which the fullscreen app counts as GPU-bound
+const GPU_BUSY_PERCENT: u32 = 50;
+
+/// How long a GPU reading of the fullscreen app is reused
+const GPU_SAMPLE_TTL: Duration = Duration::from_secs(30);
+
+/// Oldest process list a game check works with; the tray checks every 5 seconds
+const PROCESS_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// Game process names, lowercase; see [`names_match`]
+const GAMES: &[&str] = &[
+    "valorant", "valorant-win64-shipping",
+    "csgo", "cs2",
+    "fortnite", "fortniteclient-win64-shipping",
+    "minecraft",
+    "league of legends", "leagueclient", "leagueoflegends",
+    "overwatch", "overwatch 2",
+    "apex_legends", "apexlegends", "r5apex",
+    "pubg", "tslgame",
+    "gta5", "gtavlauncher",
+    "rdr2",
+    "cyberpunk2077",
+    "eldenring",
+    "hogwartslegacy",
+    "starfield",
+    "baldursgate3", "bg3",
+    "diablo", "diablo iv",
+    "destiny2",
+    "warframe",
+    "rocketleague",
+    "dota2",
+    "steam_oculusvr", // VR games
+];
+
+/// Launchers count as gaming with fullscreen or GPU evidence, and a game
+/// running next to one wins; matched like [`GAMES`]
+const LAUNCHERS: &[&str] = &[
+    "epicgameslauncher",
+    "origin", "eadesktop",
+    "battle.net", "battlenet",
+    "ubisoft connect", "upc", "uplay",
+    "gog galaxy",
+];
+
+/// Suffixes engines give their executables (`Game-Win64-Shipping.exe`)
+const GAME_LIKE: &[&str] = &["-win64-shipping", "-win32-shipping", "-dx11", "-dx12", "-vulkan"];
+
+/// A running game and all of its processes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningGame {
+    /// Process name, lowercase
+    pub name: String,
+    pub pids: Vec<u32>,
+}
+
+impl RunningGame {
+    /// Processes named `name` (any case), if one is running
+    pub fn find(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let pids: Vec<u32> = processes().into_iter().filter(|(_, n)| *n == name).map(|(pid, _)| pid).collect();
+        (!pids.is_empty()).then_some(Self { name, pids })
+    }
+}
+
+/// Why a program looks like a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// The user said it is one
+    Taught,
+    /// On the built-in game list
+    KnownGame,
+    /// Named like an engine build, or with a game's name inside it
+    GameLikeName,
+    /// A game launcher
+    Launcher,
+    /// Owns the fullscreen foreground window
+    Fullscreen,
+    /// Keeps the GPU's 3D engine this busy, in percent
+    GpuBusy(u32),
+}
+
+impl Signal {
+    /// Confidence this signal adds
+    pub fn weight(&self) -> f32 {
+        match self {
+            Signal::Taught => 1.0,
+            Signal::KnownGame => 0.6,
+            Signal::Launcher | Signal::GameLikeName | Signal::Fullscreen => 0.4,
+            Signal::GpuBusy(_) => 0.3,
+        }
+    }
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signal::Taught => write!(f, "taught"),
+            Signal::KnownGame => write!(f, "known game"),
+            Signal::GameLikeName => write!(f, "game-like name"),
+            Signal::Launcher => write!(f, "launcher"),
+            Signal::Fullscreen => write!(f, "fullscreen"),
+            Signal::GpuBusy(percent) => write!(f, "GPU {}%", percent),
+        }
+    }
+}
+
+/// A program with at least one game signal
+#[derive(Debug, Clone)]
+pub struct GameCandidate {
+    pub game: RunningGame,
+    /// 0.0 to 1.0; [`MIN_CONFIDENCE`] or more is a game
+    pub confidence: f32,
+    pub signals: Vec<Signal>,
+}
+
+impl GameCandidate {
+    pub fn is_game(&self) -> bool {
+        self.confidence >= MIN_CONFIDENCE
+    }
+
+    fn is_launcher(&self) -> bool {
+        self.signals.contains(&Signal::Launcher)
+    }
+}
+
+impl std::fmt::Display for GameCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let signals: Vec<String> = self.signals.iter().map(|s| s.to_string()).collect();
+        write!(f, "{} {:.0}% ({})", self.game.name, self.confidence * 100.0, signals.join(", "))
+    }
+}
+
+/// Games the user added or ruled out, by name without `.exe`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaughtGames {
+    #[serde(default)]
+    pub games: BTreeSet<String>,
+    #[serde(default)]
+    pub not_games: BTreeSet<String>,
+}
+
+impl TaughtGames {
+    /// Empty when the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+}
+
+/// Scores running programs and remembers what the user taught it
+pub struct GameDetector {
+    /// Where taught games are kept; `None` if the config dir is unavailable
+    path: Option<PathBuf>,
+    taught: TaughtGames,
+    /// Last GPU reading of the fullscreen app: pid, percent, when
+    gpu_sample: Option<(u32, Option<u32>, Instant)>,
+}
+
+impl GameDetector {
+    pub fn open_default() -> Self {
+        let path = crate::features::config_file(GAMES_FILE).ok();
+        let taught = path.as_deref().map(TaughtGames::load).unwrap_or_default();
+        Self { path, taught, gpu_sample: None }
+    }
+
+    pub fn taught(&self) -> &TaughtGames {
+        &self.taught
+    }
+
+    /// Every running program with a game signal, most likely game first
+    pub fn candidates(&mut self) -> Vec<GameCandidate> {
+        // Pick up names taught from the CLI while we run
+        if let Some(ref path) = self.path {
+            self.taught = TaughtGames::load(path);
+        }
+        let fullscreen = fullscreen_pid();
+        let cached = self.gpu_sample.filter(|(pid, _, at)| Some(*pid) == fullscreen && at.elapsed() < GPU_SAMPLE_TTL);
+        let mut sampled = None;
+        let candidates = score(processes(), &self.taught, fullscreen, |pid| {
+            let percent = cached.map_or_else(|| gpu::process_utilization(pid), |(_, percent, _)| percent);
+            sampled = Some((pid, percent));
+            percent
+        });
+        if let (None, Some((pid, percent))) = (cached, sampled) {
+            self.gpu_sample = Some((pid, percent, Instant::now()));
+        }
+        candidates
+    }
+
+    /// The most likely game, if one is confident enough
+    pub fn detect(&mut self) -> Option<GameCandidate> {
+        self.candidates().into_iter().next().filter(GameCandidate::is_game)
+    }
+
+    /// Always treat `name` as a game
+    pub fn teach(&mut self, name: &str) -> Result<(), String> {
+        let name = normalize(name)?;
+        self.taught.not_games.remove(&name);
+        self.taught.games.insert(name);
+        self.save()
+    }
+
+    /// Never treat `name` as a game
+    pub fn ignore(&mut self, name: &str) -> Result<(), String> {
+        let name = normalize(name)?;
+        self.taught.games.remove(&name);
+        self.taught.not_games.insert(name);
+        self.save()
+    }
+
+    /// Drop `name` from both lists; returns whether it was on one
+    pub fn forget(&mut self, name: &str) -> Result<bool, String> {
+        let name = normalize(name)?;
+        let found = self.taught.games.remove(&name) | self.taught.not_games.remove(&name);
+        if found {
+            self.save()?;
+        }
+        Ok(found)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = self.path.as_deref().ok_or("No config directory to keep taught games in")?;
+        self.taught.save(path)
+    }
+}
+
+/// Whether `key` is `name`, or `name` followed by a separator
+/// (`battle.net helper`), so `upc` doesn't match `setupcleanup`
+fn names_match(key: &str, name: &str) -> bool {
+    key.strip_prefix(name).is_some_and(|rest| !rest.starts_with(char::is_alphanumeric))
+}
+
+/// Lowercase, without `.exe`, as taught names are stored and matched
+fn normalize(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name).trim();
+    if name.is_empty() {
+        return Err("Process name is empty".into());
+    }
+    Ok(name.to_string())
+}
+
+fn processes() -> Vec<(u32, String)> {
+    procs::with_processes(PROCESS_MAX_AGE, |system| {
+        system
+            .processes()
+            .iter()
+            .map(|(pid, process)| (pid.as_u32(), process.name().to_string_lossy().to_lowercase()))
+            .collect()
+    })
+}
+
+/// Candidates among `processes`, most likely game first and launchers after
+/// games of equal confidence. The GPU is only asked about the fullscreen
+/// app, and only when the other signals fall short.
+fn score(
+    processes: Vec<(u32, String)>,
+    taught: &TaughtGames,
+    fullscreen: Option<u32>,
+    mut gpu_percent: impl FnMut(u32) -> Option<u32>,
+) -> Vec<GameCandidate> {
+    let mut by_name: HashMap<String, Vec<u32>> = HashMap::new();
+    for (pid, name) in processes {
+        by_name.entry(name).or_default().push(pid);
+    }
+
+    let mut candidates = Vec::new();
+    for (name, mut pids) in by_name {
+        let Ok(key) = normalize(&name) else {
+            continue;
+        };
+        if taught.not_games.contains(&key) {
+            continue;
+        }
+        pids.sort_unstable();
+
+        let mut signals = Vec::new();
+        if taught.games.contains(&key) {
+            signals.push(Signal::Taught);
+        }
+        if GAMES.iter().any(|game| names_match(&key, game)) {
+            signals.push(Signal::KnownGame);
+        } else if LAUNCHERS.iter().any(|launcher| names_match(&key, launcher)) {
+            signals.push(Signal::Launcher);
+        } else if GAMES.iter().any(|game| key.contains(game)) || GAME_LIKE.iter().any(|suffix| key.ends_with(suffix)) {
+            signals.push(Signal::GameLikeName);
+        }
+        if let Some(pid) = fullscreen.filter(|pid| pids.contains(pid)) {
+            signals.push(Signal::Fullscreen);
+            if confidence(&signals) < MIN_CONFIDENCE {
+                if let Some(percent) = gpu_percent(pid).filter(|p| *p >= GPU_BUSY_PERCENT) {
+                    signals.push(Signal::GpuBusy(percent));
+                }
+            }
+        }
+        if signals.is_empty() {
+            continue;
+        }
+
+        candidates.push(GameCandidate { confidence: confidence(&signals), game: RunningGame { name, pids }, signals });
+    }
+
+    candidates.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then(a.is_launcher().cmp(&b.is_launcher()))
+            .then_with(|| a.game.name.cmp(&b.game.name))
+    });
+    candidates
+}
+
+fn confidence(signals: &[Signal]) -> f32 {
+    signals.iter().map(Signal::weight).sum::<f32>().min(1.0)
+}
+
+/// Process owning the foreground window, if that window fills its monitor
+/// or the shell reports a Direct3D app in exclusive fullscreen
+#[cfg(target_os = "windows")]
+fn fullscreen_pid() -> Option<u32> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+    use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_RUNNING_D3D_FULL_SCREEN};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetWindowRect, GetWindowThreadProcessId,
+    };
+
+    let mut pid = 0u32;
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return None;
+        }
+
+        let exclusive = SHQueryUserNotificationState().is_ok_and(|state| state == QUNS_RUNNING_D3D_FULL_SCREEN);
+        if !exclusive {
+            // Borderless fullscreen: the window covers its whole monitor
+            let mut window = RECT::default();
+            GetWindowRect(hwnd, &mut window).ok()?;
+            let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+            if !GetMonitorInfoW(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mut info).as_bool() {
+                return None;
+            }
+            let monitor = info.rcMonitor;
+            let covers = window.left <= monitor.left
+                && window.top <= monitor.top
+                && window.right >= monitor.right
+                && window.bottom >= monitor.bottom;
+            if !covers {
+                return None;
+            }
+        }
+
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+    }
+    (pid != 0).then_some(pid)
+}
+
+#[cfg(target_os = "macos")]
+fn fullscreen_pid() -> Option<u32> {
+    const SCRIPT: &str = "tell application \"System Events\" to tell (first process whose frontmost is true) to \
+        return (unix id as text) & \"|\" & (value of attribute \"AXFullScreen\" of window 1 as text)";
+
+    // "1234|true"
+    let out = crate::apps::idle::command_output("osascript", &["-e", SCRIPT])?;
+    let (pid, fullscreen) = out.trim().split_once('|')?;
+    pid.parse().ok().filter(|_| fullscreen == "true")
+}
+
+#[cfg(target_os = "linux")]
+fn fullscreen_pid() -> Option<u32> {
+    use crate::apps::idle::command_output;
+
+    // _NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007
+    let active = command_output("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window = active.split_whitespace().last().filter(|w| w.starts_with("0x") && *w != "0x0")?;
+    parse_xprop_fullscreen(&command_output("xprop", &["-id", window, "_NET_WM_STATE", "_NET_WM_PID"])?)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn fullscreen_pid() -> Option<u32> {
+    None
+}
+
+/// The window's pid if its `_NET_WM_STATE` includes fullscreen
+#[cfg(any(target_os = "linux", test))]
+fn parse_xprop_fullscreen(props: &str) -> Option<u32> {
+    let mut fullscreen = false;
+    let mut pid = None;
+    for line in props.lines() {
+        if line.starts_with("_NET_WM_STATE(") {
+            fullscreen = line.contains("_NET_WM_STATE_FULLSCREEN");
+        } else if line.starts_with("_NET_WM_PID(") {
+            pid = line.rsplit('=').next().and_then(|p| p.trim().parse().ok());
+        }
+    }
+    pid.filter(|_| fullscreen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn procs(list: &[(u32, &str)]) -> Vec<(u32, String)> {
+        list.iter().map(|(pid, name)| (*pid, name.to_string())).collect()
+    }
+
+    #[test]
+    fn test_score_prefers_games_over_launchers() {
+        let running = procs(&[
+            (10, "epicgameslauncher.exe"),
+            (20, "explorer.exe"),
+            (31, "fortniteclient-win64-shipping.exe"),
+            (30, "fortniteclient-win64-shipping.exe"),
+        ]);
+        let candidates = score(running, &TaughtGames::default(), None, |_| None);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].game.name, "fortniteclient-win64-shipping.exe");
+        assert_eq!(candidates[0].game.pids, vec![30, 31]);
+        assert_eq!(candidates[1].signals, vec![Signal::Launcher]);
+        // A launcher alone doesn't start Game Mode
+        assert!(!candidates[1].is_game());
+
+        assert!(score(procs(&[(20, "explorer.exe")]), &TaughtGames::default(), None, |_| None).is_empty());
+    }
+
+    #[test]
+    fn test_score_matches_whole_names() {
+        let running = procs(&[
+            (1, "setupcleanup.exe"),
+            (2, "backupclient.exe"),
+            (3, "upc.exe"),
+            (4, "cs2tool.exe"),
+            (5, "battle.net helper"),
+        ]);
+        let candidates = score(running, &TaughtGames::default(), None, |_| None);
+        let signals = |name: &str| candidates.iter().find(|c| c.game.name == name).map(|c| c.signals.clone());
+        assert_eq!(signals("setupcleanup.exe"), None);
+        assert_eq!(signals("backupclient.exe"), None);
+        assert_eq!(signals("upc.exe"), Some(vec![Signal::Launcher]));
+        assert_eq!(signals("battle.net helper"), Some(vec![Signal::Launcher]));
+        // A game's name inside another name is only a hint
+        assert_eq!(signals("cs2tool.exe"), Some(vec![Signal::GameLikeName]));
+        assert!(candidates.iter().all(|c| !c.is_game()));
+
+        let fullscreen = score(procs(&[(3, "upc.exe")]), &TaughtGames::default(), Some(3), |_| None);
+        assert!(fullscreen[0].is_game());
+    }
+
+    #[test]
+    fn test_score_combines_signals() {
+        let running = procs(&[(5, "indiegame.exe"), (6, "vlc.exe"), (7, "mygame-win64-shipping.exe")]);
+        let none = TaughtGames::default();
+
+        // A fullscreen unknown program needs the GPU to count
+        let busy = score(running.clone(), &none, Some(5), |_| Some(90));
+        assert_eq!(busy[0].game.name, "indiegame.exe");
+        assert_eq!(busy[0].signals, vec![Signal::Fullscreen, Signal::GpuBusy(90)]);
+        assert!(busy[0].is_game());
+
+        let video = score(running.clone(), &none, Some(6), |_| Some(10));
+        let vlc = video.iter().find(|c| c.game.name == "vlc.exe").unwrap();
+        assert!(!vlc.is_game());
+
+        // Engine names alone fall short, fullscreen makes them games without asking the GPU
+        let windowed = score(running.clone(), &none, None, |_| panic!("GPU asked"));
+        assert!(!windowed[0].is_game());
+        let engine = score(running.clone(), &none, Some(7), |_| panic!("GPU asked"));
+        assert_eq!(engine[0].game.name, "mygame-win64-shipping.exe");
+        assert!(engine[0].is_game());
+
+        let mut taught = TaughtGames::default();
+        taught.games.insert("indiegame".into());
+        taught.not_games.insert("mygame-win64-shipping".into());
+        let candidates = score(running, &taught, Some(7), |_| None);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].signals, vec![Signal::Taught]);
+        assert_eq!(candidates[0].confidence, 1.0);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" MyGame.EXE ").unwrap(), "mygame");
+        assert_eq!(normalize("factorio").unwrap(), "factorio");
+        assert!(normalize(".exe").is_err());
+    }
+
+    #[test]
+    fn test_parse_xprop_fullscreen() {
+        let props = "_NET_WM_STATE(ATOM) = _NET_WM_STATE_FULLSCREEN, _NET_WM_STATE_FOCUSED\n_NET_WM_PID(CARDINAL) = 4242\n";
+        assert_eq!(parse_xprop_fullscreen(props), Some(4242));
+
+        let windowed = "_NET_WM_STATE(ATOM) = _NET_WM_STATE_MAXIMIZED_VERT\n_NET_WM_PID(CARDINAL) = 4242\n";
+        assert_eq!(parse_xprop_fullscreen(windowed), None);
+        assert_eq!(parse_xprop_fullscreen("_NET_WM_STATE:  not found.\n_NET_WM_PID(CARDINAL) = 1\n"), None);
+    }
+}
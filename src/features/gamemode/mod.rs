@@ -0,0 +1,304 @@
+//! Game Mode boosts, applied while a game runs and restored when it exits
+//!
+//! The game is raised to high priority and, on hybrid CPUs, pinned to the
+//! performance cores while RuVector itself moves to the efficiency cores.
+//! Background Electron apps are trimmed and the power plan is switched to
+//! high performance. Background services the user opted into are paused or
+//! limited through [`crate::features::throttle`].
+//! Processes named by `background` policy rules get below-normal priority
+//! and, on Windows, very low I/O priority, and RuVector moves itself into
+//! background mode.
+//!
+//! As with the build booster, every change records the value it replaced
+//! and a recycled pid is left alone on restore. Which program is a game is
+//! decided in [`detect`].
+
+pub mod detect;
+
+use std::collections::HashMap;
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::accel::topology::{self, CpuTopology};
+use crate::apps::ElectronManager;
+use crate::core::config::OptimizerConfig;
+use crate::core::history::{self, HistoryRecord, OptimizationTrigger};
+use crate::platform::power::{self, ActivePlan, PowerPlan};
+use crate::security::policy::{Policy, ProcessIdentity};
+
+use super::build::{priority, BoostReport};
+use super::throttle::{self, ThrottleGuard};
+
+pub use detect::{GameCandidate, GameDetector, RunningGame, Signal, TaughtGames};
+
+/// Original settings of a process we raised or lowered
+struct Saved {
+    name: String,
+    priority: Option<i32>,
+    affinity: Option<Vec<usize>>,
+    io_priority: Option<u32>,
+}
+
+/// Applies Game Mode boosts and undoes them
+#[derive(Default)]
+pub struct GameBooster {
+    game: Option<String>,
+    boosted: HashMap<u32, Saved>,
+    /// Background processes lowered by policy rules
+    throttled: HashMap<u32, Saved>,
+    /// Whether we put our own process into background mode
+    background_mode: bool,
+    /// Our own affinity before moving to the efficiency cores
+    own_affinity: Option<Vec<usize>>,
+    /// Plan active before the game, if we switched it
+    power_plan: Option<ActivePlan>,
+    services: Option<ThrottleGuard>,
+}
+
+impl GameBooster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The game currently boosted
+    pub fn game(&self) -> Option<&str> {
+        self.game.as_deref()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.game.is_some()
+    }
+
+    /// Boost `game`, restoring a previously boosted one first
+    pub fn start(&mut self, game: &RunningGame) -> BoostReport {
+        let mut report = BoostReport::default();
+        if self.game() == Some(game.name.as_str()) {
+            self.follow(game);
+            return report;
+        }
+        self.restore();
+        self.game = Some(game.name.clone());
+
+        let (raised, pinned) = self.boost_processes(game);
+        if priority::can_reprioritize() {
+            report.applied.push(format!("Raised priority of {} game process(es)", raised));
+        } else {
+            report.skipped.push("Priority changes need root".into());
+        }
+
+        let topology = CpuTopology::get();
+        if topology.is_hybrid() && topology::AFFINITY_SUPPORTED {
+            report.applied.push(format!("Pinned {} game process(es) to {} performance cores", pinned, topology.performance.len()));
+            if let Some(current) = topology::process_affinity(std::process::id()) {
+                if topology::pin_to_efficiency_cores(std::process::id()).is_ok() {
+                    self.own_affinity = Some(current);
+                    report.applied.push("Moved RuVector to efficiency cores".into());
+                }
+            }
+        }
+
+        trim_background_apps(&mut report);
+        let services = OptimizerConfig::load_or_default().throttle_services;
+        self.services = throttle::hold(&format!("Game Mode: {}", game.name), &services, &mut report);
+        let throttled = self.throttle_background();
+        if throttled > 0 {
+            report.applied.push(format!("Lowered {} background process(es) named by policy rules", throttled));
+        }
+        if priority::set_background_mode(true).is_ok() {
+            self.background_mode = true;
+            report.applied.push("Moved RuVector into background mode".into());
+        }
+
+        match power::switch_to(PowerPlan::HighPerformance) {
+            Ok(Some(previous)) => {
+                report.applied.push(format!("Switched power plan from {} to {}", previous.name, PowerPlan::HighPerformance));
+                self.power_plan = Some(previous);
+            }
+            Ok(None) => report.skipped.push(format!("Power plan already {}", PowerPlan::HighPerformance)),
+            Err(e) => report.skipped.push(format!("Power plan unchanged: {}", e)),
+        }
+        report
+    }
+
+    /// Boost processes the game started since the last call
+    pub fn follow(&mut self, game: &RunningGame) {
+        if self.game() == Some(game.name.as_str()) {
+            self.boost_processes(game);
+        }
+    }
+
+    /// Undo everything still applied
+    pub fn restore(&mut self) {
+        if !self.is_active() {
+            return;
+        }
+        self.game = None;
+
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::All, true);
+        for (pid, saved) in self.boosted.drain().chain(self.throttled.drain()) {
+            let same = system
+                .process(Pid::from_u32(pid))
+                .is_some_and(|p| p.name().to_string_lossy().to_lowercase() == saved.name);
+            if !same {
+                continue;
+            }
+            if let Some(previous) = saved.priority {
+                if let Err(e) = priority::set_priority(pid, previous) {
+                    tracing::debug!("Failed to restore priority of {}: {}", pid, e);
+                }
+            }
+            if let Some(level) = saved.io_priority {
+                if let Err(e) = priority::set_io_priority(pid, level) {
+                    tracing::debug!("Failed to restore I/O priority of {}: {}", pid, e);
+                }
+            }
+            if let Some(ref cpus) = saved.affinity {
+                if let Err(e) = topology::set_process_affinity(pid, cpus) {
+                    tracing::debug!("Failed to restore affinity of {}: {}", pid, e);
+                }
+            }
+        }
+        if let Some(cpus) = self.own_affinity.take() {
+            let _ = topology::set_process_affinity(std::process::id(), &cpus);
+        }
+        if std::mem::take(&mut self.background_mode) {
+            if let Err(e) = priority::set_background_mode(false) {
+                tracing::warn!("Failed to leave background mode: {}", e);
+            }
+        }
+        if let Some(services) = self.services.take() {
+            services.release();
+        }
+        if let Some(previous) = self.power_plan.take() {
+            if let Err(e) = power::restore(&previous) {
+                tracing::warn!("Failed to restore power plan {}: {}", previous.name, e);
+            }
+        }
+    }
+
+    /// Returns `(raised, pinned)` among processes not boosted before
+    fn boost_processes(&mut self, game: &RunningGame) -> (usize, usize) {
+        let hybrid = CpuTopology::get().is_hybrid() && topology::AFFINITY_SUPPORTED;
+        let (mut raised, mut pinned) = (0, 0);
+        for &pid in &game.pids {
+            if self.boosted.contains_key(&pid) {
+                continue;
+            }
+            let mut saved = Saved { name: game.name.clone(), priority: None, affinity: None, io_priority: None };
+
+            if priority::can_reprioritize() {
+                if let Some(current) = priority::priority(pid) {
+                    if current != priority::HIGH && priority::set_priority(pid, priority::HIGH).is_ok() {
+                        saved.priority = Some(current);
+                        raised += 1;
+                    }
+                }
+            }
+
+            if hybrid {
+                if let Some(current) = topology::process_affinity(pid) {
+                    if topology::pin_to_performance_cores(pid).is_ok() {
+                        saved.affinity = Some(current);
+                        pinned += 1;
+                    }
+                }
+            }
+
+            self.boosted.insert(pid, saved);
+        }
+        (raised, pinned)
+    }
+
+    /// Lower normal-priority processes that a `background` policy rule
+    /// names; returns how many were changed
+    fn throttle_background(&mut self) -> usize {
+        let policy = Policy::load_default();
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        for (pid, process) in system.processes() {
+            let pid = pid.as_u32();
+            if self.boosted.contains_key(&pid) || !policy.is_background(&ProcessIdentity::from_process(process, &policy)) {
+                continue;
+            }
+            let name = process.name().to_string_lossy().to_lowercase();
+            let mut saved = Saved { name, priority: None, affinity: None, io_priority: None };
+
+            if priority::can_reprioritize()
+                && priority::priority(pid) == Some(priority::NORMAL)
+                && priority::set_priority(pid, priority::BELOW_NORMAL).is_ok()
+            {
+                saved.priority = Some(priority::NORMAL);
+            }
+            if let Some(current) = priority::io_priority(pid) {
+                if current > priority::IO_VERY_LOW && priority::set_io_priority(pid, priority::IO_VERY_LOW).is_ok() {
+                    saved.io_priority = Some(current);
+                }
+            }
+
+            if saved.priority.is_some() || saved.io_priority.is_some() {
+                self.throttled.insert(pid, saved);
+            }
+        }
+        self.throttled.len()
+    }
+}
+
+impl Drop for GameBooster {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+/// Trim every running Electron app; its pages fault back in when used
+fn trim_background_apps(report: &mut BoostReport) {
+    let start = std::time::Instant::now();
+    let mut manager = ElectronManager::new();
+    manager.refresh();
+    let pids: Vec<u32> = manager.get_apps().iter().flat_map(|app| app.pids.iter().copied()).collect();
+    if pids.is_empty() {
+        return;
+    }
+    match sys::trim(&pids) {
+        Ok((freed_mb, trimmed)) => {
+            history::record(HistoryRecord::now(
+                OptimizationTrigger::Auto,
+                false,
+                freed_mb,
+                trimmed,
+                start.elapsed().as_millis() as u64,
+            ));
+            report.applied.push(format!("Trimmed {} background app process(es), freed {:.0} MB", trimmed, freed_mb));
+        }
+        Err(e) => report.skipped.push(format!("Background apps not trimmed: {}", e)),
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use crate::windows::memory::WindowsMemoryOptimizer;
+
+    /// Returns `(freed_mb, processes_trimmed)`
+    pub fn trim(pids: &[u32]) -> Result<(f64, usize), String> {
+        let mut freed_bytes = 0u64;
+        let mut trimmed = 0;
+        for &pid in pids {
+            if let Ok(bytes) = WindowsMemoryOptimizer::trim_process_working_set(pid) {
+                freed_bytes += bytes;
+                trimmed += 1;
+            }
+        }
+        Ok((freed_bytes as f64 / (1024.0 * 1024.0), trimmed))
+    }
+}
+
+#[cfg(not(windows))]
+mod sys {
+    pub fn trim(_pids: &[u32]) -> Result<(f64, usize), String> {
+        Err("trimming apps is only supported on Windows".into())
+    }
+}
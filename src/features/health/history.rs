@@ -0,0 +1,122 @@
+//! Health score history
+//!
+//! One JSON line per recorded report in `health_history.jsonl`, used for the
+//! week-over-week trend arrows.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Dimension, HealthReport};
+
+/// File name of the history inside the config directory
+pub const HEALTH_HISTORY_FILE: &str = "health_history.jsonl";
+
+/// A recorded report, reduced to its scores
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub total: u8,
+    /// Subscores by [`Dimension::key`]; unavailable ones are left out
+    pub scores: BTreeMap<String, u8>,
+}
+
+impl HealthRecord {
+    pub fn from_report(report: &HealthReport) -> Self {
+        Self {
+            timestamp_ms: report.timestamp_ms,
+            total: report.total,
+            scores: report
+                .subscores
+                .iter()
+                .filter_map(|s| Some((s.dimension.key().to_string(), s.score?)))
+                .collect(),
+        }
+    }
+}
+
+/// Average scores over a set of records
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Baseline {
+    pub total: f64,
+    pub scores: BTreeMap<Dimension, f64>,
+    pub samples: usize,
+}
+
+impl Baseline {
+    pub fn from_records(records: &[HealthRecord]) -> Option<Self> {
+        if records.is_empty() {
+            return None;
+        }
+        let total = records.iter().map(|r| r.total as f64).sum::<f64>() / records.len() as f64;
+        let mut scores = BTreeMap::new();
+        for dimension in Dimension::ALL {
+            let values: Vec<f64> = records.iter().filter_map(|r| r.scores.get(dimension.key())).map(|&s| s as f64).collect();
+            if !values.is_empty() {
+                scores.insert(dimension, values.iter().sum::<f64>() / values.len() as f64);
+            }
+        }
+        Some(Self { total, scores, samples: records.len() })
+    }
+}
+
+/// Append-only JSONL health history
+pub struct HealthHistory {
+    path: PathBuf,
+}
+
+impl HealthHistory {
+    /// Open the history at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_file(HEALTH_HISTORY_FILE)?)
+    }
+
+    /// Open a history at an explicit path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(Self { path })
+    }
+
+    pub fn append(&self, record: &HealthRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open health history {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Records with `start_ms <= timestamp < end_ms`, oldest first
+    ///
+    /// Malformed lines are skipped so a torn write never breaks the history.
+    pub fn between(&self, start_ms: u64, end_ms: u64) -> Result<Vec<HealthRecord>, String> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut records: Vec<HealthRecord> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<HealthRecord>(&line).ok())
+            .filter(|r| r.timestamp_ms >= start_ms && r.timestamp_ms < end_ms)
+            .collect();
+        records.sort_by_key(|r| r.timestamp_ms);
+        Ok(records)
+    }
+
+    /// Timestamp of the newest record
+    pub fn last_timestamp(&self) -> Option<u64> {
+        self.between(0, u64::MAX).ok()?.last().map(|r| r.timestamp_ms)
+    }
+}
@@ -0,0 +1,161 @@
+//! Platform readings the health score needs beyond sysinfo
+//!
+//! - Swap churn: cumulative pages swapped in and out (`/proc/vmstat`,
+//!   `vm_stat`, or the raw `Memory\Pages/sec` counter on Windows)
+//! - Fragmentation: share of free memory in 2 MB or larger blocks, from
+//!   `/proc/buddyinfo` (Linux only)
+//! - Disk pressure: free space on the system volume
+
+use serde::Serialize;
+
+#[cfg(any(windows, target_os = "macos"))]
+use std::process::Command;
+
+/// Buddy allocator order of a 2 MB block with 4 KB pages
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const HUGE_ORDER: usize = 9;
+
+/// Pages swapped in plus out since boot
+pub fn swap_pages() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/vmstat").ok().and_then(|s| parse_vmstat(&s))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        run("vm_stat", &[]).ok().and_then(|s| parse_vm_stat(&s))
+    }
+
+    #[cfg(windows)]
+    {
+        run(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-Command", "(Get-CimInstance Win32_PerfRawData_PerfOS_Memory).PagesPerSec"],
+        )
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Share of free memory in blocks of 2 MB or more, 0..1
+pub fn huge_block_fraction() -> Option<f64> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/buddyinfo").ok().and_then(|s| parse_buddyinfo(&s))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Free and total space of the volume holding the OS
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpace {
+    pub mount: String,
+    pub free_mb: f64,
+    pub total_mb: f64,
+}
+
+impl DiskSpace {
+    pub fn free_percent(&self) -> f64 {
+        if self.total_mb > 0.0 {
+            self.free_mb / self.total_mb * 100.0
+        } else {
+            100.0
+        }
+    }
+}
+
+pub fn system_disk() -> Option<DiskSpace> {
+    let system_mount = if cfg!(windows) {
+        std::env::var("SystemDrive").map(|d| format!("{}\\", d)).unwrap_or_else(|_| "C:\\".into())
+    } else {
+        "/".to_string()
+    };
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .find(|d| d.mount_point().to_string_lossy().eq_ignore_ascii_case(&system_mount))
+        .map(|d| DiskSpace {
+            mount: system_mount.clone(),
+            free_mb: d.available_space() as f64 / 1024.0 / 1024.0,
+            total_mb: d.total_space() as f64 / 1024.0 / 1024.0,
+        })
+}
+
+/// `pswpin + pswpout` from `/proc/vmstat`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_vmstat(content: &str) -> Option<u64> {
+    let mut total = None;
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        if let (Some("pswpin" | "pswpout"), Some(value)) = (parts.next(), parts.next()) {
+            *total.get_or_insert(0) += value.parse::<u64>().ok()?;
+        }
+    }
+    total
+}
+
+/// `Swapins + Swapouts` from `vm_stat`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn parse_vm_stat(output: &str) -> Option<u64> {
+    let mut total = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        if matches!(key.trim(), "Swapins" | "Swapouts") {
+            *total.get_or_insert(0) += value.trim().trim_end_matches('.').parse::<u64>().ok()?;
+        }
+    }
+    total
+}
+
+/// Fraction of free pages in order-9+ blocks from `/proc/buddyinfo`
+///
+/// The DMA zone is tiny and never used for large allocations, so it's left out.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_buddyinfo(content: &str) -> Option<f64> {
+    let mut free = 0u64;
+    let mut huge = 0u64;
+    for line in content.lines() {
+        let Some((_, rest)) = line.split_once("zone") else { continue };
+        let mut fields = rest.split_whitespace();
+        if fields.next() == Some("DMA") {
+            continue;
+        }
+        for (order, count) in fields.filter_map(|f| f.parse::<u64>().ok()).enumerate() {
+            let pages = count << order;
+            free += pages;
+            if order >= HUGE_ORDER {
+                huge += pages;
+            }
+        }
+    }
+    (free > 0).then(|| huge as f64 / free as f64)
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
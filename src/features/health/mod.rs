@@ -0,0 +1,590 @@
+//! System health score (ADR-014)
+//!
+//! Folds seven signals into one 0-100 answer to "how is my machine doing":
+//!
+//! | Dimension     | Weight | 100 means                                  |
+//! |---------------|--------|--------------------------------------------|
+//! | Memory        | 25%    | 40%+ of RAM available                      |
+//! | Swap churn    | 15%    | under 10 pages/s swapped in or out         |
+//! | Leaks         | 10%    | no leak suspects                           |
+//! | Fragmentation | 5%     | half of free RAM in 2 MB+ blocks (Linux)   |
+//! | Startup       | 10%    | under 10 s estimated login cost            |
+//! | Thermal       | 15%    | CPU 30°C or more below its throttle point  |
+//! | Disk          | 20%    | 25%+ free on the system volume             |
+//!
+//! Dimensions without data (no sensors, no buddyinfo, a leak detector that
+//! hasn't collected enough samples yet) are left out and the remaining
+//! weights rescaled. Reports are appended to `health_history.jsonl` at most
+//! every [`RECORD_INTERVAL`] so each score can be compared with its average
+//! over the previous week.
+
+pub mod history;
+pub mod metrics;
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+use crate::apps::LeakDetector;
+use crate::features::startup::{StartupManager, StartupState};
+use crate::features::thermal::{ThermalLevel, ThermalMonitor};
+
+pub use history::{Baseline, HealthHistory, HealthRecord};
+use crate::core::text::truncate;
+
+/// Minimum time between recorded reports
+pub const RECORD_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Score change against last week that counts as a trend
+pub const TREND_THRESHOLD: f64 = 3.0;
+
+/// Leak detector samples needed before leak suspects are scored
+const LEAK_MIN_SAMPLES: usize = 10;
+
+/// Startup items are rescanned at most this often
+const STARTUP_REFRESH: Duration = Duration::from_secs(30 * 60);
+
+const WEEK_MS: u64 = 7 * 24 * 3600 * 1000;
+
+/// One health signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Memory,
+    Swap,
+    Leaks,
+    Fragmentation,
+    Startup,
+    Thermal,
+    Disk,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 7] = [
+        Dimension::Memory,
+        Dimension::Swap,
+        Dimension::Leaks,
+        Dimension::Fragmentation,
+        Dimension::Startup,
+        Dimension::Thermal,
+        Dimension::Disk,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Dimension::Memory => "memory",
+            Dimension::Swap => "swap",
+            Dimension::Leaks => "leaks",
+            Dimension::Fragmentation => "fragmentation",
+            Dimension::Startup => "startup",
+            Dimension::Thermal => "thermal",
+            Dimension::Disk => "disk",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Dimension::Memory => "Memory headroom",
+            Dimension::Swap => "Swap churn",
+            Dimension::Leaks => "Leak suspects",
+            Dimension::Fragmentation => "Fragmentation",
+            Dimension::Startup => "Startup load",
+            Dimension::Thermal => "Thermal margin",
+            Dimension::Disk => "Disk pressure",
+        }
+    }
+
+    pub fn weight(&self) -> f64 {
+        match self {
+            Dimension::Memory => 0.25,
+            Dimension::Swap => 0.15,
+            Dimension::Leaks => 0.10,
+            Dimension::Fragmentation => 0.05,
+            Dimension::Startup => 0.10,
+            Dimension::Thermal => 0.15,
+            Dimension::Disk => 0.20,
+        }
+    }
+}
+
+/// Direction against last week
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    Up,
+    Flat,
+    Down,
+}
+
+impl Trend {
+    /// `None` until last week has data
+    pub fn between(current: f64, last_week: Option<f64>) -> Option<Self> {
+        let delta = current - last_week?;
+        Some(if delta >= TREND_THRESHOLD {
+            Trend::Up
+        } else if delta <= -TREND_THRESHOLD {
+            Trend::Down
+        } else {
+            Trend::Flat
+        })
+    }
+
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Trend::Up => "▲",
+            Trend::Flat => "▶",
+            Trend::Down => "▼",
+        }
+    }
+}
+
+/// Letter grade for a total score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthGrade {
+    #[serde(rename = "A+")]
+    APlus,
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl HealthGrade {
+    pub fn from_score(score: u8) -> Self {
+        match score {
+            90.. => HealthGrade::APlus,
+            80..=89 => HealthGrade::A,
+            70..=79 => HealthGrade::B,
+            60..=69 => HealthGrade::C,
+            40..=59 => HealthGrade::D,
+            _ => HealthGrade::F,
+        }
+    }
+}
+
+impl std::fmt::Display for HealthGrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            HealthGrade::APlus => "A+",
+            HealthGrade::A => "A",
+            HealthGrade::B => "B",
+            HealthGrade::C => "C",
+            HealthGrade::D => "D",
+            HealthGrade::F => "F",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// One dimension's score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubScore {
+    pub dimension: Dimension,
+    /// 0-100, `None` when there's no data
+    pub score: Option<u8>,
+    pub weight: f64,
+    /// What the score is based on
+    pub detail: String,
+    /// Average over last week
+    pub last_week: Option<f64>,
+    pub trend: Option<Trend>,
+}
+
+impl SubScore {
+    pub fn new(dimension: Dimension, score: Option<u8>, detail: impl Into<String>) -> Self {
+        Self { dimension, score, weight: dimension.weight(), detail: detail.into(), last_week: None, trend: None }
+    }
+}
+
+/// A full health report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub total: u8,
+    pub grade: HealthGrade,
+    pub subscores: Vec<SubScore>,
+    pub last_week: Option<f64>,
+    pub trend: Option<Trend>,
+}
+
+impl HealthReport {
+    /// Combine subscores, rescaling weights over those with data
+    pub fn from_subscores(timestamp_ms: u64, subscores: Vec<SubScore>) -> Self {
+        let (sum, weights) = subscores
+            .iter()
+            .filter_map(|s| Some((s.score? as f64 * s.weight, s.weight)))
+            .fold((0.0, 0.0), |(sum, weights), (value, weight)| (sum + value, weights + weight));
+        let total = if weights > 0.0 { (sum / weights).round().clamp(0.0, 100.0) as u8 } else { 100 };
+        Self { timestamp_ms, total, grade: HealthGrade::from_score(total), subscores, last_week: None, trend: None }
+    }
+
+    /// Fill in trend arrows from last week's average
+    pub fn apply_baseline(&mut self, baseline: Option<&Baseline>) {
+        self.last_week = baseline.map(|b| b.total);
+        self.trend = Trend::between(self.total as f64, self.last_week);
+        for sub in &mut self.subscores {
+            sub.last_week = baseline.and_then(|b| b.scores.get(&sub.dimension).copied());
+            sub.trend = sub.score.and_then(|s| Trend::between(s as f64, sub.last_week));
+        }
+    }
+
+    /// The lowest-scoring dimension with data
+    pub fn weakest(&self) -> Option<&SubScore> {
+        self.subscores.iter().filter(|s| s.score.is_some()).min_by_key(|s| s.score)
+    }
+
+    pub fn print(&self) {
+        let trend = match (self.trend, self.last_week) {
+            (Some(t), Some(w)) => format!("  {} vs {:.0} last week", t.arrow(), w),
+            _ => String::new(),
+        };
+        println!("  Score: {}/100  Grade: {}{}\n", self.total, self.grade, trend);
+
+        println!("┌──────────────────┬───────┬────────┬───┬──────────────────────────────────────┐");
+        println!("│ Dimension        │ Score │ Weight │   │ Detail                               │");
+        println!("├──────────────────┼───────┼────────┼───┼──────────────────────────────────────┤");
+        for sub in &self.subscores {
+            let score = sub.score.map(|s| s.to_string()).unwrap_or_else(|| "n/a".into());
+            println!(
+                "│ {:16} │ {:>5} │ {:>5.0}% │ {} │ {:36} │",
+                sub.dimension.label(),
+                score,
+                sub.weight * 100.0,
+                sub.trend.map(|t| t.arrow()).unwrap_or(" "),
+                truncate(&sub.detail, 36)
+            );
+        }
+        println!("└──────────────────┴───────┴────────┴───┴──────────────────────────────────────┘");
+
+        if self.last_week.is_none() {
+            println!("\n  Trend arrows appear once there is a week of history.");
+        }
+        if let Some(weak) = self.weakest().filter(|w| w.score < Some(60)) {
+            println!("\n  💡 {}", advice(weak.dimension));
+        }
+    }
+}
+
+/// What to do about a weak dimension
+pub fn advice(dimension: Dimension) -> &'static str {
+    match dimension {
+        Dimension::Memory => "Low memory headroom: run `optimize`, or close heavy apps",
+        Dimension::Swap => "Heavy swapping: memory is overcommitted; `optimize --aggressive` or close apps",
+        Dimension::Leaks => "Leak suspects found: see `leaks` and restart the growing processes",
+        Dimension::Fragmentation => "Free memory is fragmented: `optimize --aggressive` compacts it",
+        Dimension::Startup => "Slow login: `startup list` shows what to delay or disable",
+        Dimension::Thermal => "Running hot: check cooling, or switch to a cooler power plan",
+        Dimension::Disk => "System disk almost full: `disk` shows caches worth clearing, `bloatware` apps worth removing",
+    }
+}
+
+/// Linear score between a bad and a good value
+fn ramp(value: f64, bad: f64, good: f64) -> u8 {
+    let t = ((value - bad) / (good - bad)).clamp(0.0, 1.0);
+    (t * 100.0).round() as u8
+}
+
+/// 100 at 40% available, 0 at 5%
+pub fn score_memory(available_percent: f64) -> u8 {
+    ramp(available_percent, 5.0, 40.0)
+}
+
+/// 100 under 10 pages/s, 0 at 1000 pages/s, log scale
+pub fn score_swap(pages_per_sec: f64) -> u8 {
+    ramp(-pages_per_sec.max(1.0).log10(), -3.0, -1.0)
+}
+
+/// 15 points off per suspect and severity level
+pub fn score_leaks(severities: &[u8]) -> u8 {
+    let penalty: u32 = severities.iter().map(|&s| s.max(1) as u32 * 15).sum();
+    100u32.saturating_sub(penalty) as u8
+}
+
+/// 100 with half of free memory in 2 MB+ blocks, 0 with none
+pub fn score_fragmentation(huge_fraction: f64) -> u8 {
+    ramp(huge_fraction, 0.0, 0.5)
+}
+
+/// 100 under 10 s, 0 at 60 s
+pub fn score_startup(login_secs: f64) -> u8 {
+    ramp(-login_secs, -60.0, -10.0)
+}
+
+/// 100 with 30°C of margin, 0 at the limit
+pub fn score_thermal(margin_c: f64) -> u8 {
+    ramp(margin_c, 0.0, 30.0)
+}
+
+/// 100 at 25% free, 0 at 5%
+pub fn score_disk(free_percent: f64) -> u8 {
+    ramp(free_percent, 5.0, 25.0)
+}
+
+/// Computes health reports, keeping the state that needs time to build up
+pub struct HealthScorer {
+    system: System,
+    leaks: LeakDetector,
+    leaks_sampled: bool,
+    thermal: ThermalMonitor,
+    last_swap: Option<(Instant, u64)>,
+    startup: Option<(Instant, SubScore)>,
+    history: Option<HealthHistory>,
+    last_recorded_ms: Option<u64>,
+}
+
+impl HealthScorer {
+    pub fn new() -> Self {
+        let history = HealthHistory::open_default()
+            .map_err(|e| tracing::warn!("Health history unavailable: {}", e))
+            .ok();
+        let last_recorded_ms = history.as_ref().and_then(|h| h.last_timestamp());
+        Self {
+            system: System::new(),
+            leaks: LeakDetector::new(),
+            leaks_sampled: false,
+            thermal: ThermalMonitor::new(),
+            last_swap: None,
+            startup: None,
+            history,
+            last_recorded_ms,
+        }
+    }
+
+    /// Score every dimension, compare with last week and record the report
+    pub fn compute(&mut self) -> HealthReport {
+        let subscores = vec![
+            self.memory(),
+            self.swap(),
+            self.leak_suspects(),
+            fragmentation(),
+            self.startup_load(),
+            self.thermal_margin(),
+            disk_pressure(),
+        ];
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let mut report = HealthReport::from_subscores(now_ms, subscores);
+
+        if let Some(history) = &self.history {
+            let last_week = history.between(now_ms.saturating_sub(2 * WEEK_MS), now_ms.saturating_sub(WEEK_MS));
+            report.apply_baseline(last_week.ok().and_then(|r| Baseline::from_records(&r)).as_ref());
+
+            let due = self.last_recorded_ms.map_or(true, |t| now_ms.saturating_sub(t) >= RECORD_INTERVAL.as_millis() as u64);
+            if due {
+                match history.append(&HealthRecord::from_report(&report)) {
+                    Ok(()) => self.last_recorded_ms = Some(now_ms),
+                    Err(e) => tracing::warn!("Failed to record health score: {}", e),
+                }
+            }
+        }
+        report
+    }
+
+    fn memory(&mut self) -> SubScore {
+        self.system.refresh_memory();
+        let total = self.system.total_memory() as f64 / 1024.0 / 1024.0 / 1024.0;
+        if total <= 0.0 {
+            return SubScore::new(Dimension::Memory, None, "memory size unknown");
+        }
+        let available = self.system.available_memory() as f64 / 1024.0 / 1024.0 / 1024.0;
+        let percent = available / total * 100.0;
+        SubScore::new(
+            Dimension::Memory,
+            Some(score_memory(percent)),
+            format!("{:.1} of {:.1} GB free ({:.0}%)", available, total, percent),
+        )
+    }
+
+    fn swap(&mut self) -> SubScore {
+        let Some(pages) = metrics::swap_pages() else {
+            return SubScore::new(Dimension::Swap, None, "no paging counters");
+        };
+        let now = Instant::now();
+        // A one-off report needs two readings to get a rate
+        let (since, before) = match self.last_swap {
+            Some(previous) => previous,
+            None => {
+                std::thread::sleep(Duration::from_secs(1));
+                let after = metrics::swap_pages().unwrap_or(pages);
+                self.last_swap = Some((Instant::now(), after));
+                let rate = after.saturating_sub(pages) as f64 / now.elapsed().as_secs_f64().max(0.001);
+                return swap_subscore(rate);
+            }
+        };
+        self.last_swap = Some((now, pages));
+        let rate = pages.saturating_sub(before) as f64 / now.duration_since(since).as_secs_f64().max(0.001);
+        swap_subscore(rate)
+    }
+
+    fn leak_suspects(&mut self) -> SubScore {
+        if !self.leaks_sampled || self.leaks.should_sample() {
+            self.leaks.sample();
+            self.leaks_sampled = true;
+        }
+        let samples = self.leaks.stats().sample_count;
+        if samples < LEAK_MIN_SAMPLES {
+            return SubScore::new(
+                Dimension::Leaks,
+                None,
+                format!("collecting ({}/{} samples)", samples, LEAK_MIN_SAMPLES),
+            );
+        }
+        let leaks = self.leaks.get_leaks();
+        let severities: Vec<u8> = leaks.iter().map(|l| l.severity.level()).collect();
+        let detail = match leaks.first() {
+            None => "no leak suspects".to_string(),
+            Some(worst) => format!(
+                "{} suspect(s), {} +{:.0} MB/h",
+                leaks.len(),
+                worst.process_name,
+                worst.growth_rate_mb_per_hour
+            ),
+        };
+        SubScore::new(Dimension::Leaks, Some(score_leaks(&severities)), detail)
+    }
+
+    fn startup_load(&mut self) -> SubScore {
+        if let Some((at, cached)) = &self.startup {
+            if at.elapsed() < STARTUP_REFRESH {
+                return cached.clone();
+            }
+        }
+        let manager = StartupManager::new();
+        let active: Vec<_> = manager.items().iter().filter(|i| i.state == StartupState::Enabled).collect();
+        let sub = if manager.items().is_empty() {
+            SubScore::new(Dimension::Startup, None, "no startup items found")
+        } else {
+            let secs: f64 = active.iter().map(|i| i.impact.estimated_secs).sum();
+            SubScore::new(
+                Dimension::Startup,
+                Some(score_startup(secs)),
+                format!("{} item(s), ~{:.0}s at login", active.len(), secs),
+            )
+        };
+        self.startup = Some((Instant::now(), sub.clone()));
+        sub
+    }
+
+    fn thermal_margin(&mut self) -> SubScore {
+        let status = self.thermal.sample();
+        let Some(cpu) = status.reading.cpu_c else {
+            return SubScore::new(Dimension::Thermal, None, "no temperature sensor");
+        };
+        let mut score = score_thermal(status.cpu_limit_c - cpu);
+        if status.level == ThermalLevel::Hot {
+            score = score.min(20);
+        }
+        SubScore::new(
+            Dimension::Thermal,
+            Some(score),
+            format!("CPU {:.0}°C, limit {:.0}°C", cpu, status.cpu_limit_c),
+        )
+    }
+}
+
+impl Default for HealthScorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn swap_subscore(pages_per_sec: f64) -> SubScore {
+    SubScore::new(Dimension::Swap, Some(score_swap(pages_per_sec)), format!("{:.0} pages/s swapped", pages_per_sec))
+}
+
+fn fragmentation() -> SubScore {
+    match metrics::huge_block_fraction() {
+        Some(fraction) => SubScore::new(
+            Dimension::Fragmentation,
+            Some(score_fragmentation(fraction)),
+            format!("{:.0}% of free RAM in 2 MB+ blocks", fraction * 100.0),
+        ),
+        None => SubScore::new(Dimension::Fragmentation, None, "not reported on this platform"),
+    }
+}
+
+fn disk_pressure() -> SubScore {
+    match metrics::system_disk() {
+        Some(disk) => SubScore::new(
+            Dimension::Disk,
+            Some(score_disk(disk.free_percent())),
+            format!("{} {:.0} GB free ({:.0}%)", disk.mount, disk.free_mb / 1024.0, disk.free_percent()),
+        ),
+        None => SubScore::new(Dimension::Disk, None, "system volume not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscores_and_total() {
+        assert_eq!(score_memory(50.0), 100);
+        assert_eq!(score_memory(5.0), 0);
+        assert_eq!(score_swap(5.0), 100);
+        assert_eq!(score_swap(100.0), 50);
+        assert_eq!(score_swap(5000.0), 0);
+        assert_eq!(score_leaks(&[]), 100);
+        assert_eq!(score_leaks(&[3, 1]), 40);
+        assert_eq!(score_startup(35.0), 50);
+        assert_eq!(score_disk(15.0), 50);
+
+        let subscores = vec![
+            SubScore::new(Dimension::Memory, Some(100), ""),
+            SubScore::new(Dimension::Disk, Some(50), ""),
+            SubScore::new(Dimension::Thermal, None, ""),
+        ];
+        let report = HealthReport::from_subscores(0, subscores);
+        // (100 * 0.25 + 50 * 0.20) / 0.45, thermal left out
+        assert_eq!(report.total, 78);
+        assert_eq!(report.grade, HealthGrade::B);
+        assert_eq!(report.weakest().map(|s| s.dimension), Some(Dimension::Disk));
+    }
+
+    #[test]
+    fn test_trend_against_last_week() {
+        let dir = std::env::temp_dir().join(format!("ruvector-health-{}", std::process::id()));
+        let history = HealthHistory::open(dir.join(history::HEALTH_HISTORY_FILE)).unwrap();
+        let now = 20 * WEEK_MS;
+        for (offset, memory) in [(WEEK_MS + 1000, 80u8), (WEEK_MS + 2000, 90), (1000, 10)] {
+            let mut report = HealthReport::from_subscores(
+                now - offset,
+                vec![SubScore::new(Dimension::Memory, Some(memory), ""), SubScore::new(Dimension::Disk, Some(60), "")],
+            );
+            report.total = memory;
+            history.append(&HealthRecord::from_report(&report)).unwrap();
+        }
+
+        let last_week = history.between(now - 2 * WEEK_MS, now - WEEK_MS).unwrap();
+        assert_eq!(last_week.len(), 2);
+        let baseline = Baseline::from_records(&last_week).unwrap();
+        assert_eq!(baseline.total, 85.0);
+
+        let mut report = HealthReport::from_subscores(
+            now,
+            vec![SubScore::new(Dimension::Memory, Some(70), ""), SubScore::new(Dimension::Disk, Some(61), "")],
+        );
+        report.apply_baseline(Some(&baseline));
+        assert_eq!(report.subscores[0].trend, Some(Trend::Down));
+        assert_eq!(report.subscores[1].trend, Some(Trend::Flat));
+        assert_eq!(report.last_week, Some(85.0));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_platform_counters() {
+        assert_eq!(metrics::parse_vmstat("pgfault 10\npswpin 120\npswpout 30\n"), Some(150));
+        assert_eq!(metrics::parse_vmstat("pgfault 10\n"), None);
+        assert_eq!(metrics::parse_vm_stat("Pages free:   1000.\nSwapins:   40.\nSwapouts:   2.\n"), Some(42));
+
+        let buddyinfo = "Node 0, zone      DMA      1      1      1      0      2      1      1      0      1      1      3\n\
+                         Node 0, zone    DMA32      0      0      0      0      0      0      0      0      0      1      0\n\
+                         Node 0, zone   Normal    512      0      0      0      0      0      0      0      0      0      0\n";
+        // 512 order-0 pages against one order-9 block of 512 pages
+        assert_eq!(metrics::parse_buddyinfo(buddyinfo), Some(0.5));
+    }
+}
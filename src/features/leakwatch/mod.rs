@@ -0,0 +1,372 @@
+//! Background leak monitoring
+//!
+//! [`LeakMonitor`] runs a [`LeakDetector`] over a rolling multi-hour window
+//! from the tray and daemons. A process becomes a suspect once it has grown
+//! faster than `leak_alert_mb_per_hour` (with a clean fit) for
+//! `leak_alert_after_minutes`; it is then alerted once, appended to
+//! `leak_suspects.jsonl` and recorded on the timeline.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apps::leaks::MIN_R_SQUARED;
+use crate::apps::{LeakDetector, LeakReport, LeakSeverity};
+use crate::core::config::OptimizerConfig;
+use crate::features::timeline::{self, EventKind};
+use crate::monitor::notify::{self, Notification};
+use crate::core::text::truncate;
+
+/// File name of the suspect journal inside the config directory
+pub const SUSPECTS_FILE: &str = "leak_suspects.jsonl";
+
+/// How often processes are sampled
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Growth is fitted over this much recent history
+pub const WINDOW: Duration = Duration::from_secs(4 * 3600);
+
+/// Default `leak_alert_mb_per_hour`
+pub const DEFAULT_ALERT_MB_PER_HOUR: f64 = 20.0;
+
+/// Default `leak_alert_after_minutes`
+pub const DEFAULT_ALERT_AFTER_MINUTES: u64 = 60;
+
+/// When a growing process is worth an alert
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlertPolicy {
+    pub mb_per_hour: f64,
+    /// How long the growth must be sustained
+    pub after: Duration,
+}
+
+impl Default for AlertPolicy {
+    fn default() -> Self {
+        Self {
+            mb_per_hour: DEFAULT_ALERT_MB_PER_HOUR,
+            after: Duration::from_secs(DEFAULT_ALERT_AFTER_MINUTES * 60),
+        }
+    }
+}
+
+impl AlertPolicy {
+    pub fn from_config(config: &OptimizerConfig) -> Self {
+        Self {
+            mb_per_hour: config.leak_alert_mb_per_hour,
+            after: Duration::from_secs(config.leak_alert_after_minutes * 60),
+        }
+    }
+
+    fn exceeded_by(&self, report: &LeakReport) -> bool {
+        report.growth_rate_mb_per_hour >= self.mb_per_hour && report.r_squared >= MIN_R_SQUARED
+    }
+}
+
+/// A process that kept growing past the policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suspect {
+    pub timestamp_ms: u64,
+    pub pid: u32,
+    pub process_name: String,
+    pub growth_rate_mb_per_hour: f64,
+    pub r_squared: f64,
+    pub current_memory_mb: f64,
+    pub severity: LeakSeverity,
+    /// How long the growth had been above the threshold
+    pub sustained_minutes: u64,
+}
+
+impl Suspect {
+    pub fn title(&self) -> String {
+        format!("Possible memory leak: {}", self.process_name)
+    }
+
+    pub fn message(&self) -> String {
+        format!(
+            "{} (PID {}) has grown {:.0} MB/h for {} min, now {:.0} MB",
+            self.process_name, self.pid, self.growth_rate_mb_per_hour, self.sustained_minutes, self.current_memory_mb
+        )
+    }
+}
+
+/// Samples processes and turns sustained growth into [`Suspect`]s
+pub struct LeakMonitor {
+    detector: LeakDetector,
+    policy: AlertPolicy,
+    /// When each (pid, name) first exceeded the policy in the current streak
+    exceeding_since: HashMap<(u32, String), u64>,
+    /// Processes already alerted, so each one alerts once
+    alerted: HashSet<(u32, String)>,
+}
+
+impl LeakMonitor {
+    pub fn new(policy: AlertPolicy) -> Self {
+        let mut detector = LeakDetector::new();
+        detector.set_sample_interval(SAMPLE_INTERVAL.as_secs());
+        detector.set_window(WINDOW);
+        Self { detector, policy, exceeding_since: HashMap::new(), alerted: HashSet::new() }
+    }
+
+    /// Take a sample and return the processes that just became suspects
+    pub fn sample(&mut self) -> Vec<Suspect> {
+        self.detector.sample();
+        let reports = self.detector.reports();
+        self.evaluate(&reports, now_ms())
+    }
+
+    /// Latest reports from the underlying detector
+    pub fn reports(&self) -> Vec<LeakReport> {
+        self.detector.reports()
+    }
+
+    fn evaluate(&mut self, reports: &[LeakReport], now_ms: u64) -> Vec<Suspect> {
+        let live: HashSet<(u32, String)> = reports.iter().map(key).collect();
+        self.alerted.retain(|k| live.contains(k));
+
+        let mut suspects = Vec::new();
+        for report in reports {
+            let key = key(report);
+            if !self.policy.exceeded_by(report) {
+                self.exceeding_since.remove(&key);
+                continue;
+            }
+            let since = *self.exceeding_since.entry(key.clone()).or_insert(now_ms);
+            let sustained = Duration::from_millis(now_ms.saturating_sub(since));
+            if sustained >= self.policy.after && self.alerted.insert(key) {
+                suspects.push(Suspect {
+                    timestamp_ms: now_ms,
+                    pid: report.pid,
+                    process_name: report.process_name.clone(),
+                    growth_rate_mb_per_hour: report.growth_rate_mb_per_hour,
+                    r_squared: report.r_squared,
+                    current_memory_mb: report.current_memory_mb,
+                    severity: report.severity,
+                    sustained_minutes: sustained.as_secs() / 60,
+                });
+            }
+        }
+        self.exceeding_since.retain(|k, _| live.contains(k));
+        suspects
+    }
+}
+
+fn key(report: &LeakReport) -> (u32, String) {
+    (report.pid, report.process_name.clone())
+}
+
+/// Append-only journal of suspects
+pub struct SuspectLog {
+    path: PathBuf,
+}
+
+impl SuspectLog {
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self::open(crate::features::config_file(SUSPECTS_FILE)?))
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn append(&self, suspect: &Suspect) -> Result<(), String> {
+        let line = serde_json::to_string(suspect).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Suspects recorded at or after `since_ms`, oldest first, skipping malformed lines
+    pub fn since(&self, since_ms: u64) -> Result<Vec<Suspect>, String> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut suspects: Vec<Suspect> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<Suspect>(&line).ok())
+            .filter(|s| s.timestamp_ms >= since_ms)
+            .collect();
+        suspects.sort_by_key(|s| s.timestamp_ms);
+        Ok(suspects)
+    }
+
+    /// Suspects raised within the last [`WINDOW`], one per process
+    pub fn active(&self) -> Result<Vec<Suspect>, String> {
+        let mut seen = HashSet::new();
+        let mut suspects = self.since(now_ms().saturating_sub(WINDOW.as_millis() as u64))?;
+        suspects.reverse();
+        suspects.retain(|s| seen.insert((s.pid, s.process_name.clone())));
+        Ok(suspects)
+    }
+}
+
+/// Print suspects as a table, newest first
+pub fn print_suspects(suspects: &[Suspect]) {
+    if suspects.is_empty() {
+        println!("No leak suspects recorded.");
+        return;
+    }
+    println!("┌──────────────────┬──────────────────────┬────────┬──────────┬──────────┬──────────┐");
+    println!("│ Raised           │ Process              │ PID    │ MB/hour  │ Now (MB) │ Severity │");
+    println!("├──────────────────┼──────────────────────┼────────┼──────────┼──────────┼──────────┤");
+    for s in suspects.iter().rev() {
+        println!(
+            "│ {:16} │ {:20} │ {:>6} │ {:>8.1} │ {:>8.0} │ {:8} │",
+            format_time(s.timestamp_ms),
+            truncate(&s.process_name, 20),
+            s.pid,
+            s.growth_rate_mb_per_hour,
+            s.current_memory_mb,
+            s.severity.to_string()
+        );
+    }
+    println!("└──────────────────┴──────────────────────┴────────┴──────────┴──────────┴──────────┘");
+}
+
+/// Monitor on a background thread, sending each new suspect
+///
+/// Suspects are journaled and put on the timeline before they are sent. The
+/// thread exits when the receiver is dropped.
+pub fn watch(policy: AlertPolicy) -> Receiver<Suspect> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let log = SuspectLog::open_default();
+        let mut monitor = LeakMonitor::new(policy);
+        loop {
+            for suspect in monitor.sample() {
+                tracing::warn!("{}", suspect.message());
+                match &log {
+                    Ok(log) => {
+                        if let Err(e) = log.append(&suspect) {
+                            tracing::warn!("Failed to record leak suspect: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Leak suspect journal unavailable: {}", e),
+                }
+                timeline::record(EventKind::LeakAlert, suspect.message());
+                if tx.send(suspect).is_err() {
+                    return;
+                }
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+    rx
+}
+
+/// Monitor in the background for headless daemons, alerting on the desktop
+///
+/// Under a Windows service there is no desktop; the tray shows the alert.
+pub fn spawn(policy: AlertPolicy) {
+    let alerts = watch(policy);
+    std::thread::spawn(move || {
+        for suspect in alerts {
+            notify::notify(Notification::critical(suspect.title(), suspect.message()));
+        }
+    });
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn format_time(ms: u64) -> String {
+    use chrono::{Local, TimeZone};
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(pid: u32, growth: f64, r_squared: f64) -> LeakReport {
+        LeakReport {
+            process_name: "app".into(),
+            pid,
+            current_memory_mb: 800.0,
+            start_memory_mb: 400.0,
+            peak_memory_mb: 800.0,
+            growth_rate_mb_per_hour: growth,
+            growth_percent: 100.0,
+            r_squared,
+            confidence: r_squared,
+            samples: 60,
+            span_secs: 3600,
+            severity: LeakSeverity::High,
+            restarts: 0,
+            recommendation: String::new(),
+            resource_leaks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_alerts_once_after_sustained_growth() {
+        let policy = AlertPolicy { mb_per_hour: 20.0, after: Duration::from_secs(30 * 60) };
+        let mut monitor = LeakMonitor::new(policy);
+        let minute = 60_000;
+
+        assert!(monitor.evaluate(&[report(7, 50.0, 0.95)], 0).is_empty());
+        assert!(monitor.evaluate(&[report(7, 50.0, 0.95)], 20 * minute).is_empty());
+        let suspects = monitor.evaluate(&[report(7, 50.0, 0.95)], 30 * minute);
+        assert_eq!(suspects.len(), 1);
+        assert_eq!(suspects[0].sustained_minutes, 30);
+        assert!(monitor.evaluate(&[report(7, 50.0, 0.95)], 40 * minute).is_empty());
+    }
+
+    #[test]
+    fn test_streak_resets_below_threshold_or_on_noise() {
+        let policy = AlertPolicy { mb_per_hour: 20.0, after: Duration::from_secs(30 * 60) };
+        let mut monitor = LeakMonitor::new(policy);
+        let minute = 60_000;
+
+        monitor.evaluate(&[report(7, 50.0, 0.95)], 0);
+        monitor.evaluate(&[report(7, 5.0, 0.95)], 10 * minute);
+        assert!(monitor.evaluate(&[report(7, 50.0, 0.95)], 30 * minute).is_empty());
+        assert!(monitor.evaluate(&[report(7, 50.0, 0.3)], 70 * minute).is_empty());
+        assert!(monitor.evaluate(&[report(7, 50.0, 0.95)], 80 * minute).is_empty());
+        assert_eq!(monitor.evaluate(&[report(7, 50.0, 0.95)], 110 * minute).len(), 1);
+    }
+
+    #[test]
+    fn test_suspect_log_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ruvector-leakwatch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = SuspectLog::open(dir.join(SUSPECTS_FILE));
+        let mut suspect = Suspect {
+            timestamp_ms: now_ms(),
+            pid: 7,
+            process_name: "app".into(),
+            growth_rate_mb_per_hour: 50.0,
+            r_squared: 0.95,
+            current_memory_mb: 800.0,
+            severity: LeakSeverity::High,
+            sustained_minutes: 60,
+        };
+        log.append(&suspect).unwrap();
+        suspect.current_memory_mb = 900.0;
+        log.append(&suspect).unwrap();
+
+        assert_eq!(log.since(0).unwrap().len(), 2);
+        let active = log.active().unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].current_memory_mb, 900.0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//! Optional feature modules and shared application paths
+//!
+//! Feature modules build on the core optimizer and keep their state under a
+//! single per-user directory returned by [`config_dir`].
+
+use std::path::PathBuf;
+
+pub mod agent;
+pub mod anomalywatch;
+pub mod bloatware;
+pub mod build;
+pub mod fleet;
+pub mod gamemode;
+pub mod health;
+pub mod leakwatch;
+pub mod patternwatch;
+pub mod plugins;
+pub mod prefetch;
+pub mod profiles;
+pub mod report;
+pub mod snapshot;
+pub mod startup;
+pub mod thermal;
+pub mod throttle;
+pub mod timeline;
+#[cfg(target_os = "windows")]
+pub mod wsl2;
+
+/// Environment variable that overrides the config directory
+pub const CONFIG_DIR_ENV: &str = "RUVECTOR_CONFIG_DIR";
+
+/// Per-user configuration/data directory
+///
+/// - Windows: `%APPDATA%\RuVector`
+/// - macOS: `~/Library/Application Support/RuVector`
+/// - Linux/other: `$XDG_CONFIG_HOME/ruvector` or `~/.config/ruvector`
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(appdata) = std::env::var_os("APPDATA") {
+            return PathBuf::from(appdata).join("RuVector");
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(home) = std::env::var_os("HOME") {
+            return PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+                .join("RuVector");
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME") {
+            return PathBuf::from(xdg).join("ruvector");
+        }
+        if let Some(home) = std::env::var_os("HOME") {
+            return PathBuf::from(home).join(".config").join("ruvector");
+        }
+    }
+
+    // Fallback to executable directory
+    if let Ok(exe) = std::env::current_exe() {
+        if let Some(dir) = exe.parent() {
+            return dir.to_path_buf();
+        }
+    }
+
+    PathBuf::from(".")
+}
+
+/// Path of a file inside the config directory, creating the directory if needed
+pub fn config_file(name: &str) -> Result<PathBuf, String> {
+    let dir = config_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create config directory {:?}: {}", dir, e))?;
+    Ok(dir.join(name))
+}
@@ -0,0 +1,193 @@
+//! Background memory pattern watching
+//!
+//! [`PatternWatcher`] runs a [`SpectralAnalyzer`] over a sliding window of
+//! memory load samples in the daemons and the Windows service, and reports
+//! when the dominant pattern shifts: from stable to increasing (often a
+//! leak), to oscillating (a periodic job) and so on. A new pattern has to
+//! hold for a few samples before it counts, so load hovering at a
+//! classification boundary doesn't flap. Shifts are recorded on the
+//! timeline; [`spawn`] also shows the notable ones as notifications.
+
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use crate::algorithms::spectral::{MemoryPatternClass, SpectralAnalyzer, SpectralRecommendation};
+use crate::features::timeline::{self, EventKind};
+use crate::monitor::notify::{self, Notification};
+
+/// How often memory load is sampled
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Samples in the analyzed window (10 minutes)
+pub const WINDOW_SAMPLES: usize = 120;
+
+/// Samples in a row a new pattern must hold before it counts (a minute)
+const CONFIRM_SAMPLES: usize = 12;
+
+/// The dominant memory pattern changed
+#[derive(Debug, Clone)]
+pub struct PatternShift {
+    pub from: MemoryPatternClass,
+    pub to: MemoryPatternClass,
+    /// What the analyzer suggests for the new pattern
+    pub recommendation: SpectralRecommendation,
+}
+
+impl PatternShift {
+    /// Whether the new pattern is worth a notification
+    pub fn is_notable(&self) -> bool {
+        matches!(
+            self.to,
+            MemoryPatternClass::Increasing
+                | MemoryPatternClass::Oscillating
+                | MemoryPatternClass::ConstantHigh
+                | MemoryPatternClass::Volatile
+        )
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.to {
+            MemoryPatternClass::Increasing => "Memory Keeps Growing",
+            MemoryPatternClass::Oscillating => "Memory Use Cycling",
+            MemoryPatternClass::ConstantHigh => "Memory Stays High",
+            MemoryPatternClass::Volatile => "Memory Use Erratic",
+            _ => "Memory Pattern Changed",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        let hint = match self.to {
+            MemoryPatternClass::Increasing => " - a process may be leaking",
+            MemoryPatternClass::Oscillating => " - likely a periodic job",
+            _ => "",
+        };
+        format!("Memory went from {} to {}: {}{}", self.from, self.to, self.recommendation.reason, hint)
+    }
+}
+
+/// Classifies a sliding window of memory load and reports pattern shifts
+pub struct PatternWatcher {
+    analyzer: SpectralAnalyzer,
+    current: MemoryPatternClass,
+    /// A different pattern seen lately, and for how many samples in a row
+    pending: Option<(MemoryPatternClass, usize)>,
+}
+
+impl PatternWatcher {
+    pub fn new() -> Self {
+        Self::with_window(WINDOW_SAMPLES)
+    }
+
+    pub fn with_window(samples: usize) -> Self {
+        Self { analyzer: SpectralAnalyzer::new(samples), current: MemoryPatternClass::Unknown, pending: None }
+    }
+
+    /// Pattern in effect; `Unknown` until the window has filled enough
+    pub fn current(&self) -> MemoryPatternClass {
+        self.current
+    }
+
+    /// Add a memory load sample (percent); returns the shift once a new
+    /// pattern has held for long enough
+    ///
+    /// The first pattern settled on is a baseline, not a shift.
+    pub fn sample(&mut self, load_percent: u32) -> Option<PatternShift> {
+        self.analyzer.add_sample(load_percent as f64 / 100.0);
+        let class = self.analyzer.classify();
+        if class == self.current || class == MemoryPatternClass::Unknown {
+            self.pending = None;
+            return None;
+        }
+
+        let held = match self.pending {
+            Some((pending, count)) if pending == class => count + 1,
+            _ => 1,
+        };
+        if held < CONFIRM_SAMPLES {
+            self.pending = Some((class, held));
+            return None;
+        }
+        self.pending = None;
+        let from = std::mem::replace(&mut self.current, class);
+        (from != MemoryPatternClass::Unknown).then(|| PatternShift {
+            from,
+            to: class,
+            recommendation: self.analyzer.get_recommendation(),
+        })
+    }
+}
+
+impl Default for PatternWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sample memory load in the background; shifts are logged, recorded on
+/// the timeline and sent on the returned channel
+pub fn watch() -> Receiver<PatternShift> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut system = sysinfo::System::new();
+        let mut watcher = PatternWatcher::new();
+        loop {
+            system.refresh_memory();
+            if let Some(load) = (system.used_memory() * 100).checked_div(system.total_memory()) {
+                if let Some(shift) = watcher.sample(load as u32) {
+                    tracing::info!("{}", shift.message());
+                    timeline::record(EventKind::PatternShift, format!("Memory pattern {} -> {}", shift.from, shift.to));
+                    if tx.send(shift).is_err() {
+                        return;
+                    }
+                }
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+    rx
+}
+
+/// Watch in the background for headless daemons, notifying on notable
+/// shifts; `on_shift` sees every shift, e.g. to tell connected clients
+pub fn spawn(on_shift: impl Fn(&PatternShift) + Send + 'static) {
+    let shifts = watch();
+    std::thread::spawn(move || {
+        for shift in shifts {
+            on_shift(&shift);
+            if shift.is_notable() {
+                notify::notify(Notification::new(shift.title(), shift.message()));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stable_watcher() -> PatternWatcher {
+        let mut watcher = PatternWatcher::with_window(20);
+        let shifts: Vec<PatternShift> = (0..20).filter_map(|_| watcher.sample(50)).collect();
+        assert!(shifts.is_empty(), "the first pattern is a baseline");
+        assert_eq!(watcher.current(), MemoryPatternClass::Stable);
+        watcher
+    }
+
+    #[test]
+    fn test_brief_change_is_ignored() {
+        let mut watcher = stable_watcher();
+        let spike = [80, 80, 80].into_iter().chain([50; 25]);
+        assert!(spike.filter_map(|load| watcher.sample(load)).next().is_none());
+        assert_eq!(watcher.current(), MemoryPatternClass::Stable);
+    }
+
+    #[test]
+    fn test_sustained_growth_is_a_shift() {
+        let mut watcher = stable_watcher();
+        let shift = (50..80).find_map(|load| watcher.sample(load)).expect("a shift");
+        assert_eq!(shift.from, MemoryPatternClass::Stable);
+        assert_eq!(shift.to, MemoryPatternClass::Increasing);
+        assert!(shift.is_notable());
+        assert!(shift.message().ends_with("a process may be leaking"), "{}", shift.message());
+    }
+}
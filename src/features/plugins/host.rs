@@ -0,0 +1,651 @@
+//! Plugin discovery, loading and dispatch
+//!
+//! Every loaded plugin lives on its own thread and is driven over channels,
+//! so the host never blocks longer than the call's time limit. Panics are
+//! caught on the plugin thread. A plugin that panics or overruns is marked
+//! failed and receives no further calls until the next start; a stuck thread
+//! cannot be killed, but it is detached and exits once the hook returns.
+//!
+//! This only contains mistakes. A native plugin shares the host's process,
+//! memory and privileges, and nothing stops it from using them.
+
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::wasm::{self, WasmLimits};
+use super::{
+    plugins_dir, AbiVersionFn, CreateFn, HeuristicInput, OptimizeOutcome, OptimizeRequest, Plugin, PluginCommand,
+    Recommendation, Sample, Verdict, ABI_VERSION_SYMBOL, CREATE_SYMBOL, PLUGIN_ABI_VERSION,
+};
+use crate::monitor::pressure::PressureEvent;
+
+/// Longest a hook may run before the plugin is disabled
+pub const HOOK_TIME_LIMIT: Duration = Duration::from_millis(100);
+
+/// Longest a `plugins run` command may take
+pub const COMMAND_TIME_LIMIT: Duration = Duration::from_secs(30);
+
+/// Longest loading and `on_load` may take
+const LOAD_TIME_LIMIT: Duration = Duration::from_secs(5);
+
+/// File name of the enabled-plugin list inside the config directory
+const SETTINGS_FILE: &str = "plugins.toml";
+
+/// Which plugins are switched on; everything else stays unloaded
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PluginSettings {
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    /// Limits applied to every `.wasm` plugin
+    #[serde(default)]
+    pub wasm: WasmLimits,
+}
+
+impl PluginSettings {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.enabled.iter().any(|e| e == id)
+    }
+}
+
+/// Lifecycle state of a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginState {
+    Disabled,
+    /// Switched on but not loaded in this process
+    Enabled,
+    Running,
+    /// Failed to load, panicked or overran a time limit
+    Failed(String),
+}
+
+impl fmt::Display for PluginState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginState::Disabled => write!(f, "disabled"),
+            PluginState::Enabled => write!(f, "enabled"),
+            PluginState::Running => write!(f, "running"),
+            PluginState::Failed(reason) => write!(f, "failed: {}", reason),
+        }
+    }
+}
+
+/// What `plugins list` shows about a plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    /// File stem, without the `lib` prefix on Unix
+    pub id: String,
+    pub path: Option<PathBuf>,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub commands: Vec<PluginCommand>,
+    pub state: PluginState,
+}
+
+impl PluginInfo {
+    fn new(id: &str, path: Option<PathBuf>) -> Self {
+        Self {
+            id: id.to_string(),
+            path,
+            name: id.to_string(),
+            version: String::new(),
+            description: String::new(),
+            commands: Vec::new(),
+            state: PluginState::Disabled,
+        }
+    }
+
+    /// Name and version, e.g. `load-logger v1.2.0`
+    pub fn title(&self) -> String {
+        match self.version.is_empty() {
+            true => self.name.clone(),
+            false => format!("{} v{}", self.name, self.version),
+        }
+    }
+
+    pub fn print(&self) {
+        let version = if self.version.is_empty() { String::new() } else { format!(" v{}", self.version) };
+        println!("  {}{}  [{}]", self.id, version, self.state);
+        if self.name != self.id {
+            println!("    Name: {}", self.name);
+        }
+        if !self.description.is_empty() {
+            println!("    {}", self.description);
+        }
+        for command in &self.commands {
+            println!("    plugins run {} {:<12} {}", self.id, command.name, command.about);
+        }
+    }
+}
+
+/// A plugin object and the library its code lives in
+///
+/// Field order matters: the plugin is dropped before the library is unloaded.
+struct Loaded {
+    plugin: Box<dyn Plugin>,
+    _library: Option<libloading::Library>,
+}
+
+/// Load a native plugin library and create its plugin
+fn load_native(path: &Path) -> Result<Loaded, String> {
+    // SAFETY: loading runs the library's initialisers; only libraries the user
+    // placed in the plugins directory and enabled get here
+    let library = unsafe { libloading::Library::new(path) }.map_err(|e| e.to_string())?;
+    // SAFETY: the symbols are declared by `declare_plugin!` with these signatures
+    let plugin = unsafe {
+        let abi: libloading::Symbol<AbiVersionFn> = library
+            .get(ABI_VERSION_SYMBOL)
+            .map_err(|_| "Not a RuVector plugin (missing declare_plugin!)".to_string())?;
+        let version = abi();
+        if version != PLUGIN_ABI_VERSION {
+            return Err(format!("Plugin ABI {} does not match host ABI {}", version, PLUGIN_ABI_VERSION));
+        }
+        let create: libloading::Symbol<CreateFn> = library.get(CREATE_SYMBOL).map_err(|e| e.to_string())?;
+        let raw = create();
+        if raw.is_null() {
+            return Err("Plugin constructor returned null".into());
+        }
+        *Box::from_raw(raw)
+    };
+    Ok(Loaded { plugin, _library: Some(library) })
+}
+
+fn is_wasm(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"))
+}
+
+enum Call {
+    Sample(Sample),
+    Pressure(PressureEvent),
+    PreOptimize(OptimizeRequest),
+    PostOptimize(OptimizeOutcome),
+    Recommend(HeuristicInput),
+    Command(String, Vec<String>),
+}
+
+enum Reply {
+    Done,
+    Verdict(Verdict),
+    Recommendations(Vec<Recommendation>),
+    Output(Result<String, String>),
+}
+
+fn dispatch(plugin: &mut dyn Plugin, call: Call) -> Reply {
+    match call {
+        Call::Sample(sample) => {
+            plugin.on_sample(&sample);
+            Reply::Done
+        }
+        Call::Pressure(event) => {
+            plugin.on_pressure(&event);
+            Reply::Done
+        }
+        Call::PreOptimize(request) => Reply::Verdict(plugin.on_pre_optimize(&request)),
+        Call::PostOptimize(outcome) => {
+            plugin.on_post_optimize(&outcome);
+            Reply::Done
+        }
+        Call::Recommend(input) => Reply::Recommendations(plugin.recommend(&input)),
+        Call::Command(name, args) => Reply::Output(plugin.run_command(&name, &args)),
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    format!("panicked: {}", message)
+}
+
+/// Name, version, description and commands reported once a plugin is up
+type Metadata = (String, String, String, Vec<PluginCommand>);
+
+/// Channel pair to a plugin thread
+struct PluginThread {
+    calls: Sender<Call>,
+    replies: Receiver<Result<Reply, String>>,
+}
+
+impl PluginThread {
+    /// Start a plugin thread; `load` runs on that thread
+    fn spawn<F>(id: &str, load: F) -> Result<(Self, Metadata), String>
+    where
+        F: FnOnce() -> Result<Loaded, String> + Send + 'static,
+    {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (calls, call_rx) = mpsc::channel::<Call>();
+        let (reply_tx, replies) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name(format!("plugin-{}", id))
+            .spawn(move || {
+                let started = catch_unwind(AssertUnwindSafe(|| {
+                    let mut loaded = load()?;
+                    loaded.plugin.on_load()?;
+                    let plugin = &loaded.plugin;
+                    let metadata: Metadata = (
+                        plugin.name().to_string(),
+                        plugin.version().to_string(),
+                        plugin.description().to_string(),
+                        plugin.commands(),
+                    );
+                    Ok((loaded, metadata))
+                }))
+                .unwrap_or_else(|payload| Err(panic_message(payload)));
+
+                let mut loaded = match started {
+                    Ok((loaded, metadata)) => {
+                        if ready_tx.send(Ok(metadata)).is_err() {
+                            return;
+                        }
+                        loaded
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+                while let Ok(call) = call_rx.recv() {
+                    let reply = catch_unwind(AssertUnwindSafe(|| dispatch(loaded.plugin.as_mut(), call)))
+                        .map_err(panic_message);
+                    let failed = reply.is_err();
+                    if reply_tx.send(reply).is_err() || failed {
+                        break;
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        let metadata = match ready_rx.recv_timeout(LOAD_TIME_LIMIT) {
+            Ok(result) => result?,
+            Err(RecvTimeoutError::Timeout) => return Err(format!("Did not load within {:?}", LOAD_TIME_LIMIT)),
+            Err(RecvTimeoutError::Disconnected) => return Err("Plugin thread exited while loading".into()),
+        };
+        Ok((Self { calls, replies }, metadata))
+    }
+
+    fn call(&self, call: Call, limit: Duration) -> Result<Reply, String> {
+        self.calls.send(call).map_err(|_| "plugin thread exited".to_string())?;
+        match self.replies.recv_timeout(limit) {
+            Ok(reply) => reply,
+            Err(RecvTimeoutError::Timeout) => Err(format!("no response within {:?}", limit)),
+            Err(RecvTimeoutError::Disconnected) => Err("plugin thread exited".into()),
+        }
+    }
+}
+
+struct Slot {
+    info: PluginInfo,
+    thread: Option<PluginThread>,
+}
+
+/// Discovered plugins and the running ones among them
+pub struct PluginHost {
+    dir: PathBuf,
+    settings_path: PathBuf,
+    settings: PluginSettings,
+    slots: Vec<Slot>,
+}
+
+impl PluginHost {
+    /// Scan [`plugins_dir`] without loading anything
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(plugins_dir(), crate::features::config_file(SETTINGS_FILE)?)
+    }
+
+    pub fn open(dir: PathBuf, settings_path: PathBuf) -> Result<Self, String> {
+        let settings = PluginSettings::load(&settings_path)?;
+        let mut host = Self { dir, settings_path, settings, slots: Vec::new() };
+        for (id, path) in host.discover()? {
+            let mut info = PluginInfo::new(&id, Some(path));
+            if host.settings.is_enabled(&id) {
+                info.state = PluginState::Enabled;
+            }
+            host.slots.push(Slot { info, thread: None });
+        }
+        Ok(host)
+    }
+
+    /// Open the default host and load every enabled plugin
+    ///
+    /// Never fails: problems are logged and leave the host empty or the
+    /// affected plugin failed.
+    pub fn load_default() -> Self {
+        let mut host = match Self::open_default() {
+            Ok(host) => host,
+            Err(e) => {
+                tracing::warn!("Plugins unavailable: {}", e);
+                return Self {
+                    dir: plugins_dir(),
+                    settings_path: PathBuf::new(),
+                    settings: PluginSettings::default(),
+                    slots: Vec::new(),
+                };
+            }
+        };
+        host.load_enabled();
+        host
+    }
+
+    /// Library and `.wasm` files in the plugins directory as `(id, path)`, sorted by id
+    fn discover(&self) -> Result<Vec<(String, PathBuf)>, String> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| format!("Failed to read {:?}: {}", self.dir, e))?;
+        let mut found: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension().is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case(std::env::consts::DLL_EXTENSION) || ext.eq_ignore_ascii_case("wasm")
+                })
+            })
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?;
+                let id = if cfg!(windows) { stem } else { stem.strip_prefix("lib").unwrap_or(stem) };
+                Some((id.to_string(), path.clone()))
+            })
+            .collect();
+        found.sort();
+        Ok(found)
+    }
+
+    /// Load every enabled plugin that is not running yet
+    pub fn load_enabled(&mut self) {
+        for i in 0..self.slots.len() {
+            let slot = &self.slots[i];
+            if slot.thread.is_some() || !self.settings.is_enabled(&slot.info.id) {
+                continue;
+            }
+            let Some(path) = slot.info.path.clone() else { continue };
+            let id = slot.info.id.clone();
+            let result = if is_wasm(&path) {
+                let limits = self.settings.wasm;
+                let plugin_id = id.clone();
+                PluginThread::spawn(&id, move || {
+                    let plugin = wasm::load(&plugin_id, &path, limits)?;
+                    Ok(Loaded { plugin, _library: None })
+                })
+            } else {
+                PluginThread::spawn(&id, move || load_native(&path))
+            };
+            self.start(i, result);
+        }
+    }
+
+    /// Run an in-process plugin on its own thread, like loaded ones
+    pub fn register<F>(&mut self, id: &str, create: F) -> Result<(), String>
+    where
+        F: FnOnce() -> Box<dyn Plugin> + Send + 'static,
+    {
+        if self.slots.iter().any(|s| s.info.id == id) {
+            return Err(format!("Plugin '{}' is already registered", id));
+        }
+        self.slots.push(Slot { info: PluginInfo::new(id, None), thread: None });
+        let result = PluginThread::spawn(id, move || Ok(Loaded { plugin: create(), _library: None }));
+        self.start(self.slots.len() - 1, result);
+        match &self.slots[self.slots.len() - 1].info.state {
+            PluginState::Failed(reason) => Err(reason.clone()),
+            _ => Ok(()),
+        }
+    }
+
+    fn start(&mut self, index: usize, result: Result<(PluginThread, Metadata), String>) {
+        let slot = &mut self.slots[index];
+        match result {
+            Ok((thread, (name, version, description, commands))) => {
+                tracing::info!("Loaded plugin {} v{}", name, version);
+                slot.info.name = name;
+                slot.info.version = version;
+                slot.info.description = description;
+                slot.info.commands = commands;
+                slot.info.state = PluginState::Running;
+                slot.thread = Some(thread);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load plugin {}: {}", slot.info.id, e);
+                slot.info.state = PluginState::Failed(e);
+            }
+        }
+    }
+
+    /// All discovered plugins
+    pub fn list(&self) -> Vec<PluginInfo> {
+        self.slots.iter().map(|s| s.info.clone()).collect()
+    }
+
+    /// Whether any plugin is running
+    pub fn is_active(&self) -> bool {
+        self.slots.iter().any(|s| s.thread.is_some())
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Switch a plugin on after checking that it loads
+    pub fn enable(&mut self, id: &str) -> Result<PluginInfo, String> {
+        let index = self
+            .slots
+            .iter()
+            .position(|s| s.info.id == id)
+            .ok_or_else(|| format!("No plugin '{}' in {:?}", id, self.dir))?;
+        if !self.settings.is_enabled(id) {
+            self.settings.enabled.push(id.to_string());
+        }
+        self.load_enabled();
+        let info = self.slots[index].info.clone();
+        if let PluginState::Failed(reason) = &info.state {
+            self.settings.enabled.retain(|e| e != id);
+            return Err(format!("Plugin '{}' failed to load: {}", id, reason));
+        }
+        self.settings.save(&self.settings_path)?;
+        Ok(info)
+    }
+
+    /// Switch a plugin off; it is unloaded the next time the host starts
+    pub fn disable(&mut self, id: &str) -> Result<(), String> {
+        if !self.settings.is_enabled(id) {
+            return Err(format!("Plugin '{}' is not enabled", id));
+        }
+        self.settings.enabled.retain(|e| e != id);
+        if let Some(slot) = self.slots.iter_mut().find(|s| s.info.id == id) {
+            slot.thread = None;
+            slot.info.state = PluginState::Disabled;
+        }
+        self.settings.save(&self.settings_path)
+    }
+
+    /// Send one call to every running plugin, failing those that misbehave
+    fn broadcast(&mut self, limit: Duration, make: impl Fn() -> Call, mut on_reply: impl FnMut(&str, Reply) -> bool) {
+        for slot in &mut self.slots {
+            let Some(thread) = &slot.thread else { continue };
+            match thread.call(make(), limit) {
+                Ok(reply) => {
+                    if !on_reply(&slot.info.id, reply) {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Plugin {} disabled for this session: {}", slot.info.id, e);
+                    slot.thread = None;
+                    slot.info.state = PluginState::Failed(e);
+                }
+            }
+        }
+    }
+
+    pub fn on_sample(&mut self, sample: &Sample) {
+        self.broadcast(HOOK_TIME_LIMIT, || Call::Sample(sample.clone()), |_, _| true);
+    }
+
+    pub fn on_pressure(&mut self, event: &PressureEvent) {
+        self.broadcast(HOOK_TIME_LIMIT, || Call::Pressure(event.clone()), |_, _| true);
+    }
+
+    /// Ask every plugin; the first one to skip wins
+    pub fn pre_optimize(&mut self, request: &OptimizeRequest) -> Verdict {
+        let mut verdict = Verdict::Proceed;
+        self.broadcast(HOOK_TIME_LIMIT, || Call::PreOptimize(request.clone()), |id, reply| match reply {
+            Reply::Verdict(Verdict::Skip(reason)) => {
+                verdict = Verdict::Skip(format!("{}: {}", id, reason));
+                false
+            }
+            _ => true,
+        });
+        verdict
+    }
+
+    pub fn post_optimize(&mut self, outcome: &OptimizeOutcome) {
+        self.broadcast(HOOK_TIME_LIMIT, || Call::PostOptimize(outcome.clone()), |_, _| true);
+    }
+
+    /// Collect recommendations from every plugin as `(id, recommendation)`
+    pub fn recommend(&mut self, input: &HeuristicInput) -> Vec<(String, Recommendation)> {
+        let mut all = Vec::new();
+        self.broadcast(HOOK_TIME_LIMIT, || Call::Recommend(input.clone()), |id, reply| {
+            if let Reply::Recommendations(recommendations) = reply {
+                all.extend(recommendations.into_iter().map(|r| (id.to_string(), r)));
+            }
+            true
+        });
+        all
+    }
+
+    /// Run a plugin subcommand
+    pub fn run_command(&mut self, id: &str, command: &str, args: &[String]) -> Result<String, String> {
+        let slot = self
+            .slots
+            .iter_mut()
+            .find(|s| s.info.id == id)
+            .ok_or_else(|| format!("No plugin '{}'", id))?;
+        let Some(thread) = &slot.thread else {
+            return Err(format!("Plugin '{}' is {}", id, slot.info.state));
+        };
+        if !slot.info.commands.iter().any(|c| c.name == command) {
+            let known: Vec<&str> = slot.info.commands.iter().map(|c| c.name.as_str()).collect();
+            return Err(match known.len() {
+                0 => format!("Plugin '{}' has no commands", id),
+                _ => format!("Plugin '{}' has no command '{}' (available: {})", id, command, known.join(", ")),
+            });
+        }
+        match thread.call(Call::Command(command.to_string(), args.to_vec()), COMMAND_TIME_LIMIT) {
+            Ok(Reply::Output(output)) => output,
+            Ok(_) => Err("Unexpected reply".into()),
+            Err(e) => {
+                slot.thread = None;
+                slot.info.state = PluginState::Failed(e.clone());
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::OptimizationTrigger;
+
+    struct TestPlugin {
+        sleep: Duration,
+    }
+
+    impl Plugin for TestPlugin {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn version(&self) -> &str {
+            "1.0"
+        }
+
+        fn on_sample(&mut self, sample: &Sample) {
+            if sample.load_percent > 90 {
+                panic!("load too high");
+            }
+            std::thread::sleep(self.sleep);
+        }
+
+        fn on_pre_optimize(&mut self, request: &OptimizeRequest) -> Verdict {
+            match request.aggressive {
+                true => Verdict::Skip("no aggressive runs".into()),
+                false => Verdict::Proceed,
+            }
+        }
+
+        fn commands(&self) -> Vec<PluginCommand> {
+            vec![PluginCommand::new("echo", "Echo the arguments")]
+        }
+
+        fn run_command(&mut self, _name: &str, args: &[String]) -> Result<String, String> {
+            Ok(args.join(" "))
+        }
+    }
+
+    fn host(name: &str) -> PluginHost {
+        let dir = std::env::temp_dir().join(format!("ruvector-plugins-{}-{}", name, std::process::id()));
+        PluginHost::open(dir.join("plugins"), dir.join(SETTINGS_FILE)).unwrap()
+    }
+
+    fn sample(load_percent: u32) -> Sample {
+        Sample { timestamp_ms: 0, load_percent, total_mb: 16384.0, available_mb: 4096.0 }
+    }
+
+    #[test]
+    fn test_hooks_and_commands() {
+        let mut host = host("hooks");
+        host.register("test", || Box::new(TestPlugin { sleep: Duration::ZERO })).unwrap();
+        host.on_sample(&sample(50));
+
+        let mut request = OptimizeRequest {
+            trigger: OptimizationTrigger::Auto,
+            aggressive: false,
+            reason: "test".into(),
+            load_percent: Some(80),
+        };
+        assert_eq!(host.pre_optimize(&request), Verdict::Proceed);
+        request.aggressive = true;
+        assert_eq!(host.pre_optimize(&request), Verdict::Skip("test: no aggressive runs".into()));
+
+        assert_eq!(host.run_command("test", "echo", &["a".into(), "b".into()]).unwrap(), "a b");
+        assert!(host.run_command("test", "missing", &[]).is_err());
+        assert_eq!(host.list()[0].state, PluginState::Running);
+    }
+
+    #[test]
+    fn test_overrun_and_panic_disable_plugin() {
+        let mut host = host("limits");
+        host.register("slow", || Box::new(TestPlugin { sleep: HOOK_TIME_LIMIT * 5 })).unwrap();
+        host.register("crashy", || Box::new(TestPlugin { sleep: Duration::ZERO })).unwrap();
+
+        host.on_sample(&sample(50));
+        host.on_sample(&sample(95));
+
+        let states: Vec<PluginState> = host.list().into_iter().map(|p| p.state).collect();
+        assert!(matches!(&states[0], PluginState::Failed(r) if r.contains("no response")));
+        assert!(matches!(&states[1], PluginState::Failed(r) if r.contains("load too high")));
+        assert!(!host.is_active());
+    }
+}
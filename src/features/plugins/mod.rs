@@ -0,0 +1,351 @@
+//! Plugin SDK (ADR-021)
+//!
+//! A plugin implements [`Plugin`] and exports it with [`declare_plugin!`] from
+//! a `cdylib` crate that depends on `ruvector-memopt`:
+//!
+//! ```ignore
+//! use ruvector_memopt::features::plugins::{Plugin, PluginCommand, Sample};
+//!
+//! #[derive(Default)]
+//! struct LoadLogger;
+//!
+//! impl Plugin for LoadLogger {
+//!     fn name(&self) -> &str { "load-logger" }
+//!     fn version(&self) -> &str { env!("CARGO_PKG_VERSION") }
+//!
+//!     fn on_sample(&mut self, sample: &Sample) {
+//!         eprintln!("load {}%", sample.load_percent);
+//!     }
+//!
+//!     fn commands(&self) -> Vec<PluginCommand> {
+//!         vec![PluginCommand::new("hello", "Say hello")]
+//!     }
+//!
+//!     fn run_command(&mut self, _name: &str, _args: &[String]) -> Result<String, String> {
+//!         Ok("hello".into())
+//!     }
+//! }
+//!
+//! ruvector_memopt::declare_plugin!(LoadLogger::default());
+//! ```
+//!
+//! The resulting library goes into [`plugins_dir`] and is switched on with
+//! `plugins enable <id>`. Rust trait objects have no stable ABI, so plugins
+//! must be built with the same compiler and crate version as the host; the
+//! loader rejects libraries whose [`PLUGIN_ABI_VERSION`] differs. A native
+//! plugin is not sandboxed: it runs inside the optimizer with its privileges,
+//! so only enable libraries you trust.
+//!
+//! `.wasm` modules in the same directory are the sandboxed alternative: they
+//! only see the serialized [`HeuristicInput`] and can only answer with
+//! [`Recommendation`]s, under fuel and memory limits (see [`wasm`]). Running
+//! them needs the `wasm-plugins` feature.
+
+mod host;
+pub mod wasm;
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessesToUpdate, System};
+
+use crate::core::history::{self, HistoryRecord, OptimizationTrigger};
+use crate::monitor::pressure::PressureEvent;
+
+pub use host::{PluginHost, PluginInfo, PluginSettings, PluginState, COMMAND_TIME_LIMIT, HOOK_TIME_LIMIT};
+
+/// Bumped whenever [`Plugin`] or the hook types change
+pub const PLUGIN_ABI_VERSION: u32 = 2;
+
+/// Symbol returning the plugin's [`PLUGIN_ABI_VERSION`]
+pub const ABI_VERSION_SYMBOL: &[u8] = b"ruvector_plugin_abi_version";
+
+/// Symbol returning a boxed [`Plugin`]
+pub const CREATE_SYMBOL: &[u8] = b"ruvector_plugin_create";
+
+/// Signature of [`CREATE_SYMBOL`]
+///
+/// The trait object is boxed twice so the pointer crossing the boundary is thin.
+pub type CreateFn = unsafe extern "C" fn() -> *mut Box<dyn Plugin>;
+
+/// Signature of [`ABI_VERSION_SYMBOL`]
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+
+/// Directory scanned for plugin libraries
+pub fn plugins_dir() -> PathBuf {
+    crate::features::config_dir().join("plugins")
+}
+
+/// Memory sample passed to [`Plugin::on_sample`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    pub timestamp_ms: u64,
+    pub load_percent: u32,
+    pub total_mb: f64,
+    pub available_mb: f64,
+}
+
+impl Sample {
+    /// A sample taken now
+    pub fn new(load_percent: u32, total_mb: f64, available_mb: f64) -> Self {
+        let timestamp_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self { timestamp_ms, load_percent, total_mb, available_mb }
+    }
+}
+
+/// An optimization about to run
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizeRequest {
+    pub trigger: OptimizationTrigger,
+    pub aggressive: bool,
+    pub reason: String,
+    pub load_percent: Option<u32>,
+}
+
+/// Result of a finished optimization
+#[derive(Debug, Clone)]
+pub struct OptimizeOutcome {
+    pub trigger: OptimizationTrigger,
+    pub aggressive: bool,
+    pub freed_mb: f64,
+    pub processes_trimmed: usize,
+    pub duration_ms: u64,
+}
+
+/// Answer to [`Plugin::on_pre_optimize`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Proceed,
+    /// Skip this optimization with the given reason
+    Skip(String),
+}
+
+/// One process in a [`HeuristicInput`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub name: String,
+    pub memory_mb: u64,
+}
+
+/// What a heuristic gets to look at
+#[derive(Debug, Clone, Serialize)]
+pub struct HeuristicInput {
+    pub memory: Sample,
+    /// Largest processes first; empty before an optimization
+    pub processes: Vec<ProcessSample>,
+    /// Set when asked about an optimization that is about to run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<OptimizeRequest>,
+}
+
+/// Processes passed to heuristics, largest first
+const MAX_HEURISTIC_PROCESSES: usize = 200;
+
+impl HeuristicInput {
+    /// Current memory and the largest processes
+    pub fn collect() -> Self {
+        let mut system = System::new();
+        system.refresh_memory();
+        system.refresh_processes(ProcessesToUpdate::All, true);
+
+        let total = system.total_memory();
+        let load_percent = match total {
+            0 => 0,
+            _ => (system.used_memory() * 100 / total) as u32,
+        };
+        let mut processes: Vec<ProcessSample> = system
+            .processes()
+            .iter()
+            .map(|(pid, p)| ProcessSample {
+                pid: pid.as_u32(),
+                name: p.name().to_string_lossy().into_owned(),
+                memory_mb: p.memory() / 1024 / 1024,
+            })
+            .collect();
+        processes.sort_by_key(|p| std::cmp::Reverse(p.memory_mb));
+        processes.truncate(MAX_HEURISTIC_PROCESSES);
+
+        Self {
+            memory: Sample::new(
+                load_percent,
+                total as f64 / 1024.0 / 1024.0,
+                system.available_memory() as f64 / 1024.0 / 1024.0,
+            ),
+            processes,
+            pending: None,
+        }
+    }
+
+    /// Input for an optimization about to run; no process list
+    pub fn pending(request: &OptimizeRequest) -> Self {
+        Self {
+            memory: Sample::new(request.load_percent.unwrap_or(0), 0.0, 0.0),
+            processes: Vec::new(),
+            pending: Some(request.clone()),
+        }
+    }
+}
+
+/// An action a plugin suggests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Recommendation {
+    /// Trim one process's working set
+    Trim {
+        pid: u32,
+        #[serde(default)]
+        reason: String,
+    },
+    /// Run a system-wide optimization
+    Optimize {
+        #[serde(default)]
+        aggressive: bool,
+        #[serde(default)]
+        reason: String,
+    },
+    /// Hold off optimizing
+    Skip {
+        #[serde(default)]
+        reason: String,
+    },
+}
+
+impl Recommendation {
+    pub fn describe(&self) -> String {
+        let (action, reason) = match self {
+            Recommendation::Trim { pid, reason } => (format!("trim pid {}", pid), reason),
+            Recommendation::Optimize { aggressive: false, reason } => ("optimize".to_string(), reason),
+            Recommendation::Optimize { aggressive: true, reason } => ("optimize (aggressive)".to_string(), reason),
+            Recommendation::Skip { reason } => ("skip optimization".to_string(), reason),
+        };
+        match reason.is_empty() {
+            true => action,
+            false => format!("{}: {}", action, reason),
+        }
+    }
+
+    /// Carry out the recommendation and record it in the history
+    pub fn apply(&self) -> Result<String, String> {
+        match self {
+            Recommendation::Trim { pid, .. } => trim_pid(*pid),
+            Recommendation::Optimize { aggressive, .. } => {
+                let result = crate::platform::create_optimizer().optimize(*aggressive)?;
+                history::record(HistoryRecord::now(
+                    OptimizationTrigger::Manual,
+                    *aggressive,
+                    result.freed_mb,
+                    result.processes_affected,
+                    result.duration_ms,
+                ));
+                Ok(format!("freed {:.0} MB", result.freed_mb))
+            }
+            Recommendation::Skip { .. } => Ok("nothing to do".to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn trim_pid(pid: u32) -> Result<String, String> {
+    use crate::windows::memory::WindowsMemoryOptimizer;
+
+    let start = std::time::Instant::now();
+    let freed_mb = WindowsMemoryOptimizer::trim_process_working_set(pid)? as f64 / (1024.0 * 1024.0);
+    history::record(HistoryRecord::now(
+        OptimizationTrigger::Manual,
+        false,
+        freed_mb,
+        1,
+        start.elapsed().as_millis() as u64,
+    ));
+    Ok(format!("freed {:.0} MB", freed_mb))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn trim_pid(_pid: u32) -> Result<String, String> {
+    Err("Trimming single processes is only supported on Windows".into())
+}
+
+/// A subcommand a plugin adds under `plugins run <id>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginCommand {
+    pub name: String,
+    pub about: String,
+}
+
+impl PluginCommand {
+    pub fn new(name: impl Into<String>, about: impl Into<String>) -> Self {
+        Self { name: name.into(), about: about.into() }
+    }
+}
+
+/// Hooks a plugin can implement; all have no-op defaults
+///
+/// Each plugin runs on its own thread. Hooks must return within
+/// [`HOOK_TIME_LIMIT`]; a plugin that overruns or panics is disabled for the
+/// rest of the session.
+pub trait Plugin {
+    fn name(&self) -> &str;
+
+    fn version(&self) -> &str;
+
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Called once after loading
+    fn on_load(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called for every memory sample the daemon takes
+    fn on_sample(&mut self, _sample: &Sample) {}
+
+    /// Called when memory pressure changes level
+    fn on_pressure(&mut self, _event: &PressureEvent) {}
+
+    /// Called before an optimization; any plugin can veto it
+    fn on_pre_optimize(&mut self, _request: &OptimizeRequest) -> Verdict {
+        Verdict::Proceed
+    }
+
+    /// Called after an optimization finished
+    fn on_post_optimize(&mut self, _outcome: &OptimizeOutcome) {}
+
+    /// Suggest actions for the current state, shown by `plugins recommend`
+    fn recommend(&mut self, _input: &HeuristicInput) -> Vec<Recommendation> {
+        Vec::new()
+    }
+
+    /// Subcommands available through `plugins run`
+    fn commands(&self) -> Vec<PluginCommand> {
+        Vec::new()
+    }
+
+    /// Run one of [`Plugin::commands`]; the output is printed by the CLI
+    fn run_command(&mut self, name: &str, _args: &[String]) -> Result<String, String> {
+        Err(format!("Unknown command '{}'", name))
+    }
+}
+
+/// Export a [`Plugin`] from a `cdylib`
+///
+/// The expression is evaluated once per load to build the plugin.
+#[macro_export]
+macro_rules! declare_plugin {
+    ($constructor:expr) => {
+        #[no_mangle]
+        pub extern "C" fn ruvector_plugin_abi_version() -> u32 {
+            $crate::features::plugins::PLUGIN_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn ruvector_plugin_create() -> *mut Box<dyn $crate::features::plugins::Plugin> {
+            let plugin: Box<dyn $crate::features::plugins::Plugin> = Box::new($constructor);
+            Box::into_raw(Box::new(plugin))
+        }
+    };
+}
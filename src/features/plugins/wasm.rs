@@ -0,0 +1,329 @@
+//! WASM heuristics: plugins that can only compute
+//!
+//! A module gets no imports at all. It sees the system as JSON and answers
+//! with JSON, so the worst it can do is return bad advice. It must export:
+//!
+//! - `memory`
+//! - `alloc(len: i32) -> i32`, returning a buffer the host writes the input to
+//! - `recommend(ptr: i32, len: i32) -> i64`, reading a [`HeuristicInput`] and
+//!   returning `(out_ptr << 32) | out_len` of a JSON array of
+//!   [`Recommendation`]s, e.g. `[{"action":"trim","pid":4242,"reason":"idle"}]`
+//!
+//! Each call gets a fresh fuel budget (one unit per instruction) and linear
+//! memory is capped; running out of either traps the call. Running modules
+//! needs a build with the `wasm-plugins` feature.
+
+use std::path::Path;
+#[cfg(feature = "wasm-plugins")]
+use std::ptr::NonNull;
+#[cfg(feature = "wasm-plugins")]
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm-plugins")]
+use wasmer::sys::{BaseTunables, EngineBuilder};
+#[cfg(feature = "wasm-plugins")]
+use wasmer::vm::{MemoryError, MemoryStyle, TableStyle, VMMemory, VMMemoryDefinition, VMTable, VMTableDefinition};
+#[cfg(feature = "wasm-plugins")]
+use wasmer::wasmparser::Operator;
+#[cfg(feature = "wasm-plugins")]
+use wasmer::{
+    imports, CompilerConfig, Instance, Memory, MemoryType, Module, Pages, Store, TableType, Target, Tunables,
+    TypedFunction,
+};
+#[cfg(feature = "wasm-plugins")]
+use wasmer_compiler_singlepass::Singlepass;
+#[cfg(feature = "wasm-plugins")]
+use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints};
+#[cfg(feature = "wasm-plugins")]
+use wasmer_middlewares::Metering;
+
+use super::Plugin;
+#[cfg(feature = "wasm-plugins")]
+use super::{HeuristicInput, OptimizeRequest, Recommendation, Verdict};
+
+/// Largest JSON answer accepted from a module
+#[cfg(feature = "wasm-plugins")]
+const MAX_OUTPUT_BYTES: u32 = 1024 * 1024;
+
+/// Per-module resource limits, set in `plugins.toml` under `[wasm]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WasmLimits {
+    /// Instructions per call
+    pub fuel: u64,
+    /// Linear memory cap in MiB
+    pub memory_mb: u32,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self { fuel: 50_000_000, memory_mb: 32 }
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl WasmLimits {
+    fn pages(&self) -> Pages {
+        // 64 KiB per page
+        Pages(self.memory_mb.max(1) * 16)
+    }
+}
+
+/// Load the module at `path` as a plugin
+#[cfg(feature = "wasm-plugins")]
+pub fn load(id: &str, path: &Path, limits: WasmLimits) -> Result<Box<dyn Plugin>, String> {
+    Ok(Box::new(WasmPlugin::load(id, path, limits)?))
+}
+
+/// Without the `wasm-plugins` feature no module can run
+#[cfg(not(feature = "wasm-plugins"))]
+pub fn load(_id: &str, path: &Path, _limits: WasmLimits) -> Result<Box<dyn Plugin>, String> {
+    Err(format!("{:?} needs a build with WASM plugin support (wasm-plugins feature)", path))
+}
+
+/// Base tunables with every memory capped at a fixed size
+#[cfg(feature = "wasm-plugins")]
+struct LimitingTunables {
+    base: BaseTunables,
+    limit: Pages,
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl LimitingTunables {
+    /// Give memories without a maximum the limit as their maximum
+    fn adjust(&self, ty: &MemoryType) -> MemoryType {
+        let mut adjusted = *ty;
+        if ty.maximum.is_none() {
+            adjusted.maximum = Some(self.limit);
+        }
+        adjusted
+    }
+
+    fn validate(&self, ty: &MemoryType) -> Result<(), MemoryError> {
+        if ty.minimum > self.limit {
+            return Err(MemoryError::Generic(format!(
+                "module needs {} pages, limit is {}",
+                ty.minimum.0, self.limit.0
+            )));
+        }
+        if ty.maximum.is_some_and(|max| max > self.limit) {
+            return Err(MemoryError::Generic(format!("memory may grow past the {} page limit", self.limit.0)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl Tunables for LimitingTunables {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.base.memory_style(&self.adjust(memory))
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(&self, ty: &MemoryType, style: &MemoryStyle) -> Result<VMMemory, MemoryError> {
+        let adjusted = self.adjust(ty);
+        self.validate(&adjusted)?;
+        self.base.create_host_memory(&adjusted, style)
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<VMMemory, MemoryError> {
+        let adjusted = self.adjust(ty);
+        self.validate(&adjusted)?;
+        self.base.create_vm_memory(&adjusted, style, vm_definition_location)
+    }
+
+    fn create_host_table(&self, ty: &TableType, style: &TableStyle) -> Result<VMTable, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<VMTable, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
+
+/// An instantiated heuristic module
+#[cfg(feature = "wasm-plugins")]
+pub struct WasmHeuristic {
+    store: Store,
+    instance: Instance,
+    memory: Memory,
+    alloc: TypedFunction<i32, i32>,
+    recommend: TypedFunction<(i32, i32), i64>,
+    limits: WasmLimits,
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl WasmHeuristic {
+    pub fn load(path: &Path, limits: WasmLimits) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Self::from_bytes(&bytes, limits)
+    }
+
+    pub fn from_bytes(bytes: &[u8], limits: WasmLimits) -> Result<Self, String> {
+        let mut compiler = Singlepass::default();
+        compiler.push_middleware(Arc::new(Metering::new(limits.fuel, |_: &Operator| 1)));
+        let mut engine = EngineBuilder::new(compiler).engine();
+        engine.set_tunables(LimitingTunables {
+            base: BaseTunables::for_target(&Target::default()),
+            limit: limits.pages(),
+        });
+        let mut store = Store::new(engine);
+
+        let module = Module::new(&store, bytes).map_err(|e| format!("Invalid WASM module: {}", e))?;
+        if let Some(import) = module.imports().next() {
+            return Err(format!(
+                "WASM plugins may not import anything (imports {}::{})",
+                import.module(),
+                import.name()
+            ));
+        }
+        let instance = Instance::new(&mut store, &module, &imports! {}).map_err(|e| e.to_string())?;
+        let exports = &instance.exports;
+        let memory = exports.get_memory("memory").map_err(|e| e.to_string())?.clone();
+        let alloc = exports.get_typed_function(&store, "alloc").map_err(|e| e.to_string())?;
+        let recommend = exports.get_typed_function(&store, "recommend").map_err(|e| e.to_string())?;
+        Ok(Self { store, instance, memory, alloc, recommend, limits })
+    }
+
+    /// Run the module on `input` with a fresh fuel budget
+    pub fn evaluate(&mut self, input: &HeuristicInput) -> Result<Vec<Recommendation>, String> {
+        let json = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+        let len = i32::try_from(json.len()).map_err(|_| "Input too large".to_string())?;
+        set_remaining_points(&mut self.store, &self.instance, self.limits.fuel);
+
+        let ptr = self.alloc.call(&mut self.store, len).map_err(|e| self.trap(e))?;
+        self.memory
+            .view(&self.store)
+            .write(ptr as u32 as u64, &json)
+            .map_err(|e| format!("alloc returned a bad buffer: {}", e))?;
+        let packed = self.recommend.call(&mut self.store, ptr, len).map_err(|e| self.trap(e))?;
+
+        let (out_ptr, out_len) = ((packed as u64 >> 32) as u32, packed as u32);
+        if out_len > MAX_OUTPUT_BYTES {
+            return Err(format!("Answer of {} bytes exceeds {} bytes", out_len, MAX_OUTPUT_BYTES));
+        }
+        let mut out = vec![0u8; out_len as usize];
+        self.memory
+            .view(&self.store)
+            .read(out_ptr as u64, &mut out)
+            .map_err(|e| format!("recommend returned a bad buffer: {}", e))?;
+        serde_json::from_slice(&out).map_err(|e| format!("Invalid recommendations: {}", e))
+    }
+
+    fn trap(&mut self, error: wasmer::RuntimeError) -> String {
+        match get_remaining_points(&mut self.store, &self.instance) {
+            MeteringPoints::Exhausted => format!("ran out of fuel after {} instructions", self.limits.fuel),
+            MeteringPoints::Remaining(_) => format!("trapped: {}", error),
+        }
+    }
+}
+
+/// Runs a [`WasmHeuristic`] as a [`Plugin`]
+#[cfg(feature = "wasm-plugins")]
+pub struct WasmPlugin {
+    id: String,
+    heuristic: WasmHeuristic,
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl WasmPlugin {
+    pub fn load(id: &str, path: &Path, limits: WasmLimits) -> Result<Self, String> {
+        Ok(Self { id: id.to_string(), heuristic: WasmHeuristic::load(path, limits)? })
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl Plugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    fn version(&self) -> &str {
+        ""
+    }
+
+    fn description(&self) -> &str {
+        "WASM heuristic"
+    }
+
+    /// Asks the module about the optimization and honours a `skip`
+    fn on_pre_optimize(&mut self, request: &OptimizeRequest) -> Verdict {
+        match self.heuristic.evaluate(&HeuristicInput::pending(request)) {
+            Ok(recommendations) => recommendations
+                .into_iter()
+                .find_map(|r| match r {
+                    Recommendation::Skip { reason } => Some(Verdict::Skip(reason)),
+                    _ => None,
+                })
+                .unwrap_or(Verdict::Proceed),
+            Err(e) => {
+                tracing::warn!("WASM plugin {}: {}", self.id, e);
+                Verdict::Proceed
+            }
+        }
+    }
+
+    fn recommend(&mut self, input: &HeuristicInput) -> Vec<Recommendation> {
+        self.heuristic.evaluate(input).unwrap_or_else(|e| {
+            tracing::warn!("WASM plugin {}: {}", self.id, e);
+            Vec::new()
+        })
+    }
+}
+
+#[cfg(all(test, feature = "wasm-plugins"))]
+mod tests {
+    use super::*;
+    use crate::features::plugins::Sample;
+
+    // Answers every input with one fixed recommendation
+    const FIXED: &str = r#"(module
+        (memory (export "memory") 1)
+        (data (i32.const 0) "[{\"action\":\"skip\",\"reason\":\"busy\"}]")
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "recommend") (param i32 i32) (result i64) (i64.const 35)))"#;
+
+    // Never returns
+    const SPIN: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "recommend") (param i32 i32) (result i64) (loop (br 0)) (i64.const 0)))"#;
+
+    fn input() -> HeuristicInput {
+        HeuristicInput {
+            memory: Sample { timestamp_ms: 0, load_percent: 50, total_mb: 1024.0, available_mb: 512.0 },
+            processes: Vec::new(),
+            pending: None,
+        }
+    }
+
+    #[test]
+    fn test_recommendations_are_parsed() {
+        let mut heuristic = WasmHeuristic::from_bytes(FIXED.as_bytes(), WasmLimits::default()).unwrap();
+        assert_eq!(heuristic.evaluate(&input()).unwrap(), vec![Recommendation::Skip { reason: "busy".into() }]);
+    }
+
+    #[test]
+    fn test_fuel_and_memory_limits() {
+        let limits = WasmLimits { fuel: 10_000, memory_mb: 1 };
+        let mut spin = WasmHeuristic::from_bytes(SPIN.as_bytes(), limits).unwrap();
+        assert!(spin.evaluate(&input()).unwrap_err().contains("fuel"));
+
+        let greedy = FIXED.replace("(memory (export \"memory\") 1)", "(memory (export \"memory\") 64)");
+        assert!(WasmHeuristic::from_bytes(greedy.as_bytes(), limits).is_err());
+    }
+}
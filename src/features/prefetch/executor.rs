@@ -0,0 +1,487 @@
+//! Prefetch executor
+//!
+//! Pre-reads a predicted app's executable, its top imported DLLs (or bundled
+//! frameworks on macOS) and its config files into the OS file cache. Reads run
+//! on a background thread at the lowest I/O priority and stop at the memory
+//! budget. Whether the predicted app actually came to the foreground is
+//! recorded in `prefetch_stats.json` and fed back into prediction confidence.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::Prediction;
+
+/// File name of the hit/miss statistics inside the config directory
+pub const STATS_FILE: &str = "prefetch_stats.json";
+
+/// Default memory budget per prediction
+pub const DEFAULT_BUDGET_MB: u64 = 512;
+
+/// Imported DLLs / frameworks warmed per app
+const MAX_LIBRARIES: usize = 16;
+
+/// Config files warmed per app
+const MAX_CONFIG_FILES: usize = 64;
+
+/// Config files larger than this are skipped
+const MAX_CONFIG_FILE_BYTES: u64 = 1024 * 1024;
+
+/// A prediction counts as a hit if the app comes up within this window
+const HIT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Don't re-warm an app whose files were read this recently
+const REWARM_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// Skip warming when memory use is above this percent
+const MAX_LOAD_PERCENT: u64 = 80;
+
+/// Hit/miss counts for one app
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppPrefetchStats {
+    pub hits: u32,
+    pub misses: u32,
+}
+
+/// Prefetch outcome statistics, persisted across runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrefetchStats {
+    pub warmed: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub bytes_warmed: u64,
+    pub per_app: HashMap<String, AppPrefetchStats>,
+}
+
+impl PrefetchStats {
+    /// Load from the default location
+    pub fn load() -> Self {
+        crate::features::config_file(STATS_FILE)
+            .ok()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = crate::features::config_file(STATS_FILE)?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+
+    /// 0.5-1.5 multiplier on prediction confidence from this app's track record
+    ///
+    /// Laplace-smoothed hit rate relative to a coin flip, so untested apps
+    /// are left alone and apps that keep missing stop being prefetched.
+    pub fn confidence_factor(&self, app: &str) -> f64 {
+        let Some(stats) = self.per_app.get(app) else { return 1.0 };
+        let rate = (stats.hits as f64 + 1.0) / (stats.hits as f64 + stats.misses as f64 + 2.0);
+        (rate * 2.0).clamp(0.5, 1.5)
+    }
+
+    fn record(&mut self, app: &str, hit: bool) {
+        let entry = self.per_app.entry(app.to_string()).or_default();
+        if hit {
+            self.hits += 1;
+            entry.hits += 1;
+        } else {
+            self.misses += 1;
+            entry.misses += 1;
+        }
+    }
+}
+
+/// Files read for one prediction
+#[derive(Debug, Clone, Default)]
+pub struct WarmResult {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Warms predicted apps and tracks whether the predictions paid off
+pub struct PrefetchExecutor {
+    budget_bytes: u64,
+    stats: Arc<Mutex<PrefetchStats>>,
+    /// Apps waiting to be confirmed, with when they were warmed
+    pending: HashMap<String, Instant>,
+    last_warmed: HashMap<String, Instant>,
+}
+
+impl PrefetchExecutor {
+    pub fn new(budget_mb: u64) -> Self {
+        Self {
+            budget_bytes: budget_mb * 1024 * 1024,
+            stats: Arc::new(Mutex::new(PrefetchStats::load())),
+            pending: HashMap::new(),
+            last_warmed: HashMap::new(),
+        }
+    }
+
+    pub fn stats(&self) -> PrefetchStats {
+        self.stats.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Multiplier for a prediction's confidence
+    pub fn confidence_factor(&self, app: &str) -> f64 {
+        self.stats.lock().map(|s| s.confidence_factor(app)).unwrap_or(1.0)
+    }
+
+    /// Warm a predicted app in the background; returns false if skipped
+    pub fn warm(&mut self, prediction: &Prediction) -> bool {
+        let Some(ref path) = prediction.path else { return false };
+        if self.last_warmed.get(&prediction.app).is_some_and(|t| t.elapsed() < REWARM_AFTER) {
+            return false;
+        }
+        if memory_load_percent() > MAX_LOAD_PERCENT {
+            tracing::debug!("Skipping prefetch of {}: memory pressure", prediction.app);
+            return false;
+        }
+
+        self.pending.insert(prediction.app.clone(), Instant::now());
+        self.last_warmed.insert(prediction.app.clone(), Instant::now());
+
+        let app = prediction.app.clone();
+        let path = path.clone();
+        let budget = self.budget_bytes;
+        let stats = self.stats.clone();
+        std::thread::spawn(move || {
+            lower_io_priority();
+            let result = warm_app(&path, budget);
+            tracing::info!("Prefetched {}: {} files, {:.1} MB", app, result.files, result.bytes as f64 / 1024.0 / 1024.0);
+            if let Ok(mut stats) = stats.lock() {
+                stats.warmed += 1;
+                stats.bytes_warmed += result.bytes;
+                let _ = stats.save();
+            }
+        });
+        true
+    }
+
+    /// Settle pending predictions when an app comes to the foreground
+    pub fn on_foreground(&mut self, app: &str) {
+        let mut settled: Vec<(String, bool)> = Vec::new();
+        if self.pending.remove(app).is_some() {
+            settled.push((app.to_string(), true));
+        }
+        self.pending.retain(|name, warmed| {
+            let expired = warmed.elapsed() >= HIT_WINDOW;
+            if expired {
+                settled.push((name.clone(), false));
+            }
+            !expired
+        });
+
+        if settled.is_empty() {
+            return;
+        }
+        if let Ok(mut stats) = self.stats.lock() {
+            for (name, hit) in &settled {
+                stats.record(name, *hit);
+            }
+            let _ = stats.save();
+        }
+    }
+}
+
+/// Read an app's files into the file cache, stopping at `budget` bytes
+pub fn warm_app(path: &Path, budget: u64) -> WarmResult {
+    let mut result = WarmResult::default();
+    for file in plan_files(path) {
+        if result.bytes >= budget {
+            break;
+        }
+        let read = read_into_cache(&file, budget - result.bytes);
+        if read > 0 {
+            result.files += 1;
+            result.bytes += read;
+        }
+    }
+    result
+}
+
+/// Files to warm for an app, most important first
+pub fn plan_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    // macOS: the prediction path is the .app bundle
+    if path.extension().is_some_and(|e| e == "app") && path.is_dir() {
+        files.extend(list_files(&path.join("Contents").join("MacOS")));
+        files.extend(bundle_frameworks(path).into_iter().take(MAX_LIBRARIES));
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+        files.extend(imported_libraries(path).into_iter().take(MAX_LIBRARIES));
+    }
+
+    let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    files.extend(config_files(&name).into_iter().take(MAX_CONFIG_FILES));
+    files
+}
+
+/// Read `path` sequentially and discard the data, up to `limit` bytes
+fn read_into_cache(path: &Path, limit: u64) -> u64 {
+    let Ok(file) = std::fs::File::open(path) else { return 0 };
+    let mut reader = file.take(limit);
+    let mut buf = vec![0u8; 1024 * 1024];
+    let mut total = 0u64;
+    while let Ok(n) = reader.read(&mut buf) {
+        if n == 0 {
+            break;
+        }
+        total += n as u64;
+    }
+    total
+}
+
+/// DLLs a PE executable imports, app-local ones first
+fn imported_libraries(exe: &Path) -> Vec<PathBuf> {
+    let Ok(data) = std::fs::read(exe) else { return Vec::new() };
+    let app_dir = exe.parent().map(Path::to_path_buf).unwrap_or_default();
+    let system_dir = std::env::var_os("SystemRoot").map(|r| PathBuf::from(r).join("System32"));
+
+    let mut local = Vec::new();
+    let mut system = Vec::new();
+    for name in parse_pe_imports(&data) {
+        let lower = name.to_lowercase();
+        // API sets are virtual and resolve to already-loaded system DLLs
+        if lower.starts_with("api-ms-win-") || lower.starts_with("ext-ms-") {
+            continue;
+        }
+        let candidate = app_dir.join(&name);
+        if candidate.is_file() {
+            local.push(candidate);
+        } else if let Some(ref dir) = system_dir {
+            let candidate = dir.join(&name);
+            if candidate.is_file() {
+                system.push(candidate);
+            }
+        }
+    }
+    local.extend(system);
+    local
+}
+
+/// Names in a PE file's import directory
+pub fn parse_pe_imports(data: &[u8]) -> Vec<String> {
+    let u16_at = |off: usize| data.get(off..off + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let u32_at = |off: usize| data.get(off..off + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+
+    let mut names = Vec::new();
+    if !data.starts_with(b"MZ") {
+        return names;
+    }
+    let Some(pe) = u32_at(0x3C) else { return names };
+    if data.get(pe..pe + 4) != Some(&b"PE\0\0"[..]) {
+        return names;
+    }
+
+    let coff = pe + 4;
+    let (Some(sections), Some(opt_size)) = (u16_at(coff + 2), u16_at(coff + 16)) else {
+        return names;
+    };
+    let opt = coff + 20;
+    let dirs = match u16_at(opt) {
+        Some(0x10b) => opt + 96,  // PE32
+        Some(0x20b) => opt + 112, // PE32+
+        _ => return names,
+    };
+    let Some(import_rva) = u32_at(dirs + 8) else { return names };
+    if import_rva == 0 {
+        return names;
+    }
+
+    let section_table = opt + opt_size;
+    let rva_to_offset = |rva: usize| {
+        (0..sections).find_map(|i| {
+            let s = section_table + i * 40;
+            let (vsize, va, raw_size, raw_ptr) = (u32_at(s + 8)?, u32_at(s + 12)?, u32_at(s + 16)?, u32_at(s + 20)?);
+            (rva >= va && rva < va + vsize.max(raw_size)).then(|| rva - va + raw_ptr)
+        })
+    };
+
+    let Some(mut desc) = rva_to_offset(import_rva) else { return names };
+    // IMAGE_IMPORT_DESCRIPTOR is 20 bytes; a zeroed one ends the list
+    while let Some(name_rva) = u32_at(desc + 12) {
+        if name_rva == 0 || names.len() >= 512 {
+            break;
+        }
+        if let Some(name) = rva_to_offset(name_rva).and_then(|off| {
+            let bytes = data.get(off..)?;
+            let end = bytes.iter().position(|&b| b == 0)?;
+            std::str::from_utf8(&bytes[..end]).ok().map(str::to_string)
+        }) {
+            names.push(name);
+        }
+        desc += 20;
+    }
+    names
+}
+
+/// Framework binaries and dylibs in an app bundle, largest first
+fn bundle_frameworks(bundle: &Path) -> Vec<PathBuf> {
+    let frameworks = bundle.join("Contents").join("Frameworks");
+    let Ok(entries) = std::fs::read_dir(&frameworks) else { return Vec::new() };
+
+    let mut libs: Vec<(u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let binary = match path.extension().and_then(|e| e.to_str()) {
+                Some("framework") => path.join(path.file_stem()?),
+                Some("dylib") => path,
+                _ => return None,
+            };
+            let size = std::fs::metadata(&binary).ok()?.len();
+            Some((size, binary))
+        })
+        .collect();
+    libs.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+    libs.into_iter().map(|(_, p)| p).collect()
+}
+
+/// Small files in the app's per-user config directories
+fn config_files(app_name: &str) -> Vec<PathBuf> {
+    if app_name.is_empty() {
+        return Vec::new();
+    }
+
+    let mut dirs = Vec::new();
+    #[cfg(windows)]
+    for var in ["APPDATA", "LOCALAPPDATA"] {
+        if let Some(base) = std::env::var_os(var) {
+            dirs.push(PathBuf::from(base).join(app_name));
+        }
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join("Library").join("Application Support").join(app_name));
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".config").join(app_name.to_lowercase()));
+    }
+
+    let mut files = Vec::new();
+    for dir in dirs {
+        // Top level plus one level down covers most settings layouts
+        for path in list_files(&dir).into_iter().chain(
+            list_dirs(&dir).iter().flat_map(|d| list_files(d)),
+        ) {
+            if std::fs::metadata(&path).is_ok_and(|m| m.len() <= MAX_CONFIG_FILE_BYTES) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect())
+        .unwrap_or_default()
+}
+
+fn list_dirs(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect())
+        .unwrap_or_default()
+}
+
+fn memory_load_percent() -> u64 {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let total = system.total_memory();
+    if total == 0 {
+        return 0;
+    }
+    (total - system.available_memory()) * 100 / total
+}
+
+/// Drop the calling thread to background / idle I/O priority
+fn lower_io_priority() {
+    #[cfg(windows)]
+    unsafe {
+        use windows::Win32::System::Threading::{GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN};
+        let _ = SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN);
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe {
+        extern "C" {
+            fn setiopolicy_np(iotype: libc::c_int, scope: libc::c_int, policy: libc::c_int) -> libc::c_int;
+        }
+        const IOPOL_TYPE_DISK: libc::c_int = 0;
+        const IOPOL_SCOPE_THREAD: libc::c_int = 1;
+        const IOPOL_THROTTLE: libc::c_int = 3;
+        setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_THREAD, IOPOL_THROTTLE);
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe {
+        const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+        const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+        // who = 0 targets the calling thread
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, IOPRIO_CLASS_IDLE << 13);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal PE32+ image with one section holding two imports
+    fn fake_pe() -> Vec<u8> {
+        let mut data = vec![0u8; 0x400];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        data[0x80..0x84].copy_from_slice(b"PE\0\0");
+        let coff = 0x84;
+        data[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes());
+        data[coff + 16..coff + 18].copy_from_slice(&240u16.to_le_bytes());
+        let opt = coff + 20;
+        data[opt..opt + 2].copy_from_slice(&0x20bu16.to_le_bytes());
+        // Import directory RVA
+        data[opt + 120..opt + 124].copy_from_slice(&0x1000u32.to_le_bytes());
+
+        // .idata: VA 0x1000, raw at 0x200
+        let section = opt + 240;
+        data[section + 8..section + 12].copy_from_slice(&0x200u32.to_le_bytes());
+        data[section + 12..section + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        data[section + 16..section + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        data[section + 20..section + 24].copy_from_slice(&0x200u32.to_le_bytes());
+
+        // Two descriptors pointing at names at RVA 0x1100 and 0x1110
+        data[0x200 + 12..0x200 + 16].copy_from_slice(&0x1100u32.to_le_bytes());
+        data[0x214 + 12..0x214 + 16].copy_from_slice(&0x1110u32.to_le_bytes());
+        data[0x300..0x30B].copy_from_slice(b"ffmpeg.dll\0");
+        data[0x310..0x31D].copy_from_slice(b"KERNEL32.dll\0");
+        data
+    }
+
+    #[test]
+    fn test_parse_pe_imports() {
+        assert_eq!(parse_pe_imports(&fake_pe()), vec!["ffmpeg.dll", "KERNEL32.dll"]);
+        assert!(parse_pe_imports(b"not a pe file").is_empty());
+    }
+
+    #[test]
+    fn test_confidence_factor() {
+        let mut stats = PrefetchStats::default();
+        assert_eq!(stats.confidence_factor("code"), 1.0);
+        for _ in 0..8 {
+            stats.record("code", false);
+        }
+        stats.record("slack", true);
+        assert_eq!(stats.confidence_factor("code"), 0.5);
+        assert!(stats.confidence_factor("slack") > 1.0);
+        assert!((stats.hit_rate() - 1.0 / 9.0).abs() < 1e-9);
+    }
+}
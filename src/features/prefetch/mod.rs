@@ -0,0 +1,767 @@
+//! Predictive prefetcher (ADR-019)
+//!
+//! Watches which application comes to the foreground, keeps the last
+//! [`MAX_HISTORY`] switches in `prefetch.jsonl`, and learns a Markov
+//! transition model from them: first order on the current app, second order
+//! on the previous two, weighted by how often each candidate is used at this
+//! time of day. Predictions below the confidence threshold are dropped.
+//!
+//! Foreground tracking uses `SetWinEventHook(EVENT_SYSTEM_FOREGROUND)` on
+//! Windows and `NSWorkspaceDidActivateApplicationNotification` on macOS.
+//! Confident predictions are handed to the [`PrefetchExecutor`], which warms
+//! the app's files into the OS cache.
+
+mod executor;
+
+pub use executor::{PrefetchExecutor, PrefetchStats, WarmResult, DEFAULT_BUDGET_MB};
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use crate::core::text::truncate;
+
+/// File name of the launch log inside the config directory
+pub const PREFETCH_FILE: &str = "prefetch.jsonl";
+
+/// Switches kept for training (~1 month of normal use)
+pub const MAX_HISTORY: usize = 10_000;
+
+/// Only predict above this confidence
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.7;
+
+/// Apps remembered as context for the second-order model
+const RECENT_APPS: usize = 3;
+
+/// Observations of a context needed before it is trusted
+const MIN_SUPPORT: u32 = 3;
+
+/// Retrain after this many new switches
+const RETRAIN_EVERY: usize = 20;
+
+/// Predictions warmed after each switch
+const MAX_WARM_PER_SWITCH: usize = 3;
+
+/// Shell and system surfaces that take focus but are not apps
+const IGNORED_APPS: &[&str] = &[
+    "explorer.exe", "searchhost.exe", "searchapp.exe", "shellexperiencehost.exe",
+    "startmenuexperiencehost.exe", "lockapp.exe", "applicationframehost.exe",
+    "ruvector-memopt.exe", "ruvector-memopt-tray.exe", "ruvectortray.exe",
+    "loginwindow", "dock", "systemuiserver", "controlcenter", "ruvector-memopt-macos",
+];
+
+/// One foreground switch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppLaunch {
+    pub app: String,
+    pub path: Option<PathBuf>,
+    pub timestamp_ms: u64,
+    pub hour: u8,
+    /// 0 = Monday
+    pub day_of_week: u8,
+    /// Apps in the foreground before this one, most recent first
+    pub previous: Vec<String>,
+}
+
+/// App that just came to the foreground
+#[derive(Debug, Clone)]
+pub struct ForegroundApp {
+    pub name: String,
+    pub path: Option<PathBuf>,
+}
+
+/// Context to predict from
+#[derive(Debug, Clone)]
+pub struct AppContext {
+    pub current_app: String,
+    pub hour: u8,
+    /// Apps before the current one, most recent first
+    pub recent_apps: Vec<String>,
+}
+
+/// Predicted next app
+#[derive(Debug, Clone)]
+pub struct Prediction {
+    pub app: String,
+    pub path: Option<PathBuf>,
+    pub confidence: f64,
+}
+
+/// Ring buffer of foreground switches, persisted as JSON lines
+pub struct AppObserver {
+    path: Option<PathBuf>,
+    history: VecDeque<AppLaunch>,
+    max_history: usize,
+}
+
+impl AppObserver {
+    /// In-memory observer (nothing is persisted)
+    pub fn new() -> Self {
+        Self { path: None, history: VecDeque::new(), max_history: MAX_HISTORY }
+    }
+
+    /// Load the log at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_file(PREFETCH_FILE)?)
+    }
+
+    /// Load the log at `path`, keeping the newest `MAX_HISTORY` entries
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut observer = Self { path: Some(path.clone()), ..Self::new() };
+
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(observer),
+            Err(e) => return Err(format!("Failed to open {:?}: {}", path, e)),
+        };
+
+        let mut lines = 0;
+        for launch in BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<AppLaunch>(&line).ok())
+        {
+            lines += 1;
+            observer.push(launch);
+        }
+
+        // Keep the append-only log from growing without bound
+        if lines > observer.max_history * 2 {
+            observer.compact()?;
+        }
+        Ok(observer)
+    }
+
+    pub fn history(&self) -> &VecDeque<AppLaunch> {
+        &self.history
+    }
+
+    pub fn current_app(&self) -> Option<&str> {
+        self.history.back().map(|l| l.app.as_str())
+    }
+
+    /// Context for predicting what follows the current app
+    pub fn context(&self) -> Option<AppContext> {
+        let current = self.history.back()?;
+        Some(AppContext {
+            current_app: current.app.clone(),
+            hour: chrono::Local::now().hour() as u8,
+            recent_apps: current.previous.clone(),
+        })
+    }
+
+    /// Record a foreground switch; returns false for repeats and shell windows
+    pub fn on_foreground_change(&mut self, app: &ForegroundApp) -> bool {
+        let name = app.name.to_lowercase();
+        if name.is_empty() || IGNORED_APPS.contains(&name.as_str()) {
+            return false;
+        }
+        if self.current_app() == Some(name.as_str()) {
+            return false;
+        }
+
+        let now = chrono::Local::now();
+        let mut previous: Vec<String> = Vec::with_capacity(RECENT_APPS);
+        if let Some(last) = self.history.back() {
+            previous.push(last.app.clone());
+            previous.extend(last.previous.iter().take(RECENT_APPS - 1).cloned());
+        }
+
+        let launch = AppLaunch {
+            app: name,
+            path: app.path.clone(),
+            timestamp_ms: now.timestamp_millis() as u64,
+            hour: now.hour() as u8,
+            day_of_week: now.weekday().num_days_from_monday() as u8,
+            previous,
+        };
+
+        if let Some(ref path) = self.path {
+            if let Err(e) = append_line(path, &launch) {
+                tracing::warn!("Failed to record app switch: {}", e);
+            }
+        }
+        self.push(launch);
+        true
+    }
+
+    fn push(&mut self, launch: AppLaunch) {
+        self.history.push_back(launch);
+        while self.history.len() > self.max_history {
+            self.history.pop_front();
+        }
+    }
+
+    /// Rewrite the log with only the retained entries
+    fn compact(&self) -> Result<(), String> {
+        let Some(ref path) = self.path else { return Ok(()) };
+        let tmp = path.with_extension("jsonl.tmp");
+        {
+            let mut file = File::create(&tmp).map_err(|e| e.to_string())?;
+            for launch in &self.history {
+                let line = serde_json::to_string(launch).map_err(|e| e.to_string())?;
+                writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+            }
+        }
+        std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+    }
+
+    /// Build a transition model from the recorded switches
+    pub fn train(&self) -> AppSequenceModel {
+        let mut model = AppSequenceModel::new();
+        for launch in &self.history {
+            model.observe(launch);
+        }
+        model
+    }
+}
+
+impl Default for AppObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn append_line(path: &Path, launch: &AppLaunch) -> Result<(), String> {
+    let line = serde_json::to_string(launch).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+/// Markov transition model over foreground apps
+pub struct AppSequenceModel {
+    /// current -> next -> count
+    first_order: HashMap<String, HashMap<String, u32>>,
+    /// (previous, current) -> next -> count
+    second_order: HashMap<(String, String), HashMap<String, u32>>,
+    /// app -> launches per hour of day
+    hourly: HashMap<String, [u32; 24]>,
+    /// Last seen executable per app
+    paths: HashMap<String, PathBuf>,
+    pub min_confidence: f64,
+    samples: usize,
+}
+
+impl AppSequenceModel {
+    pub fn new() -> Self {
+        Self {
+            first_order: HashMap::new(),
+            second_order: HashMap::new(),
+            hourly: HashMap::new(),
+            paths: HashMap::new(),
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            samples: 0,
+        }
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Number of switches the model was trained on
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Add one switch to the model
+    pub fn observe(&mut self, launch: &AppLaunch) {
+        self.samples += 1;
+        self.hourly.entry(launch.app.clone()).or_insert([0; 24])[launch.hour as usize % 24] += 1;
+        if let Some(ref path) = launch.path {
+            self.paths.insert(launch.app.clone(), path.clone());
+        }
+
+        let Some(current) = launch.previous.first() else { return };
+        *self
+            .first_order
+            .entry(current.clone())
+            .or_default()
+            .entry(launch.app.clone())
+            .or_default() += 1;
+
+        if let Some(before) = launch.previous.get(1) {
+            *self
+                .second_order
+                .entry((before.clone(), current.clone()))
+                .or_default()
+                .entry(launch.app.clone())
+                .or_default() += 1;
+        }
+    }
+
+    /// Apps likely to follow `context`, most confident first
+    pub fn predict(&self, context: &AppContext) -> Vec<Prediction> {
+        let Some(next) = self.first_order.get(&context.current_app) else {
+            return Vec::new();
+        };
+        let total: u32 = next.values().sum();
+        if total < MIN_SUPPORT {
+            return Vec::new();
+        }
+
+        let second = context
+            .recent_apps
+            .first()
+            .and_then(|before| self.second_order.get(&(before.clone(), context.current_app.clone())))
+            .filter(|counts| counts.values().sum::<u32>() >= MIN_SUPPORT);
+
+        let mut predictions: Vec<Prediction> = next
+            .iter()
+            .filter(|(app, _)| **app != context.current_app)
+            .map(|(app, &count)| {
+                let p1 = count as f64 / total as f64;
+                let p = match second {
+                    Some(counts) => {
+                        let total2: u32 = counts.values().sum();
+                        let p2 = counts.get(app).copied().unwrap_or(0) as f64 / total2 as f64;
+                        0.6 * p2 + 0.4 * p1
+                    }
+                    None => p1,
+                };
+                Prediction {
+                    app: app.clone(),
+                    path: self.paths.get(app).cloned(),
+                    confidence: (p * self.temporal_boost(app, context.hour)).min(1.0),
+                }
+            })
+            .filter(|p| p.confidence >= self.min_confidence)
+            .collect();
+
+        predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+        predictions
+    }
+
+    /// 0.93-1.5: how much more than uniformly `app` is used around `hour`
+    fn temporal_boost(&self, app: &str, hour: u8) -> f64 {
+        let Some(hours) = self.hourly.get(app) else { return 1.0 };
+        let total: u32 = hours.iter().sum();
+        if total < MIN_SUPPORT {
+            return 1.0;
+        }
+        let h = hour as usize % 24;
+        let near = hours[(h + 23) % 24] + hours[h] + hours[(h + 1) % 24];
+        let share = near as f64 / total as f64;
+        let uniform = 3.0 / 24.0;
+        1.0 + 0.5 * (share - uniform) / (1.0 - uniform)
+    }
+
+    /// Most frequent transitions, for display
+    pub fn top_transitions(&self, limit: usize) -> Vec<(String, String, u32, f64)> {
+        let mut rows: Vec<(String, String, u32, f64)> = self
+            .first_order
+            .iter()
+            .flat_map(|(from, next)| {
+                let total: u32 = next.values().sum();
+                next.iter()
+                    .map(move |(to, &count)| (from.clone(), to.clone(), count, count as f64 / total as f64))
+            })
+            .collect();
+        rows.sort_by_key(|r| std::cmp::Reverse(r.2));
+        rows.truncate(limit);
+        rows
+    }
+}
+
+impl Default for AppSequenceModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Foreground tracking plus a model that retrains as switches come in
+pub struct Prefetcher {
+    observer: AppObserver,
+    model: AppSequenceModel,
+    events: Option<Receiver<ForegroundApp>>,
+    since_train: usize,
+    executor: PrefetchExecutor,
+}
+
+impl Prefetcher {
+    /// Load recorded switches and train on them
+    pub fn load() -> Self {
+        let observer = AppObserver::open_default().unwrap_or_else(|e| {
+            tracing::warn!("Prefetch history unavailable: {}", e);
+            AppObserver::new()
+        });
+        let model = observer.train();
+        let budget_mb = crate::core::config::OptimizerConfig::load_or_default().prefetch_budget_mb;
+        Self { observer, model, events: None, since_train: 0, executor: PrefetchExecutor::new(budget_mb) }
+    }
+
+    pub fn model(&self) -> &AppSequenceModel {
+        &self.model
+    }
+
+    pub fn observer(&self) -> &AppObserver {
+        &self.observer
+    }
+
+    pub fn executor(&self) -> &PrefetchExecutor {
+        &self.executor
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Start foreground tracking (no-op if already running or unsupported)
+    pub fn start(&mut self) {
+        if self.events.is_none() {
+            self.events = watch_foreground();
+        }
+    }
+
+    /// Stop recording switches
+    pub fn stop(&mut self) {
+        self.events = None;
+    }
+
+    /// Record pending switches, settle earlier prefetches and warm the next
+    /// predicted apps; returns predictions after the latest switch
+    pub fn poll(&mut self) -> Vec<Prediction> {
+        let Some(ref events) = self.events else { return Vec::new() };
+
+        let mut switched = false;
+        loop {
+            match events.try_recv() {
+                Ok(app) => switched |= self.observer.on_foreground_change(&app),
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.events = None;
+                    break;
+                }
+            }
+        }
+        if !switched {
+            return Vec::new();
+        }
+
+        self.since_train += 1;
+        if self.since_train >= RETRAIN_EVERY {
+            self.model = self.observer.train();
+            self.since_train = 0;
+        } else if let Some(launch) = self.observer.history().back() {
+            self.model.observe(launch);
+        }
+
+        if let Some(current) = self.observer.current_app() {
+            self.executor.on_foreground(current);
+        }
+        let predictions = self.predict_next();
+        for prediction in predictions.iter().take(MAX_WARM_PER_SWITCH) {
+            self.executor.warm(prediction);
+        }
+        predictions
+    }
+
+    /// Predictions for the app currently in the foreground
+    ///
+    /// Confidence is scaled by each app's prefetch hit rate, so apps that were
+    /// warmed but never opened drop below the threshold over time.
+    pub fn predict_next(&self) -> Vec<Prediction> {
+        let Some(context) = self.observer.context() else { return Vec::new() };
+        let mut predictions: Vec<Prediction> = self
+            .model
+            .predict(&context)
+            .into_iter()
+            .map(|mut p| {
+                p.confidence = (p.confidence * self.executor.confidence_factor(&p.app)).min(1.0);
+                p
+            })
+            .filter(|p| p.confidence >= self.model.min_confidence)
+            .collect();
+        predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+        predictions
+    }
+
+    /// Record switches until interrupted, printing each with its prediction
+    pub fn watch_and_print(&mut self) {
+        self.start();
+        if !self.is_watching() {
+            println!("Foreground app tracking is not supported on this platform");
+            return;
+        }
+        println!("Recording app switches (Ctrl+C to stop)...\n");
+
+        let mut last_seen = self.observer.history().back().map(|l| l.timestamp_ms);
+        loop {
+            let predictions = self.poll();
+            let latest = self.observer.history().back();
+            if latest.map(|l| l.timestamp_ms) != last_seen {
+                last_seen = latest.map(|l| l.timestamp_ms);
+                let current = latest.map(|l| l.app.as_str()).unwrap_or_default();
+                match predictions.first() {
+                    Some(next) => println!("→ {:24} next: {} ({:.0}%)", current, next.app, next.confidence * 100.0),
+                    None => println!("→ {}", current),
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n🔮 Predictive Prefetcher\n");
+        println!("  Recorded switches: {}", self.observer.history().len());
+        println!("  Minimum confidence: {:.0}%", self.model.min_confidence * 100.0);
+
+        let stats = self.executor.stats();
+        println!("  Apps prefetched:    {} ({:.1} MB read)", stats.warmed, stats.bytes_warmed as f64 / 1024.0 / 1024.0);
+        if stats.hits + stats.misses > 0 {
+            println!("  Prefetch hit rate:  {:.0}% ({} hit / {} miss)", stats.hit_rate() * 100.0, stats.hits, stats.misses);
+        }
+
+        let transitions = self.model.top_transitions(10);
+        if transitions.is_empty() {
+            println!("\n  Not enough data yet. Keep the tray or `prefetch watch` running.");
+            return;
+        }
+
+        println!("\n┌──────────────────────┬──────────────────────┬───────┬────────┐");
+        println!("│ From                 │ To                   │ Count │ P      │");
+        println!("├──────────────────────┼──────────────────────┼───────┼────────┤");
+        for (from, to, count, p) in transitions {
+            println!("│ {:20} │ {:20} │ {:>5} │ {:>5.0}% │", truncate(&from, 20), truncate(&to, 20), count, p * 100.0);
+        }
+        println!("└──────────────────────┴──────────────────────┴───────┴────────┘");
+
+        if let Some(current) = self.observer.current_app() {
+            let predictions = self.predict_next();
+            match predictions.first() {
+                Some(p) => println!("\n  After {}: {} ({:.0}% confident)", current, p.app, p.confidence * 100.0),
+                None => println!("\n  After {}: no confident prediction", current),
+            }
+        }
+    }
+}
+
+/// Stream of foreground switches, or `None` where tracking is unsupported
+pub fn watch_foreground() -> Option<Receiver<ForegroundApp>> {
+    #[cfg(windows)]
+    {
+        windows_observer::spawn()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos_observer::spawn()
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        None
+    }
+}
+
+#[cfg(windows)]
+mod windows_observer {
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::sync::{Mutex, OnceLock};
+
+    use windows::Win32::Foundation::{HMODULE, HWND};
+    use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, GetMessageW, GetWindowThreadProcessId, TranslateMessage, EVENT_SYSTEM_FOREGROUND,
+        MSG, WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS,
+    };
+
+    use super::ForegroundApp;
+
+    /// The hook callback has no user data, so the current subscriber lives here
+    static SUBSCRIBER: OnceLock<Mutex<Option<Sender<u32>>>> = OnceLock::new();
+
+    unsafe extern "system" fn on_foreground(
+        _hook: HWINEVENTHOOK,
+        _event: u32,
+        hwnd: HWND,
+        _id_object: i32,
+        _id_child: i32,
+        _thread: u32,
+        _time: u32,
+    ) {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+        if pid == 0 {
+            return;
+        }
+        if let Some(Ok(guard)) = SUBSCRIBER.get().map(|s| s.lock()) {
+            if let Some(ref tx) = *guard {
+                let _ = tx.send(pid);
+            }
+        }
+    }
+
+    pub fn spawn() -> Option<Receiver<ForegroundApp>> {
+        let (pid_tx, pid_rx) = channel::<u32>();
+        let (app_tx, app_rx) = channel();
+
+        // One hook thread per process; later subscribers just take over the sender
+        let first = SUBSCRIBER.get().is_none();
+        *SUBSCRIBER.get_or_init(|| Mutex::new(None)).lock().ok()? = Some(pid_tx);
+        if first {
+            std::thread::spawn(|| unsafe {
+                let hook = SetWinEventHook(
+                    EVENT_SYSTEM_FOREGROUND,
+                    EVENT_SYSTEM_FOREGROUND,
+                    HMODULE(std::ptr::null_mut()),
+                    Some(on_foreground),
+                    0,
+                    0,
+                    WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+                );
+                if hook.is_invalid() {
+                    tracing::warn!("SetWinEventHook failed; app switches will not be recorded");
+                    return;
+                }
+                let mut msg = MSG::default();
+                while GetMessageW(&mut msg, HWND(std::ptr::null_mut()), 0, 0).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+                let _ = UnhookWinEvent(hook);
+            });
+        }
+
+        // Resolve pids off the hook thread so the callback stays cheap
+        std::thread::spawn(move || {
+            let mut system = sysinfo::System::new();
+            for pid in pid_rx {
+                let pid = sysinfo::Pid::from_u32(pid);
+                system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+                let Some(process) = system.process(pid) else { continue };
+                let app = ForegroundApp {
+                    name: process.name().to_string_lossy().into_owned(),
+                    path: process.exe().map(|p| p.to_path_buf()),
+                };
+                if app_tx.send(app).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Some(app_rx)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos_observer {
+    use std::io::{BufRead, BufReader};
+    use std::path::PathBuf;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc::{channel, Receiver};
+
+    use super::ForegroundApp;
+
+    /// JXA observer for NSWorkspaceDidActivateApplicationNotification;
+    /// prints `name|bundle path` for every activation
+    const OBSERVER_SCRIPT: &str = r#"
+ObjC.import('AppKit');
+ObjC.registerSubclass({
+  name: 'RuVectorAppObserver',
+  methods: {
+    'activated:': {
+      types: ['void', ['id']],
+      implementation: function (note) {
+        var app = note.userInfo.objectForKey($.NSWorkspaceApplicationKey);
+        var path = app.bundleURL.isNil() ? '' : ObjC.unwrap(app.bundleURL.path);
+        var line = ObjC.unwrap(app.localizedName) + '|' + path + '\n';
+        $.NSFileHandle.fileHandleWithStandardOutput.writeData(
+          $(line).dataUsingEncoding($.NSUTF8StringEncoding));
+      }
+    }
+  }
+});
+var observer = $.RuVectorAppObserver.alloc.init;
+$.NSWorkspace.sharedWorkspace.notificationCenter.addObserverSelectorNameObject(
+  observer, 'activated:', $.NSWorkspaceDidActivateApplicationNotification, $());
+$.NSRunLoop.currentRunLoop.run;
+"#;
+
+    pub fn spawn() -> Option<Receiver<ForegroundApp>> {
+        let mut child = Command::new("osascript")
+            .args(["-l", "JavaScript", "-e", OBSERVER_SCRIPT])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let stdout = child.stdout.take()?;
+        let (tx, rx) = channel();
+
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let (name, path) = line.split_once('|').unwrap_or((line.as_str(), ""));
+                let app = ForegroundApp {
+                    name: name.to_string(),
+                    path: (!path.is_empty()).then(|| PathBuf::from(path)),
+                };
+                if tx.send(app).is_err() {
+                    break;
+                }
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+
+        Some(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn switch(observer: &mut AppObserver, name: &str) {
+        observer.on_foreground_change(&ForegroundApp { name: name.into(), path: None });
+    }
+
+    #[test]
+    fn test_observer_dedupes_and_tracks_context() {
+        let mut observer = AppObserver::new();
+        switch(&mut observer, "Code.exe");
+        switch(&mut observer, "code.exe");
+        switch(&mut observer, "explorer.exe");
+        switch(&mut observer, "WindowsTerminal.exe");
+        switch(&mut observer, "chrome.exe");
+
+        assert_eq!(observer.history().len(), 3);
+        let last = observer.history().back().unwrap();
+        assert_eq!(last.previous, vec!["windowsterminal.exe", "code.exe"]);
+    }
+
+    #[test]
+    fn test_markov_prediction() {
+        let mut observer = AppObserver::new();
+        for _ in 0..5 {
+            switch(&mut observer, "code");
+            switch(&mut observer, "terminal");
+            switch(&mut observer, "chrome");
+        }
+        switch(&mut observer, "code");
+        switch(&mut observer, "slack");
+        switch(&mut observer, "code");
+
+        let model = observer.train().with_min_confidence(0.5);
+        let context = AppContext { current_app: "code".into(), hour: 10, recent_apps: vec!["slack".into()] };
+        let predictions = model.predict(&context);
+        assert_eq!(predictions[0].app, "terminal");
+
+        // Too little data after slack to predict anything
+        let context = AppContext { current_app: "slack".into(), hour: 10, recent_apps: vec![] };
+        assert!(model.predict(&context).is_empty());
+
+        // Nothing passes an impossible threshold
+        let strict = observer.train().with_min_confidence(1.1);
+        let context = AppContext { current_app: "code".into(), hour: 10, recent_apps: vec![] };
+        assert!(strict.predict(&context).is_empty());
+    }
+}
@@ -0,0 +1,658 @@
+//! Optimization profiles (ADR-013)
+//!
+//! A profile is a named bundle of overrides - thresholds, aggressive mode,
+//! protected processes, AI Mode toggles and extra schedules - applied with
+//! one command (`profile apply gaming`), from the tray or automatically by
+//! activation rules (on battery, while an app runs).
+//!
+//! Applying a profile rewrites the saved settings (`config.toml` and, on
+//! Windows, the tray settings) and adds the profile's policy rules and
+//! schedules. The values from before the first profile are kept in
+//! `profile_state.toml`, so switching never stacks profiles and `profile off`
+//! puts everything back.
+
+mod store;
+
+pub use store::{builtin_profiles, parse_profiles, ProfileStore, PROFILES_FILE};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::OptimizerConfig;
+use crate::core::scheduler::{Schedule, ScheduleStore};
+use crate::features::timeline::{self, EventKind};
+use crate::monitor::procs;
+use crate::platform::power::{self, PowerSource};
+use crate::security::policy::{PolicyEffect, PolicyRule, PolicyStore, RuleKind};
+use crate::security::validator::matches_glob;
+use crate::core::text::truncate;
+
+/// File name of the active-profile state inside the config directory
+pub const PROFILE_STATE_FILE: &str = "profile_state.toml";
+
+/// How often long-running callers should evaluate activation rules
+pub const AUTO_SWITCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Oldest process list activation rules are checked against
+const PROCESS_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// AI Mode toggles; unset ones keep their current value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiToggles {
+    pub game_mode: Option<bool>,
+    pub focus_mode: Option<bool>,
+    pub thermal_prediction: Option<bool>,
+    pub predictive_preload: Option<bool>,
+}
+
+/// A schedule added while the profile is active
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSchedule {
+    /// `optimize`, `aggressive` or `browser-trim`
+    pub action: String,
+    /// Daily at `HH:MM`
+    pub at: Option<String>,
+    /// Every N minutes
+    pub every: Option<u32>,
+    #[serde(default = "default_days")]
+    pub days: String,
+    /// Hour window `START-END` for `every`
+    pub hours: Option<String>,
+}
+
+fn default_days() -> String {
+    "all".to_string()
+}
+
+impl ProfileSchedule {
+    pub fn every(action: &str, minutes: u32) -> Self {
+        Self { action: action.into(), at: None, every: Some(minutes), days: default_days(), hours: None }
+    }
+
+    pub fn to_schedule(&self) -> Result<Schedule, String> {
+        Schedule::from_args(&self.action, self.at.as_deref(), self.every, &self.days, self.hours.as_deref())
+    }
+}
+
+/// When a profile switches on by itself
+///
+/// All given conditions must hold; a rule with none never matches.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Activation {
+    pub power: Option<PowerSource>,
+    /// Process names or globs, any of which running activates the profile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub apps: Vec<String>,
+}
+
+impl Activation {
+    pub fn matches(&self, conditions: &Conditions) -> bool {
+        if self.power.is_none() && self.apps.is_empty() {
+            return false;
+        }
+        let power_ok = self.power.map_or(true, |p| p == conditions.power);
+        let apps_ok = self.apps.is_empty()
+            || self.apps.iter().any(|pattern| conditions.processes.iter().any(|name| app_matches(pattern, name)));
+        power_ok && apps_ok
+    }
+
+    /// App rules beat power rules, and rules with both beat either
+    fn specificity(&self) -> u8 {
+        (!self.apps.is_empty()) as u8 * 2 + self.power.is_some() as u8
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(power) = self.power {
+            parts.push(format!("on {}", power));
+        }
+        if !self.apps.is_empty() {
+            parts.push(format!("while {} runs", self.apps.join("/")));
+        }
+        if parts.is_empty() {
+            "never".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Process name match ignoring case and a trailing `.exe`
+pub fn app_matches(pattern: &str, name: &str) -> bool {
+    let strip = |s: &str| {
+        let s = s.to_lowercase();
+        s.strip_suffix(".exe").map(str::to_string).unwrap_or(s)
+    };
+    matches_glob(&strip(pattern), &strip(name))
+}
+
+/// A named bundle of setting overrides; unset fields keep their value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub description: String,
+    /// Memory usage (%) that triggers optimization
+    pub threshold: Option<u32>,
+    pub auto_optimize: Option<bool>,
+    /// Seconds between optimization passes
+    pub interval_secs: Option<u64>,
+    /// Aggressive optimization (clears system caches)
+    pub aggressive: Option<bool>,
+    /// Process globs the optimizer leaves alone while the profile is active
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub protected: Vec<String>,
+    pub ai: AiToggles,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ProfileSchedule>,
+    pub activate: Option<Activation>,
+}
+
+impl Profile {
+    /// One line per override, for `profile show`
+    pub fn overrides(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let on_off = |b: bool| if b { "on" } else { "off" };
+        if let Some(t) = self.threshold {
+            lines.push(format!("threshold {}%", t));
+        }
+        if let Some(b) = self.auto_optimize {
+            lines.push(format!("auto-optimize {}", on_off(b)));
+        }
+        if let Some(s) = self.interval_secs {
+            lines.push(format!("interval {}s", s));
+        }
+        if let Some(b) = self.aggressive {
+            lines.push(format!("aggressive {}", on_off(b)));
+        }
+        for (label, value) in [
+            ("Game Mode", self.ai.game_mode),
+            ("Focus Mode", self.ai.focus_mode),
+            ("thermal prediction", self.ai.thermal_prediction),
+            ("predictive preloading", self.ai.predictive_preload),
+        ] {
+            if let Some(b) = value {
+                lines.push(format!("{} {}", label, on_off(b)));
+            }
+        }
+        if !self.protected.is_empty() {
+            lines.push(format!("protect {}", self.protected.join(", ")));
+        }
+        for schedule in &self.schedules {
+            match schedule.to_schedule() {
+                Ok(s) => lines.push(format!("schedule {}", s.describe())),
+                Err(e) => lines.push(format!("schedule (invalid: {})", e)),
+            }
+        }
+        lines
+    }
+}
+
+/// What activation rules are checked against
+#[derive(Debug, Clone)]
+pub struct Conditions {
+    pub power: PowerSource,
+    /// Lowercase names of running processes
+    pub processes: Vec<String>,
+}
+
+impl Conditions {
+    pub fn current() -> Self {
+        let mut processes: Vec<String> = procs::with_processes(PROCESS_MAX_AGE, |system| {
+            system.processes().values().map(|p| p.name().to_string_lossy().to_lowercase()).collect()
+        });
+        processes.sort();
+        processes.dedup();
+        Self { power: power::power_source(), processes }
+    }
+}
+
+/// Profile whose activation rule matches, most specific first, then by name
+pub fn matching_profile<'a>(profiles: &'a BTreeMap<String, Profile>, conditions: &Conditions) -> Option<&'a str> {
+    profiles
+        .iter()
+        .filter_map(|(name, p)| p.activate.as_ref().filter(|a| a.matches(conditions)).map(|a| (name, a)))
+        .max_by_key(|(name, a)| (a.specificity(), Reverse(name.as_str())))
+        .map(|(name, _)| name.as_str())
+}
+
+/// Tray values a profile can change (Windows only)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrayValues {
+    pub threshold: u32,
+    pub auto_optimize: bool,
+    pub interval_secs: u64,
+    pub game_mode: bool,
+    pub focus_mode: bool,
+    pub thermal_prediction: bool,
+    pub predictive_preload: bool,
+}
+
+/// The saved settings a profile changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    pub pressure_threshold: u32,
+    pub min_interval_secs: u64,
+    pub aggressive_mode: bool,
+    pub tray: Option<TrayValues>,
+}
+
+impl Settings {
+    /// Settings as currently saved
+    pub fn current() -> Self {
+        let config = OptimizerConfig::load_file();
+
+        #[cfg(target_os = "windows")]
+        let tray = {
+            let s = crate::tray::TraySettings::load();
+            Some(TrayValues {
+                threshold: s.threshold,
+                auto_optimize: s.auto_optimize,
+                interval_secs: s.interval_secs,
+                game_mode: s.ai_mode.game_mode,
+                focus_mode: s.ai_mode.focus_mode,
+                thermal_prediction: s.ai_mode.thermal_prediction,
+                predictive_preload: s.ai_mode.predictive_preload,
+            })
+        };
+        #[cfg(not(target_os = "windows"))]
+        let tray = None;
+
+        Self {
+            pressure_threshold: config.pressure_threshold,
+            min_interval_secs: config.min_interval_secs,
+            aggressive_mode: config.aggressive_mode,
+            tray,
+        }
+    }
+
+    /// These settings with a profile's overrides applied
+    pub fn with_profile(&self, profile: &Profile) -> Self {
+        let mut out = self.clone();
+        if let Some(t) = profile.threshold {
+            out.pressure_threshold = t;
+        }
+        if let Some(s) = profile.interval_secs {
+            out.min_interval_secs = s;
+        }
+        if let Some(b) = profile.aggressive {
+            out.aggressive_mode = b;
+        }
+        if let Some(tray) = out.tray.as_mut() {
+            tray.threshold = profile.threshold.unwrap_or(tray.threshold);
+            tray.interval_secs = profile.interval_secs.unwrap_or(tray.interval_secs);
+            tray.auto_optimize = profile.auto_optimize.unwrap_or(tray.auto_optimize);
+            tray.game_mode = profile.ai.game_mode.unwrap_or(tray.game_mode);
+            tray.focus_mode = profile.ai.focus_mode.unwrap_or(tray.focus_mode);
+            tray.thermal_prediction = profile.ai.thermal_prediction.unwrap_or(tray.thermal_prediction);
+            tray.predictive_preload = profile.ai.predictive_preload.unwrap_or(tray.predictive_preload);
+        }
+        out
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = OptimizerConfig::default_path();
+        let mut config = OptimizerConfig::load_file();
+        config.pressure_threshold = self.pressure_threshold;
+        config.min_interval_secs = self.min_interval_secs;
+        config.aggressive_mode = self.aggressive_mode;
+        config.save(&path).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+        #[cfg(target_os = "windows")]
+        if let Some(values) = &self.tray {
+            let mut s = crate::tray::TraySettings::load();
+            s.threshold = values.threshold;
+            s.auto_optimize = values.auto_optimize;
+            s.interval_secs = values.interval_secs;
+            s.ai_mode.game_mode = values.game_mode;
+            s.ai_mode.focus_mode = values.focus_mode;
+            s.ai_mode.thermal_prediction = values.thermal_prediction;
+            s.ai_mode.predictive_preload = values.predictive_preload;
+            s.save()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfileState {
+    active: Option<String>,
+    /// Whether `active` was switched on by an activation rule
+    automatic: bool,
+    /// Last profile applied by hand, restored when an automatic one stops matching
+    manual: Option<String>,
+    /// Profile whose rule matched at the last check
+    matched: Option<String>,
+    /// Policy rule and schedule IDs added by the active profile
+    policy_rules: Vec<u32>,
+    schedules: Vec<u32>,
+    /// Settings from before the first profile, restored by `profile off`
+    baseline: Option<Settings>,
+}
+
+/// A profile switch made by the activation rules
+#[derive(Debug, Clone)]
+pub struct ProfileChange {
+    /// New active profile, `None` when back to the normal settings
+    pub profile: Option<String>,
+    pub reason: String,
+}
+
+impl ProfileChange {
+    /// Title and message for a desktop notification
+    pub fn notification(&self) -> (String, String) {
+        match &self.profile {
+            Some(name) => (format!("Profile: {}", name), self.reason.clone()),
+            None => ("Profile off".to_string(), self.reason.clone()),
+        }
+    }
+}
+
+/// Applies profiles and tracks which one is active
+pub struct ProfileManager {
+    store: ProfileStore,
+    state_path: PathBuf,
+    profiles: BTreeMap<String, Profile>,
+    state: ProfileState,
+}
+
+impl ProfileManager {
+    pub fn open_default() -> Result<Self, String> {
+        let mut manager = Self {
+            store: ProfileStore::open_default()?,
+            state_path: crate::features::config_file(PROFILE_STATE_FILE)?,
+            profiles: BTreeMap::new(),
+            state: ProfileState::default(),
+        };
+        manager.reload()?;
+        Ok(manager)
+    }
+
+    /// Re-read profiles and state, which the CLI and tray share on disk
+    pub fn reload(&mut self) -> Result<(), String> {
+        self.profiles = self.store.load()?;
+        self.state = if self.state_path.exists() {
+            let content = std::fs::read_to_string(&self.state_path).map_err(|e| e.to_string())?;
+            toml::from_str(&content).map_err(|e| format!("Failed to parse {:?}: {}", self.state_path, e))?
+        } else {
+            ProfileState::default()
+        };
+        Ok(())
+    }
+
+    pub fn profiles(&self) -> &BTreeMap<String, Profile> {
+        &self.profiles
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(&name.to_lowercase())
+    }
+
+    pub fn active(&self) -> Option<&str> {
+        self.state.active.as_deref()
+    }
+
+    pub fn store(&self) -> &ProfileStore {
+        &self.store
+    }
+
+    /// Switch to a profile by hand
+    pub fn apply(&mut self, name: &str) -> Result<(), String> {
+        self.reload()?;
+        self.activate(&name.to_lowercase(), false)?;
+        self.save_state()
+    }
+
+    /// Drop the active profile and restore the settings from before it
+    pub fn off(&mut self) -> Result<(), String> {
+        self.reload()?;
+        self.deactivate()?;
+        self.state.manual = None;
+        self.save_state()
+    }
+
+    /// Evaluate activation rules and switch if the matching profile changed
+    ///
+    /// Only a change in which rule matches causes a switch, so a profile
+    /// chosen by hand stays until conditions change.
+    pub fn auto_switch(&mut self) -> Result<Option<ProfileChange>, String> {
+        self.reload()?;
+        self.auto_switch_with(&Conditions::current())
+    }
+
+    fn auto_switch_with(&mut self, conditions: &Conditions) -> Result<Option<ProfileChange>, String> {
+        let matched = matching_profile(&self.profiles, conditions).map(str::to_string);
+        if matched == self.state.matched {
+            return Ok(None);
+        }
+        self.state.matched = matched.clone();
+
+        let change = match matched {
+            Some(name) if self.state.active.as_deref() != Some(name.as_str()) => {
+                self.activate(&name, true)?;
+                let reason = self.profiles[&name].activate.as_ref().map(Activation::describe).unwrap_or_default();
+                Some(ProfileChange { profile: Some(name), reason: format!("Activated {}", reason) })
+            }
+            None if self.state.automatic => {
+                let reason = "Activation rule no longer matches".to_string();
+                match self.state.manual.clone().filter(|m| self.profiles.contains_key(m)) {
+                    Some(manual) => {
+                        self.activate(&manual, false)?;
+                        Some(ProfileChange { profile: Some(manual), reason })
+                    }
+                    None => {
+                        self.deactivate()?;
+                        Some(ProfileChange { profile: None, reason })
+                    }
+                }
+            }
+            _ => None,
+        };
+        self.save_state()?;
+        Ok(change)
+    }
+
+    fn activate(&mut self, name: &str, automatic: bool) -> Result<(), String> {
+        let profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("No profile named '{}' (see `profile list`)", name))?;
+        // Validate everything before touching any setting
+        let schedules = profile.schedules.iter().map(ProfileSchedule::to_schedule).collect::<Result<Vec<_>, _>>()?;
+        let rules: Vec<PolicyRule> = profile
+            .protected
+            .iter()
+            .map(|pattern| PolicyRule::new(PolicyEffect::Deny, RuleKind::Glob, pattern.clone()))
+            .collect();
+
+        let baseline = self.state.baseline.clone().unwrap_or_else(Settings::current);
+        self.remove_additions();
+        baseline.with_profile(&profile).save()?;
+
+        if !rules.is_empty() {
+            let store = PolicyStore::open_default()?;
+            for rule in rules {
+                self.state.policy_rules.push(store.add(rule)?);
+            }
+        }
+        if !schedules.is_empty() {
+            let store = ScheduleStore::open_default()?;
+            for schedule in schedules {
+                self.state.schedules.push(store.add(schedule)?);
+            }
+        }
+
+        self.state.baseline = Some(baseline);
+        self.state.active = Some(name.to_string());
+        self.state.automatic = automatic;
+        if !automatic {
+            self.state.manual = Some(name.to_string());
+        }
+        tracing::info!("Applied profile '{}'{}", name, if automatic { " (auto)" } else { "" });
+        timeline::record(EventKind::ModeSwitch, format!("Profile {}{}", name, if automatic { " (auto)" } else { "" }));
+        Ok(())
+    }
+
+    fn deactivate(&mut self) -> Result<(), String> {
+        self.remove_additions();
+        if let Some(baseline) = self.state.baseline.take() {
+            baseline.save()?;
+        }
+        if let Some(name) = self.state.active.take() {
+            timeline::record(EventKind::ModeSwitch, format!("Profile {} off", name));
+        }
+        self.state.automatic = false;
+        Ok(())
+    }
+
+    /// Remove the active profile's policy rules and schedules
+    ///
+    /// Ones the user already deleted by hand are skipped.
+    fn remove_additions(&mut self) {
+        let rules = std::mem::take(&mut self.state.policy_rules);
+        if let (false, Ok(store)) = (rules.is_empty(), PolicyStore::open_default()) {
+            for id in rules {
+                let _ = store.remove(id);
+            }
+        }
+        let schedules = std::mem::take(&mut self.state.schedules);
+        if let (false, Ok(store)) = (schedules.is_empty(), ScheduleStore::open_default()) {
+            for id in schedules {
+                let _ = store.remove(id);
+            }
+        }
+    }
+
+    fn save_state(&self) -> Result<(), String> {
+        let content = toml::to_string_pretty(&self.state).map_err(|e| e.to_string())?;
+        std::fs::write(&self.state_path, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", self.state_path, e))
+    }
+
+    /// Print all profiles as a table
+    pub fn print_table(&self) {
+        println!("\n🎛️  Optimization Profiles\n");
+        println!("┌───┬────────────┬────────────────────────────────────────────────┬──────────────────────────┐");
+        println!("│   │ Profile    │ Description                                    │ Auto-activate            │");
+        println!("├───┼────────────┼────────────────────────────────────────────────┼──────────────────────────┤");
+        for (name, profile) in &self.profiles {
+            let marker = if self.active() == Some(name.as_str()) { "●" } else { " " };
+            let activate = profile.activate.as_ref().map(Activation::describe).unwrap_or_else(|| "-".into());
+            println!(
+                "│ {} │ {:10} │ {:46} │ {:24} │",
+                marker,
+                truncate(name, 10),
+                truncate(&profile.description, 46),
+                truncate(&activate, 24)
+            );
+        }
+        println!("└───┴────────────┴────────────────────────────────────────────────┴──────────────────────────┘");
+
+        match (&self.state.active, self.state.automatic) {
+            (Some(name), true) => println!("\n  Active: {} (switched on automatically)", name),
+            (Some(name), false) => println!("\n  Active: {}", name),
+            (None, _) => println!("\n  No profile active."),
+        }
+        println!("  Profiles file: {:?}", self.store.path());
+    }
+
+    /// Print one profile's overrides
+    pub fn print_profile(&self, name: &str) -> Result<(), String> {
+        let profile = self.get(name).ok_or_else(|| format!("No profile named '{}' (see `profile list`)", name))?;
+        println!("\n🎛️  Profile: {}\n", name.to_lowercase());
+        if !profile.description.is_empty() {
+            println!("  {}\n", profile.description);
+        }
+        let overrides = profile.overrides();
+        if overrides.is_empty() {
+            println!("  (no overrides)");
+        }
+        for line in overrides {
+            println!("  • {}", line);
+        }
+        if let Some(activate) = &profile.activate {
+            println!("\n  Auto-activates {}", activate.describe());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_merges_builtins() {
+        let content = r#"
+            [profiles.Gaming]
+            description = "Mine"
+            threshold = 95
+            activate = { apps = ["steam"] }
+
+            [profiles.render]
+            aggressive = true
+            schedules = [{ action = "optimize", at = "03:00", days = "weekdays" }]
+        "#;
+        let profiles = parse_profiles(content).unwrap();
+        assert_eq!(profiles["gaming"].threshold, Some(95));
+        assert_eq!(profiles["gaming"].ai, AiToggles::default());
+        assert!(profiles.contains_key("battery"));
+        assert_eq!(profiles["render"].schedules[0].to_schedule().unwrap().describe(), "optimize at 03:00, weekdays");
+
+        let path = std::env::temp_dir().join(format!("ruvector-profiles-{}.toml", std::process::id()));
+        let store = ProfileStore::open(path.clone());
+        store.save(&profiles).unwrap();
+        assert_eq!(store.load().unwrap(), profiles);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let mut profiles = builtin_profiles();
+        profiles.get_mut("gaming").unwrap().activate =
+            Some(Activation { power: None, apps: vec!["steam".into(), "*-win64-shipping".into()] });
+        let at = |power, processes: &[&str]| Conditions {
+            power,
+            processes: processes.iter().map(|p| p.to_string()).collect(),
+        };
+
+        assert_eq!(matching_profile(&profiles, &at(PowerSource::Ac, &["explorer.exe"])), None);
+        assert_eq!(matching_profile(&profiles, &at(PowerSource::Battery, &["explorer.exe"])), Some("battery"));
+        // App rules win over the battery rule
+        let game = at(PowerSource::Battery, &["fortniteclient-win64-shipping.exe"]);
+        assert_eq!(matching_profile(&profiles, &game), Some("gaming"));
+        assert_eq!(matching_profile(&profiles, &at(PowerSource::Ac, &["Steam.exe"])), Some("gaming"));
+        assert!(!Activation::default().matches(&game));
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let base = Settings {
+            pressure_threshold: 80,
+            min_interval_secs: 30,
+            aggressive_mode: true,
+            tray: Some(TrayValues {
+                threshold: 75,
+                auto_optimize: true,
+                interval_secs: 60,
+                game_mode: true,
+                focus_mode: true,
+                thermal_prediction: true,
+                predictive_preload: true,
+            }),
+        };
+        let battery = &builtin_profiles()["battery"];
+        let applied = base.with_profile(battery);
+        assert_eq!(applied.pressure_threshold, 85);
+        assert!(!applied.aggressive_mode);
+        let tray = applied.tray.unwrap();
+        assert_eq!((tray.threshold, tray.interval_secs), (85, 300));
+        assert!(!tray.predictive_preload && tray.game_mode);
+        assert_eq!(base.with_profile(&Profile::default()), base);
+    }
+}
@@ -0,0 +1,131 @@
+//! Profile definitions in `profiles.toml`
+//!
+//! ```toml
+//! [profiles.gaming]
+//! description = "Stay out of the way while playing"
+//! threshold = 90
+//! ai = { game_mode = true, predictive_preload = false }
+//! activate = { apps = ["steam", "*-win64-shipping"] }
+//! ```
+//!
+//! The built-in profiles are always available; a section with the same name
+//! replaces one.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Activation, AiToggles, Profile, ProfileSchedule};
+use crate::platform::power::PowerSource;
+
+/// File name of the profile definitions inside the config directory
+pub const PROFILES_FILE: &str = "profiles.toml";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// Profiles shipped with the optimizer
+pub fn builtin_profiles() -> BTreeMap<String, Profile> {
+    let mut profiles = BTreeMap::new();
+    profiles.insert(
+        "gaming".to_string(),
+        Profile {
+            description: "Trim only under real pressure, never mid-game".into(),
+            threshold: Some(90),
+            aggressive: Some(false),
+            protected: vec!["steam*".into(), "epicgameslauncher*".into(), "battle.net*".into()],
+            ai: AiToggles {
+                game_mode: Some(true),
+                focus_mode: Some(false),
+                predictive_preload: Some(false),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    profiles.insert(
+        "meetings".to_string(),
+        Profile {
+            description: "Keep calls smooth; trim browsers every 30 min".into(),
+            threshold: Some(80),
+            protected: vec!["zoom*".into(), "*teams*".into(), "webex*".into()],
+            ai: AiToggles {
+                game_mode: Some(false),
+                focus_mode: Some(true),
+                ..Default::default()
+            },
+            schedules: vec![ProfileSchedule::every("browser-trim", 30)],
+            ..Default::default()
+        },
+    );
+    profiles.insert(
+        "battery".to_string(),
+        Profile {
+            description: "Fewer, lighter passes to save power".into(),
+            threshold: Some(85),
+            interval_secs: Some(300),
+            aggressive: Some(false),
+            ai: AiToggles {
+                thermal_prediction: Some(true),
+                predictive_preload: Some(false),
+                ..Default::default()
+            },
+            activate: Some(Activation { power: Some(PowerSource::Battery), apps: Vec::new() }),
+            ..Default::default()
+        },
+    );
+    profiles
+}
+
+/// Parse `profiles.toml` content and merge it over the built-in profiles
+///
+/// Names are case-insensitive and stored lowercase.
+pub fn parse_profiles(content: &str) -> Result<BTreeMap<String, Profile>, String> {
+    let file: ProfilesFile = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut profiles = builtin_profiles();
+    for (name, profile) in file.profiles {
+        profiles.insert(name.to_lowercase(), profile);
+    }
+    Ok(profiles)
+}
+
+/// Persistent profile definitions
+pub struct ProfileStore {
+    path: PathBuf,
+}
+
+impl ProfileStore {
+    /// Store at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self { path: crate::features::config_file(PROFILES_FILE)? })
+    }
+
+    pub fn open(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Load all profiles (only the built-ins if the file does not exist)
+    pub fn load(&self) -> Result<BTreeMap<String, Profile>, String> {
+        if !self.path.exists() {
+            return Ok(builtin_profiles());
+        }
+        let content = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        parse_profiles(&content).map_err(|e| format!("Failed to parse {:?}: {}", self.path, e))
+    }
+
+    /// Write the profiles, e.g. to seed the file with the built-ins for editing
+    pub fn save(&self, profiles: &BTreeMap<String, Profile>) -> Result<(), String> {
+        let file = ProfilesFile { profiles: profiles.clone() };
+        let content = toml::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+}
@@ -0,0 +1,766 @@
+//! Daily and weekly summary reports
+//!
+//! `report generate` gathers a day or a week from the stores the other
+//! features already keep: the timeline's memory samples and events, the
+//! optimization history, leak suspects and the health history. A snapshot
+//! is taken and compared with the last one from before the period to find
+//! the apps that grew. The result is written as a single HTML page with
+//! inline styles, or as Markdown, along with recommendations drawn from
+//! the same data.
+//!
+//! Schedules with the `report-day` or `report-week` action make the daemon,
+//! service and tray write one into `report_dir`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::OptimizerConfig;
+use crate::core::history::{HistoryFilter, HistoryStore, HistorySummary, TimeRange};
+use crate::features::health::{self, Baseline, HealthHistory};
+use crate::features::health::metrics;
+use crate::features::leakwatch::{Suspect, SuspectLog};
+use crate::features::snapshot::{self, AppChange, Snapshot, SnapshotDiff};
+use crate::features::timeline::{EventKind, MemoryPoint, Timeline};
+
+/// Default directory of written reports inside the config directory
+pub const REPORTS_DIR: &str = "reports";
+
+/// Apps listed as growing the most
+const TOP_GROWERS: usize = 10;
+
+/// Growth that earns an app its own recommendation
+const LARGE_GROWTH_MB: f64 = 1024.0;
+
+/// Share of samples above the pressure threshold worth a recommendation
+const HIGH_PRESSURE_SHARE: f64 = 0.1;
+
+/// Health subscore below which its advice is repeated
+const WEAK_SCORE: f64 = 60.0;
+
+/// Free share of the system drive worth a recommendation
+const LOW_DISK_PERCENT: f64 = 15.0;
+
+/// Width of the Markdown trend bars in characters
+const BAR_WIDTH: usize = 20;
+
+/// How much time a report covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Day,
+    Week,
+}
+
+impl ReportPeriod {
+    pub fn millis(&self) -> u64 {
+        match self {
+            ReportPeriod::Day => 24 * 3600 * 1000,
+            ReportPeriod::Week => 7 * 24 * 3600 * 1000,
+        }
+    }
+
+    /// Trend buckets: hours of a day, days of a week
+    fn bucket_format(&self) -> &'static str {
+        match self {
+            ReportPeriod::Day => "%H:00",
+            ReportPeriod::Week => "%a %d %b",
+        }
+    }
+}
+
+impl std::fmt::Display for ReportPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportPeriod::Day => write!(f, "day"),
+            ReportPeriod::Week => write!(f, "week"),
+        }
+    }
+}
+
+impl std::str::FromStr for ReportPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(ReportPeriod::Day),
+            "week" | "weekly" => Ok(ReportPeriod::Week),
+            other => Err(format!("Unknown period: {} (day, week)", other)),
+        }
+    }
+}
+
+/// How a report is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        }
+    }
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "html" => Ok(ReportFormat::Html),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            other => Err(format!("Unknown format: {} (html, markdown)", other)),
+        }
+    }
+}
+
+/// Memory load over one hour or day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrendPoint {
+    pub label: String,
+    pub avg_load_percent: f64,
+    pub peak_load_percent: u32,
+    pub peak_swap_mb: u64,
+}
+
+/// Optimizations started one way
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TriggerTotals {
+    pub trigger: String,
+    pub count: usize,
+    pub freed_mb: f64,
+}
+
+/// Leak alerts for one process name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LeakIncident {
+    pub process_name: String,
+    pub alerts: usize,
+    pub max_growth_mb_per_hour: f64,
+    pub last_memory_mb: f64,
+    pub last_ms: u64,
+}
+
+/// Average health score over one hour or day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthPoint {
+    pub label: String,
+    pub score: f64,
+}
+
+/// Everything a report shows
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub period: ReportPeriod,
+    pub host: String,
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub memory: Vec<TrendPoint>,
+    pub avg_load_percent: Option<f64>,
+    pub peak_load_percent: Option<u32>,
+    /// Share of samples at or above `pressure_threshold`
+    pub high_pressure_share: f64,
+    pub pressure_spikes: usize,
+    pub anomalies: usize,
+    pub optimizations: HistorySummary,
+    pub by_trigger: Vec<TriggerTotals>,
+    /// When the snapshot the growth is measured from was taken
+    pub growth_since_ms: Option<u64>,
+    pub growers: Vec<AppChange>,
+    pub leaks: Vec<LeakIncident>,
+    pub health: Vec<HealthPoint>,
+    pub recommendations: Vec<String>,
+}
+
+impl Report {
+    /// Gather the period ending now
+    ///
+    /// A store that can't be read leaves its section empty rather than
+    /// failing the whole report.
+    pub fn build(period: ReportPeriod, config: &OptimizerConfig) -> Self {
+        let to_ms = now_ms();
+        let from_ms = to_ms.saturating_sub(period.millis());
+
+        let (samples, events) = readable("timeline", Timeline::open_default().and_then(|t| t.read(from_ms, to_ms)));
+        let history = readable(
+            "optimization history",
+            HistoryStore::open_default().and_then(|store| {
+                store.query(TimeRange { start_ms: Some(from_ms), end_ms: Some(to_ms) }, &HistoryFilter::default())
+            }),
+        );
+        let suspects = readable("leak suspects", SuspectLog::open_default().and_then(|log| log.since(from_ms)));
+        let health_records =
+            readable("health history", HealthHistory::open_default().and_then(|h| h.between(from_ms, to_ms)));
+        let growth = app_growth(from_ms);
+
+        let mut by_trigger: BTreeMap<String, TriggerTotals> = BTreeMap::new();
+        for record in &history {
+            let totals = by_trigger.entry(record.trigger.to_string()).or_insert_with(|| TriggerTotals {
+                trigger: record.trigger.to_string(),
+                count: 0,
+                freed_mb: 0.0,
+            });
+            totals.count += 1;
+            totals.freed_mb += record.freed_mb;
+        }
+
+        let loads: Vec<u32> = samples.iter().map(|p| p.load_percent).collect();
+        let high = loads.iter().filter(|&&load| load >= config.pressure_threshold).count();
+
+        let mut report = Self {
+            period,
+            host: crate::features::fleet::host_name(),
+            from_ms,
+            to_ms,
+            memory: memory_trend(&samples, period),
+            avg_load_percent: (!loads.is_empty())
+                .then(|| loads.iter().map(|&l| l as f64).sum::<f64>() / loads.len() as f64),
+            peak_load_percent: loads.iter().copied().max(),
+            high_pressure_share: if loads.is_empty() { 0.0 } else { high as f64 / loads.len() as f64 },
+            pressure_spikes: events.iter().filter(|e| e.kind == EventKind::PressureSpike).count(),
+            anomalies: events.iter().filter(|e| e.kind == EventKind::Anomaly).count(),
+            optimizations: HistoryStore::summarize(&history),
+            by_trigger: by_trigger.into_values().collect(),
+            growth_since_ms: growth.as_ref().map(|diff| diff.from_ms),
+            growers: growth.map(|diff| diff.growers(TOP_GROWERS).cloned().collect()).unwrap_or_default(),
+            leaks: leak_incidents(&suspects),
+            health: bucket(&health_records, |r| r.timestamp_ms, period)
+                .into_iter()
+                .map(|(label, records)| HealthPoint {
+                    label,
+                    score: records.iter().map(|r| r.total as f64).sum::<f64>() / records.len() as f64,
+                })
+                .collect(),
+            recommendations: Vec::new(),
+        };
+        report.recommendations = report.recommend(config, Baseline::from_records(&health_records));
+        report
+    }
+
+    /// Advice drawn from the gathered data, most pressing first
+    fn recommend(&self, config: &OptimizerConfig, health: Option<Baseline>) -> Vec<String> {
+        let mut advice = Vec::new();
+        if self.high_pressure_share >= HIGH_PRESSURE_SHARE {
+            advice.push(format!(
+                "Memory was at {}% or more for {:.0}% of the {}. Close or restart the apps that grew, or add RAM.",
+                config.pressure_threshold,
+                self.high_pressure_share * 100.0,
+                self.period
+            ));
+            if self.optimizations.count == 0 {
+                advice.push(
+                    "No optimizations ran. Start the daemon, or add a schedule such as `schedule add -a optimize --every 60`."
+                        .to_string(),
+                );
+            }
+        }
+        for leak in self.leaks.iter().take(3) {
+            let flagged = if leak.alerts == 1 { "once".to_string() } else { format!("{} times", leak.alerts) };
+            advice.push(format!(
+                "Restart {}: it was flagged as leaking {}, growing up to {:.0} MB/h. `leaks` has the details.",
+                leak.process_name, flagged, leak.max_growth_mb_per_hour
+            ));
+        }
+        if let Some(grower) = self.growers.first().filter(|g| g.memory_delta_mb >= LARGE_GROWTH_MB) {
+            advice.push(format!(
+                "{} grew by {:.1} GB. `watch --name {}` follows it live.",
+                grower.name,
+                grower.memory_delta_mb / 1024.0,
+                grower.name
+            ));
+        }
+        if let Some(health) = health {
+            let weakest = health.scores.iter().min_by(|a, b| a.1.total_cmp(b.1));
+            if let Some((&dimension, &score)) = weakest.filter(|(_, score)| **score < WEAK_SCORE) {
+                advice.push(format!("{} averaged {:.0}/100. {}.", dimension.label(), score, health::advice(dimension)));
+            }
+        }
+        if let Some(disk) = metrics::system_disk().filter(|d| d.free_percent() < LOW_DISK_PERCENT) {
+            advice.push(format!(
+                "The system drive has {:.0}% free, which leaves little room for the pagefile or swap. `disk` shows caches worth clearing.",
+                disk.free_percent()
+            ));
+        }
+        if advice.is_empty() {
+            advice.push("Nothing needs attention.".to_string());
+        }
+        advice
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Html => self.to_html(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn title(&self) -> String {
+        let span = match self.period {
+            ReportPeriod::Day => format_time(self.to_ms, "%A %d %B %Y"),
+            ReportPeriod::Week => {
+                format!("{} – {}", format_time(self.from_ms, "%d %b"), format_time(self.to_ms, "%d %b %Y"))
+            }
+        };
+        let kind = match self.period {
+            ReportPeriod::Day => "Daily",
+            ReportPeriod::Week => "Weekly",
+        };
+        format!("{} memory report for {}, {}", kind, self.host, span)
+    }
+
+    /// Headline numbers as label and value pairs
+    fn overview(&self) -> Vec<(&'static str, String)> {
+        let percent = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.0}%", v));
+        vec![
+            ("Average memory load", percent(self.avg_load_percent)),
+            ("Peak memory load", percent(self.peak_load_percent.map(f64::from))),
+            ("Pressure spikes", self.pressure_spikes.to_string()),
+            ("Anomalies", self.anomalies.to_string()),
+            ("Optimizations", self.optimizations.count.to_string()),
+            ("Memory freed", format!("{:.0} MB", self.optimizations.total_freed_mb)),
+            ("Leak alerts", self.leaks.iter().map(|l| l.alerts).sum::<usize>().to_string()),
+        ]
+    }
+
+    fn growth_heading(&self) -> String {
+        match self.growth_since_ms {
+            Some(ms) => format!("Top growing apps since {}", format_time(ms, "%Y-%m-%d %H:%M")),
+            None => "Top growing apps".to_string(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+        let _ = writeln!(out, "Generated {}.\n", format_time(self.to_ms, "%Y-%m-%d %H:%M"));
+
+        let _ = writeln!(out, "## Overview\n\n| | |\n|---|---:|");
+        for (label, value) in self.overview() {
+            let _ = writeln!(out, "| {} | {} |", label, value);
+        }
+
+        let _ = writeln!(out, "\n## Recommendations\n");
+        for advice in &self.recommendations {
+            let _ = writeln!(out, "- {}", advice);
+        }
+
+        let _ = writeln!(out, "\n## Memory trend\n");
+        if self.memory.is_empty() {
+            let _ = writeln!(out, "No memory samples. The timeline is recorded while the tray or a daemon runs.");
+        } else {
+            let _ = writeln!(out, "| {} | Average | Peak | Peak swap | |\n|---|---:|---:|---:|---|", bucket_name(self.period));
+            for point in &self.memory {
+                let _ = writeln!(
+                    out,
+                    "| {} | {:.0}% | {}% | {} MB | `{}` |",
+                    point.label,
+                    point.avg_load_percent,
+                    point.peak_load_percent,
+                    point.peak_swap_mb,
+                    bar(point.avg_load_percent)
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Optimizations\n");
+        if self.by_trigger.is_empty() {
+            let _ = writeln!(out, "No optimizations ran.");
+        } else {
+            let _ = writeln!(out, "| Trigger | Runs | Freed |\n|---|---:|---:|");
+            for totals in &self.by_trigger {
+                let _ = writeln!(out, "| {} | {} | {:.0} MB |", totals.trigger, totals.count, totals.freed_mb);
+            }
+        }
+
+        let _ = writeln!(out, "\n## {}\n", self.growth_heading());
+        if self.growers.is_empty() {
+            let _ = writeln!(out, "No earlier snapshot to compare with yet; the next report will have one.");
+        } else {
+            let _ = writeln!(out, "| App | Before | Now | Change |\n|---|---:|---:|---:|");
+            for app in &self.growers {
+                let (before, after) = app_sizes(app);
+                let _ = writeln!(out, "| {} | {:.0} MB | {:.0} MB | +{:.0} MB |", app.name, before, after, app.memory_delta_mb);
+            }
+        }
+
+        let _ = writeln!(out, "\n## Leak incidents\n");
+        if self.leaks.is_empty() {
+            let _ = writeln!(out, "No leak alerts.");
+        } else {
+            let _ = writeln!(out, "| Process | Alerts | Max growth | Last size | Last alert |\n|---|---:|---:|---:|---|");
+            for leak in &self.leaks {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {:.0} MB/h | {:.0} MB | {} |",
+                    leak.process_name,
+                    leak.alerts,
+                    leak.max_growth_mb_per_hour,
+                    leak.last_memory_mb,
+                    format_time(leak.last_ms, "%Y-%m-%d %H:%M")
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Health score\n");
+        if self.health.is_empty() {
+            let _ = writeln!(out, "No health scores recorded. `health` records one each time it runs.");
+        } else {
+            let _ = writeln!(out, "| {} | Score | |\n|---|---:|---|", bucket_name(self.period));
+            for point in &self.health {
+                let _ = writeln!(out, "| {} | {:.0} | `{}` |", point.label, point.score, bar(point.score));
+            }
+        }
+        out
+    }
+
+    fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = escape(&self.title());
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<p class=\"muted\">Generated {}</p>\n",
+            title,
+            STYLE,
+            title,
+            format_time(self.to_ms, "%Y-%m-%d %H:%M")
+        );
+
+        out.push_str("<h2>Overview</h2>\n<div class=\"cards\">\n");
+        for (label, value) in self.overview() {
+            let _ = writeln!(out, "<div class=\"card\"><span>{}</span><b>{}</b></div>", label, escape(&value));
+        }
+        out.push_str("</div>\n<h2>Recommendations</h2>\n<ul>\n");
+        for advice in &self.recommendations {
+            let _ = writeln!(out, "<li>{}</li>", escape(advice));
+        }
+        out.push_str("</ul>\n<h2>Memory trend</h2>\n");
+        if self.memory.is_empty() {
+            out.push_str("<p>No memory samples. The timeline is recorded while the tray or a daemon runs.</p>\n");
+        } else {
+            let _ = writeln!(
+                out,
+                "<table><tr><th>{}</th><th>Load</th><th class=\"num\">Average</th><th class=\"num\">Peak</th><th class=\"num\">Peak swap</th></tr>",
+                bucket_name(self.period)
+            );
+            for point in &self.memory {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td class=\"bar\"><div style=\"width:{:.0}%\"></div><i style=\"left:{}%\"></i></td><td class=\"num\">{:.0}%</td><td class=\"num\">{}%</td><td class=\"num\">{} MB</td></tr>",
+                    escape(&point.label),
+                    point.avg_load_percent,
+                    point.peak_load_percent.min(100),
+                    point.avg_load_percent,
+                    point.peak_load_percent,
+                    point.peak_swap_mb
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        out.push_str("<h2>Optimizations</h2>\n");
+        if self.by_trigger.is_empty() {
+            out.push_str("<p>No optimizations ran.</p>\n");
+        } else {
+            out.push_str("<table><tr><th>Trigger</th><th class=\"num\">Runs</th><th class=\"num\">Freed</th></tr>\n");
+            for totals in &self.by_trigger {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.0} MB</td></tr>",
+                    escape(&totals.trigger),
+                    totals.count,
+                    totals.freed_mb
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        let _ = writeln!(out, "<h2>{}</h2>", escape(&self.growth_heading()));
+        if self.growers.is_empty() {
+            out.push_str("<p>No earlier snapshot to compare with yet; the next report will have one.</p>\n");
+        } else {
+            out.push_str("<table><tr><th>App</th><th class=\"num\">Before</th><th class=\"num\">Now</th><th class=\"num\">Change</th></tr>\n");
+            for app in &self.growers {
+                let (before, after) = app_sizes(app);
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td class=\"num\">{:.0} MB</td><td class=\"num\">{:.0} MB</td><td class=\"num\">+{:.0} MB</td></tr>",
+                    escape(&app.name),
+                    before,
+                    after,
+                    app.memory_delta_mb
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        out.push_str("<h2>Leak incidents</h2>\n");
+        if self.leaks.is_empty() {
+            out.push_str("<p>No leak alerts.</p>\n");
+        } else {
+            out.push_str("<table><tr><th>Process</th><th class=\"num\">Alerts</th><th class=\"num\">Max growth</th><th class=\"num\">Last size</th><th>Last alert</th></tr>\n");
+            for leak in &self.leaks {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.0} MB/h</td><td class=\"num\">{:.0} MB</td><td>{}</td></tr>",
+                    escape(&leak.process_name),
+                    leak.alerts,
+                    leak.max_growth_mb_per_hour,
+                    leak.last_memory_mb,
+                    format_time(leak.last_ms, "%Y-%m-%d %H:%M")
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        out.push_str("<h2>Health score</h2>\n");
+        if self.health.is_empty() {
+            out.push_str("<p>No health scores recorded. <code>health</code> records one each time it runs.</p>\n");
+        } else {
+            let _ = writeln!(out, "<table><tr><th>{}</th><th>Score</th><th class=\"num\"></th></tr>", bucket_name(self.period));
+            for point in &self.health {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td class=\"bar health\"><div style=\"width:{:.0}%\"></div></td><td class=\"num\">{:.0}</td></tr>",
+                    escape(&point.label),
+                    point.score,
+                    point.score
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:860px;margin:2em auto;padding:0 1em;color:#222}\
+h1{font-size:1.5em}h2{font-size:1.15em;margin-top:2em;border-bottom:1px solid #ddd}\
+.muted{color:#777}.cards{display:flex;flex-wrap:wrap;gap:.6em}\
+.card{border:1px solid #ddd;border-radius:6px;padding:.5em .8em;min-width:9em}\
+.card span{display:block;font-size:.8em;color:#777}.card b{font-size:1.2em}\
+table{border-collapse:collapse;width:100%}th,td{padding:.25em .5em;border-bottom:1px solid #eee;text-align:left}\
+.num{text-align:right}.bar{position:relative;width:40%}\
+.bar div{height:.8em;background:#5b8def;border-radius:2px}.bar.health div{background:#4caf50}\
+.bar i{position:absolute;top:.2em;width:2px;height:1.2em;background:#d33}";
+
+/// Write a report for the period ending now into `dir`, or `report_dir`
+///
+/// Files are named after the period and the day it ends, so a second
+/// report on the same day replaces the first. Returns the written path.
+pub fn generate(period: ReportPeriod, format: ReportFormat, dir: Option<&Path>) -> Result<PathBuf, String> {
+    let config = OptimizerConfig::load_or_default();
+    let dir = match dir {
+        Some(dir) => dir.to_path_buf(),
+        None => report_dir(&config),
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+
+    let report = Report::build(period, &config);
+    let path = dir.join(format!("report-{}-{}.{}", period, format_time(report.to_ms, "%Y-%m-%d"), format.extension()));
+    std::fs::write(&path, report.render(format)).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    Ok(path)
+}
+
+/// Run a `report-day` or `report-week` schedule with the configured format
+pub fn run_scheduled(period: ReportPeriod) {
+    let format = OptimizerConfig::load_or_default().report_format;
+    match generate(period, format, None) {
+        Ok(path) => tracing::info!("Wrote {} report to {}", period, path.display()),
+        Err(e) => tracing::error!("Scheduled {} report failed: {}", period, e),
+    }
+}
+
+/// `report_dir`, or [`REPORTS_DIR`] in the config directory
+pub fn report_dir(config: &OptimizerConfig) -> PathBuf {
+    config.report_dir.clone().unwrap_or_else(|| crate::features::config_dir().join(REPORTS_DIR))
+}
+
+/// Growth since the newest snapshot from before `from_ms`, or else the
+/// oldest one since
+///
+/// A snapshot is taken and saved first, so the next report has something
+/// to compare with.
+fn app_growth(from_ms: u64) -> Option<SnapshotDiff> {
+    let saved = snapshot::list()
+        .map_err(|e| tracing::warn!("Report without app growth: {}", e))
+        .ok()?;
+    let now = Snapshot::take()
+        .map_err(|e| tracing::warn!("Report without app growth: {}", e))
+        .ok()?;
+    if let Err(e) = now.save(None) {
+        tracing::warn!("Failed to save the report's snapshot: {}", e);
+    }
+
+    let snapshots: Vec<Snapshot> = saved.iter().filter_map(|s| Snapshot::load(&s.path).ok()).collect();
+    let baseline = snapshots
+        .iter()
+        .filter(|s| s.taken_ms <= from_ms)
+        .max_by_key(|s| s.taken_ms)
+        .or_else(|| snapshots.iter().min_by_key(|s| s.taken_ms))?;
+    Some(SnapshotDiff::between(baseline, &now))
+}
+
+/// Average and peak load per bucket
+fn memory_trend(samples: &[MemoryPoint], period: ReportPeriod) -> Vec<TrendPoint> {
+    bucket(samples, |p| p.timestamp_ms, period)
+        .into_iter()
+        .map(|(label, points)| TrendPoint {
+            label,
+            avg_load_percent: points.iter().map(|p| p.load_percent as f64).sum::<f64>() / points.len() as f64,
+            peak_load_percent: points.iter().map(|p| p.load_percent).max().unwrap_or(0),
+            peak_swap_mb: points.iter().map(|p| p.swap_used_mb).max().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Items grouped by local hour or day, in the order they come
+fn bucket<T>(items: &[T], timestamp_ms: impl Fn(&T) -> u64, period: ReportPeriod) -> Vec<(String, Vec<&T>)> {
+    let mut buckets: Vec<(String, Vec<&T>)> = Vec::new();
+    for item in items {
+        let label = format_time(timestamp_ms(item), period.bucket_format());
+        match buckets.last_mut() {
+            Some((last, group)) if *last == label => group.push(item),
+            _ => buckets.push((label, vec![item])),
+        }
+    }
+    buckets
+}
+
+/// Suspects grouped by process name, the most alerted first
+fn leak_incidents(suspects: &[Suspect]) -> Vec<LeakIncident> {
+    let mut incidents: Vec<LeakIncident> = Vec::new();
+    for suspect in suspects {
+        match incidents.iter_mut().find(|i| i.process_name == suspect.process_name) {
+            Some(incident) => {
+                incident.alerts += 1;
+                incident.max_growth_mb_per_hour = incident.max_growth_mb_per_hour.max(suspect.growth_rate_mb_per_hour);
+                if suspect.timestamp_ms >= incident.last_ms {
+                    incident.last_ms = suspect.timestamp_ms;
+                    incident.last_memory_mb = suspect.current_memory_mb;
+                }
+            }
+            None => incidents.push(LeakIncident {
+                process_name: suspect.process_name.clone(),
+                alerts: 1,
+                max_growth_mb_per_hour: suspect.growth_rate_mb_per_hour,
+                last_memory_mb: suspect.current_memory_mb,
+                last_ms: suspect.timestamp_ms,
+            }),
+        }
+    }
+    incidents.sort_by(|a, b| {
+        b.alerts
+            .cmp(&a.alerts)
+            .then(b.max_growth_mb_per_hour.total_cmp(&a.max_growth_mb_per_hour))
+    });
+    incidents
+}
+
+fn readable<T: Default>(what: &str, result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Report without {}: {}", what, e);
+        T::default()
+    })
+}
+
+fn app_sizes(app: &AppChange) -> (f64, f64) {
+    let memory = |totals: &Option<snapshot::AppTotals>| totals.as_ref().map_or(0.0, |t| t.memory_mb);
+    (memory(&app.before), memory(&app.after))
+}
+
+fn bucket_name(period: ReportPeriod) -> &'static str {
+    match period {
+        ReportPeriod::Day => "Hour",
+        ReportPeriod::Week => "Day",
+    }
+}
+
+/// A 0-100 value as a bar of [`BAR_WIDTH`] characters
+fn bar(value: f64) -> String {
+    let filled = ((value.clamp(0.0, 100.0) / 100.0) * BAR_WIDTH as f64).round() as usize;
+    format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn format_time(ms: u64, format: &str) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_else(|| "?".into())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::LeakSeverity;
+
+    fn suspect(name: &str, timestamp_ms: u64, rate: f64, memory_mb: f64) -> Suspect {
+        Suspect {
+            timestamp_ms,
+            pid: 1,
+            process_name: name.to_string(),
+            growth_rate_mb_per_hour: rate,
+            r_squared: 0.9,
+            current_memory_mb: memory_mb,
+            severity: LeakSeverity::Medium,
+            sustained_minutes: 60,
+        }
+    }
+
+    #[test]
+    fn test_leak_incidents_group_by_name() {
+        let incidents = leak_incidents(&[
+            suspect("node", 1_000, 30.0, 800.0),
+            suspect("slack", 2_000, 90.0, 1200.0),
+            suspect("node", 3_000, 50.0, 950.0),
+        ]);
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(incidents[0].process_name, "node");
+        assert_eq!((incidents[0].alerts, incidents[0].max_growth_mb_per_hour), (2, 50.0));
+        assert_eq!((incidents[0].last_ms, incidents[0].last_memory_mb), (3_000, 950.0));
+    }
+
+    #[test]
+    fn test_memory_trend_buckets_by_hour() {
+        let hour = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap().timestamp_millis() as u64;
+        let point = |offset_ms: u64, load: u32| MemoryPoint {
+            timestamp_ms: hour + offset_ms,
+            load_percent: load,
+            used_mb: 0,
+            swap_used_mb: load as u64,
+        };
+        let samples = [point(0, 40), point(1_800_000, 60), point(3_600_000, 90)];
+
+        let trend = memory_trend(&samples, ReportPeriod::Day);
+        assert_eq!(trend.len(), 2);
+        assert_eq!(trend[0].label, "09:00");
+        assert_eq!((trend[0].avg_load_percent, trend[0].peak_load_percent), (50.0, 60));
+        assert_eq!((trend[1].label.as_str(), trend[1].peak_swap_mb), ("10:00", 90));
+    }
+
+    #[test]
+    fn test_html_escapes_names() {
+        assert_eq!(escape("<a&b>"), "&lt;a&amp;b&gt;");
+        assert_eq!(bar(50.0).chars().filter(|&c| c == '█').count(), BAR_WIDTH / 2);
+    }
+}
@@ -0,0 +1,558 @@
+//! System snapshots, and what changed between two of them
+//!
+//! `snapshot take` saves every process's memory, handle and thread counts
+//! and VRAM, with its app category, and each GPU's memory, as JSON under
+//! `snapshots/` in the config directory. `snapshot diff` groups the
+//! processes of two snapshots by name, since PIDs rarely survive from the
+//! morning to the afternoon, and lists the apps that grew and shrank the
+//! most.
+//!
+//! Handle counts come from `GetProcessHandleCount` on Windows and open file
+//! descriptors on Linux, where other users' processes need root. macOS
+//! snapshots have neither.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::apps::{self, leaks, AppCategory};
+use crate::monitor::gpu::GpuMonitor;
+use crate::monitor::procs;
+use crate::core::text::truncate;
+
+/// Directory of saved snapshots inside the config directory
+pub const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Format of the files written by this version
+const FORMAT_VERSION: u32 = 1;
+
+/// Changes smaller than this are noise between two snapshots
+const MIN_CHANGE_MB: f64 = 1.0;
+
+/// One process at snapshot time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub name: String,
+    pub category: AppCategory,
+    pub memory_mb: f64,
+    pub virtual_mb: f64,
+    pub handles: Option<u32>,
+    pub threads: Option<u32>,
+    pub vram_mb: Option<f64>,
+}
+
+/// One GPU at snapshot time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuSnapshot {
+    pub index: u32,
+    pub name: String,
+    pub used_mb: u64,
+    pub total_mb: u64,
+}
+
+/// Everything `snapshot take` records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub host: String,
+    pub taken_ms: u64,
+    pub total_mb: f64,
+    pub available_mb: f64,
+    pub memory_load_percent: u32,
+    pub processes: Vec<ProcessSnapshot>,
+    pub gpus: Vec<GpuSnapshot>,
+}
+
+impl Snapshot {
+    /// Capture this machine now
+    pub fn take() -> Result<Self, String> {
+        let status = crate::platform::create_optimizer().get_memory_status()?;
+        let counts = leaks::resource_counts();
+
+        let gpu = GpuMonitor::new().ok();
+        let mut vram: HashMap<u32, u64> = HashMap::new();
+        let mut gpus = Vec::new();
+        if let Some(ref gpu) = gpu {
+            for process in gpu.get_gpu_processes() {
+                *vram.entry(process.pid).or_default() += process.vram_bytes;
+            }
+            for status in gpu.status() {
+                let name = gpu.gpus().iter().find(|g| g.index == status.gpu_index).map(|g| g.name.clone());
+                gpus.push(GpuSnapshot {
+                    index: status.gpu_index,
+                    name: name.unwrap_or_default(),
+                    used_mb: status.used_mb(),
+                    total_mb: status.total_mb(),
+                });
+            }
+        }
+
+        let mut processes = procs::with_processes(Duration::ZERO, |system| {
+            system
+                .processes()
+                .iter()
+                // Linux lists threads as processes too
+                .filter(|(_, process)| process.thread_kind().is_none())
+                .map(|(pid, process)| {
+                    let pid = pid.as_u32();
+                    let name = process.name().to_string_lossy().to_string();
+                    let resources = counts.get(&pid).copied().unwrap_or_default();
+                    ProcessSnapshot {
+                        pid,
+                        category: apps::categorize(&name),
+                        name,
+                        memory_mb: process.memory() as f64 / 1024.0 / 1024.0,
+                        virtual_mb: process.virtual_memory() as f64 / 1024.0 / 1024.0,
+                        handles: resources.handles,
+                        threads: resources.threads,
+                        vram_mb: vram.get(&pid).map(|&bytes| bytes as f64 / 1024.0 / 1024.0),
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+        processes.sort_by(|a, b| b.memory_mb.total_cmp(&a.memory_mb));
+
+        Ok(Self {
+            version: FORMAT_VERSION,
+            host: crate::features::fleet::host_name(),
+            taken_ms: now_ms(),
+            total_mb: status.total_physical_mb,
+            available_mb: status.available_physical_mb,
+            memory_load_percent: status.memory_load_percent,
+            processes,
+            gpus,
+        })
+    }
+
+    /// Write to `path`, or to a file named after the time it was taken in
+    /// [`SNAPSHOT_DIR`]; returns where it went
+    pub fn save(&self, path: Option<&Path>) -> Result<PathBuf, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let dir = snapshot_dir()?;
+                dir.join(format!("{}.json", format_time(self.taken_ms, "%Y%m%d-%H%M%S")))
+            }
+        };
+        let json = serde_json::to_string(self).map_err(|e| format!("Failed to encode snapshot: {}", e))?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let snapshot: Self =
+            serde_json::from_str(&json).map_err(|e| format!("{:?} is not a snapshot: {}", path, e))?;
+        if snapshot.version > FORMAT_VERSION {
+            return Err(format!("{:?} was written by a newer version", path));
+        }
+        Ok(snapshot)
+    }
+
+    /// Processes grouped by name
+    pub fn apps(&self) -> BTreeMap<String, AppTotals> {
+        let mut apps: BTreeMap<String, AppTotals> = BTreeMap::new();
+        for process in &self.processes {
+            let totals = apps.entry(process.name.clone()).or_insert_with(|| AppTotals {
+                category: process.category,
+                ..AppTotals::default()
+            });
+            totals.processes += 1;
+            totals.memory_mb += process.memory_mb;
+            totals.vram_mb += process.vram_mb.unwrap_or(0.0);
+            if let Some(handles) = process.handles {
+                *totals.handles.get_or_insert(0) += handles as u64;
+            }
+            if let Some(threads) = process.threads {
+                *totals.threads.get_or_insert(0) += threads as u64;
+            }
+        }
+        apps
+    }
+}
+
+/// All processes of one app in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppTotals {
+    pub category: AppCategory,
+    pub processes: usize,
+    pub memory_mb: f64,
+    /// Over the processes whose count could be read
+    pub handles: Option<u64>,
+    pub threads: Option<u64>,
+    pub vram_mb: f64,
+}
+
+impl Default for AppTotals {
+    fn default() -> Self {
+        Self { category: AppCategory::Other, processes: 0, memory_mb: 0.0, handles: None, threads: None, vram_mb: 0.0 }
+    }
+}
+
+/// How one app changed between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppChange {
+    pub name: String,
+    pub category: AppCategory,
+    /// `None` when it wasn't running in that snapshot
+    pub before: Option<AppTotals>,
+    pub after: Option<AppTotals>,
+    pub memory_delta_mb: f64,
+    pub handles_delta: Option<i64>,
+    pub threads_delta: Option<i64>,
+    pub vram_delta_mb: f64,
+}
+
+impl AppChange {
+    fn new(name: String, before: Option<AppTotals>, after: Option<AppTotals>) -> Self {
+        let memory = |t: &Option<AppTotals>| t.as_ref().map_or(0.0, |t| t.memory_mb);
+        let vram = |t: &Option<AppTotals>| t.as_ref().map_or(0.0, |t| t.vram_mb);
+        let delta = |f: fn(&AppTotals) -> Option<u64>| {
+            let before = before.as_ref().map_or(Some(0), f)?;
+            let after = after.as_ref().map_or(Some(0), f)?;
+            Some(after as i64 - before as i64)
+        };
+        Self {
+            category: after.as_ref().or(before.as_ref()).map_or(AppCategory::Other, |t| t.category),
+            memory_delta_mb: memory(&after) - memory(&before),
+            handles_delta: delta(|t| t.handles),
+            threads_delta: delta(|t| t.threads),
+            vram_delta_mb: vram(&after) - vram(&before),
+            name,
+            before,
+            after,
+        }
+    }
+}
+
+/// How one GPU's memory changed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GpuChange {
+    pub index: u32,
+    pub name: String,
+    pub before_mb: u64,
+    pub after_mb: u64,
+}
+
+/// What changed between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub available_before_mb: f64,
+    pub available_after_mb: f64,
+    pub load_before_percent: u32,
+    pub load_after_percent: u32,
+    /// Every app that changed by at least 1 MB, the biggest grower first
+    /// and the biggest shrinker last
+    pub apps: Vec<AppChange>,
+    /// Memory change per category, largest change first
+    pub categories: Vec<(AppCategory, f64)>,
+    pub gpus: Vec<GpuChange>,
+}
+
+impl SnapshotDiff {
+    pub fn between(before: &Snapshot, after: &Snapshot) -> Self {
+        let mut before_apps = before.apps();
+        let mut changes: Vec<AppChange> = after
+            .apps()
+            .into_iter()
+            .map(|(name, totals)| {
+                let previous = before_apps.remove(&name);
+                AppChange::new(name, previous, Some(totals))
+            })
+            .collect();
+        changes.extend(before_apps.into_iter().map(|(name, totals)| AppChange::new(name, Some(totals), None)));
+
+        let mut categories: Vec<(AppCategory, f64)> = Vec::new();
+        for change in &changes {
+            match categories.iter_mut().find(|(category, _)| *category == change.category) {
+                Some((_, delta)) => *delta += change.memory_delta_mb,
+                None => categories.push((change.category, change.memory_delta_mb)),
+            }
+        }
+        categories.retain(|(_, delta)| delta.abs() >= MIN_CHANGE_MB);
+        categories.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+
+        changes.retain(|c| c.memory_delta_mb.abs() >= MIN_CHANGE_MB);
+        changes.sort_by(|a, b| b.memory_delta_mb.total_cmp(&a.memory_delta_mb));
+
+        let gpus = after
+            .gpus
+            .iter()
+            .map(|gpu| GpuChange {
+                index: gpu.index,
+                name: gpu.name.clone(),
+                before_mb: before.gpus.iter().find(|g| g.index == gpu.index).map_or(0, |g| g.used_mb),
+                after_mb: gpu.used_mb,
+            })
+            .collect();
+
+        Self {
+            from_ms: before.taken_ms,
+            to_ms: after.taken_ms,
+            available_before_mb: before.available_mb,
+            available_after_mb: after.available_mb,
+            load_before_percent: before.memory_load_percent,
+            load_after_percent: after.memory_load_percent,
+            apps: changes,
+            categories,
+            gpus,
+        }
+    }
+
+    /// The `limit` apps that grew the most
+    pub fn growers(&self, limit: usize) -> impl Iterator<Item = &AppChange> {
+        self.apps.iter().filter(|c| c.memory_delta_mb > 0.0).take(limit)
+    }
+
+    /// The `limit` apps that shrank the most
+    pub fn shrinkers(&self, limit: usize) -> impl Iterator<Item = &AppChange> {
+        self.apps.iter().rev().filter(|c| c.memory_delta_mb < 0.0).take(limit)
+    }
+
+    pub fn print(&self, limit: usize) {
+        let minutes = self.to_ms.saturating_sub(self.from_ms) / 60_000;
+        println!(
+            "\n📸 Snapshot diff {} → {} ({}h {:02}m)\n",
+            format_time(self.from_ms, "%Y-%m-%d %H:%M"),
+            format_time(self.to_ms, "%Y-%m-%d %H:%M"),
+            minutes / 60,
+            minutes % 60
+        );
+        println!(
+            "  Available  {:.0} MB → {:.0} MB ({:+.0} MB), load {}% → {}%",
+            self.available_before_mb,
+            self.available_after_mb,
+            self.available_after_mb - self.available_before_mb,
+            self.load_before_percent,
+            self.load_after_percent
+        );
+        for gpu in &self.gpus {
+            println!(
+                "  GPU {} {}  {} MB → {} MB ({:+} MB)",
+                gpu.index,
+                gpu.name,
+                gpu.before_mb,
+                gpu.after_mb,
+                gpu.after_mb as i64 - gpu.before_mb as i64
+            );
+        }
+        if !self.categories.is_empty() {
+            let categories: Vec<String> =
+                self.categories.iter().map(|(category, delta)| format!("{:?} {:+.0} MB", category, delta)).collect();
+            println!("  By category  {}", categories.join(", "));
+        }
+
+        print_table("Biggest growers", self.growers(limit));
+        print_table("Biggest shrinkers", self.shrinkers(limit));
+    }
+}
+
+fn print_table<'a>(title: &str, changes: impl Iterator<Item = &'a AppChange>) {
+    let changes: Vec<&AppChange> = changes.collect();
+    println!("\n  {}", title);
+    if changes.is_empty() {
+        println!("  None");
+        return;
+    }
+    println!("┌──────────────────────────────┬────────────────┬──────────┬──────────┬──────────┬───────┬──────────┬─────────┐");
+    println!("│ App                          │ Category       │ Before   │ After    │ Change   │ Procs │ Handles  │ Threads │");
+    println!("├──────────────────────────────┼────────────────┼──────────┼──────────┼──────────┼───────┼──────────┼─────────┤");
+    for change in changes {
+        let mb = |t: &Option<AppTotals>| t.as_ref().map_or("-".to_string(), |t| format!("{:.0} MB", t.memory_mb));
+        let procs = |t: &Option<AppTotals>| t.as_ref().map_or(0, |t| t.processes);
+        let signed = |d: Option<i64>| d.map_or("-".to_string(), |d| format!("{:+}", d));
+        println!(
+            "│ {:28} │ {:14} │ {:>8} │ {:>8} │ {:>8} │ {:>5} │ {:>8} │ {:>7} │",
+            truncate(&change.name, 28),
+            format!("{:?}", change.category),
+            mb(&change.before),
+            mb(&change.after),
+            format!("{:+.0} MB", change.memory_delta_mb),
+            format!("{:+}", procs(&change.after) as i64 - procs(&change.before) as i64),
+            signed(change.handles_delta),
+            signed(change.threads_delta)
+        );
+    }
+    println!("└──────────────────────────────┴────────────────┴──────────┴──────────┴──────────┴───────┴──────────┴─────────┘");
+}
+
+/// A saved snapshot
+#[derive(Debug, Clone)]
+pub struct SavedSnapshot {
+    pub path: PathBuf,
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// Snapshots in [`SNAPSHOT_DIR`], oldest first
+pub fn list() -> Result<Vec<SavedSnapshot>, String> {
+    let dir = snapshot_dir()?;
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+    let mut saved: Vec<SavedSnapshot> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .map(|entry| SavedSnapshot {
+            name: entry.path().file_stem().unwrap_or_default().to_string_lossy().to_string(),
+            size_bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            path: entry.path(),
+        })
+        .collect();
+    // Names are timestamps, so they sort by age
+    saved.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(saved)
+}
+
+pub fn print_list(saved: &[SavedSnapshot]) {
+    if saved.is_empty() {
+        println!("No snapshots yet; take one with `snapshot take`.");
+        return;
+    }
+    for snapshot in saved {
+        println!("  {}  ({} KB)", snapshot.name, snapshot.size_bytes / 1024);
+    }
+}
+
+/// Path of a snapshot given as a file, a name from [`list`], or `latest`
+pub fn resolve(name: &str) -> Result<PathBuf, String> {
+    if name == "latest" {
+        return list()?
+            .pop()
+            .map(|s| s.path)
+            .ok_or_else(|| "No snapshots yet; take one with `snapshot take`".to_string());
+    }
+    let path = PathBuf::from(name);
+    if path.is_file() {
+        return Ok(path);
+    }
+    let saved = snapshot_dir()?.join(format!("{}.json", name.trim_end_matches(".json")));
+    if saved.is_file() {
+        return Ok(saved);
+    }
+    Err(format!("No snapshot '{}'", name))
+}
+
+fn snapshot_dir() -> Result<PathBuf, String> {
+    let dir = crate::features::config_dir().join(SNAPSHOT_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+fn format_time(ms: u64, format: &str) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_else(|| "?".into())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, memory_mb: f64, handles: Option<u32>) -> ProcessSnapshot {
+        ProcessSnapshot {
+            pid,
+            name: name.to_string(),
+            category: apps::categorize(name),
+            memory_mb,
+            virtual_mb: memory_mb * 2.0,
+            handles,
+            threads: Some(10),
+            vram_mb: None,
+        }
+    }
+
+    fn snapshot(taken_ms: u64, processes: Vec<ProcessSnapshot>) -> Snapshot {
+        Snapshot {
+            version: FORMAT_VERSION,
+            host: "test".to_string(),
+            taken_ms,
+            total_mb: 16384.0,
+            available_mb: 8192.0,
+            memory_load_percent: 50,
+            processes,
+            gpus: vec![],
+        }
+    }
+
+    #[test]
+    fn test_categorize() {
+        assert_eq!(apps::categorize("chrome.exe"), AppCategory::Browser);
+        assert_eq!(apps::categorize("Slack Helper (Renderer)"), AppCategory::Communication);
+        assert_eq!(apps::categorize("Vmmem"), AppCategory::VirtualMachine);
+        assert_eq!(apps::categorize("ollama"), AppCategory::AI);
+        assert_eq!(apps::categorize("notepad.exe"), AppCategory::Other);
+    }
+
+    #[test]
+    fn test_diff() {
+        let morning = snapshot(
+            0,
+            vec![
+                process(1, "chrome.exe", 500.0, Some(1000)),
+                process(2, "chrome.exe", 300.0, Some(500)),
+                process(3, "ollama", 4000.0, None),
+                process(4, "notepad.exe", 20.0, Some(50)),
+            ],
+        );
+        let now = snapshot(
+            3_600_000,
+            vec![
+                process(10, "chrome.exe", 900.0, Some(1200)),
+                process(11, "chrome.exe", 400.0, Some(600)),
+                process(12, "chrome.exe", 200.0, Some(100)),
+                process(3, "ollama", 1000.0, None),
+                process(4, "notepad.exe", 20.5, Some(50)),
+                process(5, "cargo", 250.0, Some(80)),
+            ],
+        );
+
+        let apps = now.apps();
+        assert_eq!(apps["chrome.exe"].processes, 3);
+        assert_eq!(apps["chrome.exe"].handles, Some(1900));
+        assert_eq!(apps["ollama"].handles, None);
+
+        let diff = SnapshotDiff::between(&morning, &now);
+        let growers: Vec<&str> = diff.growers(10).map(|c| c.name.as_str()).collect();
+        let shrinkers: Vec<&str> = diff.shrinkers(10).map(|c| c.name.as_str()).collect();
+        assert_eq!(growers, ["chrome.exe", "cargo"], "notepad moved less than 1 MB");
+        assert_eq!(shrinkers, ["ollama"]);
+
+        let chrome = &diff.apps[0];
+        assert_eq!(chrome.memory_delta_mb, 700.0);
+        assert_eq!(chrome.handles_delta, Some(400));
+        assert_eq!(chrome.threads_delta, Some(10));
+        assert!(diff.apps.iter().find(|c| c.name == "cargo").unwrap().before.is_none());
+        assert_eq!(diff.growers(1).count(), 1);
+
+        assert_eq!(diff.categories[0], (AppCategory::AI, -3000.0));
+        assert_eq!(diff.categories[1], (AppCategory::Browser, 700.0));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("ruvector-snapshot-{}.json", std::process::id()));
+        let original = snapshot(42, vec![process(1, "chrome.exe", 500.0, Some(1000))]);
+        assert_eq!(original.save(Some(&path)).unwrap(), path);
+        assert_eq!(Snapshot::load(&path).unwrap(), original);
+        assert_eq!(resolve(path.to_str().unwrap()).unwrap(), path);
+
+        let newer = Snapshot { version: FORMAT_VERSION + 1, ..original };
+        newer.save(Some(&path)).unwrap();
+        assert!(Snapshot::load(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,252 @@
+//! Linux startup sources: systemd user units and XDG autostart entries
+//!
+//! Autostart entries are changed through a copy in the user's autostart
+//! directory, which overrides the system-wide entry of the same name as the
+//! XDG spec describes. Delays use `X-GNOME-Autostart-Delay` for autostart
+//! entries and an `ExecStartPre=sleep` drop-in for systemd units.
+
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::{StartupItem, StartupSource, StartupState};
+
+/// Drop-in file holding our start delay for a unit
+const DELAY_DROP_IN: &str = "ruvector-delay.conf";
+
+pub fn scan() -> Vec<StartupItem> {
+    let mut items = scan_systemd();
+    items.extend(scan_autostart());
+    items
+}
+
+fn scan_systemd() -> Vec<StartupItem> {
+    let output = match run(
+        "systemctl",
+        &["--user", "list-unit-files", "--type=service", "--state=enabled", "--no-legend", "--no-pager"],
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::debug!("systemd user units unavailable: {}", e);
+            return Vec::new();
+        }
+    };
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        // Template units only run through their instances
+        .filter(|unit| !unit.contains("@."))
+        .map(|unit| {
+            let stem = unit.trim_end_matches(".service");
+            let delay = drop_in_path(unit)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|content| parse_sleep_delay(&content));
+            StartupItem {
+                name: stem.to_string(),
+                command: stem.to_string(),
+                source: StartupSource::SystemdUser,
+                location: unit.to_string(),
+                state: match delay {
+                    Some(secs) => StartupState::Delayed { secs },
+                    None => StartupState::Enabled,
+                },
+                impact: Default::default(),
+            }
+        })
+        .collect()
+}
+
+fn scan_autostart() -> Vec<StartupItem> {
+    let mut items: Vec<StartupItem> = Vec::new();
+    // User entries first so they shadow system entries with the same file name
+    let dirs = user_autostart_dir().into_iter().chain(std::iter::once(PathBuf::from("/etc/xdg/autostart")));
+    for dir in dirs {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if !file_name.ends_with(".desktop") || items.iter().any(|i| i.location == file_name) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(entry.path()) else { continue };
+            if let Some(item) = parse_desktop_entry(&file_name, &content) {
+                items.push(item);
+            }
+        }
+    }
+    items
+}
+
+/// Value of `key` in the `[Desktop Entry]` group
+fn desktop_value<'a>(content: &'a str, key: &str) -> Option<&'a str> {
+    let mut in_entry = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        if let Some((k, v)) = line.split_once('=') {
+            if k.trim() == key {
+                return Some(v.trim());
+            }
+        }
+    }
+    None
+}
+
+/// Startup item from an autostart `.desktop` file
+pub fn parse_desktop_entry(file_name: &str, content: &str) -> Option<StartupItem> {
+    let exec = desktop_value(content, "Exec")?;
+    let name = desktop_value(content, "Name").unwrap_or_else(|| file_name.trim_end_matches(".desktop"));
+    let hidden = desktop_value(content, "Hidden") == Some("true");
+    let gnome_off = desktop_value(content, "X-GNOME-Autostart-enabled") == Some("false");
+    let delay = desktop_value(content, "X-GNOME-Autostart-Delay").and_then(|d| d.parse::<u32>().ok()).unwrap_or(0);
+    let state = if hidden || gnome_off {
+        StartupState::Disabled
+    } else if delay > 0 {
+        StartupState::Delayed { secs: delay }
+    } else {
+        StartupState::Enabled
+    };
+    Some(StartupItem {
+        name: name.to_string(),
+        // Field codes like %U are filled in by the launcher
+        command: exec.split_whitespace().filter(|a| !a.starts_with('%')).collect::<Vec<_>>().join(" "),
+        source: StartupSource::XdgAutostart,
+        location: file_name.to_string(),
+        state,
+        impact: Default::default(),
+    })
+}
+
+/// Set (or with `None`, remove) `key` in the `[Desktop Entry]` group
+pub fn set_desktop_value(content: &str, key: &str, value: Option<&str>) -> String {
+    let mut out = Vec::new();
+    let mut in_entry = false;
+    let mut written = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_entry && !written {
+                if let Some(v) = value {
+                    out.push(format!("{}={}", key, v));
+                }
+                written = true;
+            }
+            in_entry = trimmed == "[Desktop Entry]";
+        } else if in_entry && trimmed.split_once('=').is_some_and(|(k, _)| k.trim() == key) {
+            if let Some(v) = value {
+                if !written {
+                    out.push(format!("{}={}", key, v));
+                }
+            }
+            written = true;
+            continue;
+        }
+        out.push(line.to_string());
+    }
+    if in_entry && !written {
+        if let Some(v) = value {
+            out.push(format!("{}={}", key, v));
+        }
+    }
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Delay from our drop-in's `ExecStartPre=/bin/sleep N` line
+fn parse_sleep_delay(content: &str) -> Option<u32> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("ExecStartPre="))
+        .and_then(|cmd| cmd.split_whitespace().nth(1))
+        .and_then(|secs| secs.parse().ok())
+}
+
+pub fn set_state(item: &StartupItem, state: StartupState) -> Result<(), String> {
+    match item.source {
+        StartupSource::SystemdUser => set_unit_state(&item.location, state),
+        StartupSource::XdgAutostart => set_autostart_state(&item.location, state),
+        _ => Err(format!("{} items are not managed on Linux", item.source.label())),
+    }
+}
+
+fn set_unit_state(unit: &str, state: StartupState) -> Result<(), String> {
+    let drop_in = drop_in_path(unit).ok_or("Cannot locate the systemd user config directory")?;
+    match state {
+        StartupState::Disabled => {
+            run("systemctl", &["--user", "disable", unit])?;
+        }
+        StartupState::Enabled => {
+            if drop_in.exists() {
+                fs::remove_file(&drop_in).map_err(|e| format!("Failed to remove {:?}: {}", drop_in, e))?;
+            }
+            run("systemctl", &["--user", "enable", unit])?;
+        }
+        StartupState::Delayed { secs } => {
+            if let Some(dir) = drop_in.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+            }
+            let content = format!(
+                "# Written by ruvector-memopt `startup delay`\n[Service]\nExecStartPre=/bin/sleep {}\nTimeoutStartSec={}\n",
+                secs,
+                secs + 90
+            );
+            fs::write(&drop_in, content).map_err(|e| format!("Failed to write {:?}: {}", drop_in, e))?;
+            run("systemctl", &["--user", "enable", unit])?;
+        }
+    }
+    run("systemctl", &["--user", "daemon-reload"]).map(|_| ())
+}
+
+fn set_autostart_state(file_name: &str, state: StartupState) -> Result<(), String> {
+    let user_dir = user_autostart_dir().ok_or("Cannot locate the autostart directory")?;
+    let user_file = user_dir.join(file_name);
+    let content = fs::read_to_string(&user_file)
+        .or_else(|_| fs::read_to_string(PathBuf::from("/etc/xdg/autostart").join(file_name)))
+        .map_err(|e| format!("Failed to read {}: {}", file_name, e))?;
+
+    let (hidden, delay) = match state {
+        StartupState::Disabled => ("true", None),
+        StartupState::Enabled => ("false", None),
+        StartupState::Delayed { secs } => ("false", Some(secs.to_string())),
+    };
+    let mut content = set_desktop_value(&content, "Hidden", Some(hidden));
+    // Clear GNOME's own switch so Hidden alone decides
+    content = set_desktop_value(&content, "X-GNOME-Autostart-enabled", None);
+    content = set_desktop_value(&content, "X-GNOME-Autostart-Delay", delay.as_deref());
+
+    fs::create_dir_all(&user_dir).map_err(|e| format!("Failed to create {:?}: {}", user_dir, e))?;
+    fs::write(&user_file, content).map_err(|e| format!("Failed to write {:?}: {}", user_file, e))
+}
+
+fn config_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+fn user_autostart_dir() -> Option<PathBuf> {
+    config_home().map(|dir| dir.join("autostart"))
+}
+
+fn drop_in_path(unit: &str) -> Option<PathBuf> {
+    config_home().map(|dir| dir.join("systemd/user").join(format!("{}.d", unit)).join(DELAY_DROP_IN))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
@@ -0,0 +1,185 @@
+//! macOS startup sources: LaunchAgents, LaunchDaemons and login items
+//!
+//! Launchd jobs are disabled with `launchctl disable`, which is what the
+//! Login Items settings pane does for background items. The plist stays in
+//! place. Login items are removed through System Events and re-added from
+//! the change journal. Launchd has no start delay, so delaying isn't offered.
+
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{StartupItem, StartupSource, StartupState};
+
+const LOGIN_ITEMS_SCRIPT: &str = r#"set out to ""
+tell application "System Events"
+    repeat with i in login items
+        set out to out & (name of i) & "|" & (path of i) & linefeed
+    end repeat
+end tell
+return out"#;
+
+pub fn scan() -> Vec<StartupItem> {
+    let mut items = Vec::new();
+    let uid = current_uid();
+
+    let user_disabled = launchctl_disabled(&format!("gui/{}", uid));
+    let system_disabled = launchctl_disabled("system");
+    let mut dirs: Vec<(PathBuf, StartupSource)> = vec![
+        (PathBuf::from("/Library/LaunchAgents"), StartupSource::LaunchAgent),
+        (PathBuf::from("/Library/LaunchDaemons"), StartupSource::LaunchDaemon),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.insert(0, (PathBuf::from(home).join("Library/LaunchAgents"), StartupSource::LaunchAgent));
+    }
+    for (dir, source) in dirs {
+        let disabled = if source == StartupSource::LaunchDaemon { &system_disabled } else { &user_disabled };
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "plist") {
+                if let Some(item) = read_job(&path, source, disabled) {
+                    items.push(item);
+                }
+            }
+        }
+    }
+
+    if let Ok(output) = run("osascript", &["-e", LOGIN_ITEMS_SCRIPT]) {
+        items.extend(parse_login_items(&output));
+    }
+    items
+}
+
+/// A launchd job that runs at load, from its plist
+fn read_job(path: &Path, source: StartupSource, disabled: &[(String, bool)]) -> Option<StartupItem> {
+    let json = run("plutil", &["-convert", "json", "-o", "-", &path.to_string_lossy()]).ok()?;
+    let plist: serde_json::Value = serde_json::from_str(&json).ok()?;
+    let label = plist.get("Label")?.as_str()?.to_string();
+    let run_at_load = plist.get("RunAtLoad").and_then(|v| v.as_bool()).unwrap_or(false);
+    let keep_alive = plist.get("KeepAlive").is_some_and(|v| v.as_bool() != Some(false));
+    if !run_at_load && !keep_alive {
+        return None;
+    }
+
+    let command = match plist.get("ProgramArguments").and_then(|v| v.as_array()) {
+        Some(args) => args.iter().filter_map(|a| a.as_str()).collect::<Vec<_>>().join(" "),
+        None => plist.get("Program").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+    };
+    // launchctl overrides win over the plist's own Disabled key
+    let off = disabled
+        .iter()
+        .find(|(l, _)| *l == label)
+        .map(|(_, d)| *d)
+        .unwrap_or_else(|| plist.get("Disabled").and_then(|v| v.as_bool()).unwrap_or(false));
+    Some(StartupItem {
+        name: label.clone(),
+        command,
+        source,
+        location: label,
+        state: if off { StartupState::Disabled } else { StartupState::Enabled },
+        impact: Default::default(),
+    })
+}
+
+fn launchctl_disabled(domain: &str) -> Vec<(String, bool)> {
+    run("launchctl", &["print-disabled", domain]).map(|out| parse_print_disabled(&out)).unwrap_or_default()
+}
+
+/// `"label" => disabled|enabled|true|false` lines from `launchctl print-disabled`
+pub fn parse_print_disabled(output: &str) -> Vec<(String, bool)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (label, value) = line.trim().split_once("=>")?;
+            let label = label.trim().trim_matches('"').to_string();
+            let disabled = match value.trim() {
+                "disabled" | "true" => true,
+                "enabled" | "false" => false,
+                _ => return None,
+            };
+            Some((label, disabled))
+        })
+        .collect()
+}
+
+/// `name|path` lines from [`LOGIN_ITEMS_SCRIPT`]
+pub fn parse_login_items(output: &str) -> Vec<StartupItem> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, path) = line.trim().split_once('|')?;
+            Some(StartupItem {
+                name: name.to_string(),
+                command: path.to_string(),
+                source: StartupSource::LoginItem,
+                location: path.to_string(),
+                state: StartupState::Enabled,
+                impact: Default::default(),
+            })
+        })
+        .collect()
+}
+
+pub fn set_state(item: &StartupItem, state: StartupState) -> Result<(), String> {
+    if let StartupState::Delayed { .. } = state {
+        return Err("launchd has no start delay; disable the item instead".into());
+    }
+    let enable = state == StartupState::Enabled;
+    match item.source {
+        StartupSource::LaunchAgent | StartupSource::LaunchDaemon => {
+            let domain = if item.source == StartupSource::LaunchDaemon {
+                "system".to_string()
+            } else {
+                format!("gui/{}", current_uid())
+            };
+            let target = format!("{}/{}", domain, item.location);
+            let verb = if enable { "enable" } else { "disable" };
+            run("launchctl", &[verb, &target]).map(|_| ()).map_err(|e| {
+                if item.source == StartupSource::LaunchDaemon && current_uid() != 0 {
+                    "Changing LaunchDaemons needs root (sudo)".to_string()
+                } else {
+                    e
+                }
+            })
+        }
+        StartupSource::LoginItem => {
+            let script = if enable {
+                format!(
+                    r#"tell application "System Events" to make login item at end with properties {{path:"{}", hidden:false}}"#,
+                    escape(&item.location)
+                )
+            } else {
+                format!(r#"tell application "System Events" to delete login item "{}""#, escape(&item.name))
+            };
+            run("osascript", &["-e", &script]).map(|_| ())
+        }
+        _ => Err(format!("{} items are not managed on macOS", item.source.label())),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> u32 {
+    0
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
@@ -0,0 +1,573 @@
+//! Startup program analyzer and manager (ADR-015)
+//!
+//! Enumerates everything that starts with the session:
+//!
+//! - Windows: Run keys, Startup folders, logon/boot scheduled tasks and
+//!   third-party auto-start services
+//! - macOS: LaunchAgents, LaunchDaemons and login items
+//! - Linux: enabled systemd user units and XDG autostart entries
+//!
+//! Each item gets a boot impact estimate from its current memory footprint,
+//! or a known-heavy-app heuristic when it isn't running. Items can be
+//! disabled, re-enabled or delayed using the platform's own mechanism, so
+//! the OS settings UI shows the same state. Every change is appended to
+//! `startup_changes.jsonl`; that keeps items whose entry disappears when
+//! disabled (services set to manual, removed login items) listed and
+//! re-enableable.
+
+#[cfg(any(target_os = "linux", test))]
+mod linux;
+#[cfg(any(target_os = "macos", test))]
+mod macos;
+#[cfg(any(windows, test))]
+mod windows;
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessesToUpdate, System};
+use crate::core::text::truncate;
+
+/// File name of the change journal inside the config directory
+pub const CHANGES_FILE: &str = "startup_changes.jsonl";
+
+/// Default `startup delay` in seconds
+pub const DEFAULT_DELAY_SECS: u32 = 60;
+
+/// Security software is never disabled or delayed
+const SECURITY_SOFTWARE: &[&str] = &[
+    "securityhealth", "windows defender", "msmpeng", "malwarebytes", "mbam", "norton", "kaspersky",
+    "bitdefender", "egui", "ekrn", "avast", "avgui", "mcafee", "sophos", "crowdstrike", "csfalcon", "sentinel",
+    "xprotect", "littlesnitch", "clamav",
+];
+
+/// Apps known to load heavily at login even when their footprint is unknown
+const HEAVY_APPS: &[&str] = &[
+    "teams", "ms-teams", "onedrive", "dropbox", "googledrivefs", "spotify", "discord", "steam",
+    "epicgameslauncher", "creative cloud", "adobe", "slack", "zoom", "skype", "icloud", "battle.net",
+    "origin", "eadesktop", "java", "docker",
+];
+
+/// Where a startup entry lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupSource {
+    /// `HKCU\...\CurrentVersion\Run`
+    RunKeyUser,
+    /// `HKLM\...\CurrentVersion\Run`
+    RunKeyMachine,
+    StartupFolder,
+    ScheduledTask,
+    Service,
+    LaunchAgent,
+    LaunchDaemon,
+    LoginItem,
+    SystemdUser,
+    XdgAutostart,
+}
+
+impl StartupSource {
+    /// Prefix of item ids
+    pub fn key(&self) -> &'static str {
+        match self {
+            StartupSource::RunKeyUser => "run",
+            StartupSource::RunKeyMachine => "run-machine",
+            StartupSource::StartupFolder => "folder",
+            StartupSource::ScheduledTask => "task",
+            StartupSource::Service => "service",
+            StartupSource::LaunchAgent => "agent",
+            StartupSource::LaunchDaemon => "daemon",
+            StartupSource::LoginItem => "login",
+            StartupSource::SystemdUser => "systemd",
+            StartupSource::XdgAutostart => "autostart",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StartupSource::RunKeyUser => "Run (user)",
+            StartupSource::RunKeyMachine => "Run (all)",
+            StartupSource::StartupFolder => "Folder",
+            StartupSource::ScheduledTask => "Task",
+            StartupSource::Service => "Service",
+            StartupSource::LaunchAgent => "Agent",
+            StartupSource::LaunchDaemon => "Daemon",
+            StartupSource::LoginItem => "Login item",
+            StartupSource::SystemdUser => "systemd",
+            StartupSource::XdgAutostart => "Autostart",
+        }
+    }
+
+    /// Services, daemons and machine-wide entries need admin/root to change
+    pub fn needs_elevation(&self) -> bool {
+        matches!(self, StartupSource::RunKeyMachine | StartupSource::Service | StartupSource::LaunchDaemon)
+    }
+}
+
+/// Whether an item runs at login
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupState {
+    Enabled,
+    Disabled,
+    Delayed { secs: u32 },
+}
+
+impl std::fmt::Display for StartupState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupState::Enabled => write!(f, "enabled"),
+            StartupState::Disabled => write!(f, "disabled"),
+            StartupState::Delayed { secs } => write!(f, "delay {}s", secs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImpactLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for ImpactLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImpactLevel::Low => write!(f, "Low"),
+            ImpactLevel::Medium => write!(f, "Medium"),
+            ImpactLevel::High => write!(f, "High"),
+        }
+    }
+}
+
+/// Estimated cost of an item at login
+#[derive(Debug, Clone, Copy)]
+pub struct BootImpact {
+    pub level: ImpactLevel,
+    /// Resident memory of the running program, if it is running now
+    pub memory_mb: Option<f64>,
+    /// Rough contribution to login time
+    pub estimated_secs: f64,
+}
+
+impl Default for BootImpact {
+    fn default() -> Self {
+        Self { level: ImpactLevel::Medium, memory_mb: None, estimated_secs: 0.8 }
+    }
+}
+
+/// One startup entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupItem {
+    pub name: String,
+    pub command: String,
+    pub source: StartupSource,
+    /// Registry key, file path, task path, label or unit that identifies the entry
+    pub location: String,
+    pub state: StartupState,
+    #[serde(skip)]
+    pub impact: BootImpact,
+}
+
+impl StartupItem {
+    /// `source:name`, unique per machine
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.source.key(), self.name)
+    }
+
+    /// File stem of the program the command runs, lowercased
+    pub fn program(&self) -> String {
+        program_stem(&self.command).unwrap_or_else(|| self.name.to_lowercase())
+    }
+
+    pub fn is_security_software(&self) -> bool {
+        let name = self.name.to_lowercase();
+        let program = self.program();
+        SECURITY_SOFTWARE.iter().any(|s| name.contains(s) || program.contains(s))
+    }
+}
+
+/// Program file stem from a command line, handling quotes and arguments
+fn program_stem(command: &str) -> Option<String> {
+    let command = command.trim();
+    let path = if let Some(rest) = command.strip_prefix('"') {
+        rest.split('"').next()?
+    } else {
+        // Unquoted Windows paths may contain spaces; cut at the executable
+        let lower = command.to_ascii_lowercase();
+        match lower.find(".exe") {
+            Some(end) => &command[..end + 4],
+            None => command.split_whitespace().next()?,
+        }
+    };
+    let file = path.rsplit(['/', '\\']).next()?;
+    let stem = file.strip_suffix(".exe").or_else(|| file.strip_suffix(".EXE")).unwrap_or(file);
+    (!stem.is_empty()).then(|| stem.to_lowercase())
+}
+
+/// Estimate login cost from the running process footprint or known names
+///
+/// `running` maps lowercased program stems to resident MB.
+pub fn estimate_impact(item: &StartupItem, running: &HashMap<String, f64>) -> BootImpact {
+    let program = item.program();
+    if let Some(&memory_mb) = running.get(&program) {
+        // Loading a program's working set dominates its login cost
+        let estimated_secs = 0.2 + memory_mb / 200.0;
+        let level = if memory_mb >= 250.0 {
+            ImpactLevel::High
+        } else if memory_mb >= 80.0 {
+            ImpactLevel::Medium
+        } else {
+            ImpactLevel::Low
+        };
+        return BootImpact { level, memory_mb: Some(memory_mb), estimated_secs };
+    }
+
+    let name = item.name.to_lowercase();
+    if HEAVY_APPS.iter().any(|app| name.contains(app) || program.contains(app)) {
+        return BootImpact { level: ImpactLevel::High, memory_mb: None, estimated_secs: 2.0 };
+    }
+    match item.source {
+        StartupSource::Service | StartupSource::LaunchDaemon | StartupSource::SystemdUser | StartupSource::ScheduledTask => {
+            BootImpact { level: ImpactLevel::Low, memory_mb: None, estimated_secs: 0.3 }
+        }
+        _ => BootImpact::default(),
+    }
+}
+
+/// One recorded change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupChange {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub item: StartupItem,
+    pub previous: StartupState,
+}
+
+/// Append-only log of startup changes
+pub struct StartupJournal {
+    path: Option<PathBuf>,
+    changes: Vec<StartupChange>,
+}
+
+impl StartupJournal {
+    /// In-memory journal that is never written to disk
+    pub fn new() -> Self {
+        Self { path: None, changes: Vec::new() }
+    }
+
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_file(CHANGES_FILE)?)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut changes = Vec::new();
+        if path.exists() {
+            let file = File::open(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if let Ok(change) = serde_json::from_str(&line) {
+                    changes.push(change);
+                }
+            }
+        }
+        Ok(Self { path: Some(path), changes })
+    }
+
+    pub fn changes(&self) -> &[StartupChange] {
+        &self.changes
+    }
+
+    pub fn record(&mut self, item: &StartupItem, previous: StartupState) -> Result<(), String> {
+        let change = StartupChange {
+            timestamp_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+            item: item.clone(),
+            previous,
+        };
+        if let Some(ref path) = self.path {
+            let line = serde_json::to_string(&change).map_err(|e| e.to_string())?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+            writeln!(file, "{}", line).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        }
+        self.changes.push(change);
+        Ok(())
+    }
+
+    /// Latest recorded version of each item, keyed by id
+    pub fn latest(&self) -> HashMap<String, &StartupItem> {
+        self.changes.iter().map(|c| (c.item.id(), &c.item)).collect()
+    }
+}
+
+impl Default for StartupJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Scans startup items and applies changes
+pub struct StartupManager {
+    items: Vec<StartupItem>,
+    journal: StartupJournal,
+}
+
+impl StartupManager {
+    pub fn new() -> Self {
+        let journal = StartupJournal::open_default().unwrap_or_else(|e| {
+            tracing::warn!("Startup change journal unavailable: {}", e);
+            StartupJournal::new()
+        });
+        let mut manager = Self { items: Vec::new(), journal };
+        manager.refresh();
+        manager
+    }
+
+    /// Re-read every startup source and estimate impacts
+    pub fn refresh(&mut self) {
+        let mut items = sys::scan();
+
+        // Entries that vanish when disabled are only known from the journal
+        for (id, item) in self.journal.latest() {
+            if item.state == StartupState::Disabled && !items.iter().any(|i| i.id() == id) {
+                items.push(item.clone());
+            }
+        }
+
+        let running = running_programs();
+        for item in &mut items {
+            item.impact = estimate_impact(item, &running);
+        }
+        items.sort_by(|a, b| {
+            (b.state != StartupState::Disabled)
+                .cmp(&(a.state != StartupState::Disabled))
+                .then(b.impact.estimated_secs.partial_cmp(&a.impact.estimated_secs).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        self.items = items;
+    }
+
+    /// Items ranked by impact, disabled ones last
+    pub fn items(&self) -> &[StartupItem] {
+        &self.items
+    }
+
+    /// Find an item by id or case-insensitive name
+    pub fn find(&self, query: &str) -> Result<&StartupItem, String> {
+        if let Some(item) = self.items.iter().find(|i| i.id().eq_ignore_ascii_case(query)) {
+            return Ok(item);
+        }
+        let matches: Vec<&StartupItem> = self.items.iter().filter(|i| i.name.eq_ignore_ascii_case(query)).collect();
+        match matches.as_slice() {
+            [item] => Ok(item),
+            [] => Err(format!("No startup item named '{}'. Run `startup list --all` to see them.", query)),
+            several => Err(format!(
+                "'{}' is ambiguous; use one of: {}",
+                query,
+                several.iter().map(|i| i.id()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    pub fn disable(&mut self, query: &str) -> Result<StartupItem, String> {
+        self.apply(query, StartupState::Disabled)
+    }
+
+    pub fn enable(&mut self, query: &str) -> Result<StartupItem, String> {
+        self.apply(query, StartupState::Enabled)
+    }
+
+    /// Start the item `secs` after login instead of immediately
+    pub fn delay(&mut self, query: &str, secs: u32) -> Result<StartupItem, String> {
+        if secs == 0 {
+            return self.enable(query);
+        }
+        self.apply(query, StartupState::Delayed { secs })
+    }
+
+    fn apply(&mut self, query: &str, state: StartupState) -> Result<StartupItem, String> {
+        let item = self.find(query)?.clone();
+        if item.state == state {
+            return Err(format!("{} is already {}", item.name, state));
+        }
+        if state != StartupState::Enabled && item.is_security_software() {
+            return Err(format!("{} is security software and stays enabled", item.name));
+        }
+
+        sys::set_state(&item, state).map_err(|e| {
+            if item.source.needs_elevation() {
+                format!("{} ({} items need administrator/root rights)", e, item.source.label())
+            } else {
+                e
+            }
+        })?;
+
+        let previous = item.state;
+        let mut changed = item;
+        changed.state = state;
+        if let Err(e) = self.journal.record(&changed, previous) {
+            tracing::warn!("Failed to record startup change: {}", e);
+        }
+        if let Some(existing) = self.items.iter_mut().find(|i| i.id() == changed.id()) {
+            existing.state = state;
+        }
+        Ok(changed)
+    }
+
+    pub fn print_table(&self, include_disabled: bool) {
+        println!("\n🚀 Startup Programs\n");
+        let shown: Vec<&StartupItem> = self
+            .items
+            .iter()
+            .filter(|i| include_disabled || i.state != StartupState::Disabled)
+            .collect();
+        if shown.is_empty() {
+            println!("  No startup items found.");
+            return;
+        }
+
+        println!("┌────────┬──────────────────────────┬────────────┬────────────┬─────────┬─────────┐");
+        println!("│ Impact │ Name                     │ Source     │ State      │ Memory  │ Est.    │");
+        println!("├────────┼──────────────────────────┼────────────┼────────────┼─────────┼─────────┤");
+        for item in &shown {
+            let memory = item.impact.memory_mb.map(|m| format!("{:.0} MB", m)).unwrap_or_else(|| "-".into());
+            println!(
+                "│ {:6} │ {:24} │ {:10} │ {:10} │ {:>7} │ {:>6.1}s │",
+                item.impact.level.to_string(),
+                truncate(&item.name, 24),
+                item.source.label(),
+                item.state.to_string(),
+                memory,
+                item.impact.estimated_secs
+            );
+        }
+        println!("└────────┴──────────────────────────┴────────────┴────────────┴─────────┴─────────┘");
+
+        let active: Vec<&&StartupItem> = shown.iter().filter(|i| i.state == StartupState::Enabled).collect();
+        let total: f64 = active.iter().map(|i| i.impact.estimated_secs).sum();
+        let heavy = active.iter().filter(|i| i.impact.level == ImpactLevel::High).count();
+        println!("\n  {} item(s) start immediately, ~{:.0}s estimated login cost.", active.len(), total);
+        if heavy > 0 {
+            println!("  💡 {} high-impact item(s): `startup delay <name>` or `startup disable <name>`", heavy);
+        }
+    }
+}
+
+impl Default for StartupManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resident MB of each running program, summed across its processes
+fn running_programs() -> HashMap<String, f64> {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let mut running: HashMap<String, f64> = HashMap::new();
+    for process in system.processes().values() {
+        let name = process.name().to_string_lossy().to_lowercase();
+        let stem = name.strip_suffix(".exe").unwrap_or(&name).to_string();
+        *running.entry(stem).or_default() += process.memory() as f64 / 1024.0 / 1024.0;
+    }
+    running
+}
+
+#[cfg(windows)]
+use self::windows as sys;
+
+#[cfg(target_os = "macos")]
+use self::macos as sys;
+
+#[cfg(target_os = "linux")]
+use self::linux as sys;
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+mod sys {
+    use super::{StartupItem, StartupState};
+
+    pub fn scan() -> Vec<StartupItem> {
+        Vec::new()
+    }
+
+    pub fn set_state(_item: &StartupItem, _state: StartupState) -> Result<(), String> {
+        Err("Startup management is not supported on this platform".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, command: &str, source: StartupSource) -> StartupItem {
+        StartupItem {
+            name: name.into(),
+            command: command.into(),
+            source,
+            location: String::new(),
+            state: StartupState::Enabled,
+            impact: BootImpact::default(),
+        }
+    }
+
+    #[test]
+    fn test_program_and_impact() {
+        let onedrive = item("OneDrive", r#""C:\Program Files\Microsoft OneDrive\OneDrive.exe" /background"#, StartupSource::RunKeyUser);
+        assert_eq!(onedrive.program(), "onedrive");
+        let unquoted = item("Tool", r"C:\Program Files\Tool\tool.exe -silent", StartupSource::RunKeyUser);
+        assert_eq!(unquoted.program(), "tool");
+        assert_eq!(item("x", "/usr/bin/nextcloud --background", StartupSource::XdgAutostart).program(), "nextcloud");
+
+        let running = HashMap::from([("tool".to_string(), 300.0)]);
+        let measured = estimate_impact(&unquoted, &running);
+        assert_eq!(measured.level, ImpactLevel::High);
+        assert_eq!(measured.memory_mb, Some(300.0));
+        assert_eq!(estimate_impact(&onedrive, &HashMap::new()).level, ImpactLevel::High);
+        assert_eq!(estimate_impact(&item("svc", "svc.exe", StartupSource::Service), &HashMap::new()).level, ImpactLevel::Low);
+
+        assert!(item("SecurityHealth", "SecurityHealthSystray.exe", StartupSource::RunKeyMachine).is_security_software());
+    }
+
+    #[test]
+    fn test_platform_parsers() {
+        let scan = "run-HKCU|HKCU|OneDrive|disabled|\"C:\\OneDrive.exe\" /background\r\n\
+                    task|\\Vendor\\Updater|Updater|delayed:PT1M30S|C:\\up.exe --a|b\n\
+                    garbage line\n";
+        let items = windows::parse_scan(scan);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id(), "run:OneDrive");
+        assert_eq!(items[0].state, StartupState::Disabled);
+        assert_eq!(items[1].state, StartupState::Delayed { secs: 90 });
+        assert_eq!(items[1].command, "C:\\up.exe --a|b");
+
+        let desktop = "[Desktop Entry]\nName=Nextcloud\nExec=nextcloud --background %U\nX-GNOME-Autostart-Delay=30\n\n[Desktop Action New]\nExec=x\n";
+        let entry = linux::parse_desktop_entry("nextcloud.desktop", desktop).unwrap();
+        assert_eq!(entry.command, "nextcloud --background");
+        assert_eq!(entry.state, StartupState::Delayed { secs: 30 });
+        let hidden = linux::set_desktop_value(desktop, "Hidden", Some("true"));
+        let cleared = linux::set_desktop_value(&hidden, "X-GNOME-Autostart-Delay", None);
+        assert_eq!(linux::parse_desktop_entry("nextcloud.desktop", &cleared).unwrap().state, StartupState::Disabled);
+        assert!(cleared.contains("[Desktop Action New]\nExec=x"));
+
+        let disabled = macos::parse_print_disabled("disabled services = {\n\t\"com.foo.agent\" => disabled\n\t\"com.bar\" => false\n}\n");
+        assert_eq!(disabled, vec![("com.foo.agent".to_string(), true), ("com.bar".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_journal_keeps_latest_state() {
+        let mut journal = StartupJournal::new();
+        let mut svc = item("Updater", "updater.exe", StartupSource::Service);
+        svc.state = StartupState::Disabled;
+        journal.record(&svc, StartupState::Enabled).unwrap();
+        svc.state = StartupState::Enabled;
+        journal.record(&svc, StartupState::Disabled).unwrap();
+
+        let latest = journal.latest();
+        assert_eq!(latest["service:Updater"].state, StartupState::Enabled);
+        assert_eq!(journal.changes().len(), 2);
+    }
+}
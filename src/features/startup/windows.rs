@@ -0,0 +1,237 @@
+//! Windows startup sources: Run keys, Startup folders, scheduled tasks, services
+//!
+//! Run keys and Startup folder entries are disabled through the same
+//! `StartupApproved` registry values Task Manager uses. Delaying one of them
+//! disables it and creates a logon task under `\RuVector\Startup\` with the
+//! requested delay. Tasks get a trigger delay, and services are switched
+//! between automatic, delayed automatic and manual start.
+
+#![cfg_attr(not(windows), allow(dead_code))]
+
+use std::process::Command;
+
+use super::{StartupItem, StartupSource, StartupState};
+
+/// Task folder holding delayed Run key and Startup folder items
+const DELAYED_TASK_PATH: &str = r"\RuVector\Startup\";
+
+const APPROVED_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved";
+
+/// `StartupApproved` values; bit 0 set means disabled
+const APPROVED_ENABLED: &str = "020000000000000000000000";
+const APPROVED_DISABLED: &str = "030000000000000000000000";
+
+/// Emits `kind|location|name|state|command` lines
+const SCAN_SCRIPT: &str = r#"
+$ErrorActionPreference = 'SilentlyContinue'
+function Approved($key, $name) {
+    $v = (Get-ItemProperty -Path $key -Name $name).$name
+    if ($v -and ($v[0] -band 1)) { 'disabled' } else { 'enabled' }
+}
+$delayed = @{}
+Get-ScheduledTask -TaskPath '\RuVector\Startup\' | ForEach-Object { $delayed[$_.TaskName] = $_.Triggers[0].Delay }
+function State($approvedKey, $valueName, $name) {
+    if ($delayed.ContainsKey($name)) { "delayed:$($delayed[$name])" } else { Approved $approvedKey $valueName }
+}
+foreach ($hive in 'HKCU', 'HKLM') {
+    $run = "${hive}:\Software\Microsoft\Windows\CurrentVersion\Run"
+    $approved = "${hive}:\Software\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved\Run"
+    $props = Get-ItemProperty -Path $run
+    if ($props) {
+        $props.PSObject.Properties | Where-Object { $_.Name -notlike 'PS*' } | ForEach-Object {
+            "run-$hive|$hive|$($_.Name)|$(State $approved $_.Name $_.Name)|$($_.Value)"
+        }
+    }
+}
+$shell = New-Object -ComObject WScript.Shell
+foreach ($pair in @(@([Environment]::GetFolderPath('Startup'), 'HKCU'), @([Environment]::GetFolderPath('CommonStartup'), 'HKLM'))) {
+    $approved = "$($pair[1]):\Software\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved\StartupFolder"
+    Get-ChildItem -Path $pair[0] -File | Where-Object { $_.Name -ne 'desktop.ini' } | ForEach-Object {
+        $target = if ($_.Extension -eq '.lnk') { $s = $shell.CreateShortcut($_.FullName); "`"$($s.TargetPath)`" $($s.Arguments)" } else { $_.FullName }
+        "folder|$($_.FullName)|$($_.BaseName)|$(State $approved $_.Name $_.BaseName)|$target"
+    }
+}
+Get-ScheduledTask | Where-Object { $_.TaskPath -notlike '\Microsoft\*' -and $_.TaskPath -ne '\RuVector\Startup\' } | ForEach-Object {
+    $trigger = $_.Triggers | Where-Object { $_.CimClass.CimClassName -in 'MSFT_TaskLogonTrigger', 'MSFT_TaskBootTrigger' } | Select-Object -First 1
+    if ($trigger) {
+        $action = $_.Actions | Select-Object -First 1
+        $state = if ($_.State -eq 'Disabled') { 'disabled' } elseif ($trigger.Delay) { "delayed:$($trigger.Delay)" } else { 'enabled' }
+        "task|$($_.TaskPath)$($_.TaskName)|$($_.TaskName)|$state|$($action.Execute) $($action.Arguments)"
+    }
+}
+Get-CimInstance Win32_Service -Filter "StartMode='Auto'" | Where-Object { $_.PathName -and $_.PathName -notmatch '\\Windows\\' } | ForEach-Object {
+    $state = if ($_.DelayedAutoStart) { 'delayed:120' } else { 'enabled' }
+    "service|$($_.Name)|$($_.DisplayName)|$state|$($_.PathName)"
+}
+"#;
+
+pub fn scan() -> Vec<StartupItem> {
+    match powershell(SCAN_SCRIPT) {
+        Ok(output) => parse_scan(&output),
+        Err(e) => {
+            tracing::warn!("Startup scan failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Items from [`SCAN_SCRIPT`] output
+pub fn parse_scan(output: &str) -> Vec<StartupItem> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim_end_matches('\r').splitn(5, '|');
+            let (kind, location, name, state, command) =
+                (parts.next()?, parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+            let source = match kind {
+                "run-HKCU" => StartupSource::RunKeyUser,
+                "run-HKLM" => StartupSource::RunKeyMachine,
+                "folder" => StartupSource::StartupFolder,
+                "task" => StartupSource::ScheduledTask,
+                "service" => StartupSource::Service,
+                _ => return None,
+            };
+            let state = match state.strip_prefix("delayed:") {
+                Some(delay) => StartupState::Delayed { secs: parse_task_delay(delay).unwrap_or(super::DEFAULT_DELAY_SECS) },
+                None if state == "disabled" => StartupState::Disabled,
+                None => StartupState::Enabled,
+            };
+            Some(StartupItem {
+                name: name.to_string(),
+                command: command.trim().to_string(),
+                source,
+                location: location.to_string(),
+                state,
+                impact: Default::default(),
+            })
+        })
+        .collect()
+}
+
+/// Seconds in a task trigger delay such as `PT1M30S`
+pub fn parse_task_delay(delay: &str) -> Option<u32> {
+    let time = delay.trim().strip_prefix("PT")?;
+    let mut secs = 0;
+    let mut number = String::new();
+    for c in time.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: u32 = number.parse().ok()?;
+        number.clear();
+        secs += match c {
+            'H' => n * 3600,
+            'M' => n * 60,
+            'S' => n,
+            _ => return None,
+        };
+    }
+    Some(secs)
+}
+
+pub fn set_state(item: &StartupItem, state: StartupState) -> Result<(), String> {
+    match item.source {
+        StartupSource::RunKeyUser | StartupSource::RunKeyMachine | StartupSource::StartupFolder => {
+            set_approved(item, state != StartupState::Enabled)?;
+            // Dropping a stale delayed task is fine to fail when there is none
+            let _ = run("schtasks", &["/Delete", "/TN", &delayed_task_name(item), "/F"]);
+            if let StartupState::Delayed { secs } = state {
+                let delay = format!("{:04}:{:02}", secs / 60, secs % 60);
+                run(
+                    "schtasks",
+                    &["/Create", "/TN", &delayed_task_name(item), "/TR", &item.command, "/SC", "ONLOGON", "/DELAY", &delay, "/F"],
+                )?;
+            }
+            Ok(())
+        }
+        StartupSource::ScheduledTask => {
+            let (folder, name) = split_task_path(&item.location);
+            let delay = match state {
+                StartupState::Delayed { secs } => format!("PT{}S", secs),
+                _ => String::new(),
+            };
+            if state != StartupState::Disabled {
+                let script = format!(
+                    "$t = Get-ScheduledTask -TaskPath '{}' -TaskName '{}' -ErrorAction Stop; \
+                     $t.Triggers | Where-Object {{ $_.CimClass.CimClassName -in 'MSFT_TaskLogonTrigger','MSFT_TaskBootTrigger' }} | \
+                     ForEach-Object {{ $_.Delay = '{}' }}; Set-ScheduledTask -InputObject $t -ErrorAction Stop | Out-Null",
+                    ps_quote(folder),
+                    ps_quote(name),
+                    delay
+                );
+                powershell(&script)?;
+            }
+            let flag = if state == StartupState::Disabled { "/DISABLE" } else { "/ENABLE" };
+            run("schtasks", &["/Change", "/TN", &item.location, flag]).map(|_| ())
+        }
+        StartupSource::Service => {
+            let start = match state {
+                StartupState::Enabled => "auto",
+                StartupState::Delayed { .. } => "delayed-auto",
+                StartupState::Disabled => "demand",
+            };
+            run("sc.exe", &["config", &item.location, "start=", start]).map(|_| ())
+        }
+        _ => Err(format!("{} items are not managed on Windows", item.source.label())),
+    }
+}
+
+/// Write the Task Manager enabled/disabled flag for a Run key or Startup folder item
+fn set_approved(item: &StartupItem, disabled: bool) -> Result<(), String> {
+    let (hive, subkey, value) = match item.source {
+        StartupSource::StartupFolder => {
+            let hive = if item.location.to_lowercase().contains(r"\programdata\") { "HKLM" } else { "HKCU" };
+            let file = item.location.rsplit('\\').next().unwrap_or(&item.name);
+            (hive, "StartupFolder", file)
+        }
+        StartupSource::RunKeyMachine => ("HKLM", "Run", item.name.as_str()),
+        _ => ("HKCU", "Run", item.name.as_str()),
+    };
+    let key = format!(r"{}\{}\{}", hive, APPROVED_KEY, subkey);
+    let data = if disabled { APPROVED_DISABLED } else { APPROVED_ENABLED };
+    run("reg", &["add", &key, "/v", value, "/t", "REG_BINARY", "/d", data, "/f"]).map(|_| ())
+}
+
+fn delayed_task_name(item: &StartupItem) -> String {
+    format!("{}{}", DELAYED_TASK_PATH, item.name)
+}
+
+/// `\Folder\Name` into (`\Folder\`, `Name`)
+fn split_task_path(path: &str) -> (&str, &str) {
+    match path.rfind('\\') {
+        Some(i) => (&path[..=i], &path[i + 1..]),
+        None => ("\\", path),
+    }
+}
+
+fn ps_quote(s: &str) -> String {
+    s.replace('\'', "''")
+}
+
+fn powershell(script: &str) -> Result<String, String> {
+    run("powershell", &["-NoProfile", "-NonInteractive", "-Command", script])
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.trim();
+        if message.contains("Access is denied") {
+            Err(format!("{} needs administrator rights", program))
+        } else {
+            Err(format!("{} failed: {}", program, message))
+        }
+    }
+}
@@ -0,0 +1,600 @@
+//! Thermal monitoring and throttle prediction (ADR-020)
+//!
+//! Samples CPU and GPU temperatures and fits a line through the last minute
+//! of readings. From that line it estimates when the CPU will reach its
+//! throttle point, so background work can back off before the firmware
+//! throttles the foreground app.
+//!
+//! CPU temperature sources:
+//! - Windows: LibreHardwareMonitor / OpenHardwareMonitor WMI sensors when one
+//!   of them is running, otherwise ACPI thermal zones (needs admin)
+//! - Linux: hwmon sysfs (coretemp, k10temp, ...) with thermal zones as fallback
+//! - macOS: SMC temperature keys through IOKit
+//!
+//! GPU temperature comes from [`GpuMonitor`].
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::monitor::gpu::GpuMonitor;
+use crate::platform::power::{self, PowerPlan};
+
+/// CPU temperature treated as the throttle point (TjMax 100°C minus a 15°C margin)
+pub const DEFAULT_CPU_LIMIT_C: f64 = 85.0;
+
+/// GPU temperature treated as the throttle point
+pub const DEFAULT_GPU_LIMIT_C: f64 = 83.0;
+
+/// Default time between samples
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Warn when the limit is predicted within this horizon
+pub const PREDICTION_HORIZON: Duration = Duration::from_secs(120);
+
+/// Readings the trend is fitted over
+const FORECAST_WINDOW: Duration = Duration::from_secs(60);
+
+/// Readings needed before forecasting
+const MIN_SAMPLES: usize = 4;
+
+/// A hot component must cool this far below its limit to count as normal again
+const COOL_DOWN_MARGIN_C: f64 = 5.0;
+
+/// One temperature sample
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThermalReading {
+    pub cpu_c: Option<f64>,
+    pub gpu_c: Option<f64>,
+}
+
+/// Current CPU temperature, hottest GPU temperature
+pub fn read_temperatures(gpu: Option<&GpuMonitor>) -> ThermalReading {
+    ThermalReading {
+        cpu_c: read_cpu_temperature(),
+        gpu_c: gpu.and_then(|g| g.status().iter().filter_map(|s| s.temperature).max()).map(f64::from),
+    }
+}
+
+/// Least-squares trend over a sliding window of readings
+pub struct TemperatureForecaster {
+    window: Duration,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl TemperatureForecaster {
+    pub fn new(window: Duration) -> Self {
+        Self { window, samples: VecDeque::new() }
+    }
+
+    pub fn push(&mut self, at: Instant, celsius: f64) {
+        self.samples.push_back((at, celsius));
+        while self.samples.front().is_some_and(|(t, _)| at.saturating_duration_since(*t) > self.window) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().map(|(_, c)| *c)
+    }
+
+    /// Degrees per second, `None` until enough samples exist
+    pub fn trend(&self) -> Option<f64> {
+        if self.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let start = self.samples.front()?.0;
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .map(|(t, c)| (t.saturating_duration_since(start).as_secs_f64(), *c))
+            .collect();
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        (variance > 0.0).then(|| covariance / variance)
+    }
+
+    /// Predicted temperature `horizon` from the latest sample
+    pub fn forecast(&self, horizon: Duration) -> Option<f64> {
+        Some(self.latest()? + self.trend()? * horizon.as_secs_f64())
+    }
+
+    /// How long until `limit` is reached at the current trend
+    ///
+    /// Zero when already there, `None` when stable or cooling.
+    pub fn time_to(&self, limit: f64) -> Option<Duration> {
+        let latest = self.latest()?;
+        if latest >= limit {
+            return Some(Duration::ZERO);
+        }
+        let trend = self.trend()?;
+        (trend > 0.0).then(|| Duration::from_secs_f64((limit - latest) / trend))
+    }
+}
+
+/// How close the machine is to throttling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThermalLevel {
+    Normal,
+    /// Predicted to reach the limit within [`PREDICTION_HORIZON`]
+    Rising,
+    /// At or above the limit
+    Hot,
+}
+
+impl std::fmt::Display for ThermalLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThermalLevel::Normal => write!(f, "normal"),
+            ThermalLevel::Rising => write!(f, "rising"),
+            ThermalLevel::Hot => write!(f, "hot"),
+        }
+    }
+}
+
+/// Result of one sample
+#[derive(Debug, Clone)]
+pub struct ThermalStatus {
+    pub reading: ThermalReading,
+    pub level: ThermalLevel,
+    /// CPU trend in °C per minute
+    pub cpu_trend_per_min: Option<f64>,
+    /// Predicted time until the CPU limit, if heating up
+    pub time_to_limit: Option<Duration>,
+    pub cpu_limit_c: f64,
+    /// Active power plan, looked up only while not normal
+    pub power_plan: Option<PowerPlan>,
+}
+
+impl ThermalStatus {
+    /// Whether optimizer background work (auto-optimize, prefetch) should pause
+    pub fn reduce_background(&self) -> bool {
+        self.level != ThermalLevel::Normal
+    }
+
+    /// Suggested power plan change while hot or heating up
+    ///
+    /// Steps one plan down from the active one; nothing once in power saver.
+    pub fn power_advice(&self) -> Option<String> {
+        if self.level == ThermalLevel::Normal {
+            return None;
+        }
+        let target = match self.power_plan {
+            Some(plan) => plan.cooler()?,
+            None => PowerPlan::PowerSaver,
+        };
+        Some(format!("Switch to {} to cool down", target))
+    }
+
+    /// Tray notification text for this status
+    pub fn notification(&self) -> (String, String) {
+        let cpu = self.reading.cpu_c.map(|c| format!("CPU {:.0}°C", c)).unwrap_or_else(|| "CPU".into());
+        let title = match self.level {
+            ThermalLevel::Hot => "Running Hot".to_string(),
+            _ => "Heating Up".to_string(),
+        };
+        let mut message = match (self.level, self.time_to_limit) {
+            (ThermalLevel::Rising, Some(eta)) => {
+                format!("{} — throttling likely in ~{}s. Background optimization paused.", cpu, eta.as_secs())
+            }
+            _ => format!("{} — background optimization paused.", cpu),
+        };
+        if let Some(advice) = self.power_advice() {
+            message.push(' ');
+            message.push_str(&advice);
+            message.push('.');
+        }
+        (title, message)
+    }
+
+    pub fn print(&self) {
+        let temp = |t: Option<f64>| t.map(|c| format!("{:.1}°C", c)).unwrap_or_else(|| "n/a".into());
+        println!("  CPU:    {}", temp(self.reading.cpu_c));
+        println!("  GPU:    {}", temp(self.reading.gpu_c));
+        match self.cpu_trend_per_min {
+            Some(trend) => println!("  Trend:  {:+.1}°C/min", trend),
+            None => println!("  Trend:  collecting samples..."),
+        }
+        match self.time_to_limit {
+            Some(eta) if eta.is_zero() => println!("  Limit:  {:.0}°C reached", self.cpu_limit_c),
+            Some(eta) => println!("  Limit:  {:.0}°C in ~{}s", self.cpu_limit_c, eta.as_secs()),
+            None => println!("  Limit:  {:.0}°C, not approaching", self.cpu_limit_c),
+        }
+        println!("  Level:  {}", self.level);
+        if let Some(advice) = self.power_advice() {
+            println!("\n  💡 {}", advice);
+        }
+    }
+}
+
+/// Samples temperatures and classifies the throttle risk
+pub struct ThermalMonitor {
+    cpu: TemperatureForecaster,
+    gpu: TemperatureForecaster,
+    cpu_limit_c: f64,
+    gpu_limit_c: f64,
+    gpu_monitor: Option<GpuMonitor>,
+    level: ThermalLevel,
+}
+
+impl ThermalMonitor {
+    pub fn new() -> Self {
+        Self::with_limits(DEFAULT_CPU_LIMIT_C, DEFAULT_GPU_LIMIT_C)
+    }
+
+    pub fn with_limits(cpu_limit_c: f64, gpu_limit_c: f64) -> Self {
+        Self {
+            cpu: TemperatureForecaster::new(FORECAST_WINDOW),
+            gpu: TemperatureForecaster::new(FORECAST_WINDOW),
+            cpu_limit_c,
+            gpu_limit_c,
+            gpu_monitor: GpuMonitor::new().ok(),
+            level: ThermalLevel::Normal,
+        }
+    }
+
+    /// Read sensors and update the forecast
+    pub fn sample(&mut self) -> ThermalStatus {
+        let reading = read_temperatures(self.gpu_monitor.as_ref());
+        let mut status = self.update(Instant::now(), reading);
+        if status.level != ThermalLevel::Normal {
+            status.power_plan = power::get_active_plan().ok().and_then(|p| p.plan);
+        }
+        status
+    }
+
+    fn update(&mut self, now: Instant, reading: ThermalReading) -> ThermalStatus {
+        if let Some(c) = reading.cpu_c {
+            self.cpu.push(now, c);
+        }
+        if let Some(c) = reading.gpu_c {
+            self.gpu.push(now, c);
+        }
+
+        let time_to_limit = self.cpu.time_to(self.cpu_limit_c);
+        let over = |latest: Option<f64>, limit: f64, margin: f64| latest.is_some_and(|c| c >= limit - margin);
+        // Stay hot until well below the limit so notifications don't flap
+        let margin = if self.level == ThermalLevel::Hot { COOL_DOWN_MARGIN_C } else { 0.0 };
+        let hot = over(self.cpu.latest(), self.cpu_limit_c, margin) || over(self.gpu.latest(), self.gpu_limit_c, margin);
+        let rising = time_to_limit.is_some_and(|eta| eta <= PREDICTION_HORIZON)
+            || self.gpu.time_to(self.gpu_limit_c).is_some_and(|eta| eta <= PREDICTION_HORIZON);
+
+        self.level = if hot {
+            ThermalLevel::Hot
+        } else if rising {
+            ThermalLevel::Rising
+        } else {
+            ThermalLevel::Normal
+        };
+
+        ThermalStatus {
+            reading,
+            level: self.level,
+            cpu_trend_per_min: self.cpu.trend().map(|t| t * 60.0),
+            time_to_limit,
+            cpu_limit_c: self.cpu_limit_c,
+            power_plan: None,
+        }
+    }
+}
+
+impl Default for ThermalMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sample on a background thread; stops when the receiver is dropped
+///
+/// Sensor reads can shell out (WMI, nvidia-smi), so they stay off UI threads.
+pub fn watch(interval: Duration) -> Receiver<ThermalStatus> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut monitor = ThermalMonitor::new();
+        loop {
+            if tx.send(monitor.sample()).is_err() {
+                break;
+            }
+            std::thread::sleep(interval);
+        }
+    });
+    rx
+}
+
+/// Print readings until interrupted
+pub fn watch_and_print(interval: Duration) {
+    let mut monitor = ThermalMonitor::new();
+    loop {
+        let status = monitor.sample();
+        print!("\x1B[2J\x1B[1;1H");
+        println!("🌡️  Thermal Status\n");
+        status.print();
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(windows)]
+fn read_cpu_temperature() -> Option<f64> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const SCRIPT: &str = "$s = Get-CimInstance -Namespace root/LibreHardwareMonitor -ClassName Sensor -ErrorAction SilentlyContinue; \
+        if (-not $s) { $s = Get-CimInstance -Namespace root/OpenHardwareMonitor -ClassName Sensor -ErrorAction SilentlyContinue }; \
+        $s | Where-Object { $_.SensorType -eq 'Temperature' -and $_.Identifier -match 'cpu' } | \
+        ForEach-Object { \"hw|$($_.Name)|$($_.Value)\" }; \
+        Get-CimInstance -Namespace root/wmi -ClassName MSAcpi_ThermalZoneTemperature -ErrorAction SilentlyContinue | \
+        ForEach-Object { \"acpi|zone|$($_.CurrentTemperature)\" }";
+
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    parse_windows_sensors(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// CPU temperature from `source|name|value` lines
+///
+/// Hardware monitor package sensors win over per-core ones. ACPI zones,
+/// reported in tenths of a Kelvin, are only a fallback.
+pub fn parse_windows_sensors(output: &str) -> Option<f64> {
+    let mut package = None;
+    let mut hottest: Option<f64> = None;
+    let mut acpi: Option<f64> = None;
+    for line in output.lines() {
+        let mut parts = line.trim().splitn(3, '|');
+        let (Some(source), Some(name), Some(value)) = (parts.next(), parts.next(), parts.next()) else { continue };
+        let Ok(value) = value.trim().replace(',', ".").parse::<f64>() else { continue };
+        match source {
+            "hw" if name.contains("Package") || name.contains("Tctl") || name.contains("Tdie") => {
+                package = Some(package.map_or(value, |p: f64| p.max(value)));
+            }
+            "hw" => hottest = Some(hottest.map_or(value, |h| h.max(value))),
+            "acpi" => {
+                let celsius = value / 10.0 - 273.15;
+                acpi = Some(acpi.map_or(celsius, |a| a.max(celsius)));
+            }
+            _ => {}
+        }
+    }
+    package.or(hottest).or(acpi).filter(|c| *c > 0.0 && *c < 150.0)
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_temperature() -> Option<f64> {
+    use std::fs;
+
+    const CPU_DRIVERS: &[&str] = &["coretemp", "k10temp", "zenpower", "cpu_thermal", "soc_thermal"];
+    let read = |path: std::path::PathBuf| fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+
+    for entry in fs::read_dir("/sys/class/hwmon").ok()?.flatten() {
+        let dir = entry.path();
+        let Some(name) = read(dir.join("name")) else { continue };
+        if !CPU_DRIVERS.contains(&name.as_str()) {
+            continue;
+        }
+        let mut sensors: Vec<(String, f64)> = Vec::new();
+        for file in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let file_name = file.file_name().to_string_lossy().into_owned();
+            let Some(index) = file_name.strip_prefix("temp").and_then(|s| s.strip_suffix("_input")) else { continue };
+            let Some(millis) = read(file.path()).and_then(|v| v.parse::<f64>().ok()) else { continue };
+            let label = read(dir.join(format!("temp{}_label", index))).unwrap_or_default();
+            sensors.push((label, millis / 1000.0));
+        }
+        if let Some(temp) = pick_hwmon_sensor(&sensors) {
+            return Some(temp);
+        }
+    }
+
+    // Thermal zones: prefer the package sensor, else the hottest zone
+    let mut hottest: Option<f64> = None;
+    for entry in fs::read_dir("/sys/class/thermal").ok()?.flatten() {
+        let Some(kind) = read(entry.path().join("type")) else { continue };
+        let Some(temp) = read(entry.path().join("temp")).and_then(|v| v.parse::<f64>().ok()).map(|m| m / 1000.0) else {
+            continue;
+        };
+        if kind == "x86_pkg_temp" {
+            return Some(temp);
+        }
+        hottest = Some(hottest.map_or(temp, |h| h.max(temp)));
+    }
+    hottest
+}
+
+/// Package/die sensor if labeled, else the hottest reading
+#[cfg(any(target_os = "linux", test))]
+fn pick_hwmon_sensor(sensors: &[(String, f64)]) -> Option<f64> {
+    const PACKAGE_LABELS: &[&str] = &["Package id 0", "Tctl", "Tdie"];
+    sensors
+        .iter()
+        .find(|(label, _)| PACKAGE_LABELS.contains(&label.as_str()))
+        .map(|(_, t)| *t)
+        .or_else(|| sensors.iter().map(|(_, t)| *t).reduce(f64::max))
+}
+
+#[cfg(target_os = "macos")]
+fn read_cpu_temperature() -> Option<f64> {
+    smc::cpu_temperature()
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn read_cpu_temperature() -> Option<f64> {
+    None
+}
+
+/// Minimal AppleSMC client for temperature keys
+#[cfg(target_os = "macos")]
+mod smc {
+    use std::ffi::c_void;
+    use std::os::raw::c_char;
+
+    /// Intel CPU proximity/die keys, then Apple Silicon P-core keys
+    const CPU_KEYS: &[&[u8; 4]] = &[b"TC0P", b"TC0D", b"TC0E", b"TC0F", b"Tp09", b"Tp0T", b"Tp01", b"Tp05", b"Tp0D", b"Tp0H"];
+
+    const KERNEL_INDEX_SMC: u32 = 2;
+    const CMD_READ_BYTES: u8 = 5;
+    const CMD_READ_KEYINFO: u8 = 9;
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct Version {
+        major: u8,
+        minor: u8,
+        build: u8,
+        reserved: u8,
+        release: u16,
+    }
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct PLimitData {
+        version: u16,
+        length: u16,
+        cpu_p_limit: u32,
+        gpu_p_limit: u32,
+        mem_p_limit: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyInfo {
+        data_size: u32,
+        data_type: u32,
+        data_attributes: u8,
+    }
+
+    #[repr(C)]
+    #[derive(Default, Clone, Copy)]
+    struct KeyData {
+        key: u32,
+        version: Version,
+        p_limit: PLimitData,
+        key_info: KeyInfo,
+        result: u8,
+        status: u8,
+        data8: u8,
+        data32: u32,
+        bytes: [u8; 32],
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+        fn IOServiceOpen(service: u32, owning_task: u32, kind: u32, connect: *mut u32) -> i32;
+        fn IOServiceClose(connect: u32) -> i32;
+        fn IOObjectRelease(object: u32) -> i32;
+        fn IOConnectCallStructMethod(
+            connect: u32,
+            selector: u32,
+            input: *const c_void,
+            input_size: usize,
+            output: *mut c_void,
+            output_size: *mut usize,
+        ) -> i32;
+    }
+
+    /// Hottest CPU temperature key that reads as a plausible value
+    pub fn cpu_temperature() -> Option<f64> {
+        unsafe {
+            let service = IOServiceGetMatchingService(0, IOServiceMatching(c"AppleSMC".as_ptr()));
+            if service == 0 {
+                return None;
+            }
+            let mut connection = 0u32;
+            #[allow(deprecated)]
+            let opened = IOServiceOpen(service, libc::mach_task_self(), 0, &mut connection);
+            IOObjectRelease(service);
+            if opened != 0 {
+                return None;
+            }
+
+            let hottest = CPU_KEYS
+                .iter()
+                .filter_map(|key| read_temperature(connection, key))
+                .filter(|c| *c > 0.0 && *c < 150.0)
+                .reduce(f64::max);
+            IOServiceClose(connection);
+            hottest
+        }
+    }
+
+    unsafe fn call(connection: u32, input: &KeyData) -> Option<KeyData> {
+        let mut output = KeyData::default();
+        let mut size = std::mem::size_of::<KeyData>();
+        let result = IOConnectCallStructMethod(
+            connection,
+            KERNEL_INDEX_SMC,
+            input as *const KeyData as *const c_void,
+            std::mem::size_of::<KeyData>(),
+            &mut output as *mut KeyData as *mut c_void,
+            &mut size,
+        );
+        (result == 0 && output.result == 0).then_some(output)
+    }
+
+    unsafe fn read_temperature(connection: u32, key: &[u8; 4]) -> Option<f64> {
+        let key = u32::from_be_bytes(*key);
+        let info = call(connection, &KeyData { key, data8: CMD_READ_KEYINFO, ..Default::default() })?.key_info;
+        let value = call(connection, &KeyData { key, key_info: info, data8: CMD_READ_BYTES, ..Default::default() })?;
+        match &info.data_type.to_be_bytes() {
+            // Signed fixed point, 8 fractional bits
+            b"sp78" => Some(i16::from_be_bytes([value.bytes[0], value.bytes[1]]) as f64 / 256.0),
+            b"flt " => Some(f32::from_le_bytes([value.bytes[0], value.bytes[1], value.bytes[2], value.bytes[3]]) as f64),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forecast_predicts_throttle() {
+        let mut monitor = ThermalMonitor::with_limits(85.0, 83.0);
+        let start = Instant::now();
+        // Heating 0.5°C/s from 60°C: 85°C is 50s past the 30s sample
+        let mut status = None;
+        for i in 0..7 {
+            let reading = ThermalReading { cpu_c: Some(60.0 + i as f64 * 2.5), gpu_c: None };
+            status = Some(monitor.update(start + Duration::from_secs(i * 5), reading));
+        }
+        let status = status.unwrap();
+        assert_eq!(status.level, ThermalLevel::Rising);
+        let eta = status.time_to_limit.unwrap().as_secs_f64();
+        assert!((eta - 20.0).abs() < 0.5, "eta {}", eta);
+        assert!((status.cpu_trend_per_min.unwrap() - 30.0).abs() < 1e-6);
+        assert!(status.reduce_background());
+    }
+
+    #[test]
+    fn test_hot_level_has_hysteresis() {
+        let mut monitor = ThermalMonitor::with_limits(85.0, 83.0);
+        let start = Instant::now();
+        let cpu = |c: f64| ThermalReading { cpu_c: Some(c), gpu_c: None };
+        assert_eq!(monitor.update(start, cpu(86.0)).level, ThermalLevel::Hot);
+        // Just under the limit is still hot; stable readings are normal once cool
+        assert_eq!(monitor.update(start + Duration::from_secs(5), cpu(82.0)).level, ThermalLevel::Hot);
+        let mut level = ThermalLevel::Hot;
+        for i in 2..8 {
+            level = monitor.update(start + Duration::from_secs(i * 5), cpu(70.0)).level;
+        }
+        assert_eq!(level, ThermalLevel::Normal);
+    }
+
+    #[test]
+    fn test_sensor_parsing() {
+        let windows = "hw|CPU Core #1|71.0\nhw|CPU Package|74,5\nacpi|zone|3232\n";
+        assert_eq!(parse_windows_sensors(windows), Some(74.5));
+        let acpi = parse_windows_sensors("acpi|zone|3232\n").unwrap();
+        assert!((acpi - 50.05).abs() < 1e-6);
+
+        let labeled = vec![("Core 0".into(), 60.0), ("Package id 0".into(), 58.0)];
+        assert_eq!(pick_hwmon_sensor(&labeled), Some(58.0));
+        assert_eq!(pick_hwmon_sensor(&[("".into(), 40.0), ("".into(), 45.0)]), Some(45.0));
+    }
+}
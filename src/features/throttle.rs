@@ -0,0 +1,540 @@
+//! Background service throttling while a game or build runs
+//!
+//! Windows Update, Windows Search and scheduled Defender scans compete with
+//! games and builds for disk and CPU. Game Mode and the build booster pause
+//! or limit the ones listed in `throttle_services`; nothing is touched
+//! without that opt-in.
+//!
+//! Every change is written to `throttle.json` before it is made. A holder
+//! that crashes or is killed leaves its changes there, and the next
+//! [`restore_stale`] puts them back. Game Mode and a build can hold the
+//! throttle at once, even from different processes; a service is resumed
+//! when the last holder that wants it lets go. Each change to the file is
+//! made under an exclusive lock on `throttle.json.lock` and lands by rename.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use super::build::BoostReport;
+
+/// File name of the hold list inside the config directory
+pub const THROTTLE_FILE: &str = "throttle.json";
+
+/// Defender's `ScanAvgCPULoadFactor` while throttled (5 is its minimum)
+const DEFENDER_SCAN_LOAD: u32 = 5;
+
+/// A background service that can be paused or limited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleService {
+    /// `wuauserv`, stopped
+    WindowsUpdate,
+    /// `WSearch`, stopped
+    WindowsSearch,
+    /// Defender scans, held to a small CPU share
+    DefenderScans,
+}
+
+impl ThrottleService {
+    pub const ALL: [ThrottleService; 3] =
+        [ThrottleService::WindowsUpdate, ThrottleService::WindowsSearch, ThrottleService::DefenderScans];
+
+    /// Name used in `throttle_services`
+    pub fn key(&self) -> &'static str {
+        match self {
+            ThrottleService::WindowsUpdate => "windows_update",
+            ThrottleService::WindowsSearch => "windows_search",
+            ThrottleService::DefenderScans => "defender_scans",
+        }
+    }
+}
+
+impl std::fmt::Display for ThrottleService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThrottleService::WindowsUpdate => write!(f, "Windows Update"),
+            ThrottleService::WindowsSearch => write!(f, "Windows Search"),
+            ThrottleService::DefenderScans => write!(f, "Defender scans"),
+        }
+    }
+}
+
+/// One change made to a service, with what to put back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Change {
+    /// The service was running and we stopped it
+    Stopped { service: ThrottleService },
+    /// Defender's scan CPU share was lowered from `previous`
+    Limited { service: ThrottleService, previous: u32 },
+}
+
+impl Change {
+    pub fn service(&self) -> ThrottleService {
+        match *self {
+            Change::Stopped { service } | Change::Limited { service, .. } => service,
+        }
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Stopped { service } => write!(f, "{} stopped", service),
+            Change::Limited { service, .. } => write!(f, "{} held to {}% CPU", service, DEFENDER_SCAN_LOAD),
+        }
+    }
+}
+
+/// One game or build holding the throttle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hold {
+    pub id: u64,
+    /// What holds it, e.g. "Game Mode: eldenring.exe"
+    pub reason: String,
+    pub owner_pid: u32,
+    /// Start time of the owner, so a recycled pid doesn't keep a hold alive
+    pub owner_started: u64,
+    /// Services this holder asked for
+    pub wanted: Vec<ThrottleService>,
+    /// Changes this holder is responsible for putting back
+    pub changes: Vec<Change>,
+}
+
+/// Contents of `throttle.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThrottleState {
+    pub holds: Vec<Hold>,
+}
+
+impl ThrottleState {
+    /// Whether a hold other than `id` has already changed `service`
+    fn changed_by_other(&self, service: ThrottleService, id: u64) -> bool {
+        self.holds
+            .iter()
+            .any(|h| h.id != id && h.changes.iter().any(|c| c.service() == service))
+    }
+
+    /// Remove hold `id` and return the changes nobody else still wants
+    ///
+    /// A change another hold also asked for is handed over to it instead,
+    /// so the service stays throttled until that one lets go too.
+    fn remove(&mut self, id: u64) -> Vec<Change> {
+        let Some(index) = self.holds.iter().position(|h| h.id == id) else {
+            return Vec::new();
+        };
+        let hold = self.holds.remove(index);
+        let mut restore = Vec::new();
+        for change in hold.changes {
+            match self.holds.iter_mut().find(|h| h.wanted.contains(&change.service())) {
+                Some(other) => other.changes.push(change),
+                None => restore.push(change),
+            }
+        }
+        restore
+    }
+
+    /// Every change currently applied, with its holder's reason
+    pub fn active(&self) -> Vec<(&Change, &str)> {
+        self.holds
+            .iter()
+            .flat_map(|h| h.changes.iter().map(move |c| (c, h.reason.as_str())))
+            .collect()
+    }
+
+    /// One line for the tray, e.g. "Paused: Windows Search stopped (Game Mode: x)"
+    pub fn summary(&self) -> String {
+        let active = self.active();
+        if active.is_empty() {
+            return "Background services: normal".to_string();
+        }
+        let changes: Vec<String> = active.iter().map(|(c, _)| c.to_string()).collect();
+        let mut reasons: Vec<&str> = active.iter().map(|&(_, r)| r).collect();
+        reasons.dedup();
+        format!("Paused: {} ({})", changes.join(", "), reasons.join(", "))
+    }
+}
+
+fn path() -> Result<PathBuf, String> {
+    super::config_file(THROTTLE_FILE)
+}
+
+/// Current holds, for status displays
+///
+/// A corrupt file is logged and shown as no holds; changes go through
+/// [`update`], which refuses to overwrite it.
+pub fn load() -> ThrottleState {
+    match path().and_then(|path| read(&path)) {
+        Ok(state) => state,
+        Err(e) => {
+            tracing::error!("{}", e);
+            ThrottleState::default()
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<ThrottleState, String> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("{:?} is corrupt, so pending restores are unknown: {}", path, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ThrottleState::default()),
+        Err(e) => Err(format!("Failed to read {:?}: {}", path, e)),
+    }
+}
+
+/// Written to a temporary file and renamed, so a crash never leaves half a file
+fn save(path: &Path, state: &ThrottleState) -> Result<(), String> {
+    if state.holds.is_empty() {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        };
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// Apply `change` to the current holds and save them, under an exclusive lock
+///
+/// Holders in other processes change the file too, so every change re-reads
+/// it with the lock held rather than reusing an earlier copy.
+fn update<T>(change: impl FnOnce(&mut ThrottleState) -> T) -> Result<T, String> {
+    update_at(&path()?, change)
+}
+
+fn update_at<T>(path: &Path, change: impl FnOnce(&mut ThrottleState) -> T) -> Result<T, String> {
+    let lock = crate::core::lock::lock_exclusive(&path.with_extension("json.lock"))?;
+    let mut state = read(path)?;
+    let result = change(&mut state);
+    save(path, &state)?;
+    drop(lock);
+    Ok(result)
+}
+
+/// Start time of a running process, `None` once it has exited
+fn started(pid: u32) -> Option<u64> {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).map(|p| p.start_time())
+}
+
+/// Put back every change in `restore`, logging the ones that fail
+fn restore_all(restore: Vec<Change>) -> usize {
+    let mut restored = 0;
+    for change in restore {
+        match sys::restore(&change) {
+            Ok(()) => restored += 1,
+            Err(e) => tracing::warn!("Failed to restore {}: {}", change.service(), e),
+        }
+    }
+    restored
+}
+
+/// Put back changes left by holders that exited without releasing
+///
+/// Called before every new hold and when the tray or `build watch` starts.
+/// Returns how many changes were restored.
+pub fn restore_stale() -> usize {
+    let result = update(|state| {
+        let stale: Vec<u64> = state
+            .holds
+            .iter()
+            .filter(|h| started(h.owner_pid) != Some(h.owner_started))
+            .map(|h| h.id)
+            .collect();
+        restore_all(stale.into_iter().flat_map(|id| state.remove(id)).collect())
+    });
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Failed to update {}: {}", THROTTLE_FILE, e);
+        0
+    })
+}
+
+/// The throttle as held by one game or build; released on drop
+pub struct ThrottleGuard {
+    id: u64,
+}
+
+impl ThrottleGuard {
+    /// Put back what this holder changed, unless another holder still wants it
+    pub fn release(self) {
+        drop(self)
+    }
+}
+
+impl Drop for ThrottleGuard {
+    fn drop(&mut self) {
+        if let Err(e) = update(|state| restore_all(state.remove(self.id))) {
+            tracing::warn!("Failed to update {}: {}", THROTTLE_FILE, e);
+        }
+    }
+}
+
+/// Pause or limit `services` for as long as the returned guard lives
+///
+/// Services another holder has already throttled are left as they are.
+/// Returns `None` when nothing was asked for or throttling isn't possible.
+pub fn hold(reason: &str, services: &[ThrottleService], report: &mut BoostReport) -> Option<ThrottleGuard> {
+    if services.is_empty() {
+        return None;
+    }
+    if !sys::SUPPORTED {
+        report.skipped.push("Background services can only be throttled on Windows".into());
+        return None;
+    }
+    restore_stale();
+
+    let owner_pid = std::process::id();
+    let id = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+        ^ owner_pid as u64;
+    let new_hold = Hold {
+        id,
+        reason: reason.to_string(),
+        owner_pid,
+        owner_started: started(owner_pid).unwrap_or(0),
+        wanted: services.to_vec(),
+        changes: Vec::new(),
+    };
+    if let Err(e) = update(|state| state.holds.push(new_hold)) {
+        report.skipped.push(format!("Background services unchanged: {}", e));
+        return None;
+    }
+    let guard = ThrottleGuard { id };
+
+    for &service in services {
+        let planned = update(|state| {
+            if state.changed_by_other(service, id) {
+                return Err(format!("{} already throttled", service));
+            }
+            let change = match sys::plan(service) {
+                Ok(Some(change)) => change,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("{} unchanged: {}", service, e)),
+            };
+            // Recorded first, so a crash mid-change is still restored
+            let hold = state
+                .holds
+                .iter_mut()
+                .find(|h| h.id == id)
+                .ok_or_else(|| format!("{} unchanged: the hold was released", service))?;
+            hold.changes.push(change);
+            Ok(Some(change))
+        });
+        let change = match planned.and_then(|planned| planned) {
+            Ok(Some(change)) => change,
+            Ok(None) => continue,
+            Err(e) => {
+                report.skipped.push(e);
+                continue;
+            }
+        };
+        match sys::apply(&change) {
+            Ok(()) => report.applied.push(format!("Throttled background service: {}", change)),
+            Err(e) => {
+                let forgot = update(|state| {
+                    if let Some(hold) = state.holds.iter_mut().find(|h| h.id == id) {
+                        hold.changes.retain(|c| c != &change);
+                    }
+                });
+                if let Err(e) = forgot {
+                    tracing::warn!("Failed to update {}: {}", THROTTLE_FILE, e);
+                }
+                report.skipped.push(format!("{} unchanged: {}", service, e));
+            }
+        }
+    }
+    Some(guard)
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::process::Command;
+
+    use super::{Change, ThrottleService, DEFENDER_SCAN_LOAD};
+
+    pub const SUPPORTED: bool = true;
+
+    fn service_name(service: ThrottleService) -> &'static str {
+        match service {
+            ThrottleService::WindowsUpdate => "wuauserv",
+            ThrottleService::WindowsSearch => "WSearch",
+            ThrottleService::DefenderScans => "WinDefend",
+        }
+    }
+
+    /// The change that would throttle `service`, or `None` if it is already quiet
+    pub fn plan(service: ThrottleService) -> Result<Option<Change>, String> {
+        match service {
+            ThrottleService::DefenderScans => {
+                let load = powershell("(Get-MpPreference).ScanAvgCPULoadFactor")?
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| "Defender is not in use".to_string())?;
+                Ok((load > DEFENDER_SCAN_LOAD).then_some(Change::Limited { service, previous: load }))
+            }
+            _ => {
+                let state = run("sc", &["query", service_name(service)])?;
+                Ok(state.contains("RUNNING").then_some(Change::Stopped { service }))
+            }
+        }
+    }
+
+    pub fn apply(change: &Change) -> Result<(), String> {
+        match *change {
+            Change::Stopped { service } => run("sc", &["stop", service_name(service)]).map(|_| ()),
+            Change::Limited { .. } => set_defender_load(DEFENDER_SCAN_LOAD),
+        }
+    }
+
+    pub fn restore(change: &Change) -> Result<(), String> {
+        match *change {
+            Change::Stopped { service } => match run("sc", &["start", service_name(service)]) {
+                // Started again by Windows in the meantime
+                Err(e) if e.contains("already been started") => Ok(()),
+                result => result.map(|_| ()),
+            },
+            Change::Limited { previous, .. } => set_defender_load(previous),
+        }
+    }
+
+    fn set_defender_load(load: u32) -> Result<(), String> {
+        powershell(&format!("Set-MpPreference -ScanAvgCPULoadFactor {}", load)).map(|_| ())
+    }
+
+    fn powershell(script: &str) -> Result<String, String> {
+        run("powershell", &["-NoProfile", "-NonInteractive", "-Command", script])
+    }
+
+    fn run(program: &str, args: &[&str]) -> Result<String, String> {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        let output = Command::new(program)
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        if output.status.success() {
+            return Ok(stdout);
+        }
+        // `sc` reports its errors on stdout
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+        if message.contains("Access is denied") || message.contains("PermissionDenied") {
+            Err("needs administrator rights".into())
+        } else {
+            Err(message.lines().last().unwrap_or("failed").trim().to_string())
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod sys {
+    use super::{Change, ThrottleService};
+
+    pub const SUPPORTED: bool = false;
+
+    pub fn plan(_service: ThrottleService) -> Result<Option<Change>, String> {
+        Err("only available on Windows".into())
+    }
+
+    pub fn apply(_change: &Change) -> Result<(), String> {
+        Err("only available on Windows".into())
+    }
+
+    pub fn restore(_change: &Change) -> Result<(), String> {
+        Err("only available on Windows".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hold(id: u64, wanted: &[ThrottleService], changes: &[Change]) -> Hold {
+        Hold {
+            id,
+            reason: format!("hold {}", id),
+            owner_pid: 1,
+            owner_started: 0,
+            wanted: wanted.to_vec(),
+            changes: changes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_last_holder_restores() {
+        let search = Change::Stopped { service: ThrottleService::WindowsSearch };
+        let defender = Change::Limited { service: ThrottleService::DefenderScans, previous: 50 };
+        let mut state = ThrottleState {
+            holds: vec![
+                hold(1, &[ThrottleService::WindowsSearch, ThrottleService::DefenderScans], &[search, defender]),
+                hold(2, &[ThrottleService::WindowsSearch], &[]),
+            ],
+        };
+        assert!(state.changed_by_other(ThrottleService::WindowsSearch, 2));
+
+        // Search is still wanted by hold 2, so only Defender comes back
+        assert_eq!(state.remove(1), vec![defender]);
+        assert_eq!(state.holds[0].changes, vec![search]);
+        assert_eq!(state.remove(2), vec![search]);
+        assert!(state.holds.is_empty());
+        assert!(state.remove(3).is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut state = ThrottleState::default();
+        assert_eq!(state.summary(), "Background services: normal");
+        state.holds.push(hold(1, &[], &[Change::Stopped { service: ThrottleService::WindowsUpdate }]));
+        assert_eq!(state.summary(), "Paused: Windows Update stopped (hold 1)");
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("\"action\":\"stopped\""));
+        assert!(json.contains("\"service\":\"windows_update\""));
+    }
+
+    #[test]
+    fn test_update_is_serialized_and_atomic() {
+        let dir = std::env::temp_dir().join(format!("ruvector-throttle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(THROTTLE_FILE);
+
+        // Every holder's change survives when they all write at once
+        let writers: Vec<_> = (0..8)
+            .map(|id| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    update_at(&path, |state| {
+                        std::thread::sleep(std::time::Duration::from_millis(5));
+                        state.holds.push(hold(id, &[], &[]));
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(read(&path).unwrap().holds.len(), 8);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        // A torn file is an error, and is left alone rather than emptied
+        std::fs::write(&path, "{\"holds\": [{\"id\": 1,").unwrap();
+        assert!(read(&path).unwrap_err().contains("corrupt"));
+        assert!(update_at(&path, |state| state.holds.clear()).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"holds\": [{\"id\": 1,");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,596 @@
+//! Event timeline (ADR-024)
+//!
+//! A journal of what happened on the machine - memory samples, pressure
+//! spikes, app launches, leak alerts, mode switches, memory pattern
+//! shifts, anomalies and watchdog restarts - in `timeline.jsonl`, with
+//! optimizations merged in from the history store.
+//! [`Timeline::view`] lines the events up against the memory curve and ranks
+//! the ones most likely behind a spike, to answer "what happened at 14:32
+//! when everything froze".
+//!
+//! [`TimelineRecorder`] fills the journal from the tray and daemons; other
+//! modules add events with [`record`].
+
+mod recorder;
+
+pub use recorder::{LaunchTracker, PressureTracker, TimelineRecorder, RETENTION, SAMPLE_INTERVAL};
+
+use std::cmp::Reverse;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::algorithms::forecast::{Forecast, HoltForecaster};
+use crate::core::history::{HistoryFilter, HistoryStore, TimeRange};
+use crate::core::text::truncate;
+
+/// File name of the journal inside the config directory
+pub const TIMELINE_FILE: &str = "timeline.jsonl";
+
+/// Memory movement after an event that counts as related
+const RELATED_WINDOW_MS: u64 = 60_000;
+/// How far from the moment of interest an event still counts as nearby
+const PROXIMITY_MS: f64 = 120_000.0;
+/// Events shown as likely related
+const MAX_CORRELATIONS: usize = 5;
+/// Width of the memory curve in characters
+const CURVE_WIDTH: usize = 60;
+/// Journal history a forecast starts from
+pub const FORECAST_HISTORY_MS: u64 = 3 * 3600 * 1000;
+
+/// What kind of thing happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Optimization,
+    PressureSpike,
+    AppLaunch,
+    LeakAlert,
+    ModeSwitch,
+    PatternShift,
+    Anomaly,
+    Restart,
+}
+
+impl EventKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::Optimization => "optimization",
+            EventKind::PressureSpike => "pressure",
+            EventKind::AppLaunch => "app launch",
+            EventKind::LeakAlert => "leak alert",
+            EventKind::ModeSwitch => "mode switch",
+            EventKind::PatternShift => "pattern shift",
+            EventKind::Anomaly => "anomaly",
+            EventKind::Restart => "restart",
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// A single journal event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub kind: EventKind,
+    pub summary: String,
+    /// Memory load when the event was recorded
+    #[serde(default)]
+    pub memory_load: Option<u32>,
+}
+
+impl TimelineEvent {
+    /// Event stamped with the current time and memory load
+    pub fn now(kind: EventKind, summary: impl Into<String>) -> Self {
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        Self {
+            timestamp_ms: now_ms(),
+            kind,
+            summary: summary.into(),
+            memory_load: load_percent(system.used_memory(), system.total_memory()),
+        }
+    }
+
+    /// App name of an app launch written by [`TimelineRecorder`]
+    pub fn launched_app(&self) -> Option<&str> {
+        if self.kind != EventKind::AppLaunch {
+            return None;
+        }
+        self.summary.rsplit_once(" started (").map(|(name, _)| name)
+    }
+}
+
+/// A point on the memory curve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MemoryPoint {
+    pub timestamp_ms: u64,
+    pub load_percent: u32,
+    pub used_mb: u64,
+    pub swap_used_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Entry {
+    Memory(MemoryPoint),
+    Event(TimelineEvent),
+}
+
+impl Entry {
+    fn timestamp_ms(&self) -> u64 {
+        match self {
+            Entry::Memory(p) => p.timestamp_ms,
+            Entry::Event(e) => e.timestamp_ms,
+        }
+    }
+}
+
+/// Append-only JSONL journal
+pub struct Timeline {
+    path: PathBuf,
+}
+
+impl Timeline {
+    /// Open the journal at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(crate::features::config_file(TIMELINE_FILE)?)
+    }
+
+    /// Open a journal at an explicit path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(Self { path })
+    }
+
+    pub fn record_event(&self, event: &TimelineEvent) -> Result<(), String> {
+        self.append(&Entry::Event(event.clone()))
+    }
+
+    pub fn record_memory(&self, point: &MemoryPoint) -> Result<(), String> {
+        self.append(&Entry::Memory(*point))
+    }
+
+    fn append(&self, entry: &Entry) -> Result<(), String> {
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open timeline {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    /// Entries with `start_ms <= timestamp <= end_ms`, skipping malformed lines
+    fn entries(&self, start_ms: u64, end_ms: u64) -> Result<Vec<Entry>, String> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<Entry>(&line).ok())
+            .filter(|e| (start_ms..=end_ms).contains(&e.timestamp_ms()))
+            .collect())
+    }
+
+    /// Memory samples and events in a range, oldest first
+    pub fn read(&self, start_ms: u64, end_ms: u64) -> Result<(Vec<MemoryPoint>, Vec<TimelineEvent>), String> {
+        let mut samples = Vec::new();
+        let mut events = Vec::new();
+        for entry in self.entries(start_ms, end_ms)? {
+            match entry {
+                Entry::Memory(p) => samples.push(p),
+                Entry::Event(e) => events.push(e),
+            }
+        }
+        samples.sort_by_key(|p| p.timestamp_ms);
+        events.sort_by_key(|e| e.timestamp_ms);
+        Ok((samples, events))
+    }
+
+    /// Drop entries older than `keep_after_ms`, returning how many were removed
+    pub fn prune(&self, keep_after_ms: u64) -> Result<usize, String> {
+        let all = self.entries(0, u64::MAX)?;
+        let kept: Vec<&Entry> = all.iter().filter(|e| e.timestamp_ms() >= keep_after_ms).collect();
+        let removed = all.len() - kept.len();
+        if removed == 0 {
+            return Ok(0);
+        }
+        let mut content = String::new();
+        for entry in kept {
+            content.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+            content.push('\n');
+        }
+        std::fs::write(&self.path, content).map_err(|e| format!("Failed to write {:?}: {}", self.path, e))?;
+        Ok(removed)
+    }
+
+    /// Journal plus optimization history for a range, correlated around `focus_ms`
+    pub fn view(&self, start_ms: u64, end_ms: u64, focus_ms: Option<u64>) -> Result<TimelineView, String> {
+        let (samples, mut events) = self.read(start_ms, end_ms)?;
+        let range = TimeRange { start_ms: Some(start_ms), end_ms: Some(end_ms) };
+        match HistoryStore::open_default().and_then(|s| s.query(range, &HistoryFilter::default())) {
+            Ok(records) => events.extend(records.iter().map(|r| TimelineEvent {
+                timestamp_ms: r.timestamp_ms,
+                kind: EventKind::Optimization,
+                summary: format!(
+                    "{} {} optimization freed {:.0} MB from {} processes",
+                    r.trigger, r.mode, r.freed_mb, r.processes_trimmed
+                ),
+                memory_load: r.memory_load_percent,
+            })),
+            Err(e) => tracing::debug!("Timeline without optimization history: {}", e),
+        }
+        events.sort_by_key(|e| e.timestamp_ms);
+        Ok(TimelineView::build(start_ms, end_ms, focus_ms, samples, events))
+    }
+}
+
+/// An event ranked by how much memory moved right after it
+#[derive(Debug, Clone, Serialize)]
+pub struct Correlation {
+    pub event: TimelineEvent,
+    /// Peak load in the minute after the event minus the average before it
+    pub rise_percent: f64,
+    /// Seconds from the event to the moment of interest
+    pub lead_secs: i64,
+    pub score: f64,
+}
+
+/// Memory curve and events for a time window
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineView {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Moment being asked about; the memory peak when none was given
+    pub focus_ms: Option<u64>,
+    pub samples: Vec<MemoryPoint>,
+    pub events: Vec<TimelineEvent>,
+    pub peak: Option<MemoryPoint>,
+    pub correlations: Vec<Correlation>,
+}
+
+impl TimelineView {
+    pub fn build(
+        start_ms: u64,
+        end_ms: u64,
+        focus_ms: Option<u64>,
+        samples: Vec<MemoryPoint>,
+        events: Vec<TimelineEvent>,
+    ) -> Self {
+        let peak = samples.iter().copied().max_by_key(|p| (p.load_percent, Reverse(p.timestamp_ms)));
+        let focus_ms = focus_ms.or(peak.map(|p| p.timestamp_ms));
+        let correlations = focus_ms.map(|f| correlate(&samples, &events, f)).unwrap_or_default();
+        Self { start_ms, end_ms, focus_ms, samples, events, peak, correlations }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "\n🕒 Timeline {} – {}\n",
+            format_time(self.start_ms, "%Y-%m-%d %H:%M"),
+            format_time(self.end_ms, "%H:%M")
+        );
+
+        if self.samples.is_empty() {
+            println!("  No memory samples in this window (is the tray or daemon running?)");
+        } else {
+            let curve = memory_curve(&self.samples, self.start_ms, self.end_ms, CURVE_WIDTH);
+            println!("  Memory  {}", curve);
+            if let Some(focus) = self.focus_ms.filter(|f| (self.start_ms..=self.end_ms).contains(f)) {
+                let span = (self.end_ms - self.start_ms).max(1);
+                let column = ((focus - self.start_ms) as u128 * (CURVE_WIDTH - 1) as u128 / span as u128) as usize;
+                println!("          {}^ {}", " ".repeat(column), format_time(focus, "%H:%M:%S"));
+            }
+            if let Some(peak) = self.peak {
+                println!("  Peak {}% at {} ({} MB used)", peak.load_percent, format_time(peak.timestamp_ms, "%H:%M:%S"), peak.used_mb);
+            }
+        }
+
+        if self.events.is_empty() {
+            println!("\n  No events in this window.");
+            return;
+        }
+
+        println!("\n┌──────────┬──────────────┬──────────────────────────────────────────────────────┬──────┐");
+        println!("│ Time     │ Kind         │ Event                                                │ Mem  │");
+        println!("├──────────┼──────────────┼──────────────────────────────────────────────────────┼──────┤");
+        for event in &self.events {
+            let mem = event.memory_load.map(|m| format!("{}%", m)).unwrap_or_else(|| "-".into());
+            println!(
+                "│ {} │ {:12} │ {:52} │ {:>4} │",
+                format_time(event.timestamp_ms, "%H:%M:%S"),
+                event.kind.label(),
+                truncate(&event.summary, 52),
+                mem
+            );
+        }
+        println!("└──────────┴──────────────┴──────────────────────────────────────────────────────┴──────┘");
+
+        if let (false, Some(focus)) = (self.correlations.is_empty(), self.focus_ms) {
+            println!("\n🔗 Likely related to {}:", format_time(focus, "%H:%M:%S"));
+            for (i, c) in self.correlations.iter().enumerate() {
+                let when = match c.lead_secs {
+                    s if s > 0 => format!("{}s before", s),
+                    s if s < 0 => format!("{}s after", -s),
+                    _ => "at that moment".to_string(),
+                };
+                println!(
+                    "  {}. {} {}: {} (memory +{:.0}% within a minute, {})",
+                    i + 1,
+                    format_time(c.event.timestamp_ms, "%H:%M:%S"),
+                    c.event.kind,
+                    c.event.summary,
+                    c.rise_percent,
+                    when
+                );
+            }
+        }
+    }
+}
+
+/// Rank events leading up to `focus_ms` by the memory rise right after them
+fn correlate(samples: &[MemoryPoint], events: &[TimelineEvent], focus_ms: u64) -> Vec<Correlation> {
+    let mut correlations: Vec<Correlation> = events
+        .iter()
+        // Causes come first; allow for the sampling interval
+        .filter(|e| e.timestamp_ms <= focus_ms + SAMPLE_INTERVAL.as_millis() as u64)
+        .filter(|e| e.kind != EventKind::Optimization)
+        .filter_map(|event| {
+            let t = event.timestamp_ms;
+            let before: Vec<f64> = samples
+                .iter()
+                .filter(|p| p.timestamp_ms < t && p.timestamp_ms + RELATED_WINDOW_MS >= t)
+                .map(|p| p.load_percent as f64)
+                .collect();
+            let before = if before.is_empty() {
+                event.memory_load? as f64
+            } else {
+                before.iter().sum::<f64>() / before.len() as f64
+            };
+            let after = samples
+                .iter()
+                .filter(|p| p.timestamp_ms >= t && p.timestamp_ms <= t + RELATED_WINDOW_MS)
+                .map(|p| p.load_percent as f64)
+                .fold(f64::NAN, f64::max);
+            let rise = after - before;
+            let lead_ms = focus_ms as i64 - t as i64;
+            let score = rise * (1.0 / (1.0 + lead_ms.unsigned_abs() as f64 / PROXIMITY_MS));
+            // NaN (no samples after) fails this check too
+            (rise >= 1.0).then(|| Correlation { event: event.clone(), rise_percent: rise, lead_secs: lead_ms / 1000, score })
+        })
+        .collect();
+    correlations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    correlations.truncate(MAX_CORRELATIONS);
+    correlations
+}
+
+/// Sparkline of memory load across `[start_ms, end_ms]`; gaps show as spaces
+fn memory_curve(samples: &[MemoryPoint], start_ms: u64, end_ms: u64, width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let span = (end_ms.saturating_sub(start_ms)).max(1) as u128;
+    let mut peaks: Vec<Option<u32>> = vec![None; width];
+    for p in samples.iter().filter(|p| (start_ms..=end_ms).contains(&p.timestamp_ms)) {
+        let column = ((p.timestamp_ms - start_ms) as u128 * (width - 1) as u128 / span) as usize;
+        let slot = &mut peaks[column];
+        *slot = Some(slot.map_or(p.load_percent, |v| v.max(p.load_percent)));
+    }
+    peaks
+        .iter()
+        .map(|v| match v {
+            Some(load) => BARS[((*load).min(100) as usize * (BARS.len() - 1)) / 100],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Parse `HH:MM[:SS]` (most recent past occurrence) or `YYYY-MM-DD HH:MM[:SS]`
+pub fn parse_at(s: &str, now: DateTime<Local>) -> Result<DateTime<Local>, String> {
+    let s = s.trim();
+    let local = |naive: NaiveDateTime| {
+        Local.from_local_datetime(&naive).earliest().ok_or_else(|| format!("'{}' does not exist in local time", s))
+    };
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, format) {
+            return local(naive);
+        }
+    }
+    let time = NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM or YYYY-MM-DD HH:MM", s))?;
+    let today: NaiveDate = now.date_naive();
+    let at = local(today.and_time(time))?;
+    if at > now {
+        local((today - chrono::Duration::days(1)).and_time(time))
+    } else {
+        Ok(at)
+    }
+}
+
+/// View of the default journal for `minutes` around `at`, or up to now
+///
+/// `at` is parsed with [`parse_at`] and becomes the moment correlated against;
+/// without it the window ends now and the memory peak is used.
+pub fn query(at: Option<&str>, minutes: u64) -> Result<TimelineView, String> {
+    let span_ms = minutes.max(1) * 60_000;
+    let (start_ms, end_ms, focus_ms) = match at {
+        Some(at) => {
+            let focus = parse_at(at, Local::now())?.timestamp_millis().max(0) as u64;
+            (focus.saturating_sub(span_ms / 2), focus + span_ms / 2, Some(focus))
+        }
+        None => {
+            let now = now_ms();
+            (now.saturating_sub(span_ms), now, None)
+        }
+    };
+    Timeline::open_default()?.view(start_ms, end_ms, focus_ms)
+}
+
+/// Recent available memory and where it is heading
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastView {
+    pub total_mb: f64,
+    /// `(timestamp_ms, available_mb)` from the journal, oldest first
+    pub history: Vec<(u64, f64)>,
+    /// `None` while the journal holds too little recent history
+    pub forecast: Option<Forecast>,
+    /// Available memory at the critical threshold
+    pub floor_mb: f64,
+    /// Minutes until available memory is forecast to reach `floor_mb`,
+    /// within the longest horizon
+    pub exhaustion_minutes: Option<u64>,
+}
+
+impl ForecastView {
+    pub fn print(&self) {
+        println!("\n📈 Memory forecast\n");
+        let Some(forecast) = &self.forecast else {
+            println!("  Not enough recent memory samples yet (is the tray or daemon running?)");
+            return;
+        };
+        forecast.print();
+        println!();
+        match self.exhaustion_minutes {
+            Some(0) => println!("  Available memory is already below {:.0} MB", self.floor_mb),
+            Some(minutes) => println!("  ⚠️  Available memory may fall below {:.0} MB in ~{} min", self.floor_mb, minutes),
+            None => {
+                let longest = forecast.points.last().map_or(0, |p| p.minutes);
+                println!("  No exhaustion expected within {} min", longest)
+            }
+        }
+    }
+}
+
+/// Forecaster fed with the last [`FORECAST_HISTORY_MS`] of the default
+/// journal, and the `(timestamp_ms, available_mb)` samples it saw
+///
+/// The journal keeps used memory; `total_mb` turns it into available memory.
+pub fn forecaster(total_mb: f64) -> Result<(HoltForecaster, Vec<(u64, f64)>), String> {
+    let end_ms = now_ms();
+    let (samples, _) = Timeline::open_default()?.read(end_ms.saturating_sub(FORECAST_HISTORY_MS), end_ms)?;
+    let history: Vec<(u64, f64)> =
+        samples.iter().map(|p| (p.timestamp_ms, (total_mb - p.used_mb as f64).max(0.0))).collect();
+    Ok((HoltForecaster::from_samples(history.iter().copied()), history))
+}
+
+/// Forecast at `horizons` from the default journal, against the available
+/// memory left at `critical_threshold` percent load
+pub fn forecast(critical_threshold: u32, horizons: &[u64]) -> Result<ForecastView, String> {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let total_mb = (system.total_memory() / 1024 / 1024) as f64;
+    let (forecaster, history) = forecaster(total_mb)?;
+    let floor_mb = total_mb * 100u32.saturating_sub(critical_threshold) as f64 / 100.0;
+    let longest = horizons.iter().copied().max().unwrap_or(0);
+    Ok(ForecastView {
+        total_mb,
+        forecast: forecaster.forecast(total_mb, horizons),
+        exhaustion_minutes: forecaster.minutes_until(floor_mb, longest),
+        floor_mb,
+        history,
+    })
+}
+
+/// Record an event in the default journal, logging instead of failing
+pub fn record(kind: EventKind, summary: impl Into<String>) {
+    let event = TimelineEvent::now(kind, summary);
+    if let Err(e) = Timeline::open_default().and_then(|t| t.record_event(&event)) {
+        tracing::warn!("Failed to record timeline event: {}", e);
+    }
+}
+
+fn load_percent(used: u64, total: u64) -> Option<u32> {
+    (total > 0).then(|| (used as f64 / total as f64 * 100.0).round() as u32)
+}
+
+fn format_time(ms: u64, format: &str) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_else(|| "?".into())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(secs: u64, load: u32) -> MemoryPoint {
+        MemoryPoint { timestamp_ms: secs * 1000, load_percent: load, used_mb: 0, swap_used_mb: 0 }
+    }
+
+    fn event(secs: u64, kind: EventKind, summary: &str) -> TimelineEvent {
+        TimelineEvent { timestamp_ms: secs * 1000, kind, summary: summary.into(), memory_load: None }
+    }
+
+    #[test]
+    fn test_correlation_ranks_cause_of_spike() {
+        let samples: Vec<MemoryPoint> = (0..30)
+            .map(|i| point(1000 + i * 10, if i < 15 { 60 } else { 60 + (i as u32 - 14) * 3 }))
+            .collect();
+        let events = vec![
+            event(1020, EventKind::AppLaunch, "notepad"),
+            event(1148, EventKind::AppLaunch, "teams"),
+            event(1200, EventKind::Optimization, "freed 300 MB"),
+        ];
+        let view = TimelineView::build(1_000_000, 1_300_000, None, samples, events);
+        assert_eq!(view.peak.unwrap().load_percent, 105);
+        assert_eq!(view.focus_ms, Some(1_290_000));
+        assert_eq!(view.correlations.len(), 1);
+        let top = &view.correlations[0];
+        assert_eq!(top.event.summary, "teams");
+        assert_eq!(top.lead_secs, 142);
+        assert!(top.rise_percent >= 3.0);
+    }
+
+    #[test]
+    fn test_journal_roundtrip_and_prune() {
+        let dir = std::env::temp_dir().join(format!("ruvector-timeline-{}", std::process::id()));
+        let timeline = Timeline::open(dir.join(TIMELINE_FILE)).unwrap();
+        timeline.record_memory(&point(10, 50)).unwrap();
+        timeline.record_event(&event(20, EventKind::ModeSwitch, "Profile gaming")).unwrap();
+        timeline.record_memory(&point(30, 55)).unwrap();
+
+        let (samples, events) = timeline.read(0, 25_000).unwrap();
+        assert_eq!((samples.len(), events.len()), (1, 1));
+        assert_eq!(events[0].kind, EventKind::ModeSwitch);
+        assert_eq!(timeline.prune(15_000).unwrap(), 1);
+        assert_eq!(timeline.read(0, u64::MAX).unwrap().0, vec![point(30, 55)]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_parse_at_and_curve() {
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap();
+        assert_eq!(parse_at("08:30", now).unwrap(), Local.with_ymd_and_hms(2026, 3, 10, 8, 30, 0).unwrap());
+        assert_eq!(parse_at("14:32", now).unwrap(), Local.with_ymd_and_hms(2026, 3, 9, 14, 32, 0).unwrap());
+        assert_eq!(parse_at("2026-03-01 14:32:10", now).unwrap(), Local.with_ymd_and_hms(2026, 3, 1, 14, 32, 10).unwrap());
+        assert!(parse_at("25:00", now).is_err());
+
+        let curve = memory_curve(&[point(0, 0), point(9, 100)], 0, 9_000, 10);
+        assert_eq!(curve, "▁        █");
+    }
+}
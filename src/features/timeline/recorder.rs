@@ -0,0 +1,228 @@
+//! Background sampling that fills the timeline journal
+//!
+//! Every [`SAMPLE_INTERVAL`] the recorder writes a memory sample and checks
+//! for pressure spikes and newly launched apps. Entries older than
+//! [`RETENTION`] are pruned once a day.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use sysinfo::System;
+
+use super::{load_percent, now_ms, EventKind, MemoryPoint, Timeline, TimelineEvent};
+use crate::monitor::procs;
+
+/// Time between memory samples
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Oldest process list launches are looked for in
+const PROCESS_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// How long journal entries are kept
+pub const RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// How often old entries are pruned
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// Memory load that counts as a pressure spike
+const SPIKE_LOAD_PERCENT: u32 = 85;
+/// Load must drop this far below the spike level before another spike is reported
+const SPIKE_RESET_MARGIN: u32 = 5;
+/// Rise between two samples that counts as a spike regardless of level
+const SPIKE_JUMP_PERCENT: u32 = 15;
+
+/// Apps below this working set on launch are not worth a journal entry
+const MIN_LAUNCH_MB: u64 = 50;
+
+/// Reports rising edges of memory pressure
+#[derive(Debug, Default)]
+pub struct PressureTracker {
+    last_load: Option<u32>,
+    in_spike: bool,
+}
+
+impl PressureTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a load sample; returns a summary when a spike starts
+    pub fn observe(&mut self, load: u32) -> Option<String> {
+        let previous = self.last_load.replace(load);
+        let jump = previous.map_or(0, |p| load.saturating_sub(p));
+
+        if self.in_spike {
+            if load + SPIKE_RESET_MARGIN < SPIKE_LOAD_PERCENT {
+                self.in_spike = false;
+            }
+            return None;
+        }
+
+        if load >= SPIKE_LOAD_PERCENT {
+            self.in_spike = true;
+            Some(format!("Memory load reached {}%", load))
+        } else if jump >= SPIKE_JUMP_PERCENT {
+            Some(format!("Memory load jumped {}% to {}%", jump, load))
+        } else {
+            None
+        }
+    }
+}
+
+/// Reports processes that were not running at the previous sample
+#[derive(Debug, Default)]
+pub struct LaunchTracker {
+    known: Option<HashSet<u32>>,
+}
+
+impl LaunchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed `(pid, name, memory_mb)` for all running processes
+    ///
+    /// Returns `(name, memory_mb)` of sizeable new processes. The first call
+    /// only records the baseline, and one entry is reported per app name even
+    /// when it starts several processes.
+    pub fn observe<'a>(&mut self, processes: impl IntoIterator<Item = (u32, &'a str, u64)>) -> Vec<(String, u64)> {
+        let mut current = HashSet::new();
+        let mut launched: Vec<(String, u64)> = Vec::new();
+        for (pid, name, memory_mb) in processes {
+            current.insert(pid);
+            let is_new = self.known.as_ref().is_some_and(|known| !known.contains(&pid));
+            if !is_new {
+                continue;
+            }
+            match launched.iter_mut().find(|(n, _)| n == name) {
+                Some((_, total)) => *total += memory_mb,
+                None => launched.push((name.to_string(), memory_mb)),
+            }
+        }
+        self.known = Some(current);
+        launched.retain(|(_, mb)| *mb >= MIN_LAUNCH_MB);
+        launched.sort_by_key(|l| std::cmp::Reverse(l.1));
+        launched
+    }
+}
+
+/// Writes memory samples, pressure spikes and app launches to the journal
+pub struct TimelineRecorder {
+    timeline: Timeline,
+    system: System,
+    pressure: PressureTracker,
+    launches: LaunchTracker,
+    last_sample: Option<Instant>,
+    last_prune: Option<Instant>,
+}
+
+impl TimelineRecorder {
+    pub fn new(timeline: Timeline) -> Self {
+        Self {
+            timeline,
+            system: System::new(),
+            pressure: PressureTracker::new(),
+            launches: LaunchTracker::new(),
+            last_sample: None,
+            last_prune: None,
+        }
+    }
+
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self::new(Timeline::open_default()?))
+    }
+
+    /// Sample if [`SAMPLE_INTERVAL`] has passed; cheap to call from a UI loop
+    pub fn tick(&mut self) {
+        if self.last_sample.is_some_and(|t| t.elapsed() < SAMPLE_INTERVAL) {
+            return;
+        }
+        self.last_sample = Some(Instant::now());
+        if let Err(e) = self.sample() {
+            tracing::warn!("Timeline sample failed: {}", e);
+        }
+
+        if self.last_prune.map_or(true, |t| t.elapsed() > PRUNE_INTERVAL) {
+            self.last_prune = Some(Instant::now());
+            match self.timeline.prune(now_ms().saturating_sub(RETENTION.as_millis() as u64)) {
+                Ok(0) => {}
+                Ok(removed) => tracing::debug!("Pruned {} old timeline entries", removed),
+                Err(e) => tracing::warn!("Timeline prune failed: {}", e),
+            }
+        }
+    }
+
+    /// Record one memory sample plus any spike or launch events
+    pub fn sample(&mut self) -> Result<(), String> {
+        self.system.refresh_memory();
+
+        let total = self.system.total_memory();
+        let load = load_percent(self.system.used_memory(), total).unwrap_or(0);
+        let point = MemoryPoint {
+            timestamp_ms: now_ms(),
+            load_percent: load,
+            used_mb: self.system.used_memory() / 1024 / 1024,
+            swap_used_mb: self.system.used_swap() / 1024 / 1024,
+        };
+        self.timeline.record_memory(&point)?;
+
+        let event = |kind, summary: String| TimelineEvent {
+            timestamp_ms: point.timestamp_ms,
+            kind,
+            summary,
+            memory_load: Some(load),
+        };
+
+        if let Some(summary) = self.pressure.observe(load) {
+            self.timeline.record_event(&event(EventKind::PressureSpike, summary))?;
+        }
+
+        let names: Vec<(u32, String, u64)> = procs::with_processes(PROCESS_MAX_AGE, |system| {
+            system
+                .processes()
+                .iter()
+                .map(|(pid, p)| (pid.as_u32(), p.name().to_string_lossy().into_owned(), p.memory() / 1024 / 1024))
+                .collect()
+        });
+        let launched = self.launches.observe(names.iter().map(|(pid, name, mb)| (*pid, name.as_str(), *mb)));
+        for (name, memory_mb) in launched {
+            let summary = format!("{} started ({} MB)", name, memory_mb);
+            self.timeline.record_event(&event(EventKind::AppLaunch, summary))?;
+        }
+        Ok(())
+    }
+
+    /// Sample on a background thread for the life of the process
+    pub fn spawn(mut self) {
+        std::thread::spawn(move || loop {
+            self.tick();
+            std::thread::sleep(SAMPLE_INTERVAL);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressure_spike_edges() {
+        let mut tracker = PressureTracker::new();
+        assert_eq!(tracker.observe(60), None);
+        assert_eq!(tracker.observe(80).as_deref(), Some("Memory load jumped 20% to 80%"));
+        assert_eq!(tracker.observe(88).as_deref(), Some("Memory load reached 88%"));
+        assert_eq!(tracker.observe(92), None);
+        assert_eq!(tracker.observe(82), None);
+        assert_eq!(tracker.observe(79), None);
+        assert!(tracker.observe(86).is_some());
+    }
+
+    #[test]
+    fn test_launches_after_baseline() {
+        let mut tracker = LaunchTracker::new();
+        assert!(tracker.observe([(1, "explorer", 200)]).is_empty());
+        let launched = tracker.observe([(1, "explorer", 200), (2, "teams", 300), (3, "teams", 150), (4, "tiny", 5)]);
+        assert_eq!(launched, vec![("teams".to_string(), 450)]);
+        assert!(tracker.observe([(1, "explorer", 200), (2, "teams", 300)]).is_empty());
+    }
+}
@@ -0,0 +1,490 @@
+//! WSL2 memory reclaim (ADR-016)
+//!
+//! WSL2 runs its Linux kernel in a lightweight VM hosted by the `Vmmem` /
+//! `VmmemWSL` process. The VM grows as Linux fills its page cache and rarely
+//! hands memory back to Windows. This module:
+//! - measures host-side VM memory against what the guest actually uses
+//! - reclaims it via `drop_caches` inside the guest or `wsl --shutdown`
+//! - advises on (and can write) `.wslconfig` memory settings
+//!
+//! All guest commands are fixed strings; nothing from config or user input
+//! is interpolated into a shell.
+
+use std::path::PathBuf;
+use std::process::Command;
+use sysinfo::{ProcessesToUpdate, System};
+
+/// Host processes that back the WSL2 VM
+pub const VMMEM_PROCESSES: &[&str] = &["vmmemwsl", "vmmem"];
+
+/// Vmmem below this is not worth reclaiming
+pub const MIN_RECLAIM_MB: f64 = 1024.0;
+
+/// Fixed guest command that flushes the page cache
+const DROP_CACHES_SCRIPT: &str = "sync; echo 1 > /proc/sys/vm/drop_caches";
+
+/// Value written for `autoMemoryReclaim`
+const AUTO_MEMORY_RECLAIM: &str = "gradual";
+
+/// Host-side view of the WSL2 VM process
+#[derive(Debug, Clone, Copy)]
+pub struct VmmemProcess {
+    pub pid: u32,
+    pub memory_mb: f64,
+}
+
+impl VmmemProcess {
+    /// Find the running Vmmem/VmmemWSL process, if any
+    pub fn detect() -> Option<Self> {
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::All, true);
+
+        system
+            .processes()
+            .iter()
+            .filter(|(_, p)| {
+                let name = p.name().to_string_lossy().to_lowercase();
+                let name = name.trim_end_matches(".exe");
+                VMMEM_PROCESSES.contains(&name)
+            })
+            .map(|(pid, p)| Self {
+                pid: pid.as_u32(),
+                memory_mb: p.memory() as f64 / (1024.0 * 1024.0),
+            })
+            .max_by(|a, b| a.memory_mb.partial_cmp(&b.memory_mb).unwrap())
+    }
+}
+
+/// Guest memory as reported by `/proc/meminfo` inside WSL
+#[derive(Debug, Clone, Default)]
+pub struct WslMemoryState {
+    pub total_mb: f64,
+    pub used_mb: f64,
+    pub cached_mb: f64,
+    pub buffers_mb: f64,
+    pub available_mb: f64,
+    pub swap_used_mb: f64,
+    pub active_mb: f64,
+    pub inactive_mb: f64,
+    pub slab_reclaimable_mb: f64,
+}
+
+impl WslMemoryState {
+    /// Read guest memory from the default distribution
+    pub fn read() -> Result<Self, String> {
+        let output = wsl(&["-e", "cat", "/proc/meminfo"])?;
+        Self::parse_meminfo(&output)
+    }
+
+    /// Parse `/proc/meminfo` contents
+    pub fn parse_meminfo(content: &str) -> Result<Self, String> {
+        let mut fields = std::collections::HashMap::new();
+        for line in content.lines() {
+            let Some((key, rest)) = line.split_once(':') else {
+                continue;
+            };
+            let Some(Ok(kb)) = rest.split_whitespace().next().map(str::parse::<f64>) else {
+                continue;
+            };
+            fields.insert(key.trim(), kb / 1024.0);
+        }
+
+        let get = |key: &str| fields.get(key).copied().unwrap_or(0.0);
+        let total_mb = get("MemTotal");
+        if total_mb <= 0.0 {
+            return Err("meminfo has no MemTotal".into());
+        }
+
+        Ok(Self {
+            total_mb,
+            used_mb: total_mb - get("MemFree"),
+            cached_mb: get("Cached"),
+            buffers_mb: get("Buffers"),
+            available_mb: get("MemAvailable"),
+            swap_used_mb: get("SwapTotal") - get("SwapFree"),
+            active_mb: get("Active"),
+            inactive_mb: get("Inactive"),
+            slab_reclaimable_mb: get("SReclaimable"),
+        })
+    }
+
+    /// Memory that can be reclaimed without killing processes
+    pub fn reclaimable_mb(&self) -> f64 {
+        self.cached_mb + self.buffers_mb + self.slab_reclaimable_mb
+    }
+
+    /// Memory that Linux is actually using for applications
+    pub fn actual_usage_mb(&self) -> f64 {
+        (self.used_mb - self.reclaimable_mb()).max(0.0)
+    }
+}
+
+/// Combined host/guest snapshot
+#[derive(Debug, Clone)]
+pub struct WslSnapshot {
+    pub vmmem: VmmemProcess,
+    /// `None` when WSL is idle or the guest could not be queried
+    pub guest: Option<WslMemoryState>,
+    pub running_distros: Vec<String>,
+}
+
+impl WslSnapshot {
+    /// Capture the current state, or `None` if the WSL2 VM is not running
+    pub fn capture() -> Option<Self> {
+        let vmmem = VmmemProcess::detect()?;
+        let running_distros = running_distros().unwrap_or_default();
+        let guest = if running_distros.is_empty() {
+            None
+        } else {
+            WslMemoryState::read().ok()
+        };
+
+        Some(Self { vmmem, guest, running_distros })
+    }
+
+    /// Host memory held by the VM beyond what guest applications use
+    ///
+    /// This is the ballooned share: page cache, freed-but-not-returned
+    /// pages and VM overhead, all of which Windows cannot use.
+    pub fn ballooned_mb(&self) -> f64 {
+        match &self.guest {
+            Some(guest) => (self.vmmem.memory_mb - guest.actual_usage_mb()).max(0.0),
+            None => self.vmmem.memory_mb,
+        }
+    }
+
+    /// Cheapest reclaim method that will help, or `None` if not worth it
+    pub fn recommended_method(&self) -> Option<WslReclaimMethod> {
+        if self.ballooned_mb() < MIN_RECLAIM_MB {
+            return None;
+        }
+        if self.running_distros.is_empty() {
+            // Nothing is running inside; the VM is only holding cache
+            return Some(WslReclaimMethod::Shutdown);
+        }
+        self.guest.as_ref()?;
+        Some(WslReclaimMethod::DropCaches)
+    }
+
+    pub fn print(&self) {
+        println!("\n🐧 WSL2 Memory\n");
+        println!("  Vmmem (PID {}):     {:>8.0} MB", self.vmmem.pid, self.vmmem.memory_mb);
+        match &self.guest {
+            Some(guest) => {
+                println!("  Guest total:        {:>8.0} MB", guest.total_mb);
+                println!("  Guest in use:       {:>8.0} MB", guest.actual_usage_mb());
+                println!("  Guest cache:        {:>8.0} MB", guest.reclaimable_mb());
+            }
+            None => println!("  Guest:              idle"),
+        }
+        println!("  Ballooned (host):   {:>8.0} MB", self.ballooned_mb());
+        if !self.running_distros.is_empty() {
+            println!("  Running:            {}", self.running_distros.join(", "));
+        }
+        match self.recommended_method() {
+            Some(method) => println!("\n💡 Reclaim with: {}", method),
+            None => println!("\n✓ Nothing worth reclaiming"),
+        }
+    }
+}
+
+/// How to hand VM memory back to Windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WslReclaimMethod {
+    /// Flush the guest page cache; running Linux processes are untouched
+    DropCaches,
+    /// Stop the VM entirely (`wsl --shutdown`)
+    Shutdown,
+}
+
+impl std::fmt::Display for WslReclaimMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WslReclaimMethod::DropCaches => write!(f, "drop_caches"),
+            WslReclaimMethod::Shutdown => write!(f, "wsl --shutdown"),
+        }
+    }
+}
+
+/// Outcome of a reclaim
+#[derive(Debug, Clone)]
+pub struct WslReclaimResult {
+    pub method: WslReclaimMethod,
+    pub before_mb: f64,
+    pub after_mb: f64,
+}
+
+impl WslReclaimResult {
+    pub fn freed_mb(&self) -> f64 {
+        (self.before_mb - self.after_mb).max(0.0)
+    }
+}
+
+/// Reclaim VM memory with the given method
+pub fn reclaim(method: WslReclaimMethod) -> Result<WslReclaimResult, String> {
+    let before_mb = VmmemProcess::detect()
+        .map(|v| v.memory_mb)
+        .ok_or("WSL2 is not running")?;
+
+    match method {
+        WslReclaimMethod::DropCaches => {
+            wsl(&["-u", "root", "-e", "sh", "-c", DROP_CACHES_SCRIPT])?;
+        }
+        WslReclaimMethod::Shutdown => {
+            wsl(&["--shutdown"])?;
+        }
+    }
+
+    // Vmmem releases pages asynchronously after the guest frees them
+    std::thread::sleep(std::time::Duration::from_secs(2));
+    let after_mb = VmmemProcess::detect().map(|v| v.memory_mb).unwrap_or(0.0);
+
+    Ok(WslReclaimResult { method, before_mb, after_mb })
+}
+
+/// Reclaim automatically when the VM holds enough unused memory
+///
+/// Shuts the VM down only when no distribution is running; otherwise
+/// flushes the guest page cache.
+pub fn auto_reclaim() -> Option<Result<WslReclaimResult, String>> {
+    let method = WslSnapshot::capture()?.recommended_method()?;
+    Some(reclaim(method))
+}
+
+/// Names of running distributions (`wsl -l --running -q`)
+pub fn running_distros() -> Result<Vec<String>, String> {
+    let output = wsl(&["--list", "--running", "--quiet"])?;
+    Ok(output
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Run `wsl.exe` with fixed arguments and return its decoded stdout
+fn wsl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("wsl.exe")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run wsl.exe: {}", e))?;
+
+    let stdout = decode_output(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_output(&output.stderr);
+        let detail = if stderr.trim().is_empty() { stdout } else { stderr };
+        return Err(format!("wsl.exe {} failed: {}", args.join(" "), detail.trim()));
+    }
+    Ok(stdout)
+}
+
+/// Decode wsl.exe output, which is UTF-16LE for its own messages and
+/// UTF-8 for anything printed by the guest
+fn decode_output(bytes: &[u8]) -> String {
+    let looks_utf16 = bytes.len() >= 2 && bytes.iter().skip(1).step_by(2).all(|&b| b == 0);
+    if looks_utf16 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units).trim_start_matches('\u{feff}').to_string()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Recommended `.wslconfig` settings
+#[derive(Debug, Clone)]
+pub struct WslConfigAdvice {
+    pub path: PathBuf,
+    pub current_memory: Option<String>,
+    pub current_auto_reclaim: Option<String>,
+    pub memory_gb: u64,
+    pub reason: String,
+}
+
+impl WslConfigAdvice {
+    /// Build advice from host RAM and, if available, current guest usage
+    pub fn recommend(guest: Option<&WslMemoryState>) -> Self {
+        let mut system = System::new();
+        system.refresh_memory();
+        let host_gb = system.total_memory() as f64 / (1024.0 * 1024.0 * 1024.0);
+        let path = wslconfig_path();
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+
+        let (memory_gb, reason) = recommend_memory_gb(host_gb, guest.map(|g| g.actual_usage_mb()));
+        Self {
+            current_memory: ini_get(&content, "wsl2", "memory"),
+            current_auto_reclaim: ini_get(&content, "experimental", "autoMemoryReclaim"),
+            path,
+            memory_gb,
+            reason,
+        }
+    }
+
+    pub fn print(&self) {
+        println!("\n⚙️  .wslconfig Advisor ({})\n", self.path.display());
+        println!(
+            "  [wsl2] memory:                   {} → {}GB",
+            self.current_memory.as_deref().unwrap_or("(default 50%)"),
+            self.memory_gb
+        );
+        println!(
+            "  [experimental] autoMemoryReclaim: {} → {}",
+            self.current_auto_reclaim.as_deref().unwrap_or("(disabled)"),
+            AUTO_MEMORY_RECLAIM
+        );
+        println!("\n  {}", self.reason);
+    }
+
+    /// Write the recommended settings, keeping a `.bak` copy of the old file
+    pub fn write(&self) -> Result<(), String> {
+        let content = std::fs::read_to_string(&self.path).unwrap_or_default();
+        if !content.is_empty() {
+            let backup = self.path.with_file_name(".wslconfig.bak");
+            std::fs::write(&backup, &content)
+                .map_err(|e| format!("Failed to back up {:?}: {}", self.path, e))?;
+        }
+
+        let memory = format!("{}GB", self.memory_gb);
+        let updated = ini_set(&content, "wsl2", "memory", &memory);
+        let updated = ini_set(&updated, "experimental", "autoMemoryReclaim", AUTO_MEMORY_RECLAIM);
+        std::fs::write(&self.path, updated)
+            .map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+}
+
+/// `%USERPROFILE%\.wslconfig`
+pub fn wslconfig_path() -> PathBuf {
+    std::env::var_os("USERPROFILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".wslconfig")
+}
+
+/// Cap guest memory at 1.5× observed use (min 4 GB), never above half the host
+fn recommend_memory_gb(host_gb: f64, guest_usage_mb: Option<f64>) -> (u64, String) {
+    let half_host = ((host_gb / 2.0).floor() as u64).max(2);
+    match guest_usage_mb {
+        Some(usage_mb) => {
+            let wanted = ((usage_mb * 1.5 / 1024.0).ceil() as u64).max(4);
+            let gb = wanted.min(half_host);
+            (
+                gb,
+                format!(
+                    "Guest applications use {:.1} GB; {} GB leaves headroom while returning the rest to Windows.",
+                    usage_mb / 1024.0,
+                    gb
+                ),
+            )
+        }
+        None => (
+            half_host,
+            "WSL is idle; capping at half of host RAM. Re-run while your usual workload is running for a tighter limit."
+                .to_string(),
+        ),
+    }
+}
+
+/// Read `key` from `[section]` of an INI file (case-insensitive)
+fn ini_get(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            in_section = name.trim().eq_ignore_ascii_case(section);
+        } else if in_section {
+            if let Some((k, v)) = trimmed.split_once('=') {
+                if k.trim().eq_ignore_ascii_case(key) {
+                    return Some(v.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Set `key` in `[section]`, replacing an existing entry or appending one
+fn ini_set(content: &str, section: &str, key: &str, value: &str) -> String {
+    let entry = format!("{}={}", key, value);
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut in_section = false;
+    let mut section_end = None;
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            if in_section {
+                break;
+            }
+            in_section = name.trim().eq_ignore_ascii_case(section);
+            if in_section {
+                section_end = Some(i + 1);
+            }
+        } else if in_section {
+            if let Some((k, _)) = trimmed.split_once('=') {
+                if k.trim().eq_ignore_ascii_case(key) {
+                    *line = entry;
+                    return lines.join("\n") + "\n";
+                }
+            }
+            if !trimmed.is_empty() {
+                section_end = Some(i + 1);
+            }
+        }
+    }
+
+    match section_end {
+        Some(at) => lines.insert(at, entry),
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", section));
+            lines.push(entry);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16384000 kB\n\
+                       MemFree:         2048000 kB\n\
+                       MemAvailable:   12288000 kB\n\
+                       Buffers:          512000 kB\n\
+                       Cached:          8192000 kB\n\
+                       SwapTotal:       4096000 kB\n\
+                       SwapFree:        4096000 kB\n\
+                       SReclaimable:     512000 kB\n";
+        let state = WslMemoryState::parse_meminfo(meminfo).unwrap();
+        assert_eq!(state.total_mb, 16000.0);
+        assert_eq!(state.reclaimable_mb(), 9000.0);
+        assert_eq!(state.actual_usage_mb(), 5000.0);
+        assert!(WslMemoryState::parse_meminfo("").is_err());
+    }
+
+    #[test]
+    fn test_decode_utf16_output() {
+        let bytes: Vec<u8> = "Ubuntu\r\n".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        assert_eq!(decode_output(&bytes), "Ubuntu\r\n");
+        assert_eq!(decode_output(b"MemTotal: 1 kB"), "MemTotal: 1 kB");
+    }
+
+    #[test]
+    fn test_ini_set_preserves_other_settings() {
+        let content = "[wsl2]\nmemory=8GB\nprocessors=4\n\n[interop]\nenabled=true\n";
+        let updated = ini_set(content, "wsl2", "memory", "6GB");
+        assert!(updated.contains("memory=6GB\nprocessors=4"));
+
+        let updated = ini_set(&updated, "experimental", "autoMemoryReclaim", "gradual");
+        assert_eq!(ini_get(&updated, "experimental", "automemoryreclaim").as_deref(), Some("gradual"));
+        assert_eq!(ini_get(&updated, "interop", "enabled").as_deref(), Some("true"));
+
+        let added = ini_set("[wsl2]\nprocessors=4\n\n[interop]\n", "wsl2", "memory", "4GB");
+        assert!(added.starts_with("[wsl2]\nprocessors=4\nmemory=4GB\n"));
+    }
+}
@@ -7,6 +7,7 @@
 //!
 //! - **Windows**: Full optimization via Win32 APIs
 //! - **macOS**: Memory pressure hints, purge command, Apple Silicon support
+//! - **Linux**: cgroup v2 reclaim, drop_caches, systemd service
 //!
 //! ## Features
 //!
@@ -34,6 +35,7 @@ pub mod algorithms;
 pub mod dashboard;
 pub mod accel;
 pub mod platform;
+pub mod features;
 
 // Platform-specific modules
 #[cfg(target_os = "windows")]
@@ -53,7 +55,6 @@ pub mod apps;
 pub use core::config::OptimizerConfig;
 #[cfg(target_os = "windows")]
 pub use core::optimizer::IntelligentOptimizer;
-#[cfg(target_os = "windows")]
 pub use neural::engine::NeuralDecisionEngine;
 #[cfg(target_os = "windows")]
 pub use monitor::realtime::RealtimeMonitor;
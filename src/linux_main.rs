@@ -0,0 +1,2143 @@
+//! RuVector Memory Optimizer - Linux CLI
+//!
+//! Linux memory optimizer using cgroup v2 reclaim, cache dropping and systemd.
+
+// Stub main for non-Linux platforms - must be first and unconditional
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("This binary is Linux-only.");
+    eprintln!("On Windows use ruvector-memopt, on macOS use ruvector-memopt-macos.");
+    std::process::exit(1);
+}
+
+// All Linux-specific code below
+#[cfg(target_os = "linux")]
+mod linux_impl {
+    use clap::{Parser, Subcommand};
+    use std::time::Duration;
+    use tracing::{error, info, Level};
+    use tracing_subscriber::FmtSubscriber;
+
+    use crate::algorithms::forecast::{HoltForecaster, FORECAST_RUN_INTERVAL, HORIZONS};
+    use crate::apps::diskspace;
+    use crate::apps::idle::ActivityRecorder;
+    use crate::bench::advanced::AdvancedBenchmarkRunner;
+    use crate::bench::baseline::BaselineRequest;
+    use crate::apps::{jvm, BrowserOptimizer, JvmManager, LeakDetector, SmartSuggestions, SuggestionRisk, VmManager};
+    use crate::core::budget::{self, SelfMonitor};
+    use crate::core::config::{ConfigWatcher, OptimizerConfig};
+    use crate::core::decision::OptimizationDecision;
+    use crate::core::history::{HistoryFilter, HistoryRecord, HistoryStore, OptimizationTrigger, TimeRange};
+    use crate::core::ipc::{self, DaemonControl, DaemonEvent, IpcClient, OptimizeReport, Pause};
+    use crate::core::patterns::MemoryPattern;
+    use crate::core::scheduler::{self, Schedule, ScheduleStore, ScheduledAction, Scheduler};
+    use crate::core::text::truncate;
+    use crate::core::undo;
+    use crate::core::watchdog::{Heartbeat, Watchdog};
+    use crate::features::agent::{self, Agent, RuleStore};
+    use crate::features::anomalywatch::{self, AnomalyPolicy};
+    use crate::features::fleet;
+    use crate::features::build::{BuildHistory, BuildWatcher};
+    use crate::features::bloatware::BloatwareScanner;
+    use crate::features::health::HealthScorer;
+    use crate::features::leakwatch::{self, AlertPolicy, SuspectLog};
+    use crate::features::patternwatch;
+    use crate::features::plugins::{HeuristicInput, OptimizeOutcome, OptimizeRequest, PluginHost, Sample, Verdict};
+    use crate::features::gamemode::GameDetector;
+    use crate::features::profiles::{self, ProfileManager};
+    use crate::features::report::{self, ReportPeriod};
+    use crate::features::startup::StartupManager;
+    use crate::features::thermal::{self, ThermalMonitor};
+    use crate::features::snapshot::{self, Snapshot, SnapshotDiff};
+    use crate::features::timeline::{self, TimelineRecorder};
+    use crate::monitor::alerts;
+    use crate::monitor::gpu::GpuMonitor;
+    #[cfg(feature = "ai")]
+    use crate::ai::{cuda, ollama, AIMode, AIModeConfig};
+    use crate::monitor::pressure::{PressureLevel, PressureWatcher};
+    use crate::monitor::watch::{self, WatchTarget};
+    use crate::platform::cleanup::{self, CleanupOptions};
+    use crate::neural::engine::NeuralDecisionEngine;
+    use crate::platform::{MemoryOptimizer, MemoryStatus};
+    use crate::security::audit::AuditLog;
+    use crate::security::broker::{Broker, BrokerClient, BrokerRequest};
+    use crate::security::policy::{Policy, PolicyRule, PolicyStore};
+    use crate::security::privileges::PrivilegeManager;
+    use crate::platform::linux::lowmem::{self, LowMemoryConfig};
+    use crate::platform::linux::process as oom;
+    use crate::platform::linux::{
+        CompressionAdvice, CompressionStatus, LinuxDaemonService, LinuxMemoryOptimizer, SafetyConfig, SafetyGuard,
+    };
+
+    #[derive(Parser)]
+    #[command(name = "ruvector-memopt")]
+    #[command(about = "Intelligent memory optimizer for Linux", long_about = None)]
+    struct Cli {
+        #[command(subcommand)]
+        command: Commands,
+
+        /// Config file to use instead of the shared config.toml
+        #[arg(long, global = true, value_name = "PATH")]
+        config: Option<std::path::PathBuf>,
+
+        /// Override a config key for this run (repeatable)
+        #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+        overrides: Vec<String>,
+    }
+
+    #[derive(Subcommand)]
+    enum Commands {
+        /// Show current memory status
+        Status,
+
+        /// Run one-time optimization
+        Optimize {
+            #[arg(short, long)]
+            aggressive: bool,
+
+            #[arg(long)]
+            dry_run: bool,
+
+            /// Optimize in this process even when a daemon is running
+            #[arg(long)]
+            local: bool,
+        },
+
+        /// Inspect zram/zswap and apply a recommended setup
+        Compression {
+            /// Apply the recommended changes (root, until reboot)
+            #[arg(long)]
+            apply: bool,
+        },
+
+        /// Show OOM scores and the order the kernel would kill processes in
+        Oom {
+            /// Adjust protected and expendable processes now
+            #[arg(long)]
+            apply: bool,
+            /// Number of processes to show
+            #[arg(long, default_value = "20")]
+            top: usize,
+        },
+
+        /// Start continuous optimization daemon
+        Daemon {
+            #[arg(short, long, default_value = "60")]
+            interval: u64,
+            /// Terminate a process when memory is exhausted and thrashing
+            #[arg(long)]
+            oom_killer: bool,
+            /// Log what the low-memory killer would terminate instead
+            #[arg(long, requires = "oom_killer")]
+            oom_dry_run: bool,
+            /// Restart the daemon when it crashes or stops responding
+            #[arg(long)]
+            watchdog: bool,
+        },
+
+        /// Pause the running daemon's automatic optimization
+        Pause {
+            /// How long, like `2h`, `30m` or `1h30m`, or until a time like
+            /// `17:00`; omit to pause until `resume`
+            when: Option<String>,
+        },
+
+        /// Resume the running daemon after `pause`
+        Resume,
+
+        /// Print the running daemon's events as they happen
+        Events,
+
+        /// Analyze processes with PageRank priority scoring
+        Pagerank {
+            #[arg(short, long, default_value = "10")]
+            top: usize,
+            /// Seconds to watch page faults for the coldness column
+            #[arg(long, default_value = "3")]
+            sample_secs: u64,
+        },
+
+        /// Show process clusters (MinCut analysis)
+        Clusters {
+            #[arg(short, long, default_value = "5")]
+            max: usize,
+        },
+
+        /// Run the algorithm benchmarks (MinCut, PageRank, Sketch, Spectral)
+        Bench {
+            #[arg(short, long, default_value = "100")]
+            iterations: usize,
+
+            /// Save the results as a named baseline
+            #[arg(long, value_name = "NAME")]
+            save_baseline: Option<String>,
+
+            /// Compare the results with a saved baseline; exits with 1 on a regression
+            #[arg(long, value_name = "NAME")]
+            compare: Option<String>,
+
+            /// Change in ops/sec or p95 latency, in percent, that counts as a regression
+            #[arg(long, default_value = "10")]
+            fail_threshold: f64,
+
+            /// Directory of baselines (default: bench/ in the config directory)
+            #[arg(long)]
+            baseline_dir: Option<std::path::PathBuf>,
+
+            /// Optimize for real and report freed memory, refault time and latency impact
+            #[arg(long)]
+            real: bool,
+
+            /// Seconds to watch memory refault after each optimization with --real
+            #[arg(long, default_value = "60")]
+            refault_secs: u64,
+
+            /// Print the results, the comparison or the --real report as JSON
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// Show browser memory usage (Chrome, Firefox, Edge, Brave)
+        Browsers,
+
+        /// AI runtimes, GPU memory and Ollama models (needs the ai feature)
+        Ai {
+            #[command(subcommand)]
+            action: AiCommand,
+        },
+
+        /// Show GPU VRAM usage
+        Gpu {
+            #[command(subcommand)]
+            action: GpuCommand,
+        },
+
+        /// Show virtual machine memory (VirtualBox, VMware)
+        Vms,
+
+        /// Show JVM IDEs and Gradle daemons with heap sizing advice
+        Jvm {
+            /// Output as JSON
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// List and manage programs that start at login
+        Startup {
+            #[command(subcommand)]
+            action: StartupCommand,
+        },
+
+        /// Detect builds and boost them while they run
+        Build {
+            #[command(subcommand)]
+            action: BuildCommand,
+        },
+
+        /// Show what looks like a game and teach Game Mode about others
+        Games {
+            #[command(subcommand)]
+            action: GamesCommand,
+        },
+
+        /// Show CPU/GPU temperatures and throttle forecast
+        Thermal {
+            /// Keep sampling and show the trend
+            #[arg(long)]
+            watch: bool,
+        },
+
+        /// Find unused or preinstalled apps worth uninstalling
+        Bloatware {
+            /// List every installed app, not just recommendations
+            #[arg(long)]
+            all: bool,
+            /// App name or id to uninstall
+            #[arg(long, value_name = "APP")]
+            remove: Option<String>,
+            /// Actually run the uninstall command for --remove
+            #[arg(long)]
+            confirm: bool,
+        },
+
+        /// Show the system health score and its trend against last week
+        Health,
+
+        /// Switch optimization profiles (gaming, meetings, battery, ...)
+        Profile {
+            #[command(subcommand)]
+            action: ProfileCommand,
+        },
+
+        /// Automation rules mapping triggers to actions
+        Agent {
+            #[command(subcommand)]
+            action: AgentCommand,
+        },
+
+        /// Manage plugins from the plugins directory
+        Plugins {
+            #[command(subcommand)]
+            action: PluginsCommand,
+        },
+
+        /// Detect potential memory leaks
+        Leaks {
+            /// Number of samples to take
+            #[arg(short, long, default_value = "10")]
+            samples: usize,
+
+            /// Interval between samples in seconds
+            #[arg(short, long, default_value = "5")]
+            interval: u64,
+
+            /// Print leak reports as JSON
+            #[arg(long)]
+            json: bool,
+
+            /// Show suspects raised by the background monitor instead of sampling
+            #[arg(long)]
+            history: bool,
+        },
+
+        /// Show smart optimization suggestions
+        Suggest {
+            /// Carry out the suggestion with this id
+            #[arg(long, value_name = "ID")]
+            apply: Option<String>,
+
+            /// Apply a medium- or high-risk suggestion (without it, only show what would happen)
+            #[arg(long)]
+            confirm: bool,
+
+            /// Output as JSON
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// Show optimization history
+        History {
+            /// Only show runs from the last N hours (0 = all)
+            #[arg(long, default_value = "24")]
+            hours: u64,
+
+            /// Filter by trigger (manual, auto, startup, scheduled, service, agent)
+            #[arg(short, long)]
+            trigger: Option<String>,
+
+            /// Maximum number of runs to show
+            #[arg(short, long, default_value = "20")]
+            limit: usize,
+        },
+
+        /// Show or change configuration
+        Config {
+            #[command(subcommand)]
+            action: Option<ConfigCommand>,
+        },
+
+        /// Revert the most recent optimization (suspended apps, paused containers, throttled processes)
+        Undo {
+            /// Show what would be reverted without changing anything
+            #[arg(long)]
+            show: bool,
+        },
+
+        /// Show the signed audit log of actions taken on other processes
+        Audit {
+            #[command(subcommand)]
+            action: AuditCommand,
+        },
+
+        /// Show what happened around a moment, correlated with the memory curve
+        Timeline {
+            /// Moment to explain (HH:MM, or YYYY-MM-DD HH:MM); defaults to the peak of the last window
+            #[arg(long)]
+            at: Option<String>,
+
+            /// Window length in minutes
+            #[arg(short, long, default_value = "30")]
+            minutes: u64,
+
+            /// Print JSON instead of a table
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// Show the terminal dashboard (works over SSH)
+        Dashboard,
+
+        /// Live memory, CPU and handles of one app or category, refreshed in place
+        Watch {
+            /// Part of the process name, e.g. chrome
+            #[arg(long, conflicts_with = "category")]
+            name: Option<String>,
+
+            /// App category: browser, development, communication, media, container, vm or ai
+            #[arg(long)]
+            category: Option<String>,
+
+            /// Seconds between samples
+            #[arg(short, long, default_value = "1")]
+            interval: u64,
+        },
+
+        /// Save a snapshot of every process, or compare two snapshots
+        Snapshot {
+            #[command(subcommand)]
+            action: SnapshotCommand,
+        },
+
+        /// Forecast available memory 30, 60 and 120 minutes ahead
+        Forecast {
+            /// Print JSON instead of a table
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// Flush the DNS cache and remove old temp files, thumbnails and app caches
+        Cleanup {
+            /// Show what would be removed without removing anything
+            #[arg(long)]
+            dry_run: bool,
+            /// Only these targets (dns, temp, thumbnails, caches); repeatable. Caches are only cleaned when named
+            #[arg(short, long)]
+            target: Vec<String>,
+            /// Keep files matching this glob, on top of `cleanup_exclude`; repeatable
+            #[arg(short = 'x', long)]
+            exclude: Vec<String>,
+            /// Print JSON instead of a summary
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// Show how much space browser caches, package caches and old installers take
+        Disk {
+            /// Print JSON instead of a table
+            #[arg(long)]
+            json: bool,
+        },
+
+        /// Write a daily or weekly summary report
+        Report {
+            #[command(subcommand)]
+            action: ReportCommand,
+        },
+
+        /// Send alerts on critical events to webhooks, Slack, Discord or email
+        Alerts {
+            #[command(subcommand)]
+            action: AlertsCommand,
+        },
+
+        /// Manage optimization schedules
+        Schedule {
+            #[command(subcommand)]
+            action: ScheduleCommand,
+        },
+
+        /// Manage process include/exclude rules
+        Policy {
+            #[command(subcommand)]
+            action: PolicyCommand,
+        },
+
+        /// Manage the systemd service
+        Service {
+            #[command(subcommand)]
+            action: ServiceAction,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum ScheduleCommand {
+        /// List configured schedules
+        List,
+        /// Add a schedule
+        Add {
+            /// Action to run (optimize, aggressive, browser-trim, report-day, report-week)
+            #[arg(short, long, default_value = "optimize")]
+            action: String,
+
+            /// Run daily at HH:MM
+            #[arg(long, conflicts_with = "every", required_unless_present = "every")]
+            at: Option<String>,
+
+            /// Run every N minutes
+            #[arg(long)]
+            every: Option<u32>,
+
+            /// Days to run on (all, weekdays, weekends, or e.g. mon,wed,fri)
+            #[arg(long, default_value = "all")]
+            days: String,
+
+            /// Only run within this hour window (e.g. 9-18)
+            #[arg(long)]
+            hours: Option<String>,
+        },
+        /// Remove a schedule by ID
+        Remove {
+            id: u32,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum StartupCommand {
+        /// List startup items ranked by estimated login impact
+        List {
+            /// Include disabled items
+            #[arg(long)]
+            all: bool,
+        },
+        /// Stop an item from starting at login
+        Disable {
+            /// Item name or id (from `startup list`)
+            item: String,
+        },
+        /// Start an item at login again
+        Enable {
+            /// Item name or id (from `startup list`)
+            item: String,
+        },
+        /// Start an item some time after login instead of immediately
+        Delay {
+            /// Item name or id (from `startup list`)
+            item: String,
+            /// Delay in seconds
+            #[arg(short, long, default_value = "60")]
+            secs: u32,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum BuildCommand {
+        /// Boost builds as they start and report measured speedups
+        Watch {
+            /// Only measure, to record an unboosted baseline
+            #[arg(long)]
+            no_boost: bool,
+        },
+        /// Show recorded builds and their speedups
+        History {
+            /// Number of builds to show
+            #[arg(short = 'n', long, default_value = "20")]
+            limit: usize,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum GamesCommand {
+        /// Score running programs and show why each looks like a game
+        Detect,
+        /// List games you taught and programs you ruled out
+        List,
+        /// Always treat a program as a game
+        Teach {
+            /// Process name, e.g. MyGame.exe
+            name: String,
+        },
+        /// Never treat a program as a game
+        Ignore {
+            /// Process name
+            name: String,
+        },
+        /// Drop a program from both lists
+        Forget {
+            /// Process name
+            name: String,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum ProfileCommand {
+        /// List profiles and show which one is active
+        List,
+        /// Show what a profile changes
+        Show {
+            /// Profile name
+            name: String,
+        },
+        /// Switch to a profile
+        Apply {
+            /// Profile name
+            name: String,
+        },
+        /// Leave the active profile and restore the settings from before it
+        Off,
+        /// Switch profiles by their activation rules (AC/battery, running apps)
+        Auto {
+            /// Keep checking until interrupted
+            #[arg(long)]
+            watch: bool,
+        },
+        /// Write the built-in profiles to profiles.toml for editing
+        Init,
+    }
+
+    #[derive(Subcommand)]
+    enum AgentCommand {
+        /// List rules
+        List,
+        /// Evaluate the rules and run the actions of those that match
+        Run {
+            /// Show what would run without running it
+            #[arg(long)]
+            dry_run: bool,
+            /// Keep evaluating until interrupted
+            #[arg(long)]
+            watch: bool,
+        },
+        /// Replay recent timeline history against the rules without running anything
+        Test {
+            /// Hours of history to replay
+            #[arg(long, default_value = "24")]
+            hours: u64,
+        },
+        /// Write example rules to agent_rules.toml for editing
+        Init,
+    }
+
+    #[derive(Subcommand)]
+    enum PluginsCommand {
+        /// List installed plugins and their state
+        List,
+        /// Enable a plugin after checking that it loads
+        Enable {
+            /// Plugin id (library file name without extension)
+            id: String,
+        },
+        /// Disable a plugin
+        Disable {
+            /// Plugin id
+            id: String,
+        },
+        /// Run a command a plugin provides
+        Run {
+            /// Plugin id
+            id: String,
+            /// Command name
+            command: String,
+            /// Arguments passed to the command
+            #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+            args: Vec<String>,
+        },
+        /// Ask plugins and WASM heuristics what they would do right now
+        Recommend {
+            /// Carry out the recommendations
+            #[arg(long)]
+            apply: bool,
+        },
+    }
+
+    #[derive(Subcommand)]
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
+    enum AiCommand {
+        /// Show AI runtimes, GPU memory and loaded Ollama models
+        Status {
+            /// Output as JSON
+            #[arg(long)]
+            json: bool,
+        },
+        /// List Ollama models with their size and when they were last used
+        Models {
+            /// Output as JSON
+            #[arg(long)]
+            json: bool,
+        },
+        /// Unload Ollama models from memory
+        Unload {
+            /// Models to unload, e.g. llama3.2
+            #[arg(required_unless_present = "all")]
+            models: Vec<String>,
+            /// Unload every loaded model
+            #[arg(long, conflicts_with = "models")]
+            all: bool,
+        },
+        /// Unload idle models, or all of them when VRAM is nearly full
+        Optimize,
+        /// Find PyTorch/CUDA processes and tell training from inference
+        Cuda {
+            /// Lower the priority of training processes (undo with `undo`)
+            #[arg(long)]
+            lower_priority: bool,
+            /// Output as JSON
+            #[arg(long)]
+            json: bool,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum GpuCommand {
+        /// Show VRAM, temperature and load per GPU
+        Status,
+        /// Refresh the GPU table until interrupted
+        Watch {
+            /// Refresh interval in seconds
+            #[arg(short, long, default_value = "2")]
+            interval: u64,
+        },
+        /// List processes by VRAM usage
+        Top {
+            /// Number of processes to show
+            #[arg(short = 'n', long, default_value = "15")]
+            limit: usize,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum PolicyCommand {
+        /// List rules in evaluation order
+        List,
+        /// Add a rule
+        Add {
+            /// allow (always optimize), deny (never touch) or background (lowered during builds and games)
+            effect: String,
+
+            /// Pattern to match
+            pattern: String,
+
+            /// What the pattern matches (glob, regex, path, publisher)
+            #[arg(short, long, default_value = "glob")]
+            kind: String,
+        },
+        /// Remove a rule by ID
+        Remove {
+            id: u32,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum ConfigCommand {
+        /// Print the effective config (file, environment and --set overrides)
+        Show,
+        /// Print one key, e.g. `config get pressure_threshold`
+        Get { key: String },
+        /// Change one key in config.toml, e.g. `config set pressure_threshold 85`
+        Set { key: String, value: String },
+        /// Open config.toml in $VISUAL/$EDITOR and save it once it validates
+        Edit,
+        /// Check config.toml (or another file) for errors and unknown keys
+        Validate { path: Option<std::path::PathBuf> },
+    }
+
+    #[derive(Subcommand)]
+    enum AuditCommand {
+        /// Print the most recent records, flagging any with a bad signature
+        Tail {
+            /// Number of records to show
+            #[arg(short = 'n', long, default_value = "20")]
+            lines: usize,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum ReportCommand {
+        /// Write memory trends, optimizations, growing apps, leaks, health and advice to a file
+        Generate {
+            /// Period to cover (day, week)
+            #[arg(short, long, default_value = "week")]
+            period: String,
+            /// html or markdown; defaults to `report_format`
+            #[arg(short, long)]
+            format: Option<String>,
+            /// Directory to write to; defaults to `report_dir`
+            #[arg(short, long)]
+            output: Option<std::path::PathBuf>,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum AlertsCommand {
+        /// Send a test alert to every channel in `[alerts]`
+        Test,
+    }
+
+    #[derive(Subcommand)]
+    enum SnapshotCommand {
+        /// Save per-process memory, handles, threads and VRAM, and GPU memory
+        Take {
+            /// File to write; defaults to snapshots/<date>-<time>.json in the config directory
+            #[arg(short, long)]
+            output: Option<std::path::PathBuf>,
+        },
+        /// List saved snapshots
+        List,
+        /// Show the apps that grew and shrank the most between two snapshots
+        Diff {
+            /// Earlier snapshot: a file, a name from `snapshot list`, or `latest`
+            from: String,
+
+            /// Later snapshot; defaults to one taken now
+            to: Option<String>,
+
+            /// Apps to show in each direction
+            #[arg(short = 'n', long, default_value = "10")]
+            limit: usize,
+
+            /// Print JSON instead of tables
+            #[arg(long)]
+            json: bool,
+        },
+    }
+
+    #[derive(Subcommand)]
+    enum ServiceAction {
+        /// Install and start the systemd unit (user unit unless run as root)
+        Install {
+            #[arg(short, long, default_value = "60")]
+            interval: u64,
+        },
+        /// Stop and remove the systemd unit
+        Uninstall,
+        /// Show service state
+        Status,
+    }
+
+    pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+        // Initialize logging
+        let subscriber = FmtSubscriber::builder()
+            .with_max_level(Level::INFO)
+            .with_target(false)
+            .finish();
+        tracing::subscriber::set_global_default(subscriber)?;
+
+        let cli = Cli::parse();
+        if let Some(path) = cli.config.clone() {
+            crate::core::config::set_config_path(path);
+        }
+        crate::core::config::set_cli_overrides(&cli.overrides)?;
+
+        match cli.command {
+            Commands::Status => {
+                let status = LinuxMemoryOptimizer::get_memory_status()?;
+                println!("Memory Status:");
+                println!("  Total:     {:.0} MB", status.total_physical_mb);
+                println!("  Available: {:.0} MB", status.available_physical_mb);
+                println!("  Used:      {:.0} MB", status.used_physical_mb());
+                println!("  Cached:    {:.0} MB", status.cached_mb);
+                println!("  Load:      {}%", status.memory_load_percent);
+                println!("  Swap:      {:.0}/{:.0} MB",
+                    status.total_swap_mb - status.available_swap_mb,
+                    status.total_swap_mb);
+                let compression = CompressionStatus::detect();
+                if let Some(summary) = compression.summary() {
+                    println!("  Compressed: {:.0} MB ({})", compression.compressed_mb(), summary);
+                }
+                println!("  Pressure:  {}",
+                    if status.is_critical() { "CRITICAL" }
+                    else if status.is_high_pressure() { "HIGH" }
+                    else { "Normal" }
+                );
+                if let Some(daemon) = IpcClient::connect().and_then(|mut client| client.status().ok()) {
+                    println!();
+                    daemon.print();
+                }
+            }
+
+            Commands::Compression { apply } => {
+                let compression = CompressionStatus::detect();
+                compression.print();
+                let status = LinuxMemoryOptimizer::get_memory_status()?;
+                let advice = CompressionAdvice::recommend(&compression, status.total_physical_mb);
+                advice.print();
+                if apply && !advice.changes.is_empty() {
+                    match advice.apply() {
+                        Ok(applied) => {
+                            for change in applied {
+                                println!("  ✓ {}", change);
+                            }
+                            println!("\nChanges last until reboot.");
+                        }
+                        Err(e) => println!("\nFailed to apply: {}", e),
+                    }
+                }
+            }
+
+            Commands::Oom { apply, top } => {
+                let policy = Policy::load_default();
+                if apply {
+                    let is_root = unsafe { libc::geteuid() == 0 };
+                    let result = oom::apply_oom_policy(&policy, is_root);
+                    println!("Adjusted {} processes ({} failed)", result.adjusted, result.failed);
+                    if !is_root {
+                        println!("Run as root to also protect system processes.");
+                    }
+                }
+                oom::print_table(&oom::oom_table(&policy), top);
+            }
+
+            Commands::Optimize { aggressive, dry_run, local } => {
+                // A running daemon applies its own rate limit and safety checks
+                if !dry_run && !local {
+                    if let Some(mut daemon) = IpcClient::connect() {
+                        match daemon.optimize(aggressive) {
+                            Ok(report) => report.print(),
+                            Err(e) => println!("Daemon did not optimize: {}", e),
+                        }
+                        return Ok(());
+                    }
+                }
+
+                let mut safety = SafetyGuard::new(SafetyConfig {
+                    dry_run,
+                    ..Default::default()
+                });
+
+                let status = LinuxMemoryOptimizer::get_memory_status()?;
+
+                if let Err(e) = safety.check_safe(status.available_physical_mb) {
+                    println!("Safety check failed: {}", e);
+                    return Ok(());
+                }
+
+                let optimizer = LinuxMemoryOptimizer::new();
+
+                if dry_run {
+                    println!("DRY RUN - No changes will be made");
+                    println!("Would run: {} optimization ({})",
+                        if aggressive { "aggressive" } else { "standard" },
+                        if optimizer.has_root_privileges() { "drop caches" } else { "cgroup reclaim" });
+                    return Ok(());
+                }
+
+                let result = optimizer.optimize(aggressive)?;
+
+                safety.record_attempt(result.freed_mb >= 0.0);
+                crate::core::history::record(
+                    HistoryRecord::now(
+                        OptimizationTrigger::Manual,
+                        aggressive,
+                        result.freed_mb,
+                        result.processes_affected,
+                        result.duration_ms,
+                    )
+                    .with_memory_load(status.memory_load_percent),
+                );
+
+                println!("Optimization complete:");
+                println!("  Method:    {:?}", result.method);
+                println!("  Freed:     {:.1} MB", result.freed_mb);
+                println!("  Before:    {:.1} MB available", result.before_available_mb);
+                println!("  After:     {:.1} MB available", result.after_available_mb);
+                println!("  Duration:  {} ms", result.duration_ms);
+            }
+
+            Commands::Daemon { interval, oom_killer, oom_dry_run, watchdog } => {
+                if watchdog {
+                    let hang_after = crate::core::watchdog::hang_after(Duration::from_secs(interval));
+                    Watchdog::new("daemon", Some(hang_after)).run()?;
+                    return Ok(());
+                }
+                crate::core::watchdog::install_panic_hook();
+                let mut config = OptimizerConfig::load_or_default();
+                let worker = BrokerClient::is_worker();
+                if config.least_privilege && !worker {
+                    return run_broker(&config, interval, oom_killer, oom_dry_run);
+                }
+                // Bound before a worker drops root, so it can use /run
+                let wake = std::sync::Arc::new(tokio::sync::Notify::new());
+                let control = {
+                    let wake = wake.clone();
+                    DaemonControl::new(config.clone(), move || wake.notify_one())
+                };
+                match ipc::listen(control.clone()) {
+                    Ok(path) => info!("Listening for clients on {}", path.display()),
+                    Err(e) => tracing::warn!("Control channel unavailable: {}", e),
+                }
+                // Privileged work goes through the broker from here on
+                let optimizer: Box<dyn MemoryOptimizer> = if worker {
+                    PrivilegeManager::new().drop_to_user(&config.worker_user)?;
+                    Box::new(BrokerClient::connect()?)
+                } else {
+                    crate::platform::create_optimizer()
+                };
+                info!("Starting optimization daemon (interval: {}s)", interval);
+
+                match TimelineRecorder::open_default() {
+                    Ok(recorder) => recorder.spawn(),
+                    Err(e) => tracing::warn!("Timeline unavailable: {}", e),
+                }
+                match ActivityRecorder::open_default() {
+                    Ok(recorder) => recorder.spawn(),
+                    Err(e) => tracing::warn!("App activity unavailable: {}", e),
+                }
+
+                let mut config_watcher = ConfigWatcher::new();
+                leakwatch::spawn(AlertPolicy::from_config(&config));
+                patternwatch::spawn({
+                    let control = control.clone();
+                    move |shift| {
+                        control.publish(DaemonEvent::PatternShift {
+                            from: shift.from.to_string(),
+                            to: shift.to.to_string(),
+                        })
+                    }
+                });
+                anomalywatch::spawn(AnomalyPolicy::from_config(&config), {
+                    let control = control.clone();
+                    move |anomaly| {
+                        control.publish(DaemonEvent::Anomaly {
+                            metric: anomaly.metric.to_string(),
+                            message: anomaly.message(),
+                        })
+                    }
+                });
+                fleet::spawn();
+                crate::monitor::mqtt::spawn(&config);
+                alerts::spawn(&config);
+                tokio::spawn(alerts::exit_on_signal());
+                if oom_killer && !worker {
+                    if let Err(e) = lowmem::spawn(LowMemoryConfig::from_config(&config, oom_dry_run)) {
+                        error!("Low-memory killer disabled: {}", e);
+                    }
+                }
+                let mut safety = SafetyGuard::new(SafetyConfig {
+                    min_interval: Duration::from_secs(config.min_interval_secs),
+                    ..Default::default()
+                });
+                let mut schedules = Scheduler::load_default();
+                let mut engine = if config.neural_enabled {
+                    match NeuralDecisionEngine::new(&config) {
+                        Ok(engine) => Some(engine),
+                        Err(e) => {
+                            error!("Failed to initialize neural engine: {}. Using load thresholds.", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+                let mut pressure = PressureWatcher::from_config(&config).subscribe();
+                let mut watching = true;
+                let mut level = PressureLevel::Normal;
+                let mut plugins = PluginHost::load_default();
+                let oom_policy = Policy::load_default();
+                let is_root = unsafe { libc::geteuid() == 0 };
+                let mut forecaster: Option<HoltForecaster> = None;
+                let mut last_forecast_run: Option<std::time::Instant> = None;
+                let mut heartbeat = Heartbeat::from_env();
+                let mut self_monitor = SelfMonitor::new(&config);
+
+                loop {
+                    heartbeat.beat();
+                    if self_monitor.sample().is_some_and(|footprint| footprint.over_memory()) {
+                        if let Some(ref mut engine) = engine {
+                            engine.shrink(budget::keep_after_trim(engine.pattern_count()));
+                        }
+                        self_monitor.trimmed();
+                    }
+                    // Wake on a pressure transition, or after the interval to run
+                    // schedules and retry while pressure persists
+                    tokio::select! {
+                        event = pressure.next(), if watching => match event {
+                            Some(event) => {
+                                info!("Memory pressure {} ({}% used)", event.level(), event.reading().load_percent);
+                                level = event.level();
+                                plugins.on_pressure(&event);
+                                control.publish(DaemonEvent::Pressure {
+                                    level: event.level().to_string(),
+                                    load_percent: event.reading().load_percent,
+                                });
+                            }
+                            None => {
+                                error!("Pressure watcher stopped, falling back to load thresholds");
+                                watching = false;
+                            }
+                        },
+                        _ = wake.notified() => {}
+                        _ = tokio::time::sleep(self_monitor.stretch(Duration::from_secs(interval))) => {}
+                    }
+
+                    // Config edits, `config set` or SIGHUP (`systemctl reload`)
+                    if let Some(reloaded) = config_watcher.reload(&config) {
+                        if reloaded.neural_enabled != config.neural_enabled {
+                            engine = if reloaded.neural_enabled {
+                                NeuralDecisionEngine::new(&reloaded)
+                                    .map_err(|e| error!("Failed to initialize neural engine: {}", e))
+                                    .ok()
+                            } else {
+                                None
+                            };
+                        }
+                        config = reloaded;
+                        control.set_config(config.clone());
+                        self_monitor.set_config(&config);
+                        safety = SafetyGuard::new(SafetyConfig {
+                            min_interval: Duration::from_secs(config.min_interval_secs),
+                            ..Default::default()
+                        });
+                        schedules = Scheduler::load_default();
+                        pressure = PressureWatcher::from_config(&config).subscribe();
+                        watching = true;
+                    }
+
+                    // `optimize` from the CLI or tray, under the same safety guard
+                    for call in control.take_calls() {
+                        let result = optimize_on_request(optimizer.as_ref(), &mut safety, call.aggressive);
+                        control.complete(call, result);
+                    }
+
+                    let paused = control.is_paused();
+                    for schedule in schedules.due_now() {
+                        if paused {
+                            info!("Skipping schedule #{} while paused", schedule.id);
+                            continue;
+                        }
+                        info!("Running schedule #{}: {}", schedule.id, schedule.describe());
+                        match schedule.action {
+                            ScheduledAction::Optimize | ScheduledAction::AggressiveOptimize => {
+                                let aggressive = schedule.action == ScheduledAction::AggressiveOptimize;
+                                match optimizer.optimize(aggressive) {
+                                    Ok(result) => {
+                                        alerts::record_optimization(Ok(()));
+                                        crate::core::history::record(HistoryRecord::now(
+                                            OptimizationTrigger::Scheduled,
+                                            aggressive,
+                                            result.freed_mb,
+                                            result.processes_affected,
+                                            result.duration_ms,
+                                        ));
+                                        control.record(OptimizeReport {
+                                            trigger: OptimizationTrigger::Scheduled,
+                                            aggressive,
+                                            freed_mb: result.freed_mb,
+                                            processes: result.processes_affected,
+                                            duration_ms: result.duration_ms,
+                                        });
+                                    }
+                                    Err(e) => {
+                                        alerts::record_optimization(Err(&e));
+                                        error!("Scheduled optimization failed: {}", e);
+                                    }
+                                }
+                            }
+                            ScheduledAction::BrowserTrim => {
+                                scheduler::trim_browsers(OptimizationTrigger::Scheduled);
+                            }
+                            ScheduledAction::DailyReport => report::run_scheduled(ReportPeriod::Day),
+                            ScheduledAction::WeeklyReport => report::run_scheduled(ReportPeriod::Week),
+                        }
+                    }
+
+                    // New processes start at the kernel default, so keep
+                    // re-applying the OOM policy (the broker does it for a worker)
+                    if !worker {
+                        let adjusted = oom::apply_oom_policy(&oom_policy, is_root);
+                        if adjusted.failed > 0 {
+                            tracing::debug!("{} OOM score adjustments failed", adjusted.failed);
+                        }
+                    }
+
+                    let status = match LinuxMemoryOptimizer::get_memory_status() {
+                        Ok(status) => status,
+                        Err(e) => {
+                            error!("Failed to read memory status: {}", e);
+                            continue;
+                        }
+                    };
+                    let platform_status: MemoryStatus = (&status).into();
+                    forecaster
+                        .get_or_insert_with(|| {
+                            timeline::forecaster(status.total_physical_mb).map(|(f, _)| f).unwrap_or_else(|e| {
+                                tracing::debug!("Forecasting without history: {}", e);
+                                HoltForecaster::new()
+                            })
+                        })
+                        .observe(chrono::Utc::now().timestamp_millis().max(0) as u64, status.available_physical_mb);
+                    // Run ahead of a forecast exhaustion of available memory
+                    let floor_mb = status.total_physical_mb * 100u32.saturating_sub(config.critical_threshold) as f64 / 100.0;
+                    let forecast_minutes = forecaster
+                        .as_ref()
+                        .filter(|_| config.forecast_horizon_minutes > 0)
+                        .filter(|_| last_forecast_run.map_or(true, |t| t.elapsed() >= FORECAST_RUN_INTERVAL))
+                        .and_then(|f| f.minutes_until(floor_mb, config.forecast_horizon_minutes));
+                    plugins.on_sample(&Sample::new(
+                        status.memory_load_percent,
+                        status.total_physical_mb,
+                        status.available_physical_mb,
+                    ));
+                    let neural = match engine {
+                        Some(ref engine) => engine
+                            .decide(&MemoryPattern::from_status(&platform_status), &platform_status)
+                            .await
+                            .ok(),
+                        None => None,
+                    };
+
+                    if paused {
+                        tracing::debug!("Skipping: paused");
+                    } else if level > PressureLevel::Normal
+                        || (!watching && status.memory_load_percent >= config.pressure_threshold)
+                        || neural.as_ref().is_some_and(|d| d.should_optimize)
+                        || forecast_minutes.is_some()
+                    {
+                        if let Err(e) = safety.check_safe(status.available_physical_mb) {
+                            info!("Skipping: {}", e);
+                        } else if !config.allows_auto_optimize(status.memory_load_percent) {
+                            tracing::debug!("Skipping: quiet hours");
+                        } else {
+                            let aggressive = level == PressureLevel::Critical
+                                || status.memory_load_percent >= config.critical_threshold
+                                || neural.as_ref().is_some_and(|d| d.aggressive);
+                            let pressured = level > PressureLevel::Normal
+                                || status.memory_load_percent >= config.pressure_threshold
+                                || neural.as_ref().is_some_and(|d| d.should_optimize);
+                            let reason = match forecast_minutes {
+                                Some(minutes) if !pressured => {
+                                    info!("Forecast: memory runs out in ~{} min", minutes);
+                                    last_forecast_run = Some(std::time::Instant::now());
+                                    format!("Forecast: memory runs out in ~{} min", minutes)
+                                }
+                                _ => format!("Memory load {}%", status.memory_load_percent),
+                            };
+                            let request = OptimizeRequest {
+                                trigger: OptimizationTrigger::Auto,
+                                aggressive,
+                                reason: reason.clone(),
+                                load_percent: Some(status.memory_load_percent),
+                            };
+                            if let Verdict::Skip(reason) = plugins.pre_optimize(&request) {
+                                info!("Skipped by plugin {}", reason);
+                                continue;
+                            }
+                            match optimizer.optimize(aggressive) {
+                                Ok(result) => {
+                                    safety.record_attempt(true);
+                                    alerts::record_optimization(Ok(()));
+                                    plugins.post_optimize(&OptimizeOutcome {
+                                        trigger: OptimizationTrigger::Auto,
+                                        aggressive,
+                                        freed_mb: result.freed_mb,
+                                        processes_trimmed: result.processes_affected,
+                                        duration_ms: result.duration_ms,
+                                    });
+                                    crate::core::history::record(
+                                        HistoryRecord::now(
+                                            OptimizationTrigger::Auto,
+                                            aggressive,
+                                            result.freed_mb,
+                                            result.processes_affected,
+                                            result.duration_ms,
+                                        )
+                                        .with_memory_load(status.memory_load_percent),
+                                    );
+                                    control.record(OptimizeReport {
+                                        trigger: OptimizationTrigger::Auto,
+                                        aggressive,
+                                        freed_mb: result.freed_mb,
+                                        processes: result.processes_affected,
+                                        duration_ms: result.duration_ms,
+                                    });
+                                    match engine {
+                                        Some(ref mut engine) if config.learning_enabled => {
+                                            let decision = OptimizationDecision {
+                                                should_optimize: true,
+                                                aggressive,
+                                                confidence: neural.as_ref().map_or(1.0, |d| d.confidence),
+                                                reason,
+                                                target_processes: vec![],
+                                            };
+                                            let success = result.freed_mb > 100.0;
+                                            engine.learn_from_result(&decision, &result, success).await;
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                Err(e) => {
+                                    safety.record_attempt(false);
+                                    alerts::record_optimization(Err(&e));
+                                    error!("Optimization failed: {}", e);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            Commands::Pause { when } => {
+                let pause = when.as_deref().map_or(Ok(Pause::until_resumed()), Pause::parse);
+                match pause.and_then(|pause| IpcClient::connect_required()?.pause(pause).map(|()| pause)) {
+                    Ok(pause) => println!("Daemon paused {}", pause),
+                    Err(e) => println!("{}", e),
+                }
+            }
+
+            Commands::Resume => match IpcClient::connect_required().and_then(|mut d| d.resume()) {
+                Ok(()) => println!("Daemon resumed"),
+                Err(e) => println!("{}", e),
+            },
+
+            Commands::Events => match IpcClient::connect_required().and_then(IpcClient::subscribe) {
+                Ok(events) => {
+                    for event in events {
+                        println!("{}  {}", chrono::Local::now().format("%H:%M:%S"), event);
+                    }
+                    println!("Daemon stopped");
+                }
+                Err(e) => println!("{}", e),
+            },
+
+            Commands::Pagerank { top, sample_secs } => {
+                println!("Analyzing processes with PageRank...\n");
+
+                let mut system = sysinfo::System::new_all();
+                system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+                let mut pagerank = crate::algorithms::ProcessPageRank::new();
+                let relations = crate::monitor::relations::collect(&system);
+                pagerank.compute_with(&system, &relations);
+
+                // Coldness: how rarely each process faulted pages in while we watched
+                let mut access = crate::algorithms::AccessTracker::new();
+                access.record(crate::monitor::faults::sample_from(&system), std::time::Instant::now());
+                tokio::time::sleep(std::time::Duration::from_secs(sample_secs)).await;
+                access.record(crate::monitor::faults::sample_from(&system), std::time::Instant::now());
+                let coldness = access.coldness_all();
+                let coldness_of = |pid: &u32| coldness.get(pid).map_or("-".to_string(), |c| format!("{:.2}", c));
+
+                let candidates = pagerank.get_trim_candidates(top);
+                let critical = pagerank.get_critical_processes(top);
+
+                println!("LOW PRIORITY (trim candidates):");
+                println!("┌─────────┬────────────────────────────┬──────────────┬──────────┐");
+                println!("│   PID   │ Process                    │ PageRank     │ Coldness │");
+                println!("├─────────┼────────────────────────────┼──────────────┼──────────┤");
+                for (pid, score) in &candidates {
+                    let name = system
+                        .process(sysinfo::Pid::from_u32(*pid))
+                        .map(|p| p.name().to_string_lossy().to_string())
+                        .unwrap_or_else(|| "unknown".into());
+                    println!("│ {:>7} │ {:26} │ {:>12.6} │ {:>8} │", pid, truncate(&name, 26), score, coldness_of(pid));
+                }
+                println!("└─────────┴────────────────────────────┴──────────────┴──────────┘");
+
+                println!("\nHIGH PRIORITY (preserve):");
+                println!("┌─────────┬────────────────────────────┬──────────────┬──────────┐");
+                println!("│   PID   │ Process                    │ PageRank     │ Coldness │");
+                println!("├─────────┼────────────────────────────┼──────────────┼──────────┤");
+                for (pid, score) in &critical {
+                    let name = system
+                        .process(sysinfo::Pid::from_u32(*pid))
+                        .map(|p| p.name().to_string_lossy().to_string())
+                        .unwrap_or_else(|| "unknown".into());
+                    println!("│ {:>7} │ {:26} │ {:>12.6} │ {:>8} │", pid, truncate(&name, 26), score, coldness_of(pid));
+                }
+                println!("└─────────┴────────────────────────────┴──────────────┴──────────┘");
+
+                let stats = pagerank.stats();
+                println!("\nStatistics:");
+                println!("  Processes: {}", stats.process_count);
+                println!("  Mean score: {:.6}", stats.mean_score);
+                println!("  Std dev:    {:.6}", stats.std_dev);
+                let edges: Vec<String> = crate::monitor::relations::count_by_kind(&relations)
+                    .iter()
+                    .map(|(kind, count)| format!("{} {}", count, kind))
+                    .collect();
+                println!("  Edges:      {}", if edges.is_empty() { "none".to_string() } else { edges.join(", ") });
+                println!("  Coldness:   0.00 for the process faulting most, 1.00 for none in {}s", sample_secs);
+            }
+
+            Commands::Clusters { max } => {
+                println!("Analyzing process clusters with MinCut...\n");
+
+                let mut system = sysinfo::System::new_all();
+                system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+                let mut clusterer = crate::algorithms::MinCutClusterer::new();
+                clusterer.build_graph(&system);
+
+                let clusters = clusterer.find_clusters(max);
+                let stats = clusterer.stats();
+
+                println!("Found {} clusters from {} processes ({} edges)\n",
+                    clusters.len(), stats.total_processes, stats.total_edges);
+
+                for cluster in &clusters {
+                    println!("Cluster {} ({} processes, {:.1} MB, connectivity: {:.2})",
+                        cluster.id, cluster.processes.len(), cluster.total_memory_mb, cluster.connectivity);
+
+                    let trim_order = clusterer.get_trim_order(cluster);
+                    for (i, pid) in trim_order.iter().take(5).enumerate() {
+                        let name = system
+                            .process(sysinfo::Pid::from_u32(*pid))
+                            .map(|p| p.name().to_string_lossy().to_string())
+                            .unwrap_or_else(|| "unknown".into());
+                        let mem = system
+                            .process(sysinfo::Pid::from_u32(*pid))
+                            .map(|p| p.memory() as f64 / (1024.0 * 1024.0))
+                            .unwrap_or(0.0);
+                        println!("   {}. {} (PID {}) - {:.1} MB", i + 1, name, pid, mem);
+                    }
+                    if cluster.processes.len() > 5 {
+                        println!("   ... and {} more", cluster.processes.len() - 5);
+                    }
+                    println!();
+                }
+            }
+
+            Commands::Bench { iterations, save_baseline, compare, fail_threshold, baseline_dir, real, refault_secs, json } => {
+                if real {
+                    if let Err(e) = crate::bench::real::run_and_print(refault_secs, json) {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                    return Ok(());
+                }
+                if !json {
+                    println!("Running RuVector algorithm benchmarks ({} iterations)...", iterations);
+                }
+                let suite = AdvancedBenchmarkRunner::new(iterations).with_progress(!json).run_all();
+                if !json {
+                    suite.print_summary();
+                }
+                let baselines = BaselineRequest { save: save_baseline, compare, fail_threshold, dir: baseline_dir, json };
+                match baselines.apply(iterations, &suite) {
+                    Ok(true) => {}
+                    Ok(false) => std::process::exit(1),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            Commands::Browsers => {
+                println!("Analyzing browser memory usage...\n");
+                let mut optimizer = BrowserOptimizer::new();
+                optimizer.refresh();
+                optimizer.print_summary();
+            }
+
+            Commands::Ai { action } => {
+                #[cfg(feature = "ai")]
+                {
+                    let ai = AIMode::new(AIModeConfig::default());
+                    match action {
+                        AiCommand::Status { json } => {
+                            let status = ai.status().await;
+                            if json {
+                                println!("{}", serde_json::to_string_pretty(&status)?);
+                            } else {
+                                status.print();
+                            }
+                        }
+                        AiCommand::Models { json } => match ai.ollama_models().await {
+                            Ok(models) if json => println!("{}", serde_json::to_string_pretty(&models)?),
+                            Ok(models) => ollama::print_models(&models),
+                            Err(e) => println!("Ollama is not reachable: {}", e),
+                        },
+                        AiCommand::Unload { models, all } => {
+                            let names = if all { None } else { Some(models.as_slice()) };
+                            match ai.unload(names).await {
+                                Ok(unloaded) if unloaded.is_empty() => println!("No models were loaded."),
+                                Ok(unloaded) => println!("Unloaded {}", unloaded.join(", ")),
+                                Err(e) => println!("Not unloaded: {}", e),
+                            }
+                        }
+                        AiCommand::Optimize => println!("AI optimization: {}", ai.optimize().await),
+                        AiCommand::Cuda { lower_priority, json } => {
+                            let processes = ai.cuda_workloads();
+                            if json {
+                                println!("{}", serde_json::to_string_pretty(&processes)?);
+                            } else {
+                                cuda::print(&processes);
+                            }
+                            if lower_priority {
+                                match cuda::lower_training_priority(&processes) {
+                                    Ok(lowered) if lowered.is_empty() => println!("No training process to lower."),
+                                    Ok(lowered) => {
+                                        println!("Lowered priority of {} training process(es); `undo` restores it", lowered.len())
+                                    }
+                                    Err(e) => println!("Priority not changed: {}", e),
+                                }
+                            }
+                        }
+                    }
+                }
+                #[cfg(not(feature = "ai"))]
+                {
+                    let _ = action;
+                    println!("This build has no AI Mode. Rebuild with --features ai.");
+                }
+            }
+
+            Commands::Gpu { action } => {
+                let monitor = GpuMonitor::default();
+                match action {
+                    GpuCommand::Status => monitor.print_status(),
+                    GpuCommand::Top { limit } => monitor.print_top(limit),
+                    GpuCommand::Watch { interval } => loop {
+                        print!("\x1B[2J\x1B[1;1H");
+                        monitor.print_status();
+                        std::thread::sleep(std::time::Duration::from_secs(interval));
+                    },
+                }
+            }
+
+            Commands::Vms => {
+                let mut manager = VmManager::new();
+                manager.refresh();
+                manager.print_summary();
+            }
+
+            Commands::Jvm { json } => {
+                let mut manager = JvmManager::new();
+                manager.refresh();
+                manager.read_heaps(jvm::HEAP_CHECK_MIN_MB);
+                if json {
+                    let report = serde_json::json!({ "jvms": manager.get_jvms(), "advice": manager.advice() });
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    manager.print_summary();
+                }
+            }
+
+            Commands::Startup { action } => {
+                let mut manager = StartupManager::new();
+                let changed = match action {
+                    StartupCommand::List { all } => {
+                        manager.print_table(all);
+                        None
+                    }
+                    StartupCommand::Disable { item } => Some(manager.disable(&item)),
+                    StartupCommand::Enable { item } => Some(manager.enable(&item)),
+                    StartupCommand::Delay { item, secs } => Some(manager.delay(&item, secs)),
+                };
+                match changed {
+                    Some(Ok(item)) => println!("✓ {}: {} at login", item.name, item.state),
+                    Some(Err(e)) => println!("Failed to change startup item: {}", e),
+                    None => {}
+                }
+            }
+
+            Commands::Build { action } => match action {
+                BuildCommand::Watch { no_boost } => {
+                    let mut watcher = BuildWatcher::new(!no_boost);
+                    let mode = if no_boost { "measuring only" } else { "boosting" };
+                    println!("Watching for builds, {} (Ctrl+C to stop)...\n", mode);
+                    loop {
+                        tokio::select! {
+                            _ = tokio::signal::ctrl_c() => break,
+                            _ = tokio::time::sleep(Duration::from_secs(1)) => watcher.tick(),
+                        }
+                    }
+                    watcher.restore();
+                }
+                BuildCommand::History { limit } => match BuildHistory::open_default() {
+                    Ok(history) => history.print_summary(limit),
+                    Err(e) => println!("Failed to open build history: {}", e),
+                },
+            },
+
+            Commands::Games { action } => {
+                let mut detector = GameDetector::open_default();
+                let result = match action {
+                    GamesCommand::Detect => {
+                        let candidates = detector.candidates();
+                        if candidates.is_empty() {
+                            println!("Nothing running looks like a game");
+                        }
+                        for candidate in candidates {
+                            let mark = if candidate.is_game() { "🎮" } else { "  " };
+                            println!("{} {}", mark, candidate);
+                        }
+                        Ok(())
+                    }
+                    GamesCommand::List => {
+                        let taught = detector.taught();
+                        println!("Games:     {}", taught.games.iter().cloned().collect::<Vec<_>>().join(", "));
+                        println!("Not games: {}", taught.not_games.iter().cloned().collect::<Vec<_>>().join(", "));
+                        Ok(())
+                    }
+                    GamesCommand::Teach { name } => detector.teach(&name).map(|()| println!("✓ {} is a game", name)),
+                    GamesCommand::Ignore { name } => detector.ignore(&name).map(|()| println!("✓ {} is never a game", name)),
+                    GamesCommand::Forget { name } => detector.forget(&name).map(|found| {
+                        if found {
+                            println!("✓ Forgot {}", name);
+                        } else {
+                            println!("{} was not taught", name);
+                        }
+                    }),
+                };
+                if let Err(e) = result {
+                    println!("{}", e);
+                }
+            }
+            Commands::Thermal { watch } => {
+                if watch {
+                    thermal::watch_and_print(thermal::SAMPLE_INTERVAL);
+                } else {
+                    println!("🌡️  Thermal Status\n");
+                    ThermalMonitor::new().sample().print();
+                    println!("\n  Run with --watch to forecast throttling from the trend.");
+                }
+            }
+
+            Commands::Bloatware { all, remove, confirm } => {
+                let scanner = BloatwareScanner::new();
+                match remove {
+                    None => scanner.print_table(all),
+                    Some(app) if !confirm => match scanner.find(&app) {
+                        Ok(found) => {
+                            println!("Would remove {} with:\n  {}", found.app.name, found.app.uninstall);
+                            println!("\nNothing was changed. Re-run with --confirm to uninstall.");
+                        }
+                        Err(e) => println!("{}", e),
+                    },
+                    Some(app) => match scanner.remove(&app) {
+                        Ok(removed) => println!("✓ Removed {}", removed.app.name),
+                        Err(e) => println!("Failed to remove {}: {}", app, e),
+                    },
+                }
+            }
+
+            Commands::Health => {
+                println!("🩺 System Health\n");
+                HealthScorer::new().compute().print();
+            }
+
+            Commands::Profile { action } => match ProfileManager::open_default() {
+                Ok(mut manager) => match action {
+                    ProfileCommand::List => manager.print_table(),
+                    ProfileCommand::Show { name } => {
+                        if let Err(e) = manager.print_profile(&name) {
+                            println!("{}", e);
+                        }
+                    }
+                    ProfileCommand::Apply { name } => match manager.apply(&name) {
+                        Ok(()) => println!("✓ Profile '{}' applied", name.to_lowercase()),
+                        Err(e) => println!("Failed to apply profile: {}", e),
+                    },
+                    ProfileCommand::Off => match manager.off() {
+                        Ok(()) => println!("✓ Profile off, previous settings restored"),
+                        Err(e) => println!("Failed to restore settings: {}", e),
+                    },
+                    ProfileCommand::Auto { watch } => {
+                        if watch {
+                            println!("Watching profile activation rules (Ctrl+C to stop)...\n");
+                        }
+                        loop {
+                            match manager.auto_switch() {
+                                Ok(Some(change)) => {
+                                    let (title, msg) = change.notification();
+                                    println!("{} - {}", title, msg);
+                                }
+                                Ok(None) if !watch => println!("No switch needed (active: {})", manager.active().unwrap_or("none")),
+                                Ok(None) => {}
+                                Err(e) => println!("Profile switch failed: {}", e),
+                            }
+                            if !watch {
+                                break;
+                            }
+                            tokio::select! {
+                                _ = tokio::signal::ctrl_c() => break,
+                                _ = tokio::time::sleep(profiles::AUTO_SWITCH_INTERVAL) => {}
+                            }
+                        }
+                    }
+                    ProfileCommand::Init => {
+                        let path = manager.store().path().clone();
+                        if path.exists() {
+                            println!("{:?} already exists", path);
+                        } else {
+                            match manager.store().save(&profiles::builtin_profiles()) {
+                                Ok(()) => println!("✓ Wrote built-in profiles to {:?}", path),
+                                Err(e) => println!("Failed to write profiles: {}", e),
+                            }
+                        }
+                    }
+                },
+                Err(e) => println!("Failed to open profiles: {}", e),
+            },
+
+            Commands::Agent { action } => match action {
+                AgentCommand::List => match Agent::open_default() {
+                    Ok(agent) => agent.print_rules(),
+                    Err(e) => println!("Failed to load rules: {}", e),
+                },
+                AgentCommand::Run { dry_run, watch } => match Agent::open_default() {
+                    Ok(agent) if agent.engine().is_empty() => agent.print_rules(),
+                    Ok(mut agent) => {
+                        if watch {
+                            println!("Watching {} rules (Ctrl+C to stop)...\n", agent.engine().len());
+                        } else {
+                            // Sustained-memory and app-launch triggers need more than one sample
+                            println!("Evaluating {} rules once; use --watch for time-based triggers\n", agent.engine().len());
+                        }
+                        let mut fired = false;
+                        loop {
+                            for firing in agent.tick() {
+                                agent::run_and_print(&firing, dry_run);
+                                fired = true;
+                            }
+                            if !watch {
+                                break;
+                            }
+                            tokio::select! {
+                                _ = tokio::signal::ctrl_c() => break,
+                                _ = tokio::time::sleep(agent::AGENT_INTERVAL) => {}
+                            }
+                        }
+                        if !fired && !watch {
+                            println!("No rule matched.");
+                        }
+                    }
+                    Err(e) => println!("Failed to load rules: {}", e),
+                },
+                AgentCommand::Test { hours } => match agent::replay_recent(hours) {
+                    Ok(report) => report.print(),
+                    Err(e) => println!("Failed to replay rules: {}", e),
+                },
+                AgentCommand::Init => match RuleStore::open_default() {
+                    Ok(store) if store.path().exists() => println!("{:?} already exists", store.path()),
+                    Ok(store) => match store.save(&agent::example_rules()) {
+                        Ok(()) => println!("✓ Wrote example rules to {:?}", store.path()),
+                        Err(e) => println!("Failed to write rules: {}", e),
+                    },
+                    Err(e) => println!("Failed to open rules: {}", e),
+                },
+            },
+
+            Commands::Plugins { action } => match action {
+                PluginsCommand::List => {
+                    let host = PluginHost::load_default();
+                    let plugins = host.list();
+                    if plugins.is_empty() {
+                        println!("No plugins in {:?}", host.dir());
+                    } else {
+                        println!("Plugins in {:?}:\n", host.dir());
+                        for plugin in &plugins {
+                            plugin.print();
+                        }
+                    }
+                }
+                PluginsCommand::Enable { id } => match PluginHost::open_default() {
+                    Ok(mut host) => match host.enable(&id) {
+                        Ok(info) => println!("✓ Enabled {}", info.title()),
+                        Err(e) => println!("Failed to enable plugin: {}", e),
+                    },
+                    Err(e) => println!("Failed to open plugins: {}", e),
+                },
+                PluginsCommand::Disable { id } => match PluginHost::open_default() {
+                    Ok(mut host) => match host.disable(&id) {
+                        Ok(()) => println!("✓ Disabled {}", id),
+                        Err(e) => println!("Failed to disable plugin: {}", e),
+                    },
+                    Err(e) => println!("Failed to open plugins: {}", e),
+                },
+                PluginsCommand::Run { id, command, args } => {
+                    let mut host = PluginHost::load_default();
+                    match host.run_command(&id, &command, &args) {
+                        Ok(output) => println!("{}", output),
+                        Err(e) => println!("Plugin command failed: {}", e),
+                    }
+                }
+                PluginsCommand::Recommend { apply } => {
+                    let mut host = PluginHost::load_default();
+                    let recommendations = host.recommend(&HeuristicInput::collect());
+                    if recommendations.is_empty() {
+                        println!("No recommendations.");
+                    }
+                    for (id, recommendation) in &recommendations {
+                        println!("  [{}] {}", id, recommendation.describe());
+                        if apply {
+                            match recommendation.apply() {
+                                Ok(result) => println!("    ✓ {}", result),
+                                Err(e) => println!("    ✗ {}", e),
+                            }
+                        }
+                    }
+                }
+            },
+
+            Commands::Leaks { history: true, json, .. } => {
+                match SuspectLog::open_default().and_then(|log| log.since(0)) {
+                    Ok(suspects) if json => println!("{}", serde_json::to_string_pretty(&suspects)?),
+                    Ok(suspects) => leakwatch::print_suspects(&suspects),
+                    Err(e) => println!("Failed to read leak suspects: {}", e),
+                }
+            }
+
+            Commands::Leaks { samples, interval, json, .. } => {
+                if !json {
+                    println!("Monitoring for memory leaks...");
+                    println!("Taking {} samples at {} second intervals\n", samples, interval);
+                }
+
+                let mut detector = LeakDetector::new();
+                detector.set_sample_interval(interval);
+
+                for i in 0..samples {
+                    detector.sample();
+                    if !json {
+                        print!("\rSampling... {}/{}", i + 1, samples);
+                        std::io::Write::flush(&mut std::io::stdout()).ok();
+                    }
+
+                    if i < samples - 1 {
+                        std::thread::sleep(Duration::from_secs(interval));
+                    }
+                }
+
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&detector.reports())?);
+                } else {
+                    println!();
+                    detector.print_summary();
+                }
+            }
+
+            Commands::Suggest { apply: None, json, .. } => {
+                if !json {
+                    println!("Generating smart optimization suggestions...\n");
+                }
+                let mut engine = SmartSuggestions::new();
+                engine.refresh();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(engine.get_suggestions())?);
+                } else {
+                    engine.print_summary();
+                }
+            }
+
+            Commands::Suggest { apply: Some(id), confirm, .. } => {
+                let mut engine = SmartSuggestions::new();
+                engine.refresh();
+                match engine.get(&id).cloned() {
+                    None => println!("No suggestion '{}'; run `suggest` to list current ids.", id),
+                    Some(suggestion) if suggestion.actionable && suggestion.risk > SuggestionRisk::Low && !confirm => {
+                        println!("Would apply '{}' ({} risk):\n  {}", suggestion.title, suggestion.risk, suggestion.description);
+                        println!("\nNothing was changed. Re-run with --confirm to apply it.");
+                    }
+                    Some(suggestion) => match engine.apply(&id) {
+                        Ok(outcome) => println!("✓ {}: {}", suggestion.title, outcome.message),
+                        Err(e) => println!("Failed to apply '{}': {}", suggestion.title, e),
+                    },
+                }
+            }
+
+            Commands::History { hours, trigger, limit } => {
+                let trigger = match trigger {
+                    Some(t) => Some(t.parse::<OptimizationTrigger>()?),
+                    None => None,
+                };
+                let range = if hours == 0 {
+                    TimeRange::all()
+                } else {
+                    TimeRange::last(Duration::from_secs(hours * 3600))
+                };
+                let filter = HistoryFilter {
+                    trigger,
+                    limit: Some(limit),
+                    ..Default::default()
+                };
+
+                let store = HistoryStore::open_default()?;
+                let records = store.query(range, &filter)?;
+                HistoryStore::print_records(&records);
+            }
+
+            Commands::Config { action } => match action.unwrap_or(ConfigCommand::Show) {
+                ConfigCommand::Show => {
+                    let config = OptimizerConfig::load_or_default();
+                    println!("Current Configuration ({}):", OptimizerConfig::default_path().display());
+                    println!("{}", toml::to_string_pretty(&config)?);
+                }
+                ConfigCommand::Get { key } => match OptimizerConfig::load_or_default().get_value(&key) {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => println!("{}", e),
+                },
+                ConfigCommand::Set { key, value } => match crate::core::config::set_in_file(&key, &value) {
+                    Ok(path) => println!("Set {} = {} in {}", key, value, path.display()),
+                    Err(e) => println!("Not saved: {}", e),
+                },
+                ConfigCommand::Edit => match crate::core::config::edit() {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => println!("{}", e),
+                },
+                ConfigCommand::Validate { path } => {
+                    let path = path.unwrap_or_else(OptimizerConfig::default_path);
+                    let validation = crate::core::config::validate_file(&path);
+                    validation.print(&path);
+                    if !validation.is_ok() {
+                        std::process::exit(1);
+                    }
+                }
+            },
+
+            Commands::Undo { show } => {
+                let window = undo::window(&OptimizerConfig::load_or_default());
+                if show {
+                    match undo::last_undoable(window) {
+                        Some(entry) => entry.print(),
+                        None => println!("Nothing to undo from the last {} minutes.", window.as_secs() / 60),
+                    }
+                } else {
+                    match undo::undo_last(window) {
+                        Ok(report) => report.print(),
+                        Err(e) => println!("{}", e),
+                    }
+                }
+            }
+
+            Commands::Audit { action } => match action {
+                AuditCommand::Tail { lines } => {
+                    let log = AuditLog::open_default()?;
+                    AuditLog::print_tail(&log.tail(lines)?);
+                }
+            },
+
+            Commands::Dashboard => crate::monitor::dashboard::run()?,
+
+            Commands::Watch { name, category, interval } => {
+                let target = WatchTarget::from_args(name, category.as_deref())?;
+                watch::run(target, Duration::from_secs(interval.max(1)))?;
+            }
+
+            Commands::Snapshot { action } => match action {
+                SnapshotCommand::Take { output } => {
+                    let taken = Snapshot::take()?;
+                    let path = taken.save(output.as_deref())?;
+                    println!("Saved {} processes to {}", taken.processes.len(), path.display());
+                }
+                SnapshotCommand::List => snapshot::print_list(&snapshot::list()?),
+                SnapshotCommand::Diff { from, to, limit, json } => {
+                    let before = Snapshot::load(&snapshot::resolve(&from)?)?;
+                    let after = match to {
+                        Some(to) => Snapshot::load(&snapshot::resolve(&to)?)?,
+                        None => Snapshot::take()?,
+                    };
+                    let diff = SnapshotDiff::between(&before, &after);
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&diff)?);
+                    } else {
+                        diff.print(limit);
+                    }
+                }
+            },
+
+            Commands::Timeline { at, minutes, json } => match timeline::query(at.as_deref(), minutes) {
+                Ok(view) if json => println!("{}", serde_json::to_string_pretty(&view)?),
+                Ok(view) => view.print(),
+                Err(e) => println!("Failed to read timeline: {}", e),
+            },
+
+            Commands::Forecast { json } => {
+                let config = OptimizerConfig::load_or_default();
+                match timeline::forecast(config.critical_threshold, &HORIZONS) {
+                    Ok(view) if json => println!("{}", serde_json::to_string_pretty(&view)?),
+                    Ok(view) => view.print(),
+                    Err(e) => println!("Failed to read timeline: {}", e),
+                }
+            }
+
+            Commands::Cleanup { dry_run, target, exclude, json } => {
+                let mut options = CleanupOptions {
+                    targets: target.iter().map(|t| t.parse()).collect::<Result<_, String>>()?,
+                    exclude: OptimizerConfig::load_or_default().cleanup_exclude,
+                    dry_run,
+                };
+                options.exclude.extend(exclude);
+                let report = cleanup::clean(&options);
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    report.print();
+                }
+            }
+
+            Commands::Disk { json } => {
+                let report = diskspace::scan();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    report.print();
+                }
+            }
+
+            Commands::Report { action: ReportCommand::Generate { period, format, output } } => {
+                let format = match format {
+                    Some(format) => format.parse()?,
+                    None => OptimizerConfig::load_or_default().report_format,
+                };
+                let path = report::generate(period.parse()?, format, output.as_deref())?;
+                println!("Wrote {}", path.display());
+            }
+
+            Commands::Alerts { action: AlertsCommand::Test } => {
+                let config = OptimizerConfig::load_or_default();
+                if config.alerts.channels.is_empty() {
+                    println!("No alert channels; add [[alerts.channels]] to config.toml");
+                }
+                for (channel, result) in alerts::send_test(&config.alerts) {
+                    match result {
+                        Ok(()) => println!("✓ {}", channel),
+                        Err(e) => println!("✗ {}: {}", channel, e),
+                    }
+                }
+            }
+
+            Commands::Schedule { action } => {
+                let store = ScheduleStore::open_default()?;
+                match action {
+                    ScheduleCommand::List => {
+                        Scheduler::print_schedules(&store.load()?);
+                    }
+                    ScheduleCommand::Add { action, at, every, days, hours } => {
+                        let schedule = Schedule::from_args(
+                            &action, at.as_deref(), every, &days, hours.as_deref())?;
+                        let description = schedule.describe();
+                        let id = store.add(schedule)?;
+                        println!("Added schedule #{}: {}", id, description);
+                    }
+                    ScheduleCommand::Remove { id } => {
+                        store.remove(id)?;
+                        println!("Removed schedule #{}", id);
+                    }
+                }
+            }
+
+            Commands::Policy { action } => {
+                let store = PolicyStore::open_default()?;
+                match action {
+                    PolicyCommand::List => {
+                        PolicyStore::print_rules(&store.load()?);
+                    }
+                    PolicyCommand::Add { effect, pattern, kind } => {
+                        let rule = PolicyRule::new(effect.parse()?, kind.parse()?, pattern);
+                        let description = rule.describe();
+                        let id = store.add(rule)?;
+                        println!("Added policy rule #{}: {}", id, description);
+                    }
+                    PolicyCommand::Remove { id } => {
+                        store.remove(id)?;
+                        println!("Removed policy rule #{}", id);
+                    }
+                }
+            }
+
+            Commands::Service { action } => {
+                let service = LinuxDaemonService::new();
+                match action {
+                    ServiceAction::Install { interval } => {
+                        println!("{}", service.install(interval)?);
+                    }
+                    ServiceAction::Uninstall => {
+                        println!("{}", service.uninstall()?);
+                    }
+                    ServiceAction::Status => {
+                        let status = service.status()?;
+                        println!("Service Status:");
+                        println!("  Unit:      {:?} ({})", status.unit_path,
+                            if status.user_unit { "user" } else { "system" });
+                        println!("  Installed: {}", if status.installed { "Yes" } else { "No" });
+                        println!("  Enabled:   {}", if status.enabled { "Yes" } else { "No" });
+                        println!("  Active:    {}", if status.active { "Running" } else { "Stopped" });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Privileged half of least-privilege mode
+    ///
+    /// Starts the worker, answers its optimize requests, and keeps the jobs
+    /// that only work as root: the OOM score policy and the low-memory killer.
+    fn run_broker(
+        config: &OptimizerConfig,
+        interval: u64,
+        oom_killer: bool,
+        oom_dry_run: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        info!("Starting optimization daemon as broker (worker user: {})", config.worker_user);
+        if oom_killer {
+            if let Err(e) = lowmem::spawn(LowMemoryConfig::from_config(config, oom_dry_run)) {
+                error!("Low-memory killer disabled: {}", e);
+            }
+        }
+
+        let optimizer = crate::platform::create_optimizer();
+        let oom_policy = Policy::load_default();
+        let mut last_oom_pass: Option<std::time::Instant> = None;
+        let mut heartbeat = Heartbeat::from_env();
+        let broker = Broker::spawn_worker(&config.worker_user, Duration::from_secs(config.min_interval_secs))?;
+        let status = broker.serve(
+            |request| match request {
+                BrokerRequest::Optimize { aggressive } => optimizer
+                    .optimize(*aggressive)
+                    .and_then(|result| serde_json::to_value(result).map_err(|e| e.to_string())),
+            },
+            || {
+                heartbeat.beat();
+                if last_oom_pass.map_or(true, |t| t.elapsed() >= Duration::from_secs(interval)) {
+                    oom::apply_oom_policy(&oom_policy, true);
+                    last_oom_pass = Some(std::time::Instant::now());
+                }
+                true
+            },
+        )?;
+        Err(format!("Worker exited ({})", status).into())
+    }
+
+    /// Run an optimization a client asked the daemon for
+    fn optimize_on_request(
+        optimizer: &dyn MemoryOptimizer,
+        safety: &mut SafetyGuard,
+        aggressive: bool,
+    ) -> Result<OptimizeReport, String> {
+        let status = LinuxMemoryOptimizer::get_memory_status()?;
+        safety.check_safe(status.available_physical_mb)?;
+        let result = optimizer.optimize(aggressive);
+        safety.record_attempt(result.is_ok());
+        let result = result?;
+        crate::core::history::record(
+            HistoryRecord::now(
+                OptimizationTrigger::Manual,
+                aggressive,
+                result.freed_mb,
+                result.processes_affected,
+                result.duration_ms,
+            )
+            .with_memory_load(status.memory_load_percent),
+        );
+        Ok(OptimizeReport {
+            trigger: OptimizationTrigger::Manual,
+            aggressive,
+            freed_mb: result.freed_mb,
+            processes: result.processes_affected,
+            duration_ms: result.duration_ms,
+        })
+    }
+}
+
+// Module declarations needed for Linux
+#[cfg(target_os = "linux")]
+mod core;
+#[cfg(target_os = "linux")]
+mod accel;
+#[cfg(target_os = "linux")]
+mod algorithms;
+#[cfg(target_os = "linux")]
+mod platform;
+#[cfg(target_os = "linux")]
+mod apps;
+#[cfg(target_os = "linux")]
+mod features;
+#[cfg(target_os = "linux")]
+mod security;
+#[cfg(target_os = "linux")]
+mod monitor;
+#[cfg(all(target_os = "linux", feature = "ai"))]
+mod ai;
+#[cfg(target_os = "linux")]
+mod neural;
+#[cfg(target_os = "linux")]
+mod bench;
+
+#[cfg(target_os = "linux")]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    linux_impl::run().await
+}
@@ -8,6 +8,9 @@ use std::process::Command;
 use std::time::Instant;
 use tracing::{info, warn, debug};
 
+use crate::security::audit::{self, AuditAction};
+use crate::security::policy::{Policy, ProcessIdentity};
+
 /// Memory status information (cross-platform compatible)
 #[derive(Debug, Clone)]
 pub struct MemoryStatus {
@@ -20,6 +23,11 @@ pub struct MemoryStatus {
     pub pressure_level: u32,
     /// macOS specific: is Apple Silicon
     pub is_apple_silicon: bool,
+    /// Memory occupied by the compressor
+    pub compressed_mb: f64,
+    /// Pages swapped in and out since boot
+    pub swapins: u64,
+    pub swapouts: u64,
 }
 
 impl MemoryStatus {
@@ -45,6 +53,8 @@ pub struct OptimizationResult {
     pub processes_affected: usize,
     pub duration_ms: u64,
     pub method: OptimizationMethod,
+    /// Pressure level whose strategy was run
+    pub pressure_level: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -59,10 +69,159 @@ pub enum OptimizationMethod {
     None,
 }
 
+/// Names for pressure levels 0-4
+pub const PRESSURE_LEVEL_NAMES: [&str; 5] = ["Normal", "Warn", "Critical", "Urgent", "Extreme"];
+
+/// What an optimization does at a given pressure level
+#[derive(Debug, Clone, PartialEq)]
+pub struct PressureStrategy {
+    /// Largest processes to hint
+    pub max_processes: usize,
+    /// Smaller processes are left alone
+    pub min_process_mb: f64,
+    /// Flush dirty file pages before purging
+    pub sync: bool,
+    /// Run `purge` when root
+    pub purge: bool,
+    pub description: &'static str,
+}
+
+impl PressureStrategy {
+    pub fn for_level(level: u32) -> Self {
+        match level {
+            0 => Self {
+                max_processes: 20, min_process_mb: 200.0, sync: false, purge: false,
+                description: "hint the largest processes",
+            },
+            1 => Self {
+                max_processes: 50, min_process_mb: 100.0, sync: false, purge: false,
+                description: "hint all large processes",
+            },
+            2 => Self {
+                max_processes: 50, min_process_mb: 100.0, sync: false, purge: true,
+                description: "purge inactive memory",
+            },
+            3 => Self {
+                max_processes: 100, min_process_mb: 50.0, sync: true, purge: true,
+                description: "flush dirty pages, then purge",
+            },
+            _ => Self {
+                max_processes: 200, min_process_mb: 20.0, sync: true, purge: true,
+                description: "flush, purge and hint every sizeable process",
+            },
+        }
+    }
+}
+
+/// Counters from `vm_stat`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VmStats {
+    /// Memory occupied by the compressor
+    pub compressed_mb: f64,
+    /// Memory that cannot be paged out, including what the GPU has mapped
+    pub wired_mb: f64,
+    pub swapins: u64,
+    pub swapouts: u64,
+}
+
+/// Parse `vm_stat` output; counts are in pages of the size in its header
+pub fn parse_vm_stat(output: &str) -> VmStats {
+    let page_size = output
+        .split("page size of ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|n| n.parse::<u64>().ok())
+        .unwrap_or(4096);
+    let counter = |label: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(label))
+            .and_then(|rest| rest.trim().trim_end_matches('.').parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    let mb = |label: &str| (counter(label) * page_size) as f64 / 1024.0 / 1024.0;
+    VmStats {
+        compressed_mb: mb("Pages occupied by compressor:"),
+        wired_mb: mb("Pages wired down:"),
+        swapins: counter("Swapins:"),
+        swapouts: counter("Swapouts:"),
+    }
+}
+
+/// `kern.memorystatus_vm_pressure_level`: 1 normal, 2 warn, 4 critical
+fn kernel_pressure_level() -> Option<i32> {
+    let mut level: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            b"kern.memorystatus_vm_pressure_level\0".as_ptr() as *const libc::c_char,
+            &mut level as *mut _ as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    (ret == 0).then_some(level)
+}
+
+/// Pressure level 0-4 from the kernel's three levels, refined by swap use
+///
+/// The kernel stops at critical; once swap is filling up too the system is
+/// close to Jetsam kills, so that is reported as Urgent or Extreme.
+fn pressure_level(kernel: Option<i32>, load_percent: u32, swap_used_ratio: f64) -> u32 {
+    match kernel {
+        Some(4) if swap_used_ratio > 0.9 || load_percent > 98 => 4,
+        Some(4) if swap_used_ratio > 0.5 => 3,
+        Some(4) => 2,
+        Some(2) => 1,
+        Some(_) => 0,
+        None if load_percent > 95 => 3,
+        None if load_percent > 85 => 2,
+        None if load_percent > 75 => 1,
+        None => 0,
+    }
+}
+
+/// Level `memory_pressure -l` can drive the system to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimulatedPressure {
+    Warn,
+    Critical,
+}
+
+impl std::str::FromStr for SimulatedPressure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(Self::Warn),
+            "critical" => Ok(Self::Critical),
+            other => Err(format!("Unknown pressure level '{}' (expected warn or critical)", other)),
+        }
+    }
+}
+
+/// Allocate memory with `memory_pressure -l` until the system reaches `level`
+///
+/// For testing how the optimizer reacts. Needs root; the returned child
+/// holds the pressure until it is killed.
+pub fn simulate_pressure(level: SimulatedPressure) -> Result<std::process::Child, String> {
+    let arg = match level {
+        SimulatedPressure::Warn => "warn",
+        SimulatedPressure::Critical => "critical",
+    };
+    Command::new("memory_pressure")
+        .args(["-l", arg])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run memory_pressure: {}", e))
+}
+
 /// macOS Memory Optimizer
 pub struct MacMemoryOptimizer {
     has_sudo: bool,
     is_apple_silicon: bool,
+    policy: Policy,
 }
 
 impl MacMemoryOptimizer {
@@ -83,6 +242,7 @@ impl MacMemoryOptimizer {
         Self {
             has_sudo,
             is_apple_silicon,
+            policy: Policy::load_default(),
         }
     }
 
@@ -122,9 +282,12 @@ impl MacMemoryOptimizer {
         let total_swap = sys.total_swap() as f64 / 1024.0 / 1024.0;
         let used_swap = sys.used_swap() as f64 / 1024.0 / 1024.0;
         let available_swap = total_swap - used_swap;
+        let swap_used_ratio = if total_swap > 0.0 { used_swap / total_swap } else { 0.0 };
 
-        // Get memory pressure level from vm_stat or memory_pressure
-        let pressure_level = Self::get_memory_pressure_level();
+        let vm = Command::new("vm_stat")
+            .output()
+            .map(|o| parse_vm_stat(&String::from_utf8_lossy(&o.stdout)))
+            .unwrap_or_default();
 
         Ok(MemoryStatus {
             total_physical_mb: total,
@@ -132,71 +295,38 @@ impl MacMemoryOptimizer {
             memory_load_percent: load,
             total_swap_mb: total_swap,
             available_swap_mb: available_swap,
-            pressure_level,
+            pressure_level: pressure_level(kernel_pressure_level(), load, swap_used_ratio),
             is_apple_silicon: Self::detect_apple_silicon(),
+            compressed_mb: vm.compressed_mb,
+            swapins: vm.swapins,
+            swapouts: vm.swapouts,
         })
     }
 
-    /// Get memory pressure level (0=normal, 1=warn, 2=critical, 3=urgent, 4=extreme)
-    fn get_memory_pressure_level() -> u32 {
-        // Try to get from memory_pressure command
-        if let Ok(output) = Command::new("memory_pressure").output() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if stdout.contains("normal") {
-                return 0;
-            } else if stdout.contains("warn") {
-                return 1;
-            } else if stdout.contains("critical") {
-                return 2;
-            }
-        }
-
-        // Fallback: estimate from memory usage
-        if let Ok(status) = Self::get_memory_status_basic() {
-            if status.memory_load_percent > 95 {
-                return 3;
-            } else if status.memory_load_percent > 85 {
-                return 2;
-            } else if status.memory_load_percent > 75 {
-                return 1;
-            }
-        }
-
-        0
-    }
-
-    /// Basic memory status without pressure (to avoid recursion)
-    fn get_memory_status_basic() -> Result<MemoryStatus, String> {
-        let mut sys = System::new();
-        sys.refresh_memory();
-
-        let total = sys.total_memory() as f64 / 1024.0 / 1024.0;
-        let available = sys.available_memory() as f64 / 1024.0 / 1024.0;
-        let used = sys.used_memory() as f64 / 1024.0 / 1024.0;
-        let load = if total > 0.0 { ((used / total) * 100.0) as u32 } else { 0 };
-
-        Ok(MemoryStatus {
-            total_physical_mb: total,
-            available_physical_mb: available,
-            memory_load_percent: load,
-            total_swap_mb: 0.0,
-            available_swap_mb: 0.0,
-            pressure_level: 0,
-            is_apple_silicon: false,
-        })
+    /// Run the strategy for the current pressure level
+    ///
+    /// `aggressive` raises the level to at least Critical, so a purge is
+    /// attempted even when the system is calm.
+    pub fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+        let level = Self::get_memory_status()?.pressure_level;
+        self.optimize_at(if aggressive { level.max(2) } else { level })
     }
 
-    /// Run memory optimization
-    pub fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+    /// Run the strategy for a given pressure level (0-4)
+    pub fn optimize_at(&self, level: u32) -> Result<OptimizationResult, String> {
         let start = Instant::now();
         let before = Self::get_memory_status()?;
+        let strategy = PressureStrategy::for_level(level);
+        debug!("Pressure level {}: {}", level, strategy.description);
 
-        let (method, processes_affected) = if self.has_sudo && aggressive {
-            // Use purge command (requires sudo)
-            self.run_purge()?
+        if strategy.sync {
+            // Write dirty file pages out so purge can release them too
+            let _ = Command::new("sync").output();
+        }
+        let (method, processes_affected) = if strategy.purge && self.has_sudo {
+            self.run_purge(&strategy)?
         } else {
-            // Use madvise hints on processes
-            self.run_madvise_hints()?
+            self.run_madvise_hints(&strategy)?
         };
 
         // Wait for memory to settle
@@ -206,7 +336,8 @@ impl MacMemoryOptimizer {
         let freed = after.available_physical_mb - before.available_physical_mb;
 
         info!(
-            "Optimized: method={:?}, affected {} processes, freed {:.1} MB in {}ms",
+            "Optimized at pressure level {}: method={:?}, affected {} processes, freed {:.1} MB in {}ms",
+            level,
             method,
             processes_affected,
             freed.max(0.0),
@@ -220,39 +351,47 @@ impl MacMemoryOptimizer {
             processes_affected,
             duration_ms: start.elapsed().as_millis() as u64,
             method,
+            pressure_level: level,
         })
     }
 
     /// Run purge command (requires sudo)
-    fn run_purge(&self) -> Result<(OptimizationMethod, usize), String> {
+    fn run_purge(&self, strategy: &PressureStrategy) -> Result<(OptimizationMethod, usize), String> {
         info!("Running purge command (sudo required)");
 
         let output = Command::new("sudo")
             .arg("purge")
             .output()
             .map_err(|e| format!("Failed to run purge: {}", e))?;
-
-        if output.status.success() {
-            Ok((OptimizationMethod::PurgeTool, 0))
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            warn!("Purge failed: {}", stderr);
-            // Fallback to madvise hints
-            self.run_madvise_hints()
+        let result = purge_result(&output);
+        audit::record(AuditAction::CacheDrop, "disk cache", None, "purge", &result);
+
+        match result {
+            Ok(()) => Ok((OptimizationMethod::PurgeTool, 0)),
+            Err(stderr) => {
+                warn!("Purge failed: {}", stderr);
+                // Fallback to madvise hints
+                self.run_madvise_hints(strategy)
+            }
         }
     }
 
     /// Use madvise hints to suggest memory cleanup
-    fn run_madvise_hints(&self) -> Result<(OptimizationMethod, usize), String> {
+    fn run_madvise_hints(&self, strategy: &PressureStrategy) -> Result<(OptimizationMethod, usize), String> {
         debug!("Using madvise hints for memory optimization");
 
         // Get list of user processes with high memory usage
         let processes = super::process::list_user_processes()?;
         let mut affected = 0;
 
-        for (pid, _name, memory_mb) in processes.iter().take(50) {
+        let candidates = processes
+            .iter()
+            .filter(|(_, name, _)| !self.policy.is_excluded(&ProcessIdentity::new(name.as_str())))
+            .take(strategy.max_processes);
+
+        for (pid, _name, memory_mb) in candidates {
             // Only target processes using significant memory
-            if *memory_mb > 100.0 {
+            if *memory_mb > strategy.min_process_mb {
                 if self.hint_process_memory(*pid) {
                     affected += 1;
                 }
@@ -286,7 +425,9 @@ impl MacMemoryOptimizer {
 
         // sync and purge
         let _ = Command::new("sync").output();
-        let _ = Command::new("sudo").arg("purge").output();
+        if let Ok(output) = Command::new("sudo").arg("purge").output() {
+            audit::record(AuditAction::CacheDrop, "disk cache", None, "purge", &purge_result(&output));
+        }
 
         std::thread::sleep(std::time::Duration::from_millis(500));
 
@@ -311,6 +452,15 @@ impl Default for MacMemoryOptimizer {
     }
 }
 
+/// `purge` succeeded, or its stderr
+fn purge_result(output: &std::process::Output) -> Result<(), String> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,4 +472,31 @@ mod tests {
         assert!(status.available_physical_mb > 0.0);
         assert!(status.memory_load_percent <= 100);
     }
+
+    #[test]
+    fn test_parse_vm_stat() {
+        let output = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\n\
+                      Pages free:                               12345.\n\
+                      Pages wired down:                         131072.\n\
+                      Pages occupied by compressor:             65536.\n\
+                      Swapins:                                  120.\n\
+                      Swapouts:                                 450.\n";
+        let stats = parse_vm_stat(output);
+        assert_eq!(stats.compressed_mb, 1024.0);
+        assert_eq!(stats.wired_mb, 2048.0);
+        assert_eq!(stats.swapins, 120);
+        assert_eq!(stats.swapouts, 450);
+    }
+
+    #[test]
+    fn test_pressure_level() {
+        assert_eq!(pressure_level(Some(1), 90, 0.0), 0);
+        assert_eq!(pressure_level(Some(2), 50, 0.0), 1);
+        assert_eq!(pressure_level(Some(4), 80, 0.2), 2);
+        assert_eq!(pressure_level(Some(4), 80, 0.6), 3);
+        assert_eq!(pressure_level(Some(4), 99, 0.0), 4);
+        assert_eq!(pressure_level(None, 90, 0.0), 2);
+        assert!(PressureStrategy::for_level(3).purge);
+        assert!(!PressureStrategy::for_level(1).purge);
+    }
 }
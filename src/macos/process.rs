@@ -1,7 +1,16 @@
 //! macOS process enumeration and management
 
+use serde::{Deserialize, Serialize};
 use sysinfo::{System, Pid, ProcessesToUpdate, UpdateKind};
 use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use crate::platform::ProcessMemoryInfo;
+use crate::security::audit::{self, AuditAction};
+
+/// Apps frozen by the optimizer, so they can be thawed again later
+pub const SUSPENDED_FILE: &str = "suspended.json";
 
 /// Process info tuple: (pid, name, memory_mb)
 pub type ProcessInfo = (u32, String, f64);
@@ -65,6 +74,132 @@ pub fn get_process_memory(pid: u32) -> Option<u64> {
     sys.process(Pid::from_u32(pid)).map(|p| p.memory())
 }
 
+/// `PROC_PIDREGIONPATHINFO` from `<sys/proc_info.h>`, not in `libc`
+const PROC_PIDREGIONPATHINFO: libc::c_int = 8;
+
+/// `struct proc_regioninfo`; only a few fields are read, the rest keep the layout
+#[repr(C)]
+#[allow(dead_code)]
+struct RegionInfo {
+    pri_protection: u32,
+    pri_max_protection: u32,
+    pri_inheritance: u32,
+    pri_flags: u32,
+    pri_offset: u64,
+    pri_behavior: u32,
+    pri_user_wired_count: u32,
+    pri_user_tag: u32,
+    pri_pages_resident: u32,
+    pri_pages_shared_now_private: u32,
+    pri_pages_swapped_out: u32,
+    pri_pages_dirtied: u32,
+    pri_ref_count: u32,
+    pri_shadow_depth: u32,
+    pri_share_mode: u32,
+    pri_private_pages_resident: u32,
+    pri_shared_pages_resident: u32,
+    pri_obj_id: u32,
+    pri_depth: u32,
+    pri_address: u64,
+    pri_size: u64,
+}
+
+/// `struct proc_regionwithpathinfo`
+#[repr(C)]
+struct RegionWithPathInfo {
+    info: RegionInfo,
+    vnode: libc::vnode_info_path,
+}
+
+/// `phys_footprint` and resident size from `proc_pid_rusage`, split by
+/// walking the regions the way `vmmap` does; regions with a vnode path are
+/// file-backed
+pub fn get_memory_info(pid: u32) -> Result<ProcessMemoryInfo, String> {
+    let usage = rusage(pid)
+        .ok_or_else(|| format!("Failed to read memory of process {}: {}", pid, std::io::Error::last_os_error()))?;
+
+    let (mut private, mut shared, mut mapped) = (0u64, 0u64, 0u64);
+    walk_regions(pid, |region| {
+        let info = &region.info;
+        private += info.pri_private_pages_resident as u64;
+        shared += info.pri_shared_pages_resident as u64;
+        if region.vnode.vip_path[0][0] != 0 {
+            mapped += info.pri_pages_resident as u64;
+        }
+    });
+
+    let page_mb = page_size() as f64 / 1024.0 / 1024.0;
+    Ok(ProcessMemoryInfo {
+        resident_mb: usage.ri_resident_size as f64 / 1024.0 / 1024.0,
+        private_mb: private as f64 * page_mb,
+        shared_mb: shared as f64 * page_mb,
+        mapped_mb: mapped as f64 * page_mb,
+        footprint_mb: usage.ri_phys_footprint as f64 / 1024.0 / 1024.0,
+    })
+}
+
+/// Memory as Activity Monitor's Memory column shows it
+///
+/// The resident size counts the shared framework pages mapped into every
+/// app and leaves out compressed pages; `phys_footprint` is the app's own
+/// dirty memory, compressed or not. Cheap: one `proc_pid_rusage` call.
+pub fn phys_footprint_mb(pid: u32) -> Option<f64> {
+    rusage(pid).map(|usage| usage.ri_phys_footprint as f64 / 1024.0 / 1024.0)
+}
+
+/// Pages of the process held by the compressor or swapped out, as in
+/// Activity Monitor's Compressed Memory column; walks every region
+pub fn compressed_mb(pid: u32) -> Option<f64> {
+    let mut pages = 0u64;
+    let regions = walk_regions(pid, |region| pages += region.info.pri_pages_swapped_out as u64);
+    (regions > 0).then(|| pages as f64 * page_size() as f64 / 1024.0 / 1024.0)
+}
+
+fn rusage(pid: u32) -> Option<libc::rusage_info_v2> {
+    let mut usage: libc::rusage_info_v2 = unsafe { std::mem::zeroed() };
+    let status = unsafe {
+        libc::proc_pid_rusage(
+            pid as libc::c_int,
+            libc::RUSAGE_INFO_V2,
+            &mut usage as *mut libc::rusage_info_v2 as *mut libc::rusage_info_t,
+        )
+    };
+    (status == 0).then_some(usage)
+}
+
+/// Call `visit` for each region of the process; returns how many there were
+fn walk_regions(pid: u32, mut visit: impl FnMut(&RegionWithPathInfo)) -> usize {
+    let mut count = 0;
+    let mut address = 0u64;
+    loop {
+        let mut region: RegionWithPathInfo = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<RegionWithPathInfo>() as libc::c_int;
+        let read = unsafe {
+            libc::proc_pidinfo(
+                pid as libc::c_int,
+                PROC_PIDREGIONPATHINFO,
+                address,
+                &mut region as *mut RegionWithPathInfo as *mut libc::c_void,
+                size,
+            )
+        };
+        if read < size {
+            return count;
+        }
+        visit(&region);
+        count += 1;
+        let next = region.info.pri_address.saturating_add(region.info.pri_size);
+        if next <= address {
+            return count;
+        }
+        address = next;
+    }
+}
+
+fn page_size() -> u64 {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(4096) as u64
+}
+
 /// Get process parent PID
 pub fn get_parent_pid(pid: u32) -> Option<u32> {
     let mut sys = System::new();
@@ -126,6 +261,178 @@ pub fn get_memory_by_pattern(pattern: &str) -> f64 {
         .sum()
 }
 
+/// Freeze a process with SIGSTOP
+///
+/// Refuses system processes and the optimizer itself. A stopped app keeps
+/// its memory but stops touching it, so the compressor and swap can take
+/// its pages; use `resume` to let it run again.
+pub fn suspend(pid: u32) -> Result<(), String> {
+    if pid == std::process::id() {
+        return Err("Refusing to suspend the optimizer itself".into());
+    }
+    if is_system_process(pid) {
+        return Err(format!("Refusing to suspend system process {}", pid));
+    }
+    let result = signal(pid, libc::SIGSTOP);
+    audit::record(AuditAction::Suspend, "", Some(pid), "SIGSTOP", &result);
+    result
+}
+
+/// Let a process suspended with `suspend` run again
+pub fn resume(pid: u32) -> Result<(), String> {
+    let result = signal(pid, libc::SIGCONT);
+    audit::record(AuditAction::Resume, "", Some(pid), "SIGCONT", &result);
+    result
+}
+
+fn signal(pid: u32, sig: libc::c_int) -> Result<(), String> {
+    if unsafe { libc::kill(pid as libc::pid_t, sig) } == 0 {
+        Ok(())
+    } else {
+        Err(format!("kill({}) failed: {}", pid, std::io::Error::last_os_error()))
+    }
+}
+
+/// A group of processes suspended together, e.g. an Electron app and its helpers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspendedApp {
+    pub name: String,
+    pub pids: Vec<u32>,
+    pub since_ms: u64,
+}
+
+/// Record of suspended apps in `suspended.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuspendedApps {
+    pub apps: Vec<SuspendedApp>,
+}
+
+impl SuspendedApps {
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+}
+
+/// Suspend every process of an app and remember them; returns how many stopped
+pub fn suspend_app(name: &str, pids: &[u32]) -> Result<usize, String> {
+    let path = crate::features::config_file(SUSPENDED_FILE)?;
+    let stopped: Vec<u32> = pids.iter().copied().filter(|&pid| suspend(pid).is_ok()).collect();
+    if stopped.is_empty() {
+        return Err(format!("None of {}'s processes could be suspended", name));
+    }
+
+    let mut suspended = SuspendedApps::load(&path);
+    suspended.apps.retain(|app| app.name != name);
+    suspended.apps.push(SuspendedApp {
+        name: name.to_string(),
+        pids: stopped.clone(),
+        since_ms: chrono::Utc::now().timestamp_millis() as u64,
+    });
+    suspended.save(&path)?;
+    Ok(stopped.len())
+}
+
+/// Resume the suspended app that `pid` belongs to, if any; returns its name
+pub fn resume_app_of(pid: u32) -> Option<String> {
+    let path = crate::features::config_file(SUSPENDED_FILE).ok()?;
+    if !path.exists() {
+        return None;
+    }
+    let mut suspended = SuspendedApps::load(&path);
+    let index = suspended.apps.iter().position(|app| app.pids.contains(&pid))?;
+    let app = suspended.apps.remove(index);
+    for &pid in &app.pids {
+        let _ = resume(pid);
+    }
+    let _ = suspended.save(&path);
+    Some(app.name)
+}
+
+/// Resume every app suspended by the optimizer; returns their names
+pub fn resume_all() -> Result<Vec<String>, String> {
+    let path = crate::features::config_file(SUSPENDED_FILE)?;
+    let suspended = SuspendedApps::load(&path);
+    for app in &suspended.apps {
+        for &pid in &app.pids {
+            let _ = resume(pid);
+        }
+    }
+    SuspendedApps::default().save(&path)?;
+    Ok(suspended.apps.into_iter().map(|app| app.name).collect())
+}
+
+/// Whether macOS may put an app into App Nap
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppNapStatus {
+    /// Nothing keeps the app awake while it is hidden
+    Eligible,
+    /// A power assertion held by the app keeps it awake
+    Prevented { assertion: String },
+    /// `NSAppSleepDisabled` is set for the app's bundle
+    Disabled,
+    /// Not a GUI app; App Nap does not apply
+    NotApplicable,
+}
+
+impl std::fmt::Display for AppNapStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eligible => write!(f, "eligible"),
+            Self::Prevented { assertion } => write!(f, "prevented by {}", assertion),
+            Self::Disabled => write!(f, "disabled (NSAppSleepDisabled)"),
+            Self::NotApplicable => write!(f, "not a GUI app"),
+        }
+    }
+}
+
+/// Inspect whether `pid` can be napped
+pub fn app_nap_status(pid: u32) -> AppNapStatus {
+    let Some(bundle_id) = bundle_id(pid) else {
+        return AppNapStatus::NotApplicable;
+    };
+    let disabled = command_output("defaults", &["read", &bundle_id, "NSAppSleepDisabled"])
+        .is_some_and(|value| value.trim() == "1");
+    if disabled {
+        return AppNapStatus::Disabled;
+    }
+    match command_output("pmset", &["-g", "assertions"]).and_then(|out| assertion_for(&out, pid)) {
+        Some(assertion) => AppNapStatus::Prevented { assertion },
+        None => AppNapStatus::Eligible,
+    }
+}
+
+/// `CFBundleIdentifier` of a GUI app from `lsappinfo`
+fn bundle_id(pid: u32) -> Option<String> {
+    let output = command_output("lsappinfo", &["info", "-only", "bundleid", &pid.to_string()])?;
+    let value = output.split_once('=')?.1.trim().trim_matches('"');
+    (!value.is_empty() && value != "[ NULL ]").then(|| value.to_string())
+}
+
+/// First assertion type listed for `pid` in `pmset -g assertions`
+///
+/// Lines look like `pid 412(Slack): [0x...] 00:10:02 PreventUserIdleSystemSleep named: "..."`.
+fn assertion_for(output: &str, pid: u32) -> Option<String> {
+    let prefix = format!("pid {}(", pid);
+    output.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(&prefix)?;
+        let after_time = rest.split_once("] ")?.1;
+        after_time.split_whitespace().nth(1).map(str::to_string)
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +449,13 @@ mod tests {
         // Should have at least our own process
         assert!(!procs.is_empty());
     }
+
+    #[test]
+    fn test_assertion_for() {
+        let output = "Listed by owning process:\n   \
+            pid 412(Slack): [0x0000a1b2000192c3] 00:10:02 PreventUserIdleSystemSleep named: \"Call in progress\"\n   \
+            pid 98(coreaudiod): [0x0000a1b2000192c4] 00:00:05 PreventUserIdleSleep named: \"audio\"\n";
+        assert_eq!(assertion_for(output, 412).as_deref(), Some("PreventUserIdleSystemSleep"));
+        assert_eq!(assertion_for(output, 41), None);
+    }
 }
@@ -3,8 +3,15 @@
 //! Provides a menu bar icon with memory status and optimization controls
 //! similar to the Windows system tray, but using macOS conventions.
 
+use crate::apps::idle::ActivityRecorder;
 use crate::macos::memory::MacMemoryOptimizer;
 use crate::accel::CpuCapabilities;
+use crate::core::config::{ConfigWatcher, OptimizerConfig};
+use crate::core::undo;
+use crate::features::leakwatch::{self, AlertPolicy};
+use crate::features::timeline::TimelineRecorder;
+use crate::monitor::capture;
+use crate::monitor::notify::{self, notify, Notification, NotifyPolicy, Quiet};
 use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU32, Ordering}};
 use std::process::Command;
 use tray_icon::{
@@ -24,16 +31,49 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 /// LaunchAgent plist identifier
 const LAUNCH_AGENT_LABEL: &str = "com.ruvector.memopt";
 
+/// Fields stored as top-level optimizer keys rather than under `[tray]`
+const SHARED_KEYS: &[&str] = &["threshold", "interval_secs"];
+
 /// Tray settings for persistence
+///
+/// Kept in the shared `config.toml`: threshold and interval are the
+/// optimizer's `pressure_threshold` and `min_interval_secs`, the rest goes in
+/// a `[tray]` section. The old `tray-settings.json` is imported once.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct TraySettings {
     pub threshold: u32,
     pub auto_optimize: bool,
     pub interval_secs: u64,
     pub ai_mode: AIModeSettings,
+    pub display: MenuBarDisplay,
+}
+
+/// What the status item shows in the menu bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuBarDisplay {
+    /// Colored usage chip
+    #[default]
+    Icon,
+    /// Usage as text, e.g. "72%"
+    Text,
+    /// Chip followed by the text
+    Both,
+}
+
+impl MenuBarDisplay {
+    fn shows_icon(&self) -> bool {
+        matches!(self, Self::Icon | Self::Both)
+    }
+
+    fn shows_text(&self) -> bool {
+        matches!(self, Self::Text | Self::Both)
+    }
 }
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct AIModeSettings {
     pub focus_mode: bool,
     pub thermal_prediction: bool,
@@ -47,12 +87,13 @@ impl Default for TraySettings {
             auto_optimize: true,
             interval_secs: 60,
             ai_mode: AIModeSettings::default(),
+            display: MenuBarDisplay::default(),
         }
     }
 }
 
 impl TraySettings {
-    fn config_path() -> std::path::PathBuf {
+    fn legacy_path() -> std::path::PathBuf {
         let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
         std::path::PathBuf::from(home)
             .join(".config")
@@ -61,21 +102,43 @@ impl TraySettings {
     }
 
     pub fn load() -> Self {
-        let path = Self::config_path();
-        if let Ok(data) = std::fs::read_to_string(&path) {
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            Self::default()
-        }
+        let config = OptimizerConfig::load_file();
+        let mut settings = match config.tray.clone() {
+            Some(table) => table.try_into().unwrap_or_default(),
+            None => match std::fs::read_to_string(Self::legacy_path()) {
+                Ok(data) => {
+                    let settings: Self = serde_json::from_str(&data).unwrap_or_default();
+                    let _ = settings.save();
+                    return settings;
+                }
+                Err(_) => Self::default(),
+            },
+        };
+        settings.take_from(&config);
+        settings
+    }
+
+    /// Take threshold and interval from the shared optimizer config
+    pub fn take_from(&mut self, config: &OptimizerConfig) {
+        self.threshold = config.pressure_threshold;
+        self.interval_secs = config.min_interval_secs;
     }
 
     pub fn save(&self) -> std::io::Result<()> {
-        let path = Self::config_path();
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+        let path = OptimizerConfig::default_path();
+        let mut config = if path.exists() {
+            OptimizerConfig::load(&path).map_err(|e| std::io::Error::other(e.to_string()))?
+        } else {
+            OptimizerConfig::default()
+        };
+        config.pressure_threshold = self.threshold;
+        config.min_interval_secs = self.interval_secs;
+        let mut table = toml::Table::try_from(self).map_err(std::io::Error::other)?;
+        for key in SHARED_KEYS {
+            table.remove(*key);
         }
-        let data = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, data)
+        config.tray = Some(table);
+        config.save(&path).map_err(|e| std::io::Error::other(e.to_string()))
     }
 }
 
@@ -93,6 +156,7 @@ struct TrayState {
     optimize_id: tray_icon::menu::MenuId,
     purge_id: tray_icon::menu::MenuId,
     app_id: tray_icon::menu::MenuId,
+    undo_id: tray_icon::menu::MenuId,
     cpu_id: tray_icon::menu::MenuId,
     activity_id: tray_icon::menu::MenuId,
     quit_id: tray_icon::menu::MenuId,
@@ -107,6 +171,12 @@ struct TrayState {
     threshold_80: CheckMenuItem,
     threshold_85: CheckMenuItem,
     threshold_90: CheckMenuItem,
+    display_icon_id: tray_icon::menu::MenuId,
+    display_text_id: tray_icon::menu::MenuId,
+    display_both_id: tray_icon::menu::MenuId,
+    display_icon: CheckMenuItem,
+    display_text: CheckMenuItem,
+    display_both: CheckMenuItem,
 }
 
 impl MacTrayApp {
@@ -144,6 +214,8 @@ impl MacTrayApp {
 
         let mut last_update = std::time::Instant::now();
         let mut last_auto_optimize = std::time::Instant::now();
+        let mut config_watcher = ConfigWatcher::new();
+        let mut current_config = OptimizerConfig::load_or_default();
         let auto_enabled = Arc::new(AtomicBool::new(initial_settings.auto_optimize));
         let current_threshold = Arc::new(AtomicU32::new(initial_settings.threshold));
         let initial_usage = MacMemoryOptimizer::get_memory_status()
@@ -152,8 +224,24 @@ impl MacTrayApp {
         let last_usage = Arc::new(AtomicU32::new(initial_usage));
         let total_freed = Arc::new(AtomicU32::new(0));
         let settings_for_loop = initial_settings.clone();
+        let mut display = initial_settings.display;
         let mut initialized = false;
 
+        // Memory curve, spikes and app launches for `timeline`
+        match TimelineRecorder::open_default() {
+            Ok(recorder) => recorder.spawn(),
+            Err(e) => tracing::warn!("Timeline unavailable: {}", e),
+        }
+
+        // Which apps are actually used, for idle-app suggestions
+        match ActivityRecorder::open_default() {
+            Ok(recorder) => recorder.spawn(),
+            Err(e) => tracing::warn!("App activity unavailable: {}", e),
+        }
+
+        // Processes that keep growing over hours
+        let leak_rx = leakwatch::watch(AlertPolicy::from_config(&OptimizerConfig::load_or_default()));
+
         // Run event loop
         #[allow(deprecated)]
         event_loop.run(move |_event, event_loop| {
@@ -161,6 +249,10 @@ impl MacTrayApp {
                 std::time::Instant::now() + std::time::Duration::from_millis(100),
             ));
 
+            while let Ok(suspect) = leak_rx.try_recv() {
+                notify(Notification::critical(suspect.title(), suspect.message()));
+            }
+
             // Create tray icon on first iteration (AFTER event loop is running)
             if !initialized {
                 initialized = true;
@@ -200,6 +292,7 @@ impl MacTrayApp {
                 let optimize_item = MenuItem::new("Optimize Now", true, None);
                 let purge_item = MenuItem::new("Deep Clean (sudo)", true, None);
                 let app_item = MenuItem::new("Optimize Apps", true, None);
+                let undo_item = MenuItem::new("Undo Last Optimization", true, None);
 
                 // Settings submenu
                 let settings_menu = Submenu::new("Settings", true);
@@ -211,6 +304,13 @@ impl MacTrayApp {
                 let _ = settings_menu.append(&threshold_80);
                 let _ = settings_menu.append(&threshold_85);
                 let _ = settings_menu.append(&threshold_90);
+                let display_icon = CheckMenuItem::new("Menu Bar: Icon", true, display == MenuBarDisplay::Icon, None);
+                let display_text = CheckMenuItem::new("Menu Bar: Percentage", true, display == MenuBarDisplay::Text, None);
+                let display_both = CheckMenuItem::new("Menu Bar: Icon and Percentage", true, display == MenuBarDisplay::Both, None);
+                let _ = settings_menu.append(&PredefinedMenuItem::separator());
+                let _ = settings_menu.append(&display_icon);
+                let _ = settings_menu.append(&display_text);
+                let _ = settings_menu.append(&display_both);
 
                 // Info section
                 let cpu_item = MenuItem::new("System Info", true, None);
@@ -228,6 +328,7 @@ impl MacTrayApp {
                 let _ = menu.append(&optimize_item);
                 let _ = menu.append(&purge_item);
                 let _ = menu.append(&app_item);
+                let _ = menu.append(&undo_item);
                 let _ = menu.append(&PredefinedMenuItem::separator());
                 let _ = menu.append(&settings_menu);
                 let _ = menu.append(&PredefinedMenuItem::separator());
@@ -241,6 +342,7 @@ impl MacTrayApp {
                 let optimize_id = optimize_item.id().clone();
                 let purge_id = purge_item.id().clone();
                 let app_id = app_item.id().clone();
+                let undo_id = undo_item.id().clone();
                 let cpu_id = cpu_item.id().clone();
                 let activity_id = activity_item.id().clone();
                 let quit_id = quit_item.id().clone();
@@ -251,46 +353,59 @@ impl MacTrayApp {
                 let threshold_80_id = threshold_80.id().clone();
                 let threshold_85_id = threshold_85.id().clone();
                 let threshold_90_id = threshold_90.id().clone();
+                let display_icon_id = display_icon.id().clone();
+                let display_text_id = display_text.id().clone();
+                let display_both_id = display_both.id().clone();
 
                 // Create tray icon (MUST be after event loop starts on macOS)
-                let icon_data = create_icon_with_usage(initial_usage);
-                if let Ok(icon) = Icon::from_rgba(icon_data, 32, 32) {
-                    match TrayIconBuilder::new()
-                        .with_menu(Box::new(menu))
-                        .with_tooltip(&format!("RuVector MemOpt v{}", VERSION))
-                        .with_icon(icon)
-                        .build()
-                    {
-                        Ok(tray_icon) => {
-                            let state = TrayState {
-                                tray_icon,
-                                status_item,
-                                auto_item,
-                                autostart_item,
-                                optimize_id,
-                                purge_id,
-                                app_id,
-                                cpu_id,
-                                activity_id,
-                                quit_id,
-                                auto_id,
-                                autostart_id,
-                                github_id,
-                                threshold_75_id,
-                                threshold_80_id,
-                                threshold_85_id,
-                                threshold_90_id,
-                                threshold_75,
-                                threshold_80,
-                                threshold_85,
-                                threshold_90,
-                            };
-                            *tray_state_clone.borrow_mut() = Some(state);
-                            tracing::info!("Tray icon created successfully");
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to create tray icon: {}", e);
-                        }
+                let mut builder = TrayIconBuilder::new()
+                    .with_menu(Box::new(menu))
+                    .with_tooltip(&format!("RuVector MemOpt v{}", VERSION));
+                if display.shows_icon() {
+                    if let Ok(icon) = Icon::from_rgba(create_icon_with_usage(initial_usage), 32, 32) {
+                        builder = builder.with_icon(icon);
+                    }
+                }
+                if display.shows_text() {
+                    builder = builder.with_title(menu_bar_title(initial_usage));
+                }
+                match builder.build() {
+                    Ok(tray_icon) => {
+                        let state = TrayState {
+                            tray_icon,
+                            status_item,
+                            auto_item,
+                            autostart_item,
+                            optimize_id,
+                            purge_id,
+                            app_id,
+                            undo_id,
+                            cpu_id,
+                            activity_id,
+                            quit_id,
+                            auto_id,
+                            autostart_id,
+                            github_id,
+                            threshold_75_id,
+                            threshold_80_id,
+                            threshold_85_id,
+                            threshold_90_id,
+                            threshold_75,
+                            threshold_80,
+                            threshold_85,
+                            threshold_90,
+                            display_icon_id,
+                            display_text_id,
+                            display_both_id,
+                            display_icon,
+                            display_text,
+                            display_both,
+                        };
+                        *tray_state_clone.borrow_mut() = Some(state);
+                        tracing::info!("Tray icon created successfully");
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to create tray icon: {}", e);
                     }
                 }
 
@@ -305,6 +420,21 @@ impl MacTrayApp {
 
             // Update status every 5 seconds
             if last_update.elapsed() > std::time::Duration::from_secs(5) {
+                // Pick up a threshold changed outside the tray, or on SIGHUP
+                if let Some(config) = config_watcher.reload(&current_config) {
+                    let threshold = config.pressure_threshold;
+                    current_threshold.store(threshold, Ordering::SeqCst);
+                    let _ = state.threshold_75.set_checked(threshold == 75);
+                    let _ = state.threshold_80.set_checked(threshold == 80);
+                    let _ = state.threshold_85.set_checked(threshold == 85);
+                    let _ = state.threshold_90.set_checked(threshold == 90);
+                    if let Ok(mut s) = settings.lock() {
+                        s.threshold = threshold;
+                    }
+                    notify::center().set_policy(NotifyPolicy::from_config(&config));
+                    current_config = config;
+                }
+
                 if let Ok(status) = MacMemoryOptimizer::get_memory_status() {
                     let usage = status.memory_load_percent;
                     last_usage.store(usage, Ordering::SeqCst);
@@ -330,11 +460,8 @@ impl MacTrayApp {
                     };
                     let _ = state.status_item.set_text(&text);
 
-                    // Update icon color
-                    let icon_data = create_icon_with_usage(usage);
-                    if let Ok(new_icon) = Icon::from_rgba(icon_data, 32, 32) {
-                        let _ = state.tray_icon.set_icon(Some(new_icon));
-                    }
+                    // Update icon color and/or percentage text
+                    update_menu_bar(&state.tray_icon, display, usage);
 
                     // Update tooltip
                     let tooltip = if auto_enabled.load(Ordering::SeqCst) {
@@ -364,6 +491,11 @@ impl MacTrayApp {
                         last_auto_optimize = std::time::Instant::now();
                     }
                 }
+
+                // Hold notifications during calls (Focus Mode) and Focus
+                let focus_mode = settings.lock().map_or(false, |s| s.ai_mode.focus_mode);
+                notify::center().set_quiet(Quiet::Call, focus_mode && !capture::in_use().is_empty());
+                notify::center().poll_system();
                 last_update = std::time::Instant::now();
             }
 
@@ -381,6 +513,8 @@ impl MacTrayApp {
                 } else if event.id == state.app_id {
                     let total_freed_clone = total_freed.clone();
                     run_app_optimization(total_freed_clone);
+                } else if event.id == state.undo_id {
+                    undo_last_optimization();
                 } else if event.id == state.cpu_id {
                     show_system_info();
                 } else if event.id == state.activity_id {
@@ -402,20 +536,20 @@ impl MacTrayApp {
                         match uninstall_autostart() {
                             Ok(()) => {
                                 let _ = state.autostart_item.set_checked(false);
-                                show_toast("Start at Login", "Disabled - won't launch at login", 0.0);
+                                notify(Notification::reply("Start at Login", "Disabled - won't launch at login"));
                             }
                             Err(e) => {
-                                show_toast("Error", &format!("Failed to disable: {}", e), 0.0);
+                                notify(Notification::reply("Error", format!("Failed to disable: {}", e)));
                             }
                         }
                     } else {
                         match install_autostart() {
                             Ok(()) => {
                                 let _ = state.autostart_item.set_checked(true);
-                                show_toast("Start at Login", "Enabled - will launch at login", 0.0);
+                                notify(Notification::reply("Start at Login", "Enabled - will launch at login"));
                             }
                             Err(e) => {
-                                show_toast("Error", &format!("Failed to enable: {}", e), 0.0);
+                                notify(Notification::reply("Error", format!("Failed to enable: {}", e)));
                             }
                         }
                     }
@@ -459,6 +593,25 @@ impl MacTrayApp {
                         s.threshold = 90;
                         let _ = s.save();
                     }
+                } else if event.id == state.display_icon_id
+                    || event.id == state.display_text_id
+                    || event.id == state.display_both_id
+                {
+                    display = if event.id == state.display_icon_id {
+                        MenuBarDisplay::Icon
+                    } else if event.id == state.display_text_id {
+                        MenuBarDisplay::Text
+                    } else {
+                        MenuBarDisplay::Both
+                    };
+                    let _ = state.display_icon.set_checked(display == MenuBarDisplay::Icon);
+                    let _ = state.display_text.set_checked(display == MenuBarDisplay::Text);
+                    let _ = state.display_both.set_checked(display == MenuBarDisplay::Both);
+                    update_menu_bar(&state.tray_icon, display, last_usage.load(Ordering::SeqCst));
+                    if let Ok(mut s) = settings.lock() {
+                        s.display = display;
+                        let _ = s.save();
+                    }
                 }
             }
         })?;
@@ -467,6 +620,26 @@ impl MacTrayApp {
     }
 }
 
+/// Memory usage as shown in the menu bar
+fn menu_bar_title(usage_percent: u32) -> String {
+    format!("{}%", usage_percent)
+}
+
+/// Show the chip, the percentage or both on the status item
+///
+/// The title is drawn by AppKit in the menu bar's own text color, so it
+/// follows light/dark mode like any other status item text.
+fn update_menu_bar(tray_icon: &tray_icon::TrayIcon, display: MenuBarDisplay, usage_percent: u32) {
+    if display.shows_icon() {
+        if let Ok(icon) = Icon::from_rgba(create_icon_with_usage(usage_percent), 32, 32) {
+            let _ = tray_icon.set_icon(Some(icon));
+        }
+    } else {
+        let _ = tray_icon.set_icon(None);
+    }
+    tray_icon.set_title(display.shows_text().then(|| menu_bar_title(usage_percent)));
+}
+
 fn get_memory_status_text() -> String {
     if let Ok(status) = MacMemoryOptimizer::get_memory_status() {
         format!(
@@ -505,14 +678,14 @@ fn run_optimization(aggressive: bool, total_freed: Arc<AtomicU32>) {
                         "System caches were already clean".to_string()
                     };
 
-                    show_toast(title, &msg, freed);
+                    notify(Notification::reply(title, msg).with_freed(freed));
                     tracing::info!("Admin purge: freed {:.1} MB in {}ms", freed, duration);
                 }
                 Err(e) => {
                     if e.contains("cancelled") {
                         tracing::info!("User cancelled admin purge");
                     } else {
-                        show_toast("❌ Deep Clean Failed", &e, 0.0);
+                        notify(Notification::reply("❌ Deep Clean Failed", e));
                         tracing::error!("Admin purge error: {}", e);
                     }
                 }
@@ -541,12 +714,12 @@ fn run_optimization(aggressive: bool, total_freed: Arc<AtomicU32>) {
                     "No memory to reclaim right now".to_string()
                 };
 
-                show_toast(title, &msg, result.freed_mb);
+                notify(Notification::reply(title, msg).with_freed(result.freed_mb));
                 tracing::info!("Optimized: method={:?}, affected {} processes, freed {:.1} MB in {}ms",
                     result.method, result.processes_affected, result.freed_mb, result.duration_ms);
             }
             Err(e) => {
-                show_toast("❌ Optimization Failed", &e.to_string(), 0.0);
+                notify(Notification::reply("❌ Optimization Failed", e.to_string()));
                 tracing::error!("Optimization error: {}", e);
             }
         }
@@ -591,7 +764,7 @@ fn run_app_optimization(total_freed: Arc<AtomicU32>) {
         }
 
         if apps_found.is_empty() {
-            show_toast("ℹ️ No Apps Found", "No browsers or Electron apps running", 0.0);
+            notify(Notification::reply("ℹ️ No Apps Found", "No browsers or Electron apps running"));
             return;
         }
 
@@ -642,7 +815,7 @@ fn run_app_optimization(total_freed: Arc<AtomicU32>) {
             "💻 Apps Analyzed".to_string()
         };
 
-        show_toast(&title, &summary, freed);
+        notify(Notification::reply(title, summary).with_freed(freed));
 
         // Log details
         tracing::info!("App optimization: found {} apps, {:.0} MB total, freed {:.1} MB",
@@ -651,6 +824,20 @@ fn run_app_optimization(total_freed: Arc<AtomicU32>) {
     });
 }
 
+fn undo_last_optimization() {
+    std::thread::spawn(|| {
+        let window = undo::window(&OptimizerConfig::load_or_default());
+        match undo::undo_last(window) {
+            Ok(report) if report.failed.is_empty() => notify(Notification::reply("Optimization Undone", report.description)),
+            Ok(report) => notify(Notification::reply(
+                "Optimization Partly Undone",
+                format!("{}: {}", report.description, report.failed.join("; ")),
+            )),
+            Err(e) => notify(Notification::reply("Nothing Undone", e)),
+        }
+    });
+}
+
 fn show_system_info() {
     let caps = CpuCapabilities::detect();
     let optimizer = MacMemoryOptimizer::new();
@@ -666,7 +853,7 @@ fn show_system_info() {
     // Show brief notification
     let brief = format!("{} • {} cores • Sudo: {}",
         arch, caps.core_count, sudo_status);
-    show_toast("💻 System Info", &brief, 0.0);
+    notify(Notification::reply("💻 System Info", brief));
 
     // Print full details to console
     println!("\n╭─────────────────────────────────────╮");
@@ -694,52 +881,6 @@ fn open_github() {
     let _ = Command::new("open").arg(GITHUB_URL).spawn();
 }
 
-/// Show iOS-style toast notification (uses alert with auto-dismiss to bypass Focus mode)
-fn show_toast(title: &str, message: &str, freed_mb: f64) {
-    let title = title.to_string();
-    let message = message.to_string();
-
-    std::thread::spawn(move || {
-        // Clean message for AppleScript
-        let clean_title = title.replace("\"", "'").replace("\\", "");
-        let clean_message = message.replace("\"", "'").replace("\\", "").replace("\n", " • ");
-
-        // Play sound based on result
-        let sound = if freed_mb > 100.0 {
-            "Glass"
-        } else if freed_mb > 0.0 {
-            "Pop"
-        } else {
-            "Blow"
-        };
-
-        // Play sound
-        let _ = Command::new("afplay")
-            .arg(format!("/System/Library/Sounds/{}.aiff", sound))
-            .spawn();
-
-        // Use display alert with giving up (auto-dismiss after 3 seconds)
-        // This bypasses Focus mode notification blocking
-        let script = format!(
-            r#"display alert "{}" message "{}" giving up after 3"#,
-            clean_title, clean_message
-        );
-
-        let _ = Command::new("osascript")
-            .arg("-e")
-            .arg(&script)
-            .spawn();
-
-        // Also print to console for debugging
-        println!("\n{}\n{}\n", title, message);
-    });
-}
-
-/// Show notification for informational messages
-fn show_notification(title: &str, message: &str) {
-    show_toast(title, message, 0.0);
-}
-
 /// Create icon with usage percentage color coding
 fn create_icon_with_usage(usage_percent: u32) -> Vec<u8> {
     let mut data = Vec::with_capacity(32 * 32 * 4);
@@ -906,13 +1047,23 @@ fn run_purge_with_admin() -> Result<(f64, u64), String> {
         .output()
         .map_err(|e| format!("Failed to run osascript: {}", e))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let result = if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         if stderr.contains("User canceled") || stderr.contains("-128") {
             return Err("User cancelled".into());
         }
-        return Err(format!("Purge failed: {}", stderr));
-    }
+        Err(stderr)
+    };
+    crate::security::audit::record(
+        crate::security::audit::AuditAction::CacheDrop,
+        "disk cache",
+        None,
+        "purge",
+        &result,
+    );
+    result.map_err(|stderr| format!("Purge failed: {}", stderr))?;
 
     // Wait for memory to settle
     std::thread::sleep(std::time::Duration::from_millis(500));
@@ -5,6 +5,7 @@
 #![cfg(target_os = "windows")]
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 use std::time::Duration;
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
@@ -19,16 +20,60 @@ mod tray;
 mod algorithms;
 mod dashboard;
 mod apps;
+mod features;
+mod security;
+mod platform;
+#[cfg(feature = "ai")]
+mod ai;
 
 use core::config::OptimizerConfig;
-use apps::{BrowserOptimizer, ElectronManager, DockerManager, LeakDetector, SmartSuggestions};
+use core::history::{HistoryFilter, HistoryRecord, HistoryStore, OptimizationTrigger, TimeRange};
+use core::ipc::{IpcClient, Pause};
+use core::scheduler::{Schedule, ScheduleStore, Scheduler};
+use core::text::truncate;
+use core::undo;
+use core::watchdog::Watchdog;
+use security::audit::AuditLog;
+use security::policy::{PolicyRule, PolicyStore};
+use features::wsl2::{self, WslConfigAdvice, WslReclaimMethod, WslSnapshot};
+use apps::electron::{self, HeapSnapshot};
+use apps::diskspace;
+use apps::idle::ActivityRecorder;
+use apps::{jvm, BrowserOptimizer, ElectronManager, DockerManager, JvmManager, LeakDetector, SmartSuggestions, SuggestionRisk, VmManager};
 use core::optimizer::IntelligentOptimizer;
-use windows::memory::WindowsMemoryOptimizer;
+use windows::memory::{clean_standby, memory_lists, WindowsMemoryOptimizer};
+use windows::pagefile::{PagefileAdvice, PagefileStatus};
+use windows::performance::{self, MemoryCompression};
 use windows::safety::{SafetyConfig, SafetyGuard};
+use algorithms::forecast::HORIZONS;
 use bench::runner::BenchmarkRunner;
 use bench::advanced::AdvancedBenchmarkRunner;
-use monitor::dashboard::render_dashboard;
+use bench::baseline::BaselineRequest;
+use monitor::gpu::GpuMonitor;
+#[cfg(feature = "ai")]
+use ai::{cuda, ollama, AIMode, AIModeConfig};
+use monitor::watch::{self, WatchTarget};
+use platform::cleanup::{self, CleanupOptions};
+use neural::checkpoint::Checkpoint;
+use features::agent::{self, Agent, RuleStore};
+use features::anomalywatch::{self, AnomalyPolicy};
+use features::build::{BuildHistory, BuildWatcher};
+use features::fleet;
+use features::prefetch::Prefetcher;
+use features::gamemode::GameDetector;
+use features::profiles::{self, ProfileManager};
+use features::report;
+use features::bloatware::BloatwareScanner;
+use features::health::HealthScorer;
+use features::leakwatch::{self, AlertPolicy, SuspectLog};
+use features::patternwatch;
+use features::plugins::{HeuristicInput, PluginHost};
+use features::startup::StartupManager;
+use features::thermal::{self, ThermalMonitor};
+use features::snapshot::{self, Snapshot, SnapshotDiff};
+use features::timeline::{self, TimelineRecorder};
 use dashboard::DashboardServer;
+use dashboard::access::{Credentials, ServeOptions, TlsFiles};
 
 #[derive(Parser)]
 #[command(name = "ruvector-memopt")]
@@ -36,6 +81,14 @@ use dashboard::DashboardServer;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Config file to use instead of the shared config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Override a config key for this run (repeatable)
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    overrides: Vec<String>,
 }
 
 #[derive(Subcommand)]
@@ -50,16 +103,74 @@ enum Commands {
 
         #[arg(long)]
         dry_run: bool,
+
+        /// Show the per-process plan without executing it
+        #[arg(long)]
+        plan: bool,
+
+        /// Optimize in this process even when the service is running
+        #[arg(long)]
+        local: bool,
+
+        /// Trim loosely tied process clusters together (see `clusters`);
+        /// runs in this process
+        #[arg(long)]
+        clusters: bool,
+    },
+
+    /// Trim the working set of specific processes
+    Trim {
+        /// Process ID to trim
+        #[arg(short, long, conflicts_with = "name", required_unless_present = "name")]
+        pid: Option<u32>,
+
+        /// Process name or glob pattern (e.g. "chrome*")
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Show what would be trimmed without trimming
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Purge the standby list, showing page list sizes before and after
+    Standby {
+        /// Flush the modified page list to disk first
+        #[arg(long)]
+        modified: bool,
+
+        /// Only show the current page list sizes
+        #[arg(long)]
+        status: bool,
     },
 
     /// Start continuous optimization daemon
     Daemon {
         #[arg(short, long, default_value = "60")]
         interval: u64,
+        /// Restart the daemon when it crashes or stops responding
+        #[arg(long)]
+        watchdog: bool,
+    },
+
+    /// Pause the running service's automatic optimization
+    Pause {
+        /// How long, like `2h`, `30m` or `1h30m`, or until a time like
+        /// `17:00`; omit to pause until `resume`
+        when: Option<String>,
     },
 
-    /// Run startup optimization mode
-    Startup,
+    /// Resume the running service after `pause`
+    Resume,
+
+    /// Print the running service's events as they happen
+    Events,
+
+    /// Run startup optimization mode, or manage startup programs
+    Startup {
+        #[command(subcommand)]
+        action: Option<StartupCommand>,
+    },
 
     /// Run benchmarks
     Bench {
@@ -69,24 +180,83 @@ enum Commands {
         /// Run advanced algorithm benchmarks (MinCut, PageRank, Sketch, Spectral)
         #[arg(long)]
         advanced: bool,
+
+        /// Save the advanced results as a named baseline
+        #[arg(long, value_name = "NAME")]
+        save_baseline: Option<String>,
+
+        /// Compare the advanced results with a saved baseline; exits with 1 on a regression
+        #[arg(long, value_name = "NAME")]
+        compare: Option<String>,
+
+        /// Change in ops/sec or p95 latency, in percent, that counts as a regression
+        #[arg(long, default_value = "10")]
+        fail_threshold: f64,
+
+        /// Directory of baselines (default: bench/ in the config directory)
+        #[arg(long)]
+        baseline_dir: Option<PathBuf>,
+
+        /// Optimize for real and report freed memory, refault time and latency impact
+        #[arg(long)]
+        real: bool,
+
+        /// Seconds to watch memory refault after each optimization with --real
+        #[arg(long, default_value = "60")]
+        refault_secs: u64,
+
+        /// Print the results, the comparison or the --real report as JSON
+        #[arg(long)]
+        json: bool,
     },
 
-    /// Show real-time dashboard
+    /// Show the terminal dashboard (works over SSH)
     Dashboard,
 
     /// Start dashboard server (JSON API)
     DashboardServer {
         #[arg(short, long, default_value = "8080")]
         port: u16,
+
+        /// Address to listen on; anything but loopback needs --token or --basic-auth
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: std::net::IpAddr,
+
+        /// API token clients send as `Authorization: Bearer` (default: $RUVECTOR_DASHBOARD_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Basic auth credentials instead of a token, as user:password
+        #[arg(long, conflicts_with = "token")]
+        basic_auth: Option<String>,
+
+        /// PEM certificate chain to serve HTTPS (needs the dashboard-tls feature)
+        #[arg(long, requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+
+        /// PEM private key for --tls-cert
+        #[arg(long, requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+
+        /// Origin allowed to call the API from a browser page (repeatable)
+        #[arg(long = "cors-origin")]
+        cors_origins: Vec<String>,
+
+        /// Collect reports from other machines' daemons (fleet hub)
+        #[arg(long)]
+        hub: bool,
     },
 
-    /// Show configuration
-    Config,
+    /// Show or change configuration
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommand>,
+    },
 
     /// Launch system tray icon
     Tray,
 
-    /// Show CPU/SIMD capabilities
+    /// Show CPU/SIMD capabilities and hybrid core layout
     Cpu,
 
     /// Analyze processes with PageRank priority scoring
@@ -94,6 +264,9 @@ enum Commands {
         /// Number of top processes to show
         #[arg(short, long, default_value = "10")]
         top: usize,
+        /// Seconds to watch page faults for the coldness column
+        #[arg(long, default_value = "3")]
+        sample_secs: u64,
     },
 
     /// Show process clusters (MinCut analysis)
@@ -114,11 +287,107 @@ enum Commands {
     Browsers,
 
     /// Show Electron app memory usage (VS Code, Discord, Slack, etc.)
-    Electron,
+    Electron {
+        /// Read renderer and extension host heaps over the DevTools protocol
+        #[arg(long)]
+        heap: bool,
+
+        /// Seconds to sample new allocations for attribution
+        #[arg(long, default_value = "10")]
+        window: u64,
+
+        #[command(subcommand)]
+        action: Option<ElectronCommand>,
+    },
 
     /// Show Docker container resource usage
     Docker,
 
+    /// Show JVM IDEs and Gradle daemons with heap sizing advice
+    Jvm {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// AI runtimes, GPU memory and Ollama models (needs the ai feature)
+    Ai {
+        #[command(subcommand)]
+        action: AiCommand,
+    },
+
+    /// Show GPU VRAM usage
+    Gpu {
+        #[command(subcommand)]
+        action: GpuCommand,
+    },
+
+    /// Show virtual machine memory (Hyper-V, VirtualBox, VMware)
+    Vms {
+        /// Balloon a Hyper-V dynamic-memory VM down to its demand
+        #[arg(long)]
+        balloon: Option<String>,
+    },
+
+    /// Learn app-switch patterns for predictive preloading
+    Prefetch {
+        #[command(subcommand)]
+        action: PrefetchCommand,
+    },
+
+    /// Detect builds and boost them while they run
+    Build {
+        #[command(subcommand)]
+        action: BuildCommand,
+    },
+
+    /// Show what looks like a game and teach Game Mode about others
+    Games {
+        #[command(subcommand)]
+        action: GamesCommand,
+    },
+
+    /// Show CPU/GPU temperatures and throttle forecast
+    Thermal {
+        /// Keep sampling and show the trend
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Find unused or preinstalled apps worth uninstalling
+    Bloatware {
+        /// List every installed app, not just recommendations
+        #[arg(long)]
+        all: bool,
+        /// App name or id to uninstall
+        #[arg(long, value_name = "APP")]
+        remove: Option<String>,
+        /// Actually run the uninstall command for --remove
+        #[arg(long)]
+        confirm: bool,
+    },
+
+    /// Show the system health score and its trend against last week
+    Health,
+
+    /// Switch optimization profiles (gaming, meetings, battery, ...)
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommand,
+    },
+
+    /// Automation rules mapping triggers to actions
+    Agent {
+        #[command(subcommand)]
+        action: AgentCommand,
+    },
+
+    /// Manage plugins from the plugins directory
+    Plugins {
+        #[command(subcommand)]
+        action: PluginsCommand,
+    },
+
     /// Detect potential memory leaks
     Leaks {
         /// Number of samples to take
@@ -128,10 +397,599 @@ enum Commands {
         /// Interval between samples in seconds
         #[arg(short, long, default_value = "5")]
         interval: u64,
+
+        /// Print leak reports as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show suspects raised by the background monitor instead of sampling
+        #[arg(long)]
+        history: bool,
     },
 
     /// Show smart optimization suggestions
-    Suggest,
+    Suggest {
+        /// Carry out the suggestion with this id
+        #[arg(long, value_name = "ID")]
+        apply: Option<String>,
+
+        /// Apply a medium- or high-risk suggestion (without it, only show what would happen)
+        #[arg(long)]
+        confirm: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show optimization history
+    History {
+        /// Only show runs from the last N hours (0 = all)
+        #[arg(long, default_value = "24")]
+        hours: u64,
+
+        /// Filter by trigger (manual, auto, startup, scheduled, service, agent)
+        #[arg(short, long)]
+        trigger: Option<String>,
+
+        /// Maximum number of runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Revert the most recent optimization (suspended apps, paused containers, throttled processes)
+    Undo {
+        /// Show what would be reverted without changing anything
+        #[arg(long)]
+        show: bool,
+    },
+
+    /// Show the signed audit log of actions taken on other processes
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommand,
+    },
+
+    /// Show what happened around a moment, correlated with the memory curve
+    Timeline {
+        /// Moment to explain (HH:MM, or YYYY-MM-DD HH:MM); defaults to the peak of the last window
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Window length in minutes
+        #[arg(short, long, default_value = "30")]
+        minutes: u64,
+
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Live memory, CPU and handles of one app or category, refreshed in place
+    Watch {
+        /// Part of the process name, e.g. chrome
+        #[arg(long, conflicts_with = "category")]
+        name: Option<String>,
+
+        /// App category: browser, development, communication, media, container, vm or ai
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Seconds between samples
+        #[arg(short, long, default_value = "1")]
+        interval: u64,
+    },
+
+    /// Save a snapshot of every process, or compare two snapshots
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotCommand,
+    },
+
+    /// Forecast available memory 30, 60 and 120 minutes ahead
+    Forecast {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Flush the DNS cache and remove old temp files, thumbnails and app caches
+    Cleanup {
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Only these targets (dns, temp, thumbnails, caches); repeatable. Caches are only cleaned when named
+        #[arg(short, long)]
+        target: Vec<String>,
+        /// Keep files matching this glob, on top of `cleanup_exclude`; repeatable
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
+        /// Print JSON instead of a summary
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show how much space browser caches, package caches and old installers take
+    Disk {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Write a daily or weekly summary report
+    Report {
+        #[command(subcommand)]
+        action: ReportCommand,
+    },
+
+    /// Send alerts on critical events to webhooks, Slack, Discord or email
+    Alerts {
+        #[command(subcommand)]
+        action: AlertsCommand,
+    },
+
+    /// Manage optimization schedules
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleCommand,
+    },
+
+    /// Manage process include/exclude rules
+    Policy {
+        #[command(subcommand)]
+        action: PolicyCommand,
+    },
+
+    /// Inspect and reclaim WSL2 (Vmmem) memory
+    Wsl {
+        #[command(subcommand)]
+        action: WslCommand,
+    },
+
+    /// Check pagefile size and usage, and apply a recommended size
+    Pagefile {
+        /// Set the recommended size (takes effect after a restart)
+        #[arg(long)]
+        apply: bool,
+    },
+
+    /// Inspect memory compression and page combining
+    Compression {
+        #[command(subcommand)]
+        action: CompressionCommand,
+    },
+
+    /// Inspect, back up or restore the trained neural model
+    Neural {
+        #[command(subcommand)]
+        action: NeuralCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommand {
+    /// List configured schedules
+    List,
+    /// Add a schedule
+    Add {
+        /// Action to run (optimize, aggressive, browser-trim, report-day, report-week)
+        #[arg(short, long, default_value = "optimize")]
+        action: String,
+
+        /// Run daily at HH:MM
+        #[arg(long, conflicts_with = "every", required_unless_present = "every")]
+        at: Option<String>,
+
+        /// Run every N minutes
+        #[arg(long)]
+        every: Option<u32>,
+
+        /// Days to run on (all, weekdays, weekends, or e.g. mon,wed,fri)
+        #[arg(long, default_value = "all")]
+        days: String,
+
+        /// Only run within this hour window (e.g. 9-18)
+        #[arg(long)]
+        hours: Option<String>,
+    },
+    /// Remove a schedule by ID
+    Remove {
+        id: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum PolicyCommand {
+    /// List rules in evaluation order
+    List,
+    /// Add a rule
+    Add {
+        /// allow (always optimize), deny (never touch) or background (lowered during builds and games)
+        effect: String,
+
+        /// Pattern to match
+        pattern: String,
+
+        /// What the pattern matches (glob, regex, path, publisher)
+        #[arg(short, long, default_value = "glob")]
+        kind: String,
+    },
+    /// Remove a rule by ID
+    Remove {
+        id: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum PrefetchCommand {
+    /// Show learned app transitions and the current prediction
+    Status,
+    /// Record app switches in the foreground until interrupted
+    Watch,
+}
+
+#[derive(Subcommand)]
+enum ElectronCommand {
+    /// Close an app gracefully and start it again with the same workspace
+    Restart {
+        /// App name (`vscode`, `slack`, ...) or display name
+        app: String,
+        /// Actually restart; without it the relaunch command is only shown
+        #[arg(long)]
+        confirm: bool,
+        /// Seconds to wait for the app to exit before giving up
+        #[arg(long, default_value = "30")]
+        timeout: u64,
+    },
+    /// Let `electron restart` restart an app
+    Allow {
+        app: String,
+    },
+    /// Stop `electron restart` from restarting an app
+    Disallow {
+        app: String,
+    },
+    /// Which extensions VS Code's memory goes to, and which one to disable
+    Vscode {
+        /// Also split the extension host's heap (needs `code --inspect-extensions=9333`)
+        #[arg(long)]
+        heap: bool,
+        /// Seconds to sample new allocations for
+        #[arg(long, default_value = "10")]
+        window: u64,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StartupCommand {
+    /// List startup items ranked by estimated login impact
+    List {
+        /// Include disabled items
+        #[arg(long)]
+        all: bool,
+    },
+    /// Stop an item from starting at login
+    Disable {
+        /// Item name or id (from `startup list`)
+        item: String,
+    },
+    /// Start an item at login again
+    Enable {
+        /// Item name or id (from `startup list`)
+        item: String,
+    },
+    /// Start an item some time after login instead of immediately
+    Delay {
+        /// Item name or id (from `startup list`)
+        item: String,
+        /// Delay in seconds
+        #[arg(short, long, default_value = "60")]
+        secs: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum BuildCommand {
+    /// Boost builds as they start and report measured speedups
+    Watch {
+        /// Only measure, to record an unboosted baseline
+        #[arg(long)]
+        no_boost: bool,
+    },
+    /// Show recorded builds and their speedups
+    History {
+        /// Number of builds to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum GamesCommand {
+    /// Score running programs and show why each looks like a game
+    Detect,
+    /// List games you taught and programs you ruled out
+    List,
+    /// Always treat a program as a game
+    Teach {
+        /// Process name, e.g. MyGame.exe
+        name: String,
+    },
+    /// Never treat a program as a game
+    Ignore {
+        /// Process name
+        name: String,
+    },
+    /// Drop a program from both lists
+    Forget {
+        /// Process name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    /// List profiles and show which one is active
+    List,
+    /// Show what a profile changes
+    Show {
+        /// Profile name
+        name: String,
+    },
+    /// Switch to a profile
+    Apply {
+        /// Profile name
+        name: String,
+    },
+    /// Leave the active profile and restore the settings from before it
+    Off,
+    /// Switch profiles by their activation rules (AC/battery, running apps)
+    Auto {
+        /// Keep checking until interrupted
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Write the built-in profiles to profiles.toml for editing
+    Init,
+}
+
+#[derive(Subcommand)]
+enum AgentCommand {
+    /// List rules
+    List,
+    /// Evaluate the rules and run the actions of those that match
+    Run {
+        /// Show what would run without running it
+        #[arg(long)]
+        dry_run: bool,
+        /// Keep evaluating until interrupted
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Replay recent timeline history against the rules without running anything
+    Test {
+        /// Hours of history to replay
+        #[arg(long, default_value = "24")]
+        hours: u64,
+    },
+    /// Write example rules to agent_rules.toml for editing
+    Init,
+}
+
+#[derive(Subcommand)]
+enum PluginsCommand {
+    /// List installed plugins and their state
+    List,
+    /// Enable a plugin after checking that it loads
+    Enable {
+        /// Plugin id (library file name without extension)
+        id: String,
+    },
+    /// Disable a plugin
+    Disable {
+        /// Plugin id
+        id: String,
+    },
+    /// Run a command a plugin provides
+    Run {
+        /// Plugin id
+        id: String,
+        /// Command name
+        command: String,
+        /// Arguments passed to the command
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Ask plugins and WASM heuristics what they would do right now
+    Recommend {
+        /// Carry out the recommendations
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Subcommand)]
+#[cfg_attr(not(feature = "ai"), allow(dead_code))]
+enum AiCommand {
+    /// Show AI runtimes, GPU memory and loaded Ollama models
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List Ollama models with their size and when they were last used
+    Models {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Unload Ollama models from memory
+    Unload {
+        /// Models to unload, e.g. llama3.2
+        #[arg(required_unless_present = "all")]
+        models: Vec<String>,
+        /// Unload every loaded model
+        #[arg(long, conflicts_with = "models")]
+        all: bool,
+    },
+    /// Unload idle models, or all of them when VRAM is nearly full
+    Optimize,
+    /// Find PyTorch/CUDA processes and tell training from inference
+    Cuda {
+        /// Lower the priority of training processes (undo with `undo`)
+        #[arg(long)]
+        lower_priority: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GpuCommand {
+    /// Show VRAM, temperature and load per GPU
+    Status,
+    /// Refresh the GPU table until interrupted
+    Watch {
+        /// Refresh interval in seconds
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+    },
+    /// List processes by VRAM usage
+    Top {
+        /// Number of processes to show
+        #[arg(short = 'n', long, default_value = "15")]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum NeuralCommand {
+    /// Show what the model has learned so far
+    Status,
+    /// Write the trained model to a file
+    Export {
+        /// Destination checkpoint file
+        path: PathBuf,
+    },
+    /// Replace the trained model with one from a file
+    Import {
+        /// Checkpoint file to import (older versions are migrated)
+        path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum WslCommand {
+    /// Show Vmmem vs guest memory
+    Status,
+    /// Return VM memory to Windows (drop_caches, or shutdown when idle)
+    Reclaim {
+        /// Force `wsl --shutdown` (stops all running distributions)
+        #[arg(long)]
+        shutdown: bool,
+    },
+    /// Recommend .wslconfig memory settings
+    Config {
+        /// Write the recommended settings (keeps .wslconfig.bak)
+        #[arg(long)]
+        write: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the effective config (file, environment and --set overrides)
+    Show,
+    /// Print one key, e.g. `config get pressure_threshold`
+    Get { key: String },
+    /// Change one key in config.toml, e.g. `config set pressure_threshold 85`
+    Set { key: String, value: String },
+    /// Open config.toml in $VISUAL/$EDITOR and save it once it validates
+    Edit,
+    /// Check config.toml (or another file) for errors and unknown keys
+    Validate { path: Option<PathBuf> },
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+    /// Print the most recent records, flagging any with a bad signature
+    Tail {
+        /// Number of records to show
+        #[arg(short = 'n', long, default_value = "20")]
+        lines: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Write memory trends, optimizations, growing apps, leaks, health and advice to a file
+    Generate {
+        /// Period to cover (day, week)
+        #[arg(short, long, default_value = "week")]
+        period: String,
+        /// html or markdown; defaults to `report_format`
+        #[arg(short, long)]
+        format: Option<String>,
+        /// Directory to write to; defaults to `report_dir`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AlertsCommand {
+    /// Send a test alert to every channel in `[alerts]`
+    Test,
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommand {
+    /// Save per-process memory, handles, threads and VRAM, and GPU memory
+    Take {
+        /// File to write; defaults to snapshots/<date>-<time>.json in the config directory
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List saved snapshots
+    List,
+    /// Show the apps that grew and shrank the most between two snapshots
+    Diff {
+        /// Earlier snapshot: a file, a name from `snapshot list`, or `latest`
+        from: String,
+
+        /// Later snapshot; defaults to one taken now
+        to: Option<String>,
+
+        /// Apps to show in each direction
+        #[arg(short = 'n', long, default_value = "10")]
+        limit: usize,
+
+        /// Print JSON instead of tables
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CompressionCommand {
+    /// Show MMAgent settings and the compression store size
+    Status,
+    /// Turn page combining on or off
+    Combining {
+        /// Disable instead of enable
+        #[arg(long)]
+        off: bool,
+    },
+    /// Turn memory compression on or off (applies after a restart)
+    Compress {
+        /// Disable instead of enable
+        #[arg(long)]
+        off: bool,
+    },
 }
 
 #[tokio::main]
@@ -144,6 +1002,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::subscriber::set_global_default(subscriber)?;
     
     let cli = Cli::parse();
+    if let Some(path) = cli.config.clone() {
+        core::config::set_config_path(path);
+    }
+    core::config::set_cli_overrides(&cli.overrides)?;
     
     match cli.command {
         Commands::Status => {
@@ -153,17 +1015,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("  Available: {:.0} MB", status.available_physical_mb);
             println!("  Used:      {:.0} MB", status.used_physical_mb());
             println!("  Load:      {}%", status.memory_load_percent);
+            println!("  Commit:    {:.0} / {:.0} MB ({}%, peak {:.0} MB)",
+                status.commit_charge_mb,
+                status.commit_limit_mb,
+                status.commit_percent(),
+                status.commit_peak_mb
+            );
             println!("  Pressure:  {}", 
                 if status.is_critical() { "CRITICAL" }
                 else if status.is_high_pressure() { "HIGH" }
                 else { "Normal" }
             );
+
+            let mut system = sysinfo::System::new();
+            system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+            if let Some(store_mb) = performance::compression_store_mb(&system) {
+                println!("  Compressed: {:.0} MB", store_mb);
+            }
+            if let Some(daemon) = IpcClient::connect().and_then(|mut client| client.status().ok()) {
+                println!();
+                daemon.print();
+            }
         }
         
-        Commands::Optimize { aggressive, dry_run } => {
+        Commands::Optimize { aggressive, dry_run, plan, local, clusters } => {
+            // The running service applies its own rate limit and safety checks
+            if !dry_run && !plan && !local && !clusters {
+                if let Some(mut daemon) = IpcClient::connect() {
+                    match daemon.optimize(aggressive) {
+                        Ok(report) => report.print(),
+                        Err(e) => println!("Service did not optimize: {}", e),
+                    }
+                    return Ok(());
+                }
+            }
+
             let config = OptimizerConfig {
                 aggressive_mode: aggressive,
-                ..Default::default()
+                ..OptimizerConfig::load_or_default()
             };
             
             let mut safety = SafetyGuard::new(SafetyConfig {
@@ -178,36 +1067,213 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Ok(());
             }
             
-            if dry_run {
-                println!("DRY RUN - No changes will be made");
+            let mut optimizer = WindowsMemoryOptimizer::new();
+            optimizer.set_group_clusters(clusters);
+            let opt_plan = optimizer.plan(config.aggressive_mode)?;
+
+            if plan || dry_run {
+                if dry_run {
+                    println!("DRY RUN - No changes will be made");
+                }
+                opt_plan.print_summary(25);
+                return Ok(());
             }
+
+            if let Some(reason) = &opt_plan.downgrade_reason {
+                println!("⚠ Aggressive trimming skipped: {}", reason);
+            }
+            let execution = optimizer.execute(&opt_plan)?;
             
-            let optimizer = WindowsMemoryOptimizer::new();
-            let result = optimizer.optimize(aggressive)?;
-            
-            safety.record_attempt(result.freed_mb >= 0.0);
+            safety.record_attempt(execution.freed_mb >= 0.0);
+            core::history::record(
+                HistoryRecord::now(
+                    OptimizationTrigger::Manual,
+                    opt_plan.aggressive,
+                    execution.freed_mb,
+                    execution.processes_trimmed(),
+                    execution.duration_ms,
+                )
+                .with_memory_load(status.memory_load_percent),
+            );
             
-            println!("Optimization complete:");
-            println!("  Freed:     {:.1} MB", result.freed_mb);
-            println!("  Trimmed:   {} processes", result.processes_trimmed);
-            println!("  Duration:  {} ms", result.duration_ms);
+            execution.print_summary(10);
         }
         
-        Commands::Daemon { interval } => {
+        Commands::Standby { modified, status } => {
+            if !WindowsMemoryOptimizer::new().has_admin_privileges() {
+                println!("The standby list can only be read or purged from an elevated prompt.");
+                return Ok(());
+            }
+            if status {
+                let lists = memory_lists()?;
+                println!("Standby:  {:>10.1} MB", lists.standby_mb);
+                println!("Modified: {:>10.1} MB", lists.modified_mb);
+                println!("Free:     {:>10.1} MB", lists.free_mb + lists.zeroed_mb);
+                return Ok(());
+            }
+            match clean_standby(modified) {
+                Ok(cleanup) => {
+                    println!("✅ Standby list purged\n");
+                    cleanup.print_summary();
+                }
+                Err(e) => println!("Failed to purge the standby list: {}", e),
+            }
+        }
+
+        Commands::Trim { pid, name, dry_run } => {
+            use security::policy::{Policy, ProcessIdentity};
+            use security::validator::matches_glob;
+
+            let policy = Policy::load_default();
+            let safety = SafetyGuard::new(SafetyConfig {
+                dry_run,
+                ..Default::default()
+            });
+
+            let mut system = sysinfo::System::new();
+            system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+            let targets: Vec<(u32, String, f64, bool)> = system.processes()
+                .iter()
+                .filter(|(p, proc_)| match (pid, &name) {
+                    (Some(target), _) => p.as_u32() == target,
+                    (None, Some(pattern)) => matches_glob(pattern, &proc_.name().to_string_lossy()),
+                    (None, None) => false,
+                })
+                .map(|(p, proc_)| (
+                    p.as_u32(),
+                    proc_.name().to_string_lossy().to_string(),
+                    proc_.memory() as f64 / (1024.0 * 1024.0),
+                    policy.is_excluded(&ProcessIdentity::from_process(proc_, &policy)),
+                ))
+                .collect();
+
+            if targets.is_empty() {
+                println!("No matching processes found.");
+                return Ok(());
+            }
+
+            if safety.is_dry_run() {
+                println!("DRY RUN - No changes will be made");
+            }
+
+            let mut outcomes: Vec<(u32, String, f64, &str)> = Vec::new();
+            for (target_pid, target_name, _, excluded) in &targets {
+                let outcome = if *excluded {
+                    "protected"
+                } else if safety.is_dry_run() {
+                    "would trim"
+                } else {
+                    match WindowsMemoryOptimizer::trim_process_working_set(*target_pid) {
+                        Ok(_) => "trimmed",
+                        Err(_) => "failed",
+                    }
+                };
+                outcomes.push((*target_pid, target_name.clone(), 0.0, outcome));
+            }
+
+            // Measure RSS after trimming
+            system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+            for entry in outcomes.iter_mut() {
+                entry.2 = system.process(sysinfo::Pid::from_u32(entry.0))
+                    .map(|p| p.memory() as f64 / (1024.0 * 1024.0))
+                    .unwrap_or(0.0);
+            }
+
+            println!("┌─────────┬──────────────────────────┬────────────┬────────────┬────────────┐");
+            println!("│   PID   │ Process                  │ Before MB  │ After MB   │ Result     │");
+            println!("├─────────┼──────────────────────────┼────────────┼────────────┼────────────┤");
+            let mut total_freed = 0.0;
+            for ((_, _, before, _), (target_pid, target_name, after, outcome)) in targets.iter().zip(&outcomes) {
+                if *outcome == "trimmed" {
+                    total_freed += (before - after).max(0.0);
+                }
+                println!("│ {:>7} │ {:24} │ {:>10.1} │ {:>10.1} │ {:10} │",
+                    target_pid, truncate(target_name, 24), before, after, outcome);
+            }
+            println!("└─────────┴──────────────────────────┴────────────┴────────────┴────────────┘");
+            println!("\nFreed {:.1} MB from {} processes", total_freed,
+                outcomes.iter().filter(|o| o.3 == "trimmed").count());
+        }
+
+        Commands::Daemon { interval, watchdog } => {
+            if watchdog {
+                let hang_after = core::watchdog::hang_after(Duration::from_secs(interval));
+                Watchdog::new("daemon", Some(hang_after)).run()?;
+                return Ok(());
+            }
+            core::watchdog::install_panic_hook();
             info!("Starting optimization daemon (interval: {}s)", interval);
+
+            match TimelineRecorder::open_default() {
+                Ok(recorder) => recorder.spawn(),
+                Err(e) => tracing::warn!("Timeline unavailable: {}", e),
+            }
+            match ActivityRecorder::open_default() {
+                Ok(recorder) => recorder.spawn(),
+                Err(e) => tracing::warn!("App activity unavailable: {}", e),
+            }
             
-            let config = OptimizerConfig::default();
+            let config = OptimizerConfig::load_or_default();
+            leakwatch::spawn(AlertPolicy::from_config(&config));
+            patternwatch::spawn(|_| {});
+            anomalywatch::spawn(AnomalyPolicy::from_config(&config), |_| {});
+            fleet::spawn();
+            monitor::mqtt::spawn(&config);
+            monitor::alerts::spawn(&config);
+            tokio::spawn(monitor::alerts::exit_on_signal());
             let mut optimizer = IntelligentOptimizer::new(config);
             
             optimizer.run_loop(Duration::from_secs(interval)).await;
         }
+
+        Commands::Pause { when } => {
+            let pause = when.as_deref().map_or(Ok(Pause::until_resumed()), Pause::parse);
+            match pause.and_then(|pause| IpcClient::connect_required()?.pause(pause).map(|()| pause)) {
+                Ok(pause) => println!("Service paused {}", pause),
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        Commands::Resume => match IpcClient::connect_required().and_then(|mut d| d.resume()) {
+            Ok(()) => println!("Service resumed"),
+            Err(e) => println!("{}", e),
+        },
+
+        Commands::Events => match IpcClient::connect_required().and_then(IpcClient::subscribe) {
+            Ok(events) => {
+                for event in events {
+                    println!("{}  {}", chrono::Local::now().format("%H:%M:%S"), event);
+                }
+                println!("Service stopped");
+            }
+            Err(e) => println!("{}", e),
+        },
         
-        Commands::Startup => {
+        Commands::Startup { action: Some(action) } => {
+            let mut manager = StartupManager::new();
+            let changed = match action {
+                StartupCommand::List { all } => {
+                    manager.print_table(all);
+                    None
+                }
+                StartupCommand::Disable { item } => Some(manager.disable(&item)),
+                StartupCommand::Enable { item } => Some(manager.enable(&item)),
+                StartupCommand::Delay { item, secs } => Some(manager.delay(&item, secs)),
+            };
+            match changed {
+                Some(Ok(item)) => println!("✓ {}: {} at login", item.name, item.state),
+                Some(Err(e)) => println!("Failed to change startup item: {}", e),
+                None => {}
+            }
+        }
+
+        Commands::Startup { action: None } => {
             info!("Running startup optimization");
             
             let config = OptimizerConfig {
                 startup_mode: true,
-                ..Default::default()
+                ..OptimizerConfig::load_or_default()
             };
             let mut optimizer = IntelligentOptimizer::new(config);
             
@@ -222,13 +1288,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         
-        Commands::Bench { iterations, advanced } => {
-            if advanced {
-                println!("Running advanced RuVector algorithm benchmarks ({} iterations)...", iterations);
+        Commands::Bench { iterations, advanced, save_baseline, compare, fail_threshold, baseline_dir, real, refault_secs, json } => {
+            let baselines = BaselineRequest { save: save_baseline, compare, fail_threshold, dir: baseline_dir, json };
+            if real {
+                if let Err(e) = bench::real::run_and_print(refault_secs, json) {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            } else if advanced || baselines.needs_suite() {
+                if !json {
+                    println!("Running advanced RuVector algorithm benchmarks ({} iterations)...", iterations);
+                }
 
-                let runner = AdvancedBenchmarkRunner::new(iterations);
+                let runner = AdvancedBenchmarkRunner::new(iterations).with_progress(!json);
                 let suite = runner.run_all();
-                suite.print_summary();
+                if !json {
+                    suite.print_summary();
+                }
+                match baselines.apply(iterations, &suite) {
+                    Ok(true) => {}
+                    Ok(false) => std::process::exit(1),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
             } else {
                 println!("Running benchmarks ({} iterations)...", iterations);
 
@@ -249,26 +1333,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         
-        Commands::Dashboard => {
-            println!("Starting real-time dashboard (Ctrl+C to exit)...\n");
-            
-            let metrics = bench::metrics::BenchmarkMetrics::new();
-            
-            loop {
-                if let Ok(status) = WindowsMemoryOptimizer::get_memory_status() {
-                    // Clear screen
-                    print!("\x1B[2J\x1B[1;1H");
-                    println!("{}", render_dashboard(&status, &metrics.summary()));
+        Commands::Dashboard => monitor::dashboard::run()?,
+        
+        Commands::Config { action } => match action.unwrap_or(ConfigCommand::Show) {
+            ConfigCommand::Show => {
+                let config = OptimizerConfig::load_or_default();
+                println!("Current Configuration ({}):", OptimizerConfig::default_path().display());
+                println!("{}", toml::to_string_pretty(&config)?);
+            }
+            ConfigCommand::Get { key } => match OptimizerConfig::load_or_default().get_value(&key) {
+                Ok(value) => println!("{}", value),
+                Err(e) => println!("{}", e),
+            },
+            ConfigCommand::Set { key, value } => match core::config::set_in_file(&key, &value) {
+                Ok(path) => println!("Set {} = {} in {}", key, value, path.display()),
+                Err(e) => println!("Not saved: {}", e),
+            },
+            ConfigCommand::Edit => match core::config::edit() {
+                Ok(message) => println!("{}", message),
+                Err(e) => println!("{}", e),
+            },
+            ConfigCommand::Validate { path } => {
+                let path = path.unwrap_or_else(OptimizerConfig::default_path);
+                let validation = core::config::validate_file(&path);
+                validation.print(&path);
+                if !validation.is_ok() {
+                    std::process::exit(1);
                 }
-                tokio::time::sleep(Duration::from_secs(1)).await;
             }
-        }
-        
-        Commands::Config => {
-            let config = OptimizerConfig::default();
-            println!("Current Configuration:");
-            println!("{}", toml::to_string_pretty(&config)?);
-        }
+        },
 
         Commands::Tray => {
             println!("Starting system tray icon...");
@@ -282,62 +1375,87 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let caps = accel::CpuCapabilities::detect();
             caps.print_report();
 
+            accel::CpuTopology::get().print_report();
+
             println!("\nRunning quick SIMD benchmark...");
             let simd = accel::SimdOptimizer::new();
             let (scalar, simd_time, speedup) = simd.benchmark(1024, 10000);
+            println!("  Kernel:       {} ({} lanes, used by pattern search)", simd.kernel(), simd.kernel().lanes());
             println!("  Scalar time:  {:.4}s", scalar);
             println!("  SIMD time:    {:.4}s", simd_time);
             println!("  Speedup:      {:.2}x", speedup);
         }
 
-        Commands::DashboardServer { port } => {
+        Commands::DashboardServer { port, bind, token, basic_auth, tls_cert, tls_key, cors_origins, hub } => {
+            let credentials = match (basic_auth, token.or_else(|| std::env::var(dashboard::access::TOKEN_ENV).ok())) {
+                (Some(basic), _) => Credentials::parse_basic(&basic)?,
+                (None, Some(token)) => Credentials::Token(token),
+                (None, None) => Credentials::None,
+            };
+            let tls = tls_cert.zip(tls_key).map(|(cert, key)| TlsFiles { cert, key });
+            let options = ServeOptions { bind, port, credentials, tls, cors_origins, hub };
             let server = DashboardServer::new();
-            server.serve(port).await?;
+            server.serve(options).await?;
         }
 
-        Commands::Pagerank { top } => {
+        Commands::Pagerank { top, sample_secs } => {
             println!("Analyzing processes with PageRank...\n");
 
             let mut system = sysinfo::System::new_all();
             system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
             let mut pagerank = algorithms::ProcessPageRank::new();
-            pagerank.compute(&system);
+            let relations = monitor::relations::collect(&system);
+            pagerank.compute_with(&system, &relations);
+
+            // Coldness: how rarely each process faulted pages in while we watched
+            let mut access = algorithms::AccessTracker::new();
+            access.record(monitor::faults::sample_from(&system), std::time::Instant::now());
+            tokio::time::sleep(std::time::Duration::from_secs(sample_secs)).await;
+            access.record(monitor::faults::sample_from(&system), std::time::Instant::now());
+            let coldness = access.coldness_all();
+            let coldness_of = |pid: &u32| coldness.get(pid).map_or("-".to_string(), |c| format!("{:.2}", c));
 
             let candidates = pagerank.get_trim_candidates(top);
             let critical = pagerank.get_critical_processes(top);
 
             println!("📉 LOW PRIORITY (trim candidates):");
-            println!("┌─────────┬────────────────────────────┬──────────────┐");
-            println!("│   PID   │ Process                    │ PageRank     │");
-            println!("├─────────┼────────────────────────────┼──────────────┤");
+            println!("┌─────────┬────────────────────────────┬──────────────┬──────────┐");
+            println!("│   PID   │ Process                    │ PageRank     │ Coldness │");
+            println!("├─────────┼────────────────────────────┼──────────────┼──────────┤");
             for (pid, score) in &candidates {
                 let name = system
                     .process(sysinfo::Pid::from_u32(*pid))
                     .map(|p| p.name().to_string_lossy().to_string())
                     .unwrap_or_else(|| "unknown".into());
-                println!("│ {:>7} │ {:26} │ {:>12.6} │", pid, truncate(&name, 26), score);
+                println!("│ {:>7} │ {:26} │ {:>12.6} │ {:>8} │", pid, truncate(&name, 26), score, coldness_of(pid));
             }
-            println!("└─────────┴────────────────────────────┴──────────────┘");
+            println!("└─────────┴────────────────────────────┴──────────────┴──────────┘");
 
             println!("\n📈 HIGH PRIORITY (preserve):");
-            println!("┌─────────┬────────────────────────────┬──────────────┐");
-            println!("│   PID   │ Process                    │ PageRank     │");
-            println!("├─────────┼────────────────────────────┼──────────────┤");
+            println!("┌─────────┬────────────────────────────┬──────────────┬──────────┐");
+            println!("│   PID   │ Process                    │ PageRank     │ Coldness │");
+            println!("├─────────┼────────────────────────────┼──────────────┼──────────┤");
             for (pid, score) in &critical {
                 let name = system
                     .process(sysinfo::Pid::from_u32(*pid))
                     .map(|p| p.name().to_string_lossy().to_string())
                     .unwrap_or_else(|| "unknown".into());
-                println!("│ {:>7} │ {:26} │ {:>12.6} │", pid, truncate(&name, 26), score);
+                println!("│ {:>7} │ {:26} │ {:>12.6} │ {:>8} │", pid, truncate(&name, 26), score, coldness_of(pid));
             }
-            println!("└─────────┴────────────────────────────┴──────────────┘");
+            println!("└─────────┴────────────────────────────┴──────────────┴──────────┘");
 
             let stats = pagerank.stats();
             println!("\nStatistics:");
             println!("  Processes: {}", stats.process_count);
             println!("  Mean score: {:.6}", stats.mean_score);
             println!("  Std dev:    {:.6}", stats.std_dev);
+            let edges: Vec<String> = monitor::relations::count_by_kind(&relations)
+                .iter()
+                .map(|(kind, count)| format!("{} {}", count, kind))
+                .collect();
+            println!("  Edges:      {}", if edges.is_empty() { "none".to_string() } else { edges.join(", ") });
+            println!("  Coldness:   0.00 for the process faulting most, 1.00 for none in {}s", sample_secs);
         }
 
         Commands::Clusters { max } => {
@@ -425,11 +1543,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             optimizer.print_summary();
         }
 
-        Commands::Electron => {
+        Commands::Electron { action: Some(action), .. } => match action {
+            ElectronCommand::Restart { app, confirm, timeout } => {
+                let allowed = OptimizerConfig::load_or_default().electron_restart_apps;
+                let mut manager = ElectronManager::new();
+                manager.refresh();
+                match manager.relaunch_plan(&app) {
+                    Err(e) => println!("{}", e),
+                    Ok(plan) if !confirm => {
+                        println!("Would close {} (PID {}) and start it again with:\n  {}", app, plan.pid, plan.command_line());
+                        if !allowed.iter().any(|a| a.eq_ignore_ascii_case(&app)) {
+                            println!("\n{} is not allowed to be restarted yet; run `electron allow {}` first.", app, app);
+                        }
+                        println!("\nNothing was changed. Re-run with --confirm to restart.");
+                    }
+                    Ok(_) => match manager.restart(&app, &allowed, Duration::from_secs(timeout)) {
+                        Ok(outcome) => println!(
+                            "✓ Restarted {} (was using {:.0} MB, new PID {})",
+                            outcome.display_name, outcome.before_mb, outcome.new_pid
+                        ),
+                        Err(e) => println!("Failed to restart {}: {}", app, e),
+                    },
+                }
+            }
+            ElectronCommand::Allow { app } => match electron::set_restart_allowed(&app, true) {
+                Ok(name) => println!("✓ {} may be restarted by `electron restart`", name),
+                Err(e) => println!("{}", e),
+            },
+            ElectronCommand::Disallow { app } => match electron::set_restart_allowed(&app, false) {
+                Ok(name) => println!("✓ {} will no longer be restarted", name),
+                Err(e) => println!("{}", e),
+            },
+            ElectronCommand::Vscode { heap, window, json } => {
+                let mut manager = ElectronManager::new();
+                manager.refresh();
+                if heap {
+                    println!("Sampling the extension host heap for {} seconds...", window);
+                }
+                match manager.vscode_blame(heap.then(|| Duration::from_secs(window))) {
+                    None => println!("VS Code is not running."),
+                    Some(blame) if json => println!("{}", serde_json::to_string_pretty(&blame)?),
+                    Some(blame) => blame.print(),
+                }
+            }
+        },
+
+        Commands::Electron { heap, window, action: None } => {
             println!("Analyzing Electron app memory usage...\n");
             let mut manager = ElectronManager::new();
             manager.refresh();
             manager.print_summary();
+
+            if heap {
+                println!("\nSampling JS heaps for {} seconds...", window);
+                let snapshot = manager.heap_snapshot(Duration::from_secs(window));
+                snapshot.print();
+                if let Some(previous) = HeapSnapshot::load_previous() {
+                    for growth in snapshot.growth_since(&previous).iter().take(5) {
+                        println!("   • {}", growth.describe());
+                    }
+                }
+                if let Err(e) = snapshot.save() {
+                    println!("Failed to save heap snapshot: {}", e);
+                }
+                let hidden = manager.apps_without_port();
+                if !hidden.is_empty() {
+                    println!("\nNo debugging port: {}", hidden.join(", "));
+                    println!("Start them with --remote-debugging-port=9222 (VS Code: --inspect-extensions=9333) to see their heaps.");
+                }
+            }
         }
 
         Commands::Docker => {
@@ -442,44 +1624,662 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             manager.print_summary();
         }
 
-        Commands::Leaks { samples, interval } => {
-            println!("Monitoring for memory leaks...");
-            println!("Taking {} samples at {} second intervals\n", samples, interval);
+        Commands::Jvm { json } => {
+            let mut manager = JvmManager::new();
+            manager.refresh();
+            manager.read_heaps(jvm::HEAP_CHECK_MIN_MB);
+            if json {
+                let report = serde_json::json!({ "jvms": manager.get_jvms(), "advice": manager.advice() });
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                manager.print_summary();
+            }
+        }
+
+        Commands::Ai { action } => {
+            #[cfg(feature = "ai")]
+            {
+                let ai = AIMode::new(AIModeConfig::default());
+                match action {
+                    AiCommand::Status { json } => {
+                        let status = ai.status().await;
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&status)?);
+                        } else {
+                            status.print();
+                        }
+                    }
+                    AiCommand::Models { json } => match ai.ollama_models().await {
+                        Ok(models) if json => println!("{}", serde_json::to_string_pretty(&models)?),
+                        Ok(models) => ollama::print_models(&models),
+                        Err(e) => println!("Ollama is not reachable: {}", e),
+                    },
+                    AiCommand::Unload { models, all } => {
+                        let names = if all { None } else { Some(models.as_slice()) };
+                        match ai.unload(names).await {
+                            Ok(unloaded) if unloaded.is_empty() => println!("No models were loaded."),
+                            Ok(unloaded) => println!("Unloaded {}", unloaded.join(", ")),
+                            Err(e) => println!("Not unloaded: {}", e),
+                        }
+                    }
+                    AiCommand::Optimize => println!("AI optimization: {}", ai.optimize().await),
+                    AiCommand::Cuda { lower_priority, json } => {
+                        let processes = ai.cuda_workloads();
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&processes)?);
+                        } else {
+                            cuda::print(&processes);
+                        }
+                        if lower_priority {
+                            match cuda::lower_training_priority(&processes) {
+                                Ok(lowered) if lowered.is_empty() => println!("No training process to lower."),
+                                Ok(lowered) => {
+                                    println!("Lowered priority of {} training process(es); `undo` restores it", lowered.len())
+                                }
+                                Err(e) => println!("Priority not changed: {}", e),
+                            }
+                        }
+                    }
+                }
+            }
+            #[cfg(not(feature = "ai"))]
+            {
+                let _ = action;
+                println!("This build has no AI Mode. Rebuild with --features ai.");
+            }
+        }
+
+        Commands::Gpu { action } => {
+            let monitor = GpuMonitor::default();
+            match action {
+                GpuCommand::Status => monitor.print_status(),
+                GpuCommand::Top { limit } => monitor.print_top(limit),
+                GpuCommand::Watch { interval } => loop {
+                    print!("\x1B[2J\x1B[1;1H");
+                    monitor.print_status();
+                    tokio::time::sleep(Duration::from_secs(interval)).await;
+                },
+            }
+        }
+
+        Commands::Vms { balloon } => {
+            let mut manager = VmManager::new();
+            manager.refresh();
+            match balloon {
+                Some(name) => {
+                    let result = manager.balloon(&name);
+                    println!("{}: {}", result.app_name, result.message);
+                }
+                None => manager.print_summary(),
+            }
+        }
+
+        Commands::Prefetch { action } => {
+            let mut prefetcher = Prefetcher::load();
+            match action {
+                PrefetchCommand::Status => prefetcher.print_summary(),
+                PrefetchCommand::Watch => prefetcher.watch_and_print(),
+            }
+        }
+
+        Commands::Build { action } => match action {
+            BuildCommand::Watch { no_boost } => {
+                let mut watcher = BuildWatcher::new(!no_boost);
+                let mode = if no_boost { "measuring only" } else { "boosting" };
+                println!("Watching for builds, {} (Ctrl+C to stop)...\n", mode);
+                loop {
+                    tokio::select! {
+                        _ = tokio::signal::ctrl_c() => break,
+                        _ = tokio::time::sleep(Duration::from_secs(1)) => watcher.tick(),
+                    }
+                }
+                watcher.restore();
+            }
+            BuildCommand::History { limit } => match BuildHistory::open_default() {
+                Ok(history) => history.print_summary(limit),
+                Err(e) => println!("Failed to open build history: {}", e),
+            },
+        },
+
+        Commands::Games { action } => {
+            let mut detector = GameDetector::open_default();
+            let result = match action {
+                GamesCommand::Detect => {
+                    let candidates = detector.candidates();
+                    if candidates.is_empty() {
+                        println!("Nothing running looks like a game");
+                    }
+                    for candidate in candidates {
+                        let mark = if candidate.is_game() { "🎮" } else { "  " };
+                        println!("{} {}", mark, candidate);
+                    }
+                    Ok(())
+                }
+                GamesCommand::List => {
+                    let taught = detector.taught();
+                    println!("Games:     {}", taught.games.iter().cloned().collect::<Vec<_>>().join(", "));
+                    println!("Not games: {}", taught.not_games.iter().cloned().collect::<Vec<_>>().join(", "));
+                    Ok(())
+                }
+                GamesCommand::Teach { name } => detector.teach(&name).map(|()| println!("✓ {} is a game", name)),
+                GamesCommand::Ignore { name } => detector.ignore(&name).map(|()| println!("✓ {} is never a game", name)),
+                GamesCommand::Forget { name } => detector.forget(&name).map(|found| {
+                    if found {
+                        println!("✓ Forgot {}", name);
+                    } else {
+                        println!("{} was not taught", name);
+                    }
+                }),
+            };
+            if let Err(e) = result {
+                println!("{}", e);
+            }
+        }
+        Commands::Thermal { watch } => {
+            if watch {
+                thermal::watch_and_print(thermal::SAMPLE_INTERVAL);
+            } else {
+                println!("🌡️  Thermal Status\n");
+                ThermalMonitor::new().sample().print();
+                println!("\n  Run with --watch to forecast throttling from the trend.");
+            }
+        }
+
+        Commands::Bloatware { all, remove, confirm } => {
+            let scanner = BloatwareScanner::new();
+            match remove {
+                None => scanner.print_table(all),
+                Some(app) if !confirm => match scanner.find(&app) {
+                    Ok(found) => {
+                        println!("Would remove {} with:\n  {}", found.app.name, found.app.uninstall);
+                        println!("\nNothing was changed. Re-run with --confirm to uninstall.");
+                    }
+                    Err(e) => println!("{}", e),
+                },
+                Some(app) => match scanner.remove(&app) {
+                    Ok(removed) => println!("✓ Removed {}", removed.app.name),
+                    Err(e) => println!("Failed to remove {}: {}", app, e),
+                },
+            }
+        }
+
+        Commands::Health => {
+            println!("🩺 System Health\n");
+            HealthScorer::new().compute().print();
+        }
+
+        Commands::Profile { action } => match ProfileManager::open_default() {
+            Ok(mut manager) => match action {
+                ProfileCommand::List => manager.print_table(),
+                ProfileCommand::Show { name } => {
+                    if let Err(e) = manager.print_profile(&name) {
+                        println!("{}", e);
+                    }
+                }
+                ProfileCommand::Apply { name } => match manager.apply(&name) {
+                    Ok(()) => println!("✓ Profile '{}' applied", name.to_lowercase()),
+                    Err(e) => println!("Failed to apply profile: {}", e),
+                },
+                ProfileCommand::Off => match manager.off() {
+                    Ok(()) => println!("✓ Profile off, previous settings restored"),
+                    Err(e) => println!("Failed to restore settings: {}", e),
+                },
+                ProfileCommand::Auto { watch } => {
+                    if watch {
+                        println!("Watching profile activation rules (Ctrl+C to stop)...\n");
+                    }
+                    loop {
+                        match manager.auto_switch() {
+                            Ok(Some(change)) => {
+                                let (title, msg) = change.notification();
+                                println!("{} - {}", title, msg);
+                            }
+                            Ok(None) if !watch => println!("No switch needed (active: {})", manager.active().unwrap_or("none")),
+                            Ok(None) => {}
+                            Err(e) => println!("Profile switch failed: {}", e),
+                        }
+                        if !watch {
+                            break;
+                        }
+                        tokio::select! {
+                            _ = tokio::signal::ctrl_c() => break,
+                            _ = tokio::time::sleep(profiles::AUTO_SWITCH_INTERVAL) => {}
+                        }
+                    }
+                }
+                ProfileCommand::Init => {
+                    let path = manager.store().path().clone();
+                    if path.exists() {
+                        println!("{:?} already exists", path);
+                    } else {
+                        match manager.store().save(&profiles::builtin_profiles()) {
+                            Ok(()) => println!("✓ Wrote built-in profiles to {:?}", path),
+                            Err(e) => println!("Failed to write profiles: {}", e),
+                        }
+                    }
+                }
+            },
+            Err(e) => println!("Failed to open profiles: {}", e),
+        },
+
+        Commands::Agent { action } => match action {
+            AgentCommand::List => match Agent::open_default() {
+                Ok(agent) => agent.print_rules(),
+                Err(e) => println!("Failed to load rules: {}", e),
+            },
+            AgentCommand::Run { dry_run, watch } => match Agent::open_default() {
+                Ok(agent) if agent.engine().is_empty() => agent.print_rules(),
+                Ok(mut agent) => {
+                    if watch {
+                        println!("Watching {} rules (Ctrl+C to stop)...\n", agent.engine().len());
+                    } else {
+                        // Sustained-memory and app-launch triggers need more than one sample
+                        println!("Evaluating {} rules once; use --watch for time-based triggers\n", agent.engine().len());
+                    }
+                    let mut fired = false;
+                    loop {
+                        for firing in agent.tick() {
+                            agent::run_and_print(&firing, dry_run);
+                            fired = true;
+                        }
+                        if !watch {
+                            break;
+                        }
+                        tokio::select! {
+                            _ = tokio::signal::ctrl_c() => break,
+                            _ = tokio::time::sleep(agent::AGENT_INTERVAL) => {}
+                        }
+                    }
+                    if !fired && !watch {
+                        println!("No rule matched.");
+                    }
+                }
+                Err(e) => println!("Failed to load rules: {}", e),
+            },
+            AgentCommand::Test { hours } => match agent::replay_recent(hours) {
+                Ok(report) => report.print(),
+                Err(e) => println!("Failed to replay rules: {}", e),
+            },
+            AgentCommand::Init => match RuleStore::open_default() {
+                Ok(store) if store.path().exists() => println!("{:?} already exists", store.path()),
+                Ok(store) => match store.save(&agent::example_rules()) {
+                    Ok(()) => println!("✓ Wrote example rules to {:?}", store.path()),
+                    Err(e) => println!("Failed to write rules: {}", e),
+                },
+                Err(e) => println!("Failed to open rules: {}", e),
+            },
+        },
+
+        Commands::Plugins { action } => match action {
+            PluginsCommand::List => {
+                let host = PluginHost::load_default();
+                let plugins = host.list();
+                if plugins.is_empty() {
+                    println!("No plugins in {:?}", host.dir());
+                } else {
+                    println!("Plugins in {:?}:\n", host.dir());
+                    for plugin in &plugins {
+                        plugin.print();
+                    }
+                }
+            }
+            PluginsCommand::Enable { id } => match PluginHost::open_default() {
+                Ok(mut host) => match host.enable(&id) {
+                    Ok(info) => println!("✓ Enabled {}", info.title()),
+                    Err(e) => println!("Failed to enable plugin: {}", e),
+                },
+                Err(e) => println!("Failed to open plugins: {}", e),
+            },
+            PluginsCommand::Disable { id } => match PluginHost::open_default() {
+                Ok(mut host) => match host.disable(&id) {
+                    Ok(()) => println!("✓ Disabled {}", id),
+                    Err(e) => println!("Failed to disable plugin: {}", e),
+                },
+                Err(e) => println!("Failed to open plugins: {}", e),
+            },
+            PluginsCommand::Run { id, command, args } => {
+                let mut host = PluginHost::load_default();
+                match host.run_command(&id, &command, &args) {
+                    Ok(output) => println!("{}", output),
+                    Err(e) => println!("Plugin command failed: {}", e),
+                }
+            }
+            PluginsCommand::Recommend { apply } => {
+                let mut host = PluginHost::load_default();
+                let recommendations = host.recommend(&HeuristicInput::collect());
+                if recommendations.is_empty() {
+                    println!("No recommendations.");
+                }
+                for (id, recommendation) in &recommendations {
+                    println!("  [{}] {}", id, recommendation.describe());
+                    if apply {
+                        match recommendation.apply() {
+                            Ok(result) => println!("    ✓ {}", result),
+                            Err(e) => println!("    ✗ {}", e),
+                        }
+                    }
+                }
+            }
+        },
+
+        Commands::Leaks { history: true, json, .. } => {
+            match SuspectLog::open_default().and_then(|log| log.since(0)) {
+                Ok(suspects) if json => println!("{}", serde_json::to_string_pretty(&suspects)?),
+                Ok(suspects) => leakwatch::print_suspects(&suspects),
+                Err(e) => println!("Failed to read leak suspects: {}", e),
+            }
+        }
+
+        Commands::Leaks { samples, interval, json, .. } => {
+            if !json {
+                println!("Monitoring for memory leaks...");
+                println!("Taking {} samples at {} second intervals\n", samples, interval);
+            }
 
             let mut detector = LeakDetector::new();
             detector.set_sample_interval(interval);
 
             for i in 0..samples {
                 detector.sample();
-                print!("\rSampling... {}/{}", i + 1, samples);
-                std::io::Write::flush(&mut std::io::stdout()).ok();
+                if !json {
+                    print!("\rSampling... {}/{}", i + 1, samples);
+                    std::io::Write::flush(&mut std::io::stdout()).ok();
+                }
 
                 if i < samples - 1 {
                     tokio::time::sleep(Duration::from_secs(interval)).await;
                 }
             }
-            println!();
 
-            detector.print_summary();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&detector.reports())?);
+            } else {
+                println!();
+                detector.print_summary();
+            }
+        }
+
+        Commands::Suggest { apply: None, json, .. } => {
+            if !json {
+                println!("Generating smart optimization suggestions...\n");
+            }
+            let mut engine = SmartSuggestions::new();
+            engine.refresh();
+            if json {
+                println!("{}", serde_json::to_string_pretty(engine.get_suggestions())?);
+            } else {
+                engine.print_summary();
+            }
         }
 
-        Commands::Suggest => {
-            println!("Generating smart optimization suggestions...\n");
+        Commands::Suggest { apply: Some(id), confirm, .. } => {
             let mut engine = SmartSuggestions::new();
             engine.refresh();
-            engine.print_summary();
+            match engine.get(&id).cloned() {
+                None => println!("No suggestion '{}'; run `suggest` to list current ids.", id),
+                Some(suggestion) if suggestion.actionable && suggestion.risk > SuggestionRisk::Low && !confirm => {
+                    println!("Would apply '{}' ({} risk):\n  {}", suggestion.title, suggestion.risk, suggestion.description);
+                    println!("\nNothing was changed. Re-run with --confirm to apply it.");
+                }
+                Some(suggestion) => match engine.apply(&id) {
+                    Ok(outcome) => println!("✓ {}: {}", suggestion.title, outcome.message),
+                    Err(e) => println!("Failed to apply '{}': {}", suggestion.title, e),
+                },
+            }
+        }
+
+        Commands::History { hours, trigger, limit } => {
+            let trigger = match trigger {
+                Some(t) => Some(t.parse::<OptimizationTrigger>()?),
+                None => None,
+            };
+            let range = if hours == 0 {
+                TimeRange::all()
+            } else {
+                TimeRange::last(Duration::from_secs(hours * 3600))
+            };
+            let filter = HistoryFilter {
+                trigger,
+                limit: Some(limit),
+                ..Default::default()
+            };
+
+            let store = HistoryStore::open_default()?;
+            let records = store.query(range, &filter)?;
+            HistoryStore::print_records(&records);
+        }
+
+    Commands::Undo { show } => {
+        let window = undo::window(&OptimizerConfig::load_or_default());
+        if show {
+            match undo::last_undoable(window) {
+                Some(entry) => entry.print(),
+                None => println!("Nothing to undo from the last {} minutes.", window.as_secs() / 60),
+            }
+        } else {
+            match undo::undo_last(window) {
+                Ok(report) => report.print(),
+                Err(e) => println!("{}", e),
+            }
         }
     }
 
-    Ok(())
-}
+        Commands::Audit { action } => match action {
+            AuditCommand::Tail { lines } => {
+                let log = AuditLog::open_default()?;
+                AuditLog::print_tail(&log.tail(lines)?);
+            }
+        },
+
+        Commands::Watch { name, category, interval } => {
+            let target = WatchTarget::from_args(name, category.as_deref())?;
+            watch::run(target, Duration::from_secs(interval.max(1)))?;
+        }
+
+        Commands::Snapshot { action } => match action {
+            SnapshotCommand::Take { output } => {
+                let taken = Snapshot::take()?;
+                let path = taken.save(output.as_deref())?;
+                println!("Saved {} processes to {}", taken.processes.len(), path.display());
+            }
+            SnapshotCommand::List => snapshot::print_list(&snapshot::list()?),
+            SnapshotCommand::Diff { from, to, limit, json } => {
+                let before = Snapshot::load(&snapshot::resolve(&from)?)?;
+                let after = match to {
+                    Some(to) => Snapshot::load(&snapshot::resolve(&to)?)?,
+                    None => Snapshot::take()?,
+                };
+                let diff = SnapshotDiff::between(&before, &after);
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&diff)?);
+                } else {
+                    diff.print(limit);
+                }
+            }
+        },
+
+        Commands::Timeline { at, minutes, json } => match timeline::query(at.as_deref(), minutes) {
+            Ok(view) if json => println!("{}", serde_json::to_string_pretty(&view)?),
+            Ok(view) => view.print(),
+            Err(e) => println!("Failed to read timeline: {}", e),
+        },
+
+        Commands::Forecast { json } => {
+            let config = OptimizerConfig::load_or_default();
+            match timeline::forecast(config.critical_threshold, &HORIZONS) {
+                Ok(view) if json => println!("{}", serde_json::to_string_pretty(&view)?),
+                Ok(view) => view.print(),
+                Err(e) => println!("Failed to read timeline: {}", e),
+            }
+        }
+
+        Commands::Cleanup { dry_run, target, exclude, json } => {
+            let mut options = CleanupOptions {
+                targets: target.iter().map(|t| t.parse()).collect::<Result<_, String>>()?,
+                exclude: OptimizerConfig::load_or_default().cleanup_exclude,
+                dry_run,
+            };
+            options.exclude.extend(exclude);
+            let report = cleanup::clean(&options);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                report.print();
+            }
+        }
+
+        Commands::Disk { json } => {
+            let report = diskspace::scan();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                report.print();
+            }
+        }
+
+        Commands::Report { action: ReportCommand::Generate { period, format, output } } => {
+            let format = match format {
+                Some(format) => format.parse()?,
+                None => OptimizerConfig::load_or_default().report_format,
+            };
+            let path = report::generate(period.parse()?, format, output.as_deref())?;
+            println!("Wrote {}", path.display());
+        }
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max - 3])
+        Commands::Alerts { action: AlertsCommand::Test } => {
+            let config = OptimizerConfig::load_or_default();
+            if config.alerts.channels.is_empty() {
+                println!("No alert channels; add [[alerts.channels]] to config.toml");
+            }
+            for (channel, result) in monitor::alerts::send_test(&config.alerts) {
+                match result {
+                    Ok(()) => println!("✓ {}", channel),
+                    Err(e) => println!("✗ {}: {}", channel, e),
+                }
+            }
+        }
+
+        Commands::Schedule { action } => {
+            let store = ScheduleStore::open_default()?;
+            match action {
+                ScheduleCommand::List => {
+                    Scheduler::print_schedules(&store.load()?);
+                }
+                ScheduleCommand::Add { action, at, every, days, hours } => {
+                    let schedule = Schedule::from_args(
+                        &action, at.as_deref(), every, &days, hours.as_deref())?;
+                    let description = schedule.describe();
+                    let id = store.add(schedule)?;
+                    println!("Added schedule #{}: {}", id, description);
+                }
+                ScheduleCommand::Remove { id } => {
+                    store.remove(id)?;
+                    println!("Removed schedule #{}", id);
+                }
+            }
+        }
+
+        Commands::Policy { action } => {
+            let store = PolicyStore::open_default()?;
+            match action {
+                PolicyCommand::List => {
+                    PolicyStore::print_rules(&store.load()?);
+                }
+                PolicyCommand::Add { effect, pattern, kind } => {
+                    let rule = PolicyRule::new(effect.parse()?, kind.parse()?, pattern);
+                    let description = rule.describe();
+                    let id = store.add(rule)?;
+                    println!("Added policy rule #{}: {}", id, description);
+                }
+                PolicyCommand::Remove { id } => {
+                    store.remove(id)?;
+                    println!("Removed policy rule #{}", id);
+                }
+            }
+        }
+
+        Commands::Wsl { action } => match action {
+            WslCommand::Status => match WslSnapshot::capture() {
+                Some(snapshot) => snapshot.print(),
+                None => println!("WSL2 is not running"),
+            },
+            WslCommand::Reclaim { shutdown } => {
+                let method = if shutdown {
+                    Some(WslReclaimMethod::Shutdown)
+                } else {
+                    WslSnapshot::capture().and_then(|s| s.recommended_method())
+                };
+                match method {
+                    Some(method) => {
+                        let result = wsl2::reclaim(method)?;
+                        println!("Reclaimed {:.0} MB via {}", result.freed_mb(), result.method);
+                    }
+                    None => println!("Nothing worth reclaiming"),
+                }
+            }
+            WslCommand::Config { write } => {
+                let guest = WslSnapshot::capture().and_then(|s| s.guest);
+                let advice = WslConfigAdvice::recommend(guest.as_ref());
+                advice.print();
+                if write {
+                    advice.write()?;
+                    println!("\nWrote {} (run 'wsl --shutdown' to apply)", advice.path.display());
+                }
+            }
+        },
+
+        Commands::Pagefile { apply } => {
+            let status = PagefileStatus::query()?;
+            status.print();
+            let advice = PagefileAdvice::recommend(&status);
+            advice.print();
+            if apply {
+                match advice.apply() {
+                    Ok(()) => println!("\nPagefile size set. Restart Windows to apply it."),
+                    Err(e) => println!("\nFailed to set the pagefile size: {}", e),
+                }
+            }
+        }
+
+        Commands::Compression { action } => match action {
+            CompressionCommand::Status => MemoryCompression::query()?.print(),
+            CompressionCommand::Combining { off } => match performance::set_page_combining(!off) {
+                Ok(message) => println!("{}", message),
+                Err(e) => println!("Failed to change page combining: {}", e),
+            },
+            CompressionCommand::Compress { off } => match performance::set_memory_compression(!off) {
+                Ok(message) => println!("{}", message),
+                Err(e) => println!("Failed to change memory compression: {}", e),
+            },
+        },
+
+        Commands::Neural { action } => {
+            let config = OptimizerConfig::load_or_default();
+            match action {
+                NeuralCommand::Status => {
+                    println!("🧠 Neural Model ({})\n", config.model_path.display());
+                    match Checkpoint::load(&config.model_path, config.ewc_lambda)? {
+                        Some(checkpoint) => checkpoint.print_summary(),
+                        None => println!("  No trained model yet"),
+                    }
+                }
+                NeuralCommand::Export { path } => {
+                    let checkpoint = Checkpoint::export(&config.model_path, &path, config.ewc_lambda)?;
+                    println!("Exported {} patterns to {}", checkpoint.history.len(), path.display());
+                }
+                NeuralCommand::Import { path } => {
+                    let checkpoint = Checkpoint::import(&path, &config.model_path, config.ewc_lambda)?;
+                    println!(
+                        "Imported {} patterns into {} (restart the daemon to use it)",
+                        checkpoint.history.len(),
+                        config.model_path.display()
+                    );
+                }
+            }
+        }
     }
+
+    Ok(())
 }
 
 // Stub main for non-Windows platforms
@@ -0,0 +1,308 @@
+//! Microphone and camera use, as the OS privacy indicators report it
+//!
+//! A call app being installed or running says little; holding the
+//! microphone or camera open does. Each platform reads the same state its
+//! own indicator shows:
+//! - Windows: the capability access `ConsentStore` in the registry, where an
+//!   app in use has `LastUsedTimeStop` = 0
+//! - macOS: CoreAudio's "running somewhere" flag on the default input device
+//!   (no app attribution, no camera)
+//! - Linux: uncorked PulseAudio/PipeWire source outputs (`pactl`) and
+//!   processes holding a `/dev/video*` device open
+
+/// A capture device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Microphone,
+    Camera,
+}
+
+impl std::fmt::Display for Device {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Device::Microphone => write!(f, "Microphone"),
+            Device::Camera => write!(f, "Camera"),
+        }
+    }
+}
+
+/// A device some app is capturing from right now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureUse {
+    pub device: Device,
+    /// The app using it, where the OS says
+    pub app: Option<String>,
+}
+
+impl std::fmt::Display for CaptureUse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.app {
+            Some(ref app) => write!(f, "{} in use by {}", self.device, app),
+            None => write!(f, "{} in use", self.device),
+        }
+    }
+}
+
+/// Microphones and cameras in use right now
+#[cfg(target_os = "windows")]
+pub fn in_use() -> Vec<CaptureUse> {
+    const CONSENT_STORE: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+    let mut uses = Vec::new();
+    for (device, store) in [(Device::Microphone, "microphone"), (Device::Camera, "webcam")] {
+        let key = format!(r"{}\{}", CONSENT_STORE, store);
+        if let Some(output) = run("reg", &["query", &key, "/s"]) {
+            uses.extend(parse_consent_store(&output, device));
+        }
+    }
+    uses
+}
+
+#[cfg(target_os = "macos")]
+pub fn in_use() -> Vec<CaptureUse> {
+    if coreaudio::default_input_running() {
+        vec![CaptureUse { device: Device::Microphone, app: None }]
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn in_use() -> Vec<CaptureUse> {
+    let mut uses = run("pactl", &["list", "source-outputs"])
+        .map(|output| parse_source_outputs(&output))
+        .unwrap_or_default();
+    uses.extend(video_device_users().into_iter().map(|app| CaptureUse { device: Device::Camera, app: Some(app) }));
+    uses
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn in_use() -> Vec<CaptureUse> {
+    Vec::new()
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Apps in `reg query <ConsentStore>\<device> /s` output that started
+/// capturing and have not stopped
+#[cfg(any(target_os = "windows", test))]
+fn parse_consent_store(output: &str, device: Device) -> Vec<CaptureUse> {
+    // One key per app, then its values:
+    // HKEY_CURRENT_USER\...\ConsentStore\microphone\NonPackaged\C:#Program Files#Zoom#bin#Zoom.exe
+    //     LastUsedTimeStart    REG_QWORD    0x1da0c5b2f3e4d10
+    //     LastUsedTimeStop    REG_QWORD    0x0
+    fn flush(key: &str, start: Option<u64>, stop: Option<u64>, device: Device, uses: &mut Vec<CaptureUse>) {
+        if start.unwrap_or(0) > 0 && stop == Some(0) {
+            let app = key.rsplit('\\').next().unwrap_or(key);
+            // Non-packaged apps are paths with '#' for '\', packaged ones
+            // are package family names (`Microsoft.WindowsCamera_8wekyb3d8bbwe`)
+            let app = match app.rsplit_once('#') {
+                Some((_, exe)) => exe,
+                None => app.split('_').next().unwrap_or(app),
+            };
+            uses.push(CaptureUse { device, app: Some(app.to_string()) });
+        }
+    }
+
+    let mut uses = Vec::new();
+    let (mut key, mut start, mut stop) = ("", None, None);
+    for line in output.lines().map(str::trim) {
+        if line.starts_with("HKEY_") {
+            flush(key, start, stop, device, &mut uses);
+            (key, start, stop) = (line, None, None);
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some("REG_QWORD"), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let value = u64::from_str_radix(value.trim_start_matches("0x"), 16).ok();
+        match name {
+            "LastUsedTimeStart" => start = value,
+            "LastUsedTimeStop" => stop = value,
+            _ => {}
+        }
+    }
+    flush(key, start, stop, device, &mut uses);
+    uses
+}
+
+/// Recording streams in `pactl list source-outputs` output that are not
+/// corked, skipping level meters
+#[cfg(any(target_os = "linux", test))]
+fn parse_source_outputs(output: &str) -> Vec<CaptureUse> {
+    let mut uses: Vec<CaptureUse> = Vec::new();
+    for stream in output.split("Source Output #").skip(1) {
+        let mut corked = false;
+        let mut meter = false;
+        let (mut binary, mut name) = (None, None);
+        for line in stream.lines().map(str::trim) {
+            if let Some(value) = line.strip_prefix("Corked:") {
+                corked = value.trim() == "yes";
+            } else if let Some((key, value)) = line.split_once(" = ") {
+                let value = value.trim_matches('"');
+                match key {
+                    "media.name" => meter = value.contains("Peak detect"),
+                    "application.process.binary" => binary = Some(value.to_string()),
+                    "application.name" => name = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+        if corked || meter {
+            continue;
+        }
+        let capture = CaptureUse { device: Device::Microphone, app: binary.or(name) };
+        if !uses.contains(&capture) {
+            uses.push(capture);
+        }
+    }
+    uses
+}
+
+/// Names of processes holding a V4L2 video device open
+#[cfg(target_os = "linux")]
+fn video_device_users() -> Vec<String> {
+    // PipeWire opens cameras on behalf of portal clients; those show up
+    // by their microphone stream instead
+    const SKIP: &[&str] = &["pipewire", "wireplumber"];
+
+    let mut users = Vec::new();
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return users;
+    };
+    for entry in procs.flatten() {
+        if entry.file_name().to_string_lossy().parse::<u32>().is_err() {
+            continue;
+        }
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let holds_camera = fds
+            .flatten()
+            .any(|fd| std::fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/video")));
+        if !holds_camera {
+            continue;
+        }
+        let Ok(comm) = std::fs::read_to_string(entry.path().join("comm")) else {
+            continue;
+        };
+        let comm = comm.trim().to_string();
+        if !SKIP.contains(&comm.as_str()) && !users.contains(&comm) {
+            users.push(comm);
+        }
+    }
+    users
+}
+
+#[cfg(target_os = "macos")]
+mod coreaudio {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct PropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectGetPropertyData(
+            object: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+    }
+
+    const SYSTEM_OBJECT: u32 = 1;
+    const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+    const ELEMENT_MAIN: u32 = 0;
+    const DEFAULT_INPUT_DEVICE: u32 = u32::from_be_bytes(*b"dIn ");
+    const IS_RUNNING_SOMEWHERE: u32 = u32::from_be_bytes(*b"gone");
+
+    /// Whether any process is capturing from the default input device
+    pub fn default_input_running() -> bool {
+        let Some(device) = property(SYSTEM_OBJECT, DEFAULT_INPUT_DEVICE).filter(|&d| d != 0) else {
+            return false;
+        };
+        property(device, IS_RUNNING_SOMEWHERE).is_some_and(|running| running != 0)
+    }
+
+    /// A 32-bit global property (device ids and flags are both `UInt32`)
+    fn property(object: u32, selector: u32) -> Option<u32> {
+        let address = PropertyAddress { selector, scope: SCOPE_GLOBAL, element: ELEMENT_MAIN };
+        let mut value = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                object,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut value as *mut u32 as *mut c_void,
+            )
+        };
+        (status == 0).then_some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_consent_store() {
+        let output = r"
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone
+    Value    REG_SZ    Allow
+
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone\Microsoft.SkypeApp_kzf8qxf38zg5c
+    Value    REG_SZ    Allow
+    LastUsedTimeStart    REG_QWORD    0x1d9e1c0a8b7c6d5
+    LastUsedTimeStop    REG_QWORD    0x1d9e1c2b3a4f5e6
+
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone\NonPackaged
+
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone\NonPackaged\C:#Program Files#Zoom#bin#Zoom.exe
+    LastUsedTimeStart    REG_QWORD    0x1da0c5b2f3e4d10
+    LastUsedTimeStop    REG_QWORD    0x0
+";
+        let uses = parse_consent_store(output, Device::Microphone);
+        assert_eq!(uses, vec![CaptureUse { device: Device::Microphone, app: Some("Zoom.exe".into()) }]);
+
+        let packaged = "HKEY_CURRENT_USER\\...\\webcam\\Microsoft.WindowsCamera_8wekyb3d8bbwe\n    \
+                        LastUsedTimeStart    REG_QWORD    0x1da0c5b2f3e4d10\n    LastUsedTimeStop    REG_QWORD    0x0\n";
+        let uses = parse_consent_store(packaged, Device::Camera);
+        assert_eq!(uses[0].to_string(), "Camera in use by Microsoft.WindowsCamera");
+    }
+
+    #[test]
+    fn test_parse_source_outputs() {
+        let output = "Source Output #42\n\tDriver: protocol-native.c\n\tCorked: no\n\tMute: no\n\tProperties:\n\
+                      \t\tmedia.name = \"RecordStream\"\n\t\tapplication.name = \"ZOOM VoiceEngine\"\n\
+                      \t\tapplication.process.binary = \"zoom\"\n\n\
+                      Source Output #43\n\tCorked: yes\n\tProperties:\n\t\tapplication.process.binary = \"firefox\"\n\n\
+                      Source Output #44\n\tCorked: no\n\tProperties:\n\t\tmedia.name = \"Peak detect\"\n\
+                      \t\tapplication.process.binary = \"pavucontrol\"\n";
+        let uses = parse_source_outputs(output);
+        assert_eq!(uses, vec![CaptureUse { device: Device::Microphone, app: Some("zoom".into()) }]);
+        assert!(parse_source_outputs("").is_empty());
+    }
+}
@@ -1,52 +1,534 @@
-//! Simple text-based dashboard
+//! Terminal dashboard
 //!
-//! Currently Windows-only, requires the windows module.
+//! `dashboard` draws a full-screen view that also works over SSH, where the
+//! web dashboard can't be reached: memory load with a sparkline of the last
+//! minutes, memory per app category, recent optimizations from the history
+//! and leak suspects from the background leak monitor. Keys, or a click on
+//! the buttons in the bottom line, run an optimization, an aggressive one,
+//! or list suggestions.
+//!
+//! Optimizations go through the daemon or service when one is running, so
+//! its safety checks and rate limit apply, and run in this process
+//! otherwise. Slow work runs on a worker thread so the view keeps drawing.
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use chrono::{Local, TimeZone};
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton,
+    MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Clear, Gauge, List, ListItem, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::apps::{self, AppCategory, SmartSuggestions};
+use crate::apps::suggestions::Suggestion;
+use crate::core::history::{self, HistoryFilter, HistoryRecord, HistoryStore, OptimizationTrigger, TimeRange};
+use crate::core::ipc::{IpcClient, OptimizeReport};
+use crate::features::leakwatch::{Suspect, SuspectLog};
+use crate::monitor::procs;
+use crate::platform::MemoryStatus;
+use crate::core::text::truncate;
+
+/// Time between memory samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time between reads of the process list, history and suspects
+const SLOW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Load samples kept for the sparkline (10 minutes)
+const LOAD_HISTORY: usize = 600;
+
+const RECENT_OPTIMIZATIONS: usize = 8;
+const TOP_SUGGESTIONS: usize = 8;
+const MAX_CATEGORIES: usize = 8;
+
+/// Something a key or button does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Optimize,
+    Aggressive,
+    Suggest,
+    Quit,
+}
+
+impl Action {
+    const ALL: [Action; 4] = [Action::Optimize, Action::Aggressive, Action::Suggest, Action::Quit];
+
+    fn key(self) -> char {
+        match self {
+            Action::Optimize => 'o',
+            Action::Aggressive => 'a',
+            Action::Suggest => 's',
+            Action::Quit => 'q',
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Action::Optimize => "Optimize",
+            Action::Aggressive => "Aggressive",
+            Action::Suggest => "Suggest",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// Where a footer button was drawn, for mouse clicks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Button {
+    pub action: Action,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+}
+
+/// The button under a click, if any
+pub fn button_at(buttons: &[Button], column: u16, row: u16) -> Option<Action> {
+    buttons
+        .iter()
+        .find(|b| b.y == row && (b.x..b.x + b.width).contains(&column))
+        .map(|b| b.action)
+}
+
+/// Resident memory per app category, largest first; `processes` are
+/// `(name, resident MB)`
+pub fn category_totals(processes: &[(String, f64)]) -> Vec<(AppCategory, f64)> {
+    let mut totals: Vec<(AppCategory, f64)> = Vec::new();
+    for (name, mb) in processes {
+        let category = apps::categorize(name);
+        match totals.iter_mut().find(|(c, _)| *c == category) {
+            Some((_, total)) => *total += mb,
+            None => totals.push((category, *mb)),
+        }
+    }
+    totals.sort_by(|a, b| b.1.total_cmp(&a.1));
+    totals
+}
+
+/// Finished work from the worker thread
+enum Done {
+    Optimized { aggressive: bool, result: Result<OptimizeReport, String> },
+    Suggestions(Vec<Suggestion>),
+}
+
+struct Dashboard {
+    status: Option<MemoryStatus>,
+    load: VecDeque<u64>,
+    categories: Vec<(AppCategory, f64)>,
+    recent: Vec<HistoryRecord>,
+    suspects: Vec<Suspect>,
+    /// Shown over the panels once listed, until Esc
+    suggestions: Option<Vec<Suggestion>>,
+    message: String,
+    busy: bool,
+    buttons: Vec<Button>,
+    done_tx: Sender<Done>,
+    done_rx: Receiver<Done>,
+}
+
+impl Dashboard {
+    fn new() -> Self {
+        let (done_tx, done_rx) = mpsc::channel();
+        Self {
+            status: None,
+            load: VecDeque::with_capacity(LOAD_HISTORY),
+            categories: Vec::new(),
+            recent: Vec::new(),
+            suspects: Vec::new(),
+            suggestions: None,
+            message: String::new(),
+            busy: false,
+            buttons: Vec::new(),
+            done_tx,
+            done_rx,
+        }
+    }
+
+    fn sample(&mut self) {
+        self.status = crate::platform::create_optimizer().get_memory_status().ok();
+        if let Some(ref status) = self.status {
+            if self.load.len() == LOAD_HISTORY {
+                self.load.pop_front();
+            }
+            self.load.push_back(status.memory_load_percent as u64);
+        }
+    }
 
-#![cfg(target_os = "windows")]
+    fn sample_slow(&mut self) {
+        let processes: Vec<(String, f64)> = procs::with_processes(SLOW_INTERVAL, |system| {
+            system
+                .processes()
+                .values()
+                .filter(|p| p.thread_kind().is_none())
+                .map(|p| (p.name().to_string_lossy().to_string(), p.memory() as f64 / 1024.0 / 1024.0))
+                .collect()
+        });
+        self.categories = category_totals(&processes);
 
-use crate::windows::memory::MemoryStatus;
-use crate::bench::metrics::MetricsSummary;
+        let filter = HistoryFilter { limit: Some(RECENT_OPTIMIZATIONS), ..Default::default() };
+        let day = TimeRange::last(Duration::from_secs(24 * 3600));
+        if let Ok(mut recent) = HistoryStore::open_default().and_then(|s| s.query(day, &filter)) {
+            recent.reverse();
+            self.recent = recent;
+        }
+        if let Ok(suspects) = SuspectLog::open_default().and_then(|log| log.active()) {
+            self.suspects = suspects;
+        }
+    }
 
-pub fn render_dashboard(status: &MemoryStatus, metrics: &MetricsSummary) -> String {
-    let bar_width = 40;
-    let filled = (status.memory_load_percent as usize * bar_width) / 100;
-    let bar: String = format!(
-        "[{}{}]",
-        "#".repeat(filled),
-        "-".repeat(bar_width - filled)
+    /// Start an action; returns false to quit
+    fn act(&mut self, action: Action) -> bool {
+        if action == Action::Quit {
+            return false;
+        }
+        if self.busy {
+            self.message = "Still working on the last request".to_string();
+            return true;
+        }
+        self.busy = true;
+        let done = self.done_tx.clone();
+        match action {
+            Action::Optimize | Action::Aggressive => {
+                let aggressive = action == Action::Aggressive;
+                self.message = if aggressive { "Optimizing (aggressive)..." } else { "Optimizing..." }.to_string();
+                std::thread::spawn(move || {
+                    let _ = done.send(Done::Optimized { aggressive, result: optimize(aggressive) });
+                });
+            }
+            Action::Suggest => {
+                self.message = "Looking for suggestions...".to_string();
+                std::thread::spawn(move || {
+                    let mut engine = SmartSuggestions::new();
+                    engine.refresh();
+                    let top = engine.get_top(TOP_SUGGESTIONS).into_iter().cloned().collect();
+                    let _ = done.send(Done::Suggestions(top));
+                });
+            }
+            Action::Quit => unreachable!(),
+        }
+        true
+    }
+
+    fn finish(&mut self, done: Done) {
+        self.busy = false;
+        match done {
+            Done::Optimized { aggressive, result: Ok(report) } => {
+                let mode = if aggressive { "Aggressive optimization" } else { "Optimization" };
+                self.message =
+                    format!("{} freed {:.0} MB from {} processes", mode, report.freed_mb, report.processes);
+                // Show it in the recent panel right away
+                self.sample_slow();
+            }
+            Done::Optimized { result: Err(e), .. } => self.message = format!("Not optimized: {}", e),
+            Done::Suggestions(suggestions) => {
+                self.message = format!("{} suggestions; apply one with `suggest --apply <id>`", suggestions.len());
+                self.suggestions = Some(suggestions);
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, chart, panels, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(7),
+            Constraint::Min(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(panels);
+        let [recent, suspects] = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(right);
+
+        self.draw_gauge(frame, header);
+        let load: Vec<u64> = self.load.iter().copied().collect();
+        let visible = &load[load.len().saturating_sub(chart.width.saturating_sub(2) as usize)..];
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(" Memory load, last 10 minutes "))
+                .data(visible)
+                .max(100)
+                .style(Style::default().fg(Color::Cyan)),
+            chart,
+        );
+        self.draw_categories(frame, left);
+        self.draw_recent(frame, recent);
+        self.draw_suspects(frame, suspects);
+        self.draw_footer(frame, footer);
+
+        if let Some(ref suggestions) = self.suggestions {
+            draw_suggestions(frame, panels, suggestions);
+        }
+    }
+
+    fn draw_gauge(&self, frame: &mut Frame, area: Rect) {
+        let title = format!(" RuVector MemOpt · {} ", Local::now().format("%H:%M:%S"));
+        let block = Block::bordered().title(title);
+        let Some(ref status) = self.status else {
+            frame.render_widget(Paragraph::new("Memory status unavailable").block(block), area);
+            return;
+        };
+        let color = if status.is_critical() {
+            Color::Red
+        } else if status.is_high_pressure() {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        let label = format!(
+            "{}% · {:.1} of {:.1} GB used · {:.1} GB available",
+            status.memory_load_percent,
+            status.used_physical_mb() / 1024.0,
+            status.total_physical_mb / 1024.0,
+            status.available_physical_mb / 1024.0
+        );
+        frame.render_widget(
+            Gauge::default()
+                .block(block)
+                .gauge_style(Style::default().fg(color))
+                .ratio((status.memory_load_percent as f64 / 100.0).clamp(0.0, 1.0))
+                .label(label),
+            area,
+        );
+    }
+
+    fn draw_categories(&self, frame: &mut Frame, area: Rect) {
+        let bars: Vec<Bar> = self
+            .categories
+            .iter()
+            .take(MAX_CATEGORIES)
+            .map(|(category, mb)| {
+                Bar::default()
+                    .label(Line::from(format!("{:?}", category)))
+                    .value(*mb as u64)
+                    .text_value(format!("{:.0} MB", mb))
+            })
+            .collect();
+        frame.render_widget(
+            BarChart::default()
+                .block(Block::bordered().title(" Memory by app category "))
+                .direction(Direction::Horizontal)
+                .bar_width(1)
+                .bar_gap(0)
+                .bar_style(Style::default().fg(Color::Blue))
+                .data(BarGroup::default().bars(&bars)),
+            area,
+        );
+    }
+
+    fn draw_recent(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .recent
+            .iter()
+            .map(|r| {
+                ListItem::new(format!(
+                    "{}  {:9} {:10} {:>6.0} MB  {} procs",
+                    format_time(r.timestamp_ms),
+                    r.trigger.to_string(),
+                    r.mode.to_string(),
+                    r.freed_mb,
+                    r.processes_trimmed
+                ))
+            })
+            .collect();
+        let list = if items.is_empty() { vec![ListItem::new("None in the last 24 hours")] } else { items };
+        frame.render_widget(List::new(list).block(Block::bordered().title(" Recent optimizations ")), area);
+    }
+
+    fn draw_suspects(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .suspects
+            .iter()
+            .map(|s| {
+                ListItem::new(format!(
+                    "{:20} {:>6} {:>6.0} MB/h  now {:.0} MB",
+                    truncate(&s.process_name, 20),
+                    s.pid,
+                    s.growth_rate_mb_per_hour,
+                    s.current_memory_mb
+                ))
+                .style(Style::default().fg(Color::Yellow))
+            })
+            .collect();
+        let list = if items.is_empty() {
+            vec![ListItem::new("None (needs the tray or daemon running for a while)")]
+        } else {
+            items
+        };
+        frame.render_widget(List::new(list).block(Block::bordered().title(" Leak suspects ")), area);
+    }
+
+    fn draw_footer(&mut self, frame: &mut Frame, area: Rect) {
+        let mut spans = Vec::new();
+        let mut x = area.x;
+        self.buttons.clear();
+        for action in Action::ALL {
+            let text = format!(" [{}] {} ", action.key(), action.label());
+            let width = text.chars().count() as u16;
+            self.buttons.push(Button { action, x, y: area.y, width });
+            spans.push(Span::styled(text, Style::default().add_modifier(Modifier::REVERSED)));
+            spans.push(Span::raw(" "));
+            x += width + 1;
+        }
+        let busy = if self.busy { "⏳ " } else { "" };
+        spans.push(Span::styled(format!("{}{}", busy, self.message), Style::default().fg(Color::DarkGray)));
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
+
+fn draw_suggestions(frame: &mut Frame, area: Rect, suggestions: &[Suggestion]) {
+    let area = Rect {
+        x: area.x + 2,
+        y: area.y + 1,
+        width: area.width.saturating_sub(4),
+        height: area.height.saturating_sub(2),
+    };
+    let items: Vec<ListItem> = if suggestions.is_empty() {
+        vec![ListItem::new("Nothing to suggest right now")]
+    } else {
+        suggestions
+            .iter()
+            .map(|s| {
+                ListItem::new(format!(
+                    "{:>6.0} MB  {:6} {}  ({})",
+                    s.estimated_mb,
+                    s.risk.to_string(),
+                    s.title,
+                    s.id
+                ))
+            })
+            .collect()
+    };
+    frame.render_widget(Clear, area);
+    frame.render_widget(List::new(items).block(Block::bordered().title(" Suggestions · Esc to close ")), area);
+}
+
+/// Run the dashboard until `q`, Esc or Ctrl+C
+pub fn run() -> Result<(), String> {
+    let mut terminal = ratatui::try_init().map_err(|e| format!("Failed to set up the terminal: {}", e))?;
+    let _ = execute!(std::io::stdout(), EnableMouseCapture);
+    let result = event_loop(&mut terminal, Dashboard::new());
+    let _ = execute!(std::io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, mut dashboard: Dashboard) -> Result<(), String> {
+    let mut next_sample = Instant::now();
+    let mut next_slow = Instant::now();
+    loop {
+        let now = Instant::now();
+        if now >= next_sample {
+            dashboard.sample();
+            next_sample = now + SAMPLE_INTERVAL;
+        }
+        if now >= next_slow {
+            dashboard.sample_slow();
+            next_slow = now + SLOW_INTERVAL;
+        }
+        while let Ok(done) = dashboard.done_rx.try_recv() {
+            dashboard.finish(done);
+        }
+
+        terminal.draw(|frame| dashboard.draw(frame)).map_err(|e| format!("Failed to draw: {}", e))?;
+
+        // Wake up often enough to pick up finished work
+        let timeout = next_sample.saturating_duration_since(Instant::now()).min(Duration::from_millis(250));
+        if !event::poll(timeout).map_err(|e| format!("Failed to read input: {}", e))? {
+            continue;
+        }
+        let action = match event::read().map_err(|e| format!("Failed to read input: {}", e))? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Esc if dashboard.suggestions.is_some() => {
+                    dashboard.suggestions = None;
+                    None
+                }
+                KeyCode::Esc => Some(Action::Quit),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+                KeyCode::Char(c) => Action::ALL.into_iter().find(|a| a.key() == c),
+                _ => None,
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                button_at(&dashboard.buttons, mouse.column, mouse.row)
+            }
+            _ => None,
+        };
+        if let Some(action) = action {
+            if !dashboard.act(action) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn optimize(aggressive: bool) -> Result<OptimizeReport, String> {
+    if let Some(mut daemon) = IpcClient::connect() {
+        return daemon.optimize(aggressive);
+    }
+    let optimizer = crate::platform::create_optimizer();
+    let load = optimizer.get_memory_status().ok().map(|s| s.memory_load_percent);
+    let result = optimizer.optimize(aggressive)?;
+    let mut record = HistoryRecord::now(
+        OptimizationTrigger::Manual,
+        aggressive,
+        result.freed_mb,
+        result.processes_affected,
+        result.duration_ms,
     );
-    
-    format!(
-r#"
-================ RuVector MemOpt Dashboard ================
-
-Memory Usage: {} {}%
-Total:     {:>8.0} MB
-Available: {:>8.0} MB  
-Used:      {:>8.0} MB
-
--------------------- Optimization Stats --------------------
-Total Freed:    {:>8.1} MB
-Optimizations:  {:>8}
-Avg Freed:      {:>8.1} MB
-Avg Duration:   {:>8} ms
-Uptime:         {:>8} sec
-
-Status: {}
-============================================================
-"#,
-        bar,
-        status.memory_load_percent,
-        status.total_physical_mb,
-        status.available_physical_mb,
-        status.used_physical_mb(),
-        metrics.total_freed_mb,
-        metrics.total_optimizations,
-        metrics.avg_freed_mb,
-        metrics.avg_duration_ms,
-        metrics.uptime_secs,
-        if status.is_critical() { "CRITICAL" } 
-        else if status.is_high_pressure() { "HIGH PRESSURE" } 
-        else { "OK" }
-    )
+    if let Some(load) = load {
+        record = record.with_memory_load(load);
+    }
+    history::record(record);
+    Ok(OptimizeReport {
+        trigger: OptimizationTrigger::Manual,
+        aggressive,
+        freed_mb: result.freed_mb,
+        processes: result.processes_affected,
+        duration_ms: result.duration_ms,
+    })
+}
+
+fn format_time(ms: u64) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_else(|| "?".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_totals() {
+        let processes = vec![
+            ("chrome.exe".to_string(), 500.0),
+            ("msedge.exe".to_string(), 300.0),
+            ("cargo".to_string(), 1200.0),
+            ("notepad.exe".to_string(), 20.0),
+        ];
+        assert_eq!(
+            category_totals(&processes),
+            [(AppCategory::Development, 1200.0), (AppCategory::Browser, 800.0), (AppCategory::Other, 20.0)]
+        );
+    }
+
+    #[test]
+    fn test_button_at() {
+        let buttons = [
+            Button { action: Action::Optimize, x: 0, y: 20, width: 14 },
+            Button { action: Action::Quit, x: 15, y: 20, width: 10 },
+        ];
+        assert_eq!(button_at(&buttons, 0, 20), Some(Action::Optimize));
+        assert_eq!(button_at(&buttons, 13, 20), Some(Action::Optimize));
+        assert_eq!(button_at(&buttons, 14, 20), None, "the gap between buttons");
+        assert_eq!(button_at(&buttons, 20, 20), Some(Action::Quit));
+        assert_eq!(button_at(&buttons, 20, 19), None);
+    }
 }
@@ -0,0 +1,1345 @@
+//! GPU Monitoring and VRAM Management
+//!
+//! Provides GPU detection and VRAM monitoring for games, video editing and
+//! AI workloads. Backends, tried in order: NVML (`nvml` feature),
+//! `nvidia-smi`, unified memory (Apple silicon), DXGI (Windows, capacity
+//! only), DRM sysfs (Linux amdgpu).
+
+use serde::{Deserialize, Serialize};
+use crate::core::text::truncate_padded;
+
+/// GPU vendor types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GpuVendor {
+    Nvidia,
+    AMD,
+    Intel,
+    Apple,
+    Unknown,
+}
+
+/// GPU information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuInfo {
+    /// GPU index
+    pub index: u32,
+    /// GPU name
+    pub name: String,
+    /// Vendor
+    pub vendor: GpuVendor,
+    /// Total VRAM in MB
+    pub total_vram_mb: u64,
+    /// Driver version
+    pub driver_version: String,
+    /// Compute capability (NVIDIA)
+    pub compute_capability: Option<String>,
+}
+
+/// VRAM status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VramStatus {
+    /// GPU index
+    pub gpu_index: u32,
+    /// Total VRAM in bytes
+    pub total: u64,
+    /// Used VRAM in bytes
+    pub used: u64,
+    /// Free VRAM in bytes
+    pub free: u64,
+    /// GPU temperature in Celsius
+    pub temperature: Option<u32>,
+    /// GPU utilization percentage
+    pub utilization: Option<u32>,
+    /// Power usage in watts
+    pub power_watts: Option<u32>,
+}
+
+impl VramStatus {
+    /// Get usage percentage
+    pub fn usage_percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.used as f64 / self.total as f64) * 100.0
+    }
+
+    /// Get free percentage
+    pub fn free_percent(&self) -> f64 {
+        100.0 - self.usage_percent()
+    }
+
+    /// Get used in MB
+    pub fn used_mb(&self) -> u64 {
+        self.used / (1024 * 1024)
+    }
+
+    /// Get free in MB
+    pub fn free_mb(&self) -> u64 {
+        self.free / (1024 * 1024)
+    }
+
+    /// Get total in MB
+    pub fn total_mb(&self) -> u64 {
+        self.total / (1024 * 1024)
+    }
+}
+
+/// Memory shared by the CPU and GPU on Apple silicon
+///
+/// The GPU has no VRAM of its own: it wires system RAM, up to a working-set
+/// limit the kernel sets. Moving layers from the GPU to the CPU frees
+/// nothing, so the only relief is unloading or shrinking what is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UnifiedMemory {
+    pub total_mb: u64,
+    /// Wired pages, which include everything the GPU has mapped
+    pub wired_mb: u64,
+    /// System memory the GPU driver has in use
+    pub gpu_in_use_mb: u64,
+    /// System memory the GPU driver has allocated
+    pub gpu_alloc_mb: u64,
+    /// Most the GPU may wire at once (`iogpu.wired_limit_mb`)
+    pub gpu_limit_mb: u64,
+    /// Device utilization from the Metal performance counters
+    pub utilization: Option<u32>,
+}
+
+impl UnifiedMemory {
+    /// Share of the GPU limit in use or of RAM wired, whichever is higher
+    pub fn usage_percent(&self) -> f64 {
+        let percent = |used: u64, total: u64| if total == 0 { 0.0 } else { used as f64 / total as f64 * 100.0 };
+        percent(self.gpu_in_use_mb, self.gpu_limit_mb).max(percent(self.wired_mb, self.total_mb))
+    }
+
+    /// What the GPU can still take before hitting its limit or wiring all RAM
+    pub fn available_mb(&self) -> u64 {
+        self.gpu_limit_mb
+            .saturating_sub(self.gpu_in_use_mb)
+            .min(self.total_mb.saturating_sub(self.wired_mb))
+    }
+
+    /// The GPU limit as VRAM, for code that only knows about VRAM
+    pub fn vram_status(&self) -> VramStatus {
+        const MB: u64 = 1024 * 1024;
+        VramStatus {
+            gpu_index: 0,
+            total: self.gpu_limit_mb * MB,
+            used: self.gpu_in_use_mb * MB,
+            free: self.available_mb() * MB,
+            temperature: None,
+            utilization: self.utilization,
+            power_watts: None,
+        }
+    }
+}
+
+/// VRAM held by a single process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    /// GPU the memory lives on, when the backend can tell
+    pub gpu_index: Option<u32>,
+    pub vram_bytes: u64,
+}
+
+impl GpuProcess {
+    pub fn vram_mb(&self) -> u64 {
+        self.vram_bytes / (1024 * 1024)
+    }
+
+    /// Advice for freeing this process's VRAM
+    pub fn suggestion(&self) -> String {
+        let name = self.name.to_lowercase();
+        if ["ollama", "llama", "python", "vllm", "lmstudio", "comfyui"].iter().any(|p| name.contains(p)) {
+            "unload idle models or offload layers to CPU".to_string()
+        } else if ["chrome", "msedge", "firefox", "brave", "opera", "electron", "code", "discord", "slack"]
+            .iter()
+            .any(|p| name.contains(p))
+        {
+            "disable hardware acceleration or restart it".to_string()
+        } else {
+            "close it if it is not in use".to_string()
+        }
+    }
+}
+
+/// Where GPU data comes from, in order of preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GpuBackend {
+    /// NVIDIA Management Library (`nvml` feature)
+    Nvml,
+    /// `nvidia-smi` CSV queries
+    NvidiaSmi,
+    /// Apple silicon GPU sharing system RAM (`ioreg` and `vm_stat`)
+    Unified,
+    /// DXGI adapter enumeration (Windows, total VRAM only)
+    Dxgi,
+    /// DRM sysfs (`/sys/class/drm/card*/device`, Linux amdgpu)
+    Drm,
+    /// No GPU found
+    None,
+}
+
+impl std::fmt::Display for GpuBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GpuBackend::Nvml => write!(f, "NVML"),
+            GpuBackend::NvidiaSmi => write!(f, "nvidia-smi"),
+            GpuBackend::Unified => write!(f, "unified memory"),
+            GpuBackend::Dxgi => write!(f, "DXGI"),
+            GpuBackend::Drm => write!(f, "DRM sysfs"),
+            GpuBackend::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Processes holding at least this much VRAM get a suggestion in `gpu top`
+const TOP_SUGGESTION_MB: u64 = 512;
+
+/// Fields requested from `nvidia-smi --query-gpu`
+const NVIDIA_SMI_QUERY: &str =
+    "index,name,memory.total,memory.used,memory.free,temperature.gpu,utilization.gpu,power.draw,driver_version";
+
+/// GPU monitor for tracking VRAM and GPU metrics
+pub struct GpuMonitor {
+    gpus: Vec<GpuInfo>,
+    backend: GpuBackend,
+    /// sysfs `device` directory per GPU (DRM backend)
+    drm_devices: Vec<std::path::PathBuf>,
+    #[cfg(feature = "nvml")]
+    nvml: Option<nvml_wrapper::Nvml>,
+}
+
+impl GpuMonitor {
+    /// Create a new GPU monitor
+    pub fn new() -> Result<Self, String> {
+        // Try NVIDIA first via NVML
+        #[cfg(feature = "nvml")]
+        {
+            if let Ok(nvml) = nvml_wrapper::Nvml::init() {
+                let mut gpus = Vec::new();
+                if let Ok(count) = nvml.device_count() {
+                    for i in 0..count {
+                        if let Ok(device) = nvml.device_by_index(i) {
+                            let name = device.name().unwrap_or_default();
+                            let memory = device.memory_info().ok();
+                            let total_vram = memory.map(|m| m.total / (1024 * 1024)).unwrap_or(0);
+
+                            gpus.push(GpuInfo {
+                                index: i,
+                                name,
+                                vendor: GpuVendor::Nvidia,
+                                total_vram_mb: total_vram,
+                                driver_version: nvml.sys_driver_version().unwrap_or_default(),
+                                compute_capability: device.cuda_compute_capability()
+                                    .ok()
+                                    .map(|cc| format!("{}.{}", cc.major, cc.minor)),
+                            });
+                        }
+                    }
+                }
+
+                if !gpus.is_empty() {
+                    return Ok(Self {
+                        gpus,
+                        backend: GpuBackend::Nvml,
+                        drm_devices: Vec::new(),
+                        nvml: Some(nvml),
+                    });
+                }
+            }
+        }
+
+        // NVIDIA without NVML: the driver always ships nvidia-smi
+        if let Ok(rows) = Self::query_nvidia_smi() {
+            if !rows.is_empty() {
+                return Ok(Self::with_backend(
+                    rows.into_iter().map(|(info, _)| info).collect(),
+                    GpuBackend::NvidiaSmi,
+                    Vec::new(),
+                ));
+            }
+        }
+
+        if let Some(gpu) = Self::detect_unified() {
+            return Ok(Self::with_backend(vec![gpu], GpuBackend::Unified, Vec::new()));
+        }
+
+        // Fallback: Use DirectX/DXGI to detect GPUs
+        let gpus = Self::detect_via_dxgi()?;
+        if !gpus.is_empty() {
+            return Ok(Self::with_backend(gpus, GpuBackend::Dxgi, Vec::new()));
+        }
+
+        let (gpus, devices) = Self::detect_via_drm();
+        if !gpus.is_empty() {
+            return Ok(Self::with_backend(gpus, GpuBackend::Drm, devices));
+        }
+
+        Ok(Self::with_backend(Vec::new(), GpuBackend::None, Vec::new()))
+    }
+
+    fn with_backend(gpus: Vec<GpuInfo>, backend: GpuBackend, drm_devices: Vec<std::path::PathBuf>) -> Self {
+        Self {
+            gpus,
+            backend,
+            drm_devices,
+            #[cfg(feature = "nvml")]
+            nvml: None,
+        }
+    }
+
+    /// Run `nvidia-smi` and parse one row per GPU
+    fn query_nvidia_smi() -> Result<Vec<(GpuInfo, VramStatus)>, String> {
+        let output = std::process::Command::new("nvidia-smi")
+            .args([
+                &format!("--query-gpu={}", NVIDIA_SMI_QUERY),
+                "--format=csv,noheader,nounits",
+            ])
+            .output()
+            .map_err(|e| format!("nvidia-smi not available: {}", e))?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+
+        Ok(parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Detect the Apple silicon GPU, sized to its working-set limit
+    #[cfg(target_os = "macos")]
+    fn detect_unified() -> Option<GpuInfo> {
+        if !cfg!(target_arch = "aarch64") {
+            return None;
+        }
+        let total_mb = sysctl("hw.memsize")?.parse::<u64>().ok()? / (1024 * 1024);
+        let chip = sysctl("machdep.cpu.brand_string").unwrap_or_else(|| "Apple silicon".to_string());
+        Some(GpuInfo {
+            index: 0,
+            name: format!("{} GPU", chip),
+            vendor: GpuVendor::Apple,
+            total_vram_mb: gpu_wired_limit_mb(total_mb, configured_wired_limit_mb()),
+            driver_version: String::new(),
+            compute_capability: None,
+        })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn detect_unified() -> Option<GpuInfo> {
+        None
+    }
+
+    /// Detect GPUs via DXGI (Windows)
+    #[cfg(windows)]
+    fn detect_via_dxgi() -> Result<Vec<GpuInfo>, String> {
+        use windows::Win32::Graphics::Dxgi::{
+            CreateDXGIFactory1, IDXGIFactory1,
+        };
+
+        let mut gpus = Vec::new();
+
+        unsafe {
+            let factory: IDXGIFactory1 = CreateDXGIFactory1()
+                .map_err(|e| format!("Failed to create DXGI factory: {}", e))?;
+
+            let mut i = 0u32;
+            loop {
+                match factory.EnumAdapters1(i) {
+                    Ok(adapter) => {
+                        if let Ok(desc) = adapter.GetDesc1() {
+                            let name = String::from_utf16_lossy(
+                                &desc.Description[..desc.Description.iter()
+                                    .position(|&c| c == 0)
+                                    .unwrap_or(desc.Description.len())]
+                            );
+
+                            // Determine vendor
+                            let vendor = vendor_from_pci_id(desc.VendorId);
+
+                            // Skip software adapters
+                            if desc.Flags & 0x2 != 0 { // DXGI_ADAPTER_FLAG_SOFTWARE
+                                i += 1;
+                                continue;
+                            }
+
+                            gpus.push(GpuInfo {
+                                index: i,
+                                name,
+                                vendor,
+                                total_vram_mb: desc.DedicatedVideoMemory as u64 / (1024 * 1024),
+                                driver_version: String::new(),
+                                compute_capability: None,
+                            });
+                        }
+                        i += 1;
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+
+        Ok(gpus)
+    }
+
+    #[cfg(not(windows))]
+    fn detect_via_dxgi() -> Result<Vec<GpuInfo>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Detect GPUs that expose VRAM counters in DRM sysfs (Linux amdgpu)
+    fn detect_via_drm() -> (Vec<GpuInfo>, Vec<std::path::PathBuf>) {
+        let mut gpus = Vec::new();
+        let mut devices = Vec::new();
+
+        let Ok(entries) = std::fs::read_dir("/sys/class/drm") else {
+            return (gpus, devices);
+        };
+        let mut cards: Vec<_> = entries
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("card")
+                    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .collect();
+        cards.sort_by_key(|e| e.file_name());
+
+        for card in cards {
+            let device = card.path().join("device");
+            let Some(total) = read_sysfs_u64(&device.join("mem_info_vram_total")) else {
+                continue;
+            };
+            let vendor_id = std::fs::read_to_string(device.join("vendor"))
+                .ok()
+                .and_then(|v| u32::from_str_radix(v.trim().trim_start_matches("0x"), 16).ok())
+                .unwrap_or(0);
+            let vendor = vendor_from_pci_id(vendor_id);
+            let name = std::fs::read_to_string(device.join("product_name"))
+                .ok()
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| format!("{:?} GPU ({})", vendor, card.file_name().to_string_lossy()));
+
+            gpus.push(GpuInfo {
+                index: gpus.len() as u32,
+                name,
+                vendor,
+                total_vram_mb: total / (1024 * 1024),
+                driver_version: String::new(),
+                compute_capability: None,
+            });
+            devices.push(device);
+        }
+
+        (gpus, devices)
+    }
+
+    /// Get list of detected GPUs
+    pub fn gpus(&self) -> &[GpuInfo] {
+        &self.gpus
+    }
+
+    /// Backend used for detection and polling
+    pub fn backend(&self) -> GpuBackend {
+        self.backend
+    }
+
+    /// Get VRAM status for all GPUs
+    pub fn status(&self) -> Vec<VramStatus> {
+        let mut statuses = Vec::new();
+
+        #[cfg(feature = "nvml")]
+        if let Some(ref nvml) = self.nvml {
+            for gpu in &self.gpus {
+                if gpu.vendor == GpuVendor::Nvidia {
+                    if let Ok(device) = nvml.device_by_index(gpu.index) {
+                        let memory = device.memory_info().ok();
+                        let temp = device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu).ok();
+                        let util = device.utilization_rates().ok();
+                        let power = device.power_usage().ok();
+
+                        statuses.push(VramStatus {
+                            gpu_index: gpu.index,
+                            total: memory.map(|m| m.total).unwrap_or(0),
+                            used: memory.map(|m| m.used).unwrap_or(0),
+                            free: memory.map(|m| m.free).unwrap_or(0),
+                            temperature: temp,
+                            utilization: util.map(|u| u.gpu),
+                            power_watts: power.map(|p| p / 1000), // mW to W
+                        });
+                    }
+                }
+            }
+        }
+
+        match self.backend {
+            GpuBackend::NvidiaSmi => {
+                if let Ok(rows) = Self::query_nvidia_smi() {
+                    statuses.extend(rows.into_iter().map(|(_, status)| status));
+                }
+            }
+            GpuBackend::Drm => {
+                for (gpu, device) in self.gpus.iter().zip(&self.drm_devices) {
+                    statuses.push(read_drm_status(gpu, device));
+                }
+            }
+            GpuBackend::Unified => {
+                statuses.extend(self.unified_memory().map(|unified| unified.vram_status()));
+            }
+            _ => {}
+        }
+
+        // Fallback for DXGI-only systems: total is known, usage is not
+        if statuses.is_empty() {
+            for gpu in &self.gpus {
+                statuses.push(VramStatus {
+                    gpu_index: gpu.index,
+                    total: gpu.total_vram_mb * 1024 * 1024,
+                    used: 0, // Can't determine without vendor API
+                    free: gpu.total_vram_mb * 1024 * 1024,
+                    temperature: None,
+                    utilization: None,
+                    power_watts: None,
+                });
+            }
+        }
+
+        statuses
+    }
+
+    /// Get status for a specific GPU
+    pub fn status_for(&self, gpu_index: u32) -> Option<VramStatus> {
+        self.status().into_iter().find(|s| s.gpu_index == gpu_index)
+    }
+
+    /// Check if any GPU is under memory pressure
+    ///
+    /// With unified memory this also counts RAM wired by everything else,
+    /// since the GPU cannot have it.
+    pub fn is_under_pressure(&self, threshold_percent: f64) -> bool {
+        if let Some(unified) = self.unified_memory() {
+            return unified.usage_percent() > threshold_percent;
+        }
+        self.status().iter().any(|s| s.usage_percent() > threshold_percent)
+    }
+
+    /// Wired and GPU-mapped memory, when the GPU shares system RAM
+    pub fn unified_memory(&self) -> Option<UnifiedMemory> {
+        if self.backend != GpuBackend::Unified {
+            return None;
+        }
+        read_unified_memory()
+    }
+
+    /// Get total VRAM across all GPUs
+    pub fn total_vram(&self) -> u64 {
+        self.gpus.iter().map(|g| g.total_vram_mb).sum::<u64>() * 1024 * 1024
+    }
+
+    /// Get total used VRAM across all GPUs
+    pub fn total_used_vram(&self) -> u64 {
+        self.status().iter().map(|s| s.used).sum()
+    }
+
+    /// Get total free VRAM across all GPUs
+    pub fn total_free_vram(&self) -> u64 {
+        self.status().iter().map(|s| s.free).sum()
+    }
+
+    /// Per-process VRAM usage across all GPUs, largest first
+    ///
+    /// - NVML: running compute and graphics processes
+    /// - Windows: dxgkrnl's per-process segment statistics (the D3DKMT data
+    ///   behind Task Manager), read from the `GPU Process Memory` counters
+    /// - Linux DRM: `drm-memory-vram` / `drm-resident-vram*` in `/proc/*/fdinfo`
+    /// - nvidia-smi: compute apps (used memory is N/A under WDDM)
+    pub fn get_gpu_processes(&self) -> Vec<GpuProcess> {
+        let mut processes = Vec::new();
+
+        #[cfg(feature = "nvml")]
+        if let Some(ref nvml) = self.nvml {
+            use nvml_wrapper::enums::device::UsedGpuMemory;
+            for gpu in &self.gpus {
+                let Ok(device) = nvml.device_by_index(gpu.index) else {
+                    continue;
+                };
+                let compute = device.running_compute_processes().unwrap_or_default();
+                let graphics = device.running_graphics_processes().unwrap_or_default();
+                for proc in compute.into_iter().chain(graphics) {
+                    if let UsedGpuMemory::Used(bytes) = proc.used_gpu_memory {
+                        processes.push(GpuProcess {
+                            pid: proc.pid,
+                            name: String::new(),
+                            gpu_index: Some(gpu.index),
+                            vram_bytes: bytes,
+                        });
+                    }
+                }
+            }
+        }
+
+        #[cfg(windows)]
+        if processes.is_empty() {
+            processes = self.processes_via_counters();
+        }
+
+        if processes.is_empty() && self.backend == GpuBackend::Drm {
+            processes = self.processes_via_fdinfo();
+        }
+
+        if processes.is_empty() && self.backend == GpuBackend::NvidiaSmi {
+            processes = Self::processes_via_nvidia_smi();
+        }
+
+        merge_gpu_processes(processes)
+    }
+
+    /// SM utilization per process on NVIDIA GPUs, one value per second
+    ///
+    /// Runs `nvidia-smi pmon` for `samples` seconds. Other backends have no
+    /// per-process history, so this is empty there.
+    pub fn sample_process_utilization(&self, samples: u32) -> std::collections::HashMap<u32, Vec<u32>> {
+        let mut history = std::collections::HashMap::new();
+        if !matches!(self.backend, GpuBackend::Nvml | GpuBackend::NvidiaSmi) || samples == 0 {
+            return history;
+        }
+        let Ok(output) = std::process::Command::new("nvidia-smi")
+            .args(["pmon", "-s", "u", "-c", &samples.to_string()])
+            .output()
+        else {
+            return history;
+        };
+        for (pid, sm) in parse_nvidia_smi_pmon(&String::from_utf8_lossy(&output.stdout)) {
+            history.entry(pid).or_default().push(sm.unwrap_or(0));
+        }
+        history
+    }
+
+    /// Compute apps reported by `nvidia-smi`
+    fn processes_via_nvidia_smi() -> Vec<GpuProcess> {
+        let run = |args: &[&str]| {
+            std::process::Command::new("nvidia-smi")
+                .args(args)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+        };
+
+        let Some(uuids) = run(&["--query-gpu=index,uuid", "--format=csv,noheader"]) else {
+            return Vec::new();
+        };
+        let Some(apps) = run(&["--query-compute-apps=pid,used_memory,gpu_uuid", "--format=csv,noheader,nounits"]) else {
+            return Vec::new();
+        };
+
+        parse_nvidia_smi_apps(&apps, &uuids)
+    }
+
+    /// Sum per-client VRAM from DRM fdinfo
+    fn processes_via_fdinfo(&self) -> Vec<GpuProcess> {
+        // drm-pdev in fdinfo is the PCI address the device symlink points at
+        let pdevs: Vec<Option<String>> = self
+            .drm_devices
+            .iter()
+            .map(|d| {
+                std::fs::canonicalize(d)
+                    .ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            })
+            .collect();
+
+        let mut processes = Vec::new();
+        let Ok(procs) = std::fs::read_dir("/proc") else {
+            return processes;
+        };
+
+        for entry in procs.flatten() {
+            let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+                continue;
+            };
+            let Ok(fds) = std::fs::read_dir(entry.path().join("fdinfo")) else {
+                continue;
+            };
+
+            // Several fds can share one DRM client; count each client once
+            let mut seen_clients = std::collections::HashSet::new();
+            for fd in fds.flatten() {
+                let Ok(info) = std::fs::read_to_string(fd.path()) else {
+                    continue;
+                };
+                let Some(client) = parse_drm_fdinfo(&info) else {
+                    continue;
+                };
+                if client.vram_bytes == 0 || !seen_clients.insert((client.pdev.clone(), client.client_id)) {
+                    continue;
+                }
+
+                let gpu_index = pdevs
+                    .iter()
+                    .position(|p| p.as_deref() == Some(client.pdev.as_str()))
+                    .map(|i| self.gpus[i].index);
+                processes.push(GpuProcess {
+                    pid,
+                    name: String::new(),
+                    gpu_index,
+                    vram_bytes: client.vram_bytes,
+                });
+            }
+        }
+
+        processes
+    }
+
+    /// Dedicated VRAM per process from the `GPU Process Memory` counters
+    #[cfg(windows)]
+    fn processes_via_counters(&self) -> Vec<GpuProcess> {
+        const SCRIPT: &str = "(Get-Counter '\\GPU Process Memory(*)\\Dedicated Usage' -ErrorAction SilentlyContinue).CounterSamples | \
+            ForEach-Object { \"$($_.InstanceName)|$($_.CookedValue)\" }";
+
+        let Ok(output) = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+            .output()
+        else {
+            return Vec::new();
+        };
+
+        let adapters = dxgi_adapter_luids();
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (instance, value) = line.trim().split_once('|')?;
+                let (pid, luid) = parse_gpu_counter_instance(instance)?;
+                let vram_bytes = value.trim().parse::<f64>().ok()? as u64;
+                let gpu_index = adapters
+                    .iter()
+                    .find(|(l, _)| l.eq_ignore_ascii_case(&luid))
+                    .and_then(|(_, name)| self.gpus.iter().find(|g| &g.name == name))
+                    .map(|g| g.index);
+                Some(GpuProcess { pid, name: String::new(), gpu_index, vram_bytes })
+            })
+            .filter(|p| p.vram_bytes > 0)
+            .collect()
+    }
+
+    /// Print the top VRAM consumers with suggestions
+    pub fn print_top(&self, limit: usize) {
+        println!("\n🎮 Top VRAM Consumers ({})\n", self.backend);
+
+        let processes = self.get_gpu_processes();
+        if processes.is_empty() {
+            println!("No per-process VRAM data available.");
+            return;
+        }
+
+        println!("┌────────┬──────────────────────────┬─────┬───────────┐");
+        println!("│ PID    │ Process                  │ GPU │ VRAM      │");
+        println!("├────────┼──────────────────────────┼─────┼───────────┤");
+        for proc in processes.iter().take(limit) {
+            println!(
+                "│ {:>6} │ {:24} │ {:>3} │ {:>6} MB │",
+                proc.pid,
+                truncate_padded(&proc.name, 24),
+                proc.gpu_index.map(|i| i.to_string()).unwrap_or_else(|| "-".into()),
+                proc.vram_mb()
+            );
+        }
+        println!("└────────┴──────────────────────────┴─────┴───────────┘");
+
+        let total: u64 = processes.iter().map(|p| p.vram_mb()).sum();
+        println!("\nTotal: {} MB across {} processes", total, processes.len());
+
+        let heavy: Vec<_> = processes.iter().filter(|p| p.vram_mb() >= TOP_SUGGESTION_MB).take(3).collect();
+        if !heavy.is_empty() {
+            println!("\n💡 Suggestions:");
+            for proc in heavy {
+                println!("   • {} ({} MB VRAM): {}", proc.name, proc.vram_mb(), proc.suggestion());
+            }
+        }
+    }
+
+    /// Print a VRAM table for every GPU
+    pub fn print_status(&self) {
+        println!("\n🎮 GPU Memory ({})\n", self.backend);
+
+        if self.gpus.is_empty() {
+            println!("No GPUs detected.");
+            return;
+        }
+
+        println!("┌────┬──────────────────────────┬──────────────────────┬────────┬───────┬───────┐");
+        println!("│ #  │ GPU                      │ VRAM                 │ Used   │ Temp  │ Util  │");
+        println!("├────┼──────────────────────────┼──────────────────────┼────────┼───────┼───────┤");
+
+        for status in self.status() {
+            let name = self
+                .gpus
+                .iter()
+                .find(|g| g.index == status.gpu_index)
+                .map(|g| g.name.as_str())
+                .unwrap_or("?");
+            let indicator = if status.usage_percent() > 90.0 {
+                "🔴"
+            } else if status.usage_percent() > 75.0 {
+                "🟡"
+            } else {
+                "🟢"
+            };
+
+            println!(
+                "│ {:<2} │ {:24} │ {:>8} / {:>6} MB │ {} {:>3.0}% │ {:>5} │ {:>5} │",
+                status.gpu_index,
+                truncate_padded(name, 24),
+                status.used_mb(),
+                status.total_mb(),
+                indicator,
+                status.usage_percent(),
+                status.temperature.map(|t| format!("{}°C", t)).unwrap_or_else(|| "-".into()),
+                status.utilization.map(|u| format!("{}%", u)).unwrap_or_else(|| "-".into()),
+            );
+        }
+
+        println!("└────┴──────────────────────────┴──────────────────────┴────────┴───────┴───────┘");
+
+        if self.backend == GpuBackend::Dxgi {
+            println!("\nVRAM usage needs NVML or nvidia-smi; DXGI only reports capacity.");
+        }
+        if let Some(unified) = self.unified_memory() {
+            println!(
+                "\nThe GPU shares {} MB of RAM with the CPU and may wire up to {} MB; {} MB is wired now.",
+                unified.total_mb, unified.gpu_limit_mb, unified.wired_mb
+            );
+        }
+    }
+}
+
+impl Default for GpuMonitor {
+    fn default() -> Self {
+        Self::new().unwrap_or_else(|_| Self::with_backend(Vec::new(), GpuBackend::None, Vec::new()))
+    }
+}
+
+/// How busy the GPU's 3D engine is with `pid`, in percent
+///
+/// Windows reads the `GPU Engine` counters and Linux samples DRM fdinfo
+/// over a quarter second. macOS only reports the whole device, so there it
+/// is the utilization of the GPU rather than of the process.
+#[cfg(windows)]
+pub fn process_utilization(pid: u32) -> Option<u32> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let script = format!(
+        "((Get-Counter '\\GPU Engine(pid_{}_*engtype_3D)\\Utilization Percentage' -ErrorAction SilentlyContinue).CounterSamples | \
+         Measure-Object CookedValue -Sum).Sum",
+        pid
+    );
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let percent: f64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    Some(percent.clamp(0.0, 100.0) as u32)
+}
+
+#[cfg(target_os = "linux")]
+pub fn process_utilization(pid: u32) -> Option<u32> {
+    const WINDOW: std::time::Duration = std::time::Duration::from_millis(250);
+
+    let before = drm_render_ns(pid)?;
+    std::thread::sleep(WINDOW);
+    let after = drm_render_ns(pid)?;
+    let percent = after.saturating_sub(before) as f64 / WINDOW.as_nanos() as f64 * 100.0;
+    Some(percent.min(100.0) as u32)
+}
+
+#[cfg(target_os = "macos")]
+pub fn process_utilization(_pid: u32) -> Option<u32> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-r", "-d", "1", "-c", "IOAccelerator"])
+        .output()
+        .ok()?;
+    parse_ioreg_utilization(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+pub fn process_utilization(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Render time of every DRM client `pid` holds; `None` if it holds none
+#[cfg(target_os = "linux")]
+fn drm_render_ns(pid: u32) -> Option<u64> {
+    let fds = std::fs::read_dir(format!("/proc/{}/fdinfo", pid)).ok()?;
+    let mut seen_clients = std::collections::HashSet::new();
+    let mut total = None;
+    for fd in fds.flatten() {
+        let Some(client) = std::fs::read_to_string(fd.path()).ok().and_then(|info| parse_drm_fdinfo(&info)) else {
+            continue;
+        };
+        if seen_clients.insert((client.pdev, client.client_id)) {
+            *total.get_or_insert(0) += client.render_ns;
+        }
+    }
+    total
+}
+
+/// Busiest accelerator in `ioreg -c IOAccelerator` output
+/// (`"PerformanceStatistics" = {..,"Device Utilization %"=37,..}`)
+#[cfg(any(target_os = "macos", test))]
+fn parse_ioreg_utilization(output: &str) -> Option<u32> {
+    ioreg_statistic(output, "Device Utilization %").map(|u| u as u32)
+}
+
+/// Largest value of one `PerformanceStatistics` key across accelerators
+#[cfg(any(target_os = "macos", test))]
+fn ioreg_statistic(output: &str, key: &str) -> Option<u64> {
+    output
+        .split(&format!("\"{}\"=", key))
+        .skip(1)
+        .filter_map(|rest| {
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .max()
+}
+
+/// Wired and GPU memory from `vm_stat`, the AGX driver's counters and sysctl
+#[cfg(target_os = "macos")]
+fn read_unified_memory() -> Option<UnifiedMemory> {
+    const MB: u64 = 1024 * 1024;
+
+    let run = |program: &str, args: &[&str]| {
+        std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+    let total_mb = sysctl("hw.memsize")?.parse::<u64>().ok()? / MB;
+    let ioreg = run("ioreg", &["-r", "-d", "1", "-c", "IOAccelerator"]).unwrap_or_default();
+    let vm = crate::macos::memory::parse_vm_stat(&run("vm_stat", &[]).unwrap_or_default());
+
+    Some(UnifiedMemory {
+        total_mb,
+        wired_mb: vm.wired_mb as u64,
+        gpu_in_use_mb: ioreg_statistic(&ioreg, "In use system memory").unwrap_or(0) / MB,
+        gpu_alloc_mb: ioreg_statistic(&ioreg, "Alloc system memory").unwrap_or(0) / MB,
+        gpu_limit_mb: gpu_wired_limit_mb(total_mb, configured_wired_limit_mb()),
+        utilization: parse_ioreg_utilization(&ioreg),
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_unified_memory() -> Option<UnifiedMemory> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn sysctl(name: &str) -> Option<String> {
+    let output = std::process::Command::new("sysctl").args(["-n", name]).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// `iogpu.wired_limit_mb`, 0 unless raised with `sudo sysctl`
+#[cfg(target_os = "macos")]
+fn configured_wired_limit_mb() -> u64 {
+    sysctl("iogpu.wired_limit_mb").and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+/// How much RAM the GPU may wire: the configured limit, or by default about
+/// two thirds of RAM up to 36 GB and three quarters above
+#[cfg(any(target_os = "macos", test))]
+fn gpu_wired_limit_mb(total_mb: u64, configured_mb: u64) -> u64 {
+    if configured_mb > 0 {
+        return configured_mb.min(total_mb);
+    }
+    if total_mb > 36 * 1024 {
+        total_mb * 3 / 4
+    } else {
+        total_mb * 2 / 3
+    }
+}
+
+/// Map a PCI vendor ID to a GPU vendor
+fn vendor_from_pci_id(vendor_id: u32) -> GpuVendor {
+    match vendor_id {
+        0x10DE => GpuVendor::Nvidia,
+        0x1002 | 0x1022 => GpuVendor::AMD,
+        0x8086 => GpuVendor::Intel,
+        _ => GpuVendor::Unknown,
+    }
+}
+
+/// Parse `nvidia-smi --query-gpu=<NVIDIA_SMI_QUERY> --format=csv,noheader,nounits`
+fn parse_nvidia_smi(output: &str) -> Vec<(GpuInfo, VramStatus)> {
+    const MB: u64 = 1024 * 1024;
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 9 {
+                return None;
+            }
+            // Unsupported fields read "[N/A]" or "[Not Supported]"
+            let num = |i: usize| fields[i].parse::<f64>().ok();
+            let index = fields[0].parse::<u32>().ok()?;
+            let total_mb = num(2)? as u64;
+
+            let info = GpuInfo {
+                index,
+                name: fields[1].to_string(),
+                vendor: GpuVendor::Nvidia,
+                total_vram_mb: total_mb,
+                driver_version: fields[8].to_string(),
+                compute_capability: None,
+            };
+            let status = VramStatus {
+                gpu_index: index,
+                total: total_mb * MB,
+                used: num(3).unwrap_or(0.0) as u64 * MB,
+                free: num(4).unwrap_or(total_mb as f64) as u64 * MB,
+                temperature: num(5).map(|t| t as u32),
+                utilization: num(6).map(|u| u as u32),
+                power_watts: num(7).map(|p| p.round() as u32),
+            };
+            Some((info, status))
+        })
+        .collect()
+}
+
+/// Combine entries for the same process and GPU, fill in names, sort by VRAM
+fn merge_gpu_processes(processes: Vec<GpuProcess>) -> Vec<GpuProcess> {
+    let mut merged: Vec<GpuProcess> = Vec::new();
+    for proc in processes {
+        match merged.iter_mut().find(|p| p.pid == proc.pid && p.gpu_index == proc.gpu_index) {
+            Some(existing) => existing.vram_bytes += proc.vram_bytes,
+            None => merged.push(proc),
+        }
+    }
+
+    if !merged.is_empty() {
+        let mut system = sysinfo::System::new();
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        for proc in &mut merged {
+            if proc.name.is_empty() {
+                proc.name = system
+                    .process(sysinfo::Pid::from_u32(proc.pid))
+                    .map(|p| p.name().to_string_lossy().into_owned())
+                    .unwrap_or_else(|| format!("pid {}", proc.pid));
+            }
+        }
+    }
+
+    merged.sort_by_key(|p| std::cmp::Reverse(p.vram_bytes));
+    merged
+}
+
+/// Parse `--query-compute-apps=pid,used_memory,gpu_uuid` against `--query-gpu=index,uuid`
+fn parse_nvidia_smi_apps(apps: &str, uuids: &str) -> Vec<GpuProcess> {
+    let index_of = |uuid: &str| {
+        uuids.lines().find_map(|line| {
+            let (index, u) = line.split_once(',')?;
+            (u.trim() == uuid).then(|| index.trim().parse().ok()).flatten()
+        })
+    };
+
+    apps.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() < 3 {
+                return None;
+            }
+            Some(GpuProcess {
+                pid: fields[0].parse().ok()?,
+                name: String::new(),
+                gpu_index: index_of(fields[2]),
+                // "[N/A]" under WDDM
+                vram_bytes: fields[1].parse::<u64>().ok()? * 1024 * 1024,
+            })
+        })
+        .collect()
+}
+
+/// Parse `nvidia-smi pmon -s u` into (pid, SM %) rows
+///
+/// Columns differ between driver versions, so they are found from the
+/// `# gpu pid type sm ...` header. Idle processes read `-`.
+fn parse_nvidia_smi_pmon(output: &str) -> Vec<(u32, Option<u32>)> {
+    let mut columns: Option<(usize, usize)> = None;
+    let mut rows = Vec::new();
+    for line in output.lines() {
+        if let Some(header) = line.trim_start().strip_prefix('#') {
+            let names: Vec<&str> = header.split_whitespace().collect();
+            if let (Some(pid), Some(sm)) = (names.iter().position(|n| *n == "pid"), names.iter().position(|n| *n == "sm")) {
+                columns = Some((pid, sm));
+            }
+            continue;
+        }
+        let Some((pid_col, sm_col)) = columns else {
+            continue;
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some(pid) = fields.get(pid_col).and_then(|p| p.parse().ok()) else {
+            continue;
+        };
+        rows.push((pid, fields.get(sm_col).and_then(|sm| sm.parse().ok())));
+    }
+    rows
+}
+
+/// VRAM and render time of one DRM client, from a `/proc/<pid>/fdinfo/<fd>` file
+struct DrmClient {
+    pdev: String,
+    client_id: u64,
+    vram_bytes: u64,
+    /// Time the 3D engine has spent on this client since it opened the device
+    render_ns: u64,
+}
+
+/// Parse DRM fdinfo (`drm-pdev`, `drm-client-id`, `drm-memory-vram` or
+/// `drm-resident-vram*`, `drm-engine-gfx` or `drm-engine-render`); returns
+/// `None` for non-DRM fds
+fn parse_drm_fdinfo(info: &str) -> Option<DrmClient> {
+    let mut pdev = None;
+    let mut client_id = None;
+    let mut legacy_vram = 0u64;
+    let mut resident_vram = 0u64;
+    let mut render_ns = 0u64;
+
+    for line in info.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let key = key.trim();
+        match key {
+            "drm-pdev" => pdev = Some(value.to_string()),
+            "drm-client-id" => client_id = value.parse().ok(),
+            "drm-memory-vram" => legacy_vram = parse_fdinfo_size(value),
+            k if k.starts_with("drm-resident-vram") => resident_vram += parse_fdinfo_size(value),
+            // amdgpu calls its 3D engine gfx, i915 render; "123456 ns"
+            "drm-engine-gfx" | "drm-engine-render" => {
+                render_ns += value.split_whitespace().next().and_then(|n| n.parse::<u64>().ok()).unwrap_or(0)
+            }
+            _ => {}
+        }
+    }
+
+    Some(DrmClient {
+        pdev: pdev?,
+        client_id: client_id?,
+        vram_bytes: resident_vram.max(legacy_vram),
+        render_ns,
+    })
+}
+
+/// "123456 KiB" -> bytes
+fn parse_fdinfo_size(value: &str) -> u64 {
+    let mut parts = value.split_whitespace();
+    let amount: u64 = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+    match parts.next() {
+        Some("KiB") => amount * 1024,
+        Some("MiB") => amount * 1024 * 1024,
+        Some("GiB") => amount * 1024 * 1024 * 1024,
+        _ => amount,
+    }
+}
+
+/// "pid_1234_luid_0x00000000_0x0000D1A2_phys_0" -> (1234, "0x00000000_0x0000D1A2")
+#[cfg(windows)]
+fn parse_gpu_counter_instance(instance: &str) -> Option<(u32, String)> {
+    let rest = instance.strip_prefix("pid_")?;
+    let (pid, rest) = rest.split_once('_')?;
+    let luid = rest.strip_prefix("luid_")?;
+    let luid = luid.split("_phys").next()?;
+    Some((pid.parse().ok()?, luid.to_string()))
+}
+
+/// DXGI adapters as (LUID in counter-instance form, description)
+#[cfg(windows)]
+fn dxgi_adapter_luids() -> Vec<(String, String)> {
+    use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1};
+
+    let mut adapters = Vec::new();
+    unsafe {
+        let Ok(factory) = CreateDXGIFactory1::<IDXGIFactory1>() else {
+            return adapters;
+        };
+        let mut i = 0u32;
+        while let Ok(adapter) = factory.EnumAdapters1(i) {
+            if let Ok(desc) = adapter.GetDesc1() {
+                let len = desc.Description.iter().position(|&c| c == 0).unwrap_or(desc.Description.len());
+                let luid = format!(
+                    "0x{:08X}_0x{:08X}",
+                    desc.AdapterLuid.HighPart as u32, desc.AdapterLuid.LowPart
+                );
+                adapters.push((luid, String::from_utf16_lossy(&desc.Description[..len])));
+            }
+            i += 1;
+        }
+    }
+    adapters
+}
+
+/// Read VRAM, temperature and load for one DRM device
+fn read_drm_status(gpu: &GpuInfo, device: &std::path::Path) -> VramStatus {
+    let total = read_sysfs_u64(&device.join("mem_info_vram_total")).unwrap_or(gpu.total_vram_mb * 1024 * 1024);
+    let used = read_sysfs_u64(&device.join("mem_info_vram_used")).unwrap_or(0);
+
+    // hwmon reports millidegrees and microwatts
+    let hwmon = std::fs::read_dir(device.join("hwmon"))
+        .ok()
+        .and_then(|mut entries| entries.next())
+        .and_then(|e| e.ok())
+        .map(|e| e.path());
+    let temperature = hwmon
+        .as_ref()
+        .and_then(|h| read_sysfs_u64(&h.join("temp1_input")))
+        .map(|t| (t / 1000) as u32);
+    let power_watts = hwmon
+        .as_ref()
+        .and_then(|h| read_sysfs_u64(&h.join("power1_average")))
+        .map(|p| (p / 1_000_000) as u32);
+
+    VramStatus {
+        gpu_index: gpu.index,
+        total,
+        used,
+        free: total.saturating_sub(used),
+        temperature,
+        utilization: read_sysfs_u64(&device.join("gpu_busy_percent")).map(|u| u as u32),
+        power_watts,
+    }
+}
+
+fn read_sysfs_u64(path: &std::path::Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+impl std::fmt::Display for VramStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "GPU {}: {}/{} MB ({:.1}%)",
+            self.gpu_index,
+            self.used_mb(),
+            self.total_mb(),
+            self.usage_percent()
+        )?;
+
+        if let Some(temp) = self.temperature {
+            write!(f, " | {}°C", temp)?;
+        }
+
+        if let Some(util) = self.utilization {
+            write!(f, " | {}% util", util)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let output = "0, NVIDIA GeForce RTX 3080, 10240, 2048, 8192, 45, 12, 85.31, 535.54\n\
+                      1, Tesla T4, 15360, 0, 15360, [N/A], [N/A], [N/A], 535.54\n";
+        let rows = parse_nvidia_smi(output);
+        assert_eq!(rows.len(), 2);
+
+        let (info, status) = &rows[0];
+        assert_eq!(info.name, "NVIDIA GeForce RTX 3080");
+        assert_eq!(status.used_mb(), 2048);
+        assert_eq!(status.temperature, Some(45));
+        assert_eq!(status.power_watts, Some(85));
+        assert_eq!(status.usage_percent(), 20.0);
+
+        let (_, status) = &rows[1];
+        assert_eq!(status.temperature, None);
+        assert_eq!(status.total_mb(), 15360);
+    }
+
+    #[test]
+    fn test_parse_drm_fdinfo() {
+        let amdgpu = "pos:\t0\nflags:\t02100002\ndrm-driver:\tamdgpu\ndrm-pdev:\t0000:03:00.0\n\
+                      drm-client-id:\t42\ndrm-memory-vram:\t524288 KiB\ndrm-memory-gtt:\t2048 KiB\n";
+        let client = parse_drm_fdinfo(amdgpu).unwrap();
+        assert_eq!(client.pdev, "0000:03:00.0");
+        assert_eq!(client.client_id, 42);
+        assert_eq!(client.vram_bytes, 512 * 1024 * 1024);
+        assert_eq!(client.render_ns, 0);
+
+        let busy = format!("{}drm-engine-gfx:\t1500000 ns\ndrm-engine-compute:\t900 ns\n", amdgpu);
+        assert_eq!(parse_drm_fdinfo(&busy).unwrap().render_ns, 1_500_000);
+
+        let xe = "drm-driver:\txe\ndrm-pdev:\t0000:00:02.0\ndrm-client-id:\t7\n\
+                  drm-resident-vram0:\t64 MiB\ndrm-resident-system:\t10 MiB\n";
+        assert_eq!(parse_drm_fdinfo(xe).unwrap().vram_bytes, 64 * 1024 * 1024);
+
+        assert!(parse_drm_fdinfo("pos:\t0\nflags:\t02\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_apps() {
+        let uuids = "0, GPU-aaaa\n1, GPU-bbbb\n";
+        let apps = "1234, 4096, GPU-bbbb\n5678, [N/A], GPU-aaaa\n";
+        let procs = parse_nvidia_smi_apps(apps, uuids);
+        assert_eq!(procs.len(), 1);
+        assert_eq!(procs[0].pid, 1234);
+        assert_eq!(procs[0].gpu_index, Some(1));
+        assert_eq!(procs[0].vram_mb(), 4096);
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_pmon() {
+        let output = "# gpu         pid   type     sm    mem    enc    dec    jpg    ofa    command\n\
+                      # Idx           #    C/G      %      %      %      %      %      %    name\n\
+                          0       2345     C     98     45      -      -      -      -    python\n\
+                          0       6789     C      -      -      -      -      -      -    python\n\
+                          1          -     -      -      -      -      -      -      -    -\n";
+        assert_eq!(parse_nvidia_smi_pmon(output), vec![(2345, Some(98)), (6789, None)]);
+        assert!(parse_nvidia_smi_pmon("    0   2345   C   98\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_ioreg_utilization() {
+        let output = "+-o AGXAcceleratorG13X  <class AGXAcceleratorG13X>\n    {\n      \"PerformanceStatistics\" = \
+                      {\"Tiler Utilization %\"=12,\"Device Utilization %\"=37,\"Renderer Utilization %\"=35}\n";
+        assert_eq!(parse_ioreg_utilization(output), Some(37));
+        assert_eq!(parse_ioreg_utilization("no accelerators\n"), None);
+    }
+
+    #[test]
+    fn test_unified_memory() {
+        let output = "\"PerformanceStatistics\" = {\"In use system memory\"=6442450944,\
+                      \"Alloc system memory\"=8589934592,\"Device Utilization %\"=80}\n";
+        assert_eq!(ioreg_statistic(output, "In use system memory"), Some(6 * 1024 * 1024 * 1024));
+        assert_eq!(ioreg_statistic(output, "Alloc system memory"), Some(8 * 1024 * 1024 * 1024));
+
+        assert_eq!(gpu_wired_limit_mb(16 * 1024, 0), 10922);
+        assert_eq!(gpu_wired_limit_mb(64 * 1024, 0), 48 * 1024);
+        assert_eq!(gpu_wired_limit_mb(64 * 1024, 56 * 1024), 56 * 1024);
+
+        let unified = UnifiedMemory {
+            total_mb: 16 * 1024,
+            wired_mb: 12 * 1024,
+            gpu_in_use_mb: 6 * 1024,
+            gpu_alloc_mb: 8 * 1024,
+            gpu_limit_mb: 10922,
+            utilization: Some(80),
+        };
+        // Wired RAM outweighs the GPU's own share of its limit
+        assert_eq!(unified.usage_percent(), 75.0);
+        assert_eq!(unified.available_mb(), 4 * 1024);
+        assert_eq!(unified.vram_status().used_mb(), 6 * 1024);
+        assert_eq!(unified.vram_status().free_mb(), 4 * 1024);
+    }
+}
@@ -1,6 +1,15 @@
 //! Real-time monitoring
 
+pub mod alerts;
+pub mod capture;
+pub mod faults;
+pub mod gpu;
+pub mod mqtt;
+pub mod notify;
+pub mod pressure;
+pub mod procs;
+pub mod relations;
+pub mod watch;
 #[cfg(target_os = "windows")]
 pub mod realtime;
-#[cfg(target_os = "windows")]
 pub mod dashboard;
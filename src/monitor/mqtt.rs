@@ -0,0 +1,374 @@
+//! MQTT publishing for home automation
+//!
+//! With `[mqtt] broker` set, the daemons and the Windows service publish to
+//! `<topic_prefix>/<host>/`:
+//! - `state` - memory load, available memory, pressure level and the health
+//!   score, every `interval_secs` (retained)
+//! - `optimized` - memory freed by each optimization, whoever ran it
+//!   (retained, so the last one survives a restart of Home Assistant)
+//! - `availability` - `online`, and `offline` as the last will
+//!
+//! Home Assistant discovery configs under `<discovery_prefix>/sensor/...`
+//! make the sensors show up as one device per machine without any YAML.
+//! `mqtts://` brokers are reached over TLS with the system's root
+//! certificates. The client needs a build with the `mqtt` feature.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::core::config::OptimizerConfig;
+use crate::monitor::pressure::PressureLevel;
+
+pub const DEFAULT_INTERVAL_SECS: u64 = 30;
+
+/// Time between health score updates; computing one reads sensors
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+const HEALTH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Broker connection and topic settings, the `[mqtt]` table of the config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// `mqtt://host[:port]` or `mqtts://host[:port]`; unset turns publishing off
+    pub broker: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Seconds between state updates
+    pub interval_secs: u64,
+    /// Topics are `<topic_prefix>/<host>/...`
+    pub topic_prefix: String,
+    /// Home Assistant discovery prefix; empty turns discovery off
+    pub discovery_prefix: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker: None,
+            username: None,
+            password: None,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            topic_prefix: "ruvector-memopt".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Problems that would keep the publisher from starting
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(Err(e)) = self.broker.as_deref().filter(|b| !b.is_empty()).map(Broker::parse) {
+            problems.push(format!("mqtt.broker: {}", e));
+        }
+        if !(5..=3600).contains(&self.interval_secs) {
+            problems.push(format!("mqtt.interval_secs = {} should be between 5 and 3600 seconds", self.interval_secs));
+        }
+        if self.topic_prefix.is_empty() || self.topic_prefix.contains(['#', '+']) {
+            problems.push(format!("mqtt.topic_prefix = '{}' should be a topic without wildcards", self.topic_prefix));
+        }
+        problems
+    }
+}
+
+/// Where the broker is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Broker {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+impl Broker {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let url = url.trim();
+        let (rest, tls) = if let Some(rest) = url.strip_prefix("mqtts://") {
+            (rest, true)
+        } else if let Some(rest) = url.strip_prefix("mqtt://") {
+            (rest, false)
+        } else {
+            return Err(format!("'{}' should start with mqtt:// or mqtts://", url));
+        };
+        let rest = rest.trim_end_matches('/');
+        let (host, port) = match rest.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| format!("Invalid port in '{}'", url))?),
+            None => (rest, if tls { 8883 } else { 1883 }),
+        };
+        if host.is_empty() || host.contains('/') {
+            return Err(format!("No host in '{}'", url));
+        }
+        Ok(Self { host: host.to_string(), port, tls })
+    }
+}
+
+/// Topics of one machine
+#[derive(Debug, Clone)]
+pub struct Topics {
+    base: String,
+    host: String,
+}
+
+impl Topics {
+    pub fn new(prefix: &str, host: &str) -> Self {
+        Self { base: format!("{}/{}", prefix.trim_end_matches('/'), host), host: host.to_string() }
+    }
+
+    pub fn state(&self) -> String {
+        format!("{}/state", self.base)
+    }
+
+    pub fn optimized(&self) -> String {
+        format!("{}/optimized", self.base)
+    }
+
+    pub fn availability(&self) -> String {
+        format!("{}/availability", self.base)
+    }
+
+    /// Home Assistant discovery topics and configs for this machine's sensors
+    pub fn discovery(&self, discovery_prefix: &str) -> Vec<(String, String)> {
+        if discovery_prefix.is_empty() {
+            return Vec::new();
+        }
+        // Discovery ids may only use letters, digits, `_` and `-`
+        let node: String =
+            self.host.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect();
+        let device = json!({
+            "identifiers": [format!("ruvector-memopt-{}", node)],
+            "name": self.host,
+            "manufacturer": "RuVector",
+            "model": "Memory Optimizer",
+            "sw_version": env!("CARGO_PKG_VERSION"),
+        });
+        let measurement = |unit: &str| json!({ "unit_of_measurement": unit, "state_class": "measurement" });
+        let sensors = [
+            ("memory_load", "Memory load", self.state(), "{{ value_json.memory_percent }}", measurement("%")),
+            ("available_memory", "Available memory", self.state(), "{{ value_json.available_mb }}", measurement("MB")),
+            (
+                "pressure",
+                "Memory pressure",
+                self.state(),
+                "{{ value_json.pressure }}",
+                json!({ "device_class": "enum", "options": ["normal", "high", "critical"] }),
+            ),
+            ("health", "Health score", self.state(), "{{ value_json.health_score }}", measurement("points")),
+            ("last_freed", "Last optimization freed", self.optimized(), "{{ value_json.freed_mb }}", json!({ "unit_of_measurement": "MB" })),
+        ];
+        sensors
+            .into_iter()
+            .map(|(object, name, state_topic, template, extra)| {
+                let mut config = json!({
+                    "name": name,
+                    "unique_id": format!("ruvector_memopt_{}_{}", node, object),
+                    "object_id": format!("{}_{}", node, object),
+                    "state_topic": state_topic,
+                    "value_template": template,
+                    "availability_topic": self.availability(),
+                    "device": device,
+                });
+                if let (Some(config), Some(extra)) = (config.as_object_mut(), extra.as_object()) {
+                    config.extend(extra.clone());
+                }
+                (format!("{}/sensor/{}/{}/config", discovery_prefix, node, object), config.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Payload of the `state` topic
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MqttState {
+    pub memory_percent: u32,
+    pub available_mb: u64,
+    pub total_mb: u64,
+    pub pressure: String,
+    pub health_score: Option<u8>,
+    pub health_grade: Option<String>,
+}
+
+impl MqttState {
+    pub fn new(
+        status: &crate::platform::MemoryStatus,
+        pressure: PressureLevel,
+        health: Option<&crate::features::health::HealthReport>,
+    ) -> Self {
+        Self {
+            memory_percent: status.memory_load_percent,
+            available_mb: status.available_physical_mb.round() as u64,
+            total_mb: status.total_physical_mb.round() as u64,
+            pressure: pressure.to_string(),
+            health_score: health.map(|h| h.total),
+            health_grade: health.map(|h| h.grade.to_string()),
+        }
+    }
+}
+
+/// Start publishing in the background if a broker is configured
+#[cfg(feature = "mqtt")]
+pub fn spawn(config: &OptimizerConfig) {
+    let Some(url) = config.mqtt.broker.clone().filter(|b| !b.is_empty()) else {
+        return;
+    };
+    let broker = match Broker::parse(&url) {
+        Ok(broker) => broker,
+        Err(e) => {
+            tracing::warn!("MQTT publishing disabled: {}", e);
+            return;
+        }
+    };
+    let config = config.clone();
+    std::thread::spawn(move || publisher::run(broker, config));
+}
+
+/// Without the `mqtt` feature there is no client; say so if one was wanted
+#[cfg(not(feature = "mqtt"))]
+pub fn spawn(config: &OptimizerConfig) {
+    if config.mqtt.broker.as_deref().is_some_and(|b| !b.is_empty()) {
+        tracing::warn!("mqtt.broker is set, but this build has no MQTT support (mqtt feature)");
+    }
+}
+
+#[cfg(feature = "mqtt")]
+mod publisher {
+    use std::time::{Duration, Instant};
+
+    use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS, Transport};
+    use serde_json::json;
+
+    use super::{Broker, MqttState, Topics, HEALTH_INTERVAL};
+    use crate::core::config::OptimizerConfig;
+    use crate::core::history::{HistoryFilter, HistoryStore, TimeRange};
+    use crate::features::health::{HealthReport, HealthScorer};
+    use crate::monitor::pressure::{PressureLevel, PressureReading, PressureThresholds, PressureTracker};
+
+    /// Wait before reconnecting after the broker drops the connection
+    const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+    pub(super) fn run(broker: Broker, config: OptimizerConfig) {
+        let mqtt = &config.mqtt;
+        let host = crate::features::fleet::host_name();
+        let topics = Topics::new(&mqtt.topic_prefix, &host);
+
+        let mut options = MqttOptions::new(format!("ruvector-memopt-{}", host), broker.host.clone(), broker.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(topics.availability(), "offline", QoS::AtLeastOnce, true));
+        if let Some(username) = &mqtt.username {
+            options.set_credentials(username.clone(), mqtt.password.clone().unwrap_or_default());
+        }
+        if broker.tls {
+            options.set_transport(Transport::tls_with_default_config());
+        }
+        let (client, mut connection) = Client::new(options, 64);
+
+        // Announce after every (re)connect; the broker forgets nothing
+        // retained, but availability went offline with the last will
+        let announcer = client.clone();
+        let announce_topics = topics.clone();
+        let discovery_prefix = mqtt.discovery_prefix.clone();
+        let address = format!("{}:{}", broker.host, broker.port);
+        std::thread::spawn(move || {
+            let mut failing = false;
+            for notification in connection.iter() {
+                match notification {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        tracing::info!("Publishing to MQTT broker {}", address);
+                        failing = false;
+                        for (topic, payload) in announce_topics.discovery(&discovery_prefix) {
+                            let _ = announcer.try_publish(topic, QoS::AtLeastOnce, true, payload);
+                        }
+                        let _ = announcer.try_publish(announce_topics.availability(), QoS::AtLeastOnce, true, "online");
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        if !failing {
+                            tracing::warn!("MQTT broker {}: {}", address, e);
+                            failing = true;
+                        }
+                        std::thread::sleep(RECONNECT_DELAY);
+                    }
+                }
+            }
+        });
+
+        let optimizer = crate::platform::create_optimizer();
+        let mut tracker = PressureTracker::new(PressureThresholds::from_config(&config));
+        let mut scorer = HealthScorer::new();
+        let mut health: Option<(Instant, HealthReport)> = None;
+        let mut last_history_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        loop {
+            if let Ok(status) = optimizer.get_memory_status() {
+                tracker.update(PressureReading {
+                    timestamp_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+                    load_percent: status.memory_load_percent,
+                    available_mb: status.available_physical_mb,
+                    native_level: PressureLevel::Normal,
+                });
+                if health.as_ref().map_or(true, |(at, _)| at.elapsed() >= HEALTH_INTERVAL) {
+                    health = Some((Instant::now(), scorer.compute()));
+                }
+                let state = MqttState::new(&status, tracker.level(), health.as_ref().map(|(_, h)| h));
+                // Dropped rather than queued while the broker is away
+                if let Ok(payload) = serde_json::to_string(&state) {
+                    let _ = client.try_publish(topics.state(), QoS::AtMostOnce, true, payload);
+                }
+            }
+
+            // Optimizations by the tray, CLI or this process all land in history
+            let range = TimeRange { start_ms: Some(last_history_ms + 1), end_ms: None };
+            if let Ok(records) = HistoryStore::open_default().and_then(|s| s.query(range, &HistoryFilter::default())) {
+                for record in records {
+                    last_history_ms = last_history_ms.max(record.timestamp_ms);
+                    let payload = json!({
+                        "freed_mb": record.freed_mb.round(),
+                        "trigger": record.trigger.to_string(),
+                        "mode": record.mode.to_string(),
+                        "processes": record.processes_trimmed,
+                        "timestamp_ms": record.timestamp_ms,
+                    });
+                    let _ = client.try_publish(topics.optimized(), QoS::AtLeastOnce, true, payload.to_string());
+                }
+            }
+
+            std::thread::sleep(Duration::from_secs(config.mqtt.interval_secs.max(5)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broker_parse() {
+        let plain = Broker::parse("mqtt://broker.lan").unwrap();
+        assert_eq!((plain.host.as_str(), plain.port, plain.tls), ("broker.lan", 1883, false));
+        let tls = Broker::parse("mqtts://broker.lan:8884/").unwrap();
+        assert_eq!((tls.host.as_str(), tls.port, tls.tls), ("broker.lan", 8884, true));
+        assert!(Broker::parse("tcp://broker.lan").is_err());
+        assert!(Broker::parse("mqtt://:1883").is_err());
+        assert!(Broker::parse("mqtt://broker.lan:port").is_err());
+
+        let mut config = MqttConfig { broker: Some("mqtt://broker.lan".into()), ..Default::default() };
+        assert!(config.problems().is_empty());
+        config.topic_prefix = "home/#".into();
+        assert_eq!(config.problems().len(), 1);
+    }
+
+    #[test]
+    fn test_discovery() {
+        let topics = Topics::new("ruvector-memopt/", "ws-01.lan");
+        assert_eq!(topics.state(), "ruvector-memopt/ws-01.lan/state");
+        let discovery = topics.discovery("homeassistant");
+        assert_eq!(discovery.len(), 5);
+        let (topic, payload) = &discovery[0];
+        assert_eq!(topic, "homeassistant/sensor/ws-01_lan/memory_load/config");
+        let payload: serde_json::Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(payload["state_topic"], "ruvector-memopt/ws-01.lan/state");
+        assert_eq!(payload["unit_of_measurement"], "%");
+        assert_eq!(payload["availability_topic"], "ruvector-memopt/ws-01.lan/availability");
+        assert_eq!(payload["device"]["name"], "ws-01.lan");
+        assert!(topics.discovery("").is_empty());
+    }
+}
@@ -0,0 +1,626 @@
+//! Desktop notifications for the trays, daemons and CLI
+//!
+//! Notifications go through one [`NotificationCenter`] per process, reached
+//! with [`notify`], which puts them on screen the platform's way without
+//! blocking: a WinRT toast on Windows, Notification Center on macOS and the
+//! freedesktop notification service (`notify-send`, or `gdbus`) on Linux.
+//!
+//! Routine notifications may wait. While a call, a game, a presentation or
+//! the OS's own do-not-disturb is on, they are held and shown afterwards as
+//! one digest; past `notify_per_minute` the rest are batched the same way.
+//! Replies to something the user just did and alerts (memory leaks,
+//! critical temperatures) are always shown.
+
+#[cfg(target_os = "windows")]
+mod toast;
+
+#[cfg(target_os = "windows")]
+pub use toast::register_app_id;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::core::config::OptimizerConfig;
+
+/// Default for `notify_per_minute`
+pub const DEFAULT_PER_MINUTE: u32 = 3;
+
+/// Window the per-minute limit counts over
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Most held notifications spelled out in a digest
+const DIGEST_LINES: usize = 5;
+
+/// Name notifications are shown under
+#[cfg(target_os = "linux")]
+const APP_NAME: &str = "RuVector MemOpt";
+
+/// Whether a notification may wait
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    /// Results of automatic work; held while quiet and rate limited
+    Normal,
+    /// A reply to something the user just did
+    Reply,
+    /// An alert that can't wait
+    Critical,
+}
+
+/// Button on a notification, where the platform has them (Windows toasts)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Undo,
+    Details,
+}
+
+#[derive(Clone)]
+pub struct Notification {
+    pub title: String,
+    pub message: String,
+    pub urgency: Urgency,
+    /// Memory freed, for the icon and sound some platforms pick
+    pub freed_mb: Option<f64>,
+    pub actions: Vec<Action>,
+    on_action: Option<Arc<dyn Fn(Action) + Send + Sync>>,
+}
+
+impl Notification {
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            urgency: Urgency::Normal,
+            freed_mb: None,
+            actions: Vec::new(),
+            on_action: None,
+        }
+    }
+
+    pub fn reply(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { urgency: Urgency::Reply, ..Self::new(title, message) }
+    }
+
+    pub fn critical(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { urgency: Urgency::Critical, ..Self::new(title, message) }
+    }
+
+    pub fn with_freed(mut self, freed_mb: f64) -> Self {
+        self.freed_mb = Some(freed_mb);
+        self
+    }
+
+    /// Offer `actions` as buttons; `on_action` runs on a platform thread when
+    /// one is clicked. A notification held for a digest loses its buttons.
+    pub fn with_actions(mut self, actions: &[Action], on_action: impl Fn(Action) + Send + Sync + 'static) -> Self {
+        self.actions = actions.to_vec();
+        self.on_action = Some(Arc::new(on_action));
+        self
+    }
+
+    fn without_actions(&self) -> Self {
+        Self { actions: Vec::new(), on_action: None, ..self.clone() }
+    }
+}
+
+impl std::fmt::Debug for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notification")
+            .field("title", &self.title)
+            .field("message", &self.message)
+            .field("urgency", &self.urgency)
+            .field("freed_mb", &self.freed_mb)
+            .field("actions", &self.actions)
+            .finish_non_exhaustive()
+    }
+}
+/// Why notifications are held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quiet {
+    Call,
+    Game,
+    Presentation,
+    /// The OS's do-not-disturb or focus setting
+    DoNotDisturb,
+}
+
+impl std::fmt::Display for Quiet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quiet::Call => write!(f, "your call"),
+            Quiet::Game => write!(f, "your game"),
+            Quiet::Presentation => write!(f, "your presentation"),
+            Quiet::DoNotDisturb => write!(f, "Do Not Disturb"),
+        }
+    }
+}
+
+/// When routine notifications have to wait
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotifyPolicy {
+    /// Hold them during calls, games, presentations and do-not-disturb
+    pub respect_dnd: bool,
+    /// Shown per minute before the rest are batched; 0 for no limit
+    pub per_minute: u32,
+}
+
+impl Default for NotifyPolicy {
+    fn default() -> Self {
+        Self { respect_dnd: true, per_minute: DEFAULT_PER_MINUTE }
+    }
+}
+
+impl NotifyPolicy {
+    pub fn from_config(config: &OptimizerConfig) -> Self {
+        Self { respect_dnd: config.notify_respect_dnd, per_minute: config.notify_per_minute }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    policy: NotifyPolicy,
+    /// Reasons set by the app (Focus Mode, Game Mode)
+    modes: Vec<Quiet>,
+    /// Reason the OS reported at the last poll
+    system: Option<Quiet>,
+    held: Vec<Notification>,
+    /// Why the first held notification had to wait; `None` for the rate limit
+    held_during: Option<Quiet>,
+    /// When routine notifications were shown within the last minute
+    shown: VecDeque<Instant>,
+}
+
+impl State {
+    fn reason(&self) -> Option<Quiet> {
+        self.modes.first().copied().or(self.system)
+    }
+
+    /// The quiet reason, if the policy holds notifications for it
+    fn holding(&self) -> Option<Quiet> {
+        self.reason().filter(|_| self.policy.respect_dnd)
+    }
+
+    /// Whether the per-minute limit lets one more through at `now`
+    fn has_room(&mut self, now: Instant) -> bool {
+        while self.shown.front().is_some_and(|t| now.saturating_duration_since(*t) >= RATE_WINDOW) {
+            self.shown.pop_front();
+        }
+        self.policy.per_minute == 0 || self.shown.len() < self.policy.per_minute as usize
+    }
+}
+
+/// Shows notifications now or holds them for a digest
+pub struct NotificationCenter {
+    state: Mutex<State>,
+    show: Box<dyn Fn(&Notification) + Send + Sync>,
+}
+
+impl NotificationCenter {
+    /// `show` puts a notification on screen
+    pub fn new(show: impl Fn(&Notification) + Send + Sync + 'static) -> Self {
+        Self { state: Mutex::new(State::default()), show: Box::new(show) }
+    }
+
+    /// Show `notification`, or hold it for the digest if it has to wait
+    pub fn notify(&self, notification: Notification) {
+        self.notify_at(notification, Instant::now());
+    }
+
+    fn notify_at(&self, notification: Notification, now: Instant) {
+        if self.hold(&notification, now) {
+            // Earlier ones may have been held by the rate limit alone
+            self.update(|_| {}, now);
+        } else {
+            (self.show)(&notification);
+        }
+    }
+
+    /// Hold `notification` if it has to wait; returns whether it was held
+    fn hold(&self, notification: &Notification, now: Instant) -> bool {
+        if notification.urgency != Urgency::Normal {
+            return false;
+        }
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if let Some(reason) = state.holding() {
+            state.held_during.get_or_insert(reason);
+        } else if state.held.is_empty() && state.has_room(now) {
+            state.shown.push_back(now);
+            return false;
+        }
+        let repeat = state.held.iter().any(|n| n.title == notification.title && n.message == notification.message);
+        if !repeat {
+            state.held.push(notification.without_actions());
+        }
+        true
+    }
+
+    /// Start or stop holding notifications for `reason`
+    pub fn set_quiet(&self, reason: Quiet, on: bool) {
+        self.update(
+            |state| {
+                let present = state.modes.contains(&reason);
+                if on && !present {
+                    state.modes.push(reason);
+                } else if !on && present {
+                    state.modes.retain(|r| *r != reason);
+                }
+            },
+            Instant::now(),
+        );
+    }
+
+    /// Pick up the OS's do-not-disturb and presentation state and show what
+    /// the rate limit held; call on a status tick
+    pub fn poll_system(&self) {
+        let system = system_quiet();
+        self.update(|state| state.system = system, Instant::now());
+    }
+
+    pub fn set_policy(&self, policy: NotifyPolicy) {
+        self.update(|state| state.policy = policy, Instant::now());
+    }
+
+    /// Why notifications are held right now
+    pub fn quiet_reason(&self) -> Option<Quiet> {
+        self.state.lock().ok().and_then(|state| state.holding())
+    }
+
+    /// Apply `change`, then show the digest if nothing holds it any more
+    fn update(&self, change: impl FnOnce(&mut State), now: Instant) {
+        let digest = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            change(&mut state);
+            if state.held.is_empty() || state.holding().is_some() || !state.has_room(now) {
+                return;
+            }
+            state.shown.push_back(now);
+            let during = state.held_during.take();
+            digest(std::mem::take(&mut state.held), during)
+        };
+        (self.show)(&digest);
+    }
+}
+
+/// One notification summing up everything held, during `during` if quiet
+fn digest(mut held: Vec<Notification>, during: Option<Quiet>) -> Notification {
+    if held.len() == 1 {
+        return held.remove(0);
+    }
+    let mut lines: Vec<String> = held.iter().take(DIGEST_LINES).map(|n| format!("{}: {}", n.title, n.message)).collect();
+    if held.len() > DIGEST_LINES {
+        lines.push(format!("and {} more", held.len() - DIGEST_LINES));
+    }
+    let freed: f64 = held.iter().filter_map(|n| n.freed_mb).sum();
+    let title = match during {
+        Some(reason) => format!("{} notifications during {}", held.len(), reason),
+        None => format!("{} notifications", held.len()),
+    };
+    let digest = Notification::new(title, lines.join("\n"));
+    if freed > 0.0 {
+        digest.with_freed(freed)
+    } else {
+        digest
+    }
+}
+
+/// The process's notification center, under the policy in `config.toml`
+pub fn center() -> &'static NotificationCenter {
+    static CENTER: OnceLock<NotificationCenter> = OnceLock::new();
+    CENTER.get_or_init(|| {
+        let center = NotificationCenter::new(show);
+        center.set_policy(NotifyPolicy::from_config(&OptimizerConfig::load_or_default()));
+        center
+    })
+}
+
+/// Show a notification (non-blocking, auto-dismiss) unless it has to wait
+pub fn notify(notification: Notification) {
+    center().notify(notification);
+}
+
+static FALLBACK: OnceLock<fn(&Notification)> = OnceLock::new();
+
+/// Show notifications with `fallback` (an in-app banner, say) when the
+/// platform can't
+pub fn set_fallback(fallback: fn(&Notification)) {
+    let _ = FALLBACK.set(fallback);
+}
+
+/// Put a notification on screen from a worker thread
+fn show(notification: &Notification) {
+    let notification = notification.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = deliver(&notification) {
+            tracing::debug!("Notification not delivered: {}", e);
+            match FALLBACK.get() {
+                Some(fallback) => fallback(&notification),
+                None => tracing::info!("{}: {}", notification.title, notification.message),
+            }
+        }
+    });
+}
+
+/// A toast, or a notification-area balloon (no buttons) where WinRT can't
+#[cfg(target_os = "windows")]
+fn deliver(notification: &Notification) -> Result<(), String> {
+    let Notification { title, message, actions, on_action, .. } = notification;
+    let on_action = on_action.clone();
+    toast::show(title, message, actions, move |action| {
+        if let Some(on_action) = &on_action {
+            on_action(action);
+        }
+    })
+    .or_else(|e| {
+        tracing::debug!("Toast failed ({}), using balloon", e);
+        toast::show_balloon(title, message)
+    })
+}
+
+/// A Notification Center banner with a sound picked by the memory freed;
+/// alerts use a dialog that Focus doesn't hide and that closes by itself
+#[cfg(target_os = "macos")]
+fn deliver(notification: &Notification) -> Result<(), String> {
+    let title = applescript_string(&notification.title);
+    let message = applescript_string(&notification.message);
+    let script = if notification.urgency == Urgency::Critical {
+        format!("display alert {} message {} as critical giving up after 15", title, message)
+    } else {
+        let sound = match notification.freed_mb {
+            Some(freed) if freed > 100.0 => "Glass",
+            Some(freed) if freed > 0.0 => "Pop",
+            _ => "Blow",
+        };
+        format!("display notification {} with title {} sound name \"{}\"", message, title, sound)
+    };
+    run("osascript", &["-e", &script])
+}
+
+/// The freedesktop notification service, through `notify-send` or, where
+/// libnotify's tools aren't installed, `gdbus`
+#[cfg(target_os = "linux")]
+fn deliver(notification: &Notification) -> Result<(), String> {
+    let (urgency, level) = match notification.urgency {
+        Urgency::Normal => ("low", 0),
+        Urgency::Reply => ("normal", 1),
+        Urgency::Critical => ("critical", 2),
+    };
+    let app_name = format!("--app-name={}", APP_NAME);
+    let urgency = format!("--urgency={}", urgency);
+    run("notify-send", &[&app_name, &urgency, &notification.title, &notification.message]).or_else(|_| {
+        let hints = format!("{{'urgency': <byte {}>}}", level);
+        run(
+            "gdbus",
+            &[
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.Notifications",
+                "--object-path",
+                "/org/freedesktop/Notifications",
+                "--method",
+                "org.freedesktop.Notifications.Notify",
+                &gvariant_string(APP_NAME),
+                "0",
+                "''",
+                &gvariant_string(&notification.title),
+                &gvariant_string(&notification.message),
+                "[]",
+                &hints,
+                "-1",
+            ],
+        )
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn deliver(_notification: &Notification) -> Result<(), String> {
+    Err("no desktop notifications on this platform".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = std::process::Command::new(program)
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", program, status))
+    }
+}
+
+/// AppleScript string literal, on one line
+#[cfg(any(target_os = "macos", test))]
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', " • "))
+}
+
+/// GVariant text-format string, as `gdbus call` parses its arguments
+#[cfg(any(target_os = "linux", test))]
+fn gvariant_string(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// The shell's notification state: fullscreen apps and presentation mode
+/// count as presenting, quiet time as do-not-disturb
+#[cfg(target_os = "windows")]
+fn system_quiet() -> Option<Quiet> {
+    use windows::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME, QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(QUNS_PRESENTATION_MODE) | Ok(QUNS_BUSY) => Some(Quiet::Presentation),
+        Ok(QUNS_RUNNING_D3D_FULL_SCREEN) => Some(Quiet::Game),
+        Ok(QUNS_QUIET_TIME) => Some(Quiet::DoNotDisturb),
+        _ => None,
+    }
+}
+
+/// An active Focus (macOS 12+) or the older Do Not Disturb switch
+#[cfg(target_os = "macos")]
+fn system_quiet() -> Option<Quiet> {
+    let home = std::env::var_os("HOME")?;
+    let assertions = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    let focus = match std::fs::read_to_string(assertions) {
+        Ok(json) => focus_active(&json),
+        Err(_) => std::process::Command::new("defaults")
+            .args(["-currentHost", "read", "com.apple.notificationcenterui", "doNotDisturb"])
+            .output()
+            .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "1"),
+    };
+    focus.then_some(Quiet::DoNotDisturb)
+}
+
+/// GNOME's do-not-disturb hides banners
+#[cfg(target_os = "linux")]
+fn system_quiet() -> Option<Quiet> {
+    let out = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .ok()?;
+    (String::from_utf8_lossy(&out.stdout).trim() == "false").then_some(Quiet::DoNotDisturb)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn system_quiet() -> Option<Quiet> {
+    None
+}
+
+/// Whether `Assertions.json` holds a Focus assertion
+#[cfg(any(target_os = "macos", test))]
+fn focus_active(json: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return false;
+    };
+    value["data"].as_array().is_some_and(|data| {
+        data.iter()
+            .any(|entry| entry["storeAssertionRecords"].as_array().is_some_and(|records| !records.is_empty()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn center() -> (NotificationCenter, Arc<Mutex<Vec<Notification>>>) {
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let sink = shown.clone();
+        (NotificationCenter::new(move |n| sink.lock().unwrap().push(n.clone())), shown)
+    }
+
+    #[test]
+    fn test_holds_until_quiet_ends() {
+        let (center, shown) = center();
+        center.notify(Notification::new("Profile", "Switched to battery"));
+        assert_eq!(shown.lock().unwrap().len(), 1);
+
+        center.set_quiet(Quiet::Game, true);
+        center.set_quiet(Quiet::Call, true);
+        center.notify(Notification::new("Memory Optimized!", "Freed 300 MB").with_freed(300.0));
+        center.notify(Notification::new("Rule fired", "Trimmed browsers").with_freed(200.0));
+        center.notify(Notification::reply("Optimization Undone", "Resumed Slack"));
+        center.notify(Notification::critical("Possible leak", "node grows 40 MB/min"));
+        assert_eq!(shown.lock().unwrap().len(), 3);
+        assert_eq!(center.quiet_reason(), Some(Quiet::Game));
+
+        center.set_quiet(Quiet::Game, false);
+        assert_eq!(shown.lock().unwrap().len(), 3);
+        center.set_quiet(Quiet::Call, false);
+
+        let shown = shown.lock().unwrap();
+        assert_eq!(shown.len(), 4);
+        let digest = &shown[3];
+        assert_eq!(digest.title, "2 notifications during your game");
+        assert_eq!(digest.message, "Memory Optimized!: Freed 300 MB\nRule fired: Trimmed browsers");
+        assert_eq!(digest.freed_mb, Some(500.0));
+        assert_eq!(center.quiet_reason(), None);
+    }
+
+    #[test]
+    fn test_single_held_notification_is_shown_as_is() {
+        let (center, shown) = center();
+        center.set_quiet(Quiet::Presentation, true);
+        center.notify(Notification::new("Profile", "Switched to meetings"));
+        center.set_quiet(Quiet::Presentation, false);
+        let messages: Vec<String> = shown.lock().unwrap().iter().map(|n| n.message.clone()).collect();
+        assert_eq!(messages, vec!["Switched to meetings"]);
+
+        // Nothing held, nothing to show
+        center.set_quiet(Quiet::Presentation, true);
+        center.set_quiet(Quiet::Presentation, false);
+        assert_eq!(shown.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_digest_is_capped() {
+        let held: Vec<Notification> = (0..8).map(|i| Notification::new(format!("N{}", i), "x")).collect();
+        let digest = digest(held, Some(Quiet::DoNotDisturb));
+        assert_eq!(digest.title, "8 notifications during Do Not Disturb");
+        assert_eq!(digest.message.lines().count(), DIGEST_LINES + 1);
+        assert!(digest.message.ends_with("and 3 more"));
+        assert_eq!(digest.freed_mb, None);
+    }
+
+    #[test]
+    fn test_rate_limit_batches_the_rest() {
+        let (center, shown) = center();
+        center.set_policy(NotifyPolicy { respect_dnd: true, per_minute: 2 });
+        let start = Instant::now();
+        for i in 0..3 {
+            center.notify_at(Notification::new("Rule fired", format!("Trimmed {}", i)), start);
+        }
+        center.notify_at(Notification::new("Rule fired", "Trimmed 2"), start);
+        center.notify_at(Notification::new("Rule fired", "Trimmed 3"), start);
+        center.notify_at(Notification::reply("Optimization Undone", "Resumed Slack"), start);
+        assert_eq!(shown.lock().unwrap().len(), 3);
+
+        center.update(|_| {}, start + Duration::from_secs(30));
+        assert_eq!(shown.lock().unwrap().len(), 3);
+        center.update(|_| {}, start + RATE_WINDOW);
+        let shown = shown.lock().unwrap();
+        assert_eq!(shown.len(), 4);
+        assert_eq!(shown[3].title, "2 notifications");
+        assert_eq!(shown[3].message, "Rule fired: Trimmed 2\nRule fired: Trimmed 3");
+    }
+
+    #[test]
+    fn test_policy_can_ignore_quiet() {
+        let (center, shown) = center();
+        center.set_policy(NotifyPolicy { respect_dnd: false, per_minute: 0 });
+        center.set_quiet(Quiet::Call, true);
+        center.notify(Notification::new("Profile", "Switched to battery").with_actions(&[Action::Undo], |_| {}));
+        assert_eq!(shown.lock().unwrap()[0].actions, vec![Action::Undo]);
+        assert_eq!(center.quiet_reason(), None);
+
+        // Turning the policy back on holds again, and drops the buttons
+        center.set_policy(NotifyPolicy::default());
+        center.notify(Notification::new("Profile", "Switched to AC").with_actions(&[Action::Undo], |_| {}));
+        center.set_quiet(Quiet::Call, false);
+        let shown = shown.lock().unwrap();
+        assert_eq!(shown.len(), 2);
+        assert!(shown[1].actions.is_empty());
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(applescript_string("Say \"hi\"\nC:\\"), r#""Say \"hi\" • C:\\""#);
+        assert_eq!(gvariant_string("it's C:\\"), r#"'it\'s C:\\'"#);
+    }
+
+    #[test]
+    fn test_focus_active() {
+        let on = r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":{"assertionDetailsModeIdentifier":"com.apple.donotdisturb.mode.default"}}]}]}"#;
+        assert!(focus_active(on));
+        assert!(!focus_active(r#"{"data":[{}]}"#));
+        assert!(!focus_active(r#"{"data":[{"storeAssertionRecords":[]}]}"#));
+        assert!(!focus_active("not json"));
+    }
+}
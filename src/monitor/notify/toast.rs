@@ -0,0 +1,193 @@
+//! Native Windows toast notifications
+//!
+//! Shows toasts through `Windows.UI.Notifications` under the app's own
+//! AppUserModelID, with optional "Undo"/"Details" buttons whose clicks are
+//! reported back while the process is running. When WinRT is unavailable
+//! (Windows 7/8, or notifications blocked for the AUMID), a classic
+//! `Shell_NotifyIcon` balloon is shown instead.
+
+#![cfg(windows)]
+
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+
+use windows::core::{w, IInspectable, Interface, HSTRING, PCWSTR};
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::Foundation::TypedEventHandler;
+use windows::UI::Notifications::{ToastActivatedEventArgs, ToastNotification, ToastNotificationManager};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegCreateKeyExW, RegSetValueExW, HKEY, HKEY_CURRENT_USER, KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ,
+};
+use windows::Win32::UI::Shell::{
+    SetCurrentProcessExplicitAppUserModelID, Shell_NotifyIconW, NIF_INFO, NIIF_INFO, NIM_ADD, NIM_DELETE,
+    NOTIFYICONDATAW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{CreateWindowExW, DestroyWindow, HWND_MESSAGE, WINDOW_EX_STYLE, WINDOW_STYLE};
+
+use super::Action;
+
+/// AppUserModelID the toasts are shown under
+pub const APP_ID: &str = "RuVector.MemOpt";
+/// Name Windows shows on the toast and in notification settings
+const DISPLAY_NAME: &str = "RuVector MemOpt";
+/// How long the fallback balloon's icon stays registered
+const BALLOON_SHOW_MS: u64 = 6000;
+
+/// Toast button label and the argument its click reports
+fn button(action: Action) -> (&'static str, &'static str) {
+    match action {
+        Action::Undo => ("Undo", "undo"),
+        Action::Details => ("Details", "details"),
+    }
+}
+
+fn action_from_argument(argument: &str) -> Option<Action> {
+    [Action::Undo, Action::Details].into_iter().find(|a| button(*a).1 == argument)
+}
+
+/// Register the AUMID for the current user and adopt it for this process
+///
+/// Unpackaged apps need the `AppUserModelId` key before Windows will show
+/// their toasts; call once at startup.
+pub fn register_app_id() -> Result<(), String> {
+    let subkey = to_wide(&format!("Software\\Classes\\AppUserModelId\\{}", APP_ID));
+    let display_name = to_wide(DISPLAY_NAME);
+    unsafe {
+        let mut key = HKEY::default();
+        RegCreateKeyExW(
+            HKEY_CURRENT_USER,
+            PCWSTR(subkey.as_ptr()),
+            0,
+            None,
+            REG_OPTION_NON_VOLATILE,
+            KEY_WRITE,
+            None,
+            &mut key,
+            None,
+        )
+        .ok()
+        .map_err(|e| format!("Failed to register {}: {}", APP_ID, e))?;
+        let bytes = std::slice::from_raw_parts(display_name.as_ptr() as *const u8, display_name.len() * 2);
+        let result = RegSetValueExW(key, w!("DisplayName"), 0, REG_SZ, Some(bytes)).ok();
+        let _ = RegCloseKey(key);
+        result.map_err(|e| format!("Failed to register {}: {}", APP_ID, e))?;
+
+        SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(APP_ID)).map_err(|e| e.to_string())
+    }
+}
+
+/// Show a toast; `on_action` runs on a WinRT thread when a button is clicked
+pub fn show<F>(title: &str, message: &str, actions: &[Action], on_action: F) -> Result<(), String>
+where
+    F: Fn(Action) + Send + 'static,
+{
+    show_toast(title, message, actions, on_action).map_err(|e| e.message().to_string())
+}
+
+fn show_toast<F>(title: &str, message: &str, actions: &[Action], on_action: F) -> windows::core::Result<()>
+where
+    F: Fn(Action) + Send + 'static,
+{
+    let xml = XmlDocument::new()?;
+    xml.LoadXml(&HSTRING::from(toast_xml(title, message, actions)))?;
+    let toast = ToastNotification::CreateToastNotification(&xml)?;
+
+    if !actions.is_empty() {
+        toast.Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(
+            move |_, args: &Option<IInspectable>| {
+                let Some(args) = args else { return Ok(()) };
+                let arguments = args.cast::<ToastActivatedEventArgs>()?.Arguments()?;
+                if let Some(action) = action_from_argument(&arguments.to_string()) {
+                    on_action(action);
+                }
+                Ok(())
+            },
+        ))?;
+    }
+
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(APP_ID))?.Show(&toast)
+}
+
+/// Toast content in the generic template, with one button per action
+fn toast_xml(title: &str, message: &str, actions: &[Action]) -> String {
+    let buttons: String = actions
+        .iter()
+        .map(|a| {
+            let (label, argument) = button(*a);
+            format!(r#"<action content="{}" arguments="{}" activationType="foreground"/>"#, label, argument)
+        })
+        .collect();
+    format!(
+        r#"<toast><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual>{}</toast>"#,
+        escape_xml(title),
+        escape_xml(message),
+        if buttons.is_empty() { String::new() } else { format!("<actions>{}</actions>", buttons) }
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Classic balloon from a short-lived notification-area icon
+///
+/// Blocks for a few seconds so the icon outlives the balloon; call from a
+/// worker thread.
+pub fn show_balloon(title: &str, message: &str) -> Result<(), String> {
+    unsafe {
+        let hwnd: HWND = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            w!("STATIC"),
+            w!(""),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| format!("Failed to create balloon window: {}", e))?;
+
+        let mut data = NOTIFYICONDATAW {
+            cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: hwnd,
+            uID: 1,
+            uFlags: NIF_INFO,
+            dwInfoFlags: NIIF_INFO,
+            ..Default::default()
+        };
+        copy_truncated(&mut data.szInfoTitle, title);
+        copy_truncated(&mut data.szInfo, message);
+
+        let shown = Shell_NotifyIconW(NIM_ADD, &data).as_bool();
+        if shown {
+            std::thread::sleep(std::time::Duration::from_millis(BALLOON_SHOW_MS));
+            let _ = Shell_NotifyIconW(NIM_DELETE, &data);
+        }
+        let _ = DestroyWindow(hwnd);
+        if shown {
+            Ok(())
+        } else {
+            Err("Shell_NotifyIcon failed".to_string())
+        }
+    }
+}
+
+/// Copy into a fixed UTF-16 buffer, leaving room for the terminator
+fn copy_truncated(buffer: &mut [u16], text: &str) {
+    for (slot, unit) in buffer.iter_mut().zip(OsStr::new(text).encode_wide().take(buffer.len() - 1)) {
+        *slot = unit;
+    }
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+}
@@ -0,0 +1,453 @@
+//! Memory pressure event subscription
+//!
+//! `PressureWatcher` turns raw memory readings into typed transitions
+//! (`EnterHigh`, `EnterCritical`, `Recovered`). Each platform contributes a
+//! native signal on top of the memory load thresholds:
+//! - Windows: low-memory resource notification (`CreateMemoryResourceNotification`)
+//! - Linux: PSI (`/proc/pressure/memory`) with a poll trigger where permitted
+//! - macOS: `kern.memorystatus_vm_pressure_level`
+//!
+//! Events are delivered through `PressureStream`, which implements
+//! `futures_core::Stream` and also offers an inherent `next()`.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use sysinfo::System;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::core::config::OptimizerConfig;
+
+/// Load must drop this many points below a threshold before the level
+/// is lowered, so readings hovering at the threshold don't flap
+pub const DEFAULT_RECOVERY_MARGIN: u32 = 5;
+
+/// Pressure level, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PressureLevel {
+    Normal,
+    High,
+    Critical,
+}
+
+impl std::fmt::Display for PressureLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PressureLevel::Normal => write!(f, "normal"),
+            PressureLevel::High => write!(f, "high"),
+            PressureLevel::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Memory state at the time of an event
+#[derive(Debug, Clone)]
+pub struct PressureReading {
+    pub timestamp_ms: u64,
+    pub load_percent: u32,
+    pub available_mb: f64,
+    /// Level reported by the OS signal alone
+    pub native_level: PressureLevel,
+}
+
+/// Pressure level transition
+#[derive(Debug, Clone)]
+pub enum PressureEvent {
+    EnterHigh(PressureReading),
+    EnterCritical(PressureReading),
+    Recovered(PressureReading),
+}
+
+impl PressureEvent {
+    pub fn reading(&self) -> &PressureReading {
+        match self {
+            PressureEvent::EnterHigh(r) | PressureEvent::EnterCritical(r) | PressureEvent::Recovered(r) => r,
+        }
+    }
+
+    /// Level after the transition
+    pub fn level(&self) -> PressureLevel {
+        match self {
+            PressureEvent::EnterHigh(_) => PressureLevel::High,
+            PressureEvent::EnterCritical(_) => PressureLevel::Critical,
+            PressureEvent::Recovered(_) => PressureLevel::Normal,
+        }
+    }
+}
+
+/// Load thresholds for classifying readings
+#[derive(Debug, Clone, Copy)]
+pub struct PressureThresholds {
+    pub high_percent: u32,
+    pub critical_percent: u32,
+    pub recovery_margin: u32,
+}
+
+impl PressureThresholds {
+    pub fn from_config(config: &OptimizerConfig) -> Self {
+        Self {
+            high_percent: config.pressure_threshold,
+            critical_percent: config.critical_threshold,
+            recovery_margin: DEFAULT_RECOVERY_MARGIN,
+        }
+    }
+}
+
+impl Default for PressureThresholds {
+    fn default() -> Self {
+        Self::from_config(&OptimizerConfig::default())
+    }
+}
+
+/// Level state machine with hysteresis
+#[derive(Debug)]
+pub struct PressureTracker {
+    thresholds: PressureThresholds,
+    level: PressureLevel,
+}
+
+impl PressureTracker {
+    pub fn new(thresholds: PressureThresholds) -> Self {
+        Self { thresholds, level: PressureLevel::Normal }
+    }
+
+    pub fn level(&self) -> PressureLevel {
+        self.level
+    }
+
+    /// Feed a reading, returning an event if the level changed
+    ///
+    /// Leaving `Critical` for `High` is not reported; `Recovered` is only
+    /// emitted once pressure is back to `Normal`.
+    pub fn update(&mut self, reading: PressureReading) -> Option<PressureEvent> {
+        let t = &self.thresholds;
+        let load = reading.load_percent;
+        // Stay at the current level until load clears the threshold by the margin
+        let clears = |threshold: u32| load + t.recovery_margin < threshold;
+
+        let load_level = if load >= t.critical_percent
+            || (self.level == PressureLevel::Critical && !clears(t.critical_percent))
+        {
+            PressureLevel::Critical
+        } else if load >= t.high_percent
+            || (self.level >= PressureLevel::High && !clears(t.high_percent))
+        {
+            PressureLevel::High
+        } else {
+            PressureLevel::Normal
+        };
+        let next = load_level.max(reading.native_level);
+
+        let previous = std::mem::replace(&mut self.level, next);
+        match (previous, next) {
+            (p, PressureLevel::Critical) if p != PressureLevel::Critical => {
+                Some(PressureEvent::EnterCritical(reading))
+            }
+            (PressureLevel::Normal, PressureLevel::High) => Some(PressureEvent::EnterHigh(reading)),
+            (p, PressureLevel::Normal) if p != PressureLevel::Normal => {
+                Some(PressureEvent::Recovered(reading))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Watches memory pressure and emits level transitions
+pub struct PressureWatcher {
+    thresholds: PressureThresholds,
+    interval: Duration,
+}
+
+impl PressureWatcher {
+    pub fn new(thresholds: PressureThresholds) -> Self {
+        Self { thresholds, interval: Duration::from_secs(1) }
+    }
+
+    pub fn from_config(config: &OptimizerConfig) -> Self {
+        Self::new(PressureThresholds::from_config(config))
+    }
+
+    /// Maximum time between readings when no native signal fires
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Start watching on a background thread
+    ///
+    /// The thread exits once the returned stream is dropped.
+    pub fn subscribe(&self) -> PressureStream {
+        let (tx, rx) = mpsc::channel(16);
+        let thresholds = self.thresholds;
+        let interval = self.interval;
+
+        std::thread::Builder::new()
+            .name("pressure-watcher".into())
+            .spawn(move || watch(thresholds, interval, tx))
+            .expect("failed to spawn pressure watcher thread");
+
+        PressureStream { rx }
+    }
+}
+
+impl Default for PressureWatcher {
+    fn default() -> Self {
+        Self::new(PressureThresholds::default())
+    }
+}
+
+/// Stream of pressure events
+pub struct PressureStream {
+    rx: mpsc::Receiver<PressureEvent>,
+}
+
+impl PressureStream {
+    /// Wait for the next event (`None` if the watcher stopped)
+    pub async fn next(&mut self) -> Option<PressureEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl futures_core::Stream for PressureStream {
+    type Item = PressureEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+fn watch(thresholds: PressureThresholds, interval: Duration, tx: mpsc::Sender<PressureEvent>) {
+    let mut tracker = PressureTracker::new(thresholds);
+    let mut signal = NativeSignal::new();
+    let mut system = System::new();
+
+    loop {
+        let native_level = signal.wait(interval);
+
+        system.refresh_memory();
+        let total = system.total_memory() as f64 / 1024.0 / 1024.0;
+        let available = system.available_memory() as f64 / 1024.0 / 1024.0;
+        let load_percent = if total > 0.0 {
+            (((total - available) / total) * 100.0) as u32
+        } else {
+            0
+        };
+
+        let reading = PressureReading {
+            timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+            load_percent,
+            available_mb: available,
+            native_level,
+        };
+
+        if let Some(event) = tracker.update(reading) {
+            debug!("Memory pressure: {:?}", event);
+            if tx.blocking_send(event).is_err() {
+                return;
+            }
+        } else if tx.is_closed() {
+            return;
+        }
+    }
+}
+
+// ============================================================================
+// Native signals
+// ============================================================================
+
+/// Low-memory resource notification
+#[cfg(target_os = "windows")]
+struct NativeSignal {
+    handle: Option<windows::Win32::Foundation::HANDLE>,
+    last_signaled: bool,
+}
+
+#[cfg(target_os = "windows")]
+impl NativeSignal {
+    fn new() -> Self {
+        use windows::Win32::System::Memory::{CreateMemoryResourceNotification, LowMemoryResourceNotification};
+
+        let handle = unsafe { CreateMemoryResourceNotification(LowMemoryResourceNotification) }.ok();
+        Self { handle, last_signaled: false }
+    }
+
+    fn wait(&mut self, timeout: Duration) -> PressureLevel {
+        use windows::Win32::Foundation::BOOL;
+        use windows::Win32::System::Memory::QueryMemoryResourceNotification;
+        use windows::Win32::System::Threading::WaitForSingleObject;
+
+        let Some(handle) = self.handle else {
+            std::thread::sleep(timeout);
+            return PressureLevel::Normal;
+        };
+
+        // The notification stays signaled while memory is low, so don't
+        // spin on it once it has fired
+        if self.last_signaled {
+            std::thread::sleep(timeout);
+        } else {
+            unsafe { WaitForSingleObject(handle, timeout.as_millis() as u32) };
+        }
+
+        let mut low = BOOL(0);
+        let signaled = unsafe { QueryMemoryResourceNotification(handle, &mut low) }.is_ok() && low.as_bool();
+        self.last_signaled = signaled;
+        if signaled { PressureLevel::Critical } else { PressureLevel::Normal }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for NativeSignal {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = unsafe { windows::Win32::Foundation::CloseHandle(handle) };
+        }
+    }
+}
+
+/// PSI averages with an optional poll trigger
+#[cfg(target_os = "linux")]
+struct NativeSignal {
+    trigger: Option<std::fs::File>,
+}
+
+/// PSI `some avg10` (percent of time any task stalled) that counts as high
+#[cfg(target_os = "linux")]
+const PSI_HIGH_SOME_AVG10: f64 = 20.0;
+/// PSI `full avg10` (percent of time all tasks stalled) that counts as critical
+#[cfg(target_os = "linux")]
+const PSI_CRITICAL_FULL_AVG10: f64 = 10.0;
+
+#[cfg(target_os = "linux")]
+impl NativeSignal {
+    fn new() -> Self {
+        use std::io::Write;
+
+        // Wake when tasks stall on memory for 150ms within a 2s window.
+        // Unprivileged triggers need a window that is a multiple of 2s.
+        let trigger = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(crate::platform::linux::memory::PSI_MEMORY_PATH)
+            .and_then(|mut f| f.write_all(b"some 150000 2000000\0").map(|_| f))
+            .map_err(|e| debug!("PSI trigger unavailable, polling instead: {}", e))
+            .ok();
+        Self { trigger }
+    }
+
+    fn wait(&mut self, timeout: Duration) -> PressureLevel {
+        use std::os::unix::io::AsRawFd;
+
+        match &self.trigger {
+            Some(file) => {
+                let mut pfd = libc::pollfd { fd: file.as_raw_fd(), events: libc::POLLPRI, revents: 0 };
+                let n = unsafe { libc::poll(&mut pfd, 1, timeout.as_millis() as libc::c_int) };
+                if n < 0 || pfd.revents & libc::POLLERR != 0 {
+                    debug!("PSI trigger failed, falling back to polling");
+                    self.trigger = None;
+                }
+            }
+            None => std::thread::sleep(timeout),
+        }
+
+        match crate::platform::linux::LinuxMemoryOptimizer::read_psi() {
+            Some(psi) if psi.full_avg10 >= PSI_CRITICAL_FULL_AVG10 => PressureLevel::Critical,
+            Some(psi) if psi.some_avg10 >= PSI_HIGH_SOME_AVG10 => PressureLevel::High,
+            _ => PressureLevel::Normal,
+        }
+    }
+}
+
+/// Kernel VM pressure level
+#[cfg(target_os = "macos")]
+struct NativeSignal;
+
+#[cfg(target_os = "macos")]
+impl NativeSignal {
+    fn new() -> Self {
+        Self
+    }
+
+    fn wait(&mut self, timeout: Duration) -> PressureLevel {
+        std::thread::sleep(timeout);
+
+        let mut level: libc::c_int = 0;
+        let mut size = std::mem::size_of::<libc::c_int>();
+        let ret = unsafe {
+            libc::sysctlbyname(
+                b"kern.memorystatus_vm_pressure_level\0".as_ptr() as *const libc::c_char,
+                &mut level as *mut _ as *mut libc::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+
+        // 1 = normal, 2 = warn, 4 = critical
+        match (ret, level) {
+            (0, 4) => PressureLevel::Critical,
+            (0, 2) => PressureLevel::High,
+            _ => PressureLevel::Normal,
+        }
+    }
+}
+
+/// No native signal: thresholds only
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+struct NativeSignal;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+impl NativeSignal {
+    fn new() -> Self {
+        Self
+    }
+
+    fn wait(&mut self, timeout: Duration) -> PressureLevel {
+        std::thread::sleep(timeout);
+        PressureLevel::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(load_percent: u32) -> PressureReading {
+        PressureReading {
+            timestamp_ms: 0,
+            load_percent,
+            available_mb: 0.0,
+            native_level: PressureLevel::Normal,
+        }
+    }
+
+    #[test]
+    fn test_transitions_with_hysteresis() {
+        let mut tracker = PressureTracker::new(PressureThresholds {
+            high_percent: 80,
+            critical_percent: 95,
+            recovery_margin: 5,
+        });
+
+        assert!(tracker.update(reading(70)).is_none());
+        assert!(matches!(tracker.update(reading(82)), Some(PressureEvent::EnterHigh(_))));
+        // Hovering just under the threshold keeps the level
+        assert!(tracker.update(reading(78)).is_none());
+        assert!(matches!(tracker.update(reading(96)), Some(PressureEvent::EnterCritical(_))));
+        // Critical -> High is not reported
+        assert!(tracker.update(reading(85)).is_none());
+        assert_eq!(tracker.level(), PressureLevel::High);
+        assert!(matches!(tracker.update(reading(60)), Some(PressureEvent::Recovered(_))));
+    }
+
+    #[test]
+    fn test_native_signal_escalates() {
+        let mut tracker = PressureTracker::new(PressureThresholds::default());
+        let event = tracker.update(PressureReading {
+            native_level: PressureLevel::Critical,
+            ..reading(40)
+        });
+        assert!(matches!(event, Some(PressureEvent::EnterCritical(_))));
+    }
+}
@@ -0,0 +1,223 @@
+//! Incremental process refresh for the background monitors
+//!
+//! `refresh_processes(All, true)` reads memory, CPU and disk usage of every
+//! process, plus the executable path of every one it hasn't seen. The tray
+//! and the daemons used to do that from half a dozen detectors, each with
+//! its own `System`, some of them new on every call so every path was read
+//! again. [`ProcessRefresher`] keeps one list up to date instead: a pass
+//! reads memory and CPU for every process and the rest only for processes
+//! that started since the last pass (a reused PID counts as started). Every
+//! [`FULL_REFRESH_EVERY`] passes it reads everything, which catches
+//! processes that exec'd into something else.
+//!
+//! [`with_processes`] shares one refresher in the process. Each detector
+//! passes how old a list it can work with, usually its own interval, so the
+//! game check every 5 seconds and the agent every 15 share passes instead of
+//! each causing their own.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// Passes between refreshes of everything
+pub const FULL_REFRESH_EVERY: u64 = 20;
+
+/// Time between debug logs of the refresh cost
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// What every pass reads
+fn light() -> ProcessRefreshKind {
+    ProcessRefreshKind::nothing().with_memory().with_cpu()
+}
+
+/// What new processes and full passes read; the same as `refresh_processes`
+fn full() -> ProcessRefreshKind {
+    light().with_disk_usage().with_exe(UpdateKind::OnlyIfNotSet)
+}
+
+/// Processes that started and exited between two passes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Churn {
+    pub started: Vec<u32>,
+    pub exited: Vec<u32>,
+}
+
+impl Churn {
+    /// Difference between two `pid -> start time` maps; a PID whose start
+    /// time changed was reused, and counts as exited and started
+    pub fn between(previous: &HashMap<u32, u64>, current: &HashMap<u32, u64>) -> Self {
+        let mut started: Vec<u32> =
+            current.iter().filter(|(pid, start)| previous.get(pid) != Some(*start)).map(|(pid, _)| *pid).collect();
+        let mut exited: Vec<u32> =
+            previous.iter().filter(|(pid, start)| current.get(pid) != Some(*start)).map(|(pid, _)| *pid).collect();
+        started.sort_unstable();
+        exited.sort_unstable();
+        Self { started, exited }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.started.is_empty() && self.exited.is_empty()
+    }
+}
+
+/// What refreshing has cost so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct RefreshStats {
+    pub passes: u64,
+    pub full_passes: u64,
+    pub started: u64,
+    pub exited: u64,
+    /// Time spent refreshing
+    pub busy_ms: f64,
+    /// Time since the first pass
+    pub elapsed_secs: f64,
+}
+
+impl RefreshStats {
+    /// Average share of one core spent refreshing, in percent
+    pub fn cpu_percent(&self) -> f64 {
+        if self.elapsed_secs > 0.0 {
+            self.busy_ms / 10.0 / self.elapsed_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A process list kept up to date incrementally
+pub struct ProcessRefresher {
+    system: System,
+    /// Start time of every process at the last pass
+    known: HashMap<u32, u64>,
+    stats: RefreshStats,
+    first_pass: Option<Instant>,
+    last_log: Option<Instant>,
+}
+
+impl ProcessRefresher {
+    pub fn new() -> Self {
+        Self { system: System::new(), known: HashMap::new(), stats: RefreshStats::default(), first_pass: None, last_log: None }
+    }
+
+    /// Bring the list up to date; returns what started and exited since the
+    /// last pass
+    pub fn refresh(&mut self) -> Churn {
+        let started_at = Instant::now();
+        let full_pass = self.stats.passes % FULL_REFRESH_EVERY == 0;
+        let kind = if full_pass { full() } else { light() };
+        self.system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+
+        let current: HashMap<u32, u64> =
+            self.system.processes().iter().map(|(pid, p)| (pid.as_u32(), p.start_time())).collect();
+        let churn = Churn::between(&self.known, &current);
+        if !full_pass && !churn.started.is_empty() {
+            let pids: Vec<Pid> = churn.started.iter().map(|&pid| Pid::from_u32(pid)).collect();
+            self.system.refresh_processes_specifics(ProcessesToUpdate::Some(&pids), false, full());
+        }
+        self.known = current;
+
+        let first_pass = *self.first_pass.get_or_insert(started_at);
+        self.stats.passes += 1;
+        self.stats.full_passes += full_pass as u64;
+        self.stats.started += churn.started.len() as u64;
+        self.stats.exited += churn.exited.len() as u64;
+        self.stats.busy_ms += started_at.elapsed().as_secs_f64() * 1000.0;
+        self.stats.elapsed_secs = first_pass.elapsed().as_secs_f64();
+
+        if self.last_log.map_or(true, |t| t.elapsed() > STATS_LOG_INTERVAL) {
+            self.last_log = Some(Instant::now());
+            let s = &self.stats;
+            tracing::debug!(
+                "Process refresh: {} passes ({} full), {} started, {} exited, {:.2}% of a core",
+                s.passes,
+                s.full_passes,
+                s.started,
+                s.exited,
+                s.cpu_percent()
+            );
+        }
+        churn
+    }
+
+    pub fn system(&self) -> &System {
+        &self.system
+    }
+
+    pub fn stats(&self) -> RefreshStats {
+        self.stats
+    }
+}
+
+impl Default for ProcessRefresher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Shared {
+    refresher: ProcessRefresher,
+    refreshed: Option<Instant>,
+}
+
+fn shared() -> &'static Mutex<Shared> {
+    static SHARED: OnceLock<Mutex<Shared>> = OnceLock::new();
+    SHARED.get_or_init(|| Mutex::new(Shared { refresher: ProcessRefresher::new(), refreshed: None }))
+}
+
+/// Run `f` on the shared process list, refreshing it first if it is older
+/// than `max_age`
+///
+/// The list is locked while `f` runs, so `f` should collect what it needs
+/// and return.
+pub fn with_processes<R>(max_age: Duration, f: impl FnOnce(&System) -> R) -> R {
+    let mut shared = shared().lock().unwrap_or_else(PoisonError::into_inner);
+    if shared.refreshed.map_or(true, |t| t.elapsed() >= max_age) {
+        shared.refresher.refresh();
+        shared.refreshed = Some(Instant::now());
+    }
+    f(shared.refresher.system())
+}
+
+/// Cost of the shared refresher so far
+pub fn stats() -> RefreshStats {
+    shared().lock().unwrap_or_else(PoisonError::into_inner).refresher.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_churn_between() {
+        let previous = HashMap::from([(1, 100), (2, 200), (3, 300)]);
+        let current = HashMap::from([(1, 100), (3, 350), (4, 400)]);
+        let churn = Churn::between(&previous, &current);
+        assert_eq!(churn.started, [3, 4], "3 was reused");
+        assert_eq!(churn.exited, [2, 3]);
+        assert!(Churn::between(&current, &current).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refresher_sees_processes_come_and_go() {
+        let mut refresher = ProcessRefresher::new();
+        let first = refresher.refresh();
+        assert!(first.started.contains(&std::process::id()));
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let started = refresher.refresh();
+        assert!(started.started.contains(&child.id()), "{:?}", started);
+        assert!(refresher.system().process(Pid::from_u32(child.id())).is_some());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let exited = refresher.refresh();
+        assert!(exited.exited.contains(&child.id()), "{:?}", exited);
+
+        let stats = refresher.stats();
+        assert_eq!((stats.passes, stats.full_passes), (3, 1));
+    }
+}
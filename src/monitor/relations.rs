@@ -0,0 +1,364 @@
+//! Relationships between processes
+//!
+//! The edges [`crate::algorithms::ProcessPageRank`] ranks processes over.
+//! Every platform has parent-child links; Windows adds processes loading the
+//! same non-system DLLs, Linux adds Unix socket and pipe peers. Each edge
+//! points from a process to one it depends on (child to parent, client to
+//! server), so rank collects on the processes others need.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use sysinfo::System;
+
+/// A pipe or module shared by more processes than this was inherited or is
+/// loaded everywhere; it says nothing about who works with whom
+#[cfg(any(target_os = "linux", target_os = "windows", test))]
+const MAX_SHARERS: usize = 8;
+
+/// How two processes are related
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelationKind {
+    /// Child to parent
+    Parent,
+    /// Unix socket client to server, or either way between socketpair ends
+    Socket,
+    /// Either end of a pipe to the other
+    Pipe,
+    /// Processes loading the same application DLLs, both ways
+    SharedModules,
+}
+
+impl RelationKind {
+    /// Edge weight before any per-edge scaling
+    fn weight(self) -> f64 {
+        match self {
+            RelationKind::Parent => 1.0,
+            RelationKind::Socket => 0.8,
+            RelationKind::Pipe | RelationKind::SharedModules => 0.5,
+        }
+    }
+}
+
+impl fmt::Display for RelationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RelationKind::Parent => "parent",
+            RelationKind::Socket => "socket",
+            RelationKind::Pipe => "pipe",
+            RelationKind::SharedModules => "shared DLLs",
+        })
+    }
+}
+
+/// `from` depends on `to`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    pub from: u32,
+    pub to: u32,
+    pub kind: RelationKind,
+    pub weight: f64,
+}
+
+impl Relation {
+    fn new(from: u32, to: u32, kind: RelationKind) -> Self {
+        Self { from, to, kind, weight: kind.weight() }
+    }
+}
+
+/// Every relationship that can be read between the processes in `system`
+///
+/// Socket and pipe peers only show for processes this user may inspect;
+/// run elevated for the full graph.
+pub fn collect(system: &System) -> Vec<Relation> {
+    let mut relations = parents(system);
+    relations.extend(platform_relations(system));
+    dedupe(relations)
+}
+
+/// Number of relations of each kind, most common first
+pub fn count_by_kind(relations: &[Relation]) -> Vec<(RelationKind, usize)> {
+    let mut counts: HashMap<RelationKind, usize> = HashMap::new();
+    for relation in relations {
+        *counts.entry(relation.kind).or_default() += 1;
+    }
+    let mut counts: Vec<(RelationKind, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|c| std::cmp::Reverse(c.1));
+    counts
+}
+
+fn parents(system: &System) -> Vec<Relation> {
+    system
+        .processes()
+        .iter()
+        .filter_map(|(pid, process)| {
+            let parent = process.parent()?;
+            system
+                .process(parent)
+                .map(|_| Relation::new(pid.as_u32(), parent.as_u32(), RelationKind::Parent))
+        })
+        .collect()
+}
+
+/// One edge per direction and kind, keeping the heaviest; no self-loops
+fn dedupe(relations: Vec<Relation>) -> Vec<Relation> {
+    let mut edges: HashMap<(u32, u32, RelationKind), Relation> = HashMap::new();
+    for relation in relations.into_iter().filter(|r| r.from != r.to) {
+        let key = (relation.from, relation.to, relation.kind);
+        match edges.get(&key) {
+            Some(existing) if existing.weight >= relation.weight => {}
+            _ => {
+                edges.insert(key, relation);
+            }
+        }
+    }
+    edges.into_values().collect()
+}
+
+/// Both-way edges between every pair of processes holding the same pipe
+#[cfg(any(target_os = "linux", test))]
+fn pipe_relations(holders: HashMap<u64, Vec<u32>>) -> Vec<Relation> {
+    let mut relations = Vec::new();
+    for mut pids in holders.into_values() {
+        pids.sort_unstable();
+        pids.dedup();
+        if pids.len() < 2 || pids.len() > MAX_SHARERS {
+            continue;
+        }
+        for &a in &pids {
+            for &b in pids.iter().filter(|&&b| b != a) {
+                relations.push(Relation::new(a, b, RelationKind::Pipe));
+            }
+        }
+    }
+    relations
+}
+
+#[cfg(target_os = "linux")]
+fn platform_relations(system: &System) -> Vec<Relation> {
+    let mut relations = std::process::Command::new("ss")
+        .args(["-xpn"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_ss(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+
+    let mut pipes: HashMap<u64, Vec<u32>> = HashMap::new();
+    for pid in system.processes().keys() {
+        let Ok(fds) = std::fs::read_dir(format!("/proc/{}/fd", pid.as_u32())) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            if let Some(inode) = pipe_inode(&target.to_string_lossy()) {
+                pipes.entry(inode).or_default().push(pid.as_u32());
+            }
+        }
+    }
+    relations.extend(pipe_relations(pipes));
+    relations
+}
+
+/// Inode of a `/proc/<pid>/fd` link like `pipe:[4242]`
+#[cfg(any(target_os = "linux", test))]
+fn pipe_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("pipe:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// Edges between the ends of connected Unix sockets in `ss -xpn` output
+///
+/// A socket bound to a path or abstract name is the server end; the other
+/// end is its client. Sockets listed without processes (other users' when
+/// not elevated) are skipped.
+#[cfg(any(target_os = "linux", test))]
+fn parse_ss(output: &str) -> Vec<Relation> {
+    struct End {
+        bound: bool,
+        peer: u64,
+        pids: Vec<u32>,
+    }
+
+    let mut ends: HashMap<u64, End> = HashMap::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 9 || fields[1] != "ESTAB" {
+            continue;
+        }
+        let (Ok(inode), Ok(peer)) = (fields[5].parse::<u64>(), fields[7].parse::<u64>()) else {
+            continue;
+        };
+        let pids: Vec<u32> = fields[8..]
+            .join(" ")
+            .split("pid=")
+            .skip(1)
+            .filter_map(|rest| rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok())
+            .collect();
+        ends.insert(inode, End { bound: fields[4] != "*", peer, pids });
+    }
+
+    let mut relations = Vec::new();
+    for end in ends.values() {
+        let Some(peer) = ends.get(&end.peer) else {
+            continue;
+        };
+        // The client's side adds client -> server
+        if end.bound && !peer.bound {
+            continue;
+        }
+        for &from in &end.pids {
+            for &to in &peer.pids {
+                relations.push(Relation::new(from, to, RelationKind::Socket));
+            }
+        }
+    }
+    relations
+}
+
+/// Both-way edges between processes loading the same application DLLs,
+/// weighted by how much of the smaller module list they share
+#[cfg(any(target_os = "windows", test))]
+fn module_relations(modules: &HashMap<u32, Vec<String>>) -> Vec<Relation> {
+    let mut loaded_by: HashMap<&str, Vec<u32>> = HashMap::new();
+    for (&pid, paths) in modules {
+        for path in paths {
+            loaded_by.entry(path.as_str()).or_default().push(pid);
+        }
+    }
+
+    let mut shared: HashMap<(u32, u32), usize> = HashMap::new();
+    for pids in loaded_by.values().filter(|pids| pids.len() >= 2 && pids.len() <= MAX_SHARERS) {
+        for &a in pids {
+            for &b in pids.iter().filter(|&&b| b != a) {
+                *shared.entry((a, b)).or_default() += 1;
+            }
+        }
+    }
+
+    shared
+        .into_iter()
+        .map(|((a, b), count)| {
+            let smaller = modules[&a].len().min(modules[&b].len()).max(1);
+            let mut relation = Relation::new(a, b, RelationKind::SharedModules);
+            relation.weight *= count as f64 / smaller as f64;
+            relation
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn platform_relations(system: &System) -> Vec<Relation> {
+    let windows_dir = std::env::var("SystemRoot")
+        .unwrap_or_else(|_| "C:\\Windows".into())
+        .to_lowercase();
+    let modules: HashMap<u32, Vec<String>> = system
+        .processes()
+        .keys()
+        .filter_map(|pid| {
+            let paths: Vec<String> = loaded_modules(pid.as_u32())?
+                .into_iter()
+                .map(|path| path.to_lowercase())
+                .filter(|path| !path.starts_with(&windows_dir))
+                .collect();
+            Some((pid.as_u32(), paths))
+        })
+        .collect();
+    module_relations(&modules)
+}
+
+/// Paths of the modules loaded into `pid`, the executable first
+#[cfg(target_os = "windows")]
+fn loaded_modules(pid: u32) -> Option<Vec<String>> {
+    use windows::Win32::Foundation::{CloseHandle, HMODULE};
+    use windows::Win32::System::ProcessStatus::{EnumProcessModulesEx, GetModuleFileNameExW, LIST_MODULES_ALL};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid).ok()?;
+        let mut handles = vec![HMODULE::default(); 512];
+        let mut needed = 0u32;
+        let size = (handles.len() * std::mem::size_of::<HMODULE>()) as u32;
+        let listed = EnumProcessModulesEx(handle, handles.as_mut_ptr(), size, &mut needed, LIST_MODULES_ALL).is_ok();
+        let count = (needed as usize / std::mem::size_of::<HMODULE>()).min(handles.len());
+
+        let mut paths = Vec::with_capacity(count);
+        let mut buffer = [0u16; 1024];
+        for &module in handles.iter().take(if listed { count } else { 0 }) {
+            let len = GetModuleFileNameExW(handle, module, &mut buffer) as usize;
+            if len > 0 {
+                paths.push(String::from_utf16_lossy(&buffer[..len]));
+            }
+        }
+        let _ = CloseHandle(handle);
+        listed.then_some(paths)
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn platform_relations(_system: &System) -> Vec<Relation> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(relations: &[Relation]) -> Vec<(u32, u32)> {
+        let mut edges: Vec<(u32, u32)> = relations.iter().map(|r| (r.from, r.to)).collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn test_parse_ss() {
+        let output = "\
+Netid State  Recv-Q Send-Q Local Address:Port          Peer Address:Port Process
+u_str ESTAB  0      0      /run/dbus/system_bus_socket 29485  * 28420 users:((\"dbus-daemon\",pid=612,fd=12))
+u_str ESTAB  0      0      * 28420                     * 29485 users:((\"NetworkManager\",pid=700,fd=9),(\"nm-helper\",pid=701,fd=3))
+u_str ESTAB  0      0      * 31000                     * 31001 users:((\"firefox\",pid=900,fd=40))
+u_str ESTAB  0      0      * 31001                     * 31000 users:((\"firefox\",pid=950,fd=7))
+u_str ESTAB  0      0      @/tmp/.X11-unix/X0 40000    * 40001
+u_str ESTAB  0      0      * 40001                     * 40000 users:((\"xterm\",pid=1200,fd=4))
+u_str LISTEN 0      4096   /run/systemd/notify 20000   * 0 users:((\"systemd\",pid=1,fd=30))
+";
+        let relations = parse_ss(output);
+        assert_eq!(edges(&relations), vec![(700, 612), (701, 612), (900, 950), (950, 900)]);
+        assert!(relations.iter().all(|r| r.kind == RelationKind::Socket));
+    }
+
+    #[test]
+    fn test_pipes_and_modules() {
+        assert_eq!(pipe_inode("pipe:[4242]"), Some(4242));
+        assert_eq!(pipe_inode("socket:[4242]"), None);
+
+        let mut holders = HashMap::new();
+        holders.insert(1, vec![10, 11, 10]);
+        holders.insert(2, vec![12]);
+        holders.insert(3, (100..120).collect());
+        assert_eq!(edges(&pipe_relations(holders)), vec![(10, 11), (11, 10)]);
+
+        let mut modules = HashMap::new();
+        modules.insert(1, vec!["app.exe".to_string(), "qt5core.dll".to_string(), "qt5gui.dll".to_string()]);
+        modules.insert(2, vec!["tool.exe".to_string(), "qt5core.dll".to_string()]);
+        modules.insert(3, vec!["other.exe".to_string()]);
+        let relations = module_relations(&modules);
+        assert_eq!(edges(&relations), vec![(1, 2), (2, 1)]);
+        // One shared module out of the smaller list of two
+        assert!((relations[0].weight - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let relations = dedupe(vec![
+            Relation::new(1, 2, RelationKind::Socket),
+            Relation::new(1, 2, RelationKind::Socket),
+            Relation::new(1, 2, RelationKind::Parent),
+            Relation::new(3, 3, RelationKind::Pipe),
+        ]);
+        assert_eq!(relations.len(), 2);
+        let counts = count_by_kind(&relations);
+        assert!(counts.contains(&(RelationKind::Parent, 1)) && counts.contains(&(RelationKind::Socket, 1)));
+    }
+}
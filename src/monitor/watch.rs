@@ -0,0 +1,526 @@
+//! `watch`: live telemetry for one app or category
+//!
+//! A top-like terminal view of the processes whose name contains `--name`
+//! or that fall in `--category`, refreshed in place: resident, private and
+//! swapped memory, CPU and handle count per process, the totals, and a
+//! sparkline of the total resident memory.
+//!
+//! Optimizations by the tray, the daemon or `t` in this view land in the
+//! history. When one does, each process's working set from the sample
+//! before it is kept, and the table shows how far the process shrank and
+//! how much of that has come back since.
+//!
+//! Private memory is `PrivateUsage` on Windows and `RssAnon` on Linux;
+//! swap is `VmSwap` on Linux only. macOS shows resident memory and CPU.
+//! The totals also add up each process's footprint, which counts the pages
+//! the processes share only once.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Paragraph, Row, Sparkline, Table};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::apps::{self, AppCategory};
+use crate::core::history::{HistoryFilter, HistoryStore, TimeRange};
+use crate::monitor::procs;
+
+/// Samples kept for the sparkline
+const HISTORY_LEN: usize = 300;
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// Which processes to watch
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchTarget {
+    /// Case-insensitive part of the process name
+    Name(String),
+    Category(AppCategory),
+}
+
+impl WatchTarget {
+    /// From `--name` or `--category`, whichever was given
+    pub fn from_args(name: Option<String>, category: Option<&str>) -> Result<Self, String> {
+        match (name, category) {
+            (Some(name), None) if !name.trim().is_empty() => Ok(Self::Name(name.trim().to_lowercase())),
+            (None, Some(category)) => parse_category(category).map(Self::Category),
+            _ => Err("Pass one of --name or --category".to_string()),
+        }
+    }
+
+    pub fn matches(&self, process_name: &str) -> bool {
+        match self {
+            Self::Name(name) => process_name.to_lowercase().contains(name.as_str()),
+            Self::Category(category) => apps::categorize(process_name) == *category,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Name(name) => format!("\"{}\"", name),
+            Self::Category(category) => format!("{:?} apps", category),
+        }
+    }
+}
+
+fn parse_category(name: &str) -> Result<AppCategory, String> {
+    let category = match name.to_lowercase().as_str() {
+        "browser" => AppCategory::Browser,
+        "electron" => AppCategory::Electron,
+        "development" | "dev" => AppCategory::Development,
+        "creative" => AppCategory::Creative,
+        "communication" | "chat" => AppCategory::Communication,
+        "media" => AppCategory::Media,
+        "system" => AppCategory::System,
+        "container" => AppCategory::Container,
+        "vm" | "virtualmachine" => AppCategory::VirtualMachine,
+        "ai" => AppCategory::AI,
+        "other" => AppCategory::Other,
+        other => return Err(format!("Unknown category '{}'", other)),
+    };
+    Ok(category)
+}
+
+/// One reading of a watched process
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub name: String,
+    pub rss_mb: f64,
+    pub private_mb: Option<f64>,
+    pub swap_mb: Option<f64>,
+    /// Memory with shared pages split between their users, see
+    /// [`ProcessMemoryInfo::footprint_mb`](crate::platform::ProcessMemoryInfo)
+    pub footprint_mb: Option<f64>,
+    pub cpu_percent: f32,
+    pub handles: Option<u32>,
+}
+
+/// Working set around the last optimization
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrimDelta {
+    /// In the sample before it
+    pub before_mb: f64,
+    /// Lowest since
+    pub low_mb: f64,
+    /// Now
+    pub current_mb: f64,
+}
+
+impl TrimDelta {
+    pub fn shrank_mb(&self) -> f64 {
+        (self.before_mb - self.low_mb).max(0.0)
+    }
+
+    pub fn regrown_mb(&self) -> f64 {
+        (self.current_mb - self.low_mb).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Memory,
+    Cpu,
+}
+
+/// What the view shows, updated once per sample
+#[derive(Debug, Clone)]
+pub struct WatchState {
+    pub target: WatchTarget,
+    pub rows: Vec<ProcessSample>,
+    pub sort: SortBy,
+    /// Total resident memory per sample, oldest first
+    pub totals: VecDeque<f64>,
+    trims: HashMap<u32, TrimDelta>,
+    previous_rss: HashMap<u32, f64>,
+    /// Last message shown in the footer
+    pub status: String,
+}
+
+impl WatchState {
+    pub fn new(target: WatchTarget) -> Self {
+        Self {
+            target,
+            rows: Vec::new(),
+            sort: SortBy::Memory,
+            totals: VecDeque::with_capacity(HISTORY_LEN),
+            trims: HashMap::new(),
+            previous_rss: HashMap::new(),
+            status: String::new(),
+        }
+    }
+
+    /// An optimization ran since the last sample; start new trim deltas
+    /// from each process's working set in that sample
+    pub fn mark_trim(&mut self) {
+        self.trims = self
+            .previous_rss
+            .iter()
+            .map(|(&pid, &rss)| (pid, TrimDelta { before_mb: rss, low_mb: rss, current_mb: rss }))
+            .collect();
+    }
+
+    pub fn update(&mut self, mut samples: Vec<ProcessSample>) {
+        match self.sort {
+            SortBy::Memory => samples.sort_by(|a, b| b.rss_mb.total_cmp(&a.rss_mb)),
+            SortBy::Cpu => samples.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent)),
+        }
+        self.trims.retain(|pid, _| samples.iter().any(|s| s.pid == *pid));
+        for sample in &samples {
+            if let Some(trim) = self.trims.get_mut(&sample.pid) {
+                trim.low_mb = trim.low_mb.min(sample.rss_mb);
+                trim.current_mb = sample.rss_mb;
+            }
+        }
+        self.previous_rss = samples.iter().map(|s| (s.pid, s.rss_mb)).collect();
+
+        if self.totals.len() == HISTORY_LEN {
+            self.totals.pop_front();
+        }
+        self.totals.push_back(samples.iter().map(|s| s.rss_mb).sum());
+        self.rows = samples;
+    }
+
+    pub fn trim(&self, pid: u32) -> Option<&TrimDelta> {
+        self.trims.get(&pid)
+    }
+
+    fn summary(&self) -> String {
+        let sum = |f: fn(&ProcessSample) -> Option<f64>| -> Option<f64> {
+            let values: Vec<f64> = self.rows.iter().filter_map(f).collect();
+            (!values.is_empty()).then(|| values.iter().sum())
+        };
+        let rss: f64 = self.rows.iter().map(|s| s.rss_mb).sum();
+        let cpu: f32 = self.rows.iter().map(|s| s.cpu_percent).sum();
+        let mut parts = vec![format!("{} processes", self.rows.len()), format!("RSS {:.0} MB", rss)];
+        if let Some(private) = sum(|s| s.private_mb) {
+            parts.push(format!("private {:.0} MB", private));
+        }
+        if let Some(swap) = sum(|s| s.swap_mb) {
+            parts.push(format!("swap {:.0} MB", swap));
+        }
+        if let Some(footprint) = sum(|s| s.footprint_mb) {
+            parts.push(format!("shared counted once {:.0} MB", footprint));
+        }
+        parts.push(format!("CPU {:.0}%", cpu));
+        if let Some(handles) = sum(|s| s.handles.map(f64::from)) {
+            parts.push(format!("handles {:.0}", handles));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Run the view until `q`, Esc or Ctrl+C
+pub fn run(target: WatchTarget, interval: Duration) -> Result<(), String> {
+    let mut terminal = ratatui::try_init().map_err(|e| format!("Failed to set up the terminal: {}", e))?;
+    let result = event_loop(&mut terminal, WatchState::new(target), interval);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, mut state: WatchState, interval: Duration) -> Result<(), String> {
+    let mut last_history_ms = now_ms();
+    let mut next_sample = Instant::now();
+    loop {
+        if Instant::now() >= next_sample {
+            if optimized_since(&mut last_history_ms) {
+                state.mark_trim();
+            }
+            let samples = sample(&state.target, interval);
+            state.update(samples);
+            next_sample = Instant::now() + interval;
+        }
+
+        terminal.draw(|frame| draw(frame, &state)).map_err(|e| format!("Failed to draw: {}", e))?;
+
+        let timeout = next_sample.saturating_duration_since(Instant::now());
+        if !event::poll(timeout).map_err(|e| format!("Failed to read input: {}", e))? {
+            continue;
+        }
+        let Event::Key(key) = event::read().map_err(|e| format!("Failed to read input: {}", e))? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('t') => {
+                let pids: Vec<(u32, String)> = state.rows.iter().map(|s| (s.pid, s.name.clone())).collect();
+                state.status = trim(&pids);
+                // Show the drop right away instead of at the next tick
+                next_sample = Instant::now();
+            }
+            KeyCode::Char('s') => {
+                state.sort = match state.sort {
+                    SortBy::Memory => SortBy::Cpu,
+                    SortBy::Cpu => SortBy::Memory,
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, state: &WatchState) {
+    let [header, chart, table, footer] =
+        Layout::vertical([Constraint::Length(3), Constraint::Length(6), Constraint::Min(4), Constraint::Length(1)])
+            .areas(frame.area());
+
+    let title = format!(" Watching {} ", state.target.describe());
+    frame.render_widget(Paragraph::new(state.summary()).block(Block::bordered().title(title)), header);
+
+    // Scaled to the range shown, so small changes stay visible
+    let high = state.totals.iter().copied().fold(0.0, f64::max);
+    let low = state.totals.iter().copied().fold(high, f64::min);
+    let data: Vec<u64> = state.totals.iter().map(|total| ((total - low) * 10.0) as u64 + 1).collect();
+    let width = chart.width.saturating_sub(2) as usize;
+    let visible = &data[data.len().saturating_sub(width)..];
+    let range = format!(" Resident memory, {:.0}–{:.0} MB ", low, high);
+    frame.render_widget(
+        Sparkline::default().block(Block::bordered().title(range)).data(visible).style(Style::default().fg(Color::Cyan)),
+        chart,
+    );
+
+    let rows = state.rows.iter().map(|s| {
+        let trim = match state.trim(s.pid) {
+            Some(t) => format!("-{:.0} / +{:.0} MB", t.shrank_mb(), t.regrown_mb()),
+            None => "-".to_string(),
+        };
+        Row::new(vec![
+            s.pid.to_string(),
+            s.name.clone(),
+            format!("{:.1}", s.rss_mb),
+            optional(s.private_mb),
+            optional(s.swap_mb),
+            format!("{:.1}", s.cpu_percent),
+            s.handles.map_or("-".to_string(), |h| h.to_string()),
+            trim,
+        ])
+    });
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Min(16),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(7),
+        Constraint::Length(8),
+        Constraint::Length(18),
+    ];
+    let sort = match state.sort {
+        SortBy::Memory => "memory",
+        SortBy::Cpu => "CPU",
+    };
+    let header_row = Row::new(["PID", "Name", "RSS MB", "Private MB", "Swap MB", "CPU %", "Handles", "Last trim / back"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    frame.render_widget(
+        Table::new(rows, widths).header(header_row).block(Block::bordered().title(format!(" By {} ", sort))),
+        table,
+    );
+
+    let keys = "q quit · s sort · t trim these processes";
+    let line = if state.status.is_empty() { keys.to_string() } else { format!("{} · {}", keys, state.status) };
+    frame.render_widget(Paragraph::new(line).style(Style::default().fg(Color::DarkGray)), footer);
+}
+
+fn optional(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |v| format!("{:.1}", v))
+}
+
+/// Read every matching process; CPU needs the shared list to be at least
+/// one refresh old, so it is refreshed at half the sample interval
+fn sample(target: &WatchTarget, interval: Duration) -> Vec<ProcessSample> {
+    let matching: Vec<(u32, String, f64, f32)> = procs::with_processes(interval / 2, |system| {
+        system
+            .processes()
+            .iter()
+            .filter(|(_, process)| process.thread_kind().is_none())
+            .filter_map(|(pid, process)| {
+                let name = process.name().to_string_lossy().to_string();
+                target
+                    .matches(&name)
+                    .then(|| (pid.as_u32(), name, process.memory() as f64 / MB, process.cpu_usage()))
+            })
+            .collect()
+    });
+    matching
+        .into_iter()
+        .map(|(pid, name, rss_mb, cpu_percent)| {
+            let details = details(pid);
+            ProcessSample {
+                pid,
+                name,
+                rss_mb,
+                private_mb: details.private_mb,
+                swap_mb: details.swap_mb,
+                footprint_mb: crate::platform::process_memory_info(pid).ok().map(|info| info.footprint_mb),
+                cpu_percent,
+                handles: details.handles,
+            }
+        })
+        .collect()
+}
+
+/// Whether an optimization was recorded after `last_ms`; moves `last_ms`
+/// to the newest one
+fn optimized_since(last_ms: &mut u64) -> bool {
+    let range = TimeRange { start_ms: Some(*last_ms + 1), end_ms: None };
+    let Ok(records) = HistoryStore::open_default().and_then(|s| s.query(range, &HistoryFilter::default())) else {
+        return false;
+    };
+    let newest = records.iter().map(|r| r.timestamp_ms).max();
+    if let Some(newest) = newest {
+        *last_ms = (*last_ms).max(newest);
+    }
+    newest.is_some()
+}
+
+#[cfg(target_os = "windows")]
+fn trim(processes: &[(u32, String)]) -> String {
+    use crate::core::history::{self, HistoryRecord, OptimizationTrigger};
+    use crate::windows::memory::WindowsMemoryOptimizer;
+    use crate::windows::safety::{SafetyConfig, SafetyGuard};
+
+    let guard = SafetyGuard::new(SafetyConfig::default());
+    let start = Instant::now();
+    let mut freed_bytes = 0u64;
+    let mut trimmed = 0;
+    for (pid, name) in processes {
+        if guard.is_protected(name) {
+            continue;
+        }
+        if let Ok(bytes) = WindowsMemoryOptimizer::trim_process_working_set(*pid) {
+            freed_bytes += bytes;
+            trimmed += 1;
+        }
+    }
+    let freed_mb = freed_bytes as f64 / MB;
+    history::record(HistoryRecord::now(
+        OptimizationTrigger::Manual,
+        false,
+        freed_mb,
+        trimmed,
+        start.elapsed().as_millis() as u64,
+    ));
+    format!("trimmed {} processes, {:.0} MB", trimmed, freed_mb)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn trim(_processes: &[(u32, String)]) -> String {
+    "trimming single apps is only supported on Windows".to_string()
+}
+
+/// What `sysinfo` doesn't read
+#[derive(Debug, Clone, Copy, Default)]
+struct Details {
+    private_mb: Option<f64>,
+    swap_mb: Option<f64>,
+    handles: Option<u32>,
+}
+
+#[cfg(target_os = "windows")]
+fn details(pid: u32) -> Details {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS, PROCESS_MEMORY_COUNTERS_EX,
+    };
+    use windows::Win32::System::Threading::{GetProcessHandleCount, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return Details::default();
+        };
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS_EX>() as u32;
+        let mut counters = PROCESS_MEMORY_COUNTERS_EX { cb: size, ..Default::default() };
+        let private_mb = GetProcessMemoryInfo(handle, &mut counters as *mut _ as *mut PROCESS_MEMORY_COUNTERS, size)
+            .is_ok()
+            .then(|| counters.PrivateUsage as f64 / MB);
+        let mut count = 0u32;
+        let handles = GetProcessHandleCount(handle, &mut count).is_ok().then_some(count);
+        let _ = CloseHandle(handle);
+        Details { private_mb, swap_mb: None, handles }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn details(pid: u32) -> Details {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+    let kb = |field: &str| -> Option<f64> {
+        let value = status.lines().find_map(|line| line.strip_prefix(field))?;
+        value.trim().trim_end_matches("kB").trim().parse::<f64>().ok().map(|kb| kb / 1024.0)
+    };
+    Details {
+        private_mb: kb("RssAnon:"),
+        swap_mb: kb("VmSwap:"),
+        handles: std::fs::read_dir(format!("/proc/{}/fd", pid)).ok().map(|fds| fds.count() as u32),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn details(_pid: u32) -> Details {
+    Details::default()
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(pid: u32, rss_mb: f64, cpu_percent: f32) -> ProcessSample {
+        ProcessSample {
+            pid,
+            name: "chrome.exe".to_string(),
+            rss_mb,
+            private_mb: None,
+            swap_mb: None,
+            footprint_mb: None,
+            cpu_percent,
+            handles: Some(100),
+        }
+    }
+
+    #[test]
+    fn test_target() {
+        assert_eq!(WatchTarget::from_args(Some("Chrome".into()), None), Ok(WatchTarget::Name("chrome".into())));
+        assert!(WatchTarget::Name("chrome".into()).matches("Google Chrome Helper"));
+        assert!(!WatchTarget::Name("chrome".into()).matches("firefox"));
+        let browsers = WatchTarget::from_args(None, Some("browser")).unwrap();
+        assert!(browsers.matches("msedge.exe"));
+        assert!(!browsers.matches("cargo"));
+        assert!(WatchTarget::from_args(None, Some("nope")).is_err());
+        assert!(WatchTarget::from_args(None, None).is_err());
+    }
+
+    #[test]
+    fn test_trim_deltas() {
+        let mut state = WatchState::new(WatchTarget::Name("chrome".into()));
+        state.update(vec![sample(1, 500.0, 1.0), sample(2, 300.0, 9.0)]);
+        assert!(state.trim(1).is_none());
+
+        state.mark_trim();
+        state.update(vec![sample(1, 200.0, 1.0), sample(2, 280.0, 9.0)]);
+        state.update(vec![sample(1, 260.0, 1.0), sample(2, 290.0, 9.0), sample(3, 50.0, 0.0)]);
+
+        let first = state.trim(1).unwrap();
+        assert_eq!((first.shrank_mb(), first.regrown_mb()), (300.0, 60.0));
+        assert_eq!(state.trim(2).unwrap().shrank_mb(), 20.0);
+        assert!(state.trim(3).is_none(), "started after the trim");
+        assert_eq!(state.totals, [800.0, 480.0, 600.0]);
+        assert_eq!(state.rows[0].pid, 2);
+
+        state.sort = SortBy::Cpu;
+        state.update(vec![sample(1, 260.0, 1.0)]);
+        assert!(state.trim(2).is_none(), "exited");
+    }
+}
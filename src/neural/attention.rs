@@ -2,10 +2,13 @@
 //! 
 //! Uses multi-head attention to weight process importance.
 
+use serde::{Deserialize, Serialize};
+
 use crate::core::patterns::MemoryPattern;
-use crate::windows::memory::MemoryStatus;
+use crate::platform::MemoryStatus;
 
 /// Attention-based scorer for optimization decisions
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AttentionScorer {
     /// Weights learned from experience
     weights: Vec<f32>,
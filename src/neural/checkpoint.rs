@@ -0,0 +1,287 @@
+//! Versioned checkpoints for the neural decision engine
+//!
+//! A checkpoint holds everything the engine learns: attention weights,
+//! EWC Fisher information, the HNSW pattern vectors and the labeled history
+//! they were built from. Files are named `checkpoint.v<N>.json`; older schema
+//! versions (including the pre-checkpoint `patterns.json`) are migrated on
+//! load and rewritten in the current format on the next save.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::patterns::{LabeledPattern, MemoryPattern, TrimModeStats};
+
+use super::attention::AttentionScorer;
+use super::ewc_learner::EWCLearner;
+use super::hnsw_patterns::PatternIndex;
+
+/// Current checkpoint schema version
+///
+/// - v1: bare `patterns.json` history array
+/// - v2: full engine state
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// History file written before checkpoints existed
+const LEGACY_HISTORY_FILE: &str = "patterns.json";
+
+/// File name for a given schema version
+pub fn checkpoint_file_name(version: u32) -> String {
+    format!("checkpoint.v{}.json", version)
+}
+
+/// Everything the neural engine has learned
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// RFC 3339 timestamp of when the checkpoint was written
+    pub created_at: String,
+    /// Feature dimension the pattern vectors were built with
+    pub dim: usize,
+    pub attention: AttentionScorer,
+    pub ewc: EWCLearner,
+    pub pattern_index: PatternIndex,
+    pub history: Vec<LabeledPattern>,
+}
+
+impl Checkpoint {
+    /// Build a checkpoint from engine state
+    pub fn new(
+        attention: &AttentionScorer,
+        ewc: &EWCLearner,
+        pattern_index: &PatternIndex,
+        history: &[LabeledPattern],
+    ) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            created_at: chrono::Local::now().to_rfc3339(),
+            dim: MemoryPattern::DIM,
+            attention: attention.clone(),
+            ewc: ewc.clone(),
+            pattern_index: pattern_index.clone(),
+            history: history.to_vec(),
+        }
+    }
+
+    /// Load the newest checkpoint in `dir`, migrating older formats
+    ///
+    /// Returns `Ok(None)` when the directory holds no model yet.
+    pub fn load(dir: &Path, ewc_lambda: f32) -> Result<Option<Self>, String> {
+        match Self::find(dir) {
+            Some(path) => Self::read(&path, ewc_lambda).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Newest checkpoint file in `dir`, falling back to the legacy history
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        (1..=CHECKPOINT_VERSION)
+            .rev()
+            .map(|v| dir.join(checkpoint_file_name(v)))
+            .chain(std::iter::once(dir.join(LEGACY_HISTORY_FILE)))
+            .find(|p| p.exists())
+    }
+
+    /// Read and migrate a checkpoint file
+    pub fn read(path: &Path, ewc_lambda: f32) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+        Self::migrate(value, ewc_lambda)
+    }
+
+    /// Upgrade any known schema to the current one
+    pub fn migrate(value: Value, ewc_lambda: f32) -> Result<Self, String> {
+        let version = match &value {
+            Value::Array(_) => 1,
+            Value::Object(map) => map
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or("Checkpoint has no version field")? as u32,
+            _ => return Err("Unrecognized checkpoint format".into()),
+        };
+
+        if version > CHECKPOINT_VERSION {
+            return Err(format!(
+                "Checkpoint version {} is newer than supported version {}",
+                version, CHECKPOINT_VERSION
+            ));
+        }
+
+        let mut checkpoint = if version == 1 {
+            let history: Vec<LabeledPattern> = serde_json::from_value(value)
+                .map_err(|e| format!("Invalid v1 history: {}", e))?;
+            let index = PatternIndex::new(MemoryPattern::DIM)?;
+            Self::new(&AttentionScorer::new(), &EWCLearner::new(ewc_lambda), &index, &history)
+        } else {
+            serde_json::from_value(value).map_err(|e| format!("Invalid v{} checkpoint: {}", version, e))?
+        };
+
+        // Feature layout changed or the index was never populated: rebuild
+        // vectors from the labeled history so neighbours line up with it
+        if checkpoint.dim != MemoryPattern::DIM || checkpoint.pattern_index.len() != checkpoint.history.len() {
+            checkpoint.rebuild_index()?;
+        }
+
+        checkpoint.version = CHECKPOINT_VERSION;
+        Ok(checkpoint)
+    }
+
+    /// Re-derive the pattern index and learners from history
+    fn rebuild_index(&mut self) -> Result<(), String> {
+        let mut index = PatternIndex::new(MemoryPattern::DIM)?;
+        for labeled in &self.history {
+            index.add(&labeled.pattern.to_vector())?;
+        }
+        if self.dim != MemoryPattern::DIM {
+            // Per-feature weights no longer map onto the new layout
+            let mut ewc = EWCLearner::new(self.ewc.lambda());
+            for labeled in &self.history {
+                ewc.update(labeled);
+            }
+            self.ewc = ewc;
+            self.attention = AttentionScorer::new();
+            self.dim = MemoryPattern::DIM;
+        }
+        self.pattern_index = index;
+        Ok(())
+    }
+
+    /// Write to `dir` in the current format and drop migrated files
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        let path = dir.join(checkpoint_file_name(CHECKPOINT_VERSION));
+        self.write(&path)?;
+
+        for old in (1..CHECKPOINT_VERSION).map(|v| dir.join(checkpoint_file_name(v))) {
+            let _ = std::fs::remove_file(old);
+        }
+        let legacy = dir.join(LEGACY_HISTORY_FILE);
+        if legacy.exists() {
+            let _ = std::fs::rename(&legacy, dir.join("patterns.json.migrated"));
+        }
+        Ok(path)
+    }
+
+    /// Write to an arbitrary path (used by `neural export`)
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        // Write then rename so a crash never leaves a truncated model
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, content).map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+        std::fs::rename(&tmp, path).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    /// Copy the model in `dir` to `dest`
+    pub fn export(dir: &Path, dest: &Path, ewc_lambda: f32) -> Result<Self, String> {
+        let checkpoint = Self::load(dir, ewc_lambda)?
+            .ok_or_else(|| format!("No trained model in {:?}", dir))?;
+        checkpoint.write(dest)?;
+        Ok(checkpoint)
+    }
+
+    /// Replace the model in `dir` with the one in `src`
+    pub fn import(src: &Path, dir: &Path, ewc_lambda: f32) -> Result<Self, String> {
+        let checkpoint = Self::read(src, ewc_lambda)?;
+        checkpoint.save(dir)?;
+        Ok(checkpoint)
+    }
+
+    pub fn print_summary(&self) {
+        println!("  Schema version: v{}", self.version);
+        println!("  Created:        {}", self.created_at);
+        println!("  Patterns:       {}", self.history.len());
+        println!("  EWC updates:    {}", self.ewc.updates());
+        let successes = self.history.iter().filter(|p| p.success).count();
+        if !self.history.is_empty() {
+            println!(
+                "  Success rate:   {:.0}%",
+                successes as f64 / self.history.len() as f64 * 100.0
+            );
+        }
+        for (label, clustered) in [("Per-process", false), ("Clusters", true)] {
+            let stats = TrimModeStats::from_history(&self.history, clustered);
+            if stats.measured > 0 {
+                println!(
+                    "  {:16}{} runs, {:.0} MB freed, {:.0} MB kept after re-faults",
+                    format!("{}:", label),
+                    stats.runs,
+                    stats.mean_freed_mb,
+                    stats.kept_mb()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labeled(load: f32, success: bool) -> LabeledPattern {
+        LabeledPattern {
+            pattern: MemoryPattern {
+                load,
+                consumption_rate: 0.0,
+                available_ratio: 1.0 - load,
+                page_file_ratio: 0.1,
+                process_count: 200,
+                hour: 10,
+                day_of_week: 2,
+                time_since_last_opt: 60.0,
+            },
+            optimized: true,
+            aggressive: false,
+            freed_mb: 300.0,
+            success,
+            clustered: false,
+            refault_penalty: None,
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_history() {
+        let history = vec![labeled(0.8, true), labeled(0.9, false)];
+        let value = serde_json::to_value(&history).unwrap();
+
+        let checkpoint = Checkpoint::migrate(value, 0.4).unwrap();
+        assert_eq!(checkpoint.version, CHECKPOINT_VERSION);
+        assert_eq!(checkpoint.history.len(), 2);
+        assert_eq!(checkpoint.pattern_index.len(), 2);
+    }
+
+    #[test]
+    fn test_roundtrip_and_version_guard() {
+        let mut ewc = EWCLearner::new(0.4);
+        let history = vec![labeled(0.85, true)];
+        ewc.update(&history[0]);
+        let mut index = PatternIndex::new(MemoryPattern::DIM).unwrap();
+        index.add(&history[0].pattern.to_vector()).unwrap();
+
+        let checkpoint = Checkpoint::new(&AttentionScorer::new(), &ewc, &index, &history);
+        let mut value = serde_json::to_value(&checkpoint).unwrap();
+        let restored = Checkpoint::migrate(value.clone(), 0.4).unwrap();
+        assert_eq!(restored.ewc.updates(), 1);
+        assert_eq!(restored.pattern_index.len(), 1);
+
+        value["version"] = (CHECKPOINT_VERSION + 1).into();
+        assert!(Checkpoint::migrate(value, 0.4).is_err());
+    }
+
+    #[test]
+    fn test_dimension_change_rebuilds_index() {
+        let history = vec![labeled(0.7, true)];
+        let mut stale = PatternIndex::new(3).unwrap();
+        stale.add(&[0.1, 0.2, 0.3]).unwrap();
+
+        let mut checkpoint = Checkpoint::new(&AttentionScorer::new(), &EWCLearner::new(0.4), &stale, &history);
+        checkpoint.dim = 3;
+        let value = serde_json::to_value(&checkpoint).unwrap();
+
+        let migrated = Checkpoint::migrate(value, 0.4).unwrap();
+        assert_eq!(migrated.dim, MemoryPattern::DIM);
+        assert!(migrated.pattern_index.search(&history[0].pattern.to_vector(), 1).is_ok());
+    }
+}
@@ -1,17 +1,22 @@
 //! Neural Decision Engine
+//!
+//! Platform-independent: works on the `platform` status and result types so
+//! the same learning stack drives the Windows, macOS and Linux daemons.
 
-use std::path::Path;
+use std::path::PathBuf;
 use chrono::{Datelike, Timelike};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::core::config::OptimizerConfig;
-use crate::core::optimizer::OptimizationDecision;
-use crate::core::patterns::{MemoryPattern, LabeledPattern};
-use crate::windows::memory::{MemoryStatus, OptimizationResult};
+use crate::core::decision::OptimizationDecision;
+use crate::core::patterns::{MemoryPattern, LabeledPattern, TrimModeStats};
+use crate::platform::{MemoryStatus, OptimizationResult};
 
 use super::hnsw_patterns::PatternIndex;
 use super::ewc_learner::EWCLearner;
 use super::attention::AttentionScorer;
+use super::checkpoint::Checkpoint;
+use super::refault::{RefaultReport, REFAULT_FAILURE_PENALTY};
 
 pub struct NeuralDecisionEngine {
     pattern_index: PatternIndex,
@@ -23,13 +28,51 @@ pub struct NeuralDecisionEngine {
 
 impl NeuralDecisionEngine {
     pub fn new(config: &OptimizerConfig) -> Result<Self, String> {
-        let pattern_index = PatternIndex::new(MemoryPattern::DIM)
-            .map_err(|e| format!("Failed to create pattern index: {}", e))?;
-        let attention = AttentionScorer::new();
-        let ewc = EWCLearner::new(config.ewc_lambda);
-        let history = Self::load_history(&config.model_path).unwrap_or_default();
-        info!("Neural engine initialized with {} historical patterns", history.len());
-        Ok(Self { pattern_index, attention, ewc, config: config.clone(), history })
+        let checkpoint = match Checkpoint::load(&config.model_path, config.ewc_lambda) {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                warn!("Ignoring unreadable neural checkpoint: {}", e);
+                None
+            }
+        };
+
+        let engine = match checkpoint {
+            Some(checkpoint) => Self::from_checkpoint(checkpoint, config),
+            None => {
+                let pattern_index = PatternIndex::new(MemoryPattern::DIM)
+                    .map_err(|e| format!("Failed to create pattern index: {}", e))?;
+                Self {
+                    pattern_index,
+                    attention: AttentionScorer::new(),
+                    ewc: EWCLearner::new(config.ewc_lambda),
+                    config: config.clone(),
+                    history: Vec::new(),
+                }
+            }
+        };
+        info!("Neural engine initialized with {} historical patterns", engine.history.len());
+        Ok(engine)
+    }
+
+    /// Restore learned state from a checkpoint
+    pub fn from_checkpoint(checkpoint: Checkpoint, config: &OptimizerConfig) -> Self {
+        Self {
+            pattern_index: checkpoint.pattern_index,
+            attention: checkpoint.attention,
+            ewc: checkpoint.ewc,
+            config: config.clone(),
+            history: checkpoint.history,
+        }
+    }
+
+    /// Snapshot learned state
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(&self.attention, &self.ewc, &self.pattern_index, &self.history)
+    }
+
+    /// Write the current checkpoint to the model directory
+    pub fn save(&self) -> Result<PathBuf, String> {
+        self.checkpoint().save(&self.config.model_path)
     }
 
     pub async fn decide(&self, pattern: &MemoryPattern, status: &MemoryStatus) -> Result<OptimizationDecision, String> {
@@ -67,7 +110,7 @@ impl NeuralDecisionEngine {
                 consumption_rate: 0.0,
                 available_ratio: (result.before_available_mb / 32000.0) as f32,
                 page_file_ratio: 0.0,
-                process_count: result.processes_trimmed as u32,
+                process_count: result.processes_affected as u32,
                 hour: now.hour() as u8,
                 day_of_week: now.weekday().num_days_from_monday() as u8,
                 time_since_last_opt: 0.0,
@@ -76,27 +119,56 @@ impl NeuralDecisionEngine {
             aggressive: decision.aggressive,
             freed_mb: result.freed_mb as f32,
             success,
+            clustered: result.clustered,
+            refault_penalty: None,
         };
         let vec = pattern.pattern.to_vector();
         let _ = self.pattern_index.add(&vec);
         self.ewc.update(&pattern);
         self.history.push(pattern);
-        if self.history.len() % 100 == 0 { let _ = self.save_history(&self.config.model_path); }
+        if let Err(e) = self.save() { warn!("Failed to save neural checkpoint: {}", e); }
         info!("Learned: success={}, freed={:.1}MB", success, result.freed_mb);
     }
 
-    fn load_history(path: &Path) -> Result<Vec<LabeledPattern>, String> {
-        let file_path = path.join("patterns.json");
-        if !file_path.exists() { return Ok(vec![]); }
-        let content = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).map_err(|e| e.to_string())
+    /// Feed measured re-fault cost back into the learners
+    pub fn learn_from_refault(&mut self, report: &RefaultReport) {
+        let Some(labeled) = self.history.get_mut(report.history_index) else { return };
+        labeled.refault_penalty = Some(report.penalty);
+        if report.penalty > REFAULT_FAILURE_PENALTY {
+            labeled.success = false;
+        }
+        self.ewc.penalize(labeled, report.penalty);
+        self.attention.update_weights(-report.penalty, &labeled.pattern);
+        if let Err(e) = self.save() { warn!("Failed to save neural checkpoint: {}", e); }
+        info!("Re-fault: {:.1}/{:.1}MB paged back in, {} faults, penalty={:.2}",
+            report.refaulted_mb, report.freed_mb, report.page_faults, report.penalty);
+    }
+
+    pub fn pattern_count(&self) -> usize { self.history.len() }
+
+    /// Keep only the newest `keep` patterns; returns how many were dropped
+    ///
+    /// History indices of the rest shift down by that many.
+    pub fn shrink(&mut self, keep: usize) -> usize {
+        let dropped = self.history.len().saturating_sub(keep);
+        if dropped > 0 {
+            self.history.drain(..dropped);
+            self.history.shrink_to_fit();
+            self.pattern_index.drop_oldest(dropped);
+            info!("Dropped {} oldest patterns to stay within the memory budget", dropped);
+        }
+        dropped
     }
 
-    fn save_history(&self, path: &Path) -> Result<(), String> {
-        std::fs::create_dir_all(path).map_err(|e| e.to_string())?;
-        let content = serde_json::to_string_pretty(&self.history).map_err(|e| e.to_string())?;
-        std::fs::write(path.join("patterns.json"), content).map_err(|e| e.to_string())
+    /// Per-process and cluster-grouped trimming, as measured so far
+    pub fn trim_modes(&self) -> (TrimModeStats, TrimModeStats) {
+        (TrimModeStats::from_history(&self.history, false), TrimModeStats::from_history(&self.history, true))
     }
 
-    pub fn pattern_count(&self) -> usize { self.history.len() }
+    /// Whether grouped trimming has kept more memory reclaimed; `None`
+    /// until both ways have been measured often enough
+    pub fn prefers_clusters(&self) -> Option<bool> {
+        let (per_process, clustered) = self.trim_modes();
+        TrimModeStats::prefer_clusters(&per_process, &clustered)
+    }
 }
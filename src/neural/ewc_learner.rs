@@ -2,9 +2,12 @@
 //!
 //! Ensures the optimizer does not forget good strategies when learning new ones.
 
+use serde::{Deserialize, Serialize};
+
 use crate::core::patterns::{MemoryPattern, LabeledPattern};
 
 /// EWC Learner - prevents catastrophic forgetting
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EWCLearner {
     /// Lambda parameter (higher = more preservation of old knowledge)
     lambda: f32,
@@ -68,6 +71,28 @@ impl EWCLearner {
         self.update_count += 1;
     }
     
+    /// Move away from a pattern whose trim was paged straight back in
+    ///
+    /// `penalty` is the measured re-fault cost (0.0-1.0); aggressive trims
+    /// are penalized harder since they are the ones that cause thrashing.
+    pub fn penalize(&mut self, labeled: &LabeledPattern, penalty: f32) {
+        let features = labeled.pattern.to_vector();
+        let scale = if labeled.aggressive { 1.5 } else { 1.0 };
+        let penalty = (penalty * scale).clamp(0.0, 1.0);
+        let alpha = 0.1 * penalty;
+
+        for (i, &f) in features.iter().enumerate() {
+            if i < self.optimal_weights.len() {
+                // Features that led to thrashing matter more
+                self.fisher_diag[i] = self.fisher_diag[i] * 0.99 + penalty * 0.01;
+                let w = self.optimal_weights[i];
+                self.optimal_weights[i] = (w - alpha * (f - w)).clamp(0.0, 1.0);
+            }
+        }
+
+        self.update_count += 1;
+    }
+
     /// Calculate EWC penalty (for loss function)
     pub fn penalty(&self, current_weights: &[f32]) -> f32 {
         if current_weights.len() != self.optimal_weights.len() {
@@ -87,4 +112,9 @@ impl EWCLearner {
     pub fn updates(&self) -> usize {
         self.update_count
     }
+
+    /// Get lambda
+    pub fn lambda(&self) -> f32 {
+        self.lambda
+    }
 }
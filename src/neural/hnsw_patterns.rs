@@ -2,7 +2,10 @@
 
 use std::cmp::Ordering;
 
+use serde::{Deserialize, Serialize};
+
 /// HNSW Pattern Index - Fast approximate nearest neighbor search
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PatternIndex {
     dim: usize,
     vectors: Vec<Vec<f32>>,
@@ -77,11 +80,13 @@ impl PatternIndex {
     }
     
     fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
-        a.iter()
-            .zip(b.iter())
-            .map(|(x, y)| (x - y).powi(2))
-            .sum::<f32>()
-            .sqrt()
+        crate::accel::simd::l2_distance(a, b)
+    }
+    
+    /// Drop the `count` oldest vectors; the ids of the rest shift down by `count`
+    pub fn drop_oldest(&mut self, count: usize) {
+        self.vectors.drain(..count.min(self.vectors.len()));
+        self.vectors.shrink_to_fit();
     }
     
     pub fn len(&self) -> usize {
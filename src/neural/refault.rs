@@ -0,0 +1,245 @@
+//! Re-fault tracking for trimmed processes
+//!
+//! A trim only helps if the process can live without the pages it lost. This
+//! watches working-set regrowth and page faults for trimmed processes over
+//! the following minute and turns them into a penalty the learners use to
+//! discourage trims that just cause thrashing.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long to watch a trimmed process after the trim
+pub const REFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// How often to sample while a window is open
+pub const REFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Fault rate (per second, summed over trimmed processes) treated as thrashing
+const THRASH_FAULTS_PER_SEC: f32 = 2000.0;
+
+/// Penalty above which the optimization is relabeled as unsuccessful
+pub const REFAULT_FAILURE_PENALTY: f32 = 0.5;
+
+/// Point-in-time memory counters for one process
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessCounters {
+    pub working_set_bytes: u64,
+    /// Cumulative page faults since process start
+    pub page_faults: u64,
+}
+
+struct Watched {
+    freed_bytes: u64,
+    baseline: ProcessCounters,
+    peak_working_set: u64,
+    last_faults: u64,
+}
+
+/// One optimization's trimmed processes under observation
+struct Window {
+    history_index: usize,
+    started: Instant,
+    processes: HashMap<u32, Watched>,
+}
+
+/// Measured re-fault cost of one optimization
+#[derive(Debug, Clone)]
+pub struct RefaultReport {
+    /// Index of the labeled pattern this optimization produced
+    pub history_index: usize,
+    pub freed_mb: f64,
+    /// Working-set regrowth over the window
+    pub refaulted_mb: f64,
+    pub page_faults: u64,
+    /// 0.0 (trim stuck) to 1.0 (everything paged straight back in)
+    pub penalty: f32,
+}
+
+impl RefaultReport {
+    fn from_window(window: &Window, elapsed: Duration) -> Self {
+        let freed: u64 = window.processes.values().map(|w| w.freed_bytes).sum();
+        let regrowth: u64 = window
+            .processes
+            .values()
+            .map(|w| w.peak_working_set.saturating_sub(w.baseline.working_set_bytes).min(w.freed_bytes))
+            .sum();
+        let faults: u64 = window
+            .processes
+            .values()
+            .map(|w| w.last_faults.saturating_sub(w.baseline.page_faults))
+            .sum();
+
+        let refault_ratio = if freed > 0 { regrowth as f32 / freed as f32 } else { 0.0 };
+        let fault_rate = faults as f32 / elapsed.as_secs_f32().max(1.0);
+        let thrash = (fault_rate / THRASH_FAULTS_PER_SEC).min(1.0);
+
+        Self {
+            history_index: window.history_index,
+            freed_mb: freed as f64 / 1024.0 / 1024.0,
+            refaulted_mb: regrowth as f64 / 1024.0 / 1024.0,
+            page_faults: faults,
+            penalty: (refault_ratio * 0.7 + thrash * 0.3).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Watches trimmed processes and reports re-fault cost once each window closes
+pub struct RefaultTracker {
+    windows: Vec<Window>,
+    window: Duration,
+}
+
+impl RefaultTracker {
+    pub fn new() -> Self {
+        Self::with_window(REFAULT_WINDOW)
+    }
+
+    pub fn with_window(window: Duration) -> Self {
+        Self { windows: Vec::new(), window }
+    }
+
+    /// Start watching processes right after they were trimmed
+    ///
+    /// `trimmed` is `(pid, freed_bytes)`; processes that freed nothing or
+    /// can no longer be sampled are skipped.
+    pub fn track<F>(&mut self, history_index: usize, trimmed: &[(u32, u64)], sample: F)
+    where
+        F: Fn(u32) -> Option<ProcessCounters>,
+    {
+        let processes: HashMap<u32, Watched> = trimmed
+            .iter()
+            .filter(|(_, freed)| *freed > 0)
+            .filter_map(|&(pid, freed_bytes)| {
+                let baseline = sample(pid)?;
+                Some((
+                    pid,
+                    Watched {
+                        freed_bytes,
+                        baseline,
+                        peak_working_set: baseline.working_set_bytes,
+                        last_faults: baseline.page_faults,
+                    },
+                ))
+            })
+            .collect();
+
+        if !processes.is_empty() {
+            self.windows.push(Window { history_index, started: Instant::now(), processes });
+        }
+    }
+
+    /// Follow [`NeuralDecisionEngine::shrink`](super::engine::NeuralDecisionEngine::shrink):
+    /// forget windows for the `dropped` oldest patterns and renumber the rest
+    pub fn forget_oldest(&mut self, dropped: usize) {
+        self.windows.retain(|window| window.history_index >= dropped);
+        for window in &mut self.windows {
+            window.history_index -= dropped;
+        }
+    }
+
+    /// Whether any window is still open
+    pub fn is_tracking(&self) -> bool {
+        !self.windows.is_empty()
+    }
+
+    /// Sample every watched process and return reports for closed windows
+    pub fn poll<F>(&mut self, sample: F) -> Vec<RefaultReport>
+    where
+        F: Fn(u32) -> Option<ProcessCounters>,
+    {
+        self.poll_at(Instant::now(), sample)
+    }
+
+    fn poll_at<F>(&mut self, now: Instant, sample: F) -> Vec<RefaultReport>
+    where
+        F: Fn(u32) -> Option<ProcessCounters>,
+    {
+        for window in &mut self.windows {
+            // Exited processes keep their last sample
+            for (pid, watched) in window.processes.iter_mut() {
+                if let Some(counters) = sample(*pid) {
+                    watched.peak_working_set = watched.peak_working_set.max(counters.working_set_bytes);
+                    watched.last_faults = watched.last_faults.max(counters.page_faults);
+                }
+            }
+        }
+
+        let mut reports = Vec::new();
+        let window_len = self.window;
+        self.windows.retain(|window| {
+            let elapsed = now.saturating_duration_since(window.started);
+            if elapsed >= window_len {
+                reports.push(RefaultReport::from_window(window, elapsed));
+                false
+            } else {
+                true
+            }
+        });
+        reports
+    }
+}
+
+impl Default for RefaultTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn counters(ws_mb: u64, faults: u64) -> Option<ProcessCounters> {
+        Some(ProcessCounters { working_set_bytes: ws_mb * MB, page_faults: faults })
+    }
+
+    #[test]
+    fn test_thrashing_trim_is_penalized() {
+        let mut tracker = RefaultTracker::with_window(Duration::from_secs(60));
+        let state = RefCell::new((100, 0));
+        let sample = |_pid: u32| {
+            let (ws, faults) = *state.borrow();
+            counters(ws, faults)
+        };
+
+        tracker.track(3, &[(42, 400 * MB)], sample);
+        assert!(tracker.is_tracking());
+
+        // Process pages nearly everything back in
+        *state.borrow_mut() = (480, 150_000);
+        let start = tracker.windows[0].started;
+        assert!(tracker.poll_at(start + Duration::from_secs(30), sample).is_empty());
+
+        let reports = tracker.poll_at(start + Duration::from_secs(60), sample);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].history_index, 3);
+        assert!(reports[0].refaulted_mb >= 379.0);
+        assert!(reports[0].penalty > REFAULT_FAILURE_PENALTY);
+        assert!(!tracker.is_tracking());
+    }
+
+    #[test]
+    fn test_stable_trim_has_low_penalty() {
+        let mut tracker = RefaultTracker::with_window(Duration::from_secs(60));
+        tracker.track(0, &[(7, 300 * MB), (8, 0)], |_| counters(50, 1000));
+        assert_eq!(tracker.windows[0].processes.len(), 1);
+
+        let start = tracker.windows[0].started;
+        let reports = tracker.poll_at(start + Duration::from_secs(61), |_| counters(60, 1500));
+        assert!(reports[0].penalty < 0.1);
+    }
+
+    #[test]
+    fn test_forget_oldest_renumbers_windows() {
+        let mut tracker = RefaultTracker::with_window(Duration::from_secs(60));
+        tracker.track(2, &[(1, 100 * MB)], |_| counters(50, 0));
+        tracker.track(5, &[(2, 100 * MB)], |_| counters(50, 0));
+
+        tracker.forget_oldest(3);
+        assert_eq!(tracker.windows.len(), 1);
+        assert_eq!(tracker.windows[0].history_index, 2);
+    }
+}
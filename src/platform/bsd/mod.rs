@@ -0,0 +1,12 @@
+//! FreeBSD/OpenBSD platform support
+//!
+//! - `sysctl vm.stats` / `vmstat -s` based memory status and `ps` process listing
+//! - ZFS ARC shrinking (FreeBSD, root only)
+//! - rc.d script management for the daemon
+
+pub mod memory;
+pub mod safety;
+pub mod service;
+
+pub use memory::BsdMemoryOptimizer;
+pub use service::BsdDaemonService;
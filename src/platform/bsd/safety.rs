@@ -0,0 +1,28 @@
+//! Safety mechanisms for FreeBSD/OpenBSD memory optimization
+
+/// Protected process names that should never be optimized
+pub const PROTECTED_PROCESSES: &[&str] = &[
+    // Init and kernel threads
+    "init",
+    "kernel",
+    "pagedaemon",
+    "vmdaemon",
+    "bufdaemon",
+    "zfskern",
+    // Core services
+    "devd",
+    "syslogd",
+    "cron",
+    "sshd",
+    "ntpd",
+    "dhclient",
+    "unbound",
+    // NAS file sharing
+    "smbd",
+    "nmbd",
+    "nfsd",
+    "mountd",
+    "rpcbind",
+    // Display server
+    "xorg",
+];
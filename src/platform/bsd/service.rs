@@ -0,0 +1,191 @@
+//! rc.d integration for running the optimizer as a background daemon
+//!
+//! FreeBSD scripts live in `/usr/local/etc/rc.d` and are enabled with
+//! `sysrc`; OpenBSD scripts live in `/etc/rc.d` and are enabled with `rcctl`.
+//! Both need root.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// rc.d script name (also the rc.conf variable prefix)
+pub const SCRIPT_NAME: &str = "ruvector_memopt";
+
+/// Installed service state
+#[derive(Debug, Clone)]
+pub struct ServiceStatus {
+    pub installed: bool,
+    pub running: bool,
+    pub enabled: bool,
+    pub script_path: PathBuf,
+}
+
+/// Manages the rc.d script for the daemon
+pub struct BsdDaemonService;
+
+impl BsdDaemonService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn script_path(&self) -> PathBuf {
+        if cfg!(target_os = "freebsd") {
+            PathBuf::from("/usr/local/etc/rc.d").join(SCRIPT_NAME)
+        } else {
+            PathBuf::from("/etc/rc.d").join(SCRIPT_NAME)
+        }
+    }
+
+    /// Render the FreeBSD script, supervised by daemon(8) so it restarts on failure
+    pub fn render_freebsd(exe: &str, interval_secs: u64) -> String {
+        format!(
+            "#!/bin/sh\n\
+             #\n\
+             # PROVIDE: {name}\n\
+             # REQUIRE: LOGIN\n\
+             # KEYWORD: shutdown\n\
+             \n\
+             . /etc/rc.subr\n\
+             \n\
+             name=\"{name}\"\n\
+             rcvar=\"{name}_enable\"\n\
+             pidfile=\"/var/run/${{name}}.pid\"\n\
+             command=\"/usr/sbin/daemon\"\n\
+             command_args=\"-r -R 10 -P ${{pidfile}} -o /var/log/${{name}}.log {exe} daemon --interval {interval}\"\n\
+             \n\
+             load_rc_config $name\n\
+             : ${{{name}_enable:=\"NO\"}}\n\
+             \n\
+             run_rc_command \"$1\"\n",
+            name = SCRIPT_NAME,
+            exe = exe,
+            interval = interval_secs,
+        )
+    }
+
+    /// Render the OpenBSD script; rc.subr backgrounds the process
+    pub fn render_openbsd(exe: &str, interval_secs: u64) -> String {
+        format!(
+            "#!/bin/ksh\n\
+             \n\
+             daemon=\"{exe}\"\n\
+             daemon_flags=\"daemon --interval {interval}\"\n\
+             \n\
+             . /etc/rc.d/rc.subr\n\
+             \n\
+             rc_bg=YES\n\
+             rc_reload=NO\n\
+             \n\
+             rc_cmd $1\n",
+            exe = exe,
+            interval = interval_secs,
+        )
+    }
+
+    /// Write the script, enable it and start the service
+    pub fn install(&self, interval_secs: u64) -> Result<String, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let exe = exe.to_string_lossy();
+        let path = self.script_path();
+        let script = if cfg!(target_os = "freebsd") {
+            Self::render_freebsd(&exe, interval_secs)
+        } else {
+            Self::render_openbsd(&exe, interval_secs)
+        };
+
+        std::fs::write(&path, script).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        set_executable(&path)?;
+
+        if cfg!(target_os = "freebsd") {
+            run("sysrc", &[&format!("{}_enable=YES", SCRIPT_NAME)])?;
+            run("service", &[SCRIPT_NAME, "start"])?;
+        } else {
+            run("rcctl", &["enable", SCRIPT_NAME])?;
+            run("rcctl", &["start", SCRIPT_NAME])?;
+        }
+
+        Ok(format!("Installed and started {} ({:?})", SCRIPT_NAME, path))
+    }
+
+    /// Stop/disable the service and remove the script
+    pub fn uninstall(&self) -> Result<String, String> {
+        let path = self.script_path();
+        if !path.exists() {
+            return Err(format!("{} is not installed", SCRIPT_NAME));
+        }
+
+        // Ignore failures here: the service may already be stopped or disabled
+        if cfg!(target_os = "freebsd") {
+            let _ = run("service", &[SCRIPT_NAME, "stop"]);
+            let _ = run("sysrc", &["-x", &format!("{}_enable", SCRIPT_NAME)]);
+        } else {
+            let _ = run("rcctl", &["stop", SCRIPT_NAME]);
+            let _ = run("rcctl", &["disable", SCRIPT_NAME]);
+        }
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+
+        Ok(format!("Removed {}", SCRIPT_NAME))
+    }
+
+    /// Query installed/running/enabled state
+    pub fn status(&self) -> ServiceStatus {
+        let script_path = self.script_path();
+        let (running, enabled) = if cfg!(target_os = "freebsd") {
+            (
+                run("service", &[SCRIPT_NAME, "status"]).is_ok(),
+                run("service", &[SCRIPT_NAME, "enabled"]).is_ok(),
+            )
+        } else {
+            (
+                run("rcctl", &["check", SCRIPT_NAME]).is_ok(),
+                run("rcctl", &["get", SCRIPT_NAME, "status"]).is_ok(),
+            )
+        };
+        ServiceStatus { installed: script_path.exists(), running, enabled, script_path }
+    }
+}
+
+impl Default for BsdDaemonService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn set_executable(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o555))
+        .map_err(|e| format!("Failed to make {:?} executable: {}", path, e))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_freebsd() {
+        let script = BsdDaemonService::render_freebsd("/usr/local/bin/ruvector-memopt-bsd", 60);
+        assert!(script.contains("# PROVIDE: ruvector_memopt\n"));
+        assert!(script.contains("rcvar=\"ruvector_memopt_enable\""));
+        assert!(script.contains("/usr/local/bin/ruvector-memopt-bsd daemon --interval 60\""));
+        assert!(script.contains(": ${ruvector_memopt_enable:=\"NO\"}"));
+    }
+
+    #[test]
+    fn test_render_openbsd() {
+        let script = BsdDaemonService::render_openbsd("/usr/local/bin/ruvector-memopt-bsd", 30);
+        assert!(script.starts_with("#!/bin/ksh\n"));
+        assert!(script.contains("daemon_flags=\"daemon --interval 30\""));
+        assert!(script.ends_with("rc_cmd $1\n"));
+    }
+}
@@ -0,0 +1,472 @@
+//! DNS cache, temp file, thumbnail and app cache cleanup
+//!
+//! - Windows: `ipconfig /flushdns`, `%TEMP%` and Explorer's `thumbcache_*`
+//! - macOS: `dscacheutil` plus mDNSResponder, `$TMPDIR`, the QuickLook
+//!   thumbnail cache and `~/Library/Caches`
+//! - Linux: `resolvectl` (or nscd), the temp directory, `~/.cache/thumbnails`
+//!   and the rest of `~/.cache`
+//!
+//! App caches are only cleaned when asked for by name. Only regular files
+//! untouched for a while are removed: a day for temp files, a week for app
+//! caches. Files in the shared temp directory must belong to
+//! us. Exclusion patterns are globs matched against each file's name and
+//! its path below the target's directory. Nothing is removed in a dry run;
+//! the report shows what would be.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::security::validator::matches_glob;
+
+/// Temp files younger than this may still be in use
+const TEMP_MIN_AGE: Duration = Duration::from_secs(24 * 3600);
+
+/// App cache files younger than this are likely to be read again soon
+const CACHE_MIN_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Cache entries left alone unless the user removes them from the list
+#[cfg(target_os = "macos")]
+const DEFAULT_CACHE_EXCLUDE: &[&str] = &["com.apple.*", "CloudKit*", "*.lock"];
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_CACHE_EXCLUDE: &[&str] = &["thumbnails", "*.lock"];
+
+/// What to clean
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CleanupTarget {
+    /// The resolver's DNS cache
+    Dns,
+    /// The user's temp directory
+    Temp,
+    /// Cached file thumbnails
+    Thumbnails,
+    /// Per-user app caches (macOS and Linux)
+    Caches,
+}
+
+impl CleanupTarget {
+    /// Targets cleaned when none are named; app caches are opt-in
+    pub const DEFAULT: [CleanupTarget; 3] = [CleanupTarget::Dns, CleanupTarget::Temp, CleanupTarget::Thumbnails];
+}
+
+impl std::fmt::Display for CleanupTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // `pad` so the report can align targets with `{:<11}`
+        f.pad(match self {
+            CleanupTarget::Dns => "dns",
+            CleanupTarget::Temp => "temp",
+            CleanupTarget::Thumbnails => "thumbnails",
+            CleanupTarget::Caches => "caches",
+        })
+    }
+}
+
+impl std::str::FromStr for CleanupTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dns" => Ok(CleanupTarget::Dns),
+            "temp" | "tmp" => Ok(CleanupTarget::Temp),
+            "thumbnails" | "thumbs" => Ok(CleanupTarget::Thumbnails),
+            "caches" | "cache" => Ok(CleanupTarget::Caches),
+            other => Err(format!(
+                "Unknown cleanup target '{}' (expected dns, temp, thumbnails or caches)", other
+            )),
+        }
+    }
+}
+
+/// What one target cleaned, or would clean in a dry run
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetResult {
+    pub target: CleanupTarget,
+    /// Directory swept; `None` for the DNS cache
+    pub location: Option<PathBuf>,
+    pub files: usize,
+    pub bytes: u64,
+    /// Files kept because they are too recent, excluded or not ours
+    pub kept: usize,
+    /// Files that could not be removed, usually because they are open
+    pub failed: usize,
+    /// Set when the target was skipped or failed outright
+    pub error: Option<String>,
+}
+
+impl TargetResult {
+    fn new(target: CleanupTarget, location: Option<PathBuf>) -> Self {
+        Self { target, location, files: 0, bytes: 0, kept: 0, failed: 0, error: None }
+    }
+
+    fn failed(target: CleanupTarget, error: impl Into<String>) -> Self {
+        Self { error: Some(error.into()), ..Self::new(target, None) }
+    }
+
+    pub fn mb(&self) -> f64 {
+        self.bytes as f64 / 1024.0 / 1024.0
+    }
+}
+
+/// Results of a cleanup run
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    pub dry_run: bool,
+    pub results: Vec<TargetResult>,
+}
+
+impl CleanupReport {
+    pub fn total_bytes(&self) -> u64 {
+        self.results.iter().map(|r| r.bytes).sum()
+    }
+
+    pub fn print(&self) {
+        let verb = if self.dry_run { "Would free" } else { "Freed" };
+        println!("\n🧹 Cleanup{}\n", if self.dry_run { " (dry run)" } else { "" });
+        for result in &self.results {
+            match (&result.error, result.target) {
+                (Some(error), target) => println!("  ✗ {:<11} {}", target, error),
+                (None, CleanupTarget::Dns) => {
+                    println!("  ✓ {:<11} {}", "dns", if self.dry_run { "would flush" } else { "flushed" })
+                }
+                (None, target) => {
+                    let mut line = format!("  ✓ {:<11} {} files, {:.1} MB", target, result.files, result.mb());
+                    if result.kept > 0 {
+                        line.push_str(&format!(", {} kept", result.kept));
+                    }
+                    if result.failed > 0 {
+                        line.push_str(&format!(", {} in use", result.failed));
+                    }
+                    println!("{}", line);
+                    if let Some(location) = &result.location {
+                        println!("    {}", location.display());
+                    }
+                }
+            }
+        }
+        println!("\n{} {:.1} MB", verb, self.total_bytes() as f64 / 1024.0 / 1024.0);
+    }
+}
+
+/// How to clean
+#[derive(Debug, Clone, Default)]
+pub struct CleanupOptions {
+    /// Targets to clean; empty means [`CleanupTarget::DEFAULT`]
+    pub targets: Vec<CleanupTarget>,
+    /// Globs for files to keep (see the module docs)
+    pub exclude: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// Clean every requested target
+pub fn clean(options: &CleanupOptions) -> CleanupReport {
+    let targets = if options.targets.is_empty() { CleanupTarget::DEFAULT.to_vec() } else { options.targets.clone() };
+    let results = targets
+        .into_iter()
+        .map(|target| match target {
+            CleanupTarget::Dns => flush_dns(options.dry_run),
+            CleanupTarget::Temp => {
+                let sweep = Sweep { min_age: TEMP_MIN_AGE, only: None, owned_only: true, prune_dirs: true };
+                sweep.run(target, Some(std::env::temp_dir()), &options.exclude, options.dry_run)
+            }
+            CleanupTarget::Thumbnails => thumbnails(&options.exclude, options.dry_run),
+            CleanupTarget::Caches => {
+                let mut exclude: Vec<String> = DEFAULT_CACHE_EXCLUDE.iter().map(|s| s.to_string()).collect();
+                exclude.extend(options.exclude.iter().cloned());
+                let sweep = Sweep { min_age: CACHE_MIN_AGE, only: None, owned_only: false, prune_dirs: false };
+                sweep.run(target, cache_dir(), &exclude, options.dry_run)
+            }
+        })
+        .collect();
+    CleanupReport { dry_run: options.dry_run, results }
+}
+
+/// Which files under a directory to remove
+struct Sweep {
+    min_age: Duration,
+    /// Only file names matching this glob
+    only: Option<&'static str>,
+    /// Skip files owned by another user (Unix)
+    owned_only: bool,
+    /// Remove directories left empty
+    prune_dirs: bool,
+}
+
+impl Sweep {
+    fn run(&self, target: CleanupTarget, root: Option<PathBuf>, exclude: &[String], dry_run: bool) -> TargetResult {
+        let Some(root) = root else {
+            return TargetResult::failed(target, "not available on this platform");
+        };
+        if !root.is_dir() {
+            return TargetResult::failed(target, format!("{} not found", root.display()));
+        }
+        let mut result = TargetResult::new(target, Some(root.clone()));
+        self.walk(&root, &root, exclude, dry_run, &mut result);
+        result
+    }
+
+    fn walk(&self, root: &Path, dir: &Path, exclude: &[String], dry_run: bool, result: &mut TargetResult) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Symlinks are never followed or removed
+            let Ok(metadata) = entry.path().symlink_metadata() else { continue };
+            if metadata.file_type().is_symlink() {
+                continue;
+            }
+            let relative = relative_path(root, &path);
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_excluded(&relative, &name, exclude) || (self.owned_only && !owned_by_us(&metadata)) {
+                result.kept += 1;
+                continue;
+            }
+
+            if metadata.is_dir() {
+                self.walk(root, &path, exclude, dry_run, result);
+                if self.prune_dirs && !dry_run && is_old(&metadata, self.min_age) {
+                    // Fails harmlessly while anything is left inside
+                    let _ = std::fs::remove_dir(&path);
+                }
+                continue;
+            }
+
+            // Sockets, FIFOs and device nodes belong to running programs
+            if !metadata.is_file() || self.only.is_some_and(|only| !matches_glob(only, &name)) {
+                continue;
+            }
+            if !is_old(&metadata, self.min_age) {
+                result.kept += 1;
+                continue;
+            }
+            if dry_run || std::fs::remove_file(&path).is_ok() {
+                result.files += 1;
+                result.bytes += metadata.len();
+            } else {
+                result.failed += 1;
+            }
+        }
+    }
+}
+
+/// `path` below `root` with `/` separators, for exclusion patterns
+fn relative_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn is_excluded(relative: &str, name: &str, exclude: &[String]) -> bool {
+    exclude.iter().any(|pattern| matches_glob(pattern, name) || matches_glob(pattern, relative))
+}
+
+fn is_old(metadata: &std::fs::Metadata, min_age: Duration) -> bool {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map_or(true, |age| age >= min_age)
+}
+
+#[cfg(unix)]
+fn owned_by_us(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.uid() == unsafe { libc::geteuid() }
+}
+
+/// `%TEMP%` is per-user on Windows
+#[cfg(not(unix))]
+fn owned_by_us(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn cache_dir() -> Option<PathBuf> {
+    home().map(|home| home.join("Library").join("Caches"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home().map(|home| home.join(".cache")))
+}
+
+/// Windows apps keep their caches in too many places to clean generically
+#[cfg(not(unix))]
+fn cache_dir() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn thumbnails(exclude: &[String], dry_run: bool) -> TargetResult {
+    let root = std::env::var_os("LOCALAPPDATA")
+        .map(|dir| PathBuf::from(dir).join("Microsoft").join("Windows").join("Explorer"));
+    // Explorer keeps the current databases open, so those count as in use
+    let sweep = Sweep { min_age: Duration::ZERO, only: Some("thumbcache_*.db"), owned_only: false, prune_dirs: false };
+    sweep.run(CleanupTarget::Thumbnails, root, exclude, dry_run)
+}
+
+/// QuickLook's cache is sized here but reset with `qlmanage`, which also
+/// tells the running QuickLook server
+#[cfg(target_os = "macos")]
+fn thumbnails(exclude: &[String], dry_run: bool) -> TargetResult {
+    let root = run("getconf", &["DARWIN_USER_CACHE_DIR"])
+        .ok()
+        .map(|dir| PathBuf::from(dir.trim()).join("com.apple.QuickLook.thumbnailcache"));
+    let sweep = Sweep { min_age: Duration::ZERO, only: None, owned_only: false, prune_dirs: false };
+    let mut result = sweep.run(CleanupTarget::Thumbnails, root, exclude, true);
+    if !dry_run && result.error.is_none() {
+        if let Err(e) = run("qlmanage", &["-r", "cache"]) {
+            result = TargetResult { error: Some(e), ..result };
+        }
+    }
+    result
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn thumbnails(exclude: &[String], dry_run: bool) -> TargetResult {
+    let sweep = Sweep { min_age: Duration::ZERO, only: None, owned_only: false, prune_dirs: false };
+    sweep.run(CleanupTarget::Thumbnails, cache_dir().map(|dir| dir.join("thumbnails")), exclude, dry_run)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn thumbnails(_exclude: &[String], _dry_run: bool) -> TargetResult {
+    TargetResult::failed(CleanupTarget::Thumbnails, "not available on this platform")
+}
+
+#[cfg(target_os = "windows")]
+fn flush_dns(dry_run: bool) -> TargetResult {
+    if dry_run {
+        return TargetResult::new(CleanupTarget::Dns, None);
+    }
+    match run("ipconfig", &["/flushdns"]) {
+        Ok(_) => TargetResult::new(CleanupTarget::Dns, None),
+        Err(e) => TargetResult::failed(CleanupTarget::Dns, e),
+    }
+}
+
+/// `dscacheutil` clears the directory service cache; mDNSResponder holds
+/// the actual DNS cache and only a HUP from root clears it
+#[cfg(target_os = "macos")]
+fn flush_dns(dry_run: bool) -> TargetResult {
+    if dry_run {
+        return TargetResult::new(CleanupTarget::Dns, None);
+    }
+    if let Err(e) = run("dscacheutil", &["-flushcache"]) {
+        return TargetResult::failed(CleanupTarget::Dns, e);
+    }
+    match run("killall", &["-HUP", "mDNSResponder"]) {
+        Ok(_) => TargetResult::new(CleanupTarget::Dns, None),
+        Err(_) => TargetResult::failed(CleanupTarget::Dns, "mDNSResponder needs root to flush"),
+    }
+}
+
+/// systemd-resolved first, then nscd; without either there is no local cache
+#[cfg(all(unix, not(target_os = "macos")))]
+fn flush_dns(dry_run: bool) -> TargetResult {
+    let attempts: [(&str, &[&str]); 3] = [
+        ("resolvectl", &["flush-caches"]),
+        ("systemd-resolve", &["--flush-caches"]),
+        ("nscd", &["-i", "hosts"]),
+    ];
+    if dry_run {
+        return TargetResult::new(CleanupTarget::Dns, None);
+    }
+    let mut last_error = "no local DNS cache (systemd-resolved or nscd) found".to_string();
+    for (program, args) in attempts {
+        match run(program, args) {
+            Ok(_) => return TargetResult::new(CleanupTarget::Dns, None),
+            Err(e) if !e.starts_with("Failed to run") => last_error = e,
+            Err(_) => {}
+        }
+    }
+    TargetResult::failed(CleanupTarget::Dns, last_error)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn flush_dns(_dry_run: bool) -> TargetResult {
+    TargetResult::failed(CleanupTarget::Dns, "not available on this platform")
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+    Err(format!("{} failed: {}", program, message.lines().last().unwrap_or("").trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclusions() {
+        let exclude = vec!["*.lock".to_string(), "google/*".to_string()];
+        assert!(is_excluded("pip/http/x.lock", "x.lock", &exclude));
+        assert!(is_excluded("google/chrome/data", "data", &exclude));
+        assert!(!is_excluded("mozilla/firefox/data", "data", &exclude));
+        assert_eq!("thumbs".parse::<CleanupTarget>(), Ok(CleanupTarget::Thumbnails));
+        assert!("browser".parse::<CleanupTarget>().is_err());
+    }
+
+    #[test]
+    fn test_sweep_keeps_recent_and_excluded() {
+        let root = std::env::temp_dir().join(format!("ruvector-cleanup-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3 * 24 * 3600);
+        for name in ["app/old.bin", "app/keep.lock"] {
+            std::fs::write(root.join(name), [0u8; 100]).unwrap();
+            std::fs::File::options().write(true).open(root.join(name)).unwrap().set_modified(old).unwrap();
+        }
+        std::fs::write(root.join("app/new.bin"), [0u8; 100]).unwrap();
+
+        let sweep = Sweep { min_age: TEMP_MIN_AGE, only: None, owned_only: true, prune_dirs: true };
+        let exclude = vec!["*.lock".to_string()];
+        let dry = sweep.run(CleanupTarget::Temp, Some(root.clone()), &exclude, true);
+        assert_eq!((dry.files, dry.bytes, dry.kept), (1, 100, 2));
+        assert!(root.join("app/old.bin").exists());
+
+        let done = sweep.run(CleanupTarget::Temp, Some(root.clone()), &exclude, false);
+        assert_eq!(done.files, 1);
+        assert!(!root.join("app/old.bin").exists());
+        assert!(root.join("app/new.bin").exists() && root.join("app/keep.lock").exists());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sweep_only_removes_regular_files() {
+        let root = std::env::temp_dir().join(format!("ruvector-cleanup-special-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let socket = root.join("app.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        std::fs::write(root.join("stale.bin"), [0u8; 10]).unwrap();
+
+        let sweep = Sweep { min_age: Duration::ZERO, only: None, owned_only: true, prune_dirs: false };
+        let done = sweep.run(CleanupTarget::Temp, Some(root.clone()), &[], false);
+        assert_eq!(done.files, 1);
+        assert!(socket.exists() && !root.join("stale.bin").exists());
+        assert!(!CleanupTarget::DEFAULT.contains(&CleanupTarget::Caches));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
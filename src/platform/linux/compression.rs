@@ -0,0 +1,386 @@
+//! zram and zswap inspection and configuration
+//!
+//! zram is a compressed block device used as swap; zswap is a compressed
+//! cache in front of a disk swap device. Running both compresses pages twice,
+//! so the advisor picks one based on whether disk swap exists. Applying
+//! changes needs root and lasts until the next reboot.
+
+use std::path::Path;
+use std::process::Command;
+
+const ZSWAP_PARAMETERS: &str = "/sys/module/zswap/parameters";
+/// Only readable by root, and only with debugfs mounted
+const ZSWAP_DEBUGFS: &str = "/sys/kernel/debug/zswap";
+/// Priority that makes the kernel use zram before any disk swap
+const ZRAM_SWAP_PRIORITY: &str = "100";
+const PREFERRED_ALGORITHM: &str = "zstd";
+/// zswap pool cap as a share of RAM
+const ZSWAP_POOL_PERCENT: u32 = 20;
+
+/// One `/sys/block/zramN` device
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZramDevice {
+    pub name: String,
+    pub disksize_mb: f64,
+    pub algorithm: String,
+    /// Uncompressed size of the data stored
+    pub orig_mb: f64,
+    pub compressed_mb: f64,
+    /// RAM used, including allocator overhead
+    pub used_mb: f64,
+    /// Listed in `/proc/swaps`
+    pub is_swap: bool,
+}
+
+impl ZramDevice {
+    pub fn ratio(&self) -> Option<f64> {
+        (self.compressed_mb > 0.0).then(|| self.orig_mb / self.compressed_mb)
+    }
+}
+
+/// zswap parameters and pool statistics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZswapState {
+    pub enabled: bool,
+    pub compressor: String,
+    pub max_pool_percent: u32,
+    /// From debugfs; `None` when not readable
+    pub pool_mb: Option<f64>,
+    pub stored_mb: Option<f64>,
+}
+
+impl ZswapState {
+    pub fn ratio(&self) -> Option<f64> {
+        match (self.stored_mb, self.pool_mb) {
+            (Some(stored), Some(pool)) if pool > 0.0 => Some(stored / pool),
+            _ => None,
+        }
+    }
+}
+
+/// Current memory compression setup
+#[derive(Debug, Clone, Default)]
+pub struct CompressionStatus {
+    pub zram: Vec<ZramDevice>,
+    /// `None` when the kernel was built without zswap
+    pub zswap: Option<ZswapState>,
+    /// Swap devices other than zram
+    pub disk_swap: bool,
+}
+
+impl CompressionStatus {
+    pub fn detect() -> Self {
+        let swaps = std::fs::read_to_string("/proc/swaps").unwrap_or_default();
+        let swap_devices: Vec<&str> = swaps
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+
+        let mut zram = Vec::new();
+        if let Ok(entries) = std::fs::read_dir("/sys/block") {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("zram") {
+                    let mut device = read_zram(&entry.path(), &name);
+                    device.is_swap = swap_devices.contains(&format!("/dev/{}", name).as_str());
+                    zram.push(device);
+                }
+            }
+        }
+        zram.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            zram,
+            zswap: read_zswap(),
+            disk_swap: swap_devices.iter().any(|d| !d.starts_with("/dev/zram")),
+        }
+    }
+
+    /// zram devices in use as swap
+    pub fn active_zram(&self) -> impl Iterator<Item = &ZramDevice> {
+        self.zram.iter().filter(|d| d.is_swap)
+    }
+
+    /// RAM held by compressed pages across zram and zswap
+    pub fn compressed_mb(&self) -> f64 {
+        let zram: f64 = self.active_zram().map(|d| d.used_mb).sum();
+        zram + self.zswap.as_ref().and_then(|z| z.pool_mb).unwrap_or(0.0)
+    }
+
+    /// One line for `status`, `None` when nothing is compressing
+    pub fn summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        let zram: Vec<&ZramDevice> = self.active_zram().collect();
+        if !zram.is_empty() {
+            let orig: f64 = zram.iter().map(|d| d.orig_mb).sum();
+            let compressed: f64 = zram.iter().map(|d| d.compressed_mb).sum();
+            let ratio = if compressed > 0.0 { orig / compressed } else { 0.0 };
+            parts.push(format!("zram {:.0} MB → {:.0} MB ({:.1}x)", orig, compressed, ratio));
+        }
+        if let Some(zswap) = self.zswap.as_ref().filter(|z| z.enabled) {
+            match zswap.pool_mb {
+                Some(pool) => parts.push(format!("zswap pool {:.0} MB", pool)),
+                None => parts.push("zswap on".to_string()),
+            }
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    pub fn print(&self) {
+        println!("\n🗜️  Memory Compression\n");
+        if self.zram.is_empty() {
+            println!("  zram:  no devices");
+        }
+        for device in &self.zram {
+            println!(
+                "  {}: {:.0} MB disk, {}, {:.0} MB stored in {:.0} MB{}{}",
+                device.name,
+                device.disksize_mb,
+                device.algorithm,
+                device.orig_mb,
+                device.used_mb,
+                device.ratio().map(|r| format!(" ({:.1}x)", r)).unwrap_or_default(),
+                if device.is_swap { "" } else { " (not used as swap)" }
+            );
+        }
+        match &self.zswap {
+            Some(zswap) => println!(
+                "  zswap: {}, {}, pool up to {}% of RAM{}",
+                if zswap.enabled { "enabled" } else { "disabled" },
+                zswap.compressor,
+                zswap.max_pool_percent,
+                match (zswap.pool_mb, zswap.ratio()) {
+                    (Some(pool), Some(ratio)) => format!(", {:.0} MB pool ({:.1}x)", pool, ratio),
+                    (Some(pool), None) => format!(", {:.0} MB pool", pool),
+                    _ => String::new(),
+                }
+            ),
+            None => println!("  zswap: not available in this kernel"),
+        }
+        println!("  Disk swap: {}", if self.disk_swap { "yes" } else { "no" });
+    }
+}
+
+/// What to change
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompressionChange {
+    /// Create `/dev/zram0` as high-priority swap
+    EnableZram { size_mb: u64, algorithm: String },
+    EnableZswap { compressor: String, max_pool_percent: u32 },
+    /// zram already compresses swap; zswap would compress pages twice
+    DisableZswap,
+}
+
+impl std::fmt::Display for CompressionChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EnableZram { size_mb, algorithm } => {
+                write!(f, "Create a {} MB zram swap device using {}", size_mb, algorithm)
+            }
+            Self::EnableZswap { compressor, max_pool_percent } => write!(
+                f,
+                "Enable zswap with {} and a pool of up to {}% of RAM",
+                compressor, max_pool_percent
+            ),
+            Self::DisableZswap => write!(f, "Disable zswap, since zram already compresses swap"),
+        }
+    }
+}
+
+/// Recommended compression setup for this machine
+#[derive(Debug, Clone)]
+pub struct CompressionAdvice {
+    pub changes: Vec<CompressionChange>,
+    pub reason: String,
+}
+
+impl CompressionAdvice {
+    /// zram when there is no disk swap, zswap in front of disk swap, never both
+    pub fn recommend(status: &CompressionStatus, total_ram_mb: f64) -> Self {
+        let zswap_enabled = status.zswap.as_ref().is_some_and(|z| z.enabled);
+        if status.active_zram().next().is_some() {
+            let changes = if zswap_enabled { vec![CompressionChange::DisableZswap] } else { Vec::new() };
+            return Self { changes, reason: "zram swap is already set up.".into() };
+        }
+        if status.disk_swap && status.zswap.is_some() {
+            let changes = if zswap_enabled {
+                Vec::new()
+            } else {
+                vec![CompressionChange::EnableZswap {
+                    compressor: PREFERRED_ALGORITHM.into(),
+                    max_pool_percent: ZSWAP_POOL_PERCENT,
+                }]
+            };
+            return Self {
+                changes,
+                reason: "Disk swap exists; zswap keeps recently swapped pages compressed in RAM in front of it.".into(),
+            };
+        }
+
+        let size_mb = zram_size_mb(total_ram_mb);
+        Self {
+            changes: vec![CompressionChange::EnableZram { size_mb, algorithm: PREFERRED_ALGORITHM.into() }],
+            reason: format!(
+                "No swap to compress into; zram holds about {:.0} MB of pages in {} MB of RAM at a typical 3x ratio.",
+                size_mb as f64, size_mb / 3
+            ),
+        }
+    }
+
+    pub fn print(&self) {
+        println!("\n  {}", self.reason);
+        if self.changes.is_empty() {
+            println!("  No changes recommended");
+        }
+        for change in &self.changes {
+            println!("  → {}", change);
+        }
+    }
+
+    /// Apply every change; needs root and lasts until reboot
+    pub fn apply(&self) -> Result<Vec<String>, String> {
+        if unsafe { libc::geteuid() } != 0 {
+            return Err("Changing zram or zswap requires root".into());
+        }
+        let mut applied = Vec::new();
+        for change in &self.changes {
+            match change {
+                CompressionChange::EnableZram { size_mb, algorithm } => enable_zram(*size_mb, algorithm)?,
+                CompressionChange::EnableZswap { compressor, max_pool_percent } => {
+                    // Unsupported compressors are rejected by the kernel; keep the default then
+                    let _ = write_param("compressor", compressor);
+                    write_param("max_pool_percent", &max_pool_percent.to_string())?;
+                    write_param("enabled", "Y")?;
+                }
+                CompressionChange::DisableZswap => write_param("enabled", "N")?,
+            }
+            applied.push(change.to_string());
+        }
+        Ok(applied)
+    }
+}
+
+/// All of RAM on small machines, half up to 16 GB, then a flat 8 GB
+fn zram_size_mb(total_ram_mb: f64) -> u64 {
+    let ram = total_ram_mb as u64;
+    match ram {
+        0..=4096 => ram,
+        4097..=16384 => ram / 2,
+        _ => 8192,
+    }
+}
+
+fn enable_zram(size_mb: u64, algorithm: &str) -> Result<(), String> {
+    let device = Path::new("/sys/block/zram0");
+    if !device.exists() {
+        run("modprobe", &["zram", "num_devices=1"])?;
+    }
+    if std::fs::read_to_string("/proc/swaps").unwrap_or_default().contains("/dev/zram0") {
+        return Err("/dev/zram0 is already in use as swap".into());
+    }
+    // The algorithm can only be changed before the device is sized
+    let _ = std::fs::write(device.join("reset"), "1");
+    let _ = std::fs::write(device.join("comp_algorithm"), algorithm);
+    std::fs::write(device.join("disksize"), format!("{}M", size_mb))
+        .map_err(|e| format!("Failed to size /dev/zram0: {}", e))?;
+    run("mkswap", &["/dev/zram0"])?;
+    run("swapon", &["-p", ZRAM_SWAP_PRIORITY, "/dev/zram0"])
+}
+
+fn write_param(name: &str, value: &str) -> Result<(), String> {
+    let path = Path::new(ZSWAP_PARAMETERS).join(name);
+    std::fs::write(&path, value).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+fn read_zram(dir: &Path, name: &str) -> ZramDevice {
+    let read = |file: &str| std::fs::read_to_string(dir.join(file)).unwrap_or_default();
+    let (orig_mb, compressed_mb, used_mb) = parse_mm_stat(&read("mm_stat"));
+    ZramDevice {
+        name: name.to_string(),
+        disksize_mb: read("disksize").trim().parse::<u64>().unwrap_or(0) as f64 / 1024.0 / 1024.0,
+        algorithm: selected_algorithm(&read("comp_algorithm")).unwrap_or_default(),
+        orig_mb,
+        compressed_mb,
+        used_mb,
+        is_swap: false,
+    }
+}
+
+fn read_zswap() -> Option<ZswapState> {
+    let read = |file: &str| std::fs::read_to_string(Path::new(ZSWAP_PARAMETERS).join(file)).ok();
+    let enabled = read("enabled")?;
+    let debug = |file: &str| {
+        std::fs::read_to_string(Path::new(ZSWAP_DEBUGFS).join(file))
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+    };
+    Some(ZswapState {
+        enabled: enabled.trim() == "Y",
+        compressor: read("compressor").unwrap_or_default().trim().to_string(),
+        max_pool_percent: read("max_pool_percent").and_then(|s| s.trim().parse().ok()).unwrap_or(0),
+        pool_mb: debug("pool_total_size").map(|b| b as f64 / 1024.0 / 1024.0),
+        stored_mb: debug("stored_pages").map(|p| (p * 4096) as f64 / 1024.0 / 1024.0),
+    })
+}
+
+/// `orig_data_size compr_data_size mem_used_total ...` in bytes, as MB
+pub fn parse_mm_stat(content: &str) -> (f64, f64, f64) {
+    let fields: Vec<f64> = content
+        .split_whitespace()
+        .take(3)
+        .map(|f| f.parse::<u64>().unwrap_or(0) as f64 / 1024.0 / 1024.0)
+        .collect();
+    match fields.as_slice() {
+        [orig, compressed, used] => (*orig, *compressed, *used),
+        _ => (0.0, 0.0, 0.0),
+    }
+}
+
+/// The bracketed entry of `comp_algorithm`, e.g. `lzo [lz4] zstd`
+pub fn selected_algorithm(content: &str) -> Option<String> {
+    let start = content.find('[')?;
+    let end = content[start..].find(']')? + start;
+    Some(content[start + 1..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zram_sysfs() {
+        let (orig, compressed, used) = parse_mm_stat("3145728000 1048576000 1101004800 0 1200000000 1024 0 0\n");
+        assert_eq!(orig, 3000.0);
+        assert_eq!(compressed, 1000.0);
+        assert_eq!(used, 1050.0);
+        assert_eq!(selected_algorithm("lzo lzo-rle [lz4] zstd\n").as_deref(), Some("lz4"));
+        assert_eq!(selected_algorithm("zstd"), None);
+    }
+
+    #[test]
+    fn test_recommend() {
+        let none = CompressionStatus::default();
+        let advice = CompressionAdvice::recommend(&none, 8192.0);
+        assert_eq!(advice.changes, vec![CompressionChange::EnableZram { size_mb: 4096, algorithm: "zstd".into() }]);
+
+        let both = CompressionStatus {
+            zram: vec![ZramDevice { name: "zram0".into(), is_swap: true, ..Default::default() }],
+            zswap: Some(ZswapState { enabled: true, ..Default::default() }),
+            disk_swap: false,
+        };
+        assert_eq!(CompressionAdvice::recommend(&both, 8192.0).changes, vec![CompressionChange::DisableZswap]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Userspace low-memory killer
+//!
+//! An opt-in, earlyoom-style watchdog for the daemon. When PSI shows every
+//! task stalling on memory *and* available memory is nearly gone, it sends
+//! SIGTERM to the least valuable process before the kernel OOM killer runs,
+//! which on a desktop usually arrives only after minutes of thrashing.
+//! Only expendable apps and processes the policy marks as background are
+//! victims, ranked by memory use and PageRank; anything the process policy or
+//! the never-kill list protects is skipped. `oom_kill_any` widens the pool to
+//! every unprotected process.
+
+use std::time::{Duration, Instant};
+use sysinfo::System;
+use tracing::{error, info, warn};
+
+use super::memory::LinuxMemoryOptimizer;
+use super::process::is_expendable;
+use crate::algorithms::ProcessPageRank;
+use crate::core::config::OptimizerConfig;
+use crate::security::audit::{self, AuditAction};
+use crate::security::policy::{Policy, ProcessIdentity};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Time the victim gets to exit and the kernel gets to settle
+const COOLDOWN: Duration = Duration::from_secs(10);
+/// Weight of memory use against low PageRank when ranking victims
+const MEMORY_WEIGHT: f64 = 0.7;
+const RANK_WEIGHT: f64 = 0.3;
+/// Added to expendable and background apps so they rank above everything else
+const EXPENDABLE_BONUS: f64 = 1.0;
+
+/// When to act and what never to touch
+#[derive(Debug, Clone)]
+pub struct LowMemoryConfig {
+    pub psi_full_avg10: f64,
+    pub min_available_percent: f64,
+    /// Process names that are never killed, in addition to the policy
+    pub never_kill: Vec<String>,
+    /// Fall back to processes that are neither expendable nor background
+    pub kill_any: bool,
+    /// Log the victim instead of signalling it
+    pub dry_run: bool,
+}
+
+impl LowMemoryConfig {
+    pub fn from_config(config: &OptimizerConfig, dry_run: bool) -> Self {
+        Self {
+            psi_full_avg10: config.oom_psi_full_avg10,
+            min_available_percent: config.oom_min_available_percent,
+            never_kill: config.oom_never_kill.clone(),
+            kill_any: config.oom_kill_any,
+            dry_run,
+        }
+    }
+
+    /// Both thresholds must be crossed; either alone is normal under load
+    pub fn should_act(&self, psi_full_avg10: f64, available_percent: f64) -> bool {
+        psi_full_avg10 >= self.psi_full_avg10 && available_percent <= self.min_available_percent
+    }
+
+    fn never_kill(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.never_kill.iter().any(|n| name == n.to_lowercase())
+    }
+
+    /// Score added to a process that may be killed, `None` when it must not be
+    fn kill_bonus(&self, identity: &ProcessIdentity, policy: &Policy) -> Option<f64> {
+        if policy.is_excluded(identity) || self.never_kill(&identity.name) {
+            None
+        } else if is_expendable(&identity.name) || policy.is_background(identity) {
+            Some(EXPENDABLE_BONUS)
+        } else {
+            self.kill_any.then_some(0.0)
+        }
+    }
+}
+
+/// A process that may be killed
+#[derive(Debug, Clone)]
+pub struct Victim {
+    pub pid: u32,
+    pub name: String,
+    pub rss_mb: f64,
+    pub score: f64,
+}
+
+/// Killable processes, best victim first
+///
+/// Thread entries are skipped: each reports its process's RSS under its own
+/// name, and signalling one signals the whole process.
+pub fn rank_victims(system: &System, policy: &Policy, config: &LowMemoryConfig) -> Vec<Victim> {
+    let mut pagerank = ProcessPageRank::new();
+    pagerank.compute(system);
+    let own_pid = std::process::id();
+
+    let mut victims: Vec<Victim> = pagerank
+        .get_weighted_candidates(system, MEMORY_WEIGHT, RANK_WEIGHT, usize::MAX)
+        .into_iter()
+        .filter(|(pid, _)| *pid > 1 && *pid != own_pid)
+        .filter_map(|(pid, score)| {
+            let process = system.process(sysinfo::Pid::from_u32(pid))?;
+            if process.thread_kind().is_some() {
+                return None;
+            }
+            let identity = ProcessIdentity::from_process(process, policy);
+            let bonus = config.kill_bonus(&identity, policy)?;
+            Some(Victim {
+                pid,
+                rss_mb: process.memory() as f64 / 1024.0 / 1024.0,
+                score: score + bonus,
+                name: identity.name,
+            })
+        })
+        .collect();
+    victims.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    victims
+}
+
+/// Watch memory on a background thread and kill when thrashing starts
+///
+/// Fails when the kernel does not expose PSI, since available memory alone
+/// cannot tell a full page cache from a machine that is about to freeze.
+pub fn spawn(config: LowMemoryConfig) -> Result<(), String> {
+    if LinuxMemoryOptimizer::read_psi().is_none() {
+        return Err("memory PSI is unavailable (needs Linux 4.20+ with CONFIG_PSI)".to_string());
+    }
+    info!(
+        "Low-memory killer armed: PSI full avg10 >= {}% and available <= {}%{}",
+        config.psi_full_avg10,
+        config.min_available_percent,
+        if config.dry_run { " (dry run)" } else { "" }
+    );
+    std::thread::spawn(move || watch(config));
+    Ok(())
+}
+
+fn watch(config: LowMemoryConfig) {
+    let policy = Policy::load_default();
+    let mut system = System::new();
+    let mut last_kill: Option<Instant> = None;
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if last_kill.is_some_and(|t| t.elapsed() < COOLDOWN) {
+            continue;
+        }
+        let (Some(psi), Ok(status)) = (LinuxMemoryOptimizer::read_psi(), LinuxMemoryOptimizer::get_memory_status())
+        else {
+            continue;
+        };
+        let available_percent = status.available_physical_mb / status.total_physical_mb.max(1.0) * 100.0;
+        if !config.should_act(psi.full_avg10, available_percent) {
+            continue;
+        }
+
+        warn!(
+            "Memory exhausted: PSI full avg10 {:.1}%, {:.0} MB ({:.1}%) available",
+            psi.full_avg10, status.available_physical_mb, available_percent
+        );
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        last_kill = Some(Instant::now());
+        for victim in rank_victims(&system, &policy, &config) {
+            if config.dry_run {
+                info!("Dry run: would send SIGTERM to {} ({}, {:.0} MB)", victim.name, victim.pid, victim.rss_mb);
+                break;
+            }
+            let result = terminate(victim.pid);
+            audit::record(AuditAction::Kill, &victim.name, Some(victim.pid), "SIGTERM", &result);
+            match result {
+                Ok(()) => {
+                    warn!("Sent SIGTERM to {} ({}, {:.0} MB)", victim.name, victim.pid, victim.rss_mb);
+                    break;
+                }
+                // Usually another user's process; try the next one
+                Err(e) => error!("Could not terminate {} ({}): {}", victim.name, victim.pid, e),
+            }
+        }
+    }
+}
+
+fn terminate(pid: u32) -> Result<(), String> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LowMemoryConfig {
+        LowMemoryConfig {
+            psi_full_avg10: 10.0,
+            min_available_percent: 5.0,
+            never_kill: vec!["Firefox".into()],
+            kill_any: false,
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn acts_only_when_both_thresholds_cross() {
+        let config = config();
+        assert!(config.should_act(25.0, 3.0));
+        assert!(!config.should_act(25.0, 20.0));
+        assert!(!config.should_act(2.0, 3.0));
+    }
+
+    #[test]
+    fn never_kill_ignores_case() {
+        let config = config();
+        assert!(config.never_kill("firefox"));
+        assert!(!config.never_kill("firefox-bin"));
+    }
+
+    #[test]
+    fn only_expendable_or_background_processes_are_victims() {
+        use crate::security::policy::{PolicyEffect, PolicyRule, RuleKind};
+
+        let policy = Policy::new(vec![PolicyRule::new(PolicyEffect::Background, RuleKind::Glob, "my_indexer")]);
+        let config = config();
+        assert_eq!(config.kill_bonus(&ProcessIdentity::new("tracker-miner-fs-3"), &policy), Some(EXPENDABLE_BONUS));
+        assert_eq!(config.kill_bonus(&ProcessIdentity::new("my_indexer"), &policy), Some(EXPENDABLE_BONUS));
+        assert_eq!(config.kill_bonus(&ProcessIdentity::new("postgres"), &policy), None);
+        assert_eq!(config.kill_bonus(&ProcessIdentity::new("firefox"), &policy), None);
+
+        let config = LowMemoryConfig { kill_any: true, ..config };
+        assert_eq!(config.kill_bonus(&ProcessIdentity::new("postgres"), &policy), Some(0.0));
+        assert_eq!(config.kill_bonus(&ProcessIdentity::new("firefox"), &policy), None);
+    }
+
+    #[test]
+    fn thread_entries_are_never_victims() {
+        let (done, wait) = std::sync::mpsc::channel::<()>();
+        let worker = std::thread::spawn(move || wait.recv());
+        let mut system = System::new();
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        done.send(()).unwrap();
+        worker.join().unwrap().unwrap();
+
+        assert!(system.processes().values().any(|p| p.thread_kind().is_some()));
+        let config = LowMemoryConfig { kill_any: true, never_kill: Vec::new(), ..config() };
+        let victims = rank_victims(&system, &Policy::new(Vec::new()), &config);
+        assert!(!victims.is_empty());
+        for victim in victims {
+            let process = system.process(sysinfo::Pid::from_u32(victim.pid)).unwrap();
+            assert!(process.thread_kind().is_none(), "{} ({}) is a thread", victim.name, victim.pid);
+        }
+    }
+}
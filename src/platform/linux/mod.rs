@@ -0,0 +1,20 @@
+//! Linux platform support
+//!
+//! - `/proc/meminfo` based memory status and PSI (`/proc/pressure/memory`)
+//! - cgroup v2 proactive reclaim and root-only cache dropping
+//! - systemd unit management for the daemon
+//! - zram/zswap inspection and configuration
+//! - OOM-score adjustment for protected and expendable processes
+//! - opt-in userspace low-memory killer driven by PSI
+
+pub mod compression;
+pub mod lowmem;
+pub mod memory;
+pub mod process;
+pub mod safety;
+pub mod service;
+
+pub use compression::{CompressionAdvice, CompressionStatus};
+pub use memory::{LinuxMemoryOptimizer, PsiStats};
+pub use safety::{SafetyConfig, SafetyGuard};
+pub use service::LinuxDaemonService;
@@ -0,0 +1,319 @@
+//! Per-process OOM-score management
+//!
+//! The kernel OOM killer picks the process with the highest `oom_score`,
+//! which is its share of memory plus `oom_score_adj` (-1000..1000). Protected
+//! processes are pushed down so a runaway app dies before the desktop does,
+//! and known background apps are pushed up so they go first.
+
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+use super::safety::EXPENDABLE_PROCESSES;
+use crate::platform::ProcessMemoryInfo;
+use crate::security::audit::{self, AuditAction};
+use crate::security::policy::{Policy, ProcessIdentity};
+
+/// Adjustment given to protected processes; lowering needs root
+pub const PROTECTED_OOM_ADJ: i32 = -500;
+/// Adjustment given to expendable background apps
+pub const EXPENDABLE_OOM_ADJ: i32 = 500;
+
+const OOM_ADJ_MIN: i32 = -1000;
+const OOM_ADJ_MAX: i32 = 1000;
+/// `comm` is truncated to 15 bytes by the kernel
+const COMM_LEN: usize = 15;
+
+/// How the OOM policy treats a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OomRole {
+    /// Excluded by policy; killed last
+    Protected,
+    /// Known background app; killed first
+    Expendable,
+    /// Left to the kernel's default
+    Normal,
+}
+
+impl std::fmt::Display for OomRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Protected => write!(f, "protected"),
+            Self::Expendable => write!(f, "expendable"),
+            Self::Normal => write!(f, "-"),
+        }
+    }
+}
+
+impl OomRole {
+    /// Adjustment the policy wants, or `None` to leave the process alone
+    pub fn target_adj(&self) -> Option<i32> {
+        match self {
+            Self::Protected => Some(PROTECTED_OOM_ADJ),
+            Self::Expendable => Some(EXPENDABLE_OOM_ADJ),
+            Self::Normal => None,
+        }
+    }
+}
+
+/// One process as the OOM killer sees it
+#[derive(Debug, Clone)]
+pub struct OomEntry {
+    pub pid: u32,
+    pub name: String,
+    pub rss_mb: f64,
+    /// Badness the kernel computed; highest is killed first
+    pub score: i32,
+    pub adj: i32,
+    pub role: OomRole,
+}
+
+/// Result of one pass of the OOM policy
+#[derive(Debug, Clone, Default)]
+pub struct OomPolicyResult {
+    pub adjusted: usize,
+    pub failed: usize,
+}
+
+pub fn oom_score(pid: u32) -> Option<i32> {
+    read_i32(pid, "oom_score")
+}
+
+pub fn oom_score_adj(pid: u32) -> Option<i32> {
+    read_i32(pid, "oom_score_adj")
+}
+
+/// Write `/proc/<pid>/oom_score_adj`
+///
+/// Raising the value works for the owner of the process; lowering it below
+/// what the process started with needs root (CAP_SYS_RESOURCE).
+pub fn set_oom_score_adj(pid: u32, adj: i32) -> Result<(), String> {
+    let adj = adj.clamp(OOM_ADJ_MIN, OOM_ADJ_MAX);
+    let result = std::fs::write(proc_path(pid, "oom_score_adj"), adj.to_string()).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::PermissionDenied {
+            format!("Setting oom_score_adj of {} to {} needs root", pid, adj)
+        } else {
+            format!("Failed to set oom_score_adj of {}: {}", pid, e)
+        }
+    });
+    audit::record(AuditAction::OomScore, "", Some(pid), &format!("oom_score_adj {}", adj), &result);
+    result
+}
+
+/// Whether `name` (a possibly truncated `comm`) is a known expendable app
+pub fn is_expendable(name: &str) -> bool {
+    let name = name.to_lowercase();
+    !name.is_empty()
+        && EXPENDABLE_PROCESSES.iter().any(|p| {
+            let truncated = &p[..p.len().min(COMM_LEN)];
+            name == *p || name == truncated
+        })
+}
+
+pub fn role_of(policy: &Policy, identity: &ProcessIdentity) -> OomRole {
+    if policy.is_excluded(identity) {
+        OomRole::Protected
+    } else if is_expendable(&identity.name) {
+        OomRole::Expendable
+    } else {
+        OomRole::Normal
+    }
+}
+
+/// Every user-space process, in the order the kernel would kill them
+pub fn oom_table(policy: &Policy) -> Vec<OomEntry> {
+    let mut entries: Vec<OomEntry> = user_pids()
+        .into_iter()
+        .filter_map(|pid| {
+            let identity = process_identity(pid);
+            Some(OomEntry {
+                pid,
+                score: oom_score(pid)?,
+                adj: oom_score_adj(pid)?,
+                rss_mb: rss_mb(pid),
+                role: role_of(policy, &identity),
+                name: identity.name,
+            })
+        })
+        .collect();
+    sort_kill_order(&mut entries);
+    entries
+}
+
+/// Highest score first; the kernel breaks ties arbitrarily, larger RSS here
+pub fn sort_kill_order(entries: &mut [OomEntry]) {
+    entries.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(b.rss_mb.partial_cmp(&a.rss_mb).unwrap_or(std::cmp::Ordering::Equal))
+    });
+}
+
+/// Move protected processes down and expendable ones up the kill order
+///
+/// Only touches processes whose adjustment is not already at least as strong
+/// as the target, so a `-1000` set by systemd is never raised to `-500`.
+/// Without root, protected processes are skipped since lowering would fail.
+pub fn apply_oom_policy(policy: &Policy, is_root: bool) -> OomPolicyResult {
+    let mut result = OomPolicyResult::default();
+    for pid in user_pids() {
+        let identity = process_identity(pid);
+        let role = role_of(policy, &identity);
+        let (Some(target), Some(current)) = (role.target_adj(), oom_score_adj(pid)) else {
+            continue;
+        };
+        let needed = match role {
+            OomRole::Protected => is_root && current > target,
+            _ => current < target,
+        };
+        if !needed {
+            continue;
+        }
+        match set_oom_score_adj(pid, target) {
+            Ok(()) => {
+                info!("oom_score_adj of {} ({}) {} -> {}", identity.name, pid, current, target);
+                result.adjusted += 1;
+            }
+            Err(e) => {
+                debug!("{}", e);
+                result.failed += 1;
+            }
+        }
+    }
+    result
+}
+
+pub fn print_table(entries: &[OomEntry], top: usize) {
+    println!("\n☠ OOM kill order (first is killed first)\n");
+    println!("  {:>3} {:>7} {:20} {:>9} {:>6} {:>6}  Role", "#", "PID", "Name", "RSS", "Score", "Adj");
+    for (rank, entry) in entries.iter().take(top).enumerate() {
+        println!(
+            "  {:>3} {:>7} {:20} {:>6.0} MB {:>6} {:>6}  {}",
+            rank + 1,
+            entry.pid,
+            entry.name,
+            entry.rss_mb,
+            entry.score,
+            entry.adj,
+            entry.role
+        );
+    }
+    if entries.len() > top {
+        println!("  ... {} more", entries.len() - top);
+    }
+}
+
+/// Pids of user-space processes; kernel threads have an empty cmdline
+fn user_pids() -> Vec<u32> {
+    let Ok(dir) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    dir.filter_map(|e| e.ok()?.file_name().to_str()?.parse().ok())
+        .filter(|pid| std::fs::read(proc_path(*pid, "cmdline")).is_ok_and(|c| !c.is_empty()))
+        .collect()
+}
+
+fn process_identity(pid: u32) -> ProcessIdentity {
+    let name = std::fs::read_to_string(proc_path(pid, "comm")).unwrap_or_default();
+    ProcessIdentity::new(name.trim()).with_path(std::fs::read_link(proc_path(pid, "exe")).ok())
+}
+
+/// Resident set size from the second field of `statm` (pages)
+fn rss_mb(pid: u32) -> f64 {
+    let pages: u64 = std::fs::read_to_string(proc_path(pid, "statm"))
+        .ok()
+        .and_then(|s| s.split_whitespace().nth(1)?.parse().ok())
+        .unwrap_or(0);
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(4096) as u64;
+    (pages * page_size) as f64 / 1024.0 / 1024.0
+}
+
+/// Private, shared and file-backed memory from `smaps_rollup` (Linux 4.14+)
+pub fn memory_info(pid: u32) -> Result<ProcessMemoryInfo, String> {
+    let path = proc_path(pid, "smaps_rollup");
+    let rollup = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_smaps_rollup(&rollup).ok_or_else(|| format!("No memory totals in {}", path.display()))
+}
+
+/// `None` when the file has no `Rss` line, as for kernel threads
+fn parse_smaps_rollup(rollup: &str) -> Option<ProcessMemoryInfo> {
+    let mb = |field: &str| -> Option<f64> {
+        let line = rollup.lines().find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))?;
+        let kb: f64 = line.trim().trim_end_matches("kB").trim().parse().ok()?;
+        Some(kb / 1024.0)
+    };
+    let resident_mb = mb("Rss")?;
+    let field = |name: &str| mb(name).unwrap_or(0.0);
+    Some(ProcessMemoryInfo {
+        resident_mb,
+        private_mb: field("Private_Clean") + field("Private_Dirty"),
+        shared_mb: field("Shared_Clean") + field("Shared_Dirty"),
+        // Shared memory (shmem, tmpfs) counts as file-backed here too
+        mapped_mb: (resident_mb - field("Anonymous")).max(0.0),
+        footprint_mb: field("Pss"),
+    })
+}
+
+fn read_i32(pid: u32, file: &str) -> Option<i32> {
+    std::fs::read_to_string(proc_path(pid, file)).ok()?.trim().parse().ok()
+}
+
+fn proc_path(pid: u32, file: &str) -> PathBuf {
+    PathBuf::from(format!("/proc/{}/{}", pid, file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, score: i32, rss_mb: f64) -> OomEntry {
+        OomEntry { pid, name: String::new(), rss_mb, score, adj: 0, role: OomRole::Normal }
+    }
+
+    #[test]
+    fn expendable_matches_truncated_comm() {
+        assert!(is_expendable("tracker-miner-f"));
+        assert!(is_expendable("packagekitd"));
+        assert!(!is_expendable("tracker"));
+        assert!(!is_expendable(""));
+    }
+
+    #[test]
+    fn kill_order_is_highest_score_then_rss() {
+        let mut entries = vec![entry(1, 10, 100.0), entry(2, 800, 50.0), entry(3, 10, 400.0)];
+        sort_kill_order(&mut entries);
+        let pids: Vec<u32> = entries.iter().map(|e| e.pid).collect();
+        assert_eq!(pids, vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn protected_roles_win_over_expendable() {
+        let policy = Policy::default();
+        assert_eq!(role_of(&policy, &ProcessIdentity::new("systemd")), OomRole::Protected);
+        assert_eq!(role_of(&policy, &ProcessIdentity::new("baloo_file")), OomRole::Expendable);
+        assert_eq!(role_of(&policy, &ProcessIdentity::new("firefox")), OomRole::Normal);
+    }
+
+    #[test]
+    fn smaps_rollup_splits_private_shared_and_mapped() {
+        let rollup = "\
+55d0c8a4e000-7ffd2b9fe000 ---p 00000000 00:00 0                          [rollup]
+Rss:              204800 kB
+Pss:              153600 kB
+Pss_Anon:         100000 kB
+Shared_Clean:      61440 kB
+Shared_Dirty:      20480 kB
+Private_Clean:     30720 kB
+Private_Dirty:     92160 kB
+Referenced:       204800 kB
+Anonymous:        102400 kB
+Swap:                  0 kB
+";
+        let info = parse_smaps_rollup(rollup).unwrap();
+        assert_eq!(info.resident_mb, 200.0);
+        assert_eq!(info.footprint_mb, 150.0);
+        assert_eq!(info.shared_mb, 80.0);
+        assert_eq!(info.private_mb, 120.0);
+        assert_eq!(info.mapped_mb, 100.0);
+        assert!(parse_smaps_rollup("").is_none());
+    }
+}
@@ -0,0 +1,201 @@
+//! Safety mechanisms for Linux memory optimization
+
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Protected process names that should never be optimized
+pub const PROTECTED_PROCESSES: &[&str] = &[
+    // Init and kernel threads
+    "systemd",
+    "init",
+    "kthreadd",
+    "kswapd",
+    // Core services
+    "dbus-daemon",
+    "dbus-broker",
+    "journald",
+    "udevd",
+    "logind",
+    "networkmanager",
+    "sshd",
+    "polkitd",
+    "pipewire",
+    "pulseaudio",
+    "wireplumber",
+    // Display servers and compositors
+    "xorg",
+    "xwayland",
+    "gnome-shell",
+    "kwin",
+    "plasmashell",
+    "sway",
+    // OOM handling
+    "earlyoom",
+    "oomd",
+];
+
+/// Background apps that are cheap to lose and restart on their own
+pub const EXPENDABLE_PROCESSES: &[&str] = &[
+    // File indexers
+    "tracker-miner-fs-3",
+    "tracker-miner-fs",
+    "tracker-extract-3",
+    "tracker-extract",
+    "baloo_file",
+    "baloo_file_extractor",
+    "localsearch-3",
+    // Software centres and update checkers
+    "packagekitd",
+    "gnome-software",
+    "plasma-discover",
+    "update-notifier",
+    "snap-store",
+    // Activity logging
+    "zeitgeist-daemon",
+    "zeitgeist-datahub",
+];
+
+/// Safety configuration
+#[derive(Debug, Clone)]
+pub struct SafetyConfig {
+    /// Minimum available memory to maintain (MB)
+    pub min_available_mb: f64,
+    /// Maximum optimization frequency
+    pub min_interval: Duration,
+    /// Maximum processes to affect per optimization
+    pub max_processes_per_run: usize,
+    /// Enable dry-run mode (no actual changes)
+    pub dry_run: bool,
+    /// Additional protected process names
+    pub additional_protected: Vec<String>,
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        Self {
+            min_available_mb: 512.0,
+            min_interval: Duration::from_secs(30),
+            max_processes_per_run: 50,
+            dry_run: false,
+            additional_protected: vec![],
+        }
+    }
+}
+
+/// Safety guard for memory optimization
+pub struct SafetyGuard {
+    config: SafetyConfig,
+    last_optimization: Option<Instant>,
+    consecutive_failures: usize,
+    total_optimizations: usize,
+}
+
+impl SafetyGuard {
+    pub fn new(config: SafetyConfig) -> Self {
+        Self {
+            config,
+            last_optimization: None,
+            consecutive_failures: 0,
+            total_optimizations: 0,
+        }
+    }
+
+    /// Check if optimization is safe to proceed
+    pub fn check_safe(&self, current_available_mb: f64) -> Result<(), String> {
+        // Check memory floor
+        if current_available_mb < self.config.min_available_mb {
+            return Err(format!(
+                "Available memory ({:.0}MB) below safety floor ({:.0}MB)",
+                current_available_mb, self.config.min_available_mb
+            ));
+        }
+
+        // Check rate limit
+        if let Some(last) = self.last_optimization {
+            let elapsed = last.elapsed();
+            if elapsed < self.config.min_interval {
+                return Err(format!(
+                    "Rate limited: {:?} remaining",
+                    self.config.min_interval - elapsed
+                ));
+            }
+        }
+
+        // Check consecutive failures
+        if self.consecutive_failures >= 3 {
+            return Err(format!(
+                "Too many consecutive failures ({}). Manual intervention needed.",
+                self.consecutive_failures
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Check if a process is protected
+    pub fn is_protected(&self, process_name: &str) -> bool {
+        let name_lower = process_name.to_lowercase();
+
+        PROTECTED_PROCESSES.iter().any(|p| name_lower.contains(p))
+            || self
+                .config
+                .additional_protected
+                .iter()
+                .any(|p| name_lower.contains(&p.to_lowercase()))
+    }
+
+    /// Record optimization attempt
+    pub fn record_attempt(&mut self, success: bool) {
+        self.last_optimization = Some(Instant::now());
+        self.total_optimizations += 1;
+
+        if success {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures += 1;
+            warn!(
+                "Optimization failed. Consecutive failures: {}",
+                self.consecutive_failures
+            );
+        }
+    }
+
+    /// Check if dry-run mode
+    pub fn is_dry_run(&self) -> bool {
+        self.config.dry_run
+    }
+
+    /// Get max processes per run
+    pub fn max_processes(&self) -> usize {
+        self.config.max_processes_per_run
+    }
+
+    /// Emergency stop - disable further optimizations
+    pub fn emergency_stop(&mut self) {
+        self.consecutive_failures = 100;
+        warn!("Emergency stop activated - optimizations disabled");
+    }
+
+    /// Reset safety counters
+    pub fn reset(&mut self) {
+        self.consecutive_failures = 0;
+        self.last_optimization = None;
+        info!("Safety counters reset");
+    }
+
+    /// Get statistics
+    pub fn stats(&self) -> SafetyStats {
+        SafetyStats {
+            total_optimizations: self.total_optimizations,
+            consecutive_failures: self.consecutive_failures,
+            is_healthy: self.consecutive_failures < 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SafetyStats {
+    pub total_optimizations: usize,
+    pub consecutive_failures: usize,
+    pub is_healthy: bool,
+}
@@ -0,0 +1,134 @@
+//! systemd integration for running the optimizer as a background daemon
+//!
+//! Installs a system unit when running as root, otherwise a user unit under
+//! `~/.config/systemd/user`.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// systemd unit name
+pub const UNIT_NAME: &str = "ruvector-memopt.service";
+
+/// Installed service state
+#[derive(Debug, Clone)]
+pub struct ServiceStatus {
+    pub installed: bool,
+    pub active: bool,
+    pub enabled: bool,
+    pub unit_path: PathBuf,
+    pub user_unit: bool,
+}
+
+/// Manages the systemd unit for the daemon
+pub struct LinuxDaemonService {
+    user_unit: bool,
+}
+
+impl LinuxDaemonService {
+    pub fn new() -> Self {
+        Self {
+            user_unit: unsafe { libc::geteuid() != 0 },
+        }
+    }
+
+    /// Path of the unit file for the current mode
+    pub fn unit_path(&self) -> Result<PathBuf, String> {
+        if self.user_unit {
+            let home = std::env::var_os("HOME").ok_or("HOME is not set")?;
+            Ok(PathBuf::from(home).join(".config/systemd/user").join(UNIT_NAME))
+        } else {
+            Ok(PathBuf::from("/etc/systemd/system").join(UNIT_NAME))
+        }
+    }
+
+    /// Render the unit file for `exe daemon --interval <interval>`
+    pub fn render_unit(exe: &str, interval_secs: u64, user_unit: bool) -> String {
+        format!(
+            "[Unit]\n\
+             Description=RuVector Memory Optimizer\n\
+             After={}\n\
+             \n\
+             [Service]\n\
+             Type=simple\n\
+             ExecStart={} daemon --interval {}\n\
+             ExecReload=/bin/kill -HUP $MAINPID\n\
+             Restart=on-failure\n\
+             RestartSec=10\n\
+             Nice=10\n\
+             \n\
+             [Install]\n\
+             WantedBy={}\n",
+            if user_unit { "default.target" } else { "multi-user.target" },
+            exe,
+            interval_secs,
+            if user_unit { "default.target" } else { "multi-user.target" },
+        )
+    }
+
+    /// Write the unit file, reload systemd and enable/start the service
+    pub fn install(&self, interval_secs: u64) -> Result<String, String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let path = self.unit_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let unit = Self::render_unit(&exe.to_string_lossy(), interval_secs, self.user_unit);
+        std::fs::write(&path, unit)
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+        self.systemctl(&["daemon-reload"])?;
+        self.systemctl(&["enable", "--now", UNIT_NAME])?;
+
+        Ok(format!("Installed and started {} ({:?})", UNIT_NAME, path))
+    }
+
+    /// Stop/disable the service and remove the unit file
+    pub fn uninstall(&self) -> Result<String, String> {
+        let path = self.unit_path()?;
+        if !path.exists() {
+            return Err(format!("{} is not installed", UNIT_NAME));
+        }
+
+        // Ignore failures here: the unit may already be stopped or disabled
+        let _ = self.systemctl(&["disable", "--now", UNIT_NAME]);
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+        self.systemctl(&["daemon-reload"])?;
+
+        Ok(format!("Removed {}", UNIT_NAME))
+    }
+
+    /// Query installed/active/enabled state
+    pub fn status(&self) -> Result<ServiceStatus, String> {
+        let unit_path = self.unit_path()?;
+        Ok(ServiceStatus {
+            installed: unit_path.exists(),
+            active: self.systemctl(&["is-active", "--quiet", UNIT_NAME]).is_ok(),
+            enabled: self.systemctl(&["is-enabled", "--quiet", UNIT_NAME]).is_ok(),
+            unit_path,
+            user_unit: self.user_unit,
+        })
+    }
+
+    fn systemctl(&self, args: &[&str]) -> Result<String, String> {
+        let mut cmd = Command::new("systemctl");
+        if self.user_unit {
+            cmd.arg("--user");
+        }
+        let output = cmd.args(args).output()
+            .map_err(|e| format!("Failed to run systemctl: {}", e))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+impl Default for LinuxDaemonService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
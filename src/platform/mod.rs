@@ -2,12 +2,58 @@
 //!
 //! Provides unified traits that abstract OS-specific implementations.
 
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+pub mod bsd;
+pub mod cleanup;
+#[cfg(target_os = "linux")]
+pub mod linux;
+pub mod power;
+
+/// Built-in protected process names for the current platform
+///
+/// Used by `security::policy` when no user rule matches a process.
+pub fn default_protected_processes() -> &'static [&'static str] {
+    #[cfg(target_os = "windows")]
+    {
+        crate::windows::safety::PROTECTED_PROCESSES
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        crate::macos::safety::PROTECTED_PROCESSES
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux::safety::PROTECTED_PROCESSES
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+    {
+        bsd::safety::PROTECTED_PROCESSES
+    }
+
+    #[cfg(not(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    )))]
+    {
+        &[]
+    }
+}
+
 /// Memory status (cross-platform)
 #[derive(Debug, Clone)]
 pub struct MemoryStatus {
     pub total_physical_mb: f64,
     pub available_physical_mb: f64,
     pub memory_load_percent: u32,
+    /// Swap (page file on Windows)
+    pub total_swap_mb: f64,
+    pub available_swap_mb: f64,
 }
 
 impl MemoryStatus {
@@ -22,16 +68,89 @@ impl MemoryStatus {
     pub fn is_critical(&self) -> bool {
         self.memory_load_percent > 95
     }
+
+    /// Fraction of swap in use (0.0 when there is none)
+    pub fn swap_used_ratio(&self) -> f64 {
+        if self.total_swap_mb > 0.0 {
+            (1.0 - self.available_swap_mb / self.total_swap_mb).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Where a process's resident memory lives (cross-platform)
+///
+/// `private_mb + shared_mb` is the resident size. `mapped_mb` is the part
+/// of it backed by files (executables, libraries, memory-mapped data such
+/// as database caches), whether private or shared. Summing `resident_mb`
+/// over the processes of one app counts the pages they share once per
+/// process; sum `footprint_mb` instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProcessMemoryInfo {
+    pub resident_mb: f64,
+    /// Resident pages no other process maps
+    pub private_mb: f64,
+    /// Resident pages other processes map too: shared memory sections, libraries
+    pub shared_mb: f64,
+    /// Resident pages backed by files
+    pub mapped_mb: f64,
+    /// What the process costs without double-counting: PSS on Linux, the
+    /// private working set on Windows, `phys_footprint` on macOS
+    pub footprint_mb: f64,
+}
+
+impl ProcessMemoryInfo {
+    /// Totals over several processes, or `None` if none could be read
+    pub fn combined(pids: &[u32]) -> Option<Self> {
+        pids.iter().filter_map(|&pid| process_memory_info(pid).ok()).reduce(|a, b| Self {
+            resident_mb: a.resident_mb + b.resident_mb,
+            private_mb: a.private_mb + b.private_mb,
+            shared_mb: a.shared_mb + b.shared_mb,
+            mapped_mb: a.mapped_mb + b.mapped_mb,
+            footprint_mb: a.footprint_mb + b.footprint_mb,
+        })
+    }
+}
+
+/// Private, shared and file-backed memory of one process
+///
+/// Reads the whole address space, so it is slower than the resident size
+/// `sysinfo` reports; other users' processes usually need elevation.
+pub fn process_memory_info(pid: u32) -> Result<ProcessMemoryInfo, String> {
+    #[cfg(target_os = "windows")]
+    {
+        crate::windows::process::get_memory_info(pid)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        crate::macos::process::get_memory_info(pid)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux::process::memory_info(pid)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = pid;
+        Err("Memory breakdowns are not supported on this platform".to_string())
+    }
 }
 
 /// Optimization result (cross-platform)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OptimizationResult {
     pub freed_mb: f64,
     pub before_available_mb: f64,
     pub after_available_mb: f64,
     pub processes_affected: usize,
     pub duration_ms: u64,
+    /// Whether loosely tied process clusters were trimmed together
+    #[serde(default)]
+    pub clustered: bool,
 }
 
 /// Platform-agnostic memory optimizer trait
@@ -61,7 +180,23 @@ pub fn create_optimizer() -> Box<dyn MemoryOptimizer> {
         Box::new(MacOptimizer::new())
     }
 
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(LinuxOptimizer::new())
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+    {
+        Box::new(BsdOptimizer::new())
+    }
+
+    #[cfg(not(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "openbsd"
+    )))]
     {
         Box::new(StubOptimizer::new())
     }
@@ -86,25 +221,42 @@ impl WindowsOptimizer {
 }
 
 #[cfg(target_os = "windows")]
-impl MemoryOptimizer for WindowsOptimizer {
-    fn get_memory_status(&self) -> Result<MemoryStatus, String> {
-        let status = crate::windows::memory::WindowsMemoryOptimizer::get_memory_status()?;
-        Ok(MemoryStatus {
+impl From<&crate::windows::memory::MemoryStatus> for MemoryStatus {
+    fn from(status: &crate::windows::memory::MemoryStatus) -> Self {
+        Self {
             total_physical_mb: status.total_physical_mb,
             available_physical_mb: status.available_physical_mb,
             memory_load_percent: status.memory_load_percent,
-        })
+            total_swap_mb: status.total_page_file_mb,
+            available_swap_mb: status.available_page_file_mb,
+        }
     }
+}
 
-    fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
-        let result = self.inner.optimize(aggressive)?;
-        Ok(OptimizationResult {
+#[cfg(target_os = "windows")]
+impl From<&crate::windows::memory::OptimizationResult> for OptimizationResult {
+    fn from(result: &crate::windows::memory::OptimizationResult) -> Self {
+        Self {
             freed_mb: result.freed_mb,
             before_available_mb: result.before_available_mb,
             after_available_mb: result.after_available_mb,
             processes_affected: result.processes_trimmed,
             duration_ms: result.duration_ms,
-        })
+            clustered: result.clustered,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl MemoryOptimizer for WindowsOptimizer {
+    fn get_memory_status(&self) -> Result<MemoryStatus, String> {
+        let status = crate::windows::memory::WindowsMemoryOptimizer::get_memory_status()?;
+        Ok((&status).into())
+    }
+
+    fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+        let result = self.inner.optimize(aggressive)?;
+        Ok((&result).into())
     }
 
     fn has_elevated_privileges(&self) -> bool {
@@ -134,37 +286,197 @@ impl MacOptimizer {
     }
 }
 
+#[cfg(target_os = "macos")]
+impl From<&crate::macos::memory::MemoryStatus> for MemoryStatus {
+    fn from(status: &crate::macos::memory::MemoryStatus) -> Self {
+        Self {
+            total_physical_mb: status.total_physical_mb,
+            available_physical_mb: status.available_physical_mb,
+            memory_load_percent: status.memory_load_percent,
+            total_swap_mb: status.total_swap_mb,
+            available_swap_mb: status.available_swap_mb,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<&crate::macos::memory::OptimizationResult> for OptimizationResult {
+    fn from(result: &crate::macos::memory::OptimizationResult) -> Self {
+        Self {
+            freed_mb: result.freed_mb,
+            before_available_mb: result.before_available_mb,
+            after_available_mb: result.after_available_mb,
+            processes_affected: result.processes_affected,
+            duration_ms: result.duration_ms,
+            clustered: false,
+        }
+    }
+}
+
 #[cfg(target_os = "macos")]
 impl MemoryOptimizer for MacOptimizer {
     fn get_memory_status(&self) -> Result<MemoryStatus, String> {
         let status = crate::macos::memory::MacMemoryOptimizer::get_memory_status()?;
-        Ok(MemoryStatus {
+        Ok((&status).into())
+    }
+
+    fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+        let result = self.inner.optimize(aggressive)?;
+        Ok((&result).into())
+    }
+
+    fn has_elevated_privileges(&self) -> bool {
+        self.inner.has_sudo_privileges()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        if self.inner.is_apple_silicon() {
+            "macOS (Apple Silicon)"
+        } else {
+            "macOS (Intel)"
+        }
+    }
+}
+
+// ============================================================================
+// Linux Implementation
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+pub struct LinuxOptimizer {
+    inner: linux::LinuxMemoryOptimizer,
+}
+
+#[cfg(target_os = "linux")]
+impl LinuxOptimizer {
+    pub fn new() -> Self {
+        Self {
+            inner: linux::LinuxMemoryOptimizer::new(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Default for LinuxOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<&linux::memory::MemoryStatus> for MemoryStatus {
+    fn from(status: &linux::memory::MemoryStatus) -> Self {
+        Self {
             total_physical_mb: status.total_physical_mb,
             available_physical_mb: status.available_physical_mb,
             memory_load_percent: status.memory_load_percent,
-        })
+            total_swap_mb: status.total_swap_mb,
+            available_swap_mb: status.available_swap_mb,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<&linux::memory::OptimizationResult> for OptimizationResult {
+    fn from(result: &linux::memory::OptimizationResult) -> Self {
+        Self {
+            freed_mb: result.freed_mb,
+            before_available_mb: result.before_available_mb,
+            after_available_mb: result.after_available_mb,
+            processes_affected: result.processes_affected,
+            duration_ms: result.duration_ms,
+            clustered: false,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl MemoryOptimizer for LinuxOptimizer {
+    fn get_memory_status(&self) -> Result<MemoryStatus, String> {
+        let status = linux::LinuxMemoryOptimizer::get_memory_status()?;
+        Ok((&status).into())
     }
 
     fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
         let result = self.inner.optimize(aggressive)?;
-        Ok(OptimizationResult {
+        Ok((&result).into())
+    }
+
+    fn has_elevated_privileges(&self) -> bool {
+        self.inner.has_root_privileges()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "Linux"
+    }
+}
+
+// ============================================================================
+// FreeBSD/OpenBSD Implementation
+// ============================================================================
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+pub struct BsdOptimizer {
+    inner: bsd::BsdMemoryOptimizer,
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+impl BsdOptimizer {
+    pub fn new() -> Self {
+        Self {
+            inner: bsd::BsdMemoryOptimizer::new(),
+        }
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+impl From<&bsd::memory::MemoryStatus> for MemoryStatus {
+    fn from(status: &bsd::memory::MemoryStatus) -> Self {
+        Self {
+            total_physical_mb: status.total_physical_mb,
+            available_physical_mb: status.available_physical_mb,
+            memory_load_percent: status.memory_load_percent,
+            total_swap_mb: status.total_swap_mb,
+            available_swap_mb: status.available_swap_mb,
+        }
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+impl From<&bsd::memory::OptimizationResult> for OptimizationResult {
+    fn from(result: &bsd::memory::OptimizationResult) -> Self {
+        Self {
             freed_mb: result.freed_mb,
             before_available_mb: result.before_available_mb,
             after_available_mb: result.after_available_mb,
             processes_affected: result.processes_affected,
             duration_ms: result.duration_ms,
-        })
+            clustered: false,
+        }
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "openbsd"))]
+impl MemoryOptimizer for BsdOptimizer {
+    fn get_memory_status(&self) -> Result<MemoryStatus, String> {
+        let status = bsd::BsdMemoryOptimizer::get_memory_status()?;
+        Ok((&status).into())
+    }
+
+    fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+        let result = self.inner.optimize(aggressive)?;
+        Ok((&result).into())
     }
 
     fn has_elevated_privileges(&self) -> bool {
-        self.inner.has_sudo_privileges()
+        self.inner.has_root_privileges()
     }
 
     fn platform_name(&self) -> &'static str {
-        if self.inner.is_apple_silicon() {
-            "macOS (Apple Silicon)"
+        if cfg!(target_os = "freebsd") {
+            "FreeBSD"
         } else {
-            "macOS (Intel)"
+            "OpenBSD"
         }
     }
 }
@@ -173,17 +485,35 @@ impl MemoryOptimizer for MacOptimizer {
 // Stub Implementation (for unsupported platforms)
 // ============================================================================
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd"
+)))]
 pub struct StubOptimizer;
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd"
+)))]
 impl StubOptimizer {
     pub fn new() -> Self {
         Self
     }
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd"
+)))]
 impl MemoryOptimizer for StubOptimizer {
     fn get_memory_status(&self) -> Result<MemoryStatus, String> {
         use sysinfo::System;
@@ -193,6 +523,8 @@ impl MemoryOptimizer for StubOptimizer {
 
         let total = sys.total_memory() as f64 / 1024.0 / 1024.0;
         let available = sys.available_memory() as f64 / 1024.0 / 1024.0;
+        let total_swap = sys.total_swap() as f64 / 1024.0 / 1024.0;
+        let free_swap = sys.free_swap() as f64 / 1024.0 / 1024.0;
         let load = if total > 0.0 {
             (((total - available) / total) * 100.0) as u32
         } else {
@@ -203,6 +535,8 @@ impl MemoryOptimizer for StubOptimizer {
             total_physical_mb: total,
             available_physical_mb: available,
             memory_load_percent: load,
+            total_swap_mb: total_swap,
+            available_swap_mb: free_swap,
         })
     }
 
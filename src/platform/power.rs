@@ -0,0 +1,541 @@
+//! Power plan / energy mode management
+//!
+//! One switch for the build booster, Game Mode and thermal module:
+//!
+//! - Windows: `powercfg` power schemes by their built-in GUIDs
+//! - Linux: power-profiles-daemon when running, otherwise the cpufreq
+//!   governor (and energy preference where the driver has one) via sysfs
+//! - macOS: `pmset` low power / high power mode
+//!
+//! [`get_active_plan`] returns the exact backend setting, so a caller can
+//! switch plans temporarily and put back a custom scheme with [`restore`].
+//! [`power_source`] tells whether the machine is running on AC or battery.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Built-in plans common to every platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerPlan {
+    HighPerformance,
+    Balanced,
+    PowerSaver,
+}
+
+impl PowerPlan {
+    /// Name the OS uses for this plan in its own settings UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            #[cfg(target_os = "macos")]
+            PowerPlan::HighPerformance => "High Power Mode",
+            #[cfg(target_os = "macos")]
+            PowerPlan::Balanced => "Automatic",
+            #[cfg(target_os = "macos")]
+            PowerPlan::PowerSaver => "Low Power Mode",
+            #[cfg(target_os = "linux")]
+            PowerPlan::HighPerformance => "performance",
+            #[cfg(target_os = "linux")]
+            PowerPlan::Balanced => "balanced",
+            #[cfg(target_os = "linux")]
+            PowerPlan::PowerSaver => "power-saver",
+            #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+            PowerPlan::HighPerformance => "High performance",
+            #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+            PowerPlan::Balanced => "Balanced",
+            #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+            PowerPlan::PowerSaver => "Power saver",
+        }
+    }
+
+    /// The next plan down, for cooling off
+    pub fn cooler(&self) -> Option<PowerPlan> {
+        match self {
+            PowerPlan::HighPerformance => Some(PowerPlan::Balanced),
+            PowerPlan::Balanced => Some(PowerPlan::PowerSaver),
+            PowerPlan::PowerSaver => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PowerPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// The power setting currently in effect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivePlan {
+    /// `None` for custom schemes and governors that don't map to a plan
+    pub plan: Option<PowerPlan>,
+    /// Display name as the OS reports it
+    pub name: String,
+    /// Backend value to restore: scheme GUID, profile/governor or pmset keys
+    setting: String,
+}
+
+/// Read the active plan
+pub fn get_active_plan() -> Result<ActivePlan, String> {
+    sys::get_active_plan()
+}
+
+/// Switch to one of the built-in plans
+pub fn set_plan(plan: PowerPlan) -> Result<(), String> {
+    sys::set_plan(plan)
+}
+
+/// Put back a plan returned by [`get_active_plan`], including custom ones
+pub fn restore(previous: &ActivePlan) -> Result<(), String> {
+    sys::apply(&previous.setting)
+}
+
+/// Switch to `plan`, returning the plan to restore afterwards
+///
+/// `Ok(None)` when `plan` was already active.
+pub fn switch_to(plan: PowerPlan) -> Result<Option<ActivePlan>, String> {
+    let previous = get_active_plan()?;
+    if previous.plan == Some(plan) {
+        return Ok(None);
+    }
+    set_plan(plan)?;
+    Ok(Some(previous))
+}
+
+/// Where the machine is drawing power from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+impl std::fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PowerSource::Ac => write!(f, "AC power"),
+            PowerSource::Battery => write!(f, "battery"),
+        }
+    }
+}
+
+/// Current power source; desktops and unknown states report [`PowerSource::Ac`]
+pub fn power_source() -> PowerSource {
+    sys::power_source()
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let message = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+        Err(format!("{} failed: {}", program, message))
+    }
+}
+
+// Built-in scheme GUIDs; the same on every Windows install
+const GUID_HIGH_PERFORMANCE: &str = "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c";
+const GUID_ULTIMATE_PERFORMANCE: &str = "e9a42b02-d5df-448d-aa00-03f14749eb61";
+const GUID_BALANCED: &str = "381b4222-f694-41f0-9685-ff5bb260df2e";
+const GUID_POWER_SAVER: &str = "a1841308-3541-4fab-bc81-f71556f20b4a";
+
+/// Scheme GUID and name from `powercfg /getactivescheme`
+///
+/// The text around them is localized, so only the GUID itself and the
+/// parenthesized name are relied on.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_active_scheme(output: &str) -> Option<ActivePlan> {
+    let guid = output
+        .split_whitespace()
+        .find(|w| w.len() == 36 && w.chars().filter(|c| *c == '-').count() == 4)?
+        .to_lowercase();
+    let name = output
+        .split_once('(')
+        .and_then(|(_, rest)| rest.rsplit_once(')'))
+        .map(|(name, _)| name.trim().to_string())
+        .unwrap_or_else(|| guid.clone());
+    let plan = match guid.as_str() {
+        GUID_HIGH_PERFORMANCE | GUID_ULTIMATE_PERFORMANCE => Some(PowerPlan::HighPerformance),
+        GUID_BALANCED => Some(PowerPlan::Balanced),
+        GUID_POWER_SAVER => Some(PowerPlan::PowerSaver),
+        _ => None,
+    };
+    Some(ActivePlan { plan, name, setting: guid })
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::*;
+
+    pub fn get_active_plan() -> Result<ActivePlan, String> {
+        let output = run("powercfg", &["/getactivescheme"])?;
+        parse_active_scheme(&output).ok_or_else(|| "Unrecognized powercfg output".to_string())
+    }
+
+    pub fn set_plan(plan: PowerPlan) -> Result<(), String> {
+        let guid = match plan {
+            PowerPlan::HighPerformance => GUID_HIGH_PERFORMANCE,
+            PowerPlan::Balanced => GUID_BALANCED,
+            PowerPlan::PowerSaver => GUID_POWER_SAVER,
+        };
+        // Modern Standby machines ship with only Balanced installed
+        apply(guid).map_err(|e| format!("{} plan unavailable ({}); see `powercfg /list`", plan, e))
+    }
+
+    pub fn apply(guid: &str) -> Result<(), String> {
+        run("powercfg", &["/setactive", guid]).map(|_| ())
+    }
+
+    pub fn power_source() -> PowerSource {
+        use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        // ACLineStatus: 0 offline, 1 online, 255 unknown
+        if unsafe { GetSystemPowerStatus(&mut status) } != 0 && status.ACLineStatus == 0 {
+            PowerSource::Battery
+        } else {
+            PowerSource::Ac
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const CPU_DIR: &str = "/sys/devices/system/cpu";
+    const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+    /// Prefix marking a power-profiles-daemon setting
+    const PPD: &str = "ppd:";
+
+    pub fn get_active_plan() -> Result<ActivePlan, String> {
+        if let Ok(profile) = run("powerprofilesctl", &["get"]) {
+            let profile = profile.trim().to_string();
+            let plan = match profile.as_str() {
+                "performance" => Some(PowerPlan::HighPerformance),
+                "balanced" => Some(PowerPlan::Balanced),
+                "power-saver" => Some(PowerPlan::PowerSaver),
+                _ => None,
+            };
+            return Ok(ActivePlan { plan, name: profile.clone(), setting: format!("{}{}", PPD, profile) });
+        }
+
+        let cpufreq = cpufreq_dirs().into_iter().next().ok_or("No cpufreq support on this system")?;
+        let governor = read(&cpufreq.join("scaling_governor")).ok_or("Cannot read the CPU governor")?;
+        let preference = read(&cpufreq.join("energy_performance_preference"));
+        let plan = governor_plan(&governor, preference.as_deref());
+        let (name, setting) = match preference {
+            Some(pref) => (format!("{} ({})", governor, pref), format!("{}:{}", governor, pref)),
+            None => (governor.clone(), governor),
+        };
+        Ok(ActivePlan { plan, name, setting })
+    }
+
+    pub fn set_plan(plan: PowerPlan) -> Result<(), String> {
+        if run("powerprofilesctl", &["get"]).is_ok() {
+            let profile = match plan {
+                PowerPlan::HighPerformance => "performance",
+                PowerPlan::Balanced => "balanced",
+                PowerPlan::PowerSaver => "power-saver",
+            };
+            return apply(&format!("{}{}", PPD, profile));
+        }
+
+        let cpufreq = cpufreq_dirs().into_iter().next().ok_or("No cpufreq support on this system")?;
+        let available = read(&cpufreq.join("scaling_available_governors")).unwrap_or_default();
+        let available: Vec<&str> = available.split_whitespace().collect();
+        let has_preference = cpufreq.join("energy_performance_preference").exists();
+        apply(&governor_for(plan, &available, has_preference)?)
+    }
+
+    /// `ppd:<profile>`, `<governor>` or `<governor>:<energy preference>`
+    pub fn apply(setting: &str) -> Result<(), String> {
+        if let Some(profile) = setting.strip_prefix(PPD) {
+            return run("powerprofilesctl", &["set", profile]).map(|_| ());
+        }
+        let (governor, preference) = match setting.split_once(':') {
+            Some((g, p)) => (g, Some(p)),
+            None => (setting, None),
+        };
+        let dirs = cpufreq_dirs();
+        if dirs.is_empty() {
+            return Err("No cpufreq support on this system".into());
+        }
+        for dir in dirs {
+            write(&dir.join("scaling_governor"), governor)?;
+            if let Some(preference) = preference {
+                // "performance" governor pins the preference; writing it fails harmlessly
+                let _ = write(&dir.join("energy_performance_preference"), preference);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn power_source() -> PowerSource {
+        let supplies: Vec<(String, String, String)> = fs::read_dir(POWER_SUPPLY_DIR)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            // Mice, keyboards and headsets report their own batteries
+            .filter(|p| read(&p.join("scope")).as_deref() != Some("Device"))
+            .map(|p| {
+                let field = |name: &str| read(&p.join(name)).unwrap_or_default();
+                (field("type"), field("online"), field("status"))
+            })
+            .collect();
+        classify_supplies(&supplies)
+    }
+
+    fn cpufreq_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = fs::read_dir(CPU_DIR)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("cpu").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(|e| e.path().join("cpufreq"))
+            .filter(|p| p.join("scaling_governor").exists())
+            .collect();
+        dirs.sort();
+        dirs
+    }
+
+    fn read(path: &std::path::Path) -> Option<String> {
+        fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+    }
+
+    fn write(path: &std::path::Path, value: &str) -> Result<(), String> {
+        fs::write(path, value).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                "Changing the CPU governor needs root".to_string()
+            } else {
+                format!("Failed to write {:?}: {}", path, e)
+            }
+        })
+    }
+}
+
+/// Plan matching a governor and optional energy preference
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn governor_plan(governor: &str, preference: Option<&str>) -> Option<PowerPlan> {
+    match (governor, preference) {
+        ("performance", _) => Some(PowerPlan::HighPerformance),
+        // intel_pstate/amd-pstate: "powersave" is the normal governor and the
+        // energy preference decides how it behaves
+        ("powersave", Some("performance" | "balance_performance")) => Some(PowerPlan::Balanced),
+        ("powersave", Some("default")) => Some(PowerPlan::Balanced),
+        ("powersave", _) => Some(PowerPlan::PowerSaver),
+        ("schedutil" | "ondemand" | "conservative", _) => Some(PowerPlan::Balanced),
+        _ => None,
+    }
+}
+
+/// Setting string for `plan` given the driver's governors
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn governor_for(plan: PowerPlan, available: &[&str], has_preference: bool) -> Result<String, String> {
+    let pick = |options: &[&str]| options.iter().find(|g| available.contains(g)).map(|g| g.to_string());
+    let setting = match (plan, has_preference) {
+        (PowerPlan::HighPerformance, _) => pick(&["performance"]),
+        (PowerPlan::Balanced, true) => pick(&["powersave"]).map(|g| format!("{}:balance_performance", g)),
+        (PowerPlan::Balanced, false) => pick(&["schedutil", "ondemand", "conservative"]),
+        (PowerPlan::PowerSaver, true) => pick(&["powersave"]).map(|g| format!("{}:power", g)),
+        (PowerPlan::PowerSaver, false) => pick(&["powersave", "conservative"]),
+    };
+    setting.ok_or_else(|| format!("No governor for the {} plan (available: {})", plan, available.join(" ")))
+}
+
+/// Power source from `(type, online, status)` of each `/sys/class/power_supply` entry
+///
+/// On battery only when a battery is discharging and no adapter is online;
+/// a full battery on a plugged-in laptop reports "Not charging", not AC.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn classify_supplies(supplies: &[(String, String, String)]) -> PowerSource {
+    let adapter_online = supplies.iter().any(|(kind, online, _)| kind != "Battery" && online == "1");
+    let discharging = supplies.iter().any(|(kind, _, status)| kind == "Battery" && status == "Discharging");
+    if discharging && !adapter_online {
+        PowerSource::Battery
+    } else {
+        PowerSource::Ac
+    }
+}
+
+/// Power source from `pmset -g batt` ("Now drawing from 'Battery Power'")
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset_batt(output: &str) -> PowerSource {
+    if output.lines().next().is_some_and(|line| line.contains("'Battery Power'")) {
+        PowerSource::Battery
+    } else {
+        PowerSource::Ac
+    }
+}
+
+/// pmset keys this module reads and writes
+const PMSET_KEYS: &[&str] = &["lowpowermode", "highpowermode", "powermode"];
+
+/// Values of [`PMSET_KEYS`] from `pmset -g`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> Vec<(String, u8)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let key = parts.next()?;
+            let value = parts.next()?.parse().ok()?;
+            PMSET_KEYS.contains(&key).then(|| (key.to_string(), value))
+        })
+        .collect()
+}
+
+/// Plan for pmset values; `powermode` is 0 auto, 1 low, 2 high
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn pmset_plan(values: &[(String, u8)]) -> PowerPlan {
+    let get = |key: &str| values.iter().find(|(k, _)| k == key).map(|(_, v)| *v);
+    match (get("powermode"), get("lowpowermode"), get("highpowermode")) {
+        (Some(2), _, _) | (_, _, Some(1)) => PowerPlan::HighPerformance,
+        (Some(1), _, _) | (_, Some(1), _) => PowerPlan::PowerSaver,
+        _ => PowerPlan::Balanced,
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod sys {
+    use super::*;
+
+    fn read() -> Result<Vec<(String, u8)>, String> {
+        Ok(parse_pmset(&run("pmset", &["-g"])?))
+    }
+
+    pub fn get_active_plan() -> Result<ActivePlan, String> {
+        let values = read()?;
+        let plan = pmset_plan(&values);
+        let setting = values.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(",");
+        Ok(ActivePlan { plan: Some(plan), name: plan.label().to_string(), setting })
+    }
+
+    pub fn set_plan(plan: PowerPlan) -> Result<(), String> {
+        let values = read()?;
+        let has = |key: &str| values.iter().any(|(k, _)| k == key);
+        let mut settings = Vec::new();
+        if has("powermode") {
+            let mode = match plan {
+                PowerPlan::HighPerformance => 2,
+                PowerPlan::Balanced => 0,
+                PowerPlan::PowerSaver => 1,
+            };
+            settings.push(format!("powermode={}", mode));
+        } else {
+            settings.push(format!("lowpowermode={}", (plan == PowerPlan::PowerSaver) as u8));
+            if has("highpowermode") {
+                settings.push(format!("highpowermode={}", (plan == PowerPlan::HighPerformance) as u8));
+            } else if plan == PowerPlan::HighPerformance {
+                // Without High Power Mode, not being in Low Power Mode is the fastest setting
+                tracing::debug!("High Power Mode not available on this Mac");
+            }
+        }
+        apply(&settings.join(","))
+    }
+
+    /// Comma-separated `key=value` pmset settings
+    pub fn apply(setting: &str) -> Result<(), String> {
+        let mut args = vec!["-a".to_string()];
+        for pair in setting.split(',').filter(|p| !p.is_empty()) {
+            let Some((key, value)) = pair.split_once('=') else { continue };
+            args.push(key.to_string());
+            args.push(value.to_string());
+        }
+        if args.len() == 1 {
+            return Ok(());
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run("pmset", &args).map(|_| ()).map_err(|e| {
+            if unsafe { libc::geteuid() } != 0 {
+                "Changing the power mode needs root (sudo)".to_string()
+            } else {
+                e
+            }
+        })
+    }
+
+    pub fn power_source() -> PowerSource {
+        run("pmset", &["-g", "batt"]).map(|out| parse_pmset_batt(&out)).unwrap_or(PowerSource::Ac)
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+mod sys {
+    use super::*;
+
+    pub fn get_active_plan() -> Result<ActivePlan, String> {
+        Err("Power plans are not supported on this platform".into())
+    }
+
+    pub fn set_plan(_plan: PowerPlan) -> Result<(), String> {
+        Err("Power plans are not supported on this platform".into())
+    }
+
+    pub fn apply(_setting: &str) -> Result<(), String> {
+        Err("Power plans are not supported on this platform".into())
+    }
+
+    pub fn power_source() -> PowerSource {
+        PowerSource::Ac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_powercfg() {
+        let plan = parse_active_scheme("Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)\r\n").unwrap();
+        assert_eq!(plan.plan, Some(PowerPlan::Balanced));
+        assert_eq!(plan.name, "Balanced");
+
+        let custom = parse_active_scheme("GUID du mode de gestion : 1ca3a6d1-0000-4e0a-9f3e-6b8a0c1d2e3f  (Mon plan)").unwrap();
+        assert_eq!(custom.plan, None);
+        assert_eq!(custom.setting, "1ca3a6d1-0000-4e0a-9f3e-6b8a0c1d2e3f");
+        assert!(parse_active_scheme("").is_none());
+    }
+
+    #[test]
+    fn test_linux_and_macos_mapping() {
+        assert_eq!(governor_plan("powersave", Some("balance_performance")), Some(PowerPlan::Balanced));
+        assert_eq!(governor_plan("powersave", None), Some(PowerPlan::PowerSaver));
+        assert_eq!(governor_plan("userspace", None), None);
+
+        let pstate = ["performance", "powersave"];
+        assert_eq!(governor_for(PowerPlan::Balanced, &pstate, true).unwrap(), "powersave:balance_performance");
+        let acpi = ["conservative", "ondemand", "userspace", "powersave", "performance", "schedutil"];
+        assert_eq!(governor_for(PowerPlan::Balanced, &acpi, false).unwrap(), "schedutil");
+        assert!(governor_for(PowerPlan::HighPerformance, &["schedutil"], false).is_err());
+
+        let values = parse_pmset("System-wide power settings:\n lowpowermode         1\n sleep                1\n");
+        assert_eq!(values, vec![("lowpowermode".to_string(), 1)]);
+        assert_eq!(pmset_plan(&values), PowerPlan::PowerSaver);
+    }
+
+    #[test]
+    fn test_power_source() {
+        let supply = |kind: &str, online: &str, status: &str| (kind.to_string(), online.to_string(), status.to_string());
+        let unplugged = [supply("Mains", "0", ""), supply("Battery", "", "Discharging")];
+        assert_eq!(classify_supplies(&unplugged), PowerSource::Battery);
+        let plugged = [supply("Mains", "1", ""), supply("Battery", "", "Not charging")];
+        assert_eq!(classify_supplies(&plugged), PowerSource::Ac);
+        assert_eq!(classify_supplies(&[]), PowerSource::Ac);
+
+        let batt = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t87%; discharging; 5:12 remaining present: true\n";
+        assert_eq!(parse_pmset_batt(batt), PowerSource::Battery);
+        assert_eq!(parse_pmset_batt("Now drawing from 'AC Power'\n"), PowerSource::Ac);
+    }
+}
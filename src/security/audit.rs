@@ -0,0 +1,635 @@
+//! Audit log of every action that touches another process
+//!
+//! Trims, suspends, kills, priority and affinity changes and cache drops each
+//! append one JSON line to `audit.jsonl` under
+//! [`crate::features::config_dir`]: who did it, when, to what, and whether it
+//! worked. Every record carries an HMAC-SHA256 signature made with a per-user
+//! key (`audit.key`), so an edited or forged line shows up in `audit tail`.
+//! Records are chained: each signs its sequence number and the signature of
+//! the one before, and `audit.jsonl.head` keeps the latest, so deleted,
+//! reordered or truncated lines show up as gaps. The chain continues across
+//! rotation.
+//! With `audit_system_log` set, records also go to the Windows Event Log or
+//! journald.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// File name of the audit log inside the config directory
+pub const AUDIT_FILE: &str = "audit.jsonl";
+/// File name of the signing key inside the config directory
+pub const AUDIT_KEY_FILE: &str = "audit.key";
+/// Size at which the log is rotated to `audit.jsonl.1`
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+/// Event source / syslog identifier for system log copies
+const SYSTEM_LOG_SOURCE: &str = "RuVectorMemOpt";
+
+/// What was done to the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Working set trimmed
+    Trim,
+    /// Process stopped (SIGSTOP, app suspend)
+    Suspend,
+    /// Stopped process continued
+    Resume,
+    /// Process asked or forced to exit
+    Kill,
+    /// Scheduling priority or nice value changed
+    Priority,
+    /// I/O priority changed (Windows)
+    IoPriority,
+    /// CPU affinity changed
+    Affinity,
+    /// Linux OOM score changed
+    OomScore,
+    /// System cache dropped (standby list, page cache, `purge`)
+    CacheDrop,
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Trim => "trim",
+            Self::Suspend => "suspend",
+            Self::Resume => "resume",
+            Self::Kill => "kill",
+            Self::Priority => "priority",
+            Self::IoPriority => "io_priority",
+            Self::Affinity => "affinity",
+            Self::OomScore => "oom_score",
+            Self::CacheDrop => "cache_drop",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One signed audit entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Account the acting process runs as
+    pub user: String,
+    /// Acting program and its PID, e.g. `ruvector-memopt (4120)`
+    pub actor: String,
+    pub action: AuditAction,
+    /// Process name, or the system resource for cache drops
+    pub target: String,
+    pub target_pid: Option<u32>,
+    /// Action parameters, e.g. the new priority
+    #[serde(default)]
+    pub detail: String,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Position in the chain, from 1; 0 on records written before chaining
+    #[serde(default)]
+    pub seq: u64,
+    /// Signature of the record before this one, empty for the first
+    #[serde(default)]
+    pub prev: String,
+    /// Hex HMAC-SHA256 of the record with this field empty
+    #[serde(default)]
+    pub signature: String,
+}
+
+impl AuditRecord {
+    /// Record stamped with the current time, user and program
+    pub fn now<T, E: std::fmt::Display>(
+        action: AuditAction,
+        target: &str,
+        target_pid: Option<u32>,
+        detail: &str,
+        result: &Result<T, E>,
+    ) -> Self {
+        Self {
+            timestamp_ms: now_ms(),
+            user: current_user(),
+            actor: actor(),
+            action,
+            target: target.to_string(),
+            target_pid,
+            detail: detail.to_string(),
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            seq: 0,
+            prev: String::new(),
+            signature: String::new(),
+        }
+    }
+
+    /// Whether this record comes straight after `previous` in the chain
+    fn follows(&self, previous: &AuditRecord) -> bool {
+        self.seq == previous.seq + 1 && self.prev == previous.signature
+    }
+
+    fn payload(&self) -> String {
+        let unsigned = Self { signature: String::new(), ..self.clone() };
+        serde_json::to_string(&unsigned).unwrap_or_default()
+    }
+
+    /// Fill in the signature
+    pub fn sign(&mut self, key: &[u8]) {
+        self.signature = hex(&hmac_sha256(key, self.payload().as_bytes()));
+    }
+
+    /// Whether the signature matches the record's contents
+    pub fn verify(&self, key: &[u8]) -> bool {
+        hex(&hmac_sha256(key, self.payload().as_bytes())) == self.signature
+    }
+
+    /// One-line summary for system logs and `audit tail`
+    pub fn summary(&self) -> String {
+        let target = match self.target_pid {
+            Some(pid) if self.target.is_empty() => format!("PID {}", pid),
+            Some(pid) => format!("{} ({})", self.target, pid),
+            None => self.target.clone(),
+        };
+        let mut line = format!("{} {} by {} [{}]", self.action, target, self.user, self.actor);
+        if !self.detail.is_empty() {
+            line.push_str(&format!(" {}", self.detail));
+        }
+        match &self.error {
+            Some(e) => line.push_str(&format!(": failed, {}", e)),
+            None => line.push_str(": ok"),
+        }
+        line
+    }
+}
+
+/// Latest record in the chain, kept next to the log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ChainHead {
+    seq: u64,
+    signature: String,
+}
+
+/// `audit tail` output: the records shown and any breaks in the chain
+#[derive(Debug, Default)]
+pub struct AuditTail {
+    /// The last records, each with whether its signature checks out
+    pub records: Vec<(AuditRecord, bool)>,
+    /// Records that were removed, reordered or replaced anywhere in the log
+    pub gaps: Vec<String>,
+}
+
+/// Append-only, signed and chained JSONL audit log
+pub struct AuditLog {
+    path: PathBuf,
+    key: Vec<u8>,
+}
+
+impl AuditLog {
+    /// Open the log and key at the default location, creating the key once
+    pub fn open_default() -> Result<Self, String> {
+        let path = crate::features::config_file(AUDIT_FILE)?;
+        let key = load_or_create_key(&crate::features::config_file(AUDIT_KEY_FILE)?)?;
+        Ok(Self { path, key })
+    }
+
+    /// Open a log at an explicit path with a given key
+    pub fn open<P: AsRef<Path>>(path: P, key: Vec<u8>) -> Self {
+        Self { path: path.as_ref().to_path_buf(), key }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        self.path.with_extension("jsonl.1")
+    }
+
+    fn head_path(&self) -> PathBuf {
+        self.path.with_extension("jsonl.head")
+    }
+
+    fn read_head(&self) -> Result<Option<ChainHead>, String> {
+        let path = self.head_path();
+        match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| format!("Audit chain head {:?} is corrupt: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {:?}: {}", path, e)),
+        }
+    }
+
+    fn write_head(&self, head: &ChainHead) -> Result<(), String> {
+        let path = self.head_path();
+        let tmp = path.with_extension("head.tmp");
+        let json = serde_json::to_string(head).map_err(|e| e.to_string())?;
+        std::fs::write(&tmp, json)
+            .and_then(|_| std::fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    /// Sign and append a record after the chain head, rotating the file
+    /// once it gets large
+    ///
+    /// Holds a lock beside the log, so writers in different processes
+    /// extend one chain rather than forking it.
+    pub fn append(&self, mut record: AuditRecord) -> Result<AuditRecord, String> {
+        let lock = crate::core::lock::lock_exclusive(&self.path.with_extension("jsonl.lock"))?;
+        // Logs from before the head file existed chain on from their last record
+        let head = self.read_head().unwrap_or_else(|e| {
+            tracing::warn!("{}", e);
+            None
+        });
+        let head = head.or_else(|| {
+            last_record(&self.path)
+                .or_else(|| last_record(&self.rotated_path()))
+                .map(|r| ChainHead { seq: r.seq, signature: r.signature })
+        });
+        record.seq = head.as_ref().map_or(1, |h| h.seq + 1);
+        record.prev = head.map(|h| h.signature).unwrap_or_default();
+        record.sign(&self.key);
+        let line = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+
+        if std::fs::metadata(&self.path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+            std::fs::rename(&self.path, self.rotated_path())
+                .map_err(|e| format!("Failed to rotate audit log {:?}: {}", self.path, e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log {:?}: {}", self.path, e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        self.write_head(&ChainHead { seq: record.seq, signature: record.signature.clone() })?;
+        drop(lock);
+        Ok(record)
+    }
+
+    /// The last `count` records, each with whether its signature checks out,
+    /// and every break in the chain
+    ///
+    /// Lines that don't parse are returned as failed records, so tampering
+    /// can't hide an entry by breaking its JSON.
+    pub fn tail(&self, count: usize) -> Result<AuditTail, String> {
+        let lines: Vec<String> = match File::open(&self.path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter(|line| !line.trim().is_empty())
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        let parsed: Vec<Result<AuditRecord, String>> =
+            lines.iter().map(|line| serde_json::from_str(line).map_err(|e| e.to_string())).collect();
+
+        // The first record links to the last one in the rotated file
+        let mut gaps = Vec::new();
+        let mut previous = last_record(&self.rotated_path());
+        for record in parsed.iter().flatten() {
+            if record.seq > 0 {
+                match &previous {
+                    Some(p) if record.follows(p) => {}
+                    Some(p) => gaps.push(format!(
+                        "#{} does not follow #{}: records were removed, reordered or replaced",
+                        record.seq, p.seq
+                    )),
+                    None if record.seq == 1 && record.prev.is_empty() => {}
+                    None => gaps.push(format!("records before #{} are missing", record.seq)),
+                }
+            }
+            previous = Some(record.clone());
+        }
+        let last = previous.filter(|r| r.seq > 0).map(|r| ChainHead { seq: r.seq, signature: r.signature });
+        match (self.read_head(), last) {
+            (Ok(head), last) if head == last => {}
+            (Ok(Some(head)), last) => gaps.push(format!(
+                "the log ends at #{} but #{} was written: records were truncated or an older copy restored",
+                last.map_or(0, |l| l.seq),
+                head.seq
+            )),
+            (Ok(None), Some(last)) => gaps.push(format!("the chain head is missing (log ends at #{})", last.seq)),
+            (Ok(None), None) => {}
+            (Err(e), _) => gaps.push(e),
+        }
+
+        let start = lines.len().saturating_sub(count);
+        let records = lines[start..]
+            .iter()
+            .zip(&parsed[start..])
+            .map(|(line, parsed)| match parsed {
+                Ok(record) => (record.clone(), record.verify(&self.key)),
+                Err(e) => (unreadable(line, e), false),
+            })
+            .collect();
+        Ok(AuditTail { records, gaps })
+    }
+
+    /// Print records, oldest first, flagging any that fail verification
+    pub fn print_tail(tail: &AuditTail) {
+        println!("\n🔏 Audit Log\n");
+        let records = &tail.records;
+        if records.is_empty() {
+            println!("  No actions recorded yet.");
+        }
+
+        for (record, valid) in records {
+            let time = chrono::DateTime::from_timestamp_millis(record.timestamp_ms as i64)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "?".into());
+            let mark = if !valid { "⚠" } else if record.success { "✓" } else { "✗" };
+            println!("  {} {}  {}", mark, time, record.summary());
+        }
+
+        let tampered = records.iter().filter(|(_, valid)| !valid).count();
+        if tampered > 0 {
+            println!("\n  ⚠ {} record(s) failed signature verification", tampered);
+        }
+        for gap in &tail.gaps {
+            println!("  ⚠ Chain broken: {}", gap);
+        }
+    }
+}
+
+/// Record an action in the audit log (and the system log if enabled)
+///
+/// Never fails the action itself: a record that can't be written is logged
+/// as a warning.
+pub fn record<T, E: std::fmt::Display>(
+    action: AuditAction,
+    target: &str,
+    target_pid: Option<u32>,
+    detail: &str,
+    result: &Result<T, E>,
+) {
+    let (enabled, system_log) = *settings();
+    if !enabled {
+        return;
+    }
+
+    // Opened once per process; one writer at a time keeps lines whole
+    static LOG: OnceLock<Option<Mutex<AuditLog>>> = OnceLock::new();
+    let log = LOG.get_or_init(|| match AuditLog::open_default() {
+        Ok(log) => Some(Mutex::new(log)),
+        Err(e) => {
+            tracing::warn!("Audit log unavailable: {}", e);
+            None
+        }
+    });
+    let Some(Ok(log)) = log.as_ref().map(|log| log.lock()) else { return };
+
+    let record = AuditRecord::now(action, target, target_pid, detail, result);
+    match log.append(record) {
+        Ok(record) if system_log => send_to_system_log(&record),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to write audit record: {}", e),
+    }
+}
+
+/// `audit_enabled` and `audit_system_log`, read once per process
+fn settings() -> &'static (bool, bool) {
+    static SETTINGS: OnceLock<(bool, bool)> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let config = crate::core::config::OptimizerConfig::load_or_default();
+        (config.audit_enabled, config.audit_system_log)
+    })
+}
+
+#[cfg(windows)]
+fn send_to_system_log(record: &AuditRecord) {
+    crate::windows::eventlog::EventLog::new(SYSTEM_LOG_SOURCE).audit(&record.summary(), record.success);
+}
+
+/// Send the record to journald over its native socket, with structured fields
+#[cfg(target_os = "linux")]
+fn send_to_system_log(record: &AuditRecord) {
+    use std::os::unix::net::UnixDatagram;
+
+    let mut message = format!(
+        "MESSAGE={}\nPRIORITY={}\nSYSLOG_IDENTIFIER={}\nRUVECTOR_AUDIT_ACTION={}\nRUVECTOR_AUDIT_USER={}\n",
+        record.summary().replace('\n', " "),
+        if record.success { 6 } else { 4 },
+        SYSTEM_LOG_SOURCE,
+        record.action,
+        record.user,
+    );
+    if let Some(pid) = record.target_pid {
+        message.push_str(&format!("RUVECTOR_AUDIT_TARGET_PID={}\n", pid));
+    }
+    let sent = UnixDatagram::unbound()
+        .and_then(|socket| socket.send_to(message.as_bytes(), "/run/systemd/journal/socket"));
+    if let Err(e) = sent {
+        tracing::debug!("journald unavailable for audit records: {}", e);
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn send_to_system_log(_record: &AuditRecord) {}
+
+/// Read the signing key, or create a random one readable only by this user
+fn load_or_create_key(path: &Path) -> Result<Vec<u8>, String> {
+    if let Ok(text) = std::fs::read_to_string(path) {
+        return unhex(text.trim()).ok_or_else(|| format!("Audit key {:?} is corrupt", path));
+    }
+
+    let key: [u8; 32] = rand::random();
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create audit key {:?}: {}", path, e))?;
+    file.write_all(hex(&key).as_bytes()).map_err(|e| e.to_string())?;
+    Ok(key.to_vec())
+}
+
+/// HMAC-SHA256 (RFC 2104)
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Last record of a log file that still parses
+fn last_record(path: &Path) -> Option<AuditRecord> {
+    let file = File::open(path).ok()?;
+    let line = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .last()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// Placeholder for a line that no longer parses
+fn unreadable(line: &str, error: &str) -> AuditRecord {
+    AuditRecord {
+        timestamp_ms: 0,
+        user: "?".into(),
+        actor: "?".into(),
+        action: AuditAction::Trim,
+        target: format!("unreadable line: {}", line.chars().take(60).collect::<String>()),
+        target_pid: None,
+        detail: String::new(),
+        success: false,
+        error: Some(error.to_string()),
+        seq: 0,
+        prev: String::new(),
+        signature: String::new(),
+    }
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| {
+            #[cfg(unix)]
+            {
+                format!("uid {}", unsafe { libc::geteuid() })
+            }
+            #[cfg(not(unix))]
+            {
+                "unknown".to_string()
+            }
+        })
+}
+
+fn actor() -> String {
+    let program = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "ruvector-memopt".into());
+    format!("{} ({})", program, std::process::id())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231 test case 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex(&mac), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        assert_eq!(unhex(&hex(&mac)).unwrap(), mac.to_vec());
+    }
+
+    fn remove_log(path: &Path) {
+        for extension in ["jsonl", "jsonl.1", "jsonl.head", "jsonl.lock"] {
+            let _ = std::fs::remove_file(path.with_extension(extension));
+        }
+    }
+
+    #[test]
+    fn test_append_and_detect_tampering() {
+        let path = std::env::temp_dir().join(format!("ruvector-audit-{}.jsonl", std::process::id()));
+        remove_log(&path);
+        let log = AuditLog::open(&path, b"test key".to_vec());
+
+        let ok: Result<(), String> = Ok(());
+        let failed: Result<(), String> = Err("access denied".into());
+        log.append(AuditRecord::now(AuditAction::Trim, "chrome", Some(42), "", &ok)).unwrap();
+        log.append(AuditRecord::now(AuditAction::Kill, "java", Some(7), "SIGTERM", &failed)).unwrap();
+
+        let tail = log.tail(10).unwrap();
+        assert_eq!(tail.records.len(), 2);
+        assert!(tail.records.iter().all(|(_, valid)| *valid));
+        assert!(tail.gaps.is_empty(), "{:?}", tail.gaps);
+        assert_eq!(tail.records[1].0.error.as_deref(), Some("access denied"));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replace("\"java\"", "\"javaw\"")).unwrap();
+        let tail = log.tail(1).unwrap();
+        assert!(!tail.records[0].1);
+
+        remove_log(&path);
+    }
+
+    #[test]
+    fn test_chain_detects_missing_and_reordered_records() {
+        let path = std::env::temp_dir().join(format!("ruvector-audit-chain-{}.jsonl", std::process::id()));
+        remove_log(&path);
+        let log = AuditLog::open(&path, b"test key".to_vec());
+        let ok: Result<(), String> = Ok(());
+        let append = |target: &str| log.append(AuditRecord::now(AuditAction::Trim, target, None, "", &ok)).unwrap();
+
+        append("a");
+        append("b");
+        // Rotation carries the chain into the new file
+        std::fs::rename(&path, path.with_extension("jsonl.1")).unwrap();
+        append("c");
+        append("d");
+        let tail = log.tail(10).unwrap();
+        assert_eq!(tail.records.iter().map(|(r, _)| r.seq).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(tail.gaps.is_empty(), "{:?}", tail.gaps);
+        let full = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = full.lines().collect();
+
+        // Every line still verifies on its own, but the chain does not
+        std::fs::write(&path, format!("{}\n{}\n", lines[1], lines[0])).unwrap();
+        let tail = log.tail(10).unwrap();
+        assert!(tail.records.iter().all(|(_, valid)| *valid));
+        assert!(!tail.gaps.is_empty());
+
+        std::fs::write(&path, format!("{}\n", lines[0])).unwrap();
+        assert!(log.tail(10).unwrap().gaps[0].contains("truncated"));
+
+        std::fs::write(&path, format!("{}\n", lines[1])).unwrap();
+        assert!(log.tail(10).unwrap().gaps[0].contains("#4 does not follow #2"));
+
+        // With the lines put back, new records carry the chain on
+        std::fs::write(&path, &full).unwrap();
+        append("e");
+        assert!(log.tail(10).unwrap().gaps.is_empty());
+
+        remove_log(&path);
+    }
+
+    #[test]
+    fn test_summary() {
+        let result: Result<(), String> = Ok(());
+        let mut record = AuditRecord::now(AuditAction::Priority, "cargo", Some(99), "nice 5", &result);
+        record.user = "alice".into();
+        record.actor = "ruvector-memopt (1)".into();
+        assert_eq!(record.summary(), "priority cargo (99) by alice [ruvector-memopt (1)] nice 5: ok");
+    }
+}
@@ -0,0 +1,411 @@
+//! Least-privilege mode: a privileged broker and an unprivileged worker
+//!
+//! With `least_privilege = true` the daemon (or Windows service) starts a
+//! second copy of itself as the worker. The worker drops its privileges
+//! first thing and runs everything that doesn't need them: pressure
+//! monitoring, neural inference, plugins, the timeline and leak watch. When
+//! it decides to optimize it asks the broker, the original privileged
+//! process, over a loopback connection authenticated with a one-time token.
+//!
+//! The broker accepts only the fixed operations in [`BrokerRequest`] and
+//! spaces optimizations at least `min_interval_secs` apart, so a
+//! compromised worker can ask for nothing it couldn't already trigger.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::validator::constant_time_eq;
+use crate::core::config::ConfigWatcher;
+use crate::platform::{MemoryOptimizer, MemoryStatus, OptimizationResult};
+
+/// Set in the worker's environment to the broker's address
+pub const BROKER_ENV: &str = "RUVECTOR_BROKER";
+/// Worker state directory inside the broker's config directory (Unix)
+pub const WORKER_DIR: &str = "worker";
+/// How long the worker gets to connect after it starts
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the broker checks for config changes while idle
+const IDLE_POLL: Duration = Duration::from_secs(1);
+
+/// Privileged operations the worker may ask for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BrokerRequest {
+    /// Run the platform optimizer once
+    Optimize { aggressive: bool },
+}
+
+/// Privileged side: owns the worker process and answers its requests
+pub struct Broker {
+    /// Closed once the worker has connected, or after the first bad token
+    listener: Option<TcpListener>,
+    token: String,
+    worker: Child,
+    min_interval: Duration,
+    #[cfg(unix)]
+    worker_dir: Option<(std::path::PathBuf, u32, u32)>,
+}
+
+impl Broker {
+    /// Start this program again, with the same arguments, as the worker
+    ///
+    /// On Unix the worker gets its own config directory owned by
+    /// `worker_user`, since it can no longer write to root's; config.toml is
+    /// copied there now and whenever it changes, without trusting anything
+    /// the worker may have left in that directory.
+    pub fn spawn_worker(worker_user: &str, min_interval: Duration) -> Result<Self, String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).map_err(|e| format!("Broker bind failed: {}", e))?;
+        let address = listener.local_addr().map_err(|e| e.to_string())?;
+        let token: String = rand::random::<[u8; 32]>().iter().map(|b| format!("{:02x}", b)).collect();
+
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let mut command = Command::new(exe);
+        command.args(std::env::args_os().skip(1)).env(BROKER_ENV, address.to_string()).stdin(Stdio::piped());
+        // The broker beats for the pair when a watchdog runs it
+        command.env_remove(crate::core::watchdog::HEARTBEAT_ENV);
+
+        #[cfg(unix)]
+        let worker_dir = {
+            let (uid, gid) = super::privileges::lookup_user(worker_user)?;
+            let dir = crate::features::config_dir().join(WORKER_DIR);
+            prepare_worker_dir(&dir, uid, gid)?;
+            command.env(crate::features::CONFIG_DIR_ENV, &dir);
+            Some((dir, uid, gid))
+        };
+        #[cfg(not(unix))]
+        let _ = worker_user;
+
+        let mut worker = command.spawn().map_err(|e| format!("Failed to start worker: {}", e))?;
+        // The token goes over stdin so it never shows up in the worker's environment
+        if let Some(mut stdin) = worker.stdin.take() {
+            writeln!(stdin, "{}", token).map_err(|e| format!("Failed to hand the worker its token: {}", e))?;
+        }
+        info!("Started worker process {} (broker on {})", worker.id(), address);
+
+        Ok(Self {
+            listener: Some(listener),
+            token,
+            worker,
+            min_interval,
+            #[cfg(unix)]
+            worker_dir,
+        })
+    }
+
+    /// Answer the worker's requests until it exits or `keep_running` says stop
+    ///
+    /// `keep_running` is polled about once a second; returning false kills
+    /// the worker.
+    pub fn serve<F, K>(mut self, mut handler: F, mut keep_running: K) -> Result<ExitStatus, String>
+    where
+        F: FnMut(&BrokerRequest) -> Result<serde_json::Value, String>,
+        K: FnMut() -> bool,
+    {
+        let mut reader = match self.accept() {
+            Ok(reader) => reader,
+            Err(e) => {
+                let _ = self.worker.kill();
+                return Err(e);
+            }
+        };
+        reader.get_ref().set_read_timeout(Some(IDLE_POLL)).map_err(|e| e.to_string())?;
+        let mut writer = reader.get_ref().try_clone().map_err(|e| e.to_string())?;
+        let mut watcher = ConfigWatcher::new();
+        let mut last_optimize: Option<Instant> = None;
+        let mut line = String::new();
+
+        loop {
+            // read_line keeps a partial line in `line` across timeouts
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+                    if !keep_running() {
+                        let _ = self.worker.kill();
+                        break;
+                    }
+                    if watcher.changed() {
+                        self.sync_config();
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Broker connection failed: {}", e);
+                    break;
+                }
+            }
+
+            let reply = match serde_json::from_str::<BrokerRequest>(line.trim()) {
+                Ok(BrokerRequest::Optimize { .. }) if last_optimize.is_some_and(|t| t.elapsed() < self.min_interval) => {
+                    Err("Refused: optimized too recently".to_string())
+                }
+                Ok(request) => {
+                    if matches!(request, BrokerRequest::Optimize { .. }) {
+                        last_optimize = Some(Instant::now());
+                    }
+                    handler(&request)
+                }
+                Err(e) => Err(format!("Unknown request: {}", e)),
+            };
+            line.clear();
+            let reply = serde_json::to_string(&reply).map_err(|e| e.to_string())?;
+            if writeln!(writer, "{}", reply).is_err() {
+                break;
+            }
+        }
+
+        self.worker.wait().map_err(|e| e.to_string())
+    }
+
+    /// Wait for the worker to connect and present the token
+    ///
+    /// Returns the reader, which may already hold the first request. Any
+    /// local user can connect, so one wrong token closes the listener
+    /// instead of allowing another guess.
+    fn accept(&mut self) -> Result<BufReader<TcpStream>, String> {
+        let listener = self.listener.take().ok_or("Broker already accepted its worker")?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(status)) = self.worker.try_wait() {
+                return Err(format!("Worker exited before connecting ({})", status));
+            }
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(50));
+                    continue;
+                }
+                Err(e) => return Err(format!("Broker accept failed: {}", e)),
+            };
+
+            stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+            stream.set_read_timeout(Some(Duration::from_secs(5))).map_err(|e| e.to_string())?;
+            let mut reader = BufReader::new(stream);
+            let mut presented = String::new();
+            let _ = reader.read_line(&mut presented);
+            if constant_time_eq(presented.trim().as_bytes(), self.token.as_bytes()) {
+                return Ok(reader);
+            }
+            return Err("Rejected a broker connection with a bad token; closed the broker".into());
+        }
+        Err("Worker did not connect in time".into())
+    }
+
+    /// Copy config.toml into the worker's directory
+    #[cfg(unix)]
+    fn sync_config(&self) {
+        let Some((dir, uid, gid)) = &self.worker_dir else { return };
+        if let Err(e) = copy_config(dir, *uid, *gid) {
+            warn!("Failed to pass config changes to the worker: {}", e);
+        }
+    }
+
+    /// Same account, same config file: nothing to copy
+    #[cfg(not(unix))]
+    fn sync_config(&self) {}
+}
+
+#[cfg(unix)]
+fn prepare_worker_dir(dir: &std::path::Path, uid: u32, gid: u32) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let metadata = std::fs::symlink_metadata(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+    if !metadata.is_dir() {
+        return Err(format!("{:?} is not a directory", dir));
+    }
+    std::os::unix::fs::lchown(dir, Some(uid), Some(gid)).map_err(|e| format!("Failed to chown {:?}: {}", dir, e))?;
+    copy_config(dir, uid, gid)
+}
+
+#[cfg(unix)]
+fn copy_config(dir: &std::path::Path, uid: u32, gid: u32) -> Result<(), String> {
+    let source = crate::core::config::OptimizerConfig::default_path();
+    if !source.exists() {
+        return Ok(());
+    }
+    let contents = std::fs::read(&source).map_err(|e| format!("Failed to read {:?}: {}", source, e))?;
+    write_for_worker(dir, crate::core::config::CONFIG_FILE, &contents, uid, gid)
+}
+
+/// Write `name` into the worker's directory as the worker's file
+///
+/// The worker owns the directory, so anything already in it may be a
+/// planted symlink. The contents go to a fresh file opened with `O_EXCL`
+/// and `O_NOFOLLOW`, change owner through its descriptor and are renamed
+/// over `name`, which replaces a symlink there instead of following it.
+#[cfg(unix)]
+fn write_for_worker(dir: &std::path::Path, name: &str, contents: &[u8], uid: u32, gid: u32) -> Result<(), String> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let temp = dir.join(format!(".{}.{:016x}", name, rand::random::<u64>()));
+    let target = dir.join(name);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(&temp)
+        .map_err(|e| format!("Failed to create {:?}: {}", temp, e))?;
+    let result = file
+        .write_all(contents)
+        .map_err(|e| format!("Failed to write {:?}: {}", temp, e))
+        .and_then(|_| {
+            std::os::unix::fs::fchown(&file, Some(uid), Some(gid)).map_err(|e| format!("Failed to chown {:?}: {}", temp, e))
+        })
+        .and_then(|_| std::fs::rename(&temp, &target).map_err(|e| format!("Failed to replace {:?}: {}", target, e)));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Unprivileged side: the worker's connection to the broker
+pub struct BrokerClient {
+    connection: Mutex<(BufReader<TcpStream>, TcpStream)>,
+    closed: AtomicBool,
+    local: Box<dyn MemoryOptimizer>,
+}
+
+impl BrokerClient {
+    /// Whether this process was started by a broker
+    pub fn is_worker() -> bool {
+        std::env::var_os(BROKER_ENV).is_some()
+    }
+
+    /// Connect to the broker named in the environment, reading the token from stdin
+    pub fn connect() -> Result<Self, String> {
+        let address = std::env::var(BROKER_ENV).map_err(|_| format!("{} is not set", BROKER_ENV))?;
+        let mut token = String::new();
+        std::io::stdin()
+            .read_line(&mut token)
+            .map_err(|e| format!("Failed to read the broker token: {}", e))?;
+
+        let mut stream = TcpStream::connect(&address).map_err(|e| format!("Failed to reach broker at {}: {}", address, e))?;
+        writeln!(stream, "{}", token.trim()).map_err(|e| e.to_string())?;
+        let reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        Ok(Self {
+            connection: Mutex::new((reader, stream)),
+            closed: AtomicBool::new(false),
+            local: crate::platform::create_optimizer(),
+        })
+    }
+
+    /// Whether a request has found the broker gone
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Send a request and decode the broker's answer
+    pub fn call<T: DeserializeOwned>(&self, request: &BrokerRequest) -> Result<T, String> {
+        let mut connection = self.connection.lock().map_err(|_| "Broker connection poisoned".to_string())?;
+        let (reader, writer) = &mut *connection;
+        let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        let mut reply = String::new();
+        let received = writeln!(writer, "{}", line).and_then(|_| reader.read_line(&mut reply));
+        match received {
+            Ok(0) => {
+                self.closed.store(true, Ordering::Relaxed);
+                return Err("Broker closed the connection".into());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.closed.store(true, Ordering::Relaxed);
+                return Err(format!("Broker unavailable: {}", e));
+            }
+        }
+        let reply: Result<serde_json::Value, String> =
+            serde_json::from_str(&reply).map_err(|e| format!("Bad broker reply: {}", e))?;
+        serde_json::from_value(reply?).map_err(|e| format!("Bad broker reply: {}", e))
+    }
+}
+
+/// Reads status locally and optimizes through the broker
+impl MemoryOptimizer for BrokerClient {
+    fn get_memory_status(&self) -> Result<MemoryStatus, String> {
+        self.local.get_memory_status()
+    }
+
+    fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+        self.call(&BrokerRequest::Optimize { aggressive })
+    }
+
+    fn has_elevated_privileges(&self) -> bool {
+        true
+    }
+
+    fn platform_name(&self) -> &'static str {
+        self.local.platform_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let request = BrokerRequest::Optimize { aggressive: true };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(line, r#"{"op":"optimize","aggressive":true}"#);
+        assert_eq!(serde_json::from_str::<BrokerRequest>(&line).unwrap(), request);
+        assert!(serde_json::from_str::<BrokerRequest>(r#"{"op":"kill","pid":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_reply_wire_format() {
+        let ok: Result<serde_json::Value, String> = Ok(serde_json::json!({ "freed_mb": 2.0 }));
+        let err: Result<serde_json::Value, String> = Err("Refused".into());
+        assert_eq!(serde_json::to_string(&ok).unwrap(), r#"{"Ok":{"freed_mb":2.0}}"#);
+        assert_eq!(serde_json::to_string(&err).unwrap(), r#"{"Err":"Refused"}"#);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bad_token_closes_the_listener() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut broker = Broker {
+            listener: Some(listener),
+            token: "right".into(),
+            worker: Command::new("sleep").arg("30").spawn().unwrap(),
+            min_interval: Duration::from_secs(60),
+            worker_dir: None,
+        };
+
+        let mut guess = TcpStream::connect(address).unwrap();
+        writeln!(guess, "wrong").unwrap();
+        assert!(broker.accept().unwrap_err().contains("bad token"));
+        assert!(broker.listener.is_none());
+        assert!(TcpStream::connect(address).is_err());
+        let _ = broker.worker.kill();
+        let _ = broker.worker.wait();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_worker_copy_replaces_planted_symlink() {
+        let dir = std::env::temp_dir().join(format!("ruvector-broker-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let victim = dir.join("victim");
+        std::fs::write(&victim, "secret").unwrap();
+        let target = dir.join("config.toml");
+        let _ = std::fs::remove_file(&target);
+        std::os::unix::fs::symlink(&victim, &target).unwrap();
+
+        let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+        write_for_worker(&dir, "config.toml", b"interval_secs = 30\n", uid, gid).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&victim).unwrap(), "secret");
+        assert!(std::fs::symlink_metadata(&target).unwrap().is_file());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "interval_secs = 30\n");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,3 +3,6 @@
 pub mod validator;
 pub mod privileges;
 pub mod integrity;
+pub mod policy;
+pub mod audit;
+pub mod broker;
@@ -0,0 +1,487 @@
+//! User-defined process include/exclude policy
+//!
+//! Rules live in `policy.toml` in the config directory and are evaluated in
+//! order: the first matching rule decides. `deny` rules keep the optimizer
+//! away from a process; `allow` rules opt a process back in even when it is
+//! on the platform's built-in protected list. `background` rules name
+//! indexers and updaters that the build booster and Game Mode lower while
+//! they run; for optimization they count as unmatched. Processes that no
+//! rule matches fall back to `platform::default_protected_processes`.
+
+use regex_automata::meta::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use super::validator::matches_glob;
+use crate::core::text::truncate;
+
+/// Policy file name inside the config directory
+pub const POLICY_FILE: &str = "policy.toml";
+
+/// What a matching rule does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyEffect {
+    /// Always eligible for optimization
+    Allow,
+    /// Never touched by the optimizer
+    Deny,
+    /// Lowered in CPU and I/O priority during builds and games
+    Background,
+}
+
+impl std::fmt::Display for PolicyEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyEffect::Allow => write!(f, "allow"),
+            PolicyEffect::Deny => write!(f, "deny"),
+            PolicyEffect::Background => write!(f, "background"),
+        }
+    }
+}
+
+impl std::str::FromStr for PolicyEffect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "allow" | "include" => Ok(PolicyEffect::Allow),
+            "deny" | "exclude" => Ok(PolicyEffect::Deny),
+            "background" | "demote" => Ok(PolicyEffect::Background),
+            other => Err(format!("Unknown effect '{}' (expected allow, deny or background)", other)),
+        }
+    }
+}
+
+/// What a rule's pattern is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    /// Case-insensitive glob on the process name
+    Glob,
+    /// Regular expression on the process name
+    Regex,
+    /// Executable path; glob if it contains wildcards, otherwise a prefix
+    Path,
+    /// Executable publisher (company name from the version resource, Windows only)
+    Publisher,
+}
+
+impl std::fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleKind::Glob => write!(f, "glob"),
+            RuleKind::Regex => write!(f, "regex"),
+            RuleKind::Path => write!(f, "path"),
+            RuleKind::Publisher => write!(f, "publisher"),
+        }
+    }
+}
+
+impl std::str::FromStr for RuleKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "glob" | "name" => Ok(RuleKind::Glob),
+            "regex" => Ok(RuleKind::Regex),
+            "path" => Ok(RuleKind::Path),
+            "publisher" => Ok(RuleKind::Publisher),
+            other => Err(format!(
+                "Unknown rule kind '{}' (expected glob, regex, path or publisher)", other
+            )),
+        }
+    }
+}
+
+/// A single policy rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    #[serde(default)]
+    pub id: u32,
+    pub effect: PolicyEffect,
+    pub kind: RuleKind,
+    pub pattern: String,
+}
+
+impl PolicyRule {
+    pub fn new(effect: PolicyEffect, kind: RuleKind, pattern: impl Into<String>) -> Self {
+        Self { id: 0, effect, kind, pattern: pattern.into() }
+    }
+
+    /// Check the pattern is usable (currently only regexes can be invalid)
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kind == RuleKind::Regex {
+            compile_regex(&self.pattern)?;
+        }
+        Ok(())
+    }
+
+    /// Human-readable description
+    pub fn describe(&self) -> String {
+        format!("{} {} {}", self.effect, self.kind, self.pattern)
+    }
+}
+
+/// What the policy knows about a process
+#[derive(Debug, Clone, Default)]
+pub struct ProcessIdentity {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub publisher: Option<String>,
+}
+
+impl ProcessIdentity {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), ..Default::default() }
+    }
+
+    pub fn with_path(mut self, path: Option<PathBuf>) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Build from a sysinfo process, looking up the publisher only when the
+    /// policy has publisher rules
+    pub fn from_process(process: &sysinfo::Process, policy: &Policy) -> Self {
+        let path = process.exe().map(Path::to_path_buf);
+        let publisher = if policy.needs_publisher() {
+            path.as_deref().and_then(file_publisher)
+        } else {
+            None
+        };
+        Self {
+            name: process.name().to_string_lossy().to_string(),
+            path,
+            publisher,
+        }
+    }
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| {
+        let detail = e.syntax_error().map(|s| s.to_string()).unwrap_or_else(|| e.to_string());
+        format!("Invalid regex '{}': {}", pattern, detail)
+    })
+}
+
+struct CompiledRule {
+    rule: PolicyRule,
+    regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, process: &ProcessIdentity) -> bool {
+        match self.rule.kind {
+            RuleKind::Glob => matches_glob(&self.rule.pattern, &process.name),
+            RuleKind::Regex => self.regex.as_ref()
+                .map(|r| r.is_match(&process.name))
+                .unwrap_or(false),
+            RuleKind::Path => match &process.path {
+                Some(path) => {
+                    let path = path.to_string_lossy();
+                    if self.rule.pattern.contains(['*', '?']) {
+                        matches_glob(&self.rule.pattern, &path)
+                    } else {
+                        path.to_lowercase().starts_with(&self.rule.pattern.to_lowercase())
+                    }
+                }
+                None => false,
+            },
+            RuleKind::Publisher => match &process.publisher {
+                Some(publisher) => matches_glob(&self.rule.pattern, publisher),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Compiled policy consulted by the platform optimizers
+pub struct Policy {
+    rules: Vec<CompiledRule>,
+    defaults: &'static [&'static str],
+}
+
+impl Policy {
+    /// Compile rules, dropping (and logging) any that are invalid
+    pub fn new(rules: Vec<PolicyRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let regex = if rule.kind == RuleKind::Regex {
+                    match compile_regex(&rule.pattern) {
+                        Ok(r) => Some(r),
+                        Err(e) => {
+                            warn!("Ignoring policy rule #{}: {}", rule.id, e);
+                            return None;
+                        }
+                    }
+                } else {
+                    None
+                };
+                Some(CompiledRule { rule, regex })
+            })
+            .collect();
+
+        Self {
+            rules,
+            defaults: crate::platform::default_protected_processes(),
+        }
+    }
+
+    /// Load the user's policy, falling back to built-in protection only
+    pub fn load_default() -> Self {
+        let rules = PolicyStore::open_default()
+            .and_then(|store| store.load())
+            .unwrap_or_else(|e| {
+                warn!("Failed to load process policy: {}", e);
+                Vec::new()
+            });
+        Self::new(rules)
+    }
+
+    /// Whether any rule needs the executable publisher
+    pub fn needs_publisher(&self) -> bool {
+        self.rules.iter().any(|r| r.rule.kind == RuleKind::Publisher)
+    }
+
+    /// First rule matching the process, if any
+    pub fn matching_rule(&self, process: &ProcessIdentity) -> Option<&PolicyRule> {
+        self.rules.iter().find(|r| r.matches(process)).map(|r| &r.rule)
+    }
+
+    /// Why the process must be left alone, or `None` if it may be optimized
+    pub fn exclusion_reason(&self, process: &ProcessIdentity) -> Option<String> {
+        match self.matching_rule(process) {
+            Some(rule) if rule.effect == PolicyEffect::Deny => {
+                Some(format!("policy rule #{}", rule.id))
+            }
+            Some(rule) if rule.effect == PolicyEffect::Allow => None,
+            _ => {
+                let name = process.name.to_lowercase();
+                self.defaults
+                    .iter()
+                    .any(|p| name.contains(&p.to_lowercase()))
+                    .then(|| "protected process".to_string())
+            }
+        }
+    }
+
+    pub fn is_excluded(&self, process: &ProcessIdentity) -> bool {
+        self.exclusion_reason(process).is_some()
+    }
+
+    /// Whether the first matching rule marks the process as background work
+    pub fn is_background(&self, process: &ProcessIdentity) -> bool {
+        self.matching_rule(process).is_some_and(|rule| rule.effect == PolicyEffect::Background)
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PolicyFile {
+    #[serde(default)]
+    rules: Vec<PolicyRule>,
+}
+
+/// Persistent rule list
+pub struct PolicyStore {
+    path: PathBuf,
+}
+
+impl PolicyStore {
+    /// Store at the default location
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self { path: crate::features::config_file(POLICY_FILE)? })
+    }
+
+    pub fn open(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Load all rules in evaluation order (empty if the file does not exist)
+    pub fn load(&self) -> Result<Vec<PolicyRule>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        let file: PolicyFile = toml::from_str(&content)
+            .map_err(|e| format!("Failed to parse {:?}: {}", self.path, e))?;
+        Ok(file.rules)
+    }
+
+    pub fn save(&self, rules: &[PolicyRule]) -> Result<(), String> {
+        let file = PolicyFile { rules: rules.to_vec() };
+        let content = toml::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+
+    /// Validate and append a rule, assigning the next free ID
+    pub fn add(&self, mut rule: PolicyRule) -> Result<u32, String> {
+        rule.validate()?;
+        let mut rules = self.load()?;
+        rule.id = rules.iter().map(|r| r.id).max().unwrap_or(0) + 1;
+        let id = rule.id;
+        rules.push(rule);
+        self.save(&rules)?;
+        Ok(id)
+    }
+
+    /// Remove a rule by ID
+    pub fn remove(&self, id: u32) -> Result<(), String> {
+        let mut rules = self.load()?;
+        let before = rules.len();
+        rules.retain(|r| r.id != id);
+        if rules.len() == before {
+            return Err(format!("No policy rule with id {}", id));
+        }
+        self.save(&rules)
+    }
+
+    /// Print rules as a table
+    pub fn print_rules(rules: &[PolicyRule]) {
+        println!("\n🛡️  Process Policy\n");
+
+        if rules.is_empty() {
+            println!("  No rules defined. Built-in protected processes still apply.");
+            println!("  Add one with `policy add deny <pattern>`.");
+            return;
+        }
+
+        println!("┌──────┬────────┬───────────┬──────────────────────────────────────────┐");
+        println!("│ ID   │ Effect │ Kind      │ Pattern                                  │");
+        println!("├──────┼────────┼───────────┼──────────────────────────────────────────┤");
+        for r in rules {
+            println!("│ {:>4} │ {:6} │ {:9} │ {:40} │",
+                r.id, r.effect.to_string(), r.kind.to_string(), truncate(&r.pattern, 40));
+        }
+        println!("└──────┴────────┴───────────┴──────────────────────────────────────────┘");
+        println!("\nRules are evaluated top to bottom; the first match wins.");
+    }
+}
+
+/// Company name from an executable's version resource
+#[cfg(target_os = "windows")]
+pub fn file_publisher(path: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+    };
+
+    let wide = |s: &std::ffi::OsStr| -> Vec<u16> { s.encode_wide().chain(Some(0)).collect() };
+    let file = wide(path.as_os_str());
+
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(file.as_ptr()), None);
+        if size == 0 {
+            return None;
+        }
+        let mut data = vec![0u8; size as usize];
+        GetFileVersionInfoW(PCWSTR(file.as_ptr()), 0, size, data.as_mut_ptr() as *mut _).ok()?;
+
+        // First language/codepage pair
+        let mut ptr = std::ptr::null_mut();
+        let mut len = 0u32;
+        let key = wide(std::ffi::OsStr::new("\\VarFileInfo\\Translation"));
+        if !VerQueryValueW(data.as_ptr() as *const _, PCWSTR(key.as_ptr()), &mut ptr, &mut len).as_bool()
+            || len < 4
+        {
+            return None;
+        }
+        let lang = *(ptr as *const u16);
+        let codepage = *(ptr as *const u16).add(1);
+
+        let key = format!("\\StringFileInfo\\{:04x}{:04x}\\CompanyName", lang, codepage);
+        let key = wide(std::ffi::OsStr::new(&key));
+        if !VerQueryValueW(data.as_ptr() as *const _, PCWSTR(key.as_ptr()), &mut ptr, &mut len).as_bool()
+            || len == 0
+        {
+            return None;
+        }
+        let chars = std::slice::from_raw_parts(ptr as *const u16, len as usize);
+        let name = String::from_utf16_lossy(chars).trim_end_matches('\0').trim().to_string();
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+/// Publisher lookup is only implemented on Windows
+#[cfg(not(target_os = "windows"))]
+pub fn file_publisher(_path: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: u32, effect: PolicyEffect, kind: RuleKind, pattern: &str) -> PolicyRule {
+        PolicyRule { id, ..PolicyRule::new(effect, kind, pattern) }
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let policy = Policy::new(vec![
+            rule(1, PolicyEffect::Allow, RuleKind::Glob, "chrome-helper*"),
+            rule(2, PolicyEffect::Deny, RuleKind::Regex, "^chrome"),
+            rule(3, PolicyEffect::Deny, RuleKind::Path, "/opt/games"),
+        ]);
+
+        assert!(!policy.is_excluded(&ProcessIdentity::new("chrome-helper-gpu")));
+        assert_eq!(
+            policy.exclusion_reason(&ProcessIdentity::new("chrome")),
+            Some("policy rule #2".to_string())
+        );
+        assert!(policy.is_excluded(
+            &ProcessIdentity::new("game").with_path(Some(PathBuf::from("/opt/games/bin/game")))
+        ));
+        assert!(!policy.is_excluded(&ProcessIdentity::new("my_custom_app")));
+    }
+
+    #[test]
+    fn test_background_rules() {
+        let policy = Policy::new(vec![
+            rule(1, PolicyEffect::Background, RuleKind::Glob, "searchindexer*"),
+            rule(2, PolicyEffect::Background, RuleKind::Glob, "my_updater"),
+        ]);
+
+        assert!(policy.is_background(&ProcessIdentity::new("SearchIndexer.exe")));
+        assert!(!policy.is_background(&ProcessIdentity::new("cargo")));
+        // Background rules don't decide optimization; the built-in list still does
+        assert!(!policy.is_excluded(&ProcessIdentity::new("my_updater")));
+        assert_eq!("demote".parse::<PolicyEffect>(), Ok(PolicyEffect::Background));
+    }
+
+    #[test]
+    fn test_invalid_regex_rejected() {
+        let bad = rule(1, PolicyEffect::Deny, RuleKind::Regex, "(unclosed");
+        assert!(bad.validate().is_err());
+        // Invalid rules are dropped rather than failing the whole policy
+        assert!(Policy::new(vec![bad]).matching_rule(&ProcessIdentity::new("(unclosed")).is_none());
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!("ruvector-policy-{}.toml", std::process::id()));
+        let store = PolicyStore::open(path.clone());
+
+        let id = store.add(PolicyRule::new(PolicyEffect::Deny, RuleKind::Glob, "obs*")).unwrap();
+        store.add(PolicyRule::new(PolicyEffect::Allow, RuleKind::Publisher, "Valve*")).unwrap();
+        assert_eq!(store.load().unwrap().len(), 2);
+
+        store.remove(id).unwrap();
+        let rules = store.load().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].kind, RuleKind::Publisher);
+        assert!(store.remove(id).is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -36,6 +36,13 @@ impl PrivilegeManager {
                 return PrivilegeLevel::Admin;
             }
         }
+
+        #[cfg(unix)]
+        {
+            if unsafe { libc::geteuid() } == 0 {
+                return PrivilegeLevel::Admin;
+            }
+        }
         
         PrivilegeLevel::Standard
     }
@@ -55,6 +62,97 @@ impl PrivilegeManager {
     pub fn can_install_service(&self) -> bool {
         matches!(self.level, PrivilegeLevel::Admin)
     }
+
+    /// Become `user` for the rest of the process's life (needs root)
+    ///
+    /// Sets the supplementary groups, gid and uid, then checks that root
+    /// can't be regained.
+    #[cfg(unix)]
+    pub fn drop_to_user(&mut self, user: &str) -> Result<(), String> {
+        let (uid, gid) = lookup_user(user)?;
+        unsafe {
+            if libc::setgroups(1, &gid) != 0 {
+                return Err(format!("setgroups failed: {}", std::io::Error::last_os_error()));
+            }
+            if libc::setgid(gid) != 0 {
+                return Err(format!("setgid({}) failed: {}", gid, std::io::Error::last_os_error()));
+            }
+            if libc::setuid(uid) != 0 {
+                return Err(format!("setuid({}) failed: {}", uid, std::io::Error::last_os_error()));
+            }
+            if uid != 0 && libc::setuid(0) == 0 {
+                return Err("Privileges were not dropped: root could be regained".into());
+            }
+        }
+        self.level = PrivilegeLevel::Standard;
+        info!("Dropped privileges to {} (uid {}, gid {})", user, uid, gid);
+        Ok(())
+    }
+
+    /// Remove every privilege from this process's token
+    ///
+    /// The account stays the same, but removed privileges can't be enabled
+    /// again, so the process can no longer trim other sessions' processes,
+    /// purge the standby list or debug anything.
+    #[cfg(windows)]
+    pub fn restrict_token(&mut self) -> Result<(), String> {
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::Security::{
+            AdjustTokenPrivileges, GetTokenInformation, TokenPrivileges, SE_PRIVILEGE_REMOVED,
+            TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY,
+        };
+        use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+        unsafe {
+            let mut token = HANDLE::default();
+            OpenProcessToken(GetCurrentProcess(), TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY, &mut token)
+                .map_err(|e| format!("OpenProcessToken failed: {}", e))?;
+
+            let mut size = 0u32;
+            let _ = GetTokenInformation(token, TokenPrivileges, None, 0, &mut size);
+            // u64 backing keeps the buffer aligned for TOKEN_PRIVILEGES
+            let mut buffer = vec![0u64; (size as usize).div_ceil(8).max(1)];
+            let removed = GetTokenInformation(
+                token,
+                TokenPrivileges,
+                Some(buffer.as_mut_ptr() as *mut _),
+                size,
+                &mut size,
+            )
+            .and_then(|_| {
+                let privileges = &mut *(buffer.as_mut_ptr() as *mut TOKEN_PRIVILEGES);
+                let entries = std::slice::from_raw_parts_mut(
+                    privileges.Privileges.as_mut_ptr(),
+                    privileges.PrivilegeCount as usize,
+                );
+                for entry in entries {
+                    entry.Attributes = SE_PRIVILEGE_REMOVED;
+                }
+                AdjustTokenPrivileges(token, false, Some(privileges), 0, None, None)
+            });
+            let _ = CloseHandle(token);
+            removed.map_err(|e| format!("Failed to remove token privileges: {}", e))?;
+        }
+        self.level = PrivilegeLevel::Standard;
+        info!("Removed all privileges from the process token");
+        Ok(())
+    }
+}
+
+/// `(uid, gid)` of a local account
+#[cfg(unix)]
+pub fn lookup_user(user: &str) -> Result<(u32, u32), String> {
+    let name = std::ffi::CString::new(user).map_err(|_| format!("Invalid user name {:?}", user))?;
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut found: *mut libc::passwd = std::ptr::null_mut();
+    let status = unsafe {
+        libc::getpwnam_r(name.as_ptr(), &mut entry, buffer.as_mut_ptr(), buffer.len(), &mut found)
+    };
+    if status != 0 || found.is_null() {
+        return Err(format!("Unknown user {}", user));
+    }
+    Ok((entry.pw_uid, entry.pw_gid))
 }
 
 #[cfg(windows)]
@@ -36,10 +36,70 @@ pub fn validate_config_value(key: &str, value: &str) -> bool {
         "ewc_lambda" => {
             value.parse::<f32>().map(|v| v >= 0.0 && v <= 1.0).unwrap_or(false)
         }
+        "anomaly_threshold" => {
+            value.parse::<f64>().map(|v| (2.0..=10.0).contains(&v)).unwrap_or(false)
+        }
+        "quiet_hours" => {
+            value.trim().is_empty() || crate::features::agent::TimeWindow::parse(value).is_ok()
+        }
+        "fleet_hub" => {
+            value.trim().is_empty() || crate::features::fleet::parse_hub_url(value).is_ok()
+        }
+        "fleet_push_secs" => {
+            value.parse::<u64>().map(|v| (10..=3600).contains(&v)).unwrap_or(false)
+        }
+        "self_max_rss_mb" => {
+            value.parse::<u64>().map(|v| v == 0 || v >= 32).unwrap_or(false)
+        }
+        "self_max_cpu_percent" => {
+            value.parse::<f64>().map(|v| (0.0..=100.0).contains(&v)).unwrap_or(false)
+        }
         _ => true
     }
 }
 
+/// Case-insensitive glob match supporting `*` and `?`
+pub fn matches_glob(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<usize> = None;
+    let mut star_ti = 0;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some(pi);
+            star_ti = ti;
+            pi += 1;
+        } else if let Some(s) = star {
+            // Backtrack: let the last '*' absorb one more character
+            pi = s + 1;
+            star_ti += 1;
+            ti = star_ti;
+        } else {
+            return false;
+        }
+    }
+
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
+
+/// Compare without stopping at the first difference, so response time
+/// doesn't tell how much of a guess was right
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +115,28 @@ mod tests {
         assert!(validate_path(Path::new("C:/safe/path")));
         assert!(!validate_path(Path::new("../../../etc/passwd")));
     }
+
+    #[test]
+    fn test_quiet_hours_validation() {
+        assert!(validate_config_value("quiet_hours", "22:00-07:00"));
+        assert!(validate_config_value("quiet_hours", ""));
+        assert!(!validate_config_value("quiet_hours", "late"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokeN"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert!(matches_glob("chrome*", "chrome.exe"));
+        assert!(matches_glob("*.EXE", "Code.exe"));
+        assert!(matches_glob("note?ad.exe", "notepad.exe"));
+        assert!(matches_glob("slack.exe", "Slack.exe"));
+        assert!(!matches_glob("chrome*", "msedge.exe"));
+        assert!(!matches_glob("code", "code.exe"));
+    }
 }
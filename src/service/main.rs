@@ -1,4 +1,12 @@
 //! Windows Service entry point for RuVector MemOpt
+//!
+//! Runs the `IntelligentOptimizer` loop under the Service Control Manager,
+//! honoring stop/pause/continue controls and reporting results and leak
+//! suspects to the Windows Event Log. The CLI and tray reach the running
+//! service over the named pipe in `core::ipc`.
+//!
+//! With `least_privilege = true` the service only brokers optimizations;
+//! the loop runs in a worker process whose token has no privileges left.
 
 #[cfg(windows)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -8,74 +16,284 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ServiceControl, ServiceControlAccept, ServiceExitCode,
             ServiceState, ServiceStatus, ServiceType,
         },
-        service_control_handler::{self, ServiceControlHandlerResult},
+        service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle},
         service_dispatcher,
     };
+    use ruvector_memopt::core::config::{ConfigWatcher, OptimizerConfig};
+    use ruvector_memopt::core::history::OptimizationTrigger;
+    use ruvector_memopt::core::ipc::{self, DaemonControl, DaemonEvent, Pause};
+    use ruvector_memopt::core::optimizer::IntelligentOptimizer;
+    use ruvector_memopt::features::leakwatch::{self, AlertPolicy, Suspect};
+    use ruvector_memopt::features::patternwatch::{self, PatternShift};
+    use ruvector_memopt::features::anomalywatch;
+    use ruvector_memopt::features::fleet;
+    use ruvector_memopt::monitor::{alerts, mqtt};
+    use ruvector_memopt::algorithms::anomaly::Anomaly;
+    use ruvector_memopt::security::broker::{Broker, BrokerClient, BrokerRequest};
+    use ruvector_memopt::security::privileges::PrivilegeManager;
     use std::ffi::OsString;
-    use std::sync::mpsc;
-    use std::time::Duration;
-    
+    use std::path::Path;
+    use std::sync::{mpsc, Arc};
+    use std::time::{Duration, Instant};
+    use ruvector_memopt::windows::eventlog::EventLog;
+    use ruvector_memopt::windows::memory::WindowsMemoryOptimizer;
+
     const SERVICE_NAME: &str = "RuVectorMemOpt";
-    
+    /// How often the service evaluates memory pressure
+    const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// What wakes the service loop
+    enum Wake {
+        Control(ServiceControl),
+        /// A client queued an optimize request
+        Client,
+    }
+
     define_windows_service!(ffi_service_main, service_main);
-    
+
     fn service_main(arguments: Vec<OsString>) {
         if let Err(e) = run_service(arguments) {
-            eprintln!("Service error: {}", e);
+            EventLog::new(SERVICE_NAME).error(&format!("Service error: {}", e));
         }
     }
-    
+
+    fn set_state(
+        handle: &ServiceStatusHandle,
+        state: ServiceState,
+        controls: ServiceControlAccept,
+    ) -> Result<(), windows_service::Error> {
+        handle.set_service_status(ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted: controls,
+            exit_code: ServiceExitCode::Win32(0),
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+        })
+    }
+
     fn run_service(_arguments: Vec<OsString>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let (shutdown_tx, shutdown_rx) = mpsc::channel();
-        
+        let log = EventLog::new(SERVICE_NAME);
+        let (control_tx, control_rx) = mpsc::channel();
+        let wake_tx = control_tx.clone();
+
         let event_handler = move |control_event| -> ServiceControlHandlerResult {
             match control_event {
-                ServiceControl::Stop => {
-                    let _ = shutdown_tx.send(());
+                ServiceControl::Stop | ServiceControl::Pause | ServiceControl::Continue => {
+                    let _ = control_tx.send(Wake::Control(control_event));
                     ServiceControlHandlerResult::NoError
                 }
                 ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
                 _ => ServiceControlHandlerResult::NotImplemented,
             }
         };
-        
+
         let status_handle = service_control_handler::register(SERVICE_NAME, event_handler)?;
-        
+        let accepted = ServiceControlAccept::STOP | ServiceControlAccept::PAUSE_CONTINUE;
+
+        let config_path = OptimizerConfig::default_path();
+        let config = OptimizerConfig::load_or_default();
+        log.info(&format!(
+            "Service starting (config: {:?}, threshold: {}%, neural: {})",
+            config_path, config.pressure_threshold, config.neural_enabled
+        ));
+
+        if config.least_privilege {
+            return run_broker(&log, &status_handle, &control_rx, &config);
+        }
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let watchers = Watchers::start(&config);
+        fleet::spawn();
+        mqtt::spawn(&config);
+        alerts::spawn(&config);
+        let control = start_control(&log, &config, wake_tx);
+        let mut optimizer = IntelligentOptimizer::new(config);
+        optimizer.set_control(control.clone());
+        let mut config_watcher = ConfigWatcher::new();
+
         // Report running
-        status_handle.set_service_status(ServiceStatus {
-            service_type: ServiceType::OWN_PROCESS,
-            current_state: ServiceState::Running,
-            controls_accepted: ServiceControlAccept::STOP,
-            exit_code: ServiceExitCode::Win32(0),
-            checkpoint: 0,
-            wait_hint: Duration::default(),
-            process_id: None,
-        })?;
-        
-        // Main service loop
+        set_state(&status_handle, ServiceState::Running, accepted)?;
+
+        // Main service loop; client requests don't push the next check back
+        let mut next_check = Instant::now() + CHECK_INTERVAL;
         loop {
-            match shutdown_rx.recv_timeout(Duration::from_secs(60)) {
-                Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            match control_rx.recv_timeout(next_check.saturating_duration_since(Instant::now())) {
+                Ok(Wake::Control(ServiceControl::Pause)) => {
+                    control.pause(Pause::until_resumed());
+                    set_state(&status_handle, ServiceState::Paused, accepted)?;
+                    log.info("Service paused");
+                }
+                Ok(Wake::Control(ServiceControl::Continue)) => {
+                    control.resume();
+                    set_state(&status_handle, ServiceState::Running, accepted)?;
+                    log.info("Service resumed");
+                }
+                Ok(Wake::Client) => runtime.block_on(optimizer.serve_requests()),
+                Ok(Wake::Control(_)) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
                 Err(mpsc::RecvTimeoutError::Timeout) => {
-                    // Run optimization check here
+                    check(&log, &runtime, &mut optimizer, &mut config_watcher, &watchers, &config_path, &control);
+                    next_check = Instant::now() + CHECK_INTERVAL;
                 }
             }
         }
-        
+
+        log.info("Service stopping");
+        alerts::clean_exit();
+
         // Report stopped
-        status_handle.set_service_status(ServiceStatus {
-            service_type: ServiceType::OWN_PROCESS,
-            current_state: ServiceState::Stopped,
-            controls_accepted: ServiceControlAccept::empty(),
-            exit_code: ServiceExitCode::Win32(0),
-            checkpoint: 0,
-            wait_hint: Duration::default(),
-            process_id: None,
-        })?;
-        
+        set_state(&status_handle, ServiceState::Stopped, ServiceControlAccept::empty())?;
+
+        Ok(())
+    }
+
+    /// Listen for the CLI and tray; their requests arrive as [`Wake::Client`]
+    fn start_control(log: &EventLog, config: &OptimizerConfig, wake_tx: mpsc::Sender<Wake>) -> Arc<DaemonControl> {
+        let control = DaemonControl::new(config.clone(), move || {
+            let _ = wake_tx.send(Wake::Client);
+        });
+        match ipc::listen(control.clone()) {
+            Ok(path) => log.info(&format!("Listening for clients on {}", path.display())),
+            Err(e) => log.warn(&format!("Control channel unavailable: {}", e)),
+        }
+        control
+    }
+
+    /// Background watchers whose findings the service loop passes on
+    struct Watchers {
+        leak_alerts: mpsc::Receiver<Suspect>,
+        pattern_shifts: mpsc::Receiver<PatternShift>,
+        anomalies: mpsc::Receiver<Anomaly>,
+    }
+
+    impl Watchers {
+        fn start(config: &OptimizerConfig) -> Self {
+            Self {
+                leak_alerts: leakwatch::watch(AlertPolicy::from_config(config)),
+                pattern_shifts: patternwatch::watch(),
+                anomalies: anomalywatch::watch(config.anomaly_threshold),
+            }
+        }
+    }
+
+    /// One pass of the service loop: watcher findings, config reload, then a tick
+    fn check(
+        log: &EventLog,
+        runtime: &tokio::runtime::Runtime,
+        optimizer: &mut IntelligentOptimizer,
+        config_watcher: &mut ConfigWatcher,
+        watchers: &Watchers,
+        config_path: &Path,
+        control: &DaemonControl,
+    ) {
+        // Sessions have no desktop here; the tray notifies the user
+        while let Ok(suspect) = watchers.leak_alerts.try_recv() {
+            log.warn(&suspect.message());
+        }
+        while let Ok(shift) = watchers.pattern_shifts.try_recv() {
+            log.info(&shift.message());
+            control.publish(DaemonEvent::PatternShift { from: shift.from.to_string(), to: shift.to.to_string() });
+        }
+        while let Ok(anomaly) = watchers.anomalies.try_recv() {
+            log.warn(&anomaly.message());
+            control.publish(DaemonEvent::Anomaly { metric: anomaly.metric.to_string(), message: anomaly.message() });
+        }
+        if let Some(config) = config_watcher.reload(optimizer.config()) {
+            let changes = optimizer.config().diff(&config);
+            log.info(&format!("Reloaded {:?}: {}", config_path,
+                if changes.is_empty() { "no changes".to_string() } else { changes.join(", ") }));
+            control.set_config(config.clone());
+            optimizer.set_config(config);
+        }
+        if control.is_paused() {
+            return;
+        }
+        match runtime.block_on(optimizer.tick(OptimizationTrigger::Service)) {
+            Ok(Some(result)) => log.info(&format!(
+                "Optimized: freed {:.1} MB from {} processes in {} ms",
+                result.freed_mb, result.processes_trimmed, result.duration_ms
+            )),
+            Ok(None) => {}
+            Err(e) => log.warn(&e),
+        }
+    }
+
+    /// Privileged half of least-privilege mode: optimize only when the worker asks
+    fn run_broker(
+        log: &EventLog,
+        status_handle: &ServiceStatusHandle,
+        control_rx: &mpsc::Receiver<Wake>,
+        config: &OptimizerConfig,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let broker = Broker::spawn_worker(&config.worker_user, Duration::from_secs(config.min_interval_secs))?;
+        set_state(status_handle, ServiceState::Running, ServiceControlAccept::STOP)?;
+        log.info("Service running as broker for a restricted worker");
+
+        let optimizer = WindowsMemoryOptimizer::new();
+        let served = broker.serve(
+            |request| match request {
+                BrokerRequest::Optimize { aggressive } => optimizer
+                    .optimize(*aggressive)
+                    .and_then(|result| serde_json::to_value(result).map_err(|e| e.to_string())),
+            },
+            || !matches!(control_rx.try_recv(), Ok(Wake::Control(ServiceControl::Stop)) | Err(mpsc::TryRecvError::Disconnected)),
+        );
+        match served {
+            Ok(status) if !status.success() => log.warn(&format!("Worker exited ({})", status)),
+            Ok(_) => {}
+            Err(e) => log.error(&format!("Broker failed: {}", e)),
+        }
+
+        // The worker is killed on stop, so its mark is cleared here
+        alerts::clean_exit();
+        log.info("Service stopping");
+        set_state(status_handle, ServiceState::Stopped, ServiceControlAccept::empty())?;
         Ok(())
     }
-    
+
+    /// Restricted half of least-privilege mode, started by `run_broker`
+    fn run_worker() -> Result<(), Box<dyn std::error::Error>> {
+        PrivilegeManager::new().restrict_token()?;
+        let log = EventLog::new(SERVICE_NAME);
+        let broker = BrokerClient::connect()?;
+
+        let config_path = OptimizerConfig::default_path();
+        let config = OptimizerConfig::load_or_default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let watchers = Watchers::start(&config);
+        fleet::spawn();
+        mqtt::spawn(&config);
+        alerts::spawn(&config);
+        let (wake_tx, wake_rx) = mpsc::channel();
+        let control = start_control(&log, &config, wake_tx);
+        let mut optimizer = IntelligentOptimizer::new(config);
+        optimizer.set_broker(broker);
+        optimizer.set_control(control.clone());
+        let mut config_watcher = ConfigWatcher::new();
+        log.info("Worker running with a restricted token");
+
+        // The broker kills the worker on stop; a lost connection means it died
+        let mut next_check = Instant::now() + CHECK_INTERVAL;
+        while !optimizer.broker_lost() {
+            match wake_rx.recv_timeout(next_check.saturating_duration_since(Instant::now())) {
+                Ok(_) => runtime.block_on(optimizer.serve_requests()),
+                Err(_) => {
+                    check(&log, &runtime, &mut optimizer, &mut config_watcher, &watchers, &config_path, &control);
+                    next_check = Instant::now() + CHECK_INTERVAL;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    if BrokerClient::is_worker() {
+        return run_worker();
+    }
     service_dispatcher::start(SERVICE_NAME, ffi_service_main)?;
     Ok(())
 }
@@ -9,11 +9,19 @@ use std::sync::{Arc, Mutex};
 
 use super::settings::TraySettings;
 use crate::accel::CpuCapabilities;
+use crate::algorithms::forecast::chart_horizons;
+use crate::core::config::OptimizerConfig;
+use crate::features::health::HealthScorer;
+use crate::features::timeline;
+use crate::security::validator::validate_config_value;
 use crate::windows::memory::WindowsMemoryOptimizer;
 
 /// Prevents opening multiple Control Center windows simultaneously.
 static CC_OPEN: AtomicBool = AtomicBool::new(false);
 
+/// Kept across requests so leak suspects and swap rates build up while open.
+static HEALTH: Mutex<Option<HealthScorer>> = Mutex::new(None);
+
 /// Events sent from the IPC handler to the tao event loop.
 #[derive(Debug)]
 enum CenterEvent {
@@ -22,6 +30,15 @@ enum CenterEvent {
 
 /// Open the Control Center window (non-blocking, spawns a thread).
 pub fn open(settings: Arc<Mutex<TraySettings>>) {
+    open_at(settings, "");
+}
+
+/// Open the Control Center scrolled to the settings card.
+pub fn open_settings(settings: Arc<Mutex<TraySettings>>) {
+    open_at(settings, "settings");
+}
+
+fn open_at(settings: Arc<Mutex<TraySettings>>, focus: &'static str) {
     if CC_OPEN
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
         .is_err()
@@ -33,7 +50,7 @@ pub fn open(settings: Arc<Mutex<TraySettings>>) {
     std::thread::spawn(move || {
         tracing::info!("Opening Control Center");
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            if let Err(e) = run(settings) {
+            if let Err(e) = run(settings, focus) {
                 tracing::error!("Control Center error: {}", e);
             }
         }));
@@ -53,7 +70,7 @@ pub fn open(settings: Arc<Mutex<TraySettings>>) {
 }
 
 /// Create and run the Control Center window until the user closes it.
-fn run(settings: Arc<Mutex<TraySettings>>) -> Result<(), Box<dyn std::error::Error>> {
+fn run(settings: Arc<Mutex<TraySettings>>, focus: &str) -> Result<(), Box<dyn std::error::Error>> {
     use tao::event::{Event, WindowEvent};
     use tao::event_loop::EventLoopBuilder;
     use tao::platform::run_return::EventLoopExtRunReturn;
@@ -82,7 +99,8 @@ fn run(settings: Arc<Mutex<TraySettings>>) -> Result<(), Box<dyn std::error::Err
     let html_src = include_str!("../web/index.html");
     let html = html_src
         .replace("{{THEME}}", &theme)
-        .replace("{{WELCOME_SHOWN}}", &welcome_shown.to_string());
+        .replace("{{WELCOME_SHOWN}}", &welcome_shown.to_string())
+        .replace("{{FOCUS}}", focus);
 
     let settings_ipc = settings.clone();
     let proxy_ipc = proxy.clone();
@@ -155,6 +173,22 @@ fn handle_ipc(
             let json = run_optimize(aggressive);
             push_js(proxy, &format!("window.optimizeResult({})", json));
         }
+        Some("get_health") => {
+            // Scoring scans startup items and sensors; keep the window responsive
+            let proxy = proxy.clone();
+            std::thread::spawn(move || {
+                let json = gather_health_json();
+                push_js(&proxy, &format!("window.updateHealth({})", json));
+            });
+        }
+        Some("get_forecast") => {
+            // Reads through the timeline journal; keep the window responsive
+            let proxy = proxy.clone();
+            std::thread::spawn(move || {
+                let json = gather_forecast_json();
+                push_js(&proxy, &format!("window.updateForecast({})", json));
+            });
+        }
         Some("get_settings") => {
             let json = gather_settings_json(settings);
             push_js(proxy, &format!("window.updateSettings({})", json));
@@ -229,6 +263,23 @@ fn gather_metrics_json() -> String {
     serde_json::Value::Object(m).to_string()
 }
 
+fn gather_health_json() -> String {
+    let mut scorer = HEALTH.lock().unwrap_or_else(|e| e.into_inner());
+    let report = scorer.get_or_insert_with(HealthScorer::new).compute();
+    serde_json::to_string(&report).unwrap_or_else(|_| "null".into())
+}
+
+fn gather_forecast_json() -> String {
+    let critical_threshold = OptimizerConfig::load_or_default().critical_threshold;
+    match timeline::forecast(critical_threshold, &chart_horizons()) {
+        Ok(view) => serde_json::to_string(&view).unwrap_or_else(|_| "null".into()),
+        Err(e) => {
+            tracing::debug!("Forecast unavailable: {}", e);
+            "null".into()
+        }
+    }
+}
+
 fn gather_processes_json() -> String {
     use sysinfo::System;
 
@@ -288,6 +339,8 @@ fn gather_settings_json(settings: &Arc<Mutex<TraySettings>>) -> String {
             "auto_optimize": s.auto_optimize,
             "threshold": s.threshold,
             "interval_secs": s.interval_secs,
+            "aggressive": s.aggressive,
+            "quiet_hours": s.quiet_hours.clone().unwrap_or_default(),
             "ai_game_mode": s.ai_mode.game_mode,
             "ai_focus_mode": s.ai_mode.focus_mode,
             "ai_thermal": s.ai_mode.thermal_prediction,
@@ -309,13 +362,27 @@ fn apply_setting(settings: &Arc<Mutex<TraySettings>>, key: &str, value: &serde_j
                 }
             }
             "threshold" => {
-                if let Some(v) = value.as_u64() {
-                    s.threshold = v as u32;
+                match value.as_u64().filter(|v| validate_config_value("pressure_threshold", &v.to_string())) {
+                    Some(v) => s.threshold = v as u32,
+                    None => return,
                 }
             }
             "interval_secs" => {
-                if let Some(v) = value.as_u64() {
-                    s.interval_secs = v;
+                match value.as_u64().filter(|v| validate_config_value("min_interval_secs", &v.to_string())) {
+                    Some(v) => s.interval_secs = v,
+                    None => return,
+                }
+            }
+            "aggressive" => {
+                if let Some(v) = value.as_bool() {
+                    s.aggressive = v;
+                }
+            }
+            "quiet_hours" => {
+                match value.as_str().filter(|v| validate_config_value("quiet_hours", v)) {
+                    Some(v) if v.trim().is_empty() => s.quiet_hours = None,
+                    Some(v) => s.quiet_hours = Some(v.trim().to_string()),
+                    None => return,
                 }
             }
             "ai_game_mode" => {
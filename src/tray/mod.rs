@@ -7,9 +7,28 @@ pub use settings::{TraySettings, AIModeSettings};
 
 use crate::windows::memory::WindowsMemoryOptimizer;
 use crate::accel::CpuCapabilities;
+use crate::apps::idle::ActivityRecorder;
+use crate::apps::{SmartSuggestions, SuggestionRisk};
+use crate::core::config::{self, ConfigWatcher, OptimizerConfig};
+use crate::core::history::{self, HistoryRecord, OptimizationTrigger};
+use crate::core::ipc::{IpcClient, OptimizeReport, Pause};
+use crate::core::scheduler::{self, ScheduledAction, Scheduler};
+use crate::core::undo;
+use crate::features::agent::{self, Agent};
+use crate::features::anomalywatch::{self, AnomalyPolicy};
+use crate::features::gamemode::{GameBooster, GameDetector, RunningGame};
+use crate::features::leakwatch::{self, AlertPolicy};
+use crate::features::prefetch::Prefetcher;
+use crate::features::profiles::{self, ProfileManager};
+use crate::features::report::{self, ReportPeriod};
+use crate::features::thermal::{self, ThermalLevel, ThermalStatus};
+use crate::features::throttle::{self, ThrottleService};
+use crate::features::timeline::{self, EventKind, TimelineRecorder};
+use crate::monitor::capture;
+use crate::monitor::notify::{self, notify, Action, Notification, NotifyPolicy, Quiet};
 use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU32, Ordering}};
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, CheckMenuItem, Submenu, PredefinedMenuItem},
+    menu::{Menu, MenuEvent, MenuId, MenuItem, CheckMenuItem, Submenu, PredefinedMenuItem},
     TrayIconBuilder, Icon,
 };
 use winit::event_loop::{ControlFlow, EventLoop};
@@ -65,8 +84,22 @@ impl TrayApp {
             None
         );
         let optimize_item = MenuItem::new("Optimize Now", true, None);
+
+        // Pause submenu - a "leave my machine alone" switch for automatic runs
+        let pause_menu = Submenu::new("Pause Auto-Optimize", true);
+        let pause_items: Vec<(Option<u64>, MenuItem)> = PAUSE_PRESETS
+            .iter()
+            .map(|&(minutes, label)| (minutes, MenuItem::new(label, true, None)))
+            .collect();
+        let resume_item = MenuItem::new("Resume", false, None);
+        for (_, item) in &pause_items {
+            pause_menu.append(item)?;
+        }
+        pause_menu.append(&PredefinedMenuItem::separator())?;
+        pause_menu.append(&resume_item)?;
         let aggressive_item = MenuItem::new("Deep Clean", true, None);
         let browser_item = MenuItem::new("Optimize Apps (Browsers/Electron)", true, None);
+        let undo_item = MenuItem::new("Undo Last Optimization", true, None);
         let control_center_item = MenuItem::new("Control Center", true, None);
 
         // AI Mode submenu - use saved settings
@@ -89,16 +122,59 @@ impl TrayApp {
         ai_menu.append(&thermal_item)?;
         ai_menu.append(&preload_item)?;
 
-        // Settings submenu - use saved threshold
+        // Background Services submenu - what is paused right now, and which
+        // services Game Mode and builds may pause at all
+        let opted_in = OptimizerConfig::load_file().throttle_services;
+        let services_menu = Submenu::new("Background Services", true);
+        let throttle_status_item = MenuItem::new(&throttle::load().summary(), false, None);
+        let throttle_items: Vec<(ThrottleService, CheckMenuItem)> = ThrottleService::ALL
+            .iter()
+            .map(|&s| (s, CheckMenuItem::new(&format!("Pause {}", s), true, opted_in.contains(&s), None)))
+            .collect();
+        services_menu.append(&throttle_status_item)?;
+        services_menu.append(&PredefinedMenuItem::separator())?;
+        for (_, item) in &throttle_items {
+            services_menu.append(item)?;
+        }
+
+        // Settings submenu - presets plus the saved threshold if it is none of them;
+        // "Advanced..." opens the Control Center settings for any value
         let settings_menu = Submenu::new("Settings", true);
-        let threshold_75 = CheckMenuItem::new("Threshold: 75%", true, initial_settings.threshold == 75, None);
-        let threshold_80 = CheckMenuItem::new("Threshold: 80%", true, initial_settings.threshold == 80, None);
-        let threshold_85 = CheckMenuItem::new("Threshold: 85%", true, initial_settings.threshold == 85, None);
-        let threshold_90 = CheckMenuItem::new("Threshold: 90%", true, initial_settings.threshold == 90, None);
-        settings_menu.append(&threshold_75)?;
-        settings_menu.append(&threshold_80)?;
-        settings_menu.append(&threshold_85)?;
-        settings_menu.append(&threshold_90)?;
+        let threshold_items: Vec<(u32, CheckMenuItem)> = THRESHOLD_PRESETS
+            .iter()
+            .map(|&t| (t, CheckMenuItem::new(&format!("Threshold: {}%", t), true, initial_settings.threshold == t, None)))
+            .collect();
+        let threshold_custom = CheckMenuItem::new("Threshold: Custom", false, false, None);
+        sync_threshold_items(&threshold_items, &threshold_custom, initial_settings.threshold);
+        let advanced_item = MenuItem::new("Advanced...", true, None);
+        for (_, item) in &threshold_items {
+            settings_menu.append(item)?;
+        }
+        settings_menu.append(&threshold_custom)?;
+        settings_menu.append(&PredefinedMenuItem::separator())?;
+        settings_menu.append(&advanced_item)?;
+
+        // Profiles submenu - one entry per profile plus "None"
+        let mut profile_manager = ProfileManager::open_default()
+            .map_err(|e| tracing::warn!("Profiles unavailable: {}", e))
+            .ok();
+        let active_profile = profile_manager.as_ref().and_then(|m| m.active().map(str::to_string));
+        let profiles_menu = Submenu::new("Profiles", profile_manager.is_some());
+        let profile_none = CheckMenuItem::new("None", true, active_profile.is_none(), None);
+        let mut profile_items = Vec::new();
+        for name in profile_manager.iter().flat_map(|m| m.profiles().keys()) {
+            let item = CheckMenuItem::new(&capitalize(name), true, active_profile.as_deref() == Some(name.as_str()), None);
+            profiles_menu.append(&item)?;
+            profile_items.push((item.id().clone(), name.clone(), item));
+        }
+        profiles_menu.append(&PredefinedMenuItem::separator())?;
+        profiles_menu.append(&profile_none)?;
+
+        // Suggestions submenu - filled in the background, see refresh_suggestions
+        let suggestions_menu = Submenu::new("Suggestions", true);
+        let mut suggestion_placeholder = MenuItem::new("Looking for suggestions...", false, None);
+        suggestions_menu.append(&suggestion_placeholder)?;
+        let mut suggestion_items: Vec<(MenuId, String, MenuItem)> = Vec::new();
 
         // Info section
         let cpu_item = MenuItem::new("System Info", true, None);
@@ -110,13 +186,18 @@ impl TrayApp {
         menu.append(&version_item)?;
         menu.append(&PredefinedMenuItem::separator())?;
         menu.append(&auto_item)?;
+        menu.append(&pause_menu)?;
         menu.append(&optimize_item)?;
         menu.append(&aggressive_item)?;
         menu.append(&browser_item)?;
+        menu.append(&suggestions_menu)?;
+        menu.append(&undo_item)?;
         menu.append(&PredefinedMenuItem::separator())?;
         menu.append(&control_center_item)?;
         menu.append(&PredefinedMenuItem::separator())?;
         menu.append(&ai_menu)?;
+        menu.append(&services_menu)?;
+        menu.append(&profiles_menu)?;
         menu.append(&settings_menu)?;
         menu.append(&PredefinedMenuItem::separator())?;
         menu.append(&cpu_item)?;
@@ -143,32 +224,82 @@ impl TrayApp {
         let optimize_id = optimize_item.id().clone();
         let aggressive_id = aggressive_item.id().clone();
         let browser_id = browser_item.id().clone();
+        let undo_id = undo_item.id().clone();
         let control_center_id = control_center_item.id().clone();
         let cpu_id = cpu_item.id().clone();
         let quit_id = quit_item.id().clone();
         let auto_id = auto_item.id().clone();
+        let resume_id = resume_item.id().clone();
         let github_id = github_item.id().clone();
         let game_mode_id = game_mode_item.id().clone();
         let focus_mode_id = focus_mode_item.id().clone();
         let thermal_id = thermal_item.id().clone();
         let preload_id = preload_item.id().clone();
-        let threshold_75_id = threshold_75.id().clone();
-        let threshold_80_id = threshold_80.id().clone();
-        let threshold_85_id = threshold_85.id().clone();
-        let threshold_90_id = threshold_90.id().clone();
+        let advanced_id = advanced_item.id().clone();
+        let profile_none_id = profile_none.id().clone();
 
         let running = self.running.clone();
         let settings = self.settings.clone();
         let mut last_update = std::time::Instant::now();
         let mut last_auto_optimize = std::time::Instant::now();
+        let mut last_profile_check: Option<std::time::Instant> = None;
+        let mut automation = Agent::open_default()
+            .map_err(|e| tracing::warn!("Automation rules unavailable: {}", e))
+            .ok();
+        let mut last_agent_check = std::time::Instant::now();
+        let agent_switched_profile = Arc::new(AtomicBool::new(false));
         let auto_enabled = Arc::new(AtomicBool::new(initial_settings.auto_optimize));
         let game_mode_enabled = Arc::new(AtomicBool::new(initial_settings.ai_mode.game_mode));
+        let mut game_detector = GameDetector::open_default();
+        let game_booster = Arc::new(Mutex::new(GameBooster::new()));
+        let mut boosted_game: Option<String> = None;
         let focus_mode_enabled = Arc::new(AtomicBool::new(initial_settings.ai_mode.focus_mode));
         let thermal_enabled = Arc::new(AtomicBool::new(initial_settings.ai_mode.thermal_prediction));
         let preload_enabled = Arc::new(AtomicBool::new(initial_settings.ai_mode.predictive_preload));
-        let current_threshold = Arc::new(AtomicU32::new(initial_settings.threshold));
+        let mut shown_settings = (initial_settings.threshold, initial_settings.interval_secs);
+        let config = OptimizerConfig::load_or_default();
+        let mut critical_threshold = config.critical_threshold;
+        let mut quiet_critical = config.quiet_hours_critical;
+        let mut pause: Option<Pause> = None;
+        let mut config_watcher = ConfigWatcher::new();
         let last_usage = Arc::new(AtomicU32::new(initial_usage));
         let total_freed = Arc::new(AtomicU32::new(0));
+        let mut scheduler = Scheduler::load_default();
+        let mut prefetcher = Prefetcher::load();
+        let mut thermal_rx: Option<std::sync::mpsc::Receiver<ThermalStatus>> = None;
+        let mut thermal_level = ThermalLevel::Normal;
+
+        // Services a crashed earlier run left paused
+        std::thread::spawn(throttle::restore_stale);
+
+        // Memory curve, spikes and app launches for `timeline`
+        match TimelineRecorder::open_default() {
+            Ok(recorder) => recorder.spawn(),
+            Err(e) => tracing::warn!("Timeline unavailable: {}", e),
+        }
+
+        // Which apps are actually used, for idle-app suggestions
+        match ActivityRecorder::open_default() {
+            Ok(recorder) => recorder.spawn(),
+            Err(e) => tracing::warn!("App activity unavailable: {}", e),
+        }
+
+        let mut suggestion_rx = Some(refresh_suggestions());
+        let mut last_suggestions = std::time::Instant::now();
+
+        // Processes that keep growing over hours
+        let leak_rx = leakwatch::watch(AlertPolicy::from_config(&config));
+
+        // Readings far from normal for the time of day
+        let anomaly_policy = AnomalyPolicy::from_config(&config);
+        let anomaly_rx = anomalywatch::watch(anomaly_policy.threshold);
+
+        // Toasts are shown under our own AUMID; "Undo" clicks come back here
+        if let Err(e) = notify::register_app_id() {
+            tracing::warn!("Toast notifications unavailable: {}", e);
+        }
+        notify::set_fallback(|n| dialog::show_banner(&n.title, &n.message, n.freed_mb));
+        let (undo_profile_tx, undo_profile_rx) = std::sync::mpsc::channel::<()>();
 
         // Run event loop
         #[allow(deprecated)]
@@ -177,15 +308,113 @@ impl TrayApp {
                 std::time::Instant::now() + std::time::Duration::from_secs(1)
             ));
 
+            // Thermal prediction: warn before throttling and back off while hot
+            if thermal_enabled.load(Ordering::SeqCst) {
+                let rx = thermal_rx.get_or_insert_with(|| thermal::watch(thermal::SAMPLE_INTERVAL));
+                while let Ok(status) = rx.try_recv() {
+                    if status.level > thermal_level {
+                        let (title, msg) = status.notification();
+                        notify(if status.level == ThermalLevel::Hot {
+                            Notification::critical(title, msg)
+                        } else {
+                            Notification::new(title, msg)
+                        });
+                    }
+                    if status.level != thermal_level {
+                        tracing::info!("Thermal level: {} -> {}", thermal_level, status.level);
+                    }
+                    thermal_level = status.level;
+                }
+            } else if thermal_rx.take().is_some() {
+                thermal_level = ThermalLevel::Normal;
+            }
+            let thermal_busy = thermal_level != ThermalLevel::Normal;
+
+            while let Ok(suspect) = leak_rx.try_recv() {
+                notify(Notification::critical(suspect.title(), suspect.message()));
+            }
+            while let Ok(anomaly) = anomaly_rx.try_recv() {
+                if anomaly_policy.notify {
+                    notify(Notification::new(anomaly.title(), anomaly.message()));
+                }
+            }
+
+            // Rebuild the Suggestions submenu when a background refresh lands
+            if last_suggestions.elapsed() > SUGGESTION_REFRESH && suggestion_rx.is_none() {
+                suggestion_rx = Some(refresh_suggestions());
+            }
+            if let Some(Ok(entries)) = suggestion_rx.as_ref().map(|rx| rx.try_recv()) {
+                suggestion_rx = None;
+                last_suggestions = std::time::Instant::now();
+                for (_, _, item) in suggestion_items.drain(..) {
+                    let _ = suggestions_menu.remove(&item);
+                }
+                let _ = suggestions_menu.remove(&suggestion_placeholder);
+                for (id, label) in entries {
+                    let item = MenuItem::new(&label, true, None);
+                    let _ = suggestions_menu.append(&item);
+                    suggestion_items.push((item.id().clone(), id, item));
+                }
+                if suggestion_items.is_empty() {
+                    suggestion_placeholder = MenuItem::new("No suggestions right now", false, None);
+                    let _ = suggestions_menu.append(&suggestion_placeholder);
+                }
+            }
+
+            // Predictive preloading: learn app switches and predict the next one
+            // (warming reads from disk, so it pauses while the machine is hot)
+            if preload_enabled.load(Ordering::SeqCst) && !thermal_busy {
+                prefetcher.start();
+                if let Some(next) = prefetcher.poll().first() {
+                    tracing::info!("Predicted next app: {} ({:.0}%)", next.app, next.confidence * 100.0);
+                }
+            } else if prefetcher.is_watching() {
+                prefetcher.stop();
+            }
+
             // Update status and check for auto-optimization every 5 seconds
             if last_update.elapsed() > std::time::Duration::from_secs(5) {
+                // A running service does the automatic and scheduled work, so
+                // its pause is the one that counts
+                let service = IpcClient::connect().and_then(|mut service| service.status().ok());
+                let service_running = service.is_some();
+                if pause.is_some_and(|p| p.expired()) {
+                    pause = None;
+                }
+                let paused = match &service {
+                    Some(status) => status.paused.then_some(Pause { until_ms: status.paused_until_ms }),
+                    None => pause,
+                };
+                let _ = resume_item.set_enabled(paused.is_some());
                 if let Ok(status) = WindowsMemoryOptimizer::get_memory_status() {
                     let usage = status.memory_load_percent;
                     last_usage.store(usage, Ordering::SeqCst);
 
+                    // Pick up edits to config.toml made outside the tray
+                    if config_watcher.changed() {
+                        let config = OptimizerConfig::load_file();
+                        critical_threshold = config.critical_threshold;
+                        quiet_critical = config.quiet_hours_critical;
+                        notify::center().set_policy(NotifyPolicy::from_config(&config));
+                        if let Ok(mut s) = settings.lock() {
+                            s.take_from(&config);
+                        }
+                    }
+
+                    // Threshold, interval, Deep Clean and quiet hours can also
+                    // change from the Control Center, so read them every tick
+                    let (threshold, interval_secs, deep_clean, quiet) = match settings.lock() {
+                        Ok(s) => (s.threshold, s.interval_secs, s.aggressive, s.in_quiet_hours()),
+                        Err(_) => (AUTO_OPTIMIZE_THRESHOLD, AUTO_OPTIMIZE_INTERVAL, false, false),
+                    };
+                    if shown_settings != (threshold, interval_secs) {
+                        sync_threshold_items(&threshold_items, &threshold_custom, threshold);
+                        let _ = auto_item.set_text(format!("Auto-Optimize ({}s)", interval_secs));
+                        shown_settings = (threshold, interval_secs);
+                    }
+
                     // Update status text
                     let freed = total_freed.load(Ordering::SeqCst);
-                    let threshold = current_threshold.load(Ordering::SeqCst);
                     let text = if freed > 0 {
                         format!(
                             "Memory: {:.0}% ({:.1}/{:.1} GB) | Freed: {} MB",
@@ -203,6 +432,7 @@ impl TrayApp {
                         )
                     };
                     let _ = status_item.set_text(&text);
+                    let _ = throttle_status_item.set_text(throttle::load().summary());
 
                     // Update icon color based on usage
                     let icon_data = create_icon_with_usage(usage);
@@ -215,7 +445,11 @@ impl TrayApp {
                         game_mode_enabled.load(Ordering::SeqCst),
                         focus_mode_enabled.load(Ordering::SeqCst),
                     );
-                    let tooltip = if auto_enabled.load(Ordering::SeqCst) {
+                    let tooltip = if let Some(pause) = paused {
+                        format!("RuVector v{} - {}% | Paused {}{}", VERSION, usage, pause, modes)
+                    } else if service_running {
+                        format!("RuVector v{} - {}% | Service{}", VERSION, usage, modes)
+                    } else if auto_enabled.load(Ordering::SeqCst) {
                         format!("RuVector v{} - {}% | Auto @{}%{}", VERSION, usage, threshold, modes)
                     } else {
                         format!("RuVector v{} - {}% | Manual{}", VERSION, usage, modes)
@@ -223,23 +457,43 @@ impl TrayApp {
                     let _ = tray_icon.set_tooltip(Some(tooltip));
 
                     // Check AI Mode conditions
-                    let game_active = game_mode_enabled.load(Ordering::SeqCst) && is_game_running();
+                    let game = if game_mode_enabled.load(Ordering::SeqCst) { game_detector.detect().map(|c| c.game) } else { None };
+                    let game_active = game.is_some();
+                    if game.as_ref().map(|g| &g.name) != boosted_game.as_ref() {
+                        boosted_game = game.as_ref().map(|g| g.name.clone());
+                        boost_game(game_booster.clone(), game);
+                    } else if let (Some(game), Ok(mut booster)) = (&game, game_booster.try_lock()) {
+                        booster.follow(game);
+                    }
                     let focus_active = focus_mode_enabled.load(Ordering::SeqCst) && is_video_call_active();
+                    notify::center().set_quiet(Quiet::Game, game_active);
+                    notify::center().set_quiet(Quiet::Call, focus_active);
+                    notify::center().poll_system();
 
                     // Adjust behavior based on AI modes
-                    let should_skip = game_active; // Don't interrupt games
-                    let aggressive_mode = focus_active; // Be more aggressive during video calls
+                    let should_skip = paused.is_some()
+                        || game_active || thermal_busy // Don't interrupt games or add heat
+                        || (quiet && (!quiet_critical || usage < critical_threshold)); // At most critical pressure in quiet hours
+                    let aggressive_mode = deep_clean || focus_active; // Be more aggressive during video calls
 
                     // Auto-optimize if enabled and conditions met
                     if auto_enabled.load(Ordering::SeqCst)
+                        && !service_running
                         && usage > threshold
                         && !should_skip
-                        && last_auto_optimize.elapsed() > std::time::Duration::from_secs(AUTO_OPTIMIZE_INTERVAL)
+                        && last_auto_optimize.elapsed() > std::time::Duration::from_secs(interval_secs)
                     {
                         let total_freed_clone = total_freed.clone();
                         std::thread::spawn(move || {
                             let optimizer = WindowsMemoryOptimizer::new();
                             if let Ok(result) = optimizer.optimize(aggressive_mode) {
+                                history::record(HistoryRecord::now(
+                                    OptimizationTrigger::Auto,
+                                    aggressive_mode,
+                                    result.freed_mb,
+                                    result.processes_trimmed,
+                                    result.duration_ms,
+                                ).with_memory_load(usage));
                                 if result.freed_mb > 100.0 {
                                     let current = total_freed_clone.load(Ordering::SeqCst);
                                     total_freed_clone.store(current + result.freed_mb as u32, Ordering::SeqCst);
@@ -249,12 +503,136 @@ impl TrayApp {
                         last_auto_optimize = std::time::Instant::now();
                     }
                 }
+
+                for schedule in scheduler.due_now() {
+                    if service_running || paused.is_some() {
+                        continue;
+                    }
+                    tracing::info!("Running schedule #{}: {}", schedule.id, schedule.describe());
+                    run_scheduled(schedule.action, total_freed.clone());
+                }
                 last_update = std::time::Instant::now();
             }
 
+            // Profile activation rules (AC/battery, running apps)
+            let mut profile_changed = false;
+            if let Some(manager) = profile_manager.as_mut() {
+                if last_profile_check.map_or(true, |t| t.elapsed() > profiles::AUTO_SWITCH_INTERVAL) {
+                    match manager.auto_switch() {
+                        Ok(Some(change)) => {
+                            let (title, msg) = change.notification();
+                            if change.profile.is_some() {
+                                let tx = undo_profile_tx.clone();
+                                notify(Notification::new(title, msg).with_actions(&[Action::Undo], move |_| {
+                                    let _ = tx.send(());
+                                }));
+                            } else {
+                                notify(Notification::new(title, msg));
+                            }
+                            profile_changed = true;
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("Profile switch failed: {}", e),
+                    }
+                    last_profile_check = Some(std::time::Instant::now());
+                }
+            }
+
+            // Automation rules; actions can take a while, so they run off the UI thread
+            if let Some(automation) = automation.as_mut() {
+                if last_agent_check.elapsed() > agent::AGENT_INTERVAL {
+                    for firing in automation.tick() {
+                        tracing::info!("Rule '{}' fired ({})", firing.rule, firing.reason);
+                        let switched = agent_switched_profile.clone();
+                        std::thread::spawn(move || {
+                            agent::execute(&firing, &mut |title, msg| notify(Notification::new(title, msg)));
+                            if firing.switches_profile() {
+                                switched.store(true, Ordering::SeqCst);
+                            }
+                        });
+                    }
+                    last_agent_check = std::time::Instant::now();
+                }
+            }
+            if agent_switched_profile.swap(false, Ordering::SeqCst) {
+                if let Some(manager) = profile_manager.as_mut() {
+                    if let Err(e) = manager.reload() {
+                        tracing::warn!("Failed to reload profiles: {}", e);
+                    }
+                }
+                profile_changed = true;
+            }
+            if undo_profile_rx.try_recv().is_ok() {
+                if let Some(manager) = profile_manager.as_mut() {
+                    if let Err(e) = manager.off() {
+                        notify(Notification::reply("Profile Not Restored", e));
+                    }
+                }
+                profile_changed = true;
+            }
+
             // Handle menu events
             if let Ok(event) = MenuEvent::receiver().try_recv() {
-                if event.id == quit_id {
+                let picked_profile = profile_items.iter().find(|(id, _, _)| *id == event.id).map(|(_, name, _)| name.clone());
+                let picked_suggestion = suggestion_items.iter().position(|(id, _, _)| *id == event.id);
+                let picked_threshold = threshold_items.iter().find(|(_, item)| *item.id() == event.id).map(|(t, _)| *t);
+                let picked_pause = pause_items.iter().find(|(_, item)| *item.id() == event.id).map(|(m, _)| *m);
+                let picked_service = throttle_items.iter().find(|(_, item)| *item.id() == event.id).map(|(s, _)| *s);
+                if let Some(index) = picked_suggestion {
+                    // Drop it from the menu so it cannot be applied twice
+                    let (_, id, item) = suggestion_items.remove(index);
+                    let _ = suggestions_menu.remove(&item);
+                    apply_suggestion(id);
+                } else if picked_profile.is_some() || event.id == profile_none_id {
+                    if let Some(manager) = profile_manager.as_mut() {
+                        let result = match &picked_profile {
+                            Some(name) => manager.apply(name),
+                            None => manager.off(),
+                        };
+                        if let Err(e) = result {
+                            notify(Notification::reply("Profile Not Applied", e));
+                        }
+                    }
+                    // Also puts the check marks back if applying failed
+                    profile_changed = true;
+                } else if let Some(minutes) = picked_pause {
+                    let until = minutes.map_or(Pause::until_resumed(), Pause::for_minutes);
+                    pause = Some(until);
+                    if let Some(Err(e)) = IpcClient::connect().map(|mut service| service.pause(until)) {
+                        tracing::warn!("Failed to pause the service: {}", e);
+                    }
+                    let _ = resume_item.set_enabled(true);
+                    timeline::record(EventKind::ModeSwitch, format!("Auto-optimize paused {}", until));
+                    notify(Notification::reply("Auto-Optimize Paused", format!("No automatic optimization {}", until)));
+                } else if let Some(service) = picked_service {
+                    // Takes effect from the next game or build
+                    let opted_in: Vec<String> = throttle_items
+                        .iter()
+                        .filter(|(_, item)| item.is_checked())
+                        .map(|(s, _)| format!("\"{}\"", s.key()))
+                        .collect();
+                    let value = format!("[{}]", opted_in.join(", "));
+                    match config::set_values_in_file(&[("throttle_services", &value)]) {
+                        Ok(_) => tracing::info!("Background services Game Mode and builds may pause: {}", value),
+                        Err(e) => {
+                            let saved = OptimizerConfig::load_file().throttle_services;
+                            for (s, item) in &throttle_items {
+                                let _ = item.set_checked(saved.contains(s));
+                            }
+                            notify(Notification::reply(format!("{} Setting Not Saved", service), e));
+                        }
+                    }
+                } else if event.id == resume_id {
+                    pause = None;
+                    if let Some(Err(e)) = IpcClient::connect().map(|mut service| service.resume()) {
+                        tracing::warn!("Failed to resume the service: {}", e);
+                    }
+                    let _ = resume_item.set_enabled(false);
+                    timeline::record(EventKind::ModeSwitch, "Auto-optimize resumed");
+                } else if event.id == quit_id {
+                    if let Ok(mut booster) = game_booster.lock() {
+                        booster.restore();
+                    }
                     running.store(false, Ordering::SeqCst);
                     event_loop.exit();
                 } else if event.id == optimize_id {
@@ -266,6 +644,8 @@ impl TrayApp {
                 } else if event.id == browser_id {
                     let total_freed_clone = total_freed.clone();
                     run_browser_optimization(total_freed_clone);
+                } else if event.id == undo_id {
+                    undo_last_optimization();
                 } else if event.id == control_center_id {
                     control_center::open(settings.clone());
                 } else if event.id == cpu_id {
@@ -292,6 +672,7 @@ impl TrayApp {
                         s.ai_mode.game_mode = new_val;
                         let _ = s.save();
                     }
+                    timeline::record(EventKind::ModeSwitch, format!("Game Mode {}", if new_val { "on" } else { "off" }));
                     if new_val {
                         tracing::info!("Game Mode enabled - will detect games and prioritize");
                     }
@@ -305,6 +686,7 @@ impl TrayApp {
                         s.ai_mode.focus_mode = new_val;
                         let _ = s.save();
                     }
+                    timeline::record(EventKind::ModeSwitch, format!("Focus Mode {}", if new_val { "on" } else { "off" }));
                     if new_val {
                         tracing::info!("Focus Mode enabled - will detect video calls");
                     }
@@ -328,34 +710,36 @@ impl TrayApp {
                         s.ai_mode.predictive_preload = new_val;
                         let _ = s.save();
                     }
-                } else if event.id == threshold_75_id {
-                    current_threshold.store(75, Ordering::SeqCst);
-                    let _ = threshold_75.set_checked(true);
-                    let _ = threshold_80.set_checked(false);
-                    let _ = threshold_85.set_checked(false);
-                    let _ = threshold_90.set_checked(false);
-                    if let Ok(mut s) = settings.lock() { s.threshold = 75; let _ = s.save(); }
-                } else if event.id == threshold_80_id {
-                    current_threshold.store(80, Ordering::SeqCst);
-                    let _ = threshold_75.set_checked(false);
-                    let _ = threshold_80.set_checked(true);
-                    let _ = threshold_85.set_checked(false);
-                    let _ = threshold_90.set_checked(false);
-                    if let Ok(mut s) = settings.lock() { s.threshold = 80; let _ = s.save(); }
-                } else if event.id == threshold_85_id {
-                    current_threshold.store(85, Ordering::SeqCst);
-                    let _ = threshold_75.set_checked(false);
-                    let _ = threshold_80.set_checked(false);
-                    let _ = threshold_85.set_checked(true);
-                    let _ = threshold_90.set_checked(false);
-                    if let Ok(mut s) = settings.lock() { s.threshold = 85; let _ = s.save(); }
-                } else if event.id == threshold_90_id {
-                    current_threshold.store(90, Ordering::SeqCst);
-                    let _ = threshold_75.set_checked(false);
-                    let _ = threshold_80.set_checked(false);
-                    let _ = threshold_85.set_checked(false);
-                    let _ = threshold_90.set_checked(true);
-                    if let Ok(mut s) = settings.lock() { s.threshold = 90; let _ = s.save(); }
+                } else if let Some(threshold) = picked_threshold {
+                    sync_threshold_items(&threshold_items, &threshold_custom, threshold);
+                    if let Ok(mut s) = settings.lock() { s.threshold = threshold; let _ = s.save(); }
+                } else if event.id == advanced_id {
+                    control_center::open_settings(settings.clone());
+                }
+            }
+
+            // A profile rewrote the saved settings: reload them into the menu
+            if profile_changed {
+                let s = TraySettings::load();
+                auto_enabled.store(s.auto_optimize, Ordering::SeqCst);
+                game_mode_enabled.store(s.ai_mode.game_mode, Ordering::SeqCst);
+                focus_mode_enabled.store(s.ai_mode.focus_mode, Ordering::SeqCst);
+                thermal_enabled.store(s.ai_mode.thermal_prediction, Ordering::SeqCst);
+                preload_enabled.store(s.ai_mode.predictive_preload, Ordering::SeqCst);
+                let _ = auto_item.set_checked(s.auto_optimize);
+                let _ = game_mode_item.set_checked(s.ai_mode.game_mode);
+                let _ = focus_mode_item.set_checked(s.ai_mode.focus_mode);
+                let _ = thermal_item.set_checked(s.ai_mode.thermal_prediction);
+                let _ = preload_item.set_checked(s.ai_mode.predictive_preload);
+                sync_threshold_items(&threshold_items, &threshold_custom, s.threshold);
+
+                let active = profile_manager.as_ref().and_then(|m| m.active());
+                for (_, name, item) in &profile_items {
+                    let _ = item.set_checked(active == Some(name.as_str()));
+                }
+                let _ = profile_none.set_checked(active.is_none());
+                if let Ok(mut current) = settings.lock() {
+                    *current = s;
                 }
             }
         })?;
@@ -364,6 +748,35 @@ impl TrayApp {
     }
 }
 
+/// Thresholds offered directly in the Settings submenu
+const THRESHOLD_PRESETS: [u32; 4] = [75, 80, 85, 90];
+
+/// Entries of the Pause submenu, in minutes; `None` lasts until Resume
+const PAUSE_PRESETS: [(Option<u64>, &str); 4] = [
+    (Some(30), "For 30 Minutes"),
+    (Some(60), "For 1 Hour"),
+    (Some(120), "For 2 Hours"),
+    (None, "Until Resumed"),
+];
+
+/// Check the preset matching `threshold`, or show it as the custom entry
+fn sync_threshold_items(presets: &[(u32, CheckMenuItem)], custom: &CheckMenuItem, threshold: u32) {
+    for (t, item) in presets {
+        item.set_checked(*t == threshold);
+    }
+    let is_custom = !THRESHOLD_PRESETS.contains(&threshold);
+    custom.set_text(if is_custom { format!("Threshold: {}% (custom)", threshold) } else { "Threshold: Custom".to_string() });
+    custom.set_checked(is_custom);
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 fn build_mode_string(game: bool, focus: bool) -> String {
     let mut modes = Vec::new();
     if game { modes.push("Game"); }
@@ -388,10 +801,101 @@ fn get_memory_status_text() -> String {
     }
 }
 
+/// Suggestions listed in the tray menu
+const MAX_TRAY_SUGGESTIONS: usize = 5;
+
+/// How often the Suggestions submenu is rebuilt
+const SUGGESTION_REFRESH: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Collect the top one-click suggestions as `(id, label)` off the UI thread
+///
+/// High-risk ones (closing apps, stopping containers) are left to the CLI
+/// and dashboard, which ask for confirmation.
+fn refresh_suggestions() -> std::sync::mpsc::Receiver<Vec<(String, String)>> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut engine = SmartSuggestions::new();
+        engine.refresh();
+        let entries = engine
+            .get_suggestions()
+            .iter()
+            .filter(|s| s.actionable && s.risk < SuggestionRisk::High)
+            .take(MAX_TRAY_SUGGESTIONS)
+            .map(|s| match s.estimated_mb > 0.0 {
+                true => (s.id.clone(), format!("{} (~{:.0} MB)", s.title, s.estimated_mb)),
+                false => (s.id.clone(), s.title.clone()),
+            })
+            .collect();
+        let _ = tx.send(entries);
+    });
+    rx
+}
+
+fn apply_suggestion(id: String) {
+    std::thread::spawn(move || {
+        let mut engine = SmartSuggestions::new();
+        engine.refresh();
+        let title = engine.get(&id).map(|s| s.title.clone()).unwrap_or_else(|| id.clone());
+        let start = std::time::Instant::now();
+        match engine.apply(&id) {
+            Ok(outcome) => {
+                // Offer Undo only if this suggestion journaled something
+                let window = undo::window(&OptimizerConfig::load_or_default());
+                if undo::last_undoable(window).is_some_and(|entry| entry.age() <= start.elapsed()) {
+                    let notification = Notification::reply(title, outcome.message).with_freed(outcome.freed_mb);
+                    notify(notification.with_actions(&[Action::Undo], |_| {
+                        undo_last_optimization();
+                    }));
+                } else {
+                    notify(Notification::reply(title, outcome.message).with_freed(outcome.freed_mb));
+                }
+            }
+            Err(e) => notify(Notification::reply("Suggestion Not Applied", e)),
+        }
+    });
+}
+
+fn undo_last_optimization() {
+    std::thread::spawn(|| {
+        let window = undo::window(&OptimizerConfig::load_or_default());
+        match undo::undo_last(window) {
+            Ok(report) if report.failed.is_empty() => {
+                notify(Notification::reply("Optimization Undone", report.description));
+            }
+            Ok(report) => {
+                let msg = format!("{}: {}", report.description, report.failed.join("; "));
+                notify(Notification::reply("Optimization Partly Undone", msg));
+            }
+            Err(e) => notify(Notification::reply("Nothing Undone", e)),
+        }
+    });
+}
+
+/// Optimize through the service if it is running, otherwise in the tray
+fn optimize_now(aggressive: bool) -> Result<OptimizeReport, String> {
+    if let Some(mut service) = IpcClient::connect() {
+        return service.optimize(aggressive);
+    }
+    let result = WindowsMemoryOptimizer::new().optimize(aggressive)?;
+    history::record(HistoryRecord::now(
+        OptimizationTrigger::Manual,
+        aggressive,
+        result.freed_mb,
+        result.processes_trimmed,
+        result.duration_ms,
+    ));
+    Ok(OptimizeReport {
+        trigger: OptimizationTrigger::Manual,
+        aggressive,
+        freed_mb: result.freed_mb,
+        processes: result.processes_trimmed,
+        duration_ms: result.duration_ms,
+    })
+}
+
 fn run_optimization(aggressive: bool, total_freed: Arc<AtomicU32>) {
     std::thread::spawn(move || {
-        let optimizer = WindowsMemoryOptimizer::new();
-        match optimizer.optimize(aggressive) {
+        match optimize_now(aggressive) {
             Ok(result) => {
                 let current = total_freed.load(Ordering::SeqCst);
                 total_freed.store(current + result.freed_mb as u32, Ordering::SeqCst);
@@ -406,23 +910,54 @@ fn run_optimization(aggressive: bool, total_freed: Arc<AtomicU32>) {
                 };
 
                 let msg = if result.freed_mb > 0.0 {
-                    format!("Freed {:.0} MB from {} processes", result.freed_mb, result.processes_trimmed)
+                    format!("Freed {:.0} MB from {} processes", result.freed_mb, result.processes)
                 } else {
                     "No memory to reclaim right now".to_string()
                 };
 
-                show_notification(title, &msg, Some(result.freed_mb));
+                notify(Notification::reply(title, msg).with_freed(result.freed_mb));
                 tracing::info!("Optimized: affected {} processes, freed {:.1} MB in {}ms",
-                    result.processes_trimmed, result.freed_mb, result.duration_ms);
+                    result.processes, result.freed_mb, result.duration_ms);
             }
             Err(e) => {
-                show_notification("Optimization Failed", &format!("{}", e), None);
+                notify(Notification::reply("Optimization Failed", e.to_string()));
                 tracing::error!("Optimization error: {}", e);
             }
         }
     });
 }
 
+fn run_scheduled(action: ScheduledAction, total_freed: Arc<AtomicU32>) {
+    std::thread::spawn(move || {
+        let freed = match action {
+            ScheduledAction::Optimize | ScheduledAction::AggressiveOptimize => {
+                let aggressive = action == ScheduledAction::AggressiveOptimize;
+                match WindowsMemoryOptimizer::new().optimize(aggressive) {
+                    Ok(result) => {
+                        history::record(HistoryRecord::now(
+                            OptimizationTrigger::Scheduled,
+                            aggressive,
+                            result.freed_mb,
+                            result.processes_trimmed,
+                            result.duration_ms,
+                        ));
+                        result.freed_mb
+                    }
+                    Err(e) => {
+                        tracing::error!("Scheduled optimization error: {}", e);
+                        return;
+                    }
+                }
+            }
+            ScheduledAction::BrowserTrim => scheduler::trim_browsers(OptimizationTrigger::Scheduled).0,
+            ScheduledAction::DailyReport => return report::run_scheduled(ReportPeriod::Day),
+            ScheduledAction::WeeklyReport => return report::run_scheduled(ReportPeriod::Week),
+        };
+        let current = total_freed.load(Ordering::SeqCst);
+        total_freed.store(current + freed as u32, Ordering::SeqCst);
+    });
+}
+
 fn run_browser_optimization(total_freed: Arc<AtomicU32>) {
     std::thread::spawn(move || {
         use sysinfo::System;
@@ -464,8 +999,7 @@ fn run_browser_optimization(total_freed: Arc<AtomicU32>) {
         }
 
         if apps.is_empty() {
-            show_notification("No Apps Found",
-                "No browsers or Electron apps running", None);
+            notify(Notification::reply("No Apps Found", "No browsers or Electron apps running"));
             return;
         }
 
@@ -521,7 +1055,16 @@ fn run_browser_optimization(total_freed: Arc<AtomicU32>) {
             format!("{} apps already optimized", app_count)
         };
 
-        show_notification(title, &msg, Some(freed_mb));
+        let mut breakdown: Vec<String> = details
+            .iter()
+            .map(|(name, freed, trimmed, count)| format!("{}: {:.0} MB ({}/{} processes)", name, freed, trimmed, count))
+            .collect();
+        breakdown.sort();
+        let breakdown = breakdown.join("\n");
+        let details_title = title.to_string();
+        notify(Notification::reply(title, msg).with_freed(freed_mb).with_actions(&[Action::Details], move |_| {
+            show_message_box(&details_title, &breakdown);
+        }));
 
         // Log details
         tracing::info!("App optimization: freed {:.1} MB from {}/{} processes",
@@ -569,51 +1112,6 @@ fn open_github() {
     }
 }
 
-/// Show macOS-style notification banner (non-blocking, auto-dismiss)
-fn show_notification(title: &str, message: &str, freed_mb: Option<f64>) {
-    let title = title.to_string();
-    let message = message.to_string();
-
-    std::thread::spawn(move || {
-        #[cfg(windows)]
-        dialog::show_banner(&title, &message, freed_mb);
-
-        #[cfg(target_os = "macos")]
-        show_macos_notification(&title, &message, freed_mb);
-    });
-}
-
-/// macOS notification using osascript (native Notification Center)
-#[cfg(target_os = "macos")]
-fn show_macos_notification(title: &str, message: &str, freed_mb: Option<f64>) {
-    // Build a clean, iOS-style notification message
-    let icon = if let Some(freed) = freed_mb {
-        if freed > 100.0 { "✅ " }
-        else if freed > 0.0 { "💾 " }
-        else { "ℹ️ " }
-    } else {
-        ""
-    };
-
-    // Clean message for AppleScript (escape quotes)
-    let clean_message = message
-        .replace("\\", "\\\\")
-        .replace("\"", "\\\"")
-        .replace("\n", " • ");
-
-    let clean_title = format!("{}{}", icon, title);
-
-    // Use osascript to show native macOS notification
-    let script = format!(
-        r#"display notification "{}" with title "{}" sound name "Glass""#,
-        clean_message, clean_title
-    );
-
-    let _ = std::process::Command::new("osascript")
-        .args(["-e", &script])
-        .spawn();
-}
-
 /// Show macOS-style info dialog (blocks until dismissed)
 fn show_message_box(title: &str, message: &str) {
     #[cfg(windows)]
@@ -723,88 +1221,45 @@ impl Default for TrayApp {
     }
 }
 
-/// Check if a game is currently running (Game Mode)
-fn is_game_running() -> bool {
-    use sysinfo::System;
-
-    // Known game process names (lowercase for comparison)
-    const GAME_PROCESSES: &[&str] = &[
-        // Popular games
-        "valorant", "valorant-win64-shipping",
-        "csgo", "cs2",
-        "fortnite", "fortniteclient-win64-shipping",
-        "minecraft", "javaw", // Minecraft Java
-        "league of legends", "leagueclient",
-        "overwatch", "overwatch 2",
-        "apex_legends", "r5apex",
-        "pubg", "tslgame",
-        "gta5", "gtavlauncher",
-        "rdr2",
-        "cyberpunk2077",
-        "eldenring",
-        "hogwartslegacy",
-        "starfield",
-        "baldursgate3",
-        "diablo", "diablo iv",
-        "destiny2",
-        "warframe",
-        "rocketleague",
-        "dota2",
-        "steam_oculusvr", // VR games
-        // Game launchers (if in focus, likely gaming)
-        "epicgameslauncher",
-        "origin",
-        "battle.net",
-        "ubisoft connect",
-    ];
-
-    let mut system = System::new();
-    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-
-    for (_pid, process) in system.processes() {
-        let name = process.name().to_string_lossy().to_lowercase();
-        for game in GAME_PROCESSES {
-            if name.contains(game) {
-                tracing::debug!("Game detected: {}", name);
-                return true;
+/// Boost a newly detected game, or restore everything once it has exited
+///
+/// Stopping services and switching the power plan can take a few seconds,
+/// so this runs off the UI thread.
+fn boost_game(booster: Arc<Mutex<GameBooster>>, game: Option<RunningGame>) {
+    std::thread::spawn(move || {
+        let Ok(mut booster) = booster.lock() else {
+            return;
+        };
+        match game {
+            Some(game) => {
+                let report = booster.start(&game);
+                for line in report.applied.iter().chain(&report.skipped) {
+                    tracing::info!("Game Mode: {}", line);
+                }
+                timeline::record(EventKind::ModeSwitch, format!("Game Mode boosting {}", game.name));
+                notify(Notification::new(
+                    "Game Mode",
+                    format!("Boosting {} ({} changes, undone when it exits)", game.name, report.applied.len()),
+                ));
+            }
+            None => {
+                if let Some(game) = booster.game().map(str::to_string) {
+                    booster.restore();
+                    timeline::record(EventKind::ModeSwitch, format!("Game Mode restored after {}", game));
+                }
             }
         }
-    }
-
-    false
+    });
 }
 
-/// Check if a video call application is active (Focus Mode)
+/// Check if a call is in progress (Focus Mode): an app holds the
+/// microphone or camera open
 fn is_video_call_active() -> bool {
-    use sysinfo::System;
-
-    // Video call applications
-    const VIDEO_CALL_PROCESSES: &[&str] = &[
-        "zoom",
-        "teams", "ms-teams",
-        "slack",
-        "discord",
-        "webex",
-        "gotomeeting",
-        "skype",
-        "facetime",
-        "google meet", // Usually runs in browser
-        "obs", "obs64", // Streaming/recording
-        "streamlabs",
-    ];
-
-    let mut system = System::new();
-    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-
-    for (_pid, process) in system.processes() {
-        let name = process.name().to_string_lossy().to_lowercase();
-        for app in VIDEO_CALL_PROCESSES {
-            if name.contains(app) {
-                tracing::debug!("Video call app detected: {}", name);
-                return true;
-            }
+    match capture::in_use().first() {
+        Some(capture) => {
+            tracing::debug!("Call detected: {}", capture);
+            true
         }
+        None => false,
     }
-
-    false
 }
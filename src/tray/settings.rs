@@ -1,12 +1,22 @@
 //! Settings persistence for the tray application
 //!
-//! Saves and loads user preferences to a TOML config file.
+//! Preferences live in the shared `config.toml`. The automatic optimization
+//! knobs (threshold, interval, deep clean, quiet hours) are the optimizer's
+//! own top-level keys, so the daemon and service follow whatever the tray is
+//! set to; everything else goes in a `[tray]` section. Settings from the old
+//! `memopt.toml` are imported the first time.
 
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::core::config::{self, OptimizerConfig};
+
+/// Fields stored as top-level optimizer keys rather than under `[tray]`
+const SHARED_KEYS: &[&str] = &["threshold", "interval_secs", "aggressive", "quiet_hours"];
+
 /// Application settings that persist between sessions
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TraySettings {
     /// Auto-optimization enabled
     pub auto_optimize: bool,
@@ -14,20 +24,23 @@ pub struct TraySettings {
     pub threshold: u32,
     /// Auto-optimization interval in seconds
     pub interval_secs: u64,
+    /// Automatic runs use Deep Clean
+    pub aggressive: bool,
+    /// Daily `HH:MM-HH:MM` window with no automatic runs below critical pressure
+    pub quiet_hours: Option<String>,
 
     /// AI Mode settings
     pub ai_mode: AIModeSettings,
 
     /// UI theme ("dark" or "light")
-    #[serde(default = "default_theme")]
     pub theme: String,
     /// Whether the welcome screen has been shown
-    #[serde(default)]
     pub welcome_shown: bool,
 }
 
 /// AI Mode specific settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AIModeSettings {
     /// Game Mode auto-detection enabled
     pub game_mode: bool,
@@ -53,6 +66,8 @@ impl Default for TraySettings {
             auto_optimize: true,
             threshold: 75,
             interval_secs: 60,
+            aggressive: false,
+            quiet_hours: None,
             ai_mode: AIModeSettings::default(),
             theme: default_theme(),
             welcome_shown: false,
@@ -74,8 +89,8 @@ impl Default for AIModeSettings {
 }
 
 impl TraySettings {
-    /// Get the config file path
-    pub fn config_path() -> PathBuf {
+    /// Where settings were kept before they moved into `config.toml`
+    pub fn legacy_path() -> PathBuf {
         // Use %APPDATA%\RuVector\memopt.toml
         if let Some(appdata) = std::env::var_os("APPDATA") {
             let mut path = PathBuf::from(appdata);
@@ -95,53 +110,96 @@ impl TraySettings {
         PathBuf::from("memopt.toml")
     }
 
-    /// Load settings from config file, or return defaults
+    /// Load settings from the shared config, or return defaults
     pub fn load() -> Self {
-        let path = Self::config_path();
-
-        if path.exists() {
-            match std::fs::read_to_string(&path) {
-                Ok(content) => {
-                    match toml::from_str(&content) {
-                        Ok(settings) => {
-                            tracing::info!("Loaded settings from {:?}", path);
-                            return settings;
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to parse settings: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to read settings file: {}", e);
+        let config = OptimizerConfig::load_file();
+
+        let Some(table) = config.tray.clone() else {
+            if let Some(settings) = Self::load_legacy() {
+                if let Err(e) = settings.save() {
+                    tracing::warn!("Failed to import settings: {}", e);
                 }
+                return settings;
             }
-        }
+            let mut settings = Self::default();
+            settings.take_from(&config);
+            return settings;
+        };
 
-        tracing::info!("Using default settings");
-        Self::default()
+        let mut settings = match table.try_into::<Self>() {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!("Failed to parse [tray] settings: {}", e);
+                Self::default()
+            }
+        };
+        settings.take_from(&config);
+        settings
     }
 
-    /// Save settings to config file
-    pub fn save(&self) -> Result<(), String> {
-        let path = Self::config_path();
-
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    /// Settings from the old `memopt.toml`, if there is one
+    fn load_legacy() -> Option<Self> {
+        let path = Self::legacy_path();
+        let content = std::fs::read_to_string(&path).ok()?;
+        match toml::from_str(&content) {
+            Ok(settings) => {
+                tracing::info!("Importing settings from {:?}", path);
+                Some(settings)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to parse settings: {}", e);
+                None
+            }
         }
+    }
 
-        let content = toml::to_string_pretty(self)
+    /// Save settings into the shared config; a file that fails to parse is left alone
+    pub fn save(&self) -> Result<(), String> {
+        let path = OptimizerConfig::default_path();
+        let mut config = if path.exists() {
+            OptimizerConfig::load(&path)
+                .map_err(|e| format!("Not saving settings, {:?} failed to parse: {}", path, e))?
+        } else {
+            OptimizerConfig::default()
+        };
+
+        self.apply_to(&mut config);
+        let mut table = toml::Table::try_from(self)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        for key in SHARED_KEYS {
+            table.remove(*key);
+        }
+        config.tray = Some(table);
 
-        std::fs::write(&path, content)
+        config
+            .save(&path)
             .map_err(|e| format!("Failed to write settings: {}", e))?;
 
         tracing::info!("Saved settings to {:?}", path);
         Ok(())
     }
 
+    /// Copy the automatic optimization knobs into an optimizer config
+    pub fn apply_to(&self, config: &mut OptimizerConfig) {
+        config.pressure_threshold = self.threshold;
+        config.min_interval_secs = self.interval_secs;
+        config.aggressive_mode = self.aggressive;
+        config.quiet_hours = self.quiet_hours.clone().filter(|w| !w.trim().is_empty());
+    }
+
+    /// Take the automatic optimization knobs from an optimizer config
+    pub fn take_from(&mut self, config: &OptimizerConfig) {
+        self.threshold = config.pressure_threshold;
+        self.interval_secs = config.min_interval_secs;
+        self.aggressive = config.aggressive_mode;
+        self.quiet_hours = config.quiet_hours.clone();
+    }
+
+    /// Whether automatic optimization is in its quiet hours right now
+    pub fn in_quiet_hours(&self) -> bool {
+        config::in_quiet_hours(self.quiet_hours.as_deref())
+    }
+
     /// Update a single setting and save
     pub fn update<F>(&mut self, f: F) -> Result<(), String>
     where
@@ -171,4 +229,34 @@ mod tests {
         let restored: TraySettings = toml::from_str(&toml).unwrap();
         assert_eq!(settings.threshold, restored.threshold);
     }
+
+    #[test]
+    fn test_apply_to_config() {
+        let settings = TraySettings {
+            threshold: 83,
+            interval_secs: 45,
+            aggressive: true,
+            quiet_hours: Some(" ".into()),
+            ..TraySettings::default()
+        };
+        let mut config = OptimizerConfig::default();
+        settings.apply_to(&mut config);
+        assert_eq!(config.pressure_threshold, 83);
+        assert_eq!(config.min_interval_secs, 45);
+        assert!(config.aggressive_mode);
+        assert_eq!(config.quiet_hours, None);
+    }
+
+    #[test]
+    fn test_partial_tray_section() {
+        let table: toml::Table = toml::from_str("theme = \"light\"\n[ai_mode]\ngame_mode = false\n").unwrap();
+        let mut settings: TraySettings = table.try_into().unwrap();
+        assert_eq!(settings.theme, "light");
+        assert!(!settings.ai_mode.game_mode);
+        assert!(settings.ai_mode.focus_mode);
+
+        let config = OptimizerConfig { pressure_threshold: 88, ..OptimizerConfig::default() };
+        settings.take_from(&config);
+        assert_eq!(settings.threshold, 88);
+    }
 }
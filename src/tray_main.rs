@@ -23,6 +23,14 @@ mod tray;
 mod algorithms;
 #[cfg(windows)]
 mod dashboard;
+#[cfg(windows)]
+mod apps;
+#[cfg(windows)]
+mod features;
+#[cfg(windows)]
+mod platform;
+#[cfg(windows)]
+mod security;
 
 #[cfg(windows)]
 fn main() {
@@ -47,6 +55,15 @@ fn main() {
             .try_init();
     }
 
+    // The first process only watches over the real tray, restarting it if it crashes
+    if !core::watchdog::is_supervised() {
+        if let Err(e) = core::watchdog::Watchdog::new("tray", None).run() {
+            tracing::error!("Tray watchdog: {}", e);
+        }
+        return;
+    }
+    core::watchdog::install_panic_hook();
+
     let tray_app = tray::TrayApp::new();
     if let Err(e) = tray_app.run() {
         // Log the error since console is hidden
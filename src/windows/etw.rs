@@ -0,0 +1,232 @@
+//! ETW provider for correlating optimizations with WPA and xperf traces
+//!
+//! Events are self-describing TraceLogging events, so WPA shows their
+//! fields without a manifest. The provider GUID is the one ETW derives from
+//! the name, which lets WPR and xperf enable it as `*RuVector-MemOpt` too.
+//! Nothing is built unless a trace session has the provider enabled.
+
+use std::sync::OnceLock;
+
+use windows::core::GUID;
+use windows::Win32::System::Diagnostics::Etw::{
+    EventProviderEnabled, EventProviderSetTraits, EventRegister, EventSetInformation,
+    EventWriteTransfer, EVENT_DATA_DESCRIPTOR, EVENT_DATA_DESCRIPTOR_0, EVENT_DATA_DESCRIPTOR_0_0,
+    EVENT_DESCRIPTOR, REGHANDLE,
+};
+
+pub const PROVIDER_NAME: &str = "RuVector-MemOpt";
+
+/// Name hash of [`PROVIDER_NAME`]
+pub const PROVIDER_GUID: GUID = GUID::from_u128(0x3d2ee52f_be30_56f8_abf3_7c3ce401b559);
+
+/// Keywords, for filtering in a trace session
+pub const KEYWORD_OPTIMIZATION: u64 = 0x1;
+pub const KEYWORD_TRIM: u64 = 0x2;
+pub const KEYWORD_SAFETY: u64 = 0x4;
+pub const KEYWORD_DECISION: u64 = 0x8;
+
+const LEVEL_WARNING: u8 = 3;
+const LEVEL_INFO: u8 = 4;
+const LEVEL_VERBOSE: u8 = 5;
+
+const OPCODE_INFO: u8 = 0;
+const OPCODE_START: u8 = 1;
+const OPCODE_STOP: u8 = 2;
+
+/// Channel that marks an event as TraceLogging on older decoders
+const CHANNEL_TRACELOGGING: u8 = 11;
+
+/// Data descriptor types for the metadata blocks
+const DESCRIPTOR_EVENT_METADATA: u8 = 1;
+const DESCRIPTOR_PROVIDER_METADATA: u8 = 2;
+
+/// Field in-types, and the out-type shown for strings
+const IN_ANSISTRING: u8 = 2;
+const IN_UINT32: u8 = 8;
+const IN_UINT64: u8 = 10;
+const IN_DOUBLE: u8 = 12;
+const IN_BOOL32: u8 = 13;
+const HAS_OUT_TYPE: u8 = 0x80;
+const OUT_UTF8: u8 = 35;
+
+/// Registered on first use and never unregistered; the handle lives as
+/// long as the process
+fn provider() -> Option<REGHANDLE> {
+    static HANDLE: OnceLock<Option<REGHANDLE>> = OnceLock::new();
+    *HANDLE.get_or_init(|| {
+        let mut handle = REGHANDLE::default();
+        let status = unsafe { EventRegister(&PROVIDER_GUID, None, None, &mut handle) };
+        if status != 0 {
+            tracing::debug!("ETW provider registration failed: {}", status);
+            return None;
+        }
+        let traits = provider_traits();
+        unsafe {
+            let _ = EventSetInformation(
+                handle,
+                EventProviderSetTraits,
+                traits.as_ptr() as *const _,
+                traits.len() as u32,
+            );
+        }
+        Some(handle)
+    })
+}
+
+/// Handle to write with, if a session wants events at this level and keyword
+fn enabled(level: u8, keyword: u64) -> Option<REGHANDLE> {
+    let handle = provider()?;
+    unsafe { EventProviderEnabled(handle, level, keyword) }.as_bool().then_some(handle)
+}
+
+/// Size-prefixed provider name, sent with every event so decoders know it
+fn provider_traits() -> Vec<u8> {
+    let mut traits = vec![0, 0];
+    traits.extend_from_slice(PROVIDER_NAME.as_bytes());
+    traits.push(0);
+    let size = traits.len() as u16;
+    traits[..2].copy_from_slice(&size.to_le_bytes());
+    traits
+}
+
+/// Metadata and payload of one event, built field by field
+struct Event {
+    meta: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl Event {
+    fn new(name: &str) -> Self {
+        // Size, filled in on write, then no tags
+        let mut meta = vec![0, 0, 0];
+        meta.extend_from_slice(name.as_bytes());
+        meta.push(0);
+        Self { meta, data: Vec::new() }
+    }
+
+    fn field(&mut self, name: &str, in_type: u8) {
+        self.meta.extend_from_slice(name.as_bytes());
+        self.meta.push(0);
+        self.meta.push(in_type);
+    }
+
+    fn str(mut self, name: &str, value: &str) -> Self {
+        self.field(name, IN_ANSISTRING | HAS_OUT_TYPE);
+        self.meta.push(OUT_UTF8);
+        self.data.extend(value.bytes().filter(|&b| b != 0));
+        self.data.push(0);
+        self
+    }
+
+    fn u32(mut self, name: &str, value: u32) -> Self {
+        self.field(name, IN_UINT32);
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u64(mut self, name: &str, value: u64) -> Self {
+        self.field(name, IN_UINT64);
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn f64(mut self, name: &str, value: f64) -> Self {
+        self.field(name, IN_DOUBLE);
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn bool(mut self, name: &str, value: bool) -> Self {
+        self.field(name, IN_BOOL32);
+        self.data.extend_from_slice(&(value as u32).to_le_bytes());
+        self
+    }
+
+    fn write(mut self, handle: REGHANDLE, level: u8, opcode: u8, keyword: u64) {
+        let size = self.meta.len() as u16;
+        self.meta[..2].copy_from_slice(&size.to_le_bytes());
+        let traits = provider_traits();
+        let descriptor = EVENT_DESCRIPTOR {
+            Id: 0,
+            Version: 0,
+            Channel: CHANNEL_TRACELOGGING,
+            Level: level,
+            Opcode: opcode,
+            Task: 0,
+            Keyword: keyword,
+        };
+        let blocks = [
+            data_descriptor(&traits, DESCRIPTOR_PROVIDER_METADATA),
+            data_descriptor(&self.meta, DESCRIPTOR_EVENT_METADATA),
+            data_descriptor(&self.data, 0),
+        ];
+        unsafe {
+            let _ = EventWriteTransfer(handle, &descriptor, None, None, Some(&blocks));
+        }
+    }
+}
+
+fn data_descriptor(bytes: &[u8], kind: u8) -> EVENT_DATA_DESCRIPTOR {
+    EVENT_DATA_DESCRIPTOR {
+        Ptr: bytes.as_ptr() as u64,
+        Size: bytes.len() as u32,
+        Anonymous: EVENT_DATA_DESCRIPTOR_0 {
+            Anonymous: EVENT_DATA_DESCRIPTOR_0_0 { Type: kind, Reserved1: 0, Reserved2: 0 },
+        },
+    }
+}
+
+/// An optimization is about to trim `planned` processes
+pub fn optimization_start(aggressive: bool, planned: u32, memory_load: u32, available_mb: f64) {
+    let Some(handle) = enabled(LEVEL_INFO, KEYWORD_OPTIMIZATION) else { return };
+    Event::new("OptimizationStart")
+        .bool("Aggressive", aggressive)
+        .u32("PlannedProcesses", planned)
+        .u32("MemoryLoadPercent", memory_load)
+        .f64("AvailableMB", available_mb)
+        .write(handle, LEVEL_INFO, OPCODE_START, KEYWORD_OPTIMIZATION);
+}
+
+/// The optimization announced by [`optimization_start`] finished
+pub fn optimization_stop(freed_mb: f64, trimmed: u32, failed: u32, duration_ms: u64) {
+    let Some(handle) = enabled(LEVEL_INFO, KEYWORD_OPTIMIZATION) else { return };
+    Event::new("OptimizationStop")
+        .f64("FreedMB", freed_mb)
+        .u32("ProcessesTrimmed", trimmed)
+        .u32("ProcessesFailed", failed)
+        .u64("DurationMs", duration_ms)
+        .write(handle, LEVEL_INFO, OPCODE_STOP, KEYWORD_OPTIMIZATION);
+}
+
+/// One process's working set was trimmed, or trimming it failed
+pub fn process_trimmed(pid: u32, name: &str, freed_mb: f64, error: Option<&str>) {
+    let Some(handle) = enabled(LEVEL_VERBOSE, KEYWORD_TRIM) else { return };
+    Event::new("ProcessTrim")
+        .u32("ProcessId", pid)
+        .str("ProcessName", name)
+        .f64("FreedMB", freed_mb)
+        .bool("Success", error.is_none())
+        .str("Error", error.unwrap_or(""))
+        .write(handle, LEVEL_VERBOSE, OPCODE_INFO, KEYWORD_TRIM);
+}
+
+/// The safety guard refused an optimization
+pub fn safety_rejected(check: &str, reason: &str) {
+    let Some(handle) = enabled(LEVEL_WARNING, KEYWORD_SAFETY) else { return };
+    Event::new("SafetyRejection")
+        .str("Check", check)
+        .str("Reason", reason)
+        .write(handle, LEVEL_WARNING, OPCODE_INFO, KEYWORD_SAFETY);
+}
+
+/// The neural engine or the rule-based fallback decided whether to optimize
+pub fn decision(source: &str, should_optimize: bool, aggressive: bool, confidence: f32, reason: &str) {
+    let Some(handle) = enabled(LEVEL_INFO, KEYWORD_DECISION) else { return };
+    Event::new("Decision")
+        .str("Source", source)
+        .bool("ShouldOptimize", should_optimize)
+        .bool("Aggressive", aggressive)
+        .f64("Confidence", confidence as f64)
+        .str("Reason", reason)
+        .write(handle, LEVEL_INFO, OPCODE_INFO, KEYWORD_DECISION);
+}
@@ -0,0 +1,76 @@
+//! Minimal Windows Event Log writer for the service and audit records
+
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::Security::PSID;
+use windows::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+    EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE, REPORT_EVENT_TYPE,
+};
+
+/// Event ID used for service messages
+const EVENT_ID: u32 = 1000;
+/// Event ID used for audit records
+const AUDIT_EVENT_ID: u32 = 1001;
+
+/// Handle to a registered event source
+pub struct EventLog {
+    handle: Option<HANDLE>,
+}
+
+impl EventLog {
+    /// Register `source` as an event source; logging becomes a no-op on failure
+    pub fn new(source: &str) -> Self {
+        let wide = to_wide(source);
+        let handle = unsafe { RegisterEventSourceW(PCWSTR::null(), PCWSTR(wide.as_ptr())) }.ok();
+        Self { handle }
+    }
+
+    pub fn info(&self, message: &str) {
+        tracing::info!("{}", message);
+        self.report(EVENTLOG_INFORMATION_TYPE, message);
+    }
+
+    pub fn warn(&self, message: &str) {
+        tracing::warn!("{}", message);
+        self.report(EVENTLOG_WARNING_TYPE, message);
+    }
+
+    pub fn error(&self, message: &str) {
+        tracing::error!("{}", message);
+        self.report(EVENTLOG_ERROR_TYPE, message);
+    }
+
+    /// Report an audit record, without echoing it to the local log
+    pub fn audit(&self, message: &str, success: bool) {
+        let kind = if success { EVENTLOG_INFORMATION_TYPE } else { EVENTLOG_WARNING_TYPE };
+        self.report_with_id(kind, AUDIT_EVENT_ID, message);
+    }
+
+    fn report(&self, kind: REPORT_EVENT_TYPE, message: &str) {
+        self.report_with_id(kind, EVENT_ID, message);
+    }
+
+    fn report_with_id(&self, kind: REPORT_EVENT_TYPE, id: u32, message: &str) {
+        let Some(handle) = self.handle else { return };
+        let wide = to_wide(message);
+        let strings = [PCWSTR(wide.as_ptr())];
+        unsafe {
+            let _ = ReportEventW(handle, kind, 0, id, PSID::default(), 0, Some(&strings), None);
+        }
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            unsafe {
+                let _ = DeregisterEventSource(handle);
+            }
+        }
+    }
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
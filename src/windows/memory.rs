@@ -1,9 +1,25 @@
 //! Windows Memory Management Core with Real Win32 APIs
 
-use sysinfo::System;
+use serde::{Deserialize, Serialize};
+use sysinfo::{System, ProcessesToUpdate};
 use tracing::{info, warn};
+use std::collections::HashMap;
 use std::time::Instant;
 
+use crate::algorithms::MinCutClusterer;
+use crate::apps::jvm;
+use crate::security::audit::{self, AuditAction};
+use crate::security::policy::{file_publisher, Policy, ProcessIdentity};
+use super::etw;
+use super::safety::{SafetyConfig, SafetyGuard};
+use super::plan::{
+    OptimizationPlan, PlannedAction, PlannedProcess, PlanExecution, ProcessOutcome,
+    AGGRESSIVE_RECLAIM_RATIO, HOT_COLDNESS, MAX_PLAN_PROCESSES, NORMAL_RECLAIM_RATIO,
+};
+
+/// How long hard faults are counted before an aggressive plan
+const FAULT_SAMPLE_WINDOW: std::time::Duration = std::time::Duration::from_millis(500);
+
 #[derive(Debug, Clone)]
 pub struct MemoryStatus {
     pub total_physical_mb: f64,
@@ -13,25 +29,46 @@ pub struct MemoryStatus {
     pub available_page_file_mb: f64,
     pub total_virtual_mb: f64,
     pub available_virtual_mb: f64,
+    /// Memory committed by all processes and the kernel
+    pub commit_charge_mb: f64,
+    /// RAM plus pagefiles; allocations fail once the charge reaches it
+    pub commit_limit_mb: f64,
+    pub commit_peak_mb: f64,
 }
 
 impl MemoryStatus {
     pub fn used_physical_mb(&self) -> f64 { self.total_physical_mb - self.available_physical_mb }
-    pub fn is_high_pressure(&self) -> bool { self.memory_load_percent > 80 }
-    pub fn is_critical(&self) -> bool { self.memory_load_percent > 95 }
+    pub fn commit_available_mb(&self) -> f64 { (self.commit_limit_mb - self.commit_charge_mb).max(0.0) }
+    /// Share of the commit limit already committed
+    pub fn commit_percent(&self) -> u32 {
+        if self.commit_limit_mb <= 0.0 { return 0; }
+        ((self.commit_charge_mb / self.commit_limit_mb).clamp(0.0, 1.0) * 100.0) as u32
+    }
+    pub fn is_high_pressure(&self) -> bool { self.memory_load_percent > 80 || self.commit_percent() > 90 }
+    pub fn is_critical(&self) -> bool { self.memory_load_percent > 95 || self.commit_percent() > 97 }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationResult {
     pub freed_mb: f64,
     pub before_available_mb: f64,
     pub after_available_mb: f64,
     pub processes_trimmed: usize,
     pub duration_ms: u64,
+    /// `(pid, freed_bytes)` for every process whose working set was trimmed
+    pub trimmed: Vec<(u32, u64)>,
+    /// Whether loosely tied process clusters were trimmed together
+    #[serde(default)]
+    pub clustered: bool,
 }
 
 pub struct WindowsMemoryOptimizer {
     has_admin: bool,
+    policy: Policy,
+    /// Page coldness by PID from an `AccessTracker`; empty when untracked
+    coldness: HashMap<u32, f64>,
+    /// Trim loosely tied process clusters together in the next plans
+    group_clusters: bool,
 }
 
 impl WindowsMemoryOptimizer {
@@ -39,7 +76,7 @@ impl WindowsMemoryOptimizer {
         let has_admin = Self::check_admin();
         if !has_admin { warn!("Running without admin - limited optimization"); }
         else { info!("Running with admin privileges - full optimization available"); }
-        Self { has_admin }
+        Self { has_admin, policy: Policy::load_default(), coldness: HashMap::new(), group_clusters: false }
     }
 
     fn check_admin() -> bool {
@@ -75,6 +112,43 @@ impl WindowsMemoryOptimizer {
         { false }
     }
 
+    /// Physical memory from `GlobalMemoryStatusEx`, commit from `GetPerformanceInfo`
+    ///
+    /// The page file fields hold the commit limit (RAM plus pagefiles) and
+    /// the commit still available, which is what runs out first on a
+    /// system with a small pagefile.
+    #[cfg(windows)]
+    pub fn get_memory_status() -> Result<MemoryStatus, String> {
+        use windows::Win32::System::ProcessStatus::{GetPerformanceInfo, PERFORMANCE_INFORMATION};
+        use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+        let mut info = MEMORYSTATUSEX {
+            dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+            ..Default::default()
+        };
+        unsafe { GlobalMemoryStatusEx(&mut info) }
+            .map_err(|e| format!("GlobalMemoryStatusEx failed: {}", e))?;
+        let mut perf = PERFORMANCE_INFORMATION::default();
+        unsafe { GetPerformanceInfo(&mut perf, std::mem::size_of::<PERFORMANCE_INFORMATION>() as u32) }
+            .map_err(|e| format!("GetPerformanceInfo failed: {}", e))?;
+
+        let mb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0;
+        let pages_mb = |pages: usize| mb(pages as u64 * perf.PageSize as u64);
+        Ok(MemoryStatus {
+            total_physical_mb: mb(info.ullTotalPhys),
+            available_physical_mb: mb(info.ullAvailPhys),
+            memory_load_percent: info.dwMemoryLoad,
+            total_page_file_mb: mb(info.ullTotalPageFile),
+            available_page_file_mb: mb(info.ullAvailPageFile),
+            total_virtual_mb: mb(info.ullTotalVirtual),
+            available_virtual_mb: mb(info.ullAvailVirtual),
+            commit_charge_mb: pages_mb(perf.CommitTotal),
+            commit_limit_mb: pages_mb(perf.CommitLimit),
+            commit_peak_mb: pages_mb(perf.CommitPeak),
+        })
+    }
+
+    #[cfg(not(windows))]
     pub fn get_memory_status() -> Result<MemoryStatus, String> {
         let mut sys = System::new();
         sys.refresh_memory();
@@ -85,11 +159,34 @@ impl WindowsMemoryOptimizer {
             total_physical_mb: total, available_physical_mb: avail, memory_load_percent: load,
             total_page_file_mb: total * 1.5, available_page_file_mb: avail,
             total_virtual_mb: total * 2.0, available_virtual_mb: avail * 2.0,
+            commit_charge_mb: total - avail, commit_limit_mb: total * 1.5, commit_peak_mb: total - avail,
         })
     }
 
+    /// Pages read from disk to resolve hard faults, per second over `window`
+    ///
+    /// Sustained high rates mean the system is already paging; trimming
+    /// harder then only adds to it.
+    #[cfg(windows)]
+    pub fn hard_faults_per_sec(window: std::time::Duration) -> Option<f64> {
+        let first = page_read_count()?;
+        std::thread::sleep(window);
+        let second = page_read_count()?;
+        Some(second.wrapping_sub(first) as f64 / window.as_secs_f64())
+    }
+
+    #[cfg(not(windows))]
+    pub fn hard_faults_per_sec(_window: std::time::Duration) -> Option<f64> { None }
+
     #[cfg(windows)]
     pub fn trim_process_working_set(pid: u32) -> Result<u64, String> {
+        let result = Self::trim_working_set(pid);
+        audit::record(AuditAction::Trim, "", Some(pid), "", &result);
+        result
+    }
+
+    #[cfg(windows)]
+    fn trim_working_set(pid: u32) -> Result<u64, String> {
         use windows::Win32::Foundation::CloseHandle;
         use windows::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_QUERY_INFORMATION};
         use windows::Win32::System::Memory::SetProcessWorkingSetSizeEx;
@@ -99,7 +196,7 @@ impl WindowsMemoryOptimizer {
         unsafe {
             let handle = match OpenProcess(PROCESS_SET_QUOTA | PROCESS_QUERY_INFORMATION, false, pid) {
                 Ok(h) => h,
-                Err(_) => return Ok(0),
+                Err(e) => return Err(format!("OpenProcess failed for {}: {}", pid, e)),
             };
 
             let mut mem_counters = PROCESS_MEMORY_COUNTERS::default();
@@ -123,26 +220,183 @@ impl WindowsMemoryOptimizer {
     #[cfg(not(windows))]
     pub fn trim_process_working_set(_pid: u32) -> Result<u64, String> { Ok(0) }
 
-    pub fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+    /// Prefer trimming processes whose pages are cold in the next plans
+    pub fn set_coldness(&mut self, coldness: HashMap<u32, f64>) {
+        self.coldness = coldness;
+    }
+
+    /// Trim each loosely tied process cluster as one group in the next plans
+    pub fn set_group_clusters(&mut self, group: bool) {
+        self.group_clusters = group;
+    }
+
+    /// Build an optimization plan without touching any process
+    ///
+    /// Aggressive plans fall back to a normal trim when commit is close to
+    /// its limit or the system is already paging heavily; the plan records why.
+    /// With page coldness set, cold processes come first and normal plans
+    /// skip hot ones. With cluster grouping on, a cluster's members follow
+    /// its best-placed member, in the cluster's own trim order.
+    pub fn plan(&self, aggressive: bool) -> Result<OptimizationPlan, String> {
+        let status = Self::get_memory_status()?;
+        let downgrade_reason = if aggressive {
+            let guard = SafetyGuard::new(SafetyConfig::default());
+            guard.check_aggressive(&status, Self::hard_faults_per_sec(FAULT_SAMPLE_WINDOW)).err()
+        } else {
+            None
+        };
+        if let Some(reason) = &downgrade_reason {
+            warn!("Not trimming aggressively: {}", reason);
+        }
+        let aggressive = aggressive && downgrade_reason.is_none();
+        let ratio = if aggressive { AGGRESSIVE_RECLAIM_RATIO } else { NORMAL_RECLAIM_RATIO };
+        let self_pid = std::process::id();
+
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+
+        let mut procs: Vec<(u32, String, f64, Option<std::path::PathBuf>)> = sys.processes()
+            .iter()
+            .map(|(pid, p)| (
+                pid.as_u32(),
+                p.name().to_string_lossy().to_string(),
+                p.memory() as f64 / 1024.0 / 1024.0,
+                p.exe().map(|e| e.to_path_buf()),
+            ))
+            .collect();
+        // Weigh working sets by coldness: 1.5x fully cold, 0.5x hottest
+        let weight = |pid: &u32| self.coldness.get(pid).map_or(1.0, |c| 0.5 + c);
+        procs.sort_by(|a, b| (b.2 * weight(&b.0)).total_cmp(&(a.2 * weight(&a.0))));
+
+        let mut clusters: HashMap<u32, usize> = HashMap::new();
+        if self.group_clusters {
+            let mut clusterer = MinCutClusterer::new();
+            clusterer.build_graph(&sys);
+            let ranked: Vec<u32> = procs.iter().map(|p| p.0).collect();
+            let mut by_pid: HashMap<u32, _> = procs.into_iter().map(|p| (p.0, p)).collect();
+            let order = clusterer.group_order(&ranked);
+            procs = order.iter().filter_map(|(pid, _)| by_pid.remove(pid)).collect();
+            clusters = order.into_iter().filter_map(|(pid, cluster)| Some((pid, cluster?))).collect();
+        }
+
+        let entries = procs.into_iter()
+            .take(MAX_PLAN_PROCESSES)
+            .map(|(pid, name, working_set_mb, path)| {
+                let cluster = clusters.get(&pid).copied();
+                let coldness = self.coldness.get(&pid).copied();
+                let skip_reason = if pid == 0 || pid == 4 {
+                    Some("system process".to_string())
+                } else if pid == self_pid {
+                    Some("optimizer process".to_string())
+                } else if jvm::is_jvm(&name) {
+                    // Trimmed heap pages all fault back in on the next GC
+                    Some("JVM heap, see `jvm`".to_string())
+                } else if !aggressive && coldness.is_some_and(|c| c < HOT_COLDNESS) {
+                    Some("pages in active use".to_string())
+                } else {
+                    let mut identity = ProcessIdentity::new(name.clone()).with_path(path);
+                    if self.policy.needs_publisher() {
+                        identity.publisher = identity.path.as_deref().and_then(file_publisher);
+                    }
+                    self.policy.exclusion_reason(&identity)
+                };
+
+                match skip_reason {
+                    Some(reason) => PlannedProcess {
+                        pid, name, working_set_mb,
+                        action: PlannedAction::Skip { reason },
+                        estimated_reclaim_mb: 0.0,
+                        coldness,
+                        cluster,
+                    },
+                    None => PlannedProcess {
+                        pid, name, working_set_mb,
+                        action: PlannedAction::TrimWorkingSet,
+                        estimated_reclaim_mb: working_set_mb * ratio,
+                        coldness,
+                        cluster,
+                    },
+                }
+            })
+            .collect();
+
+        Ok(OptimizationPlan {
+            aggressive,
+            created_at_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            available_mb: status.available_physical_mb,
+            entries,
+            downgrade_reason,
+            clustered: self.group_clusters,
+        })
+    }
+
+    /// Apply a previously built plan and report the outcome for every entry
+    pub fn execute(&self, plan: &OptimizationPlan) -> Result<PlanExecution, String> {
         let start = Instant::now();
         let before = Self::get_memory_status()?;
-        let mut trimmed = 0usize;
+        let planned = plan.entries.iter().filter(|e| e.action == PlannedAction::TrimWorkingSet).count();
+        etw::optimization_start(plan.aggressive, planned as u32, before.memory_load_percent, before.available_physical_mb);
+        let mut outcomes = Vec::with_capacity(plan.entries.len());
         let mut total_freed: u64 = 0;
 
-        if let Ok(procs) = super::process::list_processes() {
-            for pid in procs.iter().take(150) {
-                match Self::trim_process_working_set(*pid) {
+        for entry in &plan.entries {
+            let outcome = match entry.action {
+                PlannedAction::TrimWorkingSet => match Self::trim_process_working_set(entry.pid) {
                     Ok(freed) => {
-                        if freed > 0 {
-                            total_freed += freed;
-                            trimmed += 1;
+                        total_freed += freed;
+                        ProcessOutcome {
+                            pid: entry.pid,
+                            name: entry.name.clone(),
+                            action: entry.action.clone(),
+                            success: true,
+                            estimated_reclaim_mb: entry.estimated_reclaim_mb,
+                            freed_mb: freed as f64 / 1024.0 / 1024.0,
+                            error: None,
                         }
                     }
-                    Err(_) => {}
-                }
+                    Err(e) => ProcessOutcome {
+                        pid: entry.pid,
+                        name: entry.name.clone(),
+                        action: entry.action.clone(),
+                        success: false,
+                        estimated_reclaim_mb: entry.estimated_reclaim_mb,
+                        freed_mb: 0.0,
+                        error: Some(e),
+                    },
+                },
+                PlannedAction::Skip { .. } => ProcessOutcome {
+                    pid: entry.pid,
+                    name: entry.name.clone(),
+                    action: entry.action.clone(),
+                    success: true,
+                    estimated_reclaim_mb: 0.0,
+                    freed_mb: 0.0,
+                    error: None,
+                },
+            };
+            if outcome.action == PlannedAction::TrimWorkingSet {
+                etw::process_trimmed(outcome.pid, &outcome.name, outcome.freed_mb, outcome.error.as_deref());
             }
+            outcomes.push(outcome);
         }
 
+        // Trimmed pages land on the modified and standby lists; aggressive
+        // runs release those too when elevated
+        let standby = if plan.aggressive && self.has_admin {
+            match clean_standby(true) {
+                Ok(cleanup) => Some(cleanup),
+                Err(e) => {
+                    warn!("Standby cleanup skipped: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         // Force garbage collection pause
         std::thread::sleep(std::time::Duration::from_millis(100));
 
@@ -151,15 +405,35 @@ impl WindowsMemoryOptimizer {
         let calculated_freed = total_freed as f64 / 1024.0 / 1024.0;
         let freed_mb = measured_freed.max(calculated_freed).max(0.0);
 
-        info!("Optimized: trimmed {} processes, freed {:.1} MB in {}ms", 
-            trimmed, freed_mb, start.elapsed().as_millis());
-
-        Ok(OptimizationResult {
+        let execution = PlanExecution {
+            outcomes,
             freed_mb,
             before_available_mb: before.available_physical_mb,
             after_available_mb: after.available_physical_mb,
-            processes_trimmed: trimmed,
             duration_ms: start.elapsed().as_millis() as u64,
+            standby,
+        };
+        let failed = execution.outcomes.iter().filter(|o| !o.success).count();
+        etw::optimization_stop(freed_mb, execution.processes_trimmed() as u32, failed as u32, execution.duration_ms);
+        Ok(execution)
+    }
+
+    pub fn optimize(&self, aggressive: bool) -> Result<OptimizationResult, String> {
+        let plan = self.plan(aggressive)?;
+        let execution = self.execute(&plan)?;
+        let trimmed = execution.processes_trimmed();
+
+        info!("Optimized: trimmed {} processes, freed {:.1} MB in {}ms",
+            trimmed, execution.freed_mb, execution.duration_ms);
+
+        Ok(OptimizationResult {
+            freed_mb: execution.freed_mb,
+            before_available_mb: execution.before_available_mb,
+            after_available_mb: execution.after_available_mb,
+            processes_trimmed: trimmed,
+            duration_ms: execution.duration_ms,
+            trimmed: execution.trimmed(),
+            clustered: plan.clustered,
         })
     }
 
@@ -167,3 +441,227 @@ impl WindowsMemoryOptimizer {
 }
 
 impl Default for WindowsMemoryOptimizer { fn default() -> Self { Self::new() } }
+
+/// `PageReadCount` from `SYSTEM_PERFORMANCE_INFORMATION`
+///
+/// Only the fixed prefix of the structure is declared; the buffer is sized
+/// generously since the full layout grows between Windows releases.
+#[cfg(windows)]
+fn page_read_count() -> Option<u32> {
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtQuerySystemInformation(class: i32, info: *mut std::ffi::c_void, length: u32, returned: *mut u32) -> i32;
+    }
+    const SYSTEM_PERFORMANCE_INFORMATION: i32 = 2;
+    /// Byte offset of `PageReadCount`: four LARGE_INTEGERs, then ULONGs
+    const PAGE_READ_COUNT_OFFSET: usize = 80;
+
+    #[repr(C, align(8))]
+    struct Buffer([u8; 1024]);
+    let mut buffer = Buffer([0; 1024]);
+    let mut returned = 0u32;
+    let status = unsafe {
+        NtQuerySystemInformation(
+            SYSTEM_PERFORMANCE_INFORMATION,
+            buffer.0.as_mut_ptr() as *mut _,
+            buffer.0.len() as u32,
+            &mut returned,
+        )
+    };
+    if status < 0 || (returned as usize) < PAGE_READ_COUNT_OFFSET + 4 {
+        return None;
+    }
+    let bytes = &buffer.0[PAGE_READ_COUNT_OFFSET..PAGE_READ_COUNT_OFFSET + 4];
+    Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+}
+
+/// Sizes of the kernel's physical page lists
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MemoryLists {
+    /// Cached pages that can be repurposed without a disk write
+    pub standby_mb: f64,
+    /// Dirty pages waiting to be written to the pagefile
+    pub modified_mb: f64,
+    pub free_mb: f64,
+    pub zeroed_mb: f64,
+}
+
+/// Page list sizes around a standby cleanup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandbyCleanup {
+    pub before: MemoryLists,
+    pub after: MemoryLists,
+    pub flushed_modified: bool,
+}
+
+impl StandbyCleanup {
+    /// Standby memory released back to the free list
+    pub fn freed_mb(&self) -> f64 {
+        (self.before.standby_mb - self.after.standby_mb).max(0.0)
+    }
+
+    pub fn print_summary(&self) {
+        println!("                 Before        After");
+        println!("  Standby   {:>10.1} MB {:>10.1} MB", self.before.standby_mb, self.after.standby_mb);
+        println!("  Modified  {:>10.1} MB {:>10.1} MB", self.before.modified_mb, self.after.modified_mb);
+        println!("  Free      {:>10.1} MB {:>10.1} MB",
+            self.before.free_mb + self.before.zeroed_mb, self.after.free_mb + self.after.zeroed_mb);
+        println!("\n  Released {:.1} MB of standby memory", self.freed_mb());
+    }
+}
+
+#[cfg(windows)]
+mod memory_lists {
+    use super::MemoryLists;
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtQuerySystemInformation(class: i32, info: *mut std::ffi::c_void, length: u32, returned: *mut u32) -> i32;
+        fn NtSetSystemInformation(class: i32, info: *const std::ffi::c_void, length: u32) -> i32;
+    }
+    const SYSTEM_MEMORY_LIST_INFORMATION: i32 = 80;
+    pub const MEMORY_FLUSH_MODIFIED_LIST: u32 = 3;
+    pub const MEMORY_PURGE_STANDBY_LIST: u32 = 4;
+
+    /// `SYSTEM_MEMORY_LIST_INFORMATION`, all counts in pages
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemMemoryListInformation {
+        zero_page_count: usize,
+        free_page_count: usize,
+        modified_page_count: usize,
+        modified_no_write_page_count: usize,
+        bad_page_count: usize,
+        page_count_by_priority: [usize; 8],
+        repurposed_pages_by_priority: [usize; 8],
+        modified_page_count_page_file: usize,
+    }
+
+    /// Enable `SeProfileSingleProcessPrivilege`, which both querying and
+    /// changing the page lists require
+    pub fn enable_privilege() -> Result<(), String> {
+        use windows::core::w;
+        use windows::Win32::Foundation::{CloseHandle, HANDLE, LUID};
+        use windows::Win32::Security::{
+            AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES, SE_PRIVILEGE_ENABLED,
+            TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES, TOKEN_QUERY,
+        };
+        use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+        unsafe {
+            let mut token = HANDLE::default();
+            OpenProcessToken(GetCurrentProcess(), TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY, &mut token)
+                .map_err(|e| format!("OpenProcessToken failed: {}", e))?;
+            let mut luid = LUID::default();
+            let enabled = LookupPrivilegeValueW(None, w!("SeProfileSingleProcessPrivilege"), &mut luid).and_then(|_| {
+                let privileges = TOKEN_PRIVILEGES {
+                    PrivilegeCount: 1,
+                    Privileges: [LUID_AND_ATTRIBUTES { Luid: luid, Attributes: SE_PRIVILEGE_ENABLED }],
+                };
+                AdjustTokenPrivileges(token, false, Some(&privileges), 0, None, None)
+            });
+            let _ = CloseHandle(token);
+            enabled.map_err(|e| format!("Failed to enable SeProfileSingleProcessPrivilege: {}", e))
+        }
+    }
+
+    pub fn query() -> Result<MemoryLists, String> {
+        use windows::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
+
+        enable_privilege()?;
+        let mut info = SystemMemoryListInformation::default();
+        let mut system = SYSTEM_INFO::default();
+        let status = unsafe {
+            GetSystemInfo(&mut system);
+            NtQuerySystemInformation(
+                SYSTEM_MEMORY_LIST_INFORMATION,
+                &mut info as *mut _ as *mut _,
+                std::mem::size_of::<SystemMemoryListInformation>() as u32,
+                std::ptr::null_mut(),
+            )
+        };
+        if status < 0 {
+            return Err(format!("NtQuerySystemInformation failed: 0x{:08X}", status as u32));
+        }
+
+        let page_mb = system.dwPageSize as f64 / 1024.0 / 1024.0;
+        Ok(MemoryLists {
+            standby_mb: info.page_count_by_priority.iter().sum::<usize>() as f64 * page_mb,
+            modified_mb: info.modified_page_count as f64 * page_mb,
+            free_mb: info.free_page_count as f64 * page_mb,
+            zeroed_mb: info.zero_page_count as f64 * page_mb,
+        })
+    }
+
+    pub fn command(command: u32) -> Result<(), String> {
+        enable_privilege()?;
+        let status = unsafe {
+            NtSetSystemInformation(
+                SYSTEM_MEMORY_LIST_INFORMATION,
+                &command as *const u32 as *const _,
+                std::mem::size_of::<u32>() as u32,
+            )
+        };
+        if status < 0 {
+            return Err(format!("NtSetSystemInformation failed: 0x{:08X}", status as u32));
+        }
+        Ok(())
+    }
+}
+
+/// Current standby, modified, free and zeroed list sizes
+///
+/// Needs an elevated process, like the commands that change the lists.
+#[cfg(windows)]
+pub fn memory_lists() -> Result<MemoryLists, String> {
+    memory_lists::query()
+}
+
+#[cfg(not(windows))]
+pub fn memory_lists() -> Result<MemoryLists, String> {
+    Err("The standby list only exists on Windows".into())
+}
+
+/// Empty the standby list so its pages become free memory
+///
+/// Needs an elevated process; enables `SeProfileSingleProcessPrivilege` on
+/// the current token before calling `NtSetSystemInformation`.
+#[cfg(windows)]
+pub fn purge_standby_list() -> Result<(), String> {
+    let result = memory_lists::command(memory_lists::MEMORY_PURGE_STANDBY_LIST);
+    audit::record(AuditAction::CacheDrop, "standby list", None, "purge", &result);
+    result
+}
+
+#[cfg(not(windows))]
+pub fn purge_standby_list() -> Result<(), String> {
+    Err("The standby list only exists on Windows".into())
+}
+
+/// Write the modified list out so its pages move to the standby list
+#[cfg(windows)]
+pub fn flush_modified_list() -> Result<(), String> {
+    let result = memory_lists::command(memory_lists::MEMORY_FLUSH_MODIFIED_LIST);
+    audit::record(AuditAction::CacheDrop, "modified list", None, "flush", &result);
+    result
+}
+
+#[cfg(not(windows))]
+pub fn flush_modified_list() -> Result<(), String> {
+    Err("The modified page list only exists on Windows".into())
+}
+
+/// Optionally flush the modified list, then purge standby, measuring both sides
+///
+/// Flushing first means freshly trimmed dirty pages are released too instead
+/// of lingering on the modified list.
+pub fn clean_standby(flush_modified: bool) -> Result<StandbyCleanup, String> {
+    let before = memory_lists()?;
+    if flush_modified {
+        flush_modified_list()?;
+    }
+    purge_standby_list()?;
+    let after = memory_lists()?;
+    info!("Purged standby list: {:.1} MB -> {:.1} MB", before.standby_mb, after.standby_mb);
+    Ok(StandbyCleanup { before, after, flushed_modified: flush_modified })
+}
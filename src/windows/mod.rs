@@ -1,11 +1,16 @@
 //! Windows Memory Management APIs with Safety Guards
 
+pub mod etw;
+pub mod eventlog;
 pub mod memory;
+pub mod pagefile;
+pub mod plan;
 pub mod process;
 pub mod system;
 pub mod safety;
 pub mod performance;
 
 pub use memory::*;
+pub use plan::*;
 pub use process::*;
 pub use safety::*;
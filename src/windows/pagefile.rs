@@ -0,0 +1,279 @@
+//! Pagefile advisor
+//!
+//! Reads the pagefile configuration and its usage since boot through WMI,
+//! flags pagefiles that are too small for the observed peak, far larger than
+//! needed, or allowed to grow (and fragment) in small steps, and can apply a
+//! fixed initial/maximum size. Size changes take effect after a restart.
+
+use std::process::Command;
+use sysinfo::System;
+
+/// Usage above this share of the maximum size risks running out of commit
+const TIGHT_PEAK_RATIO: f64 = 0.8;
+/// Allocated sizes above this multiple of the peak are mostly wasted disk
+const OVERSIZED_RATIO: f64 = 4.0;
+/// Headroom added on top of the observed peak
+const PEAK_HEADROOM: f64 = 1.5;
+/// Smallest initial size recommended; crash dumps need some pagefile
+const MIN_INITIAL_MB: u64 = 2048;
+/// Sizes are rounded up to this step
+const SIZE_STEP_MB: u64 = 512;
+
+const QUERY_SCRIPT: &str = r#"
+$cs = Get-CimInstance Win32_ComputerSystem
+"auto|$($cs.AutomaticManagedPagefile)"
+Get-CimInstance Win32_PageFileUsage | ForEach-Object { "usage|$($_.Name)|$($_.AllocatedBaseSize)|$($_.CurrentUsage)|$($_.PeakUsage)" }
+Get-CimInstance Win32_PageFileSetting | ForEach-Object { "setting|$($_.Name)|$($_.InitialSize)|$($_.MaximumSize)" }
+"#;
+
+/// One pagefile and how it is configured
+#[derive(Debug, Clone, Default)]
+pub struct Pagefile {
+    pub path: String,
+    pub allocated_mb: u64,
+    pub current_usage_mb: u64,
+    /// Highest usage since boot
+    pub peak_usage_mb: u64,
+    /// `None` when Windows manages the size
+    pub initial_mb: Option<u64>,
+    pub maximum_mb: Option<u64>,
+}
+
+/// Problems found with the current configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum PagefileIssue {
+    /// No pagefile at all; commit is limited to physical RAM
+    Missing,
+    /// Peak usage came close to the maximum size
+    TooSmall { path: String, peak_mb: u64, maximum_mb: u64 },
+    /// Allocated far beyond anything used since boot
+    Oversized { path: String, allocated_mb: u64, peak_mb: u64 },
+    /// Initial and maximum differ, so the file grows in pieces on demand
+    MayFragment { path: String },
+}
+
+impl std::fmt::Display for PagefileIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "No pagefile; apps fail to allocate once RAM is committed"),
+            Self::TooSmall { path, peak_mb, maximum_mb } => {
+                write!(f, "{} peaked at {} MB of a {} MB maximum", path, peak_mb, maximum_mb)
+            }
+            Self::Oversized { path, allocated_mb, peak_mb } => {
+                write!(f, "{} is {} MB but never used more than {} MB", path, allocated_mb, peak_mb)
+            }
+            Self::MayFragment { path } => {
+                write!(f, "{} grows on demand and can end up fragmented", path)
+            }
+        }
+    }
+}
+
+/// Current pagefile configuration and usage
+#[derive(Debug, Clone, Default)]
+pub struct PagefileStatus {
+    /// Windows picks the size of every pagefile
+    pub system_managed: bool,
+    pub pagefiles: Vec<Pagefile>,
+}
+
+impl PagefileStatus {
+    /// Query WMI for pagefile settings and usage
+    pub fn query() -> Result<Self, String> {
+        Ok(Self::parse(&powershell(QUERY_SCRIPT)?))
+    }
+
+    /// Parse the `auto|`, `usage|` and `setting|` lines printed by `QUERY_SCRIPT`
+    pub fn parse(output: &str) -> Self {
+        let mut status = Self::default();
+        for line in output.lines() {
+            let fields: Vec<&str> = line.trim().split('|').collect();
+            match fields.as_slice() {
+                ["auto", value] => status.system_managed = value.eq_ignore_ascii_case("true"),
+                ["usage", path, allocated, current, peak] => {
+                    let pagefile = status.entry(path);
+                    pagefile.allocated_mb = allocated.parse().unwrap_or(0);
+                    pagefile.current_usage_mb = current.parse().unwrap_or(0);
+                    pagefile.peak_usage_mb = peak.parse().unwrap_or(0);
+                }
+                ["setting", path, initial, maximum] => {
+                    // 0/0 means "system managed size" for this file
+                    let initial = initial.parse().unwrap_or(0);
+                    let maximum = maximum.parse().unwrap_or(0);
+                    if initial > 0 || maximum > 0 {
+                        let pagefile = status.entry(path);
+                        pagefile.initial_mb = Some(initial);
+                        pagefile.maximum_mb = Some(maximum);
+                    }
+                }
+                _ => {}
+            }
+        }
+        status
+    }
+
+    fn entry(&mut self, path: &str) -> &mut Pagefile {
+        let index = match self.pagefiles.iter().position(|p| p.path.eq_ignore_ascii_case(path)) {
+            Some(index) => index,
+            None => {
+                self.pagefiles.push(Pagefile { path: path.to_string(), ..Default::default() });
+                self.pagefiles.len() - 1
+            }
+        };
+        &mut self.pagefiles[index]
+    }
+
+    /// Highest usage since boot across all pagefiles
+    pub fn peak_usage_mb(&self) -> u64 {
+        self.pagefiles.iter().map(|p| p.peak_usage_mb).sum()
+    }
+
+    pub fn issues(&self) -> Vec<PagefileIssue> {
+        if self.pagefiles.is_empty() {
+            return vec![PagefileIssue::Missing];
+        }
+
+        let mut issues = Vec::new();
+        for pagefile in &self.pagefiles {
+            let path = pagefile.path.clone();
+            if let Some(maximum_mb) = pagefile.maximum_mb {
+                if pagefile.peak_usage_mb as f64 > maximum_mb as f64 * TIGHT_PEAK_RATIO {
+                    issues.push(PagefileIssue::TooSmall { path: path.clone(), peak_mb: pagefile.peak_usage_mb, maximum_mb });
+                }
+            }
+            let floor = pagefile.peak_usage_mb.max(MIN_INITIAL_MB) as f64;
+            if pagefile.allocated_mb as f64 > floor * OVERSIZED_RATIO {
+                issues.push(PagefileIssue::Oversized {
+                    path: path.clone(),
+                    allocated_mb: pagefile.allocated_mb,
+                    peak_mb: pagefile.peak_usage_mb,
+                });
+            }
+            if !self.system_managed && pagefile.initial_mb != pagefile.maximum_mb {
+                issues.push(PagefileIssue::MayFragment { path });
+            }
+        }
+        issues
+    }
+
+    pub fn print(&self) {
+        println!("\n💾 Pagefile ({})\n", if self.system_managed { "system managed" } else { "custom size" });
+        if self.pagefiles.is_empty() {
+            println!("  No pagefile configured");
+        }
+        for pagefile in &self.pagefiles {
+            let size = match (pagefile.initial_mb, pagefile.maximum_mb) {
+                (Some(initial), Some(maximum)) => format!("{}-{} MB", initial, maximum),
+                _ => "auto".to_string(),
+            };
+            println!(
+                "  {:24} {:>7} MB allocated, {:>6} MB in use, {:>6} MB peak, size {}",
+                pagefile.path, pagefile.allocated_mb, pagefile.current_usage_mb, pagefile.peak_usage_mb, size
+            );
+        }
+    }
+}
+
+/// Recommended fixed pagefile size
+#[derive(Debug, Clone)]
+pub struct PagefileAdvice {
+    pub path: String,
+    pub initial_mb: u64,
+    pub maximum_mb: u64,
+    pub issues: Vec<PagefileIssue>,
+}
+
+impl PagefileAdvice {
+    /// Size the pagefile from its peak since boot and installed RAM
+    pub fn recommend(status: &PagefileStatus) -> Self {
+        let mut system = System::new();
+        system.refresh_memory();
+        let ram_mb = system.total_memory() / 1024 / 1024;
+        let (initial_mb, maximum_mb) = recommend_size_mb(status.peak_usage_mb(), ram_mb);
+
+        let path = status
+            .pagefiles
+            .first()
+            .map(|p| p.path.clone())
+            .unwrap_or_else(default_pagefile_path);
+        Self { path, initial_mb, maximum_mb, issues: status.issues() }
+    }
+
+    pub fn print(&self) {
+        if self.issues.is_empty() {
+            println!("\n  No problems found");
+        } else {
+            println!("\n  Issues:");
+            for issue in &self.issues {
+                println!("    ⚠ {}", issue);
+            }
+        }
+        println!("\n  Recommended: {} initial {} MB, maximum {} MB", self.path, self.initial_mb, self.maximum_mb);
+    }
+
+    /// Switch off automatic management and set the recommended size
+    ///
+    /// Needs an elevated prompt; Windows resizes the file on the next restart.
+    pub fn apply(&self) -> Result<(), String> {
+        if !is_pagefile_path(&self.path) {
+            return Err(format!("Refusing unexpected pagefile path {}", self.path));
+        }
+        let script = format!(
+            r#"
+$cs = Get-CimInstance Win32_ComputerSystem
+if ($cs.AutomaticManagedPagefile) {{ Set-CimInstance -InputObject $cs -Property @{{AutomaticManagedPagefile=$false}} }}
+$pf = Get-CimInstance Win32_PageFileSetting | Where-Object {{ $_.Name -eq '{path}' }}
+if (-not $pf) {{ $pf = New-CimInstance -ClassName Win32_PageFileSetting -Property @{{Name='{path}'}} }}
+Set-CimInstance -InputObject $pf -Property @{{InitialSize=[uint32]{initial}; MaximumSize=[uint32]{maximum}}}
+"#,
+            path = self.path,
+            initial = self.initial_mb,
+            maximum = self.maximum_mb,
+        );
+        powershell(&script).map(|_| ())
+    }
+}
+
+/// 1.5× the peak (at least 2 GB) up front, growing to RAM or twice that
+fn recommend_size_mb(peak_mb: u64, ram_mb: u64) -> (u64, u64) {
+    let initial = round_up(((peak_mb as f64 * PEAK_HEADROOM) as u64).max(MIN_INITIAL_MB));
+    let maximum = round_up((initial * 2).max(ram_mb));
+    (initial, maximum)
+}
+
+fn round_up(mb: u64) -> u64 {
+    mb.div_ceil(SIZE_STEP_MB) * SIZE_STEP_MB
+}
+
+/// `X:\pagefile.sys`; anything else is not interpolated into a script
+fn is_pagefile_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() == 15 && bytes[0].is_ascii_alphabetic() && path[1..].eq_ignore_ascii_case(":\\pagefile.sys")
+}
+
+fn default_pagefile_path() -> String {
+    let drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+    format!("{}\\pagefile.sys", drive)
+}
+
+fn powershell(script: &str) -> Result<String, String> {
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", script]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|e| format!("Failed to run powershell: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("Access denied") || stderr.contains("Access is denied") {
+            Err("Changing the pagefile needs an elevated prompt".to_string())
+        } else {
+            Err(format!("powershell failed: {}", stderr.trim()))
+        }
+    }
+}
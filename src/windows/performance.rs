@@ -1,87 +1,104 @@
 //! Windows performance optimization utilities
 
+use serde::{Deserialize, Serialize};
 use std::process::Command;
-use std::path::PathBuf;
+use sysinfo::System;
 
-/// Clear temporary files
-pub fn cleanup_temp_files() -> Result<String, String> {
-    let temp_dirs = [
-        std::env::var("TEMP").unwrap_or_default(),
-        std::env::var("TMP").unwrap_or_default(),
-    ];
-    
-    let mut total_freed = 0u64;
-    let mut files_deleted = 0u32;
-    
-    for dir in &temp_dirs {
-        if dir.is_empty() { continue; }
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                if let Ok(metadata) = entry.metadata() {
-                    let size = metadata.len();
-                    if std::fs::remove_file(entry.path()).is_ok() ||
-                       std::fs::remove_dir_all(entry.path()).is_ok() {
-                        total_freed += size;
-                        files_deleted += 1;
-                    }
-                }
-            }
+/// Process whose working set is the memory compression store
+const COMPRESSION_PROCESS: &str = "Memory Compression";
+
+/// Memory Manager agent (MMAgent) settings and compression store size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryCompression {
+    pub compression_enabled: bool,
+    pub page_combining_enabled: bool,
+    /// Memory held by compressed pages, `None` when the store isn't running
+    pub store_mb: Option<f64>,
+}
+
+impl MemoryCompression {
+    /// Query `Get-MMAgent` and measure the compression store
+    pub fn query() -> Result<Self, String> {
+        let output = powershell("$m = Get-MMAgent; \"$($m.MemoryCompression)|$($m.PageCombining)\"")?;
+        let (compression_enabled, page_combining_enabled) = parse_mmagent(&output)
+            .ok_or_else(|| format!("Unexpected Get-MMAgent output: {}", output.trim()))?;
+
+        let mut system = System::new();
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        Ok(Self {
+            compression_enabled,
+            page_combining_enabled,
+            store_mb: compression_store_mb(&system),
+        })
+    }
+
+    pub fn print(&self) {
+        let state = |on: bool| if on { "enabled" } else { "disabled" };
+        println!("Memory compression: {}", state(self.compression_enabled));
+        println!("Page combining:     {}", state(self.page_combining_enabled));
+        match self.store_mb {
+            Some(mb) => println!("Compression store:  {:.0} MB", mb),
+            None => println!("Compression store:  not running"),
         }
     }
-    
-    Ok(format!("Deleted {} items\nFreed: {:.1} MB", 
-        files_deleted, total_freed as f64 / 1024.0 / 1024.0))
 }
 
-/// Flush DNS cache
-pub fn flush_dns() -> Result<String, String> {
-    let output = Command::new("ipconfig")
-        .args(["/flushdns"])
+/// Size of the compression store in an already refreshed process list
+pub fn compression_store_mb(system: &System) -> Option<f64> {
+    system
+        .processes()
+        .values()
+        .find(|p| p.name().eq_ignore_ascii_case(COMPRESSION_PROCESS))
+        .map(|p| p.memory() as f64 / 1024.0 / 1024.0)
+}
+
+/// Turn page combining on or off; needs an elevated prompt
+pub fn set_page_combining(enabled: bool) -> Result<String, String> {
+    powershell(&format!("{}-MMAgent -PageCombining", if enabled { "Enable" } else { "Disable" }))?;
+    Ok(format!("Page combining {}", if enabled { "enabled" } else { "disabled" }))
+}
+
+/// Turn memory compression on or off; Windows applies it after a restart
+pub fn set_memory_compression(enabled: bool) -> Result<String, String> {
+    powershell(&format!("{}-MMAgent -MemoryCompression", if enabled { "Enable" } else { "Disable" }))?;
+    Ok(format!(
+        "Memory compression will be {} after a restart",
+        if enabled { "enabled" } else { "disabled" }
+    ))
+}
+
+/// `True|False` from the `Get-MMAgent` script into (compression, combining)
+fn parse_mmagent(output: &str) -> Option<(bool, bool)> {
+    let (compression, combining) = output.trim().split_once('|')?;
+    let flag = |s: &str| match s.trim() {
+        "True" => Some(true),
+        "False" => Some(false),
+        _ => None,
+    };
+    Some((flag(compression)?, flag(combining)?))
+}
+
+fn powershell(script: &str) -> Result<String, String> {
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
         .output()
         .map_err(|e| e.to_string())?;
-    
     if output.status.success() {
-        Ok("DNS cache flushed".to_string())
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
     } else {
-        Err("Failed to flush DNS".to_string())
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("Access is denied") || stderr.contains("PermissionDenied") {
+            Err("Changing MMAgent settings needs an elevated prompt".to_string())
+        } else {
+            Err(format!("powershell failed: {}", stderr.trim()))
+        }
     }
 }
 
 /// Set high performance power plan
 pub fn set_high_performance() -> Result<String, String> {
-    let output = Command::new("powercfg")
-        .args(["/setactive", "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    
-    if output.status.success() {
-        Ok("High Performance mode activated".to_string())
-    } else {
-        Err("Need admin rights for power plan".to_string())
-    }
-}
+    use crate::platform::power::{self, PowerPlan};
 
-/// Clear thumbnail cache
-pub fn cleanup_thumbnails() -> Result<String, String> {
-    let mut thumb_path = PathBuf::from(std::env::var("USERPROFILE").unwrap_or_default());
-    thumb_path.push("AppData");
-    thumb_path.push("Local");
-    thumb_path.push("Microsoft");
-    thumb_path.push("Windows");
-    thumb_path.push("Explorer");
-    
-    let mut deleted = 0u32;
-    
-    if let Ok(entries) = std::fs::read_dir(&thumb_path) {
-        for entry in entries.flatten() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            if name.starts_with("thumbcache_") {
-                if std::fs::remove_file(entry.path()).is_ok() {
-                    deleted += 1;
-                }
-            }
-        }
-    }
-    
-    Ok(format!("Cleared {} thumbnail caches", deleted))
+    power::set_plan(PowerPlan::HighPerformance)?;
+    Ok("High Performance mode activated".to_string())
 }
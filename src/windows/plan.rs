@@ -0,0 +1,230 @@
+//! Two-phase optimization: build a plan, then execute it
+//!
+//! `WindowsMemoryOptimizer::plan()` inspects running processes and produces an
+//! `OptimizationPlan` describing exactly which PIDs will be touched and how much
+//! memory each is expected to give back. `execute()` applies the plan and
+//! returns a per-process `ProcessOutcome` for every entry.
+
+use serde::{Deserialize, Serialize};
+
+use super::memory::StandbyCleanup;
+use crate::core::text::truncate;
+
+/// Maximum number of processes a single plan will trim
+pub const MAX_PLAN_PROCESSES: usize = 150;
+
+/// Fraction of a working set expected to be reclaimed by a normal trim
+pub const NORMAL_RECLAIM_RATIO: f64 = 0.30;
+/// Fraction of a working set expected to be reclaimed by an aggressive trim
+pub const AGGRESSIVE_RECLAIM_RATIO: f64 = 0.60;
+
+/// Page coldness below which a normal trim leaves a process alone; its
+/// pages would fault straight back in
+pub const HOT_COLDNESS: f64 = 0.15;
+
+/// Action planned for a single process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlannedAction {
+    /// Empty the process working set
+    TrimWorkingSet,
+    /// Leave the process alone
+    Skip { reason: String },
+}
+
+impl std::fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlannedAction::TrimWorkingSet => write!(f, "Trim"),
+            PlannedAction::Skip { reason } => write!(f, "Skip ({})", reason),
+        }
+    }
+}
+
+/// A single process entry in an optimization plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedProcess {
+    pub pid: u32,
+    pub name: String,
+    /// Working set at planning time
+    pub working_set_mb: f64,
+    pub action: PlannedAction,
+    /// Expected reclaim if the action succeeds
+    pub estimated_reclaim_mb: f64,
+    /// How cold the process's pages are (0.0 hot to 1.0 cold), once its
+    /// page faults have been tracked for a while
+    #[serde(default)]
+    pub coldness: Option<f64>,
+    /// Cluster the process is trimmed with, when clusters are grouped
+    #[serde(default)]
+    pub cluster: Option<usize>,
+}
+
+/// Structured description of what an optimization run will do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationPlan {
+    pub aggressive: bool,
+    /// Unix timestamp (ms) the plan was built
+    pub created_at_ms: u64,
+    /// Available memory when the plan was built
+    pub available_mb: f64,
+    pub entries: Vec<PlannedProcess>,
+    /// Why an aggressive request was planned as a normal trim
+    #[serde(default)]
+    pub downgrade_reason: Option<String>,
+    /// Whether loosely tied process clusters are trimmed together
+    #[serde(default)]
+    pub clustered: bool,
+}
+
+impl OptimizationPlan {
+    /// Entries that will actually be acted on
+    pub fn actionable(&self) -> impl Iterator<Item = &PlannedProcess> {
+        self.entries.iter().filter(|e| e.action == PlannedAction::TrimWorkingSet)
+    }
+
+    /// Number of processes that will be trimmed
+    pub fn actionable_count(&self) -> usize {
+        self.actionable().count()
+    }
+
+    /// Total estimated reclaim across all actionable entries
+    pub fn estimated_total_mb(&self) -> f64 {
+        self.actionable().map(|e| e.estimated_reclaim_mb).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actionable_count() == 0
+    }
+
+    /// Number of clusters with at least one process to trim
+    pub fn cluster_count(&self) -> usize {
+        let clusters: std::collections::HashSet<usize> = self.actionable().filter_map(|e| e.cluster).collect();
+        clusters.len()
+    }
+
+    /// Print a summary table of the plan
+    pub fn print_summary(&self, limit: usize) {
+        println!(
+            "\n📋 Optimization Plan ({}{})\n",
+            if self.aggressive { "aggressive" } else { "normal" },
+            if self.clustered { ", by cluster" } else { "" }
+        );
+        if let Some(reason) = &self.downgrade_reason {
+            println!("  ⚠ Aggressive trimming skipped: {}\n", reason);
+        }
+        println!("┌────────┬──────────────────────────┬────────────┬────────────┬──────────────────┐");
+        println!("│ PID    │ Process                  │ Working MB │ Est. MB    │ Action           │");
+        println!("├────────┼──────────────────────────┼────────────┼────────────┼──────────────────┤");
+
+        for entry in self.entries.iter().take(limit) {
+            println!(
+                "│ {:>6} │ {:24} │ {:>10.1} │ {:>10.1} │ {:16} │",
+                entry.pid,
+                truncate(&entry.name, 24),
+                entry.working_set_mb,
+                entry.estimated_reclaim_mb,
+                truncate(&entry.action.to_string(), 16)
+            );
+        }
+
+        println!("└────────┴──────────────────────────┴────────────┴────────────┴──────────────────┘");
+        if self.entries.len() > limit {
+            println!("  ... and {} more", self.entries.len() - limit);
+        }
+        println!(
+            "\n  {} processes to trim, ~{:.0} MB estimated reclaim",
+            self.actionable_count(),
+            self.estimated_total_mb()
+        );
+        if self.clustered {
+            println!("  {} clusters trimmed as groups", self.cluster_count());
+        }
+    }
+}
+
+/// Result of applying one plan entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessOutcome {
+    pub pid: u32,
+    pub name: String,
+    pub action: PlannedAction,
+    pub success: bool,
+    pub estimated_reclaim_mb: f64,
+    /// Measured working set reduction
+    pub freed_mb: f64,
+    pub error: Option<String>,
+}
+
+/// Result of executing a full plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanExecution {
+    pub outcomes: Vec<ProcessOutcome>,
+    pub freed_mb: f64,
+    pub before_available_mb: f64,
+    pub after_available_mb: f64,
+    pub duration_ms: u64,
+    /// Standby cleanup done by an elevated aggressive run
+    #[serde(default)]
+    pub standby: Option<StandbyCleanup>,
+}
+
+impl PlanExecution {
+    /// Processes that were trimmed and gave memory back
+    pub fn processes_trimmed(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.success && o.freed_mb > 0.0).count()
+    }
+
+    /// `(pid, freed_bytes)` for the processes counted by `processes_trimmed`
+    pub fn trimmed(&self) -> Vec<(u32, u64)> {
+        self.outcomes
+            .iter()
+            .filter(|o| o.success && o.freed_mb > 0.0)
+            .map(|o| (o.pid, (o.freed_mb * 1024.0 * 1024.0) as u64))
+            .collect()
+    }
+
+    /// Processes where the action failed
+    pub fn failures(&self) -> impl Iterator<Item = &ProcessOutcome> {
+        self.outcomes.iter().filter(|o| !o.success)
+    }
+
+    /// Print per-process outcomes, largest reclaim first
+    pub fn print_summary(&self, limit: usize) {
+        let mut sorted: Vec<&ProcessOutcome> = self.outcomes.iter()
+            .filter(|o| !matches!(o.action, PlannedAction::Skip { .. }))
+            .collect();
+        sorted.sort_by(|a, b| b.freed_mb.partial_cmp(&a.freed_mb).unwrap_or(std::cmp::Ordering::Equal));
+
+        println!("\n✅ Plan Executed\n");
+        println!("┌────────┬──────────────────────────┬────────────┬────────────┬──────────┐");
+        println!("│ PID    │ Process                  │ Est. MB    │ Freed MB   │ Result   │");
+        println!("├────────┼──────────────────────────┼────────────┼────────────┼──────────┤");
+
+        for outcome in sorted.iter().take(limit) {
+            println!(
+                "│ {:>6} │ {:24} │ {:>10.1} │ {:>10.1} │ {:8} │",
+                outcome.pid,
+                truncate(&outcome.name, 24),
+                outcome.estimated_reclaim_mb,
+                outcome.freed_mb,
+                if outcome.success { "OK" } else { "FAILED" }
+            );
+        }
+
+        println!("└────────┴──────────────────────────┴────────────┴────────────┴──────────┘");
+        println!(
+            "\n  Freed {:.1} MB from {} processes in {} ms ({} failed)",
+            self.freed_mb,
+            self.processes_trimmed(),
+            self.duration_ms,
+            self.failures().count()
+        );
+        if let Some(standby) = &self.standby {
+            println!(
+                "  Standby list {:.1} MB -> {:.1} MB",
+                standby.before.standby_mb, standby.after.standby_mb
+            );
+        }
+    }
+}
+
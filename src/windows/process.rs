@@ -2,6 +2,10 @@
 
 use sysinfo::{System, Pid, ProcessesToUpdate};
 
+use crate::neural::refault::ProcessCounters;
+use crate::platform::ProcessMemoryInfo;
+use crate::security::audit::{self, AuditAction};
+
 /// List all process IDs
 pub fn list_processes() -> Result<Vec<u32>, String> {
     let mut sys = System::new_all();
@@ -24,6 +28,108 @@ pub fn get_process_name(pid: u32) -> Option<String> {
         .map(|p| p.name().to_string_lossy().to_string())
 }
 
+/// Working set and page fault count for a process
+#[cfg(windows)]
+pub fn get_process_counters(pid: u32) -> Option<ProcessCounters> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+    use std::mem::size_of;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let size = size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let mut counters = PROCESS_MEMORY_COUNTERS { cb: size, ..Default::default() };
+        let ok = GetProcessMemoryInfo(handle, &mut counters, size).is_ok();
+        let _ = CloseHandle(handle);
+
+        ok.then(|| ProcessCounters {
+            working_set_bytes: counters.WorkingSetSize as u64,
+            page_faults: counters.PageFaultCount as u64,
+        })
+    }
+}
+
+#[cfg(not(windows))]
+pub fn get_process_counters(_pid: u32) -> Option<ProcessCounters> { None }
+
+/// Working set split into private and shared pages with `QueryWorkingSet`;
+/// pages inside image and file-mapping regions count as mapped
+#[cfg(windows)]
+pub fn get_memory_info(pid: u32) -> Result<ProcessMemoryInfo, String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Memory::{VirtualQueryEx, MEMORY_BASIC_INFORMATION, MEM_IMAGE, MEM_MAPPED};
+    use windows::Win32::System::ProcessStatus::QueryWorkingSet;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+    use std::mem::size_of;
+
+    const PAGE_SIZE: usize = 4096;
+    // `PSAPI_WORKING_SET_BLOCK` bits: Shared at bit 8, the page number from bit 12
+    const SHARED_BIT: usize = 1 << 8;
+    const PAGE_SHIFT: u32 = 12;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid)
+            .map_err(|e| format!("Failed to open process {}: {}", pid, e))?;
+
+        // The first word is the entry count; grow until the working set fits
+        let mut buffer: Vec<usize> = vec![0; 64 * 1024];
+        let mut read = false;
+        for _ in 0..4 {
+            let bytes = (buffer.len() * size_of::<usize>()) as u32;
+            if QueryWorkingSet(handle, buffer.as_mut_ptr() as *mut _, bytes).is_ok() {
+                read = true;
+                break;
+            }
+            let needed = buffer[0] + 1;
+            if needed <= buffer.len() {
+                break;
+            }
+            buffer.resize(needed + needed / 8, 0);
+        }
+        if !read {
+            let _ = CloseHandle(handle);
+            return Err(format!("Failed to read the working set of process {}", pid));
+        }
+
+        let entries = &buffer[1..=buffer[0].min(buffer.len() - 1)];
+        let shared = entries.iter().filter(|&&e| e & SHARED_BIT != 0).count();
+        let mut pages: Vec<usize> = entries.iter().map(|&e| e >> PAGE_SHIFT << PAGE_SHIFT).collect();
+        pages.sort_unstable();
+
+        // Count resident pages inside image and mapped-file regions
+        let mut mapped = 0;
+        let mut address = 0usize;
+        let mut info = MEMORY_BASIC_INFORMATION::default();
+        while VirtualQueryEx(handle, Some(address as *const _), &mut info, size_of::<MEMORY_BASIC_INFORMATION>()) != 0 {
+            let start = info.BaseAddress as usize;
+            let end = start.saturating_add(info.RegionSize);
+            if info.Type == MEM_IMAGE || info.Type == MEM_MAPPED {
+                mapped += pages.partition_point(|&p| p < end) - pages.partition_point(|&p| p < start);
+            }
+            if end <= address {
+                break;
+            }
+            address = end;
+        }
+        let _ = CloseHandle(handle);
+
+        let mb = |pages: usize| (pages * PAGE_SIZE) as f64 / 1024.0 / 1024.0;
+        Ok(ProcessMemoryInfo {
+            resident_mb: mb(entries.len()),
+            private_mb: mb(entries.len() - shared),
+            shared_mb: mb(shared),
+            mapped_mb: mb(mapped),
+            footprint_mb: mb(entries.len() - shared),
+        })
+    }
+}
+
+#[cfg(not(windows))]
+pub fn get_memory_info(_pid: u32) -> Result<ProcessMemoryInfo, String> {
+    Err("Working set breakdowns need Windows".to_string())
+}
+
 /// Get memory usage for a process (bytes)
 pub fn get_process_memory(pid: u32) -> Option<u64> {
     let mut sys = System::new();
@@ -32,3 +138,249 @@ pub fn get_process_memory(pid: u32) -> Option<u64> {
     sys.process(Pid::from_u32(pid))
         .map(|p| p.memory())
 }
+
+/// Priority class of a process (`GetPriorityClass` value)
+#[cfg(windows)]
+pub fn get_priority_class(pid: u32) -> Option<u32> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{GetPriorityClass, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let class = GetPriorityClass(handle);
+        let _ = CloseHandle(handle);
+        (class != 0).then_some(class)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn get_priority_class(_pid: u32) -> Option<u32> { None }
+
+/// Set a process priority class (e.g. `HIGH_PRIORITY_CLASS.0`)
+#[cfg(windows)]
+pub fn set_priority_class(pid: u32, class: u32) -> Result<(), String> {
+    let result = apply_priority_class(pid, class);
+    audit::record(AuditAction::Priority, "", Some(pid), &format!("class 0x{:X}", class), &result);
+    result
+}
+
+#[cfg(windows)]
+fn apply_priority_class(pid: u32, class: u32) -> Result<(), String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, SetPriorityClass, PROCESS_CREATION_FLAGS, PROCESS_SET_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
+            .map_err(|e| format!("OpenProcess failed for {}: {}", pid, e))?;
+        let result = SetPriorityClass(handle, PROCESS_CREATION_FLAGS(class));
+        let _ = CloseHandle(handle);
+        result.map_err(|e| format!("SetPriorityClass failed for {}: {}", pid, e))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_priority_class(_pid: u32, _class: u32) -> Result<(), String> {
+    Err("Priority classes are only available on Windows".into())
+}
+
+/// `(process, system)` affinity masks
+#[cfg(windows)]
+pub fn get_affinity_mask(pid: u32) -> Option<(usize, usize)> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{GetProcessAffinityMask, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let (mut process_mask, mut system_mask) = (0usize, 0usize);
+        let ok = GetProcessAffinityMask(handle, &mut process_mask, &mut system_mask).is_ok();
+        let _ = CloseHandle(handle);
+        ok.then_some((process_mask, system_mask))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn get_affinity_mask(_pid: u32) -> Option<(usize, usize)> { None }
+
+/// Restrict a process to the CPUs in `mask`
+#[cfg(windows)]
+pub fn set_affinity_mask(pid: u32, mask: usize) -> Result<(), String> {
+    let result = apply_affinity_mask(pid, mask);
+    audit::record(AuditAction::Affinity, "", Some(pid), &format!("mask 0x{:X}", mask), &result);
+    result
+}
+
+#[cfg(windows)]
+fn apply_affinity_mask(pid: u32, mask: usize) -> Result<(), String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, SetProcessAffinityMask, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+            .map_err(|e| format!("OpenProcess failed for {}: {}", pid, e))?;
+        let result = SetProcessAffinityMask(handle, mask);
+        let _ = CloseHandle(handle);
+        result.map_err(|e| format!("SetProcessAffinityMask failed for {}: {}", pid, e))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_affinity_mask(_pid: u32, _mask: usize) -> Result<(), String> {
+    Err("Affinity masks are only available on Windows".into())
+}
+
+/// I/O priority hint, the order the kernel serves a process's disk requests in
+///
+/// `High` and `Critical` exist too but need `SeIncreaseBasePriorityPrivilege`;
+/// the optimizer only ever lowers I/O priority and puts it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoPriority {
+    VeryLow = 0,
+    Low = 1,
+    Normal = 2,
+}
+
+impl IoPriority {
+    /// From the raw `IO_PRIORITY_HINT` value
+    pub fn from_raw(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(IoPriority::VeryLow),
+            1 => Some(IoPriority::Low),
+            2 => Some(IoPriority::Normal),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoPriority::VeryLow => write!(f, "very low"),
+            IoPriority::Low => write!(f, "low"),
+            IoPriority::Normal => write!(f, "normal"),
+        }
+    }
+}
+
+#[cfg(windows)]
+#[link(name = "ntdll")]
+extern "system" {
+    fn NtQueryInformationProcess(process: *mut std::ffi::c_void, class: i32, info: *mut std::ffi::c_void, length: u32, returned: *mut u32) -> i32;
+    fn NtSetInformationProcess(process: *mut std::ffi::c_void, class: i32, info: *const std::ffi::c_void, length: u32) -> i32;
+}
+
+/// `PROCESSINFOCLASS` value for `ProcessIoPriority`
+#[cfg(windows)]
+const PROCESS_IO_PRIORITY: i32 = 33;
+
+/// I/O priority of a process; higher levels than `Normal` read as `None`
+#[cfg(windows)]
+pub fn get_io_priority(pid: u32) -> Option<IoPriority> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut level = 0u32;
+        let mut returned = 0u32;
+        let status = NtQueryInformationProcess(
+            handle.0 as *mut _,
+            PROCESS_IO_PRIORITY,
+            &mut level as *mut u32 as *mut _,
+            std::mem::size_of::<u32>() as u32,
+            &mut returned,
+        );
+        let _ = CloseHandle(handle);
+        if status < 0 {
+            return None;
+        }
+        IoPriority::from_raw(level)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn get_io_priority(_pid: u32) -> Option<IoPriority> { None }
+
+/// Set the I/O priority of a process
+#[cfg(windows)]
+pub fn set_io_priority(pid: u32, level: IoPriority) -> Result<(), String> {
+    let result = apply_io_priority(pid, level);
+    audit::record(AuditAction::IoPriority, "", Some(pid), &format!("io {}", level), &result);
+    result
+}
+
+#[cfg(windows)]
+fn apply_io_priority(pid: u32, level: IoPriority) -> Result<(), String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_SET_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
+            .map_err(|e| format!("OpenProcess failed for {}: {}", pid, e))?;
+        let level = level as u32;
+        let status = NtSetInformationProcess(
+            handle.0 as *mut _,
+            PROCESS_IO_PRIORITY,
+            &level as *const u32 as *const _,
+            std::mem::size_of::<u32>() as u32,
+        );
+        let _ = CloseHandle(handle);
+        if status < 0 {
+            return Err(format!("NtSetInformationProcess failed for {}: 0x{:08X}", pid, status as u32));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_io_priority(_pid: u32, _level: IoPriority) -> Result<(), String> {
+    Err("I/O priority is only available on Windows".into())
+}
+
+/// Move RuVector itself into background processing mode, or back out
+///
+/// `PROCESS_MODE_BACKGROUND_BEGIN` lowers CPU, I/O and memory priority in
+/// one step, but Windows only accepts it for the calling process. Other
+/// processes get the I/O and CPU parts through [`set_io_priority`] and
+/// [`set_priority_class`].
+#[cfg(windows)]
+pub fn set_background_mode(enabled: bool) -> Result<(), String> {
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, SetPriorityClass, PROCESS_MODE_BACKGROUND_BEGIN, PROCESS_MODE_BACKGROUND_END,
+    };
+
+    let mode = if enabled { PROCESS_MODE_BACKGROUND_BEGIN } else { PROCESS_MODE_BACKGROUND_END };
+    let result = unsafe { SetPriorityClass(GetCurrentProcess(), mode) }
+        .map_err(|e| format!("SetPriorityClass failed: {}", e));
+    let detail = if enabled { "background mode begin" } else { "background mode end" };
+    audit::record(AuditAction::Priority, "", Some(std::process::id()), detail, &result);
+    result
+}
+
+#[cfg(not(windows))]
+pub fn set_background_mode(_enabled: bool) -> Result<(), String> {
+    Err("Background mode is only available on Windows".into())
+}
+
+/// Write a minidump of a process (a hung one, say) to `path`
+#[cfg(windows)]
+pub fn write_minidump(pid: u32, path: &std::path::Path) -> Result<(), String> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::Diagnostics::Debug::{MiniDumpWithThreadInfo, MiniDumpWriteDump};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid)
+            .map_err(|e| format!("OpenProcess failed for {}: {}", pid, e))?;
+        let file_handle = HANDLE(file.as_raw_handle() as _);
+        let result = MiniDumpWriteDump(handle, pid, file_handle, MiniDumpWithThreadInfo, None, None, None);
+        let _ = CloseHandle(handle);
+        result.map_err(|e| format!("MiniDumpWriteDump failed for {}: {}", pid, e))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn write_minidump(_pid: u32, _path: &std::path::Path) -> Result<(), String> {
+    Err("Minidumps are only available on Windows".into())
+}
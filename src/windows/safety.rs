@@ -3,6 +3,29 @@
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
+use super::etw;
+use super::memory::MemoryStatus;
+
+/// Protected process names that should never be optimized
+pub const PROTECTED_PROCESSES: &[&str] = &[
+    // Windows critical
+    "system",
+    "csrss.exe",
+    "smss.exe",
+    "lsass.exe",
+    "services.exe",
+    "wininit.exe",
+    "winlogon.exe",
+    "dwm.exe",
+    "explorer.exe",
+    // Security
+    "msmpeng.exe", // Windows Defender
+    "securityhealthservice.exe",
+    // Anti-virus common
+    "avgnt.exe",
+    "avp.exe",
+];
+
 /// Safety configuration
 #[derive(Debug, Clone)]
 pub struct SafetyConfig {
@@ -16,6 +39,12 @@ pub struct SafetyConfig {
     pub dry_run: bool,
     /// Protected process names (case-insensitive)
     pub protected_processes: Vec<String>,
+    /// Commit left (MB) below which aggressive trims are refused
+    pub min_commit_available_mb: f64,
+    /// Commit charge (% of limit) above which aggressive trims are refused
+    pub max_commit_percent: u32,
+    /// Hard-fault page reads per second above which aggressive trims are refused
+    pub max_hard_faults_per_sec: f64,
 }
 
 impl Default for SafetyConfig {
@@ -25,24 +54,10 @@ impl Default for SafetyConfig {
             min_interval: Duration::from_secs(30),
             max_processes_per_run: 50,
             dry_run: false,
-            protected_processes: vec![
-                // Windows critical
-                "system".into(),
-                "csrss.exe".into(),
-                "smss.exe".into(),
-                "lsass.exe".into(),
-                "services.exe".into(),
-                "wininit.exe".into(),
-                "winlogon.exe".into(),
-                "dwm.exe".into(),
-                "explorer.exe".into(),
-                // Security
-                "msmpeng.exe".into(), // Windows Defender
-                "securityhealthservice.exe".into(),
-                // Anti-virus common
-                "avgnt.exe".into(),
-                "avp.exe".into(),
-            ],
+            protected_processes: PROTECTED_PROCESSES.iter().map(|p| p.to_string()).collect(),
+            min_commit_available_mb: 1024.0,
+            max_commit_percent: 90,
+            max_hard_faults_per_sec: 1000.0,
         }
     }
 }
@@ -67,6 +82,13 @@ impl SafetyGuard {
     
     /// Check if optimization is safe to proceed
     pub fn check_safe(&self, current_available_mb: f64) -> Result<(), String> {
+        self.safe_to_proceed(current_available_mb).map_err(|reason| {
+            etw::safety_rejected("Safe", &reason);
+            reason
+        })
+    }
+
+    fn safe_to_proceed(&self, current_available_mb: f64) -> Result<(), String> {
         // Check memory floor
         if current_available_mb < self.config.min_available_mb {
             return Err(format!(
@@ -97,6 +119,42 @@ impl SafetyGuard {
         Ok(())
     }
     
+    /// Check if an aggressive trim is safe on top of `check_safe`
+    ///
+    /// Trimmed pages have to be faulted back in. Near the commit limit that
+    /// pushes the pagefile to grow or allocations to fail, which crashes
+    /// apps instead of just slowing them down.
+    pub fn check_aggressive(&self, status: &MemoryStatus, hard_faults_per_sec: Option<f64>) -> Result<(), String> {
+        self.aggressive_allowed(status, hard_faults_per_sec).map_err(|reason| {
+            etw::safety_rejected("Aggressive", &reason);
+            reason
+        })
+    }
+
+    fn aggressive_allowed(&self, status: &MemoryStatus, hard_faults_per_sec: Option<f64>) -> Result<(), String> {
+        if status.commit_limit_mb > 0.0 && status.commit_available_mb() < self.config.min_commit_available_mb {
+            return Err(format!(
+                "Only {:.0}MB of commit left (floor {:.0}MB)",
+                status.commit_available_mb(), self.config.min_commit_available_mb
+            ));
+        }
+        if status.commit_percent() > self.config.max_commit_percent {
+            return Err(format!(
+                "Commit charge at {}% of its limit (max {}%)",
+                status.commit_percent(), self.config.max_commit_percent
+            ));
+        }
+        if let Some(rate) = hard_faults_per_sec {
+            if rate > self.config.max_hard_faults_per_sec {
+                return Err(format!(
+                    "Already paging {:.0} pages/s from disk (max {:.0})",
+                    rate, self.config.max_hard_faults_per_sec
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Check if a process is protected
     pub fn is_protected(&self, process_name: &str) -> bool {
         let name_lower = process_name.to_lowercase();